@@ -0,0 +1,119 @@
+use criterion::*;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use ark_bn254::{constraints::GVar, Fr, G1Projective as G};
+use ark_grumpkin::{constraints::GVar as GVar2, Projective as G2};
+
+use folding_schemes::{
+    commitment::pedersen::Pedersen,
+    folding::nova::{Nova, PreprocessorParam},
+    frontend::{FCircuit, VecF},
+    transcript::poseidon::poseidon_canonical_config,
+    FoldingScheme,
+};
+use frontends::circom::{CircomFCircuit, CircomFCircuitParams, WitnessBackend};
+
+type FC = CircomFCircuit<Fr, 0>;
+type N = Nova<G, GVar, G2, GVar2, FC, Pedersen<G>, Pedersen<G2>, false>;
+
+// number of constraints of the step of `pipeline_circuit.circom`, 2 per iteration
+const N_CONSTRAINTS: usize = 2 * 16384;
+// compiled with `compile.sh`
+const R1CS_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/circom/test_folder/pipeline_circuit.r1cs"
+);
+const WASM_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/circom/test_folder/pipeline_circuit_js/pipeline_circuit.wasm"
+);
+
+fn f_circuit_params() -> CircomFCircuitParams {
+    CircomFCircuitParams {
+        r1cs: Path::new(R1CS_PATH).into(),
+        witness_backend: WitnessBackend::Wasm(Path::new(WASM_PATH).into()),
+        signal_map: None,
+    }
+}
+
+fn bench_circom_pipeline(c: &mut Criterion) {
+    let n_steps = 10;
+    let mut rng = rand::rngs::OsRng;
+    let poseidon_config = poseidon_canonical_config::<Fr>();
+
+    let f_circuit = FC::new(f_circuit_params()).unwrap();
+    let params = N::preprocess(
+        &mut rng,
+        &PreprocessorParam::new(poseidon_config, f_circuit.clone()),
+    )
+    .unwrap();
+    let mut nova = N::init(&params, f_circuit.clone(), vec![Fr::from(3_u32)]).unwrap();
+    for _ in 0..3 {
+        nova.prove_step(rng, VecF(vec![]), None).unwrap();
+    }
+
+    // report the share of the step spent in the witness calculation of circom, and the speedup of
+    // the pipelined proving, which is at most 1 / (1 - share)
+    let start = Instant::now();
+    f_circuit
+        .step_native(0, nova.z_i.clone(), VecF(vec![]))
+        .unwrap();
+    let native_time = start.elapsed();
+    let start = Instant::now();
+    nova.clone().prove_step(rng, VecF(vec![]), None).unwrap();
+    let step_time = start.elapsed();
+    let sequential_time = time_steps(|| {
+        let mut nova = nova.clone();
+        for _ in 0..n_steps {
+            nova.prove_step(rng, VecF(vec![]), None).unwrap();
+        }
+    });
+    let pipelined_time = time_steps(|| {
+        nova.clone()
+            .prove_steps_pipelined_with(rng, vec![VecF(vec![]); n_steps], || {
+                FC::new(f_circuit_params())
+            })
+            .unwrap()
+    });
+    println!(
+        "circom witness calculation: {:.0}% of the step time, pipelined speedup over {} steps: {:.2}x",
+        100.0 * native_time.as_secs_f64() / step_time.as_secs_f64(),
+        n_steps,
+        sequential_time.as_secs_f64() / pipelined_time.as_secs_f64()
+    );
+
+    let mut group = c.benchmark_group(format!(
+        "Nova - BN254-Grumpkin curves - circom FCircuit: {} constraints - {} steps",
+        N_CONSTRAINTS, n_steps
+    ));
+    group.significance_level(0.1).sample_size(10);
+    group.bench_function("prove_step", |b| {
+        b.iter(|| {
+            let mut nova = black_box(nova.clone());
+            for _ in 0..n_steps {
+                nova.prove_step(rng, VecF(vec![]), None).unwrap();
+            }
+        })
+    });
+    // the worker thread builds its own CircomFCircuit, since its witness calculator is not Send
+    group.bench_function("prove_steps_pipelined", |b| {
+        b.iter(|| {
+            black_box(nova.clone())
+                .prove_steps_pipelined_with(rng, vec![VecF(vec![]); n_steps], || {
+                    FC::new(f_circuit_params())
+                })
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn time_steps(f: impl FnOnce()) -> Duration {
+    let start = Instant::now();
+    f();
+    start.elapsed()
+}
+
+criterion_group!(benches, bench_circom_pipeline);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+use criterion::*;
+use pprof::criterion::{Output, PProfProfiler};
+
+use ark_bn254::{Bn254, Fr, G1Projective};
+use ark_std::{UniformRand, Zero};
+
+use folding_schemes::commitment::{kzg::KZG, pedersen::Pedersen, CommitmentScheme};
+
+fn bench_commit_sparse(c: &mut Criterion) {
+    let mut rng = rand::rngs::OsRng;
+
+    for n in [16_usize, 18].iter() {
+        let len = 1 << n; // 2^n
+        let (pedersen_params, _) = Pedersen::<G1Projective>::setup(&mut rng, len).unwrap();
+        let (mut kzg_pk, _) = KZG::<Bn254>::setup(&mut rng, len).unwrap();
+        kzg_pk.add_lagrange_basis(len).unwrap();
+
+        // vector with 1% of non-zero entries
+        let pairs: Vec<(usize, Fr)> = (0..len)
+            .step_by(100)
+            .map(|i| (i, Fr::rand(&mut rng)))
+            .collect();
+        let mut v = vec![Fr::zero(); len];
+        for (i, v_i) in &pairs {
+            v[*i] = *v_i;
+        }
+
+        let mut group = c.benchmark_group(format!(
+            "Sparse commit, 1% density - vector length: {} (2^{})",
+            len, n
+        ));
+        group.significance_level(0.1).sample_size(10);
+        group.bench_function("Pedersen commit", |b| {
+            b.iter(|| {
+                Pedersen::<G1Projective>::commit(black_box(&pedersen_params), &v, &Fr::zero())
+                    .unwrap()
+            })
+        });
+        group.bench_function("Pedersen commit_sparse", |b| {
+            b.iter(|| {
+                Pedersen::<G1Projective>::commit_sparse(
+                    black_box(&pedersen_params),
+                    len,
+                    &pairs,
+                    &Fr::zero(),
+                )
+                .unwrap()
+            })
+        });
+        group.bench_function("KZG commit (Lagrange basis)", |b| {
+            b.iter(|| KZG::<Bn254>::commit(black_box(&kzg_pk), &v, &Fr::zero()).unwrap())
+        });
+        group.bench_function("KZG commit_sparse (Lagrange basis)", |b| {
+            b.iter(|| {
+                KZG::<Bn254>::commit_sparse(black_box(&kzg_pk), len, &pairs, &Fr::zero()).unwrap()
+            })
+        });
+        group.finish();
+    }
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().with_profiler(PProfProfiler::new(100, Output::Flamegraph(None)));
+    targets = bench_commit_sparse
+}
+criterion_main!(benches);
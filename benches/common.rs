@@ -35,7 +35,7 @@ where
 
     // warmup steps
     for _ in 0..5 {
-        fs.prove_step(rng, vec![], None)?;
+        fs.prove_step(rng, (), None)?;
     }
 
     let mut group = c.benchmark_group(format!(
@@ -44,7 +44,7 @@ where
     ));
     group.significance_level(0.1).sample_size(10);
     group.bench_function("prove_step", |b| {
-        b.iter(|| black_box(fs.clone()).prove_step(rng, vec![], None).unwrap())
+        b.iter(|| black_box(fs.clone()).prove_step(rng, (), None).unwrap())
     });
 
     // verify the IVCProof
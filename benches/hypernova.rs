@@ -10,6 +10,7 @@ use folding_schemes::{
     folding::{hypernova::HyperNova, nova::PreprocessorParam},
     frontend::{utils::CustomFCircuit, FCircuit},
     transcript::poseidon::poseidon_canonical_config,
+    FoldingScheme, MultiFolding,
 };
 
 mod common;
@@ -79,5 +80,61 @@ fn bench_hypernova_ivc(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, bench_hypernova_ivc);
+/// Benchmarks a multifolding `prove_step`, with `MU = NU = 2`, whose augmented circuit contains
+/// the recombination of `c` of the NIMFS verifier for several instances. To compare against a
+/// previous implementation, save a criterion baseline on it and compare against it, see
+/// benches/README.md.
+fn bench_hypernova_multifolding(c: &mut Criterion) {
+    type HN = HyperNova<
+        pallas_G,
+        pallas_GVar,
+        vesta_G,
+        vesta_GVar,
+        CustomFCircuit<pallas_Fr>,
+        Pedersen<pallas_G>,
+        Pedersen<vesta_G>,
+        2,
+        2,
+        false,
+    >;
+
+    let mut rng = rand::rngs::OsRng;
+    let poseidon_config = poseidon_canonical_config::<pallas_Fr>();
+
+    for n in [14_usize, 16].iter() {
+        let fcircuit = CustomFCircuit::<pallas_Fr>::new(1 << n).unwrap();
+        let prep_param = PreprocessorParam::new(poseidon_config.clone(), fcircuit);
+        let hn_params = HN::preprocess(&mut rng, &prep_param).unwrap();
+        let mut hn = HN::init(&hn_params, fcircuit, vec![pallas_Fr::from(3_u32)]).unwrap();
+
+        let other_instances = (
+            vec![hn
+                .new_running_instance(rng, vec![pallas_Fr::from(85_u32)], ())
+                .unwrap()],
+            vec![hn
+                .new_incoming_instance(rng, vec![pallas_Fr::from(15_u32)], ())
+                .unwrap()],
+        );
+        // warmup step
+        hn.prove_step(rng, (), Some(other_instances.clone()))
+            .unwrap();
+
+        let mut group = c.benchmark_group(format!(
+            "HyperNova MU=2 NU=2 - Pallas-Vesta curves - FCircuit: {} (2^{}) constraints",
+            1 << n,
+            n
+        ));
+        group.significance_level(0.1).sample_size(10);
+        group.bench_function("prove_step", |b| {
+            b.iter(|| {
+                black_box(hn.clone())
+                    .prove_step(rng, (), Some(other_instances.clone()))
+                    .unwrap()
+            })
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_hypernova_ivc, bench_hypernova_multifolding);
 criterion_main!(benches);
@@ -0,0 +1,92 @@
+use criterion::*;
+use pprof::criterion::{Output, PProfProfiler};
+
+use ark_bn254::Fr;
+use ark_std::{log2, UniformRand};
+use rand::Rng;
+
+use folding_schemes::arith::ccs::CCS;
+use folding_schemes::folding::hypernova::{
+    nimfs::SigmasThetas,
+    utils::{compute_c, compute_sigmas_thetas},
+};
+use folding_schemes::utils::vec::SparseMatrix;
+
+/// Returns a random CCS with t=3 matrices of `m` rows and `n` columns with 3 non-zero entries per
+/// row, with the R1CS-like structure `M_0 z ∘ M_1 z - M_2 z`.
+fn random_ccs(m: usize, n: usize) -> CCS<Fr> {
+    let mut rng = rand::rngs::OsRng;
+    let M = (0..3)
+        .map(|_| SparseMatrix {
+            n_rows: m,
+            n_cols: n,
+            coeffs: (0..m)
+                .map(|_| {
+                    (0..3)
+                        .map(|_| (Fr::rand(&mut rng), rng.gen_range(0..n)))
+                        .collect()
+                })
+                .collect(),
+        })
+        .collect();
+    CCS {
+        m,
+        n,
+        l: 1,
+        t: 3,
+        q: 2,
+        d: 2,
+        s: log2(m) as usize,
+        s_prime: log2(n) as usize,
+        M,
+        S: vec![vec![0, 1], vec![2]],
+        c: vec![Fr::from(1), -Fr::from(1)],
+    }
+}
+
+fn bench_sigmas_thetas_c(c: &mut Criterion) {
+    let mut rng = rand::rngs::OsRng;
+
+    let n = 16;
+    let ccs = random_ccs(1 << n, 1 << n);
+    let (mu, nu) = (2, 2);
+    let random_vec = |rng: &mut rand::rngs::OsRng, len: usize| -> Vec<Fr> {
+        std::iter::repeat_with(|| Fr::rand(rng)).take(len).collect()
+    };
+    let z_lcccs: Vec<Vec<Fr>> = (0..mu).map(|_| random_vec(&mut rng, ccs.n)).collect();
+    let z_cccs: Vec<Vec<Fr>> = (0..nu).map(|_| random_vec(&mut rng, ccs.n)).collect();
+    let vec_r_x: Vec<Vec<Fr>> = (0..mu).map(|_| random_vec(&mut rng, ccs.s)).collect();
+    let beta = random_vec(&mut rng, ccs.s);
+    let r_x_prime = random_vec(&mut rng, ccs.s);
+    let gamma = Fr::rand(&mut rng);
+
+    let mut group = c.benchmark_group(format!(
+        "HyperNova sigmas, thetas & c - CCS with t=3 and 2^{} rows, mu={}, nu={}",
+        n, mu, nu
+    ));
+    group.significance_level(0.1).sample_size(10);
+    for n_threads in [1, 2, 4, 8] {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(n_threads)
+            .build()
+            .unwrap();
+        group.bench_function(format!("{} threads", n_threads), |b| {
+            b.iter(|| {
+                pool.install(|| {
+                    let sigmas_thetas: SigmasThetas<Fr> =
+                        compute_sigmas_thetas(black_box(&ccs), &z_lcccs, &z_cccs, &r_x_prime)
+                            .unwrap();
+                    compute_c(&ccs, &sigmas_thetas, gamma, &beta, &vec_r_x, &r_x_prime).unwrap()
+                })
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().with_profiler(PProfProfiler::new(100, Output::Flamegraph(None)));
+    targets = bench_sigmas_thetas_c
+}
+criterion_main!(benches);
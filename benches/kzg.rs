@@ -0,0 +1,60 @@
+use criterion::*;
+use pprof::criterion::{Output, PProfProfiler};
+
+use ark_bn254::{Bn254, Fr};
+use ark_std::{UniformRand, Zero};
+
+use folding_schemes::commitment::{kzg::KZG, CommitmentScheme};
+
+fn bench_kzg_commit(c: &mut Criterion) {
+    let mut rng = rand::rngs::OsRng;
+
+    for n in [16_usize, 18, 20].iter() {
+        let len = 1 << n; // 2^n
+        let (pk, _) = KZG::<Bn254>::setup(&mut rng, len).unwrap();
+        let mut pk_lagrange = pk.clone();
+        pk_lagrange.add_lagrange_basis(len).unwrap();
+
+        let v: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(&mut rng))
+            .take(len)
+            .collect();
+        let point = Fr::rand(&mut rng);
+
+        let mut group = c.benchmark_group(format!("KZG - vector length: {} (2^{})", len, n));
+        group.significance_level(0.1).sample_size(10);
+        group.bench_function("commit (monomial basis)", |b| {
+            b.iter(|| KZG::<Bn254>::commit(black_box(&pk), black_box(&v), &Fr::zero()).unwrap())
+        });
+        group.bench_function("commit (Lagrange basis)", |b| {
+            b.iter(|| {
+                KZG::<Bn254>::commit(black_box(&pk_lagrange), black_box(&v), &Fr::zero()).unwrap()
+            })
+        });
+        group.bench_function("prove (monomial basis)", |b| {
+            b.iter(|| {
+                KZG::<Bn254>::prove_with_challenge(black_box(&pk), point, &v, &Fr::zero(), None)
+                    .unwrap()
+            })
+        });
+        group.bench_function("prove (Lagrange basis)", |b| {
+            b.iter(|| {
+                KZG::<Bn254>::prove_with_challenge(
+                    black_box(&pk_lagrange),
+                    point,
+                    &v,
+                    &Fr::zero(),
+                    None,
+                )
+                .unwrap()
+            })
+        });
+        group.finish();
+    }
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().with_profiler(PProfProfiler::new(100, Output::Flamegraph(None)));
+    targets = bench_kzg_commit
+}
+criterion_main!(benches);
@@ -0,0 +1,115 @@
+use criterion::*;
+use std::time::Instant;
+
+use ark_bn254::{constraints::GVar, Fr, G1Projective as G};
+use ark_grumpkin::{constraints::GVar as GVar2, Projective as G2};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+use folding_schemes::{
+    commitment::pedersen::Pedersen,
+    folding::nova::{Nova, PreprocessorParam},
+    frontend::{utils::CustomFCircuit, FCircuit},
+    transcript::poseidon::poseidon_canonical_config,
+    Error, FoldingScheme,
+};
+
+/// FCircuit with the constraints of a CustomFCircuit, whose native step repeats the computation
+/// `native_rounds` times, to emulate a frontend whose witness generation is as expensive as the
+/// folding (eg. circom's witness calculator).
+#[derive(Clone, Copy, Debug)]
+struct SlowNativeFCircuit {
+    inner: CustomFCircuit<Fr>,
+    native_rounds: usize,
+}
+
+impl FCircuit<Fr> for SlowNativeFCircuit {
+    // (number of constraints, native rounds)
+    type Params = (usize, usize);
+    type ExternalInputs = ();
+    type ExternalInputsVar = ();
+
+    fn new((n_constraints, native_rounds): Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            inner: CustomFCircuit::new(n_constraints)?,
+            native_rounds,
+        })
+    }
+    fn state_len(&self) -> usize {
+        self.inner.state_len()
+    }
+    fn step_native(&self, i: usize, z_i: Vec<Fr>, _: ()) -> Result<Vec<Fr>, Error> {
+        let z_i1 = self.inner.step_native(i, z_i.clone(), ())?;
+        for _ in 1..self.native_rounds {
+            black_box(self.inner.step_native(i, z_i.clone(), ())?);
+        }
+        Ok(z_i1)
+    }
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<Fr>,
+        i: usize,
+        z_i: Vec<FpVar<Fr>>,
+        _: (),
+    ) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+        self.inner.generate_step_constraints(cs, i, z_i, ())
+    }
+}
+
+type N = Nova<G, GVar, G2, GVar2, SlowNativeFCircuit, Pedersen<G>, Pedersen<G2>, false>;
+
+fn bench_nova_pipeline(c: &mut Criterion) {
+    let n = 16;
+    let n_steps = 10;
+    let mut rng = rand::rngs::OsRng;
+    let poseidon_config = poseidon_canonical_config::<Fr>();
+
+    // calibrate the native rounds so that the native step takes as long as the rest of the step,
+    // ie. half of the sequential step time
+    let f_circuit = SlowNativeFCircuit::new((1 << n, 1)).unwrap();
+    let params = N::preprocess(
+        &mut rng,
+        &PreprocessorParam::new(poseidon_config, f_circuit),
+    )
+    .unwrap();
+    let mut nova = N::init(&params, f_circuit, vec![Fr::from(3_u32)]).unwrap();
+    for _ in 0..3 {
+        nova.prove_step(rng, (), None).unwrap();
+    }
+    let start = Instant::now();
+    nova.clone().prove_step(rng, (), None).unwrap();
+    let step_time = start.elapsed();
+    let start = Instant::now();
+    f_circuit.step_native(0, nova.z_i.clone(), ()).unwrap();
+    let native_time = start.elapsed().max(std::time::Duration::from_nanos(1));
+    let native_rounds = (step_time.as_nanos() / native_time.as_nanos()).max(1) as usize;
+
+    let f_circuit = SlowNativeFCircuit::new((1 << n, native_rounds)).unwrap();
+    nova.F = f_circuit;
+
+    let mut group = c.benchmark_group(format!(
+        "Nova - BN254-Grumpkin curves - FCircuit: 2^{} constraints, native step ~50% of the step time - {} steps",
+        n, n_steps
+    ));
+    group.significance_level(0.1).sample_size(10);
+    group.bench_function("prove_step", |b| {
+        b.iter(|| {
+            let mut nova = black_box(nova.clone());
+            for _ in 0..n_steps {
+                nova.prove_step(rng, (), None).unwrap();
+            }
+        })
+    });
+    // with full overlap, the time is ~half of the sequential one, plus one native step
+    group.bench_function("prove_steps_pipelined", |b| {
+        b.iter(|| {
+            black_box(nova.clone())
+                .prove_steps_pipelined(rng, vec![(); n_steps])
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_nova_pipeline);
+criterion_main!(benches);
@@ -10,6 +10,7 @@ use folding_schemes::{
     folding::protogalaxy::ProtoGalaxy,
     frontend::{utils::CustomFCircuit, FCircuit},
     transcript::poseidon::poseidon_canonical_config,
+    FoldingScheme,
 };
 
 mod common;
@@ -73,5 +74,74 @@ fn bench_protogalaxy_ivc(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, bench_protogalaxy_ivc);
+/// Benchmarks folding 3 instances of the FCircuit, either with 3 `prove_step` calls with `K = 1`,
+/// or with a single `prove_step` with `K = 3`. The measurement of the latter includes generating
+/// the 2 other incoming instances, since they are computed by the prover in both cases.
+fn bench_protogalaxy_multi_instances(c: &mut Criterion) {
+    type PG<const K: usize> = ProtoGalaxy<
+        pallas_G,
+        pallas_GVar,
+        vesta_G,
+        vesta_GVar,
+        CustomFCircuit<pallas_Fr>,
+        Pedersen<pallas_G>,
+        Pedersen<vesta_G>,
+        K,
+    >;
+
+    let mut rng = rand::rngs::OsRng;
+    let poseidon_config = poseidon_canonical_config::<pallas_Fr>();
+
+    for n in [14_usize, 16].iter() {
+        let fcircuit = CustomFCircuit::<pallas_Fr>::new(1 << n).unwrap();
+        let prep_param = (poseidon_config.clone(), fcircuit);
+        let z_0 = vec![pallas_Fr::from(3_u32)];
+
+        let pg1_params = PG::<1>::preprocess(&mut rng, &prep_param).unwrap();
+        let mut pg1 = PG::<1>::init(&pg1_params, fcircuit, z_0.clone()).unwrap();
+        // warmup step
+        pg1.prove_step(rng, (), None).unwrap();
+
+        let pg3_params = PG::<3>::preprocess(&mut rng, &prep_param).unwrap();
+        let mut pg3 = PG::<3>::init(&pg3_params, fcircuit, z_0).unwrap();
+        let new_incoming_instances = |pg: &PG<3>| {
+            (0..2)
+                .map(|i| pg.new_incoming_instance(vec![pallas_Fr::from(i as u32)], ()))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        };
+        // warmup step
+        let other_instances = new_incoming_instances(&pg3);
+        pg3.prove_step(rng, (), Some(other_instances)).unwrap();
+
+        let mut group = c.benchmark_group(format!(
+            "ProtoGalaxy multi-instances - Pallas-Vesta curves - FCircuit: {} (2^{}) constraints",
+            1 << n,
+            n
+        ));
+        group.significance_level(0.1).sample_size(10);
+        group.bench_function("3 instances, K=1", |b| {
+            b.iter(|| {
+                let mut pg = black_box(pg1.clone());
+                for _ in 0..3 {
+                    pg.prove_step(rng, (), None).unwrap();
+                }
+            })
+        });
+        group.bench_function("3 instances, K=3", |b| {
+            b.iter(|| {
+                let mut pg = black_box(pg3.clone());
+                let other_instances = new_incoming_instances(&pg);
+                pg.prove_step(rng, (), Some(other_instances)).unwrap();
+            })
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(
+    benches,
+    bench_protogalaxy_ivc,
+    bench_protogalaxy_multi_instances
+);
 criterion_main!(benches);
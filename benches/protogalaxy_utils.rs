@@ -0,0 +1,35 @@
+use criterion::*;
+use pprof::criterion::{Output, PProfProfiler};
+
+use ark_bn254::Fr;
+use ark_std::UniformRand;
+
+use folding_schemes::folding::protogalaxy::utils::{pow_i, pow_i_table};
+
+fn bench_pow_i(c: &mut Criterion) {
+    let mut rng = rand::rngs::OsRng;
+
+    let t = 18;
+    let betas: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(&mut rng))
+        .take(t)
+        .collect();
+
+    let mut group = c.benchmark_group(format!("ProtoGalaxy pow_i over 2^{} rows", t));
+    group.significance_level(0.1).sample_size(10);
+    group.bench_function("naive", |b| {
+        b.iter(|| {
+            (0..1 << t)
+                .map(|i| pow_i(i, black_box(&betas)))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("table", |b| b.iter(|| pow_i_table(black_box(&betas))));
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().with_profiler(PProfProfiler::new(100, Output::Flamegraph(None)));
+    targets = bench_pow_i
+}
+criterion_main!(benches);
@@ -0,0 +1,70 @@
+use criterion::*;
+use pprof::criterion::{Output, PProfProfiler};
+
+use ark_bn254::Fr;
+use ark_std::UniformRand;
+use rand::Rng;
+
+use folding_schemes::arith::r1cs::R1CS;
+use folding_schemes::utils::vec::{check_equal_rows, SparseMatrix};
+
+/// Returns a random R1CS of `m` rows and `n` columns with 3 non-zero entries per row in each
+/// matrix.
+fn random_r1cs(m: usize, n: usize) -> R1CS<Fr> {
+    let mut rng = rand::rngs::OsRng;
+    let mut random_matrix = || SparseMatrix {
+        n_rows: m,
+        n_cols: n,
+        coeffs: (0..m)
+            .map(|_| {
+                (0..3)
+                    .map(|_| (Fr::rand(&mut rng), rng.gen_range(0..n)))
+                    .collect()
+            })
+            .collect(),
+    };
+    R1CS {
+        l: 1,
+        A: random_matrix(),
+        B: random_matrix(),
+        C: random_matrix(),
+    }
+}
+
+fn bench_relation_check(c: &mut Criterion) {
+    let mut rng = rand::rngs::OsRng;
+
+    let n = 18;
+    let r1cs = random_r1cs(1 << n, 1 << n);
+    let z: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(&mut rng))
+        .take(r1cs.A.n_cols)
+        .collect();
+    // the evaluation of a relaxed R1CS is compared against its error term E, which for this
+    // random z is the evaluation itself, so that all the rows are checked
+    let expected = r1cs.eval_at_z(&z).unwrap();
+
+    let mut group = c.benchmark_group(format!("Relaxed R1CS relation check - 2^{} rows", n));
+    group.significance_level(0.1).sample_size(10);
+    for n_threads in [1, 2, 4, 8] {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(n_threads)
+            .build()
+            .unwrap();
+        group.bench_function(format!("{} threads", n_threads), |b| {
+            b.iter(|| {
+                pool.install(|| {
+                    let e = r1cs.eval_at_z(black_box(&z)).unwrap();
+                    check_equal_rows(&e, &expected).unwrap()
+                })
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().with_profiler(PProfProfiler::new(100, Output::Flamegraph(None)));
+    targets = bench_relation_check
+}
+criterion_main!(benches);
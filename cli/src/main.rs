@@ -1,6 +1,7 @@
 use ::clap::Parser;
 use ark_serialize::Write;
 use settings::Cli;
+use solidity_verifiers::foundry_test_file;
 use std::path::Path;
 use std::{fs, io};
 
@@ -17,6 +18,9 @@ fn create_or_open_then_write<T: AsRef<[u8]>>(path: &Path, content: &T) -> Result
 
 fn main() {
     let cli = Cli::parse();
+    if let Err(e) = cli.validate() {
+        e.exit();
+    }
 
     // generate a subscriber with the desired log level
     env_logger::builder()
@@ -31,10 +35,39 @@ fn main() {
     // Fetch the protocol data passed by the user from the file.
     let protocol_vk = std::fs::read(cli.protocol_vk).unwrap();
 
+    if cli.split {
+        let split_verifier = protocol.render_split(&protocol_vk, cli.pragma).unwrap();
+        let out_dir = out_path.parent().unwrap_or(Path::new("."));
+        for (name, solidity) in split_verifier.artifacts() {
+            create_or_open_then_write(&out_dir.join(name), &solidity).unwrap();
+        }
+        create_or_open_then_write(
+            &out_dir.join("DEPLOYMENT.md"),
+            &split_verifier.deployment_order(),
+        )
+        .unwrap();
+        return;
+    }
+
     // Generate the Solidity Verifier contract for the selected protocol with the given data.
     create_or_open_then_write(
         &out_path,
-        &protocol.render(&protocol_vk, cli.pragma).unwrap(),
+        &protocol.render(&protocol_vk, cli.pragma.clone()).unwrap(),
     )
     .unwrap();
+
+    // Generate the Foundry test of the verifier, which imports it from the same directory.
+    if let Some(calldata) = cli.calldata {
+        let calldata = fs::read(calldata).unwrap();
+        let verifier_file = out_path.file_name().unwrap().to_str().unwrap();
+        let decider = protocol.decider_name().unwrap_or_default();
+        create_or_open_then_write(
+            &out_path
+                .parent()
+                .unwrap_or(Path::new("."))
+                .join(foundry_test_file(&decider)),
+            &protocol.render_foundry_test(verifier_file, &calldata, cli.pragma),
+        )
+        .unwrap();
+    }
 }
@@ -1,7 +1,8 @@
 use ark_serialize::SerializationError;
-use clap::{Parser, ValueEnum};
+use clap::{error::ErrorKind, CommandFactory, Parser, ValueEnum};
 use solidity_verifiers::{
-    Groth16VerifierKey, KZG10VerifierKey, NovaCycleFoldVerifierKey, ProtocolVerifierKey,
+    get_foundry_test_for_decider, ContractNames, Groth16VerifierKey, KZG10VerifierKey,
+    NovaCycleFoldSplitVerifier, NovaCycleFoldVerifierKey, ProtocolVerifierKey,
 };
 use std::{env, fmt::Display, path::PathBuf};
 
@@ -44,6 +45,53 @@ impl Protocol {
             .render_as_template(pragma)),
         }
     }
+
+    /// Whether the verifier can be split into several contracts, which is only supported by the
+    /// Nova + CycleFold decider.
+    pub(crate) fn can_split(&self) -> bool {
+        matches!(self, Self::NovaCycleFold)
+    }
+
+    /// Renders the verifier split into several contracts. Only called for the protocols for which
+    /// `can_split` holds, as checked by `Cli::validate`.
+    pub(crate) fn render_split(
+        &self,
+        data: &[u8],
+        pragma: Option<String>,
+    ) -> Result<NovaCycleFoldSplitVerifier, SerializationError> {
+        match self {
+            Self::NovaCycleFold => Ok(NovaCycleFoldVerifierKey::deserialize_protocol_verifier_key(
+                data,
+            )?
+            .render_split_as_templates(pragma)),
+            _ => unreachable!("the {} verifier can not be split", self),
+        }
+    }
+
+    /// Name of the verifier contract of the protocol, which is only a decider for the Nova +
+    /// CycleFold protocol.
+    pub(crate) fn decider_name(&self) -> Option<String> {
+        match self {
+            Self::NovaCycleFold => Some(ContractNames::default().decider),
+            _ => None,
+        }
+    }
+
+    /// Renders the Foundry test of the decider verifier written to `verifier_file`, which checks
+    /// it against the given calldata of a proof.
+    pub(crate) fn render_foundry_test(
+        &self,
+        verifier_file: &str,
+        calldata: &[u8],
+        pragma: Option<String>,
+    ) -> String {
+        match self.decider_name() {
+            Some(decider) => {
+                get_foundry_test_for_decider(&decider, verifier_file, calldata, pragma)
+            }
+            None => unreachable!("checked by Cli::validate"),
+        }
+    }
 }
 
 const ABOUT: &str = "A Command-Line Interface (CLI) tool to generate the Solidity smart contracts that verify proofs of Zero Knowledge cryptographic protocols.
@@ -110,4 +158,38 @@ pub(crate) struct Cli {
     /// Selects the Solidity compiler version to be set in the Solidity Verifier contract artifact.
     #[arg(long, default_value=None)]
     pub pragma: Option<String>,
+
+    /// Splits the Nova + CycleFold decider verifier into the Groth16Verifier, KZG10Verifier and NovaDecider contracts, for the cases in which the single contract exceeds the EIP-170 size limit. They are written to the directory of the output path, together with their deployment order in `DEPLOYMENT.md`.
+    #[arg(long, default_value_t = false)]
+    pub split: bool,
+
+    /// Sets the input path for the file containing the calldata of a proof, as returned by `prepare_calldata`, with which a Foundry test of the Nova + CycleFold decider verifier is emitted next to it, as `NovaDecider.t.sol`.
+    #[arg(long, conflicts_with = "split")]
+    pub calldata: Option<PathBuf>,
+}
+
+impl Cli {
+    /// Checks the combinations of arguments that clap can not express, returning the error with
+    /// which clap reports them.
+    pub(crate) fn validate(&self) -> Result<(), clap::Error> {
+        if self.split && !self.protocol.can_split() {
+            return Err(Self::command().error(
+                ErrorKind::ArgumentConflict,
+                format!(
+                    "the {} verifier can not be split, `--split` is only supported by the NovaCycleFold protocol",
+                    self.protocol
+                ),
+            ));
+        }
+        if self.calldata.is_some() && self.protocol.decider_name().is_none() {
+            return Err(Self::command().error(
+                ErrorKind::ArgumentConflict,
+                format!(
+                    "Foundry tests are only emitted for deciders, `--calldata` is not supported by the {} protocol",
+                    self.protocol
+                ),
+            ));
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,161 @@
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(non_camel_case_types)]
+#![allow(clippy::upper_case_acronyms)]
+
+use ark_bn254::{constraints::GVar, Fr, G1Projective as Projective};
+use ark_crypto_primitives::{
+    crh::{
+        poseidon::constraints::{CRHGadget, CRHParametersVar},
+        poseidon::CRH,
+        CRHScheme, CRHSchemeGadget,
+    },
+    sponge::{poseidon::PoseidonConfig, Absorb},
+};
+use ark_ff::PrimeField;
+use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use std::rc::Rc;
+use std::time::Instant;
+
+use folding_schemes::commitment::pedersen::Pedersen;
+use folding_schemes::folding::nova::{Nova, PreprocessorParam};
+use folding_schemes::frontend::{
+    adapter::{ArkCircuitAdapter, ArkCircuitBuilder, ArkCircuitIOMap},
+    FCircuit, VecF,
+};
+use folding_schemes::transcript::poseidon::poseidon_canonical_config;
+use folding_schemes::FoldingScheme;
+
+/// This is an arkworks circuit that was not written for folding: it only implements
+/// `ConstraintSynthesizer`, and checks that the public `digest` is the Poseidon hash of the
+/// private `preimage`.
+///
+/// Instead of rewriting it as an `FCircuit`, we use the `ArkCircuitAdapter` to fold it in a chain
+/// of hashes, where the preimage of each step is the digest of the previous one:
+///
+///        ┌─┐      ┌─┐      ┌─┐
+/// ──────►│F├─────►│F├─────►│F├─────►
+///  z_1   └─┘ z_2  └─┘ z_3  └─┘ z_4
+///
+/// where each F checks that z_{i+1} = Hash(z_i).
+#[derive(Clone, Debug)]
+pub struct PoseidonPreimageCircuit<F: PrimeField + Absorb> {
+    poseidon_config: PoseidonConfig<F>,
+    preimage: F,
+    digest: F,
+}
+
+impl<F: PrimeField + Absorb> ConstraintSynthesizer<F> for PoseidonPreimageCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let digest = FpVar::new_input(cs.clone(), || Ok(self.digest))?;
+        let preimage = FpVar::new_witness(cs.clone(), || Ok(self.preimage))?;
+        let crh_params = CRHParametersVar::<F>::new_constant(cs.clone(), self.poseidon_config)?;
+        let h = CRHGadget::<F>::evaluate(&crh_params, &[preimage])?;
+        h.enforce_equal(&digest)
+    }
+}
+
+/// cargo test --example ark_circuit_adapter
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use ark_r1cs_std::R1CSVar;
+    use ark_relations::r1cs::ConstraintSystem;
+    use folding_schemes::frontend::VecFpVar;
+
+    // test to check that the adapted circuit computes the same values inside and outside the
+    // circuit
+    #[test]
+    fn test_f_circuit() {
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let F_circuit = adapted_circuit(poseidon_config.clone());
+
+        let z_i = vec![Fr::from(1_u32)];
+        let z_i1 = F_circuit.step_native(0, z_i.clone(), VecF(vec![])).unwrap();
+        assert_eq!(
+            z_i1,
+            vec![CRH::<Fr>::evaluate(&poseidon_config, z_i.clone()).unwrap()]
+        );
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_iVar = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let computed_z_i1Var = F_circuit
+            .generate_step_constraints(cs.clone(), 0, z_iVar, VecFpVar(vec![]))
+            .unwrap();
+        assert_eq!(computed_z_i1Var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
+
+/// returns the PoseidonPreimageCircuit adapted as an FCircuit without external inputs
+fn adapted_circuit(
+    poseidon_config: PoseidonConfig<Fr>,
+) -> ArkCircuitAdapter<Fr, PoseidonPreimageCircuit<Fr>, 0> {
+    // the builder instantiates the circuit of each step from the current state, as it would be
+    // done to use the circuit on its own
+    let builder: ArkCircuitBuilder<Fr, PoseidonPreimageCircuit<Fr>> =
+        Rc::new(move |_i, z_i, _external_inputs| PoseidonPreimageCircuit {
+            poseidon_config: poseidon_config.clone(),
+            preimage: z_i[0],
+            digest: CRH::<Fr>::evaluate(&poseidon_config, [z_i[0]]).unwrap(),
+        });
+    // the circuit allocates the public `digest` (variable 0) and then the private `preimage`
+    // (variable 1), which are the outgoing and the incoming state respectively
+    let io_map = ArkCircuitIOMap {
+        state_in: vec![1],
+        state_out: vec![0],
+        external_inputs: vec![],
+    };
+    ArkCircuitAdapter::new((builder, io_map)).unwrap()
+}
+
+/// cargo run --release --example ark_circuit_adapter
+fn main() {
+    let num_steps = 5;
+    let initial_state = vec![Fr::from(1_u32)];
+
+    let poseidon_config = poseidon_canonical_config::<Fr>();
+    let F_circuit = adapted_circuit(poseidon_config.clone());
+
+    type N = Nova<
+        Projective,
+        GVar,
+        Projective2,
+        GVar2,
+        ArkCircuitAdapter<Fr, PoseidonPreimageCircuit<Fr>, 0>,
+        Pedersen<Projective>,
+        Pedersen<Projective2>,
+        false,
+    >;
+
+    let mut rng = rand::rngs::OsRng;
+
+    println!("Prepare Nova's ProverParams & VerifierParams");
+    let nova_preprocess_params = PreprocessorParam::new(poseidon_config, F_circuit.clone());
+    let nova_params = N::preprocess(&mut rng, &nova_preprocess_params).unwrap();
+
+    println!("Initialize FoldingScheme");
+    let mut folding_scheme = N::init(&nova_params, F_circuit, initial_state.clone()).unwrap();
+
+    // compute a step of the IVC
+    for i in 0..num_steps {
+        let start = Instant::now();
+        folding_scheme.prove_step(rng, VecF(vec![]), None).unwrap();
+        println!("Nova::prove_step {}: {:?}", i, start.elapsed());
+    }
+    println!(
+        "state at last step (after {} iterations): {:?}",
+        num_steps,
+        folding_scheme.state().z_i
+    );
+
+    println!("Run the Nova's IVC verifier");
+    let ivc_proof = folding_scheme.ivc_proof();
+    N::verify(
+        nova_params.1, // Nova's verifier params
+        ivc_proof,
+    )
+    .unwrap();
+}
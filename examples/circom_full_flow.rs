@@ -21,16 +21,16 @@ use folding_schemes::{
     commitment::{kzg::KZG, pedersen::Pedersen},
     folding::{
         nova::{
-            decider_eth::{prepare_calldata, Decider as DeciderEth},
+            decider_eth::{decode_verifier_output, prepare_calldata, Decider as DeciderEth},
             Nova, PreprocessorParam,
         },
         traits::CommittedInstanceOps,
     },
-    frontend::FCircuit,
+    frontend::{FCircuit, VecF},
     transcript::poseidon::poseidon_canonical_config,
     Decider, FoldingScheme,
 };
-use frontends::circom::CircomFCircuit;
+use frontends::circom::{CircomFCircuit, CircomFCircuitParams, WitnessBackend};
 use solidity_verifiers::{
     evm::{compile_solidity, Evm},
     utils::get_function_selector_for_nova_cyclefold_verifier,
@@ -45,16 +45,16 @@ fn main() {
     // set the external inputs to be used at each step of the IVC, it has length of 10 since this
     // is the number of steps that we will do
     let external_inputs = vec![
-        vec![Fr::from(6u32), Fr::from(7u32)],
-        vec![Fr::from(8u32), Fr::from(9u32)],
-        vec![Fr::from(10u32), Fr::from(11u32)],
-        vec![Fr::from(12u32), Fr::from(13u32)],
-        vec![Fr::from(14u32), Fr::from(15u32)],
-        vec![Fr::from(6u32), Fr::from(7u32)],
-        vec![Fr::from(8u32), Fr::from(9u32)],
-        vec![Fr::from(10u32), Fr::from(11u32)],
-        vec![Fr::from(12u32), Fr::from(13u32)],
-        vec![Fr::from(14u32), Fr::from(15u32)],
+        VecF(vec![Fr::from(6u32), Fr::from(7u32)]),
+        VecF(vec![Fr::from(8u32), Fr::from(9u32)]),
+        VecF(vec![Fr::from(10u32), Fr::from(11u32)]),
+        VecF(vec![Fr::from(12u32), Fr::from(13u32)]),
+        VecF(vec![Fr::from(14u32), Fr::from(15u32)]),
+        VecF(vec![Fr::from(6u32), Fr::from(7u32)]),
+        VecF(vec![Fr::from(8u32), Fr::from(9u32)]),
+        VecF(vec![Fr::from(10u32), Fr::from(11u32)]),
+        VecF(vec![Fr::from(12u32), Fr::from(13u32)]),
+        VecF(vec![Fr::from(14u32), Fr::from(15u32)]),
     ];
 
     // initialize the Circom circuit
@@ -63,17 +63,21 @@ fn main() {
         "./frontends/src/circom/test_folder/with_external_inputs_js/with_external_inputs.wasm",
     );
 
-    let f_circuit_params = (r1cs_path.into(), wasm_path.into(), 1, 2);
-    let f_circuit = CircomFCircuit::<Fr>::new(f_circuit_params).unwrap();
+    let f_circuit_params = CircomFCircuitParams {
+        r1cs: r1cs_path.into(),
+        witness_backend: WitnessBackend::Wasm(wasm_path.into()),
+        signal_map: None,
+    };
+    let f_circuit = CircomFCircuit::<Fr, 2>::new(f_circuit_params).unwrap();
 
     pub type N =
-        Nova<G1, GVar, G2, GVar2, CircomFCircuit<Fr>, KZG<'static, Bn254>, Pedersen<G2>, false>;
+        Nova<G1, GVar, G2, GVar2, CircomFCircuit<Fr, 2>, KZG<'static, Bn254>, Pedersen<G2>, false>;
     pub type D = DeciderEth<
         G1,
         GVar,
         G2,
         GVar2,
-        CircomFCircuit<Fr>,
+        CircomFCircuit<Fr, 2>,
         KZG<'static, Bn254>,
         Pedersen<G2>,
         Groth16<Bn254>,
@@ -153,7 +157,12 @@ fn main() {
     let mut evm = Evm::default();
     let verifier_address = evm.create(nova_cyclefold_verifier_bytecode);
     let (_, output) = evm.call(verifier_address, calldata.clone());
-    assert_eq!(*output.last().unwrap(), 1);
+    // the verifier returns the verified number of steps and final state
+    let (i, z_i) = decode_verifier_output(&output, f_circuit.state_len()).unwrap();
+    println!(
+        "Solidity verifier accepted the proof of step {}, with z_i: {:?}",
+        i, z_i
+    );
 
     // save smart contract and the calldata
     println!("storing nova-verifier.sol and the calldata into files");
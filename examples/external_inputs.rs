@@ -14,10 +14,10 @@ use ark_crypto_primitives::{
 };
 use ark_ff::PrimeField;
 use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
-use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::alloc::{AllocVar, AllocationMode};
 use ark_r1cs_std::fields::fp::FpVar;
-use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
-use core::marker::PhantomData;
+use ark_relations::r1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+use core::{borrow::Borrow, marker::PhantomData};
 use std::time::Instant;
 
 use folding_schemes::commitment::{kzg::KZG, pedersen::Pedersen};
@@ -62,6 +62,11 @@ use folding_schemes::{Error, FoldingScheme};
 ///
 /// The last state z_i is used together with the external input w_i as inputs to compute the new
 /// state z_{i+1}.
+///
+/// The external inputs are given as the `ExternalInputs` struct and allocated in-circuit as the
+/// `ExternalInputsVar` struct, so that the step logic does not need to flatten them into a vector
+/// of field elements. Circuits whose external inputs are just a vector of field elements can use
+/// the `VecF` and `VecFpVar` wrappers from `folding_schemes::frontend` instead.
 #[derive(Clone, Debug)]
 pub struct ExternalInputsCircuit<F: PrimeField>
 where
@@ -70,11 +75,41 @@ where
     _f: PhantomData<F>,
     poseidon_config: PoseidonConfig<F>,
 }
+
+/// External inputs of each step of the ExternalInputsCircuit
+#[derive(Clone, Debug, Default)]
+pub struct ExternalInputs<F: PrimeField> {
+    /// value hashed together with the state
+    pub w: F,
+}
+
+/// In-circuit representation of ExternalInputs
+#[derive(Clone, Debug)]
+pub struct ExternalInputsVar<F: PrimeField> {
+    pub w: FpVar<F>,
+}
+
+impl<F: PrimeField> AllocVar<ExternalInputs<F>, F> for ExternalInputsVar<F> {
+    fn new_variable<T: Borrow<ExternalInputs<F>>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|val| {
+            let cs = cs.into();
+            let w = FpVar::new_variable(cs, || Ok(val.borrow().w), mode)?;
+            Ok(Self { w })
+        })
+    }
+}
+
 impl<F: PrimeField> FCircuit<F> for ExternalInputsCircuit<F>
 where
     F: Absorb,
 {
     type Params = PoseidonConfig<F>;
+    type ExternalInputs = ExternalInputs<F>;
+    type ExternalInputsVar = ExternalInputsVar<F>;
 
     fn new(params: Self::Params) -> Result<Self, Error> {
         Ok(Self {
@@ -85,9 +120,6 @@ where
     fn state_len(&self) -> usize {
         1
     }
-    fn external_inputs_len(&self) -> usize {
-        1
-    }
 
     /// computes the next state value for the step of F for the given z_i and external_inputs
     /// z_{i+1}
@@ -95,9 +127,9 @@ where
         &self,
         _i: usize,
         z_i: Vec<F>,
-        external_inputs: Vec<F>,
+        external_inputs: Self::ExternalInputs,
     ) -> Result<Vec<F>, Error> {
-        let hash_input: [F; 2] = [z_i[0], external_inputs[0]];
+        let hash_input: [F; 2] = [z_i[0], external_inputs.w];
         let h = CRH::<F>::evaluate(&self.poseidon_config, hash_input).unwrap();
         Ok(vec![h])
     }
@@ -109,11 +141,11 @@ where
         cs: ConstraintSystemRef<F>,
         _i: usize,
         z_i: Vec<FpVar<F>>,
-        external_inputs: Vec<FpVar<F>>,
+        external_inputs: Self::ExternalInputsVar,
     ) -> Result<Vec<FpVar<F>>, SynthesisError> {
         let crh_params =
             CRHParametersVar::<F>::new_constant(cs.clone(), self.poseidon_config.clone())?;
-        let hash_input: [FpVar<F>; 2] = [z_i[0].clone(), external_inputs[0].clone()];
+        let hash_input: [FpVar<F>; 2] = [z_i[0].clone(), external_inputs.w];
         let h = CRHGadget::<F>::evaluate(&crh_params, &hash_input)?;
         Ok(vec![h])
     }
@@ -135,7 +167,7 @@ pub mod tests {
 
         let circuit = ExternalInputsCircuit::<Fr>::new(poseidon_config).unwrap();
         let z_i = vec![Fr::from(1_u32)];
-        let external_inputs = vec![Fr::from(3_u32)];
+        let external_inputs = ExternalInputs { w: Fr::from(3_u32) };
 
         let z_i1 = circuit
             .step_native(0, z_i.clone(), external_inputs.clone())
@@ -143,7 +175,7 @@ pub mod tests {
 
         let z_iVar = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
         let external_inputsVar =
-            Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+            ExternalInputsVar::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
 
         let computed_z_i1Var = circuit
             .generate_step_constraints(cs.clone(), 0, z_iVar, external_inputsVar)
@@ -158,13 +190,9 @@ fn main() {
     let initial_state = vec![Fr::from(1_u32)];
 
     // prepare the external inputs to be used at each folding step
-    let external_inputs = vec![
-        vec![Fr::from(3_u32)],
-        vec![Fr::from(33_u32)],
-        vec![Fr::from(73_u32)],
-        vec![Fr::from(103_u32)],
-        vec![Fr::from(125_u32)],
-    ];
+    let external_inputs = [3_u32, 33, 73, 103, 125]
+        .map(|w| ExternalInputs { w: Fr::from(w) })
+        .to_vec();
     assert_eq!(external_inputs.len(), num_steps);
 
     let poseidon_config = poseidon_canonical_config::<Fr>();
@@ -204,7 +232,7 @@ fn main() {
     println!(
         "state at last step (after {} iterations): {:?}",
         num_steps,
-        folding_scheme.state()
+        folding_scheme.state().z_i
     );
 
     println!("Run the Nova's IVC verifier");
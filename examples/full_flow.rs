@@ -23,7 +23,7 @@ use folding_schemes::{
     commitment::{kzg::KZG, pedersen::Pedersen},
     folding::{
         nova::{
-            decider_eth::{prepare_calldata, Decider as DeciderEth},
+            decider_eth::{decode_verifier_output, prepare_calldata, Decider as DeciderEth},
             Nova, PreprocessorParam,
         },
         traits::CommittedInstanceOps,
@@ -46,20 +46,19 @@ pub struct CubicFCircuit<F: PrimeField> {
 }
 impl<F: PrimeField> FCircuit<F> for CubicFCircuit<F> {
     type Params = ();
+    type ExternalInputs = ();
+    type ExternalInputsVar = ();
     fn new(_params: Self::Params) -> Result<Self, Error> {
         Ok(Self { _f: PhantomData })
     }
     fn state_len(&self) -> usize {
         1
     }
-    fn external_inputs_len(&self) -> usize {
-        0
-    }
     fn step_native(
         &self,
         _i: usize,
         z_i: Vec<F>,
-        _external_inputs: Vec<F>,
+        _external_inputs: Self::ExternalInputs,
     ) -> Result<Vec<F>, Error> {
         Ok(vec![z_i[0] * z_i[0] * z_i[0] + z_i[0] + F::from(5_u32)])
     }
@@ -68,7 +67,7 @@ impl<F: PrimeField> FCircuit<F> for CubicFCircuit<F> {
         cs: ConstraintSystemRef<F>,
         _i: usize,
         z_i: Vec<FpVar<F>>,
-        _external_inputs: Vec<FpVar<F>>,
+        _external_inputs: Self::ExternalInputsVar,
     ) -> Result<Vec<FpVar<F>>, SynthesisError> {
         let five = FpVar::<F>::new_constant(cs.clone(), F::from(5u32))?;
         let z_i = z_i[0].clone();
@@ -114,7 +113,7 @@ fn main() {
     // run n steps of the folding iteration
     for i in 0..n_steps {
         let start = Instant::now();
-        nova.prove_step(rng, vec![], None).unwrap();
+        nova.prove_step(rng, (), None).unwrap();
         println!("Nova::prove_step {}: {:?}", i, start.elapsed());
     }
 
@@ -161,7 +160,12 @@ fn main() {
     let mut evm = Evm::default();
     let verifier_address = evm.create(nova_cyclefold_verifier_bytecode);
     let (_, output) = evm.call(verifier_address, calldata.clone());
-    assert_eq!(*output.last().unwrap(), 1);
+    // the verifier returns the verified number of steps and final state
+    let (i, z_i) = decode_verifier_output(&output, f_circuit.state_len()).unwrap();
+    println!(
+        "Solidity verifier accepted the proof of step {}, with z_i: {:?}",
+        i, z_i
+    );
 
     // save smart contract and the calldata
     println!("storing nova-verifier.sol and the calldata into files");
@@ -0,0 +1,100 @@
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(non_camel_case_types)]
+#![allow(clippy::upper_case_acronyms)]
+
+use ark_bls12_377::{
+    constraints::{G1Var, PairingVar},
+    Bls12_377, Fq, Fr, G1Projective as Projective,
+};
+use ark_bw6_761::BW6_761;
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::Groth16;
+use ark_poly_commit::kzg10::VerifierKey;
+use ark_r1cs_std::{
+    alloc::AllocVar, boolean::Boolean, eq::EqGadget, fields::fp::FpVar, ToBitsGadget,
+};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_snark::SNARK;
+use ark_std::{UniformRand, Zero};
+use std::time::Instant;
+
+use folding_schemes::commitment::{
+    kzg::{Proof, KZG},
+    CommitmentScheme,
+};
+use folding_schemes::folding::circuits::decider::KZGVerifyGadget;
+
+/// Wrapper circuit that verifies a KZG opening over BLS12-377 inside a BW6-761 circuit, whose
+/// constraint field is the base field of BLS12-377. This is the building block to wrap a proof
+/// that contains KZG openings (such as the decider proof) into an outer SNARK.
+///
+/// The opening point and the evaluation are the public inputs of the wrapper, packed as elements
+/// of the BLS12-377 base field, while the commitment and the proof are witnesses.
+#[derive(Clone, Debug)]
+pub struct KZGVerifyWrapperCircuit {
+    pub vk: VerifierKey<Bls12_377>,
+    pub cm: Projective,
+    pub point: Fr,
+    pub proof: Proof<Projective>,
+}
+
+/// Maps an element of the BLS12-377 scalar field into its base field, which is larger.
+fn to_base_field(x: Fr) -> Fq {
+    Fq::from_le_bytes_mod_order(&x.into_bigint().to_bytes_le())
+}
+
+impl ConstraintSynthesizer<Fq> for KZGVerifyWrapperCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fq>) -> Result<(), SynthesisError> {
+        let point = FpVar::new_input(cs.clone(), || Ok(to_base_field(self.point)))?;
+        let eval = FpVar::new_input(cs.clone(), || Ok(to_base_field(self.proof.eval)))?;
+        let cm = G1Var::new_witness(cs.clone(), || Ok(self.cm))?;
+        let proof = G1Var::new_witness(cs.clone(), || Ok(self.proof.proof))?;
+
+        KZGVerifyGadget::<Bls12_377, PairingVar>::verify(
+            &self.vk,
+            &cm,
+            &point.to_bits_le()?,
+            &eval.to_bits_le()?,
+            &proof,
+        )?
+        .enforce_equal(&Boolean::TRUE)
+    }
+}
+
+fn main() {
+    let mut rng = rand::rngs::OsRng;
+
+    // open a random polynomial of degree n-1 at a random point
+    let n = 16;
+    let (pk, vk) = KZG::<Bls12_377>::setup(&mut rng, n).unwrap();
+    let v: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(&mut rng))
+        .take(n)
+        .collect();
+    let cm = KZG::<Bls12_377>::commit(&pk, &v, &Fr::zero()).unwrap();
+    let point = Fr::rand(&mut rng);
+    let proof = KZG::<Bls12_377>::prove_with_challenge(&pk, point, &v, &Fr::zero(), None).unwrap();
+    KZG::<Bls12_377>::verify_with_challenge(&vk, point, &cm, &proof).unwrap();
+
+    let circuit = KZGVerifyWrapperCircuit {
+        vk,
+        cm,
+        point,
+        proof: proof.clone(),
+    };
+
+    // prove the opening verification with Groth16 over BW6-761
+    let start = Instant::now();
+    let (g16_pk, g16_vk) =
+        Groth16::<BW6_761>::circuit_specific_setup(circuit.clone(), &mut rng).unwrap();
+    println!("Groth16 setup, {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let g16_proof = Groth16::<BW6_761>::prove(&g16_pk, circuit, &mut rng).unwrap();
+    println!("Groth16 prove, {:?}", start.elapsed());
+
+    let public_inputs = [to_base_field(point), to_base_field(proof.eval)];
+    let verified = Groth16::<BW6_761>::verify(&g16_vk, &public_inputs, &g16_proof).unwrap();
+    assert!(verified);
+    println!("Wrapped KZG opening verified");
+}
@@ -18,12 +18,12 @@ use folding_schemes::{
     commitment::{kzg::KZG, pedersen::Pedersen},
     folding::{
         nova::{
-            decider_eth::{prepare_calldata, Decider as DeciderEth},
+            decider_eth::{decode_verifier_output, prepare_calldata, Decider as DeciderEth},
             Nova, PreprocessorParam,
         },
         traits::CommittedInstanceOps,
     },
-    frontend::FCircuit,
+    frontend::{FCircuit, VecF},
     transcript::poseidon::poseidon_canonical_config,
     Decider, FoldingScheme,
 };
@@ -45,19 +45,18 @@ fn main() {
     let circuit_path = format!("./frontends/src/noir/test_folder/test_mimc/target/test_mimc.json",);
 
     let circuit = load_noir_circuit(circuit_path).unwrap();
-    let f_circuit = NoirFCircuit {
+    let f_circuit = NoirFCircuit::<Fr, 0> {
         circuit,
         state_len: 1,
-        external_inputs_len: 0,
     };
 
-    pub type N = Nova<G1, GVar, G2, GVar2, NoirFCircuit<Fr>, KZG<'static, Bn254>, Pedersen<G2>>;
+    pub type N = Nova<G1, GVar, G2, GVar2, NoirFCircuit<Fr, 0>, KZG<'static, Bn254>, Pedersen<G2>>;
     pub type D = DeciderEth<
         G1,
         GVar,
         G2,
         GVar2,
-        NoirFCircuit<Fr>,
+        NoirFCircuit<Fr, 0>,
         KZG<'static, Bn254>,
         Pedersen<G2>,
         Groth16<Bn254>,
@@ -81,7 +80,7 @@ fn main() {
     // run n steps of the folding iteration
     for i in 0..5 {
         let start = Instant::now();
-        nova.prove_step(rng, vec![], None).unwrap();
+        nova.prove_step(rng, VecF(vec![]), None).unwrap();
         println!("Nova::prove_step {}: {:?}", i, start.elapsed());
     }
     // verify the last IVC proof
@@ -135,7 +134,12 @@ fn main() {
     let mut evm = Evm::default();
     let verifier_address = evm.create(nova_cyclefold_verifier_bytecode);
     let (_, output) = evm.call(verifier_address, calldata.clone());
-    assert_eq!(*output.last().unwrap(), 1);
+    // the verifier returns the verified number of steps and final state
+    let (i, z_i) = decode_verifier_output(&output, f_circuit.state_len()).unwrap();
+    println!(
+        "Solidity verifier accepted the proof of step {}, with z_i: {:?}",
+        i, z_i
+    );
 
     // save smart contract and the calldata
     println!("storing nova-verifier.sol and the calldata into files");
@@ -19,12 +19,12 @@ use folding_schemes::{
     commitment::{kzg::KZG, pedersen::Pedersen},
     folding::{
         nova::{
-            decider_eth::{prepare_calldata, Decider as DeciderEth},
+            decider_eth::{decode_verifier_output, prepare_calldata, Decider as DeciderEth},
             Nova, PreprocessorParam,
         },
         traits::CommittedInstanceOps,
     },
-    frontend::FCircuit,
+    frontend::{FCircuit, VecF},
     transcript::poseidon::poseidon_canonical_config,
     Decider, FoldingScheme,
 };
@@ -53,20 +53,20 @@ fn main() {
     // set the external inputs to be used at each step of the IVC, it has length of 10 since this
     // is the number of steps that we will do
     let external_inputs = vec![
-        vec![Fr::from(8u32), Fr::from(2u32)],
-        vec![Fr::from(40), Fr::from(5)],
+        VecF(vec![Fr::from(8u32), Fr::from(2u32)]),
+        VecF(vec![Fr::from(40), Fr::from(5)]),
     ];
 
     // initialize the noname circuit
-    let f_circuit_params = (NONAME_CIRCUIT_EXTERNAL_INPUTS.to_owned(), 2, 2);
-    let f_circuit = NonameFCircuit::<Fr, R1csBn254Field>::new(f_circuit_params).unwrap();
+    let f_circuit_params = NONAME_CIRCUIT_EXTERNAL_INPUTS.to_owned().into();
+    let f_circuit = NonameFCircuit::<Fr, R1csBn254Field, 2>::new(f_circuit_params).unwrap();
 
     pub type N = Nova<
         G1,
         GVar,
         G2,
         GVar2,
-        NonameFCircuit<Fr, R1csBn254Field>,
+        NonameFCircuit<Fr, R1csBn254Field, 2>,
         KZG<'static, Bn254>,
         Pedersen<G2>,
     >;
@@ -75,7 +75,7 @@ fn main() {
         GVar,
         G2,
         GVar2,
-        NonameFCircuit<Fr, R1csBn254Field>,
+        NonameFCircuit<Fr, R1csBn254Field, 2>,
         KZG<'static, Bn254>,
         Pedersen<G2>,
         Groth16<Bn254>,
@@ -155,7 +155,12 @@ fn main() {
     let mut evm = Evm::default();
     let verifier_address = evm.create(nova_cyclefold_verifier_bytecode);
     let (_, output) = evm.call(verifier_address, calldata.clone());
-    assert_eq!(*output.last().unwrap(), 1);
+    // the verifier returns the verified number of steps and final state
+    let (i, z_i) = decode_verifier_output(&output, f_circuit.state_len()).unwrap();
+    println!(
+        "Solidity verifier accepted the proof of step {}, with z_i: {:?}",
+        i, z_i
+    );
 
     // save smart contract and the calldata
     println!("storing nova-verifier.sol and the calldata into files");
@@ -0,0 +1,496 @@
+/// Builder for [`CCS`] structures, which takes care of the bookkeeping of the matrices, the
+/// multisets and the coefficients, and of computing the derived parameters (`m`, `t`, `q`, `d`,
+/// `s`, `s_prime`).
+///
+/// A CCS over `z = (1, x, w)` is satisfied when `Σ_i c_i ⋅ ∘_{j ∈ S_i} M_j z = 0`, where `∘` is
+/// the Hadamard product. Each call to [`CCSBuilder::add_term`] appends one summand `c_i ⋅ ∘_{j ∈
+/// S_i} M_j z`, where the matrices are the ones previously registered with
+/// [`CCSBuilder::add_matrix`]. R1CS constraints can be added row by row with
+/// [`CCSBuilder::add_r1cs_constraint`], which appends rows to the `A`, `B`, `C` matrices of the
+/// terms `A z ∘ B z - C z`. All the matrices share the same rows, which are padded with zeros up
+/// to the maximum number of rows.
+use ark_ff::PrimeField;
+use ark_std::{log2, string::ToString, vec::Vec};
+
+use super::CCS;
+use crate::utils::vec::SparseMatrix;
+use crate::Error;
+
+/// Reference to a matrix registered in a [`CCSBuilder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatrixRef(usize);
+
+/// Sparse row of a matrix, as pairs `(coefficient, column)`.
+pub type Row<F> = Vec<(F, usize)>;
+
+#[derive(Debug, Clone)]
+pub struct CCSBuilder<F: PrimeField> {
+    n: usize,
+    l: usize,
+    M: Vec<Vec<Row<F>>>,
+    terms: Vec<(F, Vec<usize>)>,
+    /// `A`, `B`, `C` matrices of the R1CS constraints, registered at the first constraint
+    r1cs: Option<[usize; 3]>,
+}
+
+impl<F: PrimeField> CCSBuilder<F> {
+    /// Creates a builder for a CCS over `z = (1, x, w)`, where `n_vars = |z|` and
+    /// `n_public = |x|`.
+    pub fn new(n_vars: usize, n_public: usize) -> Self {
+        Self {
+            n: n_vars,
+            l: n_public,
+            M: vec![],
+            terms: vec![],
+            r1cs: None,
+        }
+    }
+
+    /// Creates a builder holding the matrices and the terms of the given CCS, so that more
+    /// constraints can be added to it.
+    pub fn from_ccs(ccs: &CCS<F>) -> Self {
+        let mut builder = Self::new(ccs.n, ccs.l);
+        for M_j in ccs.M.iter() {
+            builder.add_matrix(M_j.coeffs.clone());
+        }
+        builder.terms = ccs.c.iter().copied().zip(ccs.S.iter().cloned()).collect();
+        builder
+    }
+
+    /// Appends the constraints of `ccs` as new rows, after the rows of the longest matrix
+    /// registered so far, where the column `j` of `ccs` is mapped to the column `columns[j]`. The
+    /// matrices and the terms of `ccs` are added as new ones, so they only constrain the new rows.
+    pub fn append_ccs(&mut self, ccs: &CCS<F>, columns: &[usize]) -> Result<&mut Self, Error> {
+        if columns.len() != ccs.n {
+            return Err(Error::NotSameLength(
+                "columns.len()".to_string(),
+                columns.len(),
+                "ccs.n".to_string(),
+                ccs.n,
+            ));
+        }
+        let offset = self.M.iter().map(|rows| rows.len()).max().unwrap_or(0);
+        let first = self.M.len();
+        for M_j in ccs.M.iter() {
+            let mut rows = vec![vec![]; offset];
+            for row in M_j.coeffs.iter() {
+                rows.push(
+                    row.iter()
+                        .map(|&(value, col)| {
+                            Ok((value, *columns.get(col).ok_or(Error::OutOfBounds)?))
+                        })
+                        .collect::<Result<Row<F>, Error>>()?,
+                );
+            }
+            self.add_matrix(rows);
+        }
+        for (c_i, S_i) in ccs.c.iter().zip(ccs.S.iter()) {
+            self.terms
+                .push((*c_i, S_i.iter().map(|j| first + j).collect()));
+        }
+        Ok(self)
+    }
+
+    /// Registers the matrix given by its sparse rows, returning the reference to be used in the
+    /// terms.
+    pub fn add_matrix(&mut self, rows: Vec<Row<F>>) -> MatrixRef {
+        self.M.push(rows);
+        MatrixRef(self.M.len() - 1)
+    }
+
+    /// Adds the term `coeff ⋅ ∘_{M_j ∈ matrices} M_j z`. A matrix can appear several times, e.g.
+    /// `[M, M, M]` gives `(M z)^3`.
+    pub fn add_term(&mut self, coeff: F, matrices: Vec<MatrixRef>) -> &mut Self {
+        self.terms
+            .push((coeff, matrices.into_iter().map(|m| m.0).collect()));
+        self
+    }
+
+    /// Adds the R1CS constraint `<a, z> ⋅ <b, z> = <c, z>` as a new row of the `A`, `B`, `C`
+    /// matrices.
+    pub fn add_r1cs_constraint(&mut self, a: Row<F>, b: Row<F>, c: Row<F>) -> &mut Self {
+        let [A, B, C] = match self.r1cs {
+            Some(abc) => abc,
+            None => {
+                let abc = [
+                    self.add_matrix(vec![]).0,
+                    self.add_matrix(vec![]).0,
+                    self.add_matrix(vec![]).0,
+                ];
+                self.terms.push((F::one(), vec![abc[0], abc[1]]));
+                self.terms.push((-F::one(), vec![abc[2]]));
+                self.r1cs = Some(abc);
+                abc
+            }
+        };
+        self.M[A].push(a);
+        self.M[B].push(b);
+        self.M[C].push(c);
+        self
+    }
+
+    /// Builds the CCS of the single custom gate `Σ_i c_i ⋅ ∘_{j ∈ S_i} M_j z` applied row-wise,
+    /// where `terms` contains the pairs `(c_i, S_i)` over `t` matrices, and each row of the system
+    /// is given by the `t` rows of the matrices `M_0, ..., M_{t-1}`.
+    pub fn from_degree_gates(
+        n_vars: usize,
+        n_public: usize,
+        terms: &[(F, Vec<usize>)],
+        rows: Vec<Vec<Row<F>>>,
+    ) -> Result<CCS<F>, Error> {
+        let t = terms
+            .iter()
+            .flat_map(|(_, S_i)| S_i.iter())
+            .max()
+            .map_or(0, |j| j + 1);
+        let mut M = vec![vec![]; t];
+        for row in rows {
+            if row.len() != t {
+                return Err(Error::NotSameLength(
+                    "row.len()".to_string(),
+                    row.len(),
+                    "number of matrices".to_string(),
+                    t,
+                ));
+            }
+            for (M_j, row_j) in M.iter_mut().zip(row) {
+                M_j.push(row_j);
+            }
+        }
+
+        let mut builder = Self::new(n_vars, n_public);
+        let refs: Vec<MatrixRef> = M.into_iter().map(|M_j| builder.add_matrix(M_j)).collect();
+        for (c_i, S_i) in terms {
+            builder.add_term(*c_i, S_i.iter().map(|&j| refs[j]).collect());
+        }
+        builder.build()
+    }
+
+    /// Assembles the CCS, checking that it is well-formed. Equal matrices are merged, as well as
+    /// the terms with the same multiset, and the terms and matrices that do not contribute are
+    /// dropped.
+    pub fn build(&self) -> Result<CCS<F>, Error> {
+        if self.n < self.l + 1 {
+            return Err(Error::NotExpectedLength(self.n, self.l + 1));
+        }
+
+        // normalize the rows, so that equal matrices are represented equally
+        let mut M = self
+            .M
+            .iter()
+            .map(|rows| {
+                rows.iter()
+                    .map(|row| normalize_row(row, self.n))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let m = M.iter().map(|rows| rows.len()).max().unwrap_or(0);
+        for rows in M.iter_mut() {
+            rows.resize(m, vec![]);
+        }
+
+        // merge the equal matrices
+        let mut unique: Vec<Vec<Row<F>>> = vec![];
+        let mut index = vec![];
+        for M_j in M {
+            match unique.iter().position(|M_k| *M_k == M_j) {
+                Some(k) => index.push(k),
+                None => {
+                    unique.push(M_j);
+                    index.push(unique.len() - 1);
+                }
+            }
+        }
+
+        // merge the terms with the same multiset, dropping the ones that cancel out
+        let mut terms: Vec<(F, Vec<usize>)> = vec![];
+        for (c_i, S_i) in &self.terms {
+            if S_i.is_empty() {
+                return Err(Error::Empty);
+            }
+            let mut S_i = S_i
+                .iter()
+                .map(|&j| index.get(j).copied().ok_or(Error::OutOfBounds))
+                .collect::<Result<Vec<_>, _>>()?;
+            S_i.sort();
+            match terms.iter_mut().find(|(_, S)| *S == S_i) {
+                Some((c, _)) => *c += c_i,
+                None => terms.push((*c_i, S_i)),
+            }
+        }
+        terms.retain(|(c_i, _)| !c_i.is_zero());
+        if m == 0 || terms.is_empty() {
+            return Err(Error::Empty);
+        }
+
+        // keep only the matrices used by the remaining terms
+        let mut matrices: Vec<Vec<Row<F>>> = vec![];
+        let mut used = vec![None; unique.len()];
+        for (_, S_i) in terms.iter_mut() {
+            for j in S_i.iter_mut() {
+                let k = match used[*j] {
+                    Some(k) => k,
+                    None => {
+                        matrices.push(unique[*j].clone());
+                        used[*j] = Some(matrices.len() - 1);
+                        matrices.len() - 1
+                    }
+                };
+                *j = k;
+            }
+            S_i.sort();
+        }
+
+        let d = terms.iter().map(|(_, S_i)| S_i.len()).max().unwrap_or(0);
+        let (c, S): (Vec<F>, Vec<Vec<usize>>) = terms.into_iter().unzip();
+        Ok(CCS {
+            m,
+            n: self.n,
+            l: self.l,
+            t: matrices.len(),
+            q: S.len(),
+            d,
+            s: log2(m) as usize,
+            s_prime: log2(self.n) as usize,
+            M: matrices
+                .into_iter()
+                .map(|coeffs| SparseMatrix {
+                    n_rows: m,
+                    n_cols: self.n,
+                    coeffs,
+                })
+                .collect(),
+            S,
+            c,
+        })
+    }
+}
+
+/// Sorts the row by column, merging the repeated columns and dropping the zero coefficients.
+fn normalize_row<F: PrimeField>(row: &Row<F>, n_cols: usize) -> Result<Row<F>, Error> {
+    let mut row = row.clone();
+    if row.iter().any(|&(_, col)| col >= n_cols) {
+        return Err(Error::OutOfBounds);
+    }
+    row.sort_by_key(|&(_, col)| col);
+    let mut normalized: Row<F> = vec![];
+    for (value, col) in row {
+        match normalized.last_mut() {
+            Some((last_value, last_col)) if *last_col == col => *last_value += value,
+            _ => normalized.push((value, col)),
+        }
+    }
+    normalized.retain(|(value, _)| !value.is_zero());
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_crypto_primitives::sponge::{poseidon::PoseidonSponge, CryptographicSponge};
+    use ark_pallas::{Fr, Projective};
+    use ark_std::{rand::RngCore, test_rng, One, UniformRand};
+
+    use super::*;
+    use crate::arith::{
+        ccs::tests::{get_test_ccs, get_test_z},
+        r1cs::tests::get_test_r1cs,
+        Arith,
+    };
+    use crate::commitment::{pedersen::Pedersen, CommitmentScheme};
+    use crate::folding::hypernova::nimfs::NIMFS;
+    use crate::transcript::poseidon::poseidon_canonical_config;
+
+    /// Number of gates of the test CCS.
+    const N_GATES: usize = 4;
+
+    /// CCS with `N_GATES` rows of the gate `x_i^3 + x_i + 5 - y_i = 0`, over
+    /// `z = (1, y_0, ..., y_{N_GATES-1}, x_0, ..., x_{N_GATES-1})`.
+    fn get_cubic_ccs() -> CCS<Fr> {
+        let rows = (0..N_GATES)
+            .map(|i| {
+                let y_i = 1 + i;
+                let x_i = 1 + N_GATES + i;
+                vec![
+                    // M_0 z = x_i
+                    vec![(Fr::one(), x_i)],
+                    // M_1 z = x_i + 5 - y_i
+                    vec![(Fr::one(), x_i), (Fr::from(5), 0), (-Fr::one(), y_i)],
+                ]
+            })
+            .collect();
+        CCSBuilder::from_degree_gates(
+            1 + 2 * N_GATES,
+            N_GATES,
+            &[(Fr::one(), vec![0, 0, 0]), (Fr::one(), vec![1])],
+            rows,
+        )
+        .unwrap()
+    }
+
+    /// Returns a random satisfying `z` for the CCS of `get_cubic_ccs`.
+    fn get_cubic_z<R: RngCore>(rng: &mut R) -> Vec<Fr> {
+        let xs: Vec<Fr> = (0..N_GATES).map(|_| Fr::rand(rng)).collect();
+        let ys = xs.iter().map(|x| *x * x * x + x + Fr::from(5));
+        [vec![Fr::one()], ys.collect(), xs].concat()
+    }
+
+    #[test]
+    fn test_ccs_builder() {
+        let ccs = get_cubic_ccs();
+        assert_eq!((ccs.m, ccs.n, ccs.l), (N_GATES, 1 + 2 * N_GATES, N_GATES));
+        assert_eq!((ccs.t, ccs.q, ccs.d), (2, 2, 3));
+        assert_eq!((ccs.s, ccs.s_prime), (2, 4));
+
+        let mut rng = test_rng();
+        let mut z = get_cubic_z(&mut rng);
+        let (w, x) = ccs.split_z(&z);
+        ccs.check_relation(&w, &x).unwrap();
+        z[1] += Fr::one();
+        let (w, x) = ccs.split_z(&z);
+        assert!(ccs.check_relation(&w, &x).is_err());
+
+        // the R1CS constraints give the same CCS as the conversion from R1CS
+        let r1cs = get_test_r1cs::<Fr>();
+        let mut builder = CCSBuilder::new(r1cs.num_variables(), r1cs.num_public_inputs());
+        for ((a, b), c) in r1cs.A.coeffs.iter().zip(&r1cs.B.coeffs).zip(&r1cs.C.coeffs) {
+            builder.add_r1cs_constraint(a.clone(), b.clone(), c.clone());
+        }
+        let ccs = builder.build().unwrap();
+        let (w, x) = ccs.split_z(&get_test_z(3));
+        ccs.check_relation(&w, &x).unwrap();
+        assert_eq!(ccs, get_test_ccs());
+
+        // equal matrices and multisets are merged
+        let mut builder = CCSBuilder::<Fr>::new(3, 1);
+        let M_0 = builder.add_matrix(vec![vec![(Fr::one(), 2)], vec![(Fr::one(), 1)]]);
+        let M_1 = builder.add_matrix(vec![
+            vec![(Fr::from(2), 2), (-Fr::one(), 2)],
+            vec![(Fr::one(), 1), (Fr::zero(), 0)],
+        ]);
+        let M_2 = builder.add_matrix(vec![vec![(Fr::one(), 0)]]);
+        builder
+            .add_term(Fr::one(), vec![M_0, M_1])
+            .add_term(Fr::one(), vec![M_1, M_1])
+            .add_term(-Fr::from(2), vec![M_2]);
+        let ccs = builder.build().unwrap();
+        assert_eq!((ccs.m, ccs.t, ccs.q, ccs.d), (2, 2, 2, 2));
+        assert_eq!(ccs.S, vec![vec![0, 0], vec![1]]);
+        assert_eq!(ccs.c, vec![Fr::from(2), -Fr::from(2)]);
+
+        // malformed systems are rejected
+        let mut builder = CCSBuilder::<Fr>::new(3, 1);
+        let M = builder.add_matrix(vec![vec![(Fr::one(), 3)]]);
+        builder.add_term(Fr::one(), vec![M]);
+        assert!(builder.build().is_err());
+        let mut builder = CCSBuilder::<Fr>::new(3, 1);
+        builder.add_term(Fr::one(), vec![MatrixRef(0)]);
+        assert!(builder.build().is_err());
+        assert!(CCSBuilder::<Fr>::new(3, 1).build().is_err());
+        assert!(CCSBuilder::<Fr>::new(1, 1).build().is_err());
+    }
+
+    #[test]
+    fn test_ccs_builder_append() {
+        let ccs = get_test_ccs::<Fr>();
+        assert_eq!(CCSBuilder::from_ccs(&ccs).build().unwrap(), ccs);
+
+        // the gate `x^3 + x + 5 - y = 0` over `(1, x, y)`
+        let gate = CCSBuilder::from_degree_gates(
+            3,
+            0,
+            &[(Fr::one(), vec![0, 0, 0]), (Fr::one(), vec![1])],
+            vec![vec![
+                vec![(Fr::one(), 1)],
+                vec![(Fr::one(), 1), (Fr::from(5), 0), (-Fr::one(), 2)],
+            ]],
+        )
+        .unwrap();
+
+        // z = (1, x, x^3 + x + 5, ...), so the gate holds on the columns (0, 1, 2)
+        let appended = CCSBuilder::from_ccs(&ccs)
+            .append_ccs(&gate, &[0, 1, 2])
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            (appended.m, appended.n, appended.l),
+            (ccs.m + 1, ccs.n, ccs.l)
+        );
+        assert_eq!(appended.d, 3);
+        let (w, x) = appended.split_z(&get_test_z(3));
+        appended.check_relation(&w, &x).unwrap();
+
+        // but not on the columns (0, 1, 3), where z_3 = x^2
+        let appended = CCSBuilder::from_ccs(&ccs)
+            .append_ccs(&gate, &[0, 1, 3])
+            .unwrap()
+            .build()
+            .unwrap();
+        let (w, x) = appended.split_z(&get_test_z(3));
+        assert!(appended.check_relation(&w, &x).is_err());
+
+        assert!(CCSBuilder::from_ccs(&ccs)
+            .append_ccs(&gate, &[0, 1])
+            .is_err());
+        assert!(CCSBuilder::from_ccs(&ccs)
+            .append_ccs(&gate, &[0, 1, ccs.n])
+            .unwrap()
+            .build()
+            .is_err());
+    }
+
+    // folds instances of the builder-constructed CCS through the HyperNova multi-folding
+    #[test]
+    fn test_ccs_builder_folding() {
+        let mut rng = test_rng();
+        let ccs = get_cubic_ccs();
+        let (pedersen_params, _) =
+            Pedersen::<Projective>::setup(&mut rng, ccs.n - ccs.l - 1).unwrap();
+
+        let (mut running_instance, mut w1) = ccs
+            .to_lcccs::<_, _, Pedersen<Projective>, false>(
+                &mut rng,
+                &pedersen_params,
+                &get_cubic_z(&mut rng),
+            )
+            .unwrap();
+
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let mut transcript_p = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let mut transcript_v = PoseidonSponge::<Fr>::new(&poseidon_config);
+
+        for _ in 0..3 {
+            let (new_instance, w2) = ccs
+                .to_cccs::<_, _, Pedersen<Projective>, false>(
+                    &mut rng,
+                    &pedersen_params,
+                    &get_cubic_z(&mut rng),
+                )
+                .unwrap();
+
+            let (proof, folded_lcccs, folded_witness, _) =
+                NIMFS::<Projective, PoseidonSponge<Fr>>::prove(
+                    &mut transcript_p,
+                    &ccs,
+                    &[running_instance.clone()],
+                    &[new_instance.clone()],
+                    &[w1],
+                    &[w2],
+                )
+                .unwrap();
+            let folded_lcccs_v = NIMFS::<Projective, PoseidonSponge<Fr>>::verify(
+                &mut transcript_v,
+                &ccs,
+                &[running_instance],
+                &[new_instance],
+                proof,
+            )
+            .unwrap();
+            assert_eq!(folded_lcccs, folded_lcccs_v);
+            ccs.check_relation(&folded_witness, &folded_lcccs).unwrap();
+
+            running_instance = folded_lcccs;
+            w1 = folded_witness;
+        }
+    }
+}
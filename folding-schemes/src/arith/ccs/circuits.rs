@@ -6,7 +6,7 @@ use ark_r1cs_std::{
     fields::fp::FpVar,
 };
 use ark_relations::r1cs::{Namespace, SynthesisError};
-use ark_std::borrow::Borrow;
+use ark_std::{borrow::Borrow, vec::Vec};
 /// CCSMatricesVar contains the matrices 'M' of the CCS without the rest of CCS parameters.
 ///
 #[derive(Debug, Clone)]
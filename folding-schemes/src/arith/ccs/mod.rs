@@ -1,14 +1,20 @@
 use ark_ff::PrimeField;
-use ark_std::log2;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::io::{Read, Write};
+use ark_std::{cfg_iter, log2, string::ToString, vec::Vec};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 use crate::utils::vec::{
-    hadamard, is_zero_vec, mat_vec_mul, vec_add, vec_scalar_mul, SparseMatrix,
+    check_zero_rows, hadamard, mat_vec_mul, read_varint_usize, vec_add, vec_scalar_mul,
+    write_varint, SparseMatrix,
 };
 use crate::Error;
 
 use super::ArithSerializer;
 use super::{r1cs::R1CS, Arith};
 
+pub mod builder;
 pub mod circuits;
 
 /// CCS represents the Customizable Constraint Systems structure defined in
@@ -43,16 +49,19 @@ pub struct CCS<F: PrimeField> {
 impl<F: PrimeField> CCS<F> {
     /// Evaluates the CCS relation at a given vector of assignments `z`
     pub fn eval_at_z(&self, z: &[F]) -> Result<Vec<F>, Error> {
+        // the products M_j z are computed once (in parallel), as a matrix may appear in several
+        // multisets
+        let Mzs = cfg_iter!(self.M)
+            .map(|M_j| mat_vec_mul(M_j, z))
+            .collect::<Result<Vec<_>, Error>>()?;
+
         let mut result = vec![F::zero(); self.m];
 
         for i in 0..self.q {
-            // extract the needed M_j matrices out of S_i
-            let vec_M_j: Vec<&SparseMatrix<F>> = self.S[i].iter().map(|j| &self.M[*j]).collect();
-
-            // complete the hadamard chain
+            // complete the hadamard chain of the M_j z of S_i
             let mut hadamard_result = vec![F::one(); self.m];
-            for M_j in vec_M_j.into_iter() {
-                hadamard_result = hadamard(&hadamard_result, &mat_vec_mul(M_j, z)?)?;
+            for j in self.S[i].iter() {
+                hadamard_result = hadamard(&hadamard_result, &Mzs[*j])?;
             }
 
             // multiply by the coefficient of this step
@@ -69,6 +78,106 @@ impl<F: PrimeField> CCS<F> {
     pub fn split_z(&self, z: &[F]) -> (Vec<F>, Vec<F>) {
         (z[self.l + 1..].to_vec(), z[1..self.l + 1].to_vec())
     }
+
+    /// Pads the matrices with zero rows and zero columns up to the next powers of two of `m` and
+    /// `n`, updating `s` and `s_prime` accordingly. The new columns are placed at the end of `z`,
+    /// so a satisfying `z` for the original CCS extended with zeros (see `pad_z`) satisfies the
+    /// padded one.
+    pub fn pad_to_pow2(&mut self) {
+        let m = self.m.next_power_of_two();
+        let n = self.n.next_power_of_two();
+        for M_j in self.M.iter_mut() {
+            M_j.n_rows = m;
+            M_j.n_cols = n;
+            M_j.coeffs.resize(m, vec![]);
+        }
+        self.m = m;
+        self.n = n;
+        self.s = log2(m) as usize;
+        self.s_prime = log2(n) as usize;
+    }
+
+    /// Extends `z` with zeros up to the number of variables of the CCS, which is needed for the
+    /// assignments of a CCS that has been padded with `pad_to_pow2`.
+    pub fn pad_z(&self, z: &mut Vec<F>) -> Result<(), Error> {
+        if z.len() > self.n {
+            return Err(Error::NotExpectedLength(z.len(), self.n));
+        }
+        z.resize(self.n, F::zero());
+        Ok(())
+    }
+
+    /// Sets the number of public inputs to `l`. As `z = (1, x, w)` keeps its layout, this only
+    /// moves the boundary between `x` and `w`: the last public inputs become the first witness
+    /// elements when `l` is smaller than the current one, and the first witness elements become
+    /// public inputs when it is bigger.
+    pub fn normalize_public_inputs(&mut self, l: usize) -> Result<(), Error> {
+        if l >= self.n {
+            return Err(Error::OutOfBounds);
+        }
+        self.l = l;
+        Ok(())
+    }
+
+    /// Checks that the parameters of the CCS are consistent with its matrices, multisets and
+    /// coefficients.
+    pub fn sanity_check(&self) -> Result<(), Error> {
+        for (name, len, param, expected) in [
+            ("M.len()", self.M.len(), "t", self.t),
+            ("S.len()", self.S.len(), "q", self.q),
+            ("c.len()", self.c.len(), "q", self.q),
+        ] {
+            if len != expected {
+                return Err(Error::NotSameLength(
+                    name.to_string(),
+                    len,
+                    param.to_string(),
+                    expected,
+                ));
+            }
+        }
+        if self.l >= self.n {
+            return Err(Error::OutOfBounds);
+        }
+        if self.s != log2(self.m) as usize || self.s_prime != log2(self.n) as usize {
+            return Err(Error::Other(format!(
+                "s={}, s'={} do not match m={}, n={}",
+                self.s, self.s_prime, self.m, self.n
+            )));
+        }
+        for M_j in self.M.iter() {
+            if M_j.n_rows != self.m || M_j.n_cols != self.n || M_j.coeffs.len() != self.m {
+                return Err(Error::Other(format!(
+                    "matrix of {}x{} with {} rows, expected {}x{}",
+                    M_j.n_rows,
+                    M_j.n_cols,
+                    M_j.coeffs.len(),
+                    self.m,
+                    self.n
+                )));
+            }
+            if M_j.coeffs.iter().flatten().any(|(_, col)| *col >= self.n) {
+                return Err(Error::OutOfBounds);
+            }
+        }
+        let mut d = 0;
+        for S_i in self.S.iter() {
+            if S_i.is_empty() {
+                return Err(Error::Empty);
+            }
+            if S_i.iter().any(|j| *j >= self.t) {
+                return Err(Error::OutOfBounds);
+            }
+            d = d.max(S_i.len());
+        }
+        if d != self.d {
+            return Err(Error::Other(format!(
+                "d={} but the largest multiset has {} elements",
+                self.d, d
+            )));
+        }
+        Ok(())
+    }
 }
 
 impl<F: PrimeField, W: AsRef<[F]>, U: AsRef<[F]>> Arith<W, U> for CCS<F> {
@@ -79,7 +188,7 @@ impl<F: PrimeField, W: AsRef<[F]>, U: AsRef<[F]>> Arith<W, U> for CCS<F> {
     }
 
     fn check_evaluation(_w: &W, _u: &U, e: Self::Evaluation) -> Result<(), Error> {
-        is_zero_vec(&e).then_some(()).ok_or(Error::NotSatisfied)
+        check_zero_rows(&e)
     }
 }
 
@@ -95,6 +204,64 @@ impl<F: PrimeField> ArithSerializer for CCS<F> {
         ]
         .concat()
     }
+
+    fn serialize_compressed_opt<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        for param in [self.m, self.n, self.l, self.t, self.q, self.d] {
+            write_varint(&mut writer, param as u64)?;
+        }
+        for M_j in self.M.iter() {
+            M_j.serialize_compressed_opt(&mut writer)?;
+        }
+        for S_i in self.S.iter() {
+            write_varint(&mut writer, S_i.len() as u64)?;
+            for j in S_i.iter() {
+                write_varint(&mut writer, *j as u64)?;
+            }
+        }
+        for c_i in self.c.iter() {
+            c_i.serialize_compressed(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize_compressed_opt<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let mut params = [0; 6];
+        for param in params.iter_mut() {
+            *param = read_varint_usize(&mut reader)?;
+        }
+        let [m, n, l, t, q, d] = params;
+        // the lengths are not trusted, so the vectors are not preallocated
+        let M = (0..t)
+            .map(|_| SparseMatrix::deserialize_compressed_opt(&mut reader))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let S = (0..q)
+            .map(|_| {
+                let len = read_varint_usize(&mut reader)?;
+                (0..len)
+                    .map(|_| read_varint_usize(&mut reader))
+                    .collect::<Result<Vec<_>, Error>>()
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let c = (0..q)
+            .map(|_| F::deserialize_compressed(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let ccs = CCS {
+            m,
+            n,
+            l,
+            t,
+            q,
+            d,
+            s: log2(m) as usize,
+            s_prime: log2(n) as usize,
+            M,
+            S,
+            c,
+        };
+        ccs.sanity_check()?;
+        Ok(ccs)
+    }
 }
 
 impl<F: PrimeField> From<R1CS<F>> for CCS<F> {
@@ -126,6 +293,7 @@ pub mod tests {
         utils::vec::is_zero_vec,
     };
     use ark_pallas::Fr;
+    use ark_std::One;
 
     pub fn get_test_ccs<F: PrimeField>() -> CCS<F> {
         get_test_r1cs::<F>().into()
@@ -134,6 +302,21 @@ pub mod tests {
         r1cs_get_test_z(input)
     }
 
+    /// Returns the CCS of `get_test_ccs` with an extra (redundant) constraint `x^2 * 1 = x^2`, so
+    /// that neither of its dimensions (5 x 6) is a power of two.
+    pub fn get_test_odd_ccs<F: PrimeField>() -> CCS<F> {
+        let mut r1cs = get_test_r1cs::<F>();
+        for (M, row) in [
+            (&mut r1cs.A, vec![(F::one(), 3)]),
+            (&mut r1cs.B, vec![(F::one(), 0)]),
+            (&mut r1cs.C, vec![(F::one(), 3)]),
+        ] {
+            M.coeffs.push(row);
+            M.n_rows += 1;
+        }
+        r1cs.into()
+    }
+
     #[test]
     fn test_eval_ccs_relation() {
         let ccs = get_test_ccs::<Fr>();
@@ -155,4 +338,88 @@ pub mod tests {
 
         ccs.check_relation(&w, &x).unwrap();
     }
+
+    #[test]
+    fn test_check_ccs_relation_failing_row() {
+        let ccs = get_test_ccs::<Fr>();
+        let (_, x, mut w) = get_test_z_split::<Fr>(3);
+        // `x^2` is the output of the row 0 and an input of the row 1
+        w[1] = Fr::from(111);
+        assert!(matches!(
+            ccs.check_relation(&w, &x),
+            Err(Error::NotSatisfiedAt { row: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_pad_to_pow2() {
+        let mut ccs = get_test_odd_ccs::<Fr>();
+        assert_eq!((ccs.m, ccs.n), (5, 6));
+        ccs.sanity_check().unwrap();
+        let mut z = get_test_z::<Fr>(3);
+        let (w, x) = ccs.split_z(&z);
+        ccs.check_relation(&w, &x).unwrap();
+
+        ccs.pad_to_pow2();
+        ccs.sanity_check().unwrap();
+        assert_eq!((ccs.m, ccs.n, ccs.s, ccs.s_prime), (8, 8, 3, 3));
+        // padding is idempotent
+        let padded = ccs.clone();
+        ccs.pad_to_pow2();
+        assert_eq!(ccs, padded);
+
+        ccs.pad_z(&mut z).unwrap();
+        assert_eq!(z.len(), 8);
+        let (w, x) = ccs.split_z(&z);
+        ccs.check_relation(&w, &x).unwrap();
+
+        // the padding columns are not constrained, but the original ones still are
+        let mut bad_z = z.clone();
+        bad_z[2] += Fr::one();
+        let (w, x) = ccs.split_z(&bad_z);
+        assert!(ccs.check_relation(&w, &x).is_err());
+    }
+
+    #[test]
+    fn test_normalize_public_inputs_and_sanity_check() {
+        let mut ccs = get_test_ccs::<Fr>();
+        let z = get_test_z::<Fr>(3);
+        // making the output `y` public keeps `z` unchanged
+        ccs.normalize_public_inputs(2).unwrap();
+        let (w, x) = ccs.split_z(&z);
+        assert_eq!(x, vec![Fr::from(3), Fr::from(35)]);
+        ccs.check_relation(&w, &x).unwrap();
+        assert!(ccs.normalize_public_inputs(ccs.n).is_err());
+
+        let mut bad_ccs = ccs.clone();
+        bad_ccs.S[0].push(3);
+        assert!(bad_ccs.sanity_check().is_err());
+        let mut bad_ccs = ccs.clone();
+        bad_ccs.c.pop();
+        assert!(bad_ccs.sanity_check().is_err());
+        let mut bad_ccs = ccs.clone();
+        bad_ccs.M[1].coeffs[0].push((Fr::one(), ccs.n));
+        assert!(bad_ccs.sanity_check().is_err());
+        let mut bad_ccs = ccs.clone();
+        bad_ccs.s += 1;
+        assert!(bad_ccs.sanity_check().is_err());
+    }
+
+    #[test]
+    fn test_ccs_serialize_compressed_opt() {
+        let ccs = get_test_odd_ccs::<Fr>();
+        let mut bytes = Vec::new();
+        ccs.serialize_compressed_opt(&mut bytes).unwrap();
+        assert_eq!(
+            CCS::<Fr>::deserialize_compressed_opt(&bytes[..]).unwrap(),
+            ccs
+        );
+
+        // a CCS whose multisets refer to a non-existing matrix is rejected
+        let mut bad_ccs = ccs.clone();
+        bad_ccs.S[0][0] = bad_ccs.t;
+        let mut bytes = Vec::new();
+        bad_ccs.serialize_compressed_opt(&mut bytes).unwrap();
+        assert!(CCS::<Fr>::deserialize_compressed_opt(&bytes[..]).is_err());
+    }
 }
@@ -1,10 +1,12 @@
 use ark_ec::CurveGroup;
 use ark_relations::r1cs::SynthesisError;
-use ark_std::rand::RngCore;
+use ark_std::io::{Read, Write};
+use ark_std::{rand::RngCore, vec::Vec};
 
 use crate::{commitment::CommitmentScheme, folding::traits::Dummy, Error};
 
 pub mod ccs;
+pub mod plonkish;
 pub mod r1cs;
 
 /// `Arith` defines the operations that a constraint system (e.g., R1CS, CCS,
@@ -100,13 +102,22 @@ pub trait Arith<W, U>: Clone {
 
 /// `ArithSerializer` is for serializing constraint systems.
 ///
-/// Currently we only support converting parameters to bytes, but in the future
-/// we may consider implementing methods for serializing the actual data (e.g.,
-/// R1CS matrices).
+/// Besides converting the parameters to bytes, it allows serializing the whole
+/// constraint system in a compact format, where the matrices are written with
+/// `SparseMatrix::serialize_compressed_opt`.
 pub trait ArithSerializer {
     /// Returns the bytes that represent the parameters, that is, the matrices sizes, the amount of
     /// public inputs, etc, without the matrices/polynomials values.
     fn params_to_le_bytes(&self) -> Vec<u8>;
+
+    /// Serializes the constraint system, including the matrices values, in a compact format.
+    fn serialize_compressed_opt<W: Write>(&self, writer: W) -> Result<(), Error>;
+
+    /// Deserializes a constraint system serialized with `serialize_compressed_opt`, checking
+    /// that its dimensions are consistent.
+    fn deserialize_compressed_opt<R: Read>(reader: R) -> Result<Self, Error>
+    where
+        Self: Sized;
 }
 
 /// `ArithSampler` allows sampling random pairs of witness and instance that
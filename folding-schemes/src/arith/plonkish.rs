@@ -0,0 +1,318 @@
+/// Plonkish constraint systems and their conversion into CCS.
+///
+/// The conversion follows the Plonkish→CCS encoding of the
+/// [CCS paper](https://eprint.iacr.org/2023/552), where the copy constraints are handled by
+/// preprocessing: all the wires in the same cycle of the permutation are mapped to the same entry
+/// of `z`. As the selectors are fixed by the circuit, they are placed as coefficients of the
+/// matrices instead of as constants in `z`, which keeps the degree of the resulting CCS at 2.
+use ark_ff::PrimeField;
+use ark_std::{log2, string::ToString, vec::Vec};
+
+use super::ccs::CCS;
+use crate::utils::vec::SparseMatrix;
+use crate::Error;
+
+/// PlonkishCS represents a Plonkish constraint system with the standard PLONK gate
+/// `q_L⋅a + q_R⋅b + q_M⋅a⋅b + q_O⋅c + q_C = 0` at each row, and the copy constraints given by a
+/// permutation `σ` over the wire positions, where the wires `a`, `b`, `c` of the gate `i` are at
+/// the positions `i`, `n_gates + i` and `2⋅n_gates + i` respectively.
+///
+/// The public inputs are the `a` wires of the first `l` gates.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PlonkishCS<F: PrimeField> {
+    q_L: Vec<F>,
+    q_R: Vec<F>,
+    q_M: Vec<F>,
+    q_O: Vec<F>,
+    q_C: Vec<F>,
+    sigma: Vec<usize>,
+    l: usize,
+    /// index in `z = (1, x, w)` of the variable of each wire position
+    vars: Vec<usize>,
+    /// `|z|`
+    n_vars: usize,
+}
+
+impl<F: PrimeField> PlonkishCS<F> {
+    /// Creates the Plonkish constraint system from its selectors and its copy-constraint
+    /// permutation `sigma`, checking that they are well-formed.
+    pub fn new(
+        q_L: Vec<F>,
+        q_R: Vec<F>,
+        q_M: Vec<F>,
+        q_O: Vec<F>,
+        q_C: Vec<F>,
+        sigma: Vec<usize>,
+        l: usize,
+    ) -> Result<Self, Error> {
+        let n_gates = q_L.len();
+        for (name, q) in [("q_R", &q_R), ("q_M", &q_M), ("q_O", &q_O), ("q_C", &q_C)] {
+            if q.len() != n_gates {
+                return Err(Error::NotSameLength(
+                    name.to_string(),
+                    q.len(),
+                    "q_L".to_string(),
+                    n_gates,
+                ));
+            }
+        }
+        if sigma.len() != 3 * n_gates {
+            return Err(Error::NotExpectedLength(sigma.len(), 3 * n_gates));
+        }
+        if l > n_gates {
+            return Err(Error::OutOfBounds);
+        }
+        let mut seen = vec![false; sigma.len()];
+        for &p in &sigma {
+            if p >= sigma.len() {
+                return Err(Error::OutOfBounds);
+            }
+            if seen[p] {
+                return Err(Error::Other(format!(
+                    "sigma is not a permutation, {} appears twice",
+                    p
+                )));
+            }
+            seen[p] = true;
+        }
+
+        // assign a variable to each cycle of σ, starting with the public inputs
+        let mut vars = vec![usize::MAX; sigma.len()];
+        let mut n_vars = 1;
+        for start in (0..l).chain(0..sigma.len()) {
+            if vars[start] != usize::MAX {
+                if start < l && vars[start] != start + 1 {
+                    return Err(Error::Other(format!(
+                        "the public inputs {} and {} are copy-constrained",
+                        vars[start] - 1,
+                        start
+                    )));
+                }
+                continue;
+            }
+            let mut p = start;
+            loop {
+                vars[p] = n_vars;
+                p = sigma[p];
+                if p == start {
+                    break;
+                }
+            }
+            n_vars += 1;
+        }
+
+        Ok(Self {
+            q_L,
+            q_R,
+            q_M,
+            q_O,
+            q_C,
+            sigma,
+            l,
+            vars,
+            n_vars,
+        })
+    }
+
+    pub fn n_gates(&self) -> usize {
+        self.q_L.len()
+    }
+
+    /// Maps the values of the wires `a`, `b`, `c` into the vector `z = (1, x, w)` of the CCS
+    /// obtained from the conversion, checking that they satisfy the copy constraints.
+    pub fn wires_to_z(&self, a: &[F], b: &[F], c: &[F]) -> Result<Vec<F>, Error> {
+        let n_gates = self.n_gates();
+        for v in [a, b, c] {
+            if v.len() != n_gates {
+                return Err(Error::NotExpectedLength(v.len(), n_gates));
+            }
+        }
+
+        let mut z = vec![None; self.n_vars];
+        z[0] = Some(F::one());
+        for (value, var) in a.iter().chain(b).chain(c).zip(&self.vars) {
+            match z[*var] {
+                Some(v) if v != *value => return Err(Error::NotSatisfied),
+                _ => z[*var] = Some(*value),
+            }
+        }
+        // every variable comes from some wire position, thus all of them are assigned
+        Ok(z.into_iter().map(|v| v.unwrap_or_default()).collect())
+    }
+}
+
+impl<F: PrimeField> From<PlonkishCS<F>> for CCS<F> {
+    /// The gates are encoded as the CCS `M_0 z + (M_1 z) ∘ (M_2 z) = 0`, where the row `i` of
+    /// `M_0` is `q_C⋅1 + q_L⋅a_i + q_R⋅b_i + q_O⋅c_i`, of `M_1` is `q_M⋅a_i` and of `M_2` is
+    /// `b_i`, with the wires replaced by their variables in `z`.
+    fn from(plonkish: PlonkishCS<F>) -> Self {
+        let n_gates = plonkish.n_gates();
+        let var = |wire: usize, i: usize| plonkish.vars[wire * n_gates + i];
+        let nonzero = |row: Vec<(F, usize)>| -> Vec<(F, usize)> {
+            row.into_iter().filter(|(v, _)| !v.is_zero()).collect()
+        };
+
+        let mut M = vec![
+            SparseMatrix {
+                n_rows: n_gates,
+                n_cols: plonkish.n_vars,
+                coeffs: vec![],
+            };
+            3
+        ];
+        for i in 0..n_gates {
+            M[0].coeffs.push(nonzero(vec![
+                (plonkish.q_C[i], 0),
+                (plonkish.q_L[i], var(0, i)),
+                (plonkish.q_R[i], var(1, i)),
+                (plonkish.q_O[i], var(2, i)),
+            ]));
+            M[1].coeffs
+                .push(nonzero(vec![(plonkish.q_M[i], var(0, i))]));
+            M[2].coeffs.push(vec![(F::one(), var(1, i))]);
+        }
+
+        CCS {
+            m: n_gates,
+            n: plonkish.n_vars,
+            l: plonkish.l,
+            t: 3,
+            q: 2,
+            d: 2,
+            s: log2(n_gates) as usize,
+            s_prime: log2(plonkish.n_vars) as usize,
+            M,
+            S: vec![vec![0], vec![1, 2]],
+            c: vec![F::one(), F::one()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_pallas::Fr;
+    use ark_std::{One, Zero};
+
+    use super::*;
+    use crate::arith::Arith;
+
+    const N_GATES: usize = 5;
+    const A: usize = 0;
+    const B: usize = N_GATES;
+    const C: usize = 2 * N_GATES;
+
+    /// Plonkish circuit for `x^3 + x + 5 = y`, with public `x` and `y`:
+    /// - gates 0 and 1 hold the public inputs `x` and `y`,
+    /// - gate 2: `t_1 = x⋅x`,
+    /// - gate 3: `t_2 = t_1⋅x`,
+    /// - gate 4: `y = t_2 + x + 5`.
+    fn get_test_plonkish() -> PlonkishCS<Fr> {
+        let (zero, one) = (Fr::zero(), Fr::one());
+        let q_L = vec![zero, zero, zero, zero, one];
+        let q_R = vec![zero, zero, zero, zero, one];
+        let q_M = vec![zero, zero, one, one, zero];
+        let q_O = vec![zero, zero, -one, -one, -one];
+        let q_C = vec![zero, zero, zero, zero, Fr::from(5)];
+
+        let cycles = [
+            // x
+            vec![A, A + 2, B + 2, B + 3, B + 4],
+            // y
+            vec![A + 1, C + 4],
+            // t_1
+            vec![C + 2, A + 3],
+            // t_2
+            vec![C + 3, A + 4],
+        ];
+        let mut sigma: Vec<usize> = (0..3 * N_GATES).collect();
+        for cycle in cycles {
+            for (j, &p) in cycle.iter().enumerate() {
+                sigma[p] = cycle[(j + 1) % cycle.len()];
+            }
+        }
+
+        PlonkishCS::new(q_L, q_R, q_M, q_O, q_C, sigma, 2).unwrap()
+    }
+
+    /// Returns the wires `a`, `b`, `c` of the circuit of `get_test_plonkish` for the input `x`.
+    fn get_test_wires(x: u64) -> (Vec<Fr>, Vec<Fr>, Vec<Fr>) {
+        let x = Fr::from(x);
+        let (t_1, t_2) = (x * x, x * x * x);
+        let y = t_2 + x + Fr::from(5);
+        let zero = Fr::zero();
+        (
+            vec![x, y, x, t_1, t_2],
+            vec![zero, zero, x, x, x],
+            vec![zero, zero, t_1, t_2, y],
+        )
+    }
+
+    fn check_plonkish_gates(plonkish: &PlonkishCS<Fr>, a: &[Fr], b: &[Fr], c: &[Fr]) -> bool {
+        (0..plonkish.n_gates()).all(|i| {
+            (plonkish.q_L[i] * a[i]
+                + plonkish.q_R[i] * b[i]
+                + plonkish.q_M[i] * a[i] * b[i]
+                + plonkish.q_O[i] * c[i]
+                + plonkish.q_C[i])
+                .is_zero()
+        })
+    }
+
+    #[test]
+    fn test_plonkish_to_ccs() {
+        let plonkish = get_test_plonkish();
+        let ccs: CCS<Fr> = plonkish.clone().into();
+        // 1 + x, y + t_1, t_2 + the 4 unconstrained wires of the public input gates
+        assert_eq!((ccs.m, ccs.n, ccs.l), (N_GATES, 9, 2));
+
+        let (a, b, c) = get_test_wires(3);
+        assert!(check_plonkish_gates(&plonkish, &a, &b, &c));
+        let z = plonkish.wires_to_z(&a, &b, &c).unwrap();
+        let (w, x) = ccs.split_z(&z);
+        assert_eq!(x, vec![Fr::from(3), Fr::from(35)]);
+        ccs.check_relation(&w, &x).unwrap();
+
+        // wires breaking a copy constraint
+        let (a, mut b, c) = get_test_wires(3);
+        b[3] = Fr::from(4);
+        assert!(plonkish.wires_to_z(&a, &b, &c).is_err());
+
+        // wires satisfying the copy constraints but not the gates
+        let (mut a, b, mut c) = get_test_wires(3);
+        a[1] += Fr::one();
+        c[4] += Fr::one();
+        assert!(!check_plonkish_gates(&plonkish, &a, &b, &c));
+        let z = plonkish.wires_to_z(&a, &b, &c).unwrap();
+        let (w, x) = ccs.split_z(&z);
+        assert!(ccs.check_relation(&w, &x).is_err());
+    }
+
+    #[test]
+    fn test_plonkish_malformed() {
+        let plonkish = get_test_plonkish();
+        let new = |sigma: Vec<usize>, l: usize| {
+            PlonkishCS::new(
+                plonkish.q_L.clone(),
+                plonkish.q_R.clone(),
+                plonkish.q_M.clone(),
+                plonkish.q_O.clone(),
+                plonkish.q_C.clone(),
+                sigma,
+                l,
+            )
+        };
+
+        assert!(new(plonkish.sigma.clone(), 2).is_ok());
+        // σ is not a permutation
+        let mut sigma = plonkish.sigma.clone();
+        sigma[0] = sigma[1];
+        assert!(new(sigma, 2).is_err());
+        assert!(new(vec![0; 3 * N_GATES - 1], 2).is_err());
+        // public inputs copy-constrained to each other
+        let mut sigma: Vec<usize> = (0..3 * N_GATES).collect();
+        sigma.swap(A, A + 1);
+        assert!(new(sigma, 2).is_err());
+        // more public inputs than gates
+        assert!(new(plonkish.sigma.clone(), N_GATES + 1).is_err());
+    }
+}
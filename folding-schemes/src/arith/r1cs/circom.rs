@@ -0,0 +1,129 @@
+//! Exporter of the `R1CS` struct into the circom `.r1cs` binary format, as specified at
+//! https://github.com/iden3/r1csfile/blob/master/doc/r1cs_bin_format.md, so that the circuits
+//! built by the folding schemes can be consumed by external tooling such as snarkjs.
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::{io::Write, vec::Vec};
+
+use super::R1CS;
+use crate::utils::vec::SparseMatrix;
+use crate::Error;
+
+const MAGIC: &[u8; 4] = b"r1cs";
+const VERSION: u32 = 1;
+const HEADER_SECTION: u32 = 1;
+const CONSTRAINTS_SECTION: u32 = 2;
+const WIRE2LABEL_SECTION: u32 = 3;
+
+/// Writes the given R1CS in the circom `.r1cs` binary format.
+///
+/// The wires follow the layout of `z = (1, x, w)`: wire 0 is the constant 1, the `l` wires that
+/// follow are the public inputs `x`, and the remaining wires are the witness `w`. There are no
+/// public outputs, and the witness is not split into private inputs and intermediate signals, so
+/// all of it is written as intermediate signals. For the AugmentedFCircuit of Nova this means
+/// that the public inputs are `u_i.x = [H(pp_hash, context, i, z_0, z_i, U_i), H(cf_U_i)]`, in
+/// that order.
+///
+/// Each wire is labelled with its own index. The prime of the header is the modulus of `F`, so
+/// fields other than the BN254 scalar field are supported, although some tools only accept that
+/// one.
+pub fn r1cs_to_circom_bin<F: PrimeField, W: Write>(
+    r1cs: &R1CS<F>,
+    mut writer: W,
+) -> Result<(), Error> {
+    let prime = F::MODULUS.to_bytes_le();
+    let n_wires = r1cs.A.n_cols;
+
+    let mut header = Vec::new();
+    header.extend((prime.len() as u32).to_le_bytes());
+    header.extend(&prime);
+    header.extend(to_u32(n_wires)?.to_le_bytes());
+    header.extend(0_u32.to_le_bytes()); // public outputs
+    header.extend(to_u32(r1cs.l)?.to_le_bytes()); // public inputs
+    header.extend(0_u32.to_le_bytes()); // private inputs
+    header.extend((n_wires as u64).to_le_bytes()); // labels
+    header.extend(to_u32(r1cs.A.n_rows)?.to_le_bytes());
+
+    let mut constraints = Vec::new();
+    for i in 0..r1cs.A.n_rows {
+        for M in [&r1cs.A, &r1cs.B, &r1cs.C] {
+            write_linear_combination(&mut constraints, M, i)?;
+        }
+    }
+
+    let wire2label: Vec<u8> = (0..n_wires as u64).flat_map(u64::to_le_bytes).collect();
+
+    writer.write_all(MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    writer.write_all(&3_u32.to_le_bytes())?;
+    for (section_type, section) in [
+        (HEADER_SECTION, header),
+        (CONSTRAINTS_SECTION, constraints),
+        (WIRE2LABEL_SECTION, wire2label),
+    ] {
+        writer.write_all(&section_type.to_le_bytes())?;
+        writer.write_all(&(section.len() as u64).to_le_bytes())?;
+        writer.write_all(&section)?;
+    }
+    Ok(())
+}
+
+/// Writes the `i`-th row of `M` as a circom linear combination, that is, the number of non-zero
+/// entries followed by the wire index and the coefficient (in little-endian) of each of them.
+fn write_linear_combination<F: PrimeField>(
+    out: &mut Vec<u8>,
+    M: &SparseMatrix<F>,
+    i: usize,
+) -> Result<(), Error> {
+    let row = &M.coeffs[i];
+    out.extend(to_u32(row.len())?.to_le_bytes());
+    for (coeff, col) in row {
+        out.extend(to_u32(*col)?.to_le_bytes());
+        out.extend(coeff.into_bigint().to_bytes_le());
+    }
+    Ok(())
+}
+
+fn to_u32(n: usize) -> Result<u32, Error> {
+    u32::try_from(n).map_err(|_| Error::OutOfBounds)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::arith::r1cs::tests::get_test_r1cs;
+
+    #[test]
+    fn test_r1cs_to_circom_bin() {
+        let r1cs = get_test_r1cs::<ark_pallas::Fr>();
+        let mut bytes = Vec::new();
+        r1cs_to_circom_bin(&r1cs, &mut bytes).unwrap();
+
+        let read_u32 =
+            |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        assert_eq!(&bytes[0..4], MAGIC);
+        assert_eq!(read_u32(4), VERSION);
+        assert_eq!(read_u32(8), 3);
+
+        // header section, which starts after its type (u32) and size (u64)
+        assert_eq!(read_u32(12), HEADER_SECTION);
+        let header = 24;
+        assert_eq!(read_u32(header), 32);
+        assert_eq!(
+            bytes[header + 4..header + 36],
+            ark_pallas::Fr::MODULUS.to_bytes_le()
+        );
+        assert_eq!(read_u32(header + 36), r1cs.A.n_cols as u32); // wires
+        assert_eq!(read_u32(header + 44), r1cs.l as u32); // public inputs
+        assert_eq!(read_u32(header + 60), r1cs.A.n_rows as u32); // constraints
+
+        // the first entry of the first constraint is A[0][1] = 1
+        let constraints = header + 64 + 12;
+        assert_eq!(read_u32(constraints - 12), CONSTRAINTS_SECTION);
+        assert_eq!(read_u32(constraints), 1);
+        assert_eq!(read_u32(constraints + 4), 1);
+        assert_eq!(
+            bytes[constraints + 8..constraints + 40],
+            ark_pallas::Fr::from(1_u32).into_bigint().to_bytes_le()
+        );
+    }
+}
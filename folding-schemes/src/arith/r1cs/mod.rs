@@ -1,17 +1,22 @@
 use ark_ff::PrimeField;
 use ark_relations::r1cs::ConstraintSystem;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use ark_std::rand::Rng;
+use ark_std::io::{Read, Write};
+use ark_std::{rand::Rng, string::ToString, vec::Vec};
 
 use super::ccs::CCS;
 use super::{Arith, ArithSerializer};
 use crate::utils::vec::{
-    hadamard, is_zero_vec, mat_vec_mul, vec_scalar_mul, vec_sub, SparseMatrix,
+    check_zero_rows, hadamard, mat_vec_mul, read_varint_usize, vec_scalar_mul, vec_sub,
+    write_varint, SparseMatrix,
 };
 use crate::Error;
 
+pub mod circom;
 pub mod circuits;
 
+pub use circom::r1cs_to_circom_bin;
+
 #[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct R1CS<F: PrimeField> {
     pub l: usize, // io len
@@ -32,9 +37,17 @@ impl<F: PrimeField> R1CS<F> {
             ));
         }
 
-        let Az = mat_vec_mul(&self.A, z)?;
-        let Bz = mat_vec_mul(&self.B, z)?;
-        let Cz = mat_vec_mul(&self.C, z)?;
+        #[cfg(feature = "parallel")]
+        let (Az, (Bz, Cz)) = rayon::join(
+            || mat_vec_mul(&self.A, z),
+            || rayon::join(|| mat_vec_mul(&self.B, z), || mat_vec_mul(&self.C, z)),
+        );
+        #[cfg(not(feature = "parallel"))]
+        let (Az, (Bz, Cz)) = (
+            mat_vec_mul(&self.A, z),
+            (mat_vec_mul(&self.B, z), mat_vec_mul(&self.C, z)),
+        );
+        let (Az, Bz, Cz) = (Az?, Bz?, Cz?);
         // Multiply Cz by z[0] (u) here, allowing this method to be reused for
         // both relaxed and plain R1CS.
         let uCz = vec_scalar_mul(&Cz, &z[0]);
@@ -51,7 +64,7 @@ impl<F: PrimeField, W: AsRef<[F]>, U: AsRef<[F]>> Arith<W, U> for R1CS<F> {
     }
 
     fn check_evaluation(_w: &W, _u: &U, e: Self::Evaluation) -> Result<(), Error> {
-        is_zero_vec(&e).then_some(()).ok_or(Error::NotSatisfied)
+        check_zero_rows(&e)
     }
 }
 
@@ -64,6 +77,33 @@ impl<F: PrimeField> ArithSerializer for R1CS<F> {
         ]
         .concat()
     }
+
+    fn serialize_compressed_opt<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        write_varint(&mut writer, self.l as u64)?;
+        for M in [&self.A, &self.B, &self.C] {
+            M.serialize_compressed_opt(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize_compressed_opt<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let l = read_varint_usize(&mut reader)?;
+        let A = SparseMatrix::deserialize_compressed_opt(&mut reader)?;
+        let B = SparseMatrix::deserialize_compressed_opt(&mut reader)?;
+        let C = SparseMatrix::deserialize_compressed_opt(&mut reader)?;
+        for M in [&B, &C] {
+            if (M.n_rows, M.n_cols) != (A.n_rows, A.n_cols) {
+                return Err(Error::Other(format!(
+                    "R1CS matrices of different sizes, {}x{} and {}x{}",
+                    A.n_rows, A.n_cols, M.n_rows, M.n_cols
+                )));
+            }
+        }
+        if l >= A.n_cols {
+            return Err(Error::OutOfBounds);
+        }
+        Ok(R1CS { l, A, B, C })
+    }
 }
 
 impl<F: PrimeField> R1CS<F> {
@@ -231,6 +271,26 @@ pub mod tests {
         )
     }
 
+    #[test]
+    fn test_r1cs_serialize_compressed_opt() {
+        let mut rng = ark_std::test_rng();
+        for r1cs in [get_test_r1cs::<Fr>(), R1CS::<Fr>::rand(&mut rng, 10, 20)] {
+            let mut bytes = Vec::new();
+            r1cs.serialize_compressed_opt(&mut bytes).unwrap();
+            assert_eq!(
+                R1CS::<Fr>::deserialize_compressed_opt(&bytes[..]).unwrap(),
+                r1cs
+            );
+        }
+
+        // matrices of different sizes are rejected
+        let mut r1cs = get_test_r1cs::<Fr>();
+        r1cs.C = SparseMatrix::rand(&mut rng, 3, 6);
+        let mut bytes = Vec::new();
+        r1cs.serialize_compressed_opt(&mut bytes).unwrap();
+        assert!(R1CS::<Fr>::deserialize_compressed_opt(&bytes[..]).is_err());
+    }
+
     #[test]
     fn test_eval_r1cs_relation() {
         let mut rng = ark_std::test_rng();
@@ -251,4 +311,21 @@ pub mod tests {
         let (_, x, w) = get_test_z_split(5);
         r1cs.check_relation(&w, &x).unwrap();
     }
+
+    #[test]
+    fn test_check_r1cs_relation_failing_row() {
+        let r1cs = get_test_r1cs::<Fr>();
+        let (_, x, mut w) = get_test_z_split::<Fr>(5);
+        // `x^3 + x` is the output of the row 2 and an input of the row 3
+        w[3] += Fr::from(1);
+        match r1cs.check_relation(&w, &x) {
+            Err(Error::NotSatisfiedAt { row, lhs, rhs }) => {
+                assert_eq!(row, 2);
+                // (x + x^3) * 1 - (x^3 + x + 1)
+                assert_eq!(lhs, (-Fr::from(1)).to_string());
+                assert_eq!(rhs, Fr::from(0).to_string());
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
 }
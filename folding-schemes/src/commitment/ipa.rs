@@ -7,6 +7,7 @@
 /// i. <s, b> computation is done in log time following a modification of the equation 3 in section
 /// 3.2 from the paper.
 /// ii. s computation is done in 2^{k+1}-2 instead of k*2^k.
+use ark_crypto_primitives::sponge::Absorb;
 use ark_ec::{AffineRepr, CurveGroup};
 use ark_ff::{Field, PrimeField};
 use ark_r1cs_std::{
@@ -18,11 +19,18 @@ use ark_r1cs_std::{
 };
 use ark_relations::r1cs::{Namespace, SynthesisError};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use ark_std::{cfg_iter, rand::RngCore, UniformRand, Zero};
+use ark_std::{
+    borrow::ToOwned, cfg_iter, rand::RngCore, string::ToString, vec::Vec, UniformRand, Zero,
+};
 use core::{borrow::Borrow, marker::PhantomData};
+#[cfg(feature = "parallel")]
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
-use super::{pedersen::Params as PedersenParams, CommitmentScheme};
+use super::{
+    msm::Msm,
+    pedersen::{Params as PedersenParams, Pedersen},
+    CommitmentScheme,
+};
 use crate::transcript::Transcript;
 use crate::utils::{
     powers_of,
@@ -65,16 +73,30 @@ impl<C: CurveGroup, const H: bool> CommitmentScheme<C, H> for IPA<C, H> {
         mut rng: impl RngCore,
         len: usize,
     ) -> Result<(Self::ProverParams, Self::VerifierParams), Error> {
-        let generators: Vec<C::Affine> = std::iter::repeat_with(|| C::Affine::rand(&mut rng))
+        let generators: Vec<C::Affine> = ark_std::iter::repeat_with(|| C::Affine::rand(&mut rng))
             .take(len.next_power_of_two())
             .collect();
         let p = PedersenParams::<C> {
             h: C::rand(&mut rng),
             generators,
+            msm: Msm::default(),
         };
         Ok((p.clone(), p))
     }
 
+    /// The parameters are the same as Pedersen's, which are trimmed in the same way.
+    fn trim(
+        pp: &Self::ProverParams,
+        vp: &Self::VerifierParams,
+        len: usize,
+    ) -> Result<(Self::ProverParams, Self::VerifierParams), Error> {
+        Pedersen::<C, H>::trim(pp, vp, len)
+    }
+
+    fn prover_params_from_verifier(vp: &Self::VerifierParams) -> Option<Self::ProverParams> {
+        Pedersen::<C, H>::prover_params_from_verifier(vp)
+    }
+
     fn commit(
         params: &PedersenParams<C>,
         a: &[C::ScalarField],
@@ -88,11 +110,11 @@ impl<C: CurveGroup, const H: bool> CommitmentScheme<C, H> for IPA<C, H> {
         }
 
         // h⋅r + <g, a>
-        // use msm_unchecked because we already ensured at the if that lengths match
+        // the msm does not check the lengths, we already ensured at the if that they match
         if !H {
-            return Ok(C::msm_unchecked(&params.generators[..a.len()], a));
+            return Ok(params.msm.msm(&params.generators[..a.len()], a));
         }
-        Ok(params.h.mul(r) + C::msm_unchecked(&params.generators[..a.len()], a))
+        Ok(params.h.mul(r) + params.msm.msm(&params.generators[..a.len()], a))
     }
 
     fn prove(
@@ -103,6 +125,128 @@ impl<C: CurveGroup, const H: bool> CommitmentScheme<C, H> for IPA<C, H> {
         blind: &C::ScalarField,
         rng: Option<&mut dyn RngCore>,
     ) -> Result<Self::Proof, Error> {
+        transcript.absorb_nonnative(P);
+        // x is the challenge value at which we evaluate, s the one of the generator U
+        let (x, s) = challenge_pair(transcript);
+        Self::prove_at(params, transcript, a, x, s, blind, rng)
+    }
+
+    fn prove_with_challenge(
+        _params: &Self::ProverParams,
+        _challenge: Self::ProverChallenge,
+        _a: &[C::ScalarField], // vector
+        _blind: &C::ScalarField,
+        _rng: Option<&mut dyn RngCore>,
+    ) -> Result<Self::Proof, Error> {
+        // not supported because the prover logic computes challenges as it advances on the logic
+        Err(Error::NotSupported("IPA::prove_with_challenge".to_string()))
+    }
+
+    fn verify(
+        params: &Self::VerifierParams,
+        transcript: &mut impl Transcript<C::ScalarField>,
+        P: &C, // commitment
+        proof: &Self::Proof,
+    ) -> Result<(), Error> {
+        let (p, _r) = (proof.0.clone(), proof.1);
+        let k = p.L.len();
+
+        transcript.absorb_nonnative(P);
+        // x is the challenge value at which we evaluate, s the one of the generator U
+        let (x, s) = challenge_pair(transcript);
+        let U = C::generator().mul(s);
+        let mut u: Vec<C::ScalarField> = vec![C::ScalarField::zero(); k];
+        for i in (0..k).rev() {
+            transcript.absorb_nonnative(&p.L[i]);
+            transcript.absorb_nonnative(&p.R[i]);
+            u[i] = transcript.get_challenge();
+        }
+        let challenge = (x, U, u);
+
+        Self::verify_with_challenge(params, challenge, P, proof)
+    }
+
+    fn verify_with_challenge(
+        params: &Self::VerifierParams,
+        challenge: Self::Challenge,
+        P: &C, // commitment
+        proof: &Self::Proof,
+    ) -> Result<(), Error> {
+        let (p, v, r) = (proof.0.clone(), proof.1, proof.2);
+        let (x, U, u) = challenge;
+
+        let k = p.L.len();
+        if p.R.len() != k {
+            return Err(Error::CommitmentVerificationFail);
+        }
+        if !H && (!r.is_zero()) {
+            return Err(Error::BlindingNotZero);
+        }
+        if !H && (!p.l.is_empty() || !p.r.is_empty()) {
+            return Err(Error::CommitmentVerificationFail);
+        }
+        if H && (p.l.len() != k || p.r.len() != k) {
+            return Err(Error::CommitmentVerificationFail);
+        }
+
+        // U⋅v, where v=p(x), is moved to the q_1 side, as done in-circuit by IPAGadget::verify
+        let mut q_0 = *P;
+        let mut r = r;
+
+        // compute u[i]^-1 once
+        let mut u_invs = vec![C::ScalarField::zero(); u.len()];
+        for (j, u_j) in u.iter().enumerate() {
+            u_invs[j] = u_j
+                .inverse()
+                .ok_or(Error::Other("error on computing inverse".to_string()))?;
+        }
+
+        // compute b & G from s
+        let s = build_s(&u, &u_invs, k)?;
+        // b = <s, b_vec> = <s, [1, x, x^2, ..., x^d-1]>
+        let b = s_b_inner(&u, &u_invs, &x);
+        let d: usize = 2_u64.pow(k as u32) as usize;
+        if params.generators.len() < d {
+            return Err(Error::PedersenParamsLen(params.generators.len(), d));
+        }
+        let G = params.msm.msm(&params.generators, &s);
+
+        for (j, u_j) in u.iter().enumerate() {
+            let uj2 = u_j.square();
+            let uj_inv2 = u_invs[j].square();
+
+            q_0 = q_0 + p.L[j].mul(uj2) + p.R[j].mul(uj_inv2);
+            if H {
+                r = r + p.l[j] * uj2 + p.r[j] * uj_inv2;
+            }
+        }
+
+        let q_1 = if H {
+            G.mul(p.a) + params.h.mul(r) + U.mul(p.a * b - v)
+        } else {
+            G.mul(p.a) + U.mul(p.a * b - v)
+        };
+
+        if q_0 != q_1 {
+            return Err(Error::CommitmentVerificationFail);
+        }
+        Ok(())
+    }
+}
+
+impl<C: CurveGroup, const H: bool> IPA<C, H> {
+    /// Runs the IPA rounds proving the evaluation of the polynomial with coefficients `a` at the
+    /// given point `x`, where `s` is the challenge of the generator `U`, assuming that the
+    /// commitment, `x` and `s` have already been bound to the transcript.
+    fn prove_at(
+        params: &PedersenParams<C>,
+        transcript: &mut impl Transcript<C::ScalarField>,
+        a: &[C::ScalarField],
+        x: C::ScalarField,
+        s: C::ScalarField,
+        blind: &C::ScalarField,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<(Proof<C>, C::ScalarField, C::ScalarField), Error> {
         if !a.len().is_power_of_two() {
             return Err(Error::NotPowerOfTwo("a".to_string(), a.len()));
         }
@@ -110,7 +254,7 @@ impl<C: CurveGroup, const H: bool> CommitmentScheme<C, H> for IPA<C, H> {
             return Err(Error::BlindingNotZero);
         }
         let d = a.len();
-        let k = (f64::from(d as u32).log2()) as usize;
+        let k = d.trailing_zeros() as usize;
 
         if params.generators.len() < a.len() {
             return Err(Error::PedersenParamsLen(params.generators.len(), a.len()));
@@ -120,10 +264,10 @@ impl<C: CurveGroup, const H: bool> CommitmentScheme<C, H> for IPA<C, H> {
         let r: Vec<C::ScalarField>;
         if H {
             let rng = rng.ok_or(Error::MissingRandomness)?;
-            l = std::iter::repeat_with(|| C::ScalarField::rand(rng))
+            l = ark_std::iter::repeat_with(|| C::ScalarField::rand(rng))
                 .take(k)
                 .collect();
-            r = std::iter::repeat_with(|| C::ScalarField::rand(rng))
+            r = ark_std::iter::repeat_with(|| C::ScalarField::rand(rng))
                 .take(k)
                 .collect();
         } else {
@@ -131,9 +275,6 @@ impl<C: CurveGroup, const H: bool> CommitmentScheme<C, H> for IPA<C, H> {
             r = vec![];
         }
 
-        transcript.absorb_nonnative(P);
-        let x = transcript.get_challenge(); // challenge value at which we evaluate
-        let s = transcript.get_challenge();
         let U = C::generator().mul(s);
 
         let mut a = a.to_owned();
@@ -151,15 +292,15 @@ impl<C: CurveGroup, const H: bool> CommitmentScheme<C, H> for IPA<C, H> {
             let m = a.len() / 2;
 
             if H {
-                L[j] = C::msm_unchecked(&G[m..], &a[..m])
+                L[j] = params.msm.msm(&G[m..], &a[..m])
                     + params.h.mul(l[j])
                     + U.mul(inner_prod(&a[..m], &b[m..])?);
-                R[j] = C::msm_unchecked(&G[..m], &a[m..])
+                R[j] = params.msm.msm(&G[..m], &a[m..])
                     + params.h.mul(r[j])
                     + U.mul(inner_prod(&a[m..], &b[..m])?);
             } else {
-                L[j] = C::msm_unchecked(&G[m..], &a[..m]) + U.mul(inner_prod(&a[..m], &b[m..])?);
-                R[j] = C::msm_unchecked(&G[..m], &a[m..]) + U.mul(inner_prod(&a[m..], &b[..m])?);
+                L[j] = params.msm.msm(&G[m..], &a[..m]) + U.mul(inner_prod(&a[..m], &b[m..])?);
+                R[j] = params.msm.msm(&G[..m], &a[m..]) + U.mul(inner_prod(&a[m..], &b[..m])?);
             }
             // get challenge for the j-th round
             transcript.absorb_nonnative(&L[j]);
@@ -211,109 +352,218 @@ impl<C: CurveGroup, const H: bool> CommitmentScheme<C, H> for IPA<C, H> {
             *blind, // blind factor
         ))
     }
+}
 
-    fn prove_with_challenge(
-        _params: &Self::ProverParams,
-        _challenge: Self::ProverChallenge,
-        _a: &[C::ScalarField], // vector
-        _blind: &C::ScalarField,
-        _rng: Option<&mut dyn RngCore>,
-    ) -> Result<Self::Proof, Error> {
-        // not supported because the prover logic computes challenges as it advances on the logic
-        Err(Error::NotSupported("IPA::prove_with_challenge".to_string()))
-    }
+/// Single IPA proof for the openings of several commitments, possibly at different points,
+/// generated by `IPA::prove_aggregated`.
+#[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct AggregatedProof<C: CurveGroup> {
+    /// claimed evaluations p_i(x_i)
+    pub evals: Vec<C::ScalarField>,
+    /// commitment to the quotient q(X) = Σ y^i (p_i(X) - p_i(x_i)) / (X - x_i)
+    Q: C,
+    /// evaluations p_i(z) at the aggregation point z
+    evals_z: Vec<C::ScalarField>,
+    /// IPA proof of f(X) = q(X) + Σ ρ^{i+1} p_i(X) at z
+    proof: Proof<C>,
+    /// blinding factor of the commitment to f(X)
+    r: C::ScalarField,
+}
 
-    fn verify(
-        params: &Self::VerifierParams,
+impl<C: CurveGroup, const H: bool> IPA<C, H>
+where
+    C::ScalarField: Absorb,
+{
+    /// Opens the commitments `cms` to the vectors `polys` at the given `points` with a single IPA
+    /// proof, where each vector is read as the coefficients of a polynomial p_i(X). Vectors of
+    /// different lengths are zero-padded to the largest power of two, which does not change
+    /// neither their commitments nor their evaluations.
+    ///
+    /// The openings are aggregated as in the Halo2 multiopen argument:
+    /// 1. after absorbing the claims (cm_i, x_i, v_i), the prover commits to the quotient
+    ///    q(X) = Σ y^i (p_i(X) - v_i) / (X - x_i), for a challenge y.
+    /// 2. the prover sends u_i = p_i(z) for a challenge z, from which the verifier computes
+    ///    q(z) = Σ y^i (u_i - v_i) / (z - x_i).
+    /// 3. a single IPA proves that f(X) = q(X) + Σ ρ^{i+1} p_i(X), committed as
+    ///    Q + Σ ρ^{i+1} cm_i, evaluates to q(z) + Σ ρ^{i+1} u_i at z, for a challenge ρ.
+    ///
+    /// Soundness: if some v_i ≠ p_i(x_i), then (p_i(X) - v_i) / (X - x_i) is not a polynomial,
+    /// and except with probability n/|F| over y neither is the y-combination, so any committed
+    /// Q disagrees with it at the random z except with probability 2d/|F|. The ρ-combination
+    /// binds the n+1 openings at z into one, except with probability (n+1)/|F|.
+    pub fn prove_aggregated(
+        params: &PedersenParams<C>,
         transcript: &mut impl Transcript<C::ScalarField>,
-        P: &C, // commitment
-        proof: &Self::Proof,
-    ) -> Result<(), Error> {
-        let (p, _r) = (proof.0.clone(), proof.1);
-        let k = p.L.len();
-
-        transcript.absorb_nonnative(P);
-        let x = transcript.get_challenge(); // challenge value at which we evaluate
-        let s = transcript.get_challenge();
-        let U = C::generator().mul(s);
-        let mut u: Vec<C::ScalarField> = vec![C::ScalarField::zero(); k];
-        for i in (0..k).rev() {
-            transcript.absorb_nonnative(&p.L[i]);
-            transcript.absorb_nonnative(&p.R[i]);
-            u[i] = transcript.get_challenge();
+        cms: &[C],
+        polys: &[Vec<C::ScalarField>],
+        blinds: &[C::ScalarField],
+        points: &[C::ScalarField],
+        mut rng: Option<&mut dyn RngCore>,
+    ) -> Result<AggregatedProof<C>, Error> {
+        check_aggregated_lengths(cms.len(), polys.len(), "polys")?;
+        check_aggregated_lengths(cms.len(), blinds.len(), "blinds")?;
+        check_aggregated_lengths(cms.len(), points.len(), "points")?;
+        let d = polys
+            .iter()
+            .map(|p| p.len())
+            .max()
+            .unwrap_or(1)
+            .next_power_of_two();
+        if params.generators.len() < d {
+            return Err(Error::PedersenParamsLen(params.generators.len(), d));
         }
-        let challenge = (x, U, u);
-
-        Self::verify_with_challenge(params, challenge, P, proof)
+        let polys = polys
+            .iter()
+            .map(|p| [p.as_slice(), &vec![C::ScalarField::zero(); d - p.len()]].concat())
+            .collect::<Vec<_>>();
+
+        let evals = polys
+            .iter()
+            .zip(points)
+            .map(|(p, x)| inner_prod(p, &powers_of(*x, d)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let y = aggregation_challenge(transcript, cms, points, &evals);
+        let mut q = vec![C::ScalarField::zero(); d];
+        for (p, x) in polys.iter().zip(points).rev() {
+            q = vec_add(&vec_scalar_mul(&q, &y), &divide_by_linear(p, *x))?;
+        }
+        let r_Q = if H {
+            let rng = rng.as_mut().ok_or(Error::MissingRandomness)?;
+            C::ScalarField::rand(&mut **rng)
+        } else {
+            C::ScalarField::zero()
+        };
+        let Q = Self::commit(params, &q, &r_Q)?;
+        transcript.absorb_nonnative(&Q);
+        let z = transcript.get_challenge();
+
+        let b = powers_of(z, d);
+        let evals_z = polys
+            .iter()
+            .map(|p| inner_prod(p, &b))
+            .collect::<Result<Vec<_>, _>>()?;
+        transcript.absorb(&evals_z);
+        // rho is the challenge of the linear combination, s the one of the generator U of the IPA
+        let (rho, s) = challenge_pair(transcript);
+
+        // f(X) = q(X) + Σ ρ^{i+1} p_i(X), with blinding r_Q + Σ ρ^{i+1} r_i
+        let mut f = vec![C::ScalarField::zero(); d];
+        let mut r = C::ScalarField::zero();
+        for (p, r_i) in polys.iter().zip(blinds).rev() {
+            f = vec_scalar_mul(&vec_add(&f, p)?, &rho);
+            r = (r + r_i) * rho;
+        }
+        let f = vec_add(&f, &q)?;
+        let r = r + r_Q;
+
+        let (proof, _, r) = Self::prove_at(params, transcript, &f, z, s, &r, rng)?;
+        Ok(AggregatedProof {
+            evals,
+            Q,
+            evals_z,
+            proof,
+            r,
+        })
     }
 
-    fn verify_with_challenge(
-        params: &Self::VerifierParams,
-        challenge: Self::Challenge,
-        P: &C, // commitment
-        proof: &Self::Proof,
+    /// Verifies a proof generated by `prove_aggregated`, checking that each commitment `cms[i]`
+    /// opens to `proof.evals[i]` at `points[i]`.
+    pub fn verify_aggregated(
+        params: &PedersenParams<C>,
+        transcript: &mut impl Transcript<C::ScalarField>,
+        cms: &[C],
+        points: &[C::ScalarField],
+        proof: &AggregatedProof<C>,
     ) -> Result<(), Error> {
-        let (p, v, r) = (proof.0.clone(), proof.1, proof.2);
-        let (x, U, u) = challenge;
-
-        let k = p.L.len();
-        if p.R.len() != k {
-            return Err(Error::CommitmentVerificationFail);
-        }
-        if !H && (!r.is_zero()) {
-            return Err(Error::BlindingNotZero);
+        check_aggregated_lengths(cms.len(), points.len(), "points")?;
+        check_aggregated_lengths(cms.len(), proof.evals.len(), "evals")?;
+        check_aggregated_lengths(cms.len(), proof.evals_z.len(), "evals_z")?;
+
+        let y = aggregation_challenge(transcript, cms, points, &proof.evals);
+        transcript.absorb_nonnative(&proof.Q);
+        let z = transcript.get_challenge();
+        transcript.absorb(&proof.evals_z);
+        let (rho, s) = challenge_pair(transcript);
+
+        // q(z) = Σ y^i (u_i - v_i) / (z - x_i)
+        let mut q_z = C::ScalarField::zero();
+        for ((u_i, v_i), x_i) in proof.evals_z.iter().zip(&proof.evals).zip(points).rev() {
+            let den = (z - x_i)
+                .inverse()
+                .ok_or(Error::Other("error on computing inverse".to_string()))?;
+            q_z = q_z * y + (*u_i - v_i) * den;
         }
-        if !H && (!p.l.is_empty() || !p.r.is_empty()) {
-            return Err(Error::CommitmentVerificationFail);
+        // f(z) = q(z) + Σ ρ^{i+1} u_i, committed as Q + Σ ρ^{i+1} cm_i
+        let (mut P, mut v) = (C::zero(), C::ScalarField::zero());
+        for (cm, u_i) in cms.iter().zip(&proof.evals_z).rev() {
+            P = (P + cm) * rho;
+            v = (v + u_i) * rho;
         }
-        if H && (p.l.len() != k || p.r.len() != k) {
+        let P = P + proof.Q;
+        let v = v + q_z;
+
+        let k = proof.proof.L.len();
+        if proof.proof.R.len() != k {
             return Err(Error::CommitmentVerificationFail);
         }
-
-        let P = *P + U.mul(v); // where v=p(x)
-
-        let mut q_0 = P;
-        let mut r = r;
-
-        // compute u[i]^-1 once
-        let mut u_invs = vec![C::ScalarField::zero(); u.len()];
-        for (j, u_j) in u.iter().enumerate() {
-            u_invs[j] = u_j
-                .inverse()
-                .ok_or(Error::Other("error on computing inverse".to_string()))?;
+        let U = C::generator().mul(s);
+        let mut u: Vec<C::ScalarField> = vec![C::ScalarField::zero(); k];
+        for i in (0..k).rev() {
+            transcript.absorb_nonnative(&proof.proof.L[i]);
+            transcript.absorb_nonnative(&proof.proof.R[i]);
+            u[i] = transcript.get_challenge();
         }
 
-        // compute b & G from s
-        let s = build_s(&u, &u_invs, k)?;
-        // b = <s, b_vec> = <s, [1, x, x^2, ..., x^d-1]>
-        let b = s_b_inner(&u, &x)?;
-        let d: usize = 2_u64.pow(k as u32) as usize;
-        if params.generators.len() < d {
-            return Err(Error::PedersenParamsLen(params.generators.len(), d));
-        }
-        let G = C::msm_unchecked(&params.generators, &s);
+        Self::verify_with_challenge(params, (z, U, u), &P, &(proof.proof.clone(), v, proof.r))
+    }
+}
 
-        for (j, u_j) in u.iter().enumerate() {
-            let uj2 = u_j.square();
-            let uj_inv2 = u_invs[j].square();
+fn check_aggregated_lengths(n_cms: usize, n: usize, name: &str) -> Result<(), Error> {
+    if n_cms == 0 {
+        return Err(Error::Empty);
+    }
+    if n_cms != n {
+        return Err(Error::NotSameLength(
+            "cms".to_string(),
+            n_cms,
+            name.to_string(),
+            n,
+        ));
+    }
+    Ok(())
+}
 
-            q_0 = q_0 + p.L[j].mul(uj2) + p.R[j].mul(uj_inv2);
-            if H {
-                r = r + p.l[j] * uj2 + p.r[j] * uj_inv2;
-            }
-        }
+/// Squeezes the two challenges that are obtained one after the other, with `get_challenges`.
+fn challenge_pair<F: PrimeField>(transcript: &mut impl Transcript<F>) -> (F, F) {
+    let challenges = transcript.get_challenges(2);
+    (challenges[0], challenges[1])
+}
 
-        let q_1 = if H {
-            G.mul(p.a) + params.h.mul(r) + U.mul(p.a * b)
-        } else {
-            G.mul(p.a) + U.mul(p.a * b)
-        };
+fn aggregation_challenge<F: PrimeField + Absorb, C: CurveGroup<ScalarField = F>>(
+    transcript: &mut impl Transcript<F>,
+    cms: &[C],
+    points: &[F],
+    evals: &[F],
+) -> F {
+    for cm in cms {
+        transcript.absorb_nonnative(cm);
+    }
+    transcript.absorb(&points);
+    transcript.absorb(&evals);
+    transcript.get_challenge()
+}
 
-        if q_0 != q_1 {
-            return Err(Error::CommitmentVerificationFail);
-        }
-        Ok(())
+/// Computes the coefficients of (a(X) - a(x)) / (X - x) by synthetic division, padded to the
+/// length of `a`.
+fn divide_by_linear<F: PrimeField>(a: &[F], x: F) -> Vec<F> {
+    let mut q = vec![F::zero(); a.len()];
+    let mut acc = F::zero();
+    for j in (1..a.len()).rev() {
+        acc = acc * x + a[j];
+        q[j - 1] = acc;
     }
+    q
 }
 
 /// Computes s such that
@@ -350,44 +600,6 @@ fn build_s<F: PrimeField>(u: &[F], u_invs: &[F], k: usize) -> Result<Vec<F>, Err
     Ok(s)
 }
 
-/// Computes (in-circuit) s such that
-/// s = (
-///   u₁⁻¹ u₂⁻¹ … uₖ⁻¹,
-///   u₁   u₂⁻¹ … uₖ⁻¹,
-///   u₁⁻¹ u₂   … uₖ⁻¹,
-///   u₁   u₂   … uₖ⁻¹,
-///   ⋮    ⋮      ⋮
-///   u₁   u₂   … uₖ
-/// )
-/// Uses Halo2 approach computing $g(X) = \prod\limits_{i=0}^{k-1} (1 + u_{k - 1 - i} X^{2^i})$,
-/// taking 2^{k+1}-2.
-/// src: https://github.com/zcash/halo2/blob/81729eca91ba4755e247f49c3a72a4232864ec9e/halo2_proofs/src/poly/commitment/verifier.rs#L156
-fn build_s_gadget<F: PrimeField, CF: PrimeField>(
-    u: &[NonNativeFieldVar<F, CF>],
-    u_invs: &[NonNativeFieldVar<F, CF>],
-    k: usize,
-) -> Result<Vec<NonNativeFieldVar<F, CF>>, SynthesisError> {
-    let d: usize = 2_u64.pow(k as u32) as usize;
-    let mut s: Vec<NonNativeFieldVar<F, CF>> = vec![NonNativeFieldVar::one(); d];
-    for (len, (u_j, u_j_inv)) in u
-        .iter()
-        .zip(u_invs)
-        .enumerate()
-        .map(|(i, u_j)| (1 << i, u_j))
-    {
-        let (left, right) = s.split_at_mut(len);
-        let right = &mut right[0..len];
-        right.clone_from_slice(left);
-        for s in left {
-            *s *= u_j_inv;
-        }
-        for s in right {
-            *s *= u_j;
-        }
-    }
-    Ok(s)
-}
-
 fn inner_prod<F: PrimeField>(a: &[F], b: &[F]) -> Result<F, Error> {
     if a.len() != b.len() {
         return Err(Error::NotSameLength(
@@ -405,33 +617,50 @@ fn inner_prod<F: PrimeField>(a: &[F], b: &[F]) -> Result<F, Error> {
 }
 
 // g(x, u_1, u_2, ..., u_k) = <s, b>, naively takes linear, but can compute in log time through
-// g(x, u_1, u_2, ..., u_k) = \Prod u_i x^{2^i} + u_i^-1
-fn s_b_inner<F: PrimeField>(u: &[F], x: &F) -> Result<F, Error> {
-    let mut c: F = F::one();
+// g(x, u_1, u_2, ..., u_k) = \Prod u_i x^{2^i} + u_i^-1, where the product is computed as a tree
+fn s_b_inner<F: PrimeField>(u: &[F], u_invs: &[F], x: &F) -> F {
     let mut x_2_i = *x; // x_2_i is x^{2^i}, starting from x^{2^0}=x
-    for u_i in u.iter() {
-        c *= (*u_i * x_2_i)
-            + u_i
-                .inverse()
-                .ok_or(Error::Other("error on computing inverse".to_string()))?;
-        x_2_i *= x_2_i;
+    let mut terms = Vec::with_capacity(u.len());
+    for (u_i, u_inv_i) in u.iter().zip(u_invs) {
+        terms.push(*u_i * x_2_i + u_inv_i);
+        x_2_i.square_in_place();
     }
-    Ok(c)
+    while terms.len() > 1 {
+        terms = terms
+            .chunks(2)
+            .map(|c| if c.len() == 2 { c[0] * c[1] } else { c[0] })
+            .collect();
+    }
+    terms.pop().unwrap_or(F::one())
 }
 
 // g(x, u_1, u_2, ..., u_k) = <s, b>, naively takes linear, but can compute in log time through
-// g(x, u_1, u_2, ..., u_k) = \Prod u_i x^{2^i} + u_i^-1
+// g(x, u_1, u_2, ..., u_k) = \Prod u_i x^{2^i} + u_i^-1, where the product is computed as a tree
+// and the u_i^-1 are the ones already computed by the caller
 fn s_b_inner_gadget<F: PrimeField, CF: PrimeField>(
     u: &[NonNativeFieldVar<F, CF>],
+    u_invs: &[NonNativeFieldVar<F, CF>],
     x: &NonNativeFieldVar<F, CF>,
 ) -> Result<NonNativeFieldVar<F, CF>, SynthesisError> {
-    let mut c: NonNativeFieldVar<F, CF> = NonNativeFieldVar::<F, CF>::one();
     let mut x_2_i = x.clone(); // x_2_i is x^{2^i}, starting from x^{2^0}=x
-    for u_i in u.iter() {
-        c *= u_i.clone() * x_2_i.clone() + u_i.inverse()?;
-        x_2_i *= x_2_i.clone();
+    let mut terms = Vec::with_capacity(u.len());
+    for (u_i, u_inv_i) in u.iter().zip(u_invs) {
+        terms.push(u_i * &x_2_i + u_inv_i);
+        x_2_i = x_2_i.square()?;
     }
-    Ok(c)
+    while terms.len() > 1 {
+        terms = terms
+            .chunks(2)
+            .map(|c| {
+                if c.len() == 2 {
+                    &c[0] * &c[1]
+                } else {
+                    c[0].clone()
+                }
+            })
+            .collect();
+    }
+    Ok(terms.pop().unwrap_or(NonNativeFieldVar::one()))
 }
 
 pub type CF<C> = <<C as CurveGroup>::BaseField as Field>::BasePrimeField;
@@ -511,51 +740,57 @@ where
             return Err(SynthesisError::Unsatisfiable);
         }
 
-        let P_ = U.scalar_mul_le(v.to_bits_le()?.iter())? + P;
-        let mut q_0 = P_;
-        let mut r = r.clone();
-
-        // compute u[i]^-1 once
-        let mut u_invs = vec![NonNativeFieldVar::<C::ScalarField, CF<C>>::zero(); u.len()];
-        for (j, u_j) in u.iter().enumerate() {
-            u_invs[j] = u_j.inverse()?;
-        }
-
-        // compute b & G from s
-        let s = build_s_gadget(u, &u_invs, K)?;
-        // b = <s, b_vec> = <s, [1, x, x^2, ..., x^K-1]>
-        let b = s_b_inner_gadget(u, x)?;
-        // ensure that generators.len() === s.len():
-        if g.len() < K {
+        // ensure that the generators cover the 2^K-sized vector
+        if g.len() < 1 << K {
             return Err(SynthesisError::Unsatisfiable);
         }
 
-        // msm: G=<G, s>
-        let mut G = GC::zero();
-        for (i, s_i) in s.iter().enumerate() {
-            G += g[i].scalar_mul_le(s_i.to_bits_le()?.iter())?;
-        }
+        let mut q_0 = P.clone();
+        let mut r = r.clone();
 
-        for (j, u_j) in u.iter().enumerate() {
-            let uj2 = u_j.square()?;
+        // compute u[i]^-1 once
+        let u_invs = u
+            .iter()
+            .map(|u_j| u_j.inverse())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // b = <s, b_vec> = <s, [1, x, x^2, ..., x^d-1]>
+        let b = s_b_inner_gadget(u, &u_invs, x)?;
+
+        // G = <s, g> is not computed from s, since that would need the bits of its 2^K nonnative
+        // elements. Instead, as s_i = \Prod u_j^-1 ⋅ \Prod_{j: i_j=1} u_j^2, the generators are
+        // folded round by round into G' = <s, g> ⋅ \Prod u_j, with g' = g_lo + u_j^2 ⋅ g_hi, which
+        // only needs the bits of the K values u_j^2, shared with the L_j terms. The
+        // \Prod u_j^-1 factor is deferred to the final scalar multiplication by a.
+        let mut G = g[..1 << K].to_vec();
+        let mut u_invs_prod = NonNativeFieldVar::<C::ScalarField, CF<C>>::one();
+        for j in (0..K).rev() {
+            let uj2 = u[j].square()?;
             let uj_inv2 = u_invs[j].square()?; // cheaper square than inversing the uj2
+            let uj2_bits = uj2.to_bits_le()?;
+
+            let m = G.len() / 2;
+            G = G[..m]
+                .iter()
+                .zip(&G[m..])
+                .map(|(g_lo, g_hi)| Ok(g_hi.scalar_mul_le(uj2_bits.iter())? + g_lo))
+                .collect::<Result<Vec<GC>, SynthesisError>>()?;
 
             q_0 = q_0
-                + p.L[j].scalar_mul_le(uj2.to_bits_le()?.iter())?
+                + p.L[j].scalar_mul_le(uj2_bits.iter())?
                 + p.R[j].scalar_mul_le(uj_inv2.to_bits_le()?.iter())?;
             if H {
                 r = r + &p.l[j] * &uj2 + &p.r[j] * &uj_inv2;
             }
+            u_invs_prod *= &u_invs[j];
         }
 
-        let q_1 = if H {
-            G.scalar_mul_le(p.a.to_bits_le()?.iter())?
-                + h.scalar_mul_le(r.to_bits_le()?.iter())?
-                + U.scalar_mul_le((p.a.clone() * b).to_bits_le()?.iter())?
-        } else {
-            G.scalar_mul_le(p.a.to_bits_le()?.iter())?
-                + U.scalar_mul_le((p.a.clone() * b).to_bits_le()?.iter())?
-        };
+        // U⋅v is moved to the q_1 side, so that U is multiplied only once
+        let mut q_1 = G[0].scalar_mul_le((&p.a * &u_invs_prod).to_bits_le()?.iter())?
+            + U.scalar_mul_le((&p.a * &b - v).to_bits_le()?.iter())?;
+        if H {
+            q_1 += h.scalar_mul_le(r.to_bits_le()?.iter())?;
+        }
         // q_0 == q_1
         q_0.is_eq(&q_1)
     }
@@ -566,8 +801,9 @@ mod tests {
     use ark_crypto_primitives::sponge::{poseidon::PoseidonSponge, CryptographicSponge};
     use ark_ec::Group;
     use ark_pallas::{constraints::GVar, Fq, Fr, Projective};
-    use ark_r1cs_std::eq::EqGadget;
+    use ark_r1cs_std::{eq::EqGadget, R1CSVar};
     use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::One;
     use std::ops::Mul;
 
     use super::*;
@@ -617,6 +853,115 @@ mod tests {
         IPA::<Projective, hiding>::verify(&params, &mut transcript_v, &cm, &proof).unwrap();
     }
 
+    #[test]
+    fn test_ipa_aggregated() {
+        test_ipa_aggregated_opt::<false>();
+        test_ipa_aggregated_opt::<true>();
+    }
+    fn test_ipa_aggregated_opt<const hiding: bool>() {
+        let mut rng = ark_std::test_rng();
+
+        // vectors of different lengths, opened at different points
+        let lens = [16, 5, 8, 16];
+        let (params, _) = IPA::<Projective, hiding>::setup(&mut rng, 16).unwrap();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let polys: Vec<Vec<Fr>> = lens
+            .iter()
+            .map(|&n| {
+                std::iter::repeat_with(|| Fr::rand(&mut rng))
+                    .take(n)
+                    .collect()
+            })
+            .collect();
+        let blinds: Vec<Fr> = lens
+            .iter()
+            .map(|_| {
+                if hiding {
+                    Fr::rand(&mut rng)
+                } else {
+                    Fr::zero()
+                }
+            })
+            .collect();
+        let points: Vec<Fr> = lens.iter().map(|_| Fr::rand(&mut rng)).collect();
+        let cms: Vec<Projective> = polys
+            .iter()
+            .zip(&blinds)
+            .map(|(p, r)| IPA::<Projective, hiding>::commit(&params, p, r).unwrap())
+            .collect();
+
+        let mut transcript_p = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let proof = IPA::<Projective, hiding>::prove_aggregated(
+            &params,
+            &mut transcript_p,
+            &cms,
+            &polys,
+            &blinds,
+            &points,
+            Some(&mut rng),
+        )
+        .unwrap();
+        for ((p, x), v) in polys.iter().zip(&points).zip(&proof.evals) {
+            assert_eq!(inner_prod(p, &powers_of(*x, p.len())).unwrap(), *v);
+        }
+        let mut transcript_v = PoseidonSponge::<Fr>::new(&poseidon_config);
+        IPA::<Projective, hiding>::verify_aggregated(
+            &params,
+            &mut transcript_v,
+            &cms,
+            &points,
+            &proof,
+        )
+        .unwrap();
+
+        // the same openings proven individually take one IPA proof each
+        let mut individual_size = 0;
+        for ((p, r), cm) in polys.iter().zip(&blinds).zip(&cms) {
+            let p = [p.as_slice(), &vec![Fr::zero(); 16 - p.len()]].concat();
+            let mut transcript_p = PoseidonSponge::<Fr>::new(&poseidon_config);
+            let proof = IPA::<Projective, hiding>::prove(
+                &params,
+                &mut transcript_p,
+                cm,
+                &p,
+                r,
+                Some(&mut rng),
+            )
+            .unwrap();
+            let mut transcript_v = PoseidonSponge::<Fr>::new(&poseidon_config);
+            IPA::<Projective, hiding>::verify(&params, &mut transcript_v, cm, &proof).unwrap();
+            individual_size += proof.compressed_size();
+        }
+        assert!(proof.compressed_size() < individual_size);
+
+        // a wrong evaluation is rejected
+        let mut bad_proof = proof.clone();
+        bad_proof.evals[1] += Fr::one();
+        let mut transcript_v = PoseidonSponge::<Fr>::new(&poseidon_config);
+        assert!(IPA::<Projective, hiding>::verify_aggregated(
+            &params,
+            &mut transcript_v,
+            &cms,
+            &points,
+            &bad_proof,
+        )
+        .is_err());
+
+        // as well as a wrong point
+        let mut bad_points = points.clone();
+        bad_points[2] += Fr::one();
+        let mut transcript_v = PoseidonSponge::<Fr>::new(&poseidon_config);
+        assert!(IPA::<Projective, hiding>::verify_aggregated(
+            &params,
+            &mut transcript_v,
+            &cms,
+            &bad_points,
+            &proof,
+        )
+        .is_err());
+    }
+
     #[test]
     fn test_ipa_gadget() {
         test_ipa_gadget_opt::<false>();
@@ -665,8 +1010,7 @@ mod tests {
 
         let mut transcript_v = PoseidonSponge::<Fr>::new(&poseidon_config);
         transcript_v.absorb_nonnative(&cm);
-        let challenge = transcript_v.get_challenge(); // challenge value at which we evaluate
-        let s = transcript_v.get_challenge();
+        let (challenge, s) = challenge_pair(&mut transcript_v);
         let U = Projective::generator().mul(s);
         let mut u: Vec<Fr> = vec![Fr::zero(); k];
         for i in (0..k).rev() {
@@ -705,4 +1049,238 @@ mod tests {
         v.enforce_equal(&Boolean::TRUE).unwrap();
         assert!(cs.is_satisfied().unwrap());
     }
+
+    #[test]
+    fn test_ipa_gadget_native_equivalence() {
+        for tamper in [false, true] {
+            let (native, gadget, _) = ipa_gadget_check::<false, 4>(false, tamper);
+            assert_eq!(native, !tamper);
+            assert_eq!(gadget, native);
+            let (native, gadget, _) = ipa_gadget_check::<true, 4>(false, tamper);
+            assert_eq!(native, !tamper);
+            assert_eq!(gadget, native);
+        }
+    }
+
+    #[test]
+    fn test_ipa_gadget_constraints() {
+        ipa_gadget_constraints_check::<6>();
+    }
+
+    // same as `test_ipa_gadget_constraints` for vectors of 2^12 elements, which takes several
+    // minutes without optimizations
+    #[test]
+    #[ignore]
+    fn test_ipa_gadget_constraints_2_12() {
+        ipa_gadget_constraints_check::<12>();
+    }
+
+    /// Checks that, for vectors of 2^k elements, folding the generators round by round saves more
+    /// than 30% of the constraints of the naive gadget, which computes <s, G> from the bits of the
+    /// nonnative s vector.
+    fn ipa_gadget_constraints_check<const k: usize>() {
+        let (_, v, n_constraints) = ipa_gadget_check::<false, k>(false, false);
+        let (_, v_naive, n_constraints_naive) = ipa_gadget_check::<false, k>(true, false);
+        assert!(v && v_naive);
+        assert!(
+            10 * n_constraints < 7 * n_constraints_naive,
+            "{} constraints vs {} of the naive gadget",
+            n_constraints,
+            n_constraints_naive
+        );
+    }
+
+    /// Generates a random opening of a 2^k-sized vector and verifies it natively and in-circuit,
+    /// returning both results and the number of constraints of the gadget. If `tamper` is set,
+    /// the claimed evaluation is changed before verifying.
+    fn ipa_gadget_check<const hiding: bool, const k: usize>(
+        naive: bool,
+        tamper: bool,
+    ) -> (bool, bool, usize) {
+        let mut rng = ark_std::test_rng();
+        let d: usize = 1 << k;
+
+        let (params, _) = IPA::<Projective, hiding>::setup(&mut rng, d).unwrap();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let a: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(&mut rng))
+            .take(d)
+            .collect();
+        let r_blind: Fr = if hiding {
+            Fr::rand(&mut rng)
+        } else {
+            Fr::zero()
+        };
+        let cm = IPA::<Projective, hiding>::commit(&params, &a, &r_blind).unwrap();
+        let mut transcript_p = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let mut proof = IPA::<Projective, hiding>::prove(
+            &params,
+            &mut transcript_p,
+            &cm,
+            &a,
+            &r_blind,
+            Some(&mut rng),
+        )
+        .unwrap();
+        if tamper {
+            proof.1 += Fr::one();
+        }
+
+        let mut transcript_v = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let native =
+            IPA::<Projective, hiding>::verify(&params, &mut transcript_v, &cm, &proof).is_ok();
+
+        let mut transcript_v = PoseidonSponge::<Fr>::new(&poseidon_config);
+        transcript_v.absorb_nonnative(&cm);
+        let (challenge, s) = challenge_pair(&mut transcript_v);
+        let U = Projective::generator().mul(s);
+        let mut u: Vec<Fr> = vec![Fr::zero(); k];
+        for i in (0..k).rev() {
+            transcript_v.absorb_nonnative(&proof.0.L[i]);
+            transcript_v.absorb_nonnative(&proof.0.R[i]);
+            u[i] = transcript_v.get_challenge();
+        }
+
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let gVar = Vec::<GVar>::new_constant(cs.clone(), params.generators).unwrap();
+        let hVar = GVar::new_constant(cs.clone(), params.h).unwrap();
+        let challengeVar =
+            NonNativeFieldVar::<Fr, Fq>::new_witness(cs.clone(), || Ok(challenge)).unwrap();
+        let vVar = NonNativeFieldVar::<Fr, Fq>::new_witness(cs.clone(), || Ok(proof.1)).unwrap();
+        let cmVar = GVar::new_witness(cs.clone(), || Ok(cm)).unwrap();
+        let proofVar =
+            ProofVar::<Projective, GVar>::new_witness(cs.clone(), || Ok(proof.0)).unwrap();
+        let r_blindVar =
+            NonNativeFieldVar::<Fr, Fq>::new_witness(cs.clone(), || Ok(r_blind)).unwrap();
+        let uVar_vec = Vec::<NonNativeFieldVar<Fr, Fq>>::new_witness(cs.clone(), || Ok(u)).unwrap();
+        let uVar: [NonNativeFieldVar<Fr, Fq>; k] = uVar_vec.try_into().unwrap();
+        let UVar = GVar::new_witness(cs.clone(), || Ok(U)).unwrap();
+
+        let n_constraints = cs.num_constraints();
+        let v = if naive {
+            verify_naive_gadget::<k, hiding>(
+                &gVar,
+                &hVar,
+                &challengeVar,
+                &vVar,
+                &cmVar,
+                &proofVar,
+                &r_blindVar,
+                &uVar,
+                &UVar,
+            )
+        } else {
+            IPAGadget::<Projective, GVar, hiding>::verify::<k>(
+                &gVar,
+                &hVar,
+                &challengeVar,
+                &vVar,
+                &cmVar,
+                &proofVar,
+                &r_blindVar,
+                &uVar,
+                &UVar,
+            )
+        }
+        .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        (
+            native,
+            v.value().unwrap(),
+            cs.num_constraints() - n_constraints,
+        )
+    }
+
+    /// Reference IPA verification gadget computing <s, g> from the nonnative s vector, used to
+    /// measure the constraints saved by `IPAGadget::verify`.
+    #[allow(clippy::too_many_arguments)]
+    fn verify_naive_gadget<const K: usize, const H: bool>(
+        g: &[GVar],
+        h: &GVar,
+        x: &NonNativeFieldVar<Fr, Fq>,
+        v: &NonNativeFieldVar<Fr, Fq>,
+        P: &GVar,
+        p: &ProofVar<Projective, GVar>,
+        r: &NonNativeFieldVar<Fr, Fq>,
+        u: &[NonNativeFieldVar<Fr, Fq>; K],
+        U: &GVar,
+    ) -> Result<Boolean<Fq>, SynthesisError> {
+        let mut q_0 = U.scalar_mul_le(v.to_bits_le()?.iter())? + P;
+        let mut r = r.clone();
+
+        let mut u_invs = vec![NonNativeFieldVar::<Fr, Fq>::zero(); u.len()];
+        for (j, u_j) in u.iter().enumerate() {
+            u_invs[j] = u_j.inverse()?;
+        }
+
+        let s = build_s_gadget(u, &u_invs, K)?;
+        let mut b = NonNativeFieldVar::<Fr, Fq>::one();
+        let mut x_2_i = x.clone();
+        for u_i in u.iter() {
+            b *= u_i.clone() * x_2_i.clone() + u_i.inverse()?;
+            x_2_i *= x_2_i.clone();
+        }
+
+        let mut G = GVar::zero();
+        for (i, s_i) in s.iter().enumerate() {
+            G += g[i].scalar_mul_le(s_i.to_bits_le()?.iter())?;
+        }
+
+        for (j, u_j) in u.iter().enumerate() {
+            let uj2 = u_j.square()?;
+            let uj_inv2 = u_invs[j].square()?;
+
+            q_0 = q_0
+                + p.L[j].scalar_mul_le(uj2.to_bits_le()?.iter())?
+                + p.R[j].scalar_mul_le(uj_inv2.to_bits_le()?.iter())?;
+            if H {
+                r = r + &p.l[j] * &uj2 + &p.r[j] * &uj_inv2;
+            }
+        }
+
+        let mut q_1 = G.scalar_mul_le(p.a.to_bits_le()?.iter())?
+            + U.scalar_mul_le((p.a.clone() * b).to_bits_le()?.iter())?;
+        if H {
+            q_1 += h.scalar_mul_le(r.to_bits_le()?.iter())?;
+        }
+        q_0.is_eq(&q_1)
+    }
+
+    /// Computes (in-circuit) s such that
+    /// s = (
+    ///   u₁⁻¹ u₂⁻¹ … uₖ⁻¹,
+    ///   u₁   u₂⁻¹ … uₖ⁻¹,
+    ///   u₁⁻¹ u₂   … uₖ⁻¹,
+    ///   u₁   u₂   … uₖ⁻¹,
+    ///   ⋮    ⋮      ⋮
+    ///   u₁   u₂   … uₖ
+    /// )
+    /// Uses Halo2 approach computing $g(X) = \prod\limits_{i=0}^{k-1} (1 + u_{k - 1 - i} X^{2^i})$,
+    /// taking 2^{k+1}-2.
+    /// src: https://github.com/zcash/halo2/blob/81729eca91ba4755e247f49c3a72a4232864ec9e/halo2_proofs/src/poly/commitment/verifier.rs#L156
+    fn build_s_gadget<F: PrimeField, CF: PrimeField>(
+        u: &[NonNativeFieldVar<F, CF>],
+        u_invs: &[NonNativeFieldVar<F, CF>],
+        k: usize,
+    ) -> Result<Vec<NonNativeFieldVar<F, CF>>, SynthesisError> {
+        let d: usize = 2_u64.pow(k as u32) as usize;
+        let mut s: Vec<NonNativeFieldVar<F, CF>> = vec![NonNativeFieldVar::one(); d];
+        for (len, (u_j, u_j_inv)) in u
+            .iter()
+            .zip(u_invs)
+            .enumerate()
+            .map(|(i, u_j)| (1 << i, u_j))
+        {
+            let (left, right) = s.split_at_mut(len);
+            let right = &mut right[0..len];
+            right.clone_from_slice(left);
+            for s in left {
+                *s *= u_j_inv;
+            }
+            for s in right {
+                *s *= u_j;
+            }
+        }
+        Ok(s)
+    }
 }
@@ -5,23 +5,25 @@
 /// vectors indistinctly, and the arkworks KZG10 implementation contains all the methods under the
 /// same trait, which requires the Pairing trait, where the prover does not need access to the
 /// Pairing but only to G1.
-use ark_ec::{pairing::Pairing, CurveGroup, VariableBaseMSM};
-use ark_ff::PrimeField;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{batch_inversion, Field, PrimeField};
 use ark_poly::{
     univariate::{DenseOrSparsePolynomial, DensePolynomial},
-    DenseUVPolynomial, Polynomial,
+    DenseUVPolynomial, EvaluationDomain, GeneralEvaluationDomain, Polynomial,
 };
 use ark_poly_commit::kzg10::{
     Commitment as KZG10Commitment, Proof as KZG10Proof, VerifierKey, KZG10,
 };
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Valid};
-use ark_std::rand::RngCore;
-use ark_std::{borrow::Cow, fmt::Debug};
-use ark_std::{One, Zero};
+use ark_std::{borrow::Cow, collections::BTreeMap, fmt::Debug};
+use ark_std::{rand::RngCore, string::ToString, vec::Vec};
+use ark_std::{One, UniformRand, Zero};
 use core::marker::PhantomData;
+#[cfg(feature = "parallel")]
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
-use super::CommitmentScheme;
+use super::{msm::Msm, CommitmentScheme};
 use crate::transcript::Transcript;
 use crate::utils::vec::poly_from_vec;
 use crate::Error;
@@ -32,31 +34,97 @@ use crate::Error;
 pub struct ProverKey<'a, C: CurveGroup> {
     /// Group elements of the form `β^i G`, for different values of `i`.
     pub powers_of_g: Cow<'a, [C::Affine]>,
+    /// Group elements of the form `L_i(β) G`, where `L_i` are the Lagrange basis polynomials of
+    /// the evaluation domain, keyed by the size of the domain. When the table for the domain of a
+    /// vector is present, the vector is committed and opened directly from its evaluations,
+    /// avoiding the interpolation into coefficient form. The tables are computed from
+    /// `powers_of_g` by `add_lagrange_basis`, and are not serialized.
+    pub lagrange_bases: BTreeMap<usize, Vec<C::Affine>>,
+    /// MSM backend used by the prover, see `commitment::msm`. It is not serialized.
+    pub msm: Msm<C>,
+    /// Group element `γ G` multiplying the blinding factor of the hiding commitments. It is zero
+    /// when the setup does not provide it (eg. when loaded from a ptau file), in which case
+    /// hiding is not supported.
+    pub gamma_g: C::Affine,
+    /// Group element `β γ G`, which commits to the random hiding polynomial by which the
+    /// openings of hiding commitments are blinded. As `gamma_g`, it is zero when the setup does
+    /// not provide it.
+    pub beta_gamma_g: C::Affine,
+}
+
+impl<'a, C: CurveGroup> ProverKey<'a, C> {
+    /// Computes and stores the Lagrange basis table for the evaluation domain used by vectors of
+    /// length `len`, which is an iFFT over the group elements `powers_of_g`. This is done once,
+    /// so that the following commitments and openings of vectors of this length do not need to
+    /// interpolate them.
+    pub fn add_lagrange_basis(&mut self, len: usize) -> Result<(), Error> {
+        let domain =
+            GeneralEvaluationDomain::<C::ScalarField>::new(len).ok_or(Error::NewDomainFail)?;
+        let n = domain.size();
+        if n > self.powers_of_g.len() {
+            return Err(Error::PolyCommitError(
+                ark_poly_commit::error::Error::TooManyCoefficients {
+                    num_coefficients: n,
+                    num_powers: self.powers_of_g.len(),
+                },
+            ));
+        }
+        let powers = self.powers_of_g[..n]
+            .iter()
+            .map(|p| p.into_group())
+            .collect::<Vec<_>>();
+        let lagrange_basis = C::normalize_batch(&domain.ifft(&powers));
+        self.lagrange_bases.insert(n, lagrange_basis);
+        Ok(())
+    }
+
+    /// Returns the Lagrange basis table for the evaluation domain used by vectors of length
+    /// `len`, if it has been computed.
+    fn lagrange_basis(
+        &self,
+        len: usize,
+    ) -> Option<(&[C::Affine], GeneralEvaluationDomain<C::ScalarField>)> {
+        let domain = GeneralEvaluationDomain::<C::ScalarField>::new(len)?;
+        self.lagrange_bases
+            .get(&domain.size())
+            .map(|l| (l.as_slice(), domain))
+    }
 }
 
 impl<'a, C: CurveGroup> CanonicalSerialize for ProverKey<'a, C> {
-    fn serialize_with_mode<W: std::io::prelude::Write>(
+    fn serialize_with_mode<W: ark_std::io::Write>(
         &self,
         mut writer: W,
         compress: ark_serialize::Compress,
     ) -> Result<(), ark_serialize::SerializationError> {
-        self.powers_of_g.serialize_with_mode(&mut writer, compress)
+        self.powers_of_g
+            .serialize_with_mode(&mut writer, compress)?;
+        self.gamma_g.serialize_with_mode(&mut writer, compress)?;
+        self.beta_gamma_g.serialize_with_mode(&mut writer, compress)
     }
 
     fn serialized_size(&self, compress: ark_serialize::Compress) -> usize {
         self.powers_of_g.serialized_size(compress)
+            + self.gamma_g.serialized_size(compress)
+            + self.beta_gamma_g.serialized_size(compress)
     }
 }
 
 impl<'a, C: CurveGroup> CanonicalDeserialize for ProverKey<'a, C> {
-    fn deserialize_with_mode<R: std::io::prelude::Read>(
-        reader: R,
+    fn deserialize_with_mode<R: ark_std::io::Read>(
+        mut reader: R,
         compress: ark_serialize::Compress,
         validate: ark_serialize::Validate,
     ) -> Result<Self, ark_serialize::SerializationError> {
-        let powers_of_g_vec = Vec::deserialize_with_mode(reader, compress, validate)?;
+        let powers_of_g_vec = Vec::deserialize_with_mode(&mut reader, compress, validate)?;
+        let gamma_g = C::Affine::deserialize_with_mode(&mut reader, compress, validate)?;
+        let beta_gamma_g = C::Affine::deserialize_with_mode(&mut reader, compress, validate)?;
         Ok(ProverKey {
             powers_of_g: ark_std::borrow::Cow::Owned(powers_of_g_vec),
+            lagrange_bases: BTreeMap::new(),
+            msm: Msm::default(),
+            gamma_g,
+            beta_gamma_g,
         })
     }
 }
@@ -66,7 +134,9 @@ impl<'a, C: CurveGroup> Valid for ProverKey<'a, C> {
         match self.powers_of_g.clone() {
             Cow::Borrowed(powers) => powers.to_vec().check(),
             Cow::Owned(powers) => powers.check(),
-        }
+        }?;
+        self.gamma_g.check()?;
+        self.beta_gamma_g.check()
     }
 }
 
@@ -74,6 +144,13 @@ impl<'a, C: CurveGroup> Valid for ProverKey<'a, C> {
 pub struct Proof<C: CurveGroup> {
     pub eval: C::ScalarField,
     pub proof: C,
+    /// evaluation at the opening point of the hiding polynomial, which is the blinding factor of
+    /// the opened commitment plus the random hiding polynomial of the opening. Only present for
+    /// hiding commitments, so that the verifier can remove `random_v * γ G` from them.
+    pub random_v: Option<C::ScalarField>,
+    /// commitment `s β γ G` to the random hiding polynomial `s X` of the opening, which the
+    /// verifier adds to the opened commitment. Only present for hiding commitments.
+    pub hiding_commitment: Option<C>,
 }
 
 /// KZG implements the CommitmentScheme trait for the KZG commitment scheme.
@@ -113,6 +190,10 @@ where
         let powers_of_g = universal_params.powers_of_g[..=len].to_vec();
         let powers = ProverKey::<E::G1> {
             powers_of_g: ark_std::borrow::Cow::Owned(powers_of_g),
+            lagrange_bases: BTreeMap::new(),
+            msm: Msm::default(),
+            gamma_g: universal_params.powers_of_gamma_g[&0],
+            beta_gamma_g: universal_params.powers_of_gamma_g[&1],
         };
         let vk = VerifierKey {
             g: universal_params.powers_of_g[0],
@@ -125,35 +206,98 @@ where
         Ok((powers, vk))
     }
 
+    /// Keeps the `len.next_power_of_two() + 1` first powers, as `setup`, together with the
+    /// Lagrange basis tables that fit in them. The verifier key does not depend on the length.
+    fn trim(
+        pp: &Self::ProverParams,
+        vp: &Self::VerifierParams,
+        len: usize,
+    ) -> Result<(Self::ProverParams, Self::VerifierParams), Error> {
+        let num_powers = len.next_power_of_two() + 1;
+        if pp.powers_of_g.len() < num_powers {
+            return Err(Error::PolyCommitError(
+                ark_poly_commit::error::Error::TooManyCoefficients {
+                    num_coefficients: num_powers,
+                    num_powers: pp.powers_of_g.len(),
+                },
+            ));
+        }
+        let pp = ProverKey::<E::G1> {
+            powers_of_g: Cow::Owned(pp.powers_of_g[..num_powers].to_vec()),
+            lagrange_bases: pp
+                .lagrange_bases
+                .range(..=num_powers)
+                .map(|(n, basis)| (*n, basis.clone()))
+                .collect(),
+            msm: pp.msm.clone(),
+            gamma_g: pp.gamma_g,
+            beta_gamma_g: pp.beta_gamma_g,
+        };
+        Ok((pp, vp.clone()))
+    }
+
     /// commit implements the CommitmentScheme commit interface, adapting the implementation from
     /// https://github.com/arkworks-rs/poly-commit/tree/c724fa666e935bbba8db5a1421603bab542e15ab/poly-commit/src/kzg10/mod.rs#L178
-    /// with the main difference being the removal of the blinding factors and the no-dependency to
-    /// the Pairing trait.
+    /// with the main differences being that the hiding polynomial is replaced by the single
+    /// blinding factor `blind`, which keeps the commitments additively homomorphic in it as
+    /// required by folding, and the no-dependency to the Pairing trait.
     fn commit(
         params: &Self::ProverParams,
         v: &[E::ScalarField],
-        _blind: &E::ScalarField,
+        blind: &E::ScalarField,
+    ) -> Result<E::G1, Error> {
+        check_blinding::<E::G1, H>(params, blind)?;
+
+        // if the Lagrange basis is available, commit directly to the evaluations
+        let commitment = if let Some((lagrange_basis, _)) = params.lagrange_basis(v.len()) {
+            params.msm.msm(&lagrange_basis[..v.len()], v)
+        } else {
+            let polynomial = poly_from_vec(v.to_vec())?;
+            check_degree_is_too_large(polynomial.degree(), params.powers_of_g.len())?;
+
+            let (num_leading_zeros, plain_coeffs) = skip_first_zero_coeffs(&polynomial);
+            params
+                .msm
+                .msm(&params.powers_of_g[num_leading_zeros..], plain_coeffs)
+        };
+        Ok(blinded::<E::G1, H>(params, commitment, blind))
+    }
+
+    /// With the Lagrange basis of the vector's domain available, the commitment is the MSM of
+    /// the non-zero entries with their Lagrange basis elements. Otherwise the interpolated
+    /// polynomial is dense anyway, so it falls back to `commit`.
+    fn commit_sparse(
+        params: &Self::ProverParams,
+        len: usize,
+        pairs: &[(usize, E::ScalarField)],
+        blind: &E::ScalarField,
     ) -> Result<E::G1, Error> {
-        if !_blind.is_zero() || H {
-            return Err(Error::NotSupportedYet("hiding".to_string()));
+        check_blinding::<E::G1, H>(params, blind)?;
+        if pairs.iter().any(|(i, _)| *i >= len) {
+            return Err(Error::OutOfBounds);
         }
 
-        let polynomial = poly_from_vec(v.to_vec())?;
-        check_degree_is_too_large(polynomial.degree(), params.powers_of_g.len())?;
+        if let Some((lagrange_basis, _)) = params.lagrange_basis(len) {
+            let (bases, scalars): (Vec<E::G1Affine>, Vec<E::ScalarField>) = pairs
+                .iter()
+                .map(|(i, v_i)| (lagrange_basis[*i], *v_i))
+                .unzip();
+            let commitment = params.msm.msm(&bases, &scalars);
+            return Ok(blinded::<E::G1, H>(params, commitment, blind));
+        }
 
-        let (num_leading_zeros, plain_coeffs) =
-            skip_first_zero_coeffs_and_convert_to_bigints(&polynomial);
-        let commitment = <E::G1 as VariableBaseMSM>::msm_bigint(
-            &params.powers_of_g[num_leading_zeros..],
-            &plain_coeffs,
-        );
-        Ok(commitment)
+        let mut v = vec![E::ScalarField::zero(); len];
+        for (i, v_i) in pairs {
+            v[*i] += v_i;
+        }
+        Self::commit(params, &v, blind)
     }
 
     /// prove implements the CommitmentScheme prove interface, adapting the implementation from
     /// https://github.com/arkworks-rs/poly-commit/tree/c724fa666e935bbba8db5a1421603bab542e15ab/poly-commit/src/kzg10/mod.rs#L307
-    /// with the main difference being the removal of the blinding factors and the no-dependency to
-    /// the Pairing trait.
+    /// with the main differences being that, since the hiding polynomial of the commitment is the
+    /// constant blinding factor, the opening is blinded by a fresh random hiding polynomial
+    /// `s X` whose commitment is part of the proof, and the no-dependency to the Pairing trait.
     fn prove(
         params: &Self::ProverParams,
         transcript: &mut impl Transcript<E::ScalarField>,
@@ -167,49 +311,30 @@ where
         Self::prove_with_challenge(params, challenge, v, _blind, _rng)
     }
 
+    /// For hiding commitments `rng` must be given, to sample the hiding polynomial of the opening.
     fn prove_with_challenge(
         params: &Self::ProverParams,
         challenge: Self::ProverChallenge,
         v: &[E::ScalarField],
-        _blind: &E::ScalarField,
-        _rng: Option<&mut dyn RngCore>,
+        blind: &E::ScalarField,
+        rng: Option<&mut dyn RngCore>,
     ) -> Result<Self::Proof, Error> {
-        if !_blind.is_zero() || H {
-            return Err(Error::NotSupportedYet("hiding".to_string()));
-        }
+        check_blinding::<E::G1, H>(params, blind)?;
 
-        let polynomial = poly_from_vec(v.to_vec())?;
-        check_degree_is_too_large(polynomial.degree(), params.powers_of_g.len())?;
-
-        // Compute q(x) = (p(x) - p(z)) / (x-z). Observe that this quotient does not change with z
-        // because p(z) is the remainder term. We can therefore omit p(z) when computing the
-        // quotient.
-        let divisor = DensePolynomial::<E::ScalarField>::from_coefficients_vec(vec![
-            -challenge,
-            E::ScalarField::one(),
-        ]);
-        let (witness_poly, remainder_poly) = DenseOrSparsePolynomial::from(&polynomial)
-            .divide_with_q_and_r(&DenseOrSparsePolynomial::from(&divisor))
-            // the panic inside `divide_with_q_and_r` should never be reached, since the divisor
-            // polynomial is constructed right before and is set to not be zero. And the `.unwrap`
-            // should not give an error.
-            .unwrap();
-
-        let eval = if remainder_poly.is_zero() {
-            E::ScalarField::zero()
-        } else {
-            remainder_poly[0]
+        let mut proof = match open_lagrange::<E::G1>(params, v, challenge) {
+            Some(proof) => proof,
+            None => open::<E::G1>(params, &poly_from_vec(v.to_vec())?, challenge)?,
         };
-
-        check_degree_is_too_large(witness_poly.degree(), params.powers_of_g.len())?;
-        let (num_leading_zeros, witness_coeffs) =
-            skip_first_zero_coeffs_and_convert_to_bigints(&witness_poly);
-        let proof = <E::G1 as VariableBaseMSM>::msm_bigint(
-            &params.powers_of_g[num_leading_zeros..],
-            &witness_coeffs,
-        );
-
-        Ok(Proof { eval, proof })
+        if H {
+            // the hiding polynomial of the opening is `blind + s X`, whose quotient by `X - z`
+            // is `s`, so that `random_v = blind + s z` does not reveal the blinding factor
+            let rng = rng.ok_or(Error::MissingRandomness)?;
+            let s = E::ScalarField::rand(rng);
+            proof.proof += params.gamma_g * s;
+            proof.random_v = Some(*blind + s * challenge);
+            proof.hiding_commitment = Some(params.beta_gamma_g * s);
+        }
+        Ok(proof)
     }
 
     fn verify(
@@ -229,11 +354,14 @@ where
         cm: &E::G1,
         proof: &Self::Proof,
     ) -> Result<(), Error> {
-        if H {
-            return Err(Error::NotSupportedYet("hiding".to_string()));
+        // the hiding polynomial is given iff the commitments are hiding
+        if H != proof.random_v.is_some() || H != proof.hiding_commitment.is_some() {
+            return Err(Error::IncorrectBlinding(H, format!("{:?}", proof.random_v)));
         }
+        let cm = *cm + proof.hiding_commitment.unwrap_or_else(E::G1::zero);
 
-        // verify the KZG proof using arkworks method
+        // verify the KZG proof using arkworks method, which removes `random_v * γ G` from the
+        // commitment
         let v = KZG10::<E, DensePolynomial<E::ScalarField>>::check(
             params, // vk
             &KZG10Commitment(cm.into_affine()),
@@ -241,7 +369,7 @@ where
             proof.eval,
             &KZG10Proof::<E> {
                 w: proof.proof.into_affine(),
-                random_v: None,
+                random_v: proof.random_v,
             },
         )?;
         if !v {
@@ -251,6 +379,460 @@ where
     }
 }
 
+/// MultiPointVerifierKey extends the KZG `VerifierKey` with the powers of `β` in G1 and G2 needed
+/// to verify openings at several points at once, where `powers_of_g.len() == powers_of_h.len()`
+/// bounds the number of points that can be opened with a single proof.
+#[derive(Debug, Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MultiPointVerifierKey<E: Pairing> {
+    pub vk: VerifierKey<E>,
+    /// Group elements of the form `β^i G`, for `i` in `0..max_points`.
+    pub powers_of_g: Vec<E::G1Affine>,
+    /// Group elements of the form `β^i H`, for `i` in `0..=max_points`.
+    pub powers_of_h: Vec<E::G2Affine>,
+}
+
+impl<'a, E: Pairing> KZG<'a, E, false> {
+    /// Same as `setup`, but additionally returns the `MultiPointVerifierKey` that allows to verify
+    /// openings at up to `max_points` points with a single proof.
+    pub fn setup_multi(
+        mut rng: impl RngCore,
+        len: usize,
+        max_points: usize,
+    ) -> Result<(ProverKey<'a, E::G1>, MultiPointVerifierKey<E>), Error> {
+        let len = len.next_power_of_two();
+        let beta = E::ScalarField::rand(&mut rng);
+        let g = E::G1::rand(&mut rng);
+        let h = E::G2::rand(&mut rng);
+
+        let mut powers_of_beta = vec![E::ScalarField::one()];
+        for i in 0..len.max(max_points) {
+            powers_of_beta.push(powers_of_beta[i] * beta);
+        }
+
+        let powers_of_g = E::G1::normalize_batch(
+            &ark_std::cfg_iter!(powers_of_beta[..=len])
+                .map(|b| g * b)
+                .collect::<Vec<_>>(),
+        );
+        let powers_of_h = E::G2::normalize_batch(
+            &ark_std::cfg_iter!(powers_of_beta[..=max_points])
+                .map(|b| h * b)
+                .collect::<Vec<_>>(),
+        );
+
+        let vk = VerifierKey {
+            g: powers_of_g[0],
+            // hiding is not supported, so gamma_g is not used
+            gamma_g: E::G1Affine::zero(),
+            h: powers_of_h[0],
+            beta_h: powers_of_h[1],
+            prepared_h: powers_of_h[0].into(),
+            prepared_beta_h: powers_of_h[1].into(),
+        };
+        let mvk = MultiPointVerifierKey {
+            vk,
+            powers_of_g: powers_of_g[..max_points.min(powers_of_g.len())].to_vec(),
+            powers_of_h,
+        };
+        let pk = ProverKey {
+            powers_of_g: Cow::Owned(powers_of_g),
+            lagrange_bases: BTreeMap::new(),
+            msm: Msm::default(),
+            gamma_g: E::G1Affine::zero(),
+            beta_gamma_g: E::G1Affine::zero(),
+        };
+        Ok((pk, mvk))
+    }
+
+    /// Opens the polynomial interpolated from `v` at all the given `points` with a single proof.
+    ///
+    /// Returns the evaluations at each point, together with the proof, which is the commitment to
+    /// the quotient `q(X) = (p(X) - I(X)) / Z(X)`, where `Z(X) = Π (X - z_i)` is the vanishing
+    /// polynomial of the points and `I(X)` is the polynomial interpolating the evaluations.
+    pub fn prove_multi(
+        params: &ProverKey<'a, E::G1>,
+        v: &[E::ScalarField],
+        points: &[E::ScalarField],
+    ) -> Result<(Vec<E::ScalarField>, E::G1), Error> {
+        check_points(points)?;
+
+        let polynomial = poly_from_vec(v.to_vec())?;
+        check_degree_is_too_large(polynomial.degree(), params.powers_of_g.len())?;
+
+        let evals = points.iter().map(|z| polynomial.evaluate(z)).collect();
+
+        // since p(X) = q(X) Z(X) + I(X), the quotient does not depend on the evaluations, and we
+        // can discard the remainder of the division
+        let vanishing_poly = vanishing_poly(points);
+        let (witness_poly, _) = DenseOrSparsePolynomial::from(&polynomial)
+            .divide_with_q_and_r(&DenseOrSparsePolynomial::from(&vanishing_poly))
+            // the divisor is a product of non-zero degree-1 polynomials, so it can not be zero
+            .ok_or(Error::EvaluationFail)?;
+
+        check_degree_is_too_large(witness_poly.degree(), params.powers_of_g.len())?;
+        let (num_leading_zeros, witness_coeffs) = skip_first_zero_coeffs(&witness_poly);
+        let proof = params
+            .msm
+            .msm(&params.powers_of_g[num_leading_zeros..], witness_coeffs);
+
+        Ok((evals, proof))
+    }
+
+    /// Verifies a proof generated by `prove_multi`, checking that
+    /// `e(cm - [I(β)]_1, H) == e(proof, [Z(β)]_2)`.
+    pub fn verify_multi(
+        params: &MultiPointVerifierKey<E>,
+        cm: &E::G1,
+        points: &[E::ScalarField],
+        evals: &[E::ScalarField],
+        proof: &E::G1,
+    ) -> Result<(), Error> {
+        check_points(points)?;
+        if points.len() != evals.len() {
+            return Err(Error::NotSameLength(
+                "points.len()".to_string(),
+                points.len(),
+                "evals.len()".to_string(),
+                evals.len(),
+            ));
+        }
+        // deg(I) < points.len() <= powers_of_g.len(), deg(Z) = points.len() < powers_of_h.len()
+        if points.len() > params.powers_of_g.len() || points.len() >= params.powers_of_h.len() {
+            return Err(Error::PolyCommitError(
+                ark_poly_commit::error::Error::TooManyCoefficients {
+                    num_coefficients: points.len() + 1,
+                    num_powers: params.powers_of_h.len(),
+                },
+            ));
+        }
+
+        let interpolation_poly = interpolate(points, evals)?;
+        let vanishing_poly = vanishing_poly(points);
+
+        let (num_leading_zeros, coeffs) =
+            skip_first_zero_coeffs_and_convert_to_bigints(&interpolation_poly);
+        let interpolation_cm = <E::G1 as VariableBaseMSM>::msm_bigint(
+            &params.powers_of_g[num_leading_zeros..],
+            &coeffs,
+        );
+        let (num_leading_zeros, coeffs) =
+            skip_first_zero_coeffs_and_convert_to_bigints(&vanishing_poly);
+        let vanishing_cm = <E::G2 as VariableBaseMSM>::msm_bigint(
+            &params.powers_of_h[num_leading_zeros..],
+            &coeffs,
+        );
+
+        let check = E::multi_pairing(
+            [
+                (*cm - interpolation_cm).into_affine(),
+                (-*proof).into_affine(),
+            ],
+            [params.vk.h, vanishing_cm.into_affine()],
+        );
+        if !check.is_zero() {
+            return Err(Error::CommitmentVerificationFail);
+        }
+        Ok(())
+    }
+}
+
+impl<'a, E: Pairing> KZG<'a, E, false>
+where
+    E::ScalarField: Absorb,
+{
+    /// Opens all the polynomials interpolated from the vectors `vs` at the same `point` with a
+    /// single proof.
+    ///
+    /// The batching challenge `γ` is obtained from the transcript after absorbing the commitments,
+    /// the point and the evaluations. Returns the evaluations of each polynomial, together with
+    /// the opening proof of `Σ γ^j p_j(X)` at `point`.
+    pub fn prove_batch(
+        params: &ProverKey<'a, E::G1>,
+        transcript: &mut impl Transcript<E::ScalarField>,
+        cms: &[E::G1],
+        vs: &[Vec<E::ScalarField>],
+        point: E::ScalarField,
+    ) -> Result<(Vec<E::ScalarField>, E::G1), Error> {
+        let polynomials = vs
+            .iter()
+            .map(|v| poly_from_vec(v.clone()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let evals = polynomials
+            .iter()
+            .map(|p| p.evaluate(&point))
+            .collect::<Vec<_>>();
+        let gamma = batching_challenge(transcript, cms, point, &evals);
+        let proof = Self::prove_batch_with_challenge(params, gamma, vs, point)?;
+        Ok((evals, proof.proof))
+    }
+
+    /// Verifies a proof generated by `prove_batch`, with a single pairing check over the
+    /// `γ`-combined commitments and evaluations.
+    pub fn verify_batch(
+        params: &VerifierKey<E>,
+        transcript: &mut impl Transcript<E::ScalarField>,
+        cms: &[E::G1],
+        point: E::ScalarField,
+        evals: &[E::ScalarField],
+        proof: &E::G1,
+    ) -> Result<(), Error> {
+        let gamma = batching_challenge(transcript, cms, point, evals);
+        Self::verify_batch_with_challenge(params, gamma, cms, point, evals, proof)
+    }
+
+    /// same as `prove_batch` but instead of providing a Transcript to use, providing the already
+    /// computed batching challenge `γ`. The returned proof is a regular KZG proof for the
+    /// polynomial `Σ γ^j p_j(X)`, whose `eval` is `Σ γ^j p_j(point)`.
+    pub fn prove_batch_with_challenge(
+        params: &ProverKey<'a, E::G1>,
+        gamma: E::ScalarField,
+        vs: &[Vec<E::ScalarField>],
+        point: E::ScalarField,
+    ) -> Result<Proof<E::G1>, Error> {
+        if vs.is_empty() {
+            return Err(Error::Empty);
+        }
+
+        // if all the vectors share the same evaluation domain, they can be combined directly in
+        // evaluation form, and opened through the Lagrange basis if it is available
+        let max_len = vs.iter().map(|v| v.len()).max().unwrap_or(0);
+        let domain_size =
+            |len: usize| GeneralEvaluationDomain::<E::ScalarField>::new(len).map(|d| d.size());
+        if vs
+            .iter()
+            .all(|v| domain_size(v.len()) == domain_size(max_len))
+        {
+            let mut v = vec![E::ScalarField::zero(); max_len];
+            for v_j in vs.iter().rev() {
+                v.iter_mut().for_each(|v_i| *v_i *= gamma);
+                v.iter_mut().zip(v_j).for_each(|(v_i, v_ji)| *v_i += v_ji);
+            }
+            if let Some(proof) = open_lagrange::<E::G1>(params, &v, point) {
+                return Ok(proof);
+            }
+        }
+
+        let mut polynomial = DensePolynomial::zero();
+        for v in vs.iter().rev() {
+            polynomial = &(&polynomial * gamma) + &poly_from_vec(v.clone())?;
+        }
+        open::<E::G1>(params, &polynomial, point)
+    }
+
+    /// same as `verify_batch` but instead of providing a Transcript to use, providing the already
+    /// computed batching challenge `γ`.
+    pub fn verify_batch_with_challenge(
+        params: &VerifierKey<E>,
+        gamma: E::ScalarField,
+        cms: &[E::G1],
+        point: E::ScalarField,
+        evals: &[E::ScalarField],
+        proof: &E::G1,
+    ) -> Result<(), Error> {
+        if cms.len() != evals.len() {
+            return Err(Error::NotSameLength(
+                "cms.len()".to_string(),
+                cms.len(),
+                "evals.len()".to_string(),
+                evals.len(),
+            ));
+        }
+        if cms.is_empty() {
+            return Err(Error::Empty);
+        }
+        let (cm, eval) = batch_claims(gamma, cms, evals);
+        Self::verify_with_challenge(
+            params,
+            point,
+            &cm,
+            &Proof {
+                eval,
+                proof: *proof,
+                random_v: None,
+                hiding_commitment: None,
+            },
+        )
+    }
+}
+
+/// Combines the claims `(cm_j, eval_j)` about the openings of several polynomials at the same
+/// point into the single claim `(Σ γ^j cm_j, Σ γ^j eval_j)`.
+pub fn batch_claims<C: CurveGroup>(
+    gamma: C::ScalarField,
+    cms: &[C],
+    evals: &[C::ScalarField],
+) -> (C, C::ScalarField) {
+    cms.iter().zip(evals).rev().fold(
+        (C::zero(), C::ScalarField::zero()),
+        |(cm, eval), (cm_j, eval_j)| (cm * gamma + cm_j, eval * gamma + eval_j),
+    )
+}
+
+fn batching_challenge<F: PrimeField + Absorb, C: CurveGroup<ScalarField = F>>(
+    transcript: &mut impl Transcript<F>,
+    cms: &[C],
+    point: F,
+    evals: &[F],
+) -> F {
+    for cm in cms {
+        transcript.absorb_nonnative(cm);
+    }
+    transcript.absorb(&point);
+    transcript.absorb(&evals);
+    transcript.get_challenge()
+}
+
+/// Computes the KZG opening proof of `polynomial` at `point`.
+fn open<C: CurveGroup>(
+    params: &ProverKey<C>,
+    polynomial: &DensePolynomial<C::ScalarField>,
+    point: C::ScalarField,
+) -> Result<Proof<C>, Error> {
+    check_degree_is_too_large(polynomial.degree(), params.powers_of_g.len())?;
+
+    // Compute q(x) = (p(x) - p(z)) / (x-z). Observe that this quotient does not change with z
+    // because p(z) is the remainder term. We can therefore omit p(z) when computing the
+    // quotient.
+    let divisor = DensePolynomial::<C::ScalarField>::from_coefficients_vec(vec![
+        -point,
+        C::ScalarField::one(),
+    ]);
+    let (witness_poly, remainder_poly) = DenseOrSparsePolynomial::from(polynomial)
+        .divide_with_q_and_r(&DenseOrSparsePolynomial::from(&divisor))
+        // the panic inside `divide_with_q_and_r` should never be reached, since the divisor
+        // polynomial is constructed right before and is set to not be zero. And the `.unwrap`
+        // should not give an error.
+        .unwrap();
+
+    let eval = if remainder_poly.is_zero() {
+        C::ScalarField::zero()
+    } else {
+        remainder_poly[0]
+    };
+
+    check_degree_is_too_large(witness_poly.degree(), params.powers_of_g.len())?;
+    let (num_leading_zeros, witness_coeffs) = skip_first_zero_coeffs(&witness_poly);
+    let proof = params
+        .msm
+        .msm(&params.powers_of_g[num_leading_zeros..], witness_coeffs);
+
+    Ok(Proof {
+        eval,
+        proof,
+        random_v: None,
+        hiding_commitment: None,
+    })
+}
+
+/// Computes the KZG opening proof at `point` of the polynomial whose evaluations over the
+/// evaluation domain are `v`, using the Lagrange basis table of `params`.
+///
+/// The evaluation `p(z)` is computed with the barycentric formula, and the quotient
+/// `q(X) = (p(X) - p(z)) / (X - z)` is committed from its evaluations
+/// `q(ω^i) = (v_i - p(z)) / (ω^i - z)`, so no FFT is needed. Returns `None` if the Lagrange basis
+/// for the domain of `v` is not available, or if `point` is in the domain, in which cases the
+/// coefficient form path (`open`) has to be used.
+fn open_lagrange<C: CurveGroup>(
+    params: &ProverKey<C>,
+    v: &[C::ScalarField],
+    point: C::ScalarField,
+) -> Option<Proof<C>> {
+    let (lagrange_basis, domain) = params.lagrange_basis(v.len())?;
+    if domain.evaluate_vanishing_polynomial(point).is_zero() {
+        return None;
+    }
+
+    let lagrange_coeffs = domain.evaluate_all_lagrange_coefficients(point);
+    let eval: C::ScalarField = v
+        .iter()
+        .zip(&lagrange_coeffs)
+        .map(|(v_i, l_i)| *v_i * l_i)
+        .sum();
+
+    let mut denominators = domain.elements().map(|w| w - point).collect::<Vec<_>>();
+    batch_inversion(&mut denominators);
+    let quotient_evals = denominators
+        .iter()
+        .enumerate()
+        .map(|(i, d)| (v.get(i).copied().unwrap_or_default() - eval) * d)
+        .collect::<Vec<_>>();
+    let proof = params.msm.msm(lagrange_basis, &quotient_evals);
+
+    Some(Proof {
+        eval,
+        proof,
+        random_v: None,
+        hiding_commitment: None,
+    })
+}
+
+/// Checks that the blinding factor is zero for the non-hiding commitments, and that the params
+/// provide the `γ G` and `β γ G` needed by the hiding ones.
+fn check_blinding<C: CurveGroup, const H: bool>(
+    params: &ProverKey<C>,
+    blind: &C::ScalarField,
+) -> Result<(), Error> {
+    if !H && !blind.is_zero() {
+        return Err(Error::BlindingNotZero);
+    }
+    if H && (params.gamma_g.is_zero() || params.beta_gamma_g.is_zero()) {
+        return Err(Error::NotSupported(
+            "hiding KZG commitments with params without γ G and β γ G".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Adds `blind * γ G` to the commitment if it is hiding.
+fn blinded<C: CurveGroup, const H: bool>(
+    params: &ProverKey<C>,
+    commitment: C,
+    blind: &C::ScalarField,
+) -> C {
+    if !H {
+        return commitment;
+    }
+    commitment + params.gamma_g * blind
+}
+
+/// Checks that the given set of points is not empty and does not contain repeated points.
+fn check_points<F: PrimeField>(points: &[F]) -> Result<(), Error> {
+    if points.is_empty() {
+        return Err(Error::Empty);
+    }
+    let mut sorted = points.to_vec();
+    sorted.sort();
+    if sorted.windows(2).any(|w| w[0] == w[1]) {
+        return Err(Error::Other("repeated evaluation points".to_string()));
+    }
+    Ok(())
+}
+
+/// Returns `Z(X) = Π (X - z_i)`.
+fn vanishing_poly<F: PrimeField>(points: &[F]) -> DensePolynomial<F> {
+    points.iter().fold(
+        DensePolynomial::from_coefficients_vec(vec![F::one()]),
+        |acc, z| &acc * &DensePolynomial::from_coefficients_vec(vec![-*z, F::one()]),
+    )
+}
+
+/// Returns the polynomial `I(X)` of degree `< points.len()` such that `I(z_i) = y_i`.
+fn interpolate<F: PrimeField>(points: &[F], evals: &[F]) -> Result<DensePolynomial<F>, Error> {
+    let vanishing_poly = vanishing_poly(points);
+    let mut result = DensePolynomial::zero();
+    for (z, y) in points.iter().zip(evals) {
+        // L_i(X) = Z(X) / ((X - z_i) * Π_{j!=i} (z_i - z_j))
+        let divisor = DensePolynomial::from_coefficients_vec(vec![-*z, F::one()]);
+        let (numerator, _) = DenseOrSparsePolynomial::from(&vanishing_poly)
+            .divide_with_q_and_r(&DenseOrSparsePolynomial::from(&divisor))
+            .ok_or(Error::EvaluationFail)?;
+        let denominator = numerator.evaluate(z);
+        let coeff = *y * denominator.inverse().ok_or(Error::EvaluationFail)?;
+        result += &(&numerator * coeff);
+    }
+    Ok(result)
+}
+
 fn check_degree_is_too_large(
     degree: usize,
     num_powers: usize,
@@ -269,12 +851,16 @@ fn check_degree_is_too_large(
 fn skip_first_zero_coeffs_and_convert_to_bigints<F: PrimeField, P: DenseUVPolynomial<F>>(
     p: &P,
 ) -> (usize, Vec<F::BigInt>) {
+    let (num_leading_zeros, coeffs) = skip_first_zero_coeffs(p);
+    (num_leading_zeros, convert_to_bigints(coeffs))
+}
+
+fn skip_first_zero_coeffs<F: PrimeField, P: DenseUVPolynomial<F>>(p: &P) -> (usize, &[F]) {
     let mut num_leading_zeros = 0;
     while num_leading_zeros < p.coeffs().len() && p.coeffs()[num_leading_zeros].is_zero() {
         num_leading_zeros += 1;
     }
-    let coeffs = convert_to_bigints(&p.coeffs()[num_leading_zeros..]);
-    (num_leading_zeros, coeffs)
+    (num_leading_zeros, &p.coeffs()[num_leading_zeros..])
 }
 
 fn convert_to_bigints<F: PrimeField>(p: &[F]) -> Vec<F::BigInt> {
@@ -311,4 +897,212 @@ mod tests {
         // verify the proof:
         KZG::<Bn254>::verify(&vk, transcript_v, &cm, &proof).unwrap();
     }
+
+    #[test]
+    fn test_kzg_hiding_opening() {
+        let mut rng = test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let n = 10;
+        let (pk, vk): (ProverKey<G1>, VerifierKey<Bn254>) =
+            KZG::<Bn254, true>::setup(&mut rng, n).unwrap();
+
+        let v: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(&mut rng))
+            .take(n)
+            .collect();
+        let blind = Fr::rand(&mut rng);
+        let cm = KZG::<Bn254, true>::commit(&pk, &v, &blind).unwrap();
+
+        // the randomness for the hiding polynomial of the opening is needed
+        let transcript_p = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        assert!(KZG::<Bn254, true>::prove(&pk, transcript_p, &cm, &v, &blind, None).is_err());
+
+        let transcript_p = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        let proof =
+            KZG::<Bn254, true>::prove(&pk, transcript_p, &cm, &v, &blind, Some(&mut rng)).unwrap();
+        // the opening does not reveal the blinding factor of the commitment
+        assert_ne!(proof.random_v, Some(blind));
+        let transcript_v = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        KZG::<Bn254, true>::verify(&vk, transcript_v, &cm, &proof).unwrap();
+
+        // and the same opening is blinded differently each time
+        let transcript_p = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        let other_proof =
+            KZG::<Bn254, true>::prove(&pk, transcript_p, &cm, &v, &blind, Some(&mut rng)).unwrap();
+        assert_ne!(proof.random_v, other_proof.random_v);
+        assert_ne!(proof.proof, other_proof.proof);
+
+        // the proof does not verify without the commitment to its hiding polynomial
+        let transcript_v = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        let mut bad_proof = proof.clone();
+        bad_proof.hiding_commitment = Some(G1::zero());
+        assert!(KZG::<Bn254, true>::verify(&vk, transcript_v, &cm, &bad_proof).is_err());
+    }
+
+    #[test]
+    fn test_kzg_batch_opening() {
+        let mut rng = &mut test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let transcript_p = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        let transcript_v = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+
+        let n = 16;
+        let (pk, vk): (ProverKey<G1>, VerifierKey<Bn254>) =
+            KZG::<Bn254>::setup(&mut rng, n).unwrap();
+
+        // vectors of different lengths, as in the case of Nova's W and E
+        let vs: Vec<Vec<Fr>> = [n, n / 2, 3]
+            .iter()
+            .map(|&len| std::iter::repeat_with(|| Fr::rand(rng)).take(len).collect())
+            .collect();
+        let cms = vs
+            .iter()
+            .map(|v| KZG::<Bn254>::commit(&pk, v, &Fr::zero()))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let point = Fr::rand(rng);
+
+        let (evals, proof) =
+            KZG::<Bn254>::prove_batch(&pk, transcript_p, &cms, &vs, point).unwrap();
+
+        // the evaluations must match the ones of the individual proofs
+        for (v, eval) in vs.iter().zip(&evals) {
+            let single_proof =
+                KZG::<Bn254>::prove_with_challenge(&pk, point, v, &Fr::zero(), None).unwrap();
+            assert_eq!(single_proof.eval, *eval);
+        }
+
+        KZG::<Bn254>::verify_batch(&vk, &mut transcript_v.clone(), &cms, point, &evals, &proof)
+            .unwrap();
+
+        // wrong evaluations must not verify
+        let mut wrong_evals = evals.clone();
+        wrong_evals[1] += Fr::one();
+        assert!(KZG::<Bn254>::verify_batch(
+            &vk,
+            &mut transcript_v.clone(),
+            &cms,
+            point,
+            &wrong_evals,
+            &proof
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_kzg_lagrange_basis() {
+        let mut rng = &mut test_rng();
+        let n = 16;
+        let (pk, vk): (ProverKey<G1>, VerifierKey<Bn254>) =
+            KZG::<Bn254>::setup(&mut rng, n).unwrap();
+        let mut pk_lagrange = pk.clone();
+        pk_lagrange.add_lagrange_basis(n).unwrap();
+        pk_lagrange.add_lagrange_basis(n / 2).unwrap();
+        // the domain can not be bigger than the powers in the SRS
+        assert!(pk_lagrange.clone().add_lagrange_basis(4 * n).is_err());
+
+        // both paths must give the same commitments and proofs, for vectors filling the domain
+        // or not
+        for len in [n, n - 3, n / 2, 3] {
+            let v: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(rng)).take(len).collect();
+            let cm = KZG::<Bn254>::commit(&pk, &v, &Fr::zero()).unwrap();
+            let cm_lagrange = KZG::<Bn254>::commit(&pk_lagrange, &v, &Fr::zero()).unwrap();
+            assert_eq!(cm, cm_lagrange);
+
+            let point = Fr::rand(rng);
+            let proof =
+                KZG::<Bn254>::prove_with_challenge(&pk, point, &v, &Fr::zero(), None).unwrap();
+            let proof_lagrange =
+                KZG::<Bn254>::prove_with_challenge(&pk_lagrange, point, &v, &Fr::zero(), None)
+                    .unwrap();
+            assert_eq!(proof, proof_lagrange);
+            KZG::<Bn254>::verify_with_challenge(&vk, point, &cm_lagrange, &proof_lagrange).unwrap();
+
+            // a point in the evaluation domain falls back to the coefficient form path
+            let domain = GeneralEvaluationDomain::<Fr>::new(len).unwrap();
+            let point = domain.element(1);
+            let proof_lagrange =
+                KZG::<Bn254>::prove_with_challenge(&pk_lagrange, point, &v, &Fr::zero(), None)
+                    .unwrap();
+            assert_eq!(proof_lagrange.eval, v[1]);
+            KZG::<Bn254>::verify_with_challenge(&vk, point, &cm_lagrange, &proof_lagrange).unwrap();
+        }
+
+        // batch opening of vectors in the same domain
+        let vs: Vec<Vec<Fr>> = [n, n - 5]
+            .iter()
+            .map(|&len| std::iter::repeat_with(|| Fr::rand(rng)).take(len).collect())
+            .collect();
+        let (gamma, point) = (Fr::rand(rng), Fr::rand(rng));
+        let proof = KZG::<Bn254>::prove_batch_with_challenge(&pk, gamma, &vs, point).unwrap();
+        let proof_lagrange =
+            KZG::<Bn254>::prove_batch_with_challenge(&pk_lagrange, gamma, &vs, point).unwrap();
+        assert_eq!(proof, proof_lagrange);
+    }
+
+    #[test]
+    fn test_kzg_multi_point_opening() {
+        let mut rng = &mut test_rng();
+
+        let n = 16;
+        let max_points = 8;
+        let (pk, vk) = KZG::<Bn254>::setup_multi(&mut rng, n, max_points).unwrap();
+
+        for n_points in 1..=max_points {
+            let v: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(rng)).take(n).collect();
+            let cm = KZG::<Bn254>::commit(&pk, &v, &Fr::zero()).unwrap();
+            let points: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(rng))
+                .take(n_points)
+                .collect();
+
+            let (evals, proof) = KZG::<Bn254>::prove_multi(&pk, &v, &points).unwrap();
+            KZG::<Bn254>::verify_multi(&vk, &cm, &points, &evals, &proof).unwrap();
+
+            // the evaluations must match the ones of the naive per-point proofs
+            for (z, eval) in points.iter().zip(&evals) {
+                let single_proof =
+                    KZG::<Bn254>::prove_with_challenge(&pk, *z, &v, &Fr::zero(), None).unwrap();
+                assert_eq!(single_proof.eval, *eval);
+                KZG::<Bn254>::verify_with_challenge(&vk.vk, *z, &cm, &single_proof).unwrap();
+                if n_points == 1 {
+                    assert_eq!(single_proof.proof, proof);
+                }
+            }
+
+            // wrong evaluations must not verify
+            let mut wrong_evals = evals.clone();
+            wrong_evals[n_points - 1] += Fr::one();
+            assert!(KZG::<Bn254>::verify_multi(&vk, &cm, &points, &wrong_evals, &proof).is_err());
+        }
+    }
+
+    #[test]
+    fn test_kzg_multi_point_opening_edge_cases() {
+        let mut rng = &mut test_rng();
+
+        let n = 16;
+        let max_points = 4;
+        let (pk, vk) = KZG::<Bn254>::setup_multi(&mut rng, n, max_points).unwrap();
+        let v: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(rng)).take(n).collect();
+        let cm = KZG::<Bn254>::commit(&pk, &v, &Fr::zero()).unwrap();
+
+        // empty set of points
+        assert!(KZG::<Bn254>::prove_multi(&pk, &v, &[]).is_err());
+        assert!(KZG::<Bn254>::verify_multi(&vk, &cm, &[], &[], &G1::zero()).is_err());
+
+        // repeated points
+        let z = Fr::rand(rng);
+        assert!(KZG::<Bn254>::prove_multi(&pk, &v, &[z, Fr::one(), z]).is_err());
+
+        // more points than the ones supported by the verifier key
+        let points: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(rng))
+            .take(max_points + 1)
+            .collect();
+        let (evals, proof) = KZG::<Bn254>::prove_multi(&pk, &v, &points).unwrap();
+        assert!(KZG::<Bn254>::verify_multi(&vk, &cm, &points, &evals, &proof).is_err());
+
+        // evaluations and points length mismatch
+        let (evals, proof) = KZG::<Bn254>::prove_multi(&pk, &v, &points[..2]).unwrap();
+        assert!(KZG::<Bn254>::verify_multi(&vk, &cm, &points[..3], &evals, &proof).is_err());
+    }
 }
@@ -1,14 +1,20 @@
 use ark_ec::CurveGroup;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use ark_std::fmt::Debug;
 use ark_std::rand::RngCore;
+use ark_std::Zero;
+use ark_std::{fmt::Debug, string::ToString, vec::Vec};
 
 use crate::transcript::Transcript;
 use crate::Error;
 
 pub mod ipa;
 pub mod kzg;
+pub mod msm;
 pub mod pedersen;
+#[cfg(feature = "std")]
+pub mod ptau;
+pub mod versioned;
+pub mod zeromorph;
 
 /// CommitmentScheme defines the vector commitment scheme trait. Where `H` indicates if to use the
 /// commitment in hiding mode or not.
@@ -26,12 +32,57 @@ pub trait CommitmentScheme<C: CurveGroup, const H: bool = false>: Clone + Debug
         len: usize,
     ) -> Result<(Self::ProverParams, Self::VerifierParams), Error>;
 
+    /// Generates the parameters deterministically from the given domain-separation tag, so that
+    /// anyone can recompute them and check that nobody knows their discrete logs. Only transparent
+    /// schemes can support it, the rest return `Error::NotSupported`.
+    fn setup_deterministic(
+        _domain_tag: &[u8],
+        _len: usize,
+    ) -> Result<(Self::ProverParams, Self::VerifierParams), Error> {
+        Err(Error::NotSupported(
+            "deterministic setup for a trusted-setup commitment scheme".to_string(),
+        ))
+    }
+
+    /// Truncates the given parameters, which may come from a `setup` for a larger length, to the
+    /// ones that `setup` returns for `len`, so that they can be reused across circuits of
+    /// different sizes. Returns an error if they are too short for `len`.
+    fn trim(
+        pp: &Self::ProverParams,
+        vp: &Self::VerifierParams,
+        len: usize,
+    ) -> Result<(Self::ProverParams, Self::VerifierParams), Error>;
+
+    /// Returns the prover parameters that correspond to the given verifier parameters when they
+    /// can be recomputed from them, as for the schemes where both are the same, so that they can
+    /// be omitted when serializing the prover parameters. Returns `None` by default.
+    fn prover_params_from_verifier(_vp: &Self::VerifierParams) -> Option<Self::ProverParams> {
+        None
+    }
+
     fn commit(
         params: &Self::ProverParams,
         v: &[C::ScalarField],
         blind: &C::ScalarField,
     ) -> Result<C, Error>;
 
+    /// Commits to the vector of length `len` whose only non-zero entries are the given
+    /// `(index, value)` pairs, obtaining the same commitment as `commit` over the dense vector.
+    /// Repeated indexes add up. The default implementation builds the dense vector, schemes that
+    /// can skip the zero entries override it.
+    fn commit_sparse(
+        params: &Self::ProverParams,
+        len: usize,
+        pairs: &[(usize, C::ScalarField)],
+        blind: &C::ScalarField,
+    ) -> Result<C, Error> {
+        let mut v = vec![C::ScalarField::zero(); len];
+        for (i, v_i) in pairs {
+            *v.get_mut(*i).ok_or(Error::OutOfBounds)? += v_i;
+        }
+        Self::commit(params, &v, blind)
+    }
+
     fn prove(
         params: &Self::ProverParams,
         transcript: &mut impl Transcript<C::ScalarField>,
@@ -58,6 +109,34 @@ pub trait CommitmentScheme<C: CurveGroup, const H: bool = false>: Clone + Debug
         proof: &Self::Proof,
     ) -> Result<(), Error>;
 
+    /// Same as `prove`, but absorbs the domain-separation `label` into the transcript before the
+    /// commitment, binding the opening to its role in the protocol (e.g. `b"W"` or `b"cf_E"`), so
+    /// that openings in different positions of the same transcript can not be swapped.
+    fn prove_labelled(
+        params: &Self::ProverParams,
+        transcript: &mut impl Transcript<C::ScalarField>,
+        label: &'static [u8],
+        cm: &C,
+        v: &[C::ScalarField],
+        blind: &C::ScalarField,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<Self::Proof, Error> {
+        transcript.absorb_label(label);
+        Self::prove(params, transcript, cm, v, blind, rng)
+    }
+
+    /// Verifies a proof generated by `prove_labelled` with the same `label`.
+    fn verify_labelled(
+        params: &Self::VerifierParams,
+        transcript: &mut impl Transcript<C::ScalarField>,
+        label: &'static [u8],
+        cm: &C,
+        proof: &Self::Proof,
+    ) -> Result<(), Error> {
+        transcript.absorb_label(label);
+        Self::verify(params, transcript, cm, proof)
+    }
+
     /// same as `verify` but instead of providing a Transcript to use, providing the already
     /// computed challenge
     fn verify_with_challenge(
@@ -68,6 +147,90 @@ pub trait CommitmentScheme<C: CurveGroup, const H: bool = false>: Clone + Debug
     ) -> Result<(), Error>;
 }
 
+/// MultilinearCS defines the multilinear polynomial commitment scheme trait. A vector of `2^n`
+/// elements is committed as the evaluations over the boolean hypercube of a multilinear
+/// polynomial in `n` variables, where the first variable corresponds to the least significant bit
+/// of the index, and it is opened at points of `F^n`. Vectors whose length is not a power of two
+/// are padded with zeros.
+///
+/// Unlike `CommitmentScheme`, which opens a vector at a single challenge, the opening point is a
+/// vector of field elements, as needed by the sum-check based schemes.
+pub trait MultilinearCS<C: CurveGroup>: Clone + Debug {
+    type ProverParams: Clone + Debug + CanonicalSerialize + CanonicalDeserialize;
+    type VerifierParams: Clone + Debug + CanonicalSerialize + CanonicalDeserialize;
+    type Proof: Clone + Debug + CanonicalSerialize + CanonicalDeserialize;
+
+    /// Returns the parameters to commit to multilinear polynomials of up to `n_vars` variables.
+    fn setup(
+        rng: impl RngCore,
+        n_vars: usize,
+    ) -> Result<(Self::ProverParams, Self::VerifierParams), Error>;
+
+    fn commit(params: &Self::ProverParams, v: &[C::ScalarField]) -> Result<C, Error>;
+
+    /// Opens the multilinear polynomial of `v` at `point`, which has as many elements as
+    /// variables. Returns the evaluation together with the proof.
+    fn open(
+        params: &Self::ProverParams,
+        transcript: &mut impl Transcript<C::ScalarField>,
+        v: &[C::ScalarField],
+        point: &[C::ScalarField],
+    ) -> Result<(C::ScalarField, Self::Proof), Error>;
+
+    fn verify(
+        params: &Self::VerifierParams,
+        transcript: &mut impl Transcript<C::ScalarField>,
+        cm: &C,
+        point: &[C::ScalarField],
+        eval: &C::ScalarField,
+        proof: &Self::Proof,
+    ) -> Result<(), Error>;
+
+    /// Opens the multilinear polynomials of all the vectors `vs`, committed in `cms`, at the same
+    /// `point` with a single proof. Returns the evaluations of each polynomial together with the
+    /// proof.
+    fn batch_open(
+        params: &Self::ProverParams,
+        transcript: &mut impl Transcript<C::ScalarField>,
+        cms: &[C],
+        vs: &[Vec<C::ScalarField>],
+        point: &[C::ScalarField],
+    ) -> Result<(Vec<C::ScalarField>, Self::Proof), Error>;
+
+    fn batch_verify(
+        params: &Self::VerifierParams,
+        transcript: &mut impl Transcript<C::ScalarField>,
+        cms: &[C],
+        point: &[C::ScalarField],
+        evals: &[C::ScalarField],
+        proof: &Self::Proof,
+    ) -> Result<(), Error>;
+}
+
+/// Vectors with at most 1/SPARSE_COMMIT_DENSITY of non-zero entries are committed through
+/// `CommitmentScheme::commit_sparse` by `commit_maybe_sparse`.
+pub const SPARSE_COMMIT_DENSITY: usize = 8;
+
+/// Commits to `v`, going through `CS::commit_sparse` when `v` is mostly zero. Counting the
+/// non-zero entries is negligible compared to the MSM of the dense commitment.
+pub fn commit_maybe_sparse<C: CurveGroup, CS: CommitmentScheme<C, H>, const H: bool>(
+    params: &CS::ProverParams,
+    v: &[C::ScalarField],
+    blind: &C::ScalarField,
+) -> Result<C, Error> {
+    let n_non_zero = v.iter().filter(|v_i| !v_i.is_zero()).count();
+    if n_non_zero * SPARSE_COMMIT_DENSITY > v.len() {
+        return CS::commit(params, v, blind);
+    }
+    let pairs = v
+        .iter()
+        .enumerate()
+        .filter(|(_, v_i)| !v_i.is_zero())
+        .map(|(i, v_i)| (i, *v_i))
+        .collect::<Vec<_>>();
+    CS::commit_sparse(params, v.len(), &pairs, blind)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -103,7 +266,15 @@ mod tests {
             KZG::<Bn254>::setup(rng, n).unwrap();
 
         // test with Pedersen
-        test_homomorphic_property_using_Commitment_trait_opt::<G1, Pedersen<G1>>(
+        test_homomorphic_property_using_Commitment_trait_opt::<G1, Pedersen<G1>, false>(
+            &poseidon_config,
+            &pedersen_params,
+            &pedersen_params,
+            r,
+            &v_1,
+            &v_2,
+        );
+        test_homomorphic_property_using_Commitment_trait_opt::<G1, Pedersen<G1, true>, true>(
             &poseidon_config,
             &pedersen_params,
             &pedersen_params,
@@ -112,7 +283,7 @@ mod tests {
             &v_2,
         );
         // test with IPA
-        test_homomorphic_property_using_Commitment_trait_opt::<G1, IPA<G1>>(
+        test_homomorphic_property_using_Commitment_trait_opt::<G1, IPA<G1>, false>(
             &poseidon_config,
             &pedersen_params,
             &pedersen_params,
@@ -121,7 +292,15 @@ mod tests {
             &v_2,
         );
         // test with KZG
-        test_homomorphic_property_using_Commitment_trait_opt::<G1, KZG<Bn254>>(
+        test_homomorphic_property_using_Commitment_trait_opt::<G1, KZG<Bn254>, false>(
+            &poseidon_config,
+            &kzg_pk,
+            &kzg_vk,
+            r,
+            &v_1,
+            &v_2,
+        );
+        test_homomorphic_property_using_Commitment_trait_opt::<G1, KZG<Bn254, true>, true>(
             &poseidon_config,
             &kzg_pk,
             &kzg_vk,
@@ -133,7 +312,8 @@ mod tests {
 
     fn test_homomorphic_property_using_Commitment_trait_opt<
         C: CurveGroup,
-        CS: CommitmentScheme<C>,
+        CS: CommitmentScheme<C, H>,
+        const H: bool,
     >(
         poseidon_config: &PoseidonConfig<C::ScalarField>,
         prover_params: &CS::ProverParams,
@@ -144,13 +324,30 @@ mod tests {
     ) where
         <C as ark_ec::Group>::ScalarField: Absorb,
     {
+        let mut rng = test_rng();
+        let (r_1, r_2) = if H {
+            (
+                C::ScalarField::rand(&mut rng),
+                C::ScalarField::rand(&mut rng),
+            )
+        } else {
+            (C::ScalarField::zero(), C::ScalarField::zero())
+        };
+
         // compute the commitment of the two vectors using the given CommitmentScheme
-        let cm_1 = CS::commit(prover_params, v_1, &C::ScalarField::zero()).unwrap();
-        let cm_2 = CS::commit(prover_params, v_2, &C::ScalarField::zero()).unwrap();
+        let cm_1 = CS::commit(prover_params, v_1, &r_1).unwrap();
+        let cm_2 = CS::commit(prover_params, v_2, &r_2).unwrap();
+        if H {
+            // the same vector committed with another blinding factor gives another commitment
+            let cm_1_reblinded = CS::commit(prover_params, v_1, &r_2).unwrap();
+            assert_ne!(cm_1, cm_1_reblinded);
+        }
 
-        // random linear combination of the commitments and their witnesses (vectors v_i)
+        // random linear combination of the commitments and their witnesses (vectors v_i), where
+        // the blinding factors are combined in the same way
         let cm_3 = cm_1 + cm_2.mul(r);
         let v_3: Vec<C::ScalarField> = v_1.iter().zip(v_2).map(|(a, b)| *a + (r * b)).collect();
+        let r_3 = r_1 + r * r_2;
 
         // compute the proof of the cm_3
         let transcript_p = &mut PoseidonSponge::<C::ScalarField>::new(poseidon_config);
@@ -159,8 +356,8 @@ mod tests {
             transcript_p,
             &cm_3,
             &v_3,
-            &C::ScalarField::zero(),
-            None,
+            &r_3,
+            Some(&mut rng),
         )
         .unwrap();
 
@@ -168,4 +365,174 @@ mod tests {
         let transcript_v = &mut PoseidonSponge::<C::ScalarField>::new(poseidon_config);
         CS::verify(verifier_params, transcript_v, &cm_3, &proof).unwrap();
     }
+
+    #[test]
+    fn test_labelled_openings() {
+        let mut rng = &mut test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let n: usize = 16;
+
+        let (pedersen_params, _) = Pedersen::<G1>::setup(&mut rng, n).unwrap();
+        let (kzg_pk, kzg_vk): (ProverKey<G1>, VerifierKey<Bn254>) =
+            KZG::<Bn254>::setup(rng, n).unwrap();
+
+        test_labelled_openings_opt::<G1, Pedersen<G1>>(
+            &poseidon_config,
+            &pedersen_params,
+            &pedersen_params,
+            n,
+        );
+        test_labelled_openings_opt::<G1, IPA<G1>>(
+            &poseidon_config,
+            &pedersen_params,
+            &pedersen_params,
+            n,
+        );
+        test_labelled_openings_opt::<G1, KZG<Bn254>>(&poseidon_config, &kzg_pk, &kzg_vk, n);
+    }
+
+    // opens two commitments at the same transcript state under different labels, and checks that
+    // the openings verify only in their own position
+    fn test_labelled_openings_opt<C: CurveGroup, CS: CommitmentScheme<C>>(
+        poseidon_config: &PoseidonConfig<C::ScalarField>,
+        prover_params: &CS::ProverParams,
+        verifier_params: &CS::VerifierParams,
+        n: usize,
+    ) where
+        <C as ark_ec::Group>::ScalarField: Absorb,
+    {
+        let mut rng = test_rng();
+        let v_W: Vec<C::ScalarField> = std::iter::repeat_with(|| C::ScalarField::rand(&mut rng))
+            .take(n)
+            .collect();
+        let v_E: Vec<C::ScalarField> = std::iter::repeat_with(|| C::ScalarField::rand(&mut rng))
+            .take(n)
+            .collect();
+        let cm_W = CS::commit(prover_params, &v_W, &C::ScalarField::zero()).unwrap();
+        let cm_E = CS::commit(prover_params, &v_E, &C::ScalarField::zero()).unwrap();
+
+        let mut transcript = PoseidonSponge::<C::ScalarField>::new(poseidon_config);
+        transcript.absorb(&C::ScalarField::from(42_u32));
+
+        // without labels, the opening in the position of `W` can be replaced by the one of `E`
+        let proof_E = CS::prove(
+            prover_params,
+            &mut transcript.clone(),
+            &cm_E,
+            &v_E,
+            &C::ScalarField::zero(),
+            None,
+        )
+        .unwrap();
+        CS::verify(verifier_params, &mut transcript.clone(), &cm_E, &proof_E).unwrap();
+
+        // with labels, each opening only verifies in its own position
+        let proof_W = CS::prove_labelled(
+            prover_params,
+            &mut transcript.clone(),
+            b"W",
+            &cm_W,
+            &v_W,
+            &C::ScalarField::zero(),
+            None,
+        )
+        .unwrap();
+        let proof_E = CS::prove_labelled(
+            prover_params,
+            &mut transcript.clone(),
+            b"E",
+            &cm_E,
+            &v_E,
+            &C::ScalarField::zero(),
+            None,
+        )
+        .unwrap();
+        CS::verify_labelled(
+            verifier_params,
+            &mut transcript.clone(),
+            b"W",
+            &cm_W,
+            &proof_W,
+        )
+        .unwrap();
+        CS::verify_labelled(
+            verifier_params,
+            &mut transcript.clone(),
+            b"E",
+            &cm_E,
+            &proof_E,
+        )
+        .unwrap();
+        assert!(CS::verify_labelled(
+            verifier_params,
+            &mut transcript.clone(),
+            b"W",
+            &cm_E,
+            &proof_E,
+        )
+        .is_err());
+        assert!(CS::verify_labelled(
+            verifier_params,
+            &mut transcript.clone(),
+            b"E",
+            &cm_W,
+            &proof_W,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_commit_sparse() {
+        let mut rng = &mut test_rng();
+        let n: usize = 100;
+
+        // ~10% of non-zero entries, including a repeated index
+        let pairs: Vec<(usize, Fr)> = (0..n)
+            .step_by(10)
+            .chain([30])
+            .map(|i| (i, Fr::rand(rng)))
+            .collect();
+        let mut v = vec![Fr::zero(); n];
+        for (i, v_i) in &pairs {
+            v[*i] += v_i;
+        }
+
+        let (pedersen_params, _) = Pedersen::<G1>::setup(&mut rng, n).unwrap();
+        let (kzg_pk, _): (ProverKey<G1>, VerifierKey<Bn254>) =
+            KZG::<Bn254>::setup(&mut rng, n).unwrap();
+        let mut kzg_pk_lagrange = kzg_pk.clone();
+        kzg_pk_lagrange.add_lagrange_basis(n).unwrap();
+
+        test_commit_sparse_opt::<G1, Pedersen<G1>, false>(&pedersen_params, &v, &pairs, Fr::zero());
+        test_commit_sparse_opt::<G1, Pedersen<G1, true>, true>(
+            &pedersen_params,
+            &v,
+            &pairs,
+            Fr::rand(rng),
+        );
+        test_commit_sparse_opt::<G1, IPA<G1>, false>(&pedersen_params, &v, &pairs, Fr::zero());
+        test_commit_sparse_opt::<G1, KZG<Bn254>, false>(&kzg_pk, &v, &pairs, Fr::zero());
+        test_commit_sparse_opt::<G1, KZG<Bn254>, false>(&kzg_pk_lagrange, &v, &pairs, Fr::zero());
+
+        // out of bounds indexes are rejected
+        assert!(Pedersen::<G1>::commit_sparse(&pedersen_params, 10, &pairs, &Fr::zero()).is_err());
+        assert!(KZG::<Bn254>::commit_sparse(&kzg_pk, 10, &pairs, &Fr::zero()).is_err());
+    }
+
+    fn test_commit_sparse_opt<C: CurveGroup, CS: CommitmentScheme<C, H>, const H: bool>(
+        params: &CS::ProverParams,
+        v: &[C::ScalarField],
+        pairs: &[(usize, C::ScalarField)],
+        blind: C::ScalarField,
+    ) {
+        let cm = CS::commit(params, v, &blind).unwrap();
+        assert_eq!(
+            CS::commit_sparse(params, v.len(), pairs, &blind).unwrap(),
+            cm
+        );
+        assert_eq!(
+            commit_maybe_sparse::<C, CS, H>(params, v, &blind).unwrap(),
+            cm
+        );
+    }
 }
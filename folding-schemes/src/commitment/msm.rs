@@ -0,0 +1,185 @@
+/// Pluggable multi-scalar multiplication backend used by the commitment schemes.
+///
+/// The commitment parameters (`pedersen::Params`, used also by IPA, and `kzg::ProverKey`) carry an
+/// [`Msm`] handle, which by default runs the arkworks `VariableBaseMSM`. Downstream crates can
+/// plug in an accelerated implementation (e.g. a GPU library) by implementing [`MsmBackend`] and
+/// setting it into the params:
+///
+/// ```ignore
+/// let (mut pp, vp) = Pedersen::<G1>::setup(&mut rng, len)?;
+/// pp.msm = Msm::new(MyGpuMsm::new());
+/// ```
+///
+/// The backend is not part of the serialized params, and deserialized params use the arkworks
+/// backend.
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+};
+use ark_std::{fmt::Debug, sync::Arc};
+
+/// Computes the multi-scalar multiplication `Σ scalars_i ⋅ bases_i`. The slices are expected to
+/// have the same length, otherwise the extra elements of the longest one are ignored.
+pub trait MsmBackend<C: CurveGroup>: Debug + Send + Sync {
+    fn msm(&self, bases: &[C::Affine], scalars: &[C::ScalarField]) -> C;
+}
+
+/// Default backend, using the arkworks implementation.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ArkworksMsm;
+
+impl<C: CurveGroup> MsmBackend<C> for ArkworksMsm {
+    fn msm(&self, bases: &[C::Affine], scalars: &[C::ScalarField]) -> C {
+        C::msm_unchecked(bases, scalars)
+    }
+}
+
+/// Handle to the MSM backend carried inside the commitment params. It compares as equal to any
+/// other handle and serializes to nothing, so that it does not affect the params it is part of.
+#[derive(Debug, Clone)]
+pub struct Msm<C: CurveGroup>(Arc<dyn MsmBackend<C>>);
+
+impl<C: CurveGroup> Msm<C> {
+    pub fn new(backend: impl MsmBackend<C> + 'static) -> Self {
+        Self(Arc::new(backend))
+    }
+
+    pub fn msm(&self, bases: &[C::Affine], scalars: &[C::ScalarField]) -> C {
+        self.0.msm(bases, scalars)
+    }
+}
+
+impl<C: CurveGroup> Default for Msm<C> {
+    fn default() -> Self {
+        Self::new(ArkworksMsm)
+    }
+}
+
+impl<C: CurveGroup> PartialEq for Msm<C> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+impl<C: CurveGroup> Eq for Msm<C> {}
+
+impl<C: CurveGroup> CanonicalSerialize for Msm<C> {
+    fn serialize_with_mode<W: ark_std::io::Write>(
+        &self,
+        _writer: W,
+        _compress: Compress,
+    ) -> Result<(), SerializationError> {
+        Ok(())
+    }
+
+    fn serialized_size(&self, _compress: Compress) -> usize {
+        0
+    }
+}
+
+impl<C: CurveGroup> Valid for Msm<C> {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl<C: CurveGroup> CanonicalDeserialize for Msm<C> {
+    fn deserialize_with_mode<R: ark_std::io::Read>(
+        _reader: R,
+        _compress: Compress,
+        _validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Ok(Self::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::{Bn254, Fr, G1Projective as G1};
+    use ark_crypto_primitives::sponge::{poseidon::PoseidonSponge, CryptographicSponge};
+    use ark_std::sync::atomic::{AtomicUsize, Ordering};
+    use ark_std::{test_rng, UniformRand, Zero};
+
+    use super::*;
+    use crate::commitment::{ipa::IPA, kzg::KZG, pedersen::Pedersen, CommitmentScheme};
+    use crate::transcript::poseidon::poseidon_canonical_config;
+
+    /// Backend that counts its invocations and delegates to the arkworks one.
+    #[derive(Debug, Default)]
+    struct CountingMsm(Arc<AtomicUsize>);
+
+    impl<C: CurveGroup> MsmBackend<C> for CountingMsm {
+        fn msm(&self, bases: &[C::Affine], scalars: &[C::ScalarField]) -> C {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            <ArkworksMsm as MsmBackend<C>>::msm(&ArkworksMsm, bases, scalars)
+        }
+    }
+
+    #[test]
+    fn test_msm_backend() {
+        let mut rng = test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let n = 16;
+        let v: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(&mut rng))
+            .take(n)
+            .collect();
+
+        let counter = Arc::new(AtomicUsize::new(0));
+        let (pedersen_params, _) = Pedersen::<G1>::setup(&mut rng, n).unwrap();
+        let mut pedersen_params_counting = pedersen_params.clone();
+        pedersen_params_counting.msm = Msm::new(CountingMsm(counter.clone()));
+        let (kzg_pk, _) = KZG::<Bn254>::setup(&mut rng, n).unwrap();
+        let mut kzg_pk_counting = kzg_pk.clone();
+        kzg_pk_counting.msm = Msm::new(CountingMsm(counter.clone()));
+
+        // the backend is used, and gives the same commitments as the default one
+        let cm = Pedersen::<G1>::commit(&pedersen_params_counting, &v, &Fr::zero()).unwrap();
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            cm,
+            Pedersen::<G1>::commit(&pedersen_params, &v, &Fr::zero()).unwrap()
+        );
+        let cm = KZG::<Bn254>::commit(&kzg_pk_counting, &v, &Fr::zero()).unwrap();
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+        assert_eq!(cm, KZG::<Bn254>::commit(&kzg_pk, &v, &Fr::zero()).unwrap());
+
+        // as well as in the openings
+        let proof = KZG::<Bn254>::prove_with_challenge(
+            &kzg_pk_counting,
+            Fr::rand(&mut rng),
+            &v,
+            &Fr::zero(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(counter.load(Ordering::SeqCst), 3);
+        assert!(!proof.proof.is_zero());
+
+        let cm = IPA::<G1>::commit(&pedersen_params_counting, &v, &Fr::zero()).unwrap();
+        let count = counter.load(Ordering::SeqCst);
+        let mut transcript_p = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let proof = IPA::<G1>::prove(
+            &pedersen_params_counting,
+            &mut transcript_p,
+            &cm,
+            &v,
+            &Fr::zero(),
+            None,
+        )
+        .unwrap();
+        assert!(counter.load(Ordering::SeqCst) > count);
+        let mut transcript_v = PoseidonSponge::<Fr>::new(&poseidon_config);
+        IPA::<G1>::verify(&pedersen_params, &mut transcript_v, &cm, &proof).unwrap();
+
+        // the backend is not serialized, and does not affect the params equality
+        let mut bytes = vec![];
+        pedersen_params_counting
+            .serialize_compressed(&mut bytes)
+            .unwrap();
+        let mut bytes_default = vec![];
+        pedersen_params
+            .serialize_compressed(&mut bytes_default)
+            .unwrap();
+        assert_eq!(bytes, bytes_default);
+        assert_eq!(pedersen_params_counting, pedersen_params);
+    }
+}
@@ -1,13 +1,14 @@
-use ark_ec::CurveGroup;
+use ark_ec::{AffineRepr, CurveGroup};
 use ark_ff::Field;
 use ark_r1cs_std::{boolean::Boolean, prelude::CurveVar};
 use ark_relations::r1cs::SynthesisError;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use ark_std::Zero;
 use ark_std::{rand::RngCore, UniformRand};
+use ark_std::{vec::Vec, Zero};
 use core::marker::PhantomData;
+use sha3::{Digest, Sha3_256};
 
-use super::CommitmentScheme;
+use super::{msm::Msm, CommitmentScheme};
 use crate::transcript::Transcript;
 use crate::utils::vec::{vec_add, vec_scalar_mul};
 use crate::Error;
@@ -23,6 +24,8 @@ pub struct Proof<C: CurveGroup> {
 pub struct Params<C: CurveGroup> {
     pub h: C,
     pub generators: Vec<C::Affine>,
+    /// MSM backend used to commit, see `commitment::msm`
+    pub msm: Msm<C>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -49,16 +52,56 @@ impl<C: CurveGroup, const H: bool> CommitmentScheme<C, H> for Pedersen<C, H> {
         mut rng: impl RngCore,
         len: usize,
     ) -> Result<(Self::ProverParams, Self::VerifierParams), Error> {
-        let generators: Vec<C::Affine> = std::iter::repeat_with(|| C::Affine::rand(&mut rng))
+        let generators: Vec<C::Affine> = ark_std::iter::repeat_with(|| C::Affine::rand(&mut rng))
             .take(len.next_power_of_two())
             .collect();
         let p = Params::<C> {
             h: C::rand(&mut rng),
             generators,
+            msm: Msm::default(),
         };
         Ok((p.clone(), p))
     }
 
+    fn setup_deterministic(
+        domain_tag: &[u8],
+        len: usize,
+    ) -> Result<(Self::ProverParams, Self::VerifierParams), Error> {
+        let generators: Vec<C::Affine> = (0..len.next_power_of_two() as u64)
+            .map(|i| hash_to_curve::<C>(domain_tag, &[b"G".as_slice(), &i.to_le_bytes()].concat()))
+            .collect();
+        let p = Params::<C> {
+            h: hash_to_curve::<C>(domain_tag, b"H").into(),
+            generators,
+            msm: Msm::default(),
+        };
+        Ok((p.clone(), p))
+    }
+
+    fn trim(
+        pp: &Self::ProverParams,
+        vp: &Self::VerifierParams,
+        len: usize,
+    ) -> Result<(Self::ProverParams, Self::VerifierParams), Error> {
+        let n = len.next_power_of_two();
+        let trim = |p: &Params<C>| {
+            if p.generators.len() < n {
+                return Err(Error::PedersenParamsLen(p.generators.len(), n));
+            }
+            Ok(Params::<C> {
+                h: p.h,
+                generators: p.generators[..n].to_vec(),
+                msm: p.msm.clone(),
+            })
+        };
+        Ok((trim(pp)?, trim(vp)?))
+    }
+
+    /// The prover and verifier params are the same.
+    fn prover_params_from_verifier(vp: &Self::VerifierParams) -> Option<Self::ProverParams> {
+        Some(vp.clone())
+    }
+
     fn commit(
         params: &Self::ProverParams,
         v: &[C::ScalarField],
@@ -72,11 +115,39 @@ impl<C: CurveGroup, const H: bool> CommitmentScheme<C, H> for Pedersen<C, H> {
         }
 
         // h⋅r + <g, v>
-        // use msm_unchecked because we already ensured at the if that lengths match
+        // the msm does not check the lengths, we already ensured at the if that they match
         if !H {
-            return Ok(C::msm_unchecked(&params.generators[..v.len()], v));
+            return Ok(params.msm.msm(&params.generators[..v.len()], v));
         }
-        Ok(params.h.mul(r) + C::msm_unchecked(&params.generators[..v.len()], v))
+        Ok(params.h.mul(r) + params.msm.msm(&params.generators[..v.len()], v))
+    }
+
+    fn commit_sparse(
+        params: &Self::ProverParams,
+        len: usize,
+        pairs: &[(usize, C::ScalarField)],
+        r: &C::ScalarField, // blinding factor
+    ) -> Result<C, Error> {
+        if params.generators.len() < len {
+            return Err(Error::PedersenParamsLen(params.generators.len(), len));
+        }
+        if !H && (!r.is_zero()) {
+            return Err(Error::BlindingNotZero);
+        }
+        if pairs.iter().any(|(i, _)| *i >= len) {
+            return Err(Error::OutOfBounds);
+        }
+
+        // h⋅r + <g, v>, where the MSM only runs over the generators of the non-zero entries
+        let (generators, scalars): (Vec<C::Affine>, Vec<C::ScalarField>) = pairs
+            .iter()
+            .map(|(i, v_i)| (params.generators[*i], *v_i))
+            .unzip();
+        let cm = params.msm.msm(&generators, &scalars);
+        if !H {
+            return Ok(cm);
+        }
+        Ok(params.h.mul(r) + cm)
     }
 
     fn prove(
@@ -92,8 +163,8 @@ impl<C: CurveGroup, const H: bool> CommitmentScheme<C, H> for Pedersen<C, H> {
         let d = transcript.get_challenges(v.len());
 
         // R = h⋅r_1 + <g, d>
-        // use msm_unchecked because we already ensured at the if that lengths match
-        let mut R: C = C::msm_unchecked(&params.generators[..d.len()], &d);
+        // the msm does not check the lengths, we already ensured at the if that they match
+        let mut R: C = params.msm.msm(&params.generators[..d.len()], &d);
         if H {
             R += params.h.mul(r1);
         }
@@ -165,8 +236,10 @@ impl<C: CurveGroup, const H: bool> CommitmentScheme<C, H> for Pedersen<C, H> {
 
         // check that: R + cm⋅e == h⋅r_u + <g, u>
         let lhs = proof.R + cm.mul(e);
-        // use msm_unchecked because we already ensured at the if that lengths match
-        let mut rhs = C::msm_unchecked(&params.generators[..proof.u.len()], &proof.u);
+        // the msm does not check the lengths, we already ensured at the if that they match
+        let mut rhs = params
+            .msm
+            .msm(&params.generators[..proof.u.len()], &proof.u);
         if H {
             rhs += params.h.mul(proof.r_u);
         }
@@ -189,12 +262,18 @@ where
     _gc: PhantomData<GC>,
 }
 
-use ark_r1cs_std::ToBitsGadget;
+use ark_r1cs_std::{select::CondSelectGadget, R1CSVar, ToBitsGadget};
 impl<C, GC, const H: bool> PedersenGadget<C, GC, H>
 where
     C: CurveGroup,
     GC: CurveVar<C, CF<C>>,
 {
+    /// Size in bits of the windows used to multiply the constant generators.
+    pub const WINDOW_SIZE: usize = 4;
+
+    /// Computes the commitment h⋅r + <g, v> in-circuit. The generators (and h) that are constants
+    /// are multiplied through windowed fixed-base scalar multiplication, see
+    /// `fixed_base_scalar_mul_le`, while the rest use the generic double-and-add.
     pub fn commit(
         h: &GC,
         g: &[GC],
@@ -203,13 +282,96 @@ where
     ) -> Result<GC, SynthesisError> {
         let mut res = GC::zero();
         if H {
-            res += h.scalar_mul_le(r.iter())?;
+            res += Self::scalar_mul_le(h, r)?;
         }
         for (i, v_i) in v.iter().enumerate() {
-            res += g[i].scalar_mul_le(v_i.to_bits_le()?.iter())?;
+            res += Self::scalar_mul_le(&g[i], &v_i.to_bits_le()?)?;
         }
         Ok(res)
     }
+
+    fn scalar_mul_le(base: &GC, bits: &[Boolean<CF<C>>]) -> Result<GC, SynthesisError> {
+        if base.is_constant() {
+            return Self::fixed_base_scalar_mul_le(base.value()?, bits);
+        }
+        base.scalar_mul_le(bits.iter())
+    }
+
+    /// Multiplies the constant `base` by the scalar with little-endian `bits`, processing them in
+    /// windows of `WINDOW_SIZE` bits. For the j-th window, the multiples k⋅2^{WINDOW_SIZE⋅j}⋅base
+    /// for all the values k of the window are baked into the circuit as constants, so that each
+    /// window costs a lookup among constants and a single point addition, instead of a
+    /// conditional addition per bit.
+    pub fn fixed_base_scalar_mul_le(
+        base: C,
+        bits: &[Boolean<CF<C>>],
+    ) -> Result<GC, SynthesisError> {
+        let mut res = GC::zero();
+        let mut window_base = base; // 2^{WINDOW_SIZE⋅j}⋅base
+        for window in bits.chunks(Self::WINDOW_SIZE) {
+            let mut table = Vec::with_capacity(1 << window.len());
+            let mut multiple = C::zero();
+            for _ in 0..1 << window.len() {
+                table.push(GC::constant(multiple));
+                multiple += window_base;
+            }
+            res += Self::lookup_le(window, table)?;
+            for _ in 0..Self::WINDOW_SIZE {
+                window_base.double_in_place();
+            }
+        }
+        Ok(res)
+    }
+
+    /// Selects `table[k]`, where k is the value of the little-endian `bits`, through a tree of
+    /// conditional selections. The first level selects among constants, which is free.
+    fn lookup_le(bits: &[Boolean<CF<C>>], table: Vec<GC>) -> Result<GC, SynthesisError> {
+        let mut table = table;
+        for bit in bits {
+            table = table
+                .chunks(2)
+                .map(|pair| GC::conditionally_select(bit, &pair[1], &pair[0]))
+                .collect::<Result<Vec<_>, _>>()?;
+        }
+        Ok(table.remove(0))
+    }
+}
+
+/// Maps `(domain_tag, label)` to a curve point by try-and-increment: the x coordinate is read
+/// from `Sha3_256(len(domain_tag) || domain_tag || label || counter || block)`, with the flag
+/// bits cleared, and the counter is increased until it lands on the curve and is not the point at
+/// infinity after clearing the cofactor. Nobody knows the discrete log of the resulting point.
+fn hash_to_curve<C: CurveGroup>(domain_tag: &[u8], label: &[u8]) -> C::Affine {
+    let n_bytes = C::Affine::generator().compressed_size();
+    let mut counter: u64 = 0;
+    loop {
+        let mut bytes: Vec<u8> = Vec::with_capacity(n_bytes + 32);
+        let mut block: u8 = 0;
+        while bytes.len() < n_bytes {
+            let mut hasher = Sha3_256::new();
+            hasher.update((domain_tag.len() as u64).to_le_bytes());
+            hasher.update(domain_tag);
+            hasher.update(label);
+            hasher.update(counter.to_le_bytes());
+            hasher.update([block]);
+            bytes.extend_from_slice(&hasher.finalize());
+            block += 1;
+        }
+        bytes.truncate(n_bytes);
+        // `from_random_bytes` reads the flags of short Weierstrass points from the 2 top bits of
+        // the last byte: bit 7 selects the negative `y`, and bit 6 marks the point at infinity,
+        // which makes the decoding fail unless `x` is zero. Clearing both bits makes every
+        // candidate `x` decode to the point with the positive `y`, if it is on the curve, so the
+        // sign of `y` is fixed rather than taken from the hash
+        bytes[n_bytes - 1] &= 0x3f;
+        if let Some(p) = C::Affine::from_random_bytes(&bytes) {
+            let p = p.clear_cofactor();
+            if !p.is_zero() {
+                return p;
+            }
+        }
+        counter += 1;
+    }
 }
 
 #[cfg(test)]
@@ -219,6 +381,7 @@ mod tests {
     use ark_pallas::{constraints::GVar, Fq, Fr, Projective};
     use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget};
     use ark_relations::r1cs::ConstraintSystem;
+    use std::str::FromStr;
 
     use super::*;
     use crate::transcript::poseidon::poseidon_canonical_config;
@@ -294,7 +457,7 @@ mod tests {
             })
             .collect();
         let rVar = Vec::<Boolean<Fq>>::new_witness(cs.clone(), || Ok(r_bits)).unwrap();
-        let gVar = Vec::<GVar>::new_witness(cs.clone(), || Ok(params.generators)).unwrap();
+        let gVar = Vec::<GVar>::new_witness(cs.clone(), || Ok(params.generators.clone())).unwrap();
         let hVar = GVar::new_witness(cs.clone(), || Ok(params.h)).unwrap();
         let expected_cmVar = GVar::new_witness(cs.clone(), || Ok(cm)).unwrap();
 
@@ -302,5 +465,143 @@ mod tests {
         let cmVar =
             PedersenGadget::<Projective, GVar, hiding>::commit(&hVar, &gVar, &vVar, &rVar).unwrap();
         cmVar.enforce_equal(&expected_cmVar).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // with constant generators, the windowed fixed-base multiplication is used
+        let gVar = Vec::<GVar>::new_constant(cs.clone(), params.generators.clone()).unwrap();
+        let hVar = GVar::new_constant(cs.clone(), params.h).unwrap();
+        let n_constraints = cs.num_constraints();
+        let cmVar =
+            PedersenGadget::<Projective, GVar, hiding>::commit(&hVar, &gVar, &vVar, &rVar).unwrap();
+        let n_constraints_windowed = cs.num_constraints() - n_constraints;
+        cmVar.enforce_equal(&expected_cmVar).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // which takes noticeably less constraints than the double-and-add over the constants
+        let n_constraints = cs.num_constraints();
+        let mut cmVar = GVar::zero();
+        if hiding {
+            cmVar += hVar.scalar_mul_le(rVar.iter()).unwrap();
+        }
+        for (g_i, v_i) in gVar.iter().zip(&vVar) {
+            cmVar += g_i.scalar_mul_le(v_i.iter()).unwrap();
+        }
+        let n_constraints_naive = cs.num_constraints() - n_constraints;
+        cmVar.enforce_equal(&expected_cmVar).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert!(
+            n_constraints_windowed < n_constraints_naive,
+            "{} constraints vs {} of the double-and-add",
+            n_constraints_windowed,
+            n_constraints_naive
+        );
+    }
+
+    #[test]
+    fn test_fixed_base_scalar_mul() {
+        let mut rng = ark_std::test_rng();
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let base = Projective::rand(&mut rng);
+        // scalars whose bit length is not a multiple of the window size, including zero
+        for s in [Fr::zero(), Fr::from(13_u32), Fr::rand(&mut rng)] {
+            let bits =
+                Vec::<Boolean<Fq>>::new_witness(cs.clone(), || Ok(s.into_bigint().to_bits_le()))
+                    .unwrap();
+            let res =
+                PedersenGadget::<Projective, GVar>::fixed_base_scalar_mul_le(base, &bits).unwrap();
+            assert_eq!(res.value().unwrap(), base * s);
+        }
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_pedersen_setup_deterministic() {
+        let tag = b"sonobe-test";
+        let (params, _) = Pedersen::<Projective>::setup_deterministic(tag, 10).unwrap();
+        assert_eq!(params.generators.len(), 16);
+        let (params2, _) = Pedersen::<Projective>::setup_deterministic(tag, 10).unwrap();
+        assert_eq!(params, params2);
+        let (params3, _) =
+            Pedersen::<Projective>::setup_deterministic(b"sonobe-other", 10).unwrap();
+        assert_ne!(params.generators[0], params3.generators[0]);
+        assert_ne!(params.h, params3.h);
+
+        // golden vectors
+        let (params, _) = Pedersen::<ark_bn254::G1Projective>::setup_deterministic(tag, 3).unwrap();
+        let expected: Vec<ark_bn254::G1Affine> = [
+            (
+                "15241846337121089585732930075859363219734067127992153443642450688574939852806",
+                "21150600125196662370983324076349516722272252481719384306491788829006797471583",
+            ),
+            (
+                "17166974418511575549626780934624486460283374893586484647112227322379168135514",
+                "15010525762063398155727359011518310174075894343382634220498651405651201227048",
+            ),
+            (
+                "19778324965272871230932110250435782627477504046067060847801210298597963469556",
+                "11477882236007104738599215285637558819416410948436305283714480382389328510101",
+            ),
+        ]
+        .iter()
+        .map(|(x, y)| {
+            ark_bn254::G1Affine::new(
+                ark_bn254::Fq::from_str(x).unwrap(),
+                ark_bn254::Fq::from_str(y).unwrap(),
+            )
+        })
+        .collect();
+        assert_eq!(params.generators[..3].to_vec(), expected);
+        assert_eq!(
+            params.h.into_affine(),
+            ark_bn254::G1Affine::new(
+                ark_bn254::Fq::from_str(
+                    "4308145496076822462852427313029256601179525662331105161852316775896198583963"
+                )
+                .unwrap(),
+                ark_bn254::Fq::from_str(
+                    "19875823856479845645496214451375468699408744650157751298413479028206737812158"
+                )
+                .unwrap(),
+            )
+        );
+
+        let (params, _) =
+            Pedersen::<ark_grumpkin::Projective>::setup_deterministic(tag, 3).unwrap();
+        let expected: Vec<ark_grumpkin::Affine> = [
+            (
+                "5387847002389265458200052447101889635681125572174382783897612770845372386503",
+                "17065958263927516757055931055084128912345010174860726610424001916791108161674",
+            ),
+            (
+                "17166974418511575549626780934624486460283374893586484647112227322379168135514",
+                "18883648565777476290733500070398812386522651157372788811098137193967629635970",
+            ),
+            (
+                "19778324965272871230932110250435782627477504046067060847801210298597963469556",
+                "11951860390418674582054530666643338286797932337562399301110871843976365654287",
+            ),
+        ]
+        .iter()
+        .map(|(x, y)| {
+            ark_grumpkin::Affine::new(
+                ark_grumpkin::Fq::from_str(x).unwrap(),
+                ark_grumpkin::Fq::from_str(y).unwrap(),
+            )
+        })
+        .collect();
+        assert_eq!(params.generators[..3].to_vec(), expected);
+        assert_eq!(
+            params.h.into_affine(),
+            ark_grumpkin::Affine::new(
+                ark_grumpkin::Fq::from_str(
+                    "8553238907588843179338707711680765597361762988748110649987529802659761347993"
+                )
+                .unwrap(),
+                ark_grumpkin::Fq::from_str(
+                    "14650839124139213564478742846206650281154153189796917082114587038077155241992"
+                )
+                .unwrap(),
+            )
+        );
     }
 }
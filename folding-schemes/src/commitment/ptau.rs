@@ -0,0 +1,534 @@
+/// Loader of the KZG SRS from the `.ptau` files generated by the snarkjs and
+/// perpetual-powers-of-tau ceremonies, so that the KZG parameters do not depend on a locally
+/// generated (and thus known) toxic waste.
+///
+/// The `.ptau` binary format is:
+/// - magic `"ptau"`, version (u32), number of sections (u32)
+/// - for each section: section type (u32), section size (u64), section data
+///
+/// where the sections used here are:
+/// 1. header: `n8` (u32), field modulus `q` (`n8` bytes), `power` (u32), `ceremonyPower` (u32)
+/// 2. tauG1: `2^(power+1) - 1` G1 points `[τ^i]_1`
+/// 3. tauG2: `2^power` G2 points `[τ^i]_2`
+///
+/// All the integers are little-endian, and the point coordinates are encoded as little-endian
+/// field elements in Montgomery form.
+use ark_bn254::{Bn254, Fq, Fq2, G1Affine, G1Projective, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{BigInt, BigInteger, PrimeField, Zero};
+use ark_poly_commit::kzg10::VerifierKey;
+use ark_serialize::CanonicalDeserialize;
+use ark_std::{borrow::Cow, collections::BTreeMap, rand::RngCore, UniformRand};
+use std::io::{BufReader, Read, Seek, SeekFrom};
+
+use super::{
+    kzg::{ProverKey, KZG},
+    msm::Msm,
+    zeromorph::{VerifierKey as ZeromorphVerifierKey, Zeromorph},
+};
+use crate::Error;
+
+const PTAU_MAGIC: &[u8; 4] = b"ptau";
+const SECTION_HEADER: u32 = 1;
+const SECTION_TAU_G1: u32 = 2;
+const SECTION_TAU_G2: u32 = 3;
+/// Number of bytes of a bn254 base field element
+const N8: usize = 32;
+
+/// Header of a `.ptau` file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PtauHeader {
+    /// the file contains `2^(power+1) - 1` G1 powers and `2^power` G2 powers
+    pub power: u32,
+    pub ceremony_power: u32,
+}
+
+impl<'a> KZG<'a, Bn254> {
+    /// Returns the KZG (ProverKey, VerifierKey) for vectors of up to `len` elements, loaded from
+    /// a `.ptau` file instead of being generated from an RNG as in `setup`.
+    ///
+    /// The powers are read one by one from the `reader`, so that only the `len + 1` needed G1
+    /// powers are kept in memory. Before being accepted, the loaded powers are checked to be
+    /// consistent, ie. that `[τ^{i+1}]_1` is `τ` times `[τ^i]_1`, through a randomized pairing
+    /// check where the randomness is taken from `rng`.
+    ///
+    /// The returned parameters are used by a folding scheme, and by its deciders, by passing them
+    /// to `PreprocessorParam::with_cs_params`. They are not suitable for Zeromorph, whose
+    /// parameters are loaded by `Zeromorph::setup_from_ptau`.
+    pub fn setup_from_ptau<R: Read + Seek>(
+        rng: impl RngCore,
+        reader: R,
+        len: usize,
+    ) -> Result<(ProverKey<'a, G1Projective>, VerifierKey<Bn254>), Error> {
+        read_srs(rng, reader, Some(len.next_power_of_two() as u64 + 1))
+    }
+}
+
+impl<'a> Zeromorph<'a, Bn254> {
+    /// Returns the Zeromorph (ProverKey, VerifierKey) for vectors of up to `len` elements, loaded
+    /// from a `.ptau` file as in `KZG::setup_from_ptau`. Unlike there, all the G1 powers of the
+    /// file are kept, since the degree check of Zeromorph needs the highest ones (see
+    /// `zeromorph::VerifierKey`), so the smallest file that fits `len` should be used.
+    pub fn setup_from_ptau<R: Read + Seek>(
+        rng: impl RngCore,
+        reader: R,
+        len: usize,
+    ) -> Result<(ProverKey<'a, G1Projective>, ZeromorphVerifierKey<Bn254>), Error> {
+        let (pk, vk) = read_srs(rng, reader, None)?;
+        if pk.powers_of_g.len() < len.next_power_of_two() {
+            return Err(Error::PtauError(format!(
+                "the ptau file contains {} G1 powers, but {} are needed",
+                pk.powers_of_g.len(),
+                len.next_power_of_two()
+            )));
+        }
+        let vk = ZeromorphVerifierKey::new(&pk, vk)?;
+        Ok((pk, vk))
+    }
+}
+
+/// Reads the SRS of a `.ptau` file, keeping its `num_powers` first G1 powers, or all of them if
+/// `None`, and checking them as described in `KZG::setup_from_ptau`.
+fn read_srs<'a, R: Read + Seek>(
+    mut rng: impl RngCore,
+    reader: R,
+    num_powers: Option<u64>,
+) -> Result<(ProverKey<'a, G1Projective>, VerifierKey<Bn254>), Error> {
+    let mut reader = BufReader::new(reader);
+
+    let sections = read_sections(&mut reader)?;
+    let section = |t: u32| {
+        sections
+            .iter()
+            .find(|(section_type, _, _)| *section_type == t)
+            .map(|&(_, pos, size)| (pos, size))
+            .ok_or(Error::PtauError(format!("missing section {}", t)))
+    };
+
+    // header
+    let (pos, _) = section(SECTION_HEADER)?;
+    reader.seek(SeekFrom::Start(pos))?;
+    let header = read_header(&mut reader)?;
+
+    let n_g1 = (1_u64 << (header.power + 1)) - 1;
+    let num_powers = num_powers.unwrap_or(n_g1);
+    if num_powers > n_g1 {
+        return Err(Error::PtauError(format!(
+            "the ptau file contains {} G1 powers, but {} are needed",
+            n_g1, num_powers
+        )));
+    }
+
+    // tauG1
+    let (pos, size) = section(SECTION_TAU_G1)?;
+    if size != n_g1 * 2 * N8 as u64 {
+        return Err(Error::PtauError(format!(
+            "unexpected tauG1 section size {}",
+            size
+        )));
+    }
+    reader.seek(SeekFrom::Start(pos))?;
+    let powers_of_g = (0..num_powers)
+        .map(|_| read_g1(&mut reader))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // tauG2, where only [1]_2 and [τ]_2 are needed
+    let (pos, size) = section(SECTION_TAU_G2)?;
+    if size != (1_u64 << header.power) * 4 * N8 as u64 {
+        return Err(Error::PtauError(format!(
+            "unexpected tauG2 section size {}",
+            size
+        )));
+    }
+    reader.seek(SeekFrom::Start(pos))?;
+    let h = read_g2(&mut reader)?;
+    let beta_h = read_g2(&mut reader)?;
+
+    check_powers(&mut rng, &powers_of_g, h, beta_h)?;
+
+    let vk = VerifierKey {
+        g: powers_of_g[0],
+        // hiding is not supported, so gamma_g is not used
+        gamma_g: G1Affine::zero(),
+        h,
+        beta_h,
+        prepared_h: h.into(),
+        prepared_beta_h: beta_h.into(),
+    };
+    let pk = ProverKey {
+        powers_of_g: Cow::Owned(powers_of_g),
+        lagrange_bases: BTreeMap::new(),
+        msm: Msm::default(),
+        // hiding is not supported, see `vk.gamma_g`
+        gamma_g: G1Affine::zero(),
+        beta_gamma_g: G1Affine::zero(),
+    };
+    Ok((pk, vk))
+}
+
+/// Reads the file header and returns the list of `(section type, position, size)`, checking that
+/// the sections fit in the file. Since the number of sections and their sizes are read from the
+/// file, nothing is allocated from them.
+fn read_sections<R: Read + Seek>(reader: &mut R) -> Result<Vec<(u32, u64, u64)>, Error> {
+    let start = reader.stream_position()?;
+    let end = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(start))?;
+
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != PTAU_MAGIC {
+        return Err(Error::PtauError("invalid magic bytes".to_string()));
+    }
+    let _version = read_u32(reader)?;
+    let n_sections = read_u32(reader)?;
+
+    let mut sections = Vec::new();
+    for _ in 0..n_sections {
+        let section_type = read_u32(reader)?;
+        let size = read_u64(reader)?;
+        let pos = reader.stream_position()?;
+        if size > end.saturating_sub(pos) {
+            return Err(Error::PtauError(format!(
+                "section {} of size {} exceeds the file",
+                section_type, size
+            )));
+        }
+        sections.push((section_type, pos, size));
+        reader.seek(SeekFrom::Start(pos + size))?;
+    }
+    Ok(sections)
+}
+
+/// Reads the header section, checking that the declared curve is bn254.
+fn read_header<R: Read>(reader: &mut R) -> Result<PtauHeader, Error> {
+    let n8 = read_u32(reader)? as usize;
+    if n8 != N8 {
+        return Err(Error::PtauError(format!(
+            "unexpected field element size {}, expected {} (bn254)",
+            n8, N8
+        )));
+    }
+    let mut q = [0u8; N8];
+    reader.read_exact(&mut q)?;
+    if q[..] != Fq::MODULUS.to_bytes_le()[..] {
+        return Err(Error::PtauError(
+            "the declared curve is not bn254".to_string(),
+        ));
+    }
+    let power = read_u32(reader)?;
+    let ceremony_power = read_u32(reader)?;
+    // tauG1 has 2^(power+1)-1 elements, which has to fit in the u64 section size
+    if power > 32 {
+        return Err(Error::PtauError(format!("power {} is too large", power)));
+    }
+    Ok(PtauHeader {
+        power,
+        ceremony_power,
+    })
+}
+
+/// Checks that `e([τ]_1, [1]_2) == e([1]_1, [τ]_2)`, and that `powers_of_g` is a sequence of
+/// consecutive powers of `τ`, by checking the random linear combination
+/// `e(Σ r_i [τ^i]_1, [τ]_2) == e(Σ r_i [τ^{i+1}]_1, [1]_2)`.
+fn check_powers(
+    rng: &mut impl RngCore,
+    powers_of_g: &[G1Affine],
+    h: G2Affine,
+    beta_h: G2Affine,
+) -> Result<(), Error> {
+    if powers_of_g.len() < 2 || powers_of_g[0].is_zero() || h.is_zero() {
+        return Err(Error::PtauError("degenerate powers".to_string()));
+    }
+    if Bn254::pairing(powers_of_g[1], h) != Bn254::pairing(powers_of_g[0], beta_h) {
+        return Err(Error::PtauError(
+            "[τ]_1 and [τ]_2 are not consistent".to_string(),
+        ));
+    }
+
+    let r = (0..powers_of_g.len() - 1)
+        .map(|_| ark_bn254::Fr::rand(rng))
+        .collect::<Vec<_>>();
+    let lhs = G1Projective::msm_unchecked(&powers_of_g[..powers_of_g.len() - 1], &r);
+    let rhs = G1Projective::msm_unchecked(&powers_of_g[1..], &r);
+    if Bn254::pairing(lhs, beta_h) != Bn254::pairing(rhs, h) {
+        return Err(Error::PtauError(
+            "the G1 powers are not consecutive powers of τ".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, Error> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64, Error> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// Reads a base field element in Montgomery form
+fn read_fq<R: Read>(reader: &mut R) -> Result<Fq, Error> {
+    let repr = BigInt::<4>::deserialize_uncompressed_unchecked(&mut *reader)?;
+    if repr >= Fq::MODULUS {
+        return Err(Error::PtauError(
+            "field element is not canonical".to_string(),
+        ));
+    }
+    Ok(Fq::new_unchecked(repr))
+}
+
+fn read_g1<R: Read>(reader: &mut R) -> Result<G1Affine, Error> {
+    let x = read_fq(reader)?;
+    let y = read_fq(reader)?;
+    // the point at infinity is encoded as (0, 0)
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1Affine::zero());
+    }
+    let p = G1Affine::new_unchecked(x, y);
+    if !p.is_on_curve() || !p.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Error::PtauError("invalid G1 point".to_string()));
+    }
+    Ok(p)
+}
+
+fn read_g2<R: Read>(reader: &mut R) -> Result<G2Affine, Error> {
+    let x = Fq2::new(read_fq(reader)?, read_fq(reader)?);
+    let y = Fq2::new(read_fq(reader)?, read_fq(reader)?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G2Affine::zero());
+    }
+    let p = G2Affine::new_unchecked(x, y);
+    if !p.is_on_curve() || !p.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Error::PtauError("invalid G2 point".to_string()));
+    }
+    Ok(p)
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::{constraints::GVar, Fr, G2Projective};
+    use ark_crypto_primitives::sponge::{poseidon::PoseidonSponge, CryptographicSponge};
+    use ark_ec::scalar_mul::fixed_base::FixedBase;
+    use ark_ff::One;
+    use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+    use ark_serialize::CanonicalSerialize;
+    use ark_std::{log2, test_rng};
+    use std::io::{Cursor, Write};
+
+    use super::*;
+    use crate::commitment::{pedersen::Pedersen, zeromorph::Zeromorph, CommitmentScheme};
+    use crate::folding::nova::{Nova, PreprocessorParam};
+    use crate::frontend::{utils::CubicFCircuit, FCircuit};
+    use crate::transcript::poseidon::poseidon_canonical_config;
+    use crate::FoldingScheme;
+
+    fn write_fq(buf: &mut Vec<u8>, v: &Fq) {
+        // `v.0` is the Montgomery representation
+        v.0.serialize_uncompressed(&mut *buf).unwrap();
+    }
+
+    /// Writes a `.ptau` file with the given `tau`, following the format described at the top of
+    /// this file.
+    fn write_ptau(tau: Fr, power: u32, modulus: &[u8]) -> Vec<u8> {
+        let g = G1Projective::rand(&mut test_rng());
+        let h = G2Projective::rand(&mut test_rng());
+        let n_g1 = (1 << (power + 1)) - 1;
+        let n_g2 = 1 << power;
+
+        let powers_of_tau = ark_std::iter::successors(Some(Fr::one()), |t| Some(*t * tau))
+            .take(n_g1)
+            .collect::<Vec<_>>();
+        let scalar_bits = Fr::MODULUS_BIT_SIZE as usize;
+        let window_size = FixedBase::get_mul_window_size(n_g1);
+        let g_table = FixedBase::get_window_table(scalar_bits, window_size, g);
+        let powers_of_g = G1Projective::normalize_batch(&FixedBase::msm::<G1Projective>(
+            scalar_bits,
+            window_size,
+            &g_table,
+            &powers_of_tau,
+        ));
+        let h_table = FixedBase::get_window_table(scalar_bits, window_size, h);
+        let powers_of_h = G2Projective::normalize_batch(&FixedBase::msm::<G2Projective>(
+            scalar_bits,
+            window_size,
+            &h_table,
+            &powers_of_tau[..n_g2],
+        ));
+
+        let mut header = vec![];
+        header.extend((N8 as u32).to_le_bytes());
+        header.extend(modulus);
+        header.extend(power.to_le_bytes());
+        header.extend(power.to_le_bytes());
+
+        let mut tau_g1 = vec![];
+        for p in powers_of_g {
+            write_fq(&mut tau_g1, &p.x);
+            write_fq(&mut tau_g1, &p.y);
+        }
+        let mut tau_g2 = vec![];
+        for p in powers_of_h {
+            write_fq(&mut tau_g2, &p.x.c0);
+            write_fq(&mut tau_g2, &p.x.c1);
+            write_fq(&mut tau_g2, &p.y.c0);
+            write_fq(&mut tau_g2, &p.y.c1);
+        }
+
+        let mut ptau = vec![];
+        ptau.extend(PTAU_MAGIC);
+        ptau.extend(1_u32.to_le_bytes());
+        ptau.extend(3_u32.to_le_bytes());
+        for (t, data) in [
+            (SECTION_HEADER, header),
+            (SECTION_TAU_G1, tau_g1),
+            (SECTION_TAU_G2, tau_g2),
+        ] {
+            ptau.extend(t.to_le_bytes());
+            ptau.extend((data.len() as u64).to_le_bytes());
+            ptau.extend(data);
+        }
+        ptau
+    }
+
+    #[test]
+    fn test_kzg_setup_from_ptau() {
+        let mut rng = test_rng();
+        let n = 10;
+        let ptau = write_ptau(Fr::rand(&mut rng), 4, &Fq::MODULUS.to_bytes_le());
+
+        let (pk, vk) =
+            KZG::<Bn254>::setup_from_ptau(&mut rng, Cursor::new(ptau.clone()), n).unwrap();
+        assert_eq!(pk.powers_of_g.len(), n.next_power_of_two() + 1);
+
+        // commit, prove and verify with the loaded SRS
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let transcript_p = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        let transcript_v = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        let v: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(&mut rng))
+            .take(n)
+            .collect();
+        let cm = KZG::<Bn254>::commit(&pk, &v, &Fr::zero()).unwrap();
+        let proof = KZG::<Bn254>::prove(&pk, transcript_p, &cm, &v, &Fr::zero(), None).unwrap();
+        KZG::<Bn254>::verify(&vk, transcript_v, &cm, &proof).unwrap();
+
+        // the same SRS is used by the multilinear Zeromorph commitments, which keep all its powers
+        let (pk, vk) = Zeromorph::<Bn254>::setup_from_ptau(&mut rng, Cursor::new(ptau), n).unwrap();
+        assert_eq!(pk.powers_of_g.len(), 31);
+        assert_eq!(vk.max_degree, 30);
+        let transcript_p = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        let transcript_v = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        let cm = <Zeromorph<Bn254> as CommitmentScheme<G1Projective>>::commit(&pk, &v, &Fr::zero())
+            .unwrap();
+        let proof =
+            Zeromorph::<Bn254>::prove(&pk, transcript_p, &cm, &v, &Fr::zero(), None).unwrap();
+        <Zeromorph<Bn254> as CommitmentScheme<G1Projective>>::verify(
+            &vk,
+            transcript_v,
+            &cm,
+            &proof,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_kzg_setup_from_invalid_ptau() {
+        let mut rng = test_rng();
+        let ptau = write_ptau(Fr::rand(&mut rng), 4, &Fq::MODULUS.to_bytes_le());
+
+        // not enough powers
+        assert!(KZG::<Bn254>::setup_from_ptau(&mut rng, Cursor::new(ptau.clone()), 64).is_err());
+
+        // invalid magic bytes
+        let mut invalid = ptau.clone();
+        invalid[0] = b'x';
+        assert!(KZG::<Bn254>::setup_from_ptau(&mut rng, Cursor::new(invalid), 8).is_err());
+
+        // truncated file
+        let truncated = ptau[..ptau.len() / 2].to_vec();
+        assert!(KZG::<Bn254>::setup_from_ptau(&mut rng, Cursor::new(truncated), 8).is_err());
+
+        // section sizes beyond the end of the file, including sizes that do not fit in an i64
+        for size in [ptau.len() as u64, u64::MAX] {
+            let mut invalid = ptau.clone();
+            // the size of the header section follows the 12 bytes of the file header and its
+            // 4 bytes section type
+            invalid[16..24].copy_from_slice(&size.to_le_bytes());
+            assert!(matches!(
+                KZG::<Bn254>::setup_from_ptau(&mut rng, Cursor::new(invalid), 8),
+                Err(Error::PtauError(_))
+            ));
+        }
+
+        // a number of sections that the file can not contain does not preallocate them
+        let mut invalid = ptau.clone();
+        invalid[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(KZG::<Bn254>::setup_from_ptau(&mut rng, Cursor::new(invalid), 8).is_err());
+
+        // curve other than bn254
+        let ptau_other_curve = write_ptau(Fr::rand(&mut rng), 4, &Fr::MODULUS.to_bytes_le());
+        assert!(KZG::<Bn254>::setup_from_ptau(&mut rng, Cursor::new(ptau_other_curve), 8).is_err());
+
+        // powers which are not consecutive powers of tau: swap two of the G1 powers
+        let mut inconsistent = ptau.clone();
+        // header: 4 magic + 4 version + 4 n_sections + 12 section header + 40 section data,
+        // tauG1 data starts after its 12 bytes section header
+        let tau_g1_start = 12 + 12 + 40 + 12;
+        let point_size = 2 * N8;
+        let (a, b) = (tau_g1_start + 2 * point_size, tau_g1_start + 3 * point_size);
+        let p2 = inconsistent[a..a + point_size].to_vec();
+        let p3 = inconsistent[b..b + point_size].to_vec();
+        inconsistent[a..a + point_size].copy_from_slice(&p3);
+        inconsistent[b..b + point_size].copy_from_slice(&p2);
+        assert!(KZG::<Bn254>::setup_from_ptau(&mut rng, Cursor::new(inconsistent), 8).is_err());
+    }
+
+    #[test]
+    fn test_nova_preprocess_from_ptau() {
+        type N = Nova<
+            G1Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            KZG<'static, Bn254>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        let mut rng = test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+
+        // a first preprocess gives the size of the circuits, so that the ptau file can be
+        // generated with enough powers
+        let prep_param = PreprocessorParam::new(poseidon_config.clone(), F_circuit);
+        let (pp, vp) = N::preprocess(&mut rng, &prep_param).unwrap();
+        let n = vp.r1cs.A.n_rows;
+
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(&write_ptau(
+            Fr::rand(&mut rng),
+            log2(n),
+            &Fq::MODULUS.to_bytes_le(),
+        ))
+        .unwrap();
+        file.rewind().unwrap();
+        let (kzg_pk, kzg_vk) = KZG::<Bn254>::setup_from_ptau(&mut rng, file, n).unwrap();
+
+        // preprocess Nova with the SRS of the ptau file instead of a locally generated one
+        let prep_param = PreprocessorParam::new(poseidon_config, F_circuit)
+            .with_cs_params((kzg_pk.clone(), kzg_vk.clone()), (pp.cf_cs_pp, vp.cf_cs_vp));
+        let params = N::preprocess(&mut rng, &prep_param).unwrap();
+        assert_eq!(params.0.cs_pp.powers_of_g, kzg_pk.powers_of_g);
+        assert_eq!(params.1.cs_vp.beta_h, kzg_vk.beta_h);
+
+        let mut nova = N::init(&params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+        for _ in 0..2 {
+            nova.prove_step(&mut rng, (), None).unwrap();
+        }
+        N::verify(params.1, nova.ivc_proof()).unwrap();
+    }
+}
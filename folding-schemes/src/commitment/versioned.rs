@@ -0,0 +1,404 @@
+/// Versioned serialization of the commitment schemes parameters.
+///
+/// The raw `CanonicalSerialize` output of the commitment params (eg. `PedersenParams` or the KZG
+/// keys) does not contain any information about the curve or the length of the params, so
+/// loading params generated for a different setting does not fail until much later. This module
+/// wraps the serialized params of one or more commitment schemes in the following header:
+///
+/// - magic bytes `"SNBP"`
+/// - format version (u32)
+/// - compression flag (u8)
+/// - number of components (u32)
+/// - for each component: curve id (u32 length + utf8 bytes), body length in bytes (u64)
+/// - Sha3-256 checksum of the concatenated bodies
+///
+/// followed by the bodies of each component, ie. their `CanonicalSerialize` output.
+///
+/// All the integers are little-endian.
+use ark_ec::CurveGroup;
+use ark_ff::{Field, PrimeField};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Validate,
+};
+use ark_std::{
+    io::{Read, Write},
+    string::{String, ToString},
+    vec::Vec,
+};
+use sha3::{Digest, Sha3_256};
+
+use crate::Error;
+
+pub const PARAMS_MAGIC: &[u8; 4] = b"SNBP";
+pub const PARAMS_FORMAT_VERSION: u32 = 1;
+const CHECKSUM_LEN: usize = 32;
+
+/// Returns the identifier of the curve `C`, composed by the moduli of its scalar field and of its
+/// base prime field, and the extension degree of its base field. This distinguishes the curves of
+/// a cycle, and the G1 and G2 groups of a pairing.
+pub fn curve_id<C: CurveGroup>() -> String {
+    format!(
+        "{}/{}^{}",
+        C::ScalarField::MODULUS,
+        <C::BaseField as Field>::BasePrimeField::MODULUS,
+        C::BaseField::extension_degree()
+    )
+}
+
+/// Writes the header followed by the given components, where each component is a pair of
+/// `(curve id, serialized params)`.
+pub fn write_versioned<W: Write>(
+    mut writer: W,
+    compress: Compress,
+    components: &[(String, Vec<u8>)],
+) -> Result<(), SerializationError> {
+    writer.write_all(PARAMS_MAGIC)?;
+    writer.write_all(&PARAMS_FORMAT_VERSION.to_le_bytes())?;
+    writer.write_all(&[(compress == Compress::Yes) as u8])?;
+    writer.write_all(&(components.len() as u32).to_le_bytes())?;
+    let mut hasher = Sha3_256::new();
+    for (id, body) in components {
+        writer.write_all(&(id.len() as u32).to_le_bytes())?;
+        writer.write_all(id.as_bytes())?;
+        writer.write_all(&(body.len() as u64).to_le_bytes())?;
+        hasher.update(body);
+    }
+    writer.write_all(&hasher.finalize())?;
+    for (_, body) in components {
+        writer.write_all(body)?;
+    }
+    Ok(())
+}
+
+/// Returns the size of the output of `write_versioned` for components with the given curve ids
+/// and bodies sizes.
+pub fn versioned_size(components: &[(String, usize)]) -> usize {
+    PARAMS_MAGIC.len()
+        + 4
+        + 1
+        + 4
+        + components
+            .iter()
+            .map(|(id, size)| 4 + id.len() + 8 + size)
+            .sum::<usize>()
+        + CHECKSUM_LEN
+}
+
+/// Reads the header and the bodies of the components written by `write_versioned`, checking that
+/// the format version, the compression mode and the curve ids match the expected ones, and that
+/// the checksum of the bodies is correct. Returns the bodies of the components.
+pub fn read_versioned<R: Read>(
+    mut reader: R,
+    compress: Compress,
+    curve_ids: &[String],
+) -> Result<Vec<Vec<u8>>, Error> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != PARAMS_MAGIC {
+        return Err(Error::ParamsMismatch(
+            "invalid magic bytes, the params may have been serialized without header".to_string(),
+        ));
+    }
+    let version = read_u32(&mut reader)?;
+    if version != PARAMS_FORMAT_VERSION {
+        return Err(Error::ParamsMismatch(format!(
+            "unsupported format version {}, expected {}",
+            version, PARAMS_FORMAT_VERSION
+        )));
+    }
+    let mut compressed = [0u8; 1];
+    reader.read_exact(&mut compressed)?;
+    if (compressed[0] == 1) != (compress == Compress::Yes) {
+        return Err(Error::ParamsMismatch(format!(
+            "the params are serialized with compress={}, but compress={} was requested",
+            compressed[0] == 1,
+            compress == Compress::Yes
+        )));
+    }
+    let n_components = read_u32(&mut reader)? as usize;
+    if n_components != curve_ids.len() {
+        return Err(Error::ParamsMismatch(format!(
+            "found {} components, expected {}",
+            n_components,
+            curve_ids.len()
+        )));
+    }
+
+    let mut body_lens = Vec::with_capacity(n_components);
+    for (i, expected_id) in curve_ids.iter().enumerate() {
+        let id_len = read_u32(&mut reader)? as usize;
+        if id_len != expected_id.len() {
+            return Err(Error::ParamsMismatch(format!(
+                "component {}: curve id length {}, expected {}",
+                i,
+                id_len,
+                expected_id.len()
+            )));
+        }
+        let mut id = vec![0u8; id_len];
+        reader.read_exact(&mut id)?;
+        if id != expected_id.as_bytes() {
+            return Err(Error::ParamsMismatch(format!(
+                "component {}: curve id {}, expected {}",
+                i,
+                String::from_utf8_lossy(&id),
+                expected_id
+            )));
+        }
+        body_lens.push(read_u64(&mut reader)? as usize);
+    }
+    let mut checksum = [0u8; CHECKSUM_LEN];
+    reader.read_exact(&mut checksum)?;
+
+    let mut hasher = Sha3_256::new();
+    let mut bodies = Vec::with_capacity(n_components);
+    for body_len in body_lens {
+        // read through `take` so that a corrupted length does not trigger a huge allocation
+        let mut body = Vec::new();
+        (&mut reader).take(body_len as u64).read_to_end(&mut body)?;
+        if body.len() != body_len {
+            return Err(Error::ParamsMismatch(format!(
+                "truncated params, expected {} bytes, found {}",
+                body_len,
+                body.len()
+            )));
+        }
+        hasher.update(&body);
+        bodies.push(body);
+    }
+    if hasher.finalize()[..] != checksum[..] {
+        return Err(Error::ParamsMismatch("invalid checksum".to_string()));
+    }
+    Ok(bodies)
+}
+
+/// Serializes the params of the commitment schemes over `C1` and `C2` with the versioned header.
+pub fn serialize_params<C1, C2, W: Write>(
+    writer: W,
+    compress: Compress,
+    params: &impl CanonicalSerialize,
+    cf_params: &impl CanonicalSerialize,
+) -> Result<(), SerializationError>
+where
+    C1: CurveGroup,
+    C2: CurveGroup,
+{
+    let mut body = Vec::new();
+    params.serialize_with_mode(&mut body, compress)?;
+    let mut cf_body = Vec::new();
+    cf_params.serialize_with_mode(&mut cf_body, compress)?;
+    write_versioned(
+        writer,
+        compress,
+        &[(curve_id::<C1>(), body), (curve_id::<C2>(), cf_body)],
+    )
+}
+
+/// Returns the size of the output of `serialize_params`.
+pub fn params_serialized_size<C1, C2>(
+    compress: Compress,
+    params: &impl CanonicalSerialize,
+    cf_params: &impl CanonicalSerialize,
+) -> usize
+where
+    C1: CurveGroup,
+    C2: CurveGroup,
+{
+    versioned_size(&[
+        (curve_id::<C1>(), params.serialized_size(compress)),
+        (curve_id::<C2>(), cf_params.serialized_size(compress)),
+    ])
+}
+
+/// Deserializes the params of the commitment schemes over `C1` and `C2` serialized by
+/// `serialize_params`, returning `Error::ParamsMismatch` if they were serialized for other curves,
+/// for a different length, or are corrupted.
+pub fn deserialize_params<C1, C2, P1, P2, R: Read>(
+    reader: R,
+    compress: Compress,
+    validate: Validate,
+) -> Result<(P1, P2), Error>
+where
+    C1: CurveGroup,
+    C2: CurveGroup,
+    P1: CanonicalDeserialize,
+    P2: CanonicalDeserialize,
+{
+    let bodies = read_versioned(reader, compress, &[curve_id::<C1>(), curve_id::<C2>()])?;
+    Ok((
+        deserialize_body(&bodies[0], compress, validate)?,
+        deserialize_body(&bodies[1], compress, validate)?,
+    ))
+}
+
+/// Deserializes params serialized without the versioned header, as done by previous versions.
+/// Nothing is checked besides the `CanonicalDeserialize` validation, so it should only be used
+/// for trusted legacy files.
+pub fn deserialize_params_unchecked<P1, P2, R: Read>(
+    mut reader: R,
+    compress: Compress,
+    validate: Validate,
+) -> Result<(P1, P2), Error>
+where
+    P1: CanonicalDeserialize,
+    P2: CanonicalDeserialize,
+{
+    let params = P1::deserialize_with_mode(&mut reader, compress, validate)?;
+    let cf_params = P2::deserialize_with_mode(&mut reader, compress, validate)?;
+    Ok((params, cf_params))
+}
+
+/// Deserializes a component, checking that its body is fully consumed.
+pub(crate) fn deserialize_body<T: CanonicalDeserialize>(
+    body: &[u8],
+    compress: Compress,
+    validate: Validate,
+) -> Result<T, Error> {
+    let mut reader = body;
+    let value = T::deserialize_with_mode(&mut reader, compress, validate)?;
+    if !reader.is_empty() {
+        return Err(Error::ParamsMismatch(format!(
+            "{} trailing bytes after the params",
+            reader.len()
+        )));
+    }
+    Ok(value)
+}
+
+/// Converts the errors of `read_versioned` into a `SerializationError`, to be used from the
+/// `CanonicalDeserialize` implementations.
+pub fn into_serialization_error(e: Error) -> SerializationError {
+    match e {
+        Error::SerializationError(e) => e,
+        Error::IOError(e) => SerializationError::IoError(e),
+        _ => SerializationError::InvalidData,
+    }
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, Error> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64, Error> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::G1Projective;
+    use ark_grumpkin::Projective as GrumpkinProjective;
+    use ark_pallas::Projective as PallasProjective;
+    use ark_std::test_rng;
+
+    use super::*;
+    use crate::commitment::{
+        kzg::{ProverKey, KZG},
+        pedersen::{Params as PedersenParams, Pedersen},
+        CommitmentScheme,
+    };
+
+    #[test]
+    fn test_versioned_params() {
+        let mut rng = test_rng();
+        let (pedersen_params, _) = Pedersen::<G1Projective>::setup(&mut rng, 16).unwrap();
+        let (kzg_pk, _) = KZG::<ark_bn254::Bn254>::setup(&mut rng, 16).unwrap();
+
+        let mut bytes = vec![];
+        serialize_params::<G1Projective, G1Projective, _>(
+            &mut bytes,
+            Compress::Yes,
+            &kzg_pk,
+            &pedersen_params,
+        )
+        .unwrap();
+        assert_eq!(
+            bytes.len(),
+            params_serialized_size::<G1Projective, G1Projective>(
+                Compress::Yes,
+                &kzg_pk,
+                &pedersen_params
+            )
+        );
+        let (kzg_pk_2, pedersen_params_2): (ProverKey<G1Projective>, PedersenParams<G1Projective>) =
+            deserialize_params::<G1Projective, G1Projective, _, _, _>(
+                bytes.as_slice(),
+                Compress::Yes,
+                Validate::Yes,
+            )
+            .unwrap();
+        assert_eq!(kzg_pk, kzg_pk_2);
+        assert_eq!(pedersen_params, pedersen_params_2);
+
+        // different curve
+        let r = deserialize_params::<
+            G1Projective,
+            PallasProjective,
+            ProverKey<G1Projective>,
+            PedersenParams<PallasProjective>,
+            _,
+        >(bytes.as_slice(), Compress::Yes, Validate::Yes);
+        assert!(matches!(r, Err(Error::ParamsMismatch(_))));
+        let r = deserialize_params::<
+            GrumpkinProjective,
+            G1Projective,
+            PedersenParams<GrumpkinProjective>,
+            PedersenParams<G1Projective>,
+            _,
+        >(bytes.as_slice(), Compress::Yes, Validate::Yes);
+        assert!(matches!(r, Err(Error::ParamsMismatch(_))));
+
+        // different compression mode
+        let r = deserialize_params::<
+            G1Projective,
+            G1Projective,
+            ProverKey<G1Projective>,
+            PedersenParams<G1Projective>,
+            _,
+        >(bytes.as_slice(), Compress::No, Validate::Yes);
+        assert!(matches!(r, Err(Error::ParamsMismatch(_))));
+
+        // corrupted body
+        let mut corrupted = bytes.clone();
+        let l = corrupted.len();
+        corrupted[l - 1] ^= 1;
+        let r = deserialize_params::<
+            G1Projective,
+            G1Projective,
+            ProverKey<G1Projective>,
+            PedersenParams<G1Projective>,
+            _,
+        >(corrupted.as_slice(), Compress::Yes, Validate::Yes);
+        assert!(matches!(r, Err(Error::ParamsMismatch(_))));
+
+        // truncated
+        let r = deserialize_params::<
+            G1Projective,
+            G1Projective,
+            ProverKey<G1Projective>,
+            PedersenParams<G1Projective>,
+            _,
+        >(&bytes[..bytes.len() - 10], Compress::Yes, Validate::Yes);
+        assert!(matches!(r, Err(Error::ParamsMismatch(_))));
+
+        // headerless params are rejected, but can be loaded through the unchecked method
+        let mut legacy = vec![];
+        kzg_pk.serialize_compressed(&mut legacy).unwrap();
+        pedersen_params.serialize_compressed(&mut legacy).unwrap();
+        let r = deserialize_params::<
+            G1Projective,
+            G1Projective,
+            ProverKey<G1Projective>,
+            PedersenParams<G1Projective>,
+            _,
+        >(legacy.as_slice(), Compress::Yes, Validate::Yes);
+        assert!(matches!(r, Err(Error::ParamsMismatch(_))));
+        let (kzg_pk_2, pedersen_params_2): (ProverKey<G1Projective>, PedersenParams<G1Projective>) =
+            deserialize_params_unchecked(legacy.as_slice(), Compress::Yes, Validate::Yes).unwrap();
+        assert_eq!(kzg_pk, kzg_pk_2);
+        assert_eq!(pedersen_params, pedersen_params_2);
+    }
+}
@@ -0,0 +1,773 @@
+/// Implementation of the Zeromorph multilinear polynomial commitment scheme
+/// (https://eprint.iacr.org/2023/917) on top of the KZG powers of `β`, so that its parameters are
+/// the same ones used by `KZG`, and can thus be obtained from the same ptau files (see
+/// `commitment::ptau`).
+///
+/// A vector `v` of length `2^n` is seen as the evaluations over the boolean hypercube of the
+/// multilinear polynomial `f(X_0, ..., X_{n-1})`, where `X_0` corresponds to the least significant
+/// bit of the index (as in arkworks' `DenseMultilinearExtension`), and it is committed as the
+/// univariate polynomial `U(f)(X) = Σ v_i X^i`. Notice that this differs from the `KZG`
+/// commitment, which commits to the polynomial interpolated from `v`.
+///
+/// The degree check shifts the quotients up to the maximum degree `D` of the SRS, so the prover
+/// parameters keep all its powers and `D` is part of the verifier key. If the prover parameters
+/// were cut to fewer powers than the SRS has, anyone with the rest of the SRS, e.g. from the
+/// public ptau file, could commit to quotients of too high degree and open to false evaluations.
+use ark_crypto_primitives::sponge::{poseidon::PoseidonSponge, Absorb, CryptographicSponge};
+use ark_ec::{pairing::Pairing, CurveGroup};
+use ark_ff::{Field, PrimeField};
+use ark_poly_commit::kzg10::VerifierKey as KZG10VerifierKey;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{log2, rand::RngCore, string::ToString, vec::Vec, One, Zero};
+use core::marker::PhantomData;
+
+use super::{
+    kzg::{batch_claims, ProverKey, KZG},
+    CommitmentScheme, MultilinearCS,
+};
+use crate::transcript::{poseidon::poseidon_canonical_config, Transcript};
+use crate::utils::mle::{evaluate_mle, point_from_challenge};
+use crate::Error;
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Proof<C: CurveGroup> {
+    /// Evaluation of the multilinear polynomial at the opening point.
+    pub eval: C::ScalarField,
+    /// Commitments to the quotients `q_k`, for `k` in `0..n`.
+    pub q_commitments: Vec<C>,
+    /// Commitment to the batched degree-check polynomial `q̂`.
+    pub q_hat: C,
+    /// KZG proof of `ζ_x + z⋅Z_x` being zero at `x`.
+    pub proof: C,
+}
+
+/// Verifier key of Zeromorph, which is the KZG one together with the maximum degree of the SRS,
+/// by which the degree check shifts the quotients.
+#[derive(Debug, Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifierKey<E: Pairing> {
+    pub vk: KZG10VerifierKey<E>,
+    /// maximum degree `D` of the SRS, whose powers `β^0, ..., β^D` are all in the prover key
+    pub max_degree: usize,
+}
+
+impl<E: Pairing> VerifierKey<E> {
+    /// Returns the verifier key for the SRS whose powers are those of `pk`, which must not have
+    /// been trimmed from a larger SRS.
+    pub fn new(pk: &ProverKey<E::G1>, vk: KZG10VerifierKey<E>) -> Result<Self, Error> {
+        let max_degree = pk.powers_of_g.len().checked_sub(1).ok_or(Error::Empty)?;
+        Ok(Self { vk, max_degree })
+    }
+}
+
+/// Zeromorph implements the CommitmentScheme trait for the Zeromorph commitment scheme, where the
+/// opening point `(c, c^2, c^4, ..., c^{2^{n-1}})` is derived from the single field element
+/// challenge `c` by `point_from_challenge`, and for the MultilinearCS trait, which opens at
+/// arbitrary points.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Zeromorph<'a, E: Pairing, const H: bool = false> {
+    _a: PhantomData<&'a ()>,
+    _e: PhantomData<E>,
+}
+
+impl<'a, E, const H: bool> CommitmentScheme<E::G1, H> for Zeromorph<'a, E, H>
+where
+    E: Pairing,
+    E::ScalarField: Absorb,
+{
+    type ProverParams = ProverKey<'a, E::G1>;
+    type VerifierParams = VerifierKey<E>;
+    type Proof = Proof<E::G1>;
+    type ProverChallenge = E::ScalarField;
+    type Challenge = E::ScalarField;
+
+    fn is_hiding() -> bool {
+        if H {
+            return true;
+        }
+        false
+    }
+
+    /// setup returns the same ProverKey as `KZG::setup`, whose powers are the whole SRS. For real
+    /// world deployments the parameters should be loaded from a ceremony, e.g. through
+    /// `Zeromorph::setup_from_ptau`.
+    fn setup(
+        rng: impl RngCore,
+        len: usize,
+    ) -> Result<(Self::ProverParams, Self::VerifierParams), Error> {
+        let (pk, vk) = KZG::<'a, E>::setup(rng, len)?;
+        let vk = VerifierKey::new(&pk, vk)?;
+        Ok((pk, vk))
+    }
+
+    /// Only checks that the parameters are large enough for `len`, since the degree check needs
+    /// the highest powers of the SRS, so none of them can be dropped.
+    fn trim(
+        pp: &Self::ProverParams,
+        vp: &Self::VerifierParams,
+        len: usize,
+    ) -> Result<(Self::ProverParams, Self::VerifierParams), Error> {
+        check_num_powers(len.next_power_of_two(), pp.powers_of_g.len())?;
+        if pp.powers_of_g.len() != vp.max_degree + 1 {
+            return Err(Error::NotExpectedLength(
+                pp.powers_of_g.len(),
+                vp.max_degree + 1,
+            ));
+        }
+        Ok((pp.clone(), vp.clone()))
+    }
+
+    fn commit(
+        params: &Self::ProverParams,
+        v: &[E::ScalarField],
+        _blind: &E::ScalarField,
+    ) -> Result<E::G1, Error> {
+        if !_blind.is_zero() || H {
+            return Err(Error::NotSupportedYet("hiding".to_string()));
+        }
+        check_num_powers(v.len(), params.powers_of_g.len())?;
+        Ok(params.msm.msm(&params.powers_of_g[..v.len()], v))
+    }
+
+    /// Since the vector is committed in coefficient form, the commitment is the MSM of the
+    /// non-zero entries with their powers of `β`.
+    fn commit_sparse(
+        params: &Self::ProverParams,
+        len: usize,
+        pairs: &[(usize, E::ScalarField)],
+        _blind: &E::ScalarField,
+    ) -> Result<E::G1, Error> {
+        if !_blind.is_zero() || H {
+            return Err(Error::NotSupportedYet("hiding".to_string()));
+        }
+        if pairs.iter().any(|(i, _)| *i >= len) {
+            return Err(Error::OutOfBounds);
+        }
+        check_num_powers(len, params.powers_of_g.len())?;
+        let (bases, scalars): (Vec<E::G1Affine>, Vec<E::ScalarField>) = pairs
+            .iter()
+            .map(|(i, v_i)| (params.powers_of_g[*i], *v_i))
+            .unzip();
+        Ok(params.msm.msm(&bases, &scalars))
+    }
+
+    fn prove(
+        params: &Self::ProverParams,
+        transcript: &mut impl Transcript<E::ScalarField>,
+        cm: &E::G1,
+        v: &[E::ScalarField],
+        _blind: &E::ScalarField,
+        _rng: Option<&mut dyn RngCore>,
+    ) -> Result<Self::Proof, Error> {
+        transcript.absorb_nonnative(cm);
+        let challenge = transcript.get_challenge();
+        Self::prove_with_challenge(params, challenge, v, _blind, _rng)
+    }
+
+    /// Opens the multilinear polynomial of `v` at `point_from_challenge(challenge, n)`, where
+    /// `2^n` is the length of `v` padded to the next power of two. The challenges of the
+    /// Zeromorph protocol are derived from a fresh transcript that absorbs `challenge`.
+    fn prove_with_challenge(
+        params: &Self::ProverParams,
+        challenge: Self::ProverChallenge,
+        v: &[E::ScalarField],
+        _blind: &E::ScalarField,
+        _rng: Option<&mut dyn RngCore>,
+    ) -> Result<Self::Proof, Error> {
+        if !_blind.is_zero() || H {
+            return Err(Error::NotSupportedYet("hiding".to_string()));
+        }
+        let point = point_from_challenge(challenge, log2(v.len()) as usize);
+        let mut transcript = PoseidonSponge::new(&poseidon_canonical_config());
+        let (_, proof) =
+            <Zeromorph<'a, E> as MultilinearCS<E::G1>>::open(params, &mut transcript, v, &point)?;
+        Ok(proof)
+    }
+
+    fn verify(
+        params: &Self::VerifierParams,
+        transcript: &mut impl Transcript<E::ScalarField>,
+        cm: &E::G1,
+        proof: &Self::Proof,
+    ) -> Result<(), Error> {
+        transcript.absorb_nonnative(cm);
+        let challenge = transcript.get_challenge();
+        Self::verify_with_challenge(params, challenge, cm, proof)
+    }
+
+    /// The number of variables `n` of the opened polynomial is given by the number of quotients
+    /// in the proof.
+    fn verify_with_challenge(
+        params: &Self::VerifierParams,
+        challenge: Self::Challenge,
+        cm: &E::G1,
+        proof: &Self::Proof,
+    ) -> Result<(), Error> {
+        if H {
+            return Err(Error::NotSupportedYet("hiding".to_string()));
+        }
+        let point = point_from_challenge(challenge, proof.q_commitments.len());
+        let mut transcript = PoseidonSponge::new(&poseidon_canonical_config());
+        <Zeromorph<'a, E> as MultilinearCS<E::G1>>::verify(
+            params,
+            &mut transcript,
+            cm,
+            &point,
+            &proof.eval,
+            proof,
+        )
+    }
+}
+
+impl<'a, E: Pairing> MultilinearCS<E::G1> for Zeromorph<'a, E, false>
+where
+    E::ScalarField: Absorb,
+{
+    type ProverParams = ProverKey<'a, E::G1>;
+    type VerifierParams = VerifierKey<E>;
+    type Proof = Proof<E::G1>;
+
+    fn setup(
+        rng: impl RngCore,
+        n_vars: usize,
+    ) -> Result<(Self::ProverParams, Self::VerifierParams), Error> {
+        <Self as CommitmentScheme<E::G1>>::setup(rng, 1 << n_vars)
+    }
+
+    fn commit(params: &Self::ProverParams, v: &[E::ScalarField]) -> Result<E::G1, Error> {
+        <Self as CommitmentScheme<E::G1>>::commit(params, v, &E::ScalarField::zero())
+    }
+
+    /// The proof consists of the commitments to the quotients `q_k(X_0, ..., X_{k-1})` such that
+    /// `f(X) - f(point) = Σ_k (X_k - point_k) q_k(X)`, the commitment to `q̂(X) = Σ_k y^k X^{D + 1 -
+    /// 2^k} U(q_k)(X)`, where `D` is the maximum degree of the SRS, which bounds their degrees,
+    /// and a KZG proof that the polynomial `ζ_x + z⋅Z_x` vanishes at `x`, where `ζ_x` and `Z_x`
+    /// are the linear combinations of the committed polynomials that vanish at `x` if the degree
+    /// check and the Zeromorph identity hold. The challenges `y`, `x` and `z` are obtained from
+    /// the transcript.
+    fn open(
+        params: &Self::ProverParams,
+        transcript: &mut impl Transcript<E::ScalarField>,
+        v: &[E::ScalarField],
+        point: &[E::ScalarField],
+    ) -> Result<(E::ScalarField, Self::Proof), Error> {
+        let N = 1 << point.len();
+        if v.len() > N {
+            return Err(Error::NotExpectedLength(v.len(), N));
+        }
+        check_num_powers(N, params.powers_of_g.len())?;
+        let mut f = v.to_vec();
+        f.resize(N, E::ScalarField::zero());
+
+        let (eval, quotients) = compute_quotients(&f, point);
+        let max_degree = params.powers_of_g.len() - 1;
+        let proof =
+            open_with_quotients::<E>(params, transcript, &f, point, eval, &quotients, max_degree)?;
+        Ok((eval, proof))
+    }
+
+    /// Checks that `e([ζ_x + z⋅Z_x] + x⋅π, H) == e(π, β⋅H)`, where the commitment
+    /// `[ζ_x + z⋅Z_x]` is computed from `cm`, the commitments of the proof and the claimed
+    /// evaluation.
+    fn verify(
+        params: &Self::VerifierParams,
+        transcript: &mut impl Transcript<E::ScalarField>,
+        cm: &E::G1,
+        point: &[E::ScalarField],
+        eval: &E::ScalarField,
+        proof: &Self::Proof,
+    ) -> Result<(), Error> {
+        if proof.eval != *eval {
+            return Err(Error::CommitmentVerificationFail);
+        }
+        let n = point.len();
+        check_num_powers(1 << n, params.max_degree + 1)?;
+        if proof.q_commitments.len() != n {
+            return Err(Error::NotSameLength(
+                "proof.q_commitments.len()".to_string(),
+                proof.q_commitments.len(),
+                "point.len()".to_string(),
+                n,
+            ));
+        }
+
+        transcript.absorb(&point);
+        transcript.absorb(&proof.eval);
+        for q_cm in &proof.q_commitments {
+            transcript.absorb_nonnative(q_cm);
+        }
+        let y = transcript.get_challenge();
+        transcript.absorb_nonnative(&proof.q_hat);
+        let xz = transcript.get_challenges(2);
+        let (x, z) = (xz[0], xz[1]);
+
+        let scalars = quotient_scalars(y, x, z, point, params.max_degree);
+        let mut lhs =
+            proof.q_hat + *cm * z - params.vk.g * (z * proof.eval * phi(n, x)) + proof.proof * x;
+        for (q_cm, s_k) in proof.q_commitments.iter().zip(&scalars) {
+            lhs -= *q_cm * s_k;
+        }
+
+        let check = E::multi_pairing(
+            [lhs.into_affine(), (-proof.proof).into_affine()],
+            [params.vk.h, params.vk.beta_h],
+        );
+        if !check.is_zero() {
+            return Err(Error::CommitmentVerificationFail);
+        }
+        Ok(())
+    }
+
+    /// Opens `Σ γ^j f_j` at `point`, where the batching challenge `γ` is obtained from the
+    /// transcript after absorbing the commitments, the point and the evaluations.
+    fn batch_open(
+        params: &Self::ProverParams,
+        transcript: &mut impl Transcript<E::ScalarField>,
+        cms: &[E::G1],
+        vs: &[Vec<E::ScalarField>],
+        point: &[E::ScalarField],
+    ) -> Result<(Vec<E::ScalarField>, Self::Proof), Error> {
+        if vs.is_empty() {
+            return Err(Error::Empty);
+        }
+        let evals = vs
+            .iter()
+            .map(|v| evaluate_mle(v, point))
+            .collect::<Result<Vec<_>, _>>()?;
+        let gamma = batching_challenge(transcript, cms, point, &evals);
+
+        let mut v = vec![E::ScalarField::zero(); vs.iter().map(|v| v.len()).max().unwrap_or(0)];
+        for v_j in vs.iter().rev() {
+            v.iter_mut().for_each(|v_i| *v_i *= gamma);
+            v.iter_mut().zip(v_j).for_each(|(v_i, v_ji)| *v_i += v_ji);
+        }
+        let (_, proof) = Self::open(params, transcript, &v, point)?;
+        Ok((evals, proof))
+    }
+
+    fn batch_verify(
+        params: &Self::VerifierParams,
+        transcript: &mut impl Transcript<E::ScalarField>,
+        cms: &[E::G1],
+        point: &[E::ScalarField],
+        evals: &[E::ScalarField],
+        proof: &Self::Proof,
+    ) -> Result<(), Error> {
+        if cms.len() != evals.len() {
+            return Err(Error::NotSameLength(
+                "cms.len()".to_string(),
+                cms.len(),
+                "evals.len()".to_string(),
+                evals.len(),
+            ));
+        }
+        if cms.is_empty() {
+            return Err(Error::Empty);
+        }
+        let gamma = batching_challenge(transcript, cms, point, evals);
+        let (cm, eval) = batch_claims(gamma, cms, evals);
+        <Self as MultilinearCS<E::G1>>::verify(params, transcript, &cm, point, &eval, proof)
+    }
+}
+
+fn batching_challenge<F: PrimeField + Absorb, C: CurveGroup<ScalarField = F>>(
+    transcript: &mut impl Transcript<F>,
+    cms: &[C],
+    point: &[F],
+    evals: &[F],
+) -> F {
+    for cm in cms {
+        transcript.absorb_nonnative(cm);
+    }
+    transcript.absorb(&point);
+    transcript.absorb(&evals);
+    transcript.get_challenge()
+}
+
+/// Returns the evaluation of the multilinear polynomial of `f` at `point`, together with the
+/// quotients `q_k`, of `2^k` coefficients, such that `f(X) - f(point) = Σ_k (X_k - point_k)
+/// q_k(X)`.
+fn compute_quotients<F: Field>(f: &[F], point: &[F]) -> (F, Vec<Vec<F>>) {
+    // fix the variables from the last one to the first one
+    let mut quotients = vec![vec![]; point.len()];
+    let mut g = f.to_vec();
+    for k in (0..point.len()).rev() {
+        let (lo, hi) = g.split_at(1 << k);
+        let q_k = hi.iter().zip(lo).map(|(h, l)| *h - l).collect::<Vec<_>>();
+        g = lo
+            .iter()
+            .zip(&q_k)
+            .map(|(l, q)| *l + point[k] * q)
+            .collect();
+        quotients[k] = q_k;
+    }
+    (g[0], quotients)
+}
+
+/// Returns the Zeromorph proof that `f` evaluates to `eval` at `point` from the given quotients,
+/// whose degree check shifts each `q_k` by `X^{D + 1 - 2^k}` for the given maximum degree `D`.
+fn open_with_quotients<E: Pairing>(
+    params: &ProverKey<E::G1>,
+    transcript: &mut impl Transcript<E::ScalarField>,
+    f: &[E::ScalarField],
+    point: &[E::ScalarField],
+    eval: E::ScalarField,
+    quotients: &[Vec<E::ScalarField>],
+    max_degree: usize,
+) -> Result<Proof<E::G1>, Error>
+where
+    E::ScalarField: Absorb,
+{
+    let n = point.len();
+    check_num_powers(1 << n, max_degree + 1)?;
+    let q_commitments = quotients
+        .iter()
+        .map(|q_k| {
+            check_num_powers(q_k.len(), params.powers_of_g.len())?;
+            Ok(params.msm.msm(&params.powers_of_g[..q_k.len()], q_k))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    transcript.absorb(&point);
+    transcript.absorb(&eval);
+    for q_cm in &q_commitments {
+        transcript.absorb_nonnative(q_cm);
+    }
+    let y = transcript.get_challenge();
+
+    // q̂ is zero below the shift of q_{n-1}, so only its highest coefficients are committed
+    let shift = |k: usize| max_degree + 1 - (1 << k);
+    let len = quotients
+        .iter()
+        .enumerate()
+        .map(|(k, q_k)| shift(k) + q_k.len())
+        .fold(f.len(), usize::max);
+    check_num_powers(len, params.powers_of_g.len())?;
+    let mut q_hat = vec![E::ScalarField::zero(); len];
+    let mut y_k = E::ScalarField::one();
+    for (k, q_k) in quotients.iter().enumerate() {
+        q_hat[shift(k)..]
+            .iter_mut()
+            .zip(q_k)
+            .for_each(|(q_hat_i, q_ki)| *q_hat_i += y_k * q_ki);
+        y_k *= y;
+    }
+    let lowest = if n == 0 { len } else { shift(n - 1) };
+    let q_hat_commitment = params
+        .msm
+        .msm(&params.powers_of_g[lowest..len], &q_hat[lowest..]);
+    transcript.absorb_nonnative(&q_hat_commitment);
+    let xz = transcript.get_challenges(2);
+    let (x, z) = (xz[0], xz[1]);
+
+    // p(X) = ζ_x(X) + z⋅Z_x(X), which vanishes at x
+    let scalars = quotient_scalars(y, x, z, point, max_degree);
+    let mut p = q_hat;
+    p.iter_mut().zip(f).for_each(|(p_i, f_i)| *p_i += z * f_i);
+    p[0] -= z * eval * phi(n, x);
+    for (q_k, s_k) in quotients.iter().zip(&scalars) {
+        p.iter_mut()
+            .zip(q_k)
+            .for_each(|(p_i, q_ki)| *p_i -= *s_k * q_ki);
+    }
+
+    // w(X) = p(X) / (X - x), computed by synthetic division since the remainder is zero
+    let mut w = vec![E::ScalarField::zero(); len - 1];
+    let mut acc = E::ScalarField::zero();
+    for i in (1..len).rev() {
+        acc = p[i] + x * acc;
+        w[i - 1] = acc;
+    }
+    let proof = params.msm.msm(&params.powers_of_g[..len - 1], &w);
+
+    Ok(Proof {
+        eval,
+        q_commitments,
+        q_hat: q_hat_commitment,
+        proof,
+    })
+}
+
+/// Returns `Φ_m(a) = Σ_{i<2^m} a^i = Π_{j<m} (1 + a^{2^j})`.
+fn phi<F: Field>(m: usize, a: F) -> F {
+    point_from_challenge(a, m)
+        .into_iter()
+        .map(|a_j| F::one() + a_j)
+        .product()
+}
+
+/// Returns the scalars `s_k = y^k x^{D + 1 - 2^k} + z⋅(x^{2^k} Φ_{n-k-1}(x^{2^{k+1}}) - u_k
+/// Φ_{n-k}(x^{2^k}))` by which the commitments to the quotients `q_k` are subtracted in
+/// `[ζ_x + z⋅Z_x]`, for the maximum degree `D` of the SRS.
+fn quotient_scalars<F: Field>(y: F, x: F, z: F, point: &[F], max_degree: usize) -> Vec<F> {
+    let n = point.len();
+    let x_pows = point_from_challenge(x, n + 1);
+    let mut y_k = F::one();
+    point
+        .iter()
+        .enumerate()
+        .map(|(k, u_k)| {
+            let s_k = y_k * x.pow([(max_degree + 1 - (1 << k)) as u64])
+                + z * (x_pows[k] * phi(n - k - 1, x_pows[k + 1]) - *u_k * phi(n - k, x_pows[k]));
+            y_k *= y;
+            s_k
+        })
+        .collect()
+}
+
+fn check_num_powers(num_coefficients: usize, num_powers: usize) -> Result<(), Error> {
+    if num_coefficients > num_powers {
+        return Err(Error::PolyCommitError(
+            ark_poly_commit::error::Error::TooManyCoefficients {
+                num_coefficients,
+                num_powers,
+            },
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::{Bn254, Fr, G1Projective as G1};
+    use ark_poly::{
+        univariate::{DenseOrSparsePolynomial, DensePolynomial},
+        DenseMultilinearExtension, DenseUVPolynomial, MultilinearExtension,
+    };
+    use ark_std::{test_rng, UniformRand};
+
+    use super::*;
+
+    type ZM = Zeromorph<'static, Bn254>;
+
+    #[test]
+    fn test_zeromorph_opening() {
+        let mut rng = &mut test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let n_vars = 12;
+
+        let (pk, vk) = <ZM as MultilinearCS<G1>>::setup(&mut rng, n_vars).unwrap();
+        let v: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(rng))
+            .take(1 << n_vars)
+            .collect();
+        let cm = <ZM as MultilinearCS<G1>>::commit(&pk, &v).unwrap();
+        let mle = DenseMultilinearExtension::from_evaluations_slice(n_vars, &v);
+
+        for _ in 0..3 {
+            let point: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(rng))
+                .take(n_vars)
+                .collect();
+            let transcript_p = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+            let (eval, proof) = ZM::open(&pk, transcript_p, &v, &point).unwrap();
+            assert_eq!(eval, mle.evaluate(&point).unwrap());
+            assert_eq!(eval, evaluate_mle(&v, &point).unwrap());
+
+            let transcript_v = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+            <ZM as MultilinearCS<G1>>::verify(&vk, transcript_v, &cm, &point, &eval, &proof)
+                .unwrap();
+
+            // a wrong evaluation is rejected
+            let transcript_v = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+            let mut bad_proof = proof.clone();
+            bad_proof.eval += Fr::one();
+            assert!(<ZM as MultilinearCS<G1>>::verify(
+                &vk,
+                transcript_v,
+                &cm,
+                &point,
+                &bad_proof.eval,
+                &bad_proof
+            )
+            .is_err());
+            // as well as the opening of another commitment
+            let transcript_v = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+            let other_cm = cm + G1::rand(rng);
+            assert!(<ZM as MultilinearCS<G1>>::verify(
+                &vk,
+                transcript_v,
+                &other_cm,
+                &point,
+                &eval,
+                &proof
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn test_zeromorph_batch_opening() {
+        let mut rng = &mut test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let n_vars = 6;
+
+        let (pk, vk) = <ZM as MultilinearCS<G1>>::setup(&mut rng, n_vars).unwrap();
+        // vectors of different lengths, which are padded to the same number of variables
+        let vs: Vec<Vec<Fr>> = [64, 50, 3]
+            .iter()
+            .map(|n| std::iter::repeat_with(|| Fr::rand(rng)).take(*n).collect())
+            .collect();
+        let cms = vs
+            .iter()
+            .map(|v| <ZM as MultilinearCS<G1>>::commit(&pk, v))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let point: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(rng))
+            .take(n_vars)
+            .collect();
+
+        let transcript_p = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        let (evals, proof) = ZM::batch_open(&pk, transcript_p, &cms, &vs, &point).unwrap();
+        for (v, eval) in vs.iter().zip(&evals) {
+            assert_eq!(*eval, evaluate_mle(v, &point).unwrap());
+        }
+
+        let transcript_v = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        ZM::batch_verify(&vk, transcript_v, &cms, &point, &evals, &proof).unwrap();
+
+        // a wrong evaluation is rejected
+        let mut bad_evals = evals.clone();
+        bad_evals[1] += Fr::one();
+        let transcript_v = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        assert!(ZM::batch_verify(&vk, transcript_v, &cms, &point, &bad_evals, &proof).is_err());
+    }
+
+    #[test]
+    fn test_zeromorph_commitment_scheme() {
+        let mut rng = &mut test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        // a length that is not a power of two, so that the vector is padded
+        let n = 100;
+        let (pk, vk) = <ZM as CommitmentScheme<G1>>::setup(&mut rng, n).unwrap();
+        let v: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(rng)).take(n).collect();
+        let cm = <ZM as CommitmentScheme<G1>>::commit(&pk, &v, &Fr::zero()).unwrap();
+        assert_eq!(cm, <ZM as MultilinearCS<G1>>::commit(&pk, &v).unwrap());
+
+        let pairs = vec![(3, Fr::rand(rng)), (42, Fr::rand(rng))];
+        let mut v_sparse = vec![Fr::zero(); n];
+        pairs.iter().for_each(|(i, v_i)| v_sparse[*i] = *v_i);
+        assert_eq!(
+            ZM::commit_sparse(&pk, n, &pairs, &Fr::zero()).unwrap(),
+            <ZM as CommitmentScheme<G1>>::commit(&pk, &v_sparse, &Fr::zero()).unwrap()
+        );
+
+        let transcript_p = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        let proof = ZM::prove(&pk, transcript_p, &cm, &v, &Fr::zero(), None).unwrap();
+        let transcript_v = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        <ZM as CommitmentScheme<G1>>::verify(&vk, transcript_v, &cm, &proof).unwrap();
+
+        let c = Fr::rand(rng);
+        let proof = ZM::prove_with_challenge(&pk, c, &v, &Fr::zero(), None).unwrap();
+        assert_eq!(
+            proof.eval,
+            evaluate_mle(&v, &point_from_challenge(c, 7)).unwrap()
+        );
+        ZM::verify_with_challenge(&vk, c, &cm, &proof).unwrap();
+    }
+
+    /// Returns `(s, t)` such that `s⋅a + t⋅b = 1`, for coprime `a` and `b`.
+    fn bezout(
+        a: &DensePolynomial<Fr>,
+        b: &DensePolynomial<Fr>,
+    ) -> (DensePolynomial<Fr>, DensePolynomial<Fr>) {
+        let one = DensePolynomial::from_coefficients_vec(vec![Fr::one()]);
+        let (mut r0, mut r1) = (a.clone(), b.clone());
+        let (mut s0, mut s1) = (one.clone(), DensePolynomial::zero());
+        let (mut t0, mut t1) = (DensePolynomial::zero(), one);
+        while !r1.is_zero() {
+            let (q, r) = DenseOrSparsePolynomial::from(&r0)
+                .divide_with_q_and_r(&DenseOrSparsePolynomial::from(&r1))
+                .unwrap();
+            (r0, r1) = (r1, r);
+            let s = &s0 - &(&q * &s1);
+            (s0, s1) = (s1, s);
+            let t = &t0 - &(&q * &t1);
+            (t0, t1) = (t1, t);
+        }
+        // r0 is the constant gcd of a and b
+        let inv = r0.coeffs[0].inverse().unwrap();
+        (&s0 * inv, &t0 * inv)
+    }
+
+    /// A proof whose quotients exceed their degree bounds is rejected, even if the prover key
+    /// contains the powers needed to commit to them. Such quotients allow opening to any
+    /// evaluation. With the degree check shifted only up to `2^n - 1`, as for an SRS of exactly
+    /// `2^n` powers, the false evaluation is accepted.
+    #[test]
+    fn test_zeromorph_high_degree_quotients() {
+        let mut rng = &mut test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let n_vars = 2;
+        let N = 1 << n_vars;
+
+        // an SRS with more powers than the 2^n coefficients of the committed polynomials
+        let (pk, vk) = <ZM as CommitmentScheme<G1>>::setup(&mut rng, 16).unwrap();
+        assert_eq!(vk.max_degree, 16);
+        let f: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(rng)).take(N).collect();
+        let cm = <ZM as MultilinearCS<G1>>::commit(&pk, &f).unwrap();
+        let point: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(rng))
+            .take(n_vars)
+            .collect();
+        let (eval, quotients) = compute_quotients(&f, &point);
+
+        // U(f)(X) - eval⋅Φ_n(X) = Σ_k c_k(X) U(q_k)(X), with c_k(X) = X^{2^k} Φ_{n-k-1}(X^{2^{k+1}})
+        // - u_k Φ_{n-k}(X^{2^k}). Since c_0 and c_1 are coprime, adding δ⋅Φ_n(X)⋅a_k(X) to each
+        // q_k, where a_0⋅c_0 + a_1⋅c_1 = 1, moves the evaluation to eval - δ.
+        let c: Vec<DensePolynomial<Fr>> = point
+            .iter()
+            .enumerate()
+            .map(|(k, u_k)| {
+                let mut c_k = vec![Fr::zero(); N];
+                for i in 0..(1 << (n_vars - k - 1)) {
+                    c_k[(1 << k) + i * (1 << (k + 1))] += Fr::one();
+                }
+                for i in 0..(1 << (n_vars - k)) {
+                    c_k[i * (1 << k)] -= u_k;
+                }
+                DensePolynomial::from_coefficients_vec(c_k)
+            })
+            .collect();
+        let (a_0, a_1) = bezout(&c[0], &c[1]);
+        let delta = Fr::rand(rng);
+        let phi_n = DensePolynomial::from_coefficients_vec(vec![delta; N]);
+        let forged: Vec<Vec<Fr>> = quotients
+            .iter()
+            .zip([a_0, a_1])
+            .map(|(q_k, a_k)| {
+                (&DensePolynomial::from_coefficients_slice(q_k) + &(&phi_n * &a_k)).coeffs
+            })
+            .collect();
+        assert!(forged.iter().enumerate().any(|(k, q_k)| q_k.len() > 1 << k));
+        let bad_eval = eval - delta;
+
+        let transcript_p = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        let proof =
+            open_with_quotients::<Bn254>(&pk, transcript_p, &f, &point, bad_eval, &forged, N - 1)
+                .unwrap();
+
+        // accepted if the degree check assumes that the SRS has only 2^n powers
+        let trimmed_vk = VerifierKey {
+            vk: vk.vk.clone(),
+            max_degree: N - 1,
+        };
+        let transcript_v = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        <ZM as MultilinearCS<G1>>::verify(
+            &trimmed_vk,
+            transcript_v,
+            &cm,
+            &point,
+            &bad_eval,
+            &proof,
+        )
+        .unwrap();
+        // but rejected by the verifier key of the SRS
+        let transcript_v = &mut PoseidonSponge::<Fr>::new(&poseidon_config);
+        assert!(<ZM as MultilinearCS<G1>>::verify(
+            &vk,
+            transcript_v,
+            &cm,
+            &point,
+            &bad_eval,
+            &proof
+        )
+        .is_err());
+    }
+}
@@ -1,7 +1,7 @@
 /// Contains [CycleFold](https://eprint.iacr.org/2023/1192.pdf) related circuits and functions that
 /// are shared across the different folding schemes
 use ark_crypto_primitives::sponge::{Absorb, CryptographicSponge};
-use ark_ec::{AffineRepr, CurveGroup, Group};
+use ark_ec::{AffineRepr, CurveGroup, Group, VariableBaseMSM};
 use ark_ff::{BigInteger, Field, PrimeField};
 use ark_r1cs_std::{
     alloc::{AllocVar, AllocationMode},
@@ -14,17 +14,23 @@ use ark_r1cs_std::{
 use ark_relations::r1cs::{
     ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, Namespace, SynthesisError,
 };
-use ark_std::fmt::Debug;
 use ark_std::rand::RngCore;
-use ark_std::{One, Zero};
+use ark_std::{fmt::Debug, string::ToString, vec::Vec};
+use ark_std::{One, UniformRand, Zero};
 use core::{borrow::Borrow, marker::PhantomData};
 
-use super::{nonnative::uint::NonNativeUintVar, CF1, CF2};
+use super::{
+    nonnative::{affine::NonNativeAffineVar, uint::NonNativeUintVar},
+    CF1, CF2,
+};
 use crate::commitment::CommitmentScheme;
 use crate::constants::NOVA_N_BITS_RO;
 use crate::folding::nova::nifs::{nova::NIFS, NIFSTrait};
 use crate::transcript::{AbsorbNonNative, AbsorbNonNativeGadget, Transcript, TranscriptVar};
-use crate::utils::gadgets::{EquivalenceGadget, VectorGadget};
+use crate::utils::{
+    gadgets::{EquivalenceGadget, VectorGadget},
+    get_cm_coordinates,
+};
 use crate::Error;
 use crate::{
     arith::{
@@ -41,6 +47,11 @@ pub use crate::folding::nova::{
     CommittedInstance as CycleFoldCommittedInstance, Witness as CycleFoldWitness,
 };
 
+/// Domain-separation labels of the commitments of a `CycleFoldCommittedInstance`, used instead
+/// of the labels of the Nova committed instance when opening them, so that they can not be
+/// confused with the commitments of the main instance.
+pub const CF_COMMITMENT_LABELS: [&[u8]; 2] = [b"cf_W", b"cf_E"];
+
 impl<C: CurveGroup, GC: CurveVar<C, CF2<C>>> Inputize<CF2<C>, CycleFoldCommittedInstanceVar<C, GC>>
     for CycleFoldCommittedInstance<C>
 {
@@ -110,20 +121,28 @@ where
 {
     // Compatible with the in-circuit `CycleFoldCommittedInstanceVar::to_native_sponge_field_elements`
     fn to_native_sponge_field_elements(&self, dest: &mut Vec<C::BaseField>) {
-        [self.u].to_native_sponge_field_elements(dest);
-        self.x.to_native_sponge_field_elements(dest);
-        let (cmE_x, cmE_y) = match self.cmE.into_affine().xy() {
-            Some((&x, &y)) => (x, y),
-            None => (C::BaseField::zero(), C::BaseField::zero()),
-        };
-        let (cmW_x, cmW_y) = match self.cmW.into_affine().xy() {
-            Some((&x, &y)) => (x, y),
-            None => (C::BaseField::zero(), C::BaseField::zero()),
-        };
-        cmE_x.to_sponge_field_elements(dest);
-        cmE_y.to_sponge_field_elements(dest);
-        cmW_x.to_sponge_field_elements(dest);
-        cmW_y.to_sponge_field_elements(dest);
+        let n_bits = C::ScalarField::MODULUS_BIT_SIZE as usize;
+        let bits = [self.u]
+            .iter()
+            .chain(&self.x)
+            .flat_map(|v| {
+                let mut bits = v.into_bigint().to_bits_le();
+                bits.resize(n_bits, false);
+                bits
+            })
+            .collect::<Vec<_>>();
+        dest.extend(
+            bits.chunks(C::BaseField::MODULUS_BIT_SIZE as usize - 1)
+                .map(<C::BaseField as PrimeField>::BigInt::from_bits_le)
+                .map(C::BaseField::from),
+        );
+        for p in [self.cmE, self.cmW] {
+            let (x, y) = match p.into_affine().xy() {
+                Some((&x, &y)) => (x, y),
+                None => (C::BaseField::zero(), C::BaseField::zero()),
+            };
+            dest.extend([x, y]);
+        }
     }
 }
 
@@ -133,29 +152,32 @@ where
     GC: CurveVar<C, CF2<C>> + ToConstraintFieldGadget<CF2<C>>,
     C::BaseField: PrimeField + Absorb,
 {
-    /// Extracts the underlying field elements from `CycleFoldCommittedInstanceVar`, in the order
-    /// of `u`, `x`, `cmE.x`, `cmE.y`, `cmW.x`, `cmW.y`, `cmE.is_inf || cmW.is_inf` (|| is for
-    /// concat).
+    /// Extracts the underlying field elements from `CycleFoldCommittedInstanceVar` in a compressed
+    /// form: the bits of `u || x` (|| is for concat) are packed into as few field elements as
+    /// possible, which are followed by the coordinates of `cmE` and `cmW`.
+    ///
+    /// The coordinates are absorbed as they are: replacing `y` by its parity would require a
+    /// canonical decomposition of `y`, which costs more constraints than absorbing `y` itself.
     fn to_native_sponge_field_elements(&self) -> Result<Vec<FpVar<CF2<C>>>, SynthesisError> {
-        let mut cmE_elems = self.cmE.to_constraint_field()?;
-        let mut cmW_elems = self.cmW.to_constraint_field()?;
-
-        // See `transcript/poseidon.rs: TranscriptVar::absorb_point` for details
-        // why the last element is unnecessary.
-        cmE_elems.pop();
-        cmW_elems.pop();
-
-        Ok([
-            self.u.to_native_sponge_field_elements()?,
-            self.x
-                .iter()
-                .map(|i| i.to_native_sponge_field_elements())
-                .collect::<Result<Vec<_>, _>>()?
-                .concat(),
-            cmE_elems,
-            cmW_elems,
-        ]
-        .concat())
+        let n_bits = CF1::<C>::MODULUS_BIT_SIZE as usize;
+        let bits = [&self.u]
+            .into_iter()
+            .chain(&self.x)
+            .map(|v| v.to_bits_le_with_length(n_bits))
+            .collect::<Result<Vec<_>, _>>()?
+            .concat();
+        let mut elems = bits
+            .chunks(CF2::<C>::MODULUS_BIT_SIZE as usize - 1)
+            .map(Boolean::le_bits_to_fp_var)
+            .collect::<Result<Vec<_>, _>>()?;
+        for p in [&self.cmE, &self.cmW] {
+            // See `transcript/poseidon.rs: TranscriptVar::absorb_point` for details why the last
+            // element of `[x, y, is_inf]` is unnecessary.
+            let mut p_elems = p.to_constraint_field()?;
+            p_elems.pop();
+            elems.extend(p_elems);
+        }
+        Ok(elems)
     }
 }
 
@@ -302,12 +324,15 @@ where
         // ci2 is assumed to be always with cmE=0, u=1 (checks done previous to this method)
         ci2: CycleFoldCommittedInstanceVar<C, GC>,
     ) -> Result<CycleFoldCommittedInstanceVar<C, GC>, SynthesisError> {
-        // r_nonnat is equal to r_bits just that in a different format
-        let r_nonnat = {
-            let mut bits = r_bits.clone();
-            bits.resize(CF1::<C>::MODULUS_BIT_SIZE as usize, Boolean::FALSE);
-            NonNativeUintVar::from(&bits)
-        };
+        // r_bits has the length of the CycleFold challenge, which must fit in CF1<C> so that the
+        // decomposition below is not truncated
+        if r_bits.len() >= CF1::<C>::MODULUS_BIT_SIZE as usize {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        // r_nonnat is equal to r_bits just that in a different format. It is not padded to the
+        // width of CF1<C>, so that the limbs (and the upper bounds) of the unreduced `u` and `x`
+        // below stay as small as the challenge allows, which makes their reduction cheaper.
+        let r_nonnat = NonNativeUintVar::from(&r_bits);
         Ok(CycleFoldCommittedInstanceVar {
             cmE: cmT.scalar_mul_le(r_bits.iter())? + ci1.cmE,
             cmW: ci1.cmW + ci2.cmW.scalar_mul_le(r_bits.iter())?,
@@ -324,6 +349,39 @@ where
         })
     }
 
+    /// Same as `fold_committed_instance`, but `ci2` is a running instance too, ie. its `cmE` and
+    /// `u` are arbitrary. It is the in-circuit version of `NIFS::fold_committed_instances`.
+    pub fn fold_relaxed_committed_instances(
+        r_bits: Vec<Boolean<CF2<C>>>,
+        cmT: GC,
+        ci1: CycleFoldCommittedInstanceVar<C, GC>,
+        ci2: CycleFoldCommittedInstanceVar<C, GC>,
+    ) -> Result<CycleFoldCommittedInstanceVar<C, GC>, SynthesisError> {
+        if r_bits.len() >= CF1::<C>::MODULUS_BIT_SIZE as usize {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let r_nonnat = NonNativeUintVar::from(&r_bits);
+        Ok(CycleFoldCommittedInstanceVar {
+            // cmE = ci1.cmE + r * cmT + r^2 * ci2.cmE = ci1.cmE + r * (cmT + r * ci2.cmE)
+            cmE: (cmT + ci2.cmE.scalar_mul_le(r_bits.iter())?).scalar_mul_le(r_bits.iter())?
+                + ci1.cmE,
+            cmW: ci1.cmW + ci2.cmW.scalar_mul_le(r_bits.iter())?,
+            u: ci1
+                .u
+                .add_no_align(&r_nonnat.mul_no_align(&ci2.u)?)?
+                .modulo::<CF1<C>>()?,
+            x: ci1
+                .x
+                .iter()
+                .zip(ci2.x)
+                .map(|(a, b)| {
+                    a.add_no_align(&r_nonnat.mul_no_align(&b)?)?
+                        .modulo::<CF1<C>>()
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+
     pub fn verify(
         // assumes that r_bits is equal to r_nonnat just that in a different format
         r_bits: Vec<Boolean<CF2<C>>>,
@@ -381,8 +439,11 @@ where
     GC: CurveVar<C, CF2<C>> + ToConstraintFieldGadget<CF2<C>>,
     C::BaseField: PrimeField + Absorb,
 {
+    /// Derives the CycleFold NIFS challenge as `challenge_bit_length` bits, which should be
+    /// [`CycleFoldConfig::CHALLENGE_BIT_LENGTH`] of the CycleFold config in use.
     pub fn get_challenge_native<T: Transcript<C::BaseField>>(
         transcript: &mut T,
+        challenge_bit_length: usize,
         pp_hash: C::BaseField, // public params hash
         U_i: CycleFoldCommittedInstance<C>,
         u_i: CycleFoldCommittedInstance<C>,
@@ -392,12 +453,13 @@ where
         transcript.absorb_nonnative(&U_i);
         transcript.absorb_nonnative(&u_i);
         transcript.absorb_point(&cmT);
-        transcript.squeeze_bits(NOVA_N_BITS_RO)
+        transcript.squeeze_bits(challenge_bit_length)
     }
 
     // compatible with the native get_challenge_native
     pub fn get_challenge_gadget<S: CryptographicSponge, T: TranscriptVar<C::BaseField, S>>(
         transcript: &mut T,
+        challenge_bit_length: usize,
         pp_hash: FpVar<C::BaseField>, // public params hash
         U_i_vec: Vec<FpVar<C::BaseField>>,
         u_i: CycleFoldCommittedInstanceVar<C, GC>,
@@ -407,7 +469,7 @@ where
         transcript.absorb(&U_i_vec)?;
         transcript.absorb_nonnative(&u_i)?;
         transcript.absorb_point(&cmT)?;
-        transcript.squeeze_bits(NOVA_N_BITS_RO)
+        transcript.squeeze_bits(challenge_bit_length)
     }
 }
 
@@ -415,10 +477,17 @@ where
 /// according to the folding scheme we are working with.
 pub trait CycleFoldConfig {
     /// `N_INPUT_POINTS` specifies the number of input points that are folded in
-    /// [`CycleFoldCircuit`] via random linear combinations.
+    /// [`CycleFoldCircuit`] via random linear combinations. It must be at least 1, which is
+    /// checked when the circuit is built natively and when its constraints are generated (thus
+    /// also at the preprocessing of the folding scheme).
     const N_INPUT_POINTS: usize;
     /// `RANDOMNESS_BIT_LENGTH` is the (maximum) bit length of randomness `r`.
     const RANDOMNESS_BIT_LENGTH: usize;
+    /// `CHALLENGE_BIT_LENGTH` is the bit length of the challenge used to fold the CycleFold
+    /// instances. It must be non-zero and smaller than the modulus bit size of both `C`'s
+    /// scalar field (where it is squeezed from the transcript) and `C`'s base field (where it is
+    /// used as a scalar), see [`CycleFoldConfig::check_challenge_bit_length`].
+    const CHALLENGE_BIT_LENGTH: usize = NOVA_N_BITS_RO;
     /// `FIELD_CAPACITY` is the maximum number of bits that can be stored in a
     /// field element.
     ///
@@ -439,6 +508,61 @@ pub trait CycleFoldConfig {
 
     type F: Field;
     type C: CurveGroup<BaseField = Self::F>;
+
+    /// Checks that `CHALLENGE_BIT_LENGTH` is within the bounds allowed by both fields of `C`.
+    /// Called at the preprocessing of the folding schemes.
+    fn check_challenge_bit_length() -> Result<(), Error> {
+        let max = CF1::<Self::C>::MODULUS_BIT_SIZE.min(CF2::<Self::C>::MODULUS_BIT_SIZE) as usize;
+        if Self::CHALLENGE_BIT_LENGTH == 0 || Self::CHALLENGE_BIT_LENGTH >= max {
+            return Err(Error::ParamsMismatch(format!(
+                "CycleFold challenge bit length {} is not in [1, {})",
+                Self::CHALLENGE_BIT_LENGTH,
+                max
+            )));
+        }
+        Ok(())
+    }
+
+    /// Computes in-circuit, over the scalar field of `C`, the public inputs `x` of the
+    /// [`CycleFoldCircuit`] that folds `points` into `p_folded` with the randomness `r_bits`,
+    /// following the same layout as [`CycleFoldCircuit::new`], ie.
+    /// `[r, p_0, p_1, ..., p_{n-1}, p_folded]`.
+    fn x_gadget(
+        r_bits: &[Boolean<CF1<Self::C>>],
+        points: &[NonNativeAffineVar<Self::C>],
+        p_folded: &NonNativeAffineVar<Self::C>,
+    ) -> Result<Vec<NonNativeUintVar<CF1<Self::C>>>, SynthesisError> {
+        if Self::N_INPUT_POINTS == 0
+            || points.len() != Self::N_INPUT_POINTS
+            || r_bits.len() != Self::RANDOMNESS_BIT_LENGTH
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let r = r_bits.chunks(Self::FIELD_CAPACITY).map(|bits| {
+            let mut bits = bits.to_vec();
+            bits.resize(CF2::<Self::C>::MODULUS_BIT_SIZE as usize, Boolean::FALSE);
+            NonNativeUintVar::from(&bits)
+        });
+        Ok(r.chain(
+            points
+                .iter()
+                .chain([p_folded])
+                .flat_map(|p| [p.x.clone(), p.y.clone()]),
+        )
+        .collect())
+    }
+
+    /// Computes in-circuit `p * r`, which is used by the [`CycleFoldCircuit`] to fold the points.
+    /// By default this is the double-and-add of [`CurveVar::scalar_mul_le`], but configs over
+    /// curves with an efficient endomorphism can use the GLV method instead, see
+    /// [`GLVCycleFoldConfig`](super::glv::GLVCycleFoldConfig).
+    fn scalar_mul_gadget<GC>(p: &GC, r_bits: &[Boolean<Self::F>]) -> Result<GC, SynthesisError>
+    where
+        Self::F: PrimeField,
+        GC: CurveVar<Self::C, Self::F> + ToConstraintFieldGadget<Self::F>,
+    {
+        p.scalar_mul_le(r_bits.iter())
+    }
 }
 
 /// CycleFoldCircuit contains the constraints that check the correct fold of the committed
@@ -468,6 +592,54 @@ impl<CFG: CycleFoldConfig, GC: CurveVar<CFG::C, CFG::F>> CycleFoldCircuit<CFG, G
     }
 }
 
+impl<CFG: CycleFoldConfig, GC: CurveVar<CFG::C, CFG::F>> CycleFoldCircuit<CFG, GC>
+where
+    CFG::F: PrimeField,
+{
+    /// Builds the CycleFoldCircuit that folds the given `points` with the randomness `r_bits`,
+    /// computing natively the public inputs `x` with the same layout as in-circuit, ie.
+    /// `[r, p_0, p_1, ..., p_{n-1}, p_folded]`.
+    pub fn new(r_bits: Vec<bool>, points: Vec<CFG::C>) -> Result<Self, Error> {
+        let p_folded = Self::fold_points_native(&r_bits, &points)?;
+        let x = [
+            r_bits
+                .chunks(CFG::FIELD_CAPACITY)
+                .map(<CFG::F as PrimeField>::BigInt::from_bits_le)
+                .map(CFG::F::from)
+                .collect::<Vec<_>>(),
+            points.iter().flat_map(get_cm_coordinates).collect(),
+            get_cm_coordinates(&p_folded),
+        ]
+        .concat();
+        Ok(Self {
+            _gc: PhantomData,
+            r_bits: Some(r_bits),
+            points: Some(points),
+            x: Some(x),
+        })
+    }
+
+    /// Computes natively the random linear combination of the points done in-circuit by the
+    /// CycleFoldCircuit, ie. `p_folded = p_0 + r * p_1 + r^2 * p_2 + ... + r^{n-1} * p_{n-1}`.
+    pub fn fold_points_native(r_bits: &[bool], points: &[CFG::C]) -> Result<CFG::C, Error> {
+        if r_bits.len() != CFG::RANDOMNESS_BIT_LENGTH {
+            return Err(Error::NotExpectedLength(
+                r_bits.len(),
+                CFG::RANDOMNESS_BIT_LENGTH,
+            ));
+        }
+        if CFG::N_INPUT_POINTS == 0 || points.len() != CFG::N_INPUT_POINTS {
+            return Err(Error::NotExpectedLength(points.len(), CFG::N_INPUT_POINTS));
+        }
+        let r = <CFG::C as Group>::ScalarField::from_bigint(BigInteger::from_bits_le(r_bits))
+            .ok_or(Error::OutOfBounds)?;
+        Ok(points
+            .iter()
+            .rev()
+            .fold(CFG::C::zero(), |acc, p_i| acc * r + p_i))
+    }
+}
+
 impl<CFG: CycleFoldConfig, GC: CurveVar<CFG::C, CFG::F>> ConstraintSynthesizer<CFG::F>
     for CycleFoldCircuit<CFG, GC>
 where
@@ -486,10 +658,11 @@ where
                 .unwrap_or(vec![CFG::C::zero(); CFG::N_INPUT_POINTS]))
         })?;
 
-        #[cfg(test)]
+        if CFG::N_INPUT_POINTS == 0
+            || points.len() != CFG::N_INPUT_POINTS
+            || r_bits.len() != CFG::RANDOMNESS_BIT_LENGTH
         {
-            assert_eq!(CFG::N_INPUT_POINTS, points.len());
-            assert_eq!(CFG::RANDOMNESS_BIT_LENGTH, r_bits.len());
+            return Err(SynthesisError::Unsatisfiable);
         }
 
         // Fold the original points of the instances natively in CycleFold.
@@ -503,14 +676,15 @@ where
         // P_folded = (((P_{n-1} * r + P_{n-2}) * r + P_{n-3})... ) * r + P_0
         let mut p_folded: GC = points[CFG::N_INPUT_POINTS - 1].clone();
         for i in (0..CFG::N_INPUT_POINTS - 1).rev() {
-            p_folded = p_folded.scalar_mul_le(r_bits.iter())? + points[i].clone();
+            p_folded = CFG::scalar_mul_gadget(&p_folded, &r_bits)? + points[i].clone();
         }
 
         let x = Vec::<FpVar<CFG::F>>::new_input(cs.clone(), || {
             Ok(self.x.unwrap_or(vec![CFG::F::zero(); CFG::IO_LEN]))
         })?;
-        #[cfg(test)]
-        assert_eq!(x.len(), CFG::IO_LEN); // non-constrained sanity check
+        if x.len() != CFG::IO_LEN {
+            return Err(SynthesisError::Unsatisfiable);
+        }
 
         // Check that the points coordinates are placed as the public input x:
         // In Nova, this is: x == [r, p1, p2, p3] (wheere p3 is the p_folded).
@@ -518,7 +692,7 @@ where
         // computed_x = [r, p_0, p_1, p_2, ..., p_n, p_folded],
         // where each p_i is in fact p_i.to_constraint_field()
         let r_fp = r_bits
-            .chunks(CFG::F::MODULUS_BIT_SIZE as usize - 1)
+            .chunks(CFG::FIELD_CAPACITY)
             .map(Boolean::le_bits_to_fp_var)
             .collect::<Result<Vec<_>, _>>()?;
         let points_aux: Vec<FpVar<CFG::F>> = points
@@ -541,6 +715,15 @@ where
     }
 }
 
+/// A CycleFold fold `(U, u, cmT, r, U')`, where `U'` is claimed to be `NIFS.V(r, U, u, cmT)`.
+pub type CycleFoldFold<C> = (
+    CycleFoldCommittedInstance<C>,
+    CycleFoldCommittedInstance<C>,
+    C,
+    <C as Group>::ScalarField,
+    CycleFoldCommittedInstance<C>,
+);
+
 /// CycleFoldNIFS is a wrapper on top of Nova's NIFS, which just replaces the `prove` and `verify`
 /// methods to use a different ChallengeGadget, but internally reuses the other Nova's NIFS
 /// methods.
@@ -603,6 +786,67 @@ where
             ),
         )
     }
+
+    /// Verifies a batch of CycleFold folds, ie. that `U' = NIFS.V(r, U, u, cmT)` for each of them.
+    /// The linear relations of `u` and `x` are checked individually, while the group equations
+    /// of `cmE` and `cmW` of all the folds are combined with the powers of a random challenge
+    /// into a single multi-scalar multiplication.
+    ///
+    /// If the batch does not verify, the folds are verified individually, and
+    /// `Error::BatchVerificationFail` is returned with the index of the first invalid one.
+    pub fn verify_batch(mut rng: impl RngCore, folds: &[CycleFoldFold<C2>]) -> Result<(), Error> {
+        if folds.is_empty() {
+            return Err(Error::Empty);
+        }
+
+        for (j, (U_i, u_i, _, r, U_i1)) in folds.iter().enumerate() {
+            if U_i.x.len() != u_i.x.len()
+                || U_i.x.len() != U_i1.x.len()
+                || U_i1.u != U_i.u + *r * u_i.u
+                || U_i
+                    .x
+                    .iter()
+                    .zip(&u_i.x)
+                    .zip(&U_i1.x)
+                    .any(|((a, b), c)| *c != *a + *r * b)
+            {
+                return Err(Error::BatchVerificationFail(j));
+            }
+        }
+
+        // For each fold, U'.cmE - U.cmE - r * cmT - r^2 * u.cmE = 0 and
+        // U'.cmW - U.cmW - r * u.cmW = 0, which are combined with the powers of c
+        let c = C2::ScalarField::rand(&mut rng);
+        let mut c_k = C2::ScalarField::one();
+        let mut bases = Vec::with_capacity(7 * folds.len());
+        let mut scalars = Vec::with_capacity(7 * folds.len());
+        for (U_i, u_i, cmT, r, U_i1) in folds {
+            let c_k1 = c_k * c;
+            bases.extend([U_i1.cmE, U_i.cmE, *cmT, u_i.cmE, U_i1.cmW, U_i.cmW, u_i.cmW]);
+            scalars.extend([
+                c_k,
+                -c_k,
+                -c_k * r,
+                -c_k * r.square(),
+                c_k1,
+                -c_k1,
+                -c_k1 * r,
+            ]);
+            c_k = c_k1 * c;
+        }
+        if C2::msm_unchecked(&C2::normalize_batch(&bases), &scalars).is_zero() {
+            return Ok(());
+        }
+
+        // find the invalid fold
+        for (j, (U_i, u_i, cmT, r, U_i1)) in folds.iter().enumerate() {
+            if Self::verify(*r, U_i, u_i, cmT)? != *U_i1 {
+                return Err(Error::BatchVerificationFail(j));
+            }
+        }
+        // the folds are valid individually, thus also their combination, so this is not reached
+        Ok(())
+    }
 }
 
 /// Folds the given cyclefold circuit and its instances. This method is abstracted from any folding
@@ -616,7 +860,6 @@ pub fn fold_cyclefold_circuit<CFG, C1, GC1, C2, GC2, CS2, const H: bool>(
     pp_hash: C1::ScalarField,               // public params hash
     cf_W_i: CycleFoldWitness<C2>,           // witness of the running instance
     cf_U_i: CycleFoldCommittedInstance<C2>, // running instance
-    cf_u_i_x: Vec<C2::ScalarField>,
     cf_circuit: CycleFoldCircuit<CFG, GC1>,
     mut rng: impl RngCore,
 ) -> Result<
@@ -643,6 +886,14 @@ where
     <C2 as Group>::ScalarField: Absorb,
     C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
 {
+    let cf_u_i_x = cf_circuit
+        .x
+        .clone()
+        .ok_or(Error::MissingValue("cf_circuit.x".to_string()))?;
+    if cf_u_i_x.len() != CFG::IO_LEN {
+        return Err(Error::NotExpectedLength(cf_u_i_x.len(), CFG::IO_LEN));
+    }
+
     let cs2 = ConstraintSystem::<C1::BaseField>::new_ref();
     cf_circuit.generate_constraints(cs2.clone())?;
 
@@ -652,9 +903,6 @@ where
         return Err(Error::NotEqual);
     }
 
-    #[cfg(test)]
-    assert_eq!(cf_x_i.len(), CFG::IO_LEN);
-
     // fold cyclefold instances
     let cf_w_i = CycleFoldWitness::<C2>::new::<H>(cf_w_i.clone(), cf_r1cs.A.n_rows, &mut rng);
     let cf_u_i: CycleFoldCommittedInstance<C2> =
@@ -673,19 +921,18 @@ where
 
     let cf_r_bits = CycleFoldChallengeGadget::<C2, GC2>::get_challenge_native(
         transcript,
+        CFG::CHALLENGE_BIT_LENGTH,
         pp_hash,
         cf_U_i.clone(),
         cf_u_i.clone(),
         cf_cmT,
     );
     let cf_r_Fq = C1::BaseField::from_bigint(BigInteger::from_bits_le(&cf_r_bits))
-        .expect("cf_r_bits out of bounds");
+        .ok_or(Error::OutOfBounds)?;
 
     let (cf_W_i1, cf_U_i1) = CycleFoldNIFS::<C1, C2, GC2, CS2, H>::prove(
         cf_r_Fq, &cf_W_i, &cf_U_i, &cf_w_i, &cf_u_i, &cf_T, cf_cmT,
     )?;
-    let cf_r_Fq = C1::BaseField::from_bigint(BigInteger::from_bits_le(&cf_r_bits))
-        .expect("cf_r_bits out of bounds");
     Ok((cf_w_i, cf_u_i, cf_W_i1, cf_U_i1, cf_cmT, cf_r_Fq))
 }
 
@@ -696,21 +943,26 @@ pub mod tests {
         constraints::CryptographicSpongeVar,
         poseidon::{constraints::PoseidonSpongeVar, PoseidonSponge},
     };
+    use ark_grumpkin::Projective as Projective2;
     use ark_r1cs_std::R1CSVar;
     use ark_std::{One, UniformRand};
+    use num_bigint::BigUint;
 
     use super::*;
     use crate::commitment::pedersen::Pedersen;
     use crate::folding::nova::CommittedInstance;
+    use crate::folding::traits::Dummy;
     use crate::transcript::poseidon::poseidon_canonical_config;
-    use crate::utils::get_cm_coordinates;
 
-    struct TestCycleFoldConfig<C: CurveGroup, const N: usize> {
+    struct TestCycleFoldConfig<C: CurveGroup, const N: usize, const B: usize = NOVA_N_BITS_RO> {
         _c: PhantomData<C>,
     }
 
-    impl<C: CurveGroup, const N: usize> CycleFoldConfig for TestCycleFoldConfig<C, N> {
+    impl<C: CurveGroup, const N: usize, const B: usize> CycleFoldConfig
+        for TestCycleFoldConfig<C, N, B>
+    {
         const RANDOMNESS_BIT_LENGTH: usize = NOVA_N_BITS_RO;
+        const CHALLENGE_BIT_LENGTH: usize = B;
         const N_INPUT_POINTS: usize = N;
         type C = C;
         type F = C::BaseField;
@@ -781,6 +1033,105 @@ pub mod tests {
         assert!(cs.is_satisfied().unwrap());
     }
 
+    fn test_cyclefold_config_n_points<const N: usize>() {
+        let mut rng = ark_std::test_rng();
+
+        let points: Vec<Projective> = std::iter::repeat_with(|| Projective::rand(&mut rng))
+            .take(N)
+            .collect();
+        let r_bits = Fr::rand(&mut rng).into_bigint().to_bits_le()[..NOVA_N_BITS_RO].to_vec();
+        let r = Fr::from_bigint(BigInteger::from_bits_le(&r_bits)).unwrap();
+
+        // build the circuit natively, and check the folded point
+        let cf_circuit = CycleFoldCircuit::<TestCycleFoldConfig<Projective, N>, GVar>::new(
+            r_bits.clone(),
+            points.clone(),
+        )
+        .unwrap();
+        let x = cf_circuit.x.clone().unwrap();
+        assert_eq!(x.len(), TestCycleFoldConfig::<Projective, N>::IO_LEN);
+        let p_folded =
+            CycleFoldCircuit::<TestCycleFoldConfig<Projective, N>, GVar>::fold_points_native(
+                &r_bits, &points,
+            )
+            .unwrap();
+        let mut expected = Projective::zero();
+        let mut r_i = Fr::one();
+        for p_i in points.iter() {
+            expected += *p_i * r_i;
+            r_i *= r;
+        }
+        assert_eq!(p_folded, expected);
+        assert_eq!(x[x.len() - 2..], get_cm_coordinates(&p_folded));
+
+        // the in-circuit fold of the points matches the natively computed one
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        cf_circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // the x computed in the primary curve's constraint field matches the native one
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let r_bitsVar = Vec::<Boolean<Fr>>::new_witness(cs.clone(), || Ok(r_bits.clone())).unwrap();
+        let pointsVar =
+            Vec::<NonNativeAffineVar<Projective>>::new_witness(cs.clone(), || Ok(points.clone()))
+                .unwrap();
+        let p_foldedVar = NonNativeAffineVar::new_witness(cs.clone(), || Ok(p_folded)).unwrap();
+        let xVar =
+            TestCycleFoldConfig::<Projective, N>::x_gadget(&r_bitsVar, &pointsVar, &p_foldedVar)
+                .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(
+            xVar.iter()
+                .map(|x_i| x_i.value().unwrap())
+                .collect::<Vec<_>>(),
+            x.iter().map(|x_i| BigUint::from(*x_i)).collect::<Vec<_>>()
+        );
+
+        // a wrong number of points or of randomness bits is rejected
+        assert!(
+            CycleFoldCircuit::<TestCycleFoldConfig<Projective, N>, GVar>::new(
+                r_bits.clone(),
+                points[1..].to_vec()
+            )
+            .is_err()
+        );
+        assert!(
+            CycleFoldCircuit::<TestCycleFoldConfig<Projective, N>, GVar>::new(
+                r_bits[1..].to_vec(),
+                points
+            )
+            .is_err()
+        );
+        assert!(TestCycleFoldConfig::<Projective, N>::x_gadget(
+            &r_bitsVar,
+            &pointsVar[1..],
+            &p_foldedVar
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_cyclefold_config() {
+        test_cyclefold_config_n_points::<2>();
+        test_cyclefold_config_n_points::<3>();
+        test_cyclefold_config_n_points::<5>();
+
+        // a config without input points is rejected both natively and at the circuit generation
+        assert!(
+            CycleFoldCircuit::<TestCycleFoldConfig<Projective, 0>, GVar>::new(
+                vec![false; NOVA_N_BITS_RO],
+                vec![]
+            )
+            .is_err()
+        );
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        assert!(
+            CycleFoldCircuit::<TestCycleFoldConfig<Projective, 0>, GVar>::empty()
+                .generate_constraints(cs)
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_nifs_full_gadget() {
         let mut rng = ark_std::test_rng();
@@ -813,33 +1164,108 @@ pub mod tests {
             &cmT,
         )
         .unwrap();
+        assert!(check_nifs_full_gadget(r_bits, &cmT, &ci1, &ci2, &ci3).unwrap());
+
+        // full-width challenge, ie. as many bits as both fields can hold
+        let n_bits = Fr::MODULUS_BIT_SIZE.min(Fq::MODULUS_BIT_SIZE) as usize - 1;
+        let r_bits = Fr::rand(&mut rng).into_bigint().to_bits_le()[..n_bits].to_vec();
+        let r = Fr::from_bigint(BigInteger::from_bits_le(&r_bits)).unwrap();
+        let ci3 =
+            NIFS::<Projective, Pedersen<Projective>, PoseidonSponge<Fr>>::fold_committed_instances(
+                r, &ci1, &ci2, &cmT,
+            );
+        assert!(check_nifs_full_gadget(r_bits, &cmT, &ci1, &ci2, &ci3).unwrap());
+
+        // a challenge that does not fit in the scalar field is rejected
+        let r_bits = vec![true; Fr::MODULUS_BIT_SIZE as usize];
+        assert!(check_nifs_full_gadget(r_bits, &cmT, &ci1, &ci2, &ci3).is_err());
+    }
+
+    #[test]
+    fn test_nifs_full_gadget_relaxed() {
+        let mut rng = ark_std::test_rng();
+        let [ci1, ci2] = [(); 2].map(|_| CommittedInstance::<Projective> {
+            cmE: Projective::rand(&mut rng),
+            u: Fr::rand(&mut rng),
+            cmW: Projective::rand(&mut rng),
+            x: vec![Fr::rand(&mut rng); 2],
+        });
+        let cmT = Projective::rand(&mut rng);
+        let r_bits = Fr::rand(&mut rng).into_bigint().to_bits_le()[..NOVA_N_BITS_RO].to_vec();
+        let r = Fr::from_bigint(BigInteger::from_bits_le(&r_bits)).unwrap();
+        let ci3 =
+            NIFS::<Projective, Pedersen<Projective>, PoseidonSponge<Fr>>::fold_committed_instances(
+                r, &ci1, &ci2, &cmT,
+            );
 
         let cs = ConstraintSystem::<Fq>::new_ref();
         let r_bitsVar = Vec::<Boolean<Fq>>::new_witness(cs.clone(), || Ok(r_bits)).unwrap();
+        let [ci1Var, ci2Var] = [ci1, ci2].map(|ci| {
+            CycleFoldCommittedInstanceVar::<Projective, GVar>::new_witness(cs.clone(), || Ok(ci))
+                .unwrap()
+        });
+        let cmTVar = GVar::new_witness(cs.clone(), || Ok(cmT)).unwrap();
+        let ci3Var = NIFSFullGadget::<Projective, GVar>::fold_relaxed_committed_instances(
+            r_bitsVar, cmTVar, ci1Var, ci2Var,
+        )
+        .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(ci3Var.cmE.value().unwrap(), ci3.cmE);
+        assert_eq!(ci3Var.cmW.value().unwrap(), ci3.cmW);
+        assert_eq!(ci3Var.u.value().unwrap(), BigUint::from(ci3.u));
+        assert_eq!(
+            ci3Var.x.value().unwrap(),
+            ci3.x.into_iter().map(BigUint::from).collect::<Vec<_>>()
+        );
+    }
+
+    /// checks in-circuit that `ci3` is the folding of `ci1` and `ci2` with the given `r_bits`,
+    /// returning whether the constraints are satisfied
+    fn check_nifs_full_gadget(
+        r_bits: Vec<bool>,
+        cmT: &Projective,
+        ci1: &CommittedInstance<Projective>,
+        ci2: &CommittedInstance<Projective>,
+        ci3: &CommittedInstance<Projective>,
+    ) -> Result<bool, SynthesisError> {
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let r_bitsVar = Vec::<Boolean<Fq>>::new_witness(cs.clone(), || Ok(r_bits))?;
         let ci1Var =
             CycleFoldCommittedInstanceVar::<Projective, GVar>::new_witness(cs.clone(), || {
                 Ok(ci1.clone())
-            })
-            .unwrap();
+            })?;
         let ci2Var =
             CycleFoldCommittedInstanceVar::<Projective, GVar>::new_witness(cs.clone(), || {
                 Ok(ci2.clone())
-            })
-            .unwrap();
+            })?;
         let ci3Var =
             CycleFoldCommittedInstanceVar::<Projective, GVar>::new_witness(cs.clone(), || {
                 Ok(ci3.clone())
-            })
-            .unwrap();
-        let cmTVar = GVar::new_witness(cs.clone(), || Ok(cmT)).unwrap();
+            })?;
+        let cmTVar = GVar::new_witness(cs.clone(), || Ok(*cmT))?;
 
-        NIFSFullGadget::<Projective, GVar>::verify(r_bitsVar, cmTVar, ci1Var, ci2Var, ci3Var)
-            .unwrap();
-        assert!(cs.is_satisfied().unwrap());
+        NIFSFullGadget::<Projective, GVar>::verify(r_bitsVar, cmTVar, ci1Var, ci2Var, ci3Var)?;
+        cs.is_satisfied()
     }
 
     #[test]
     fn test_cyclefold_challenge_gadget() {
+        // 128-bit and full-width challenges
+        test_cyclefold_challenge_gadget_n_bits(NOVA_N_BITS_RO);
+        test_cyclefold_challenge_gadget_n_bits(
+            Fr::MODULUS_BIT_SIZE.min(Fq::MODULUS_BIT_SIZE) as usize - 1,
+        );
+    }
+
+    #[test]
+    fn test_check_challenge_bit_length() {
+        assert!(TestCycleFoldConfig::<Projective, 2>::check_challenge_bit_length().is_ok());
+        assert!(TestCycleFoldConfig::<Projective, 2, 253>::check_challenge_bit_length().is_ok());
+        assert!(TestCycleFoldConfig::<Projective, 2, 0>::check_challenge_bit_length().is_err());
+        assert!(TestCycleFoldConfig::<Projective, 2, 254>::check_challenge_bit_length().is_err());
+    }
+
+    fn test_cyclefold_challenge_gadget_n_bits(n_bits: usize) {
         let mut rng = ark_std::test_rng();
         let poseidon_config = poseidon_canonical_config::<Fq>();
         let mut transcript = PoseidonSponge::<Fq>::new(&poseidon_config);
@@ -866,6 +1292,7 @@ pub mod tests {
         let pp_hash = Fq::from(42u32); // only for test
         let r_bits = CycleFoldChallengeGadget::<Projective, GVar>::get_challenge_native(
             &mut transcript,
+            n_bits,
             pp_hash,
             U_i.clone(),
             u_i.clone(),
@@ -890,6 +1317,7 @@ pub mod tests {
         let pp_hashVar = FpVar::<Fq>::new_witness(cs.clone(), || Ok(pp_hash)).unwrap();
         let r_bitsVar = CycleFoldChallengeGadget::<Projective, GVar>::get_challenge_gadget(
             &mut transcript_var,
+            n_bits,
             pp_hashVar,
             U_iVar.to_native_sponge_field_elements().unwrap(),
             u_iVar,
@@ -902,23 +1330,13 @@ pub mod tests {
         let rVar = Boolean::le_bits_to_fp_var(&r_bitsVar).unwrap();
         let r = Fq::from_bigint(BigInteger::from_bits_le(&r_bits)).unwrap();
         assert_eq!(rVar.value().unwrap(), r);
+        assert_eq!(r_bits.len(), n_bits);
         assert_eq!(r_bitsVar.value().unwrap(), r_bits);
     }
 
-    #[test]
-    fn test_cyclefold_hash_gadget() {
-        let mut rng = ark_std::test_rng();
+    fn check_cyclefold_hash_gadget(U_i: CycleFoldCommittedInstance<Projective>) -> Fq {
         let poseidon_config = poseidon_canonical_config::<Fq>();
         let sponge = PoseidonSponge::<Fq>::new(&poseidon_config);
-
-        let U_i = CycleFoldCommittedInstance::<Projective> {
-            cmE: Projective::rand(&mut rng),
-            u: Fr::rand(&mut rng),
-            cmW: Projective::rand(&mut rng),
-            x: std::iter::repeat_with(|| Fr::rand(&mut rng))
-                .take(TestCycleFoldConfig::<Projective, 2>::IO_LEN)
-                .collect(),
-        };
         let pp_hash = Fq::from(42u32); // only for test
         let h = U_i.hash_cyclefold(&sponge, pp_hash);
 
@@ -929,7 +1347,7 @@ pub mod tests {
             })
             .unwrap();
         let pp_hashVar = FpVar::<Fq>::new_witness(cs.clone(), || Ok(pp_hash)).unwrap();
-        let (hVar, _) = U_iVar
+        let (hVar, U_vec) = U_iVar
             .hash(
                 &PoseidonSpongeVar::new(cs.clone(), &poseidon_config),
                 pp_hashVar,
@@ -938,5 +1356,141 @@ pub mod tests {
         hVar.enforce_equal(&FpVar::new_witness(cs.clone(), || Ok(h)).unwrap())
             .unwrap();
         assert!(cs.is_satisfied().unwrap());
+
+        // the absorbed elements match the native ones, and are fewer than the limbs of the
+        // nonnative fields and the coordinates of the points
+        let U_vec_native = U_i.to_native_sponge_field_elements_as_vec();
+        assert_eq!(U_vec.value().unwrap(), U_vec_native);
+        assert!(U_vec_native.len() < 2 * (U_i.x.len() + 1) + 4);
+        h
+    }
+
+    #[test]
+    fn test_cyclefold_hash_gadget() {
+        let mut rng = ark_std::test_rng();
+        let io_len = TestCycleFoldConfig::<Projective, 2>::IO_LEN;
+
+        let U_i = CycleFoldCommittedInstance::<Projective> {
+            cmE: Projective::rand(&mut rng),
+            u: Fr::rand(&mut rng),
+            cmW: Projective::rand(&mut rng),
+            x: std::iter::repeat_with(|| Fr::rand(&mut rng))
+                .take(io_len)
+                .collect(),
+        };
+        let h = check_cyclefold_hash_gadget(U_i.clone());
+
+        // the dummy instance, and an instance with cmE at infinity (as in a fresh instance)
+        check_cyclefold_hash_gadget(CycleFoldCommittedInstance::dummy(io_len));
+        let h_inf = check_cyclefold_hash_gadget(CycleFoldCommittedInstance {
+            cmE: Projective::zero(),
+            ..U_i.clone()
+        });
+        assert_ne!(h_inf, h);
+
+        // the hash binds the sign of the y coordinates
+        let h_neg = check_cyclefold_hash_gadget(CycleFoldCommittedInstance {
+            cmW: -U_i.cmW,
+            ..U_i.clone()
+        });
+        assert_ne!(h_neg, h);
+    }
+
+    #[test]
+    fn test_cyclefold_hash_gadget_constraints() {
+        let mut rng = ark_std::test_rng();
+        let io_len = TestCycleFoldConfig::<Projective, 2>::IO_LEN;
+        let U_i = CycleFoldCommittedInstance::<Projective> {
+            cmE: Projective::rand(&mut rng),
+            u: Fr::rand(&mut rng),
+            cmW: Projective::rand(&mut rng),
+            x: std::iter::repeat_with(|| Fr::rand(&mut rng))
+                .take(io_len)
+                .collect(),
+        };
+        let poseidon_config = poseidon_canonical_config::<Fq>();
+
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let U_iVar =
+            CycleFoldCommittedInstanceVar::<Projective, GVar>::new_witness(cs.clone(), || {
+                Ok(U_i.clone())
+            })
+            .unwrap();
+        let pp_hashVar = FpVar::<Fq>::new_witness(cs.clone(), || Ok(Fq::from(42u32))).unwrap();
+        let sponge = PoseidonSpongeVar::new(cs.clone(), &poseidon_config);
+
+        let n = cs.num_constraints();
+        U_iVar.hash(&sponge, pp_hashVar.clone()).unwrap();
+        let n_compressed = cs.num_constraints() - n;
+
+        // absorbing the limbs of the nonnative `u` and `x` and the coordinates of the points, as
+        // done before the compression, takes more constraints
+        let n = cs.num_constraints();
+        let mut sponge = sponge;
+        sponge.absorb(&pp_hashVar).unwrap();
+        for v in [&U_iVar.u].into_iter().chain(&U_iVar.x) {
+            sponge.absorb(&v.to_constraint_field().unwrap()).unwrap();
+        }
+        sponge.absorb_point(&U_iVar.cmE).unwrap();
+        sponge.absorb_point(&U_iVar.cmW).unwrap();
+        sponge.squeeze_field_elements(1).unwrap();
+        let n_uncompressed = cs.num_constraints() - n;
+
+        assert!(cs.is_satisfied().unwrap());
+        assert!(
+            n_compressed < n_uncompressed,
+            "{} >= {}",
+            n_compressed,
+            n_uncompressed
+        );
+    }
+
+    #[test]
+    fn test_cyclefold_nifs_verify_batch() {
+        type CFNIFS = CycleFoldNIFS<Projective2, Projective, GVar, Pedersen<Projective>>;
+        let mut rng = ark_std::test_rng();
+
+        let rand_instance = |rng: &mut dyn RngCore| CycleFoldCommittedInstance::<Projective> {
+            cmE: Projective::rand(rng),
+            u: Fr::rand(rng),
+            cmW: Projective::rand(rng),
+            x: std::iter::repeat_with(|| Fr::rand(rng))
+                .take(TestCycleFoldConfig::<Projective, 2>::IO_LEN)
+                .collect(),
+        };
+        let mut folds = (0..5)
+            .map(|_| {
+                let (U_i, u_i) = (rand_instance(&mut rng), rand_instance(&mut rng));
+                let (cmT, r) = (Projective::rand(&mut rng), Fr::rand(&mut rng));
+                let U_i1 = CFNIFS::verify(r, &U_i, &u_i, &cmT).unwrap();
+                (U_i, u_i, cmT, r, U_i1)
+            })
+            .collect::<Vec<_>>();
+        assert!(CFNIFS::verify_batch(&mut rng, &folds).is_ok());
+        assert!(matches!(
+            CFNIFS::verify_batch(&mut rng, &[]),
+            Err(Error::Empty)
+        ));
+
+        // a fold with a wrong commitment is found by the individual verification
+        let mut wrong_folds = folds.clone();
+        wrong_folds[3].4.cmE += Projective::generator();
+        assert!(matches!(
+            CFNIFS::verify_batch(&mut rng, &wrong_folds),
+            Err(Error::BatchVerificationFail(3))
+        ));
+        let mut wrong_folds = folds.clone();
+        wrong_folds[0].2 = Projective::rand(&mut rng);
+        assert!(matches!(
+            CFNIFS::verify_batch(&mut rng, &wrong_folds),
+            Err(Error::BatchVerificationFail(0))
+        ));
+
+        // a fold with a wrong public input
+        folds[1].4.x[2] += Fr::one();
+        assert!(matches!(
+            CFNIFS::verify_batch(&mut rng, &folds),
+            Err(Error::BatchVerificationFail(1))
+        ));
     }
 }
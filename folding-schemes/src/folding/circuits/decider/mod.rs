@@ -1,28 +1,41 @@
 use ark_crypto_primitives::sponge::{
-    poseidon::constraints::PoseidonSpongeVar, CryptographicSponge,
+    poseidon::constraints::PoseidonSpongeVar, Absorb, CryptographicSponge,
 };
-use ark_ec::CurveGroup;
+use ark_ec::{pairing::Pairing, CurveGroup};
 use ark_ff::PrimeField;
 use ark_poly::Polynomial;
+use ark_poly_commit::kzg10::VerifierKey;
 use ark_r1cs_std::{
+    boolean::Boolean,
+    eq::EqGadget,
     fields::{fp::FpVar, FieldVar},
+    groups::CurveVar,
+    pairing::PairingVar,
     poly::{domain::Radix2DomainVar, evaluations::univariate::EvaluationsVar},
     ToConstraintFieldGadget,
 };
 use ark_relations::r1cs::SynthesisError;
-use ark_std::log2;
+use ark_std::{log2, marker::PhantomData, string::ToString, vec::Vec};
 
 use crate::folding::traits::{CommittedInstanceOps, CommittedInstanceVarOps, Dummy, WitnessOps};
 use crate::transcript::{Transcript, TranscriptVar};
+use crate::utils::mle::evaluate_mle;
 use crate::utils::vec::poly_from_vec;
 use crate::Error;
-use crate::{arith::Arith, folding::circuits::CF1};
+use crate::{
+    arith::Arith,
+    folding::circuits::{CF1, CF2},
+};
 
 pub mod off_chain;
 pub mod on_chain;
 
 /// Gadget that computes the KZG challenges.
 /// It also offers the rust native implementation compatible with the gadget.
+///
+/// Each commitment is preceded in the transcript by its domain-separation label, usually given by
+/// `get_commitment_labels` of the committed instance, so that the challenges are bound to the
+/// position of the commitments in the protocol.
 pub struct KZGChallengesGadget {}
 
 impl KZGChallengesGadget {
@@ -32,14 +45,18 @@ impl KZGChallengesGadget {
         U: CommittedInstanceOps<C>,
     >(
         transcript: &mut T,
+        labels: &[&[u8]],
         U_i: &U,
-    ) -> Vec<CF1<C>> {
+    ) -> Result<Vec<CF1<C>>, Error> {
+        let commitments = U_i.get_commitments();
+        check_labels_len(labels, commitments.len())?;
         let mut challenges = vec![];
-        for cm in U_i.get_commitments() {
+        for (label, cm) in labels.iter().zip(commitments) {
+            transcript.absorb_label(label);
             transcript.absorb_nonnative(&cm);
             challenges.push(transcript.get_challenge());
         }
-        challenges
+        Ok(challenges)
     }
 
     pub fn get_challenges_gadget<
@@ -49,15 +66,92 @@ impl KZGChallengesGadget {
         U: CommittedInstanceVarOps<C>,
     >(
         transcript: &mut T,
+        labels: &[&[u8]],
         U_i: &U,
     ) -> Result<Vec<FpVar<CF1<C>>>, SynthesisError> {
+        let commitments = U_i.get_commitments();
+        check_labels_len(labels, commitments.len()).map_err(|_| SynthesisError::Unsatisfiable)?;
         let mut challenges = vec![];
-        for cm in U_i.get_commitments() {
+        for (label, cm) in labels.iter().zip(commitments) {
+            transcript.absorb_label(label)?;
             transcript.absorb(&cm.to_constraint_field()?)?;
             challenges.push(transcript.get_challenge()?);
         }
         Ok(challenges)
     }
+
+    /// Computes a single KZG challenge for all the commitments in `U_i`, so
+    /// that they can be opened at the same point with a single batched proof.
+    pub fn get_challenge_native<
+        C: CurveGroup,
+        T: Transcript<CF1<C>>,
+        U: CommittedInstanceOps<C>,
+    >(
+        transcript: &mut T,
+        labels: &[&[u8]],
+        U_i: &U,
+    ) -> Result<CF1<C>, Error> {
+        let commitments = U_i.get_commitments();
+        check_labels_len(labels, commitments.len())?;
+        for (label, cm) in labels.iter().zip(commitments) {
+            transcript.absorb_label(label);
+            transcript.absorb_nonnative(&cm);
+        }
+        Ok(transcript.get_challenge())
+    }
+
+    pub fn get_challenge_gadget<
+        C: CurveGroup,
+        S: CryptographicSponge,
+        T: TranscriptVar<CF1<C>, S>,
+        U: CommittedInstanceVarOps<C>,
+    >(
+        transcript: &mut T,
+        labels: &[&[u8]],
+        U_i: &U,
+    ) -> Result<FpVar<CF1<C>>, SynthesisError> {
+        let commitments = U_i.get_commitments();
+        check_labels_len(labels, commitments.len()).map_err(|_| SynthesisError::Unsatisfiable)?;
+        for (label, cm) in labels.iter().zip(commitments) {
+            transcript.absorb_label(label)?;
+            transcript.absorb(&cm.to_constraint_field()?)?;
+        }
+        transcript.get_challenge()
+    }
+
+    /// Computes the challenge `γ` used to batch the KZG openings at the same
+    /// point, after the claimed evaluations have been fixed.
+    pub fn get_batching_challenge_native<F: PrimeField + Absorb, T: Transcript<F>>(
+        transcript: &mut T,
+        evaluations: &[F],
+    ) -> F {
+        transcript.absorb(&evaluations);
+        transcript.get_challenge()
+    }
+
+    pub fn get_batching_challenge_gadget<
+        F: PrimeField,
+        S: CryptographicSponge,
+        T: TranscriptVar<F, S>,
+    >(
+        transcript: &mut T,
+        evaluations: &[FpVar<F>],
+    ) -> Result<FpVar<F>, SynthesisError> {
+        transcript.absorb(&evaluations)?;
+        transcript.get_challenge()
+    }
+}
+
+fn check_labels_len(labels: &[&[u8]], n_commitments: usize) -> Result<(), Error> {
+    if labels.len() != n_commitments {
+        return Err(Error::NotSameLength(
+            "labels.len()".to_string(),
+            labels.len(),
+            "commitments.len()".to_string(),
+            n_commitments,
+        ));
+    }
+    Ok(())
 }
 
 /// Gadget that interpolates the polynomial from the given vector and returns
@@ -92,6 +186,80 @@ impl EvalGadget {
         let evaluations_var = EvaluationsVar::from_vec_and_domain(v, domain, true);
         evaluations_var.interpolate_and_evaluate(point)
     }
+
+    /// Evaluates at `point` the multilinear extension of `v`, padded with zeros to
+    /// `2^point.len()` elements, as opened by the multilinear commitment schemes, see
+    /// `commitment::MultilinearCS`.
+    pub fn evaluate_mle_native<F: PrimeField>(v: &[F], point: &[F]) -> Result<F, Error> {
+        evaluate_mle(v, point)
+    }
+
+    pub fn evaluate_mle_gadget<F: PrimeField>(
+        v: &[FpVar<F>],
+        point: &[FpVar<F>],
+    ) -> Result<FpVar<F>, SynthesisError> {
+        if v.len() > 1 << point.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let mut v = v.to_vec();
+        v.resize(1 << point.len(), FpVar::zero());
+        for r in point {
+            v = v
+                .chunks(2)
+                .map(|pair| &pair[0] + r * (&pair[1] - &pair[0]))
+                .collect();
+        }
+        Ok(v[0].clone())
+    }
+
+    /// Evaluates at `point` the polynomial whose coefficients are `v`, as opened by IPA, see
+    /// `commitment::ipa::IPA`.
+    pub fn evaluate_coefficients_native<F: PrimeField>(v: &[F], point: F) -> F {
+        v.iter().rev().fold(F::zero(), |acc, c| acc * point + c)
+    }
+
+    pub fn evaluate_coefficients_gadget<F: PrimeField>(
+        v: &[FpVar<F>],
+        point: &FpVar<F>,
+    ) -> Result<FpVar<F>, SynthesisError> {
+        Ok(v.iter().rev().fold(FpVar::zero(), |acc, c| acc * point + c))
+    }
+}
+
+/// Gadget that verifies a KZG opening proof in-circuit, for a pairing-friendly curve `E` whose
+/// pairing is emulated natively by the `PairingVar` `P`, e.g. BLS12-377 inside BW6-761. This
+/// allows a wrapper circuit over the base field of `E` to check the KZG openings produced by the
+/// decider.
+/// It is compatible with the rust native `KZG::verify_with_challenge`.
+pub struct KZGVerifyGadget<E: Pairing, P: PairingVar<E, CF2<E::G1>>> {
+    _e: PhantomData<E>,
+    _p: PhantomData<P>,
+}
+
+impl<E: Pairing, P: PairingVar<E, CF2<E::G1>>> KZGVerifyGadget<E, P> {
+    /// Returns whether `proof` opens the commitment `cm` to `eval` at `point`, that is, whether
+    /// `e(cm - eval⋅G1, G2) == e(proof, τ⋅G2 - point⋅G2)`. The check is done as the single product
+    /// of pairings `e(cm - eval⋅G1 + point⋅proof, G2) ⋅ e(-proof, τ⋅G2) == 1`.
+    /// The verifier key is embedded into the circuit as constants, while `point` and `eval`, which
+    /// live in the scalar field of `E`, are given as their little-endian bits.
+    pub fn verify(
+        vk: &VerifierKey<E>,
+        cm: &P::G1Var,
+        point: &[Boolean<CF2<E::G1>>],
+        eval: &[Boolean<CF2<E::G1>>],
+        proof: &P::G1Var,
+    ) -> Result<Boolean<CF2<E::G1>>, SynthesisError> {
+        let g = P::G1Var::constant(vk.g.into());
+        let h = P::prepare_g2(&P::G2Var::constant(vk.h.into()))?;
+        let beta_h = P::prepare_g2(&P::G2Var::constant(vk.beta_h.into()))?;
+
+        let lhs = cm.clone() - g.scalar_mul_le(eval.iter())? + proof.scalar_mul_le(point.iter())?;
+        let pairing = P::product_of_pairings(
+            &[P::prepare_g1(&lhs)?, P::prepare_g1(&proof.negate()?)?],
+            &[h, beta_h],
+        )?;
+        pairing.is_eq(&P::GTVar::one())
+    }
 }
 
 /// This is a temporary workaround for step 6 (running NIFS.V for group elements
@@ -137,6 +305,18 @@ pub trait DeciderEnabledNIFS<
         proof: Option<Self::Proof>,
         randomness: Self::Randomness,
     ) -> Result<Vec<C>, Error>;
+
+    /// Computes in-circuit the point of `n_vars` variables at which the witness commitments of
+    /// the folded instance `U` are opened as multilinear polynomials, from `U` and the challenge
+    /// of step 7.1. Only the folding schemes whose witnesses are committed with a multilinear
+    /// commitment scheme implement it.
+    fn multilinear_opening_point_gadget(
+        _U: &RU::Var,
+        _challenge: &FpVar<CF1<C>>,
+        _n_vars: usize,
+    ) -> Result<Vec<FpVar<CF1<C>>>, SynthesisError> {
+        Err(SynthesisError::Unsatisfiable)
+    }
 }
 
 #[cfg(test)]
@@ -147,11 +327,12 @@ pub mod tests {
     use ark_pallas::{Fr, Projective};
     use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
     use ark_relations::r1cs::ConstraintSystem;
-    use ark_std::UniformRand;
+    use ark_std::{UniformRand, Zero};
 
     use super::*;
     use crate::folding::nova::{nifs::nova_circuits::CommittedInstanceVar, CommittedInstance};
     use crate::transcript::poseidon::poseidon_canonical_config;
+    use crate::utils::mle::point_from_challenge;
 
     // checks that the gadget and native implementations of the challenge computation match
     #[test]
@@ -168,7 +349,10 @@ pub mod tests {
         };
 
         // compute the challenge natively
-        let challenges = KZGChallengesGadget::get_challenges_native(&mut transcript, &U_i);
+        let labels = U_i.get_commitment_labels();
+        let challenges =
+            KZGChallengesGadget::get_challenges_native(&mut transcript.clone(), &labels, &U_i)
+                .unwrap();
 
         let cs = ConstraintSystem::<Fr>::new_ref();
         let U_iVar =
@@ -176,12 +360,92 @@ pub mod tests {
                 .unwrap();
         let mut transcript_var = PoseidonSpongeVar::<Fr>::new(cs.clone(), &poseidon_config);
 
-        let challenges_var =
-            KZGChallengesGadget::get_challenges_gadget(&mut transcript_var, &U_iVar).unwrap();
+        let challenges_var = KZGChallengesGadget::get_challenges_gadget(
+            &mut transcript_var,
+            &U_iVar.get_commitment_labels(),
+            &U_iVar,
+        )
+        .unwrap();
         assert!(cs.is_satisfied().unwrap());
 
         // check that the natively computed and in-circuit computed hashes match
         assert_eq!(challenges_var.value().unwrap(), challenges);
+
+        // the labels bind the commitments to their positions, so that swapping them leads to
+        // different challenges, even when the commitments are the same and thus swapping them
+        // would be unnoticeable without labels
+        let U_i_same = CommittedInstance::<Projective> {
+            cmE: U_i.cmW,
+            ..U_i.clone()
+        };
+        let challenges =
+            KZGChallengesGadget::get_challenges_native(&mut transcript.clone(), &labels, &U_i_same)
+                .unwrap();
+        let mut labels_swapped = labels.clone();
+        labels_swapped.swap(0, 1);
+        let challenges_swapped = KZGChallengesGadget::get_challenges_native(
+            &mut transcript.clone(),
+            &labels_swapped,
+            &U_i_same,
+        )
+        .unwrap();
+        assert_ne!(challenges, challenges_swapped);
+
+        // and the number of labels must match the number of commitments
+        assert!(
+            KZGChallengesGadget::get_challenges_native(&mut transcript, &labels[..1], &U_i)
+                .is_err()
+        );
+    }
+
+    // checks that the gadget and native implementations of the single challenge and of the
+    // batching challenge computation match
+    #[test]
+    fn test_kzg_batch_challenges_gadget() {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let mut transcript = PoseidonSponge::<Fr>::new(&poseidon_config);
+
+        let U_i = CommittedInstance::<Projective> {
+            cmE: Projective::rand(&mut rng),
+            u: Fr::rand(&mut rng),
+            cmW: Projective::rand(&mut rng),
+            x: vec![Fr::rand(&mut rng); 1],
+        };
+        let evaluations = vec![Fr::rand(&mut rng), Fr::rand(&mut rng)];
+
+        // compute the challenges natively
+        let challenge = KZGChallengesGadget::get_challenge_native(
+            &mut transcript,
+            &U_i.get_commitment_labels(),
+            &U_i,
+        )
+        .unwrap();
+        let gamma =
+            KZGChallengesGadget::get_batching_challenge_native(&mut transcript, &evaluations);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let U_iVar =
+            CommittedInstanceVar::<Projective>::new_witness(cs.clone(), || Ok(U_i.clone()))
+                .unwrap();
+        let evaluationsVar = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(evaluations)).unwrap();
+        let mut transcript_var = PoseidonSpongeVar::<Fr>::new(cs.clone(), &poseidon_config);
+
+        let challenge_var = KZGChallengesGadget::get_challenge_gadget(
+            &mut transcript_var,
+            &U_iVar.get_commitment_labels(),
+            &U_iVar,
+        )
+        .unwrap();
+        let gamma_var = KZGChallengesGadget::get_batching_challenge_gadget(
+            &mut transcript_var,
+            &evaluationsVar,
+        )
+        .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        assert_eq!(challenge_var.value().unwrap(), challenge);
+        assert_eq!(gamma_var.value().unwrap(), gamma);
     }
 
     #[test]
@@ -208,4 +472,113 @@ pub mod tests {
         assert_eq!(evalVar.value().unwrap(), eval);
         assert!(cs.is_satisfied().unwrap());
     }
+
+    #[test]
+    fn test_mle_evaluation() {
+        let mut rng = ark_std::test_rng();
+        // a length that is not a power of two, so that the vector is padded
+        let v: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(&mut rng))
+            .take(100)
+            .collect();
+        let challenge = Fr::rand(&mut rng);
+
+        let point = point_from_challenge(challenge, 7);
+        let eval = EvalGadget::evaluate_mle_native(&v, &point).unwrap();
+        let mle = ark_poly::DenseMultilinearExtension::from_evaluations_vec(
+            7,
+            [v.clone(), vec![Fr::zero(); 28]].concat(),
+        );
+        assert_eq!(
+            eval,
+            ark_poly::MultilinearExtension::evaluate(&mle, &point).unwrap()
+        );
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let vVar = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(v)).unwrap();
+        let pointVar = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(point)).unwrap();
+
+        let evalVar = EvalGadget::evaluate_mle_gadget(&vVar, &pointVar).unwrap();
+
+        assert_eq!(evalVar.value().unwrap(), eval);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_coefficients_evaluation() {
+        let mut rng = ark_std::test_rng();
+        let v: Vec<Fr> = std::iter::repeat_with(|| Fr::rand(&mut rng))
+            .take(100)
+            .collect();
+        let challenge = Fr::rand(&mut rng);
+
+        use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, Polynomial};
+        let eval = EvalGadget::evaluate_coefficients_native(&v, challenge);
+        assert_eq!(
+            eval,
+            DensePolynomial::from_coefficients_slice(&v).evaluate(&challenge)
+        );
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let vVar = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(v)).unwrap();
+        let challengeVar = FpVar::<Fr>::new_witness(cs.clone(), || Ok(challenge)).unwrap();
+
+        let evalVar = EvalGadget::evaluate_coefficients_gadget(&vVar, &challengeVar).unwrap();
+
+        assert_eq!(evalVar.value().unwrap(), eval);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    // checks that the gadget and native implementations of the KZG verification match, for
+    // BLS12-377 openings verified inside a BW6-761 circuit
+    #[test]
+    fn test_kzg_verify_gadget() {
+        use ark_bls12_377::{
+            constraints::{G1Var, PairingVar as Bls12_377PairingVar},
+            Bls12_377, Fq, Fr as Bls12_377Fr,
+        };
+        use ark_ff::{BigInteger, One, Zero};
+
+        use crate::commitment::{kzg::KZG, CommitmentScheme};
+
+        let mut rng = ark_std::test_rng();
+        let n = 8;
+        let (pk, vk) = KZG::<Bls12_377>::setup(&mut rng, n).unwrap();
+        let v: Vec<Bls12_377Fr> = std::iter::repeat_with(|| Bls12_377Fr::rand(&mut rng))
+            .take(n)
+            .collect();
+        let cm = KZG::<Bls12_377>::commit(&pk, &v, &Bls12_377Fr::zero()).unwrap();
+        let point = Bls12_377Fr::rand(&mut rng);
+        let proof =
+            KZG::<Bls12_377>::prove_with_challenge(&pk, point, &v, &Bls12_377Fr::zero(), None)
+                .unwrap();
+
+        let gadget_check = |eval: Bls12_377Fr| {
+            let cs = ConstraintSystem::<Fq>::new_ref();
+            let cmVar = G1Var::new_witness(cs.clone(), || Ok(cm)).unwrap();
+            let proofVar = G1Var::new_witness(cs.clone(), || Ok(proof.proof)).unwrap();
+            let pointVar = Vec::<Boolean<Fq>>::new_witness(cs.clone(), || {
+                Ok(point.into_bigint().to_bits_le())
+            })
+            .unwrap();
+            let evalVar =
+                Vec::<Boolean<Fq>>::new_witness(cs.clone(), || Ok(eval.into_bigint().to_bits_le()))
+                    .unwrap();
+            let valid = KZGVerifyGadget::<Bls12_377, Bls12_377PairingVar>::verify(
+                &vk, &cmVar, &pointVar, &evalVar, &proofVar,
+            )
+            .unwrap();
+            assert!(cs.is_satisfied().unwrap());
+            valid.value().unwrap()
+        };
+
+        // valid opening
+        KZG::<Bls12_377>::verify_with_challenge(&vk, point, &cm, &proof).unwrap();
+        assert!(gadget_check(proof.eval));
+
+        // opening to a wrong evaluation
+        let mut wrong_proof = proof.clone();
+        wrong_proof.eval += Bls12_377Fr::one();
+        assert!(KZG::<Bls12_377>::verify_with_challenge(&vk, point, &cm, &wrong_proof).is_err());
+        assert!(!gadget_check(wrong_proof.eval));
+    }
 }
@@ -12,7 +12,7 @@ use ark_r1cs_std::{
     alloc::AllocVar, eq::EqGadget, fields::fp::FpVar, prelude::CurveVar, ToConstraintFieldGadget,
 };
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
-use ark_std::{marker::PhantomData, Zero};
+use ark_std::{marker::PhantomData, vec::Vec, Zero};
 
 use crate::{
     arith::{
@@ -23,6 +23,7 @@ use crate::{
         circuits::{
             cyclefold::{
                 CycleFoldCommittedInstance, CycleFoldCommittedInstanceVar, CycleFoldWitness,
+                CF_COMMITMENT_LABELS,
             },
             decider::{EvalGadget, KZGChallengesGadget},
             nonnative::affine::NonNativeAffineVar,
@@ -55,6 +56,9 @@ pub struct GenericOffchainDeciderCircuit1<
     pub poseidon_config: PoseidonConfig<CF1<C1>>,
     /// public params hash
     pub pp_hash: CF1<C1>,
+    /// application context absorbed into `u_i.x[0]` alongside `pp_hash`, for the folding schemes
+    /// that support it. When set, it is allocated as a public input right after `pp_hash`.
+    pub context: Option<CF1<C1>>,
     pub i: CF1<C1>,
     /// initial state
     pub z_0: Vec<CF1<C1>>,
@@ -78,6 +82,9 @@ pub struct GenericOffchainDeciderCircuit1<
     /// KZG challenges
     pub kzg_challenges: Vec<CF1<C1>>,
     pub kzg_evaluations: Vec<CF1<C1>>,
+    /// whether the witness vectors are read as the coefficients of the committed polynomials, as
+    /// opened by IPA, instead of as their evaluations over the domain, as opened by KZG
+    pub coefficient_form: bool,
 }
 
 impl<
@@ -125,6 +132,7 @@ impl<
             _avar: PhantomData,
             poseidon_config,
             pp_hash: Zero::zero(),
+            context: None,
             i: Zero::zero(),
             z_0: vec![Zero::zero(); state_len],
             z_i: vec![Zero::zero(); state_len],
@@ -139,6 +147,7 @@ impl<
             cf_U_i: CycleFoldCommittedInstance::dummy(cf_arith),
             kzg_challenges: vec![Zero::zero(); num_commitments],
             kzg_evaluations: vec![Zero::zero(); num_commitments],
+            coefficient_form: false,
             arith,
         }
     }
@@ -164,6 +173,10 @@ where
         let arith = AVar::new_witness(cs.clone(), || Ok(&self.arith))?;
 
         let pp_hash = FpVar::new_input(cs.clone(), || Ok(self.pp_hash))?;
+        let context = self
+            .context
+            .map(|context| FpVar::new_input(cs.clone(), || Ok(context)))
+            .transpose()?;
         let i = FpVar::new_input(cs.clone(), || Ok(self.i))?;
         let z_0 = Vec::new_input(cs.clone(), || Ok(self.z_0))?;
         let z_i = Vec::new_input(cs.clone(), || Ok(self.z_i))?;
@@ -198,7 +211,8 @@ where
         u_i.enforce_incoming()?;
 
         // 3. u_i.x[0] == H(i, z_0, z_i, U_i), u_i.x[1] == H(cf_U_i)
-        let (u_i_x, U_i_vec) = U_i.hash(&sponge, &pp_hash, &i, &z_0, &z_i)?;
+        let (u_i_x, U_i_vec) =
+            U_i.hash_with_context(&sponge, &pp_hash, context.as_ref(), &i, &z_0, &z_i)?;
         let (cf_u_i_x, _) = cf_U_i.hash(&sponge, pp_hash.clone())?;
         u_i.get_public_inputs().enforce_equal(&[u_i_x, cf_u_i_x])?;
 
@@ -216,8 +230,12 @@ where
         .enforce_partial_equal(&U_i1)?;
 
         // 7.1. compute and check KZG challenges
-        KZGChallengesGadget::get_challenges_gadget(&mut transcript, &U_i1)?
-            .enforce_equal(&kzg_challenges)?;
+        KZGChallengesGadget::get_challenges_gadget(
+            &mut transcript,
+            &U_i1.get_commitment_labels(),
+            &U_i1,
+        )?
+        .enforce_equal(&kzg_challenges)?;
 
         // 7.2. check the claimed evaluations
         for (((v, _r), c), e) in W_i1
@@ -227,7 +245,11 @@ where
             .zip(&kzg_evaluations)
         {
             // The randomness `_r` is currently not used.
-            EvalGadget::evaluate_gadget(v, c)?.enforce_equal(e)?;
+            if self.coefficient_form {
+                EvalGadget::evaluate_coefficients_gadget(v, c)?.enforce_equal(e)?;
+            } else {
+                EvalGadget::evaluate_gadget(v, c)?.enforce_equal(e)?;
+            }
         }
 
         Ok(())
@@ -250,6 +272,8 @@ pub struct GenericOffchainDeciderCircuit2<C2: CurveGroup> {
     /// KZG challenges
     pub kzg_challenges: Vec<CF1<C2>>,
     pub kzg_evaluations: Vec<CF1<C2>>,
+    /// see `GenericOffchainDeciderCircuit1::coefficient_form`
+    pub coefficient_form: bool,
 }
 
 impl<C2: CurveGroup> Dummy<(R1CS<CF1<C2>>, PoseidonConfig<CF1<C2>>, usize)>
@@ -269,6 +293,7 @@ impl<C2: CurveGroup> Dummy<(R1CS<CF1<C2>>, PoseidonConfig<CF1<C2>>, usize)>
             cf_W_i: CycleFoldWitness::dummy(&cf_arith),
             kzg_challenges: vec![Zero::zero(); num_commitments],
             kzg_evaluations: vec![Zero::zero(); num_commitments],
+            coefficient_form: false,
             cf_arith,
         }
     }
@@ -297,8 +322,12 @@ impl<C2: CurveGroup> ConstraintSynthesizer<CF1<C2>> for GenericOffchainDeciderCi
         cf_r1cs.enforce_relation(&cf_W_i, &cf_U_i)?;
 
         // 4.1. compute and check KZG challenges
-        KZGChallengesGadget::get_challenges_gadget(&mut transcript, &cf_U_i)?
-            .enforce_equal(&kzg_challenges)?;
+        KZGChallengesGadget::get_challenges_gadget(
+            &mut transcript,
+            &CF_COMMITMENT_LABELS,
+            &cf_U_i,
+        )?
+        .enforce_equal(&kzg_challenges)?;
 
         // 4.2. check the claimed evaluations
         for (((v, _r), c), e) in cf_W_i
@@ -308,7 +337,11 @@ impl<C2: CurveGroup> ConstraintSynthesizer<CF1<C2>> for GenericOffchainDeciderCi
             .zip(&kzg_evaluations)
         {
             // The randomness `_r` is currently not used.
-            EvalGadget::evaluate_gadget(v, c)?.enforce_equal(e)?;
+            if self.coefficient_form {
+                EvalGadget::evaluate_coefficients_gadget(v, c)?.enforce_equal(e)?;
+            } else {
+                EvalGadget::evaluate_gadget(v, c)?.enforce_equal(e)?;
+            }
         }
 
         Ok(())
@@ -10,7 +10,7 @@ use ark_r1cs_std::{
     alloc::AllocVar, eq::EqGadget, fields::fp::FpVar, prelude::CurveVar, ToConstraintFieldGadget,
 };
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
-use ark_std::{marker::PhantomData, Zero};
+use ark_std::{marker::PhantomData, vec::Vec, Zero};
 
 use crate::{
     arith::{r1cs::R1CS, Arith, ArithGadget},
@@ -26,6 +26,7 @@ use crate::{
         },
         traits::{CommittedInstanceOps, CommittedInstanceVarOps, Dummy, WitnessOps, WitnessVarOps},
     },
+    transcript::keccak::{keccak256_digest, keccak256_digest_gadget},
 };
 
 use super::DeciderEnabledNIFS;
@@ -60,6 +61,25 @@ use super::DeciderEnabledNIFS;
 /// with the witness `W_{i+1}` and instance `U_{i+1}` in the circuit, but the
 /// actual commitment checks are done with the help of KZG.
 ///
+/// Notice that `R_cf_arith(cf_W_i, cf_U_i)` is fully enforced in the circuit, including the
+/// openings of the Pedersen commitments over C2 (whose scalar multiplications are native, since
+/// C2's base field is C1's scalar field) and the binding of `cf_U_i` to `u_i.x[1]`, so none of
+/// the CycleFold values is a public input. Besides `pp_hash`, `context`, `i`, `z_0` and `z_i`,
+/// the public inputs are the commitments of `U_{i+1}`, the folding proof and the KZG values,
+/// which are needed to check the commitments over C1 outside of the circuit.
+///
+/// This is what makes the circuit large: the checks of `R_cf_arith` (steps 4 and 5 below) account
+/// for most of its constraints, which is why they are skipped with the `light-test` feature. The
+/// two Pedersen openings cost one native scalar multiplication over C2 per element of `cf_W_i.E`
+/// and `cf_W_i.W`, and the relation of the CycleFold R1CS is checked over C2's scalar field, which
+/// is non-native here, so each of its rows needs `NonNativeUintVar` multiplications and a modular
+/// reduction. In exchange, the Solidity verifier neither receives nor checks any CycleFold value:
+/// exposing `cf_U_i` instead would add its 4 coordinates, `u` and `x` as public inputs, each of
+/// which costs the Groth16 verifier a BN254 `ecMul` and `ecAdd` (6150 gas, EIP-1108) plus its 32
+/// bytes of calldata, on top of the Pedersen openings over C2, which have no EVM precompile. The
+/// test `nova_cyclefold_solidity_verifier_hash_state` of `solidity-verifiers` measures this
+/// cost per public input with revm, by comparing the gas of the full and the hashed states.
+///
 /// For more details, see [https://privacy-scaling-explorations.github.io/sonobe-docs/design/nova-decider-onchain.html].
 pub struct GenericOnchainDeciderCircuit<
     C1: CurveGroup,
@@ -83,11 +103,19 @@ pub struct GenericOnchainDeciderCircuit<
     pub poseidon_config: PoseidonConfig<CF1<C1>>,
     /// public params hash
     pub pp_hash: CF1<C1>,
+    /// application context absorbed into `u_i.x[0]` alongside `pp_hash`, for the folding schemes
+    /// that support it. When set, it is allocated as a public input right after `pp_hash`.
+    pub context: Option<CF1<C1>>,
     pub i: CF1<C1>,
     /// initial state
     pub z_0: Vec<CF1<C1>>,
     /// current i-th state
     pub z_i: Vec<CF1<C1>>,
+    /// when set, `z_0` and `z_i` are allocated as witnesses, and only their `keccak256_digest`s
+    /// are public inputs, which the EVM verifier recomputes from the states. This keeps the
+    /// number of public inputs independent of the state length, at the cost of ~150k constraints
+    /// for each 136 bytes of state.
+    pub hash_state: bool,
     /// Folding scheme instances
     pub U_i: RU,
     pub W_i: W,
@@ -103,10 +131,23 @@ pub struct GenericOnchainDeciderCircuit<
     /// CycleFold running instance
     pub cf_U_i: CycleFoldCommittedInstance<C2>,
     pub cf_W_i: CycleFoldWitness<C2>,
+    /// When set, a random CycleFold instance and the commitment to the cross terms of its fold
+    /// into `cf_U_i`, which is folded in-circuit, and `cf_W_i` is the witness of the folded
+    /// instance. This hides the CycleFold witness of the IVC in the same way as the blinding of
+    /// the running instance of the folding scheme.
+    pub cf_blinding: Option<(CycleFoldCommittedInstance<C2>, C2)>,
 
-    /// KZG challenges
-    pub kzg_challenges: Vec<CF1<C1>>,
+    /// KZG challenge, ie. the point at which all the commitments are opened
+    pub kzg_challenge: CF1<C1>,
     pub kzg_evaluations: Vec<CF1<C1>>,
+    /// Challenge used to batch the KZG openings into a single one. Only
+    /// allocated (as public input) when there is more than one commitment.
+    pub kzg_batching_challenge: CF1<C1>,
+    /// When set, the commitments are opened as multilinear polynomials at this point, which is
+    /// computed from `U_{i+1}` and `kzg_challenge` by `D::multilinear_opening_point_gadget` and
+    /// allocated as public input. Otherwise, they are opened as univariate polynomials at
+    /// `kzg_challenge`.
+    pub opening_point: Option<Vec<CF1<C1>>>,
 }
 
 impl<
@@ -158,9 +199,11 @@ impl<
             cf_pedersen_params,
             poseidon_config,
             pp_hash: Zero::zero(),
+            context: None,
             i: Zero::zero(),
             z_0: vec![Zero::zero(); state_len],
             z_i: vec![Zero::zero(); state_len],
+            hash_state: false,
             U_i: RU::dummy(&arith),
             W_i: W::dummy(&arith),
             u_i: IU::dummy(&arith),
@@ -171,8 +214,11 @@ impl<
             randomness: D::Randomness::dummy(randomness_config),
             cf_U_i: CycleFoldCommittedInstance::dummy(&cf_arith),
             cf_W_i: CycleFoldWitness::dummy(&cf_arith),
-            kzg_challenges: vec![Zero::zero(); num_commitments],
+            cf_blinding: None,
+            kzg_challenge: Zero::zero(),
             kzg_evaluations: vec![Zero::zero(); num_commitments],
+            kzg_batching_challenge: Zero::zero(),
+            opening_point: None,
             arith,
             cf_arith,
         }
@@ -199,16 +245,36 @@ where
         let arith = AVar::new_witness(cs.clone(), || Ok(&self.arith))?;
 
         let pp_hash = FpVar::new_input(cs.clone(), || Ok(self.pp_hash))?;
+        let context = self
+            .context
+            .map(|context| FpVar::new_input(cs.clone(), || Ok(context)))
+            .transpose()?;
         let i = FpVar::new_input(cs.clone(), || Ok(self.i))?;
-        let z_0 = Vec::new_input(cs.clone(), || Ok(self.z_0))?;
-        let z_i = Vec::new_input(cs.clone(), || Ok(self.z_i))?;
+        let (z_0, z_i) = if self.hash_state {
+            let z_0_digest = FpVar::new_input(cs.clone(), || Ok(keccak256_digest(&self.z_0)))?;
+            let z_i_digest = FpVar::new_input(cs.clone(), || Ok(keccak256_digest(&self.z_i)))?;
+            let z_0 = Vec::new_witness(cs.clone(), || Ok(self.z_0))?;
+            let z_i = Vec::new_witness(cs.clone(), || Ok(self.z_i))?;
+            keccak256_digest_gadget(&z_0)?.enforce_equal(&z_0_digest)?;
+            keccak256_digest_gadget(&z_i)?.enforce_equal(&z_i_digest)?;
+            (z_0, z_i)
+        } else {
+            (
+                Vec::new_input(cs.clone(), || Ok(self.z_0))?,
+                Vec::new_input(cs.clone(), || Ok(self.z_i))?,
+            )
+        };
 
         let u_i = IU::Var::new_witness(cs.clone(), || Ok(self.u_i))?;
         let U_i = RU::Var::new_witness(cs.clone(), || Ok(self.U_i))?;
         // here (U_i1, W_i1) = NIFS.P( (U_i,W_i), (u_i,w_i))
-        let U_i1_commitments = Vec::<NonNativeAffineVar<C1>>::new_input(cs.clone(), || {
-            Ok(self.U_i1.get_commitments())
-        })?;
+        // the commitments are exposed in their packed form to reduce the number of public inputs
+        let U_i1_commitments = self
+            .U_i1
+            .get_commitments()
+            .iter()
+            .map(|c| NonNativeAffineVar::<C1>::new_packed_input(cs.clone(), || Ok(c)))
+            .collect::<Result<Vec<_>, _>>()?;
         let U_i1 = RU::Var::new_witness(cs.clone(), || Ok(self.U_i1))?;
         let W_i1 = W::Var::new_witness(cs.clone(), || Ok(self.W_i1))?;
         U_i1.get_commitments().enforce_equal(&U_i1_commitments)?;
@@ -216,9 +282,21 @@ where
         let cf_U_i =
             CycleFoldCommittedInstanceVar::<C2, GC2>::new_witness(cs.clone(), || Ok(self.cf_U_i))?;
 
-        // allocate the inputs for the check 7.1 and 7.2
-        let kzg_challenges = Vec::new_input(cs.clone(), || Ok(self.kzg_challenges))?;
+        // allocate the inputs for the check 7.1, 7.2 and 7.3
+        let batch_openings = self.kzg_evaluations.len() > 1;
+        let kzg_challenge = FpVar::new_input(cs.clone(), || Ok(self.kzg_challenge))?;
         let kzg_evaluations = Vec::new_input(cs.clone(), || Ok(self.kzg_evaluations))?;
+        let kzg_batching_challenge = if batch_openings {
+            Some(FpVar::new_input(cs.clone(), || {
+                Ok(self.kzg_batching_challenge)
+            })?)
+        } else {
+            None
+        };
+        let opening_point = self
+            .opening_point
+            .map(|point| Vec::new_input(cs.clone(), || Ok(point)))
+            .transpose()?;
 
         // `sponge` is for digest computation.
         let sponge = PoseidonSpongeVar::new(cs.clone(), &self.poseidon_config);
@@ -236,8 +314,10 @@ where
         u_i.enforce_incoming()?;
 
         // 3. u_i.x[0] == H(i, z_0, z_i, U_i), u_i.x[1] == H(cf_U_i)
-        let (u_i_x, U_i_vec) = U_i.hash(&sponge, &pp_hash, &i, &z_0, &z_i)?;
-        let (cf_u_i_x, _) = cf_U_i.hash(&sponge, pp_hash.clone())?;
+        let (u_i_x, U_i_vec) =
+            U_i.hash_with_context(&sponge, &pp_hash, context.as_ref(), &i, &z_0, &z_i)?;
+        #[cfg_attr(feature = "light-test", allow(unused_variables))]
+        let (cf_u_i_x, cf_U_i_vec) = cf_U_i.hash(&sponge, pp_hash.clone())?;
         u_i.get_public_inputs().enforce_equal(&[u_i_x, cf_u_i_x])?;
 
         #[cfg(feature = "light-test")]
@@ -254,11 +334,38 @@ where
             use crate::{
                 arith::r1cs::circuits::R1CSMatricesVar,
                 commitment::pedersen::PedersenGadget,
+                constants::NOVA_N_BITS_RO,
                 folding::circuits::{
-                    cyclefold::CycleFoldWitnessVar, nonnative::uint::NonNativeUintVar,
+                    cyclefold::{CycleFoldChallengeGadget, CycleFoldWitnessVar, NIFSFullGadget},
+                    nonnative::uint::NonNativeUintVar,
                 },
             };
             use ark_r1cs_std::ToBitsGadget;
+
+            // fold the random CycleFold instance into `cf_U_i`, if any, so that the checks below
+            // are done on the folded instance
+            let cf_U_i = match self.cf_blinding {
+                Some((cf_U_r, cf_cmT)) => {
+                    let cf_U_r =
+                        CycleFoldCommittedInstanceVar::<C2, GC2>::new_witness(cs.clone(), || {
+                            Ok(cf_U_r)
+                        })?;
+                    let cf_cmT = GC2::new_witness(cs.clone(), || Ok(cf_cmT))?;
+                    let cf_r_bits = CycleFoldChallengeGadget::<C2, GC2>::get_challenge_gadget(
+                        &mut sponge.clone(),
+                        NOVA_N_BITS_RO,
+                        pp_hash.clone(),
+                        cf_U_i_vec,
+                        cf_U_r.clone(),
+                        cf_cmT.clone(),
+                    )?;
+                    NIFSFullGadget::<C2, GC2>::fold_relaxed_committed_instances(
+                        cf_r_bits, cf_cmT, cf_U_i, cf_U_r,
+                    )?
+                }
+                None => cf_U_i,
+            };
+
             let cf_W_i = CycleFoldWitnessVar::<C2>::new_witness(cs.clone(), || Ok(self.cf_W_i))?;
             // 4. check Pedersen commitments of cf_U_i.{cmE, cmW}
             let H = GC2::constant(self.cf_pedersen_params.h);
@@ -305,19 +412,31 @@ where
         )?
         .enforce_partial_equal(&U_i1)?;
 
-        // 7.1. compute and check KZG challenges
-        KZGChallengesGadget::get_challenges_gadget(&mut transcript, &U_i1)?
-            .enforce_equal(&kzg_challenges)?;
+        // 7.1. compute and check the KZG challenge, which is shared by all the commitments
+        KZGChallengesGadget::get_challenge_gadget(
+            &mut transcript,
+            &U_i1.get_commitment_labels(),
+            &U_i1,
+        )?
+        .enforce_equal(&kzg_challenge)?;
+        if let Some(point) = &opening_point {
+            D::multilinear_opening_point_gadget(&U_i1, &kzg_challenge, point.len())?
+                .enforce_equal(point)?;
+        }
 
         // 7.2. check the claimed evaluations
-        for (((v, _r), c), e) in W_i1
-            .get_openings()
-            .iter()
-            .zip(&kzg_challenges)
-            .zip(&kzg_evaluations)
-        {
+        for ((v, _r), e) in W_i1.get_openings().iter().zip(&kzg_evaluations) {
             // The randomness `_r` is currently not used.
-            EvalGadget::evaluate_gadget(v, c)?.enforce_equal(e)?;
+            match &opening_point {
+                Some(point) => EvalGadget::evaluate_mle_gadget(v, point)?.enforce_equal(e)?,
+                None => EvalGadget::evaluate_gadget(v, &kzg_challenge)?.enforce_equal(e)?,
+            }
+        }
+
+        // 7.3. compute and check the challenge used to batch the KZG openings
+        if let Some(kzg_batching_challenge) = kzg_batching_challenge {
+            KZGChallengesGadget::get_batching_challenge_gadget(&mut transcript, &kzg_evaluations)?
+                .enforce_equal(&kzg_batching_challenge)?;
         }
 
         Ok(())
@@ -0,0 +1,558 @@
+/// Implements the scalar multiplication of points of curves with an efficient endomorphism
+/// following the [GLV](https://www.iacr.org/archive/crypto2001/21390189.pdf) method, for curves
+/// that expose the endomorphism parameters through [`GLVCurve`]. The CycleFold circuit uses it
+/// when its config is wrapped in [`GLVCycleFoldConfig`].
+use ark_ec::{CurveGroup, Group};
+use ark_ff::{BigInteger, MontFp, PrimeField};
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    prelude::CurveVar,
+    select::CondSelectGadget,
+    R1CSVar, ToConstraintFieldGadget,
+};
+use ark_relations::r1cs::SynthesisError;
+use ark_std::{marker::PhantomData, vec::Vec, One, Zero};
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
+
+use super::cyclefold::CycleFoldConfig;
+use super::nonnative::{affine::NonNativeAffineVar, uint::NonNativeUintVar};
+use super::CF1;
+use crate::Error;
+
+/// `GLVCurve` exposes the parameters of the endomorphism `φ(x, y) = (β * x, y)` of a prime-order
+/// curve of the form `y^2 = x^3 + b`, which acts on its points as `φ(P) = λ * P`.
+pub trait GLVCurve: CurveGroup {
+    /// `BETA` is the non-trivial cube root of unity of the base field that defines `φ`.
+    const BETA: Self::BaseField;
+    /// `LAMBDA` is the non-trivial cube root of unity of the scalar field such that
+    /// `φ(P) = λ * P`.
+    const LAMBDA: Self::ScalarField;
+}
+
+impl GLVCurve for ark_bn254::G1Projective {
+    const BETA: Self::BaseField =
+        MontFp!("21888242871839275220042445260109153167277707414472061641714758635765020556616");
+    const LAMBDA: Self::ScalarField =
+        MontFp!("21888242871839275217838484774961031246154997185409878258781734729429964517155");
+}
+
+impl GLVCurve for ark_grumpkin::Projective {
+    const BETA: Self::BaseField =
+        MontFp!("4407920970296243842393367215006156084916469457145843978461");
+    const LAMBDA: Self::ScalarField =
+        MontFp!("2203960485148121921418603742825762020974279258880205651966");
+}
+
+/// Parameters of the GLV decomposition of the scalars of `C`.
+struct GLVParams {
+    /// reduced basis `(v1, v2)` of the lattice `{(a, b) : a + b * λ = 0 mod n}`, with `v1` being
+    /// its shortest vector
+    basis: [(BigInt, BigInt); 2],
+    /// bit length `L` of the two scalars of the decomposition
+    bit_length: usize,
+    /// any non-zero vector of the lattice has a coordinate of absolute value at least
+    /// `2^safe_bits`
+    safe_bits: usize,
+}
+
+/// Rounds `a / b` to the nearest integer.
+fn round_div(a: &BigInt, b: &BigInt) -> BigInt {
+    let (a, b) = if b.sign() == Sign::Minus {
+        (-a, -b)
+    } else {
+        (a.clone(), b.clone())
+    };
+    (a * 2 + &b).div_floor(&(b * 2))
+}
+
+fn glv_params<C: GLVCurve>() -> GLVParams {
+    let n = BigInt::from(BigUint::from(C::ScalarField::MODULUS));
+    let lambda = BigInt::from(BigUint::from(C::LAMBDA.into_bigint()));
+    let norm = |v: &(BigInt, BigInt)| &v.0 * &v.0 + &v.1 * &v.1;
+
+    // Lagrange-Gauss reduction of the basis {(n, 0), (-λ, 1)}
+    let (mut v1, mut v2) = ((n, BigInt::zero()), (-lambda, BigInt::one()));
+    loop {
+        if norm(&v1) > norm(&v2) {
+            ark_std::mem::swap(&mut v1, &mut v2);
+        }
+        let mu = round_div(&(&v1.0 * &v2.0 + &v1.1 * &v2.1), &norm(&v1));
+        if mu.is_zero() {
+            break;
+        }
+        v2 = (&v2.0 - &mu * &v1.0, &v2.1 - &mu * &v1.1);
+    }
+
+    // the short scalars are bounded by (|v1| + |v2|) / 2 (in the max norm), and we reserve two
+    // more bits so that they can be shifted to [2^{L-1}, 2^L), see `decompose_scalar`
+    let max_norm = |v: &(BigInt, BigInt)| v.0.magnitude().max(v.1.magnitude()).clone();
+    let bit_length = (max_norm(&v1) + max_norm(&v2)).bits() as usize + 1;
+    // any non-zero vector v satisfies |v|_max >= |v|_2 / sqrt(2) >= |v1|_2 / sqrt(2)
+    let safe_bits = (norm(&v1).bits() as usize - 2) / 2;
+
+    GLVParams {
+        basis: [v1, v2],
+        bit_length,
+        safe_bits,
+    }
+}
+
+/// Returns the bit length `L` of the scalars given by [`decompose_scalar`] for the curve `C`.
+pub fn glv_bit_length<C: GLVCurve>() -> usize {
+    glv_params::<C>().bit_length
+}
+
+/// Decomposes `k` into `[k1, k2]` such that `k = k1 + k2 * λ mod n`, where both `k1` and `k2` lie
+/// in `[2^{L-1}, 2^L)` for `L = glv_bit_length::<C>()`, ie. their most significant bit is set.
+pub fn decompose_scalar<C: GLVCurve>(k: &C::ScalarField) -> [BigUint; 2] {
+    let GLVParams {
+        basis: [v1, v2],
+        bit_length,
+        ..
+    } = glv_params::<C>();
+
+    // decompose k' = k - o * (1 + λ) into short scalars (a, b), so that (a + o, b + o) is a
+    // decomposition of k
+    let offset = C::ScalarField::from(BigUint::from(3u64) << (bit_length - 2));
+    let k = BigInt::from(BigUint::from(
+        (*k - offset * (C::ScalarField::one() + C::LAMBDA)).into_bigint(),
+    ));
+    // (k, 0) - c1 * v1 - c2 * v2 is the shortest vector of the coset of (k, 0), where
+    // (k, 0) = β1 * v1 + β2 * v2 and c_i = round(β_i)
+    let det = &v1.0 * &v2.1 - &v1.1 * &v2.0;
+    let c1 = round_div(&(&k * &v2.1), &det);
+    let c2 = round_div(&(-&k * &v1.1), &det);
+    let a = &k - &c1 * &v1.0 - &c2 * &v2.0;
+    let b = -&c1 * &v1.1 - &c2 * &v2.1;
+
+    let offset = BigInt::from(3u64) << (bit_length - 2);
+    [a, b].map(|v| {
+        (v + &offset)
+            .to_biguint()
+            .expect("the shifted scalars are positive")
+    })
+}
+
+/// A point different from zero, represented in affine coordinates, whose operations use
+/// incomplete formulas for curves of the form `y^2 = x^3 + b`.
+#[derive(Clone)]
+pub(crate) struct NonZeroPointVar<F: PrimeField> {
+    pub(crate) x: FpVar<F>,
+    pub(crate) y: FpVar<F>,
+}
+
+impl<F: PrimeField> CondSelectGadget<F> for NonZeroPointVar<F> {
+    fn conditionally_select(
+        cond: &Boolean<F>,
+        true_value: &Self,
+        false_value: &Self,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            x: cond.select(&true_value.x, &false_value.x)?,
+            y: cond.select(&true_value.y, &false_value.y)?,
+        })
+    }
+}
+
+impl<F: PrimeField> NonZeroPointVar<F> {
+    pub(crate) fn negate(&self) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            x: self.x.clone(),
+            y: self.y.negate()?,
+        })
+    }
+
+    /// Computes `self + other`, assuming that `self != ±other`.
+    pub(crate) fn add_unchecked(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let lambda = (&other.y - &self.y).mul_by_inverse_unchecked(&(&other.x - &self.x))?;
+        let x = lambda.square()? - &self.x - &other.x;
+        let y = lambda * (&self.x - &x) - &self.y;
+        Ok(Self { x, y })
+    }
+
+    /// Computes `2 * self`, assuming that `self` is not of order 2.
+    pub(crate) fn double_unchecked(&self) -> Result<Self, SynthesisError> {
+        let lambda =
+            (self.x.square()? * F::from(3u64)).mul_by_inverse_unchecked(&self.y.double()?)?;
+        let x = lambda.square()? - self.x.double()?;
+        let y = lambda * (&self.x - &x) - &self.y;
+        Ok(Self { x, y })
+    }
+
+    /// Computes `2 * self + other` as `(self + other) + self`, assuming that `self != ±other` and
+    /// `2 * self + other != 0`.
+    pub(crate) fn double_and_add_unchecked(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let lambda = (&other.y - &self.y).mul_by_inverse_unchecked(&(&other.x - &self.x))?;
+        let x2 = lambda.square()? - &self.x - &other.x;
+        let lambda = (lambda
+            + self
+                .y
+                .double()?
+                .mul_by_inverse_unchecked(&(&x2 - &self.x))?)
+        .negate()?;
+        let x = lambda.square()? - &self.x - &x2;
+        let y = lambda * (&self.x - &x) - &self.y;
+        Ok(Self { x, y })
+    }
+}
+
+/// Returns the digit `d = 2 * b - 1` of the signed-digit representation of a scalar given its
+/// bit `b`.
+fn digit<F: PrimeField>(b: &Boolean<F>) -> Result<BigInt, SynthesisError> {
+    Ok(if b.value()? {
+        BigInt::one()
+    } else {
+        -BigInt::one()
+    })
+}
+
+/// Selects `d1 * P + d2 * φ(P)` given the bits `b1`, `b2` of the digits `d1`, `d2` and
+/// `s = P + φ(P)`, `d = P - φ(P)`.
+fn select_table<T: CondSelectGadget<F> + Clone, F: PrimeField>(
+    b1: &Boolean<F>,
+    b2: &Boolean<F>,
+    s: &T,
+    d: &T,
+    negate: impl Fn(&T) -> Result<T, SynthesisError>,
+) -> Result<T, SynthesisError> {
+    let t = b1.xor(b2)?.select(d, s)?;
+    b1.select(&t, &negate(&t)?)
+}
+
+/// `GLVScalarMulGadget` computes in-circuit the scalar multiplication `k * P` of a point `P` of a
+/// curve with an efficient endomorphism `φ` by decomposing `k` into `k1 + k2 * λ`, where `k1` and
+/// `k2` are half the length of `k`, and computing `k1 * P + k2 * φ(P)` with a joint
+/// double-and-add, which halves the number of iterations of the plain double-and-add.
+///
+/// This only pays off when `k` is a full-width scalar: for scalars of half the bit length of the
+/// scalar field, such as the 128-bit challenges of Nova, the decomposition is not shorter than
+/// `k` itself.
+pub struct GLVScalarMulGadget<C: GLVCurve, GC: CurveVar<C, C::BaseField>> {
+    _c: PhantomData<C>,
+    _gc: PhantomData<GC>,
+}
+
+impl<C: GLVCurve, GC: CurveVar<C, C::BaseField>> GLVScalarMulGadget<C, GC>
+where
+    C::BaseField: PrimeField,
+    GC: ToConstraintFieldGadget<C::BaseField>,
+{
+    /// Computes `k * p`, where `k_bits` is the little-endian bit representation of `k`.
+    pub fn scalar_mul_le(p: &GC, k_bits: &[Boolean<C::BaseField>]) -> Result<GC, SynthesisError> {
+        let k = k_bits
+            .value()
+            .ok()
+            .and_then(|bits| {
+                BigUint::from_radix_le(&bits.into_iter().map(u8::from).collect::<Vec<_>>(), 2)
+            })
+            .map(C::ScalarField::from)
+            .unwrap_or_default();
+        Self::scalar_mul_le_with_decomposition(p, k_bits, decompose_scalar::<C>(&k))
+    }
+
+    /// Computes `k * p` using the given decomposition `[k1, k2]` of `k`, whose validity is
+    /// enforced in-circuit.
+    fn scalar_mul_le_with_decomposition(
+        p: &GC,
+        k_bits: &[Boolean<C::BaseField>],
+        [k1, k2]: [BigUint; 2],
+    ) -> Result<GC, SynthesisError> {
+        let cs = p.cs();
+        let GLVParams {
+            bit_length: l,
+            safe_bits,
+            ..
+        } = glv_params::<C>();
+
+        // Allocate the bits of k1 and k2, whose most significant bit is always set
+        let alloc_bits = |k: &BigUint| -> Result<Vec<Boolean<C::BaseField>>, SynthesisError> {
+            Ok([
+                Vec::new_witness(cs.clone(), || {
+                    Ok((0..l - 1).map(|i| k.bit(i as u64)).collect::<Vec<_>>())
+                })?,
+                vec![Boolean::TRUE],
+            ]
+            .concat())
+        };
+        let (k1_bits, k2_bits) = (alloc_bits(&k1)?, alloc_bits(&k2)?);
+
+        // Enforce that k = k1 + k2 * λ mod n
+        let lambda = NonNativeUintVar::new_constant(cs.clone(), C::LAMBDA)?;
+        NonNativeUintVar::from(&k1_bits)
+            .add_no_align(&NonNativeUintVar::from(&k2_bits).mul_no_align(&lambda)?)?
+            .enforce_congruent::<C::ScalarField>(&NonNativeUintVar::from(k_bits))?;
+
+        // As in `CurveVar::scalar_mul_le`, we compute the scalar multiplication of a non-zero
+        // point (the generator in case that `p` is zero) and select zero at the end if `p` was
+        // zero, so that the incomplete formulas below are always satisfiable.
+        let infinity = p.is_zero()?;
+        let p = infinity.select(&GC::constant(C::generator()), p)?;
+        let p_xy = p.to_constraint_field()?;
+        let p_point = NonZeroPointVar {
+            x: p_xy[0].clone(),
+            y: p_xy[1].clone(),
+        };
+        let phi_point = NonZeroPointVar {
+            x: &p_xy[0] * C::BETA,
+            y: p_xy[1].clone(),
+        };
+
+        // Each k_i is encoded with the digits d_j = 2 * b_j - 1 in {-1, 1} of the odd scalar
+        // m_i = k_i + 1 - k_i[0], whose bits are b_j = k_i[j + 1] for j < L - 1 and b_{L-1} = 1.
+        // Thus, the accumulator acc = A1 * P + A2 * φ(P) starts at 3 * (P + φ(P)) from the two
+        // most significant digits, and at each step becomes 2 * acc + d1 * P + d2 * φ(P).
+        let s = p_point.add_unchecked(&phi_point)?;
+        let d = p_point.add_unchecked(&phi_point.negate()?)?;
+        let mut acc = s.double_unchecked()?.add_unchecked(&s)?;
+
+        // After processing the digit at position j, |A1|, |A2| < 2^{L-j}, and the incomplete
+        // formulas can only fail if a non-zero vector of at most these coordinates is in the
+        // lattice {(a, b) : a + b * λ = 0 mod n}, which is not the case when L - j <= safe_bits.
+        // Since A1, A2 >= 3, the case (A1, A2) = ±(d1, d2) does not happen either. The remaining
+        // digits are processed with complete formulas.
+        let n_complete = (l - 2).min(l.saturating_sub(safe_bits));
+        for j in (n_complete..l - 2).rev() {
+            let t = select_table(&k1_bits[j + 1], &k2_bits[j + 1], &s, &d, |t| t.negate())?;
+            acc = acc.double_and_add_unchecked(&t)?;
+        }
+
+        // Move to the complete formulas of `GC`, allocating `acc` and `φ(P)` as `GC` points whose
+        // coordinates are enforced to match the ones computed above
+        let mut acc = Self::to_gc(&acc, || {
+            let mut a = [BigInt::from(3u64), BigInt::from(3u64)];
+            for j in (n_complete..l - 2).rev() {
+                a[0] = &a[0] * 2 + digit(&k1_bits[j + 1])?;
+                a[1] = &a[1] * 2 + digit(&k2_bits[j + 1])?;
+            }
+            let [a1, a2] = a.map(|a| {
+                C::ScalarField::from(a.to_biguint().expect("the accumulator is positive"))
+            });
+            Ok(p.value()? * (a1 + a2 * C::LAMBDA))
+        })?;
+        let phi = Self::to_gc(&phi_point, || Ok(p.value()? * C::LAMBDA))?;
+        let s = p.clone() + phi.clone();
+        let d = p.clone() - phi.clone();
+        for j in (0..n_complete).rev() {
+            let t = select_table(&k1_bits[j + 1], &k2_bits[j + 1], &s, &d, |t| t.negate())?;
+            acc = acc.double()? + t;
+        }
+
+        // Undo the adjustment of the parity, ie. subtract (1 - k_i[0]) * P_i
+        acc = acc - k1_bits[0].select(&GC::zero(), &p)?;
+        acc = acc - k2_bits[0].select(&GC::zero(), &phi)?;
+
+        infinity.select(&GC::zero(), &acc)
+    }
+
+    /// Allocates the `GC` point with the given value, enforcing that it is the non-zero `point`.
+    fn to_gc(
+        point: &NonZeroPointVar<C::BaseField>,
+        value: impl FnOnce() -> Result<C, SynthesisError>,
+    ) -> Result<GC, SynthesisError> {
+        let point_gc = GC::new_witness(point.x.cs(), value)?;
+        point_gc.to_constraint_field()?.enforce_equal(&vec![
+            point.x.clone(),
+            point.y.clone(),
+            FpVar::zero(),
+        ])?;
+        Ok(point_gc)
+    }
+}
+
+/// `GLVCycleFoldConfig` wraps a [`CycleFoldConfig`] whose curve implements [`GLVCurve`], so that
+/// the [`CycleFoldCircuit`](super::cyclefold::CycleFoldCircuit) computes its scalar
+/// multiplications with the [`GLVScalarMulGadget`] instead of the plain double-and-add. The
+/// public inputs of the circuit are the same as with the wrapped config. The saving is the
+/// largest for full-length randomness, as in `NovaCycleFoldConfig<C, true>` over bn254 or
+/// grumpkin.
+pub struct GLVCycleFoldConfig<CFG: CycleFoldConfig> {
+    _cfg: PhantomData<CFG>,
+}
+
+impl<CFG: CycleFoldConfig> CycleFoldConfig for GLVCycleFoldConfig<CFG>
+where
+    CFG::C: GLVCurve,
+{
+    const N_INPUT_POINTS: usize = CFG::N_INPUT_POINTS;
+    const RANDOMNESS_BIT_LENGTH: usize = CFG::RANDOMNESS_BIT_LENGTH;
+    const CHALLENGE_BIT_LENGTH: usize = CFG::CHALLENGE_BIT_LENGTH;
+    const FIELD_CAPACITY: usize = CFG::FIELD_CAPACITY;
+    const IO_LEN: usize = CFG::IO_LEN;
+
+    type F = CFG::F;
+    type C = CFG::C;
+
+    fn check_challenge_bit_length() -> Result<(), Error> {
+        CFG::check_challenge_bit_length()
+    }
+
+    fn x_gadget(
+        r_bits: &[Boolean<CF1<Self::C>>],
+        points: &[NonNativeAffineVar<Self::C>],
+        p_folded: &NonNativeAffineVar<Self::C>,
+    ) -> Result<Vec<NonNativeUintVar<CF1<Self::C>>>, SynthesisError> {
+        CFG::x_gadget(r_bits, points, p_folded)
+    }
+
+    fn scalar_mul_gadget<GC>(p: &GC, r_bits: &[Boolean<Self::F>]) -> Result<GC, SynthesisError>
+    where
+        Self::F: PrimeField,
+        GC: CurveVar<Self::C, Self::F> + ToConstraintFieldGadget<Self::F>,
+    {
+        GLVScalarMulGadget::<Self::C, GC>::scalar_mul_le(p, r_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::{constraints::GVar, Fq, Fr, G1Projective as Projective};
+    use ark_ec::AffineRepr;
+    use ark_ff::Field;
+    use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+    use ark_std::UniformRand;
+
+    use super::*;
+    use crate::folding::circuits::cyclefold::CycleFoldCircuit;
+    use crate::folding::nova::NovaCycleFoldConfig;
+    use crate::folding::protogalaxy::ProtoGalaxyCycleFoldConfig;
+
+    fn check_endomorphism<C: GLVCurve>() {
+        let mut rng = ark_std::test_rng();
+        let p = C::rand(&mut rng).into_affine();
+        let (x, y) = p.xy().unwrap();
+        let phi = (p * C::LAMBDA).into_affine();
+        assert_eq!(phi.xy().unwrap(), (&(*x * C::BETA), y));
+        assert_eq!(C::BETA.pow([3]), C::BaseField::one());
+        assert_eq!(C::LAMBDA.pow([3]), C::ScalarField::one());
+    }
+
+    #[test]
+    fn test_endomorphism() {
+        check_endomorphism::<Projective>();
+        check_endomorphism::<Projective2>();
+    }
+
+    fn check_decompose_scalar<C: GLVCurve>() {
+        let mut rng = ark_std::test_rng();
+        let l = glv_bit_length::<C>();
+
+        let scalars = [
+            C::ScalarField::zero(),
+            C::ScalarField::one(),
+            -C::ScalarField::one(),
+            C::ScalarField::from(u128::MAX),
+        ]
+        .into_iter()
+        .chain(std::iter::repeat_with(|| C::ScalarField::rand(&mut rng)).take(100));
+        for k in scalars {
+            let [k1, k2] = decompose_scalar::<C>(&k);
+            assert_eq!(k1.bits() as usize, l);
+            assert_eq!(k2.bits() as usize, l);
+            assert_eq!(
+                C::ScalarField::from(k1) + C::ScalarField::from(k2) * C::LAMBDA,
+                k
+            );
+        }
+    }
+
+    #[test]
+    fn test_decompose_scalar() {
+        check_decompose_scalar::<Projective>();
+        check_decompose_scalar::<Projective2>();
+    }
+
+    fn check_glv_scalar_mul<C: GLVCurve, GC: CurveVar<C, C::BaseField>>()
+    where
+        C::BaseField: PrimeField,
+        GC: ToConstraintFieldGadget<C::BaseField>,
+    {
+        let mut rng = ark_std::test_rng();
+        let p = C::rand(&mut rng);
+        let k = C::ScalarField::rand(&mut rng);
+        let k_bits = k.into_bigint().to_bits_le();
+        for (p, k_bits) in [
+            // full-width scalar
+            (p, k_bits.clone()),
+            // half-width scalar
+            (p, k_bits[..128].to_vec()),
+            // zero scalar
+            (p, vec![false; k_bits.len()]),
+            // zero point
+            (C::zero(), k_bits.clone()),
+        ] {
+            let k = C::ScalarField::from_bigint(BigInteger::from_bits_le(&k_bits)).unwrap();
+
+            let cs = ConstraintSystem::<C::BaseField>::new_ref();
+            let pVar = GC::new_witness(cs.clone(), || Ok(p)).unwrap();
+            let k_bitsVar = Vec::new_witness(cs.clone(), || Ok(k_bits.clone())).unwrap();
+            let resVar = GLVScalarMulGadget::<C, GC>::scalar_mul_le(&pVar, &k_bitsVar).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+            assert_eq!(resVar.value().unwrap(), p * k);
+        }
+    }
+
+    #[test]
+    fn test_glv_scalar_mul() {
+        check_glv_scalar_mul::<Projective, GVar>();
+        check_glv_scalar_mul::<Projective2, GVar2>();
+    }
+
+    #[test]
+    fn test_glv_scalar_mul_wrong_decomposition() {
+        let mut rng = ark_std::test_rng();
+        let p = Projective::rand(&mut rng);
+        let k = Fr::rand(&mut rng);
+        let [k1, k2] = decompose_scalar::<Projective>(&k);
+
+        for decomposition in [[k1.clone() + 2u64, k2.clone()], [k1, k2 - 2u64]] {
+            let cs = ConstraintSystem::<Fq>::new_ref();
+            let pVar = GVar::new_witness(cs.clone(), || Ok(p)).unwrap();
+            let k_bitsVar =
+                Vec::new_witness(cs.clone(), || Ok(k.into_bigint().to_bits_le())).unwrap();
+            GLVScalarMulGadget::<Projective, GVar>::scalar_mul_le_with_decomposition(
+                &pVar,
+                &k_bitsVar,
+                decomposition,
+            )
+            .unwrap();
+            assert!(!cs.is_satisfied().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_glv_cyclefold_circuit_constraints() {
+        check_glv_cyclefold_circuit::<ProtoGalaxyCycleFoldConfig<Projective>>();
+        check_glv_cyclefold_circuit::<NovaCycleFoldConfig<Projective, true>>();
+    }
+
+    /// Checks that wrapping `CFG` in [`GLVCycleFoldConfig`] keeps the public inputs of the
+    /// CycleFold circuit and reduces its number of constraints.
+    fn check_glv_cyclefold_circuit<CFG: CycleFoldConfig<C = Projective, F = Fq>>() {
+        let mut rng = ark_std::test_rng();
+        let points: Vec<Projective> = (0..CFG::N_INPUT_POINTS)
+            .map(|_| Projective::rand(&mut rng))
+            .collect();
+        let r_bits =
+            Fr::rand(&mut rng).into_bigint().to_bits_le()[..CFG::RANDOMNESS_BIT_LENGTH].to_vec();
+
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let circuit = CycleFoldCircuit::<CFG, GVar>::new(r_bits.clone(), points.clone()).unwrap();
+        let x = circuit.x.clone();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let cs_glv = ConstraintSystem::<Fq>::new_ref();
+        let circuit_glv =
+            CycleFoldCircuit::<GLVCycleFoldConfig<CFG>, GVar>::new(r_bits, points).unwrap();
+        assert_eq!(circuit_glv.x, x);
+        circuit_glv.generate_constraints(cs_glv.clone()).unwrap();
+        assert!(cs_glv.is_satisfied().unwrap());
+
+        assert!(cs_glv.num_constraints() < cs.num_constraints());
+    }
+}
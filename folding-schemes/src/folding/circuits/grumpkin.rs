@@ -0,0 +1,417 @@
+/// Implements [`GrumpkinVar`], a curve variable for the points of Grumpkin over the BN254 scalar
+/// field whose scalar multiplication is cheaper than the one of the generic `ProjectiveVar`. It can
+/// be used as `GC2` for the BN254/Grumpkin cycle, where it computes the scalar multiplications of
+/// the CycleFold instances folded in the `AugmentedFCircuit`, and as the curve variable of the
+/// [`CycleFoldCircuit`](super::cyclefold::CycleFoldCircuit) when Grumpkin is the main curve.
+///
+/// It is opt-in: the generic `ark_grumpkin::constraints::GVar` remains the default in the
+/// examples and tests, and both variables allocate the same points and produce the same public
+/// inputs, so the choice does not change the folded instances.
+use ark_ec::Group;
+use ark_ff::PrimeField;
+use ark_grumpkin::{Affine, Fq, Fr, GrumpkinConfig, Projective};
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    groups::{curves::short_weierstrass::ProjectiveVar, CurveVar},
+    select::CondSelectGadget,
+    uint8::UInt8,
+    R1CSVar, ToBitsGadget, ToBytesGadget, ToConstraintFieldGadget,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+use ark_std::{
+    borrow::Borrow,
+    ops::{Add, AddAssign, Sub, SubAssign},
+    vec::Vec,
+};
+
+use super::glv::NonZeroPointVar;
+
+type GVar = ProjectiveVar<GrumpkinConfig, FpVar<Fq>>;
+
+/// Grumpkin point variable over `Fq`, the BN254 scalar field. All the operations but the scalar
+/// multiplication are the ones of the wrapped `ProjectiveVar`.
+#[derive(Clone, Debug)]
+pub struct GrumpkinVar(pub GVar);
+
+impl R1CSVar<Fq> for GrumpkinVar {
+    type Value = Projective;
+
+    fn cs(&self) -> ConstraintSystemRef<Fq> {
+        self.0.cs()
+    }
+
+    fn value(&self) -> Result<Self::Value, SynthesisError> {
+        self.0.value()
+    }
+}
+
+impl AllocVar<Projective, Fq> for GrumpkinVar {
+    fn new_variable<T: Borrow<Projective>>(
+        cs: impl Into<Namespace<Fq>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self(GVar::new_variable(cs, f, mode)?))
+    }
+}
+
+impl AllocVar<Affine, Fq> for GrumpkinVar {
+    fn new_variable<T: Borrow<Affine>>(
+        cs: impl Into<Namespace<Fq>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self(GVar::new_variable(cs, f, mode)?))
+    }
+}
+
+impl EqGadget<Fq> for GrumpkinVar {
+    fn is_eq(&self, other: &Self) -> Result<Boolean<Fq>, SynthesisError> {
+        self.0.is_eq(&other.0)
+    }
+
+    fn conditional_enforce_equal(
+        &self,
+        other: &Self,
+        should_enforce: &Boolean<Fq>,
+    ) -> Result<(), SynthesisError> {
+        self.0.conditional_enforce_equal(&other.0, should_enforce)
+    }
+
+    fn conditional_enforce_not_equal(
+        &self,
+        other: &Self,
+        should_enforce: &Boolean<Fq>,
+    ) -> Result<(), SynthesisError> {
+        self.0
+            .conditional_enforce_not_equal(&other.0, should_enforce)
+    }
+}
+
+impl CondSelectGadget<Fq> for GrumpkinVar {
+    fn conditionally_select(
+        cond: &Boolean<Fq>,
+        true_value: &Self,
+        false_value: &Self,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self(cond.select(&true_value.0, &false_value.0)?))
+    }
+}
+
+impl ToBitsGadget<Fq> for GrumpkinVar {
+    fn to_bits_le(&self) -> Result<Vec<Boolean<Fq>>, SynthesisError> {
+        self.0.to_bits_le()
+    }
+
+    fn to_non_unique_bits_le(&self) -> Result<Vec<Boolean<Fq>>, SynthesisError> {
+        self.0.to_non_unique_bits_le()
+    }
+}
+
+impl ToBytesGadget<Fq> for GrumpkinVar {
+    fn to_bytes(&self) -> Result<Vec<UInt8<Fq>>, SynthesisError> {
+        self.0.to_bytes()
+    }
+
+    fn to_non_unique_bytes(&self) -> Result<Vec<UInt8<Fq>>, SynthesisError> {
+        self.0.to_non_unique_bytes()
+    }
+}
+
+impl ToConstraintFieldGadget<Fq> for GrumpkinVar {
+    fn to_constraint_field(&self) -> Result<Vec<FpVar<Fq>>, SynthesisError> {
+        self.0.to_constraint_field()
+    }
+}
+
+/// Implements `$trait` (and `$assign_trait`) for `GrumpkinVar` and `&GrumpkinVar` with the
+/// right-hand sides that `CurveVar` requires, delegating to the ones of `ProjectiveVar`.
+macro_rules! impl_group_ops {
+    ($trait:ident, $fn:ident, $assign_trait:ident, $assign_fn:ident, $op:tt) => {
+        impl<'a> $trait<&'a GrumpkinVar> for &'a GrumpkinVar {
+            type Output = GrumpkinVar;
+            fn $fn(self, other: &'a GrumpkinVar) -> GrumpkinVar {
+                GrumpkinVar(&self.0 $op &other.0)
+            }
+        }
+
+        impl<'a> $trait<GrumpkinVar> for &'a GrumpkinVar {
+            type Output = GrumpkinVar;
+            fn $fn(self, other: GrumpkinVar) -> GrumpkinVar {
+                GrumpkinVar(&self.0 $op &other.0)
+            }
+        }
+
+        impl<'a> $trait<Projective> for &'a GrumpkinVar {
+            type Output = GrumpkinVar;
+            fn $fn(self, other: Projective) -> GrumpkinVar {
+                GrumpkinVar(&self.0 $op other)
+            }
+        }
+
+        impl<'a> $trait<&'a GrumpkinVar> for GrumpkinVar {
+            type Output = GrumpkinVar;
+            fn $fn(self, other: &'a GrumpkinVar) -> GrumpkinVar {
+                GrumpkinVar(&self.0 $op &other.0)
+            }
+        }
+
+        impl $trait<GrumpkinVar> for GrumpkinVar {
+            type Output = GrumpkinVar;
+            fn $fn(self, other: GrumpkinVar) -> GrumpkinVar {
+                GrumpkinVar(&self.0 $op &other.0)
+            }
+        }
+
+        impl $trait<Projective> for GrumpkinVar {
+            type Output = GrumpkinVar;
+            fn $fn(self, other: Projective) -> GrumpkinVar {
+                GrumpkinVar(&self.0 $op other)
+            }
+        }
+
+        impl<'a> $assign_trait<&'a GrumpkinVar> for GrumpkinVar {
+            fn $assign_fn(&mut self, other: &'a GrumpkinVar) {
+                self.0 = &self.0 $op &other.0;
+            }
+        }
+
+        impl $assign_trait<GrumpkinVar> for GrumpkinVar {
+            fn $assign_fn(&mut self, other: GrumpkinVar) {
+                self.0 = &self.0 $op &other.0;
+            }
+        }
+
+        impl $assign_trait<Projective> for GrumpkinVar {
+            fn $assign_fn(&mut self, other: Projective) {
+                self.0 = &self.0 $op other;
+            }
+        }
+    };
+}
+
+impl_group_ops!(Add, add, AddAssign, add_assign, +);
+impl_group_ops!(Sub, sub, SubAssign, sub_assign, -);
+
+impl CurveVar<Projective, Fq> for GrumpkinVar {
+    fn constant(other: Projective) -> Self {
+        Self(GVar::constant(other))
+    }
+
+    fn zero() -> Self {
+        Self(GVar::zero())
+    }
+
+    fn is_zero(&self) -> Result<Boolean<Fq>, SynthesisError> {
+        self.0.is_zero()
+    }
+
+    fn new_variable_omit_prime_order_check(
+        cs: impl Into<Namespace<Fq>>,
+        f: impl FnOnce() -> Result<Projective, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self(GVar::new_variable_omit_prime_order_check(
+            cs, f, mode,
+        )?))
+    }
+
+    fn enforce_prime_order(&self) -> Result<(), SynthesisError> {
+        self.0.enforce_prime_order()
+    }
+
+    fn double_in_place(&mut self) -> Result<(), SynthesisError> {
+        self.0.double_in_place()
+    }
+
+    fn negate(&self) -> Result<Self, SynthesisError> {
+        Ok(Self(self.0.negate()?))
+    }
+
+    /// Computes `k * P` for `P = self` with a signed-digit double-and-add over incomplete affine
+    /// formulas, where `k = sum_i b_i * 2^i` is given by its `n` little-endian bits.
+    ///
+    /// The bits `b_1, ..., b_{n-1}` are read as the digits `d_i = 2 * b_i - 1` in `{-1, 1}`, which
+    /// are accumulated as `acc = sum_{i >= 1} d_i * 2^{i-1} * P = (k - b_0 - 2^{n-1} + 1) * P`,
+    /// so that `k * P = acc + 2^{n-1} * P - (1 - b_0) * P`. Adding the signed point `d_i * Q`
+    /// costs one constraint for the sign of its `y` coordinate and three for the addition, while
+    /// the double-and-add of `ProjectiveVar` selects between `acc + Q` and `acc` with two.
+    ///
+    /// The incomplete formulas are sound for any `P`, not only for the random commitments folded
+    /// by CycleFold: all the points are multiples of the same non-zero `P`, and since Grumpkin has
+    /// prime order `r`, two of them are equal (or opposite) only if their coefficients are
+    /// congruent (or opposite) modulo `r`. Before adding `d_i * 2^{i-1} * P`, the coefficient of
+    /// `acc` is odd and smaller than `2^{i-1}` in absolute value, so it is neither `±2^{i-1}` nor
+    /// can the sum be zero, and the doubled points are never zero nor of order 2. Thus, as long as
+    /// `2^n < r`, the denominators of all the slopes are non-zero and every intermediate point is
+    /// uniquely determined by the constraints. The final correction, which gives zero for
+    /// `k = 0`, uses the complete formulas, and `P = 0` is handled by computing over the
+    /// generator and selecting zero at the end.
+    ///
+    /// Constant points, and scalars with fewer than 2 bits or with as many bits as `r`, use the
+    /// generic double-and-add.
+    fn scalar_mul_le<'a>(
+        &self,
+        bits: impl Iterator<Item = &'a Boolean<Fq>>,
+    ) -> Result<Self, SynthesisError> {
+        let bits: Vec<Boolean<Fq>> = bits.cloned().collect();
+        let n = bits.len();
+        if self.0.is_constant() || n < 2 || n >= Fr::MODULUS_BIT_SIZE as usize {
+            return Ok(Self(self.0.scalar_mul_le(bits.iter())?));
+        }
+
+        let infinity = self.0.is_zero()?;
+        let p = infinity.select(&GVar::constant(Projective::generator()), &self.0)?;
+        let p_affine = p.to_affine()?;
+        // q = 2^{i-1} * P at the i-th iteration
+        let mut q = NonZeroPointVar {
+            x: p_affine.x,
+            y: p_affine.y,
+        };
+        let signed = |q: &NonZeroPointVar<Fq>, b: &Boolean<Fq>| -> Result<_, SynthesisError> {
+            Ok(NonZeroPointVar {
+                x: q.x.clone(),
+                y: b.select(&q.y, &q.y.negate()?)?,
+            })
+        };
+
+        let mut acc = signed(&q, &bits[1])?;
+        for b in &bits[2..] {
+            q = q.double_unchecked()?;
+            acc = acc.add_unchecked(&signed(&q, b)?)?;
+        }
+        // the coefficient of acc + 2^{n-1} * P is k - b_0 + 1, which lies in [1, 2^n)
+        let acc = acc.add_unchecked(&q.double_unchecked()?)?;
+
+        let acc = GVar::new(acc.x, acc.y, FpVar::one()) - bits[0].select(&GVar::zero(), &p)?;
+        Ok(Self(infinity.select(&GVar::zero(), &acc)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::{constraints::GVar as GVar1, Fr as Fr1, G1Projective as Projective1};
+    use ark_ff::{BigInteger, Zero};
+    use ark_grumpkin::constraints::GVar as GenericGVar;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+    use ark_std::UniformRand;
+
+    use super::*;
+    use crate::commitment::pedersen::Pedersen;
+    use crate::constants::NOVA_N_BITS_RO;
+    use crate::folding::circuits::cyclefold::CycleFoldCircuit;
+    use crate::folding::nova::{Nova, NovaCycleFoldConfig, PreprocessorParam};
+    use crate::frontend::{utils::CubicFCircuit, FCircuit};
+    use crate::transcript::poseidon::poseidon_canonical_config;
+    use crate::FoldingScheme;
+
+    #[test]
+    fn test_scalar_mul() {
+        let mut rng = ark_std::test_rng();
+        let p = Projective::rand(&mut rng);
+        let k_bits = Fr::rand(&mut rng).into_bigint().to_bits_le();
+        for (p, k_bits) in [
+            // challenge-sized scalar
+            (p, k_bits[..NOVA_N_BITS_RO].to_vec()),
+            (p, vec![true; NOVA_N_BITS_RO]),
+            (p, [vec![true], vec![false; NOVA_N_BITS_RO - 1]].concat()),
+            (p, [vec![false], vec![true; NOVA_N_BITS_RO - 1]].concat()),
+            (p, vec![false; NOVA_N_BITS_RO]),
+            (p, vec![true, false]),
+            // widest scalar of the incomplete formulas, and full-width scalar (generic path)
+            (p, k_bits[..Fr::MODULUS_BIT_SIZE as usize - 1].to_vec()),
+            (p, k_bits.clone()),
+            // zero point
+            (Projective::zero(), k_bits[..NOVA_N_BITS_RO].to_vec()),
+        ] {
+            let k = Fr::from_bigint(BigInteger::from_bits_le(&k_bits)).unwrap();
+
+            let cs = ConstraintSystem::<Fq>::new_ref();
+            let pVar = GrumpkinVar::new_witness(cs.clone(), || Ok(p)).unwrap();
+            let k_bitsVar = Vec::new_witness(cs.clone(), || Ok(k_bits.clone())).unwrap();
+            let resVar = pVar.scalar_mul_le(k_bitsVar.iter()).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+            assert_eq!(resVar.value().unwrap(), p * k);
+        }
+    }
+
+    /// the CycleFold circuit with Grumpkin as main curve computes the same public inputs with
+    /// fewer constraints than with the generic curve variable, saving at least one constraint per
+    /// bit of the randomness
+    #[test]
+    fn test_cyclefold_circuit_constraints() {
+        let mut rng = ark_std::test_rng();
+        let points = vec![Projective::rand(&mut rng), Projective::rand(&mut rng)];
+        let r_bits = Fr::rand(&mut rng).into_bigint().to_bits_le()[..NOVA_N_BITS_RO].to_vec();
+
+        let cs_generic = ConstraintSystem::<Fq>::new_ref();
+        let circuit = CycleFoldCircuit::<NovaCycleFoldConfig<Projective>, GenericGVar>::new(
+            r_bits.clone(),
+            points.clone(),
+        )
+        .unwrap();
+        let x = circuit.x.clone();
+        circuit.generate_constraints(cs_generic.clone()).unwrap();
+        assert!(cs_generic.is_satisfied().unwrap());
+
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let circuit =
+            CycleFoldCircuit::<NovaCycleFoldConfig<Projective>, GrumpkinVar>::new(r_bits, points)
+                .unwrap();
+        assert_eq!(circuit.x, x);
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        assert!(cs.num_constraints() + NOVA_N_BITS_RO - 2 <= cs_generic.num_constraints());
+    }
+
+    /// Nova over BN254/Grumpkin with `GrumpkinVar` as `GC2`, whose AugmentedFCircuit is smaller
+    /// than with the generic curve variable
+    #[test]
+    fn test_ivc_with_grumpkin_var() {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr1>();
+        let F_circuit = CubicFCircuit::<Fr1>::new(()).unwrap();
+        let prep_param = PreprocessorParam::new(poseidon_config, F_circuit);
+
+        type N = Nova<
+            Projective1,
+            GVar1,
+            Projective,
+            GrumpkinVar,
+            CubicFCircuit<Fr1>,
+            Pedersen<Projective1>,
+            Pedersen<Projective>,
+            false,
+        >;
+        let nova_params = N::preprocess(&mut rng, &prep_param).unwrap();
+        let mut nova = N::init(&nova_params, F_circuit, vec![Fr1::from(3_u32)]).unwrap();
+        for _ in 0..3 {
+            nova.prove_step(&mut rng, (), None).unwrap();
+        }
+        N::verify(nova_params.1.clone(), nova.ivc_proof()).unwrap();
+
+        let mut z = vec![Fr1::from(3_u32)];
+        for i in 0..3 {
+            z = F_circuit.step_native(i, z, ()).unwrap();
+        }
+        assert_eq!(nova.state().z_i, z);
+
+        let generic_params = Nova::<
+            Projective1,
+            GVar1,
+            Projective,
+            GenericGVar,
+            CubicFCircuit<Fr1>,
+            Pedersen<Projective1>,
+            Pedersen<Projective>,
+            false,
+        >::preprocess(&mut rng, &prep_param)
+        .unwrap();
+        assert!(nova_params.1.r1cs.A.n_rows < generic_params.1.r1cs.A.n_rows);
+        assert_eq!(nova_params.1.cf_r1cs, generic_params.1.cf_r1cs);
+    }
+}
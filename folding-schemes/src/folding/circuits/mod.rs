@@ -4,7 +4,10 @@ use ark_ff::Field;
 
 pub mod cyclefold;
 pub mod decider;
+pub mod glv;
+pub mod grumpkin;
 pub mod nonnative;
+#[cfg(feature = "std")]
 pub mod sum_check;
 pub mod utils;
 
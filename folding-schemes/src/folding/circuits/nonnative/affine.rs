@@ -1,15 +1,19 @@
-use ark_ec::{short_weierstrass::SWFlags, AffineRepr, CurveGroup};
-use ark_ff::{Field, PrimeField};
+use ark_ec::{
+    short_weierstrass::{Projective, SWCurveConfig, SWFlags},
+    AffineRepr, CurveGroup, Group,
+};
+use ark_ff::{BigInteger, Field, PrimeField};
 use ark_r1cs_std::{
     alloc::{AllocVar, AllocationMode},
     eq::EqGadget,
-    fields::fp::FpVar,
+    fields::{fp::FpVar, FieldVar},
     prelude::Boolean,
+    select::CondSelectGadget,
     R1CSVar, ToConstraintFieldGadget,
 };
 use ark_relations::r1cs::{ConstraintSystemRef, Namespace, SynthesisError};
 use ark_serialize::{CanonicalSerialize, CanonicalSerializeWithFlags};
-use ark_std::Zero;
+use ark_std::{vec::Vec, Zero};
 use core::borrow::Borrow;
 
 use crate::{
@@ -17,11 +21,12 @@ use crate::{
     transcript::{AbsorbNonNative, AbsorbNonNativeGadget},
 };
 
-use super::uint::{nonnative_field_to_field_elements, NonNativeUintVar};
+use super::uint::{nonnative_field_to_field_elements, LimbVar, NonNativeUintVar};
 
 /// NonNativeAffineVar represents an elliptic curve point in Affine representation in the non-native
-/// field, over the constraint field. It is not intended to perform operations, but just to contain
-/// the affine coordinates in order to perform hash operations of the point.
+/// field, over the constraint field. It is mainly intended to contain the affine coordinates in
+/// order to perform hash operations of the point, and only supports the (expensive) group
+/// operations needed by `scalar_mul_le_small`.
 #[derive(Debug, Clone)]
 pub struct NonNativeAffineVar<C: CurveGroup> {
     pub x: NonNativeUintVar<C::ScalarField>,
@@ -47,6 +52,13 @@ where
             let x = NonNativeUintVar::new_variable(cs.clone(), || Ok(*x), mode)?;
             let y = NonNativeUintVar::new_variable(cs.clone(), || Ok(*y), mode)?;
 
+            if mode == AllocationMode::Witness {
+                // Enforce that the coordinates provided by the prover are
+                // canonical, so that each point has a unique representation.
+                x.enforce_canonical::<<C::BaseField as Field>::BasePrimeField>()?;
+                y.enforce_canonical::<<C::BaseField as Field>::BasePrimeField>()?;
+            }
+
             Ok(Self { x, y })
         })
     }
@@ -105,6 +117,19 @@ impl<C: CurveGroup> ToConstraintFieldGadget<C::ScalarField> for NonNativeAffineV
     }
 }
 
+impl<C: CurveGroup> CondSelectGadget<C::ScalarField> for NonNativeAffineVar<C> {
+    fn conditionally_select(
+        cond: &Boolean<C::ScalarField>,
+        true_value: &Self,
+        false_value: &Self,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            x: cond.select(&true_value.x, &false_value.x)?,
+            y: cond.select(&true_value.y, &false_value.y)?,
+        })
+    }
+}
+
 impl<C: CurveGroup> EqGadget<C::ScalarField> for NonNativeAffineVar<C> {
     fn is_eq(&self, other: &Self) -> Result<Boolean<C::ScalarField>, SynthesisError> {
         let mut result = Boolean::TRUE;
@@ -166,6 +191,36 @@ pub(crate) fn nonnative_affine_to_field_elements<C: CurveGroup>(
     (x, y)
 }
 
+/// The out-circuit counterpart of `NonNativeAffineVar::to_packed_field_elements`
+pub(crate) fn nonnative_affine_to_packed_field_elements<C: CurveGroup>(
+    p: C,
+) -> Vec<C::ScalarField> {
+    let affine = p.into_affine();
+    let zero = (&C::BaseField::zero(), &C::BaseField::zero());
+    let (x, y) = affine.xy().unwrap_or(zero);
+
+    assert_eq!(C::BaseField::extension_degree(), 1);
+    let n_bits = <C::BaseField as Field>::BasePrimeField::MODULUS_BIT_SIZE as usize;
+    let bits = [x, y]
+        .into_iter()
+        .flat_map(|v| {
+            // `unwrap` is safe because `C::BaseField` has extension degree 1
+            let mut bits = v
+                .to_base_prime_field_elements()
+                .next()
+                .unwrap()
+                .into_bigint()
+                .to_bits_le();
+            bits.resize(n_bits, false);
+            bits
+        })
+        .collect::<Vec<_>>();
+    bits.chunks(C::ScalarField::MODULUS_BIT_SIZE as usize - 1)
+        .map(<C::ScalarField as PrimeField>::BigInt::from_bits_le)
+        .map(C::ScalarField::from)
+        .collect()
+}
+
 impl<C: CurveGroup> Inputize<C::ScalarField, NonNativeAffineVar<C>> for C {
     fn inputize(&self) -> Vec<C::ScalarField> {
         let affine = self.into_affine();
@@ -184,6 +239,249 @@ impl<C: CurveGroup> NonNativeAffineVar<C> {
         // which is guaranteed to succeed.
         Self::new_constant(ConstraintSystemRef::None, C::zero()).unwrap()
     }
+
+    /// Packs the bits of `x || y` (|| is for concat) into as few field elements as possible, which
+    /// is a more compact encoding of the point than the limbs of its coordinates.
+    pub fn to_packed_field_elements(&self) -> Result<Vec<FpVar<C::ScalarField>>, SynthesisError> {
+        let n_bits = <C::BaseField as Field>::BasePrimeField::MODULUS_BIT_SIZE as usize;
+        let bits = [
+            self.x.to_bits_le_with_length(n_bits)?,
+            self.y.to_bits_le_with_length(n_bits)?,
+        ]
+        .concat();
+        bits.chunks(C::ScalarField::MODULUS_BIT_SIZE as usize - 1)
+            .map(Boolean::le_bits_to_fp_var)
+            .collect()
+    }
+
+    /// Allocates the point as a witness, whose packed encoding given by `to_packed_field_elements`
+    /// is allocated as public input. The verifier then computes the public input with
+    /// `nonnative_affine_to_packed_field_elements` instead of `Inputize::inputize`, which needs
+    /// fewer field elements per point (3 instead of 10 for BN254).
+    pub fn new_packed_input<T: Borrow<C>>(
+        cs: impl Into<Namespace<C::ScalarField>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+    ) -> Result<Self, SynthesisError> {
+        let cs = cs.into().cs();
+        let point = *f()?.borrow();
+
+        let var = Self::new_witness(cs.clone(), || Ok(point))?;
+        let packed = Vec::<FpVar<C::ScalarField>>::new_input(cs, || {
+            Ok(nonnative_affine_to_packed_field_elements(point))
+        })?;
+        var.to_packed_field_elements()?.enforce_equal(&packed)?;
+
+        Ok(var)
+    }
+}
+
+/// Returns whether the aligned `x` and `y` with the same limbs are equal. For canonical `x` and `y`,
+/// this is equivalent to their congruence.
+fn is_eq_aligned<F: PrimeField>(
+    x: &NonNativeUintVar<F>,
+    y: &NonNativeUintVar<F>,
+) -> Result<Boolean<F>, SynthesisError> {
+    let mut result = Boolean::TRUE;
+    for (l, r) in x.0.iter().zip(&y.0) {
+        result = result.and(&l.v.is_eq(&r.v)?)?;
+    }
+    Ok(result)
+}
+
+/// Returns whether the aligned `x` is zero.
+fn is_zero_aligned<F: PrimeField>(x: &NonNativeUintVar<F>) -> Result<Boolean<F>, SynthesisError> {
+    let mut result = Boolean::TRUE;
+    for l in &x.0 {
+        result = result.and(&l.v.is_zero()?)?;
+    }
+    Ok(result)
+}
+
+/// Enforces `lhs` to be congruent to `rhs` modulo `M::MODULUS` only if `cond` is true. Otherwise,
+/// both sides are replaced with zero, which makes the check trivially satisfied.
+fn conditional_enforce_congruent<F: PrimeField, M: PrimeField>(
+    cond: &Boolean<F>,
+    lhs: &NonNativeUintVar<F>,
+    rhs: &NonNativeUintVar<F>,
+) -> Result<(), SynthesisError> {
+    let zero_unless = |x: &NonNativeUintVar<F>| -> Result<_, SynthesisError> {
+        Ok(NonNativeUintVar(
+            x.0.iter()
+                .map(|l| {
+                    Ok(LimbVar {
+                        v: cond.select(&l.v, &FpVar::zero())?,
+                        ub: l.ub.clone(),
+                    })
+                })
+                .collect::<Result<Vec<_>, SynthesisError>>()?,
+        ))
+    };
+    zero_unless(lhs)?.enforce_congruent::<M>(&zero_unless(rhs)?)
+}
+
+/// Group operations over `NonNativeAffineVar`, where the coordinates are emulated with
+/// `NonNativeUintVar` and the point at infinity is `(0, 0)`.
+///
+/// These assume that the coordinates of the operands are canonical and that the operands are on
+/// the curve (or are the point at infinity), which holds for points allocated as witness, input or
+/// constant, and for the outputs of these operations.
+///
+/// Every operation allocates the result and the slope of the line through the operands as
+/// witnesses, and checks them with a few conditional congruences over the emulated field, which
+/// costs a few thousand constraints for ~255-bit fields.
+impl<P: SWCurveConfig> NonNativeAffineVar<Projective<P>>
+where
+    P::BaseField: PrimeField,
+{
+    /// Returns whether `self` is the point at infinity.
+    pub fn is_zero(&self) -> Result<Boolean<P::ScalarField>, SynthesisError> {
+        is_zero_aligned(&self.x)?.and(&is_zero_aligned(&self.y)?)
+    }
+
+    /// Enforces `r = (x3, y3)` to be the point whose x coordinate is `λ^2 - x1 - x2`, and whose
+    /// y coordinate is `λ (x1 - x3) - y1`, when `cond` is true.
+    /// This is the final step of both the addition (`x1 != x2`) and the doubling (`x1 = x2`) of
+    /// points `(x1, y1)` and `(x2, _)`, given the slope `λ` of the line through them.
+    fn enforce_chord_result(
+        cond: &Boolean<P::ScalarField>,
+        lambda: &NonNativeUintVar<P::ScalarField>,
+        x1: &NonNativeUintVar<P::ScalarField>,
+        y1: &NonNativeUintVar<P::ScalarField>,
+        x2: &NonNativeUintVar<P::ScalarField>,
+        r: &Self,
+    ) -> Result<(), SynthesisError> {
+        // `x3 + x1 + x2 = λ^2`
+        conditional_enforce_congruent::<_, P::BaseField>(
+            cond,
+            &r.x.add_no_align(x1)?.add_no_align(x2)?,
+            &lambda.mul_no_align(lambda)?,
+        )?;
+        // `y3 + y1 + λ x3 = λ x1`
+        conditional_enforce_congruent::<_, P::BaseField>(
+            cond,
+            &r.y.add_no_align(y1)?
+                .add_no_align(&lambda.mul_no_align(&r.x)?)?,
+            &lambda.mul_no_align(x1)?,
+        )
+    }
+
+    /// Enforces `2 λ y = 3 x^2 + a` when `cond` is true, i.e., `λ` is the slope of the tangent at
+    /// `(x, y)`.
+    fn enforce_tangent(
+        cond: &Boolean<P::ScalarField>,
+        lambda: &NonNativeUintVar<P::ScalarField>,
+        x: &NonNativeUintVar<P::ScalarField>,
+        y: &NonNativeUintVar<P::ScalarField>,
+    ) -> Result<(), SynthesisError> {
+        let a = NonNativeUintVar::new_constant(ConstraintSystemRef::None, P::COEFF_A)?;
+        let xx = x.mul_no_align(x)?;
+        conditional_enforce_congruent::<_, P::BaseField>(
+            cond,
+            &lambda.add_no_align(lambda)?.mul_no_align(y)?,
+            &xx.add_no_align(&xx)?.add_no_align(&xx)?.add_no_align(&a)?,
+        )
+    }
+
+    /// Computes `2 * self`.
+    pub fn double(&self) -> Result<Self, SynthesisError> {
+        let cs = self.cs();
+        let p = self.value().unwrap_or_default();
+        if cs.is_none() {
+            return Self::new_constant(cs, p.double());
+        }
+
+        let lambda = match p.into_affine().xy() {
+            Some((&x, &y)) if !y.is_zero() => {
+                (x.square().double() + x.square() + P::COEFF_A) / y.double()
+            }
+            _ => P::BaseField::zero(),
+        };
+        let lambda = NonNativeUintVar::new_witness(cs.clone(), || Ok(lambda))?;
+        let r = Self::new_witness(cs, || Ok(p.double()))?;
+
+        // `y = 0` iff `self` is the point at infinity or a point of order 2, in which case the
+        // result is the point at infinity.
+        let is_tangent = is_zero_aligned(&self.y)?.not();
+        r.conditional_enforce_equal(&Self::zero(), &is_tangent.not())?;
+        Self::enforce_tangent(&is_tangent, &lambda, &self.x, &self.y)?;
+        Self::enforce_chord_result(&is_tangent, &lambda, &self.x, &self.y, &self.x, &r)?;
+
+        Ok(r)
+    }
+
+    /// Computes `self + other`, handling all the exceptional cases, i.e., when either operand is
+    /// the point at infinity, or when `self = ±other`.
+    pub fn add(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let cs = self.cs().or(other.cs());
+        let p = self.value().unwrap_or_default();
+        let q = other.value().unwrap_or_default();
+        if cs.is_none() {
+            return Self::new_constant(cs, p + q);
+        }
+
+        let lambda = match (p.into_affine().xy(), q.into_affine().xy()) {
+            (Some((&x1, &y1)), Some((&x2, &y2))) if x1 != x2 => (y2 - y1) / (x2 - x1),
+            (Some((&x1, &y1)), Some((_, &y2))) if y1 == y2 && !y1.is_zero() => {
+                (x1.square().double() + x1.square() + P::COEFF_A) / y1.double()
+            }
+            _ => P::BaseField::zero(),
+        };
+        let lambda = NonNativeUintVar::new_witness(cs.clone(), || Ok(lambda))?;
+        let r = Self::new_witness(cs, || Ok(p + q))?;
+
+        let p_is_zero = self.is_zero()?;
+        let q_is_zero = other.is_zero()?;
+        let same_x = p_is_zero
+            .not()
+            .and(&q_is_zero.not())?
+            .and(&is_eq_aligned(&self.x, &other.x)?)?;
+        let same_y = is_eq_aligned(&self.y, &other.y)?.and(&is_zero_aligned(&self.y)?.not())?;
+        // `self` and `other` are distinct and not the point at infinity
+        let is_chord = p_is_zero.not().and(&q_is_zero.not())?.and(&same_x.not())?;
+        // `self = other` and `self` is neither the point at infinity nor a point of order 2
+        let is_tangent = same_x.and(&same_y)?;
+        // `self = -other`
+        let is_inverse = same_x.and(&same_y.not())?;
+
+        r.conditional_enforce_equal(other, &p_is_zero)?;
+        r.conditional_enforce_equal(self, &q_is_zero)?;
+        r.conditional_enforce_equal(&Self::zero(), &is_inverse)?;
+        // `λ (x2 - x1) = y2 - y1`
+        conditional_enforce_congruent::<_, P::BaseField>(
+            &is_chord,
+            &lambda.mul_no_align(&other.x)?.add_no_align(&self.y)?,
+            &lambda.mul_no_align(&self.x)?.add_no_align(&other.y)?,
+        )?;
+        Self::enforce_tangent(&is_tangent, &lambda, &self.x, &self.y)?;
+        Self::enforce_chord_result(
+            &is_chord.or(&is_tangent)?,
+            &lambda,
+            &self.x,
+            &self.y,
+            &other.x,
+            &r,
+        )?;
+
+        Ok(r)
+    }
+
+    /// Computes `k * self` with the double-and-add algorithm, where `k` is given by its
+    /// little-endian `bits`.
+    ///
+    /// Each bit costs a `double` and an `add`, i.e., several thousand constraints, so this is only
+    /// economical for very small scalars (e.g., 2 to 8 bits, as in cofactor clearing). Larger
+    /// scalar multiplications should be delegated to a CycleFold circuit instead.
+    pub fn scalar_mul_le_small(
+        &self,
+        bits: &[Boolean<P::ScalarField>],
+    ) -> Result<Self, SynthesisError> {
+        let mut acc = Self::zero();
+        for bit in bits.iter().rev() {
+            acc = acc.double()?;
+            acc = bit.select(&acc.add(self)?, &acc)?;
+        }
+        Ok(acc)
+    }
 }
 
 impl<C: CurveGroup> AbsorbNonNative<C::ScalarField> for C {
@@ -216,6 +514,84 @@ mod tests {
         // dealing with the 'zero' point should not panic when doing the unwrap
         let p = Projective::zero();
         assert!(NonNativeAffineVar::<Projective>::new_witness(cs.clone(), || Ok(p)).is_ok());
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_add_and_double() {
+        let mut rng = ark_std::test_rng();
+        let p = Projective::rand(&mut rng);
+        let q = Projective::rand(&mut rng);
+
+        // the generic case, and all the exceptional cases
+        for (a, b) in [
+            (p, q),
+            (p, p),
+            (p, -p),
+            (p, Projective::zero()),
+            (Projective::zero(), p),
+            (Projective::zero(), Projective::zero()),
+        ] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let aVar = NonNativeAffineVar::<Projective>::new_witness(cs.clone(), || Ok(a)).unwrap();
+            let bVar = NonNativeAffineVar::<Projective>::new_witness(cs.clone(), || Ok(b)).unwrap();
+            assert_eq!(aVar.add(&bVar).unwrap().value().unwrap(), a + b);
+            assert_eq!(aVar.double().unwrap().value().unwrap(), a.double());
+            assert_eq!(aVar.is_zero().unwrap().value().unwrap(), a.is_zero());
+            assert!(cs.is_satisfied().unwrap());
+        }
+
+        // a wrong result is rejected
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let pVar = NonNativeAffineVar::<Projective>::new_witness(cs.clone(), || Ok(p)).unwrap();
+        let qVar = NonNativeAffineVar::<Projective>::new_witness(cs.clone(), || Ok(q)).unwrap();
+        let wrong =
+            NonNativeAffineVar::<Projective>::new_witness(cs.clone(), || Ok(p + p)).unwrap();
+        pVar.add(&qVar).unwrap().enforce_equal(&wrong).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_scalar_mul_le_small() {
+        let mut rng = ark_std::test_rng();
+        let p = Projective::rand(&mut rng);
+
+        for (k, n_bits) in [
+            (0u64, 2),
+            (1, 2),
+            (2, 2),
+            (3, 2),
+            (5, 4),
+            (0b10110101, 8),
+            (255, 8),
+        ] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let pVar = NonNativeAffineVar::<Projective>::new_witness(cs.clone(), || Ok(p)).unwrap();
+            let bits = (0..n_bits).map(|i| (k >> i) & 1 == 1).collect::<Vec<_>>();
+            let bitsVar = Vec::<Boolean<Fr>>::new_witness(cs.clone(), || Ok(bits)).unwrap();
+
+            let n_constraints = cs.num_constraints();
+            let kpVar = pVar.scalar_mul_le_small(&bitsVar).unwrap();
+            // each bit costs a doubling and an addition over the emulated field
+            assert!(cs.num_constraints() - n_constraints < 10000 * n_bits);
+
+            assert_eq!(kpVar.value().unwrap(), p * Fr::from(k));
+            assert!(cs.is_satisfied().unwrap());
+        }
+
+        // multiplying the point at infinity
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let zeroVar =
+            NonNativeAffineVar::<Projective>::new_witness(cs.clone(), || Ok(Projective::zero()))
+                .unwrap();
+        let bitsVar = Vec::<Boolean<Fr>>::new_witness(cs.clone(), || Ok(vec![true; 3])).unwrap();
+        assert!(zeroVar
+            .scalar_mul_le_small(&bitsVar)
+            .unwrap()
+            .value()
+            .unwrap()
+            .is_zero());
+        assert!(cs.is_satisfied().unwrap());
     }
 
     #[test]
@@ -248,4 +624,22 @@ mod tests {
             xy
         );
     }
+
+    #[test]
+    fn test_packed_input() {
+        let mut rng = ark_std::test_rng();
+        for p in [Projective::rand(&mut rng), Projective::zero()] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let pVar =
+                NonNativeAffineVar::<Projective>::new_packed_input(cs.clone(), || Ok(p)).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+            assert_eq!(pVar.value().unwrap(), p);
+
+            let packed = nonnative_affine_to_packed_field_elements(p);
+            assert_eq!(packed.len(), 3);
+            assert!(packed.len() < p.inputize().len());
+            // the first public input of `cs` is the constant one
+            assert_eq!(cs.borrow().unwrap().instance_assignment[1..], packed);
+        }
+    }
 }
@@ -1,6 +1,7 @@
-use std::{
+use ark_std::{
     borrow::Borrow,
     cmp::{max, min},
+    vec::Vec,
 };
 
 use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
@@ -385,6 +386,62 @@ impl<F: PrimeField> NonNativeUintVar<F> {
         Ok(())
     }
 
+    /// Returns whether `self` is less than `other`, where `self` and `other`
+    /// should be aligned.
+    /// This computes `self - other` limb by limb, propagating the borrows from
+    /// the least significant limb, so that `self < other` iff a borrow is still
+    /// needed after the most significant limb.
+    pub fn is_lt(&self, other: &Self) -> Result<Boolean<F>, SynthesisError> {
+        let len = max(self.0.len(), other.0.len());
+        let zero = LimbVar::zero();
+        let cs = self.cs().or(other.cs());
+        let mode = if cs.is_none() {
+            AllocationMode::Constant
+        } else {
+            AllocationMode::Witness
+        };
+
+        let mut borrow = Boolean::FALSE;
+        for i in 0..len {
+            let x = self.0.get(i).unwrap_or(&zero);
+            let y = other.0.get(i).unwrap_or(&zero);
+            // The borrow from the next limb is worth `2^bits_per_limb`, except
+            // for the most significant limb, where we only care about the sign
+            // of the difference, and thus its (possibly partial) width suffices.
+            let width = if i == len - 1 {
+                max(x.ub.bits(), y.ub.bits()) as usize
+            } else {
+                Self::bits_per_limb()
+            };
+            let d = &x.v - &y.v - FpVar::from(borrow);
+            let next_borrow = Boolean::new_variable(
+                cs.clone(),
+                || Ok(d.value().unwrap_or_default().into_bigint() > F::MODULUS_MINUS_ONE_DIV_TWO),
+                mode,
+            )?;
+            // Enforce `0 <= d + next_borrow * 2^width < 2^width`.
+            // Since `-2^width <= d < 2^width`, this holds iff `next_borrow` is
+            // true exactly when `d` is negative.
+            Self::enforce_bit_length(
+                &(d + FpVar::from(next_borrow.clone()) * F::from(BigUint::one() << width)),
+                width,
+            )?;
+            borrow = next_borrow;
+        }
+
+        Ok(borrow)
+    }
+
+    /// Enforce `self` to be the canonical representation of an element in
+    /// `M`, i.e., `self < M::MODULUS`, where `self` should be aligned.
+    pub fn enforce_canonical<M: PrimeField>(&self) -> Result<(), SynthesisError> {
+        let m = Self::new_constant(
+            ConstraintSystemRef::None,
+            BoundedBigUint(M::MODULUS.into(), M::MODULUS_BIT_SIZE as usize),
+        )?;
+        self.enforce_lt(&m)
+    }
+
     /// Enforce `self` to be equal to `other`, where `self` and `other` are not
     /// necessarily aligned.
     ///
@@ -559,6 +616,21 @@ impl<F: PrimeField> NonNativeUintVar<F> {
         r
     }
 
+    /// Returns exactly `length` little-endian bits of `self`, where `self`
+    /// should be aligned.
+    /// If `self` may exceed `length` bits, the extra bits are enforced to be
+    /// zero.
+    pub fn to_bits_le_with_length(&self, length: usize) -> Result<Vec<Boolean<F>>, SynthesisError> {
+        let mut bits = self.to_bits_le()?;
+        if bits.len() > length {
+            for bit in bits.drain(length..) {
+                bit.enforce_equal(&Boolean::FALSE)?;
+            }
+        }
+        bits.resize(length, Boolean::FALSE);
+        Ok(bits)
+    }
+
     fn enforce_bit_length(x: &FpVar<F>, length: usize) -> Result<Vec<Boolean<F>>, SynthesisError> {
         let cs = x.cs();
 
@@ -646,6 +718,15 @@ impl<F: PrimeField> NonNativeUintVar<F> {
 
     /// Compute `self * other`, without aligning the limbs.
     /// Implements the O(n) approach described in xJsnark, Section IV.B.1)
+    ///
+    /// For operands with `n` and `n'` limbs, this costs `n + n' - 1`
+    /// constraints, which is already fewer than the multiplications needed by
+    /// Karatsuba, so the latter is not used here.
+    /// The result is unreduced, and more products and sums can be accumulated
+    /// on top of it before a single `modulo` or `enforce_congruent`, as long as
+    /// the upper bounds of the limbs fit in `F`. An operation whose result may
+    /// exceed the bounds returns `SynthesisError::Unsatisfiable` instead of
+    /// silently wrapping around `F::MODULUS`.
     pub fn mul_no_align(&self, other: &Self) -> Result<Self, SynthesisError> {
         let len = self.0.len() + other.0.len() - 1;
         if self.is_constant() || other.is_constant() {
@@ -684,7 +765,9 @@ impl<F: PrimeField> NonNativeUintVar<F> {
             }
             z.into_iter()
                 .map(|(v, ub)| {
-                    assert!(ub < F::MODULUS_MINUS_ONE_DIV_TWO.into());
+                    if ub >= F::MODULUS_MINUS_ONE_DIV_TWO.into() {
+                        return Err(SynthesisError::Unsatisfiable);
+                    }
                     Ok(LimbVar {
                         v: FpVar::new_variable(cs.clone(), || Ok(v), mode)?,
                         ub,
@@ -1065,4 +1148,155 @@ mod tests {
         assert!(cs.is_satisfied()?);
         Ok(())
     }
+
+    #[test]
+    fn test_mul_constraints() -> Result<(), Box<dyn Error>> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+
+        let rng = &mut test_rng();
+        let a_var = NonNativeUintVar::new_witness(cs.clone(), || Ok(Fq::rand(rng)))?;
+        let b_var = NonNativeUintVar::new_witness(cs.clone(), || Ok(Fq::rand(rng)))?;
+        let n = a_var.0.len();
+
+        let before = cs.num_constraints();
+        a_var.mul_no_align(&b_var)?;
+        // `2n - 1` constraints, while schoolbook and Karatsuba would need `n^2`
+        // and `n^{log_2 3}` multiplications respectively
+        assert_eq!(cs.num_constraints() - before, 2 * n - 1);
+
+        // a shorter operand results in fewer constraints, and in a smaller
+        // quotient when reducing the product
+        let short_var = NonNativeUintVar::new_witness(cs.clone(), || {
+            Ok(BoundedBigUint(rng.gen_biguint(128), 128))
+        })?;
+        let count = |x: &NonNativeUintVar<Fr>| -> Result<usize, SynthesisError> {
+            let before = cs.num_constraints();
+            x.mul_no_align(&b_var)?.modulo::<Fq>()?;
+            Ok(cs.num_constraints() - before)
+        };
+        // the same value, but with the bounds of a full-width element, as if
+        // its bits were padded with zeros
+        let padded_var = NonNativeUintVar(
+            (0..n)
+                .map(|i| LimbVar {
+                    v: short_var.0.get(i).map_or(FpVar::zero(), |l| l.v.clone()),
+                    ub: a_var.0[i].ub.clone(),
+                })
+                .collect(),
+        );
+        assert!(count(&short_var)? < count(&padded_var)?);
+
+        assert!(cs.is_satisfied()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_lazy_reduction_near_modulus() -> Result<(), Box<dyn Error>> {
+        let len = 1000;
+        let a = -Fq::one();
+        let b = -Fq::one();
+        let c = Fq::from(len as u64) * a * b;
+
+        let accumulate = |cs: ConstraintSystemRef<Fr>| -> Result<_, SynthesisError> {
+            let a_var = NonNativeUintVar::new_witness(cs.clone(), || Ok(a))?;
+            let b_var = NonNativeUintVar::new_witness(cs.clone(), || Ok(b))?;
+            let mut r_var =
+                NonNativeUintVar::new_constant(cs.clone(), BoundedBigUint(BigUint::zero(), 0))?;
+            for _ in 0..len {
+                r_var = r_var.add_no_align(&a_var.mul_no_align(&b_var)?)?;
+            }
+            Ok(r_var)
+        };
+
+        // the unreduced sum of `len` products of `|Fq| - 1` exceeds `Fr`, but
+        // is still reduced correctly
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let r_var = accumulate(cs.clone())?;
+        assert!(r_var.value()? > BigUint::from(Fr::MODULUS));
+        assert_eq!(Fq::from(r_var.modulo::<Fq>()?.value()?), c);
+        r_var.enforce_congruent::<Fq>(&NonNativeUintVar::new_witness(cs.clone(), || Ok(c))?)?;
+        assert!(cs.is_satisfied()?);
+
+        // an off-by-one result is rejected
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        accumulate(cs.clone())?
+            .enforce_congruent::<Fq>(&NonNativeUintVar::new_witness(cs.clone(), || {
+                Ok(c + Fq::one())
+            })?)?;
+        assert!(!cs.is_satisfied()?);
+
+        // multiplying unreduced products may overflow the limbs, which is
+        // reported as an error instead of wrapping around `Fr::MODULUS`
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let a_var = NonNativeUintVar::new_witness(cs.clone(), || Ok(a))?;
+        let aa_var = a_var.mul_no_align(&a_var)?;
+        let aaaa_var = aa_var.mul_no_align(&aa_var)?;
+        assert!(aaaa_var.mul_no_align(&aa_var).is_err());
+        assert!(aaaa_var.modulo::<Fq>()?.mul_no_align(&aa_var).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_comparison() -> Result<(), Box<dyn Error>> {
+        let rng = &mut test_rng();
+        let size = Fq::MODULUS_BIT_SIZE as usize;
+        let m = BigUint::from(Fq::MODULUS);
+
+        let mut values = vec![
+            BigUint::zero(),
+            BigUint::one(),
+            &m - 1u64,
+            m.clone(),
+            &m + 1u64,
+            (BigUint::one() << size) - 1u64,
+        ];
+        values.extend((0..10).map(|_| rng.gen_biguint(size as u64)));
+        // values that only differ in the least significant limb, so that the
+        // borrow has to be propagated through equal limbs
+        let v = rng.gen_biguint(size as u64) >> 1;
+        values.extend([&v << 1, (&v << 1) + 1u64]);
+
+        for a in &values {
+            for b in &values {
+                let cs = ConstraintSystem::<Fr>::new_ref();
+                let a_var = NonNativeUintVar::new_witness(cs.clone(), || {
+                    Ok(BoundedBigUint(a.clone(), size))
+                })?;
+                let b_var = NonNativeUintVar::new_witness(cs.clone(), || {
+                    Ok(BoundedBigUint(b.clone(), size))
+                })?;
+                assert_eq!(a_var.is_lt(&b_var)?.value()?, a < b);
+                assert!(cs.is_satisfied()?);
+
+                // `enforce_lt` is only satisfiable when `a < b`
+                a_var.enforce_lt(&b_var)?;
+                assert_eq!(cs.is_satisfied()?, a < b);
+            }
+
+            // the top limb of `a` is narrower than the one of `m_var`
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let a_var =
+                NonNativeUintVar::new_witness(cs.clone(), || Ok(BoundedBigUint(a.clone(), size)))?;
+            let m_var =
+                NonNativeUintVar::new_constant(cs.clone(), BoundedBigUint(m.clone(), size))?;
+            let short_var = NonNativeUintVar::new_witness(cs.clone(), || {
+                Ok(BoundedBigUint(BigUint::from(u64::MAX), 64))
+            })?;
+            assert_eq!(
+                short_var.is_lt(&a_var)?.value()?,
+                BigUint::from(u64::MAX) < *a
+            );
+            assert_eq!(
+                a_var.is_lt(&short_var)?.value()?,
+                *a < BigUint::from(u64::MAX)
+            );
+            assert_eq!(a_var.is_lt(&m_var)?.value()?, *a < m);
+            assert!(cs.is_satisfied()?);
+
+            a_var.enforce_canonical::<Fq>()?;
+            assert_eq!(cs.is_satisfied()?, *a < m);
+        }
+
+        Ok(())
+    }
 }
@@ -5,7 +5,7 @@
 /// - API made closer to gadgets found in `folding-schemes`
 use ark_crypto_primitives::sponge::{poseidon::PoseidonSponge, Absorb, CryptographicSponge};
 use ark_ff::PrimeField;
-use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_poly::univariate::DensePolynomial;
 use ark_r1cs_std::{
     alloc::{AllocVar, AllocationMode},
     boolean::Boolean,
@@ -85,6 +85,9 @@ pub struct IOPProofVar<F: PrimeField> {
     // We have to be generic over a CurveGroup because instantiating a IOPProofVar will call IOPSumCheck which requires a CurveGroup
     pub proofs: Vec<DensePolynomialVar<F>>,
     pub claim: FpVar<F>,
+    /// degree of the round polynomials, which fixes the shape of the circuit, as each of them is
+    /// allocated with `degree + 1` coefficients
+    pub degree: usize,
 }
 
 impl<F: PrimeField + Absorb> AllocVar<IOPProof<F>, F> for IOPProofVar<F> {
@@ -100,11 +103,18 @@ impl<F: PrimeField + Absorb> AllocVar<IOPProof<F>, F> for IOPProofVar<F> {
             let claim = FpVar::<F>::new_variable(cs.clone(), || Ok(claim), mode)?;
             let mut proofs = Vec::<DensePolynomialVar<F>>::with_capacity(cp.proofs.len());
             for proof in cp.proofs.iter() {
-                let poly = DensePolynomial::from_coefficients_slice(&proof.coeffs);
-                let proof = DensePolynomialVar::<F>::new_variable(cs.clone(), || Ok(poly), mode)?;
-                proofs.push(proof);
+                // the coefficients are allocated as they are, without trimming the leading zeros
+                // as `DensePolynomial::from_coefficients_slice` does, so that the shape of the
+                // circuit only depends on the degree
+                let coeffs =
+                    Vec::<FpVar<F>>::new_variable(cs.clone(), || Ok(proof.coeffs.clone()), mode)?;
+                proofs.push(DensePolynomialVar { coeffs });
             }
-            Ok(Self { proofs, claim })
+            Ok(Self {
+                proofs,
+                claim,
+                degree: cp.degree,
+            })
         })
     }
 }
@@ -145,6 +155,9 @@ pub struct SumCheckVerifierGadget<F: PrimeField> {
 }
 
 impl<F: PrimeField> SumCheckVerifierGadget<F> {
+    /// In-circuit counterpart of `IOPSumCheck::verify`, for round polynomials of any degree. The
+    /// degree and the number of rounds are given by the shape of `iop_proof_var`, which is
+    /// checked to be consistent, and they are enforced to be the ones of `poly_aux_info_var`.
     #[allow(clippy::type_complexity)]
     pub fn verify<S: CryptographicSponge, T: TranscriptVar<F, S>>(
         iop_proof_var: &IOPProofVar<F>,
@@ -152,6 +165,22 @@ impl<F: PrimeField> SumCheckVerifierGadget<F> {
         transcript_var: &mut T,
         enabled: Boolean<F>,
     ) -> Result<(Vec<FpVar<F>>, Vec<FpVar<F>>), SynthesisError> {
+        if iop_proof_var
+            .proofs
+            .iter()
+            .any(|poly_var| poly_var.coeffs.len() != iop_proof_var.degree + 1)
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        poly_aux_info_var.max_degree.conditional_enforce_equal(
+            &FpVar::constant(F::from(iop_proof_var.degree as u64)),
+            &enabled,
+        )?;
+        poly_aux_info_var.num_variables.conditional_enforce_equal(
+            &FpVar::constant(F::from(iop_proof_var.proofs.len() as u64)),
+            &enabled,
+        )?;
+
         let mut e_vars = vec![iop_proof_var.claim.clone()];
         let mut r_vars: Vec<FpVar<F>> = Vec::new();
         transcript_var.absorb(&poly_aux_info_var.num_variables)?;
@@ -178,7 +207,9 @@ mod tests {
         poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig},
     };
     use ark_pallas::Fr;
-    use ark_poly::{DenseMultilinearExtension, MultilinearExtension, Polynomial};
+    use ark_poly::{
+        DenseMultilinearExtension, DenseUVPolynomial, MultilinearExtension, Polynomial,
+    };
     use ark_r1cs_std::R1CSVar;
     use ark_relations::r1cs::ConstraintSystem;
     use std::sync::Arc;
@@ -257,4 +288,56 @@ mod tests {
             assert!(cs.is_satisfied().unwrap());
         }
     }
+
+    /// the gadget verifies the proofs of higher-degree virtual polynomials, as long as their
+    /// degree is the one of the aux info
+    #[test]
+    fn test_sum_check_circuit_degree() {
+        let mut rng = ark_std::test_rng();
+        let (num_vars, degree) = (4, 5);
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let mut virtual_poly = VirtualPolynomial::new(num_vars);
+        let mles: Vec<_> = (0..degree)
+            .map(|_| Arc::new(DenseMultilinearExtension::rand(num_vars, &mut rng)))
+            .collect();
+        virtual_poly.add_mle_list(mles, Fr::from(3_u32)).unwrap();
+        let mut transcript = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let sum_check =
+            IOPSumCheck::<Fr, PoseidonSponge<Fr>>::prove(&virtual_poly, &mut transcript).unwrap();
+
+        // returns whether the constraints are satisfied, the challenges and the last evaluation
+        let verify_gadget = |aux_info: VPAuxInfo<Fr>| {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let mut poseidon_var = PoseidonSpongeVar::new(cs.clone(), &poseidon_config);
+            let iop_proof_var =
+                IOPProofVar::<Fr>::new_witness(cs.clone(), || Ok(&sum_check)).unwrap();
+            assert!(iop_proof_var
+                .proofs
+                .iter()
+                .all(|poly_var| poly_var.coeffs.len() == degree + 1));
+            let aux_info_var =
+                VPAuxInfoVar::<Fr>::new_witness(cs.clone(), || Ok(aux_info)).unwrap();
+            let (e_vars, r_vars) = SumCheckVerifierGadget::<Fr>::verify(
+                &iop_proof_var,
+                &aux_info_var,
+                &mut poseidon_var,
+                Boolean::TRUE,
+            )
+            .unwrap();
+            (
+                cs.is_satisfied().unwrap(),
+                r_vars.value().unwrap(),
+                e_vars.last().unwrap().value().unwrap(),
+            )
+        };
+
+        let (satisfied, r, e) = verify_gadget(virtual_poly.aux_info.clone());
+        assert!(satisfied);
+        assert_eq!(r, sum_check.point);
+        assert_eq!(e, virtual_poly.evaluate(&sum_check.point).unwrap());
+
+        let mut aux_info = virtual_poly.aux_info.clone();
+        aux_info.max_degree -= 1;
+        assert!(!verify_gadget(aux_info).0);
+    }
 }
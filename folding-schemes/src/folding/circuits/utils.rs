@@ -1,7 +1,14 @@
-use ark_ff::PrimeField;
-use ark_r1cs_std::fields::{fp::FpVar, FieldVar};
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    R1CSVar,
+};
 use ark_relations::r1cs::SynthesisError;
-use std::marker::PhantomData;
+use ark_std::marker::PhantomData;
+
+use crate::Error;
 
 /// EqEval is a gadget for computing $\tilde{eq}(a, b) = \prod_{i=1}^{l}(a_i \cdot b_i + (1 - a_i)(1 - b_i))$
 /// :warning: This is not the ark_r1cs_std::eq::EqGadget
@@ -28,15 +35,54 @@ impl<F: PrimeField> EqEvalGadget<F> {
     }
 }
 
+/// Returns the element of `F` whose little-endian bit decomposition is `bits`, checking that its
+/// value is canonical, ie. smaller than the modulus, instead of reducing it (as
+/// `F::from_le_bytes_mod_order` does) or panicking (as `F::from(F::BigInt::from_bits_le(bits))`
+/// does). `bits` can be longer than `F::MODULUS_BIT_SIZE` as long as the extra bits are zero.
+///
+/// Native counterpart of [`le_bits_to_fp_var_checked`].
+pub fn le_bits_to_field_checked<F: PrimeField>(bits: &[bool]) -> Result<F, Error> {
+    let (bits, high_bits) = bits.split_at(bits.len().min(F::MODULUS_BIT_SIZE as usize));
+    if high_bits.iter().any(|b| *b) {
+        return Err(Error::OutOfBounds);
+    }
+    F::from_bigint(F::BigInt::from_bits_le(bits)).ok_or(Error::OutOfBounds)
+}
+
+/// Converts the little-endian `bits` into a field element, enforcing that their value is
+/// canonical, so that it matches [`le_bits_to_field_checked`] instead of wrapping around the
+/// modulus:
+/// - if there are less than `F::MODULUS_BIT_SIZE` bits, the value is always canonical, and no
+///   constraints are added on top of `Boolean::le_bits_to_fp_var`.
+/// - otherwise, the bits from `F::MODULUS_BIT_SIZE` on are enforced to be zero, and the rest to be
+///   smaller than the modulus by the comparison against its bits that `Boolean::le_bits_to_fp_var`
+///   runs (`Boolean::enforce_in_field_le`) for that many bits.
+/// - constant bits are checked natively, returning `SynthesisError::Unsatisfiable` if their value
+///   is not canonical, while `Boolean::le_bits_to_fp_var` would reduce it.
+pub fn le_bits_to_fp_var_checked<F: PrimeField>(
+    bits: &[Boolean<F>],
+) -> Result<FpVar<F>, SynthesisError> {
+    if bits.is_constant() {
+        return le_bits_to_field_checked(&bits.value()?)
+            .map(FpVar::constant)
+            .map_err(|_| SynthesisError::Unsatisfiable);
+    }
+    let (bits, high_bits) = bits.split_at(bits.len().min(F::MODULUS_BIT_SIZE as usize));
+    for bit in high_bits {
+        bit.enforce_equal(&Boolean::FALSE)?;
+    }
+    Boolean::le_bits_to_fp_var(bits)
+}
+
 #[cfg(test)]
 mod tests {
-    use ark_ff::Field;
+    use ark_ff::{BigInteger, Field, PrimeField};
     use ark_pallas::Fr;
-    use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, R1CSVar};
+    use ark_r1cs_std::{alloc::AllocVar, boolean::Boolean, fields::fp::FpVar, R1CSVar};
     use ark_relations::r1cs::ConstraintSystem;
-    use ark_std::{test_rng, UniformRand};
+    use ark_std::{test_rng, One, UniformRand};
 
-    use super::EqEvalGadget;
+    use super::{le_bits_to_field_checked, le_bits_to_fp_var_checked, EqEvalGadget};
     use crate::utils::virtual_polynomial::eq_eval;
 
     #[test]
@@ -71,4 +117,56 @@ mod tests {
         let gadget_eq_eval = EqEvalGadget::<Fr>::eq_eval(&x, &y);
         assert!(gadget_eq_eval.is_err());
     }
+
+    /// checks that `le_bits_to_fp_var_checked` agrees with `le_bits_to_field_checked` on `bits`,
+    /// both for witness and for constant bits, and returns the native value if it is canonical
+    fn check_le_bits_to_field(bits: &[bool]) -> Option<Fr> {
+        let expected = le_bits_to_field_checked::<Fr>(bits).ok();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let bitsVar = Vec::<Boolean<Fr>>::new_witness(cs.clone(), || Ok(bits.to_vec())).unwrap();
+        let v = le_bits_to_fp_var_checked(&bitsVar).unwrap();
+        let constant_bits: Vec<Boolean<Fr>> = bits.iter().map(|b| Boolean::constant(*b)).collect();
+        let constant = le_bits_to_fp_var_checked(&constant_bits);
+        match expected {
+            Some(x) => {
+                assert!(cs.is_satisfied().unwrap());
+                assert_eq!(v.value().unwrap(), x);
+                assert_eq!(constant.unwrap().value().unwrap(), x);
+            }
+            None => {
+                assert!(!cs.is_satisfied().unwrap());
+                assert!(constant.is_err());
+            }
+        }
+        expected
+    }
+
+    /// boundary values of the conversion of bits to field elements, around the modulus
+    #[test]
+    fn test_le_bits_to_field_checked() {
+        let n_bits = Fr::MODULUS_BIT_SIZE as usize;
+        let modulus_bits = Fr::MODULUS.to_bits_le()[..n_bits].to_vec();
+        let minus_one_bits = (-Fr::one()).into_bigint().to_bits_le()[..n_bits].to_vec();
+
+        // all ones below MODULUS_BIT_SIZE bits, which is always canonical
+        assert_eq!(
+            check_le_bits_to_field(&vec![true; n_bits - 1]),
+            Some(Fr::from(2_u32).pow([n_bits as u64 - 1]) - Fr::one())
+        );
+        // the largest canonical value, also when padded with zeros
+        assert_eq!(check_le_bits_to_field(&minus_one_bits), Some(-Fr::one()));
+        assert_eq!(
+            check_le_bits_to_field(&[minus_one_bits.clone(), vec![false; 8]].concat()),
+            Some(-Fr::one())
+        );
+        // the modulus, all ones of MODULUS_BIT_SIZE bits, and a set bit above them, which the
+        // unchecked conversions reduce (or panic on, natively)
+        assert_eq!(check_le_bits_to_field(&modulus_bits), None);
+        assert_eq!(check_le_bits_to_field(&vec![true; n_bits]), None);
+        assert_eq!(
+            check_le_bits_to_field(&[minus_one_bits, vec![false, true]].concat()),
+            None
+        );
+    }
 }
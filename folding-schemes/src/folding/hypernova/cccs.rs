@@ -14,7 +14,7 @@ use crate::folding::traits::Inputize;
 use crate::folding::traits::{CommittedInstanceOps, Dummy};
 use crate::transcript::AbsorbNonNative;
 use crate::utils::mle::dense_vec_to_dense_mle;
-use crate::utils::vec::{is_zero_vec, mat_vec_mul};
+use crate::utils::vec::{check_zero_rows, mat_vec_mul};
 use crate::utils::virtual_polynomial::{build_eq_x_r_vec, VirtualPolynomial};
 use crate::Error;
 
@@ -91,6 +91,21 @@ impl<F: PrimeField> CCS<F> {
     }
 }
 
+impl<C: CurveGroup> CCCS<C> {
+    /// Checks that the public inputs of the instance have the length expected by the given CCS.
+    pub fn check_dimensions(&self, ccs: &CCS<CF1<C>>) -> Result<(), Error> {
+        if self.x.len() != ccs.l {
+            return Err(Error::NotSameLength(
+                "x.len()".to_string(),
+                self.x.len(),
+                "ccs.l".to_string(),
+                ccs.l,
+            ));
+        }
+        Ok(())
+    }
+}
+
 impl<C: CurveGroup> Dummy<&CCS<CF1<C>>> for CCCS<C> {
     fn dummy(ccs: &CCS<CF1<C>>) -> Self {
         Self {
@@ -118,7 +133,7 @@ impl<C: CurveGroup> Arith<Witness<CF1<C>>, CCCS<C>> for CCS<CF1<C>> {
         // A CCCS relation is satisfied if the q(x) multivariate polynomial evaluates to zero in
         // the hypercube, evaluating over the whole boolean hypercube for a normal-sized instance
         // would take too much, this checks the CCS relation of the CCCS.
-        is_zero_vec(&e).then_some(()).ok_or(Error::NotSatisfied)
+        check_zero_rows(&e)
     }
 }
 
@@ -148,6 +163,10 @@ impl<C: CurveGroup> CommittedInstanceOps<C> for CCCS<C> {
         vec![self.C]
     }
 
+    fn get_commitment_labels(&self) -> Vec<&'static [u8]> {
+        vec![b"C"]
+    }
+
     fn is_incoming(&self) -> bool {
         true
     }
@@ -11,13 +11,17 @@ use ark_r1cs_std::{
     alloc::{AllocVar, AllocationMode},
     boolean::Boolean,
     eq::EqGadget,
-    fields::{fp::FpVar, FieldVar},
+    fields::{
+        fp::{AllocatedFp, FpVar},
+        FieldVar,
+    },
     prelude::CurveVar,
     uint8::UInt8,
     R1CSVar, ToConstraintFieldGadget,
 };
 use ark_relations::r1cs::{
     ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, Namespace, SynthesisError,
+    Variable,
 };
 use ark_std::{fmt::Debug, ops::Neg, One, Zero};
 use core::{borrow::Borrow, marker::PhantomData};
@@ -37,17 +41,20 @@ use crate::folding::{
         },
         nonnative::{affine::NonNativeAffineVar, uint::NonNativeUintVar},
         sum_check::{IOPProofVar, SumCheckVerifierGadget, VPAuxInfoVar},
-        utils::EqEvalGadget,
+        utils::{le_bits_to_fp_var_checked, EqEvalGadget},
         CF1, CF2,
     },
     nova::get_r1cs_from_cs,
     traits::{CommittedInstanceVarOps, Dummy},
 };
-use crate::frontend::FCircuit;
+use crate::frontend::{step_constraints, FCircuit};
 use crate::utils::virtual_polynomial::VPAuxInfo;
 use crate::Error;
 use crate::{
-    arith::{ccs::CCS, r1cs::extract_r1cs},
+    arith::{
+        ccs::{builder::CCSBuilder, CCS},
+        r1cs::extract_r1cs,
+    },
     transcript::TranscriptVar,
 };
 
@@ -88,6 +95,10 @@ impl<C: CurveGroup> CommittedInstanceVarOps<C> for CCCSVar<C> {
         vec![self.C.clone()]
     }
 
+    fn get_commitment_labels(&self) -> Vec<&'static [u8]> {
+        vec![b"C"]
+    }
+
     fn get_public_inputs(&self) -> &[FpVar<CF1<C>>] {
         &self.x
     }
@@ -167,6 +178,10 @@ impl<C: CurveGroup> CommittedInstanceVarOps<C> for LCCCSVar<C> {
         vec![self.C.clone()]
     }
 
+    fn get_commitment_labels(&self) -> Vec<&'static [u8]> {
+        vec![b"C"]
+    }
+
     fn get_public_inputs(&self) -> &[FpVar<CF1<C>>] {
         &self.x
     }
@@ -330,7 +345,7 @@ impl<C: CurveGroup> NIMFSGadget<C> {
         let rho_scalar: FpVar<CF1<C>> = FpVar::<CF1<C>>::new_constant(cs.clone(), rho_scalar_raw)?;
         transcript.absorb(&rho_scalar)?;
         let rho_bits: Vec<Boolean<CF1<C>>> = transcript.get_challenge_nbits(NOVA_N_BITS_RO)?;
-        let rho = Boolean::le_bits_to_fp_var(&rho_bits)?;
+        let rho = le_bits_to_fp_var_checked(&rho_bits)?;
 
         // Self::fold will return the folded instance
         let folded_lcccs = Self::fold(
@@ -429,34 +444,40 @@ fn compute_c_gadget<F: PrimeField>(
     vec_r_x: Vec<Vec<FpVar<F>>>,
     vec_r_x_prime: Vec<FpVar<F>>,
 ) -> Result<FpVar<F>, SynthesisError> {
-    let mut e_lcccs = Vec::new();
-    for r_x in vec_r_x.iter() {
-        e_lcccs.push(EqEvalGadget::eq_eval(r_x, &vec_r_x_prime)?);
-    }
-
-    let mut c = FpVar::<F>::zero();
-    let mut current_gamma = FpVar::<F>::one();
-    for i in 0..vec_sigmas.len() {
-        for j in 0..ccs.t {
-            c += current_gamma.clone() * e_lcccs[i].clone() * vec_sigmas[i][j].clone();
-            current_gamma *= gamma.clone();
-        }
+    // c is computed as a polynomial in gamma, whose coefficients are the terms of each instance,
+    // so that the powers gamma^0, ..., gamma^t are computed once and shared between all the
+    // instances, and the eq evaluation e_k is multiplied once instead of once per CCCS instance.
+    // This matches the arrangement of the native `compute_c`.
+    let mut gamma_powers = vec![FpVar::<F>::one()];
+    for i in 0..ccs.t {
+        gamma_powers.push(&gamma_powers[i] * &gamma);
     }
 
+    // sum_k gamma^k * (sum_i c_i * prod_{j \in S_i} theta_{k,j})
     let ccs_c = Vec::<FpVar<F>>::new_constant(cs.clone(), ccs.c.clone())?;
-    let e_k = EqEvalGadget::eq_eval(&beta, &vec_r_x_prime)?;
-    #[allow(clippy::needless_range_loop)]
-    for k in 0..vec_thetas.len() {
+    let mut c = FpVar::<F>::zero();
+    for thetas in vec_thetas.iter().rev() {
         let mut sum = FpVar::<F>::zero();
-        for i in 0..ccs.q {
+        for (S_i, c_i) in ccs.S.iter().zip(&ccs_c) {
             let mut prod = FpVar::<F>::one();
-            for j in ccs.S[i].clone() {
-                prod *= vec_thetas[k][j].clone();
+            for j in S_i {
+                prod *= &thetas[*j];
             }
-            sum += ccs_c[i].clone() * prod;
+            sum += c_i * prod;
         }
-        c += current_gamma.clone() * e_k.clone() * sum;
-        current_gamma *= gamma.clone();
+        c = c * &gamma + sum;
+    }
+    c *= EqEvalGadget::eq_eval(&beta, &vec_r_x_prime)?;
+
+    // c = c * gamma^t + e_i * sum_j gamma^j * sigma_{i,j}, for i = mu-1, ..., 0
+    for (sigmas, r_x) in vec_sigmas.iter().zip(&vec_r_x).rev() {
+        let e_i = EqEvalGadget::eq_eval(r_x, &vec_r_x_prime)?;
+        let sum = sigmas
+            .iter()
+            .zip(&gamma_powers)
+            .map(|(sigma_j, gamma_j)| sigma_j * gamma_j)
+            .sum::<FpVar<F>>();
+        c = c * &gamma_powers[ccs.t] + e_i * sum;
     }
     Ok(c)
 }
@@ -497,7 +518,7 @@ pub struct AugmentedFCircuit<
     pub(super) i_usize: Option<usize>,
     pub(super) z_0: Option<Vec<C1::ScalarField>>,
     pub(super) z_i: Option<Vec<C1::ScalarField>>,
-    pub(super) external_inputs: Option<Vec<C1::ScalarField>>,
+    pub(super) external_inputs: Option<FC::ExternalInputs>,
     pub(super) U_i: Option<LCCCS<C1>>,
     pub(super) Us: Option<Vec<LCCCS<C1>>>, // other U_i's to be folded that are not the main running instance
     pub(super) u_i_C: Option<C1>,          // u_i.C
@@ -580,9 +601,19 @@ where
             M: vec![],
         };
         let mut augmented_f_circuit = Self::default(poseidon_config, F, initial_ccs)?;
-        augmented_f_circuit.ccs = ccs
-            .ok_or(())
-            .or_else(|_| augmented_f_circuit.upper_bound_ccs())?;
+        augmented_f_circuit.ccs = match ccs {
+            Some(ccs) => ccs,
+            None => {
+                let mut ccs = augmented_f_circuit.upper_bound_ccs()?;
+                // the NIMFS and the decider work over the boolean hypercube, pad the CCS so that
+                // its dimensions are powers of two. The in-circuit part of the CCS (s, t, d, S,
+                // c) is not affected by the padding, only the witnesses are extended with zeros.
+                ccs.pad_to_pow2();
+                ccs.normalize_public_inputs(2)?;
+                ccs.sanity_check()?;
+                ccs
+            }
+        };
         Ok(augmented_f_circuit)
     }
 
@@ -600,7 +631,14 @@ where
         let mut w_i = W_i.clone();
         let mut u_i = CCCS::<C1>::dummy(&ccs);
 
-        let n_iters = 2;
+        // when the FCircuit has custom gates, the first iteration starts from a CCS without them,
+        // which changes the in-circuit parameters (t, d) of the next one, so one more iteration
+        // is needed
+        let n_iters = if self.F.custom_gates().is_some() {
+            3
+        } else {
+            2
+        };
         for _ in 0..n_iters {
             let Us = vec![U_i.clone(); MU - 1];
             let Ws = vec![W_i.clone(); MU - 1];
@@ -634,7 +672,7 @@ where
                 i_usize: Some(0),
                 z_0: Some(z_0.clone()),
                 z_i: Some(z_0.clone()),
-                external_inputs: Some(vec![C1::ScalarField::zero(); self.F.external_inputs_len()]),
+                external_inputs: Some(FC::ExternalInputs::default()),
                 U_i: Some(U_i.clone()),
                 Us: Some(Us),
                 u_i_C: Some(u_i.C),
@@ -677,18 +715,35 @@ where
         &self,
     ) -> Result<(ConstraintSystem<C1::ScalarField>, CCS<C1::ScalarField>), Error> {
         let cs = ConstraintSystem::<C1::ScalarField>::new_ref();
-        self.clone().generate_constraints(cs.clone())?;
+        let gate_vars = self.clone().synthesize(cs.clone())?;
         cs.finalize();
         let cs = cs.into_inner().ok_or(Error::NoInnerConstraintSystem)?;
         let r1cs = extract_r1cs::<C1::ScalarField>(&cs)?;
-        let ccs = CCS::from(r1cs);
+        let mut ccs = CCS::from(r1cs);
+
+        if let Some(gates) = self.F.custom_gates() {
+            // append the custom gates of the FCircuit, applied over the columns of (1, z_i,
+            // z_{i+1}) in z = (1, x, w)
+            let columns = [Variable::One]
+                .iter()
+                .chain(gate_vars.iter())
+                .map(|v| {
+                    v.get_index_unchecked(cs.num_instance_variables)
+                        .ok_or_else(|| {
+                            Error::Other("custom gate over a linear combination".to_string())
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            ccs = CCSBuilder::from_ccs(&ccs)
+                .append_ccs(&gates, &columns)?
+                .build()?;
+        }
 
         Ok((cs, ccs))
     }
 }
 
-impl<C1, C2, GC2, FC, const MU: usize, const NU: usize> ConstraintSynthesizer<CF1<C1>>
-    for AugmentedFCircuit<C1, C2, GC2, FC, MU, NU>
+impl<C1, C2, GC2, FC, const MU: usize, const NU: usize> AugmentedFCircuit<C1, C2, GC2, FC, MU, NU>
 where
     C1: CurveGroup,
     C2: CurveGroup,
@@ -700,7 +755,10 @@ where
     <C2 as Group>::ScalarField: Absorb,
     C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
 {
-    fn generate_constraints(self, cs: ConstraintSystemRef<CF1<C1>>) -> Result<(), SynthesisError> {
+    /// Generates the constraints of the AugmentedFCircuit. When the FCircuit declares custom
+    /// gates, returns the variables of `(z_i, z_{i+1})` over which the gates are applied, which
+    /// can only be mapped to the columns of the CCS once the synthesis is done.
+    fn synthesize(self, cs: ConstraintSystemRef<CF1<C1>>) -> Result<Vec<Variable>, SynthesisError> {
         let pp_hash = FpVar::<CF1<C1>>::new_witness(cs.clone(), || {
             Ok(self.pp_hash.unwrap_or_else(CF1::<C1>::zero))
         })?;
@@ -717,10 +775,8 @@ where
                 .z_i
                 .unwrap_or(vec![CF1::<C1>::zero(); self.F.state_len()]))
         })?;
-        let external_inputs = Vec::<FpVar<CF1<C1>>>::new_witness(cs.clone(), || {
-            Ok(self
-                .external_inputs
-                .unwrap_or(vec![CF1::<C1>::zero(); self.F.external_inputs_len()]))
+        let external_inputs = FC::ExternalInputsVar::new_witness(cs.clone(), || {
+            Ok(self.external_inputs.unwrap_or_default())
         })?;
 
         let U_dummy = LCCCS::<C1>::dummy(&self.ccs);
@@ -795,9 +851,19 @@ where
 
         // get z_{i+1} from the F circuit
         let i_usize = self.i_usize.unwrap_or(0);
-        let z_i1 = self
-            .F
-            .generate_step_constraints(cs.clone(), i_usize, z_i, external_inputs)?;
+        let has_custom_gates = self.F.custom_gates().is_some();
+        let mut gate_vars = vec![];
+        if has_custom_gates {
+            for z in z_i.iter() {
+                gate_vars.push(variable_of(cs.clone(), z)?);
+            }
+        }
+        let z_i1 = step_constraints(&self.F, cs.clone(), i_usize, &i, z_i, external_inputs)?;
+        if has_custom_gates {
+            for z in z_i1.iter() {
+                gate_vars.push(variable_of(cs.clone(), z)?);
+            }
+        }
 
         let (u_i1_x, _) = U_i1.clone().hash(
             &sponge,
@@ -816,26 +882,17 @@ where
         let x = FpVar::new_input(cs.clone(), || Ok(self.x.unwrap_or(u_i1_x_base.value()?)))?;
         x.enforce_equal(&is_basecase.select(&u_i1_x_base, &u_i1_x)?)?;
 
-        // convert rho_bits of the rho_vec to a `NonNativeFieldVar`
-        let mut rho_bits_resized = rho_bits.clone();
-        rho_bits_resized.resize(C1::BaseField::MODULUS_BIT_SIZE as usize, Boolean::FALSE);
-        let rho_nonnat = NonNativeUintVar::from(&rho_bits_resized);
-
         // CycleFold part
         // C.1. Compute cf1_u_i.x and cf2_u_i.x
-        let cf_x: Vec<NonNativeUintVar<CF2<C2>>> = [
-            vec![rho_nonnat],
-            all_Us
-                .iter()
-                .flat_map(|U| vec![U.C.x.clone(), U.C.y.clone()])
-                .collect(),
-            all_us
-                .iter()
-                .flat_map(|u| vec![u.C.x.clone(), u.C.y.clone()])
-                .collect(),
-            vec![U_i1.C.x, U_i1.C.y],
-        ]
-        .concat();
+        let cf_x = HyperNovaCycleFoldConfig::<C1, MU, NU>::x_gadget(
+            &rho_bits,
+            &[
+                all_Us.iter().map(|U| U.C.clone()).collect::<Vec<_>>(),
+                all_us.iter().map(|u| u.C.clone()).collect(),
+            ]
+            .concat(),
+            &U_i1.C,
+        )?;
 
         // ensure that cf_u has as public inputs the C from main instances U_i, u_i, U_i+1
         // coordinates of the commitments.
@@ -857,6 +914,7 @@ where
         // cf_r_bits is denoted by rho* in the paper.
         let cf_r_bits = CycleFoldChallengeGadget::<C2, GC2>::get_challenge_gadget(
             &mut transcript,
+            HyperNovaCycleFoldConfig::<C1, MU, NU>::CHALLENGE_BIT_LENGTH,
             pp_hash.clone(),
             cf_U_i_vec,
             cf_u_i.clone(),
@@ -879,14 +937,48 @@ where
         })?;
         cf_x.enforce_equal(&is_basecase.select(&cf_u_i1_x_base, &cf_u_i1_x)?)?;
 
-        Ok(())
+        Ok(gate_vars)
+    }
+}
+
+impl<C1, C2, GC2, FC, const MU: usize, const NU: usize> ConstraintSynthesizer<CF1<C1>>
+    for AugmentedFCircuit<C1, C2, GC2, FC, MU, NU>
+where
+    C1: CurveGroup,
+    C2: CurveGroup,
+    GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+    FC: FCircuit<CF1<C1>>,
+    <C1 as CurveGroup>::BaseField: PrimeField,
+    <C2 as CurveGroup>::BaseField: PrimeField,
+    <C1 as Group>::ScalarField: Absorb,
+    <C2 as Group>::ScalarField: Absorb,
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+{
+    fn generate_constraints(self, cs: ConstraintSystemRef<CF1<C1>>) -> Result<(), SynthesisError> {
+        self.synthesize(cs).map(|_| ())
+    }
+}
+
+/// Returns a variable holding the value of `v`, which is `v` itself when it is allocated as a
+/// single variable, and otherwise a new witness enforced to be equal to `v`.
+fn variable_of<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    v: &FpVar<F>,
+) -> Result<Variable, SynthesisError> {
+    match v {
+        FpVar::Var(v) if !v.variable.is_lc() => Ok(v.variable),
+        _ => {
+            let copy = AllocatedFp::new_witness(cs, || v.value())?;
+            FpVar::Var(copy.clone()).enforce_equal(v)?;
+            Ok(copy.variable)
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use ark_bn254::{constraints::GVar, Fq, Fr, G1Projective as Projective};
-    use ark_ff::BigInteger;
+    use ark_ff::{BigInteger, Field};
     use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
     use ark_std::{test_rng, UniformRand};
     use std::time::Instant;
@@ -894,7 +986,10 @@ mod tests {
     use super::*;
     use crate::{
         arith::{
-            ccs::tests::{get_test_ccs, get_test_z},
+            ccs::{
+                builder::CCSBuilder,
+                tests::{get_test_ccs, get_test_z},
+            },
             r1cs::extract_w_x,
             Arith,
         },
@@ -907,7 +1002,10 @@ mod tests {
             },
             traits::CommittedInstanceOps,
         },
-        frontend::utils::CubicFCircuit,
+        frontend::{
+            tests::{capture_logs, WRONG_STATE_LEN_LOG},
+            utils::{CubicFCircuit, WrongStateLenFCircuit},
+        },
         transcript::poseidon::poseidon_canonical_config,
         utils::get_cm_coordinates,
     };
@@ -1008,6 +1106,103 @@ mod tests {
         assert_eq!(expected_c, computed_c.value().unwrap());
     }
 
+    /// Term by term computation of c, where each term of the sum is multiplied by its own power of
+    /// gamma and its own eq evaluation, used as a baseline for the constraint count of
+    /// `compute_c_gadget`.
+    #[allow(clippy::too_many_arguments)]
+    fn compute_c_gadget_term_by_term<F: PrimeField>(
+        cs: ConstraintSystemRef<F>,
+        ccs: &CCS<F>,
+        vec_sigmas: &[Vec<FpVar<F>>],
+        vec_thetas: &[Vec<FpVar<F>>],
+        gamma: &FpVar<F>,
+        beta: &[FpVar<F>],
+        vec_r_x: &[Vec<FpVar<F>>],
+        vec_r_x_prime: &[FpVar<F>],
+    ) -> Result<FpVar<F>, SynthesisError> {
+        let mut c = FpVar::<F>::zero();
+        let mut current_gamma = FpVar::<F>::one();
+        for (sigmas, r_x) in vec_sigmas.iter().zip(vec_r_x) {
+            let e_i = EqEvalGadget::eq_eval(r_x, vec_r_x_prime)?;
+            for sigma_j in sigmas {
+                c += current_gamma.clone() * e_i.clone() * sigma_j;
+                current_gamma *= gamma;
+            }
+        }
+        let ccs_c = Vec::<FpVar<F>>::new_constant(cs, ccs.c.clone())?;
+        let e_k = EqEvalGadget::eq_eval(beta, vec_r_x_prime)?;
+        for thetas in vec_thetas {
+            let mut sum = FpVar::<F>::zero();
+            for (S_i, c_i) in ccs.S.iter().zip(&ccs_c) {
+                let mut prod = FpVar::<F>::one();
+                for j in S_i {
+                    prod *= &thetas[*j];
+                }
+                sum += c_i * prod;
+            }
+            c += current_gamma.clone() * e_k.clone() * sum;
+            current_gamma *= gamma;
+        }
+        Ok(c)
+    }
+
+    #[test]
+    pub fn test_compute_c_gadget_constraints() {
+        let mut rng = test_rng();
+        let ccs: CCS<Fr> = get_test_ccs();
+        let (mu, nu) = (2, 2);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let mut new_witnesses = |n: usize| {
+            let v: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+            Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(v)).unwrap()
+        };
+        let vec_sigmas: Vec<_> = (0..mu).map(|_| new_witnesses(ccs.t)).collect();
+        let vec_thetas: Vec<_> = (0..nu).map(|_| new_witnesses(ccs.t)).collect();
+        let vec_r_x: Vec<_> = (0..mu).map(|_| new_witnesses(ccs.s)).collect();
+        let vec_r_x_prime = new_witnesses(ccs.s);
+        let beta = new_witnesses(ccs.s);
+        let gamma = new_witnesses(1).remove(0);
+
+        let n_constraints = cs.num_constraints();
+        let expected_c = compute_c_gadget_term_by_term(
+            cs.clone(),
+            &ccs,
+            &vec_sigmas,
+            &vec_thetas,
+            &gamma,
+            &beta,
+            &vec_r_x,
+            &vec_r_x_prime,
+        )
+        .unwrap();
+        let n_constraints_term_by_term = cs.num_constraints() - n_constraints;
+
+        let n_constraints = cs.num_constraints();
+        let c = compute_c_gadget(
+            cs.clone(),
+            &ccs,
+            vec_sigmas,
+            vec_thetas,
+            gamma,
+            beta,
+            vec_r_x,
+            vec_r_x_prime,
+        )
+        .unwrap();
+        let n_constraints_batched = cs.num_constraints() - n_constraints;
+
+        assert_eq!(c.value().unwrap(), expected_c.value().unwrap());
+        assert!(cs.is_satisfied().unwrap());
+        // at least a 20% reduction with respect to the term by term computation
+        assert!(
+            5 * n_constraints_batched <= 4 * n_constraints_term_by_term,
+            "{} constraints, term by term: {}",
+            n_constraints_batched,
+            n_constraints_term_by_term
+        );
+    }
+
     /// Test that generates mu>1 and nu>1 instances, and folds them in a single multifolding step,
     /// to verify the folding in the NIMFSGadget circuit
     #[test]
@@ -1119,6 +1314,116 @@ mod tests {
         assert_eq!(folded_lcccsVar.u.value().unwrap(), folded_lcccs.u);
     }
 
+    /// Folds instances of a CCS of degree 5 (the gate `x^5 + x + 5 = y`, as the S-boxes of
+    /// Poseidon), whose sum-check polynomial has degree 6, natively and in the NIMFSGadget, and
+    /// checks that the gadget has the same shape with the dummy proof as with the actual one.
+    /// The same gate is folded end to end through the HyperNova IVC in `test_ivc_custom_gates`.
+    #[test]
+    pub fn test_nimfs_gadget_verify_degree_5() {
+        let mut rng = test_rng();
+        let n_gates = 4;
+        let rows = (0..n_gates)
+            .map(|i| {
+                let (y_i, x_i) = (1 + i, 1 + n_gates + i);
+                vec![
+                    vec![(Fr::one(), x_i)],
+                    vec![(Fr::one(), x_i), (Fr::from(5), 0), (-Fr::one(), y_i)],
+                ]
+            })
+            .collect();
+        let ccs = CCSBuilder::from_degree_gates(
+            1 + 2 * n_gates,
+            n_gates,
+            &[(Fr::one(), vec![0; 5]), (Fr::one(), vec![1])],
+            rows,
+        )
+        .unwrap();
+        assert_eq!((ccs.t, ccs.d), (2, 5));
+        fn get_z<R: ark_std::rand::Rng>(rng: &mut R, n_gates: usize) -> Vec<Fr> {
+            let xs: Vec<Fr> = (0..n_gates).map(|_| Fr::rand(rng)).collect();
+            let ys = xs.iter().map(|x| x.pow([5]) + x + Fr::from(5));
+            [vec![Fr::one()], ys.collect(), xs].concat()
+        }
+        let (pedersen_params, _) =
+            Pedersen::<Projective>::setup(&mut rng, ccs.n - ccs.l - 1).unwrap();
+
+        let (mu, nu) = (2, 2);
+        let (lcccs_instances, w_lcccs): (Vec<_>, Vec<_>) = (0..mu)
+            .map(|_| {
+                let z = get_z(&mut rng, n_gates);
+                ccs.to_lcccs::<_, _, Pedersen<Projective>, false>(&mut rng, &pedersen_params, &z)
+                    .unwrap()
+            })
+            .unzip();
+        let (cccs_instances, w_cccs): (Vec<_>, Vec<_>) = (0..nu)
+            .map(|_| {
+                let z = get_z(&mut rng, n_gates);
+                ccs.to_cccs::<_, _, Pedersen<Projective>, false>(&mut rng, &pedersen_params, &z)
+                    .unwrap()
+            })
+            .unzip();
+
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let mut transcript_p: PoseidonSponge<Fr> = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let (proof, folded_lcccs, folded_witness, _) =
+            NIMFS::<Projective, PoseidonSponge<Fr>>::prove(
+                &mut transcript_p,
+                &ccs,
+                &lcccs_instances,
+                &cccs_instances,
+                &w_lcccs,
+                &w_cccs,
+            )
+            .unwrap();
+        assert_eq!(proof.sc_proof.degree, ccs.d + 1);
+        let mut transcript_v: PoseidonSponge<Fr> = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let folded_lcccs_v = NIMFS::<Projective, PoseidonSponge<Fr>>::verify(
+            &mut transcript_v,
+            &ccs,
+            &lcccs_instances,
+            &cccs_instances,
+            proof.clone(),
+        )
+        .unwrap();
+        assert_eq!(folded_lcccs, folded_lcccs_v);
+        ccs.check_relation(&folded_witness, &folded_lcccs).unwrap();
+
+        // returns whether the gadget is satisfied, and its number of constraints
+        let verify_gadget = |proof: NIMFSProof<Projective>| {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let lcccs_instancesVar =
+                Vec::<LCCCSVar<Projective>>::new_witness(
+                    cs.clone(),
+                    || Ok(lcccs_instances.clone()),
+                )
+                .unwrap();
+            let cccs_instancesVar =
+                Vec::<CCCSVar<Projective>>::new_witness(cs.clone(), || Ok(cccs_instances.clone()))
+                    .unwrap();
+            let proofVar = ProofVar::<Projective>::new_witness(cs.clone(), || Ok(proof)).unwrap();
+            let mut transcriptVar = PoseidonSpongeVar::<Fr>::new(cs.clone(), &poseidon_config);
+            let (folded_lcccsVar, _) = NIMFSGadget::<Projective>::verify(
+                cs.clone(),
+                &ccs,
+                &mut transcriptVar,
+                &lcccs_instancesVar,
+                &cccs_instancesVar,
+                proofVar,
+                Boolean::TRUE,
+            )
+            .unwrap();
+            if cs.is_satisfied().unwrap() {
+                assert_eq!(folded_lcccsVar.u.value().unwrap(), folded_lcccs.u);
+            }
+            (cs.is_satisfied().unwrap(), cs.num_constraints())
+        };
+        let (satisfied, n_constraints) = verify_gadget(proof);
+        assert!(satisfied);
+        let (satisfied, n_constraints_dummy) = verify_gadget(NIMFSProof::dummy((&ccs, mu, nu)));
+        assert!(!satisfied);
+        assert_eq!(n_constraints, n_constraints_dummy);
+    }
+
     /// test that checks the native LCCCS.to_sponge_{bytes,field_elements} vs
     /// the R1CS constraints version
     #[test]
@@ -1273,7 +1578,7 @@ mod tests {
             let all_Ws = [vec![W_i.clone()], Ws].concat();
             let all_ws = [vec![w_i.clone()], ws].concat();
 
-            let z_i1 = F_circuit.step_native(i, z_i.clone(), vec![]).unwrap();
+            let z_i1 = F_circuit.step_native(i, z_i.clone(), ()).unwrap();
 
             let (U_i1, W_i1);
 
@@ -1304,7 +1609,7 @@ mod tests {
                     i_usize: Some(0),
                     z_0: Some(z_0.clone()),
                     z_i: Some(z_i.clone()),
-                    external_inputs: Some(vec![]),
+                    external_inputs: Some(()),
                     U_i: Some(U_i.clone()),
                     Us: Some(Us.clone()),
                     u_i_C: Some(u_i.C),
@@ -1371,7 +1676,7 @@ mod tests {
                         ]
                         .concat(),
                     ),
-                    x: Some(cf_u_i_x.clone()),
+                    x: Some(cf_u_i_x),
                 };
 
                 // ensure that the CycleFoldCircuit is well defined
@@ -1395,7 +1700,6 @@ mod tests {
                     pp_hash,
                     cf_W_i.clone(), // CycleFold running instance witness
                     cf_U_i.clone(), // CycleFold running instance
-                    cf_u_i_x,       // CycleFold incoming instance
                     cf_circuit,
                     &mut rng,
                 )
@@ -1422,7 +1726,7 @@ mod tests {
                     i_usize: Some(i),
                     z_0: Some(z_0.clone()),
                     z_i: Some(z_i.clone()),
-                    external_inputs: Some(vec![]),
+                    external_inputs: Some(()),
                     U_i: Some(U_i.clone()),
                     Us: Some(Us.clone()),
                     u_i_C: Some(u_i.C),
@@ -1449,7 +1753,8 @@ mod tests {
 
             let (r1cs_w_i1, r1cs_x_i1) = extract_w_x::<Fr>(&cs); // includes 1 and public inputs
             assert_eq!(r1cs_x_i1[0], augmented_f_circuit.x.unwrap());
-            let r1cs_z = [vec![Fr::one()], r1cs_x_i1.clone(), r1cs_w_i1.clone()].concat();
+            let mut r1cs_z = [vec![Fr::one()], r1cs_x_i1.clone(), r1cs_w_i1.clone()].concat();
+            ccs.pad_z(&mut r1cs_z).unwrap();
             // compute committed instances, w_{i+1}, u_{i+1}, which will be used as w_i, u_i, so we
             // assign them directly to w_i, u_i.
             (u_i, w_i) = ccs
@@ -1458,7 +1763,8 @@ mod tests {
             ccs.check_relation(&w_i, &u_i).unwrap();
 
             // sanity checks
-            assert_eq!(w_i.w, r1cs_w_i1);
+            assert_eq!(w_i.w[..r1cs_w_i1.len()], r1cs_w_i1);
+            assert!(w_i.w[r1cs_w_i1.len()..].iter().all(|w_j| w_j.is_zero()));
             assert_eq!(u_i.x, r1cs_x_i1);
             assert_eq!(u_i.x[0], augmented_f_circuit.x.unwrap());
             assert_eq!(u_i.x[1], augmented_f_circuit.cf_x.unwrap());
@@ -1486,4 +1792,26 @@ mod tests {
             println!("augmented_f_circuit step {}: {:?}", i, start.elapsed());
         }
     }
+
+    // checks that an FCircuit whose step returns a state of a wrong length makes the synthesis of
+    // the AugmentedFCircuit fail, here when computing its CCS
+    #[test]
+    fn test_augmented_f_circuit_wrong_state_len() {
+        let F_circuit = WrongStateLenFCircuit::<Fr>::new(()).unwrap();
+        let (result, logs) = capture_logs(|| {
+            AugmentedFCircuit::<
+                Projective,
+                Projective2,
+                GVar2,
+                WrongStateLenFCircuit<Fr>,
+                1,
+                1,
+            >::empty(&poseidon_canonical_config::<Fr>(), F_circuit, None)
+        });
+        assert!(matches!(
+            result,
+            Err(Error::SynthesisError(SynthesisError::Unsatisfiable))
+        ));
+        assert_eq!(logs, vec![WRONG_STATE_LEN_LOG]);
+    }
 }
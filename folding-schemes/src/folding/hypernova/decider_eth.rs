@@ -1,29 +1,50 @@
-/// This file implements the HyperNova's onchain (Ethereum's EVM) decider.
-use ark_crypto_primitives::sponge::Absorb;
+/// This file implements the HyperNova's onchain (Ethereum's EVM) deciders, `Decider`, which
+/// opens the witness commitment with KZG, and `MultilinearDecider`, which opens it with a
+/// multilinear commitment scheme such as Zeromorph.
+use ark_bn254::Bn254;
+use ark_crypto_primitives::sponge::{Absorb, CryptographicSponge};
 use ark_ec::{CurveGroup, Group};
-use ark_ff::PrimeField;
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::Groth16;
 use ark_r1cs_std::{prelude::CurveVar, ToConstraintFieldGadget};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_snark::SNARK;
 use ark_std::rand::{CryptoRng, RngCore};
-use ark_std::{One, Zero};
+use ark_std::{log2, One, Zero};
 use core::marker::PhantomData;
 
 pub use super::decider_eth_circuit::DeciderEthCircuit;
 use super::decider_eth_circuit::DeciderHyperNovaGadget;
-use super::HyperNova;
+use super::{cccs::CCCS, lcccs::LCCCS, HyperNova};
 use crate::commitment::{
-    kzg::Proof as KZGProof, pedersen::Params as PedersenParams, CommitmentScheme,
+    kzg::Proof as KZGProof, pedersen::Params as PedersenParams, zeromorph::Zeromorph,
+    CommitmentScheme, MultilinearCS,
 };
 use crate::folding::circuits::decider::DeciderEnabledNIFS;
+use crate::folding::circuits::nonnative::affine::nonnative_affine_to_packed_field_elements;
 use crate::folding::circuits::CF2;
-use crate::folding::nova::decider_eth::VerifierParam;
-use crate::folding::traits::{Inputize, WitnessOps};
+use crate::folding::nova::decider_eth::{point2_to_eth_format, point_to_eth_format};
+use crate::folding::traits::WitnessOps;
 use crate::frontend::FCircuit;
+use crate::transcript::keccak::Keccak256Transcript;
 use crate::Error;
 use crate::{Decider as DeciderTrait, FoldingScheme};
 
+/// Verifier params of the HyperNova onchain deciders, whose decider circuit has no application
+/// context nor hashed state among its public inputs, unlike the Nova one.
 #[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifierParam<C1, CS_VerifyingKey, S_VerifyingKey>
+where
+    C1: CurveGroup,
+    CS_VerifyingKey: Clone + CanonicalSerialize + CanonicalDeserialize,
+    S_VerifyingKey: Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    pub pp_hash: C1::ScalarField,
+    pub snark_vp: S_VerifyingKey,
+    pub cs_vp: CS_VerifyingKey,
+}
+
+#[derive(Debug, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct Proof<C1, CS1, S>
 where
     C1: CurveGroup,
@@ -32,11 +53,57 @@ where
 {
     snark_proof: S::Proof,
     kzg_proof: CS1::Proof,
-    // rho used at the last fold, U_{i+1}=NIMFS.V(rho, U_i, u_i), it is checked in-circuit
+    // rho used at the last fold, U_{i+1}=NIMFS.V(rho, [U_i, U_r], u_i), it is checked in-circuit
+    rho: C1::ScalarField,
+    // commitment of the blinding instance U_r folded at the last fold, it is a public input of
+    // the circuit
+    blinding_commitment: C1,
+    // the KZG challenge is provided by the prover, but in-circuit it is checked to match
+    // the in-circuit computed computed one.
+    kzg_challenge: C1::ScalarField,
+}
+
+// not derived, so that it does not require `S: Clone`, as in `nova::decider_eth::Proof`
+impl<C1, CS1, S> Clone for Proof<C1, CS1, S>
+where
+    C1: CurveGroup,
+    CS1: CommitmentScheme<C1, ProverChallenge = C1::ScalarField, Challenge = C1::ScalarField>,
+    S: SNARK<C1::ScalarField>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            snark_proof: self.snark_proof.clone(),
+            kzg_proof: self.kzg_proof.clone(),
+            rho: self.rho,
+            blinding_commitment: self.blinding_commitment,
+            kzg_challenge: self.kzg_challenge,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MultilinearProof<C1, CS1, S>
+where
+    C1: CurveGroup,
+    CS1: MultilinearCS<C1>,
+    S: SNARK<C1::ScalarField>,
+{
+    snark_proof: S::Proof,
+    // evaluation of the MLE of the witness at `point`, and its opening proof
+    eval: C1::ScalarField,
+    opening_proof: CS1::Proof,
+    // rho used at the last fold, U_{i+1}=NIMFS.V(rho, [U_i, U_r], u_i), it is checked in-circuit
     rho: C1::ScalarField,
+    // commitment of the blinding instance U_r folded at the last fold, it is a public input of
+    // the circuit
+    blinding_commitment: C1,
     // the KZG challenge is provided by the prover, but in-circuit it is checked to match
     // the in-circuit computed computed one.
     kzg_challenge: C1::ScalarField,
+    // the point at which the MLE of the witness is opened, which is made of the sum-check point
+    // `r_x` of `U_{i+1}` (extended with powers of `kzg_challenge` if needed). As `r_x` is not
+    // known by the verifier, it is a public input, which is checked in-circuit
+    point: Vec<C1::ScalarField>,
 }
 
 /// Onchain Decider, for ethereum use cases
@@ -132,24 +199,25 @@ where
         pp: Self::ProverParam,
         folding_scheme: FS,
     ) -> Result<Self::Proof, Error> {
+        trace_span!("hypernova_decider_eth_prove");
         let (snark_pk, cs_pk): (S::ProvingKey, CS1::ProverParams) = pp;
 
         let circuit = DeciderEthCircuit::<C1, C2, GC2>::try_from(HyperNova::from(folding_scheme))?;
 
         let rho = circuit.randomness;
+        let blinding_commitment = circuit.proof.U_r.C;
 
-        // get the challenges that have been already computed when preparing the circuit inputs in
-        // the above `try_from` call
-        let kzg_challenges = circuit.kzg_challenges.clone();
+        // get the challenge that has been already computed when preparing the circuit inputs in
+        // the above `try_from` call. There is a single commitment, so no batching is needed.
+        let kzg_challenge = circuit.kzg_challenge;
 
-        // generate KZG proofs
+        // generate the KZG proof
         let kzg_proofs = circuit
             .W_i1
             .get_openings()
             .iter()
-            .zip(&kzg_challenges)
-            .map(|((v, _), &c)| {
-                CS1::prove_with_challenge(&cs_pk, c, v, &C1::ScalarField::zero(), None)
+            .map(|(v, _)| {
+                CS1::prove_with_challenge(&cs_pk, kzg_challenge, v, &C1::ScalarField::zero(), None)
             })
             .collect::<Result<Vec<_>, _>>()?;
 
@@ -159,12 +227,11 @@ where
         Ok(Self::Proof {
             snark_proof,
             rho,
+            blinding_commitment,
             kzg_proof: (kzg_proofs.len() == 1)
                 .then(|| kzg_proofs[0].clone())
                 .ok_or(Error::NotExpectedLength(kzg_proofs.len(), 1))?,
-            kzg_challenge: (kzg_challenges.len() == 1)
-                .then(|| kzg_challenges[0])
-                .ok_or(Error::NotExpectedLength(kzg_challenges.len(), 1))?,
+            kzg_challenge,
         })
     }
 
@@ -178,6 +245,7 @@ where
         incoming_commitments: &Self::CommittedInstance,
         proof: &Self::Proof,
     ) -> Result<bool, Error> {
+        trace_span!("hypernova_decider_eth_verify");
         if i <= C1::ScalarField::one() {
             return Err(Error::NotEnoughSteps);
         }
@@ -188,24 +256,26 @@ where
             cs_vp,
         } = vp;
 
-        // 6.2. Fold the commitments
-        let C = DeciderHyperNovaGadget::fold_group_elements_native(
+        // 6.2. Fold the commitments, together with the one of the blinding instance
+        let C = fold_commitments(
             running_commitments,
             incoming_commitments,
-            None,
+            proof.blinding_commitment,
             proof.rho,
-        )?[0];
+        )?;
 
         // Note: the NIMFS proof is checked inside the DeciderEthCircuit, which ensures that the
         // 'proof.U_i1' is correctly computed
-        let public_input: Vec<C1::ScalarField> = [
-            &[pp_hash, i][..],
+        let public_input = public_input(
+            pp_hash,
+            i,
             &z_0,
             &z_i,
-            &C.inputize(),
-            &[proof.kzg_challenge, proof.kzg_proof.eval, proof.rho],
-        ]
-        .concat();
+            C,
+            &[proof.kzg_challenge, proof.kzg_proof.eval],
+            proof.rho,
+            proof.blinding_commitment,
+        );
 
         let snark_v = S::verify(&snark_vp, &public_input, &proof.snark_proof)
             .map_err(|e| Error::Other(e.to_string()))?;
@@ -221,6 +291,318 @@ where
     }
 }
 
+/// Onchain Decider, for ethereum use cases, whose CS1 is a multilinear commitment scheme, e.g.
+/// Zeromorph. The MLE of the witness is opened at the sum-check point of the folded instance, see
+/// `decider_eth_circuit::multilinear_opening_point`.
+#[derive(Clone, Debug)]
+pub struct MultilinearDecider<
+    C1,
+    GC1,
+    C2,
+    GC2,
+    FC,
+    CS1,
+    CS2,
+    S,
+    FS,
+    const MU: usize,
+    const NU: usize,
+> {
+    _c1: PhantomData<C1>,
+    _gc1: PhantomData<GC1>,
+    _c2: PhantomData<C2>,
+    _gc2: PhantomData<GC2>,
+    _fc: PhantomData<FC>,
+    _cs1: PhantomData<CS1>,
+    _cs2: PhantomData<CS2>,
+    _s: PhantomData<S>,
+    _fs: PhantomData<FS>,
+}
+
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS, const MU: usize, const NU: usize>
+    DeciderTrait<C1, C2, FC, FS>
+    for MultilinearDecider<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS, MU, NU>
+where
+    C1: CurveGroup,
+    C2: CurveGroup,
+    GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+    GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+    FC: FCircuit<C1::ScalarField>,
+    // CS1 is used by HyperNova to commit to the witnesses, and by the decider to open the MLE
+    // of the witness with the same parameters, e.g. Zeromorph
+    CS1: CommitmentScheme<C1>
+        + MultilinearCS<
+            C1,
+            ProverParams = <CS1 as CommitmentScheme<C1>>::ProverParams,
+            VerifierParams = <CS1 as CommitmentScheme<C1>>::VerifierParams,
+        >,
+    // enforce that the CS2 is Pedersen commitment scheme, since we're at Ethereum's EVM decider
+    CS2: CommitmentScheme<C2, ProverParams = PedersenParams<C2>>,
+    S: SNARK<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>,
+    <C1 as CurveGroup>::BaseField: PrimeField,
+    <C2 as CurveGroup>::BaseField: PrimeField,
+    <C1 as Group>::ScalarField: Absorb,
+    <C2 as Group>::ScalarField: Absorb,
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    // constrain FS into HyperNova, since this is a Decider specifically for HyperNova
+    HyperNova<C1, GC1, C2, GC2, FC, CS1, CS2, MU, NU, false>: From<FS>,
+    crate::folding::hypernova::ProverParams<C1, C2, CS1, CS2, false>:
+        From<<FS as FoldingScheme<C1, C2, FC>>::ProverParam>,
+    crate::folding::hypernova::VerifierParams<C1, C2, CS1, CS2, false>:
+        From<<FS as FoldingScheme<C1, C2, FC>>::VerifierParam>,
+{
+    type PreprocessorParam = (FS::ProverParam, FS::VerifierParam);
+    type ProverParam = (S::ProvingKey, <CS1 as MultilinearCS<C1>>::ProverParams);
+    type Proof = MultilinearProof<C1, CS1, S>;
+    /// The verifier params of CS1 come together with the number of variables of the MLE of the
+    /// witness.
+    type VerifierParam =
+        VerifierParam<C1, (<CS1 as MultilinearCS<C1>>::VerifierParams, usize), S::VerifyingKey>;
+    type PublicInput = Vec<C1::ScalarField>;
+    type CommittedInstance = Vec<C1>;
+
+    fn preprocess(
+        mut rng: impl RngCore + CryptoRng,
+        prep_param: Self::PreprocessorParam,
+        fs: FS,
+    ) -> Result<(Self::ProverParam, Self::VerifierParam), Error> {
+        let circuit = DeciderEthCircuit::<C1, C2, GC2>::try_from_multilinear(HyperNova::from(fs))?;
+        let n_vars = log2(circuit.W_i1.w.len()) as usize;
+
+        // get the Groth16 specific setup for the circuit
+        let (g16_pk, g16_vk) = S::circuit_specific_setup(circuit, &mut rng)
+            .map_err(|e| Error::SNARKSetupFail(e.to_string()))?;
+
+        // get the FoldingScheme prover & verifier params from HyperNova
+        #[allow(clippy::type_complexity)]
+        let hypernova_pp: <HyperNova<C1, GC1, C2, GC2, FC, CS1, CS2, MU, NU, false> as FoldingScheme<
+            C1,
+            C2,
+            FC,
+        >>::ProverParam = prep_param.0.into();
+        #[allow(clippy::type_complexity)]
+        let hypernova_vp: <HyperNova<C1, GC1, C2, GC2, FC, CS1, CS2, MU, NU, false> as FoldingScheme<
+            C1,
+            C2,
+            FC,
+        >>::VerifierParam = prep_param.1.into();
+        let pp_hash = hypernova_vp.pp_hash()?;
+
+        let pp = (g16_pk, hypernova_pp.cs_pp);
+
+        let vp = Self::VerifierParam {
+            pp_hash,
+            snark_vp: g16_vk,
+            cs_vp: (hypernova_vp.cs_vp, n_vars),
+        };
+        Ok((pp, vp))
+    }
+
+    fn prove(
+        mut rng: impl RngCore + CryptoRng,
+        pp: Self::ProverParam,
+        folding_scheme: FS,
+    ) -> Result<Self::Proof, Error> {
+        trace_span!("hypernova_decider_eth_prove");
+        let (snark_pk, cs_pk): (S::ProvingKey, <CS1 as MultilinearCS<C1>>::ProverParams) = pp;
+
+        let circuit = DeciderEthCircuit::<C1, C2, GC2>::try_from_multilinear(HyperNova::from(
+            folding_scheme,
+        ))?;
+
+        let rho = circuit.randomness;
+        let blinding_commitment = circuit.proof.U_r.C;
+
+        // get the challenge and the opening point that have been already computed when preparing
+        // the circuit inputs in the above `try_from_multilinear` call, and open the MLE of the
+        // witness (there is a single commitment, so no batching is needed) at the point. The
+        // challenges of the opening are obtained from a Keccak256 transcript, so that they can be
+        // recomputed natively by the verifier contract
+        let kzg_challenge = circuit.kzg_challenge;
+        let point = circuit
+            .opening_point
+            .clone()
+            .ok_or(Error::Other("missing opening point".to_string()))?;
+        let mut transcript = Keccak256Transcript::new(&());
+        let (eval, opening_proof) =
+            <CS1 as MultilinearCS<C1>>::open(&cs_pk, &mut transcript, &circuit.W_i1.w, &point)?;
+
+        let snark_proof =
+            S::prove(&snark_pk, circuit, &mut rng).map_err(|e| Error::Other(e.to_string()))?;
+
+        Ok(Self::Proof {
+            snark_proof,
+            eval,
+            opening_proof,
+            rho,
+            blinding_commitment,
+            kzg_challenge,
+            point,
+        })
+    }
+
+    fn verify(
+        vp: Self::VerifierParam,
+        i: C1::ScalarField,
+        z_0: Vec<C1::ScalarField>,
+        z_i: Vec<C1::ScalarField>,
+        // we don't use the instances at the verifier level, since we check them in-circuit
+        running_commitments: &Self::CommittedInstance,
+        incoming_commitments: &Self::CommittedInstance,
+        proof: &Self::Proof,
+    ) -> Result<bool, Error> {
+        trace_span!("hypernova_decider_eth_verify");
+        if i <= C1::ScalarField::one() {
+            return Err(Error::NotEnoughSteps);
+        }
+
+        let Self::VerifierParam {
+            pp_hash,
+            snark_vp,
+            cs_vp: (cs_vp, n_vars),
+        } = vp;
+        if proof.point.len() != n_vars {
+            return Err(Error::NotExpectedLength(proof.point.len(), n_vars));
+        }
+
+        // 6.2. Fold the commitments, together with the one of the blinding instance
+        let C = fold_commitments(
+            running_commitments,
+            incoming_commitments,
+            proof.blinding_commitment,
+            proof.rho,
+        )?;
+
+        // Note: the NIMFS proof is checked inside the DeciderEthCircuit, which ensures that the
+        // 'proof.U_i1' is correctly computed, and that `proof.point` is built from its `r_x`
+        let public_input = public_input(
+            pp_hash,
+            i,
+            &z_0,
+            &z_i,
+            C,
+            &[&[proof.kzg_challenge, proof.eval][..], &proof.point].concat(),
+            proof.rho,
+            proof.blinding_commitment,
+        );
+
+        let snark_v = S::verify(&snark_vp, &public_input, &proof.snark_proof)
+            .map_err(|e| Error::Other(e.to_string()))?;
+        if !snark_v {
+            return Err(Error::SNARKVerificationFail);
+        }
+
+        // 7.3. Verify the opening proof of the MLE of the witness
+        let mut transcript = Keccak256Transcript::new(&());
+        <CS1 as MultilinearCS<C1>>::verify(
+            &cs_vp,
+            &mut transcript,
+            &C,
+            &proof.point,
+            &proof.eval,
+            &proof.opening_proof,
+        )?;
+
+        Ok(true)
+    }
+}
+
+/// Folds the commitments of the running and incoming instances together with the one of the
+/// blinding instance, as done by `NIMFS` for the last fold of the decider.
+fn fold_commitments<C1: CurveGroup>(
+    running_commitments: &[C1],
+    incoming_commitments: &[C1],
+    blinding_commitment: C1,
+    rho: C1::ScalarField,
+) -> Result<C1, Error>
+where
+    C1::ScalarField: Absorb,
+{
+    Ok(DeciderHyperNovaGadget::fold_group_elements_native(
+        &[running_commitments, &[blinding_commitment]].concat(),
+        incoming_commitments,
+        None,
+        rho,
+    )?[0])
+}
+
+/// Returns the public inputs of the decider circuit, where `openings` are the KZG challenge and
+/// the evaluation, followed by the opening point in the multilinear case.
+#[allow(clippy::too_many_arguments)]
+fn public_input<C1: CurveGroup>(
+    pp_hash: C1::ScalarField,
+    i: C1::ScalarField,
+    z_0: &[C1::ScalarField],
+    z_i: &[C1::ScalarField],
+    C: C1,
+    openings: &[C1::ScalarField],
+    rho: C1::ScalarField,
+    blinding_commitment: C1,
+) -> Vec<C1::ScalarField> {
+    [
+        &[pp_hash, i][..],
+        z_0,
+        z_i,
+        &nonnative_affine_to_packed_field_elements(C),
+        openings,
+        &[rho],
+        &nonnative_affine_to_packed_field_elements(blinding_commitment),
+    ]
+    .concat()
+}
+
+/// Prepares solidity calldata for calling the HyperNovaDecider contract, for a proof of the
+/// `MultilinearDecider` over Zeromorph.
+///
+/// The contract folds the commitments of the running and incoming instances together with the
+/// blinding commitment of the proof, as `MultilinearDecider::verify` does, so only their
+/// commitments are encoded.
+pub fn prepare_calldata(
+    function_signature_check: [u8; 4],
+    i: ark_bn254::Fr,
+    z_0: Vec<ark_bn254::Fr>,
+    z_i: Vec<ark_bn254::Fr>,
+    running_instance: &LCCCS<ark_bn254::G1Projective>,
+    incoming_instance: &CCCS<ark_bn254::G1Projective>,
+    proof: MultilinearProof<ark_bn254::G1Projective, Zeromorph<'static, Bn254>, Groth16<Bn254>>,
+) -> Result<Vec<u8>, Error> {
+    Ok(vec![
+        function_signature_check.to_vec(),
+        i.into_bigint().to_bytes_be(), // i
+        z_0.iter()
+            .flat_map(|v| v.into_bigint().to_bytes_be())
+            .collect::<Vec<u8>>(), // z_0
+        z_i.iter()
+            .flat_map(|v| v.into_bigint().to_bytes_be())
+            .collect::<Vec<u8>>(), // z_i
+        point_to_eth_format(running_instance.C.into_affine())?,
+        point_to_eth_format(incoming_instance.C.into_affine())?,
+        point_to_eth_format(proof.blinding_commitment.into_affine())?,
+        proof.kzg_challenge.into_bigint().to_bytes_be(), // challenge
+        proof.eval.into_bigint().to_bytes_be(),          // eval
+        proof.rho.into_bigint().to_bytes_be(),           // rho
+        proof
+            .point
+            .iter()
+            .flat_map(|u_k| u_k.into_bigint().to_bytes_be())
+            .collect::<Vec<u8>>(), // opening point
+        point_to_eth_format(proof.snark_proof.a)?,       // pA
+        point2_to_eth_format(proof.snark_proof.b)?,      // pB
+        point_to_eth_format(proof.snark_proof.c)?,       // pC
+        proof
+            .opening_proof
+            .q_commitments
+            .iter()
+            .map(|q_k| point_to_eth_format(q_k.into_affine()))
+            .collect::<Result<Vec<_>, _>>()?
+            .concat(), // q_k of the Zeromorph proof
+        point_to_eth_format(proof.opening_proof.q_hat.into_affine())?, // q_hat
+        point_to_eth_format(proof.opening_proof.proof.into_affine())?, // KZG proof
+    ]
+    .concat())
+}
+
 #[cfg(test)]
 pub mod tests {
     use ark_bn254::{constraints::GVar, Bn254, Fr, G1Projective as Projective};
@@ -229,13 +611,15 @@ pub mod tests {
     use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
 
     use super::*;
-    use crate::commitment::{kzg::KZG, pedersen::Pedersen};
+    use crate::commitment::{kzg::KZG, pedersen::Pedersen, zeromorph::Zeromorph};
     use crate::folding::hypernova::cccs::CCCS;
     use crate::folding::hypernova::lcccs::LCCCS;
     use crate::folding::hypernova::PreprocessorParam;
+    use crate::folding::tests::test_corrupted_decider_proof;
     use crate::folding::traits::CommittedInstanceOps;
     use crate::frontend::utils::CubicFCircuit;
     use crate::transcript::poseidon::poseidon_canonical_config;
+    use crate::MultiFolding;
 
     #[test]
     fn test_decider() {
@@ -279,10 +663,10 @@ pub mod tests {
 
         let mut hypernova = HN::init(&hypernova_params, F_circuit, z_0.clone()).unwrap();
         hypernova
-            .prove_step(&mut rng, vec![], Some((vec![], vec![])))
+            .prove_step(&mut rng, (), Some((vec![], vec![])))
             .unwrap();
         hypernova
-            .prove_step(&mut rng, vec![], Some((vec![], vec![])))
+            .prove_step(&mut rng, (), Some((vec![], vec![])))
             .unwrap(); // do a 2nd step
 
         // prepare the Decider prover & verifier params
@@ -294,16 +678,215 @@ pub mod tests {
 
         // decider proof verification
         let verified = D::verify(
-            decider_vp,
+            decider_vp.clone(),
             hypernova.i,
-            hypernova.z_0,
-            hypernova.z_i,
+            hypernova.z_0.clone(),
+            hypernova.z_i.clone(),
             &hypernova.U_i.get_commitments(),
             &hypernova.u_i.get_commitments(),
             &proof,
         )
         .unwrap();
         assert!(verified);
+
+        // corrupted proofs and statements are rejected with an error instead of panicking
+        let corruptions: Vec<fn(&mut Proof<Projective, KZG<'static, Bn254>, Groth16<Bn254>>)> = vec![
+            |p| p.snark_proof.c = p.snark_proof.a,
+            |p| p.kzg_proof.eval += Fr::one(),
+            |p| p.kzg_proof.proof += Projective::generator(),
+            |p| p.rho += Fr::one(),
+            |p| p.blinding_commitment += Projective::generator(),
+            |p| p.kzg_challenge += Fr::one(),
+        ];
+        test_corrupted_decider_proof::<Projective, Projective2, CubicFCircuit<Fr>, HN, D>(
+            decider_vp,
+            hypernova.i,
+            hypernova.z_0,
+            hypernova.z_i,
+            hypernova.U_i.get_commitments(),
+            hypernova.u_i.get_commitments(),
+            &proof,
+            corruptions,
+        );
+    }
+
+    // runs the decider on a HyperNova instance that folds 2 LCCCS and 2 CCCS instances at each
+    // step, including the base case
+    #[test]
+    fn test_decider_multifolding() {
+        const MU: usize = 2;
+        const NU: usize = 2;
+        type HN = HyperNova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Zeromorph<'static, Bn254>,
+            Pedersen<Projective2>,
+            MU,
+            NU,
+            false,
+        >;
+        type D = MultilinearDecider<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Zeromorph<'static, Bn254>,
+            Pedersen<Projective2>,
+            Groth16<Bn254>,
+            HN,
+            MU,
+            NU,
+        >;
+
+        let mut rng = rand::rngs::OsRng;
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let z_0 = vec![Fr::from(3_u32)];
+
+        let prep_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let hypernova_params = HN::preprocess(&mut rng, &prep_param).unwrap();
+
+        let mut hypernova = HN::init(&hypernova_params, F_circuit, z_0.clone()).unwrap();
+        for i in 0..2 {
+            let (U, W) = hypernova
+                .new_running_instance(&mut rng, vec![Fr::from(85_u32 + i)], ())
+                .unwrap();
+            let (u, w) = hypernova
+                .new_incoming_instance(&mut rng, vec![Fr::from(15_u32 + i)], ())
+                .unwrap();
+            hypernova
+                .prove_step(&mut rng, (), Some((vec![(U, W)], vec![(u, w)])))
+                .unwrap();
+        }
+        HN::verify(hypernova_params.1.clone(), hypernova.ivc_proof()).unwrap();
+
+        let (decider_pp, decider_vp) =
+            D::preprocess(&mut rng, hypernova_params, hypernova.clone()).unwrap();
+        let proof = D::prove(rng, decider_pp, hypernova.clone()).unwrap();
+        let verify = |proof: &MultilinearProof<_, _, _>| {
+            D::verify(
+                decider_vp.clone(),
+                hypernova.i,
+                hypernova.z_0.clone(),
+                hypernova.z_i.clone(),
+                &hypernova.U_i.get_commitments(),
+                &hypernova.u_i.get_commitments(),
+                proof,
+            )
+        };
+        assert!(verify(&proof).unwrap());
+
+        // the opening point is bound to the sum-check point of the folded instance by the SNARK
+        let mut invalid_proof = proof;
+        invalid_proof.point[0] += Fr::one();
+        assert!(verify(&invalid_proof).is_err());
+    }
+
+    // checks that the randomized decider hides the final witness, i.e. that two decider proofs of
+    // the same statement open distinct commitments, and that they still verify
+    #[test]
+    fn test_decider_randomized() {
+        const MU: usize = 1;
+        const NU: usize = 1;
+        type HN = HyperNova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Zeromorph<'static, Bn254>,
+            Pedersen<Projective2>,
+            MU,
+            NU,
+            false,
+        >;
+        type D = MultilinearDecider<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Zeromorph<'static, Bn254>,
+            Pedersen<Projective2>,
+            Groth16<Bn254>,
+            HN,
+            MU,
+            NU,
+        >;
+
+        let mut rng = rand::rngs::OsRng;
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let z_0 = vec![Fr::from(3_u32)];
+
+        let prep_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let hypernova_params = HN::preprocess(&mut rng, &prep_param).unwrap();
+
+        let mut hypernova = HN::init(&hypernova_params, F_circuit, z_0.clone()).unwrap();
+        hypernova.prove_step(&mut rng, (), None).unwrap();
+        hypernova.prove_step(&mut rng, (), None).unwrap();
+
+        let folded_commitment = |hn: HN| {
+            DeciderEthCircuit::<Projective, Projective2, GVar2>::try_from(hn)
+                .unwrap()
+                .U_i1
+                .C
+        };
+        // without randomization, the folded instance is fully determined by the IVC state
+        assert_eq!(
+            folded_commitment(hypernova.clone()),
+            folded_commitment(hypernova.clone())
+        );
+
+        let mut hypernova_1 = hypernova.clone();
+        hypernova_1.randomize_for_decider(&mut rng).unwrap();
+        let mut hypernova_2 = hypernova.clone();
+        hypernova_2.randomize_for_decider(&mut rng).unwrap();
+        assert_ne!(
+            folded_commitment(hypernova_1.clone()),
+            folded_commitment(hypernova_2.clone())
+        );
+        assert_ne!(
+            folded_commitment(hypernova_1.clone()),
+            folded_commitment(hypernova.clone())
+        );
+
+        // the CycleFold running instance is randomized too: the witness checked by the decider
+        // is fresh at each randomization and differs from the one of the IVC
+        let cf_blinding = |hn: &HN| hn.cf_blinding.clone().unwrap();
+        let (cf_U_r_1, _, cf_W_1) = cf_blinding(&hypernova_1);
+        let (cf_U_r_2, _, cf_W_2) = cf_blinding(&hypernova_2);
+        assert_ne!(cf_U_r_1, cf_U_r_2);
+        assert_ne!(cf_W_1.W, cf_W_2.W);
+        for cf_W in [&cf_W_1, &cf_W_2] {
+            assert_ne!(cf_W.W, hypernova.cf_W_i.W);
+            assert_ne!(cf_W.E, hypernova.cf_W_i.E);
+        }
+
+        // the circuit does not depend on the randomization, so the params obtained from the
+        // non-randomized instance are valid for the randomized ones
+        let (decider_pp, decider_vp) =
+            D::preprocess(&mut rng, hypernova_params, hypernova.clone()).unwrap();
+        for hn in [hypernova_1, hypernova_2] {
+            let proof = D::prove(rng, decider_pp.clone(), hn.clone()).unwrap();
+            let verified = D::verify(
+                decider_vp.clone(),
+                hn.i,
+                hn.z_0,
+                hn.z_i,
+                &hn.U_i.get_commitments(),
+                &hn.u_i.get_commitments(),
+                &proof,
+            )
+            .unwrap();
+            assert!(verified);
+        }
     }
 
     #[test]
@@ -317,19 +900,19 @@ pub mod tests {
             Projective2,
             GVar2,
             CubicFCircuit<Fr>,
-            KZG<'static, Bn254>,
+            Zeromorph<'static, Bn254>,
             Pedersen<Projective2>,
             MU,
             NU,
             false,
         >;
-        type D = Decider<
+        type D = MultilinearDecider<
             Projective,
             GVar,
             Projective2,
             GVar2,
             CubicFCircuit<Fr>,
-            KZG<'static, Bn254>,
+            Zeromorph<'static, Bn254>,
             Pedersen<Projective2>,
             Groth16<Bn254>, // here we define the Snark to use in the decider
             HN,             // here we define the FoldingScheme to use
@@ -387,10 +970,10 @@ pub mod tests {
         let mut hypernova = HN::init(&hypernova_params, F_circuit, z_0.clone()).unwrap();
 
         hypernova
-            .prove_step(&mut rng, vec![], Some((vec![], vec![])))
+            .prove_step(&mut rng, (), Some((vec![], vec![])))
             .unwrap();
         hypernova
-            .prove_step(&mut rng, vec![], Some((vec![], vec![])))
+            .prove_step(&mut rng, (), Some((vec![], vec![])))
             .unwrap();
 
         // decider proof generation
@@ -445,16 +1028,20 @@ pub mod tests {
         let decider_vp_deserialized =
             VerifierParam::<
                 Projective,
-                <KZG<'static, Bn254> as CommitmentScheme<Projective>>::VerifierParams,
+                (
+                    <Zeromorph<'static, Bn254> as MultilinearCS<Projective>>::VerifierParams,
+                    usize,
+                ),
                 <Groth16<Bn254> as SNARK<Fr>>::VerifyingKey,
             >::deserialize_compressed(&mut decider_vp_serialized.as_slice())
             .unwrap();
 
-        let proof_deserialized =
-            Proof::<Projective, KZG<'static, Bn254>, Groth16<Bn254>>::deserialize_compressed(
-                &mut proof_serialized.as_slice(),
-            )
-            .unwrap();
+        let proof_deserialized = MultilinearProof::<
+            Projective,
+            Zeromorph<'static, Bn254>,
+            Groth16<Bn254>,
+        >::deserialize_compressed(&mut proof_serialized.as_slice())
+        .unwrap();
 
         let mut reader = public_inputs_serialized.as_slice();
         let i_deserialized = Fr::deserialize_compressed(&mut reader).unwrap();
@@ -11,22 +11,28 @@ use ark_r1cs_std::{
     alloc::{AllocVar, AllocationMode},
     boolean::Boolean,
     eq::EqGadget,
-    fields::fp::FpVar,
+    fields::{fp::FpVar, FieldVar},
     prelude::CurveVar,
     ToConstraintFieldGadget,
 };
 use ark_relations::r1cs::{Namespace, SynthesisError};
-use ark_std::{borrow::Borrow, log2, marker::PhantomData};
+use ark_std::{borrow::Borrow, log2, marker::PhantomData, Zero};
 
 use super::{
     circuits::{CCCSVar, LCCCSVar, NIMFSGadget, ProofVar as NIMFSProofVar},
     nimfs::{NIMFSProof, NIMFS},
     HyperNova, Witness, CCCS, LCCCS,
 };
-use crate::folding::circuits::{decider::on_chain::GenericOnchainDeciderCircuit, CF1, CF2};
-use crate::folding::traits::{WitnessOps, WitnessVarOps};
+use crate::folding::circuits::cyclefold::CycleFoldCommittedInstance;
+use crate::folding::circuits::nonnative::affine::NonNativeAffineVar;
+use crate::folding::circuits::{
+    decider::on_chain::GenericOnchainDeciderCircuit, utils::le_bits_to_fp_var_checked, CF1, CF2,
+};
+use crate::folding::traits::{CommittedInstanceOps, Dummy, WitnessOps, WitnessVarOps};
 use crate::frontend::FCircuit;
 use crate::utils::gadgets::{eval_mle, MatrixGadget};
+use crate::utils::mle::point_from_challenge;
+use crate::utils::vec::check_len;
 use crate::Error;
 use crate::{
     arith::{
@@ -131,30 +137,106 @@ where
 {
     type Error = Error;
 
+    /// Prepares the circuit for the KZG decider, which opens the witness commitment as the
+    /// univariate polynomial interpolated from the witness, at the KZG challenge.
     fn try_from(hn: HyperNova<C1, GC1, C2, GC2, FC, CS1, CS2, MU, NU, H>) -> Result<Self, Error> {
-        // compute the U_{i+1}, W_{i+1}, by folding the last running & incoming instances
+        Self::from_hypernova(hn, false)
+    }
+}
+
+impl<
+        C1: CurveGroup,
+        C2: CurveGroup,
+        GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+    > DeciderEthCircuit<C1, C2, GC2>
+where
+    CF1<C1>: Absorb,
+{
+    /// Prepares the circuit for the decider over a multilinear commitment scheme, which opens the
+    /// witness commitment as the MLE of the witness, at the point given by
+    /// `multilinear_opening_point`.
+    pub fn try_from_multilinear<
+        GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+        FC: FCircuit<C1::ScalarField>,
+        CS1: CommitmentScheme<C1, H>,
+        CS2: CommitmentScheme<C2, H, ProverParams = PedersenParams<C2>>,
+        const MU: usize,
+        const NU: usize,
+        const H: bool,
+    >(
+        hn: HyperNova<C1, GC1, C2, GC2, FC, CS1, CS2, MU, NU, H>,
+    ) -> Result<Self, Error> {
+        Self::from_hypernova(hn, true)
+    }
+
+    fn from_hypernova<
+        GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+        FC: FCircuit<C1::ScalarField>,
+        CS1: CommitmentScheme<C1, H>,
+        CS2: CommitmentScheme<C2, H, ProverParams = PedersenParams<C2>>,
+        const MU: usize,
+        const NU: usize,
+        const H: bool,
+    >(
+        hn: HyperNova<C1, GC1, C2, GC2, FC, CS1, CS2, MU, NU, H>,
+        multilinear_openings: bool,
+    ) -> Result<Self, Error> {
+        // the blinding instance set by `randomize_for_decider`, or the (trivially satisfied)
+        // dummy one if the decider is not randomized, so that the circuit has the same shape in
+        // both cases
+        let (U_r, W_r) = hn
+            .blinding
+            .clone()
+            .unwrap_or_else(|| (LCCCS::dummy(&hn.ccs), Witness::dummy(&hn.ccs)));
+
+        // same for the CycleFold instance: fold the random instance set by `randomize_for_decider`
+        // into `cf_U_i`, or the dummy one, whose fold leaves `cf_U_i` and `cf_W_i` unchanged
+        let (cf_U_r, cf_cmT, cf_W_i) = hn.cf_blinding.clone().unwrap_or_else(|| {
+            (
+                CycleFoldCommittedInstance::dummy(&hn.cf_r1cs),
+                C2::zero(),
+                hn.cf_W_i.clone(),
+            )
+        });
+
+        // compute the U_{i+1}, W_{i+1}, by folding the last running & incoming instances together
+        // with the blinding one
         let mut transcript = PoseidonSponge::<C1::ScalarField>::new(&hn.poseidon_config);
         transcript.absorb(&hn.pp_hash);
         let (nimfs_proof, U_i1, W_i1, rho) = NIMFS::<C1, PoseidonSponge<C1::ScalarField>>::prove(
             &mut transcript,
             &hn.ccs,
-            &[hn.U_i.clone()],
+            &[hn.U_i.clone(), U_r.clone()],
             &[hn.u_i.clone()],
-            &[hn.W_i.clone()],
+            &[hn.W_i.clone(), W_r],
             &[hn.w_i.clone()],
         )?;
 
-        // compute the KZG challenges used as inputs in the circuit
-        let kzg_challenges = KZGChallengesGadget::get_challenges_native(&mut transcript, &U_i1);
+        // compute the KZG challenge used as input in the circuit
+        let kzg_challenge = KZGChallengesGadget::get_challenge_native(
+            &mut transcript,
+            &U_i1.get_commitment_labels(),
+            &U_i1,
+        )?;
 
-        // get KZG evals
+        // get the evaluations of the witness, either of its MLE at the point built from the
+        // sum-check point of the folded instance, or of its univariate interpolation
+        let opening_point = multilinear_openings.then(|| {
+            multilinear_opening_point(&U_i1.r_x, kzg_challenge, log2(W_i1.w.len()) as usize)
+        });
         let kzg_evaluations = W_i1
             .get_openings()
             .iter()
-            .zip(&kzg_challenges)
-            .map(|((v, _), &c)| EvalGadget::evaluate_native(v, c))
+            .map(|(v, _)| match &opening_point {
+                Some(point) => EvalGadget::evaluate_mle_native(v, point),
+                None => EvalGadget::evaluate_native(v, kzg_challenge),
+            })
             .collect::<Result<Vec<_>, _>>()?;
 
+        // compute the challenge used to batch the KZG openings
+        let kzg_batching_challenge =
+            KZGChallengesGadget::get_batching_challenge_native(&mut transcript, &kzg_evaluations);
+
         Ok(Self {
             _gc2: PhantomData,
             _avar: PhantomData,
@@ -163,25 +245,79 @@ where
             cf_pedersen_params: hn.cf_cs_pp,
             poseidon_config: hn.poseidon_config,
             pp_hash: hn.pp_hash,
+            context: None,
             i: hn.i,
             z_0: hn.z_0,
             z_i: hn.z_i,
+            hash_state: false,
             U_i: hn.U_i,
             W_i: hn.W_i,
             u_i: hn.u_i,
             w_i: hn.w_i,
             U_i1,
             W_i1,
-            proof: nimfs_proof,
+            proof: DeciderNIMFSProof { nimfs_proof, U_r },
             randomness: rho,
             cf_U_i: hn.cf_U_i,
-            cf_W_i: hn.cf_W_i,
-            kzg_challenges,
+            cf_W_i,
+            cf_blinding: Some((cf_U_r, cf_cmT)),
+            kzg_challenge,
             kzg_evaluations,
+            kzg_batching_challenge,
+            opening_point,
         })
     }
 }
 
+/// Returns the point of `n_vars` variables at which the decider opens the MLE of the witness of
+/// the folded LCCCS whose sum-check point is `r_x`. Its first variables are the ones of `r_x`,
+/// which is squeezed after the witness commitments are absorbed, and, when the witness has more
+/// variables than `r_x`, the remaining ones are `point_from_challenge(challenge, ..)`.
+pub fn multilinear_opening_point<F: PrimeField>(r_x: &[F], challenge: F, n_vars: usize) -> Vec<F> {
+    let n = n_vars.min(r_x.len());
+    [&r_x[..n], &point_from_challenge(challenge, n_vars - n)[..]].concat()
+}
+
+/// In-circuit version of `multilinear_opening_point`.
+pub fn multilinear_opening_point_gadget<F: PrimeField>(
+    r_x: &[FpVar<F>],
+    challenge: &FpVar<F>,
+    n_vars: usize,
+) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    let n = n_vars.min(r_x.len());
+    let mut point = r_x[..n].to_vec();
+    let mut c = challenge.clone();
+    for _ in n..n_vars {
+        let next = c.square()?;
+        point.push(c);
+        c = next;
+    }
+    Ok(point)
+}
+
+/// Proof of the folding done by the decider, which folds the running and incoming instances
+/// together with the blinding LCCCS `U_r`.
+#[derive(Debug, Clone)]
+pub struct DeciderNIMFSProof<C: CurveGroup> {
+    pub nimfs_proof: NIMFSProof<C>,
+    pub U_r: LCCCS<C>,
+}
+
+impl<C: CurveGroup> Dummy<(usize, usize, usize, usize)> for DeciderNIMFSProof<C> {
+    fn dummy((l, s, t, d): (usize, usize, usize, usize)) -> Self {
+        Self {
+            nimfs_proof: NIMFSProof::dummy((s, t, d, 2, 1)),
+            U_r: LCCCS {
+                C: C::zero(),
+                u: CF1::<C>::zero(),
+                x: vec![CF1::<C>::zero(); l],
+                r_x: vec![CF1::<C>::zero(); s],
+                v: vec![CF1::<C>::zero(); t],
+            },
+        }
+    }
+}
+
 pub struct DeciderHyperNovaGadget;
 
 impl<C: CurveGroup> DeciderEnabledNIFS<C, LCCCS<C>, CCCS<C>, Witness<C::ScalarField>, CCS<CF1<C>>>
@@ -190,7 +326,7 @@ where
     CF1<C>: Absorb,
 {
     type ProofDummyCfg = (usize, usize, usize, usize);
-    type Proof = NIMFSProof<C>;
+    type Proof = DeciderNIMFSProof<C>;
     type Randomness = CF1<C>;
     type RandomnessDummyCfg = ();
 
@@ -206,32 +342,49 @@ where
     ) -> Result<LCCCSVar<C>, SynthesisError> {
         let cs = transcript.cs();
         transcript.absorb(&pp_hash)?;
-        let nimfs_proof = NIMFSProofVar::<C>::new_witness(cs.clone(), || Ok(proof))?;
+        let nimfs_proof = NIMFSProofVar::<C>::new_witness(cs.clone(), || Ok(proof.nimfs_proof))?;
         let rho = FpVar::<CF1<C>>::new_input(cs.clone(), || Ok(randomness))?;
+        // the commitment of the blinding instance is public, as the verifier needs it to fold the
+        // commitments natively
+        let U_r_C = NonNativeAffineVar::<C>::new_packed_input(cs.clone(), || Ok(proof.U_r.C))?;
+        let U_r = LCCCSVar::<C>::new_witness(cs.clone(), || Ok(proof.U_r))?;
+        U_r.C.enforce_equal(&U_r_C)?;
         let (computed_U_i1, rho_bits) = NIMFSGadget::<C>::verify(
             cs.clone(),
             arith,
             transcript,
-            &[U],
+            &[U, U_r],
             &[u],
             nimfs_proof,
             Boolean::TRUE, // enabled
         )?;
-        Boolean::le_bits_to_fp_var(&rho_bits)?.enforce_equal(&rho)?;
+        le_bits_to_fp_var_checked(&rho_bits)?.enforce_equal(&rho)?;
         Ok(computed_U_i1)
     }
 
+    /// `U_commitments` contains the commitment of the running instance followed by the one of the
+    /// blinding instance.
     fn fold_group_elements_native(
         U_commitments: &[C],
         u_commitments: &[C],
         _: Option<Self::Proof>,
         r: Self::Randomness,
     ) -> Result<Vec<C>, Error> {
-        let U_C = U_commitments[0];
+        check_len(U_commitments.len(), 2)?;
+        check_len(u_commitments.len(), 1)?;
+        let (U_C, U_r_C) = (U_commitments[0], U_commitments[1]);
         let u_C = u_commitments[0];
-        let C = U_C + u_C.mul(r);
+        let C = U_C + U_r_C.mul(r) + u_C.mul(r * r);
         Ok(vec![C])
     }
+
+    fn multilinear_opening_point_gadget(
+        U: &LCCCSVar<C>,
+        challenge: &FpVar<CF1<C>>,
+        n_vars: usize,
+    ) -> Result<Vec<FpVar<CF1<C>>>, SynthesisError> {
+        multilinear_opening_point_gadget(&U.r_x, challenge, n_vars)
+    }
 }
 
 #[cfg(test)]
@@ -312,7 +465,7 @@ pub mod tests {
 
         // generate a Nova instance and do a step of it
         let mut hypernova = HN::init(&hn_params, F_circuit, z_0.clone()).unwrap();
-        hypernova.prove_step(&mut rng, vec![], None).unwrap();
+        hypernova.prove_step(&mut rng, (), None).unwrap();
 
         let ivc_proof = hypernova.ivc_proof();
         HN::verify(hn_params.1, ivc_proof).unwrap();
@@ -328,4 +481,126 @@ pub mod tests {
         assert!(cs.is_satisfied().unwrap());
         dbg!(cs.num_constraints());
     }
+
+    // checks that the multilinear opening point is bound to the sum-check point of the folded
+    // instance, ie. that the circuit is not satisfied by any other point
+    #[test]
+    fn test_decider_circuit_multilinear_opening_point() {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let z_0 = vec![Fr::from(3_u32)];
+
+        type HN = HyperNova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            1,
+            1,
+            false,
+        >;
+        let prep_param = PreprocessorParam::<
+            Projective,
+            Projective2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >::new(poseidon_config, F_circuit);
+        let hn_params = HN::preprocess(&mut rng, &prep_param).unwrap();
+
+        let mut hypernova = HN::init(&hn_params, F_circuit, z_0.clone()).unwrap();
+        hypernova.prove_step(&mut rng, (), None).unwrap();
+
+        let decider_circuit = |hn: HN| {
+            DeciderEthCircuit::<Projective, Projective2, GVar2>::try_from_multilinear(hn).unwrap()
+        };
+
+        let valid_circuit = decider_circuit(hypernova.clone());
+        let point = valid_circuit.opening_point.clone().unwrap();
+        assert_eq!(point.len(), log2(valid_circuit.W_i1.w.len()) as usize);
+        let n = point.len().min(valid_circuit.U_i1.r_x.len());
+        assert_eq!(point[..n], valid_circuit.U_i1.r_x[..n]);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        valid_circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // open at another point, with the evaluation of the witness at it
+        let mut invalid_circuit = decider_circuit(hypernova);
+        invalid_circuit.opening_point.as_mut().unwrap()[0] += Fr::from(1_u32);
+        invalid_circuit.kzg_evaluations = vec![EvalGadget::evaluate_mle_native(
+            &invalid_circuit.W_i1.w,
+            invalid_circuit.opening_point.as_ref().unwrap(),
+        )
+        .unwrap()];
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        invalid_circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    // the CycleFold checks of the circuit are skipped with the 'light-test' feature
+    #[cfg(not(feature = "light-test"))]
+    #[test]
+    fn test_decider_circuit_randomized_cyclefold() {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let z_0 = vec![Fr::from(3_u32)];
+
+        type HN = HyperNova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            1,
+            1,
+            false,
+        >;
+        let prep_param = PreprocessorParam::<
+            Projective,
+            Projective2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >::new(poseidon_config, F_circuit);
+        let hn_params = HN::preprocess(&mut rng, &prep_param).unwrap();
+
+        let mut hypernova = HN::init(&hn_params, F_circuit, z_0).unwrap();
+        hypernova.prove_step(&mut rng, (), None).unwrap();
+        hypernova.randomize_for_decider(&mut rng).unwrap();
+
+        let decider_circuit =
+            |hn: HN| DeciderEthCircuit::<Projective, Projective2, GVar2>::try_from(hn).unwrap();
+
+        let valid_circuit = decider_circuit(hypernova.clone());
+        assert_ne!(valid_circuit.cf_W_i, hypernova.cf_W_i);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        valid_circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // the witness of the CycleFold instance of the IVC does not satisfy the folded instance
+        let mut invalid_circuit = decider_circuit(hypernova.clone());
+        invalid_circuit.cf_W_i = hypernova.cf_W_i.clone();
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        invalid_circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+
+        // neither does the folded witness with another commitment to the cross terms
+        let mut invalid_circuit = decider_circuit(hypernova);
+        invalid_circuit.cf_blinding.as_mut().unwrap().1 = Projective2::rand(&mut rng);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        invalid_circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
 }
@@ -5,20 +5,20 @@ use ark_poly::DenseMultilinearExtension;
 use ark_poly::MultilinearExtension;
 use ark_serialize::CanonicalDeserialize;
 use ark_serialize::CanonicalSerialize;
-use ark_std::rand::Rng;
+use ark_std::rand::{Rng, RngCore};
 use ark_std::Zero;
 
 use super::circuits::LCCCSVar;
 use super::Witness;
 use crate::arith::ccs::CCS;
-use crate::arith::Arith;
+use crate::arith::{Arith, ArithSampler};
 use crate::commitment::CommitmentScheme;
 use crate::folding::circuits::CF1;
 use crate::folding::traits::Inputize;
 use crate::folding::traits::{CommittedInstanceOps, Dummy};
 use crate::transcript::AbsorbNonNative;
 use crate::utils::mle::dense_vec_to_dense_mle;
-use crate::utils::vec::mat_vec_mul;
+use crate::utils::vec::{check_equal_rows, mat_vec_mul};
 use crate::Error;
 
 /// Linearized Committed CCS instance
@@ -81,6 +81,54 @@ impl<F: PrimeField> CCS<F> {
             Witness::<C::ScalarField> { w, r_w },
         ))
     }
+
+    /// Samples a random LCCCS together with its witness. Unlike a CCCS, which for a generic CCS
+    /// can only be obtained by actually solving the constraint system, an LCCCS is satisfied by
+    /// any `z = (u, x, w)` once its `v_j` are computed from `z`, so a random `z` gives a uniformly
+    /// distributed satisfying pair. This is used to blind the instance folded into the decider.
+    pub fn sample_satisfying_instance<R: Rng, C, CS: CommitmentScheme<C, H>, const H: bool>(
+        &self,
+        rng: &mut R,
+        cs_params: &CS::ProverParams,
+    ) -> Result<(LCCCS<C>, Witness<C::ScalarField>), Error>
+    where
+        C: CurveGroup<ScalarField = F>,
+    {
+        let z: Vec<F> = (0..self.n).map(|_| F::rand(rng)).collect();
+        self.to_lcccs::<_, C, CS, H>(rng, cs_params, &z)
+    }
+}
+
+impl<C: CurveGroup> LCCCS<C> {
+    /// Checks that the vectors of the instance have the lengths expected by the given CCS, which
+    /// is needed for instances coming from untrusted sources such as deserialized bytes.
+    pub fn check_dimensions(&self, ccs: &CCS<CF1<C>>) -> Result<(), Error> {
+        if self.x.len() != ccs.l {
+            return Err(Error::NotSameLength(
+                "x.len()".to_string(),
+                self.x.len(),
+                "ccs.l".to_string(),
+                ccs.l,
+            ));
+        }
+        if self.r_x.len() != ccs.s {
+            return Err(Error::NotSameLength(
+                "r_x.len()".to_string(),
+                self.r_x.len(),
+                "ccs.s".to_string(),
+                ccs.s,
+            ));
+        }
+        if self.v.len() != ccs.t {
+            return Err(Error::NotSameLength(
+                "v.len()".to_string(),
+                self.v.len(),
+                "ccs.t".to_string(),
+                ccs.t,
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl<C: CurveGroup> Dummy<&CCS<CF1<C>>> for LCCCS<C> {
@@ -117,7 +165,20 @@ impl<C: CurveGroup> Arith<Witness<CF1<C>>, LCCCS<C>> for CCS<CF1<C>> {
         u: &LCCCS<C>,
         e: Self::Evaluation,
     ) -> Result<(), Error> {
-        (u.v == e).then_some(()).ok_or(Error::NotSatisfied)
+        check_equal_rows(&e, &u.v)
+    }
+}
+
+/// Samples random LCCCS pairs through [`CCS::sample_satisfying_instance`], which is the
+/// linearized analogue of sampling a relaxed R1CS pair in Nova.
+impl<C: CurveGroup> ArithSampler<C, Witness<CF1<C>>, LCCCS<C>> for CCS<CF1<C>> {
+    fn sample_witness_instance<CS: CommitmentScheme<C, true>>(
+        &self,
+        params: &CS::ProverParams,
+        mut rng: impl RngCore,
+    ) -> Result<(Witness<CF1<C>>, LCCCS<C>), Error> {
+        let (U, W) = self.sample_satisfying_instance::<_, C, CS, true>(&mut rng, params)?;
+        Ok((W, U))
     }
 }
 
@@ -150,6 +211,10 @@ impl<C: CurveGroup> CommittedInstanceOps<C> for LCCCS<C> {
         vec![self.C]
     }
 
+    fn get_commitment_labels(&self) -> Vec<&'static [u8]> {
+        vec![b"C"]
+    }
+
     fn is_incoming(&self) -> bool {
         false
     }
@@ -287,4 +352,30 @@ pub mod tests {
         }
         assert!(!satisfied);
     }
+
+    #[test]
+    fn test_sample_satisfying_instance() {
+        let mut rng = test_rng();
+        let ccs = get_test_ccs();
+        let (pedersen_params, _) =
+            Pedersen::<Projective>::setup(&mut rng, ccs.n - ccs.l - 1).unwrap();
+
+        let (U_1, W_1) = ccs
+            .sample_satisfying_instance::<_, Projective, Pedersen<Projective>, false>(
+                &mut rng,
+                &pedersen_params,
+            )
+            .unwrap();
+        let (U_2, W_2) = ccs
+            .sample_satisfying_instance::<_, Projective, Pedersen<Projective>, false>(
+                &mut rng,
+                &pedersen_params,
+            )
+            .unwrap();
+        ccs.check_relation(&W_1, &U_1).unwrap();
+        ccs.check_relation(&W_2, &U_2).unwrap();
+        U_1.check_dimensions(&ccs).unwrap();
+        W_1.check_dimensions(&ccs).unwrap();
+        assert_ne!(U_1.C, U_2.C);
+    }
 }
@@ -23,30 +23,42 @@ use decider_eth_circuit::WitnessVar;
 use lcccs::LCCCS;
 use nimfs::NIMFS;
 
-use crate::commitment::CommitmentScheme;
+use crate::commitment::{
+    pedersen::{Params as PedersenParams, Pedersen},
+    versioned::{deserialize_params, params_serialized_size, serialize_params},
+    CommitmentScheme,
+};
 use crate::constants::NOVA_N_BITS_RO;
 use crate::folding::{
     circuits::{
         cyclefold::{
-            fold_cyclefold_circuit, CycleFoldCircuit, CycleFoldCommittedInstance, CycleFoldConfig,
-            CycleFoldWitness,
+            fold_cyclefold_circuit, CycleFoldChallengeGadget, CycleFoldCircuit,
+            CycleFoldCommittedInstance, CycleFoldConfig, CycleFoldWitness,
         },
         CF2,
     },
-    nova::{get_r1cs_from_cs, PreprocessorParam},
+    nova::{
+        builder::NovaBuilder,
+        get_r1cs_from_cs,
+        nifs::{nova::NIFS, NIFSTrait},
+        PreprocessorParam,
+    },
     traits::{CommittedInstanceOps, Dummy, WitnessOps},
 };
-use crate::frontend::FCircuit;
+use crate::frontend::{
+    check_state, check_state_len, check_step_constants, check_step_constants_row, FCircuit,
+};
 use crate::transcript::poseidon::poseidon_canonical_config;
-use crate::utils::{get_cm_coordinates, pp_hash};
+use crate::utils::vec::check_len;
+use crate::utils::{deterministic_rng, pp_hash};
 use crate::Error;
 use crate::{
     arith::{
         ccs::CCS,
         r1cs::{extract_w_x, R1CS},
-        Arith,
+        Arith, ArithSampler,
     },
-    FoldingScheme, MultiFolding,
+    FoldingScheme, FoldingState, MultiFolding,
 };
 
 /// Configuration for HyperNova's CycleFold circuit
@@ -81,6 +93,20 @@ impl<F: PrimeField> Witness<F> {
         // always.
         Self { w, r_w: F::zero() }
     }
+
+    /// Checks that the witness has the length expected by the given CCS.
+    pub fn check_dimensions(&self, ccs: &CCS<F>) -> Result<(), Error> {
+        let expected = ccs.n - ccs.l - 1;
+        if self.w.len() != expected {
+            return Err(Error::NotSameLength(
+                "w.len()".to_string(),
+                self.w.len(),
+                "ccs.n - ccs.l - 1".to_string(),
+                expected,
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl<F: PrimeField> Dummy<&CCS<F>> for Witness<F> {
@@ -117,6 +143,20 @@ where
     pub ccs: Option<CCS<C1::ScalarField>>,
 }
 
+impl<C1, C2, CS1, CS2, const H: bool> ProverParams<C1, C2, CS1, CS2, H>
+where
+    C1: CurveGroup,
+    C2: CurveGroup,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+{
+    /// returns the proving parameters of the commitment schemes over C1 and C2, which can be
+    /// reused to preprocess other circuits, see `PreprocessorParam::with_cs_params`
+    pub fn cs_params(&self) -> (&CS1::ProverParams, &CS2::ProverParams) {
+        (&self.cs_pp, &self.cf_cs_pp)
+    }
+}
+
 impl<
         C1: CurveGroup,
         C2: CurveGroup,
@@ -127,15 +167,14 @@ impl<
 {
     fn serialize_with_mode<W: std::io::prelude::Write>(
         &self,
-        mut writer: W,
+        writer: W,
         compress: Compress,
     ) -> Result<(), SerializationError> {
-        self.cs_pp.serialize_with_mode(&mut writer, compress)?;
-        self.cf_cs_pp.serialize_with_mode(&mut writer, compress)
+        serialize_params::<C1, C2, _>(writer, compress, &self.cs_pp, &self.cf_cs_pp)
     }
 
     fn serialized_size(&self, compress: Compress) -> usize {
-        self.cs_pp.serialized_size(compress) + self.cf_cs_pp.serialized_size(compress)
+        params_serialized_size::<C1, C2>(compress, &self.cs_pp, &self.cf_cs_pp)
     }
 }
 
@@ -158,6 +197,10 @@ pub struct VerifierParams<
     pub cs_vp: CS1::VerifierParams,
     /// Verification parameters of the underlying commitment scheme over C2
     pub cf_cs_vp: CS2::VerifierParams,
+    /// Length of the state of the folded FCircuit
+    pub state_len: usize,
+    /// Table of per-step constants of the folded FCircuit
+    pub step_constants: Vec<Vec<C1::ScalarField>>,
 }
 
 impl<C1, C2, CS1, CS2, const H: bool> CanonicalSerialize for VerifierParams<C1, C2, CS1, CS2, H>
@@ -169,15 +212,14 @@ where
 {
     fn serialize_with_mode<W: std::io::prelude::Write>(
         &self,
-        mut writer: W,
+        writer: W,
         compress: ark_serialize::Compress,
     ) -> Result<(), ark_serialize::SerializationError> {
-        self.cs_vp.serialize_with_mode(&mut writer, compress)?;
-        self.cf_cs_vp.serialize_with_mode(&mut writer, compress)
+        serialize_params::<C1, C2, _>(writer, compress, &self.cs_vp, &self.cf_cs_vp)
     }
 
     fn serialized_size(&self, compress: ark_serialize::Compress) -> usize {
-        self.cs_vp.serialized_size(compress) + self.cf_cs_vp.serialized_size(compress)
+        params_serialized_size::<C1, C2>(compress, &self.cs_vp, &self.cf_cs_vp)
     }
 }
 
@@ -188,6 +230,12 @@ where
     CS1: CommitmentScheme<C1, H>,
     CS2: CommitmentScheme<C2, H>,
 {
+    /// returns the verification parameters of the commitment schemes over C1 and C2, which can
+    /// be reused to preprocess other circuits, see `PreprocessorParam::with_cs_params`
+    pub fn cs_params(&self) -> (&CS1::VerifierParams, &CS2::VerifierParams) {
+        (&self.cs_vp, &self.cf_cs_vp)
+    }
+
     /// returns the hash of the public parameters of HyperNova
     pub fn pp_hash(&self) -> Result<C1::ScalarField, Error> {
         pp_hash::<C1, C2, CS1, CS2, H>(
@@ -196,6 +244,11 @@ where
             &self.cs_vp,
             &self.cf_cs_vp,
             &self.poseidon_config,
+            None,
+            // the CycleFold challenge bit length does not depend on MU and NU
+            HyperNovaCycleFoldConfig::<C1, 1, 1>::CHALLENGE_BIT_LENGTH,
+            self.state_len,
+            &self.step_constants,
         )
     }
 }
@@ -224,6 +277,12 @@ where
 /// For multi-instance folding, one needs to specify the const generics below:
 /// * `MU` - the number of LCCCS instances to be folded
 /// * `NU` - the number of CCCS instances to be folded
+///
+/// The CCS that is folded is the one of the AugmentedFCircuit, which is synthesized as an R1CS by
+/// arkworks and then converted with `CCS::from`, so the constraints of the FCircuit have degree 2
+/// (a gate of higher degree is split into multiplications by the R1CS synthesis). The FCircuit
+/// can instead declare its high-degree gates with `FCircuit::custom_gates`, which are appended to
+/// that CCS without being split, so that the folded CCS has their degree.
 #[derive(Clone, Debug)]
 pub struct HyperNova<
     C1,
@@ -276,6 +335,14 @@ pub struct HyperNova<
     /// CycleFold running instance
     pub cf_W_i: CycleFoldWitness<C2>,
     pub cf_U_i: CycleFoldCommittedInstance<C2>,
+
+    /// Random LCCCS and its witness set by `randomize_for_decider`, folded together with the
+    /// running and incoming instances by the decider to hide the final witness
+    pub blinding: Option<(LCCCS<C1>, Witness<C1::ScalarField>)>,
+    /// Random CycleFold instance and the commitment to the cross terms of its fold into `cf_U_i`
+    /// set by `randomize_for_decider`, together with the folded CycleFold witness, which the
+    /// decider checks instead of `cf_W_i`
+    pub cf_blinding: Option<(CycleFoldCommittedInstance<C2>, C2, CycleFoldWitness<C2>)>,
 }
 
 impl<C1, GC1, C2, GC2, FC, CS1, CS2, const MU: usize, const NU: usize, const H: bool>
@@ -305,7 +372,7 @@ where
         &self,
         mut rng: impl RngCore,
         state: Vec<C1::ScalarField>,
-        external_inputs: Vec<C1::ScalarField>,
+        external_inputs: FC::ExternalInputs,
     ) -> Result<Self::RunningInstance, Error> {
         let r1cs_z = self.new_instance_generic(state, external_inputs)?;
         // compute committed instances, w_{i+1}, u_{i+1}, which will be used as w_i, u_i, so we
@@ -327,7 +394,7 @@ where
         &self,
         mut rng: impl RngCore,
         state: Vec<C1::ScalarField>,
-        external_inputs: Vec<C1::ScalarField>,
+        external_inputs: FC::ExternalInputs,
     ) -> Result<Self::IncomingInstance, Error> {
         let r1cs_z = self.new_instance_generic(state, external_inputs)?;
         // compute committed instances, w_{i+1}, u_{i+1}, which will be used as w_i, u_i, so we
@@ -359,12 +426,18 @@ where
     <C2 as Group>::ScalarField: Absorb,
     C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
 {
+    /// Returns a builder of the `PreprocessorParam` of the FCircuit `F`, which can also run
+    /// `preprocess` with them. See `NovaBuilder`.
+    pub fn builder(F: FC) -> NovaBuilder<Self, C1, C2, FC, CS1, CS2, H> {
+        NovaBuilder::new(F)
+    }
+
     /// internal helper for new_running_instance & new_incoming_instance methods, returns the R1CS
     /// z=[u,x,w] vector to be used to create the LCCCS & CCCS fresh instances.
     fn new_instance_generic(
         &self,
         state: Vec<C1::ScalarField>,
-        external_inputs: Vec<C1::ScalarField>,
+        external_inputs: FC::ExternalInputs,
     ) -> Result<Vec<C1::ScalarField>, Error> {
         // prepare the initial dummy instances
         let U_i = LCCCS::<C1>::dummy(&self.ccs);
@@ -386,9 +459,11 @@ where
         ];
         let us = vec![u_i.clone(); NU - 1];
 
+        check_state("state", &state, self.F.state_len())?;
         let z_i1 = self
             .F
             .step_native(0, state.clone(), external_inputs.clone())?;
+        check_state("z_{i+1}", &z_i1, self.F.state_len())?;
 
         // compute u_{i+1}.x
         let U_i1 = LCCCS::dummy(&self.ccs);
@@ -438,14 +513,158 @@ where
         #[cfg(test)]
         assert_eq!(r1cs_x_i1[0], augmented_f_circuit.x.unwrap());
 
-        let r1cs_z = [
+        let mut r1cs_z = [
             vec![C1::ScalarField::one()],
             r1cs_x_i1.clone(),
             r1cs_w_i1.clone(),
         ]
         .concat();
+        // extend the assignment to the padded dimensions of the CCS
+        self.ccs.pad_z(&mut r1cs_z)?;
         Ok(r1cs_z)
     }
+
+    /// Same as `prove_step`, but drawing the randomness of the step (of the hiding commitments
+    /// and of the CycleFold instances) from `utils::deterministic_rng(seed, i)`. Proving the same
+    /// steps from the same seed results in byte-identical IVC proofs. The randomness of the
+    /// `other_instances` is the one given to `new_running_instance` & `new_incoming_instance`.
+    pub fn prove_step_deterministic(
+        &mut self,
+        seed: [u8; 32],
+        external_inputs: FC::ExternalInputs,
+        other_instances: Option<
+            <Self as FoldingScheme<C1, C2, FC>>::MultiCommittedInstanceWithWitness,
+        >,
+    ) -> Result<(), Error> {
+        self.prove_step(
+            deterministic_rng(seed, self.i),
+            external_inputs,
+            other_instances,
+        )
+    }
+
+    /// Runs an IVC step folding, together with the running and incoming instances, the given CCCS
+    /// instances produced externally, e.g. by worker machines calling `new_incoming_instance` over
+    /// the same HyperNova params and initial state. There must be `NU - 1` extra instances and no
+    /// other running instances, i.e. `MU = 1`.
+    ///
+    /// Each extra instance is checked to have the shape of the CCS, to be bound to the same
+    /// `pp_hash`, to open its commitment and to satisfy the CCS relation, before folding it.
+    pub fn prove_step_with_extra_instances(
+        &mut self,
+        rng: impl RngCore,
+        external_inputs: FC::ExternalInputs,
+        extra: Vec<(CCCS<C1>, Witness<C1::ScalarField>)>,
+    ) -> Result<(), Error> {
+        if MU != 1 {
+            return Err(Error::NotSameLength(
+                "running instances (hypernova.mu)".to_string(),
+                MU,
+                "expected running instances".to_string(),
+                1,
+            ));
+        }
+        if extra.len() + 1 != NU {
+            return Err(Error::NotSameLength(
+                "extra.len()".to_string(),
+                extra.len(),
+                "hypernova.nu - 1".to_string(),
+                NU - 1,
+            ));
+        }
+
+        // the second public input of a fresh instance is the hash of the dummy CycleFold
+        // instance, which depends only on `pp_hash`
+        let sponge = PoseidonSponge::<C1::ScalarField>::new(&self.poseidon_config);
+        let (_, cf_U_dummy): (CycleFoldWitness<C2>, CycleFoldCommittedInstance<C2>) =
+            self.cf_r1cs.dummy_witness_instance();
+        let expected_cf_x = cf_U_dummy.hash_cyclefold(&sponge, self.pp_hash);
+
+        let w_len = self.ccs.n - self.ccs.l - 1;
+        for (j, (u, w)) in extra.iter().enumerate() {
+            if u.x.len() != self.ccs.l {
+                return Err(Error::InvalidExtraInstance(
+                    j,
+                    format!("x.len()={} but ccs.l={}", u.x.len(), self.ccs.l),
+                ));
+            }
+            if w.w.len() != w_len {
+                return Err(Error::InvalidExtraInstance(
+                    j,
+                    format!("w.len()={} but expected {}", w.w.len(), w_len),
+                ));
+            }
+            if u.x[1] != expected_cf_x {
+                return Err(Error::InvalidExtraInstance(
+                    j,
+                    "not generated for the same pp_hash".to_string(),
+                ));
+            }
+            if CS1::commit(&self.cs_pp, &w.w, &w.r_w)? != u.C {
+                return Err(Error::InvalidExtraInstance(
+                    j,
+                    "the commitment does not open to the witness".to_string(),
+                ));
+            }
+            self.ccs.check_relation(w, u).map_err(|e| {
+                Error::InvalidExtraInstance(j, format!("the CCS relation is not satisfied: {}", e))
+            })?;
+        }
+
+        self.prove_step(rng, external_inputs, Some((vec![], extra)))
+    }
+
+    /// Samples a random satisfying LCCCS that the decider folds together with the running and
+    /// incoming instances, so that the witness opened by the decider proof is uniformly random
+    /// and does not leak the witnesses of the IVC. Two deciders over the same statement thus
+    /// commit to distinct witnesses. It has to be called after the last `prove_step`, since each
+    /// step discards the previous blinding.
+    ///
+    /// The CycleFold running instance `cf_U_i` is randomized in the same way, by folding it with
+    /// a random satisfying CycleFold instance, whose fold the decider circuit verifies.
+    pub fn randomize_for_decider(&mut self, mut rng: impl RngCore) -> Result<(), Error>
+    where
+        CS2: CommitmentScheme<C2, H, ProverParams = PedersenParams<C2>>,
+    {
+        self.blinding = Some(
+            self.ccs
+                .sample_satisfying_instance::<_, C1, CS1, H>(&mut rng, &self.cs_pp)?,
+        );
+
+        // the random CycleFold instance is committed with the blinding factors, so that its
+        // commitments hide its witness
+        let (cf_W_r, cf_U_r) = self
+            .cf_r1cs
+            .sample_witness_instance::<Pedersen<C2, true>>(&self.cf_cs_pp, &mut rng)?;
+        let (cf_T, cf_cmT) =
+            NIFS::<C2, CS2, PoseidonSponge<C2::ScalarField>, H>::compute_cyclefold_cmT(
+                &self.cf_cs_pp,
+                &self.cf_r1cs,
+                &self.cf_W_i,
+                &self.cf_U_i,
+                &cf_W_r,
+                &cf_U_r,
+            )?;
+        // same transcript as in the decider circuit
+        let cf_r_bits = CycleFoldChallengeGadget::<C2, GC2>::get_challenge_native(
+            &mut PoseidonSponge::<C1::ScalarField>::new(&self.poseidon_config),
+            NOVA_N_BITS_RO,
+            self.pp_hash,
+            self.cf_U_i.clone(),
+            cf_U_r.clone(),
+            cf_cmT,
+        );
+        let cf_r = C2::ScalarField::from_bigint(BigInteger::from_bits_le(&cf_r_bits))
+            .ok_or(Error::OutOfBounds)?;
+        let cf_W_i = NIFS::<C2, CS2, PoseidonSponge<C2::ScalarField>, H>::fold_witness(
+            cf_r,
+            &self.cf_W_i,
+            &cf_W_r,
+            &cf_T,
+        )?;
+        self.cf_blinding = Some((cf_U_r, cf_cmT, cf_W_i));
+        Ok(())
+    }
 }
 
 impl<C1, GC1, C2, GC2, FC, CS1, CS2, const MU: usize, const NU: usize, const H: bool>
@@ -473,10 +692,12 @@ where
     type MultiCommittedInstanceWithWitness =
         (Vec<Self::RunningInstance>, Vec<Self::IncomingInstance>);
     type CFInstance = (CycleFoldCommittedInstance<C2>, CycleFoldWitness<C2>);
+    type RunningCommittedInstance = LCCCS<C1>;
+    type IncomingCommittedInstance = CCCS<C1>;
     type IVCProof = IVCProof<C1, C2>;
 
     fn pp_deserialize_with_mode<R: std::io::prelude::Read>(
-        mut reader: R,
+        reader: R,
         compress: ark_serialize::Compress,
         validate: ark_serialize::Validate,
         fc_params: FC::Params,
@@ -495,8 +716,7 @@ where
         )?;
         let ccs = augmented_F_circuit.ccs;
 
-        let cs_pp = CS1::ProverParams::deserialize_with_mode(&mut reader, compress, validate)?;
-        let cf_cs_pp = CS2::ProverParams::deserialize_with_mode(&mut reader, compress, validate)?;
+        let (cs_pp, cf_cs_pp) = deserialize_params::<C1, C2, _, _, _>(reader, compress, validate)?;
 
         Ok(ProverParams {
             poseidon_config,
@@ -507,7 +727,7 @@ where
     }
 
     fn vp_deserialize_with_mode<R: std::io::prelude::Read>(
-        mut reader: R,
+        reader: R,
         compress: ark_serialize::Compress,
         validate: ark_serialize::Validate,
         fc_params: FC::Params,
@@ -530,8 +750,7 @@ where
         let cf_circuit = HyperNovaCycleFoldCircuit::<C1, GC1, MU, NU>::empty();
         let cf_r1cs = get_r1cs_from_cs::<C2::ScalarField>(cf_circuit)?;
 
-        let cs_vp = CS1::VerifierParams::deserialize_with_mode(&mut reader, compress, validate)?;
-        let cf_cs_vp = CS2::VerifierParams::deserialize_with_mode(&mut reader, compress, validate)?;
+        let (cs_vp, cf_cs_vp) = deserialize_params::<C1, C2, _, _, _>(reader, compress, validate)?;
 
         Ok(VerifierParams {
             poseidon_config,
@@ -539,6 +758,8 @@ where
             cf_r1cs,
             cs_vp,
             cf_cs_vp,
+            state_len: f_circuit.state_len(),
+            step_constants: f_circuit.step_constants(),
         })
     }
 
@@ -549,25 +770,53 @@ where
         if MU < 1 || NU < 1 {
             return Err(Error::CantBeZero("mu,nu".to_string()));
         }
+        if prep_param.public_pp_hash {
+            return Err(Error::NotSupportedYet(
+                "HyperNova with pp_hash as a public input".to_string(),
+            ));
+        }
+        trace_span!("hypernova_preprocess");
+        HyperNovaCycleFoldConfig::<C1, MU, NU>::check_challenge_bit_length()?;
+        let state_len = check_state_len(&prep_param.F)?;
+        let step_constants = check_step_constants(&prep_param.F)?;
+
+        // the CCS is padded to power-of-two dimensions and checked to be well formed by
+        // `AugmentedFCircuit::empty`, so that the commitment parameters below already account
+        // for the padded witness
+        let (ccs, cf_r1cs) = {
+            trace_span!("augmented_circuit_synthesis");
+            let augmented_f_circuit = AugmentedFCircuit::<C1, C2, GC2, FC, MU, NU>::empty(
+                &prep_param.poseidon_config,
+                prep_param.F.clone(),
+                None,
+            )?;
+            let cf_circuit = HyperNovaCycleFoldCircuit::<C1, GC1, MU, NU>::empty();
+            (
+                augmented_f_circuit.ccs,
+                get_r1cs_from_cs::<C2::ScalarField>(cf_circuit)?,
+            )
+        };
 
-        let augmented_f_circuit = AugmentedFCircuit::<C1, C2, GC2, FC, MU, NU>::empty(
-            &prep_param.poseidon_config,
-            prep_param.F.clone(),
-            None,
-        )?;
-        let ccs = augmented_f_circuit.ccs.clone();
-
-        let cf_circuit = HyperNovaCycleFoldCircuit::<C1, GC1, MU, NU>::empty();
-        let cf_r1cs = get_r1cs_from_cs::<C2::ScalarField>(cf_circuit)?;
-
-        // if cs params exist, use them, if not, generate new ones
+        // if cs params exist, trim and use them, if not, generate new ones
+        trace_span!(
+            "commitment_setup",
+            n_constraints = ccs.m,
+            cf_n_constraints = cf_r1cs.A.n_rows
+        );
+        let (cs_len, cf_cs_len) = (ccs.n - ccs.l - 1, cf_r1cs.A.n_cols - cf_r1cs.l - 1);
         let (cs_pp, cs_vp) = match (&prep_param.cs_pp, &prep_param.cs_vp) {
-            (Some(cs_pp), Some(cs_vp)) => (cs_pp.clone(), cs_vp.clone()),
-            _ => CS1::setup(&mut rng, ccs.n - ccs.l - 1)?,
+            (Some(cs_pp), Some(cs_vp)) => CS1::trim(cs_pp, cs_vp, cs_len)?,
+            _ => match &prep_param.domain_tag {
+                Some(tag) => CS1::setup_deterministic(tag, cs_len)?,
+                None => CS1::setup(&mut rng, cs_len)?,
+            },
         };
         let (cf_cs_pp, cf_cs_vp) = match (&prep_param.cf_cs_pp, &prep_param.cf_cs_vp) {
-            (Some(cf_cs_pp), Some(cf_cs_vp)) => (cf_cs_pp.clone(), cf_cs_vp.clone()),
-            _ => CS2::setup(&mut rng, cf_r1cs.A.n_cols - cf_r1cs.l - 1)?,
+            (Some(cf_cs_pp), Some(cf_cs_vp)) => CS2::trim(cf_cs_pp, cf_cs_vp, cf_cs_len)?,
+            _ => match &prep_param.domain_tag {
+                Some(tag) => CS2::setup_deterministic(tag, cf_cs_len)?,
+                None => CS2::setup(&mut rng, cf_cs_len)?,
+            },
         };
 
         let pp = ProverParams::<C1, C2, CS1, CS2, H> {
@@ -582,6 +831,8 @@ where
             cf_r1cs,
             cs_vp: cs_vp.clone(),
             cf_cs_vp: cf_cs_vp.clone(),
+            state_len,
+            step_constants,
         };
         Ok((pp, vp))
     }
@@ -596,6 +847,19 @@ where
         if MU < 1 || NU < 1 {
             return Err(Error::CantBeZero("mu,nu".to_string()));
         }
+        if F.state_len() != vp.state_len {
+            return Err(Error::ParamsMismatch(format!(
+                "FCircuit state length {} differs from the preprocessed one {}",
+                F.state_len(),
+                vp.state_len
+            )));
+        }
+        if F.step_constants() != vp.step_constants {
+            return Err(Error::ParamsMismatch(
+                "FCircuit step constants differ from the preprocessed ones".to_string(),
+            ));
+        }
+        check_state("z_0", &z_0, vp.state_len)?;
 
         // `sponge` is for digest computation.
         let sponge = PoseidonSponge::<C1::ScalarField>::new(&pp.poseidon_config);
@@ -650,6 +914,8 @@ where
             // cyclefold running instance
             cf_W_i: cf_W_dummy,
             cf_U_i: cf_U_dummy,
+            blinding: None,
+            cf_blinding: None,
         })
     }
 
@@ -657,9 +923,10 @@ where
     fn prove_step(
         &mut self,
         mut rng: impl RngCore,
-        external_inputs: Vec<C1::ScalarField>,
+        external_inputs: FC::ExternalInputs,
         other_instances: Option<Self::MultiCommittedInstanceWithWitness>,
     ) -> Result<(), Error> {
+        trace_span!("hypernova_prove_step", n_constraints = self.ccs.m);
         // ensure that commitments are blinding if user has specified so.
 
         if H {
@@ -718,22 +985,7 @@ where
 
         let augmented_f_circuit: AugmentedFCircuit<C1, C2, GC2, FC, MU, NU>;
 
-        if self.z_i.len() != self.F.state_len() {
-            return Err(Error::NotSameLength(
-                "z_i.len()".to_string(),
-                self.z_i.len(),
-                "F.state_len()".to_string(),
-                self.F.state_len(),
-            ));
-        }
-        if external_inputs.len() != self.F.external_inputs_len() {
-            return Err(Error::NotSameLength(
-                "F.external_inputs_len()".to_string(),
-                self.F.external_inputs_len(),
-                "external_inputs.len()".to_string(),
-                external_inputs.len(),
-            ));
-        }
+        check_state("z_i", &self.z_i, self.F.state_len())?;
 
         if self.i > C1::ScalarField::from_le_bytes_mod_order(&usize::MAX.to_le_bytes()) {
             return Err(Error::MaxStep);
@@ -755,9 +1007,13 @@ where
             i_usize = usize::from_le_bytes(i_bytes);
         }
 
-        let z_i1 = self
-            .F
-            .step_native(i_usize, self.z_i.clone(), external_inputs.clone())?;
+        check_step_constants_row(&self.F.step_constants(), i_usize)?;
+        let z_i1 = {
+            trace_span!("step_native", state_len = self.z_i.len());
+            self.F
+                .step_native(i_usize, self.z_i.clone(), external_inputs.clone())?
+        };
+        check_state("z_{i+1}", &z_i1, self.F.state_len())?;
 
         // u_{i+1}.x[1] = H(cf_U_{i+1})
         let cf_u_i1_x: C1::ScalarField;
@@ -819,14 +1075,17 @@ where
             );
 
             let (rho, nimfs_proof);
-            (nimfs_proof, U_i1, W_i1, rho) = NIMFS::<C1, PoseidonSponge<C1::ScalarField>>::prove(
-                &mut transcript_p,
-                &self.ccs,
-                &all_Us,
-                &all_us,
-                &all_Ws,
-                &all_ws,
-            )?;
+            (nimfs_proof, U_i1, W_i1, rho) = {
+                trace_span!("nimfs_prove", n_constraints = self.ccs.m, mu = MU, nu = NU);
+                NIMFS::<C1, PoseidonSponge<C1::ScalarField>>::prove(
+                    &mut transcript_p,
+                    &self.ccs,
+                    &all_Us,
+                    &all_us,
+                    &all_Ws,
+                    &all_ws,
+                )?
+            };
 
             // sanity check: check the folded instance relation
             #[cfg(test)]
@@ -841,43 +1100,21 @@ where
             );
 
             let rho_bits = rho.into_bigint().to_bits_le()[..NOVA_N_BITS_RO].to_vec();
-            let rho_Fq = C1::BaseField::from(<C1::BaseField as PrimeField>::BigInt::from_bits_le(
-                &rho_bits,
-            ));
 
+            trace_span!("cyclefold_prove", n_constraints = self.cf_r1cs.A.n_rows);
             // CycleFold part:
-            // get the vector used as public inputs 'x' in the CycleFold circuit.
-            // Place the random values and the points coordinates as the public input x:
-            // In Nova, this is: x == [r, p1, p2, p3].
-            // In multifolding schemes such as HyperNova, this is:
-            // computed_x = [r, p_0, p_1, p_2, ..., p_n],
+            // the CycleFold circuit folds the points of all the running and incoming instances,
+            // and places the random value and the points coordinates as its public input x:
+            // x = [r, p_0, p_1, p_2, ..., p_n, p_folded],
             // where each p_i is in fact p_i.to_constraint_field()
-            let cf_u_i_x = [
-                vec![rho_Fq],
-                all_Us
-                    .iter()
-                    .flat_map(|Us_i| get_cm_coordinates(&Us_i.C))
-                    .collect(),
-                all_us
-                    .iter()
-                    .flat_map(|us_i| get_cm_coordinates(&us_i.C))
-                    .collect(),
-                get_cm_coordinates(&U_i1.C),
-            ]
-            .concat();
-
-            let cf_circuit = HyperNovaCycleFoldCircuit::<C1, GC1, MU, NU> {
-                _gc: PhantomData,
-                r_bits: Some(rho_bits.clone()),
-                points: Some(
-                    [
-                        all_Us.iter().map(|Us_i| Us_i.C).collect::<Vec<_>>(),
-                        all_us.iter().map(|us_i| us_i.C).collect::<Vec<_>>(),
-                    ]
-                    .concat(),
-                ),
-                x: Some(cf_u_i_x.clone()),
-            };
+            let cf_circuit = HyperNovaCycleFoldCircuit::<C1, GC1, MU, NU>::new(
+                rho_bits,
+                [
+                    all_Us.iter().map(|Us_i| Us_i.C).collect::<Vec<_>>(),
+                    all_us.iter().map(|us_i| us_i.C).collect::<Vec<_>>(),
+                ]
+                .concat(),
+            )?;
 
             let (_cf_w_i, cf_u_i, cf_W_i1, cf_U_i1, cf_cmT, _) = fold_cyclefold_circuit::<
                 HyperNovaCycleFoldConfig<C1, MU, NU>,
@@ -894,7 +1131,6 @@ where
                 self.pp_hash,
                 self.cf_W_i.clone(), // CycleFold running instance witness
                 self.cf_U_i.clone(), // CycleFold running instance
-                cf_u_i_x,
                 cf_circuit,
                 &mut rng,
             )?;
@@ -933,24 +1169,34 @@ where
             self.cf_U_i = cf_U_i1;
         }
 
-        let (cs, _) = augmented_f_circuit.compute_cs_ccs()?;
+        let (cs, _) = {
+            trace_span!("augmented_circuit_synthesis");
+            augmented_f_circuit.compute_cs_ccs()?
+        };
 
         #[cfg(test)]
         assert!(cs.is_satisfied()?);
 
-        let (r1cs_w_i1, r1cs_x_i1) = extract_w_x::<C1::ScalarField>(&cs); // includes 1 and public inputs
+        let (r1cs_w_i1, r1cs_x_i1) = {
+            trace_span!("witness_extraction", n_variables = cs.num_witness_variables);
+            extract_w_x::<C1::ScalarField>(&cs) // includes 1 and public inputs
+        };
 
-        let r1cs_z = [
+        let mut r1cs_z = [
             vec![C1::ScalarField::one()],
             r1cs_x_i1.clone(),
             r1cs_w_i1.clone(),
         ]
         .concat();
+        // extend the assignment to the padded dimensions of the CCS
+        self.ccs.pad_z(&mut r1cs_z)?;
         // compute committed instances, w_{i+1}, u_{i+1}, which will be used as w_i, u_i, so we
         // assign them directly to w_i, u_i.
-        let (u_i, w_i) = self
-            .ccs
-            .to_cccs::<_, C1, CS1, H>(&mut rng, &self.cs_pp, &r1cs_z)?;
+        let (u_i, w_i) = {
+            trace_span!("commit", len = r1cs_z.len());
+            self.ccs
+                .to_cccs::<_, C1, CS1, H>(&mut rng, &self.cs_pp, &r1cs_z)?
+        };
         self.u_i = u_i.clone();
         self.w_i = w_i.clone();
 
@@ -960,6 +1206,8 @@ where
         self.z_i = z_i1.clone();
         self.U_i = U_i1.clone();
         self.W_i = W_i1.clone();
+        self.blinding = None;
+        self.cf_blinding = None;
 
         #[cfg(test)]
         {
@@ -972,8 +1220,15 @@ where
         Ok(())
     }
 
-    fn state(&self) -> Vec<C1::ScalarField> {
-        self.z_i.clone()
+    fn state(&self) -> FoldingState<'_, C1, C2, LCCCS<C1>, CCCS<C1>> {
+        FoldingState {
+            i: self.i,
+            z_0: &self.z_0,
+            z_i: &self.z_i,
+            U_i: &self.U_i,
+            u_i: &self.u_i,
+            cf_U_i: &self.cf_U_i,
+        }
     }
 
     fn ivc_proof(&self) -> Self::IVCProof {
@@ -1019,6 +1274,17 @@ where
         let ccs = augmented_f_circuit.ccs.clone();
         let cf_r1cs = get_r1cs_from_cs::<C2::ScalarField>(cf_circuit)?;
 
+        // the IVC proof may come from untrusted bytes, check that its dimensions match the
+        // circuit before using it
+        check_state("z_0", &z_0, vp.state_len)?;
+        check_state("z_i", &z_i, vp.state_len)?;
+        U_i.check_dimensions(&ccs)?;
+        u_i.check_dimensions(&ccs)?;
+        W_i.check_dimensions(&ccs)?;
+        w_i.check_dimensions(&ccs)?;
+        cf_U_i.check_dimensions(&cf_r1cs)?;
+        cf_W_i.check_dimensions(&cf_r1cs)?;
+
         Ok(Self {
             _gc1: PhantomData,
             _c2: PhantomData,
@@ -1039,10 +1305,12 @@ where
             U_i,
             cf_W_i,
             cf_U_i,
+            blinding: None,
+            cf_blinding: None,
         })
     }
 
-    /// Implements IVC.V of Hyp.clone()erNova+CycleFold. Notice that this method does not include the
+    /// Implements IVC.V of HyperNova+CycleFold. Notice that this method does not include the
     /// commitments verification, which is done in the Decider.
     fn verify(vp: Self::VerifierParam, ivc_proof: Self::IVCProof) -> Result<(), Error> {
         let Self::IVCProof {
@@ -1057,6 +1325,9 @@ where
             cf_U_i,
         } = ivc_proof;
 
+        check_state("z_0", &z_0, vp.state_len)?;
+        check_state("z_i", &z_i, vp.state_len)?;
+
         if num_steps == C1::ScalarField::zero() {
             if z_0 != z_i {
                 return Err(Error::IVCVerificationFail);
@@ -1066,9 +1337,8 @@ where
         // `sponge` is for digest computation.
         let sponge = PoseidonSponge::<C1::ScalarField>::new(&vp.poseidon_config);
 
-        if u_i.x.len() != 2 || U_i.x.len() != 2 {
-            return Err(Error::IVCVerificationFail);
-        }
+        check_len(u_i.x.len(), 2)?;
+        check_len(U_i.x.len(), 2)?;
 
         let pp_hash = vp.pp_hash()?;
 
@@ -1105,7 +1375,7 @@ mod tests {
 
     use super::*;
     use crate::commitment::pedersen::Pedersen;
-    use crate::frontend::utils::CubicFCircuit;
+    use crate::frontend::utils::{CubicFCircuit, QuinticFCircuit};
     use crate::transcript::poseidon::poseidon_canonical_config;
 
     #[test]
@@ -1115,19 +1385,295 @@ mod tests {
         let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
 
         // run the test using Pedersen commitments on both sides of the curve cycle
-        test_ivc_opt::<Pedersen<Projective>, Pedersen<Projective2>, false>(
+        test_ivc_opt::<Pedersen<Projective>, Pedersen<Projective2>, 2, 3, false>(
             poseidon_config.clone(),
             F_circuit,
         );
 
-        test_ivc_opt::<Pedersen<Projective, true>, Pedersen<Projective2, true>, true>(
+        test_ivc_opt::<Pedersen<Projective, true>, Pedersen<Projective2, true>, 2, 3, true>(
             poseidon_config.clone(),
             F_circuit,
         );
 
         // run the test using KZG for the commitments on the main curve, and Pedersen for the
         // commitments on the secondary curve
-        test_ivc_opt::<KZG<Bn254>, Pedersen<Projective2>, false>(poseidon_config, F_circuit);
+        test_ivc_opt::<KZG<Bn254>, Pedersen<Projective2>, 2, 3, false>(
+            poseidon_config.clone(),
+            F_circuit,
+        );
+
+        // fold the same number of extra running and incoming instances
+        test_ivc_opt::<Pedersen<Projective>, Pedersen<Projective2>, 2, 2, false>(
+            poseidon_config,
+            F_circuit,
+        );
+    }
+
+    // the CCS of the AugmentedFCircuit does not have power-of-two dimensions, check that it is
+    // padded at preprocessing and that the padded witnesses fold end to end
+    #[test]
+    fn test_ivc_padded_ccs() {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+
+        type HN = HyperNova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            1,
+            1,
+            false,
+        >;
+
+        let augmented_f_circuit =
+            AugmentedFCircuit::<Projective, Projective2, GVar2, CubicFCircuit<Fr>, 1, 1>::empty(
+                &poseidon_config,
+                F_circuit,
+                None,
+            )
+            .unwrap();
+        let unpadded_ccs = augmented_f_circuit.upper_bound_ccs().unwrap();
+        assert!(!unpadded_ccs.m.is_power_of_two() || !unpadded_ccs.n.is_power_of_two());
+
+        let prep_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let hypernova_params = HN::preprocess(&mut rng, &prep_param).unwrap();
+        let ccs = &hypernova_params.1.ccs;
+        ccs.sanity_check().unwrap();
+        assert_eq!(ccs.m, unpadded_ccs.m.next_power_of_two());
+        assert_eq!(ccs.n, unpadded_ccs.n.next_power_of_two());
+
+        let mut hypernova = HN::init(&hypernova_params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+        for _ in 0..3 {
+            hypernova.prove_step(&mut rng, (), None).unwrap();
+        }
+        assert_eq!(hypernova.w_i.w.len(), ccs.n - ccs.l - 1);
+        HN::verify(hypernova_params.1, hypernova.ivc_proof()).unwrap();
+    }
+
+    // the degree-5 custom gate of QuinticFCircuit is appended to the CCS of the AugmentedFCircuit
+    // without being split into R1CS constraints, check that the resulting degree-5 CCS is folded
+    // end to end, also with extra running and incoming instances
+    #[test]
+    fn test_ivc_custom_gates() {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let F_circuit = QuinticFCircuit::<Fr>::new(()).unwrap();
+
+        type HN = HyperNova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            QuinticFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            2,
+            2,
+            false,
+        >;
+
+        let prep_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let hypernova_params = HN::preprocess(&mut rng, &prep_param).unwrap();
+        let ccs = &hypernova_params.1.ccs;
+        ccs.sanity_check().unwrap();
+        assert_eq!(ccs.d, 5);
+
+        let mut z_i = vec![Fr::from(3_u32)];
+        let mut hypernova = HN::init(&hypernova_params, F_circuit, z_i.clone()).unwrap();
+        for i in 0..3 {
+            let (U, W) = hypernova
+                .new_running_instance(&mut rng, vec![Fr::from(85_u32)], ())
+                .unwrap();
+            let (u, w) = hypernova
+                .new_incoming_instance(&mut rng, vec![Fr::from(15_u32)], ())
+                .unwrap();
+            hypernova
+                .prove_step(&mut rng, (), Some((vec![(U, W)], vec![(u, w)])))
+                .unwrap();
+            z_i = F_circuit.step_native(i, z_i, ()).unwrap();
+        }
+        assert_eq!(hypernova.state().z_i, z_i);
+        HN::verify(hypernova_params.1, hypernova.ivc_proof()).unwrap();
+    }
+
+    // checkpoint the folding state in the middle of the chain, reload it from bytes and continue
+    // folding from it
+    #[test]
+    fn test_ivc_proof_serialization() {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+
+        type HN = HyperNova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            1,
+            1,
+            false,
+        >;
+
+        let prep_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let hypernova_params = HN::preprocess(&mut rng, &prep_param).unwrap();
+        let mut hypernova = HN::init(&hypernova_params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+        for _ in 0..2 {
+            hypernova.prove_step(&mut rng, (), None).unwrap();
+        }
+
+        for compress in [Compress::Yes, Compress::No] {
+            let ivc_proof = hypernova.ivc_proof();
+            let mut bytes = vec![];
+            ivc_proof.serialize_with_mode(&mut bytes, compress).unwrap();
+            assert_eq!(bytes.len(), ivc_proof.serialized_size(compress));
+            let ivc_proof_deserialized =
+                IVCProof::<Projective, Projective2>::deserialize_with_mode(
+                    bytes.as_slice(),
+                    compress,
+                    ark_serialize::Validate::Yes,
+                )
+                .unwrap();
+            assert_eq!(ivc_proof, ivc_proof_deserialized);
+
+            let mut reloaded =
+                HN::from_ivc_proof(ivc_proof_deserialized, (), hypernova_params.clone()).unwrap();
+            for _ in 0..2 {
+                reloaded.prove_step(&mut rng, (), None).unwrap();
+            }
+            assert_eq!(reloaded.i, Fr::from(4_u32));
+            HN::verify(hypernova_params.1.clone(), reloaded.ivc_proof()).unwrap();
+        }
+
+        // proofs whose dimensions don't match the circuit are rejected
+        let mut ivc_proof = hypernova.ivc_proof();
+        ivc_proof.U_i.r_x.pop();
+        assert!(HN::from_ivc_proof(ivc_proof, (), hypernova_params.clone()).is_err());
+        let mut ivc_proof = hypernova.ivc_proof();
+        ivc_proof.U_i.v.push(Fr::zero());
+        assert!(HN::from_ivc_proof(ivc_proof, (), hypernova_params.clone()).is_err());
+        let mut ivc_proof = hypernova.ivc_proof();
+        ivc_proof.w_i.w.pop();
+        assert!(HN::from_ivc_proof(ivc_proof, (), hypernova_params).is_err());
+    }
+
+    // two workers produce a CCCS instance each for the same CCS, which the coordinator folds into
+    // its running instance at each step
+    #[test]
+    fn test_prove_step_with_extra_instances() {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+
+        type HN = HyperNova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            1,
+            3,
+            false,
+        >;
+
+        let prep_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let hypernova_params = HN::preprocess(&mut rng, &prep_param).unwrap();
+        let z_0 = vec![Fr::from(3_u32)];
+
+        let mut coordinator = HN::init(&hypernova_params, F_circuit, z_0.clone()).unwrap();
+        let workers = [
+            HN::init(&hypernova_params, F_circuit, z_0.clone()).unwrap(),
+            HN::init(&hypernova_params, F_circuit, z_0.clone()).unwrap(),
+        ];
+
+        let num_steps: usize = 3;
+        for i in 0..num_steps {
+            let extra = workers
+                .iter()
+                .enumerate()
+                .map(|(j, worker)| {
+                    worker.new_incoming_instance(&mut rng, vec![Fr::from((10 * i + j) as u32)], ())
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            coordinator
+                .prove_step_with_extra_instances(&mut rng, (), extra)
+                .unwrap();
+        }
+        assert_eq!(Fr::from(num_steps as u32), coordinator.i);
+        HN::verify(hypernova_params.1.clone(), coordinator.ivc_proof()).unwrap();
+
+        // invalid extra instances are rejected before folding
+        let (u, w) = workers[0]
+            .new_incoming_instance(&mut rng, vec![Fr::from(1_u32)], ())
+            .unwrap();
+        let valid = (u.clone(), w.clone());
+        let assert_invalid = |coordinator: &mut HN, extra, index| {
+            let i = coordinator.i;
+            match coordinator.prove_step_with_extra_instances(ark_std::test_rng(), (), extra) {
+                Err(Error::InvalidExtraInstance(j, _)) => assert_eq!(j, index),
+                r => panic!("unexpected result {:?}", r),
+            }
+            assert_eq!(coordinator.i, i);
+        };
+
+        // wrong number of instances
+        assert!(matches!(
+            coordinator.prove_step_with_extra_instances(&mut rng, (), vec![valid.clone()]),
+            Err(Error::NotSameLength(..))
+        ));
+        // mis-shaped instance
+        let mut u_bad = u.clone();
+        u_bad.x.pop();
+        assert_invalid(&mut coordinator, vec![valid.clone(), (u_bad, w.clone())], 1);
+        // instance generated for other params
+        let mut other_worker = workers[1].clone();
+        other_worker.pp_hash = Fr::rand(&mut rng);
+        let other = other_worker
+            .new_incoming_instance(&mut rng, vec![Fr::from(1_u32)], ())
+            .unwrap();
+        assert_invalid(&mut coordinator, vec![other, valid.clone()], 0);
+        // witness not matching the commitment
+        let mut w_bad = w.clone();
+        w_bad.w[0] += Fr::one();
+        assert_invalid(&mut coordinator, vec![(u.clone(), w_bad), valid.clone()], 0);
+        // unsatisfying instance, with a consistent commitment
+        let mut w_bad = w.clone();
+        w_bad.w[0] += Fr::one();
+        let u_bad = CCCS {
+            C: Pedersen::<Projective>::commit(&coordinator.cs_pp, &w_bad.w, &Fr::zero()).unwrap(),
+            ..u
+        };
+        assert_invalid(&mut coordinator, vec![valid.clone(), (u_bad, w_bad)], 1);
+
+        // extra instances can only be folded when there are no other running instances
+        type HN2 = HyperNova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            2,
+            3,
+            false,
+        >;
+        let hypernova_params2 = HN2::preprocess(&mut rng, &prep_param).unwrap();
+        let mut hypernova2 = HN2::init(&hypernova_params2, F_circuit, z_0).unwrap();
+        assert!(matches!(
+            hypernova2.prove_step_with_extra_instances(&mut rng, (), vec![valid.clone(), valid]),
+            Err(Error::NotSameLength(_, 2, _, 1))
+        ));
     }
 
     #[allow(clippy::type_complexity)]
@@ -1135,6 +1681,8 @@ mod tests {
     pub fn test_ivc_opt<
         CS1: CommitmentScheme<Projective, H>,
         CS2: CommitmentScheme<Projective2, H>,
+        const MU: usize,
+        const NU: usize,
         const H: bool,
     >(
         poseidon_config: PoseidonConfig<Fr>,
@@ -1142,10 +1690,7 @@ mod tests {
     ) {
         let mut rng = ark_std::test_rng();
 
-        const MU: usize = 2;
-        const NU: usize = 3;
-
-        type HN<CS1, CS2, const H: bool> =
+        type HN<CS1, CS2, const MU: usize, const NU: usize, const H: bool> =
             HyperNova<Projective, GVar, Projective2, GVar2, CubicFCircuit<Fr>, CS1, CS2, MU, NU, H>;
 
         let prep_param =
@@ -1153,10 +1698,12 @@ mod tests {
                 poseidon_config.clone(),
                 F_circuit,
             );
-        let hypernova_params = HN::preprocess(&mut rng, &prep_param).unwrap();
+        let hypernova_params =
+            HN::<CS1, CS2, MU, NU, H>::preprocess(&mut rng, &prep_param).unwrap();
 
         let z_0 = vec![Fr::from(3_u32)];
-        let mut hypernova = HN::init(&hypernova_params, F_circuit, z_0.clone()).unwrap();
+        let mut hypernova =
+            HN::<CS1, CS2, MU, NU, H>::init(&hypernova_params, F_circuit, z_0.clone()).unwrap();
 
         let (w_i_blinding, W_i_blinding) = if H {
             (Fr::rand(&mut rng), Fr::rand(&mut rng))
@@ -1173,7 +1720,7 @@ mod tests {
             for j in 0..MU - 1 {
                 let instance_state = vec![Fr::from(j as u32 + 85_u32)];
                 let (U, W) = hypernova
-                    .new_running_instance(&mut rng, instance_state, vec![])
+                    .new_running_instance(&mut rng, instance_state, ())
                     .unwrap();
                 lcccs.push((U, W));
             }
@@ -1181,22 +1728,85 @@ mod tests {
             for j in 0..NU - 1 {
                 let instance_state = vec![Fr::from(j as u32 + 15_u32)];
                 let (u, w) = hypernova
-                    .new_incoming_instance(&mut rng, instance_state, vec![])
+                    .new_incoming_instance(&mut rng, instance_state, ())
                     .unwrap();
                 cccs.push((u, w));
             }
 
             hypernova
-                .prove_step(&mut rng, vec![], Some((lcccs, cccs)))
+                .prove_step(&mut rng, (), Some((lcccs, cccs)))
                 .unwrap();
         }
         assert_eq!(Fr::from(num_steps as u32), hypernova.i);
 
         let ivc_proof = hypernova.ivc_proof();
-        HN::verify(
+        HN::<CS1, CS2, MU, NU, H>::verify(
             hypernova_params.1.clone(), // verifier_params
             ivc_proof,
         )
         .unwrap();
     }
+
+    #[test]
+    fn test_preprocess_deterministic() {
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+
+        type HN = HyperNova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            1,
+            1,
+            false,
+        >;
+        let prep_param =
+            PreprocessorParam::new(poseidon_config, F_circuit).with_domain_tag(b"sonobe-test");
+        // the params do not depend on the rng
+        let (_, vp1) = HN::preprocess(ark_std::test_rng(), &prep_param).unwrap();
+        let mut rng = ark_std::test_rng();
+        rng.next_u64();
+        let (_, vp2) = HN::preprocess(&mut rng, &prep_param).unwrap();
+        assert_eq!(vp1.pp_hash().unwrap(), vp2.pp_hash().unwrap());
+        // and they are the ones derived from the tag, on both curves
+        let (_, cs_vp) =
+            <Pedersen<Projective> as CommitmentScheme<Projective>>::setup_deterministic(
+                b"sonobe-test",
+                vp1.cs_vp.generators.len(),
+            )
+            .unwrap();
+        assert_eq!(vp1.cs_vp, cs_vp);
+        let (_, cf_cs_vp) =
+            <Pedersen<Projective2> as CommitmentScheme<Projective2>>::setup_deterministic(
+                b"sonobe-test",
+                vp1.cf_cs_vp.generators.len(),
+            )
+            .unwrap();
+        assert_eq!(vp1.cf_cs_vp, cf_cs_vp);
+
+        let prep_param = prep_param.with_domain_tag(b"sonobe-other");
+        let (_, vp3) = HN::preprocess(ark_std::test_rng(), &prep_param).unwrap();
+        assert_ne!(vp1.pp_hash().unwrap(), vp3.pp_hash().unwrap());
+
+        // KZG needs a trusted setup, so it can not be derived from a tag
+        type HNKZG = HyperNova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            KZG<'static, Bn254>,
+            Pedersen<Projective2>,
+            1,
+            1,
+            false,
+        >;
+        let prep_param = PreprocessorParam::new(prep_param.poseidon_config.clone(), F_circuit)
+            .with_domain_tag(b"sonobe-test");
+        assert!(HNKZG::preprocess(ark_std::test_rng(), &prep_param).is_err());
+    }
 }
@@ -1,6 +1,6 @@
 use ark_crypto_primitives::sponge::Absorb;
 use ark_ec::{CurveGroup, Group};
-use ark_ff::{BigInteger, Field, PrimeField};
+use ark_ff::{Field, PrimeField};
 use ark_poly::univariate::DensePolynomial;
 use ark_poly::{DenseUVPolynomial, Polynomial};
 use ark_std::{One, Zero};
@@ -8,12 +8,12 @@ use ark_std::{One, Zero};
 use super::{
     cccs::CCCS,
     lcccs::LCCCS,
-    utils::{compute_c, compute_g, compute_sigmas_thetas},
+    utils::{compute_Mzs, compute_c, compute_g_from_Mzs, compute_sigmas_thetas_from_Mzs},
     Witness,
 };
 use crate::arith::ccs::CCS;
 use crate::constants::NOVA_N_BITS_RO;
-use crate::folding::circuits::CF1;
+use crate::folding::circuits::{utils::le_bits_to_field_checked, CF1};
 use crate::folding::traits::Dummy;
 use crate::transcript::Transcript;
 use crate::utils::sum_check::structs::{IOPProof as SumCheckProof, IOPProverMessage};
@@ -31,19 +31,21 @@ pub struct NIMFSProof<C: CurveGroup> {
     pub sigmas_thetas: SigmasThetas<C::ScalarField>,
 }
 
-impl<C: CurveGroup> Dummy<(usize, usize, usize, usize)> for NIMFSProof<C> {
-    fn dummy((s, t, mu, nu): (usize, usize, usize, usize)) -> Self {
+impl<C: CurveGroup> Dummy<(usize, usize, usize, usize, usize)> for NIMFSProof<C> {
+    fn dummy((s, t, d, mu, nu): (usize, usize, usize, usize, usize)) -> Self {
         // use 'C::ScalarField::one()' instead of 'zero()' to enforce the NIMFSProof to have the
         // same in-circuit representation to match the number of constraints of an actual proof.
+        // The sum-check polynomial g has degree d + 1, so each round sends d + 2 coefficients.
         NIMFSProof::<C> {
             sc_proof: SumCheckProof::<C::ScalarField> {
                 point: vec![C::ScalarField::one(); s],
                 proofs: vec![
                     IOPProverMessage {
-                        coeffs: vec![C::ScalarField::one(); t + 1]
+                        coeffs: vec![C::ScalarField::one(); d + 2]
                     };
                     s
                 ],
+                degree: d + 1,
             },
             sigmas_thetas: SigmasThetas(
                 vec![vec![C::ScalarField::one(); t]; mu],
@@ -55,7 +57,7 @@ impl<C: CurveGroup> Dummy<(usize, usize, usize, usize)> for NIMFSProof<C> {
 
 impl<C: CurveGroup> Dummy<(&CCS<CF1<C>>, usize, usize)> for NIMFSProof<C> {
     fn dummy((ccs, mu, nu): (&CCS<CF1<C>>, usize, usize)) -> Self {
-        NIMFSProof::dummy((ccs.s, ccs.t, mu, nu))
+        NIMFSProof::dummy((ccs.s, ccs.t, ccs.d, mu, nu))
     }
 }
 
@@ -246,8 +248,13 @@ where
         transcript.absorb(&beta_scalar);
         let beta: Vec<C::ScalarField> = transcript.get_challenges(ccs.s);
 
+        // Compute the M_j z vectors once, as they are needed both for g(x) and for the sigmas and
+        // thetas
+        let Mzs_lcccs = compute_Mzs(ccs, &z_lcccs)?;
+        let Mzs_cccs = compute_Mzs(ccs, &z_cccs)?;
+
         // Compute g(x)
-        let g = compute_g(ccs, running_instances, &z_lcccs, &z_cccs, gamma, &beta)?;
+        let g = compute_g_from_Mzs(ccs, running_instances, &Mzs_lcccs, &Mzs_cccs, gamma, &beta)?;
 
         // Step 3: Run the sumcheck prover
         let sumcheck_proof = IOPSumCheck::<C::ScalarField, T>::prove(&g, transcript)
@@ -257,15 +264,13 @@ where
         let r_x_prime = sumcheck_proof.point.clone();
 
         // Step 4: compute sigmas and thetas
-        let sigmas_thetas = compute_sigmas_thetas(ccs, &z_lcccs, &z_cccs, &r_x_prime)?;
+        let sigmas_thetas = compute_sigmas_thetas_from_Mzs(&Mzs_lcccs, &Mzs_cccs, &r_x_prime)?;
 
         // Step 6: Get the folding challenge
         let rho_scalar = C::ScalarField::from_le_bytes_mod_order(b"rho");
         transcript.absorb(&rho_scalar);
         let rho_bits: Vec<bool> = transcript.get_challenge_nbits(NOVA_N_BITS_RO);
-        let rho: C::ScalarField = C::ScalarField::from(
-            <C::ScalarField as PrimeField>::BigInt::from_bits_le(&rho_bits),
-        );
+        let rho: C::ScalarField = le_bits_to_field_checked(&rho_bits)?;
 
         // Step 7: Create the folded instance
         let folded_lcccs = Self::fold(
@@ -383,9 +388,7 @@ where
         let rho_scalar = C::ScalarField::from_le_bytes_mod_order(b"rho");
         transcript.absorb(&rho_scalar);
         let rho_bits: Vec<bool> = transcript.get_challenge_nbits(NOVA_N_BITS_RO);
-        let rho: C::ScalarField = C::ScalarField::from(
-            <C::ScalarField as PrimeField>::BigInt::from_bits_le(&rho_bits),
-        );
+        let rho: C::ScalarField = le_bits_to_field_checked(&rho_bits)?;
 
         // Step 7: Compute the folded instance
         Ok(Self::fold(
@@ -402,8 +405,8 @@ where
 pub mod tests {
     use super::*;
     use crate::arith::{
-        ccs::tests::{get_test_ccs, get_test_z},
-        Arith,
+        ccs::tests::{get_test_ccs, get_test_odd_ccs, get_test_z},
+        Arith, ArithSampler,
     };
     use crate::transcript::poseidon::poseidon_canonical_config;
     use ark_crypto_primitives::sponge::poseidon::PoseidonSponge;
@@ -412,6 +415,7 @@ pub mod tests {
     use ark_std::UniformRand;
 
     use crate::commitment::{pedersen::Pedersen, CommitmentScheme};
+    use crate::folding::hypernova::utils::compute_sigmas_thetas;
     use ark_pallas::{Fr, Projective};
 
     #[test]
@@ -520,6 +524,55 @@ pub mod tests {
         ccs.check_relation(&folded_witness, &folded_lcccs).unwrap();
     }
 
+    /// Multifolding over a CCS whose dimensions are not powers of two, once padded
+    #[test]
+    fn test_multifolding_padded_odd_ccs() {
+        let mut rng = test_rng();
+
+        let mut ccs = get_test_odd_ccs::<Fr>();
+        ccs.pad_to_pow2();
+        ccs.sanity_check().unwrap();
+        let (pedersen_params, _) =
+            Pedersen::<Projective>::setup(&mut rng, ccs.n - ccs.l - 1).unwrap();
+
+        let mut z_1 = get_test_z(3);
+        let mut z_2 = get_test_z(4);
+        ccs.pad_z(&mut z_1).unwrap();
+        ccs.pad_z(&mut z_2).unwrap();
+
+        let (running_instance, w1) = ccs
+            .to_lcccs::<_, _, Pedersen<Projective>, false>(&mut rng, &pedersen_params, &z_1)
+            .unwrap();
+        let (new_instance, w2) = ccs
+            .to_cccs::<_, _, Pedersen<Projective>, false>(&mut rng, &pedersen_params, &z_2)
+            .unwrap();
+
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let mut transcript_p: PoseidonSponge<Fr> = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let (proof, folded_lcccs, folded_witness, _) =
+            NIMFS::<Projective, PoseidonSponge<Fr>>::prove(
+                &mut transcript_p,
+                &ccs,
+                &[running_instance.clone()],
+                &[new_instance.clone()],
+                &[w1],
+                &[w2],
+            )
+            .unwrap();
+
+        let mut transcript_v: PoseidonSponge<Fr> = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let folded_lcccs_v = NIMFS::<Projective, PoseidonSponge<Fr>>::verify(
+            &mut transcript_v,
+            &ccs,
+            &[running_instance],
+            &[new_instance],
+            proof,
+        )
+        .unwrap();
+        assert_eq!(folded_lcccs, folded_lcccs_v);
+        ccs.check_relation(&folded_witness, &folded_lcccs).unwrap();
+    }
+
     /// Perform multiple steps of multifolding of an LCCCS instance with a CCCS instance
     #[test]
     pub fn test_multifolding_two_instances_multiple_steps() {
@@ -666,6 +719,65 @@ pub mod tests {
         ccs.check_relation(&folded_witness, &folded_lcccs).unwrap();
     }
 
+    /// Test that folding a randomly sampled LCCCS together with a real LCCCS and CCCS yields a
+    /// satisfying folded instance, as done when blinding the running instance.
+    #[test]
+    pub fn test_multifolding_sampled_lcccs() {
+        let mut rng = test_rng();
+
+        let ccs = get_test_ccs::<Fr>();
+        let (pedersen_params, _) =
+            Pedersen::<Projective, true>::setup(&mut rng, ccs.n - ccs.l - 1).unwrap();
+
+        let (running_instance, w1) = ccs
+            .to_lcccs::<_, _, Pedersen<Projective, true>, true>(
+                &mut rng,
+                &pedersen_params,
+                &get_test_z(3),
+            )
+            .unwrap();
+        let (w2, sampled_instance) = ccs
+            .sample_witness_instance::<Pedersen<Projective, true>>(&pedersen_params, &mut rng)
+            .unwrap();
+        ccs.check_relation(&w2, &sampled_instance).unwrap();
+        let (new_instance, w3) = ccs
+            .to_cccs::<_, _, Pedersen<Projective, true>, true>(
+                &mut rng,
+                &pedersen_params,
+                &get_test_z(4),
+            )
+            .unwrap();
+
+        let lcccs_instances = vec![running_instance, sampled_instance];
+        let cccs_instances = vec![new_instance];
+
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let mut transcript_p: PoseidonSponge<Fr> = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let (proof, folded_lcccs, folded_witness, _) =
+            NIMFS::<Projective, PoseidonSponge<Fr>>::prove(
+                &mut transcript_p,
+                &ccs,
+                &lcccs_instances,
+                &cccs_instances,
+                &[w1, w2],
+                &[w3],
+            )
+            .unwrap();
+
+        let mut transcript_v: PoseidonSponge<Fr> = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let folded_lcccs_v = NIMFS::<Projective, PoseidonSponge<Fr>>::verify(
+            &mut transcript_v,
+            &ccs,
+            &lcccs_instances,
+            &cccs_instances,
+            proof,
+        )
+        .unwrap();
+        assert_eq!(folded_lcccs, folded_lcccs_v);
+
+        ccs.check_relation(&folded_witness, &folded_lcccs).unwrap();
+    }
+
     /// Test that generates mu>1 and nu>1 instances, and folds them in a single multifolding step
     /// and repeats the process doing multiple steps.
     #[test]
@@ -2,9 +2,12 @@ use ark_ec::CurveGroup;
 use ark_ff::PrimeField;
 use ark_poly::DenseMultilinearExtension;
 use ark_poly::MultilinearExtension;
-use ark_std::One;
+use ark_std::{cfg_iter, One};
 use std::sync::Arc;
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
 use super::lcccs::LCCCS;
 use super::nimfs::SigmasThetas;
 use crate::arith::ccs::CCS;
@@ -13,6 +16,28 @@ use crate::utils::vec::mat_vec_mul;
 use crate::utils::virtual_polynomial::{build_eq_x_r_vec, eq_eval, VirtualPolynomial};
 use crate::Error;
 
+/// Computes the MLEs of the vectors `M_j z` for each of the given `z` vectors and each of the CCS
+/// matrices `M_j`, so that they can be computed once and then shared between [`compute_g_from_Mzs`]
+/// and [`compute_sigmas_thetas_from_Mzs`].
+#[allow(clippy::type_complexity)]
+pub fn compute_Mzs<F: PrimeField>(
+    ccs: &CCS<F>,
+    z: &[Vec<F>],
+) -> Result<Vec<Vec<Arc<DenseMultilinearExtension<F>>>>, Error> {
+    cfg_iter!(z)
+        .map(|z_i| {
+            cfg_iter!(ccs.M)
+                .map(|M_j| {
+                    Ok(Arc::new(dense_vec_to_dense_mle(
+                        ccs.s,
+                        &mat_vec_mul(M_j, z_i)?,
+                    )))
+                })
+                .collect()
+        })
+        .collect()
+}
+
 /// Compute the arrays of sigma_i and theta_i from step 4 corresponding to the LCCCS and CCCS
 /// instances
 pub fn compute_sigmas_thetas<F: PrimeField>(
@@ -21,32 +46,30 @@ pub fn compute_sigmas_thetas<F: PrimeField>(
     z_cccs: &[Vec<F>],
     r_x_prime: &[F],
 ) -> Result<SigmasThetas<F>, Error> {
-    let mut sigmas: Vec<Vec<F>> = Vec::new();
-    for z_lcccs_i in z_lcccs {
-        let mut Mzs: Vec<DenseMultilinearExtension<F>> = vec![];
-        for M_j in ccs.M.iter() {
-            Mzs.push(dense_vec_to_dense_mle(ccs.s, &mat_vec_mul(M_j, z_lcccs_i)?));
-        }
-        let sigma_i = Mzs
-            .iter()
-            .map(|Mz| Mz.evaluate(r_x_prime).ok_or(Error::EvaluationFail))
-            .collect::<Result<_, Error>>()?;
-        sigmas.push(sigma_i);
-    }
+    compute_sigmas_thetas_from_Mzs(
+        &compute_Mzs(ccs, z_lcccs)?,
+        &compute_Mzs(ccs, z_cccs)?,
+        r_x_prime,
+    )
+}
 
-    let mut thetas: Vec<Vec<F>> = Vec::new();
-    for z_cccs_i in z_cccs {
-        let mut Mzs: Vec<DenseMultilinearExtension<F>> = vec![];
-        for M_j in ccs.M.iter() {
-            Mzs.push(dense_vec_to_dense_mle(ccs.s, &mat_vec_mul(M_j, z_cccs_i)?));
-        }
-        let theta_i = Mzs
-            .iter()
-            .map(|Mz| Mz.evaluate(r_x_prime).ok_or(Error::EvaluationFail))
-            .collect::<Result<_, Error>>()?;
-        thetas.push(theta_i);
-    }
-    Ok(SigmasThetas(sigmas, thetas))
+/// Same as [`compute_sigmas_thetas`], but taking the MLEs of the `M_j z` vectors of the LCCCS and
+/// CCCS instances, as returned by [`compute_Mzs`].
+pub fn compute_sigmas_thetas_from_Mzs<F: PrimeField>(
+    Mzs_lcccs: &[Vec<Arc<DenseMultilinearExtension<F>>>],
+    Mzs_cccs: &[Vec<Arc<DenseMultilinearExtension<F>>>],
+    r_x_prime: &[F],
+) -> Result<SigmasThetas<F>, Error> {
+    let evaluate = |Mzs: &[Vec<Arc<DenseMultilinearExtension<F>>>]| {
+        cfg_iter!(Mzs)
+            .map(|Mzs_i| {
+                cfg_iter!(Mzs_i)
+                    .map(|Mz| Mz.evaluate(r_x_prime).ok_or(Error::EvaluationFail))
+                    .collect::<Result<Vec<F>, Error>>()
+            })
+            .collect::<Result<Vec<_>, Error>>()
+    };
+    Ok(SigmasThetas(evaluate(Mzs_lcccs)?, evaluate(Mzs_cccs)?))
 }
 
 /// Computes c from the step 5 in section 5 of HyperNova, adapted to multiple LCCCS & CCCS
@@ -64,39 +87,63 @@ pub fn compute_c<F: PrimeField>(
     vec_r_x: &Vec<Vec<F>>,
     r_x_prime: &[F],
 ) -> Result<F, Error> {
-    let (vec_sigmas, vec_thetas) = (st.0.clone(), st.1.clone());
-    let mut c = F::zero();
-
-    let mut e_lcccs = Vec::new();
-    for r_x in vec_r_x {
-        e_lcccs.push(eq_eval(r_x, r_x_prime)?);
-    }
-    for (i, sigmas) in vec_sigmas.iter().enumerate() {
-        // (sum gamma^j * e_i * sigma_j)
-        for (j, sigma_j) in sigmas.iter().enumerate() {
-            let gamma_j = gamma.pow([((i * ccs.t + j) as u64)]);
-            c += gamma_j * e_lcccs[i] * sigma_j;
-        }
-    }
+    let SigmasThetas(vec_sigmas, vec_thetas) = st;
+
+    // the sum is computed as a polynomial in gamma, whose coefficients are the terms of each
+    // instance, so that the powers gamma^0, ..., gamma^t are shared between all the instances and
+    // the eq evaluation e_k is computed (and multiplied) once. This is the same arrangement used
+    // by `compute_c_gadget`.
+    let gamma_powers = gamma_powers(gamma, ccs.t);
+
+    // e_i * sum_j gamma^j * sigma_{i,j}
+    let terms_lcccs = cfg_iter!(vec_sigmas)
+        .zip(vec_r_x)
+        .map(|(sigmas, r_x)| {
+            let e_i = eq_eval(r_x, r_x_prime)?;
+            Ok(e_i * inner_product(&gamma_powers, sigmas))
+        })
+        .collect::<Result<Vec<F>, Error>>()?;
+
+    // sum_i c_i * prod_{j \in S_i} theta_{k,j}
+    let terms_cccs = cfg_iter!(vec_thetas)
+        .map(|thetas| {
+            ccs.S
+                .iter()
+                .zip(&ccs.c)
+                .map(|(S_i, c_i)| *c_i * S_i.iter().map(|j| thetas[*j]).product::<F>())
+                .sum::<F>()
+        })
+        .collect::<Vec<F>>();
 
-    let mu = vec_sigmas.len();
     let e2 = eq_eval(beta, r_x_prime)?;
-    for (k, thetas) in vec_thetas.iter().enumerate() {
-        // + gamma^{t+1} * e2 * sum c_i * prod theta_j
-        let mut lhs = F::zero();
-        for i in 0..ccs.q {
-            let mut prod = F::one();
-            for j in ccs.S[i].clone() {
-                prod *= thetas[j];
-            }
-            lhs += ccs.c[i] * prod;
-        }
-        let gamma_t1 = gamma.pow([(mu * ccs.t + k) as u64]);
-        c += gamma_t1 * e2 * lhs;
+    let mut c = e2 * horner(&terms_cccs, gamma);
+    for term in terms_lcccs.iter().rev() {
+        c = c * gamma_powers[ccs.t] + term;
     }
     Ok(c)
 }
 
+/// Returns the powers `gamma^0, ..., gamma^n`.
+fn gamma_powers<F: PrimeField>(gamma: F, n: usize) -> Vec<F> {
+    let mut powers = vec![F::one()];
+    for i in 0..n {
+        powers.push(powers[i] * gamma);
+    }
+    powers
+}
+
+fn inner_product<F: PrimeField>(a: &[F], b: &[F]) -> F {
+    a.iter().zip(b).map(|(a_i, b_i)| *a_i * b_i).sum()
+}
+
+/// Evaluates at `x` the polynomial with the given coefficients.
+fn horner<F: PrimeField>(coeffs: &[F], x: F) -> F {
+    coeffs
+        .iter()
+        .rev()
+        .fold(F::zero(), |acc, coeff| acc * x + coeff)
+}
+
 /// Compute g(x) polynomial for the given inputs.
 pub fn compute_g<C: CurveGroup>(
     ccs: &CCS<C::ScalarField>,
@@ -109,48 +156,62 @@ pub fn compute_g<C: CurveGroup>(
 where
     C::ScalarField: PrimeField,
 {
-    assert_eq!(running_instances.len(), z_lcccs.len());
+    compute_g_from_Mzs(
+        ccs,
+        running_instances,
+        &compute_Mzs(ccs, z_lcccs)?,
+        &compute_Mzs(ccs, z_cccs)?,
+        gamma,
+        beta,
+    )
+}
+
+/// Same as [`compute_g`], but taking the MLEs of the `M_j z` vectors of the LCCCS and CCCS
+/// instances, as returned by [`compute_Mzs`].
+pub fn compute_g_from_Mzs<C: CurveGroup>(
+    ccs: &CCS<C::ScalarField>,
+    running_instances: &[LCCCS<C>],
+    Mzs_lcccs: &[Vec<Arc<DenseMultilinearExtension<C::ScalarField>>>],
+    Mzs_cccs: &[Vec<Arc<DenseMultilinearExtension<C::ScalarField>>>],
+    gamma: C::ScalarField,
+    beta: &[C::ScalarField],
+) -> Result<VirtualPolynomial<C::ScalarField>, Error>
+where
+    C::ScalarField: PrimeField,
+{
+    if running_instances.len() != Mzs_lcccs.len() {
+        return Err(Error::NotSameLength(
+            "running_instances.len()".to_string(),
+            running_instances.len(),
+            "Mzs_lcccs.len()".to_string(),
+            Mzs_lcccs.len(),
+        ));
+    }
 
     let mut g = VirtualPolynomial::<C::ScalarField>::new(ccs.s);
 
-    let mu = z_lcccs.len();
-    let nu = z_cccs.len();
-
     let mut gamma_pow = C::ScalarField::one();
-    for i in 0..mu {
+    for (running_instance, Mzs_i) in running_instances.iter().zip(Mzs_lcccs) {
         // L_j
-        let eq_rx = build_eq_x_r_vec(&running_instances[i].r_x)?;
-        let eq_rx_mle = dense_vec_to_dense_mle(ccs.s, &eq_rx);
-        for M_j in ccs.M.iter() {
-            let mut L_i_j = vec![dense_vec_to_dense_mle(
-                ccs.s,
-                &mat_vec_mul(M_j, &z_lcccs[i])?,
-            )];
-            L_i_j.push(eq_rx_mle.clone());
-            g.add_mle_list(L_i_j.iter().map(|v| Arc::new(v.clone())), gamma_pow)?;
+        let eq_rx = build_eq_x_r_vec(&running_instance.r_x)?;
+        let eq_rx_mle = Arc::new(dense_vec_to_dense_mle(ccs.s, &eq_rx));
+        for Mz in Mzs_i {
+            g.add_mle_list([Mz.clone(), eq_rx_mle.clone()], gamma_pow)?;
             gamma_pow *= gamma;
         }
     }
 
     let eq_beta = build_eq_x_r_vec(beta)?;
-    let eq_beta_mle = dense_vec_to_dense_mle(ccs.s, &eq_beta);
+    let eq_beta_mle = Arc::new(dense_vec_to_dense_mle(ccs.s, &eq_beta));
 
-    #[allow(clippy::needless_range_loop)]
-    for k in 0..nu {
+    for Mzs_k in Mzs_cccs {
         // Q_k
-        for i in 0..ccs.q {
-            let mut Q_k = vec![];
-            for &j in ccs.S[i].iter() {
-                Q_k.push(dense_vec_to_dense_mle(
-                    ccs.s,
-                    &mat_vec_mul(&ccs.M[j], &z_cccs[k])?,
-                ));
-            }
-            Q_k.push(eq_beta_mle.clone());
-            g.add_mle_list(
-                Q_k.iter().map(|v| Arc::new(v.clone())),
-                ccs.c[i] * gamma_pow,
-            )?;
+        for (S_i, c_i) in ccs.S.iter().zip(&ccs.c) {
+            let Q_k = S_i
+                .iter()
+                .map(|j| Mzs_k[*j].clone())
+                .chain([eq_beta_mle.clone()]);
+            g.add_mle_list(Q_k, *c_i * gamma_pow)?;
         }
         gamma_pow *= gamma;
     }
@@ -276,6 +337,76 @@ pub mod tests {
         assert_eq!(c, expected_c);
     }
 
+    // the parallel computation of the sigmas, thetas and c must match the sequential (per instance
+    // and per matrix) computation, independently of the number of threads
+    #[test]
+    fn test_compute_sigmas_thetas_c_determinism() {
+        let mut rng = test_rng();
+        let ccs = get_test_ccs::<Fr>();
+        let z_lcccs: Vec<Vec<Fr>> = (0..3).map(|i| get_test_z(i + 3)).collect();
+        let z_cccs: Vec<Vec<Fr>> = (0..2).map(|i| get_test_z(i + 10)).collect();
+        let gamma = Fr::rand(&mut rng);
+        let beta: Vec<Fr> = (0..ccs.s).map(|_| Fr::rand(&mut rng)).collect();
+        let vec_r_x: Vec<Vec<Fr>> = (0..z_lcccs.len())
+            .map(|_| (0..ccs.s).map(|_| Fr::rand(&mut rng)).collect())
+            .collect();
+        let r_x_prime: Vec<Fr> = (0..ccs.s).map(|_| Fr::rand(&mut rng)).collect();
+
+        // sequential computation
+        let evaluate = |z: &[Fr]| -> Vec<Fr> {
+            ccs.M
+                .iter()
+                .map(|M_j| {
+                    dense_vec_to_dense_mle(ccs.s, &mat_vec_mul(M_j, z).unwrap())
+                        .evaluate(&r_x_prime)
+                        .unwrap()
+                })
+                .collect()
+        };
+        let sigmas: Vec<Vec<Fr>> = z_lcccs.iter().map(|z| evaluate(z.as_slice())).collect();
+        let thetas: Vec<Vec<Fr>> = z_cccs.iter().map(|z| evaluate(z.as_slice())).collect();
+        let mut expected_c = Fr::zero();
+        for (i, sigmas_i) in sigmas.iter().enumerate() {
+            let e_i = eq_eval(&vec_r_x[i], &r_x_prime).unwrap();
+            for (j, sigma_j) in sigmas_i.iter().enumerate() {
+                expected_c += gamma.pow([(i * ccs.t + j) as u64]) * e_i * sigma_j;
+            }
+        }
+        let e2 = eq_eval(&beta, &r_x_prime).unwrap();
+        for (k, thetas_k) in thetas.iter().enumerate() {
+            let mut lhs = Fr::zero();
+            for (S_i, c_i) in ccs.S.iter().zip(&ccs.c) {
+                let mut prod = Fr::one();
+                for j in S_i.iter() {
+                    prod *= thetas_k[*j];
+                }
+                lhs += *c_i * prod;
+            }
+            expected_c += gamma.pow([(sigmas.len() * ccs.t + k) as u64]) * e2 * lhs;
+        }
+
+        let compute = || {
+            let sigmas_thetas = compute_sigmas_thetas(&ccs, &z_lcccs, &z_cccs, &r_x_prime).unwrap();
+            let c = compute_c(&ccs, &sigmas_thetas, gamma, &beta, &vec_r_x, &r_x_prime).unwrap();
+            (sigmas_thetas, c)
+        };
+        #[cfg(not(feature = "parallel"))]
+        let results = [compute()];
+        #[cfg(feature = "parallel")]
+        let results = [1, 4].map(|n_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(n_threads)
+                .build()
+                .unwrap()
+                .install(compute)
+        });
+        for (sigmas_thetas, c) in results {
+            assert_eq!(sigmas_thetas.0, sigmas);
+            assert_eq!(sigmas_thetas.1, thetas);
+            assert_eq!(c, expected_c);
+        }
+    }
+
     #[test]
     fn test_compute_g() {
         let mut rng = test_rng();
@@ -1,4 +1,6 @@
 pub mod circuits;
+// HyperNova's sum-check relies on `espresso_subroutines`, which requires `std`
+#[cfg(feature = "std")]
 pub mod hypernova;
 pub mod nova;
 pub mod protogalaxy;
@@ -6,37 +8,49 @@ pub mod traits;
 
 #[cfg(test)]
 pub mod tests {
-    use ark_ec::CurveGroup;
+    use ark_ec::{CurveGroup, Group};
     use ark_ff::PrimeField;
-    use ark_pallas::{constraints::GVar as GVar1, Fr, Projective as G1};
+    use ark_pallas::{constraints::GVar as GVar1, Fq, Fr, Projective as G1};
     use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::One;
     use ark_vesta::{constraints::GVar as GVar2, Projective as G2};
     use std::io::Write;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
 
     use crate::commitment::pedersen::Pedersen;
     use crate::folding::{
-        hypernova::HyperNova,
-        nova::{Nova, PreprocessorParam as NovaPreprocessorParam},
-        protogalaxy::ProtoGalaxy,
+        hypernova::{HyperNova, IVCProof as HyperNovaIVCProof},
+        nova::{IVCProof as NovaIVCProof, Nova, PreprocessorParam as NovaPreprocessorParam},
+        protogalaxy::{IVCProof as ProtoGalaxyIVCProof, ProtoGalaxy},
     };
-    use crate::frontend::utils::CubicFCircuit;
+    use crate::frontend::utils::{CubicFCircuit, CustomFCircuit};
     use crate::frontend::FCircuit;
     use crate::transcript::poseidon::poseidon_canonical_config;
     use crate::Error;
-    use crate::FoldingScheme;
+    use crate::{Decider, FoldingScheme};
 
     /// tests the IVC proofs and its serializers for the 3 implemented IVCs: Nova, HyperNova and
-    /// ProtoGalaxy.
+    /// ProtoGalaxy, and that the IVC proofs of another circuit are rejected.
     #[test]
     fn test_serialize_ivc_nova_hypernova_protogalaxy() {
         let poseidon_config = poseidon_canonical_config::<Fr>();
         type FC = CubicFCircuit<Fr>;
         let f_circuit = FC::new(()).unwrap();
+        // circuit with the same state length as FC, but a different number of constraints
+        type FCOther = CustomFCircuit<Fr>;
 
         // test Nova
         type N = Nova<G1, GVar1, G2, GVar2, FC, Pedersen<G1>, Pedersen<G2>, false>;
         let prep_param = NovaPreprocessorParam::new(poseidon_config.clone(), f_circuit);
         test_serialize_ivc_opt::<G1, G2, FC, N>("nova".to_string(), prep_param.clone()).unwrap();
+        let other_f_circuit = FCOther::new(10).unwrap();
+        type NOther = Nova<G1, GVar1, G2, GVar2, FCOther, Pedersen<G1>, Pedersen<G2>, false>;
+        let other_prep_param = NovaPreprocessorParam::new(poseidon_config.clone(), other_f_circuit);
+        test_ivc_proof_of_other_circuit::<G1, G2, FC, FCOther, N, NOther>(
+            prep_param.clone(),
+            other_prep_param.clone(),
+            10,
+        );
 
         // test HyperNova
         type HN = HyperNova<
@@ -51,12 +65,311 @@ pub mod tests {
             1, // nu
             false,
         >;
-        test_serialize_ivc_opt::<G1, G2, FC, HN>("hypernova".to_string(), prep_param).unwrap();
+        test_serialize_ivc_opt::<G1, G2, FC, HN>("hypernova".to_string(), prep_param.clone())
+            .unwrap();
+        type HNOther = HyperNova<
+            G1,
+            GVar1,
+            G2,
+            GVar2,
+            FCOther,
+            Pedersen<G1>,
+            Pedersen<G2>,
+            1, // mu
+            1, // nu
+            false,
+        >;
+        test_ivc_proof_of_other_circuit::<G1, G2, FC, FCOther, HN, HNOther>(
+            prep_param,
+            other_prep_param,
+            10,
+        );
+
+        // test ProtoGalaxy
+        type P = ProtoGalaxy<G1, GVar1, G2, GVar2, FC, Pedersen<G1>, Pedersen<G2>>;
+        let prep_param = (poseidon_config.clone(), f_circuit);
+        test_serialize_ivc_opt::<G1, G2, FC, P>("protogalaxy".to_string(), prep_param.clone())
+            .unwrap();
+        type POther = ProtoGalaxy<G1, GVar1, G2, GVar2, FCOther, Pedersen<G1>, Pedersen<G2>>;
+        let other_prep_param = (poseidon_config, FCOther::new(10).unwrap());
+        test_ivc_proof_of_other_circuit::<G1, G2, FC, FCOther, P, POther>(
+            prep_param,
+            other_prep_param,
+            10,
+        );
+    }
+
+    /// tests that malformed IVC proofs are rejected with an error instead of panicking, for the 3
+    /// implemented IVCs: Nova, HyperNova and ProtoGalaxy
+    #[test]
+    fn test_malformed_ivc_proof_nova_hypernova_protogalaxy() {
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        type FC = CubicFCircuit<Fr>;
+        let f_circuit = FC::new(()).unwrap();
+
+        // test Nova
+        type N = Nova<G1, GVar1, G2, GVar2, FC, Pedersen<G1>, Pedersen<G2>, false>;
+        let prep_param = NovaPreprocessorParam::new(poseidon_config.clone(), f_circuit);
+        let corruptions: Vec<fn(&mut NovaIVCProof<G1, G2>)> = vec![
+            |p| p.z_0.clear(),
+            |p| p.z_i.push(Fr::one()),
+            |p| p.u_i.x.truncate(1),
+            |p| p.u_i.x.push(Fr::one()),
+            |p| p.U_i.x.clear(),
+            |p| p.U_i.x[1] = Fr::one(),
+            |p| p.W_i.W.truncate(1),
+            |p| {
+                p.W_i.E.pop();
+            },
+            |p| p.w_i.W.push(Fr::one()),
+            |p| p.w_i.E.clear(),
+            |p| p.cf_W_i.W.clear(),
+            |p| p.cf_W_i.E.push(Fq::one()),
+            |p| p.cf_U_i.x.truncate(1),
+        ];
+        test_malformed_ivc_proof_opt::<G1, G2, FC, N>(prep_param.clone(), corruptions);
+
+        // test HyperNova
+        type HN = HyperNova<
+            G1,
+            GVar1,
+            G2,
+            GVar2,
+            FC,
+            Pedersen<G1>,
+            Pedersen<G2>,
+            1, // mu
+            1, // nu
+            false,
+        >;
+        let corruptions: Vec<fn(&mut HyperNovaIVCProof<G1, G2>)> = vec![
+            |p| p.z_0.clear(),
+            |p| p.z_i.push(Fr::one()),
+            |p| p.u_i.x.truncate(1),
+            |p| p.u_i.x.push(Fr::one()),
+            |p| p.U_i.x.clear(),
+            |p| {
+                p.U_i.r_x.pop();
+            },
+            |p| p.U_i.v.push(Fr::one()),
+            |p| p.W_i.w.truncate(1),
+            |p| p.w_i.w.push(Fr::one()),
+            |p| p.cf_W_i.W.clear(),
+            |p| p.cf_W_i.E.push(Fq::one()),
+            |p| p.cf_U_i.x.truncate(1),
+        ];
+        test_malformed_ivc_proof_opt::<G1, G2, FC, HN>(prep_param, corruptions);
 
         // test ProtoGalaxy
         type P = ProtoGalaxy<G1, GVar1, G2, GVar2, FC, Pedersen<G1>, Pedersen<G2>>;
         let prep_param = (poseidon_config, f_circuit);
-        test_serialize_ivc_opt::<G1, G2, FC, P>("protogalaxy".to_string(), prep_param).unwrap();
+        let corruptions: Vec<fn(&mut ProtoGalaxyIVCProof<G1, G2>)> = vec![
+            |p| p.z_0.clear(),
+            |p| p.z_i.push(Fr::one()),
+            |p| p.u_i.x.truncate(1),
+            |p| p.u_i.x.push(Fr::one()),
+            |p| p.U_i.x.clear(),
+            |p| p.U_i.betas.push(Fr::one()),
+            |p| p.W_i.w.truncate(1),
+            |p| p.w_i.w.push(Fr::one()),
+            |p| p.cf_W_i.W.clear(),
+            |p| p.cf_W_i.E.push(Fq::one()),
+            |p| p.cf_U_i.x.truncate(1),
+        ];
+        test_malformed_ivc_proof_opt::<G1, G2, FC, P>(prep_param, corruptions);
+    }
+
+    /// tests, for Nova, HyperNova and ProtoGalaxy, that proving the same chain from the same seed
+    /// with `prove_step_deterministic` gives byte-identical IVC proofs. Nova and HyperNova use
+    /// hiding commitments, whose randomness comes from the seed, so that another seed gives a
+    /// different proof.
+    #[test]
+    fn test_prove_step_deterministic_nova_hypernova_protogalaxy() {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        type FC = CubicFCircuit<Fr>;
+        let f_circuit = FC::new(()).unwrap();
+
+        // test Nova
+        type N = Nova<G1, GVar1, G2, GVar2, FC, Pedersen<G1, true>, Pedersen<G2, true>, true>;
+        let prep_param = NovaPreprocessorParam::new(poseidon_config.clone(), f_circuit);
+        let params = N::preprocess(&mut rng, &prep_param).unwrap();
+        let prove_chain = |seed| {
+            prove_chain_deterministic::<G1, G2, FC, N>(&params, seed, |nova, seed| {
+                nova.prove_step_deterministic(seed, ())
+            })
+        };
+        assert_eq!(prove_chain([7; 32]), prove_chain([7; 32]));
+        assert_ne!(prove_chain([7; 32]), prove_chain([8; 32]));
+
+        // test HyperNova
+        type HN = HyperNova<
+            G1,
+            GVar1,
+            G2,
+            GVar2,
+            FC,
+            Pedersen<G1, true>,
+            Pedersen<G2, true>,
+            1, // mu
+            1, // nu
+            true,
+        >;
+        let params = HN::preprocess(&mut rng, &prep_param).unwrap();
+        let prove_chain = |seed| {
+            prove_chain_deterministic::<G1, G2, FC, HN>(&params, seed, |hypernova, seed| {
+                hypernova.prove_step_deterministic(seed, (), None)
+            })
+        };
+        assert_eq!(prove_chain([7; 32]), prove_chain([7; 32]));
+        assert_ne!(prove_chain([7; 32]), prove_chain([8; 32]));
+
+        // test ProtoGalaxy
+        type P = ProtoGalaxy<G1, GVar1, G2, GVar2, FC, Pedersen<G1>, Pedersen<G2>>;
+        let params = P::preprocess(&mut rng, &(poseidon_config, f_circuit)).unwrap();
+        let prove_chain = |seed| {
+            prove_chain_deterministic::<G1, G2, FC, P>(&params, seed, |protogalaxy, seed| {
+                protogalaxy.prove_step_deterministic(seed, (), None)
+            })
+        };
+        assert_eq!(prove_chain([7; 32]), prove_chain([7; 32]));
+    }
+
+    /// proves 3 steps of the IVC of `FS` from the given `seed` through its
+    /// `prove_step_deterministic`, and returns the serialized IVC proof after checking that it
+    /// verifies.
+    fn prove_chain_deterministic<
+        C1: CurveGroup,
+        C2: CurveGroup,
+        FC: FCircuit<C1::ScalarField, Params = ()>,
+        FS: FoldingScheme<C1, C2, FC>,
+    >(
+        params: &(FS::ProverParam, FS::VerifierParam),
+        seed: [u8; 32],
+        prove_step_deterministic: fn(&mut FS, [u8; 32]) -> Result<(), Error>,
+    ) -> Vec<u8>
+    where
+        C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+        C2::BaseField: PrimeField,
+    {
+        let z_0 = vec![C1::ScalarField::from(3_u32)];
+        let mut fs = FS::init(params, FC::new(()).unwrap(), z_0).unwrap();
+        for _ in 0..3 {
+            prove_step_deterministic(&mut fs, seed).unwrap();
+        }
+        FS::verify(params.1.clone(), fs.ivc_proof()).unwrap();
+        let mut bytes = vec![];
+        fs.ivc_proof().serialize_compressed(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// tests that the IVC proofs obtained by applying each of the given `corruptions` to a valid
+    /// IVC proof are rejected by `FS::verify` with an error instead of panicking.
+    fn test_malformed_ivc_proof_opt<
+        C1: CurveGroup,
+        C2: CurveGroup,
+        FC: FCircuit<C1::ScalarField, Params = ()>,
+        FS: FoldingScheme<C1, C2, FC>,
+    >(
+        prep_param: FS::PreprocessorParam,
+        corruptions: Vec<fn(&mut FS::IVCProof)>,
+    ) where
+        C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+        C2::BaseField: PrimeField,
+    {
+        let mut rng = ark_std::test_rng();
+        let fs_params = FS::preprocess(&mut rng, &prep_param).unwrap();
+
+        let z_0 = vec![C1::ScalarField::from(3_u32)];
+        let mut fs = FS::init(&fs_params, FC::new(()).unwrap(), z_0).unwrap();
+        for _ in 0..3 {
+            fs.prove_step(&mut rng, FC::ExternalInputs::default(), None)
+                .unwrap();
+        }
+        let ivc_proof = fs.ivc_proof();
+        FS::verify(fs_params.1.clone(), ivc_proof.clone()).unwrap();
+
+        for corrupt in corruptions {
+            let mut proof = ivc_proof.clone();
+            corrupt(&mut proof);
+            let vp = fs_params.1.clone();
+            let result = catch_unwind(AssertUnwindSafe(|| FS::verify(vp, proof)));
+            assert!(matches!(result, Ok(Err(_))));
+        }
+    }
+
+    /// tests that the decider `D` rejects, with an error instead of panicking, the proofs obtained
+    /// by applying each of the given `corruptions` to the valid `proof`, and the valid `proof`
+    /// against corrupted versions of its statement `(i, z_0, z_i, running_commitments,
+    /// incoming_commitments)`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn test_corrupted_decider_proof<C1, C2, FC, FS, D>(
+        vp: D::VerifierParam,
+        i: C1::ScalarField,
+        z_0: Vec<C1::ScalarField>,
+        z_i: Vec<C1::ScalarField>,
+        running_commitments: Vec<C1>,
+        incoming_commitments: Vec<C1>,
+        proof: &D::Proof,
+        corruptions: Vec<fn(&mut D::Proof)>,
+    ) where
+        C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+        C2: CurveGroup,
+        C2::BaseField: PrimeField,
+        FC: FCircuit<C1::ScalarField>,
+        FS: FoldingScheme<C1, C2, FC>,
+        D: Decider<C1, C2, FC, FS, CommittedInstance = Vec<C1>>,
+        D::VerifierParam: Clone,
+        D::Proof: Clone,
+    {
+        type Statement<C> = (
+            <C as Group>::ScalarField,
+            Vec<<C as Group>::ScalarField>,
+            Vec<<C as Group>::ScalarField>,
+            Vec<C>,
+            Vec<C>,
+        );
+        let statement: Statement<C1> = (i, z_0, z_i, running_commitments, incoming_commitments);
+        let verify = |statement: &Statement<C1>, proof: &D::Proof| {
+            let (i, z_0, z_i, U, u) = statement.clone();
+            catch_unwind(AssertUnwindSafe(|| {
+                D::verify(vp.clone(), i, z_0, z_i, &U, &u, proof)
+            }))
+        };
+        assert!(matches!(verify(&statement, proof), Ok(Ok(true))));
+
+        let statement_corruptions: Vec<fn(&mut Statement<C1>)> = vec![
+            |s| s.0 = C1::ScalarField::one(),
+            |s| s.0 += C1::ScalarField::one(),
+            |s| s.1.clear(),
+            |s| s.1.push(C1::ScalarField::one()),
+            |s| s.2[0] += C1::ScalarField::one(),
+            |s| s.2.push(C1::ScalarField::one()),
+            |s| {
+                s.3.pop();
+            },
+            |s| s.3.push(C1::generator()),
+            |s| s.3[0] += C1::generator(),
+            |s| s.4.clear(),
+            |s| s.4.push(C1::generator()),
+        ];
+        for corrupt in statement_corruptions {
+            let mut corrupted_statement = statement.clone();
+            corrupt(&mut corrupted_statement);
+            assert!(matches!(
+                verify(&corrupted_statement, proof),
+                Ok(Err(_)) | Ok(Ok(false))
+            ));
+        }
+
+        for corrupt in corruptions {
+            let mut corrupted_proof = proof.clone();
+            corrupt(&mut corrupted_proof);
+            assert!(matches!(
+                verify(&statement, &corrupted_proof),
+                Ok(Err(_)) | Ok(Ok(false))
+            ));
+        }
     }
 
     fn test_serialize_ivc_opt<
@@ -84,7 +397,8 @@ pub mod tests {
         // perform multiple IVC steps (internally folding)
         let num_steps: usize = 3;
         for _ in 0..num_steps {
-            fs.prove_step(&mut rng, vec![], None).unwrap();
+            fs.prove_step(&mut rng, FC::ExternalInputs::default(), None)
+                .unwrap();
         }
 
         // verify the IVCProof
@@ -110,7 +424,10 @@ pub mod tests {
         FS::verify(fs_params.1.clone(), deserialized_ivc_proof.clone()).unwrap();
 
         // build the FS from the given IVCProof, FC::Params, ProverParams and VerifierParams
-        let mut new_fs = FS::from_ivc_proof(deserialized_ivc_proof, (), fs_params.clone()).unwrap();
+        let mut new_fs =
+            FS::from_ivc_proof(deserialized_ivc_proof.clone(), (), fs_params.clone()).unwrap();
+        // which returns the same IVCProof
+        assert_eq!(new_fs.ivc_proof(), deserialized_ivc_proof);
 
         // serialize the Nova params
         let mut fs_pp_serialized = vec![];
@@ -137,8 +454,11 @@ pub mod tests {
         // serialization new FS instance
         let num_steps: usize = 3;
         for _ in 0..num_steps {
-            new_fs.prove_step(&mut rng, vec![], None).unwrap();
-            fs.prove_step(&mut rng, vec![], None).unwrap();
+            new_fs
+                .prove_step(&mut rng, FC::ExternalInputs::default(), None)
+                .unwrap();
+            fs.prove_step(&mut rng, FC::ExternalInputs::default(), None)
+                .unwrap();
         }
 
         // check that the IVCProofs from both FS instances are equal
@@ -169,4 +489,44 @@ pub mod tests {
 
         Ok(())
     }
+
+    /// tests that the IVCProof of the FCircuit `FC` is rejected, both by `from_ivc_proof` and by
+    /// `verify`, by the folding scheme of the FCircuit `FCOther`, which has the same state length
+    /// but different R1CS dimensions, when it is read from the serialized IVCProof.
+    fn test_ivc_proof_of_other_circuit<
+        C1: CurveGroup,
+        C2: CurveGroup,
+        FC: FCircuit<C1::ScalarField, Params = ()>,
+        FCOther: FCircuit<C1::ScalarField>,
+        FS: FoldingScheme<C1, C2, FC>,
+        FSOther: FoldingScheme<C1, C2, FCOther>,
+    >(
+        prep_param: FS::PreprocessorParam,
+        other_prep_param: FSOther::PreprocessorParam,
+        other_fc_params: FCOther::Params,
+    ) where
+        C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+        C2::BaseField: PrimeField,
+    {
+        let mut rng = ark_std::test_rng();
+        let fs_params = FS::preprocess(&mut rng, &prep_param).unwrap();
+        let other_fs_params = FSOther::preprocess(&mut rng, &other_prep_param).unwrap();
+
+        let z_0 = vec![C1::ScalarField::from(3_u32)];
+        let mut fs = FS::init(&fs_params, FC::new(()).unwrap(), z_0).unwrap();
+        for _ in 0..2 {
+            fs.prove_step(&mut rng, FC::ExternalInputs::default(), None)
+                .unwrap();
+        }
+
+        let mut bytes = vec![];
+        fs.ivc_proof().serialize_compressed(&mut bytes).unwrap();
+        let ivc_proof = FSOther::IVCProof::deserialize_compressed(bytes.as_slice()).unwrap();
+
+        assert!(FSOther::verify(other_fs_params.1.clone(), ivc_proof.clone()).is_err());
+        assert!(matches!(
+            FSOther::from_ivc_proof(ivc_proof, other_fc_params, other_fs_params),
+            Err(Error::NotSameLength(..))
+        ));
+    }
 }
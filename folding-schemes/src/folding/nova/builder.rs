@@ -0,0 +1,300 @@
+//! Builder of the preprocessing parameters of Nova and HyperNova, see [`NovaBuilder`].
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use ark_std::{format, rand::RngCore, string::ToString, vec::Vec};
+use core::marker::PhantomData;
+
+use super::PreprocessorParam;
+use crate::commitment::CommitmentScheme;
+use crate::frontend::FCircuit;
+use crate::transcript::poseidon::poseidon_canonical_config;
+use crate::{Error, FoldingScheme};
+
+/// Builder of the [`PreprocessorParam`] of the folding scheme `FS` (Nova or HyperNova), obtained
+/// through `Nova::builder` or `HyperNova::builder`, which can also run the preprocessing of `FS`.
+///
+/// The FCircuit is the only required piece, so it is taken by the constructor. The rest of the
+/// options default to the canonical Poseidon config and to commitment scheme parameters
+/// generated by `preprocess`, and their consistency is checked by `build`.
+#[derive(Debug, Clone)]
+pub struct NovaBuilder<FS, C1, C2, FC, CS1, CS2, const H: bool>
+where
+    C1: CurveGroup,
+    C2: CurveGroup,
+    FC: FCircuit<C1::ScalarField>,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+{
+    F: FC,
+    poseidon_config: Option<PoseidonConfig<C1::ScalarField>>,
+    cs_params: Option<(CS1::ProverParams, CS1::VerifierParams)>,
+    cf_cs_params: Option<(CS2::ProverParams, CS2::VerifierParams)>,
+    domain_tag: Option<Vec<u8>>,
+    hiding: Option<bool>,
+    public_pp_hash: bool,
+    _fs: PhantomData<FS>,
+}
+
+impl<FS, C1, C2, FC, CS1, CS2, const H: bool> NovaBuilder<FS, C1, C2, FC, CS1, CS2, H>
+where
+    C1: CurveGroup,
+    C2: CurveGroup,
+    FC: FCircuit<C1::ScalarField>,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+{
+    pub fn new(F: FC) -> Self {
+        Self {
+            F,
+            poseidon_config: None,
+            cs_params: None,
+            cf_cs_params: None,
+            domain_tag: None,
+            hiding: None,
+            public_pp_hash: false,
+            _fs: PhantomData,
+        }
+    }
+
+    /// Sets the Poseidon config, which defaults to `poseidon_canonical_config`.
+    pub fn poseidon(mut self, poseidon_config: PoseidonConfig<C1::ScalarField>) -> Self {
+        self.poseidon_config = Some(poseidon_config);
+        self
+    }
+
+    /// Sets the commitment scheme parameters over C1 and C2, which are otherwise generated by
+    /// `preprocess`. See `PreprocessorParam::with_cs_params`.
+    pub fn commitment_params(
+        mut self,
+        cs_params: (CS1::ProverParams, CS1::VerifierParams),
+        cf_cs_params: (CS2::ProverParams, CS2::VerifierParams),
+    ) -> Self {
+        self.cs_params = Some(cs_params);
+        self.cf_cs_params = Some(cf_cs_params);
+        self
+    }
+
+    /// Sets the domain-separation tag from which the commitment scheme parameters are derived by
+    /// `preprocess`. See `PreprocessorParam::with_domain_tag`.
+    pub fn domain_tag(mut self, domain_tag: &[u8]) -> Self {
+        self.domain_tag = Some(domain_tag.to_vec());
+        self
+    }
+
+    /// Sets whether the commitments are expected to be hiding, which is fixed by the `H`
+    /// parameter of the folding scheme, so that `build` fails if they do not match.
+    pub fn hiding(mut self, hiding: bool) -> Self {
+        self.hiding = Some(hiding);
+        self
+    }
+
+    /// Exposes `pp_hash` as a public input of the AugmentedFCircuit, which only Nova supports.
+    /// See `PreprocessorParam::with_public_pp_hash`.
+    pub fn public_pp_hash(mut self) -> Self {
+        self.public_pp_hash = true;
+        self
+    }
+
+    /// Returns the `PreprocessorParam` with the options set so far, without checking them.
+    pub(super) fn into_param(self) -> PreprocessorParam<C1, C2, FC, CS1, CS2, H> {
+        let (cs_pp, cs_vp) = self.cs_params.unzip();
+        let (cf_cs_pp, cf_cs_vp) = self.cf_cs_params.unzip();
+        PreprocessorParam {
+            poseidon_config: self
+                .poseidon_config
+                .unwrap_or_else(poseidon_canonical_config::<C1::ScalarField>),
+            F: self.F,
+            cs_pp,
+            cs_vp,
+            cf_cs_pp,
+            cf_cs_vp,
+            domain_tag: self.domain_tag,
+            public_pp_hash: self.public_pp_hash,
+        }
+    }
+
+    /// Checks that the options are consistent with each other and with the folding scheme, and
+    /// returns the resulting `PreprocessorParam`.
+    pub fn build(self) -> Result<PreprocessorParam<C1, C2, FC, CS1, CS2, H>, Error> {
+        if let Some(hiding) = self.hiding {
+            if hiding != H {
+                return Err(Error::ParamsMismatch(format!(
+                    "hiding is set to {}, but the commitment schemes of the folding scheme are {}",
+                    hiding,
+                    if H { "hiding" } else { "not hiding" }
+                )));
+            }
+        }
+        if self.domain_tag.is_some() && (self.cs_params.is_some() || self.cf_cs_params.is_some()) {
+            return Err(Error::ParamsMismatch(
+                "both the commitment params and the domain tag to derive them are set".to_string(),
+            ));
+        }
+        Ok(self.into_param())
+    }
+
+    /// Builds the `PreprocessorParam` and runs the preprocessing of the folding scheme with them,
+    /// returning its prover and verifier params.
+    #[allow(clippy::type_complexity)]
+    pub fn build_preprocess(
+        self,
+        rng: impl RngCore,
+    ) -> Result<(FS::ProverParam, FS::VerifierParam), Error>
+    where
+        C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+        C2::BaseField: PrimeField,
+        FS: FoldingScheme<
+            C1,
+            C2,
+            FC,
+            PreprocessorParam = PreprocessorParam<C1, C2, FC, CS1, CS2, H>,
+        >,
+    {
+        FS::preprocess(rng, &self.build()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::{constraints::GVar, Fr, G1Projective as Projective};
+    use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+
+    use super::*;
+    use crate::commitment::pedersen::Pedersen;
+    use crate::folding::hypernova::HyperNova;
+    use crate::folding::nova::Nova;
+    use crate::frontend::utils::CubicFCircuit;
+
+    type N<const H: bool> = Nova<
+        Projective,
+        GVar,
+        Projective2,
+        GVar2,
+        CubicFCircuit<Fr>,
+        Pedersen<Projective, H>,
+        Pedersen<Projective2, H>,
+        H,
+    >;
+
+    #[test]
+    fn test_builder_default() {
+        let mut rng = ark_std::test_rng();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+
+        let prep_param = N::<false>::builder(F_circuit).build().unwrap();
+        assert_eq!(
+            prep_param.poseidon_config.full_rounds,
+            poseidon_canonical_config::<Fr>().full_rounds
+        );
+        assert!(prep_param.cs_pp.is_none() && prep_param.cf_cs_pp.is_none());
+        assert!(prep_param.domain_tag.is_none());
+
+        let params = N::<false>::builder(F_circuit)
+            .build_preprocess(&mut rng)
+            .unwrap();
+        let mut nova = N::<false>::init(&params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+        nova.prove_step(&mut rng, (), None).unwrap();
+        N::<false>::verify(params.1, nova.ivc_proof()).unwrap();
+    }
+
+    #[test]
+    fn test_builder_customized() {
+        let mut rng = ark_std::test_rng();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+
+        // take the commitment params of a first preprocess, and set every option of a second one
+        let first_params = N::<true>::builder(F_circuit)
+            .hiding(true)
+            .domain_tag(b"sonobe-builder-test")
+            .build_preprocess(&mut rng)
+            .unwrap();
+        let (cs_pp, cf_cs_pp) = first_params.0.cs_params();
+        let (cs_vp, cf_cs_vp) = first_params.1.cs_params();
+
+        let params = N::<true>::builder(F_circuit)
+            .poseidon(poseidon_canonical_config::<Fr>())
+            .commitment_params(
+                (cs_pp.clone(), cs_vp.clone()),
+                (cf_cs_pp.clone(), cf_cs_vp.clone()),
+            )
+            .hiding(true)
+            .build_preprocess(&mut rng)
+            .unwrap();
+        assert_eq!(params.0.cs_pp, *cs_pp);
+        assert_eq!(params.0.cf_cs_pp, *cf_cs_pp);
+
+        let mut nova = N::<true>::init(&params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+        nova.prove_step(&mut rng, (), None).unwrap();
+        N::<true>::verify(params.1, nova.ivc_proof()).unwrap();
+
+        // the same builder drives HyperNova's preprocess
+        type HN = HyperNova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            1,
+            1,
+            false,
+        >;
+        let params = HN::builder(F_circuit)
+            .domain_tag(b"sonobe-builder-test")
+            .build_preprocess(&mut rng)
+            .unwrap();
+        let mut hypernova = HN::init(&params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+        hypernova.prove_step(&mut rng, (), None).unwrap();
+        HN::verify(params.1, hypernova.ivc_proof()).unwrap();
+
+        // while only Nova supports pp_hash as a public input
+        let params = N::<false>::builder(F_circuit)
+            .public_pp_hash()
+            .build_preprocess(&mut rng)
+            .unwrap();
+        assert!(params.1.public_pp_hash);
+        assert!(matches!(
+            HN::builder(F_circuit)
+                .public_pp_hash()
+                .build_preprocess(&mut rng),
+            Err(Error::NotSupportedYet(_))
+        ));
+    }
+
+    #[test]
+    fn test_builder_inconsistent_options() {
+        let mut rng = ark_std::test_rng();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+
+        // hiding does not match the commitment schemes of the folding scheme
+        assert!(matches!(
+            N::<false>::builder(F_circuit).hiding(true).build(),
+            Err(Error::ParamsMismatch(_))
+        ));
+        assert!(matches!(
+            N::<true>::builder(F_circuit)
+                .hiding(false)
+                .build_preprocess(&mut rng),
+            Err(Error::ParamsMismatch(_))
+        ));
+
+        // commitment params and a domain tag to derive them are exclusive
+        let (pp, vp) = N::<false>::builder(F_circuit)
+            .build_preprocess(&mut rng)
+            .unwrap();
+        let (cs_pp, cf_cs_pp) = pp.cs_params();
+        let (cs_vp, cf_cs_vp) = vp.cs_params();
+        assert!(matches!(
+            N::<false>::builder(F_circuit)
+                .commitment_params(
+                    (cs_pp.clone(), cs_vp.clone()),
+                    (cf_cs_pp.clone(), cf_cs_vp.clone()),
+                )
+                .domain_tag(b"sonobe-builder-test")
+                .build(),
+            Err(Error::ParamsMismatch(_))
+        ));
+    }
+}
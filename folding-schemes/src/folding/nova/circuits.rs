@@ -8,21 +8,17 @@ use ark_ec::{CurveGroup, Group};
 use ark_ff::PrimeField;
 use ark_r1cs_std::{
     alloc::AllocVar,
-    boolean::Boolean,
     eq::EqGadget,
     fields::{fp::FpVar, FieldVar},
     prelude::CurveVar,
     R1CSVar, ToConstraintFieldGadget,
 };
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
-use ark_std::{fmt::Debug, One, Zero};
+use ark_std::{fmt::Debug, vec::Vec, One, Zero};
 use core::marker::PhantomData;
 
 use super::{
-    nifs::{
-        nova_circuits::{CommittedInstanceVar, NIFSGadget},
-        NIFSGadgetTrait,
-    },
+    nifs::nova_circuits::{CommittedInstanceVar, NIFSGadget},
     CommittedInstance, NovaCycleFoldConfig,
 };
 use crate::folding::circuits::{
@@ -34,7 +30,7 @@ use crate::folding::circuits::{
     CF1, CF2,
 };
 use crate::folding::traits::{CommittedInstanceVarOps, Dummy};
-use crate::frontend::FCircuit;
+use crate::frontend::{step_constraints, FCircuit};
 use crate::transcript::AbsorbNonNativeGadget;
 
 /// `AugmentedFCircuit` enhances the original step function `F`, so that it can
@@ -49,21 +45,31 @@ use crate::transcript::AbsorbNonNativeGadget;
 /// Furthermore, to reduce circuit size over `C2`, we implement the constraints
 /// defined in [CycleFold](https://eprint.iacr.org/2023/1192.pdf). These extra
 /// constraints verify the correct folding of CycleFold instances.
+///
+/// The folding challenge is a full scalar field element when `FULL_CHALLENGE` is set, see
+/// [`Nova`](super::Nova).
+///
+/// By default `pp_hash` is a witness, bound to the instances only through the hashes of
+/// `u_{i+1}.x`. When `public_pp_hash` is set, it is also exposed as a third public input, so that
+/// `u_{i+1}.x = [H(i+1, z_0, z_{i+1}, U_{i+1}), H(cf_U_{i+1}), pp_hash]`, see
+/// [`augmented_io_len`].
 #[derive(Debug, Clone)]
 pub struct AugmentedFCircuit<
     C1: CurveGroup,
     C2: CurveGroup,
     GC2: CurveVar<C2, CF2<C2>>,
     FC: FCircuit<CF1<C1>>,
+    const FULL_CHALLENGE: bool = false,
 > {
     pub(super) _gc2: PhantomData<GC2>,
     pub(super) poseidon_config: PoseidonConfig<CF1<C1>>,
     pub(super) pp_hash: Option<CF1<C1>>,
+    pub(super) context: Option<CF1<C1>>,
     pub(super) i: Option<CF1<C1>>,
     pub(super) i_usize: Option<usize>,
     pub(super) z_0: Option<Vec<C1::ScalarField>>,
     pub(super) z_i: Option<Vec<C1::ScalarField>>,
-    pub(super) external_inputs: Option<Vec<C1::ScalarField>>,
+    pub(super) external_inputs: Option<FC::ExternalInputs>,
     pub(super) u_i_cmW: Option<C1>,
     pub(super) U_i: Option<CommittedInstance<C1>>,
     pub(super) U_i1_cmE: Option<C1>,
@@ -81,16 +87,35 @@ pub struct AugmentedFCircuit<
     pub(super) cf1_cmT: Option<C2>,
     pub(super) cf2_cmT: Option<C2>,
     pub(super) cf_x: Option<CF1<C1>>, // public input (u_{i+1}.x[1])
+
+    // whether pp_hash is exposed as the public input u_{i+1}.x[2]
+    pub(super) public_pp_hash: bool,
+}
+
+/// Returns the number of public inputs of the `AugmentedFCircuit`, i.e. the length of `x` of its
+/// committed instances, which is 3 when `pp_hash` is exposed as a public input and 2 otherwise.
+pub const fn augmented_io_len(public_pp_hash: bool) -> usize {
+    if public_pp_hash {
+        3
+    } else {
+        2
+    }
 }
 
-impl<C1: CurveGroup, C2: CurveGroup, GC2: CurveVar<C2, CF2<C2>>, FC: FCircuit<CF1<C1>>>
-    AugmentedFCircuit<C1, C2, GC2, FC>
+impl<
+        C1: CurveGroup,
+        C2: CurveGroup,
+        GC2: CurveVar<C2, CF2<C2>>,
+        FC: FCircuit<CF1<C1>>,
+        const FULL_CHALLENGE: bool,
+    > AugmentedFCircuit<C1, C2, GC2, FC, FULL_CHALLENGE>
 {
     pub fn empty(poseidon_config: &PoseidonConfig<CF1<C1>>, F_circuit: FC) -> Self {
         Self {
             _gc2: PhantomData,
             poseidon_config: poseidon_config.clone(),
             pp_hash: None,
+            context: None,
             i: None,
             i_usize: None,
             z_0: None,
@@ -110,11 +135,19 @@ impl<C1: CurveGroup, C2: CurveGroup, GC2: CurveVar<C2, CF2<C2>>, FC: FCircuit<CF
             cf1_cmT: None,
             cf2_cmT: None,
             cf_x: None,
+            public_pp_hash: false,
         }
     }
+
+    /// Sets whether `pp_hash` is exposed as a public input of the circuit.
+    pub fn with_public_pp_hash(mut self, public_pp_hash: bool) -> Self {
+        self.public_pp_hash = public_pp_hash;
+        self
+    }
 }
 
-impl<C1, C2, GC2, FC> ConstraintSynthesizer<CF1<C1>> for AugmentedFCircuit<C1, C2, GC2, FC>
+impl<C1, C2, GC2, FC, const FULL_CHALLENGE: bool> ConstraintSynthesizer<CF1<C1>>
+    for AugmentedFCircuit<C1, C2, GC2, FC, FULL_CHALLENGE>
 where
     C1: CurveGroup,
     C2: CurveGroup,
@@ -130,6 +163,9 @@ where
         let pp_hash = FpVar::<CF1<C1>>::new_witness(cs.clone(), || {
             Ok(self.pp_hash.unwrap_or_else(CF1::<C1>::zero))
         })?;
+        let context = FpVar::<CF1<C1>>::new_witness(cs.clone(), || {
+            Ok(self.context.unwrap_or_else(CF1::<C1>::zero))
+        })?;
         let i = FpVar::<CF1<C1>>::new_witness(cs.clone(), || {
             Ok(self.i.unwrap_or_else(CF1::<C1>::zero))
         })?;
@@ -143,13 +179,11 @@ where
                 .z_i
                 .unwrap_or(vec![CF1::<C1>::zero(); self.F.state_len()]))
         })?;
-        let external_inputs = Vec::<FpVar<CF1<C1>>>::new_witness(cs.clone(), || {
-            Ok(self
-                .external_inputs
-                .unwrap_or(vec![CF1::<C1>::zero(); self.F.external_inputs_len()]))
+        let external_inputs = FC::ExternalInputsVar::new_witness(cs.clone(), || {
+            Ok(self.external_inputs.unwrap_or_default())
         })?;
 
-        let u_dummy = CommittedInstance::dummy(2);
+        let u_dummy = CommittedInstance::dummy(augmented_io_len(self.public_pp_hash));
         let U_i = CommittedInstanceVar::<C1>::new_witness(cs.clone(), || {
             Ok(self.U_i.unwrap_or(u_dummy.clone()))
         })?;
@@ -163,7 +197,8 @@ where
         let cmT =
             NonNativeAffineVar::new_witness(cs.clone(), || Ok(self.cmT.unwrap_or_else(C1::zero)))?;
 
-        let cf_u_dummy = CycleFoldCommittedInstance::dummy(NovaCycleFoldConfig::<C1>::IO_LEN);
+        let cf_u_dummy =
+            CycleFoldCommittedInstance::dummy(NovaCycleFoldConfig::<C1, FULL_CHALLENGE>::IO_LEN);
         let cf_U_i = CycleFoldCommittedInstanceVar::<C2, GC2>::new_witness(cs.clone(), || {
             Ok(self.cf_U_i.unwrap_or(cf_u_dummy.clone()))
         })?;
@@ -180,7 +215,9 @@ where
         // Primary Part
         // P.1. Compute u_i.x
         // u_i.x[0] = H(i, z_0, z_i, U_i)
-        let (u_i_x, U_i_vec) = U_i.clone().hash(&sponge, &pp_hash, &i, &z_0, &z_i)?;
+        let (u_i_x, U_i_vec) =
+            U_i.clone()
+                .hash_with_context(&sponge, &pp_hash, Some(&context), &i, &z_0, &z_i)?;
         // u_i.x[1] = H(cf_U_i)
         let (cf_u_i_x, cf_U_i_vec) = cf_U_i.clone().hash(&sponge, pp_hash.clone())?;
 
@@ -194,8 +231,12 @@ where
             cmW: NonNativeAffineVar::new_witness(cs.clone(), || {
                 Ok(self.u_i_cmW.unwrap_or(C1::zero()))
             })?,
-            // u_i.x is computed in step 1
-            x: vec![u_i_x, cf_u_i_x],
+            // u_i.x is computed in step 1, followed by pp_hash if it is a public input
+            x: if self.public_pp_hash {
+                vec![u_i_x, cf_u_i_x, pp_hash.clone()]
+            } else {
+                vec![u_i_x, cf_u_i_x]
+            },
         };
 
         // P.3. nifs.verify, obtains U_{i+1} by folding u_i & U_i.
@@ -207,7 +248,7 @@ where
             C1,
             PoseidonSponge<C1::ScalarField>,
             PoseidonSpongeVar<C1::ScalarField>,
-        >::verify(
+        >::verify_opt::<FULL_CHALLENGE>(
             &mut transcript,
             pp_hash.clone(),
             U_i.clone(),
@@ -218,54 +259,46 @@ where
         U_i1.cmE = U_i1_cmE;
         U_i1.cmW = U_i1_cmW;
 
-        // convert r_bits to a `NonNativeFieldVar`
-        let r_nonnat = {
-            let mut bits = r_bits;
-            bits.resize(C1::BaseField::MODULUS_BIT_SIZE as usize, Boolean::FALSE);
-            NonNativeUintVar::from(&bits)
-        };
-
         // P.4.a compute and check the first output of F'
 
         // get z_{i+1} from the F circuit
         let i_usize = self.i_usize.unwrap_or(0);
-        let z_i1 = self
-            .F
-            .generate_step_constraints(cs.clone(), i_usize, z_i, external_inputs)?;
+        let z_i1 = step_constraints(&self.F, cs.clone(), i_usize, &i, z_i, external_inputs)?;
 
         // Base case: u_{i+1}.x[0] == H((i+1, z_0, z_{i+1}, U_{\bot})
         // Non-base case: u_{i+1}.x[0] == H((i+1, z_0, z_{i+1}, U_{i+1})
-        let (u_i1_x, _) = U_i1.clone().hash(
+        let (u_i1_x, _) = U_i1.clone().hash_with_context(
             &sponge,
             &pp_hash,
+            Some(&context),
             &(i + FpVar::<CF1<C1>>::one()),
             &z_0,
             &z_i1,
         )?;
-        let (u_i1_x_base, _) = CommittedInstanceVar::new_constant(cs.clone(), u_dummy)?.hash(
-            &sponge,
-            &pp_hash,
-            &FpVar::<CF1<C1>>::one(),
-            &z_0,
-            &z_i1,
-        )?;
+        let (u_i1_x_base, _) = CommittedInstanceVar::new_constant(cs.clone(), u_dummy)?
+            .hash_with_context(
+                &sponge,
+                &pp_hash,
+                Some(&context),
+                &FpVar::<CF1<C1>>::one(),
+                &z_0,
+                &z_i1,
+            )?;
         let x = FpVar::new_input(cs.clone(), || Ok(self.x.unwrap_or(u_i1_x_base.value()?)))?;
         x.enforce_equal(&is_basecase.select(&u_i1_x_base, &u_i1_x)?)?;
 
         // CycleFold part
         // C.1. Compute cf1_u_i.x and cf2_u_i.x
-        let cfW_x = vec![
-            r_nonnat.clone(),
-            U_i.cmW.x,
-            U_i.cmW.y,
-            u_i.cmW.x,
-            u_i.cmW.y,
-            U_i1.cmW.x,
-            U_i1.cmW.y,
-        ];
-        let cfE_x = vec![
-            r_nonnat, U_i.cmE.x, U_i.cmE.y, cmT.x, cmT.y, U_i1.cmE.x, U_i1.cmE.y,
-        ];
+        let cfW_x = NovaCycleFoldConfig::<C1, FULL_CHALLENGE>::x_gadget(
+            &r_bits,
+            &[U_i.cmW.clone(), u_i.cmW.clone()],
+            &U_i1.cmW,
+        )?;
+        let cfE_x = NovaCycleFoldConfig::<C1, FULL_CHALLENGE>::x_gadget(
+            &r_bits,
+            &[U_i.cmE.clone(), cmT],
+            &U_i1.cmE,
+        )?;
 
         // ensure that cf1_u & cf2_u have as public inputs the cmW & cmE from main instances U_i,
         // u_i, U_i+1 coordinates of the commitments
@@ -298,6 +331,7 @@ where
         // cf_r_bits is denoted by rho* in the paper.
         let cf1_r_bits = CycleFoldChallengeGadget::<C2, GC2>::get_challenge_gadget(
             &mut transcript,
+            NovaCycleFoldConfig::<C1, FULL_CHALLENGE>::CHALLENGE_BIT_LENGTH,
             pp_hash.clone(),
             cf_U_i_vec,
             cf1_u_i.clone(),
@@ -311,6 +345,7 @@ where
         // same for cf2_r:
         let cf2_r_bits = CycleFoldChallengeGadget::<C2, GC2>::get_challenge_gadget(
             &mut transcript,
+            NovaCycleFoldConfig::<C1, FULL_CHALLENGE>::CHALLENGE_BIT_LENGTH,
             pp_hash.clone(),
             cf1_U_i1.to_native_sponge_field_elements()?,
             cf2_u_i.clone(),
@@ -328,12 +363,18 @@ where
         let (cf_u_i1_x, _) = cf_U_i1.clone().hash(&sponge, pp_hash.clone())?;
         let (cf_u_i1_x_base, _) =
             CycleFoldCommittedInstanceVar::<C2, GC2>::new_constant(cs.clone(), cf_u_dummy)?
-                .hash(&sponge, pp_hash)?;
+                .hash(&sponge, pp_hash.clone())?;
         let cf_x = FpVar::new_input(cs.clone(), || {
             Ok(self.cf_x.unwrap_or(cf_u_i1_x_base.value()?))
         })?;
         cf_x.enforce_equal(&is_basecase.select(&cf_u_i1_x_base, &cf_u_i1_x)?)?;
 
+        // u_{i+1}.x[2] == pp_hash
+        if self.public_pp_hash {
+            let pp_hash_x = FpVar::new_input(cs.clone(), || pp_hash.value())?;
+            pp_hash_x.enforce_equal(&pp_hash)?;
+        }
+
         Ok(())
     }
 }
@@ -344,18 +385,58 @@ pub mod tests {
     use ark_bn254::{Fr, G1Projective as Projective};
     use ark_crypto_primitives::sponge::{poseidon::PoseidonSponge, CryptographicSponge};
     use ark_ff::BigInteger;
+    use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+    use ark_r1cs_std::boolean::Boolean;
     use ark_relations::r1cs::ConstraintSystem;
     use ark_std::UniformRand;
 
-    use crate::folding::nova::nifs::nova::ChallengeGadget;
+    use crate::folding::nova::nifs::nova::{challenge_bit_length, ChallengeGadget};
+    use crate::folding::traits::CommittedInstanceOps;
+    use crate::frontend::{
+        tests::{capture_logs, WRONG_STATE_LEN_LOG},
+        utils::{StepConstantsFCircuit, WrongStateLenFCircuit},
+    };
+    use crate::transcript::keccak::{Keccak256Transcript, Keccak256TranscriptVar};
     use crate::transcript::poseidon::poseidon_canonical_config;
+    use crate::transcript::{Transcript, TranscriptVar};
 
-    // checks that the gadget and native implementations of the challenge computation match
+    // checks that the gadget and native implementations of the challenge computation match, for
+    // both the truncated and the full-width challenges
     #[test]
     fn test_challenge_gadget() {
-        let mut rng = ark_std::test_rng();
         let poseidon_config = poseidon_canonical_config::<Fr>();
-        let mut transcript = PoseidonSponge::<Fr>::new(&poseidon_config);
+        check_challenge_gadget::<_, PoseidonSpongeVar<Fr>, false>(
+            PoseidonSponge::<Fr>::new(&poseidon_config),
+            &poseidon_config,
+        );
+        check_challenge_gadget::<_, PoseidonSpongeVar<Fr>, true>(
+            PoseidonSponge::<Fr>::new(&poseidon_config),
+            &poseidon_config,
+        );
+    }
+
+    // same as `test_challenge_gadget`, but with the Keccak256 transcript
+    #[test]
+    fn test_challenge_gadget_keccak() {
+        check_challenge_gadget::<_, Keccak256TranscriptVar<Fr>, false>(
+            Keccak256Transcript::<Fr>::new(&()),
+            &(),
+        );
+        check_challenge_gadget::<_, Keccak256TranscriptVar<Fr>, true>(
+            Keccak256Transcript::<Fr>::new(&()),
+            &(),
+        );
+    }
+
+    fn check_challenge_gadget<
+        S: Transcript<Fr>,
+        T: TranscriptVar<Fr, S>,
+        const FULL_CHALLENGE: bool,
+    >(
+        mut transcript: S,
+        transcript_var_params: &T::Parameters,
+    ) {
+        let mut rng = ark_std::test_rng();
 
         let u_i = CommittedInstance::<Projective> {
             cmE: Projective::rand(&mut rng),
@@ -375,13 +456,14 @@ pub mod tests {
 
         // compute the challenge natively
         let r_bits =
-            ChallengeGadget::<Projective, CommittedInstance<Projective>>::get_challenge_native(
-                &mut transcript,
-                pp_hash,
-                &U_i,
-                &u_i,
-                Some(&cmT),
-            );
+            ChallengeGadget::<Projective, CommittedInstance<Projective>>::get_challenge_native_opt::<
+                FULL_CHALLENGE,
+                S,
+            >(&mut transcript, pp_hash, &U_i, &u_i, Some(&cmT));
+        assert_eq!(
+            r_bits.len(),
+            challenge_bit_length::<Projective, FULL_CHALLENGE>()
+        );
         let r = Fr::from_bigint(BigInteger::from_bits_le(&r_bits)).unwrap();
 
         let cs = ConstraintSystem::<Fr>::new_ref();
@@ -393,7 +475,7 @@ pub mod tests {
             CommittedInstanceVar::<Projective>::new_witness(cs.clone(), || Ok(U_i.clone()))
                 .unwrap();
         let cmTVar = NonNativeAffineVar::<Projective>::new_witness(cs.clone(), || Ok(cmT)).unwrap();
-        let mut transcriptVar = PoseidonSpongeVar::<Fr>::new(cs.clone(), &poseidon_config);
+        let mut transcriptVar = T::new(cs.clone(), transcript_var_params);
 
         // compute the challenge in-circuit
         let U_iVar_vec = [
@@ -403,15 +485,13 @@ pub mod tests {
             U_iVar.cmW.to_constraint_field().unwrap(),
         ]
         .concat();
-        let r_bitsVar =
-            ChallengeGadget::<Projective, CommittedInstance<Projective>>::get_challenge_gadget(
-                &mut transcriptVar,
-                pp_hashVar,
-                U_iVar_vec,
-                u_iVar,
-                Some(cmTVar),
-            )
-            .unwrap();
+        let r_bitsVar = ChallengeGadget::<Projective, CommittedInstance<Projective>>::get_challenge_gadget_opt::<
+            FULL_CHALLENGE,
+            S,
+            T,
+            CommittedInstanceVar<Projective>,
+        >(&mut transcriptVar, pp_hashVar, U_iVar_vec, u_iVar, Some(cmTVar))
+        .unwrap();
         assert!(cs.is_satisfied().unwrap());
 
         // check that the natively computed and in-circuit computed hashes match
@@ -419,4 +499,77 @@ pub mod tests {
         assert_eq!(rVar.value().unwrap(), r);
         assert_eq!(r_bitsVar.value().unwrap(), r_bits);
     }
+
+    // checks that an FCircuit whose step returns a state of a wrong length makes the synthesis of
+    // the AugmentedFCircuit fail
+    #[test]
+    fn test_augmented_f_circuit_wrong_state_len() {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let F_circuit = WrongStateLenFCircuit::<Fr>::new(()).unwrap();
+        let augmented_F_circuit =
+            AugmentedFCircuit::<Projective, Projective2, GVar2, WrongStateLenFCircuit<Fr>>::empty(
+                &poseidon_canonical_config::<Fr>(),
+                F_circuit,
+            );
+        let (result, logs) = capture_logs(|| augmented_F_circuit.generate_constraints(cs));
+        assert!(matches!(result, Err(SynthesisError::Unsatisfiable)));
+        assert_eq!(logs, vec![WRONG_STATE_LEN_LOG]);
+    }
+
+    // checks that the AugmentedFCircuit is not satisfied by a step computed with other constants
+    // than the row of the per-step constants selected from the step counter, for the same `i`
+    #[test]
+    fn test_augmented_f_circuit_wrong_step_constants() {
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let sponge = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let F_circuit = StepConstantsFCircuit::<Fr>::new(16).unwrap();
+        let pp_hash = Fr::from(42_u32);
+        let context = Fr::from(7_u32);
+        let z_0 = vec![Fr::from(3_u32)];
+        let U_dummy = CommittedInstance::<Projective>::dummy(augmented_io_len(false));
+
+        // base case circuit, whose output is computed from the given z_1
+        let augmented_F_circuit = |i_usize: usize, z_1: &[Fr]| {
+            let mut circuit = AugmentedFCircuit::<
+                Projective,
+                Projective2,
+                GVar2,
+                StepConstantsFCircuit<Fr>,
+            >::empty(&poseidon_config, F_circuit);
+            circuit.pp_hash = Some(pp_hash);
+            circuit.context = Some(context);
+            circuit.i = Some(Fr::zero());
+            circuit.i_usize = Some(i_usize);
+            circuit.z_0 = Some(z_0.clone());
+            circuit.z_i = Some(z_0.clone());
+            circuit.x = Some(U_dummy.hash_with_context(
+                &sponge,
+                pp_hash,
+                Some(context),
+                Fr::one(),
+                &z_0,
+                z_1,
+            ));
+            circuit
+        };
+
+        // the honest step uses the constants of the row 0
+        let z_1 = F_circuit.step_native(0, z_0.clone(), ()).unwrap();
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        augmented_F_circuit(0, &z_1)
+            .generate_constraints(cs.clone())
+            .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // a step computed with the constants of the row 1 at the step 0 is rejected, even if the
+        // prover also passes the index 1 to the FCircuit, since the row is selected in-circuit
+        // from the step counter
+        let z_1_forged = F_circuit.step_native(1, z_0.clone(), ()).unwrap();
+        assert_ne!(z_1, z_1_forged);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        augmented_F_circuit(1, &z_1_forged)
+            .generate_constraints(cs.clone())
+            .unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
 }
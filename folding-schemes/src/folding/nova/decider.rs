@@ -2,13 +2,23 @@
 /// DeciderEth from decider_eth.rs file.
 /// More details can be found at the documentation page:
 /// https://privacy-scaling-explorations.github.io/sonobe-docs/design/nova-decider-offchain.html
+///
+/// The decider proves a SNARK over each curve of the cycle and opens the commitments with KZG
+/// proofs (`CS1::Proof` and `CS2::Proof` must be `kzg::Proof`), so both curves need pairings.
+/// Cycles without them, such as Pasta, do not implement the `Decider` trait, see the example in
+/// [`Decider`]. For IPA commitments, such as over Pasta, use the Decider from decider_ipa.rs,
+/// which aggregates the openings of each curve into a single IPA proof.
 use ark_crypto_primitives::sponge::Absorb;
 use ark_ec::{CurveGroup, Group};
 use ark_ff::{BigInteger, PrimeField};
 use ark_r1cs_std::{groups::GroupOpsBounds, prelude::CurveVar, ToConstraintFieldGadget};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_snark::SNARK;
-use ark_std::rand::{CryptoRng, RngCore};
+use ark_std::{
+    rand::{CryptoRng, RngCore},
+    string::ToString,
+    vec::Vec,
+};
 use ark_std::{One, Zero};
 use core::marker::PhantomData;
 
@@ -19,6 +29,7 @@ use crate::commitment::CommitmentScheme;
 use crate::folding::circuits::decider::DeciderEnabledNIFS;
 use crate::folding::circuits::{
     cyclefold::{CycleFoldCommittedInstance, CycleFoldCommittedInstanceVar},
+    nonnative::affine::nonnative_affine_to_packed_field_elements,
     CF2,
 };
 use crate::folding::traits::{CommittedInstanceOps, Inputize, WitnessOps};
@@ -27,12 +38,12 @@ use crate::Error;
 use crate::{Decider as DeciderTrait, FoldingScheme};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
-pub struct Proof<C1, C2, CS1, CS2, S1, S2>
+pub struct Proof<C1, C2, CS1, CS2, S1, S2, const H: bool = false>
 where
     C1: CurveGroup,
     C2: CurveGroup,
-    CS1: CommitmentScheme<C1>,
-    CS2: CommitmentScheme<C2>,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
     S1: SNARK<C1::ScalarField>,
     S2: SNARK<C2::ScalarField>,
 {
@@ -76,15 +87,63 @@ where
     S2_VerifyingKey: Clone + CanonicalSerialize + CanonicalDeserialize,
 {
     pub pp_hash: C1::ScalarField,
+    /// application context of the IVC, a public input of `DeciderCircuit1` as in the onchain
+    /// decider, see `decider_eth::VerifierParam::context`.
+    pub context: C1::ScalarField,
     pub c1_snark_vp: S1_VerifyingKey,
     pub c1_cs_vp: CS1_VerifyingKey,
     pub c2_snark_vp: S2_VerifyingKey,
     pub c2_cs_vp: CS2_VerifyingKey,
 }
 
-/// Onchain Decider, for ethereum use cases
+/// Offchain Decider. With hiding commitments (`H = true`), the commitments are opened together
+/// with the blinding factors of the folded witnesses.
+///
+/// It is not available over the Pasta curves, whose IPA openings are not KZG proofs, whichever
+/// SNARKs are used, see `decider_ipa::Decider` instead:
+///
+/// ```compile_fail
+/// use ark_pallas::{constraints::GVar, Fr, Projective};
+/// use ark_snark::SNARK;
+/// use ark_vesta::{constraints::GVar as GVar2, Fr as Fr2, Projective as Projective2};
+/// use folding_schemes::{
+///     commitment::ipa::IPA,
+///     folding::nova::{decider::Decider, Nova},
+///     frontend::utils::CubicFCircuit,
+///     Decider as DeciderTrait,
+/// };
+///
+/// type N = Nova<
+///     Projective,
+///     GVar,
+///     Projective2,
+///     GVar2,
+///     CubicFCircuit<Fr>,
+///     IPA<Projective>,
+///     IPA<Projective2>,
+/// >;
+///
+/// fn is_decider<D: DeciderTrait<Projective, Projective2, CubicFCircuit<Fr>, N>>() {}
+///
+/// fn pasta_decider<S1: SNARK<Fr>, S2: SNARK<Fr2>>() {
+///     is_decider::<
+///         Decider<
+///             Projective,
+///             GVar,
+///             Projective2,
+///             GVar2,
+///             CubicFCircuit<Fr>,
+///             IPA<Projective>,
+///             IPA<Projective2>,
+///             S1,
+///             S2,
+///             N,
+///         >,
+///     >();
+/// }
+/// ```
 #[derive(Clone, Debug)]
-pub struct Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S1, S2, FS> {
+pub struct Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S1, S2, FS, const H: bool = false> {
     _c1: PhantomData<C1>,
     _gc1: PhantomData<GC1>,
     _c2: PhantomData<C2>,
@@ -97,8 +156,8 @@ pub struct Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S1, S2, FS> {
     _fs: PhantomData<FS>,
 }
 
-impl<C1, GC1, C2, GC2, FC, CS1, CS2, S1, S2, FS> DeciderTrait<C1, C2, FC, FS>
-    for Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S1, S2, FS>
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, S1, S2, FS, const H: bool> DeciderTrait<C1, C2, FC, FS>
+    for Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S1, S2, FS, H>
 where
     C1: CurveGroup,
     C2: CurveGroup,
@@ -107,12 +166,14 @@ where
     FC: FCircuit<C1::ScalarField>,
     CS1: CommitmentScheme<
         C1,
+        H,
         ProverChallenge = C1::ScalarField,
         Challenge = C1::ScalarField,
         Proof = crate::commitment::kzg::Proof<C1>,
     >,
     CS2: CommitmentScheme<
         C2,
+        H,
         ProverChallenge = C2::ScalarField,
         Challenge = C2::ScalarField,
         Proof = crate::commitment::kzg::Proof<C2>,
@@ -128,16 +189,16 @@ where
     for<'b> &'b GC1: GroupOpsBounds<'b, C1, GC1>,
     for<'b> &'b GC2: GroupOpsBounds<'b, C2, GC2>,
     // constrain FS into Nova, since this is a Decider specifically for Nova
-    Nova<C1, GC1, C2, GC2, FC, CS1, CS2, false>: From<FS>,
-    crate::folding::nova::ProverParams<C1, C2, CS1, CS2, false>:
+    Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H>: From<FS>,
+    crate::folding::nova::ProverParams<C1, C2, CS1, CS2, H>:
         From<<FS as FoldingScheme<C1, C2, FC>>::ProverParam>,
-    crate::folding::nova::VerifierParams<C1, C2, CS1, CS2, false>:
+    crate::folding::nova::VerifierParams<C1, C2, CS1, CS2, H>:
         From<<FS as FoldingScheme<C1, C2, FC>>::VerifierParam>,
 {
     type PreprocessorParam = (FS::ProverParam, FS::VerifierParam);
     type ProverParam =
         ProverParam<CS1::ProverParams, S1::ProvingKey, CS2::ProverParams, S2::ProvingKey>;
-    type Proof = Proof<C1, C2, CS1, CS2, S1, S2>;
+    type Proof = Proof<C1, C2, CS1, CS2, S1, S2, H>;
     type VerifierParam = VerifierParam<
         C1,
         CS1::VerifierParams,
@@ -153,8 +214,16 @@ where
         prep_param: Self::PreprocessorParam,
         fs: FS,
     ) -> Result<(Self::ProverParam, Self::VerifierParam), Error> {
-        let circuit1 = DeciderCircuit1::<C1, C2, GC2>::try_from(Nova::from(fs.clone()))?;
-        let circuit2 = DeciderCircuit2::<C2>::try_from(Nova::from(fs))?;
+        let nova = Nova::from(fs);
+        // the decider circuits enforce that `u_i.x` only contains the two hashes of the state
+        if nova.public_pp_hash {
+            return Err(Error::NotSupportedYet(
+                "Nova Decider with pp_hash as a public input".to_string(),
+            ));
+        }
+        let circuit1 = DeciderCircuit1::<C1, C2, GC2>::try_from(nova.clone())?;
+        let circuit2 = DeciderCircuit2::<C2>::try_from(nova)?;
+        let context = circuit1.context.unwrap_or_else(C1::ScalarField::zero);
 
         // get the Groth16 specific setup for the circuits
         let (c1_g16_pk, c1_g16_vk) = S1::circuit_specific_setup(circuit1, &mut rng)
@@ -164,13 +233,13 @@ where
 
         // get the FoldingScheme prover & verifier params from Nova
         #[allow(clippy::type_complexity)]
-        let nova_pp: <Nova<C1, GC1, C2, GC2, FC, CS1, CS2, false> as FoldingScheme<
+        let nova_pp: <Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H> as FoldingScheme<
             C1,
             C2,
             FC,
         >>::ProverParam = prep_param.0.clone().into();
         #[allow(clippy::type_complexity)]
-        let nova_vp: <Nova<C1, GC1, C2, GC2, FC, CS1, CS2, false> as FoldingScheme<
+        let nova_vp: <Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H> as FoldingScheme<
             C1,
             C2,
             FC,
@@ -185,6 +254,7 @@ where
         };
         let vp = Self::VerifierParam {
             pp_hash,
+            context,
             c1_snark_vp: c1_g16_vk,
             c1_cs_vp: nova_vp.cs_vp,
             c2_snark_vp: c2_g16_vk,
@@ -198,6 +268,7 @@ where
         pp: Self::ProverParam,
         fs: FS,
     ) -> Result<Self::Proof, Error> {
+        trace_span!("nova_decider_prove");
         let circuit1 = DeciderCircuit1::<C1, C2, GC2>::try_from(Nova::from(fs.clone()))?;
         let circuit2 = DeciderCircuit2::<C2>::try_from(Nova::from(fs))?;
 
@@ -205,15 +276,16 @@ where
         let r = circuit1.randomness;
         let cf_U_final = circuit1.cf_U_i.clone();
 
+        // the commitments are opened with the blinding factors of the folded witnesses, which
+        // are zero if the commitments are not hiding, and the hiding openings are blinded with
+        // randomness from `rng`
         let c1_kzg_challenges = circuit1.kzg_challenges.clone();
         let c1_kzg_proofs = circuit1
             .W_i1
             .get_openings()
             .iter()
             .zip(&c1_kzg_challenges)
-            .map(|((v, _), &c)| {
-                CS1::prove_with_challenge(&pp.c1_cs_pp, c, v, &C1::ScalarField::zero(), None)
-            })
+            .map(|((v, r), &c)| CS1::prove_with_challenge(&pp.c1_cs_pp, c, v, r, Some(&mut rng)))
             .collect::<Result<Vec<_>, _>>()?;
         let c2_kzg_challenges = circuit2.kzg_challenges.clone();
         let c2_kzg_proofs = circuit2
@@ -221,9 +293,7 @@ where
             .get_openings()
             .iter()
             .zip(&c2_kzg_challenges)
-            .map(|((v, _), &c)| {
-                CS2::prove_with_challenge(&pp.c2_cs_pp, c, v, &C2::ScalarField::zero(), None)
-            })
+            .map(|((v, r), &c)| CS2::prove_with_challenge(&pp.c2_cs_pp, c, v, r, Some(&mut rng)))
             .collect::<Result<Vec<_>, _>>()?;
 
         let c1_snark_proof = S1::prove(&pp.c1_snark_pp, circuit1, &mut rng)
@@ -262,6 +332,7 @@ where
         incoming_commitments: &Self::CommittedInstance,
         proof: &Self::Proof,
     ) -> Result<bool, Error> {
+        trace_span!("nova_decider_verify");
         if i <= C1::ScalarField::one() {
             return Err(Error::NotEnoughSteps);
         }
@@ -277,7 +348,7 @@ where
 
         // snark proof 1
         let c1_public_input = [
-            &[vp.pp_hash, i][..],
+            &[vp.pp_hash, vp.context, i][..],
             &z_0,
             &z_i,
             &U_final_commitments
@@ -287,7 +358,7 @@ where
             &Inputize::<CF2<C2>, CycleFoldCommittedInstanceVar<C2, GC2>>::inputize(&cf_U),
             &proof.cs1_challenges,
             &proof.cs1_proofs.iter().map(|p| p.eval).collect::<Vec<_>>(),
-            &proof.cmT.inputize(),
+            &nonnative_affine_to_packed_field_elements(proof.cmT),
         ]
         .concat();
 
@@ -358,32 +429,61 @@ pub mod tests {
     use crate::frontend::utils::CubicFCircuit;
     use crate::transcript::poseidon::poseidon_canonical_config;
 
+    // use Nova as FoldingScheme
+    type N<const H: bool> = Nova<
+        Projective,
+        GVar,
+        Projective2,
+        GVar2,
+        CubicFCircuit<Fr>,
+        KZG<'static, MNT4, H>,
+        KZG<'static, MNT6, H>,
+        H,
+    >;
+    type D<const H: bool> = Decider<
+        Projective,
+        GVar,
+        Projective2,
+        GVar2,
+        CubicFCircuit<Fr>,
+        KZG<'static, MNT4, H>,
+        KZG<'static, MNT6, H>,
+        Groth16<MNT4>,
+        Groth16<MNT6>,
+        N<H>, // here we define the FoldingScheme to use
+        H,
+    >;
+
     #[test]
     fn test_decider() {
-        // use Nova as FoldingScheme
-        type N = Nova<
-            Projective,
-            GVar,
-            Projective2,
-            GVar2,
-            CubicFCircuit<Fr>,
-            KZG<'static, MNT4>,
-            KZG<'static, MNT6>,
-            false,
-        >;
-        type D = Decider<
-            Projective,
-            GVar,
-            Projective2,
-            GVar2,
-            CubicFCircuit<Fr>,
-            KZG<'static, MNT4>,
-            KZG<'static, MNT6>,
-            Groth16<MNT4>,
-            Groth16<MNT6>,
-            N, // here we define the FoldingScheme to use
-        >;
+        test_decider_opt::<false>();
+    }
+
+    /// tests the decider with hiding commitments, whose openings take their blinding factors
+    #[test]
+    fn test_decider_hiding() {
+        test_decider_opt::<true>();
+    }
+
+    /// tests that the Decider rejects the params of a Nova whose pp_hash is a public input, which
+    /// its circuits don't support
+    #[test]
+    fn test_decider_public_pp_hash() {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let prep_param = PreprocessorParam::new(poseidon_config, F_circuit).with_public_pp_hash();
+        let nova_params = N::<false>::preprocess(&mut rng, &prep_param).unwrap();
+        let nova = N::<false>::init(&nova_params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+
+        assert!(matches!(
+            D::<false>::preprocess(&mut rng, nova_params, nova),
+            Err(Error::NotSupportedYet(_))
+        ));
+    }
 
+    fn test_decider_opt<const H: bool>() {
         let mut rng = ark_std::test_rng();
         let poseidon_config = poseidon_canonical_config::<Fr>();
 
@@ -392,32 +492,36 @@ pub mod tests {
 
         let start = Instant::now();
         let prep_param = PreprocessorParam::new(poseidon_config, F_circuit);
-        let nova_params = N::preprocess(&mut rng, &prep_param).unwrap();
+        let nova_params = N::<H>::preprocess(&mut rng, &prep_param).unwrap();
         println!("Nova preprocess, {:?}", start.elapsed());
 
         let start = Instant::now();
-        let mut nova = N::init(&nova_params, F_circuit, z_0.clone()).unwrap();
+        let mut nova = N::<H>::init(&nova_params, F_circuit, z_0.clone()).unwrap();
         println!("Nova initialized, {:?}", start.elapsed());
         let start = Instant::now();
-        nova.prove_step(&mut rng, vec![], None).unwrap();
+        nova.prove_step(&mut rng, (), None).unwrap();
         println!("prove_step, {:?}", start.elapsed());
-        nova.prove_step(&mut rng, vec![], None).unwrap(); // do a 2nd step
+        // do a 2nd step
+        nova.prove_step(&mut rng, (), None).unwrap();
+        // with hiding commitments, the folded witness is blinded
+        assert_eq!(!nova.W_i.rW.is_zero(), H);
 
         let mut rng = rand::rngs::OsRng;
 
         // prepare the Decider prover & verifier params
         let start = Instant::now();
-        let (decider_pp, decider_vp) = D::preprocess(&mut rng, nova_params, nova.clone()).unwrap();
+        let (decider_pp, decider_vp) =
+            D::<H>::preprocess(&mut rng, nova_params, nova.clone()).unwrap();
         println!("Decider preprocess, {:?}", start.elapsed());
 
         // decider proof generation
         let start = Instant::now();
-        let proof = D::prove(rng, decider_pp, nova.clone()).unwrap();
+        let proof = D::<H>::prove(rng, decider_pp, nova.clone()).unwrap();
         println!("Decider prove, {:?}", start.elapsed());
 
         // decider proof verification
         let start = Instant::now();
-        let verified = D::verify(
+        let verified = D::<H>::verify(
             decider_vp,
             nova.i,
             nova.z_0,
@@ -6,6 +6,7 @@ use ark_crypto_primitives::sponge::{poseidon::PoseidonSponge, Absorb, Cryptograp
 use ark_ec::CurveGroup;
 use ark_ff::{BigInteger, PrimeField};
 use ark_r1cs_std::{fields::fp::FpVar, prelude::CurveVar, ToConstraintFieldGadget};
+use ark_std::vec::Vec;
 use core::marker::PhantomData;
 
 use super::{
@@ -14,8 +15,8 @@ use super::{
     CommittedInstance, Nova, Witness,
 };
 use crate::folding::{
-    circuits::{CF1, CF2},
-    traits::WitnessOps,
+    circuits::{cyclefold::CF_COMMITMENT_LABELS, CF1, CF2},
+    traits::{CommittedInstanceOps, WitnessOps},
 };
 use crate::frontend::FCircuit;
 use crate::Error;
@@ -52,14 +53,18 @@ impl<
         CS1: CommitmentScheme<C1, H>,
         CS2: CommitmentScheme<C2, H>,
         const H: bool,
-    > TryFrom<Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H>> for DeciderCircuit1<C1, C2, GC2>
+        const FULL_CHALLENGE: bool,
+    > TryFrom<Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H, FULL_CHALLENGE>>
+    for DeciderCircuit1<C1, C2, GC2>
 where
     CF1<C1>: Absorb,
     <C1 as CurveGroup>::BaseField: PrimeField,
 {
     type Error = Error;
 
-    fn try_from(nova: Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H>) -> Result<Self, Error> {
+    fn try_from(
+        nova: Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H, FULL_CHALLENGE>,
+    ) -> Result<Self, Error> {
         let mut transcript = PoseidonSponge::<C1::ScalarField>::new(&nova.poseidon_config);
         // pp_hash is absorbed to transcript at the NIFS::prove call
 
@@ -78,7 +83,11 @@ where
             .ok_or(Error::OutOfBounds)?;
 
         // compute the KZG challenges used as inputs in the circuit
-        let kzg_challenges = KZGChallengesGadget::get_challenges_native(&mut transcript, &U_i1);
+        let kzg_challenges = KZGChallengesGadget::get_challenges_native(
+            &mut transcript,
+            &U_i1.get_commitment_labels(),
+            &U_i1,
+        )?;
 
         // get KZG evals
         let kzg_evaluations = W_i1
@@ -94,6 +103,7 @@ where
             arith: nova.r1cs,
             poseidon_config: nova.poseidon_config,
             pp_hash: nova.pp_hash,
+            context: Some(nova.context),
             i: nova.i,
             z_0: nova.z_0,
             z_i: nova.z_i,
@@ -108,6 +118,7 @@ where
             cf_U_i: nova.cf_U_i,
             kzg_challenges,
             kzg_evaluations,
+            coefficient_form: false,
         })
     }
 }
@@ -125,13 +136,16 @@ impl<
         CS1: CommitmentScheme<C1, H>,
         CS2: CommitmentScheme<C2, H>,
         const H: bool,
-    > TryFrom<Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H>> for DeciderCircuit2<C2>
+        const FULL_CHALLENGE: bool,
+    > TryFrom<Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H, FULL_CHALLENGE>> for DeciderCircuit2<C2>
 where
     CF1<C2>: Absorb,
 {
     type Error = Error;
 
-    fn try_from(nova: Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H>) -> Result<Self, Error> {
+    fn try_from(
+        nova: Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H, FULL_CHALLENGE>,
+    ) -> Result<Self, Error> {
         // compute the Commitment Scheme challenges of the CycleFold instance commitments, used as
         // inputs in the circuit
         let poseidon_config = poseidon_canonical_config::<C2::ScalarField>();
@@ -141,8 +155,11 @@ where
         transcript.absorb(&pp_hash_Fq);
 
         // compute the KZG challenges used as inputs in the circuit
-        let kzg_challenges =
-            KZGChallengesGadget::get_challenges_native(&mut transcript, &nova.cf_U_i);
+        let kzg_challenges = KZGChallengesGadget::get_challenges_native(
+            &mut transcript,
+            &CF_COMMITMENT_LABELS,
+            &nova.cf_U_i,
+        )?;
 
         // get KZG evals
         let kzg_evaluations = nova
@@ -161,6 +178,7 @@ where
             cf_W_i: nova.cf_W_i,
             kzg_challenges,
             kzg_evaluations,
+            coefficient_form: false,
         })
     }
 }
@@ -209,7 +227,7 @@ pub mod tests {
 
         // generate a Nova instance and do a step of it
         let mut nova = N::init(&nova_params, F_circuit, z_0.clone()).unwrap();
-        nova.prove_step(&mut rng, vec![], None).unwrap();
+        nova.prove_step(&mut rng, (), None).unwrap();
         // verify the IVC
         let ivc_proof = nova.ivc_proof();
         N::verify(nova_params.1, ivc_proof).unwrap();
@@ -2,33 +2,45 @@
 /// the Decider from decider.rs file will be more efficient.
 /// More details can be found at the documentation page:
 /// https://privacy-scaling-explorations.github.io/sonobe-docs/design/nova-decider-onchain.html
+use alloy_primitives::U256;
+use alloy_sol_types::{sol, SolValue};
 use ark_bn254::Bn254;
 use ark_crypto_primitives::sponge::Absorb;
-use ark_ec::{AffineRepr, CurveGroup, Group};
-use ark_ff::{BigInteger, PrimeField};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, Group, VariableBaseMSM};
+use ark_ff::{BigInteger, Field, PrimeField};
 use ark_groth16::Groth16;
+use ark_poly_commit::kzg10::VerifierKey;
 use ark_r1cs_std::{prelude::CurveVar, ToConstraintFieldGadget};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_snark::SNARK;
-use ark_std::rand::{CryptoRng, RngCore};
-use ark_std::{One, Zero};
+use ark_std::{
+    rand::{CryptoRng, RngCore},
+    string::{String, ToString},
+    vec::Vec,
+};
+use ark_std::{One, UniformRand, Zero};
 use core::marker::PhantomData;
+use core::str::FromStr;
+use num_bigint::BigUint;
 
 pub use super::decider_eth_circuit::DeciderEthCircuit;
 use super::decider_eth_circuit::DeciderNovaGadget;
 use super::{CommittedInstance, Nova};
 use crate::commitment::{
-    kzg::{Proof as KZGProof, KZG},
+    kzg::{batch_claims, Proof as KZGProof, KZG},
     pedersen::Params as PedersenParams,
     CommitmentScheme,
 };
-use crate::folding::circuits::{decider::DeciderEnabledNIFS, CF2};
-use crate::folding::traits::{Inputize, WitnessOps};
+use crate::folding::circuits::{
+    decider::DeciderEnabledNIFS, nonnative::affine::nonnative_affine_to_packed_field_elements, CF2,
+};
+use crate::folding::traits::{CommittedInstanceOps, WitnessOps};
 use crate::frontend::FCircuit;
+use crate::transcript::keccak::keccak256_digest;
 use crate::Error;
 use crate::{Decider as DeciderTrait, FoldingScheme};
 
-#[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+#[derive(Debug, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct Proof<C, CS, S>
 where
     C: CurveGroup,
@@ -36,14 +48,118 @@ where
     S: SNARK<C::ScalarField>,
 {
     snark_proof: S::Proof,
-    kzg_proofs: [CS::Proof; 2],
+    // single KZG proof for the batched opening of W and E at `kzg_challenge`, whose `eval` is the
+    // `kzg_batching_challenge`-combination of `kzg_evaluations`
+    kzg_proof: CS::Proof,
     // cmT and r are values for the last fold, U_{i+1}=NIFS.V(r, U_i, u_i, cmT), and they are
     // checked in-circuit
     cmT: C,
     r: C::ScalarField,
     // the KZG challenges are provided by the prover, but in-circuit they are checked to match
     // the in-circuit computed computed ones.
-    kzg_challenges: [C::ScalarField; 2],
+    kzg_challenge: C::ScalarField,
+    kzg_batching_challenge: C::ScalarField,
+    // evaluations of W and E at `kzg_challenge`
+    kzg_evaluations: [C::ScalarField; 2],
+}
+
+// implemented by hand, since deriving it would require `S: Clone`, which `Groth16` does not
+// implement, while `S::Proof` is always `Clone`
+impl<C, CS, S> Clone for Proof<C, CS, S>
+where
+    C: CurveGroup,
+    CS: CommitmentScheme<C, ProverChallenge = C::ScalarField, Challenge = C::ScalarField>,
+    S: SNARK<C::ScalarField>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            snark_proof: self.snark_proof.clone(),
+            kzg_proof: self.kzg_proof.clone(),
+            cmT: self.cmT,
+            r: self.r,
+            kzg_challenge: self.kzg_challenge,
+            kzg_batching_challenge: self.kzg_batching_challenge,
+            kzg_evaluations: self.kzg_evaluations,
+        }
+    }
+}
+
+impl<C, CS, S> Proof<C, CS, S>
+where
+    C: CurveGroup,
+    CS: CommitmentScheme<
+        C,
+        ProverChallenge = C::ScalarField,
+        Challenge = C::ScalarField,
+        Proof = KZGProof<C>,
+    >,
+    S: SNARK<C::ScalarField>,
+    C::ScalarField: Absorb,
+{
+    /// Returns the public inputs of the decider's SNARK proof, in the same order in which they
+    /// are allocated in the `DeciderEthCircuit`:
+    /// `[pp_hash, context, i, z_0, z_i, U_final commitments, kzg challenge, kzg evaluations,
+    /// kzg batching challenge, cmT]`, where `z_0` and `z_i` are replaced by their
+    /// `keccak256_digest`s when `hash_state` is set (see `VerifierParam::hash_state`).
+    #[allow(clippy::too_many_arguments)]
+    pub fn public_inputs(
+        &self,
+        pp_hash: C::ScalarField,
+        context: C::ScalarField,
+        i: C::ScalarField,
+        z_0: &[C::ScalarField],
+        z_i: &[C::ScalarField],
+        hash_state: bool,
+        running_commitments: &[C],
+        incoming_commitments: &[C],
+    ) -> Result<Vec<C::ScalarField>, Error> {
+        let U_final_commitments = DeciderNovaGadget::fold_group_elements_native(
+            running_commitments,
+            incoming_commitments,
+            Some(self.cmT),
+            self.r,
+        )?;
+        Ok(self.public_inputs_from_folded(
+            pp_hash,
+            context,
+            i,
+            z_0,
+            z_i,
+            hash_state,
+            &U_final_commitments,
+        ))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn public_inputs_from_folded(
+        &self,
+        pp_hash: C::ScalarField,
+        context: C::ScalarField,
+        i: C::ScalarField,
+        z_0: &[C::ScalarField],
+        z_i: &[C::ScalarField],
+        hash_state: bool,
+        U_final_commitments: &[C],
+    ) -> Vec<C::ScalarField> {
+        let state = if hash_state {
+            vec![keccak256_digest(z_0), keccak256_digest(z_i)]
+        } else {
+            [z_0, z_i].concat()
+        };
+        [
+            &[pp_hash, context, i][..],
+            &state,
+            &U_final_commitments
+                .iter()
+                .flat_map(|&c| nonnative_affine_to_packed_field_elements(c))
+                .collect::<Vec<_>>(),
+            &[self.kzg_challenge],
+            &self.kzg_evaluations,
+            &[self.kzg_batching_challenge],
+            &nonnative_affine_to_packed_field_elements(self.cmT),
+        ]
+        .concat()
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
@@ -54,13 +170,25 @@ where
     S_VerifyingKey: Clone + CanonicalSerialize + CanonicalDeserialize,
 {
     pub pp_hash: C1::ScalarField,
+    /// application context of the IVC, see `Nova::init_with_context`. It is a public input of the
+    /// decider circuit, so it can be replaced to verify proofs of IVCs with another context.
+    pub context: C1::ScalarField,
+    /// whether the decider circuit exposes the `keccak256_digest`s of `z_0` and `z_i` as public
+    /// inputs instead of the states themselves, see `Decider`.
+    pub hash_state: bool,
     pub snark_vp: S_VerifyingKey,
     pub cs_vp: CS_VerifyingKey,
 }
 
-/// Onchain Decider, for ethereum use cases
+/// Onchain Decider, for ethereum use cases.
+///
+/// When `HASH_STATE` is set, the decider circuit exposes a single public input for each of `z_0`
+/// and `z_i`, their `keccak256_digest`, instead of all their elements. The verifier contract still
+/// takes the full states as calldata and hashes them with the KECCAK256 opcode, which is much
+/// cheaper than processing each element as a Groth16 public input when the state is large, while
+/// the circuit pays ~150k constraints for each 136 bytes of state.
 #[derive(Clone, Debug)]
-pub struct Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS> {
+pub struct Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS, const HASH_STATE: bool = false> {
     _c1: PhantomData<C1>,
     _gc1: PhantomData<GC1>,
     _c2: PhantomData<C2>,
@@ -72,8 +200,8 @@ pub struct Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS> {
     _fs: PhantomData<FS>,
 }
 
-impl<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS> DeciderTrait<C1, C2, FC, FS>
-    for Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS>
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS, const HASH_STATE: bool> DeciderTrait<C1, C2, FC, FS>
+    for Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS, HASH_STATE>
 where
     C1: CurveGroup,
     GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
@@ -115,7 +243,16 @@ where
         prep_param: Self::PreprocessorParam,
         fs: FS,
     ) -> Result<(Self::ProverParam, Self::VerifierParam), Error> {
-        let circuit = DeciderEthCircuit::<C1, C2, GC2>::try_from(Nova::from(fs))?;
+        let nova = Nova::from(fs);
+        // the decider circuit enforces that `u_i.x` only contains the two hashes of the state
+        if nova.public_pp_hash {
+            return Err(Error::NotSupportedYet(
+                "Nova Decider with pp_hash as a public input".to_string(),
+            ));
+        }
+        let mut circuit = DeciderEthCircuit::<C1, C2, GC2>::try_from(nova)?;
+        circuit.hash_state = HASH_STATE;
+        let context = circuit.context.unwrap_or_else(C1::ScalarField::zero);
 
         // get the Groth16 specific setup for the circuit
         let (g16_pk, g16_vk) = S::circuit_specific_setup(circuit, &mut rng)
@@ -139,6 +276,8 @@ where
         let pp = (g16_pk, nova_pp.cs_pp);
         let vp = Self::VerifierParam {
             pp_hash,
+            context,
+            hash_state: HASH_STATE,
             snark_vp: g16_vk,
             cs_vp: nova_vp.cs_vp,
         };
@@ -150,27 +289,37 @@ where
         pp: Self::ProverParam,
         folding_scheme: FS,
     ) -> Result<Self::Proof, Error> {
+        trace_span!("nova_decider_eth_prove");
         let (snark_pk, cs_pk): (S::ProvingKey, CS1::ProverParams) = pp;
 
-        let circuit = DeciderEthCircuit::<C1, C2, GC2>::try_from(Nova::from(folding_scheme))?;
+        let mut circuit = DeciderEthCircuit::<C1, C2, GC2>::try_from(Nova::from(folding_scheme))?;
+        circuit.hash_state = HASH_STATE;
 
         let cmT = circuit.proof;
         let r = circuit.randomness;
 
         // get the challenges that have been already computed when preparing the circuit inputs in
         // the above `try_from` call
-        let kzg_challenges = circuit.kzg_challenges.clone();
+        let kzg_challenge = circuit.kzg_challenge;
+        let kzg_batching_challenge = circuit.kzg_batching_challenge;
 
-        // generate KZG proofs
+        // generate the KZG proofs of W and E at the same point, and batch them into a single
+        // one. Since the KZG quotient is linear in the polynomial, the combination of the proofs
+        // is the proof of the combination of the polynomials.
         let kzg_proofs = circuit
             .W_i1
             .get_openings()
             .iter()
-            .zip(&kzg_challenges)
-            .map(|((v, _), &c)| {
-                CS1::prove_with_challenge(&cs_pk, c, v, &C1::ScalarField::zero(), None)
+            .map(|(v, _)| {
+                CS1::prove_with_challenge(&cs_pk, kzg_challenge, v, &C1::ScalarField::zero(), None)
             })
             .collect::<Result<Vec<_>, _>>()?;
+        let kzg_evaluations = kzg_proofs.iter().map(|p| p.eval).collect::<Vec<_>>();
+        let (proof, eval) = batch_claims(
+            kzg_batching_challenge,
+            &kzg_proofs.iter().map(|p| p.proof).collect::<Vec<_>>(),
+            &kzg_evaluations,
+        );
 
         let snark_proof =
             S::prove(&snark_pk, circuit, &mut rng).map_err(|e| Error::Other(e.to_string()))?;
@@ -179,10 +328,15 @@ where
             snark_proof,
             cmT,
             r,
-            kzg_proofs: kzg_proofs
-                .try_into()
-                .map_err(|e: Vec<_>| Error::NotExpectedLength(e.len(), 2))?,
-            kzg_challenges: kzg_challenges
+            kzg_proof: KZGProof {
+                eval,
+                proof,
+                random_v: None,
+                hiding_commitment: None,
+            },
+            kzg_challenge,
+            kzg_batching_challenge,
+            kzg_evaluations: kzg_evaluations
                 .try_into()
                 .map_err(|e: Vec<_>| Error::NotExpectedLength(e.len(), 2))?,
         })
@@ -198,12 +352,15 @@ where
         incoming_commitments: &Self::CommittedInstance,
         proof: &Self::Proof,
     ) -> Result<bool, Error> {
+        trace_span!("nova_decider_eth_verify");
         if i <= C1::ScalarField::one() {
             return Err(Error::NotEnoughSteps);
         }
 
         let Self::VerifierParam {
             pp_hash,
+            context,
+            hash_state,
             snark_vp,
             cs_vp,
         } = vp;
@@ -216,19 +373,15 @@ where
             proof.r,
         )?;
 
-        let public_input = [
-            &[pp_hash, i][..],
+        let public_input = proof.public_inputs_from_folded(
+            pp_hash,
+            context,
+            i,
             &z_0,
             &z_i,
-            &U_final_commitments
-                .iter()
-                .flat_map(|c| c.inputize())
-                .collect::<Vec<_>>(),
-            &proof.kzg_challenges,
-            &proof.kzg_proofs.iter().map(|p| p.eval).collect::<Vec<_>>(),
-            &proof.cmT.inputize(),
-        ]
-        .concat();
+            hash_state,
+            &U_final_commitments,
+        );
 
         let snark_v = S::verify(&snark_vp, &public_input, &proof.snark_proof)
             .map_err(|e| Error::Other(e.to_string()))?;
@@ -236,22 +389,193 @@ where
             return Err(Error::SNARKVerificationFail);
         }
 
-        // 7.3. Verify the KZG proofs
-        for ((cm, &c), pi) in U_final_commitments
-            .iter()
-            .zip(&proof.kzg_challenges)
-            .zip(&proof.kzg_proofs)
-        {
-            // we're at the Ethereum EVM case, so the CS1 is KZG commitments
-            CS1::verify_with_challenge(&cs_vp, c, cm, pi)?;
+        // 7.3. Verify the batched KZG proof against the combined commitments and evaluations
+        let (cm, eval) = batch_claims(
+            proof.kzg_batching_challenge,
+            &U_final_commitments,
+            &proof.kzg_evaluations,
+        );
+        if eval != proof.kzg_proof.eval {
+            return Err(Error::CommitmentVerificationFail);
         }
+        // we're at the Ethereum EVM case, so the CS1 is KZG commitments
+        CS1::verify_with_challenge(&cs_vp, proof.kzg_challenge, &cm, &proof.kzg_proof)?;
 
         Ok(true)
     }
 }
 
-/// Prepares solidity calldata for calling the NovaDecider contract
-#[allow(clippy::too_many_arguments)]
+/// Public statement of a decider proof, ie. the values that the verifier needs besides the proof
+/// itself: the number of steps `i`, the initial and current states, and the commitments of the
+/// last running and incoming instances.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Statement<C: CurveGroup> {
+    pub i: C::ScalarField,
+    pub z_0: Vec<C::ScalarField>,
+    pub z_i: Vec<C::ScalarField>,
+    pub running_commitments: Vec<C>,
+    pub incoming_commitments: Vec<C>,
+}
+
+/// Group elements of a single decider proof that take part in the batched pairing check.
+struct BatchTerms<E: Pairing> {
+    // Groth16 terms: e(A, B) = e(alpha, beta) * e(vk_x, gamma) * e(C, delta)
+    a: E::G1,
+    b: E::G2Affine,
+    c: E::G1,
+    vk_x: E::G1,
+    // batched KZG terms: e(cm - eval * g + challenge * w, h) = e(w, beta_h)
+    kzg: (E::G1, E::G1),
+}
+
+impl<E, GC1, C2, GC2, FC, CS2, FS, const HASH_STATE: bool>
+    Decider<E::G1, GC1, C2, GC2, FC, KZG<'static, E>, CS2, Groth16<E>, FS, HASH_STATE>
+where
+    E: Pairing,
+    E::ScalarField: Absorb,
+{
+    /// Verifies a batch of decider proofs at once. All the Groth16 and KZG pairing checks are
+    /// combined through a random linear combination into a single multi-pairing, whose size
+    /// is `statements.len() + 5` instead of `6 * statements.len()`.
+    ///
+    /// If the batch does not verify, the invalid proofs are searched through bisection, and
+    /// `Error::BatchVerificationFail` is returned with the index of the first invalid one.
+    ///
+    /// The `verifyBatch` function of the Solidity verifier combines the same pairing equations,
+    /// with coefficients derived from the keccak256 of its calldata, so that it accepts the same
+    /// batches (see [`prepare_calldata_batch`]).
+    pub fn verify_batch(
+        mut rng: impl RngCore + CryptoRng,
+        vp: &VerifierParam<E::G1, VerifierKey<E>, ark_groth16::VerifyingKey<E>>,
+        statements: &[Statement<E::G1>],
+        proofs: &[Proof<E::G1, KZG<'static, E>, Groth16<E>>],
+    ) -> Result<(), Error> {
+        if statements.len() != proofs.len() {
+            return Err(Error::NotSameLength(
+                "statements.len()".to_string(),
+                statements.len(),
+                "proofs.len()".to_string(),
+                proofs.len(),
+            ));
+        }
+        if proofs.is_empty() {
+            return Err(Error::Empty);
+        }
+
+        let terms = statements
+            .iter()
+            .zip(proofs)
+            .map(|(statement, proof)| Self::batch_terms(vp, statement, proof))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if Self::batch_check(&mut rng, vp, &terms) {
+            return Ok(());
+        }
+
+        // bisect the batch to find the first invalid proof
+        let (mut lo, mut hi) = (0, terms.len());
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if !Self::batch_check(&mut rng, vp, &terms[lo..mid]) {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+        Err(Error::BatchVerificationFail(lo))
+    }
+
+    fn batch_terms(
+        vp: &VerifierParam<E::G1, VerifierKey<E>, ark_groth16::VerifyingKey<E>>,
+        statement: &Statement<E::G1>,
+        proof: &Proof<E::G1, KZG<'static, E>, Groth16<E>>,
+    ) -> Result<BatchTerms<E>, Error> {
+        if statement.i <= E::ScalarField::one() {
+            return Err(Error::NotEnoughSteps);
+        }
+
+        let U_final_commitments = DeciderNovaGadget::fold_group_elements_native(
+            &statement.running_commitments,
+            &statement.incoming_commitments,
+            Some(proof.cmT),
+            proof.r,
+        )?;
+        let public_input = proof.public_inputs_from_folded(
+            vp.pp_hash,
+            vp.context,
+            statement.i,
+            &statement.z_0,
+            &statement.z_i,
+            vp.hash_state,
+            &U_final_commitments,
+        );
+
+        let gamma_abc_g1 = &vp.snark_vp.gamma_abc_g1;
+        if public_input.len() + 1 != gamma_abc_g1.len() {
+            return Err(Error::NotExpectedLength(
+                public_input.len(),
+                gamma_abc_g1.len() - 1,
+            ));
+        }
+        let vk_x = E::G1::msm_unchecked(&gamma_abc_g1[1..], &public_input) + gamma_abc_g1[0];
+
+        let (cm, eval) = batch_claims(
+            proof.kzg_batching_challenge,
+            &U_final_commitments,
+            &proof.kzg_evaluations,
+        );
+        let w = proof.kzg_proof.proof;
+        let kzg = (cm - vp.cs_vp.g * eval + w * proof.kzg_challenge, w);
+
+        Ok(BatchTerms {
+            a: proof.snark_proof.a.into(),
+            b: proof.snark_proof.b,
+            c: proof.snark_proof.c.into(),
+            vk_x,
+            kzg,
+        })
+    }
+
+    /// Checks all the given terms in a single multi-pairing, where each pairing equation is
+    /// weighted by a fresh random scalar.
+    fn batch_check(
+        rng: &mut (impl RngCore + CryptoRng),
+        vp: &VerifierParam<E::G1, VerifierKey<E>, ark_groth16::VerifyingKey<E>>,
+        terms: &[BatchTerms<E>],
+    ) -> bool {
+        let mut g1s = Vec::with_capacity(terms.len() + 5);
+        let mut g2s = Vec::with_capacity(terms.len() + 5);
+        let (mut alpha, mut vk_x, mut c) = (E::G1::zero(), E::G1::zero(), E::G1::zero());
+        let (mut kzg_lhs, mut kzg_w) = (E::G1::zero(), E::G1::zero());
+        for t in terms {
+            let r = E::ScalarField::rand(rng);
+            g1s.push(-t.a * r);
+            g2s.push(t.b);
+            alpha += vp.snark_vp.alpha_g1 * r;
+            vk_x += t.vk_x * r;
+            c += t.c * r;
+            let s = E::ScalarField::rand(rng);
+            kzg_lhs += t.kzg.0 * s;
+            kzg_w += t.kzg.1 * s;
+        }
+        g1s.extend([alpha, vk_x, c, kzg_lhs, -kzg_w]);
+        g2s.extend([
+            vp.snark_vp.beta_g2,
+            vp.snark_vp.gamma_g2,
+            vp.snark_vp.delta_g2,
+            vp.cs_vp.h,
+            vp.cs_vp.beta_h,
+        ]);
+
+        E::multi_pairing(E::G1::normalize_batch(&g1s), g2s).is_zero()
+    }
+}
+
+/// Prepares solidity calldata for calling the NovaDecider contract.
+///
+/// The calldata is the same for both layouts of the public inputs (see `Decider`), since the
+/// contract always takes the full `z_0` and `z_i`, and hashes them itself when the decider
+/// exposes their digests.
 pub fn prepare_calldata(
     function_signature_check: [u8; 4],
     i: ark_bn254::Fr,
@@ -278,17 +602,400 @@ pub fn prepare_calldata(
         point_to_eth_format(proof.snark_proof.a)?,     // pA
         point2_to_eth_format(proof.snark_proof.b)?,    // pB
         point_to_eth_format(proof.snark_proof.c)?,     // pC
-        proof.kzg_challenges[0].into_bigint().to_bytes_be(), // challenge_W
-        proof.kzg_challenges[1].into_bigint().to_bytes_be(), // challenge_E
-        proof.kzg_proofs[0].eval.into_bigint().to_bytes_be(), // eval W
-        proof.kzg_proofs[1].eval.into_bigint().to_bytes_be(), // eval E
-        point_to_eth_format(proof.kzg_proofs[0].proof.into_affine())?, // W kzg_proof
-        point_to_eth_format(proof.kzg_proofs[1].proof.into_affine())?, // E kzg_proof
+        proof.kzg_challenge.into_bigint().to_bytes_be(), // challenge
+        proof.kzg_batching_challenge.into_bigint().to_bytes_be(), // batching challenge
+        proof.kzg_evaluations[0].into_bigint().to_bytes_be(), // eval W
+        proof.kzg_evaluations[1].into_bigint().to_bytes_be(), // eval E
+        point_to_eth_format(proof.kzg_proof.proof.into_affine())?, // batched kzg_proof
     ]
     .concat())
 }
 
-fn point_to_eth_format<C: AffineRepr>(p: C) -> Result<Vec<u8>, Error>
+sol! {
+    /// A proof of the Nova decider, as taken by the `verifyNovaProof(NovaProof)` and `verifyBatch`
+    /// entrypoints of the NovaDecider contract, with the points in the EVM format of
+    /// [`prepare_calldata`].
+    #[derive(Debug, PartialEq, Eq)]
+    struct NovaProof {
+        uint256 i;
+        uint256[] z0;
+        uint256[] zi;
+        // commitments of the running instance, of the incoming instance and the cross term
+        uint256[2] U_i_cmW;
+        uint256[2] U_i_cmE;
+        uint256[2] u_i_cmW;
+        uint256[2] cmT;
+        // challenge of the last fold
+        uint256 r;
+        // Groth16 proof
+        uint256[2] pA;
+        uint256[2][2] pB;
+        uint256[2] pC;
+        // batched KZG opening of W and E, with their evaluations [eval_W, eval_E]
+        uint256 kzg_challenge;
+        uint256 kzg_batching_challenge;
+        uint256[2] kzg_evaluations;
+        uint256[2] kzg_proof;
+    }
+}
+
+impl NovaProof {
+    /// Builds the `NovaProof` of a decider proof, given the commitments of the running instance
+    /// and the `cmW` of the incoming instance, which are the ones taken by the NovaDecider
+    /// contract.
+    fn new(
+        i: ark_bn254::Fr,
+        z_0: &[ark_bn254::Fr],
+        z_i: &[ark_bn254::Fr],
+        running_commitments: [ark_bn254::G1Projective; 2],
+        incoming_cmW: ark_bn254::G1Projective,
+        proof: &Proof<ark_bn254::G1Projective, KZG<'static, Bn254>, Groth16<Bn254>>,
+    ) -> Result<Self, Error> {
+        let pB = point2_to_eth_format(proof.snark_proof.b)?;
+        Ok(Self {
+            i: fr_to_word(i),
+            z0: z_0.iter().copied().map(fr_to_word).collect(),
+            zi: z_i.iter().copied().map(fr_to_word).collect(),
+            U_i_cmW: point_to_words(running_commitments[0].into_affine())?,
+            U_i_cmE: point_to_words(running_commitments[1].into_affine())?,
+            u_i_cmW: point_to_words(incoming_cmW.into_affine())?,
+            cmT: point_to_words(proof.cmT.into_affine())?,
+            r: fr_to_word(proof.r),
+            pA: point_to_words(proof.snark_proof.a)?,
+            pB: [words(&pB[..64]), words(&pB[64..])],
+            pC: point_to_words(proof.snark_proof.c)?,
+            kzg_challenge: fr_to_word(proof.kzg_challenge),
+            kzg_batching_challenge: fr_to_word(proof.kzg_batching_challenge),
+            kzg_evaluations: [
+                fr_to_word(proof.kzg_evaluations[0]),
+                fr_to_word(proof.kzg_evaluations[1]),
+            ],
+            kzg_proof: point_to_words(proof.kzg_proof.proof.into_affine())?,
+        })
+    }
+}
+
+fn fr_to_word(value: ark_bn254::Fr) -> U256 {
+    U256::from_limbs(value.into_bigint().0)
+}
+
+/// Splits the 64 bytes of two big-endian words.
+fn words(bytes: &[u8]) -> [U256; 2] {
+    [
+        U256::from_be_slice(&bytes[..32]),
+        U256::from_be_slice(&bytes[32..64]),
+    ]
+}
+
+fn point_to_words<C: AffineRepr>(p: C) -> Result<[U256; 2], Error>
+where
+    C::BaseField: PrimeField,
+{
+    Ok(words(&point_to_eth_format(p)?))
+}
+
+/// Prepares solidity calldata for calling the `verifyNovaProof(NovaProof)` entrypoint of the
+/// NovaDecider contract, which takes the proof as a [`NovaProof`] struct, so that it can be
+/// encoded and decoded with standard ABI tooling.
+pub fn prepare_calldata_abi(
+    function_signature_check: [u8; 4],
+    i: ark_bn254::Fr,
+    z_0: Vec<ark_bn254::Fr>,
+    z_i: Vec<ark_bn254::Fr>,
+    running_instance: &CommittedInstance<ark_bn254::G1Projective>,
+    incoming_instance: &CommittedInstance<ark_bn254::G1Projective>,
+    proof: Proof<ark_bn254::G1Projective, KZG<'static, Bn254>, Groth16<Bn254>>,
+) -> Result<Vec<u8>, Error> {
+    if z_0.len() != z_i.len() {
+        return Err(Error::NotSameLength(
+            "z_0.len()".to_string(),
+            z_0.len(),
+            "z_i.len()".to_string(),
+            z_i.len(),
+        ));
+    }
+    let nova_proof = NovaProof::new(
+        i,
+        &z_0,
+        &z_i,
+        [running_instance.cmW, running_instance.cmE],
+        incoming_instance.cmW,
+        &proof,
+    )?;
+    Ok([
+        function_signature_check.to_vec(),
+        (nova_proof,).abi_encode_params(),
+    ]
+    .concat())
+}
+
+/// Decodes the output of the `verifyNovaProof` entrypoints of the NovaDecider contract, which is
+/// the ABI encoding of `(bool ok, uint256 i, uint256[] z_i)`, into the verified `i` and `z_i`.
+///
+/// Returns `Error::SNARKVerificationFail` if the contract reverted or did not accept the proof.
+pub fn decode_verifier_output(
+    output: &[u8],
+    state_len: usize,
+) -> Result<(ark_bn254::Fr, Vec<ark_bn254::Fr>), Error> {
+    let word = |k: usize| &output[32 * k..32 * (k + 1)];
+    let word_to_fr = |k: usize| {
+        let value = ark_bn254::Fr::from_be_bytes_mod_order(word(k));
+        if value.into_bigint().to_bytes_be() != word(k) {
+            return Err(Error::ConversionError(
+                "uint256".to_string(),
+                "ark_bn254::Fr".to_string(),
+                "the value is not in the scalar field".to_string(),
+            ));
+        }
+        Ok(value)
+    };
+
+    // a revert returns either nothing or the encoding of an error, which is not a whole number of
+    // words
+    if output.len() % 32 != 0 || output.len() < 32 {
+        return Err(Error::SNARKVerificationFail);
+    }
+    if word_to_fr(0)? != ark_bn254::Fr::one() {
+        return Err(Error::SNARKVerificationFail);
+    }
+    // ok, i, the offset and the length of z_i, followed by its elements
+    if output.len() != 32 * (4 + state_len) {
+        return Err(Error::NotExpectedLength(output.len(), 32 * (4 + state_len)));
+    }
+    if word_to_fr(2)? != ark_bn254::Fr::from(96u64) {
+        return Err(Error::Other(
+            "the verifier output does not encode z_i after i".to_string(),
+        ));
+    }
+    if word_to_fr(3)? != ark_bn254::Fr::from(state_len as u64) {
+        return Err(Error::NotExpectedLength(
+            BigUint::from_bytes_be(word(3))
+                .try_into()
+                .unwrap_or(usize::MAX),
+            state_len,
+        ));
+    }
+    Ok((
+        word_to_fr(1)?,
+        (4..4 + state_len)
+            .map(word_to_fr)
+            .collect::<Result<Vec<_>, _>>()?,
+    ))
+}
+
+/// Prepares solidity calldata for calling the `verifyBatch` function of the NovaDecider
+/// contract, which verifies the given decider proofs at once.
+///
+/// Each proof is encoded as a [`NovaProof`], with the commitments of its running and incoming
+/// instances taken from its statement, as in [`Decider::verify_batch`].
+pub fn prepare_calldata_batch(
+    function_signature_check: [u8; 4],
+    statements: &[Statement<ark_bn254::G1Projective>],
+    proofs: &[Proof<ark_bn254::G1Projective, KZG<'static, Bn254>, Groth16<Bn254>>],
+) -> Result<Vec<u8>, Error> {
+    if statements.len() != proofs.len() {
+        return Err(Error::NotSameLength(
+            "statements.len()".to_string(),
+            statements.len(),
+            "proofs.len()".to_string(),
+            proofs.len(),
+        ));
+    }
+
+    let mut nova_proofs = Vec::with_capacity(proofs.len());
+    for (statement, proof) in statements.iter().zip(proofs) {
+        let (running_commitments, incoming_commitments) = (
+            &statement.running_commitments,
+            &statement.incoming_commitments,
+        );
+        if running_commitments.len() != 2 {
+            return Err(Error::NotExpectedLength(running_commitments.len(), 2));
+        }
+        if incoming_commitments.len() != 2 {
+            return Err(Error::NotExpectedLength(incoming_commitments.len(), 2));
+        }
+        nova_proofs.push(NovaProof::new(
+            statement.i,
+            &statement.z_0,
+            &statement.z_i,
+            [running_commitments[0], running_commitments[1]],
+            incoming_commitments[0],
+            proof,
+        )?);
+    }
+    Ok([
+        function_signature_check.to_vec(),
+        (nova_proofs,).abi_encode_params(),
+    ]
+    .concat())
+}
+
+impl Proof<ark_bn254::G1Projective, KZG<'static, Bn254>, Groth16<Bn254>> {
+    /// Exports the Groth16 proof and its public inputs in the snarkjs `proof.json` and
+    /// `public.json` formats respectively, where the field elements are encoded as decimal
+    /// strings and the points in projective coordinates.
+    /// The public inputs follow the layout of [`Proof::public_inputs`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn to_snarkjs_json(
+        &self,
+        pp_hash: ark_bn254::Fr,
+        context: ark_bn254::Fr,
+        i: ark_bn254::Fr,
+        z_0: &[ark_bn254::Fr],
+        z_i: &[ark_bn254::Fr],
+        hash_state: bool,
+        running_instance: &CommittedInstance<ark_bn254::G1Projective>,
+        incoming_instance: &CommittedInstance<ark_bn254::G1Projective>,
+    ) -> Result<(String, String), Error> {
+        let public_inputs = self.public_inputs(
+            pp_hash,
+            context,
+            i,
+            z_0,
+            z_i,
+            hash_state,
+            &running_instance.get_commitments(),
+            &incoming_instance.get_commitments(),
+        )?;
+        let proof = serde_json::json!({
+            "pi_a": g1_to_snarkjs(self.snark_proof.a),
+            "pi_b": g2_to_snarkjs(self.snark_proof.b),
+            "pi_c": g1_to_snarkjs(self.snark_proof.c),
+            "protocol": "groth16",
+            "curve": "bn128",
+        });
+        let public = serde_json::Value::from(
+            public_inputs
+                .iter()
+                .map(field_to_snarkjs)
+                .collect::<Vec<_>>(),
+        );
+        Ok((
+            serde_json::to_string_pretty(&proof)
+                .map_err(|e| Error::JSONSerdeError(e.to_string()))?,
+            serde_json::to_string_pretty(&public)
+                .map_err(|e| Error::JSONSerdeError(e.to_string()))?,
+        ))
+    }
+}
+
+/// Parses a Groth16 proof and its public inputs from the snarkjs `proof.json` and `public.json`
+/// formats, as exported by [`Proof::to_snarkjs_json`].
+pub fn from_snarkjs_json(
+    proof_json: &str,
+    public_json: &str,
+) -> Result<(ark_groth16::Proof<Bn254>, Vec<ark_bn254::Fr>), Error> {
+    let proof: serde_json::Value =
+        serde_json::from_str(proof_json).map_err(|e| Error::JSONSerdeError(e.to_string()))?;
+    let public: Vec<String> =
+        serde_json::from_str(public_json).map_err(|e| Error::JSONSerdeError(e.to_string()))?;
+
+    let snark_proof = ark_groth16::Proof {
+        a: g1_from_snarkjs(&proof["pi_a"])?,
+        b: g2_from_snarkjs(&proof["pi_b"])?,
+        c: g1_from_snarkjs(&proof["pi_c"])?,
+    };
+    let public_inputs = public
+        .iter()
+        .map(|v| field_from_snarkjs(v))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((snark_proof, public_inputs))
+}
+
+fn field_to_snarkjs<F: PrimeField>(v: &F) -> String {
+    BigUint::from(v.into_bigint()).to_string()
+}
+
+fn field_from_snarkjs<F: PrimeField>(v: &str) -> Result<F, Error> {
+    let n = BigUint::from_str(v).map_err(|e| {
+        Error::ConversionError("String".to_string(), "BigUint".to_string(), e.to_string())
+    })?;
+    // reject non-canonical encodings, so that the import is the exact inverse of the export
+    if n >= F::MODULUS.into() {
+        return Err(Error::OutOfBounds);
+    }
+    Ok(F::from(n))
+}
+
+fn snarkjs_array<'a>(
+    v: &'a serde_json::Value,
+    len: usize,
+) -> Result<&'a Vec<serde_json::Value>, Error> {
+    let a = v
+        .as_array()
+        .ok_or(Error::JSONSerdeError("expected a JSON array".to_string()))?;
+    if a.len() != len {
+        return Err(Error::NotExpectedLength(a.len(), len));
+    }
+    Ok(a)
+}
+
+fn snarkjs_str(v: &serde_json::Value) -> Result<&str, Error> {
+    v.as_str()
+        .ok_or(Error::JSONSerdeError("expected a JSON string".to_string()))
+}
+
+fn g1_to_snarkjs(p: ark_bn254::G1Affine) -> Vec<String> {
+    // snarkjs encodes the point at infinity as [0, 1, 0]
+    match p.xy() {
+        Some((x, y)) => vec![field_to_snarkjs(x), field_to_snarkjs(y), "1".to_string()],
+        None => vec!["0".to_string(), "1".to_string(), "0".to_string()],
+    }
+}
+
+fn g1_from_snarkjs(v: &serde_json::Value) -> Result<ark_bn254::G1Affine, Error> {
+    let a = snarkjs_array(v, 3)?;
+    let [x, y, z] = [
+        field_from_snarkjs::<ark_bn254::Fq>(snarkjs_str(&a[0])?)?,
+        field_from_snarkjs(snarkjs_str(&a[1])?)?,
+        field_from_snarkjs(snarkjs_str(&a[2])?)?,
+    ];
+    if z.is_zero() {
+        return Ok(ark_bn254::G1Affine::identity());
+    }
+    // snarkjs outputs affine points, ie. with z=1, but we support any projective z
+    let z_inv = z.inverse().ok_or(Error::OutOfBounds)?;
+    let p = ark_bn254::G1Affine::new_unchecked(x * z_inv, y * z_inv);
+    if !p.is_on_curve() || !p.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Error::NotSatisfied);
+    }
+    Ok(p)
+}
+
+fn g2_to_snarkjs(p: ark_bn254::G2Affine) -> Vec<Vec<String>> {
+    let fq2 = |v: &ark_bn254::Fq2| vec![field_to_snarkjs(&v.c0), field_to_snarkjs(&v.c1)];
+    match p.xy() {
+        Some((x, y)) => vec![fq2(x), fq2(y), fq2(&ark_bn254::Fq2::one())],
+        None => vec![
+            fq2(&ark_bn254::Fq2::zero()),
+            fq2(&ark_bn254::Fq2::one()),
+            fq2(&ark_bn254::Fq2::zero()),
+        ],
+    }
+}
+
+fn g2_from_snarkjs(v: &serde_json::Value) -> Result<ark_bn254::G2Affine, Error> {
+    let fq2 = |v: &serde_json::Value| -> Result<ark_bn254::Fq2, Error> {
+        let a = snarkjs_array(v, 2)?;
+        Ok(ark_bn254::Fq2::new(
+            field_from_snarkjs(snarkjs_str(&a[0])?)?,
+            field_from_snarkjs(snarkjs_str(&a[1])?)?,
+        ))
+    };
+    let a = snarkjs_array(v, 3)?;
+    let [x, y, z] = [fq2(&a[0])?, fq2(&a[1])?, fq2(&a[2])?];
+    if z.is_zero() {
+        return Ok(ark_bn254::G2Affine::identity());
+    }
+    let z_inv = z.inverse().ok_or(Error::OutOfBounds)?;
+    let p = ark_bn254::G2Affine::new_unchecked(x * z_inv, y * z_inv);
+    if !p.is_on_curve() || !p.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Error::NotSatisfied);
+    }
+    Ok(p)
+}
+
+pub(crate) fn point_to_eth_format<C: AffineRepr>(p: C) -> Result<Vec<u8>, Error>
 where
     C::BaseField: PrimeField,
 {
@@ -298,7 +1005,7 @@ where
 
     Ok([x.into_bigint().to_bytes_be(), y.into_bigint().to_bytes_be()].concat())
 }
-fn point2_to_eth_format(p: ark_bn254::G2Affine) -> Result<Vec<u8>, Error> {
+pub(crate) fn point2_to_eth_format(p: ark_bn254::G2Affine) -> Result<Vec<u8>, Error> {
     let zero_point = (&ark_bn254::Fq2::zero(), &ark_bn254::Fq2::zero());
     let (x, y) = p.xy().unwrap_or(zero_point);
 
@@ -320,7 +1027,7 @@ pub mod tests {
     use super::*;
     use crate::commitment::pedersen::Pedersen;
     use crate::folding::nova::{PreprocessorParam, ProverParams as NovaProverParams};
-    use crate::folding::traits::CommittedInstanceOps;
+    use crate::folding::tests::test_corrupted_decider_proof;
     use crate::frontend::utils::CubicFCircuit;
     use crate::transcript::poseidon::poseidon_canonical_config;
 
@@ -362,13 +1069,22 @@ pub mod tests {
         let mut nova = N::init(&nova_params, F_circuit, z_0.clone()).unwrap();
         println!("Nova initialized, {:?}", start.elapsed());
 
+        // the Decider doesn't support pp_hash as a public input of the folded instances
+        let nova_params_pp_hash =
+            N::preprocess(&mut rng, &preprocessor_param.clone().with_public_pp_hash()).unwrap();
+        let nova_pp_hash = N::init(&nova_params_pp_hash, F_circuit, z_0.clone()).unwrap();
+        assert!(matches!(
+            D::preprocess(&mut rng, nova_params_pp_hash, nova_pp_hash),
+            Err(Error::NotSupportedYet(_))
+        ));
+
         // prepare the Decider prover & verifier params
         let (decider_pp, decider_vp) = D::preprocess(&mut rng, nova_params, nova.clone()).unwrap();
 
         let start = Instant::now();
-        nova.prove_step(&mut rng, vec![], None).unwrap();
+        nova.prove_step(&mut rng, (), None).unwrap();
         println!("prove_step, {:?}", start.elapsed());
-        nova.prove_step(&mut rng, vec![], None).unwrap(); // do a 2nd step
+        nova.prove_step(&mut rng, (), None).unwrap(); // do a 2nd step
 
         // decider proof generation
         let start = Instant::now();
@@ -390,6 +1106,42 @@ pub mod tests {
         assert!(verified);
         println!("Decider verify, {:?}", start.elapsed());
 
+        // the proof must not verify under a different context
+        let mut other_context_vp = decider_vp.clone();
+        other_context_vp.context += Fr::one();
+        let verified = D::verify(
+            other_context_vp,
+            nova.i,
+            nova.z_0.clone(),
+            nova.z_i.clone(),
+            &nova.U_i.get_commitments(),
+            &nova.u_i.get_commitments(),
+            &proof,
+        );
+        assert!(!matches!(verified, Ok(true)));
+
+        // corrupted proofs and statements are rejected with an error instead of panicking
+        let corruptions: Vec<fn(&mut Proof<Projective, KZG<'static, Bn254>, Groth16<Bn254>>)> = vec![
+            |p| p.snark_proof.c = p.snark_proof.a,
+            |p| p.kzg_proof.eval += Fr::one(),
+            |p| p.kzg_proof.proof += Projective::generator(),
+            |p| p.cmT += Projective::generator(),
+            |p| p.r += Fr::one(),
+            |p| p.kzg_challenge += Fr::one(),
+            |p| p.kzg_batching_challenge += Fr::one(),
+            |p| p.kzg_evaluations[0] += Fr::one(),
+        ];
+        test_corrupted_decider_proof::<Projective, Projective2, CubicFCircuit<Fr>, N, D>(
+            decider_vp.clone(),
+            nova.i,
+            nova.z_0.clone(),
+            nova.z_i.clone(),
+            nova.U_i.get_commitments(),
+            nova.u_i.get_commitments(),
+            &proof,
+            corruptions,
+        );
+
         // decider proof verification using the deserialized data
         let verified = D::verify(
             decider_vp,
@@ -404,6 +1156,256 @@ pub mod tests {
         assert!(verified);
     }
 
+    #[test]
+    fn test_decider_snarkjs_json() {
+        type N = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            KZG<'static, Bn254>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        type D = Decider<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            KZG<'static, Bn254>,
+            Pedersen<Projective2>,
+            Groth16<Bn254>,
+            N,
+        >;
+
+        let mut rng = rand::rngs::OsRng;
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let z_0 = vec![Fr::from(3_u32)];
+
+        let preprocessor_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let nova_params = N::preprocess(&mut rng, &preprocessor_param).unwrap();
+        let mut nova = N::init(&nova_params, F_circuit, z_0).unwrap();
+        let (decider_pp, decider_vp) = D::preprocess(&mut rng, nova_params, nova.clone()).unwrap();
+        nova.prove_step(&mut rng, (), None).unwrap();
+        nova.prove_step(&mut rng, (), None).unwrap();
+
+        let proof = D::prove(rng, decider_pp, nova.clone()).unwrap();
+
+        let (proof_json, public_json) = proof
+            .to_snarkjs_json(
+                decider_vp.pp_hash,
+                decider_vp.context,
+                nova.i,
+                &nova.z_0,
+                &nova.z_i,
+                false,
+                &nova.U_i,
+                &nova.u_i,
+            )
+            .unwrap();
+
+        // re-import the exported values and check that they match the original ones
+        let (snark_proof, public_inputs) = from_snarkjs_json(&proof_json, &public_json).unwrap();
+        assert_eq!(snark_proof, proof.snark_proof);
+        assert_eq!(
+            public_inputs,
+            proof
+                .public_inputs(
+                    decider_vp.pp_hash,
+                    decider_vp.context,
+                    nova.i,
+                    &nova.z_0,
+                    &nova.z_i,
+                    false,
+                    &nova.U_i.get_commitments(),
+                    &nova.u_i.get_commitments(),
+                )
+                .unwrap()
+        );
+
+        // verify the re-imported proof with ark-groth16
+        assert!(
+            Groth16::<Bn254>::verify(&decider_vp.snark_vp, &public_inputs, &snark_proof).unwrap()
+        );
+
+        // a tampered public input must not verify
+        let mut public_inputs = public_inputs;
+        public_inputs[2] += Fr::one();
+        assert!(
+            !Groth16::<Bn254>::verify(&decider_vp.snark_vp, &public_inputs, &snark_proof).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decider_hash_state() {
+        type N = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            KZG<'static, Bn254>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        type D = Decider<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            KZG<'static, Bn254>,
+            Pedersen<Projective2>,
+            Groth16<Bn254>,
+            N,
+            true,
+        >;
+
+        let mut rng = rand::rngs::OsRng;
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let z_0 = vec![Fr::from(3_u32)];
+
+        let preprocessor_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let nova_params = N::preprocess(&mut rng, &preprocessor_param).unwrap();
+        let mut nova = N::init(&nova_params, F_circuit, z_0).unwrap();
+        let (decider_pp, decider_vp) = D::preprocess(&mut rng, nova_params, nova.clone()).unwrap();
+        assert!(decider_vp.hash_state);
+        nova.prove_step(&mut rng, (), None).unwrap();
+        nova.prove_step(&mut rng, (), None).unwrap();
+
+        let proof = D::prove(rng, decider_pp, nova.clone()).unwrap();
+        let verified = D::verify(
+            decider_vp.clone(),
+            nova.i,
+            nova.z_0.clone(),
+            nova.z_i.clone(),
+            &nova.U_i.get_commitments(),
+            &nova.u_i.get_commitments(),
+            &proof,
+        )
+        .unwrap();
+        assert!(verified);
+
+        // the states are replaced by their digests in the public inputs
+        let public_inputs = proof
+            .public_inputs(
+                decider_vp.pp_hash,
+                decider_vp.context,
+                nova.i,
+                &nova.z_0,
+                &nova.z_i,
+                true,
+                &nova.U_i.get_commitments(),
+                &nova.u_i.get_commitments(),
+            )
+            .unwrap();
+        assert_eq!(public_inputs[3], keccak256_digest(&nova.z_0));
+        assert_eq!(public_inputs[4], keccak256_digest(&nova.z_i));
+        assert!(
+            Groth16::<Bn254>::verify(&decider_vp.snark_vp, &public_inputs, &proof.snark_proof)
+                .unwrap()
+        );
+
+        // a different state must not verify
+        let mut z_i = nova.z_i.clone();
+        z_i[0] += Fr::one();
+        let verified = D::verify(
+            decider_vp,
+            nova.i,
+            nova.z_0,
+            z_i,
+            &nova.U_i.get_commitments(),
+            &nova.u_i.get_commitments(),
+            &proof,
+        );
+        assert!(!matches!(verified, Ok(true)));
+    }
+
+    #[test]
+    fn test_decider_verify_batch() {
+        type N = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            KZG<'static, Bn254>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        type D = Decider<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            KZG<'static, Bn254>,
+            Pedersen<Projective2>,
+            Groth16<Bn254>,
+            N,
+        >;
+
+        let mut rng = rand::rngs::OsRng;
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let z_0 = vec![Fr::from(3_u32)];
+
+        let preprocessor_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let nova_params = N::preprocess(&mut rng, &preprocessor_param).unwrap();
+        let mut nova = N::init(&nova_params, F_circuit, z_0).unwrap();
+        let (decider_pp, decider_vp) = D::preprocess(&mut rng, nova_params, nova.clone()).unwrap();
+        nova.prove_step(&mut rng, (), None).unwrap();
+
+        // generate decider proofs for the steps 2, 3 and 4
+        let mut statements = vec![];
+        let mut proofs = vec![];
+        for _ in 0..3 {
+            nova.prove_step(&mut rng, (), None).unwrap();
+            proofs.push(D::prove(rng, decider_pp.clone(), nova.clone()).unwrap());
+            statements.push(Statement {
+                i: nova.i,
+                z_0: nova.z_0.clone(),
+                z_i: nova.z_i.clone(),
+                running_commitments: nova.U_i.get_commitments(),
+                incoming_commitments: nova.u_i.get_commitments(),
+            });
+        }
+
+        D::verify_batch(rng, &decider_vp, &statements, &proofs).unwrap();
+
+        // corrupt the Groth16 proof at index 1
+        let mut corrupted_proofs = proofs.clone();
+        corrupted_proofs[1].snark_proof.c =
+            (corrupted_proofs[1].snark_proof.c + Projective::generator()).into_affine();
+        assert!(matches!(
+            D::verify_batch(rng, &decider_vp, &statements, &corrupted_proofs),
+            Err(Error::BatchVerificationFail(1))
+        ));
+
+        // corrupt the KZG proof at index 2
+        let mut corrupted_proofs = proofs.clone();
+        corrupted_proofs[2].kzg_proof.proof += Projective::generator();
+        assert!(matches!(
+            D::verify_batch(rng, &decider_vp, &statements, &corrupted_proofs),
+            Err(Error::BatchVerificationFail(2))
+        ));
+
+        // statement mismatch at index 0
+        let mut corrupted_statements = statements.clone();
+        corrupted_statements[0].z_i[0] += Fr::one();
+        assert!(matches!(
+            D::verify_batch(rng, &decider_vp, &corrupted_statements, &proofs),
+            Err(Error::BatchVerificationFail(0))
+        ));
+    }
+
     // Test to check the serialization and deserialization of diverse Decider related parameters.
     // This test is the same test as `test_decider` but it serializes values and then uses the
     // deserialized values to continue the checks.
@@ -488,9 +1490,9 @@ pub mod tests {
         let mut nova = N::init(&nova_params, F_circuit, z_0).unwrap();
 
         let start = Instant::now();
-        nova.prove_step(&mut rng, vec![], None).unwrap();
+        nova.prove_step(&mut rng, (), None).unwrap();
         println!("prove_step, {:?}", start.elapsed());
-        nova.prove_step(&mut rng, vec![], None).unwrap(); // do a 2nd step
+        nova.prove_step(&mut rng, (), None).unwrap(); // do a 2nd step
 
         // decider proof generation
         let start = Instant::now();
@@ -16,7 +16,7 @@ use ark_r1cs_std::{
     ToConstraintFieldGadget,
 };
 use ark_relations::r1cs::{Namespace, SynthesisError};
-use ark_std::{borrow::Borrow, marker::PhantomData};
+use ark_std::{borrow::Borrow, marker::PhantomData, string::ToString, vec::Vec};
 
 use super::{
     nifs::nova_circuits::{CommittedInstanceVar, NIFSGadget},
@@ -29,9 +29,10 @@ use crate::folding::{
         decider::on_chain::GenericOnchainDeciderCircuit, nonnative::affine::NonNativeAffineVar,
         CF1, CF2,
     },
-    traits::{WitnessOps, WitnessVarOps},
+    traits::{CommittedInstanceOps, WitnessOps, WitnessVarOps},
 };
 use crate::frontend::FCircuit;
+use crate::utils::vec::check_len;
 use crate::Error;
 use crate::{
     arith::r1cs::{circuits::R1CSMatricesVar, R1CS},
@@ -103,14 +104,18 @@ impl<
         // enforce that the CS2 is Pedersen commitment scheme, since we're at Ethereum's EVM decider
         CS2: CommitmentScheme<C2, H, ProverParams = PedersenParams<C2>>,
         const H: bool,
-    > TryFrom<Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H>> for DeciderEthCircuit<C1, C2, GC2>
+        const FULL_CHALLENGE: bool,
+    > TryFrom<Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H, FULL_CHALLENGE>>
+    for DeciderEthCircuit<C1, C2, GC2>
 where
     CF1<C1>: Absorb,
     <C1 as CurveGroup>::BaseField: PrimeField,
 {
     type Error = Error;
 
-    fn try_from(nova: Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H>) -> Result<Self, Error> {
+    fn try_from(
+        nova: Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H, FULL_CHALLENGE>,
+    ) -> Result<Self, Error> {
         let mut transcript = PoseidonSponge::<C1::ScalarField>::new(&nova.poseidon_config);
 
         // compute the U_{i+1}, W_{i+1}
@@ -127,17 +132,24 @@ where
         let r_Fr = C1::ScalarField::from_bigint(BigInteger::from_bits_le(&r_bits))
             .ok_or(Error::OutOfBounds)?;
 
-        // compute the KZG challenges used as inputs in the circuit
-        let kzg_challenges = KZGChallengesGadget::get_challenges_native(&mut transcript, &U_i1);
+        // compute the KZG challenge used as input in the circuit
+        let kzg_challenge = KZGChallengesGadget::get_challenge_native(
+            &mut transcript,
+            &U_i1.get_commitment_labels(),
+            &U_i1,
+        )?;
 
         // get KZG evals
         let kzg_evaluations = W_i1
             .get_openings()
             .iter()
-            .zip(&kzg_challenges)
-            .map(|((v, _), &c)| EvalGadget::evaluate_native(v, c))
+            .map(|(v, _)| EvalGadget::evaluate_native(v, kzg_challenge))
             .collect::<Result<Vec<_>, _>>()?;
 
+        // compute the challenge used to batch the KZG openings
+        let kzg_batching_challenge =
+            KZGChallengesGadget::get_batching_challenge_native(&mut transcript, &kzg_evaluations);
+
         Ok(Self {
             _gc2: PhantomData,
             _avar: PhantomData,
@@ -146,9 +158,11 @@ where
             cf_pedersen_params: nova.cf_cs_pp,
             poseidon_config: nova.poseidon_config,
             pp_hash: nova.pp_hash,
+            context: Some(nova.context),
             i: nova.i,
             z_0: nova.z_0,
             z_i: nova.z_i,
+            hash_state: false,
             U_i: nova.U_i,
             W_i: nova.W_i,
             u_i: nova.u_i,
@@ -159,8 +173,11 @@ where
             randomness: r_Fr,
             cf_U_i: nova.cf_U_i,
             cf_W_i: nova.cf_W_i,
-            kzg_challenges,
+            cf_blinding: None,
+            kzg_challenge,
             kzg_evaluations,
+            kzg_batching_challenge,
+            opening_point: None,
         })
     }
 }
@@ -189,7 +206,7 @@ where
         _randomness: CF1<C>,
     ) -> Result<CommittedInstanceVar<C>, SynthesisError> {
         let cs = transcript.cs();
-        let cmT = NonNativeAffineVar::new_input(cs.clone(), || Ok(proof))?;
+        let cmT = NonNativeAffineVar::new_packed_input(cs.clone(), || Ok(proof))?;
         let (new_U, _) = NIFSGadget::verify(transcript, pp_hash, U, U_vec, u, Some(cmT))?;
         Ok(new_U)
     }
@@ -200,7 +217,9 @@ where
         cmT: Option<Self::Proof>,
         r: Self::Randomness,
     ) -> Result<Vec<C>, Error> {
-        let cmT = cmT.ok_or(Error::Empty)?;
+        let cmT = cmT.ok_or(Error::MissingValue("cmT".to_string()))?;
+        check_len(U_commitments.len(), 2)?;
+        check_len(u_commitments.len(), 2)?;
         let U_cmW = U_commitments[0];
         let U_cmE = U_commitments[1];
         let u_cmW = u_commitments[0];
@@ -220,6 +239,9 @@ pub mod tests {
     use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
     use ark_vesta::{constraints::GVar as GVar2, Projective as Projective2};
 
+    use ark_ec::Group;
+    use ark_std::Zero;
+
     use super::*;
     use crate::commitment::pedersen::Pedersen;
     use crate::folding::nova::PreprocessorParam;
@@ -258,7 +280,7 @@ pub mod tests {
 
         // generate a Nova instance and do a step of it
         let mut nova = N::init(&nova_params, F_circuit, z_0.clone()).unwrap();
-        nova.prove_step(&mut rng, vec![], None).unwrap();
+        nova.prove_step(&mut rng, (), None).unwrap();
         let ivc_proof = nova.ivc_proof();
         N::verify(nova_params.1, ivc_proof).unwrap();
 
@@ -271,5 +293,41 @@ pub mod tests {
         // generate the constraints and check that are satisfied by the inputs
         decider_circuit.generate_constraints(cs.clone()).unwrap();
         assert!(cs.is_satisfied().unwrap());
+
+        // the public inputs are the constant one, `pp_hash`, `i`, `z_0`, `z_i`, the 4 KZG values,
+        // and `U_{i+1}.cmW`, `U_{i+1}.cmE` and `cmT`, each packed into 3 field elements
+        assert_eq!(
+            cs.num_instance_variables(),
+            1 + 2 + 2 * z_0.len() + 4 + 3 * 3
+        );
+    }
+
+    #[test]
+    fn test_fold_group_elements_native_lengths() {
+        let fold = |U: &[Projective], u: &[Projective], cmT: Option<Projective>| {
+            <DeciderNovaGadget as DeciderEnabledNIFS<
+                Projective,
+                CommittedInstance<Projective>,
+                CommittedInstance<Projective>,
+                Witness<Projective>,
+                R1CS<Fr>,
+            >>::fold_group_elements_native(U, u, cmT, Fr::from(2_u32))
+        };
+        let g = Projective::generator();
+        let zero = Projective::zero();
+
+        assert!(matches!(
+            fold(&[g], &[g, zero], Some(g)),
+            Err(Error::NotExpectedLength(1, 2))
+        ));
+        assert!(matches!(
+            fold(&[g, g], &[], Some(g)),
+            Err(Error::NotExpectedLength(0, 2))
+        ));
+        assert!(matches!(
+            fold(&[g, g], &[g, zero], None),
+            Err(Error::MissingValue(_))
+        ));
+        assert!(fold(&[g, g], &[g, zero], Some(g)).is_ok());
     }
 }
@@ -0,0 +1,457 @@
+/// This file implements the offchain decider for Nova instances committed with IPA, whose
+/// openings are aggregated into a single IPA proof per curve of the cycle, see
+/// `IPA::prove_aggregated`. For KZG commitments, use the Decider from decider.rs file.
+///
+/// The decider circuits are the ones of the KZG offchain decider, reading the witness vectors as
+/// the coefficients of the committed polynomials instead of as their evaluations over the domain.
+use ark_crypto_primitives::sponge::{poseidon::PoseidonSponge, Absorb, CryptographicSponge};
+use ark_ec::{CurveGroup, Group};
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{groups::GroupOpsBounds, prelude::CurveVar, ToConstraintFieldGadget};
+use ark_snark::SNARK;
+use ark_std::{
+    rand::{CryptoRng, RngCore},
+    string::ToString,
+    vec::Vec,
+};
+use ark_std::{One, Zero};
+use core::marker::PhantomData;
+
+use super::decider::{ProverParam, VerifierParam};
+use super::decider_circuits::{DeciderCircuit1, DeciderCircuit2};
+use super::decider_eth_circuit::DeciderNovaGadget;
+use super::Nova;
+use crate::commitment::{
+    ipa::{AggregatedProof, IPA},
+    pedersen::Params as PedersenParams,
+};
+use crate::folding::circuits::decider::DeciderEnabledNIFS;
+use crate::folding::circuits::{
+    cyclefold::{CycleFoldCommittedInstance, CycleFoldCommittedInstanceVar},
+    nonnative::affine::nonnative_affine_to_packed_field_elements,
+    CF2,
+};
+use crate::folding::traits::{CommittedInstanceOps, Inputize, WitnessOps};
+use crate::frontend::FCircuit;
+use crate::transcript::poseidon::poseidon_canonical_config;
+use crate::Error;
+use crate::{Decider as DeciderTrait, FoldingScheme};
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Proof<C1, C2, S1, S2>
+where
+    C1: CurveGroup,
+    C2: CurveGroup,
+    S1: SNARK<C1::ScalarField>,
+    S2: SNARK<C2::ScalarField>,
+{
+    c1_snark_proof: S1::Proof,
+    c2_snark_proof: S2::Proof,
+    // single proofs of the openings of the two commitments of each curve, whose evaluations are
+    // public inputs of the SNARK proofs
+    cs1_proof: AggregatedProof<C1>,
+    cs2_proof: AggregatedProof<C2>,
+    // cmT and r are values for the last fold, U_{i+1}=NIFS.V(r, U_i, u_i, cmT), and they are
+    // checked in-circuit
+    cmT: C1,
+    r: C1::ScalarField,
+    // cyclefold committed instance
+    cf_U_final: CycleFoldCommittedInstance<C2>,
+    // the CS challenges are provided by the prover, but in-circuit they are checked to match the
+    // in-circuit computed computed ones.
+    cs1_challenges: [C1::ScalarField; 2],
+    cs2_challenges: [C2::ScalarField; 2],
+}
+
+// implemented by hand, since deriving it would require `S1: Clone` and `S2: Clone`, which
+// `Groth16` does not implement
+impl<C1, C2, S1, S2> Clone for Proof<C1, C2, S1, S2>
+where
+    C1: CurveGroup,
+    C2: CurveGroup,
+    S1: SNARK<C1::ScalarField>,
+    S2: SNARK<C2::ScalarField>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            c1_snark_proof: self.c1_snark_proof.clone(),
+            c2_snark_proof: self.c2_snark_proof.clone(),
+            cs1_proof: self.cs1_proof.clone(),
+            cs2_proof: self.cs2_proof.clone(),
+            cmT: self.cmT,
+            r: self.r,
+            cf_U_final: self.cf_U_final.clone(),
+            cs1_challenges: self.cs1_challenges,
+            cs2_challenges: self.cs2_challenges,
+        }
+    }
+}
+
+/// Offchain Decider for IPA commitments, which, unlike the KZG one, does not need pairings for
+/// the commitments, so it is also available over cycles such as Pasta given SNARKs over them.
+/// With hiding commitments (`H = true`), the commitments are opened together with the blinding
+/// factors of the folded witnesses.
+#[derive(Clone, Debug)]
+pub struct Decider<C1, GC1, C2, GC2, FC, S1, S2, FS, const H: bool = false> {
+    _c1: PhantomData<C1>,
+    _gc1: PhantomData<GC1>,
+    _c2: PhantomData<C2>,
+    _gc2: PhantomData<GC2>,
+    _fc: PhantomData<FC>,
+    _s1: PhantomData<S1>,
+    _s2: PhantomData<S2>,
+    _fs: PhantomData<FS>,
+}
+
+impl<C1, GC1, C2, GC2, FC, S1, S2, FS, const H: bool> DeciderTrait<C1, C2, FC, FS>
+    for Decider<C1, GC1, C2, GC2, FC, S1, S2, FS, H>
+where
+    C1: CurveGroup,
+    C2: CurveGroup,
+    GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+    GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+    FC: FCircuit<C1::ScalarField>,
+    S1: SNARK<C1::ScalarField>,
+    S2: SNARK<C2::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>,
+    <C1 as CurveGroup>::BaseField: PrimeField,
+    <C2 as CurveGroup>::BaseField: PrimeField,
+    <C1 as Group>::ScalarField: Absorb,
+    <C2 as Group>::ScalarField: Absorb,
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    for<'b> &'b GC1: GroupOpsBounds<'b, C1, GC1>,
+    for<'b> &'b GC2: GroupOpsBounds<'b, C2, GC2>,
+    // constrain FS into Nova, since this is a Decider specifically for Nova
+    Nova<C1, GC1, C2, GC2, FC, IPA<C1, H>, IPA<C2, H>, H>: From<FS>,
+    crate::folding::nova::ProverParams<C1, C2, IPA<C1, H>, IPA<C2, H>, H>:
+        From<<FS as FoldingScheme<C1, C2, FC>>::ProverParam>,
+    crate::folding::nova::VerifierParams<C1, C2, IPA<C1, H>, IPA<C2, H>, H>:
+        From<<FS as FoldingScheme<C1, C2, FC>>::VerifierParam>,
+{
+    type PreprocessorParam = (FS::ProverParam, FS::VerifierParam);
+    type ProverParam =
+        ProverParam<PedersenParams<C1>, S1::ProvingKey, PedersenParams<C2>, S2::ProvingKey>;
+    type Proof = Proof<C1, C2, S1, S2>;
+    type VerifierParam = VerifierParam<
+        C1,
+        PedersenParams<C1>,
+        S1::VerifyingKey,
+        PedersenParams<C2>,
+        S2::VerifyingKey,
+    >;
+    type PublicInput = Vec<C1::ScalarField>;
+    type CommittedInstance = Vec<C1>;
+
+    fn preprocess(
+        mut rng: impl RngCore + CryptoRng,
+        prep_param: Self::PreprocessorParam,
+        fs: FS,
+    ) -> Result<(Self::ProverParam, Self::VerifierParam), Error> {
+        let nova = Nova::from(fs);
+        // the decider circuits enforce that `u_i.x` only contains the two hashes of the state
+        if nova.public_pp_hash {
+            return Err(Error::NotSupportedYet(
+                "Nova Decider with pp_hash as a public input".to_string(),
+            ));
+        }
+        let mut circuit1 = DeciderCircuit1::<C1, C2, GC2>::try_from(nova.clone())?;
+        let mut circuit2 = DeciderCircuit2::<C2>::try_from(nova)?;
+        circuit1.coefficient_form = true;
+        circuit2.coefficient_form = true;
+        let context = circuit1.context.unwrap_or_else(C1::ScalarField::zero);
+
+        // get the Groth16 specific setup for the circuits
+        let (c1_g16_pk, c1_g16_vk) = S1::circuit_specific_setup(circuit1, &mut rng)
+            .map_err(|e| Error::SNARKSetupFail(e.to_string()))?;
+        let (c2_g16_pk, c2_g16_vk) = S2::circuit_specific_setup(circuit2, &mut rng)
+            .map_err(|e| Error::SNARKSetupFail(e.to_string()))?;
+
+        // get the FoldingScheme prover & verifier params from Nova
+        #[allow(clippy::type_complexity)]
+        let nova_pp: <Nova<C1, GC1, C2, GC2, FC, IPA<C1, H>, IPA<C2, H>, H> as FoldingScheme<
+            C1,
+            C2,
+            FC,
+        >>::ProverParam = prep_param.0.clone().into();
+        #[allow(clippy::type_complexity)]
+        let nova_vp: <Nova<C1, GC1, C2, GC2, FC, IPA<C1, H>, IPA<C2, H>, H> as FoldingScheme<
+            C1,
+            C2,
+            FC,
+        >>::VerifierParam = prep_param.1.clone().into();
+
+        let pp_hash = nova_vp.pp_hash()?;
+        let pp = Self::ProverParam {
+            c1_snark_pp: c1_g16_pk,
+            c1_cs_pp: nova_pp.cs_pp,
+            c2_snark_pp: c2_g16_pk,
+            c2_cs_pp: nova_pp.cf_cs_pp,
+        };
+        let vp = Self::VerifierParam {
+            pp_hash,
+            context,
+            c1_snark_vp: c1_g16_vk,
+            c1_cs_vp: nova_vp.cs_vp,
+            c2_snark_vp: c2_g16_vk,
+            c2_cs_vp: nova_vp.cf_cs_vp,
+        };
+        Ok((pp, vp))
+    }
+
+    fn prove(
+        mut rng: impl RngCore + CryptoRng,
+        pp: Self::ProverParam,
+        fs: FS,
+    ) -> Result<Self::Proof, Error> {
+        trace_span!("nova_decider_ipa_prove");
+        let mut circuit1 = DeciderCircuit1::<C1, C2, GC2>::try_from(Nova::from(fs.clone()))?;
+        let mut circuit2 = DeciderCircuit2::<C2>::try_from(Nova::from(fs))?;
+
+        let cmT = circuit1.proof;
+        let r = circuit1.randomness;
+        let cf_U_final = circuit1.cf_U_i.clone();
+
+        // the openings of each curve are aggregated into a single IPA proof, whose evaluations
+        // replace the ones of the circuits, since IPA reads the witness vectors as coefficients
+        let (polys, blinds): (Vec<_>, Vec<_>) = circuit1
+            .W_i1
+            .get_openings()
+            .into_iter()
+            .map(|(v, r)| (v.to_vec(), r))
+            .unzip();
+        let mut transcript1 = decider_transcript(circuit1.pp_hash);
+        let cs1_proof = IPA::<C1, H>::prove_aggregated(
+            &pp.c1_cs_pp,
+            &mut transcript1,
+            &circuit1.U_i1.get_commitments(),
+            &polys,
+            &blinds,
+            &circuit1.kzg_challenges,
+            Some(&mut rng),
+        )?;
+        circuit1.kzg_evaluations = cs1_proof.evals.clone();
+        circuit1.coefficient_form = true;
+
+        let (polys, blinds): (Vec<_>, Vec<_>) = circuit2
+            .cf_W_i
+            .get_openings()
+            .into_iter()
+            .map(|(v, r)| (v.to_vec(), r))
+            .unzip();
+        let mut transcript2 = decider_transcript(circuit2.pp_hash);
+        let cs2_proof = IPA::<C2, H>::prove_aggregated(
+            &pp.c2_cs_pp,
+            &mut transcript2,
+            &circuit2.cf_U_i.get_commitments(),
+            &polys,
+            &blinds,
+            &circuit2.kzg_challenges,
+            Some(&mut rng),
+        )?;
+        circuit2.kzg_evaluations = cs2_proof.evals.clone();
+        circuit2.coefficient_form = true;
+
+        let cs1_challenges = circuit1.kzg_challenges.clone();
+        let cs2_challenges = circuit2.kzg_challenges.clone();
+
+        let c1_snark_proof = S1::prove(&pp.c1_snark_pp, circuit1, &mut rng)
+            .map_err(|e| Error::Other(e.to_string()))?;
+        let c2_snark_proof = S2::prove(&pp.c2_snark_pp, circuit2, &mut rng)
+            .map_err(|e| Error::Other(e.to_string()))?;
+
+        Ok(Self::Proof {
+            c1_snark_proof,
+            c2_snark_proof,
+            cs1_proof,
+            cs2_proof,
+            cmT,
+            r,
+            cf_U_final,
+            cs1_challenges: cs1_challenges
+                .try_into()
+                .map_err(|e: Vec<_>| Error::NotExpectedLength(e.len(), 2))?,
+            cs2_challenges: cs2_challenges
+                .try_into()
+                .map_err(|e: Vec<_>| Error::NotExpectedLength(e.len(), 2))?,
+        })
+    }
+
+    fn verify(
+        vp: Self::VerifierParam,
+        i: C1::ScalarField,
+        z_0: Vec<C1::ScalarField>,
+        z_i: Vec<C1::ScalarField>,
+        // we don't use the instances at the verifier level, since we check them in-circuit
+        running_commitments: &Self::CommittedInstance,
+        incoming_commitments: &Self::CommittedInstance,
+        proof: &Self::Proof,
+    ) -> Result<bool, Error> {
+        trace_span!("nova_decider_ipa_verify");
+        if i <= C1::ScalarField::one() {
+            return Err(Error::NotEnoughSteps);
+        }
+
+        // 6.2. Fold the commitments
+        let U_final_commitments = DeciderNovaGadget::fold_group_elements_native(
+            running_commitments,
+            incoming_commitments,
+            Some(proof.cmT),
+            proof.r,
+        )?;
+        let cf_U = proof.cf_U_final.clone();
+
+        // snark proof 1
+        let c1_public_input = [
+            &[vp.pp_hash, vp.context, i][..],
+            &z_0,
+            &z_i,
+            &U_final_commitments
+                .iter()
+                .flat_map(|c| c.inputize())
+                .collect::<Vec<_>>(),
+            &Inputize::<CF2<C2>, CycleFoldCommittedInstanceVar<C2, GC2>>::inputize(&cf_U),
+            &proof.cs1_challenges,
+            &proof.cs1_proof.evals,
+            &nonnative_affine_to_packed_field_elements(proof.cmT),
+        ]
+        .concat();
+
+        let c1_snark_v = S1::verify(&vp.c1_snark_vp, &c1_public_input, &proof.c1_snark_proof)
+            .map_err(|e| Error::Other(e.to_string()))?;
+        if !c1_snark_v {
+            return Err(Error::SNARKVerificationFail);
+        }
+
+        // snark proof 2
+        // migrate pp_hash from C1::Fr to C1::Fq
+        let pp_hash_Fq =
+            C2::ScalarField::from_le_bytes_mod_order(&vp.pp_hash.into_bigint().to_bytes_le());
+        let c2_public_input: Vec<C2::ScalarField> = [
+            &[pp_hash_Fq][..],
+            &cf_U.inputize(),
+            &proof.cs2_challenges,
+            &proof.cs2_proof.evals,
+        ]
+        .concat();
+
+        let c2_snark_v = S2::verify(&vp.c2_snark_vp, &c2_public_input, &proof.c2_snark_proof)
+            .map_err(|e| Error::Other(e.to_string()))?;
+        if !c2_snark_v {
+            return Err(Error::SNARKVerificationFail);
+        }
+
+        // 7.3. check C1 commitments (main instance commitments)
+        IPA::<C1, H>::verify_aggregated(
+            &vp.c1_cs_vp,
+            &mut decider_transcript(vp.pp_hash),
+            &U_final_commitments,
+            &proof.cs1_challenges,
+            &proof.cs1_proof,
+        )?;
+
+        // 4.3. check C2 commitments (CycleFold instance commitments)
+        IPA::<C2, H>::verify_aggregated(
+            &vp.c2_cs_vp,
+            &mut decider_transcript(pp_hash_Fq),
+            &cf_U.get_commitments(),
+            &proof.cs2_challenges,
+            &proof.cs2_proof,
+        )?;
+
+        Ok(true)
+    }
+}
+
+/// Transcript of the aggregated openings, bound to the public params hash
+fn decider_transcript<F: PrimeField + Absorb>(pp_hash: F) -> PoseidonSponge<F> {
+    let mut transcript = PoseidonSponge::<F>::new(&poseidon_canonical_config());
+    transcript.absorb(&pp_hash);
+    transcript
+}
+
+#[cfg(test)]
+pub mod tests {
+    use ark_groth16::Groth16;
+
+    // Note: do not use the MNTx_298 curves in practice, these are just for tests. Use the MNTx_753
+    // curves instead.
+    use ark_mnt4_298::{
+        constraints::G1Var as GVar, Fr, G1Projective as Projective, MNT4_298 as MNT4,
+    };
+    use ark_mnt6_298::{
+        constraints::G1Var as GVar2, G1Projective as Projective2, MNT6_298 as MNT6,
+    };
+
+    use super::*;
+    use crate::folding::nova::PreprocessorParam;
+    use crate::frontend::utils::CubicFCircuit;
+
+    // use Nova as FoldingScheme
+    type N<const H: bool> = Nova<
+        Projective,
+        GVar,
+        Projective2,
+        GVar2,
+        CubicFCircuit<Fr>,
+        IPA<Projective, H>,
+        IPA<Projective2, H>,
+        H,
+    >;
+    type D<const H: bool> = Decider<
+        Projective,
+        GVar,
+        Projective2,
+        GVar2,
+        CubicFCircuit<Fr>,
+        Groth16<MNT4>,
+        Groth16<MNT6>,
+        N<H>, // here we define the FoldingScheme to use
+        H,
+    >;
+
+    #[test]
+    fn test_decider() {
+        test_decider_opt::<false>();
+    }
+
+    /// tests the decider with hiding commitments, whose openings take their blinding factors
+    #[test]
+    fn test_decider_hiding() {
+        test_decider_opt::<true>();
+    }
+
+    fn test_decider_opt<const H: bool>() {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let prep_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let nova_params = N::<H>::preprocess(&mut rng, &prep_param).unwrap();
+        let mut nova = N::<H>::init(&nova_params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+        nova.prove_step(&mut rng, (), None).unwrap();
+        nova.prove_step(&mut rng, (), None).unwrap();
+
+        let mut rng = rand::rngs::OsRng;
+        let (decider_pp, decider_vp) =
+            D::<H>::preprocess(&mut rng, nova_params, nova.clone()).unwrap();
+        let proof = D::<H>::prove(rng, decider_pp, nova.clone()).unwrap();
+
+        let verify = |proof: &Proof<Projective, Projective2, Groth16<MNT4>, Groth16<MNT6>>| {
+            D::<H>::verify(
+                decider_vp.clone(),
+                nova.i,
+                nova.z_0.clone(),
+                nova.z_i.clone(),
+                &nova.U_i.get_commitments(),
+                &nova.u_i.get_commitments(),
+                proof,
+            )
+        };
+        assert!(verify(&proof).unwrap());
+
+        // the evaluations are bound both by the SNARK and by the aggregated opening
+        let mut bad_proof = proof.clone();
+        bad_proof.cs1_proof.evals[0] += Fr::one();
+        assert!(verify(&bad_proof).is_err());
+    }
+}
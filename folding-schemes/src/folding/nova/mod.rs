@@ -9,12 +9,14 @@ use ark_crypto_primitives::sponge::{
     Absorb, CryptographicSponge,
 };
 use ark_ec::{CurveGroup, Group};
-use ark_ff::{BigInteger, PrimeField};
+use ark_ff::PrimeField;
 use ark_r1cs_std::{prelude::CurveVar, ToConstraintFieldGadget};
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Valid};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Valid, Validate};
 use ark_std::fmt::Debug;
+use ark_std::io::{Read, Write};
 use ark_std::rand::RngCore;
+use ark_std::{string::ToString, vec::Vec};
 use ark_std::{One, UniformRand, Zero};
 use core::marker::PhantomData;
 
@@ -26,45 +28,84 @@ use crate::folding::{
     circuits::{CF1, CF2},
     traits::Dummy,
 };
-use crate::frontend::FCircuit;
-use crate::transcript::{poseidon::poseidon_canonical_config, AbsorbNonNative, Transcript};
-use crate::utils::vec::is_zero_vec;
+use crate::frontend::{
+    check_no_custom_gates, check_state, check_state_len, check_step_constants,
+    check_step_constants_row, check_step_constants_rows, FCircuit,
+};
+use crate::transcript::{
+    poseidon::{deserialize_poseidon_config, poseidon_canonical_config, serialize_poseidon_config},
+    AbsorbNonNative, Transcript,
+};
+#[cfg(feature = "std")]
+use crate::utils::alloc;
+use crate::utils::deterministic_rng;
+use crate::utils::vec::{check_len, is_zero_vec, read_varint_usize, write_varint};
 use crate::Error;
-use crate::FoldingScheme;
 use crate::{
-    arith::r1cs::{extract_r1cs, extract_w_x, R1CS},
+    arith::r1cs::{extract_r1cs, extract_w_x, r1cs_to_circom_bin, R1CS},
     constants::NOVA_N_BITS_RO,
-    utils::{get_cm_coordinates, pp_hash},
+    utils::pp_hash,
+};
+use crate::{
+    arith::{Arith, ArithSerializer},
+    commitment::{
+        commit_maybe_sparse,
+        versioned::{
+            curve_id, deserialize_body, deserialize_params, deserialize_params_unchecked,
+            into_serialization_error, params_serialized_size, read_versioned, serialize_params,
+            write_versioned, PARAMS_MAGIC,
+        },
+        CommitmentScheme,
+    },
 };
-use crate::{arith::Arith, commitment::CommitmentScheme};
+use crate::{FoldingScheme, FoldingState};
 use decider_eth_circuit::WitnessVar;
 
+pub mod builder;
 pub mod circuits;
+#[cfg(feature = "std")]
+pub mod pipeline;
+pub mod profile;
+#[cfg(feature = "serde")]
+pub mod serde_hex;
 pub mod traits;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 pub mod zk;
 
 // NIFS related:
 pub mod nifs;
 
-use circuits::AugmentedFCircuit;
-use nifs::{nova::NIFS, nova_circuits::CommittedInstanceVar, NIFSTrait};
+use builder::NovaBuilder;
+use circuits::{augmented_io_len, AugmentedFCircuit};
+use nifs::{
+    nova::{challenge_bit_length, NIFS},
+    nova_circuits::CommittedInstanceVar,
+    NIFSTrait,
+};
+use profile::{timed, PhaseProfile, StepProfile, Timer};
 
 // offchain decider
 pub mod decider;
 pub mod decider_circuits;
+pub mod decider_ipa;
 // onchain decider
 pub mod decider_eth;
 pub mod decider_eth_circuit;
 
 use super::traits::{CommittedInstanceOps, Inputize, WitnessOps};
 
-/// Configuration for Nova's CycleFold circuit
-pub struct NovaCycleFoldConfig<C: CurveGroup> {
+/// Configuration for Nova's CycleFold circuit, whose randomness is the folding challenge, which
+/// is full-width when `FULL_CHALLENGE` is set (see `Nova`)
+pub struct NovaCycleFoldConfig<C: CurveGroup, const FULL_CHALLENGE: bool = false> {
     _c: PhantomData<C>,
 }
 
-impl<C: CurveGroup> CycleFoldConfig for NovaCycleFoldConfig<C> {
-    const RANDOMNESS_BIT_LENGTH: usize = NOVA_N_BITS_RO;
+impl<C: CurveGroup, const FULL_CHALLENGE: bool> CycleFoldConfig
+    for NovaCycleFoldConfig<C, FULL_CHALLENGE>
+{
+    const RANDOMNESS_BIT_LENGTH: usize = challenge_bit_length::<C, FULL_CHALLENGE>();
+    const CHALLENGE_BIT_LENGTH: usize = NOVA_N_BITS_RO;
     // Number of points to be folded in the CycleFold circuit, in Nova's case, this is a fixed
     // amount:
     // 2 points to be folded.
@@ -75,7 +116,8 @@ impl<C: CurveGroup> CycleFoldConfig for NovaCycleFoldConfig<C> {
 
 /// CycleFold circuit for computing random linear combinations of group elements
 /// in Nova instances.
-pub type NovaCycleFoldCircuit<C, GC> = CycleFoldCircuit<NovaCycleFoldConfig<C>, GC>;
+pub type NovaCycleFoldCircuit<C, GC, const FULL_CHALLENGE: bool = false> =
+    CycleFoldCircuit<NovaCycleFoldConfig<C, FULL_CHALLENGE>, GC>;
 
 #[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct CommittedInstance<C: CurveGroup> {
@@ -102,6 +144,35 @@ impl<C: CurveGroup> Dummy<&R1CS<CF1<C>>> for CommittedInstance<C> {
     }
 }
 
+impl<C: CurveGroup> CommittedInstance<C> {
+    /// checks that the length of `x` matches the number of public inputs of the given R1CS.
+    pub fn check_dimensions(&self, r1cs: &R1CS<CF1<C>>) -> Result<(), Error> {
+        if self.x.len() != r1cs.num_public_inputs() {
+            return Err(Error::NotSameLength(
+                "x.len()".to_string(),
+                self.x.len(),
+                "r1cs.num_public_inputs()".to_string(),
+                r1cs.num_public_inputs(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the field elements that represent the instance as public inputs of the circuits,
+    /// which is the same as its `inputize`: `[u, x, cmE, cmW]`, where each coordinate of the
+    /// (affine, with `(0, 0)` for the point at infinity) commitments is split into the limbs of
+    /// `NonNativeUintVar` (55 bits each, little-endian).
+    ///
+    /// Notice that the hashes of the instance absorb other encodings of it: its `Absorb` encoding
+    /// in `CommittedInstanceOps::hash`, where the coordinates are split into limbs of
+    /// `MODULUS_BIT_SIZE - 1` bits instead, and, for a CycleFold instance, its `AbsorbNonNative`
+    /// encoding in `hash_cyclefold`, which packs the bits of `u` and `x`, followed by the
+    /// coordinates of the commitments.
+    pub fn to_public_field_elements(&self) -> Vec<C::ScalarField> {
+        self.inputize()
+    }
+}
+
 impl<C: CurveGroup> Absorb for CommittedInstance<C>
 where
     C::ScalarField: Absorb,
@@ -132,6 +203,10 @@ impl<C: CurveGroup> CommittedInstanceOps<C> for CommittedInstance<C> {
         vec![self.cmW, self.cmE]
     }
 
+    fn get_commitment_labels(&self) -> Vec<&'static [u8]> {
+        vec![b"W", b"E"]
+    }
+
     fn is_incoming(&self) -> bool {
         self.cmE == C::zero() && self.u == One::one()
     }
@@ -158,19 +233,20 @@ pub struct Witness<C: CurveGroup> {
 }
 
 impl<C: CurveGroup> Witness<C> {
+    /// Returns the witness of a fresh (non-relaxed) instance. If `H` is set, the commitment to `W`
+    /// is blinded with a random `rW`. The blinding `rE` is always zero, since `E` is zero and the
+    /// commitment of a fresh instance is `cmE = 0` (as enforced by the AugmentedFCircuit and the
+    /// CycleFold circuit), so the blinding of the running `E` only comes from the folding.
     pub fn new<const H: bool>(w: Vec<C::ScalarField>, e_len: usize, mut rng: impl RngCore) -> Self {
-        let (rW, rE) = if H {
-            (
-                C::ScalarField::rand(&mut rng),
-                C::ScalarField::rand(&mut rng),
-            )
+        let rW = if H {
+            C::ScalarField::rand(&mut rng)
         } else {
-            (C::ScalarField::zero(), C::ScalarField::zero())
+            C::ScalarField::zero()
         };
 
         Self {
             E: vec![C::ScalarField::zero(); e_len],
-            rE,
+            rE: C::ScalarField::zero(),
             W: w,
             rW,
         }
@@ -182,8 +258,8 @@ impl<C: CurveGroup> Witness<C> {
         x: Vec<C::ScalarField>,
     ) -> Result<CommittedInstance<C>, Error> {
         let mut cmE = C::zero();
-        if !is_zero_vec::<C::ScalarField>(&self.E) {
-            cmE = CS::commit(params, &self.E, &self.rE)?;
+        if !is_zero_vec::<C::ScalarField>(&self.E) || !self.rE.is_zero() {
+            cmE = commit_maybe_sparse::<C, CS, HC>(params, &self.E, &self.rE)?;
         }
         let cmW = CS::commit(params, &self.W, &self.rW)?;
         Ok(CommittedInstance {
@@ -193,6 +269,45 @@ impl<C: CurveGroup> Witness<C> {
             x,
         })
     }
+
+    /// checks that the commitments of the given CommittedInstance open to the vectors of this
+    /// Witness with its blinding factors.
+    pub fn check_commitments<CS: CommitmentScheme<C, HC>, const HC: bool>(
+        &self,
+        params: &CS::ProverParams,
+        U: &CommittedInstance<C>,
+    ) -> Result<(), Error> {
+        let expected = self.commit::<CS, HC>(params, U.x.clone())?;
+        if expected.cmW != U.cmW {
+            return Err(Error::CommitmentNotSatisfied("cmW".to_string()));
+        }
+        if expected.cmE != U.cmE {
+            return Err(Error::CommitmentNotSatisfied("cmE".to_string()));
+        }
+        Ok(())
+    }
+
+    /// checks that the lengths of `W` and `E` match the number of witnesses and of constraints of
+    /// the given R1CS.
+    pub fn check_dimensions(&self, r1cs: &R1CS<CF1<C>>) -> Result<(), Error> {
+        if self.W.len() != r1cs.num_witnesses() {
+            return Err(Error::NotSameLength(
+                "W.len()".to_string(),
+                self.W.len(),
+                "r1cs.num_witnesses()".to_string(),
+                r1cs.num_witnesses(),
+            ));
+        }
+        if self.E.len() != r1cs.num_constraints() {
+            return Err(Error::NotSameLength(
+                "E.len()".to_string(),
+                self.E.len(),
+                "r1cs.num_constraints()".to_string(),
+                r1cs.num_constraints(),
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl<C: CurveGroup> Dummy<&R1CS<CF1<C>>> for Witness<C> {
@@ -225,11 +340,18 @@ where
 {
     pub poseidon_config: PoseidonConfig<C1::ScalarField>,
     pub F: FC,
-    // cs params if not provided, will be generated at the preprocess method
+    // cs params if not provided, will be generated at the preprocess method. If provided, they
+    // are trimmed to the length needed by the circuit, see `CommitmentScheme::trim`
     pub cs_pp: Option<CS1::ProverParams>,
     pub cs_vp: Option<CS1::VerifierParams>,
     pub cf_cs_pp: Option<CS2::ProverParams>,
     pub cf_cs_vp: Option<CS2::VerifierParams>,
+    // if provided, the missing cs params are derived deterministically from this tag instead of
+    // from the rng, see `CommitmentScheme::setup_deterministic`
+    pub domain_tag: Option<Vec<u8>>,
+    // whether pp_hash is exposed as a public input of the AugmentedFCircuit, see
+    // `PreprocessorParam::with_public_pp_hash`
+    pub public_pp_hash: bool,
 }
 
 impl<C1, C2, FC, CS1, CS2, const H: bool> PreprocessorParam<C1, C2, FC, CS1, CS2, H>
@@ -241,14 +363,41 @@ where
     CS2: CommitmentScheme<C2, H>,
 {
     pub fn new(poseidon_config: PoseidonConfig<C1::ScalarField>, F: FC) -> Self {
-        Self {
-            poseidon_config,
-            F,
-            cs_pp: None,
-            cs_vp: None,
-            cf_cs_pp: None,
-            cf_cs_vp: None,
-        }
+        NovaBuilder::<(), C1, C2, FC, CS1, CS2, H>::new(F)
+            .poseidon(poseidon_config)
+            .into_param()
+    }
+
+    /// Sets the domain-separation tag from which the commitment scheme parameters are derived.
+    pub fn with_domain_tag(mut self, domain_tag: &[u8]) -> Self {
+        self.domain_tag = Some(domain_tag.to_vec());
+        self
+    }
+
+    /// Exposes `pp_hash` as a public input of the AugmentedFCircuit, appended to the committed
+    /// instances as `x[2]`, so that a proof system verifying them can pin it (Nova only). It is
+    /// recorded in the `VerifierParams`, and since it changes the R1CS, it is also bound to the
+    /// `pp_hash`.
+    pub fn with_public_pp_hash(mut self) -> Self {
+        self.public_pp_hash = true;
+        self
+    }
+
+    /// Sets the commitment scheme parameters over C1 and C2 to be used instead of generating new
+    /// ones, eg. the ones of `ProverParams::cs_params` and `VerifierParams::cs_params` from a
+    /// previous `preprocess`, so that only the circuits are regenerated when the FCircuit
+    /// changes. They are trimmed to the length needed by the new circuits, and `preprocess`
+    /// fails if they are too short.
+    pub fn with_cs_params(
+        mut self,
+        cs_params: (CS1::ProverParams, CS1::VerifierParams),
+        cf_cs_params: (CS2::ProverParams, CS2::VerifierParams),
+    ) -> Self {
+        self.cs_pp = Some(cs_params.0);
+        self.cs_vp = Some(cs_params.1);
+        self.cf_cs_pp = Some(cf_cs_params.0);
+        self.cf_cs_vp = Some(cf_cs_params.1);
+        self
     }
 }
 
@@ -296,17 +445,16 @@ where
     CS1: CommitmentScheme<C1, H>,
     CS2: CommitmentScheme<C2, H>,
 {
-    fn serialize_with_mode<W: std::io::prelude::Write>(
+    fn serialize_with_mode<W: ark_std::io::Write>(
         &self,
-        mut writer: W,
+        writer: W,
         compress: ark_serialize::Compress,
     ) -> Result<(), ark_serialize::SerializationError> {
-        self.cs_pp.serialize_with_mode(&mut writer, compress)?;
-        self.cf_cs_pp.serialize_with_mode(&mut writer, compress)
+        serialize_params::<C1, C2, _>(writer, compress, &self.cs_pp, &self.cf_cs_pp)
     }
 
     fn serialized_size(&self, compress: ark_serialize::Compress) -> usize {
-        self.cs_pp.serialized_size(compress) + self.cf_cs_pp.serialized_size(compress)
+        params_serialized_size::<C1, C2>(compress, &self.cs_pp, &self.cf_cs_pp)
     }
 }
 impl<C1, C2, CS1, CS2, const H: bool> CanonicalDeserialize for ProverParams<C1, C2, CS1, CS2, H>
@@ -316,19 +464,234 @@ where
     CS1: CommitmentScheme<C1, H>,
     CS2: CommitmentScheme<C2, H>,
 {
-    fn deserialize_with_mode<R: std::io::prelude::Read>(
-        mut reader: R,
+    fn deserialize_with_mode<R: ark_std::io::Read>(
+        reader: R,
         compress: ark_serialize::Compress,
         validate: ark_serialize::Validate,
     ) -> Result<Self, ark_serialize::SerializationError> {
-        let cs_pp = CS1::ProverParams::deserialize_with_mode(&mut reader, compress, validate)?;
-        let cf_cs_pp = CS2::ProverParams::deserialize_with_mode(&mut reader, compress, validate)?;
+        Self::deserialize_checked(reader, compress, validate).map_err(into_serialization_error)
+    }
+}
+
+// flags of the commitment schemes params omitted by `ProverParams::serialize_compact`
+const OMITTED_CS_PP: u8 = 1;
+const OMITTED_CF_CS_PP: u8 = 1 << 1;
+
+// header of `VerifierParams::serialize_standalone`. The first version of the format had no header
+// and started with the commitment schemes params, see `VerifierParams::deserialize_standalone_legacy`
+const STANDALONE_MAGIC: &[u8; 4] = b"SNBV";
+const STANDALONE_FORMAT_VERSION: u32 = 2;
+
+// reads the bytes of `prefix` followed by the ones of `reader`, as `std::io::Read::chain`, which
+// is not available without `std`
+struct Prefixed<'a, R> {
+    prefix: &'a [u8],
+    reader: R,
+}
+
+impl<R: Read> Read for Prefixed<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> ark_std::io::Result<usize> {
+        if self.prefix.is_empty() {
+            self.reader.read(buf)
+        } else {
+            self.prefix.read(buf)
+        }
+    }
+}
+
+// checks that the matrices of the R1CS have the same dimensions and room for its public inputs,
+// as `R1CS::deserialize_compressed_opt` does
+fn check_r1cs_dimensions<F: PrimeField>(which: &str, r1cs: &R1CS<F>) -> Result<(), Error> {
+    for M in [&r1cs.B, &r1cs.C] {
+        if (M.n_rows, M.n_cols) != (r1cs.A.n_rows, r1cs.A.n_cols) {
+            return Err(Error::ParamsMismatch(format!(
+                "{} R1CS matrices of different sizes, {}x{} and {}x{}",
+                which, r1cs.A.n_rows, r1cs.A.n_cols, M.n_rows, M.n_cols
+            )));
+        }
+    }
+    if r1cs.l >= r1cs.A.n_cols {
+        return Err(Error::ParamsMismatch(format!(
+            "{} R1CS with {} public inputs and {} columns",
+            which, r1cs.l, r1cs.A.n_cols
+        )));
+    }
+    Ok(())
+}
+
+// returns whether the compressed serialization of `value` is `bytes`
+fn is_serialized_as(value: &impl CanonicalSerialize, bytes: &[u8]) -> bool {
+    let mut serialized = Vec::with_capacity(bytes.len());
+    value.serialize_compressed(&mut serialized).is_ok() && serialized == bytes
+}
+
+impl<C1, C2, CS1, CS2, const H: bool> ProverParams<C1, C2, CS1, CS2, H>
+where
+    C1: CurveGroup,
+    C2: CurveGroup,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+{
+    /// returns the proving parameters of the commitment schemes over C1 and C2, which can be
+    /// reused to preprocess other circuits, see `PreprocessorParam::with_cs_params`
+    pub fn cs_params(&self) -> (&CS1::ProverParams, &CS2::ProverParams) {
+        (&self.cs_pp, &self.cf_cs_pp)
+    }
+
+    /// deserializes the ProverParams, checking the versioned header of the commitment schemes
+    /// params (see `commitment::versioned`). Returns `Error::ParamsMismatch` if they were
+    /// generated for other curves or are corrupted.
+    pub fn deserialize_checked<R: ark_std::io::Read>(
+        reader: R,
+        compress: ark_serialize::Compress,
+        validate: ark_serialize::Validate,
+    ) -> Result<Self, Error> {
+        let (cs_pp, cf_cs_pp) = deserialize_params::<C1, C2, _, _, _>(reader, compress, validate)?;
         Ok(ProverParams {
             poseidon_config: poseidon_canonical_config::<C1::ScalarField>(),
             cs_pp,
             cf_cs_pp,
         })
     }
+
+    /// deserializes ProverParams serialized without the versioned header, as done by previous
+    /// versions. None of the header checks is done, so it should only be used for trusted files.
+    pub fn deserialize_unchecked<R: ark_std::io::Read>(
+        reader: R,
+        compress: ark_serialize::Compress,
+        validate: ark_serialize::Validate,
+    ) -> Result<Self, Error> {
+        let (cs_pp, cf_cs_pp) = deserialize_params_unchecked(reader, compress, validate)?;
+        Ok(ProverParams {
+            poseidon_config: poseidon_canonical_config::<C1::ScalarField>(),
+            cs_pp,
+            cf_cs_pp,
+        })
+    }
+
+    /// checks that the commitment schemes params are long enough for the circuits of the given
+    /// VerifierParams, that is, for the length used by `preprocess`.
+    pub fn check_consistency(&self, vp: &VerifierParams<C1, C2, CS1, CS2, H>) -> Result<(), Error> {
+        CS1::trim(&self.cs_pp, &vp.cs_vp, vp.r1cs.A.n_rows)?;
+        CS2::trim(&self.cf_cs_pp, &vp.cf_cs_vp, vp.cf_r1cs.A.n_rows)?;
+        Ok(())
+    }
+
+    /// Serializes the ProverParams with compressed points. If the VerifierParams are given, the
+    /// commitment schemes params that can be recomputed from them (see
+    /// `CommitmentScheme::prover_params_from_verifier`) are omitted, which for Pedersen and IPA
+    /// leaves only the header. The output is read by `deserialize_compact`, which then needs the
+    /// same VerifierParams.
+    pub fn serialize_compact<W: Write>(
+        &self,
+        mut writer: W,
+        vp: Option<&VerifierParams<C1, C2, CS1, CS2, H>>,
+    ) -> Result<(), Error> {
+        let mut body = Vec::new();
+        self.cs_pp.serialize_compressed(&mut body)?;
+        let mut cf_body = Vec::new();
+        self.cf_cs_pp.serialize_compressed(&mut cf_body)?;
+
+        // a component is omitted only if the recomputed params are byte-identical to it
+        let mut omitted = 0u8;
+        if let Some(vp) = vp {
+            if CS1::prover_params_from_verifier(&vp.cs_vp)
+                .is_some_and(|pp| is_serialized_as(&pp, &body))
+            {
+                omitted |= OMITTED_CS_PP;
+                body.clear();
+            }
+            if CS2::prover_params_from_verifier(&vp.cf_cs_vp)
+                .is_some_and(|pp| is_serialized_as(&pp, &cf_body))
+            {
+                omitted |= OMITTED_CF_CS_PP;
+                cf_body.clear();
+            }
+        }
+
+        writer.write_all(&[omitted])?;
+        write_versioned(
+            writer,
+            Compress::Yes,
+            &[(curve_id::<C1>(), body), (curve_id::<C2>(), cf_body)],
+        )?;
+        Ok(())
+    }
+
+    /// Deserializes the ProverParams serialized by `serialize_compact`, recomputing the omitted
+    /// commitment schemes params from the given VerifierParams, and checking that the params are
+    /// consistent with them (see `check_consistency`). The Poseidon config is the one of the
+    /// VerifierParams.
+    pub fn deserialize_compact<R: Read>(
+        mut reader: R,
+        vp: &VerifierParams<C1, C2, CS1, CS2, H>,
+    ) -> Result<Self, Error> {
+        let mut omitted = [0u8; 1];
+        reader.read_exact(&mut omitted)?;
+        let omitted = omitted[0];
+        if omitted & !(OMITTED_CS_PP | OMITTED_CF_CS_PP) != 0 {
+            return Err(Error::ParamsMismatch(format!(
+                "invalid flags of the omitted params {:#04x}",
+                omitted
+            )));
+        }
+        let bodies = read_versioned(reader, Compress::Yes, &[curve_id::<C1>(), curve_id::<C2>()])?;
+        let recompute_err = |name: &str| {
+            Error::ParamsMismatch(format!(
+                "the {} params are omitted, but can not be recomputed from the verifier params",
+                name
+            ))
+        };
+        let cs_pp = if omitted & OMITTED_CS_PP != 0 {
+            check_len(bodies[0].len(), 0)?;
+            CS1::prover_params_from_verifier(&vp.cs_vp).ok_or_else(|| recompute_err("cs_pp"))?
+        } else {
+            deserialize_body(&bodies[0], Compress::Yes, Validate::Yes)?
+        };
+        let cf_cs_pp = if omitted & OMITTED_CF_CS_PP != 0 {
+            check_len(bodies[1].len(), 0)?;
+            CS2::prover_params_from_verifier(&vp.cf_cs_vp)
+                .ok_or_else(|| recompute_err("cf_cs_pp"))?
+        } else {
+            deserialize_body(&bodies[1], Compress::Yes, Validate::Yes)?
+        };
+
+        let pp = ProverParams {
+            poseidon_config: vp.poseidon_config.clone(),
+            cs_pp,
+            cf_cs_pp,
+        };
+        pp.check_consistency(vp)?;
+        Ok(pp)
+    }
+}
+
+impl<C1, C2, CS1, CS2, const H: bool> ProverParams<C1, C2, CS1, CS2, H>
+where
+    C1: CurveGroup,
+    C2: CurveGroup,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+    <C1 as CurveGroup>::BaseField: PrimeField,
+    <C2 as CurveGroup>::BaseField: PrimeField,
+    <C1 as Group>::ScalarField: Absorb,
+    <C2 as Group>::ScalarField: Absorb,
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+{
+    /// writes the R1CS of the AugmentedFCircuit of the given FCircuit in the circom `.r1cs`
+    /// binary format, so that it can be inspected by external tooling. See
+    /// `arith::r1cs::r1cs_to_circom_bin` for the layout of the wires.
+    pub fn export_augmented_r1cs<GC2, FC, W>(&self, F_circuit: FC, writer: W) -> Result<(), Error>
+    where
+        GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+        FC: FCircuit<C1::ScalarField>,
+        W: ark_std::io::Write,
+    {
+        let augmented_F_circuit =
+            AugmentedFCircuit::<C1, C2, GC2, FC>::empty(&self.poseidon_config, F_circuit);
+        let r1cs = get_r1cs_from_cs::<C1::ScalarField>(augmented_F_circuit)?;
+        r1cs_to_circom_bin(&r1cs, writer)
+    }
 }
 
 /// Verification parameters for Nova-based IVC
@@ -350,6 +713,16 @@ where
     pub cs_vp: CS1::VerifierParams,
     /// Verification parameters of the underlying commitment scheme over C2
     pub cf_cs_vp: CS2::VerifierParams,
+    /// Length of the state of the folded FCircuit
+    pub state_len: usize,
+    /// Table of per-step constants of the folded FCircuit
+    pub step_constants: Vec<Vec<C1::ScalarField>>,
+    /// Whether the folding challenges are full-width, ie. the `FULL_CHALLENGE` parameter of the
+    /// `Nova` that the params are for
+    pub full_challenge: bool,
+    /// Whether `pp_hash` is a public input of the Augmented step circuit, see
+    /// `PreprocessorParam::with_public_pp_hash`
+    pub public_pp_hash: bool,
 }
 
 impl<C1, C2, CS1, CS2, const H: bool> Valid for VerifierParams<C1, C2, CS1, CS2, H>
@@ -372,17 +745,16 @@ where
     CS1: CommitmentScheme<C1, H>,
     CS2: CommitmentScheme<C2, H>,
 {
-    fn serialize_with_mode<W: std::io::prelude::Write>(
+    fn serialize_with_mode<W: ark_std::io::Write>(
         &self,
-        mut writer: W,
+        writer: W,
         compress: ark_serialize::Compress,
     ) -> Result<(), ark_serialize::SerializationError> {
-        self.cs_vp.serialize_with_mode(&mut writer, compress)?;
-        self.cf_cs_vp.serialize_with_mode(&mut writer, compress)
+        serialize_params::<C1, C2, _>(writer, compress, &self.cs_vp, &self.cf_cs_vp)
     }
 
     fn serialized_size(&self, compress: ark_serialize::Compress) -> usize {
-        self.cs_vp.serialized_size(compress) + self.cf_cs_vp.serialized_size(compress)
+        params_serialized_size::<C1, C2>(compress, &self.cs_vp, &self.cf_cs_vp)
     }
 }
 
@@ -393,6 +765,12 @@ where
     CS1: CommitmentScheme<C1, H>,
     CS2: CommitmentScheme<C2, H>,
 {
+    /// returns the verification parameters of the commitment schemes over C1 and C2, which can
+    /// be reused to preprocess other circuits, see `PreprocessorParam::with_cs_params`
+    pub fn cs_params(&self) -> (&CS1::VerifierParams, &CS2::VerifierParams) {
+        (&self.cs_vp, &self.cf_cs_vp)
+    }
+
     /// returns the hash of the public parameters of Nova
     pub fn pp_hash(&self) -> Result<C1::ScalarField, Error> {
         pp_hash::<C1, C2, CS1, CS2, H>(
@@ -401,8 +779,216 @@ where
             &self.cs_vp,
             &self.cf_cs_vp,
             &self.poseidon_config,
+            self.full_challenge
+                .then(|| challenge_bit_length::<C1, true>()),
+            NovaCycleFoldConfig::<C1>::CHALLENGE_BIT_LENGTH,
+            self.state_len,
+            &self.step_constants,
         )
     }
+
+    /// checks that the VerifierParams are internally consistent: the matrices of each R1CS have
+    /// the same dimensions, the R1CS of the AugmentedFCircuit has the public inputs of the
+    /// committed instances (two, or three with `public_pp_hash`), and the rows of the step
+    /// constants have the same length. The
+    /// commitment schemes params are checked against the R1CS by
+    /// `ProverParams::check_consistency`.
+    pub fn check_consistency(&self) -> Result<(), Error> {
+        check_r1cs_dimensions("augmented", &self.r1cs)?;
+        check_r1cs_dimensions("CycleFold", &self.cf_r1cs)?;
+        check_len(self.r1cs.l, augmented_io_len(self.public_pp_hash))?;
+        check_step_constants_rows(&self.step_constants)
+    }
+}
+
+impl<C1, C2, CS1, CS2, const H: bool> VerifierParams<C1, C2, CS1, CS2, H>
+where
+    C1: CurveGroup,
+    C2: CurveGroup,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+    <C1 as CurveGroup>::BaseField: PrimeField,
+    <C2 as CurveGroup>::BaseField: PrimeField,
+    <C1 as Group>::ScalarField: Absorb,
+    <C2 as Group>::ScalarField: Absorb,
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+{
+    /// Verifies the IVC proof, see `Nova::verify_with_context`. Unlike the latter, it does not
+    /// depend on the FCircuit nor on the in-circuit curve gadgets, which keeps them out of the
+    /// verifier-only builds (eg. wasm).
+    pub fn verify_ivc_proof(
+        &self,
+        ivc_proof: IVCProof<C1, C2>,
+        context: Option<C1::ScalarField>,
+    ) -> Result<(), Error> {
+        let IVCProof {
+            context: proof_context,
+            i: num_steps,
+            z_0,
+            z_i,
+            W_i,
+            U_i,
+            w_i,
+            u_i,
+            cf_W_i,
+            cf_U_i,
+        } = ivc_proof;
+
+        check_state("z_0", &z_0, self.state_len)?;
+        check_state("z_i", &z_i, self.state_len)?;
+
+        // the context carried in the proof must be the expected one, and it is bound to the
+        // instances through u_i.x[0], which is checked below
+        let context = context.unwrap_or_else(C1::ScalarField::zero);
+        if proof_context != context {
+            return Err(Error::IVCVerificationFail);
+        }
+
+        let sponge = PoseidonSponge::<C1::ScalarField>::new(&self.poseidon_config);
+
+        if num_steps == C1::ScalarField::zero() {
+            if z_0 != z_i {
+                return Err(Error::IVCVerificationFail);
+            }
+            return Ok(());
+        }
+
+        let io_len = augmented_io_len(self.public_pp_hash);
+        check_len(u_i.x.len(), io_len)?;
+        check_len(U_i.x.len(), io_len)?;
+
+        let pp_hash = self.pp_hash()?;
+
+        // u_i.X[2] == pp_hash, when it is a public input
+        if self.public_pp_hash && u_i.x[2] != pp_hash {
+            return Err(Error::IVCVerificationFail);
+        }
+
+        // check that u_i's output points to the running instance
+        // u_i.X[0] == H(i, z_0, z_i, U_i)
+        let expected_u_i_x =
+            U_i.hash_with_context(&sponge, pp_hash, Some(context), num_steps, &z_0, &z_i);
+        if expected_u_i_x != u_i.x[0] {
+            return Err(Error::IVCVerificationFail);
+        }
+        // u_i.X[1] == H(cf_U_i)
+        let expected_cf_u_i_x = cf_U_i.hash_cyclefold(&sponge, pp_hash);
+        if expected_cf_u_i_x != u_i.x[1] {
+            return Err(Error::IVCVerificationFail);
+        }
+
+        // check R1CS satisfiability, which is equivalent to checking if `u_i`
+        // is an incoming instance and if `w_i` and `u_i` satisfy RelaxedR1CS
+        u_i.check_incoming()?;
+        self.r1cs.check_relation(&w_i, &u_i)?;
+        // check RelaxedR1CS satisfiability
+        self.r1cs.check_relation(&W_i, &U_i)?;
+
+        // check CycleFold RelaxedR1CS satisfiability
+        self.cf_r1cs.check_relation(&cf_W_i, &cf_U_i)?;
+
+        Ok(())
+    }
+
+    /// Serializes the VerifierParams together with the R1CS of the AugmentedFCircuit and of the
+    /// CycleFold circuit (in the compact format of `ArithSerializer::serialize_compressed_opt`),
+    /// the state length, the step constants, the Poseidon config and their `pp_hash`, after a
+    /// header with the format version. Unlike the `CanonicalSerialize` output, which only contains
+    /// the commitment schemes params, it can be deserialized with `deserialize_standalone` without
+    /// the FCircuit and without synthesizing the circuits.
+    pub fn serialize_standalone<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        writer.write_all(STANDALONE_MAGIC)?;
+        writer.write_all(&STANDALONE_FORMAT_VERSION.to_le_bytes())?;
+        serialize_params::<C1, C2, _>(&mut writer, Compress::Yes, &self.cs_vp, &self.cf_cs_vp)?;
+        self.r1cs.serialize_compressed_opt(&mut writer)?;
+        self.cf_r1cs.serialize_compressed_opt(&mut writer)?;
+        write_varint(&mut writer, self.state_len as u64)?;
+        self.step_constants.serialize_compressed(&mut writer)?;
+        serialize_poseidon_config(&self.poseidon_config, &mut writer, Compress::Yes)?;
+        self.full_challenge.serialize_compressed(&mut writer)?;
+        self.pp_hash()?.serialize_compressed(&mut writer)?;
+        Ok(())
+    }
+
+    /// Deserializes the VerifierParams serialized by `serialize_standalone`, checking their
+    /// consistency (see `check_consistency`) and that their recomputed `pp_hash` matches the
+    /// stored one, which detects corrupted files. Since the R1CS are read instead of being
+    /// generated from the FCircuit, the serialized params must still come from a trusted source,
+    /// or their `pp_hash` be checked against the expected one.
+    ///
+    /// The `public_pp_hash` flag is not stored, it is recovered from the number of public inputs of
+    /// the R1CS.
+    ///
+    /// The VerifierParams serialized by the first version of `serialize_standalone`, which had no
+    /// header, are read by `deserialize_standalone_legacy`.
+    pub fn deserialize_standalone<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic == PARAMS_MAGIC {
+            return Self::deserialize_standalone_legacy(Prefixed {
+                prefix: &magic,
+                reader,
+            });
+        }
+        if &magic != STANDALONE_MAGIC {
+            return Err(Error::ParamsMismatch(
+                "invalid magic bytes of the verifier params".to_string(),
+            ));
+        }
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != STANDALONE_FORMAT_VERSION {
+            return Err(Error::ParamsMismatch(format!(
+                "unsupported format version {} of the verifier params, expected {}",
+                version, STANDALONE_FORMAT_VERSION
+            )));
+        }
+
+        let mut vp = Self::deserialize_standalone_legacy(&mut reader)?;
+        vp.poseidon_config =
+            deserialize_poseidon_config(&mut reader, Compress::Yes, Validate::Yes)?;
+        vp.full_challenge = bool::deserialize_compressed(&mut reader)?;
+        let stored_pp_hash = C1::ScalarField::deserialize_compressed(&mut reader)?;
+        if vp.pp_hash()? != stored_pp_hash {
+            return Err(Error::ParamsMismatch(
+                "the pp_hash of the params does not match the stored one".to_string(),
+            ));
+        }
+        Ok(vp)
+    }
+
+    /// Same as `deserialize_standalone`, for the VerifierParams serialized by the first version
+    /// of `serialize_standalone`, which had no header and did not store the Poseidon config nor
+    /// the `pp_hash`. Their consistency is checked, but corruptions that keep them consistent are
+    /// not detected, so the `pp_hash` of the result should be checked against the expected one.
+    /// That version only supported the canonical Poseidon config (see
+    /// `poseidon_canonical_config`) and truncated folding challenges, so `full_challenge` is
+    /// unset, while `public_pp_hash` is recovered from the number of public inputs of the R1CS.
+    ///
+    /// `deserialize_standalone` dispatches to this method for files in that format.
+    pub fn deserialize_standalone_legacy<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let (cs_vp, cf_cs_vp) =
+            deserialize_params::<C1, C2, _, _, _>(&mut reader, Compress::Yes, Validate::Yes)?;
+        let r1cs = R1CS::deserialize_compressed_opt(&mut reader)?;
+        let cf_r1cs = R1CS::deserialize_compressed_opt(&mut reader)?;
+        let state_len = read_varint_usize(&mut reader)?;
+        let step_constants = Vec::deserialize_compressed(&mut reader)?;
+        let public_pp_hash = r1cs.l == augmented_io_len(true);
+        let vp = Self {
+            poseidon_config: poseidon_canonical_config::<C1::ScalarField>(),
+            r1cs,
+            cf_r1cs,
+            cs_vp,
+            cf_cs_vp,
+            state_len,
+            step_constants,
+            full_challenge: false,
+            public_pp_hash,
+        };
+        vp.check_consistency()?;
+        Ok(vp)
+    }
 }
 
 #[derive(PartialEq, Eq, Debug, Clone, CanonicalSerialize, CanonicalDeserialize)]
@@ -411,6 +997,8 @@ where
     C1: CurveGroup,
     C2: CurveGroup,
 {
+    // application context bound into u_i.x[0], see `Nova::init_with_context`
+    pub context: C1::ScalarField,
     // current step of the IVC
     pub i: C1::ScalarField,
     // initial state
@@ -431,9 +1019,24 @@ where
 /// Implements Nova+CycleFold's IVC, described in [Nova](https://eprint.iacr.org/2021/370.pdf) and
 /// [CycleFold](https://eprint.iacr.org/2023/1192.pdf), following the FoldingScheme trait
 /// The `H` const generic specifies whether the homorphic commitment scheme is blinding
+///
+/// The `FULL_CHALLENGE` const generic specifies whether the folding challenges are full scalar
+/// field elements, instead of being truncated to `NOVA_N_BITS_RO` bits. Full-width challenges
+/// cost a decomposition into bits in the AugmentedFCircuit, and a larger randomness in the
+/// CycleFold circuit, in exchange for a soundness error that does not depend on the truncation.
+/// It is bound to the `pp_hash`, so the params and proofs of one mode are rejected by the other.
 #[derive(Clone, Debug)]
-pub struct Nova<C1, GC1, C2, GC2, FC, CS1, CS2, const H: bool = false>
-where
+pub struct Nova<
+    C1,
+    GC1,
+    C2,
+    GC2,
+    FC,
+    CS1,
+    CS2,
+    const H: bool = false,
+    const FULL_CHALLENGE: bool = false,
+> where
     C1: CurveGroup,
     GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
     C2: CurveGroup,
@@ -458,6 +1061,10 @@ where
     pub F: FC,
     /// public params hash
     pub pp_hash: C1::ScalarField,
+    /// application context bound into `u_i.x[0]` (zero if none was given at `init_with_context`)
+    pub context: C1::ScalarField,
+    /// whether `pp_hash` is a public input of the AugmentedFCircuit, as `u_i.x[2]`
+    pub public_pp_hash: bool,
     pub i: C1::ScalarField,
     /// initial state
     pub z_0: Vec<C1::ScalarField>,
@@ -474,8 +1081,8 @@ where
     pub cf_U_i: CycleFoldCommittedInstance<C2>,
 }
 
-impl<C1, GC1, C2, GC2, FC, CS1, CS2, const H: bool> FoldingScheme<C1, C2, FC>
-    for Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H>
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, const H: bool, const FULL_CHALLENGE: bool>
+    FoldingScheme<C1, C2, FC> for Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H, FULL_CHALLENGE>
 where
     C1: CurveGroup,
     GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
@@ -497,74 +1104,65 @@ where
     type IncomingInstance = (CommittedInstance<C1>, Witness<C1>);
     type MultiCommittedInstanceWithWitness = ();
     type CFInstance = (CycleFoldCommittedInstance<C2>, CycleFoldWitness<C2>);
+    type RunningCommittedInstance = CommittedInstance<C1>;
+    type IncomingCommittedInstance = CommittedInstance<C1>;
     type IVCProof = IVCProof<C1, C2>;
 
-    fn pp_deserialize_with_mode<R: std::io::prelude::Read>(
+    fn pp_deserialize_with_mode<R: ark_std::io::Read>(
         reader: R,
         compress: ark_serialize::Compress,
         validate: ark_serialize::Validate,
         _fc_params: FC::Params, // FCircuit params
     ) -> Result<Self::ProverParam, Error> {
-        Ok(Self::ProverParam::deserialize_with_mode(
-            reader, compress, validate,
-        )?)
+        Self::ProverParam::deserialize_checked(reader, compress, validate)
     }
-    fn vp_deserialize_with_mode<R: std::io::prelude::Read>(
-        mut reader: R,
+    fn vp_deserialize_with_mode<R: ark_std::io::Read>(
+        reader: R,
         compress: ark_serialize::Compress,
         validate: ark_serialize::Validate,
         fc_params: FC::Params,
     ) -> Result<Self::VerifierParam, Error> {
-        let poseidon_config = poseidon_canonical_config::<C1::ScalarField>();
-
-        // generate the r1cs & cf_r1cs needed for the VerifierParams. In this way we avoid needing
-        // to serialize them, saving significant space in the VerifierParams serialized size.
-
-        // main circuit R1CS:
-        let f_circuit = FC::new(fc_params)?;
-        let cs = ConstraintSystem::<C1::ScalarField>::new_ref();
-        let augmented_F_circuit =
-            AugmentedFCircuit::<C1, C2, GC2, FC>::empty(&poseidon_config, f_circuit.clone());
-        augmented_F_circuit.generate_constraints(cs.clone())?;
-        cs.finalize();
-        let cs = cs.into_inner().ok_or(Error::NoInnerConstraintSystem)?;
-        let r1cs = extract_r1cs::<C1::ScalarField>(&cs)?;
-
-        // CycleFold circuit R1CS
-        let cs2 = ConstraintSystem::<C1::BaseField>::new_ref();
-        let cf_circuit = NovaCycleFoldCircuit::<C1, GC1>::empty();
-        cf_circuit.generate_constraints(cs2.clone())?;
-        cs2.finalize();
-        let cs2 = cs2.into_inner().ok_or(Error::NoInnerConstraintSystem)?;
-        let cf_r1cs = extract_r1cs::<C1::BaseField>(&cs2)?;
-
-        let cs_vp = CS1::VerifierParams::deserialize_with_mode(&mut reader, compress, validate)?;
-        let cf_cs_vp = CS2::VerifierParams::deserialize_with_mode(&mut reader, compress, validate)?;
-
-        Ok(Self::VerifierParam {
-            poseidon_config,
-            r1cs,
-            cf_r1cs,
-            cs_vp,
-            cf_cs_vp,
-        })
+        let (cs_vp, cf_cs_vp) = deserialize_params::<C1, C2, _, _, _>(reader, compress, validate)?;
+        Self::vp_from_cs_vps(fc_params, cs_vp, cf_cs_vp)
     }
 
     fn preprocess(
         mut rng: impl RngCore,
         prep_param: &Self::PreprocessorParam,
     ) -> Result<(Self::ProverParam, Self::VerifierParam), Error> {
-        let (r1cs, cf_r1cs) =
-            get_r1cs::<C1, GC1, C2, GC2, FC>(&prep_param.poseidon_config, prep_param.F.clone())?;
+        trace_span!("nova_preprocess");
+        NovaCycleFoldConfig::<C1, FULL_CHALLENGE>::check_challenge_bit_length()?;
+        let state_len = check_state_len(&prep_param.F)?;
+        let step_constants = check_step_constants(&prep_param.F)?;
+        check_no_custom_gates(&prep_param.F, "Nova")?;
+        let (r1cs, cf_r1cs) = {
+            trace_span!("augmented_circuit_synthesis");
+            get_r1cs_opt::<C1, GC1, C2, GC2, FC, FULL_CHALLENGE>(
+                &prep_param.poseidon_config,
+                prep_param.F.clone(),
+                prep_param.public_pp_hash,
+            )?
+        };
 
-        // if cs params exist, use them, if not, generate new ones
+        // if cs params exist, trim and use them, if not, generate new ones
+        trace_span!(
+            "commitment_setup",
+            n_constraints = r1cs.A.n_rows,
+            cf_n_constraints = cf_r1cs.A.n_rows
+        );
         let (cs_pp, cs_vp) = match (&prep_param.cs_pp, &prep_param.cs_vp) {
-            (Some(cs_pp), Some(cs_vp)) => (cs_pp.clone(), cs_vp.clone()),
-            _ => CS1::setup(&mut rng, r1cs.A.n_rows)?,
+            (Some(cs_pp), Some(cs_vp)) => CS1::trim(cs_pp, cs_vp, r1cs.A.n_rows)?,
+            _ => match &prep_param.domain_tag {
+                Some(tag) => CS1::setup_deterministic(tag, r1cs.A.n_rows)?,
+                None => CS1::setup(&mut rng, r1cs.A.n_rows)?,
+            },
         };
         let (cf_cs_pp, cf_cs_vp) = match (&prep_param.cf_cs_pp, &prep_param.cf_cs_vp) {
-            (Some(cf_cs_pp), Some(cf_cs_vp)) => (cf_cs_pp.clone(), cf_cs_vp.clone()),
-            _ => CS2::setup(&mut rng, cf_r1cs.A.n_rows)?,
+            (Some(cf_cs_pp), Some(cf_cs_vp)) => CS2::trim(cf_cs_pp, cf_cs_vp, cf_r1cs.A.n_rows)?,
+            _ => match &prep_param.domain_tag {
+                Some(tag) => CS2::setup_deterministic(tag, cf_r1cs.A.n_rows)?,
+                None => CS2::setup(&mut rng, cf_r1cs.A.n_rows)?,
+            },
         };
 
         let prover_params = ProverParams::<C1, C2, CS1, CS2, H> {
@@ -578,26 +1176,99 @@ where
             cf_r1cs,
             cs_vp,
             cf_cs_vp,
+            state_len,
+            step_constants,
+            full_challenge: FULL_CHALLENGE,
+            public_pp_hash: prep_param.public_pp_hash,
         };
 
         Ok((prover_params, verifier_params))
     }
 
-    /// Initializes the Nova+CycleFold's IVC for the given parameters and initial state `z_0`.
+    /// Initializes the Nova+CycleFold's IVC for the given parameters and initial state `z_0`,
+    /// without an application context. See `Nova::init_with_context`.
     fn init(
         params: &(Self::ProverParam, Self::VerifierParam),
         F: FC,
         z_0: Vec<C1::ScalarField>,
     ) -> Result<Self, Error> {
+        Self::init_with_context(params, F, z_0, None)
+    }
+
+    /// Implements IVC.P of Nova+CycleFold
+    fn prove_step(
+        &mut self,
+        rng: impl RngCore,
+        external_inputs: FC::ExternalInputs,
+        // Nova does not support multi-instances folding
+        _other_instances: Option<Self::MultiCommittedInstanceWithWitness>,
+    ) -> Result<(), Error> {
+        self.prove_step_opt::<false>(
+            rng,
+            external_inputs,
+            _other_instances,
+            None,
+            &mut StepProfile::default(),
+        )
+    }
+
+    fn state(&self) -> FoldingState<'_, C1, C2, CommittedInstance<C1>, CommittedInstance<C1>> {
+        FoldingState {
+            i: self.i,
+            z_0: &self.z_0,
+            z_i: &self.z_i,
+            U_i: &self.U_i,
+            u_i: &self.u_i,
+            cf_U_i: &self.cf_U_i,
+        }
+    }
+
+    fn ivc_proof(&self) -> Self::IVCProof {
+        Self::IVCProof {
+            context: self.context,
+            i: self.i,
+            z_0: self.z_0.clone(),
+            z_i: self.z_i.clone(),
+            W_i: self.W_i.clone(),
+            U_i: self.U_i.clone(),
+            w_i: self.w_i.clone(),
+            u_i: self.u_i.clone(),
+            cf_W_i: self.cf_W_i.clone(),
+            cf_U_i: self.cf_U_i.clone(),
+        }
+    }
+
+    fn from_ivc_proof(
+        ivc_proof: IVCProof<C1, C2>,
+        fcircuit_params: FC::Params,
+        params: (Self::ProverParam, Self::VerifierParam),
+    ) -> Result<Self, Error> {
+        let IVCProof {
+            context,
+            i,
+            z_0,
+            z_i,
+            W_i,
+            U_i,
+            w_i,
+            u_i,
+            cf_W_i,
+            cf_U_i,
+        } = ivc_proof;
         let (pp, vp) = params;
+        Self::check_full_challenge(&vp)?;
+        check_state("z_0", &z_0, vp.state_len)?;
+        check_state("z_i", &z_i, vp.state_len)?;
 
-        // prepare the circuit to obtain its R1CS
+        let f_circuit = FC::new(fcircuit_params)?;
         let cs = ConstraintSystem::<C1::ScalarField>::new_ref();
         let cs2 = ConstraintSystem::<C1::BaseField>::new_ref();
-
-        let augmented_F_circuit =
-            AugmentedFCircuit::<C1, C2, GC2, FC>::empty(&pp.poseidon_config, F.clone());
-        let cf_circuit = NovaCycleFoldCircuit::<C1, GC1>::empty();
+        let augmented_F_circuit = AugmentedFCircuit::<C1, C2, GC2, FC, FULL_CHALLENGE>::empty(
+            &pp.poseidon_config,
+            f_circuit.clone(),
+        )
+        .with_public_pp_hash(vp.public_pp_hash);
+        let cf_circuit = NovaCycleFoldCircuit::<C1, GC1, FULL_CHALLENGE>::empty();
 
         augmented_F_circuit.generate_constraints(cs.clone())?;
         cs.finalize();
@@ -609,55 +1280,182 @@ where
         let cs2 = cs2.into_inner().ok_or(Error::NoInnerConstraintSystem)?;
         let cf_r1cs = extract_r1cs::<C1::BaseField>(&cs2)?;
 
-        // compute the public params hash
-        let pp_hash = vp.pp_hash()?;
-
-        // setup the dummy instances
-        let (W_dummy, U_dummy) = r1cs.dummy_witness_instance();
-        let (w_dummy, u_dummy) = r1cs.dummy_witness_instance();
-        let (cf_W_dummy, cf_U_dummy) = cf_r1cs.dummy_witness_instance();
+        // the IVC proof may come from untrusted bytes, check that its dimensions match the
+        // circuits before using it
+        U_i.check_dimensions(&r1cs)?;
+        u_i.check_dimensions(&r1cs)?;
+        W_i.check_dimensions(&r1cs)?;
+        w_i.check_dimensions(&r1cs)?;
+        cf_U_i.check_dimensions(&cf_r1cs)?;
+        cf_W_i.check_dimensions(&cf_r1cs)?;
 
-        // W_dummy=W_0 is a 'dummy witness', all zeroes, but with the size corresponding to the
-        // R1CS that we're working with.
         Ok(Self {
             _gc1: PhantomData,
             _c2: PhantomData,
             _gc2: PhantomData,
             r1cs,
             cf_r1cs,
-            poseidon_config: pp.poseidon_config.clone(),
-            cs_pp: pp.cs_pp.clone(),
-            cf_cs_pp: pp.cf_cs_pp.clone(),
-            F,
-            pp_hash,
-            i: C1::ScalarField::zero(),
-            z_0: z_0.clone(),
-            z_i: z_0,
-            w_i: w_dummy,
-            u_i: u_dummy,
-            W_i: W_dummy,
-            U_i: U_dummy,
-            // cyclefold running instance
-            cf_W_i: cf_W_dummy,
-            cf_U_i: cf_U_dummy,
+            poseidon_config: pp.poseidon_config,
+            cs_pp: pp.cs_pp,
+            cf_cs_pp: pp.cf_cs_pp,
+            F: f_circuit,
+            pp_hash: vp.pp_hash()?,
+            context,
+            public_pp_hash: vp.public_pp_hash,
+            i,
+            z_0,
+            z_i,
+            w_i,
+            u_i,
+            W_i,
+            U_i,
+            cf_W_i,
+            cf_U_i,
         })
     }
 
-    /// Implements IVC.P of Nova+CycleFold
-    fn prove_step(
+    /// Implements IVC.V of Nova+CycleFold for the IVCs without an application context. See
+    /// `Nova::verify_with_context`.
+    fn verify(vp: Self::VerifierParam, ivc_proof: Self::IVCProof) -> Result<(), Error> {
+        Self::verify_with_context(vp, ivc_proof, None)
+    }
+}
+
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, const H: bool, const FULL_CHALLENGE: bool>
+    Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H, FULL_CHALLENGE>
+where
+    C1: CurveGroup,
+    GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+    C2: CurveGroup,
+    GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+    FC: FCircuit<C1::ScalarField>,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+    <C1 as CurveGroup>::BaseField: PrimeField,
+    <C2 as CurveGroup>::BaseField: PrimeField,
+    <C1 as Group>::ScalarField: Absorb,
+    <C2 as Group>::ScalarField: Absorb,
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+{
+    /// Returns a builder of the `PreprocessorParam` of the FCircuit `F`, which can also run
+    /// `preprocess` with them. See `NovaBuilder`.
+    pub fn builder(F: FC) -> NovaBuilder<Self, C1, C2, FC, CS1, CS2, H> {
+        NovaBuilder::new(F)
+    }
+
+    /// Same as `prove_step`, which also returns a report with the duration and size of its
+    /// phases, and with its peak memory when `utils::alloc::CountingAlloc` is the global
+    /// allocator. The plain `prove_step` does not measure anything.
+    ///
+    /// ```
+    /// use ark_bn254::{constraints::GVar, Fr, G1Projective as Projective};
+    /// use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+    /// use folding_schemes::{
+    ///     commitment::pedersen::Pedersen,
+    ///     folding::nova::{Nova, PreprocessorParam},
+    ///     frontend::{utils::CustomFCircuit, FCircuit},
+    ///     transcript::poseidon::poseidon_canonical_config,
+    ///     FoldingScheme,
+    /// };
+    ///
+    /// type N = Nova<
+    ///     Projective,
+    ///     GVar,
+    ///     Projective2,
+    ///     GVar2,
+    ///     CustomFCircuit<Fr>,
+    ///     Pedersen<Projective>,
+    ///     Pedersen<Projective2>,
+    ///     false,
+    /// >;
+    ///
+    /// let mut rng = ark_std::test_rng();
+    /// let F_circuit = CustomFCircuit::<Fr>::new(10)?;
+    /// let poseidon_config = poseidon_canonical_config::<Fr>();
+    /// let params = N::preprocess(&mut rng, &PreprocessorParam::new(poseidon_config, F_circuit))?;
+    /// let mut nova = N::init(&params, F_circuit, vec![Fr::from(2_u32)])?;
+    /// for i in 0..2 {
+    ///     let (result, profile) = nova.prove_step_profiled(&mut rng, (), None);
+    ///     result?;
+    ///     println!(
+    ///         "step {}: {:?} in total, {:?} to generate the {} witness elements",
+    ///         i, profile.total, profile.witness_generation.duration, profile.witness_generation.size
+    ///     );
+    ///     println!("{:#?}", profile);
+    /// }
+    /// # Ok::<(), folding_schemes::Error>(())
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn prove_step_profiled(
+        &mut self,
+        rng: impl RngCore,
+        external_inputs: FC::ExternalInputs,
+        other_instances: Option<()>,
+    ) -> (Result<(), Error>, StepProfile) {
+        let mut profile = StepProfile::default();
+        let baseline = alloc::reset_peak();
+        let start = Timer::start();
+        let result =
+            self.prove_step_opt::<true>(rng, external_inputs, other_instances, None, &mut profile);
+        profile.total = start.elapsed();
+        profile.peak_allocation = alloc::peak_since(baseline);
+        (result, profile)
+    }
+
+    /// returns the current step `i` as a usize, which fits since it was checked against
+    /// `Error::MaxStep` by the previous steps
+    fn i_usize(&self) -> usize {
+        let i_usize;
+
+        #[cfg(target_pointer_width = "64")]
+        {
+            let mut i_bytes: [u8; 8] = [0; 8];
+            i_bytes.copy_from_slice(&self.i.into_bigint().to_bytes_le()[..8]);
+            i_usize = usize::from_le_bytes(i_bytes);
+        }
+
+        #[cfg(target_pointer_width = "32")]
+        {
+            let mut i_bytes: [u8; 4] = [0; 4];
+            i_bytes.copy_from_slice(&self.i.into_bigint().to_bytes_le()[..4]);
+            i_usize = usize::from_le_bytes(i_bytes);
+        }
+
+        i_usize
+    }
+
+    /// Same as `prove_step`, but drawing the randomness of the step (of the hiding commitments
+    /// and of the CycleFold instances) from `utils::deterministic_rng(seed, i)`. Proving the same
+    /// steps from the same seed results in byte-identical IVC proofs.
+    pub fn prove_step_deterministic(
+        &mut self,
+        seed: [u8; 32],
+        external_inputs: FC::ExternalInputs,
+    ) -> Result<(), Error> {
+        self.prove_step(deterministic_rng(seed, self.i), external_inputs, None)
+    }
+
+    /// Implements IVC.P of Nova+CycleFold, recording the duration and size of its phases into
+    /// `profile` when `PROFILE` is set. If given, `z_i1` is used as the output of
+    /// `F.step_native` for the current step instead of running it, see `prove_steps_pipelined`.
+    fn prove_step_opt<const PROFILE: bool>(
         &mut self,
         mut rng: impl RngCore,
-        external_inputs: Vec<C1::ScalarField>,
+        external_inputs: FC::ExternalInputs,
         // Nova does not support multi-instances folding
-        _other_instances: Option<Self::MultiCommittedInstanceWithWitness>,
+        _other_instances: Option<()>,
+        z_i1: Option<Vec<C1::ScalarField>>,
+        profile: &mut StepProfile,
     ) -> Result<(), Error> {
-        // ensure that commitments are blinding if user has specified so.
+        trace_span!("nova_prove_step", n_constraints = self.r1cs.A.n_rows);
+        // ensure that commitments are blinding if user has specified so. Notice that the rE of
+        // the incoming instances is always zero, since their E is zero and cmE=0.
         if H && self.i >= C1::ScalarField::one() {
             let blinding_commitments = if self.i == C1::ScalarField::one() {
                 // blinding values of the running instances are zero at the first iteration
-                vec![self.w_i.rW, self.w_i.rE]
+                vec![self.w_i.rW]
             } else {
-                vec![self.w_i.rW, self.w_i.rE, self.W_i.rW, self.W_i.rE]
+                vec![self.w_i.rW, self.W_i.rW]
             };
             if blinding_commitments.contains(&C1::ScalarField::zero()) {
                 return Err(Error::IncorrectBlinding(
@@ -671,57 +1469,38 @@ where
         // `transcript` is for challenge generation.
         let mut transcript = sponge.clone();
 
-        let augmented_F_circuit: AugmentedFCircuit<C1, C2, GC2, FC>;
+        let augmented_F_circuit: AugmentedFCircuit<C1, C2, GC2, FC, FULL_CHALLENGE>;
 
         // Nova does not support (by design) multi-instances folding
         if _other_instances.is_some() {
             return Err(Error::NoMultiInstances);
         }
 
-        if self.z_i.len() != self.F.state_len() {
-            return Err(Error::NotSameLength(
-                "z_i.len()".to_string(),
-                self.z_i.len(),
-                "F.state_len()".to_string(),
-                self.F.state_len(),
-            ));
-        }
-        if external_inputs.len() != self.F.external_inputs_len() {
-            return Err(Error::NotSameLength(
-                "F.external_inputs_len()".to_string(),
-                self.F.external_inputs_len(),
-                "external_inputs.len()".to_string(),
-                external_inputs.len(),
-            ));
-        }
+        check_state("z_i", &self.z_i, self.F.state_len())?;
 
         if self.i > C1::ScalarField::from_le_bytes_mod_order(&usize::MAX.to_le_bytes()) {
             return Err(Error::MaxStep);
         }
 
-        let i_usize;
-
-        #[cfg(target_pointer_width = "64")]
-        {
-            let mut i_bytes: [u8; 8] = [0; 8];
-            i_bytes.copy_from_slice(&self.i.into_bigint().to_bytes_le()[..8]);
-            i_usize = usize::from_le_bytes(i_bytes);
-        }
-
-        #[cfg(target_pointer_width = "32")]
-        {
-            let mut i_bytes: [u8; 4] = [0; 4];
-            i_bytes.copy_from_slice(&self.i.into_bigint().to_bytes_le()[..4]);
-            i_usize = usize::from_le_bytes(i_bytes);
-        }
-
-        let z_i1 = self
-            .F
-            .step_native(i_usize, self.z_i.clone(), external_inputs.clone())?;
+        let i_usize = self.i_usize();
+
+        check_step_constants_row(&self.F.step_constants(), i_usize)?;
+        let z_i1 = match z_i1 {
+            Some(z_i1) => z_i1,
+            None => {
+                trace_span!("step_native", state_len = self.z_i.len());
+                timed::<PROFILE, _>(&mut profile.witness_generation, || {
+                    self.F
+                        .step_native(i_usize, self.z_i.clone(), external_inputs.clone())
+                })?
+            }
+        };
+        check_state("z_{i+1}", &z_i1, self.F.state_len())?;
 
         // fold Nova instances
-        let (W_i1, U_i1, cmT, r_bits): (Witness<C1>, CommittedInstance<C1>, C1, Vec<bool>) =
-            NIFS::<C1, CS1, PoseidonSponge<C1::ScalarField>, H>::prove(
+        let (W_i1, U_i1, cmT, r_bits): (Witness<C1>, CommittedInstance<C1>, C1, Vec<bool>) = {
+            trace_span!("nifs_prove", n_constraints = self.r1cs.A.n_rows);
+            NIFS::<C1, CS1, PoseidonSponge<C1::ScalarField>, H>::prove_opt::<PROFILE, FULL_CHALLENGE>(
                 &self.cs_pp,
                 &self.r1cs,
                 &mut transcript,
@@ -730,15 +1509,15 @@ where
                 &self.U_i,
                 &self.w_i,
                 &self.u_i,
-            )?;
-        let r_Fq = C1::BaseField::from_bigint(BigInteger::from_bits_le(&r_bits))
-            .ok_or(Error::OutOfBounds)?;
-
+                profile,
+            )?
+        };
         // folded instance output (public input, x)
         // u_{i+1}.x[0] = H(i+1, z_0, z_{i+1}, U_{i+1})
-        let u_i1_x = U_i1.hash(
+        let u_i1_x = U_i1.hash_with_context(
             &sponge,
             self.pp_hash,
+            Some(self.context),
             self.i + C1::ScalarField::one(),
             &self.z_0,
             &z_i1,
@@ -749,10 +1528,11 @@ where
         if self.i == C1::ScalarField::zero() {
             cf_u_i1_x = self.cf_U_i.hash_cyclefold(&sponge, self.pp_hash);
             // base case
-            augmented_F_circuit = AugmentedFCircuit::<C1, C2, GC2, FC> {
+            augmented_F_circuit = AugmentedFCircuit::<C1, C2, GC2, FC, FULL_CHALLENGE> {
                 _gc2: PhantomData,
                 poseidon_config: self.poseidon_config.clone(),
                 pp_hash: Some(self.pp_hash),
+                context: Some(self.context),
                 i: Some(C1::ScalarField::zero()), // = i=0
                 i_usize: Some(0),
                 z_0: Some(self.z_0.clone()), // = z_i
@@ -771,11 +1551,12 @@ where
                 cf1_cmT: None,
                 cf2_cmT: None,
                 cf_x: Some(cf_u_i1_x),
+                public_pp_hash: self.public_pp_hash,
             };
 
             #[cfg(test)]
             {
-                let r_Fr = C1::ScalarField::from_bigint(BigInteger::from_bits_le(&r_bits))
+                let r_Fr = C1::ScalarField::from_bigint(ark_ff::BigInteger::from_bits_le(&r_bits))
                     .ok_or(Error::OutOfBounds)?;
                 let expected =
                     NIFS::<C1, CS1, PoseidonSponge<C1::ScalarField>, H>::fold_committed_instances(
@@ -784,44 +1565,25 @@ where
                 assert_eq!(U_i1, expected);
             }
         } else {
+            trace_span!("cyclefold_prove", n_constraints = self.cf_r1cs.A.n_rows);
+            let cf_start = PROFILE.then(Timer::start);
             // CycleFold part:
-            // get the vector used as public inputs 'x' in the CycleFold circuit
             // cyclefold circuit for cmW
-            let cfW_u_i_x = [
-                vec![r_Fq],
-                get_cm_coordinates(&self.U_i.cmW),
-                get_cm_coordinates(&self.u_i.cmW),
-                get_cm_coordinates(&U_i1.cmW),
-            ]
-            .concat();
+            let cfW_circuit = NovaCycleFoldCircuit::<C1, GC1, FULL_CHALLENGE>::new(
+                r_bits.clone(),
+                vec![self.U_i.cmW, self.u_i.cmW],
+            )?;
             // cyclefold circuit for cmE
-            let cfE_u_i_x = [
-                vec![r_Fq],
-                get_cm_coordinates(&self.U_i.cmE),
-                get_cm_coordinates(&cmT),
-                get_cm_coordinates(&U_i1.cmE),
-            ]
-            .concat();
-
-            let cfW_circuit = NovaCycleFoldCircuit::<C1, GC1> {
-                _gc: PhantomData,
-                r_bits: Some(r_bits.clone()),
-                points: Some(vec![self.U_i.clone().cmW, self.u_i.clone().cmW]),
-                x: Some(cfW_u_i_x.clone()),
-            };
-            let cfE_circuit = NovaCycleFoldCircuit::<C1, GC1> {
-                _gc: PhantomData,
-                r_bits: Some(r_bits.clone()),
-                points: Some(vec![self.U_i.clone().cmE, cmT]),
-                x: Some(cfE_u_i_x.clone()),
-            };
+            let cfE_circuit = NovaCycleFoldCircuit::<C1, GC1, FULL_CHALLENGE>::new(
+                r_bits,
+                vec![self.U_i.cmE, cmT],
+            )?;
 
             // fold self.cf_U_i + cfW_U -> folded running with cfW
             let (_cfW_w_i, cfW_u_i, cfW_W_i1, cfW_U_i1, cfW_cmT, _) = self.fold_cyclefold_circuit(
                 &mut transcript,
                 self.cf_W_i.clone(), // CycleFold running instance witness
                 self.cf_U_i.clone(), // CycleFold running instance
-                cfW_u_i_x,
                 cfW_circuit,
                 &mut rng,
             )?;
@@ -830,17 +1592,24 @@ where
                 &mut transcript,
                 cfW_W_i1,
                 cfW_U_i1.clone(),
-                cfE_u_i_x,
                 cfE_circuit,
                 &mut rng,
             )?;
 
             cf_u_i1_x = cf_U_i1.hash_cyclefold(&sponge, self.pp_hash);
+            if let Some(cf_start) = cf_start {
+                // one CycleFold circuit for cmW and another one for cmE
+                profile.cyclefold = PhaseProfile {
+                    duration: cf_start.elapsed(),
+                    size: 2 * self.cf_r1cs.A.n_rows,
+                };
+            }
 
-            augmented_F_circuit = AugmentedFCircuit::<C1, C2, GC2, FC> {
+            augmented_F_circuit = AugmentedFCircuit::<C1, C2, GC2, FC, FULL_CHALLENGE> {
                 _gc2: PhantomData,
                 poseidon_config: self.poseidon_config.clone(),
                 pp_hash: Some(self.pp_hash),
+                context: Some(self.context),
                 i: Some(self.i),
                 i_usize: Some(i_usize),
                 z_0: Some(self.z_0.clone()),
@@ -860,6 +1629,7 @@ where
                 cf1_cmT: Some(cfW_cmT),
                 cf2_cmT: Some(cf_cmT),
                 cf_x: Some(cf_u_i1_x),
+                public_pp_hash: self.public_pp_hash,
             };
 
             self.cf_W_i = cf_W_i1;
@@ -876,28 +1646,50 @@ where
         }
 
         let cs = ConstraintSystem::<C1::ScalarField>::new_ref();
-
-        augmented_F_circuit.generate_constraints(cs.clone())?;
+        {
+            trace_span!("augmented_circuit_synthesis");
+            timed::<PROFILE, _>(&mut profile.witness_generation, || {
+                augmented_F_circuit.generate_constraints(cs.clone())
+            })?;
+        }
 
         #[cfg(test)]
         assert!(cs.is_satisfied().unwrap());
 
-        let cs = cs.into_inner().ok_or(Error::NoInnerConstraintSystem)?;
-        let (w_i1, x_i1) = extract_w_x::<C1::ScalarField>(&cs);
+        let (w_i1, x_i1) = {
+            trace_span!(
+                "witness_extraction",
+                n_variables = cs.num_witness_variables()
+            );
+            timed::<PROFILE, _>(&mut profile.witness_generation, || {
+                let cs = cs.into_inner().ok_or(Error::NoInnerConstraintSystem)?;
+                Ok::<_, Error>(extract_w_x::<C1::ScalarField>(&cs))
+            })?
+        };
+        profile.witness_generation.size = w_i1.len();
         if x_i1[0] != u_i1_x || x_i1[1] != cf_u_i1_x {
             return Err(Error::NotEqual);
         }
 
         #[cfg(test)]
-        if x_i1.len() != 2 {
-            return Err(Error::NotExpectedLength(x_i1.len(), 2));
+        if x_i1.len() != augmented_io_len(self.public_pp_hash) {
+            return Err(Error::NotExpectedLength(
+                x_i1.len(),
+                augmented_io_len(self.public_pp_hash),
+            ));
         }
 
         // set values for next iteration
         self.i += C1::ScalarField::one();
         self.z_i = z_i1;
         self.w_i = Witness::<C1>::new::<H>(w_i1, self.r1cs.A.n_rows, &mut rng);
-        self.u_i = self.w_i.commit::<CS1, H>(&self.cs_pp, x_i1)?;
+        self.u_i = {
+            trace_span!("commit", len = self.w_i.W.len());
+            timed::<PROFILE, _>(&mut profile.cmW, || {
+                self.w_i.commit::<CS1, H>(&self.cs_pp, x_i1)
+            })?
+        };
+        profile.cmW.size = self.w_i.W.len();
         self.W_i = W_i1;
         self.U_i = U_i1;
 
@@ -911,48 +1703,47 @@ where
         Ok(())
     }
 
-    fn state(&self) -> Vec<C1::ScalarField> {
-        self.z_i.clone()
-    }
-
-    fn ivc_proof(&self) -> Self::IVCProof {
-        Self::IVCProof {
-            i: self.i,
-            z_0: self.z_0.clone(),
-            z_i: self.z_i.clone(),
-            W_i: self.W_i.clone(),
-            U_i: self.U_i.clone(),
-            w_i: self.w_i.clone(),
-            u_i: self.u_i.clone(),
-            cf_W_i: self.cf_W_i.clone(),
-            cf_U_i: self.cf_U_i.clone(),
-        }
-    }
-
-    fn from_ivc_proof(
-        ivc_proof: IVCProof<C1, C2>,
-        fcircuit_params: FC::Params,
-        params: (Self::ProverParam, Self::VerifierParam),
+    /// Initializes the Nova+CycleFold's IVC for the given parameters and initial state `z_0`,
+    /// binding it to the application `context` (e.g. a chain ID or a batch hash), which is
+    /// absorbed into `u_i.x[0]` alongside `pp_hash`. Unlike `pp_hash`, the context does not depend
+    /// on the preprocessed params, so the same params can be used for different deployments, and a
+    /// proof created under one context does not verify under another. `None` is equivalent to a
+    /// zero context.
+    pub fn init_with_context(
+        params: &(
+            ProverParams<C1, C2, CS1, CS2, H>,
+            VerifierParams<C1, C2, CS1, CS2, H>,
+        ),
+        F: FC,
+        z_0: Vec<C1::ScalarField>,
+        context: Option<C1::ScalarField>,
     ) -> Result<Self, Error> {
-        let IVCProof {
-            i,
-            z_0,
-            z_i,
-            W_i,
-            U_i,
-            w_i,
-            u_i,
-            cf_W_i,
-            cf_U_i,
-        } = ivc_proof;
         let (pp, vp) = params;
+        Self::check_full_challenge(vp)?;
+        if F.state_len() != vp.state_len {
+            return Err(Error::ParamsMismatch(format!(
+                "FCircuit state length {} differs from the preprocessed one {}",
+                F.state_len(),
+                vp.state_len
+            )));
+        }
+        if F.step_constants() != vp.step_constants {
+            return Err(Error::ParamsMismatch(
+                "FCircuit step constants differ from the preprocessed ones".to_string(),
+            ));
+        }
+        check_state("z_0", &z_0, vp.state_len)?;
 
-        let f_circuit = FC::new(fcircuit_params)?;
+        // prepare the circuit to obtain its R1CS
         let cs = ConstraintSystem::<C1::ScalarField>::new_ref();
         let cs2 = ConstraintSystem::<C1::BaseField>::new_ref();
-        let augmented_F_circuit =
-            AugmentedFCircuit::<C1, C2, GC2, FC>::empty(&pp.poseidon_config, f_circuit.clone());
-        let cf_circuit = NovaCycleFoldCircuit::<C1, GC1>::empty();
+
+        let augmented_F_circuit = AugmentedFCircuit::<C1, C2, GC2, FC, FULL_CHALLENGE>::empty(
+            &pp.poseidon_config,
+            F.clone(),
+        )
+        .with_public_pp_hash(vp.public_pp_hash);
+        let cf_circuit = NovaCycleFoldCircuit::<C1, GC1, FULL_CHALLENGE>::empty();
 
         augmented_F_circuit.generate_constraints(cs.clone())?;
         cs.finalize();
@@ -964,100 +1755,117 @@ where
         let cs2 = cs2.into_inner().ok_or(Error::NoInnerConstraintSystem)?;
         let cf_r1cs = extract_r1cs::<C1::BaseField>(&cs2)?;
 
+        // compute the public params hash
+        let pp_hash = vp.pp_hash()?;
+
+        // setup the dummy instances
+        let (W_dummy, U_dummy) = r1cs.dummy_witness_instance();
+        let (w_dummy, u_dummy) = r1cs.dummy_witness_instance();
+        let (cf_W_dummy, cf_U_dummy) = cf_r1cs.dummy_witness_instance();
+
+        // W_dummy=W_0 is a 'dummy witness', all zeroes, but with the size corresponding to the
+        // R1CS that we're working with.
         Ok(Self {
             _gc1: PhantomData,
             _c2: PhantomData,
             _gc2: PhantomData,
             r1cs,
             cf_r1cs,
-            poseidon_config: pp.poseidon_config,
-            cs_pp: pp.cs_pp,
-            cf_cs_pp: pp.cf_cs_pp,
-            F: f_circuit,
-            pp_hash: vp.pp_hash()?,
-            i,
-            z_0,
-            z_i,
-            w_i,
-            u_i,
-            W_i,
-            U_i,
-            cf_W_i,
-            cf_U_i,
-        })
-    }
-
-    /// Implements IVC.V of Nov.clone()a+CycleFold. Notice that this method does not include the
-    /// commitments verification, which is done in the Decider.
-    fn verify(vp: Self::VerifierParam, ivc_proof: Self::IVCProof) -> Result<(), Error> {
-        let Self::IVCProof {
-            i: num_steps,
-            z_0,
-            z_i,
-            W_i,
-            U_i,
-            w_i,
-            u_i,
-            cf_W_i,
-            cf_U_i,
-        } = ivc_proof;
-
-        let sponge = PoseidonSponge::<C1::ScalarField>::new(&vp.poseidon_config);
-
-        if num_steps == C1::ScalarField::zero() {
-            if z_0 != z_i {
-                return Err(Error::IVCVerificationFail);
-            }
-            return Ok(());
-        }
-
-        if u_i.x.len() != 2 || U_i.x.len() != 2 {
-            return Err(Error::IVCVerificationFail);
-        }
+            poseidon_config: pp.poseidon_config.clone(),
+            cs_pp: pp.cs_pp.clone(),
+            cf_cs_pp: pp.cf_cs_pp.clone(),
+            F,
+            pp_hash,
+            context: context.unwrap_or_else(C1::ScalarField::zero),
+            public_pp_hash: vp.public_pp_hash,
+            i: C1::ScalarField::zero(),
+            z_0: z_0.clone(),
+            z_i: z_0,
+            w_i: w_dummy,
+            u_i: u_dummy,
+            W_i: W_dummy,
+            U_i: U_dummy,
+            // cyclefold running instance
+            cf_W_i: cf_W_dummy,
+            cf_U_i: cf_U_dummy,
+        })
+    }
 
-        let pp_hash = vp.pp_hash()?;
+    /// Implements IVC.V of Nova+CycleFold, for an IVC initialized with `init_with_context` under
+    /// the given `context`, which has to be provided again by the verifier. Notice that this
+    /// method does not include the commitments verification, which is done in the Decider.
+    pub fn verify_with_context(
+        vp: VerifierParams<C1, C2, CS1, CS2, H>,
+        ivc_proof: IVCProof<C1, C2>,
+        context: Option<C1::ScalarField>,
+    ) -> Result<(), Error> {
+        vp.verify_ivc_proof(ivc_proof, context)
+    }
 
-        // check that u_i's output points to the running instance
-        // u_i.X[0] == H(i, z_0, z_i, U_i)
-        let expected_u_i_x = U_i.hash(&sponge, pp_hash, num_steps, &z_0, &z_i);
-        if expected_u_i_x != u_i.x[0] {
-            return Err(Error::IVCVerificationFail);
+    // checks that the VerifierParams were preprocessed for the same challenge mode as this Nova
+    fn check_full_challenge(vp: &VerifierParams<C1, C2, CS1, CS2, H>) -> Result<(), Error> {
+        if vp.full_challenge != FULL_CHALLENGE {
+            return Err(Error::ParamsMismatch(format!(
+                "the params are for {} folding challenges, but the Nova uses {} ones",
+                if vp.full_challenge {
+                    "full-width"
+                } else {
+                    "truncated"
+                },
+                if FULL_CHALLENGE {
+                    "full-width"
+                } else {
+                    "truncated"
+                }
+            )));
         }
-        // u_i.X[1] == H(cf_U_i)
-        let expected_cf_u_i_x = cf_U_i.hash_cyclefold(&sponge, pp_hash);
-        if expected_cf_u_i_x != u_i.x[1] {
-            return Err(Error::IVCVerificationFail);
-        }
-
-        // check R1CS satisfiability, which is equivalent to checking if `u_i`
-        // is an incoming instance and if `w_i` and `u_i` satisfy RelaxedR1CS
-        u_i.check_incoming()?;
-        vp.r1cs.check_relation(&w_i, &u_i)?;
-        // check RelaxedR1CS satisfiability
-        vp.r1cs.check_relation(&W_i, &U_i)?;
+        Ok(())
+    }
 
-        // check CycleFold RelaxedR1CS satisfiability
-        vp.cf_r1cs.check_relation(&cf_W_i, &cf_U_i)?;
+    /// same as `vp_deserialize_with_mode`, but for VerifierParams serialized without the
+    /// versioned header, as done by previous versions. None of the header checks is done, so it
+    /// should only be used for trusted files.
+    pub fn vp_deserialize_unchecked<R: ark_std::io::Read>(
+        reader: R,
+        compress: ark_serialize::Compress,
+        validate: ark_serialize::Validate,
+        fc_params: FC::Params,
+    ) -> Result<VerifierParams<C1, C2, CS1, CS2, H>, Error> {
+        let (cs_vp, cf_cs_vp) = deserialize_params_unchecked(reader, compress, validate)?;
+        Self::vp_from_cs_vps(fc_params, cs_vp, cf_cs_vp)
+    }
 
-        Ok(())
+    // recovers the VerifierParams from the commitment schemes params, generating the r1cs &
+    // cf_r1cs. In this way we avoid needing to serialize them, saving significant space in the
+    // VerifierParams serialized size. The R1CS are generated without `public_pp_hash`, the params
+    // preprocessed with it have to be serialized with `serialize_standalone` instead.
+    fn vp_from_cs_vps(
+        fc_params: FC::Params,
+        cs_vp: CS1::VerifierParams,
+        cf_cs_vp: CS2::VerifierParams,
+    ) -> Result<VerifierParams<C1, C2, CS1, CS2, H>, Error> {
+        let poseidon_config = poseidon_canonical_config::<C1::ScalarField>();
+        let f_circuit = FC::new(fc_params)?;
+        let state_len = f_circuit.state_len();
+        let step_constants = f_circuit.step_constants();
+        let (r1cs, cf_r1cs) = get_r1cs_opt::<C1, GC1, C2, GC2, FC, FULL_CHALLENGE>(
+            &poseidon_config,
+            f_circuit,
+            false,
+        )?;
+        Ok(VerifierParams {
+            poseidon_config,
+            r1cs,
+            cf_r1cs,
+            cs_vp,
+            cf_cs_vp,
+            state_len,
+            step_constants,
+            full_challenge: FULL_CHALLENGE,
+            public_pp_hash: false,
+        })
     }
-}
 
-impl<C1, GC1, C2, GC2, FC, CS1, CS2, const H: bool> Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H>
-where
-    C1: CurveGroup,
-    GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
-    C2: CurveGroup,
-    GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
-    FC: FCircuit<C1::ScalarField>,
-    CS1: CommitmentScheme<C1, H>,
-    CS2: CommitmentScheme<C2, H>,
-    <C1 as CurveGroup>::BaseField: PrimeField,
-    <C2 as CurveGroup>::BaseField: PrimeField,
-    <C1 as Group>::ScalarField: Absorb,
-    <C2 as Group>::ScalarField: Absorb,
-    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
-{
     // folds the given cyclefold circuit and its instances
     #[allow(clippy::type_complexity)]
     fn fold_cyclefold_circuit<T: Transcript<C1::ScalarField>>(
@@ -1065,8 +1873,7 @@ where
         transcript: &mut T,
         cf_W_i: CycleFoldWitness<C2>, // witness of the running instance
         cf_U_i: CycleFoldCommittedInstance<C2>, // running instance
-        cf_u_i_x: Vec<C2::ScalarField>,
-        cf_circuit: NovaCycleFoldCircuit<C1, GC1>,
+        cf_circuit: NovaCycleFoldCircuit<C1, GC1, FULL_CHALLENGE>,
         rng: &mut impl RngCore,
     ) -> Result<
         (
@@ -1079,14 +1886,13 @@ where
         ),
         Error,
     > {
-        fold_cyclefold_circuit::<NovaCycleFoldConfig<C1>, C1, GC1, C2, GC2, CS2, H>(
+        fold_cyclefold_circuit::<NovaCycleFoldConfig<C1, FULL_CHALLENGE>, C1, GC1, C2, GC2, CS2, H>(
             transcript,
             self.cf_r1cs.clone(),
             self.cf_cs_pp.clone(),
             self.pp_hash,
             cf_W_i,
             cf_U_i,
-            cf_u_i_x,
             cf_circuit,
             rng,
         )
@@ -1111,6 +1917,30 @@ pub fn get_r1cs<C1, GC1, C2, GC2, FC>(
     poseidon_config: &PoseidonConfig<C1::ScalarField>,
     F_circuit: FC,
 ) -> Result<(R1CS<C1::ScalarField>, R1CS<C2::ScalarField>), Error>
+where
+    C1: CurveGroup,
+    GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+    C2: CurveGroup,
+    GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+    FC: FCircuit<C1::ScalarField>,
+    <C1 as CurveGroup>::BaseField: PrimeField,
+    <C2 as CurveGroup>::BaseField: PrimeField,
+    <C1 as Group>::ScalarField: Absorb,
+    <C2 as Group>::ScalarField: Absorb,
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+{
+    get_r1cs_opt::<C1, GC1, C2, GC2, FC, false>(poseidon_config, F_circuit, false)
+}
+
+/// Same as `get_r1cs`, for the circuits of the Nova whose folding challenges are full-width when
+/// `FULL_CHALLENGE` is set, and whose AugmentedFCircuit exposes `pp_hash` as a public input when
+/// `public_pp_hash` is set.
+#[allow(clippy::type_complexity)]
+pub fn get_r1cs_opt<C1, GC1, C2, GC2, FC, const FULL_CHALLENGE: bool>(
+    poseidon_config: &PoseidonConfig<C1::ScalarField>,
+    F_circuit: FC,
+    public_pp_hash: bool,
+) -> Result<(R1CS<C1::ScalarField>, R1CS<C2::ScalarField>), Error>
 where
     C1: CurveGroup,
     GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
@@ -1124,8 +1954,9 @@ where
     C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
 {
     let augmented_F_circuit =
-        AugmentedFCircuit::<C1, C2, GC2, FC>::empty(poseidon_config, F_circuit);
-    let cf_circuit = NovaCycleFoldCircuit::<C1, GC1>::empty();
+        AugmentedFCircuit::<C1, C2, GC2, FC, FULL_CHALLENGE>::empty(poseidon_config, F_circuit)
+            .with_public_pp_hash(public_pp_hash);
+    let cf_circuit = NovaCycleFoldCircuit::<C1, GC1, FULL_CHALLENGE>::empty();
     let r1cs = get_r1cs_from_cs::<C1::ScalarField>(augmented_F_circuit)?;
     let cf_r1cs = get_r1cs_from_cs::<C2::ScalarField>(cf_circuit)?;
     Ok((r1cs, cf_r1cs))
@@ -1160,9 +1991,15 @@ pub mod tests {
     use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
 
     use super::*;
-    use crate::commitment::pedersen::Pedersen;
-    use crate::frontend::utils::CubicFCircuit;
+    use crate::commitment::{ipa::IPA, pedersen::Pedersen};
+    use crate::folding::nova::nifs::nova::ChallengeGadget;
+    use crate::frontend::utils::{
+        CubicFCircuit, MultiInputsFCircuit, QuinticFCircuit, StepConstantsFCircuit,
+        WrongStateLenFCircuit,
+    };
     use crate::transcript::poseidon::poseidon_canonical_config;
+    use ark_ff::BigInteger;
+    use ark_serialize::{Compress, Validate};
 
     /// This test tests the Nova+CycleFold IVC, and by consequence it is also testing the
     /// AugmentedFCircuit
@@ -1190,6 +2027,883 @@ pub mod tests {
         test_ivc_opt::<KZG<Bn254>, Pedersen<Projective2>, false>(poseidon_config, F_circuit, 3);
     }
 
+    /// tests the Nova+CycleFold IVC over the Pasta curves, in both orders of the cycle, with IPA
+    /// commitments on both sides, so that no pairing nor trusted setup is involved
+    #[test]
+    fn test_ivc_pasta() {
+        use ark_pallas::{constraints::GVar as PallasGVar, Projective as Pallas};
+        use ark_vesta::{constraints::GVar as VestaGVar, Projective as Vesta};
+
+        test_ivc_pasta_opt::<Pallas, PallasGVar, Vesta, VestaGVar>();
+        test_ivc_pasta_opt::<Vesta, VestaGVar, Pallas, PallasGVar>();
+    }
+
+    fn test_ivc_pasta_opt<C1, GC1, C2, GC2>()
+    where
+        C1: CurveGroup,
+        GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+        C2: CurveGroup,
+        GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+        <C1 as CurveGroup>::BaseField: PrimeField,
+        <C2 as CurveGroup>::BaseField: PrimeField,
+        <C1 as Group>::ScalarField: Absorb,
+        <C2 as Group>::ScalarField: Absorb,
+        C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    {
+        let n_steps = 5;
+        let mut rng = ark_std::test_rng();
+        // the canonical configuration, which is the one rebuilt when deserializing the params
+        let poseidon_config = poseidon_canonical_config::<C1::ScalarField>();
+        let F_circuit = CubicFCircuit::<C1::ScalarField>::new(()).unwrap();
+
+        let nova_params = Nova::<
+            C1,
+            GC1,
+            C2,
+            GC2,
+            CubicFCircuit<C1::ScalarField>,
+            IPA<C1>,
+            IPA<C2>,
+            false,
+        >::preprocess(
+            &mut rng,
+            &PreprocessorParam::new(poseidon_config.clone(), F_circuit),
+        )
+        .unwrap();
+        let z_0 = vec![C1::ScalarField::from(3_u32)];
+        let mut nova =
+            Nova::<C1, GC1, C2, GC2, _, _, _, false>::init(&nova_params, F_circuit, z_0.clone())
+                .unwrap();
+        for _ in 0..n_steps {
+            nova.prove_step(&mut rng, (), None).unwrap();
+        }
+        assert_eq!(nova.i, C1::ScalarField::from(n_steps as u32));
+
+        // the state matches the one computed natively
+        let mut z = z_0;
+        for i in 0..n_steps {
+            z = F_circuit.step_native(i, z, ()).unwrap();
+        }
+        assert_eq!(nova.state().z_i, z);
+
+        // the verifier params recovered from their serialization verify the proof
+        let mut vp_serialized = vec![];
+        nova_params
+            .1
+            .serialize_standalone(&mut vp_serialized)
+            .unwrap();
+        let vp = VerifierParams::<C1, C2, IPA<C1>, IPA<C2>>::deserialize_standalone(
+            vp_serialized.as_slice(),
+        )
+        .unwrap();
+        assert_eq!(vp.pp_hash().unwrap(), nova_params.1.pp_hash().unwrap());
+        vp.verify_ivc_proof(nova.ivc_proof(), None).unwrap();
+
+        // the commitments of the running instance open with the IPA over the folding params, since
+        // the Nova Decider does not support the Pasta curves. The vectors are padded with zeros to a power
+        // of two, which does not change their commitments.
+        for (cm, v) in [(nova.U_i.cmW, &nova.W_i.W), (nova.U_i.cmE, &nova.W_i.E)] {
+            let mut v = v.clone();
+            v.resize(v.len().next_power_of_two(), C1::ScalarField::zero());
+            assert_eq!(
+                IPA::<C1>::commit(&nova_params.0.cs_pp, &v, &Zero::zero()).unwrap(),
+                cm
+            );
+
+            let mut transcript_p = PoseidonSponge::<C1::ScalarField>::new(&poseidon_config);
+            let proof = IPA::<C1>::prove(
+                &nova_params.0.cs_pp,
+                &mut transcript_p,
+                &cm,
+                &v,
+                &Zero::zero(),
+                None,
+            )
+            .unwrap();
+            let mut transcript_v = PoseidonSponge::<C1::ScalarField>::new(&poseidon_config);
+            IPA::<C1>::verify(&vp.cs_vp, &mut transcript_v, &cm, &proof).unwrap();
+        }
+    }
+
+    /// tests that an IVC proof created under a context only verifies under that same context
+    #[test]
+    fn test_ivc_context() {
+        type N = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+
+        let nova_params = N::preprocess(
+            &mut rng,
+            &PreprocessorParam::new(poseidon_config, F_circuit),
+        )
+        .unwrap();
+        let mut nova = N::init_with_context(
+            &nova_params,
+            F_circuit,
+            vec![Fr::from(3_u32)],
+            Some(Fr::from(42_u32)),
+        )
+        .unwrap();
+        for _ in 0..3 {
+            nova.prove_step(&mut rng, (), None).unwrap();
+        }
+        let ivc_proof = nova.ivc_proof();
+        let vp = nova_params.1;
+
+        N::verify_with_context(vp.clone(), ivc_proof.clone(), Some(Fr::from(42_u32))).unwrap();
+
+        // a different context, or no context at all, is rejected
+        assert!(
+            N::verify_with_context(vp.clone(), ivc_proof.clone(), Some(Fr::from(43_u32))).is_err()
+        );
+        assert!(N::verify(vp.clone(), ivc_proof.clone()).is_err());
+
+        // replacing the context carried in the proof does not help, since it is bound in u_i.x
+        let mut tampered_proof = ivc_proof;
+        tampered_proof.context = Fr::from(43_u32);
+        assert!(N::verify_with_context(vp, tampered_proof, Some(Fr::from(43_u32))).is_err());
+    }
+
+    /// tests the IVC with full-width folding challenges, and that its params and proofs are
+    /// rejected by the Nova with truncated challenges, and vice versa
+    #[test]
+    fn test_ivc_full_challenge() {
+        type N<const FULL_CHALLENGE: bool> = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+            FULL_CHALLENGE,
+        >;
+
+        let mut rng = ark_std::test_rng();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let prep_param = PreprocessorParam::new(poseidon_canonical_config::<Fr>(), F_circuit)
+            .with_domain_tag(b"sonobe-test");
+
+        let params = N::<true>::preprocess(&mut rng, &prep_param).unwrap();
+        assert!(params.1.full_challenge);
+        let mut nova = N::<true>::init(&params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+        for _ in 0..3 {
+            nova.prove_step(&mut rng, (), None).unwrap();
+        }
+        let ivc_proof = nova.ivc_proof();
+        N::<true>::verify(params.1.clone(), ivc_proof.clone()).unwrap();
+
+        // the randomness of the CycleFold circuit takes two field elements, and the mode is bound
+        // to the pp_hash, even for the same commitment params
+        let truncated_params = N::<false>::preprocess(&mut rng, &prep_param).unwrap();
+        assert!(!truncated_params.1.full_challenge);
+        assert_eq!(params.1.cf_r1cs.l, truncated_params.1.cf_r1cs.l + 1);
+        assert_ne!(
+            params.1.pp_hash().unwrap(),
+            truncated_params.1.pp_hash().unwrap()
+        );
+
+        // mixing the modes fails cleanly
+        assert!(matches!(
+            N::<true>::init(&truncated_params, F_circuit, vec![Fr::from(3_u32)]),
+            Err(Error::ParamsMismatch(_))
+        ));
+        assert!(matches!(
+            N::<false>::init(&params, F_circuit, vec![Fr::from(3_u32)]),
+            Err(Error::ParamsMismatch(_))
+        ));
+        assert!(matches!(
+            N::<false>::from_ivc_proof(ivc_proof.clone(), (), params.clone()),
+            Err(Error::ParamsMismatch(_))
+        ));
+        assert!(N::<false>::verify(truncated_params.1, ivc_proof.clone()).is_err());
+
+        // the standalone serialization stores the mode, which is bound to the stored pp_hash
+        let mut vp_serialized = vec![];
+        params.1.serialize_standalone(&mut vp_serialized).unwrap();
+        let vp = PedersenVerifierParams::deserialize_standalone(vp_serialized.as_slice()).unwrap();
+        assert!(vp.full_challenge);
+        vp.verify_ivc_proof(ivc_proof, None).unwrap();
+        // the flag is followed by the 32 bytes of the pp_hash
+        let flag = vp_serialized.len() - 33;
+        vp_serialized[flag] = 0;
+        assert!(matches!(
+            PedersenVerifierParams::deserialize_standalone(vp_serialized.as_slice()),
+            Err(Error::ParamsMismatch(_))
+        ));
+    }
+
+    /// tests folding and verifying with and without pp_hash as a public input, and that the
+    /// params and proofs of one mode are rejected by the other
+    #[test]
+    fn test_ivc_public_pp_hash() {
+        type N = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+
+        let mut rng = ark_std::test_rng();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let prep_param = PreprocessorParam::new(poseidon_canonical_config::<Fr>(), F_circuit)
+            .with_domain_tag(b"sonobe-test");
+
+        let mut all_params = vec![];
+        let mut ivc_proofs = vec![];
+        for public_pp_hash in [false, true] {
+            let prep_param = if public_pp_hash {
+                prep_param.clone().with_public_pp_hash()
+            } else {
+                prep_param.clone()
+            };
+            let params = N::preprocess(&mut rng, &prep_param).unwrap();
+            assert_eq!(params.1.public_pp_hash, public_pp_hash);
+            assert_eq!(params.1.r1cs.l, augmented_io_len(public_pp_hash));
+
+            let mut nova = N::init(&params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+            for _ in 0..3 {
+                nova.prove_step(&mut rng, (), None).unwrap();
+            }
+            let ivc_proof = nova.ivc_proof();
+            assert_eq!(ivc_proof.u_i.x.len(), augmented_io_len(public_pp_hash));
+            assert_eq!(ivc_proof.U_i.x.len(), augmented_io_len(public_pp_hash));
+            if public_pp_hash {
+                assert_eq!(ivc_proof.u_i.x[2], params.1.pp_hash().unwrap());
+            }
+            N::verify(params.1.clone(), ivc_proof.clone()).unwrap();
+
+            // the IVC can be resumed from its proof
+            let mut nova = N::from_ivc_proof(ivc_proof.clone(), (), params.clone()).unwrap();
+            nova.prove_step(&mut rng, (), None).unwrap();
+            N::verify(params.1.clone(), nova.ivc_proof()).unwrap();
+
+            all_params.push(params);
+            ivc_proofs.push(ivc_proof);
+        }
+        let (params, public_params) = (&all_params[0], &all_params[1]);
+
+        // the mode is bound to the pp_hash, even for the same commitment params
+        assert_ne!(
+            params.1.pp_hash().unwrap(),
+            public_params.1.pp_hash().unwrap()
+        );
+
+        // the proofs of one mode do not verify with the params of the other
+        assert!(N::verify(public_params.1.clone(), ivc_proofs[0].clone()).is_err());
+        assert!(N::verify(params.1.clone(), ivc_proofs[1].clone()).is_err());
+        assert!(N::from_ivc_proof(ivc_proofs[1].clone(), (), params.clone()).is_err());
+
+        // nor do params whose flag disagrees with their R1CS
+        let mut vp = public_params.1.clone();
+        vp.public_pp_hash = false;
+        assert!(vp.check_consistency().is_err());
+        assert!(N::verify(vp, ivc_proofs[1].clone()).is_err());
+
+        // the exposed pp_hash must be the one of the params
+        let mut tampered_proof = ivc_proofs[1].clone();
+        tampered_proof.u_i.x[2] += Fr::from(1_u32);
+        assert!(N::verify(public_params.1.clone(), tampered_proof).is_err());
+
+        // the standalone serialization recovers the mode from the R1CS
+        let mut vp_serialized = vec![];
+        public_params
+            .1
+            .serialize_standalone(&mut vp_serialized)
+            .unwrap();
+        let vp = VerifierParams::<
+            Projective,
+            Projective2,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+        >::deserialize_standalone(vp_serialized.as_slice())
+        .unwrap();
+        assert!(vp.public_pp_hash);
+        vp.verify_ivc_proof(ivc_proofs[1].clone(), None).unwrap();
+    }
+
+    #[test]
+    fn test_prove_step_profiled() {
+        type N = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        let mut rng = ark_std::test_rng();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let nova_params = N::preprocess(
+            &mut rng,
+            &PreprocessorParam::new(poseidon_canonical_config::<Fr>(), F_circuit),
+        )
+        .unwrap();
+        let mut nova = N::init(&nova_params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+        let mut profiled_nova = nova.clone();
+
+        for i in 0..3 {
+            nova.prove_step(&mut rng, (), None).unwrap();
+            let (result, profile) = profiled_nova.prove_step_profiled(&mut rng, (), None);
+            result.unwrap();
+
+            let n_constraints = nova_params.1.r1cs.A.n_rows;
+            assert_eq!(profile.cross_term.size, n_constraints);
+            assert_eq!(profile.cmT.size, n_constraints);
+            assert_eq!(profile.challenge.size, NOVA_N_BITS_RO);
+            assert_eq!(profile.cmW.size, profiled_nova.w_i.W.len());
+            assert_eq!(profile.witness_generation.size, profiled_nova.w_i.W.len());
+            // there is no CycleFold proving at the base case
+            assert_eq!(
+                profile.cyclefold.size,
+                if i == 0 {
+                    0
+                } else {
+                    2 * nova_params.1.cf_r1cs.A.n_rows
+                }
+            );
+            assert!(
+                profile.total >= profile.witness_generation.duration + profile.cyclefold.duration
+            );
+            // the test binary does not install `CountingAlloc`
+            assert_eq!(profile.peak_allocation, None);
+        }
+        // profiling does not change the computed IVC
+        assert_eq!(nova.z_i, profiled_nova.z_i);
+        N::verify(nova_params.1, profiled_nova.ivc_proof()).unwrap();
+    }
+
+    /// tests that `prove_step` reports each of its phases as a child span of `nova_prove_step`
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_prove_step_spans() {
+        use std::sync::{Arc, Mutex};
+        use tracing::{span, Event, Metadata, Subscriber};
+
+        // (name, parent name, whether it has the `n_constraints` field) of each created span
+        type Spans = Arc<Mutex<Vec<(&'static str, Option<&'static str>, bool)>>>;
+
+        #[derive(Default)]
+        struct SpanCollector {
+            spans: Spans,
+            stack: Mutex<Vec<span::Id>>,
+        }
+        impl SpanCollector {
+            fn name(&self, id: &span::Id) -> &'static str {
+                self.spans.lock().unwrap()[id.into_u64() as usize - 1].0
+            }
+        }
+        impl Subscriber for SpanCollector {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+                let parent = self.stack.lock().unwrap().last().map(|id| self.name(id));
+                let mut spans = self.spans.lock().unwrap();
+                spans.push((
+                    attrs.metadata().name(),
+                    parent,
+                    attrs.metadata().fields().field("n_constraints").is_some(),
+                ));
+                span::Id::from_u64(spans.len() as u64)
+            }
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+            fn event(&self, _: &Event<'_>) {}
+            fn enter(&self, id: &span::Id) {
+                self.stack.lock().unwrap().push(id.clone());
+            }
+            fn exit(&self, _: &span::Id) {
+                self.stack.lock().unwrap().pop();
+            }
+        }
+
+        type N = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        let mut rng = ark_std::test_rng();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let nova_params = N::preprocess(
+            &mut rng,
+            &PreprocessorParam::new(poseidon_canonical_config::<Fr>(), F_circuit),
+        )
+        .unwrap();
+        let mut nova = N::init(&nova_params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+        // the CycleFold phase only exists from the second step on
+        nova.prove_step(&mut rng, (), None).unwrap();
+
+        let collector = SpanCollector::default();
+        let spans = collector.spans.clone();
+        tracing::subscriber::with_default(collector, || {
+            nova.prove_step(&mut rng, (), None).unwrap();
+        });
+
+        let spans = spans.lock().unwrap();
+        assert_eq!(spans[0], ("nova_prove_step", None, true));
+        let children: Vec<&str> = spans
+            .iter()
+            .filter(|(_, parent, _)| *parent == Some("nova_prove_step"))
+            .map(|(name, _, _)| *name)
+            .collect();
+        assert_eq!(
+            children,
+            [
+                "step_native",
+                "nifs_prove",
+                "cyclefold_prove",
+                "augmented_circuit_synthesis",
+                "witness_extraction",
+                "commit",
+            ]
+        );
+        assert!(spans.contains(&("nifs_prove", Some("nova_prove_step"), true)));
+        assert!(spans.contains(&("cyclefold_prove", Some("nova_prove_step"), true)));
+    }
+
+    /// tests that the compact serialization of the AugmentedFCircuit's R1CS round-trips, and that
+    /// it is several times smaller than its `CanonicalSerialize` serialization
+    #[test]
+    fn test_augmented_r1cs_serialize_compressed_opt() {
+        use crate::arith::ArithSerializer;
+
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let (r1cs, _) =
+            get_r1cs::<Projective, GVar, Projective2, GVar2, _>(&poseidon_config, F_circuit)
+                .unwrap();
+
+        let mut bytes = Vec::new();
+        r1cs.serialize_compressed_opt(&mut bytes).unwrap();
+        let canonical_size = r1cs.compressed_size();
+        println!(
+            "augmented R1CS: {} bytes compact, {} bytes canonical",
+            bytes.len(),
+            canonical_size
+        );
+        assert!(bytes.len() * 3 <= canonical_size);
+
+        assert_eq!(
+            R1CS::<Fr>::deserialize_compressed_opt(&bytes[..]).unwrap(),
+            r1cs
+        );
+    }
+
+    type PedersenNova = Nova<
+        Projective,
+        GVar,
+        Projective2,
+        GVar2,
+        CubicFCircuit<Fr>,
+        Pedersen<Projective>,
+        Pedersen<Projective2>,
+        false,
+    >;
+    type PedersenProverParams =
+        ProverParams<Projective, Projective2, Pedersen<Projective>, Pedersen<Projective2>>;
+    type PedersenVerifierParams =
+        VerifierParams<Projective, Projective2, Pedersen<Projective>, Pedersen<Projective2>>;
+
+    /// tests that the compact serialization of the ProverParams and the standalone one of the
+    /// VerifierParams round-trip, and that together they are several times smaller than the
+    /// uncompressed `CanonicalSerialize` output of the params and of their R1CS
+    #[test]
+    fn test_params_compact_serialization() {
+        let mut rng = ark_std::test_rng();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let (pp, vp) = PedersenNova::preprocess(
+            &mut rng,
+            &PreprocessorParam::new(poseidon_canonical_config::<Fr>(), F_circuit),
+        )
+        .unwrap();
+
+        let mut vp_bytes = Vec::new();
+        vp.serialize_standalone(&mut vp_bytes).unwrap();
+        // the Pedersen prover params are omitted, since they are recomputed from the verifier ones
+        let mut pp_bytes = Vec::new();
+        pp.serialize_compact(&mut pp_bytes, Some(&vp)).unwrap();
+        let uncompressed_size = pp.uncompressed_size()
+            + vp.uncompressed_size()
+            + vp.r1cs.uncompressed_size()
+            + vp.cf_r1cs.uncompressed_size();
+        println!(
+            "params: {} bytes compact ({} prover, {} verifier), {} bytes uncompressed",
+            pp_bytes.len() + vp_bytes.len(),
+            pp_bytes.len(),
+            vp_bytes.len(),
+            uncompressed_size
+        );
+        assert!((pp_bytes.len() + vp_bytes.len()) * 3 <= uncompressed_size);
+
+        let vp_deserialized =
+            PedersenVerifierParams::deserialize_standalone(&vp_bytes[..]).unwrap();
+        assert_eq!(vp_deserialized.r1cs, vp.r1cs);
+        assert_eq!(vp_deserialized.cf_r1cs, vp.cf_r1cs);
+        assert_eq!(vp_deserialized.pp_hash().unwrap(), vp.pp_hash().unwrap());
+        let pp_deserialized =
+            PedersenProverParams::deserialize_compact(&pp_bytes[..], &vp_deserialized).unwrap();
+        assert_eq!(pp_deserialized.cs_params(), pp.cs_params());
+
+        // without the verifier params nothing is omitted
+        let mut full_pp_bytes = Vec::new();
+        pp.serialize_compact(&mut full_pp_bytes, None).unwrap();
+        assert!(full_pp_bytes.len() > pp_bytes.len() + pp.cs_pp.compressed_size());
+        assert_eq!(
+            PedersenProverParams::deserialize_compact(&full_pp_bytes[..], &vp)
+                .unwrap()
+                .cs_params(),
+            pp.cs_params()
+        );
+
+        // the deserialized params fold and verify
+        let mut nova = PedersenNova::init(
+            &(pp_deserialized, vp_deserialized.clone()),
+            F_circuit,
+            vec![Fr::from(3_u32)],
+        )
+        .unwrap();
+        nova.prove_step(&mut rng, (), None).unwrap();
+        PedersenNova::verify(vp_deserialized, nova.ivc_proof()).unwrap();
+    }
+
+    /// tests that corrupted and inconsistent params are rejected, and that the VerifierParams
+    /// serialized without header by the first version of `serialize_standalone` are dispatched to
+    /// `deserialize_standalone_legacy`
+    #[test]
+    fn test_params_serialization_rejects_corrupted() {
+        let mut rng = ark_std::test_rng();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let (pp, vp) = PedersenNova::preprocess(
+            &mut rng,
+            &PreprocessorParam::new(poseidon_canonical_config::<Fr>(), F_circuit),
+        )
+        .unwrap();
+        let mut vp_bytes = Vec::new();
+        vp.serialize_standalone(&mut vp_bytes).unwrap();
+
+        // a bit flipped in the stored pp_hash
+        let mut corrupted = vp_bytes.clone();
+        corrupted[vp_bytes.len() - 32] ^= 1;
+        assert!(matches!(
+            PedersenVerifierParams::deserialize_standalone(&corrupted[..]),
+            Err(Error::ParamsMismatch(_))
+        ));
+        // bits flipped in the R1CS, which are not covered by the checksum of the commitment
+        // schemes params
+        let r1cs_start = STANDALONE_MAGIC.len()
+            + 4
+            + params_serialized_size::<Projective, Projective2>(
+                Compress::Yes,
+                &vp.cs_vp,
+                &vp.cf_cs_vp,
+            );
+        for i in [
+            r1cs_start,
+            r1cs_start + 5,
+            (r1cs_start + vp_bytes.len()) / 2,
+        ] {
+            let mut corrupted = vp_bytes.clone();
+            corrupted[i] ^= 1;
+            assert!(PedersenVerifierParams::deserialize_standalone(&corrupted[..]).is_err());
+        }
+        // a truncated file
+        assert!(
+            PedersenVerifierParams::deserialize_standalone(&vp_bytes[..vp_bytes.len() / 2])
+                .is_err()
+        );
+
+        // an unknown format version
+        let mut unknown_version = vp_bytes.clone();
+        unknown_version[STANDALONE_MAGIC.len()] += 1;
+        assert!(matches!(
+            PedersenVerifierParams::deserialize_standalone(&unknown_version[..]),
+            Err(Error::ParamsMismatch(_))
+        ));
+
+        // the legacy files, without header, Poseidon config, full_challenge flag and pp_hash, are
+        // read by the legacy reader
+        let mut poseidon_config_bytes = Vec::new();
+        serialize_poseidon_config(
+            &vp.poseidon_config,
+            &mut poseidon_config_bytes,
+            Compress::Yes,
+        )
+        .unwrap();
+        let legacy = &vp_bytes
+            [STANDALONE_MAGIC.len() + 4..vp_bytes.len() - poseidon_config_bytes.len() - 1 - 32];
+        for vp_legacy in [
+            PedersenVerifierParams::deserialize_standalone(legacy).unwrap(),
+            PedersenVerifierParams::deserialize_standalone_legacy(legacy).unwrap(),
+        ] {
+            assert_eq!(vp_legacy.pp_hash().unwrap(), vp.pp_hash().unwrap());
+        }
+
+        // commitment params too short for the circuits
+        let (short_cs_pp, _) = Pedersen::<Projective>::trim(&pp.cs_pp, &vp.cs_vp, 1).unwrap();
+        let short_pp = PedersenProverParams {
+            poseidon_config: pp.poseidon_config.clone(),
+            cs_pp: short_cs_pp,
+            cf_cs_pp: pp.cf_cs_pp.clone(),
+        };
+        assert!(short_pp.check_consistency(&vp).is_err());
+        let mut pp_bytes = Vec::new();
+        short_pp
+            .serialize_compact(&mut pp_bytes, Some(&vp))
+            .unwrap();
+        assert!(PedersenProverParams::deserialize_compact(&pp_bytes[..], &vp).is_err());
+
+        // unknown flags of the omitted params
+        let mut pp_bytes = Vec::new();
+        pp.serialize_compact(&mut pp_bytes, Some(&vp)).unwrap();
+        pp_bytes[0] |= 1 << 7;
+        assert!(matches!(
+            PedersenProverParams::deserialize_compact(&pp_bytes[..], &vp),
+            Err(Error::ParamsMismatch(_))
+        ));
+    }
+
+    /// tests that with hiding enabled, the blinding factors carried in the witnesses are folded
+    /// together with the vectors, so that the folded commitments still open to them
+    #[test]
+    fn test_ivc_hiding_commitments() {
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+
+        let (_, nova) = test_ivc_opt::<Pedersen<Projective, true>, Pedersen<Projective2, true>, true>(
+            poseidon_config,
+            F_circuit,
+            4,
+        );
+        // relaxed relations
+        nova.r1cs.check_relation(&nova.W_i, &nova.U_i).unwrap();
+        nova.r1cs.check_relation(&nova.w_i, &nova.u_i).unwrap();
+        nova.cf_r1cs
+            .check_relation(&nova.cf_W_i, &nova.cf_U_i)
+            .unwrap();
+        // commitments of the running and incoming instances, including the CycleFold ones
+        nova.W_i
+            .check_commitments::<Pedersen<Projective, true>, true>(&nova.cs_pp, &nova.U_i)
+            .unwrap();
+        nova.w_i
+            .check_commitments::<Pedersen<Projective, true>, true>(&nova.cs_pp, &nova.u_i)
+            .unwrap();
+        nova.cf_W_i
+            .check_commitments::<Pedersen<Projective2, true>, true>(&nova.cf_cs_pp, &nova.cf_U_i)
+            .unwrap();
+        assert!(!nova.W_i.rW.is_zero());
+
+        // a witness with a wrong blinding does not open the commitments
+        let mut W_i = nova.W_i.clone();
+        W_i.rW += Fr::one();
+        assert!(W_i
+            .check_commitments::<Pedersen<Projective, true>, true>(&nova.cs_pp, &nova.U_i)
+            .is_err());
+
+        // equal witnesses with different blindings have different commitments
+        let mut rng = ark_std::test_rng();
+        let w1 = Witness::<Projective>::new::<true>(nova.w_i.W.clone(), nova.w_i.E.len(), &mut rng);
+        let w2 = Witness::<Projective>::new::<true>(nova.w_i.W.clone(), nova.w_i.E.len(), &mut rng);
+        let u1 = w1
+            .commit::<Pedersen<Projective, true>, true>(&nova.cs_pp, nova.u_i.x.clone())
+            .unwrap();
+        let u2 = w2
+            .commit::<Pedersen<Projective, true>, true>(&nova.cs_pp, nova.u_i.x.clone())
+            .unwrap();
+        assert_ne!(u1.cmW, u2.cmW);
+        // the commitment to E of a fresh instance is zero, as expected by the circuits
+        assert!(u1.cmE.is_zero());
+    }
+
+    /// tests that the commitment scheme params generated by the preprocess of a FCircuit can be
+    /// reused to preprocess and fold a different FCircuit, and that too short params are rejected
+    #[test]
+    fn test_preprocess_with_shared_cs_params() {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        type N<FC> = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            FC,
+            KZG<'static, Bn254>,
+            Pedersen<Projective2>,
+            false,
+        >;
+
+        let multi_inputs = MultiInputsFCircuit::<Fr>::new(()).unwrap();
+        let multi_inputs_params = N::preprocess(
+            &mut rng,
+            &PreprocessorParam::new(poseidon_config.clone(), multi_inputs),
+        )
+        .unwrap();
+        let (cs_pp, cf_cs_pp) = multi_inputs_params.0.cs_params();
+        let (cs_vp, cf_cs_vp) = multi_inputs_params.1.cs_params();
+
+        let cubic = CubicFCircuit::<Fr>::new(()).unwrap();
+        let cubic_params = N::preprocess(
+            &mut rng,
+            &PreprocessorParam::new(poseidon_config.clone(), cubic).with_cs_params(
+                (cs_pp.clone(), cs_vp.clone()),
+                (cf_cs_pp.clone(), cf_cs_vp.clone()),
+            ),
+        )
+        .unwrap();
+        // the shared params are trimmed to the length needed by each circuit
+        let (cubic_len, _) =
+            get_cs_params_len::<Projective, GVar, Projective2, GVar2, _>(&poseidon_config, cubic)
+                .unwrap();
+        assert_eq!(
+            cubic_params.0.cs_pp.powers_of_g.len(),
+            cubic_len.next_power_of_two() + 1
+        );
+        assert_eq!(cubic_params.0.cf_cs_pp, *cf_cs_pp);
+
+        let mut nova =
+            N::init(&multi_inputs_params, multi_inputs, vec![Fr::from(1_u32); 5]).unwrap();
+        let mut cubic_nova = N::init(&cubic_params, cubic, vec![Fr::from(3_u32)]).unwrap();
+        for _ in 0..2 {
+            nova.prove_step(&mut rng, (), None).unwrap();
+            cubic_nova.prove_step(&mut rng, (), None).unwrap();
+        }
+        N::verify(multi_inputs_params.1.clone(), nova.ivc_proof()).unwrap();
+        N::verify(cubic_params.1, cubic_nova.ivc_proof()).unwrap();
+
+        // params that are too short for the circuit are rejected
+        let short_cs_params = KZG::<Bn254>::setup(&mut rng, 1).unwrap();
+        assert!(N::preprocess(
+            &mut rng,
+            &PreprocessorParam::new(poseidon_config, cubic)
+                .with_cs_params(short_cs_params, (cf_cs_pp.clone(), cf_cs_vp.clone())),
+        )
+        .is_err());
+    }
+
+    /// tests that an accumulator reconstructed outside of Nova, from the incoming instances of
+    /// each step and the public NIFS methods, matches the running instance of Nova after each step
+    #[test]
+    fn test_external_accumulator() {
+        type N = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        type NF = NIFS<Projective, Pedersen<Projective>, PoseidonSponge<Fr>>;
+
+        let mut rng = ark_std::test_rng();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let params = N::preprocess(
+            &mut rng,
+            &PreprocessorParam::new(poseidon_canonical_config::<Fr>(), F_circuit),
+        )
+        .unwrap();
+        let mut nova = N::init(&params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+
+        // the external accumulator starts from the dummy running instance, as Nova does
+        let (mut W, mut U): (Witness<Projective>, CommittedInstance<Projective>) =
+            nova.r1cs.dummy_witness_instance();
+        for _ in 0..4 {
+            // the incoming instance folded at this step, which the prover shares
+            let (w_i, u_i) = (nova.w_i.clone(), nova.u_i.clone());
+            nova.prove_step(&mut rng, (), None).unwrap();
+
+            // the cross-terms are computed from the witnesses, and the challenge from a fresh
+            // transcript, as done by `prove_step`
+            let z1 = [vec![U.u], U.x.clone(), W.W.clone()].concat();
+            let z2 = [vec![u_i.u], u_i.x.clone(), w_i.W.clone()].concat();
+            let T = NF::compute_T(&nova.r1cs, U.u, u_i.u, &z1, &z2).unwrap();
+            let cmT = commit_maybe_sparse::<Projective, Pedersen<Projective>, false>(
+                &nova.cs_pp,
+                &T,
+                &Fr::zero(),
+            )
+            .unwrap();
+            let mut transcript = PoseidonSponge::<Fr>::new(&nova.poseidon_config);
+            let r_bits =
+                ChallengeGadget::<Projective, CommittedInstance<Projective>>::get_challenge_native(
+                    &mut transcript,
+                    nova.pp_hash,
+                    &U,
+                    &u_i,
+                    Some(&cmT),
+                );
+            let r = Fr::from_bigint(BigInteger::from_bits_le(&r_bits)).unwrap();
+
+            W = NF::fold_witness(r, &W, &w_i, &T).unwrap();
+            U = NF::fold_committed_instances(r, &U, &u_i, &cmT);
+            assert_eq!(W, nova.W_i);
+            assert_eq!(U, nova.U_i);
+            nova.r1cs.check_relation(&W, &U).unwrap();
+        }
+    }
+
+    /// tests that the relaxed relation and commitment checks report where they fail
+    #[test]
+    fn test_relaxed_relation_failure_location() {
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+
+        let (_, nova) = test_ivc_opt::<Pedersen<Projective>, Pedersen<Projective2>, false>(
+            poseidon_config,
+            F_circuit,
+            2,
+        );
+
+        // corrupting an entry of the error term fails the vector relation exactly at that row
+        let row = nova.W_i.E.len() / 2;
+        let mut W_i = nova.W_i.clone();
+        W_i.E[row] += Fr::one();
+        match nova.r1cs.check_relation(&W_i, &nova.U_i) {
+            Err(Error::NotSatisfiedAt { row: r, lhs, rhs }) => {
+                assert_eq!(r, row);
+                assert_eq!(lhs, nova.W_i.E[row].to_string());
+                assert_eq!(rhs, W_i.E[row].to_string());
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+        // while the commitment check reports the commitment that does not open
+        assert!(matches!(
+            W_i.check_commitments::<Pedersen<Projective>, false>(&nova.cs_pp, &nova.U_i),
+            Err(Error::CommitmentNotSatisfied(cm)) if cm == "cmE"
+        ));
+        let mut W_i = nova.W_i.clone();
+        W_i.W[0] += Fr::one();
+        assert!(matches!(
+            W_i.check_commitments::<Pedersen<Projective>, false>(&nova.cs_pp, &nova.U_i),
+            Err(Error::CommitmentNotSatisfied(cm)) if cm == "cmW"
+        ));
+    }
+
     // test_ivc allowing to choose the CommitmentSchemes
     #[allow(clippy::type_complexity)]
     pub(crate) fn test_ivc_opt<
@@ -1214,6 +2928,8 @@ pub mod tests {
                 cs_vp: None,
                 cf_cs_pp: None,
                 cf_cs_vp: None,
+                domain_tag: None,
+                public_pp_hash: false,
             };
         let nova_params = Nova::<
             Projective,
@@ -1237,7 +2953,7 @@ pub mod tests {
             .unwrap();
 
         for _ in 0..num_steps {
-            nova.prove_step(&mut rng, vec![], None).unwrap();
+            nova.prove_step(&mut rng, (), None).unwrap();
         }
         assert_eq!(Fr::from(num_steps as u32), nova.i);
 
@@ -1307,4 +3023,348 @@ pub mod tests {
 
         (z_0, nova)
     }
+
+    #[test]
+    fn test_versioned_params_serialization() {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+
+        type N = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            KZG<'static, Bn254>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        let prep_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let (pp, vp) = N::preprocess(&mut rng, &prep_param).unwrap();
+
+        let mut pp_serialized = vec![];
+        pp.serialize_compressed(&mut pp_serialized).unwrap();
+        assert_eq!(pp_serialized.len(), pp.compressed_size());
+
+        // round trip of the full prover params
+        let pp_deserialized =
+            N::pp_deserialize_with_mode(pp_serialized.as_slice(), Compress::Yes, Validate::Yes, ())
+                .unwrap();
+        assert_eq!(pp.cs_pp, pp_deserialized.cs_pp);
+        assert_eq!(pp.cf_cs_pp, pp_deserialized.cf_cs_pp);
+
+        // loading the params for another curve cycle fails
+        let r = ProverParams::<
+            ark_pallas::Projective,
+            ark_vesta::Projective,
+            Pedersen<ark_pallas::Projective>,
+            Pedersen<ark_vesta::Projective>,
+        >::deserialize_checked(
+            pp_serialized.as_slice(), Compress::Yes, Validate::Yes
+        );
+        assert!(matches!(r, Err(Error::ParamsMismatch(_))));
+
+        // truncated params fail
+        let r = N::pp_deserialize_with_mode(
+            &pp_serialized[..pp_serialized.len() - 1],
+            Compress::Yes,
+            Validate::Yes,
+            (),
+        );
+        assert!(matches!(r, Err(Error::ParamsMismatch(_))));
+
+        // the verifier params are also versioned
+        let mut vp_serialized = vec![];
+        vp.serialize_compressed(&mut vp_serialized).unwrap();
+        let vp_deserialized =
+            N::vp_deserialize_with_mode(vp_serialized.as_slice(), Compress::Yes, Validate::Yes, ())
+                .unwrap();
+        assert_eq!(vp.pp_hash().unwrap(), vp_deserialized.pp_hash().unwrap());
+
+        // headerless params are rejected, but can still be loaded through the unchecked methods
+        let mut pp_legacy = vec![];
+        pp.cs_pp.serialize_compressed(&mut pp_legacy).unwrap();
+        pp.cf_cs_pp.serialize_compressed(&mut pp_legacy).unwrap();
+        let r = N::pp_deserialize_with_mode(pp_legacy.as_slice(), Compress::Yes, Validate::Yes, ());
+        assert!(matches!(r, Err(Error::ParamsMismatch(_))));
+        let pp_deserialized = ProverParams::<
+            Projective,
+            Projective2,
+            KZG<'static, Bn254>,
+            Pedersen<Projective2>,
+        >::deserialize_unchecked(
+            pp_legacy.as_slice(), Compress::Yes, Validate::Yes
+        )
+        .unwrap();
+        assert_eq!(pp.cs_pp, pp_deserialized.cs_pp);
+
+        let mut vp_legacy = vec![];
+        vp.cs_vp.serialize_compressed(&mut vp_legacy).unwrap();
+        vp.cf_cs_vp.serialize_compressed(&mut vp_legacy).unwrap();
+        let vp_deserialized =
+            N::vp_deserialize_unchecked(vp_legacy.as_slice(), Compress::Yes, Validate::Yes, ())
+                .unwrap();
+        assert_eq!(vp.pp_hash().unwrap(), vp_deserialized.pp_hash().unwrap());
+    }
+
+    /// tests that the standalone serialization of the VerifierParams round-trips without the
+    /// FCircuit, and that the deserialized params verify the IVC proofs
+    #[test]
+    fn test_standalone_vp_serialization() {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+
+        type N = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        let prep_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let params = N::preprocess(&mut rng, &prep_param).unwrap();
+        let mut nova = N::init(&params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+        for _ in 0..2 {
+            nova.prove_step(&mut rng, (), None).unwrap();
+        }
+
+        let mut vp_serialized = vec![];
+        params.1.serialize_standalone(&mut vp_serialized).unwrap();
+        let vp = VerifierParams::<
+            Projective,
+            Projective2,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+        >::deserialize_standalone(vp_serialized.as_slice())
+        .unwrap();
+        assert_eq!(vp.r1cs, params.1.r1cs);
+        assert_eq!(vp.cf_r1cs, params.1.cf_r1cs);
+        assert_eq!(vp.pp_hash().unwrap(), params.1.pp_hash().unwrap());
+        vp.verify_ivc_proof(nova.ivc_proof(), None).unwrap();
+
+        let mut tampered_proof = nova.ivc_proof();
+        tampered_proof.z_i[0] += Fr::one();
+        assert!(vp.verify_ivc_proof(tampered_proof, None).is_err());
+
+        // truncated params fail
+        let r = VerifierParams::<
+            Projective,
+            Projective2,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+        >::deserialize_standalone(&vp_serialized[..vp_serialized.len() - 1]);
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn test_preprocess_deterministic() {
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+
+        type N = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        let prep_param =
+            PreprocessorParam::new(poseidon_config, F_circuit).with_domain_tag(b"sonobe-test");
+        // the params do not depend on the rng
+        let (_, vp1) = N::preprocess(ark_std::test_rng(), &prep_param).unwrap();
+        let mut rng = ark_std::test_rng();
+        rng.next_u64();
+        let (_, vp2) = N::preprocess(&mut rng, &prep_param).unwrap();
+        assert_eq!(vp1.pp_hash().unwrap(), vp2.pp_hash().unwrap());
+
+        let prep_param = prep_param.with_domain_tag(b"sonobe-other");
+        let (_, vp3) = N::preprocess(ark_std::test_rng(), &prep_param).unwrap();
+        assert_ne!(vp1.pp_hash().unwrap(), vp3.pp_hash().unwrap());
+
+        // a different CycleFold challenge bit length leads to a different pp_hash
+        let full_width_pp_hash =
+            pp_hash::<Projective, Projective2, Pedersen<Projective>, Pedersen<Projective2>, false>(
+                &vp1.r1cs,
+                &vp1.cf_r1cs,
+                &vp1.cs_vp,
+                &vp1.cf_cs_vp,
+                &vp1.poseidon_config,
+                None,
+                Fr::MODULUS_BIT_SIZE as usize - 1,
+                vp1.state_len,
+                &vp1.step_constants,
+            )
+            .unwrap();
+        assert_ne!(vp1.pp_hash().unwrap(), full_width_pp_hash);
+
+        // the state length is bound to the pp_hash
+        let mut vp4 = vp1.clone();
+        vp4.state_len += 1;
+        assert_ne!(vp1.pp_hash().unwrap(), vp4.pp_hash().unwrap());
+
+        // KZG needs a trusted setup, so it can not be derived from a tag
+        type NKZG = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            KZG<'static, Bn254>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        let prep_param = PreprocessorParam::new(prep_param.poseidon_config.clone(), F_circuit)
+            .with_domain_tag(b"sonobe-test");
+        assert!(NKZG::preprocess(ark_std::test_rng(), &prep_param).is_err());
+    }
+
+    #[test]
+    fn test_state_len_checks() {
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        // a circuit whose step does not return a state of the declared length is rejected at
+        // preprocessing
+        type NW = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            WrongStateLenFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        let F_circuit = WrongStateLenFCircuit::<Fr>::new(()).unwrap();
+        let prep_param = PreprocessorParam::new(poseidon_config.clone(), F_circuit);
+        assert!(matches!(
+            NW::preprocess(ark_std::test_rng(), &prep_param),
+            Err(Error::StateLenMismatch(_, 1, 2))
+        ));
+
+        type N = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let prep_param = PreprocessorParam::new(poseidon_config, F_circuit);
+        let params = N::preprocess(ark_std::test_rng(), &prep_param).unwrap();
+        assert_eq!(params.1.state_len, 1);
+
+        // z_0 of a wrong length is rejected at init
+        assert!(matches!(
+            N::init(&params, F_circuit, vec![Fr::from(3_u32); 2]),
+            Err(Error::StateLenMismatch(_, 2, 1))
+        ));
+
+        // and an IVC proof with a state of a wrong length is rejected at verification
+        let mut nova = N::init(&params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+        nova.prove_step(ark_std::test_rng(), (), None).unwrap();
+        let mut ivc_proof = nova.ivc_proof();
+        ivc_proof.z_i.push(Fr::from(0_u32));
+        assert!(matches!(
+            N::verify(params.1, ivc_proof),
+            Err(Error::StateLenMismatch(_, 2, 1))
+        ));
+    }
+
+    // the custom gates of the FCircuit are not part of the R1CS folded by Nova, so a circuit
+    // declaring them is rejected at preprocessing instead of leaving z_{i+1} unconstrained
+    #[test]
+    fn test_custom_gates_rejected() {
+        type N = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            QuinticFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        let F_circuit = QuinticFCircuit::<Fr>::new(()).unwrap();
+        let prep_param = PreprocessorParam::new(poseidon_canonical_config::<Fr>(), F_circuit);
+        assert!(matches!(
+            N::preprocess(ark_std::test_rng(), &prep_param),
+            Err(Error::NotSupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_step_constants() {
+        type N = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            StepConstantsFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let n_steps = 16;
+        let F_circuit = StepConstantsFCircuit::<Fr>::new(n_steps).unwrap();
+        let prep_param = PreprocessorParam::new(poseidon_config.clone(), F_circuit);
+        let params = N::preprocess(ark_std::test_rng(), &prep_param).unwrap();
+        assert_eq!(params.1.step_constants, F_circuit.step_constants());
+
+        // the schedule is bound to the pp_hash
+        let other_params = N::preprocess(
+            ark_std::test_rng(),
+            &PreprocessorParam::new(
+                poseidon_config,
+                StepConstantsFCircuit::<Fr>::new(n_steps + 1).unwrap(),
+            ),
+        )
+        .unwrap();
+        assert_ne!(
+            params.1.pp_hash().unwrap(),
+            other_params.1.pp_hash().unwrap()
+        );
+
+        // a circuit with a different schedule than the preprocessed one is rejected at init
+        assert!(N::init(
+            &params,
+            StepConstantsFCircuit::<Fr>::new(n_steps + 1).unwrap(),
+            vec![Fr::from(3_u32)]
+        )
+        .is_err());
+
+        let z_0 = vec![Fr::from(3_u32)];
+        let mut nova = N::init(&params, F_circuit, z_0.clone()).unwrap();
+        let mut z_i = z_0;
+        for i in 0..n_steps {
+            nova.prove_step(ark_std::test_rng(), (), None).unwrap();
+            z_i = F_circuit.step_native(i, z_i, ()).unwrap();
+        }
+        assert_eq!(nova.z_i, z_i);
+        N::verify(params.1.clone(), nova.ivc_proof()).unwrap();
+
+        // the schedule has no row for a further step
+        assert!(matches!(
+            nova.prove_step(ark_std::test_rng(), (), None),
+            Err(Error::StepConstantsExhausted(16, 16))
+        ));
+
+        // an IVC proof claiming a different step index does not verify, as `i` is bound by the
+        // hash in `u_i.x`. That the augmented circuit is not satisfied by a step computed with
+        // other constants is checked in `test_augmented_f_circuit_wrong_step_constants`
+        let mut ivc_proof = nova.ivc_proof();
+        ivc_proof.i -= Fr::from(1_u32);
+        assert!(N::verify(params.1, ivc_proof).is_err());
+    }
 }
@@ -10,8 +10,8 @@ use ark_crypto_primitives::sponge::{constraints::AbsorbGadget, Absorb, Cryptogra
 use ark_ec::CurveGroup;
 use ark_r1cs_std::{alloc::AllocVar, boolean::Boolean, fields::fp::FpVar};
 use ark_relations::r1cs::SynthesisError;
-use ark_std::fmt::Debug;
 use ark_std::rand::RngCore;
+use ark_std::{fmt::Debug, vec::Vec};
 
 use crate::arith::r1cs::R1CS;
 use crate::commitment::CommitmentScheme;
@@ -54,6 +54,15 @@ pub trait NIFSTrait<
         aux: Vec<C::ScalarField>, // t_or_e in Ova, empty for Nova
     ) -> Result<Self::CommittedInstance, Error>;
 
+    /// Folds the running witness `W` and the incoming witness `w` with the challenge `r`, which
+    /// has to be the one of `prove` and `verify`, ie. squeezed from the transcript after
+    /// absorbing `pp_hash`, the two committed instances and the proof (see each variant). `aux`
+    /// is computed by the prover from the two witnesses and instances: the cross-terms `T` in
+    /// Nova and Mova (see `nova::NIFS::compute_T`), and nothing in Ova.
+    ///
+    /// Together with the folding of the committed instances of each variant (eg.
+    /// `nova::NIFS::fold_committed_instances`), it allows to mirror the running instance and
+    /// witness of a prover without running `prove`. It does not use the transcript.
     fn fold_witness(
         r: C::ScalarField,
         W: &Self::Witness, // running witness
@@ -290,7 +299,7 @@ pub mod tests {
         N::CommittedInstance: CommittedInstanceOps<Projective>,
     {
         let poseidon_config = poseidon_canonical_config::<Fr>();
-        let sponge = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let sponge_native = PoseidonSponge::<Fr>::new(&poseidon_config);
         let pp_hash = Fr::from(42u32); // only for test
 
         let i = Fr::from(3_u32);
@@ -298,7 +307,7 @@ pub mod tests {
         let z_i = vec![Fr::from(3_u32)];
 
         // compute the CommittedInstance hash natively
-        let h = ci.hash(&sponge, pp_hash, i, &z_0, &z_i);
+        let h = ci.hash(&sponge_native, pp_hash, i, &z_0, &z_i);
 
         let cs = ConstraintSystem::<Fr>::new_ref();
 
@@ -318,5 +327,23 @@ pub mod tests {
 
         // check that the natively computed and in-circuit computed hashes match
         assert_eq!(hVar.value().unwrap(), h);
+
+        // same with an application context, which changes the hash
+        let context = Fr::from(1_u32);
+        let h_context = ci.hash_with_context(&sponge_native, pp_hash, Some(context), i, &z_0, &z_i);
+        assert_ne!(h_context, h);
+        let contextVar = FpVar::<Fr>::new_witness(cs.clone(), || Ok(context)).unwrap();
+        let (h_contextVar, _) = ciVar
+            .hash_with_context(
+                &sponge,
+                &pp_hashVar,
+                Some(&contextVar),
+                &iVar,
+                &z_0Var,
+                &z_iVar,
+            )
+            .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(h_contextVar.value().unwrap(), h_context);
     }
 }
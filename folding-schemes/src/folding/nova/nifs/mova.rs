@@ -5,10 +5,10 @@ use ark_ec::{CurveGroup, Group};
 use ark_ff::PrimeField;
 use ark_poly::MultilinearExtension;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use ark_std::log2;
+use ark_std::marker::PhantomData;
 use ark_std::rand::RngCore;
+use ark_std::{log2, vec::Vec};
 use ark_std::{One, UniformRand, Zero};
-use std::marker::PhantomData;
 
 use super::{
     nova::NIFS as NovaNIFS,
@@ -23,7 +23,7 @@ use crate::transcript::AbsorbNonNative;
 use crate::transcript::Transcript;
 use crate::utils::{
     mle::dense_vec_to_dense_mle,
-    vec::{is_zero_vec, vec_add, vec_scalar_mul},
+    vec::{check_equal_rows, is_zero_vec, vec_add, vec_scalar_mul},
 };
 use crate::Error;
 
@@ -267,7 +267,7 @@ where
 
         let alpha: C::ScalarField = transcript.get_challenge();
 
-        let ci = Self::fold_committed_instance(
+        let ci = Self::fold_committed_instances(
             alpha,
             U_i,
             u_i,
@@ -322,7 +322,7 @@ where
         let alpha: C::ScalarField = transcript.get_challenge();
 
         Ok((
-            Self::fold_committed_instance(
+            Self::fold_committed_instances(
                 alpha,
                 U_i,
                 u_i,
@@ -339,8 +339,14 @@ where
 impl<C: CurveGroup, CS: CommitmentScheme<C, H>, T: Transcript<C::ScalarField>, const H: bool>
     NIFS<C, CS, T, H>
 {
-    // Protocol 7 - point 3 (15)
-    fn fold_committed_instance(
+    /// Folds two committed instances with the given challenge `a` (`alpha`), following
+    /// Protocol 7 - point 3 (15). `rE_prime`, `mleE1_prime` and `mleE2_prime` are the evaluation
+    /// claim of the pt-vs-line protocol (the point returned by `PointVsLine::verify`, and the
+    /// evaluations in the `Proof`), and `mleT` is the evaluation of the cross-terms `T` at
+    /// `rE_prime`. `a` is squeezed after absorbing `pp_hash`, `U_i`, `u_i`, the pt-vs-line proof,
+    /// `mleE1_prime`, `mleE2_prime` and `mleT`, as done by `verify`. The result is the instance of
+    /// the witness folded by `NIFSTrait::fold_witness` with `a` and `T`.
+    pub fn fold_committed_instances(
         a: C::ScalarField,
         U_i: &CommittedInstance<C>,
         u_i: &CommittedInstance<C>,
@@ -386,7 +392,7 @@ impl<C: CurveGroup> Arith<Witness<C>, CommittedInstance<C>> for R1CS<CF1<C>> {
         _u: &CommittedInstance<C>,
         e: Self::Evaluation,
     ) -> Result<(), Error> {
-        (w.E == e).then_some(()).ok_or(Error::NotSatisfied)
+        check_equal_rows(&e, &w.E)
     }
 }
 
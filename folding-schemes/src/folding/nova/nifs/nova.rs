@@ -3,26 +3,39 @@
 use ark_crypto_primitives::sponge::{constraints::AbsorbGadget, Absorb, CryptographicSponge};
 use ark_ec::{CurveGroup, Group};
 use ark_ff::{BigInteger, PrimeField};
-use ark_r1cs_std::{boolean::Boolean, fields::fp::FpVar};
+use ark_r1cs_std::{boolean::Boolean, fields::fp::FpVar, ToBitsGadget};
 use ark_relations::r1cs::SynthesisError;
-use ark_std::rand::RngCore;
+use ark_std::marker::PhantomData;
 use ark_std::Zero;
-use std::marker::PhantomData;
+use ark_std::{rand::RngCore, string::ToString, vec::Vec};
 
 use super::NIFSTrait;
 use crate::arith::r1cs::R1CS;
-use crate::commitment::CommitmentScheme;
+use crate::commitment::{commit_maybe_sparse, CommitmentScheme};
 use crate::constants::NOVA_N_BITS_RO;
 use crate::folding::circuits::{
     cyclefold::{CycleFoldCommittedInstance, CycleFoldWitness},
     nonnative::affine::NonNativeAffineVar,
     CF1,
 };
-use crate::folding::nova::{CommittedInstance, Witness};
+use crate::folding::nova::{
+    profile::{timed, StepProfile},
+    CommittedInstance, Witness,
+};
 use crate::transcript::{Transcript, TranscriptVar};
 use crate::utils::vec::{hadamard, mat_vec_mul, vec_add, vec_scalar_mul, vec_sub};
 use crate::Error;
 
+/// Returns the bit length of the challenge of the Nova NIFS over `C`, which is `NOVA_N_BITS_RO`,
+/// or the bit size of `C`'s scalar field when `FULL_CHALLENGE` is set (see `Nova`).
+pub const fn challenge_bit_length<C: CurveGroup, const FULL_CHALLENGE: bool>() -> usize {
+    if FULL_CHALLENGE {
+        C::ScalarField::MODULUS_BIT_SIZE as usize
+    } else {
+        NOVA_N_BITS_RO
+    }
+}
+
 /// ChallengeGadget computes the RO challenge used for the Nova instances NIFS, it contains a
 /// rust-native and a in-circuit compatible versions.
 pub struct ChallengeGadget<C: CurveGroup, CI: Absorb> {
@@ -41,6 +54,19 @@ where
         U_i: &CI,
         u_i: &CI,
         cmT: Option<&C>,
+    ) -> Vec<bool> {
+        Self::get_challenge_native_opt::<false, T>(transcript, pp_hash, U_i, u_i, cmT)
+    }
+
+    /// Same as `get_challenge_native`, where the challenge is squeezed as a full scalar field
+    /// element when `FULL_CHALLENGE` is set. It is returned as its canonical bits, of length
+    /// `challenge_bit_length::<C, FULL_CHALLENGE>()`.
+    pub fn get_challenge_native_opt<const FULL_CHALLENGE: bool, T: Transcript<C::ScalarField>>(
+        transcript: &mut T,
+        pp_hash: C::ScalarField, // public params hash
+        U_i: &CI,
+        u_i: &CI,
+        cmT: Option<&C>,
     ) -> Vec<bool> {
         transcript.absorb(&pp_hash);
         transcript.absorb(&U_i);
@@ -49,7 +75,12 @@ where
         if let Some(cmT_value) = cmT {
             transcript.absorb_nonnative(cmT_value);
         }
-        transcript.squeeze_bits(NOVA_N_BITS_RO)
+        if FULL_CHALLENGE {
+            let r: C::ScalarField = transcript.squeeze_field_elements(1)[0];
+            r.into_bigint().to_bits_le()[..challenge_bit_length::<C, true>()].to_vec()
+        } else {
+            transcript.squeeze_bits(NOVA_N_BITS_RO)
+        }
     }
 
     // compatible with the native get_challenge_native
@@ -63,6 +94,24 @@ where
         U_i_vec: Vec<FpVar<CF1<C>>>, // apready processed input, so we don't have to recompute these values
         u_i: CIVar,
         cmT: Option<NonNativeAffineVar<C>>,
+    ) -> Result<Vec<Boolean<C::ScalarField>>, SynthesisError> {
+        Self::get_challenge_gadget_opt::<false, S, T, CIVar>(transcript, pp_hash, U_i_vec, u_i, cmT)
+    }
+
+    // compatible with the native get_challenge_native_opt. In the full-width case, the squeezed
+    // element is decomposed into its canonical bits, which are the only decomposition of the
+    // challenge needed by the AugmentedFCircuit
+    pub fn get_challenge_gadget_opt<
+        const FULL_CHALLENGE: bool,
+        S: CryptographicSponge,
+        T: TranscriptVar<CF1<C>, S>,
+        CIVar: AbsorbGadget<CF1<C>>,
+    >(
+        transcript: &mut T,
+        pp_hash: FpVar<CF1<C>>,      // public params hash
+        U_i_vec: Vec<FpVar<CF1<C>>>, // apready processed input, so we don't have to recompute these values
+        u_i: CIVar,
+        cmT: Option<NonNativeAffineVar<C>>,
     ) -> Result<Vec<Boolean<C::ScalarField>>, SynthesisError> {
         transcript.absorb(&pp_hash)?;
         transcript.absorb(&U_i_vec)?;
@@ -71,7 +120,11 @@ where
         if let Some(cmT_value) = cmT {
             transcript.absorb_nonnative(&cmT_value)?;
         }
-        transcript.squeeze_bits(NOVA_N_BITS_RO)
+        if FULL_CHALLENGE {
+            transcript.squeeze_field_elements(1)?[0].to_bits_le()
+        } else {
+            transcript.squeeze_bits(NOVA_N_BITS_RO)
+        }
     }
 }
 
@@ -156,29 +209,17 @@ where
         ),
         Error,
     > {
-        // compute the cross terms
-        let z1: Vec<C::ScalarField> = [vec![U_i.u], U_i.x.to_vec(), W_i.W.to_vec()].concat();
-        let z2: Vec<C::ScalarField> = [vec![u_i.u], u_i.x.to_vec(), w_i.W.to_vec()].concat();
-        let T = Self::compute_T(r1cs, U_i.u, u_i.u, &z1, &z2)?;
-
-        // use r_T=0 since we don't need hiding property for cm(T)
-        let cmT = CS::commit(cs_prover_params, &T, &C::ScalarField::zero())?;
-
-        let r_bits = ChallengeGadget::<C, Self::CommittedInstance>::get_challenge_native(
+        Self::prove_opt::<false, false>(
+            cs_prover_params,
+            r1cs,
             transcript,
             pp_hash,
+            W_i,
             U_i,
+            w_i,
             u_i,
-            Some(&cmT),
-        );
-        let r_Fr = C::ScalarField::from_bigint(BigInteger::from_bits_le(&r_bits))
-            .ok_or(Error::OutOfBounds)?;
-
-        let w = Self::fold_witness(r_Fr, W_i, w_i, &T)?;
-
-        let ci = Self::fold_committed_instances(r_Fr, U_i, u_i, &cmT);
-
-        Ok((w, ci, cmT, r_bits))
+            &mut StepProfile::default(),
+        )
     }
 
     fn verify(
@@ -207,6 +248,56 @@ impl<C: CurveGroup, CS: CommitmentScheme<C, H>, T: Transcript<C::ScalarField>, c
 where
     <C as Group>::ScalarField: Absorb,
 {
+    /// Same as `NIFSTrait::prove`, which also records the duration of the cross-term, `cmT` and
+    /// challenge phases into `profile` when `PROFILE` is set, and squeezes a full-width
+    /// challenge when `FULL_CHALLENGE` is set (see `ChallengeGadget::get_challenge_native_opt`).
+    #[allow(clippy::type_complexity)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_opt<const PROFILE: bool, const FULL_CHALLENGE: bool>(
+        cs_prover_params: &CS::ProverParams,
+        r1cs: &R1CS<C::ScalarField>,
+        transcript: &mut T,
+        pp_hash: C::ScalarField,
+        W_i: &Witness<C>,
+        U_i: &CommittedInstance<C>,
+        w_i: &Witness<C>,
+        u_i: &CommittedInstance<C>,
+        profile: &mut StepProfile,
+    ) -> Result<(Witness<C>, CommittedInstance<C>, C, Vec<bool>), Error> {
+        // compute the cross terms
+        let T = timed::<PROFILE, _>(&mut profile.cross_term, || {
+            let z1: Vec<C::ScalarField> = [vec![U_i.u], U_i.x.to_vec(), W_i.W.to_vec()].concat();
+            let z2: Vec<C::ScalarField> = [vec![u_i.u], u_i.x.to_vec(), w_i.W.to_vec()].concat();
+            Self::compute_T(r1cs, U_i.u, u_i.u, &z1, &z2)
+        })?;
+
+        // use r_T=0 since we don't need hiding property for cm(T)
+        let cmT = timed::<PROFILE, _>(&mut profile.cmT, || {
+            commit_maybe_sparse::<C, CS, H>(cs_prover_params, &T, &C::ScalarField::zero())
+        })?;
+
+        let r_bits =
+            timed::<PROFILE, _>(&mut profile.challenge, || {
+                ChallengeGadget::<C, CommittedInstance<C>>::get_challenge_native_opt::<
+                    FULL_CHALLENGE,
+                    T,
+                >(transcript, pp_hash, U_i, u_i, Some(&cmT))
+            });
+        if PROFILE {
+            profile.cross_term.size = T.len();
+            profile.cmT.size = T.len();
+            profile.challenge.size = r_bits.len();
+        }
+        let r_Fr = C::ScalarField::from_bigint(BigInteger::from_bits_le(&r_bits))
+            .ok_or(Error::OutOfBounds)?;
+
+        let w = Self::fold_witness(r_Fr, W_i, w_i, &T)?;
+
+        let ci = Self::fold_committed_instances(r_Fr, U_i, u_i, &cmT);
+
+        Ok((w, ci, cmT, r_bits))
+    }
+
     /// compute_T: compute cross-terms T
     pub fn compute_T(
         r1cs: &R1CS<C::ScalarField>,
@@ -250,12 +341,17 @@ where
         // compute cross terms
         let T = Self::compute_T(r1cs, ci1.u, ci2.u, &z1, &z2)?;
         // use r_T=0 since we don't need hiding property for cm(T)
-        let cmT = CS::commit(cs_prover_params, &T, &C::ScalarField::zero())?;
+        let cmT = commit_maybe_sparse::<C, CS, H>(cs_prover_params, &T, &C::ScalarField::zero())?;
         Ok((T, cmT))
     }
 
     /// folds two committed instances with the given r and cmT. This method is used by
     /// Nova::verify, but also by Nova::prove and the CycleFoldNIFS::verify.
+    ///
+    /// `r` is the challenge squeezed by `ChallengeGadget::get_challenge_native` after absorbing
+    /// `pp_hash`, `U_i`, `u_i` and `cmT`, which the caller has to do in that order, and `cmT` is
+    /// the commitment to the cross-terms `T` (see `compute_T`) with zero blinding. The result is
+    /// then the instance of the witness folded by `NIFSTrait::fold_witness` with `r` and `T`.
     pub fn fold_committed_instances(
         r: C::ScalarField,
         U_i: &CommittedInstance<C>,
@@ -276,6 +372,65 @@ where
         CommittedInstance { cmE, u, cmW, x }
     }
 
+    /// NIFS.V of a batch of folds, which folds each of the incoming instances `u_is` in turn into
+    /// the running instance `U_i`, with the respective `cmTs`. Unlike calling `NIFSTrait::verify`
+    /// for each of them, `pp_hash` is absorbed only once into the transcript, at the start of the
+    /// batch. Returns the final folded instance and the challenges of every fold, as vectors of
+    /// bits. It matches `NIFSGadget::verify_batch`.
+    #[allow(clippy::type_complexity)]
+    pub fn verify_batch(
+        transcript: &mut T,
+        pp_hash: C::ScalarField,
+        U_i: &CommittedInstance<C>,
+        u_is: &[CommittedInstance<C>],
+        cmTs: &[C],
+    ) -> Result<(CommittedInstance<C>, Vec<Vec<bool>>), Error> {
+        Self::verify_batch_opt::<false>(transcript, pp_hash, U_i, u_is, cmTs)
+    }
+
+    /// Same as `verify_batch`, where the challenges are full-width scalar field elements when
+    /// `FULL_CHALLENGE` is set, see `ChallengeGadget::get_challenge_native_opt`. It matches
+    /// `NIFSGadget::verify_batch_opt`.
+    #[allow(clippy::type_complexity)]
+    pub fn verify_batch_opt<const FULL_CHALLENGE: bool>(
+        transcript: &mut T,
+        pp_hash: C::ScalarField,
+        U_i: &CommittedInstance<C>,
+        u_is: &[CommittedInstance<C>],
+        cmTs: &[C],
+    ) -> Result<(CommittedInstance<C>, Vec<Vec<bool>>), Error> {
+        if u_is.len() != cmTs.len() {
+            return Err(Error::NotSameLength(
+                "u_is.len()".to_string(),
+                u_is.len(),
+                "cmTs.len()".to_string(),
+                cmTs.len(),
+            ));
+        }
+        transcript.absorb(&pp_hash);
+        let mut U = U_i.clone();
+        let mut r_bits_vec = Vec::with_capacity(u_is.len());
+        for (u_i, cmT) in u_is.iter().zip(cmTs) {
+            transcript.absorb(&U);
+            transcript.absorb(u_i);
+            transcript.absorb_nonnative(cmT);
+            let (r, r_bits) = if FULL_CHALLENGE {
+                let r: C::ScalarField = transcript.squeeze_field_elements(1)[0];
+                let r_bits =
+                    r.into_bigint().to_bits_le()[..challenge_bit_length::<C, true>()].to_vec();
+                (r, r_bits)
+            } else {
+                let r_bits = transcript.squeeze_bits(NOVA_N_BITS_RO);
+                let r = C::ScalarField::from_bigint(BigInteger::from_bits_le(&r_bits))
+                    .ok_or(Error::OutOfBounds)?;
+                (r, r_bits)
+            };
+            U = Self::fold_committed_instances(r, &U, u_i, cmT);
+            r_bits_vec.push(r_bits);
+        }
+        Ok((U, r_bits_vec))
+    }
+
     pub fn prove_commitments(
         tr: &mut impl Transcript<C::ScalarField>,
         cs_prover_params: &CS::ProverParams,
@@ -297,9 +452,14 @@ pub mod tests {
     use ark_crypto_primitives::sponge::poseidon::PoseidonSponge;
     use ark_pallas::{Fr, Projective};
 
-    use crate::arith::{r1cs::tests::get_test_r1cs, Arith};
+    use crate::arith::{
+        r1cs::tests::{get_test_r1cs, get_test_z},
+        Arith,
+    };
     use crate::commitment::pedersen::Pedersen;
     use crate::folding::nova::nifs::tests::test_nifs_opt;
+    use crate::transcript::poseidon::poseidon_canonical_config;
+    use ark_std::{One, UniformRand};
 
     #[test]
     fn test_nifs_nova() {
@@ -309,4 +469,57 @@ pub mod tests {
         let r1cs = get_test_r1cs();
         r1cs.check_relation(&W, &U).unwrap();
     }
+
+    /// tests that the sampled relaxed R1CS pairs satisfy the relation and open their
+    /// commitments, and that folding them into a real running instance keeps the relation
+    #[test]
+    fn test_nifs_nova_sampled_instances() {
+        type CS = Pedersen<Projective, true>;
+        type N = NIFS<Projective, CS, PoseidonSponge<Fr>, true>;
+
+        let mut rng = ark_std::test_rng();
+        let r1cs = get_test_r1cs::<Fr>();
+        let (pedersen_params, _) = CS::setup(&mut rng, r1cs.A.n_cols.max(r1cs.A.n_rows)).unwrap();
+
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let mut transcript_p = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let mut transcript_v = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let pp_hash = Fr::rand(&mut rng);
+
+        // real running instance
+        let (w, x) = r1cs.split_z(&get_test_z(3));
+        let mut W_i = N::new_witness(w, r1cs.A.n_rows, &mut rng);
+        let mut U_i = N::new_instance(&mut rng, &pedersen_params, &W_i, x, vec![]).unwrap();
+
+        for _ in 0..5 {
+            let (u_i, w_i) = r1cs
+                .sample_relaxed::<Projective, CS>(&mut rng, &pedersen_params)
+                .unwrap();
+            // the sampled instance is a relaxed one, with a non-trivial error term
+            assert_ne!(u_i.u, Fr::one());
+            assert!(w_i.E.iter().any(|e| !e.is_zero()));
+            r1cs.check_relation(&w_i, &u_i).unwrap();
+            w_i.check_commitments::<CS, true>(&pedersen_params, &u_i)
+                .unwrap();
+
+            let (W_i1, _, cmT, _) = N::prove(
+                &pedersen_params,
+                &r1cs,
+                &mut transcript_p,
+                pp_hash,
+                &W_i,
+                &U_i,
+                &w_i,
+                &u_i,
+            )
+            .unwrap();
+            let (U_i1, _) = N::verify(&mut transcript_v, pp_hash, &U_i, &u_i, &cmT).unwrap();
+            r1cs.check_relation(&W_i1, &U_i1).unwrap();
+            W_i1.check_commitments::<CS, true>(&pedersen_params, &U_i1)
+                .unwrap();
+
+            W_i = W_i1;
+            U_i = U_i1;
+        }
+    }
 }
@@ -7,15 +7,17 @@ use ark_r1cs_std::{
     eq::EqGadget,
     fields::{fp::FpVar, FieldVar},
     uint8::UInt8,
-    ToConstraintFieldGadget,
+    ToBitsGadget, ToConstraintFieldGadget,
 };
 use ark_relations::r1cs::{ConstraintSystemRef, Namespace, SynthesisError};
-use ark_std::{fmt::Debug, Zero};
+use ark_std::{fmt::Debug, vec::Vec, Zero};
 use core::{borrow::Borrow, marker::PhantomData};
 
 use super::NIFSGadgetTrait;
+use crate::constants::NOVA_N_BITS_RO;
 use crate::folding::circuits::{
     nonnative::{affine::NonNativeAffineVar, uint::NonNativeUintVar},
+    utils::le_bits_to_fp_var_checked,
     CF1, CF2,
 };
 use crate::folding::nova::CommittedInstance;
@@ -87,6 +89,10 @@ impl<C: CurveGroup> CommittedInstanceVarOps<C> for CommittedInstanceVar<C> {
         vec![self.cmW.clone(), self.cmE.clone()]
     }
 
+    fn get_commitment_labels(&self) -> Vec<&'static [u8]> {
+        vec![b"W", b"E"]
+    }
+
     fn get_public_inputs(&self) -> &[FpVar<CF1<C>>] {
         &self.x
     }
@@ -134,17 +140,52 @@ where
         u_i: Self::CommittedInstanceVar,
         cmT: Option<Self::ProofVar>,
     ) -> Result<(Self::CommittedInstanceVar, Vec<Boolean<CF1<C>>>), SynthesisError> {
-        let r_bits = ChallengeGadget::<C, CommittedInstance<C>>::get_challenge_gadget(
+        Self::verify_opt::<false>(transcript, pp_hash, U_i, U_i_vec, u_i, cmT)
+    }
+}
+
+impl<C, S, T> NIFSGadget<C, S, T>
+where
+    C: CurveGroup,
+    S: CryptographicSponge,
+    T: TranscriptVar<CF1<C>, S>,
+    <C as Group>::ScalarField: Absorb,
+{
+    /// Same as `NIFSGadgetTrait::verify`, where the challenge is a full-width scalar field element
+    /// when `FULL_CHALLENGE` is set, see `ChallengeGadget::get_challenge_gadget_opt`.
+    #[allow(clippy::type_complexity)]
+    pub fn verify_opt<const FULL_CHALLENGE: bool>(
+        transcript: &mut T,
+        pp_hash: FpVar<CF1<C>>,
+        U_i: CommittedInstanceVar<C>,
+        U_i_vec: Vec<FpVar<CF1<C>>>,
+        u_i: CommittedInstanceVar<C>,
+        cmT: Option<NonNativeAffineVar<C>>,
+    ) -> Result<(CommittedInstanceVar<C>, Vec<Boolean<CF1<C>>>), SynthesisError> {
+        let r_bits = ChallengeGadget::<C, CommittedInstance<C>>::get_challenge_gadget_opt::<
+            FULL_CHALLENGE,
+            S,
+            T,
+            CommittedInstanceVar<C>,
+        >(
             transcript,
             pp_hash.clone(),
             U_i_vec,
             u_i.clone(),
             cmT.clone(),
         )?;
-        let r = Boolean::le_bits_to_fp_var(&r_bits)?;
+        // the bits of a full-width challenge are already checked to be canonical, so they are
+        // recomposed without enforcing again that they are smaller than the modulus
+        let r = if FULL_CHALLENGE {
+            r_bits.iter().rev().try_fold(FpVar::zero(), |acc, bit| {
+                Ok::<_, SynthesisError>(acc.double()? + FpVar::from(bit.clone()))
+            })?
+        } else {
+            le_bits_to_fp_var_checked(&r_bits)?
+        };
 
         Ok((
-            Self::CommittedInstanceVar {
+            CommittedInstanceVar {
                 cmE: NonNativeAffineVar::new_constant(ConstraintSystemRef::None, C::zero())?,
                 cmW: NonNativeAffineVar::new_constant(ConstraintSystemRef::None, C::zero())?,
                 // ci3.u = U_i.u + r * u_i.u
@@ -160,15 +201,93 @@ where
             r_bits,
         ))
     }
+
+    /// Implements the constraints of `NIFS::verify_batch` for u and x, folding each of the
+    /// incoming instances `u_is` in turn into the running instance `U_i`, with the respective
+    /// `cmTs`. The sponge state is shared between the folds, `pp_hash` being absorbed only once,
+    /// and the vector absorbed for each intermediate running instance is built from its folded u
+    /// and x, without hashing it again.
+    ///
+    /// As in `NIFSGadgetTrait::verify`, cmE and cmW are not folded in-circuit: `U_cms` are the
+    /// (cmE, cmW) of each of the folded instances, which are absorbed for the next challenges and
+    /// set in the returned instance, and whose correctness has to be checked by the caller (eg.
+    /// on the other curve, through CycleFold). Returns the final folded instance and the bits of
+    /// every challenge.
+    #[allow(clippy::type_complexity)]
+    pub fn verify_batch(
+        transcript: &mut T,
+        pp_hash: FpVar<CF1<C>>,
+        U_i: CommittedInstanceVar<C>,
+        // U_i_vec is passed to reuse the already computed U_i_vec from previous methods
+        U_i_vec: Vec<FpVar<CF1<C>>>,
+        u_is: Vec<CommittedInstanceVar<C>>,
+        cmTs: Vec<NonNativeAffineVar<C>>,
+        U_cms: Vec<(NonNativeAffineVar<C>, NonNativeAffineVar<C>)>,
+    ) -> Result<(CommittedInstanceVar<C>, Vec<Vec<Boolean<CF1<C>>>>), SynthesisError> {
+        Self::verify_batch_opt::<false>(transcript, pp_hash, U_i, U_i_vec, u_is, cmTs, U_cms)
+    }
+
+    /// Same as `verify_batch`, where the challenges are full-width scalar field elements when
+    /// `FULL_CHALLENGE` is set, as in `verify_opt`.
+    #[allow(clippy::type_complexity)]
+    pub fn verify_batch_opt<const FULL_CHALLENGE: bool>(
+        transcript: &mut T,
+        pp_hash: FpVar<CF1<C>>,
+        U_i: CommittedInstanceVar<C>,
+        U_i_vec: Vec<FpVar<CF1<C>>>,
+        u_is: Vec<CommittedInstanceVar<C>>,
+        cmTs: Vec<NonNativeAffineVar<C>>,
+        U_cms: Vec<(NonNativeAffineVar<C>, NonNativeAffineVar<C>)>,
+    ) -> Result<(CommittedInstanceVar<C>, Vec<Vec<Boolean<CF1<C>>>>), SynthesisError> {
+        if u_is.len() != cmTs.len() || u_is.len() != U_cms.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        transcript.absorb(&pp_hash)?;
+        let mut U = U_i;
+        let mut U_vec = U_i_vec;
+        let mut r_bits_vec = Vec::with_capacity(u_is.len());
+        for ((u_i, cmT), (cmE, cmW)) in u_is.into_iter().zip(cmTs).zip(U_cms) {
+            transcript.absorb(&U_vec)?;
+            transcript.absorb(&u_i)?;
+            transcript.absorb_nonnative(&cmT)?;
+            // a full-width challenge is the squeezed element itself, so its canonical bits are
+            // only computed for the caller, and not recomposed into r
+            let (r, r_bits) = if FULL_CHALLENGE {
+                let r = transcript.squeeze_field_elements(1)?[0].clone();
+                let r_bits = r.to_bits_le()?;
+                (r, r_bits)
+            } else {
+                let r_bits = transcript.squeeze_bits(NOVA_N_BITS_RO)?;
+                (le_bits_to_fp_var_checked(&r_bits)?, r_bits)
+            };
+
+            U = CommittedInstanceVar {
+                cmE,
+                cmW,
+                u: U.u + &r * u_i.u,
+                x: U.x
+                    .iter()
+                    .zip(u_i.x)
+                    .map(|(a, b)| a + &r * &b)
+                    .collect::<Vec<FpVar<CF1<C>>>>(),
+            };
+            U_vec = U.to_sponge_field_elements()?;
+            r_bits_vec.push(r_bits);
+        }
+        Ok((U, r_bits_vec))
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use ark_crypto_primitives::sponge::constraints::CryptographicSpongeVar;
     use ark_crypto_primitives::sponge::poseidon::constraints::PoseidonSpongeVar;
     use ark_crypto_primitives::sponge::poseidon::PoseidonSponge;
+    use ark_ff::{BigInteger, PrimeField};
     use ark_pallas::{Fr, Projective};
     use ark_r1cs_std::R1CSVar;
+    use ark_relations::r1cs::ConstraintSystem;
     use ark_std::UniformRand;
 
     use crate::commitment::pedersen::Pedersen;
@@ -179,6 +298,7 @@ pub mod tests {
             test_nifs_gadget_opt,
         },
     };
+    use crate::transcript::poseidon::poseidon_canonical_config;
 
     #[test]
     fn test_nifs_gadget() {
@@ -204,6 +324,92 @@ pub mod tests {
         assert_eq!(ciVar_out.x.value().unwrap(), ci_out.x);
     }
 
+    /// tests that the batched in-circuit NIFS verification of 4 folds matches the native one
+    #[test]
+    fn test_nifs_gadget_batch() {
+        test_nifs_gadget_batch_opt::<false>();
+        test_nifs_gadget_batch_opt::<true>();
+    }
+
+    fn test_nifs_gadget_batch_opt<const FULL_CHALLENGE: bool>() {
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let n_folds = 4;
+        let rand_instance = |rng: &mut _| CommittedInstance::<Projective> {
+            cmE: Projective::rand(rng),
+            u: Fr::rand(rng),
+            cmW: Projective::rand(rng),
+            x: vec![Fr::rand(rng); 2],
+        };
+        let U_i = rand_instance(&mut rng);
+        let u_is: Vec<_> = (0..n_folds).map(|_| rand_instance(&mut rng)).collect();
+        let cmTs: Vec<_> = (0..n_folds).map(|_| Projective::rand(&mut rng)).collect();
+        let pp_hash = Fr::from(42u32); // only for testing
+
+        type N = NIFS<Projective, Pedersen<Projective>, PoseidonSponge<Fr>>;
+        let mut transcript = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let (U_i1, r_bits) =
+            N::verify_batch_opt::<FULL_CHALLENGE>(&mut transcript, pp_hash, &U_i, &u_is, &cmTs)
+                .unwrap();
+        assert_eq!(r_bits.len(), n_folds);
+
+        // the commitments of the intermediate instances are computed natively
+        let mut U = U_i.clone();
+        let mut U_cms = vec![];
+        for ((u_i, cmT), r_bits) in u_is.iter().zip(&cmTs).zip(&r_bits) {
+            let r = Fr::from_bigint(BigInteger::from_bits_le(r_bits)).unwrap();
+            U = N::fold_committed_instances(r, &U, u_i, cmT);
+            U_cms.push((U.cmE, U.cmW));
+        }
+        assert_eq!(U, U_i1);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let pp_hashVar = FpVar::new_witness(cs.clone(), || Ok(pp_hash)).unwrap();
+        let U_iVar = CommittedInstanceVar::new_witness(cs.clone(), || Ok(U_i)).unwrap();
+        let u_isVar =
+            Vec::<CommittedInstanceVar<Projective>>::new_witness(cs.clone(), || Ok(u_is)).unwrap();
+        let cmTsVar =
+            Vec::<NonNativeAffineVar<Projective>>::new_witness(cs.clone(), || Ok(cmTs)).unwrap();
+        let U_cmsVar = U_cms
+            .into_iter()
+            .map(|(cmE, cmW)| {
+                Ok((
+                    NonNativeAffineVar::new_witness(cs.clone(), || Ok(cmE))?,
+                    NonNativeAffineVar::new_witness(cs.clone(), || Ok(cmW))?,
+                ))
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()
+            .unwrap();
+        let mut transcriptVar = PoseidonSpongeVar::<Fr>::new(cs.clone(), &poseidon_config);
+        let (U_i1Var, r_bitsVar) = NIFSGadget::<
+            Projective,
+            PoseidonSponge<Fr>,
+            PoseidonSpongeVar<Fr>,
+        >::verify_batch_opt::<FULL_CHALLENGE>(
+            &mut transcriptVar,
+            pp_hashVar,
+            U_iVar.clone(),
+            U_iVar.to_sponge_field_elements().unwrap(),
+            u_isVar,
+            cmTsVar,
+            U_cmsVar,
+        )
+        .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        assert_eq!(U_i1Var.u.value().unwrap(), U_i1.u);
+        assert_eq!(U_i1Var.x.value().unwrap(), U_i1.x);
+        assert_eq!(U_i1Var.cmE.value().unwrap(), U_i1.cmE);
+        assert_eq!(U_i1Var.cmW.value().unwrap(), U_i1.cmW);
+        for (r_bitsVar, r_bits) in r_bitsVar.iter().zip(&r_bits) {
+            assert_eq!(&r_bitsVar.value().unwrap(), r_bits);
+        }
+
+        // the lengths of the batch must match
+        let mut transcript = PoseidonSponge::<Fr>::new(&poseidon_config);
+        assert!(N::verify_batch(&mut transcript, pp_hash, &U_i1, &[U_i1.clone()], &[]).is_err());
+    }
+
     #[test]
     fn test_committed_instance_to_sponge_preimage() {
         let mut rng = ark_std::test_rng();
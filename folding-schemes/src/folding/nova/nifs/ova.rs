@@ -4,10 +4,10 @@ use ark_crypto_primitives::sponge::Absorb;
 use ark_ec::{CurveGroup, Group};
 use ark_ff::{BigInteger, PrimeField};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use ark_std::fmt::Debug;
+use ark_std::marker::PhantomData;
 use ark_std::rand::RngCore;
+use ark_std::{fmt::Debug, vec::Vec};
 use ark_std::{One, UniformRand, Zero};
-use std::marker::PhantomData;
 
 use super::nova::ChallengeGadget;
 use super::ova_circuits::CommittedInstanceVar;
@@ -59,6 +59,10 @@ impl<C: CurveGroup> CommittedInstanceOps<C> for CommittedInstance<C> {
         vec![self.cmWE]
     }
 
+    fn get_commitment_labels(&self) -> Vec<&'static [u8]> {
+        vec![b"WE"]
+    }
+
     fn is_incoming(&self) -> bool {
         self.u == One::one()
     }
@@ -225,6 +229,22 @@ where
         let r = C::ScalarField::from_bigint(BigInteger::from_bits_le(&r_bits))
             .ok_or(Error::OutOfBounds)?;
 
+        Ok((Self::fold_committed_instances(r, U_i, u_i), r_bits))
+    }
+}
+
+impl<C: CurveGroup, CS: CommitmentScheme<C, H>, T: Transcript<C::ScalarField>, const H: bool>
+    NIFS<C, CS, T, H>
+{
+    /// folds two committed instances with the given r (`alpha` in Ova's hackmd), which is the
+    /// challenge squeezed by `ChallengeGadget::get_challenge_native` after absorbing `pp_hash`,
+    /// `U_i` and `u_i` (Ova has no cmT). The result is the instance of the witness folded by
+    /// `NIFSTrait::fold_witness` with the same r.
+    pub fn fold_committed_instances(
+        r: C::ScalarField,
+        U_i: &CommittedInstance<C>,
+        u_i: &CommittedInstance<C>,
+    ) -> CommittedInstance<C> {
         // recall that r=alpha, and u=mu between Nova and Ova respectively
         let u = U_i.u + r; // u_i.u is always 1 in Ova as we just can do IVC (not PCD).
         let cmWE = U_i.cmWE + u_i.cmWE.mul(r);
@@ -235,7 +255,7 @@ where
             .map(|(a, b)| *a + (r * b))
             .collect::<Vec<C::ScalarField>>();
 
-        Ok((Self::CommittedInstance { cmWE, u, x }, r_bits))
+        CommittedInstance { cmWE, u, x }
     }
 }
 
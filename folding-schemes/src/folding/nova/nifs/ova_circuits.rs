@@ -11,12 +11,14 @@ use ark_r1cs_std::{
     ToConstraintFieldGadget,
 };
 use ark_relations::r1cs::{ConstraintSystemRef, Namespace, SynthesisError};
-use ark_std::fmt::Debug;
+use ark_std::{fmt::Debug, vec::Vec};
 use core::{borrow::Borrow, marker::PhantomData};
 
 use super::ova::CommittedInstance;
 use super::NIFSGadgetTrait;
-use crate::folding::circuits::{nonnative::affine::NonNativeAffineVar, CF1};
+use crate::folding::circuits::{
+    nonnative::affine::NonNativeAffineVar, utils::le_bits_to_fp_var_checked, CF1,
+};
 use crate::folding::traits::CommittedInstanceVarOps;
 use crate::transcript::TranscriptVar;
 
@@ -79,6 +81,10 @@ impl<C: CurveGroup> CommittedInstanceVarOps<C> for CommittedInstanceVar<C> {
         vec![self.cmWE.clone()]
     }
 
+    fn get_commitment_labels(&self) -> Vec<&'static [u8]> {
+        vec![b"WE"]
+    }
+
     fn get_public_inputs(&self) -> &[FpVar<CF1<C>>] {
         &self.x
     }
@@ -132,7 +138,7 @@ where
             u_i.clone(),
             None,
         )?;
-        let r = Boolean::le_bits_to_fp_var(&r_bits)?;
+        let r = le_bits_to_fp_var_checked(&r_bits)?;
 
         Ok((
             Self::CommittedInstanceVar {
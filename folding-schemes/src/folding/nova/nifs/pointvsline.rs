@@ -4,7 +4,7 @@ use ark_ff::{One, PrimeField};
 use ark_poly::univariate::DensePolynomial;
 use ark_poly::{DenseMultilinearExtension, DenseUVPolynomial, Polynomial};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use ark_std::{log2, Zero};
+use ark_std::{log2, vec::Vec, Zero};
 
 use super::mova::{CommittedInstance, Witness};
 use crate::transcript::Transcript;
@@ -29,8 +29,8 @@ pub struct PointVsLineProof<C: CurveGroup> {
 
 #[derive(Clone, Debug, Default)]
 pub struct PointVsLine<C: CurveGroup, T: Transcript<C::ScalarField>> {
-    _phantom_C: std::marker::PhantomData<C>,
-    _phantom_T: std::marker::PhantomData<T>,
+    _phantom_C: ark_std::marker::PhantomData<C>,
+    _phantom_T: ark_std::marker::PhantomData<T>,
 }
 
 /// Protocol 6 from Mova
@@ -0,0 +1,224 @@
+//! Pipelined proving of consecutive Nova steps, see `Nova::prove_steps_pipelined`.
+//!
+//! The step `i` of the IVC runs `F.step_native` to compute `z_{i+1}`, and then folds the
+//! instances and synthesizes the AugmentedFCircuit. Since `z_{i+1}` only depends on `z_i` and on
+//! the external inputs, the native execution of the next steps does not need to wait for the
+//! folding of the current one. The pipelined driver runs it on a worker thread, a bounded number
+//! of steps ahead of the main thread, which folds the steps as their `z_{i+1}` arrive. Notice that
+//! the synthesis of the AugmentedFCircuit depends on the folded instances, so it stays on the
+//! main thread; the overlap is the duration of `F.step_native`, which is the bulk of the witness
+//! generation for the frontends that compute the witness natively (eg. circom).
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ec::{CurveGroup, Group};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{prelude::CurveVar, ToConstraintFieldGadget};
+use ark_std::rand::RngCore;
+use std::sync::mpsc::sync_channel;
+use std::thread;
+
+use super::{profile::StepProfile, Nova};
+use crate::commitment::CommitmentScheme;
+use crate::folding::circuits::CF2;
+use crate::frontend::FCircuit;
+use crate::Error;
+
+/// Number of steps that the native execution of the FCircuit can run ahead of the folding.
+pub const PIPELINE_DEPTH: usize = 2;
+
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, const H: bool, const FULL_CHALLENGE: bool>
+    Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H, FULL_CHALLENGE>
+where
+    C1: CurveGroup,
+    GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+    C2: CurveGroup,
+    GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+    FC: FCircuit<C1::ScalarField>,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+    <C1 as CurveGroup>::BaseField: PrimeField,
+    <C2 as CurveGroup>::BaseField: PrimeField,
+    <C1 as Group>::ScalarField: Absorb,
+    <C2 as Group>::ScalarField: Absorb,
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    FC::ExternalInputs: Send,
+{
+    /// Proves one step for each of the given external inputs, as calling `prove_step` with each
+    /// of them in order would do (with the same randomness), while running `F.step_native` of the
+    /// next steps on a worker thread, at most `PIPELINE_DEPTH` steps ahead of the folding. See
+    /// the `pipeline` module.
+    ///
+    /// The worker runs a clone of `self.F`, so the FCircuit must be `Send`. The FCircuits that are
+    /// not (eg. the `CircomFCircuit`, whose witness calculator is not thread-safe) can use
+    /// `prove_steps_pipelined_with`.
+    ///
+    /// If a step fails, on either thread, the error is returned and the remaining steps are
+    /// cancelled, leaving the IVC at the last step that was proved.
+    pub fn prove_steps_pipelined(
+        &mut self,
+        rng: impl RngCore,
+        external_inputs: Vec<FC::ExternalInputs>,
+    ) -> Result<(), Error>
+    where
+        FC: Send,
+    {
+        let F = self.F.clone();
+        self.prove_steps_pipelined_with(rng, external_inputs, move || Ok(F))
+    }
+
+    /// Same as `prove_steps_pipelined`, where the FCircuit run by the worker thread is built by
+    /// `new_f_circuit` on that thread, so that it does not need to be `Send`. It must compute the
+    /// same `step_native` as `self.F`, eg. by creating it from the same params.
+    pub fn prove_steps_pipelined_with(
+        &mut self,
+        mut rng: impl RngCore,
+        external_inputs: Vec<FC::ExternalInputs>,
+        new_f_circuit: impl FnOnce() -> Result<FC, Error> + Send,
+    ) -> Result<(), Error> {
+        let first_step = self.i_usize();
+        let z_i = self.z_i.clone();
+        let native_inputs = external_inputs.clone();
+
+        thread::scope(|s| {
+            let (tx, rx) = sync_channel::<Result<Vec<C1::ScalarField>, Error>>(PIPELINE_DEPTH);
+            let worker = s.spawn(move || {
+                let F = match new_f_circuit() {
+                    Ok(F) => F,
+                    Err(e) => {
+                        let _ = tx.send(Err(e));
+                        return;
+                    }
+                };
+                let mut z_i = z_i;
+                for (k, external_inputs) in native_inputs.into_iter().enumerate() {
+                    let z_i1 = F.step_native(first_step + k, z_i, external_inputs);
+                    let next_z_i = z_i1.as_ref().ok().cloned();
+                    // stop once the main thread hangs up, or after sending an error
+                    if tx.send(z_i1).is_err() {
+                        return;
+                    }
+                    match next_z_i {
+                        Some(next_z_i) => z_i = next_z_i,
+                        None => return,
+                    }
+                }
+            });
+
+            let mut result = Ok(());
+            for external_inputs in external_inputs {
+                result = rx
+                    .recv()
+                    .map_err(|_| {
+                        Error::Other("the native execution of the FCircuit stopped".to_string())
+                    })
+                    .and_then(|z_i1| {
+                        self.prove_step_opt::<false>(
+                            &mut rng,
+                            external_inputs,
+                            None,
+                            Some(z_i1?),
+                            &mut StepProfile::default(),
+                        )
+                    });
+                if result.is_err() {
+                    break;
+                }
+            }
+            // hang up before joining, so that a worker blocked on a full channel returns
+            drop(rx);
+            if worker.join().is_err() {
+                return Err(Error::Other(
+                    "the native execution of the FCircuit panicked".to_string(),
+                ));
+            }
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::{constraints::GVar, Fr, G1Projective as Projective};
+    use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+
+    use super::*;
+    use crate::commitment::pedersen::Pedersen;
+    use crate::folding::nova::PreprocessorParam;
+    use crate::frontend::utils::{CubicFCircuit, StepConstantsFCircuit};
+    use crate::transcript::poseidon::poseidon_canonical_config;
+    use crate::FoldingScheme;
+
+    type N<FC> =
+        Nova<Projective, GVar, Projective2, GVar2, FC, Pedersen<Projective>, Pedersen<Projective2>>;
+
+    /// tests that the pipelined steps result in the same IVC as the sequential ones, also when
+    /// there are more steps than the depth of the pipeline
+    #[test]
+    fn test_prove_steps_pipelined() {
+        let mut rng = ark_std::test_rng();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let params = N::preprocess(
+            &mut rng,
+            &PreprocessorParam::new(poseidon_canonical_config::<Fr>(), F_circuit),
+        )
+        .unwrap();
+        let n_steps = PIPELINE_DEPTH + 3;
+
+        let mut nova = N::init(&params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+        let mut rng_sequential = ark_std::test_rng();
+        for _ in 0..n_steps {
+            nova.prove_step(&mut rng_sequential, (), None).unwrap();
+        }
+
+        let mut pipelined = N::init(&params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+        // the first step is proved alone, so that the pipeline starts from i > 0
+        let mut rng_pipelined = ark_std::test_rng();
+        pipelined
+            .prove_steps_pipelined(&mut rng_pipelined, vec![()])
+            .unwrap();
+        pipelined
+            .prove_steps_pipelined_with(&mut rng_pipelined, vec![(); n_steps - 1], || {
+                CubicFCircuit::<Fr>::new(())
+            })
+            .unwrap();
+
+        assert_eq!(pipelined.ivc_proof(), nova.ivc_proof());
+        N::verify(params.1, pipelined.ivc_proof()).unwrap();
+    }
+
+    /// tests that the errors of either thread are returned, leaving the IVC at the last proved
+    /// step, and that the worker is stopped when the main thread fails
+    #[test]
+    fn test_prove_steps_pipelined_errors() {
+        let mut rng = ark_std::test_rng();
+        // the FCircuit has per-step constants for 2 steps only
+        let F_circuit = StepConstantsFCircuit::<Fr>::new(2).unwrap();
+        let params = N::preprocess(
+            &mut rng,
+            &PreprocessorParam::new(poseidon_canonical_config::<Fr>(), F_circuit),
+        )
+        .unwrap();
+
+        // the worker fails to build its FCircuit
+        let mut nova = N::init(&params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+        let result = nova.prove_steps_pipelined_with(&mut rng, vec![(); 3], || {
+            Err(Error::Other("no FCircuit".to_string()))
+        });
+        assert!(matches!(result, Err(Error::Other(_))));
+        assert_eq!(nova.i, Fr::from(0_u32));
+
+        // the worker fails at the third step
+        let result = nova.prove_steps_pipelined(&mut rng, vec![(); 3]);
+        assert!(matches!(result, Err(Error::StepConstantsExhausted(2, 2))));
+        assert_eq!(nova.i, Fr::from(2_u32));
+
+        // the main thread fails at the third step, while the worker, which has constants for
+        // more steps, is still running
+        let mut nova = N::init(&params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+        let result = nova.prove_steps_pipelined_with(&mut rng, vec![(); 6], || {
+            StepConstantsFCircuit::<Fr>::new(6)
+        });
+        assert!(matches!(result, Err(Error::StepConstantsExhausted(2, 2))));
+        assert_eq!(nova.i, Fr::from(2_u32));
+        N::verify(params.1, nova.ivc_proof()).unwrap();
+    }
+}
@@ -0,0 +1,74 @@
+//! Per-phase report of a Nova `prove_step`, returned by `Nova::prove_step_profiled`, so that the
+//! prover telemetry can be exposed programmatically instead of parsing logs or traces.
+use ark_std::time::Duration;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+/// Wall-clock duration and size of a phase of the prover.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PhaseProfile {
+    pub duration: Duration,
+    /// size of the phase's input, whose meaning is described at each field of `StepProfile`
+    pub size: usize,
+}
+
+/// Report of a Nova+CycleFold `prove_step`.
+///
+/// The commitments of the step are the three MSMs `cmT`, `cmW` (which also accounts for `cmE`
+/// when it is not zero) and those of the CycleFold instances, which are included in `cyclefold`.
+/// The CycleFold phase is empty at the first step, since its instances are dummy.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StepProfile {
+    /// `F.step_native`, the synthesis of the AugmentedFCircuit and the extraction of its
+    /// assignment, with the number of witness elements as size
+    pub witness_generation: PhaseProfile,
+    /// computation of the cross-term `T`, with its length (number of constraints) as size
+    pub cross_term: PhaseProfile,
+    /// commitment to the cross-term, with the length of `T` as size
+    pub cmT: PhaseProfile,
+    /// derivation of the folding challenge `r`, with its number of bits as size
+    pub challenge: PhaseProfile,
+    /// commitment to the witness of the new incoming instance, with its length as size
+    pub cmW: PhaseProfile,
+    /// proving and folding of the CycleFold instances, with the number of constraints of the
+    /// CycleFold circuits as size
+    pub cyclefold: PhaseProfile,
+    /// duration of the whole `prove_step`
+    pub total: Duration,
+    /// peak of the bytes allocated during the step, over the ones allocated when it started.
+    /// Only available when `utils::alloc::CountingAlloc` is the global allocator.
+    pub peak_allocation: Option<usize>,
+}
+
+/// Start of a timed phase. Without `std` there is no clock, and all the phases last zero.
+#[derive(Clone, Copy)]
+pub(crate) struct Timer(#[cfg(feature = "std")] Instant);
+
+impl Timer {
+    pub(crate) fn start() -> Self {
+        #[cfg(feature = "std")]
+        return Self(Instant::now());
+        #[cfg(not(feature = "std"))]
+        return Self();
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        #[cfg(feature = "std")]
+        return self.0.elapsed();
+        #[cfg(not(feature = "std"))]
+        return Duration::ZERO;
+    }
+}
+
+/// Runs `f`, adding its duration to `phase` when `PROFILE` is set. Without `PROFILE` this is just
+/// `f()`, so the non-profiled prover does not pay for the timing.
+#[inline(always)]
+pub(crate) fn timed<const PROFILE: bool, T>(phase: &mut PhaseProfile, f: impl FnOnce() -> T) -> T {
+    if !PROFILE {
+        return f();
+    }
+    let start = Timer::start();
+    let out = f();
+    phase.duration += start.elapsed();
+    out
+}
@@ -0,0 +1,212 @@
+//! serde (de)serialization of the Nova [`CommittedInstance`], and thus of the
+//! `CycleFoldCommittedInstance` (which is the same type on the CycleFold curve), so that they can
+//! be exchanged with third parties in formats such as JSON.
+//!
+//! The instance is serialized as a struct `{ cmE, u, cmW, x }`, where each point and field
+//! element is the `0x`-prefixed lowercase hex of its canonical compressed encoding
+//! (`CanonicalSerialize::serialize_compressed`), i.e. the little-endian bytes of the field
+//! elements, and of the x coordinate of the points with the flags in the top bits of the last
+//! byte. For example, over BN254:
+//!
+//! ```json
+//! {
+//!   "cmE": "0x0000000000000000000000000000000000000000000000000000000000000040",
+//!   "u": "0x0100000000000000000000000000000000000000000000000000000000000000",
+//!   "cmW": "0x0100000000000000000000000000000000000000000000000000000000000000",
+//!   "x": ["0x0200000000000000000000000000000000000000000000000000000000000000"]
+//! }
+//! ```
+//!
+//! is the instance with `cmE` the point at infinity, `cmW` the generator, `u = 1` and `x = [2]`.
+//!
+//! The deserialization checks that every field element is canonical and that every point is on
+//! the curve and in the prime-order subgroup, as `CanonicalDeserialize` does.
+use ark_ec::CurveGroup;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{format, string::String, vec::Vec};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::CommittedInstance;
+
+/// Representation of the [`CommittedInstance`] that is (de)serialized by serde.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CommittedInstanceHex {
+    cmE: String,
+    u: String,
+    cmW: String,
+    x: Vec<String>,
+}
+
+/// Returns the `0x`-prefixed hex of the canonical compressed encoding of `v`.
+fn to_hex<T: CanonicalSerialize, E: serde::ser::Error>(v: &T) -> Result<String, E> {
+    let mut bytes = Vec::with_capacity(v.compressed_size());
+    v.serialize_compressed(&mut bytes)
+        .map_err(|e| E::custom(format!("{:?}", e)))?;
+    Ok(bytes.iter().fold(String::from("0x"), |mut s, b| {
+        s.push_str(&format!("{:02x}", b));
+        s
+    }))
+}
+
+/// Decodes the `0x`-prefixed hex `s` of the canonical compressed encoding of a `T`, rejecting any
+/// trailing bytes.
+fn from_hex<T: CanonicalDeserialize, E: de::Error>(s: &str) -> Result<T, E> {
+    let hex = s
+        .strip_prefix("0x")
+        .ok_or_else(|| E::custom(format!("missing 0x prefix in {}", s)))?;
+    if hex.len() % 2 != 0 {
+        return Err(E::custom(format!("odd number of hex digits in {}", s)));
+    }
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|b| u8::from_str_radix(b, 16).ok())
+                .ok_or_else(|| E::custom(format!("invalid hex in {}", s)))
+        })
+        .collect::<Result<Vec<u8>, E>>()?;
+    let mut reader = &bytes[..];
+    let v = T::deserialize_compressed(&mut reader).map_err(|e| E::custom(format!("{:?}", e)))?;
+    if !reader.is_empty() {
+        return Err(E::custom(format!("trailing bytes in {}", s)));
+    }
+    Ok(v)
+}
+
+impl<C: CurveGroup> Serialize for CommittedInstance<C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        CommittedInstanceHex {
+            cmE: to_hex(&self.cmE)?,
+            u: to_hex(&self.u)?,
+            cmW: to_hex(&self.cmW)?,
+            x: self.x.iter().map(to_hex).collect::<Result<_, _>>()?,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, C: CurveGroup> Deserialize<'de> for CommittedInstance<C> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = CommittedInstanceHex::deserialize(deserializer)?;
+        Ok(Self {
+            cmE: from_hex(&hex.cmE)?,
+            u: from_hex(&hex.u)?,
+            cmW: from_hex(&hex.cmW)?,
+            x: hex
+                .x
+                .iter()
+                .map(|x| from_hex(x))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::{Fr, G1Projective as Projective};
+    use ark_ec::Group;
+    use ark_grumpkin::Projective as Projective2;
+    use ark_std::{UniformRand, Zero};
+
+    use super::*;
+    use crate::folding::circuits::cyclefold::CycleFoldCommittedInstance;
+
+    const ZERO_31: &str = "00000000000000000000000000000000000000000000000000000000000000";
+
+    /// pins the encoding of the instances, and of their public field elements
+    #[test]
+    fn test_committed_instance_golden_vectors() {
+        let ci = CommittedInstance::<Projective> {
+            cmE: Projective::zero(),
+            u: Fr::from(1_u32),
+            cmW: Projective::generator(),
+            x: vec![Fr::from(2_u32), Fr::from(3_u32)],
+        };
+        let json = format!(
+            "{{\"cmE\":\"0x{z}40\",\"u\":\"0x01{z}\",\"cmW\":\"0x01{z}\",\"x\":[\"0x02{z}\",\"0x03{z}\"]}}",
+            z = ZERO_31
+        );
+        assert_eq!(serde_json::to_string(&ci).unwrap(), json);
+        assert_eq!(
+            serde_json::from_str::<CommittedInstance<Projective>>(&json).unwrap(),
+            ci
+        );
+
+        // [u, x, cmE.x, cmE.y, cmW.x, cmW.y], with 5 limbs per coordinate
+        let limbs = |v: u32| [vec![Fr::from(v)], vec![Fr::zero(); 4]].concat();
+        assert_eq!(
+            ci.to_public_field_elements(),
+            [
+                vec![Fr::from(1_u32), Fr::from(2_u32), Fr::from(3_u32)],
+                limbs(0),
+                limbs(0),
+                limbs(1),
+                limbs(2),
+            ]
+            .concat()
+        );
+
+        // the CycleFold instances, over Grumpkin, whose generator has x = 1 too
+        let cf_ci = CycleFoldCommittedInstance::<Projective2> {
+            cmE: Projective2::generator(),
+            u: ark_grumpkin::Fr::from(1_u32),
+            cmW: Projective2::zero(),
+            x: vec![ark_grumpkin::Fr::from(5_u32)],
+        };
+        let json = format!(
+            "{{\"cmE\":\"0x01{z}\",\"u\":\"0x01{z}\",\"cmW\":\"0x{z}40\",\"x\":[\"0x05{z}\"]}}",
+            z = ZERO_31
+        );
+        assert_eq!(serde_json::to_string(&cf_ci).unwrap(), json);
+        assert_eq!(
+            serde_json::from_str::<CycleFoldCommittedInstance<Projective2>>(&json).unwrap(),
+            cf_ci
+        );
+    }
+
+    #[test]
+    fn test_committed_instance_serde() {
+        let mut rng = ark_std::test_rng();
+        let ci = CommittedInstance::<Projective> {
+            cmE: Projective::rand(&mut rng),
+            u: Fr::rand(&mut rng),
+            cmW: Projective::rand(&mut rng),
+            x: (0..3).map(|_| Fr::rand(&mut rng)).collect(),
+        };
+        let json = serde_json::to_string(&ci).unwrap();
+        assert_eq!(
+            serde_json::from_str::<CommittedInstance<Projective>>(&json).unwrap(),
+            ci
+        );
+
+        // malformed encodings of u are rejected
+        for u in [
+            // missing prefix
+            format!("01{}", ZERO_31),
+            // odd number of digits
+            format!("0x1{}", ZERO_31),
+            // not hex
+            format!("0xzz{}", ZERO_31),
+            // trailing bytes
+            format!("0x01{}00", ZERO_31),
+            // too short
+            "0x01".to_string(),
+            // non-canonical field element
+            format!("0x{}", "ff".repeat(32)),
+        ] {
+            let json = format!(
+                "{{\"cmE\":\"0x01{z}\",\"u\":\"{u}\",\"cmW\":\"0x01{z}\",\"x\":[]}}",
+                z = ZERO_31,
+                u = u
+            );
+            assert!(serde_json::from_str::<CommittedInstance<Projective>>(&json).is_err());
+        }
+        // unknown fields are rejected
+        let json = format!(
+            "{{\"cmE\":\"0x01{z}\",\"u\":\"0x01{z}\",\"cmW\":\"0x01{z}\",\"x\":[],\"y\":[]}}",
+            z = ZERO_31
+        );
+        assert!(serde_json::from_str::<CommittedInstance<Projective>>(&json).is_err());
+    }
+}
@@ -1,7 +1,8 @@
 use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
 use ark_r1cs_std::fields::fp::FpVar;
 use ark_relations::r1cs::SynthesisError;
-use ark_std::{rand::RngCore, UniformRand};
+use ark_std::{rand::RngCore, vec::Vec, UniformRand};
 
 use super::decider_eth_circuit::WitnessVar;
 use super::nifs::nova_circuits::CommittedInstanceVar;
@@ -13,6 +14,7 @@ use crate::arith::{
 use crate::commitment::CommitmentScheme;
 use crate::folding::circuits::CF1;
 use crate::utils::gadgets::{EquivalenceGadget, VectorGadget};
+use crate::utils::vec::check_equal_rows;
 use crate::Error;
 
 /// Implements `Arith` for R1CS, where the witness is of type [`Witness`], and
@@ -58,7 +60,7 @@ impl<C: CurveGroup> Arith<Witness<C>, CommittedInstance<C>> for R1CS<CF1<C>> {
         _u: &CommittedInstance<C>,
         e: Self::Evaluation,
     ) -> Result<(), Error> {
-        (w.E == e).then_some(()).ok_or(Error::NotSatisfied)
+        check_equal_rows(&e, &w.E)
     }
 }
 
@@ -103,6 +105,23 @@ impl<C: CurveGroup> ArithSampler<C, Witness<C>, CommittedInstance<C>> for R1CS<C
     }
 }
 
+impl<F: PrimeField> R1CS<F> {
+    /// Samples a random relaxed committed instance and witness that satisfy the relaxed R1CS
+    /// relation, picking random `W`, `x` and `u` and computing the consistent error term
+    /// `E = Az ∘ Bz - u·Cz`, see [`ArithSampler::sample_witness_instance`].
+    pub fn sample_relaxed<C: CurveGroup<ScalarField = F>, CS: CommitmentScheme<C, true>>(
+        &self,
+        rng: impl RngCore,
+        params: &CS::ProverParams,
+    ) -> Result<(CommittedInstance<C>, Witness<C>), Error> {
+        let (W, U) =
+            <Self as ArithSampler<C, Witness<C>, CommittedInstance<C>>>::sample_witness_instance::<
+                CS,
+            >(self, params, rng)?;
+        Ok((U, W))
+    }
+}
+
 impl<C: CurveGroup> ArithGadget<WitnessVar<C>, CommittedInstanceVar<C>>
     for R1CSMatricesVar<C::ScalarField, FpVar<C::ScalarField>>
 {
@@ -0,0 +1,58 @@
+//! wasm-bindgen bindings to verify Nova+CycleFold IVC proofs in the browser or in Node, for the
+//! setting used across the examples: BN254 & Grumpkin with Pedersen commitments.
+//!
+//! The verifier params are read from their standalone serialization
+//! (`VerifierParams::serialize_standalone`), so neither the FCircuit nor the circuits synthesis
+//! are needed. To keep the prover-only dependencies out of the wasm artifact, build with
+//! `default-features = false, features = ["wasm"]`.
+use ark_bn254::{Fr, G1Projective as Projective};
+use ark_ff::PrimeField;
+use ark_grumpkin::Projective as Projective2;
+use ark_serialize::CanonicalDeserialize;
+use ark_std::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use wasm_bindgen::prelude::*;
+
+use super::{IVCProof, VerifierParams};
+use crate::commitment::pedersen::Pedersen;
+
+type VP = VerifierParams<Projective, Projective2, Pedersen<Projective>, Pedersen<Projective2>>;
+
+/// Public values of a verified IVC proof, as decimal strings.
+#[wasm_bindgen(getter_with_clone)]
+pub struct VerifiedState {
+    /// number of steps of the IVC
+    pub steps: String,
+    /// initial state
+    pub z_0: Vec<String>,
+    /// state after the last step
+    pub z_i: Vec<String>,
+}
+
+/// Verifies the IVC proof given its compressed `CanonicalSerialize` serialization and the
+/// standalone serialization of the verifier params, returning the verified state. Malformed
+/// inputs are reported as errors, never as panics (which would abort the wasm instance).
+#[wasm_bindgen]
+pub fn verify_ivc(vp_bytes: &[u8], proof_bytes: &[u8]) -> Result<VerifiedState, JsError> {
+    let vp = VP::deserialize_standalone(vp_bytes).map_err(to_js_error)?;
+    let ivc_proof = IVCProof::<Projective, Projective2>::deserialize_compressed(proof_bytes)
+        .map_err(to_js_error)?;
+    let state = VerifiedState {
+        steps: to_decimal(&ivc_proof.i),
+        z_0: ivc_proof.z_0.iter().map(to_decimal).collect(),
+        z_i: ivc_proof.z_i.iter().map(to_decimal).collect(),
+    };
+    vp.verify_ivc_proof(ivc_proof, None).map_err(to_js_error)?;
+    Ok(state)
+}
+
+fn to_js_error(e: impl ToString) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+// `Fr`'s `Display` trims all the leading zeros, which prints zero as an empty string
+fn to_decimal(x: &Fr) -> String {
+    x.into_bigint().to_string()
+}
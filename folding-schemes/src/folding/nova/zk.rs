@@ -23,19 +23,25 @@
 ///   involved). In this use-case, the zk is only added at the last IVCProof. Note that this proof
 ///   will be much bigger and expensive to verify than a Decider SNARK proof.
 ///
-/// The current implementation covers the Use-case-3.
+/// The current implementation covers the Use-case-3. Both the running instance and the CycleFold
+/// running instance are folded with random satisfying instances, so that neither the witness of
+/// the IVC nor the witness of the CycleFold circuit are revealed.
 /// Use-case-1 can be achieved directly by a simpler version of the zk IVC scheme skipping steps
 /// and implemented directly at the app level by folding the original instance with a randomized
 /// instance (steps 2,3,4 from section D.4 of the [HyperNova](https://eprint.iacr.org/2023/573.pdf)
 /// paper).
 /// And the Use-case-2 would require a modified version of the Decider circuits.
 ///
-use ark_ff::PrimeField;
-use ark_std::{One, Zero};
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::{vec::Vec, One, Zero};
 
 use crate::{
     arith::{r1cs::R1CS, Arith, ArithSampler},
-    folding::traits::CommittedInstanceOps,
+    folding::{
+        circuits::cyclefold::{CycleFoldChallengeGadget, CycleFoldConfig},
+        traits::CommittedInstanceOps,
+    },
+    utils::vec::check_len,
     RngCore,
 };
 use ark_crypto_primitives::sponge::{
@@ -48,8 +54,9 @@ use ark_r1cs_std::{groups::CurveVar, ToConstraintFieldGadget};
 use crate::{commitment::CommitmentScheme, folding::circuits::CF2, frontend::FCircuit, Error};
 
 use super::{
+    circuits::augmented_io_len,
     nifs::{nova::NIFS, NIFSTrait},
-    CommittedInstance, Nova, Witness,
+    CommittedInstance, Nova, NovaCycleFoldConfig, Witness,
 };
 
 pub struct RandomizedIVCProof<C1: CurveGroup, C2: CurveGroup> {
@@ -60,7 +67,9 @@ pub struct RandomizedIVCProof<C1: CurveGroup, C2: CurveGroup> {
     pub pi_prime: C1, // proof' = cmT'
     pub W_i_prime: Witness<C1>,
     pub cf_U_i: CommittedInstance<C2>,
-    pub cf_W_i: Witness<C2>,
+    pub cf_U_r: CommittedInstance<C2>,
+    pub cf_pi: C2, // proof of the CycleFold fold = cf_cmT
+    pub cf_W_i_prime: Witness<C2>,
 }
 
 impl<C1: CurveGroup, C2: CurveGroup> RandomizedIVCProof<C1, C2>
@@ -105,9 +114,7 @@ where
         )?;
 
         // 2. Sample a satisfying relaxed R1CS instance-witness pair (W_r, U_r)
-        let (W_r, U_r) = nova
-            .r1cs
-            .sample_witness_instance::<CS1>(&nova.cs_pp, &mut rng)?;
+        let (U_r, W_r) = nova.r1cs.sample_relaxed::<C1, CS1>(&mut rng, &nova.cs_pp)?;
 
         // 3. Fold the instance-witness pair (U_f, W_f) with (U_r, W_r)
         let (W_i_prime, _, cmT_i_prime, _) =
@@ -122,6 +129,37 @@ where
                 &U_r,
             )?;
 
+        // II. Randomize the CycleFold running instance in the same way
+        // 4. Sample a satisfying relaxed R1CS instance-witness pair (cf_W_r, cf_U_r) for the
+        // CycleFold circuit
+        let (cf_W_r, cf_U_r) = nova
+            .cf_r1cs
+            .sample_witness_instance::<CS2>(&nova.cf_cs_pp, &mut rng)?;
+
+        // 5. Fold the instance-witness pair (cf_U_i, cf_W_i) with (cf_U_r, cf_W_r)
+        let (cf_T, cf_cmT) =
+            NIFS::<C2, CS2, PoseidonSponge<C2::ScalarField>, true>::compute_cyclefold_cmT(
+                &nova.cf_cs_pp,
+                &nova.cf_r1cs,
+                &nova.cf_W_i,
+                &nova.cf_U_i,
+                &cf_W_r,
+                &cf_U_r,
+            )?;
+        let cf_r = Self::get_cyclefold_challenge::<GC2>(
+            &mut transcript,
+            nova.pp_hash,
+            &nova.cf_U_i,
+            &cf_U_r,
+            cf_cmT,
+        )?;
+        let cf_W_i_prime = NIFS::<C2, CS2, PoseidonSponge<C2::ScalarField>, true>::fold_witness(
+            cf_r,
+            &nova.cf_W_i,
+            &cf_W_r,
+            &cf_T,
+        )?;
+
         Ok(RandomizedIVCProof {
             U_i: nova.U_i.clone(),
             u_i: nova.u_i.clone(),
@@ -130,7 +168,9 @@ where
             pi_prime: cmT_i_prime,
             W_i_prime,
             cf_U_i: nova.cf_U_i.clone(),
-            cf_W_i: nova.cf_W_i.clone(),
+            cf_U_r,
+            cf_pi: cf_cmT,
+            cf_W_i_prime,
         })
     }
 
@@ -145,6 +185,7 @@ where
         r1cs: &R1CS<C1::ScalarField>,
         cf_r1cs: &R1CS<C2::ScalarField>,
         pp_hash: C1::ScalarField,
+        context: C1::ScalarField,
         poseidon_config: &PoseidonConfig<C1::ScalarField>,
         i: C1::ScalarField,
         z_0: Vec<C1::ScalarField>,
@@ -168,14 +209,19 @@ where
         }
 
         // 1. Check that u_i.x is correct - including the cyclefold running instance
-        // a. Check length
-        if proof.u_i.x.len() != 2 {
-            return Err(Error::IVCVerificationFail);
+        // a. Check length, which is 3 when pp_hash is a public input of the AugmentedFCircuit
+        let public_pp_hash = r1cs.l == augmented_io_len(true);
+        check_len(proof.u_i.x.len(), augmented_io_len(public_pp_hash))?;
+        if public_pp_hash && proof.u_i.x[2] != pp_hash {
+            return Err(Error::zkIVCVerificationFail);
         }
 
         // b. Check computed hashes are correct
         let sponge = PoseidonSponge::<C1::ScalarField>::new(poseidon_config);
-        let expected_u_i_x = proof.U_i.hash(&sponge, pp_hash, i, &z_0, &z_i);
+        let expected_u_i_x =
+            proof
+                .U_i
+                .hash_with_context(&sponge, pp_hash, Some(context), i, &z_0, &z_i);
         if expected_u_i_x != proof.u_i.x[0] {
             return Err(Error::zkIVCVerificationFail);
         }
@@ -212,11 +258,51 @@ where
         // 5. Check that W^{\prime}_i is a satisfying witness
         r1cs.check_relation(&proof.W_i_prime, &U_i_prime)?;
 
-        // 6. Check that the cyclefold instance-witness pair satisfies the cyclefold relaxed r1cs
-        cf_r1cs.check_relation(&proof.cf_W_i, &proof.cf_U_i)?;
+        // 6. Obtain the randomized CycleFold instance cf_U^{\prime}_i
+        let cf_r = Self::get_cyclefold_challenge::<GC2>(
+            &mut transcript,
+            pp_hash,
+            &proof.cf_U_i,
+            &proof.cf_U_r,
+            proof.cf_pi,
+        )?;
+        let cf_U_i_prime =
+            NIFS::<C2, CS2, PoseidonSponge<C2::ScalarField>, true>::fold_committed_instances(
+                cf_r,
+                &proof.cf_U_i,
+                &proof.cf_U_r,
+                &proof.cf_pi,
+            );
+
+        // 7. Check that cf_W^{\prime}_i is a satisfying witness of the cyclefold relaxed r1cs
+        cf_r1cs.check_relation(&proof.cf_W_i_prime, &cf_U_i_prime)?;
 
         Ok(())
     }
+
+    /// Derives the challenge used to fold the CycleFold running instance `cf_U_i` with the random
+    /// instance `cf_U_r`, in the same way as the CycleFold instances are folded in Nova's steps.
+    fn get_cyclefold_challenge<GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>>(
+        transcript: &mut PoseidonSponge<C1::ScalarField>,
+        pp_hash: C1::ScalarField,
+        cf_U_i: &CommittedInstance<C2>,
+        cf_U_r: &CommittedInstance<C2>,
+        cf_cmT: C2,
+    ) -> Result<C2::ScalarField, Error>
+    where
+        <C2 as CurveGroup>::BaseField: PrimeField + Absorb,
+        C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    {
+        let cf_r_bits = CycleFoldChallengeGadget::<C2, GC2>::get_challenge_native(
+            transcript,
+            NovaCycleFoldConfig::<C1>::CHALLENGE_BIT_LENGTH,
+            pp_hash,
+            cf_U_i.clone(),
+            cf_U_r.clone(),
+            cf_cmT,
+        );
+        C2::ScalarField::from_bigint(BigInteger::from_bits_le(&cf_r_bits)).ok_or(Error::OutOfBounds)
+    }
 }
 
 #[cfg(test)]
@@ -251,6 +337,7 @@ pub mod tests {
             &nova.r1cs,
             &nova.cf_r1cs,
             nova.pp_hash,
+            nova.context,
             &nova.poseidon_config,
             nova.i,
             nova.z_0,
@@ -260,6 +347,58 @@ pub mod tests {
         assert!(verify.is_ok());
     }
 
+    // Tests that the CycleFold running instance is randomized, ie. the published CycleFold
+    // witness is fresh at each randomization and does not reveal the original one
+    #[test]
+    fn test_zk_nova_randomized_cyclefold_instance() {
+        let mut rng = OsRng;
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let (_, nova) = test_ivc_opt::<Pedersen<Projective, true>, Pedersen<Projective2, true>, true>(
+            poseidon_config.clone(),
+            F_circuit,
+            3,
+        );
+
+        let verify = |proof: &RandomizedIVCProof<Projective, Projective2>| {
+            RandomizedIVCProof::verify::<
+                Pedersen<Projective, true>,
+                GVar2,
+                Pedersen<Projective2, true>,
+            >(
+                &nova.r1cs,
+                &nova.cf_r1cs,
+                nova.pp_hash,
+                nova.context,
+                &nova.poseidon_config,
+                nova.i,
+                nova.z_0.clone(),
+                nova.z_i.clone(),
+                proof,
+            )
+        };
+
+        let proof_1 = RandomizedIVCProof::new(&nova, &mut rng).unwrap();
+        let proof_2 = RandomizedIVCProof::new(&nova, &mut rng).unwrap();
+        assert!(verify(&proof_1).is_ok());
+        assert!(verify(&proof_2).is_ok());
+
+        // the published running instance is the one committed in the IVC, while the random
+        // instances and the folded witnesses are different at each randomization
+        assert_eq!(proof_1.cf_U_i, nova.cf_U_i);
+        assert_ne!(proof_1.cf_U_r, proof_2.cf_U_r);
+        for proof in [&proof_1, &proof_2] {
+            assert_ne!(proof.cf_W_i_prime.W, nova.cf_W_i.W);
+            assert_ne!(proof.cf_W_i_prime.E, nova.cf_W_i.E);
+        }
+        assert_ne!(proof_1.cf_W_i_prime.W, proof_2.cf_W_i_prime.W);
+
+        // the original CycleFold witness does not satisfy the randomized instance
+        let mut incorrect_proof = proof_1;
+        incorrect_proof.cf_W_i_prime = nova.cf_W_i.clone();
+        assert!(verify(&incorrect_proof).is_err());
+    }
+
     #[test]
     fn test_zk_nova_when_i_is_zero() {
         let mut rng = OsRng;
@@ -280,6 +419,7 @@ pub mod tests {
             &nova.r1cs,
             &nova.cf_r1cs,
             nova.pp_hash,
+            nova.context,
             &nova.poseidon_config,
             nova.i,
             nova.z_0,
@@ -299,9 +439,9 @@ pub mod tests {
             F_circuit,
             3,
         );
-        let (_, sampled_committed_instance) = nova
+        let (sampled_committed_instance, _) = nova
             .r1cs
-            .sample_witness_instance::<Pedersen<Projective, true>>(&nova.cs_pp, rng)
+            .sample_relaxed::<Projective, Pedersen<Projective, true>>(rng, &nova.cs_pp)
             .unwrap();
 
         // proof verification fails with incorrect running instance
@@ -317,6 +457,7 @@ pub mod tests {
             &nova_with_incorrect_running_instance.r1cs,
             &nova_with_incorrect_running_instance.cf_r1cs,
             nova_with_incorrect_running_instance.pp_hash,
+            nova_with_incorrect_running_instance.context,
             &nova_with_incorrect_running_instance.poseidon_config,
             nova_with_incorrect_running_instance.i,
             nova_with_incorrect_running_instance.z_0,
@@ -336,9 +477,9 @@ pub mod tests {
             F_circuit,
             3,
         );
-        let (sampled_committed_witness, _) = nova
+        let (_, sampled_committed_witness) = nova
             .r1cs
-            .sample_witness_instance::<Pedersen<Projective, true>>(&nova.cs_pp, rng)
+            .sample_relaxed::<Projective, Pedersen<Projective, true>>(rng, &nova.cs_pp)
             .unwrap();
 
         // proof generation fails with incorrect running witness
@@ -354,6 +495,7 @@ pub mod tests {
             &nova_with_incorrect_running_witness.r1cs,
             &nova_with_incorrect_running_witness.cf_r1cs,
             nova_with_incorrect_running_witness.pp_hash,
+            nova_with_incorrect_running_witness.context,
             &nova_with_incorrect_running_witness.poseidon_config,
             nova_with_incorrect_running_witness.i,
             nova_with_incorrect_running_witness.z_0,
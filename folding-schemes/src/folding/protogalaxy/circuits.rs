@@ -12,11 +12,10 @@ use ark_r1cs_std::{
     eq::EqGadget,
     fields::{fp::FpVar, FieldVar},
     groups::CurveVar,
-    poly::polynomial::univariate::dense::DensePolynomialVar,
     R1CSVar, ToBitsGadget, ToConstraintFieldGadget,
 };
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
-use ark_std::{fmt::Debug, marker::PhantomData, One, Zero};
+use ark_std::{cmp::max, fmt::Debug, marker::PhantomData, vec::Vec, One, Zero};
 
 use super::{
     folding::lagrange_polys,
@@ -35,7 +34,7 @@ use crate::{
         },
         traits::{CommittedInstanceVarOps, Dummy},
     },
-    frontend::FCircuit,
+    frontend::{step_constraints, FCircuit},
     transcript::{AbsorbNonNativeGadget, TranscriptVar},
     utils::gadgets::VectorGadget,
 };
@@ -66,50 +65,26 @@ impl FoldingGadget {
         transcript.absorb(&F_coeffs)?;
 
         let alpha = transcript.get_challenge()?;
-        let alphas = all_powers_var(alpha.clone(), t);
 
-        // F(alpha) = e + \sum_t F_i * alpha^i
-        let mut F_alpha = instance.e.clone();
-        for (i, F_i) in F_coeffs.iter().skip(1).enumerate() {
-            F_alpha += F_i * &alphas[i + 1];
+        // F(alpha) = e + \sum_t F_i * alpha^i, evaluated with Horner's rule, which takes one
+        // constraint per coefficient and no extra powers of alpha
+        let mut F_alpha = FpVar::zero();
+        for F_i in F_coeffs.iter().skip(1).rev() {
+            F_alpha = (F_alpha + F_i) * &alpha;
         }
+        F_alpha += &instance.e;
 
         let betas_star = betas_star_var(&instance.betas, &deltas, &alpha);
 
         let k = vec_instances.len();
-        let H =
-            GeneralEvaluationDomain::new(k + 1).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
-        let L_X = lagrange_polys(H)
-            .into_iter()
-            .map(|poly| {
-                DensePolynomialVar::from_coefficients_vec(
-                    poly.coeffs
-                        .into_iter()
-                        .map(FpVar::constant)
-                        .collect::<Vec<_>>(),
-                )
-            })
-            .collect::<Vec<_>>();
-        let Z_X = DensePolynomialVar::from_coefficients_vec(
-            DensePolynomial::from(H.vanishing_polynomial())
-                .coeffs
-                .into_iter()
-                .map(FpVar::constant)
-                .collect::<Vec<_>>(),
-        );
-        let K_X = DensePolynomialVar { coeffs: K_coeffs };
 
-        transcript.absorb(&K_X.coeffs)?;
+        transcript.absorb(&K_coeffs)?;
 
         let gamma = transcript.get_challenge()?;
 
-        let L_X_evals = L_X
-            .iter()
-            .take(k + 1)
-            .map(|L| L.evaluate(&gamma))
-            .collect::<Result<Vec<_>, _>>()?;
+        let (L_X_evals, Z_gamma, K_gamma) = Self::evaluate_at_gamma(&gamma, k, &K_coeffs)?;
 
-        let e_star = F_alpha * &L_X_evals[0] + Z_X.evaluate(&gamma)? * K_X.evaluate(&gamma)?;
+        let e_star = F_alpha * &L_X_evals[0] + Z_gamma * K_gamma;
 
         let mut x_star = instance.x.mul_scalar(&L_X_evals[0])?;
         for i in 0..k {
@@ -128,6 +103,47 @@ impl FoldingGadget {
             L_X_evals,
         ))
     }
+
+    /// Evaluates the Lagrange basis `L_j(X)` and the vanishing polynomial `Z(X)` of the domain
+    /// of size `k + 1`, and `K(X)`, at `gamma`.
+    ///
+    /// All of them share a single chain of powers of `gamma`. Since the coefficients of `L_j(X)`
+    /// and `Z(X)` are constants, their evaluations are linear combinations of the powers and do
+    /// not add any constraint, so the whole evaluation costs `2 * deg(K) - 1` constraints.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn evaluate_at_gamma<F: PrimeField>(
+        gamma: &FpVar<F>,
+        k: usize,
+        K_coeffs: &[FpVar<F>],
+    ) -> Result<(Vec<FpVar<F>>, FpVar<F>, FpVar<F>), SynthesisError> {
+        let H = GeneralEvaluationDomain::<F>::new(k + 1)
+            .ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+        let Z_X = DensePolynomial::from(H.vanishing_polynomial());
+        let gammas = all_powers_var(gamma.clone(), max(K_coeffs.len(), Z_X.coeffs.len()));
+
+        // evaluates a polynomial with constant coefficients at gamma
+        let evaluate_constant = |coeffs: &[F]| {
+            coeffs
+                .iter()
+                .zip(&gammas)
+                .map(|(c, gamma_i)| gamma_i * *c)
+                .sum::<FpVar<F>>()
+        };
+
+        let L_X_evals = lagrange_polys(H)
+            .iter()
+            .take(k + 1)
+            .map(|L| evaluate_constant(&L.coeffs))
+            .collect::<Vec<_>>();
+        let Z_gamma = evaluate_constant(&Z_X.coeffs);
+        let K_gamma = K_coeffs
+            .iter()
+            .zip(&gammas)
+            .map(|(K_i, gamma_i)| K_i * gamma_i)
+            .sum::<FpVar<F>>();
+
+        Ok((L_X_evals, Z_gamma, K_gamma))
+    }
 }
 
 pub struct AugmentationGadget;
@@ -205,6 +221,7 @@ impl AugmentationGadget {
 
             let cf_r_bits = CycleFoldChallengeGadget::get_challenge_gadget(
                 transcript,
+                ProtoGalaxyCycleFoldConfig::<C1>::CHALLENGE_BIT_LENGTH,
                 pp_hash.clone(),
                 cf_U.to_native_sponge_field_elements()?,
                 cf_u.clone(),
@@ -247,9 +264,11 @@ pub struct AugmentedFCircuit<
     pub(super) i_usize: usize,
     pub(super) z_0: Vec<CF1<C1>>,
     pub(super) z_i: Vec<CF1<C1>>,
-    pub(super) external_inputs: Vec<CF1<C1>>,
+    pub(super) external_inputs: FC::ExternalInputs,
     pub(super) F: FC, // F circuit
     pub(super) u_i_phi: C1,
+    /// Incoming instances folded in addition to `u_i` (`k - 1` of them)
+    pub(super) us: Vec<CommittedInstance<C1, false>>,
     pub(super) U_i: CommittedInstance<C1, true>,
     pub(super) U_i1_phi: C1,
     pub(super) F_coeffs: Vec<CF1<C1>>,
@@ -258,11 +277,9 @@ pub struct AugmentedFCircuit<
 
     pub(super) phi_stars: Vec<C1>,
 
-    pub(super) cf1_u_i_cmW: C2,                        // input
-    pub(super) cf2_u_i_cmW: C2,                        // input
+    pub(super) cf_u_i_cmWs: Vec<C2>, // input, `k + 1` of them
     pub(super) cf_U_i: CycleFoldCommittedInstance<C2>, // input
-    pub(super) cf1_cmT: C2,
-    pub(super) cf2_cmT: C2,
+    pub(super) cf_cmTs: Vec<C2>,
     pub(super) cf_x: Option<CF1<C1>>, // public input (u_{i+1}.x[1])
 }
 
@@ -288,8 +305,9 @@ impl<C1: CurveGroup, C2: CurveGroup, GC2: CurveVar<C2, CF2<C2>>, FC: FCircuit<CF
             i_usize: 0,
             z_0: vec![CF1::<C1>::zero(); F_circuit.state_len()],
             z_i: vec![CF1::<C1>::zero(); F_circuit.state_len()],
-            external_inputs: vec![CF1::<C1>::zero(); F_circuit.external_inputs_len()],
+            external_inputs: FC::ExternalInputs::default(),
             u_i_phi: C1::zero(),
+            us: vec![CommittedInstance::dummy((2, 0)); k - 1],
             U_i: u_dummy,
             U_i1_phi: C1::zero(),
             F_coeffs: vec![CF1::<C1>::zero(); t],
@@ -298,11 +316,9 @@ impl<C1: CurveGroup, C2: CurveGroup, GC2: CurveVar<C2, CF2<C2>>, FC: FCircuit<CF
             F: F_circuit,
             x: None,
             // cyclefold values
-            cf1_u_i_cmW: C2::zero(),
-            cf2_u_i_cmW: C2::zero(),
+            cf_u_i_cmWs: vec![C2::zero(); k + 1],
             cf_U_i: cf_u_dummy,
-            cf1_cmT: C2::zero(),
-            cf2_cmT: C2::zero(),
+            cf_cmTs: vec![C2::zero(); k + 1],
             cf_x: None,
         }
     }
@@ -322,11 +338,12 @@ where
         let z_0 = Vec::<FpVar<CF1<C1>>>::new_witness(cs.clone(), || Ok(self.z_0))?;
         let z_i = Vec::<FpVar<CF1<C1>>>::new_witness(cs.clone(), || Ok(self.z_i))?;
         let external_inputs =
-            Vec::<FpVar<CF1<C1>>>::new_witness(cs.clone(), || Ok(self.external_inputs))?;
+            FC::ExternalInputsVar::new_witness(cs.clone(), || Ok(self.external_inputs))?;
 
         let u_dummy = CommittedInstance::<C1, true>::dummy((2, self.U_i.betas.len()));
         let U_i = CommittedInstanceVar::<C1, true>::new_witness(cs.clone(), || Ok(self.U_i))?;
         let u_i_phi = NonNativeAffineVar::new_witness(cs.clone(), || Ok(self.u_i_phi))?;
+        let us = Vec::<CommittedInstanceVar<C1, false>>::new_witness(cs.clone(), || Ok(self.us))?;
         let U_i1_phi = NonNativeAffineVar::new_witness(cs.clone(), || Ok(self.U_i1_phi))?;
         let phi_stars =
             Vec::<NonNativeAffineVar<C1>>::new_witness(cs.clone(), || Ok(self.phi_stars))?;
@@ -335,8 +352,8 @@ where
             CycleFoldCommittedInstance::dummy(ProtoGalaxyCycleFoldConfig::<C1>::IO_LEN);
         let cf_U_i =
             CycleFoldCommittedInstanceVar::<C2, GC2>::new_witness(cs.clone(), || Ok(self.cf_U_i))?;
-        let cf1_cmT = GC2::new_witness(cs.clone(), || Ok(self.cf1_cmT))?;
-        let cf2_cmT = GC2::new_witness(cs.clone(), || Ok(self.cf2_cmT))?;
+        let cf_u_i_cmWs = Vec::<GC2>::new_witness(cs.clone(), || Ok(self.cf_u_i_cmWs))?;
+        let cf_cmTs = Vec::<GC2>::new_witness(cs.clone(), || Ok(self.cf_cmTs))?;
 
         let F_coeffs = Vec::new_witness(cs.clone(), || Ok(self.F_coeffs))?;
         let K_coeffs = Vec::new_witness(cs.clone(), || Ok(self.K_coeffs))?;
@@ -356,12 +373,24 @@ where
         let (cf_u_i_x, _) = cf_U_i.clone().hash(&sponge, pp_hash.clone())?;
 
         // P.2. Prepare incoming primary instances
+        // `u_i` is the first incoming instance, followed by the `k - 1` other
+        // instances, whose public inputs are given as witnesses.
+        let u_phis = [
+            vec![u_i_phi],
+            us.iter().map(|u| u.phi.clone()).collect::<Vec<_>>(),
+        ]
+        .concat();
+        let u_xs = [
+            vec![vec![u_i_x, cf_u_i_x]],
+            us.into_iter().map(|u| u.x).collect::<Vec<_>>(),
+        ]
+        .concat();
         // P.3. Fold incoming primary instances into the running instance
         let (U_i1, r) = AugmentationGadget::prepare_and_fold_primary(
             &mut transcript,
             U_i.clone(),
-            vec![u_i_phi.clone()],
-            vec![vec![u_i_x, cf_u_i_x]],
+            u_phis.clone(),
+            u_xs,
             U_i1_phi,
             F_coeffs,
             K_coeffs,
@@ -370,9 +399,7 @@ where
         // P.4.a compute and check the first output of F'
 
         // get z_{i+1} from the F circuit
-        let z_i1 =
-            self.F
-                .generate_step_constraints(cs.clone(), self.i_usize, z_i, external_inputs)?;
+        let z_i1 = step_constraints(&self.F, cs.clone(), self.i_usize, &i, z_i, external_inputs)?;
 
         // Base case: u_{i+1}.x[0] == H((i+1, z_0, z_{i+1}, U_{\bot})
         // Non-base case: u_{i+1}.x[0] == H((i+1, z_0, z_{i+1}, U_{i+1})
@@ -394,49 +421,32 @@ where
         x.enforce_equal(&is_basecase.select(&u_i1_x_base, &u_i1_x)?)?;
 
         // CycleFold part
-        // C.1. Compute cf1_u_i.x and cf2_u_i.x
-        let mut r0_bits = r[0].to_bits_le()?;
-        let mut r1_bits = r[1].to_bits_le()?;
-        r0_bits.resize(C1::ScalarField::MODULUS_BIT_SIZE as usize, Boolean::FALSE);
-        r1_bits.resize(C1::ScalarField::MODULUS_BIT_SIZE as usize, Boolean::FALSE);
-        let cf1_x = [
-            r0_bits
-                .chunks(C1::BaseField::MODULUS_BIT_SIZE as usize - 1)
-                .map(|bits| {
-                    let mut bits = bits.to_vec();
-                    bits.resize(C1::BaseField::MODULUS_BIT_SIZE as usize, Boolean::FALSE);
-                    NonNativeUintVar::from(&bits)
-                })
-                .collect::<Vec<_>>(),
-            vec![
-                NonNativeUintVar::new_constant(cs.clone(), C1::BaseField::zero())?,
-                NonNativeUintVar::new_constant(cs.clone(), C1::BaseField::zero())?,
-                U_i.phi.x.clone(),
-                U_i.phi.y.clone(),
-                phi_stars[0].x.clone(),
-                phi_stars[0].y.clone(),
-            ],
-        ]
-        .concat();
-        let cf2_x = [
-            r1_bits
-                .chunks(C1::BaseField::MODULUS_BIT_SIZE as usize - 1)
-                .map(|bits| {
-                    let mut bits = bits.to_vec();
-                    bits.resize(C1::BaseField::MODULUS_BIT_SIZE as usize, Boolean::FALSE);
-                    NonNativeUintVar::from(&bits)
-                })
-                .collect::<Vec<_>>(),
-            vec![
-                phi_stars[0].x.clone(),
-                phi_stars[0].y.clone(),
-                u_i_phi.x.clone(),
-                u_i_phi.y.clone(),
-                U_i1.phi.x.clone(),
-                U_i1.phi.y.clone(),
-            ],
-        ]
-        .concat();
+        // C.1. Compute cf_u_i.x for each of the `k + 1` CycleFold instances.
+        // The j-th CycleFold circuit enforces
+        // `phi_stars[j - 1] + phis[j] * L_evals[j] == phi_stars[j]`, where
+        // `phis = [U_i.phi, u_i.phi, ...]`, `phi_stars[-1] = 0`, and the last
+        // output is `U_{i+1}.phi`.
+        let zero_point = NonNativeAffineVar::new_constant(cs.clone(), C1::zero())?;
+        let phis = [vec![U_i.phi.clone()], u_phis].concat();
+        let phi_outs = [phi_stars, vec![U_i1.phi.clone()]].concat();
+        let cf_u_i_xs = r
+            .iter()
+            .enumerate()
+            .map(|(j, r_j)| {
+                let mut r_bits = r_j.to_bits_le()?;
+                r_bits.resize(C1::ScalarField::MODULUS_BIT_SIZE as usize, Boolean::FALSE);
+                let prev = if j == 0 {
+                    zero_point.clone()
+                } else {
+                    phi_outs[j - 1].clone()
+                };
+                ProtoGalaxyCycleFoldConfig::<C1>::x_gadget(
+                    &r_bits,
+                    &[prev, phis[j].clone()],
+                    &phi_outs[j],
+                )
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
 
         // C.2. Prepare incoming CycleFold instances
         // C.3. Fold incoming CycleFold instances into the running instance
@@ -445,12 +455,9 @@ where
                 &mut transcript,
                 pp_hash.clone(),
                 cf_U_i,
-                vec![
-                    GC2::new_witness(cs.clone(), || Ok(self.cf1_u_i_cmW))?,
-                    GC2::new_witness(cs.clone(), || Ok(self.cf2_u_i_cmW))?,
-                ],
-                vec![cf1_x, cf2_x],
-                vec![cf1_cmT, cf2_cmT],
+                cf_u_i_cmWs,
+                cf_u_i_xs,
+                cf_cmTs,
             )?;
 
         // Back to Primary Part
@@ -478,11 +485,18 @@ mod tests {
     use crate::{
         arith::r1cs::tests::get_test_r1cs,
         folding::protogalaxy::folding::{tests::prepare_inputs, Folding},
+        frontend::{
+            tests::{capture_logs, WRONG_STATE_LEN_LOG},
+            utils::WrongStateLenFCircuit,
+        },
         transcript::poseidon::poseidon_canonical_config,
     };
 
     use ark_bn254::{Fr, G1Projective as Projective};
+    use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+    use ark_poly::{DenseUVPolynomial, Polynomial};
     use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::UniformRand;
 
     #[test]
     fn test_folding_gadget() -> Result<(), Box<dyn Error>> {
@@ -528,4 +542,61 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_evaluate_at_gamma() -> Result<(), Box<dyn Error>> {
+        let mut rng = ark_std::test_rng();
+        let d = 2;
+        for k in [1, 3, 7] {
+            let H = GeneralEvaluationDomain::<Fr>::new(k + 1).unwrap();
+            let gamma = Fr::rand(&mut rng);
+            let K_coeffs = (0..d * k + 1)
+                .map(|_| Fr::rand(&mut rng))
+                .collect::<Vec<_>>();
+
+            let cs = ConstraintSystem::new_ref();
+            let gamma_var = FpVar::new_witness(cs.clone(), || Ok(gamma))?;
+            let K_coeffs_var = Vec::new_witness(cs.clone(), || Ok(K_coeffs.clone()))?;
+            let num_constraints = cs.num_constraints();
+            let (L_X_evals, Z_gamma, K_gamma) =
+                FoldingGadget::evaluate_at_gamma(&gamma_var, k, &K_coeffs_var)?;
+
+            // the powers of gamma are shared, and evaluating the polynomials with constant
+            // coefficients is free
+            assert_eq!(cs.num_constraints() - num_constraints, 2 * d * k - 1);
+            assert!(cs.is_satisfied()?);
+
+            let L_X = lagrange_polys(H);
+            assert_eq!(
+                L_X_evals.value()?,
+                L_X.iter().map(|L| L.evaluate(&gamma)).collect::<Vec<_>>()
+            );
+            assert_eq!(Z_gamma.value()?, H.evaluate_vanishing_polynomial(gamma));
+            assert_eq!(
+                K_gamma.value()?,
+                DensePolynomial::from_coefficients_vec(K_coeffs).evaluate(&gamma)
+            );
+        }
+
+        Ok(())
+    }
+
+    // checks that an FCircuit whose step returns a state of a wrong length makes the synthesis of
+    // the AugmentedFCircuit fail
+    #[test]
+    fn test_augmented_f_circuit_wrong_state_len() {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let F_circuit = WrongStateLenFCircuit::<Fr>::new(()).unwrap();
+        let augmented_F_circuit = AugmentedFCircuit::<
+            Projective,
+            Projective2,
+            GVar2,
+            WrongStateLenFCircuit<Fr>,
+        >::empty(
+            &poseidon_canonical_config::<Fr>(), F_circuit, 1, 2, 1
+        );
+        let (result, logs) = capture_logs(|| augmented_F_circuit.generate_constraints(cs));
+        assert!(matches!(result, Err(SynthesisError::Unsatisfiable)));
+        assert_eq!(logs, vec![WRONG_STATE_LEN_LOG]);
+    }
 }
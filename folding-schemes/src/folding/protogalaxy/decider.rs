@@ -0,0 +1,401 @@
+/// This file implements the ProtoGalaxy's offchain decider. For ethereum use cases, use the
+/// Decider from decider_eth.rs file.
+///
+/// The decider reuses the generic decider circuit (`DeciderEthCircuit`), which checks in-circuit
+/// the relation of the folded instance (including the consistency of `betas` and `e`), the hash
+/// binding of `u_i.x`, and the CycleFold accumulator relation, so that a single SNARK over
+/// C1::ScalarField suffices, allowing cycles like BN254/Grumpkin where there is no pairing-friendly
+/// curve for the CycleFold side. The opening of the folded witness commitment, instead of with
+/// KZG, is done by the IPA over the Pedersen parameters used during folding, so the commitment
+/// scheme does not require a trusted setup.
+use ark_crypto_primitives::sponge::{poseidon::PoseidonSponge, Absorb, CryptographicSponge};
+use ark_ec::{CurveGroup, Group};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{prelude::CurveVar, ToConstraintFieldGadget};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use ark_std::{
+    marker::PhantomData,
+    rand::{CryptoRng, RngCore},
+    string::ToString,
+    vec::Vec,
+    One,
+};
+
+pub use super::decider_eth_circuit::DeciderEthCircuit;
+use super::decider_eth_circuit::DeciderProtoGalaxyGadget;
+use super::ProtoGalaxy;
+use crate::commitment::{
+    ipa::{AggregatedProof, IPA},
+    pedersen::Params as PedersenParams,
+    CommitmentScheme,
+};
+use crate::folding::circuits::decider::DeciderEnabledNIFS;
+use crate::folding::circuits::CF2;
+use crate::folding::traits::{CommittedInstanceOps, Inputize, WitnessOps};
+use crate::frontend::FCircuit;
+use crate::transcript::poseidon::poseidon_canonical_config;
+use crate::Error;
+use crate::{Decider as DeciderTrait, FoldingScheme};
+
+#[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Proof<C, S>
+where
+    C: CurveGroup,
+    S: SNARK<C::ScalarField>,
+{
+    snark_proof: S::Proof,
+    // IPA proof of the opening of the folded witness commitment at `challenge`
+    ipa_proof: AggregatedProof<C>,
+    L_X_evals: Vec<C::ScalarField>,
+    // the challenge is provided by the prover, but in-circuit it is checked to match the
+    // in-circuit computed one.
+    challenge: C::ScalarField,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifierParam<C1, S_VerifyingKey>
+where
+    C1: CurveGroup,
+    S_VerifyingKey: Clone + CanonicalSerialize + CanonicalDeserialize,
+{
+    pub pp_hash: C1::ScalarField,
+    pub snark_vp: S_VerifyingKey,
+    pub cs_vp: PedersenParams<C1>,
+}
+
+/// Offchain Decider, with a transparent opening of the folded witness commitment
+#[derive(Clone, Debug)]
+pub struct Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS, const K: usize = 1> {
+    _c1: PhantomData<C1>,
+    _gc1: PhantomData<GC1>,
+    _c2: PhantomData<C2>,
+    _gc2: PhantomData<GC2>,
+    _fc: PhantomData<FC>,
+    _cs1: PhantomData<CS1>,
+    _cs2: PhantomData<CS2>,
+    _s: PhantomData<S>,
+    _fs: PhantomData<FS>,
+}
+
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS, const K: usize> DeciderTrait<C1, C2, FC, FS>
+    for Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS, K>
+where
+    C1: CurveGroup,
+    GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+    C2: CurveGroup,
+    GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+    FC: FCircuit<C1::ScalarField>,
+    // CS1 is a Pedersen-like commitment, whose commitments are opened with the IPA
+    CS1: CommitmentScheme<
+        C1,
+        ProverParams = PedersenParams<C1>,
+        VerifierParams = PedersenParams<C1>,
+    >,
+    // CS2 is Pedersen commitment scheme, since the CycleFold commitments are checked in-circuit
+    CS2: CommitmentScheme<C2, ProverParams = PedersenParams<C2>>,
+    S: SNARK<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>,
+    <C1 as CurveGroup>::BaseField: PrimeField,
+    <C2 as CurveGroup>::BaseField: PrimeField,
+    <C1 as Group>::ScalarField: Absorb,
+    <C2 as Group>::ScalarField: Absorb,
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    // constrain FS into ProtoGalaxy, since this is a Decider specifically for ProtoGalaxy
+    ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2, K>: From<FS>,
+    crate::folding::protogalaxy::ProverParams<C1, C2, CS1, CS2>:
+        From<<FS as FoldingScheme<C1, C2, FC>>::ProverParam>,
+    crate::folding::protogalaxy::VerifierParams<C1, C2, CS1, CS2>:
+        From<<FS as FoldingScheme<C1, C2, FC>>::VerifierParam>,
+{
+    type PreprocessorParam = (FS::ProverParam, FS::VerifierParam);
+    type ProverParam = (S::ProvingKey, PedersenParams<C1>);
+    type Proof = Proof<C1, S>;
+    type VerifierParam = VerifierParam<C1, S::VerifyingKey>;
+    type PublicInput = Vec<C1::ScalarField>;
+    type CommittedInstance = Vec<C1>;
+
+    fn preprocess(
+        mut rng: impl RngCore + CryptoRng,
+        prep_param: Self::PreprocessorParam,
+        fs: FS,
+    ) -> Result<(Self::ProverParam, Self::VerifierParam), Error> {
+        let circuit = DeciderEthCircuit::<C1, C2, GC2>::try_from(ProtoGalaxy::<
+            C1,
+            GC1,
+            C2,
+            GC2,
+            FC,
+            CS1,
+            CS2,
+            K,
+        >::from(fs))?;
+
+        // get the SNARK specific setup for the circuit
+        let (snark_pk, snark_vk) = S::circuit_specific_setup(circuit, &mut rng)
+            .map_err(|e| Error::SNARKSetupFail(e.to_string()))?;
+
+        // get the FoldingScheme prover & verifier params from ProtoGalaxy
+        #[allow(clippy::type_complexity)]
+        let protogalaxy_pp: <ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2, K> as FoldingScheme<
+            C1,
+            C2,
+            FC,
+        >>::ProverParam = prep_param.0.clone().into();
+        #[allow(clippy::type_complexity)]
+        let protogalaxy_vp: <ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2, K> as FoldingScheme<
+            C1,
+            C2,
+            FC,
+        >>::VerifierParam = prep_param.1.clone().into();
+        let pp_hash = protogalaxy_vp.pp_hash()?;
+
+        let pp = (snark_pk, protogalaxy_pp.cs_params);
+        let vp = Self::VerifierParam {
+            pp_hash,
+            snark_vp: snark_vk,
+            cs_vp: protogalaxy_vp.cs_vp,
+        };
+        Ok((pp, vp))
+    }
+
+    fn prove(
+        mut rng: impl RngCore + CryptoRng,
+        pp: Self::ProverParam,
+        folding_scheme: FS,
+    ) -> Result<Self::Proof, Error> {
+        trace_span!("protogalaxy_decider_prove");
+        let (snark_pk, cs_pk): (S::ProvingKey, PedersenParams<C1>) = pp;
+
+        let protogalaxy: ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2, K> =
+            ProtoGalaxy::<C1, GC1, C2, GC2, FC, CS1, CS2, K>::from(folding_scheme);
+        let pp_hash = protogalaxy.pp_hash;
+        let circuit = DeciderEthCircuit::<C1, C2, GC2>::try_from(protogalaxy)?;
+
+        let L_X_evals = circuit.randomness.clone();
+
+        // get the challenge that has been already computed when preparing the circuit inputs in
+        // the above `try_from` call. There is a single commitment, so no batching is needed.
+        let challenge = circuit.kzg_challenge;
+
+        let cms = circuit.U_i1.get_commitments();
+        let (polys, blinds): (Vec<_>, Vec<_>) = circuit
+            .W_i1
+            .get_openings()
+            .into_iter()
+            .map(|(v, r)| (v.to_vec(), r))
+            .unzip();
+        let points = vec![challenge; cms.len()];
+
+        let mut transcript = Self::ipa_transcript(pp_hash);
+        let ipa_proof = IPA::<C1>::prove_aggregated(
+            &cs_pk,
+            &mut transcript,
+            &cms,
+            &polys,
+            &blinds,
+            &points,
+            None,
+        )?;
+
+        let snark_proof =
+            S::prove(&snark_pk, circuit, &mut rng).map_err(|e| Error::Other(e.to_string()))?;
+
+        Ok(Self::Proof {
+            snark_proof,
+            ipa_proof,
+            L_X_evals,
+            challenge,
+        })
+    }
+
+    fn verify(
+        vp: Self::VerifierParam,
+        i: C1::ScalarField,
+        z_0: Vec<C1::ScalarField>,
+        z_i: Vec<C1::ScalarField>,
+        // we don't use the instances at the verifier level, since we check them in-circuit
+        running_commitments: &Self::CommittedInstance,
+        incoming_commitments: &Self::CommittedInstance,
+        proof: &Self::Proof,
+    ) -> Result<bool, Error> {
+        trace_span!("protogalaxy_decider_verify");
+        if i <= C1::ScalarField::one() {
+            return Err(Error::NotEnoughSteps);
+        }
+
+        let Self::VerifierParam {
+            pp_hash,
+            snark_vp,
+            cs_vp,
+        } = vp;
+
+        // 6.2. Fold the commitments
+        let U_final_commitments = DeciderProtoGalaxyGadget::fold_group_elements_native(
+            running_commitments,
+            incoming_commitments,
+            None,
+            proof.L_X_evals.clone(),
+        )?;
+
+        let public_input = [
+            &[pp_hash, i][..],
+            &z_0,
+            &z_i,
+            &U_final_commitments
+                .iter()
+                .flat_map(|c| c.inputize())
+                .collect::<Vec<_>>(),
+            &[proof.challenge],
+            &proof.ipa_proof.evals,
+            &proof.L_X_evals,
+        ]
+        .concat();
+
+        let snark_v = S::verify(&snark_vp, &public_input, &proof.snark_proof)
+            .map_err(|e| Error::Other(e.to_string()))?;
+        if !snark_v {
+            return Err(Error::SNARKVerificationFail);
+        }
+
+        // 7.3. Verify the IPA proof of the opening of the folded commitment
+        let points = vec![proof.challenge; U_final_commitments.len()];
+        let mut transcript = Self::ipa_transcript(pp_hash);
+        IPA::<C1>::verify_aggregated(
+            &cs_vp,
+            &mut transcript,
+            &U_final_commitments,
+            &points,
+            &proof.ipa_proof,
+        )?;
+
+        Ok(true)
+    }
+}
+
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS, const K: usize>
+    Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS, K>
+where
+    C1: CurveGroup,
+    C1::ScalarField: Absorb,
+{
+    /// Returns the transcript used for the IPA opening proof, bound to the `pp_hash`.
+    fn ipa_transcript(pp_hash: C1::ScalarField) -> PoseidonSponge<C1::ScalarField> {
+        let poseidon_config = poseidon_canonical_config::<C1::ScalarField>();
+        let mut transcript = PoseidonSponge::<C1::ScalarField>::new(&poseidon_config);
+        transcript.absorb(&pp_hash);
+        transcript
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use ark_bn254::Bn254;
+    use ark_bn254::{constraints::GVar, Fr, G1Projective as Projective};
+    use ark_groth16::Groth16;
+    use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+    use std::time::Instant;
+
+    use super::*;
+    use crate::commitment::pedersen::Pedersen;
+    use crate::frontend::utils::CubicFCircuit;
+    use crate::transcript::poseidon::poseidon_canonical_config;
+
+    #[test]
+    fn test_decider() {
+        // use ProtoGalaxy as FoldingScheme
+        type PG = ProtoGalaxy<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+        >;
+        type D = Decider<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            Groth16<Bn254>, // here we define the Snark to use in the decider
+            PG,             // here we define the FoldingScheme to use
+        >;
+
+        let mut rng = rand::rngs::OsRng;
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let z_0 = vec![Fr::from(3_u32)];
+
+        let preprocessor_param = (poseidon_config, F_circuit);
+        let protogalaxy_params = PG::preprocess(&mut rng, &preprocessor_param).unwrap();
+
+        let start = Instant::now();
+        let mut protogalaxy = PG::init(&protogalaxy_params, F_circuit, z_0.clone()).unwrap();
+        println!("ProtoGalaxy initialized, {:?}", start.elapsed());
+        protogalaxy.prove_step(&mut rng, (), None).unwrap();
+        protogalaxy.prove_step(&mut rng, (), None).unwrap(); // do a 2nd step
+
+        // prepare the Decider prover & verifier params
+        let (decider_pp, decider_vp) =
+            D::preprocess(&mut rng, protogalaxy_params, protogalaxy.clone()).unwrap();
+
+        // decider proof generation
+        let start = Instant::now();
+        let proof = D::prove(rng, decider_pp, protogalaxy.clone()).unwrap();
+        println!("Decider prove, {:?}", start.elapsed());
+
+        // decider proof verification
+        let start = Instant::now();
+        let verified = D::verify(
+            decider_vp.clone(),
+            protogalaxy.i,
+            protogalaxy.z_0.clone(),
+            protogalaxy.z_i.clone(),
+            &protogalaxy.U_i.get_commitments(),
+            &protogalaxy.u_i.get_commitments(),
+            &proof,
+        )
+        .unwrap();
+        assert!(verified);
+        println!("Decider verify, {:?}", start.elapsed());
+
+        // serialize & deserialize the proof and verify it again
+        let mut proof_serialized = vec![];
+        proof.serialize_compressed(&mut proof_serialized).unwrap();
+        let proof_deserialized =
+            Proof::<Projective, Groth16<Bn254>>::deserialize_compressed(&proof_serialized[..])
+                .unwrap();
+        assert_eq!(proof, proof_deserialized);
+
+        // the proof does not verify for a different final state
+        let mut bad_proof = proof.clone();
+        bad_proof.ipa_proof.evals[0] += Fr::one();
+        assert!(D::verify(
+            decider_vp.clone(),
+            protogalaxy.i,
+            protogalaxy.z_0.clone(),
+            protogalaxy.z_i.clone(),
+            &protogalaxy.U_i.get_commitments(),
+            &protogalaxy.u_i.get_commitments(),
+            &bad_proof,
+        )
+        .is_err());
+        let mut bad_z_i = protogalaxy.z_i.clone();
+        bad_z_i[0] += Fr::one();
+        assert!(D::verify(
+            decider_vp,
+            protogalaxy.i,
+            protogalaxy.z_0,
+            bad_z_i,
+            &protogalaxy.U_i.get_commitments(),
+            &protogalaxy.u_i.get_commitments(),
+            &proof_deserialized,
+        )
+        .is_err());
+    }
+}
@@ -11,6 +11,8 @@ use ark_snark::SNARK;
 use ark_std::{
     marker::PhantomData,
     rand::{CryptoRng, RngCore},
+    string::ToString,
+    vec::Vec,
     One, Zero,
 };
 
@@ -21,13 +23,14 @@ use crate::commitment::{
     kzg::Proof as KZGProof, pedersen::Params as PedersenParams, CommitmentScheme,
 };
 use crate::folding::circuits::decider::DeciderEnabledNIFS;
+use crate::folding::circuits::nonnative::affine::nonnative_affine_to_packed_field_elements;
 use crate::folding::circuits::CF2;
-use crate::folding::traits::{Inputize, WitnessOps};
+use crate::folding::traits::WitnessOps;
 use crate::frontend::FCircuit;
 use crate::Error;
 use crate::{Decider as DeciderTrait, FoldingScheme};
 
-#[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+#[derive(Debug, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct Proof<C, CS, S>
 where
     C: CurveGroup,
@@ -42,6 +45,23 @@ where
     kzg_challenges: [C::ScalarField; 1],
 }
 
+// not derived, so that it does not require `S: Clone`, as in `nova::decider_eth::Proof`
+impl<C, CS, S> Clone for Proof<C, CS, S>
+where
+    C: CurveGroup,
+    CS: CommitmentScheme<C, ProverChallenge = C::ScalarField, Challenge = C::ScalarField>,
+    S: SNARK<C::ScalarField>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            snark_proof: self.snark_proof.clone(),
+            kzg_proofs: self.kzg_proofs.clone(),
+            L_X_evals: self.L_X_evals.clone(),
+            kzg_challenges: self.kzg_challenges,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct VerifierParam<C1, CS_VerifyingKey, S_VerifyingKey>
 where
@@ -56,7 +76,7 @@ where
 
 /// Onchain Decider, for ethereum use cases
 #[derive(Clone, Debug)]
-pub struct Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS> {
+pub struct Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS, const K: usize = 1> {
     _c1: PhantomData<C1>,
     _gc1: PhantomData<GC1>,
     _c2: PhantomData<C2>,
@@ -68,8 +88,8 @@ pub struct Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS> {
     _fs: PhantomData<FS>,
 }
 
-impl<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS> DeciderTrait<C1, C2, FC, FS>
-    for Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS>
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS, const K: usize> DeciderTrait<C1, C2, FC, FS>
+    for Decider<C1, GC1, C2, GC2, FC, CS1, CS2, S, FS, K>
 where
     C1: CurveGroup,
     GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
@@ -93,7 +113,7 @@ where
     <C2 as Group>::ScalarField: Absorb,
     C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
     // constrain FS into ProtoGalaxy, since this is a Decider specifically for ProtoGalaxy
-    ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2>: From<FS>,
+    ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2, K>: From<FS>,
     crate::folding::protogalaxy::ProverParams<C1, C2, CS1, CS2>:
         From<<FS as FoldingScheme<C1, C2, FC>>::ProverParam>,
     crate::folding::protogalaxy::VerifierParams<C1, C2, CS1, CS2>:
@@ -111,20 +131,30 @@ where
         prep_param: Self::PreprocessorParam,
         fs: FS,
     ) -> Result<(Self::ProverParam, Self::VerifierParam), Error> {
-        let circuit = DeciderEthCircuit::<C1, C2, GC2>::try_from(ProtoGalaxy::from(fs))?;
+        let circuit = DeciderEthCircuit::<C1, C2, GC2>::try_from(ProtoGalaxy::<
+            C1,
+            GC1,
+            C2,
+            GC2,
+            FC,
+            CS1,
+            CS2,
+            K,
+        >::from(fs))?;
 
         // get the Groth16 specific setup for the circuit
-        let (g16_pk, g16_vk) = S::circuit_specific_setup(circuit, &mut rng).unwrap();
+        let (g16_pk, g16_vk) = S::circuit_specific_setup(circuit, &mut rng)
+            .map_err(|e| Error::SNARKSetupFail(e.to_string()))?;
 
         // get the FoldingScheme prover & verifier params from ProtoGalaxy
         #[allow(clippy::type_complexity)]
-        let protogalaxy_pp: <ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2> as FoldingScheme<
+        let protogalaxy_pp: <ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2, K> as FoldingScheme<
             C1,
             C2,
             FC,
         >>::ProverParam = prep_param.0.clone().into();
         #[allow(clippy::type_complexity)]
-        let protogalaxy_vp: <ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2> as FoldingScheme<
+        let protogalaxy_vp: <ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2, K> as FoldingScheme<
             C1,
             C2,
             FC,
@@ -145,16 +175,25 @@ where
         pp: Self::ProverParam,
         folding_scheme: FS,
     ) -> Result<Self::Proof, Error> {
+        trace_span!("protogalaxy_decider_eth_prove");
         let (snark_pk, cs_pk): (S::ProvingKey, CS1::ProverParams) = pp;
 
-        let circuit =
-            DeciderEthCircuit::<C1, C2, GC2>::try_from(ProtoGalaxy::from(folding_scheme))?;
+        let circuit = DeciderEthCircuit::<C1, C2, GC2>::try_from(ProtoGalaxy::<
+            C1,
+            GC1,
+            C2,
+            GC2,
+            FC,
+            CS1,
+            CS2,
+            K,
+        >::from(folding_scheme))?;
 
         let L_X_evals = circuit.randomness.clone();
 
-        // get the challenges that have been already computed when preparing the circuit inputs in
-        // the above `try_from` call
-        let kzg_challenges = circuit.kzg_challenges.clone();
+        // get the challenge that has been already computed when preparing the circuit inputs in
+        // the above `try_from` call. There is a single commitment, so no batching is needed.
+        let kzg_challenges = vec![circuit.kzg_challenge];
 
         // generate KZG proofs
         let kzg_proofs = circuit
@@ -173,8 +212,12 @@ where
         Ok(Self::Proof {
             snark_proof,
             L_X_evals,
-            kzg_proofs: kzg_proofs.try_into().unwrap(),
-            kzg_challenges: kzg_challenges.try_into().unwrap(),
+            kzg_proofs: kzg_proofs
+                .try_into()
+                .map_err(|e: Vec<_>| Error::NotExpectedLength(e.len(), 1))?,
+            kzg_challenges: kzg_challenges
+                .try_into()
+                .map_err(|e: Vec<_>| Error::NotExpectedLength(e.len(), 1))?,
         })
     }
 
@@ -188,6 +231,7 @@ where
         incoming_commitments: &Self::CommittedInstance,
         proof: &Self::Proof,
     ) -> Result<bool, Error> {
+        trace_span!("protogalaxy_decider_eth_verify");
         if i <= C1::ScalarField::one() {
             return Err(Error::NotEnoughSteps);
         }
@@ -212,7 +256,7 @@ where
             &z_i,
             &U_final_commitments
                 .iter()
-                .flat_map(|c| c.inputize())
+                .flat_map(|&c| nonnative_affine_to_packed_field_elements(c))
                 .collect::<Vec<_>>(),
             &proof.kzg_challenges,
             &proof.kzg_proofs.iter().map(|p| p.eval).collect::<Vec<_>>(),
@@ -252,6 +296,7 @@ pub mod tests {
     use crate::commitment::kzg::KZG;
     use crate::commitment::pedersen::Pedersen;
     use crate::folding::protogalaxy::ProverParams;
+    use crate::folding::tests::test_corrupted_decider_proof;
     use crate::folding::traits::CommittedInstanceOps;
     use crate::frontend::utils::CubicFCircuit;
     use crate::transcript::poseidon::poseidon_canonical_config;
@@ -292,8 +337,8 @@ pub mod tests {
         let start = Instant::now();
         let mut protogalaxy = PG::init(&protogalaxy_params, F_circuit, z_0.clone()).unwrap();
         println!("ProtoGalaxy initialized, {:?}", start.elapsed());
-        protogalaxy.prove_step(&mut rng, vec![], None).unwrap();
-        protogalaxy.prove_step(&mut rng, vec![], None).unwrap(); // do a 2nd step
+        protogalaxy.prove_step(&mut rng, (), None).unwrap();
+        protogalaxy.prove_step(&mut rng, (), None).unwrap(); // do a 2nd step
 
         // prepare the Decider prover & verifier params
         let (decider_pp, decider_vp) =
@@ -320,6 +365,94 @@ pub mod tests {
         println!("Decider verify, {:?}", start.elapsed());
 
         // decider proof verification using the deserialized data
+        let verified = D::verify(
+            decider_vp.clone(),
+            protogalaxy.i,
+            protogalaxy.z_0.clone(),
+            protogalaxy.z_i.clone(),
+            &protogalaxy.U_i.get_commitments(),
+            &protogalaxy.u_i.get_commitments(),
+            &proof,
+        )
+        .unwrap();
+        assert!(verified);
+
+        // corrupted proofs and statements are rejected with an error instead of panicking
+        let corruptions: Vec<fn(&mut Proof<Projective, KZG<'static, Bn254>, Groth16<Bn254>>)> = vec![
+            |p| p.snark_proof.c = p.snark_proof.a,
+            |p| p.kzg_proofs[0].eval += Fr::one(),
+            |p| p.kzg_proofs[0].proof += Projective::generator(),
+            |p| p.kzg_challenges[0] += Fr::one(),
+            |p| p.L_X_evals[0] += Fr::one(),
+            |p| {
+                p.L_X_evals.pop();
+            },
+            |p| p.L_X_evals.push(Fr::one()),
+        ];
+        test_corrupted_decider_proof::<Projective, Projective2, CubicFCircuit<Fr>, PG, D>(
+            decider_vp,
+            protogalaxy.i,
+            protogalaxy.z_0,
+            protogalaxy.z_i,
+            protogalaxy.U_i.get_commitments(),
+            protogalaxy.u_i.get_commitments(),
+            &proof,
+            corruptions,
+        );
+    }
+
+    /// Tests the Decider on a ProtoGalaxy instance that folds `K = 3` incoming instances at each
+    /// step. The Decider only folds the last incoming instance, so it is independent of `K`.
+    #[test]
+    fn test_decider_multi_instances() {
+        const K: usize = 3;
+        type PG = ProtoGalaxy<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            KZG<'static, Bn254>,
+            Pedersen<Projective2>,
+            K,
+        >;
+        type D = Decider<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            KZG<'static, Bn254>,
+            Pedersen<Projective2>,
+            Groth16<Bn254>,
+            PG,
+            K,
+        >;
+
+        let mut rng = rand::rngs::OsRng;
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let z_0 = vec![Fr::from(3_u32)];
+
+        let preprocessor_param = (poseidon_config, F_circuit);
+        let protogalaxy_params = PG::preprocess(&mut rng, &preprocessor_param).unwrap();
+
+        let mut protogalaxy = PG::init(&protogalaxy_params, F_circuit, z_0).unwrap();
+        for i in 0..2 {
+            let other_instances = (0..K - 1)
+                .map(|j| protogalaxy.new_incoming_instance(vec![Fr::from((i * K + j) as u32)], ()))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            protogalaxy
+                .prove_step(&mut rng, (), Some(other_instances))
+                .unwrap();
+        }
+
+        let (decider_pp, decider_vp) =
+            D::preprocess(&mut rng, protogalaxy_params, protogalaxy.clone()).unwrap();
+        let proof = D::prove(rng, decider_pp, protogalaxy.clone()).unwrap();
+
         let verified = D::verify(
             decider_vp,
             protogalaxy.i,
@@ -372,8 +505,8 @@ pub mod tests {
         let start = Instant::now();
         let mut protogalaxy = PG::init(&protogalaxy_params, F_circuit, z_0.clone()).unwrap();
         println!("ProtoGalaxy initialized, {:?}", start.elapsed());
-        protogalaxy.prove_step(&mut rng, vec![], None).unwrap();
-        protogalaxy.prove_step(&mut rng, vec![], None).unwrap(); // do a 2nd step
+        protogalaxy.prove_step(&mut rng, (), None).unwrap();
+        protogalaxy.prove_step(&mut rng, (), None).unwrap(); // do a 2nd step
 
         // prepare the Decider prover & verifier params
         let (decider_pp, decider_vp) =
@@ -418,9 +551,9 @@ pub mod tests {
         let mut protogalaxy = PG::init(&protogalaxy_params, F_circuit, z_0).unwrap();
 
         let start = Instant::now();
-        protogalaxy.prove_step(&mut rng, vec![], None).unwrap();
+        protogalaxy.prove_step(&mut rng, (), None).unwrap();
         println!("prove_step, {:?}", start.elapsed());
-        protogalaxy.prove_step(&mut rng, vec![], None).unwrap(); // do a 2nd step
+        protogalaxy.prove_step(&mut rng, (), None).unwrap(); // do a 2nd step
 
         // decider proof generation
         let start = Instant::now();
@@ -15,7 +15,7 @@ use ark_r1cs_std::{
     ToConstraintFieldGadget,
 };
 use ark_relations::r1cs::{Namespace, SynthesisError};
-use ark_std::{borrow::Borrow, marker::PhantomData};
+use ark_std::{borrow::Borrow, marker::PhantomData, vec::Vec};
 
 use crate::{
     arith::r1cs::{circuits::R1CSMatricesVar, R1CS},
@@ -28,9 +28,10 @@ use crate::{
             },
             CF1, CF2,
         },
-        traits::{WitnessOps, WitnessVarOps},
+        traits::{CommittedInstanceOps, WitnessOps, WitnessVarOps},
     },
     frontend::FCircuit,
+    utils::vec::check_len,
     Error,
 };
 
@@ -93,13 +94,16 @@ impl<
         CS1: CommitmentScheme<C1, false>,
         // enforce that the CS2 is Pedersen commitment scheme, since we're at Ethereum's EVM decider
         CS2: CommitmentScheme<C2, false, ProverParams = PedersenParams<C2>>,
-    > TryFrom<ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2>> for DeciderEthCircuit<C1, C2, GC2>
+        const K: usize,
+    > TryFrom<ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2, K>> for DeciderEthCircuit<C1, C2, GC2>
 where
     CF1<C1>: Absorb,
 {
     type Error = Error;
 
-    fn try_from(protogalaxy: ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2>) -> Result<Self, Error> {
+    fn try_from(
+        protogalaxy: ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2, K>,
+    ) -> Result<Self, Error> {
         let mut transcript = PoseidonSponge::<C1::ScalarField>::new(&protogalaxy.poseidon_config);
 
         let (U_i1, W_i1, proof, aux) = Folding::prove(
@@ -111,17 +115,24 @@ where
             &[protogalaxy.w_i.clone()],
         )?;
 
-        // compute the KZG challenges used as inputs in the circuit
-        let kzg_challenges = KZGChallengesGadget::get_challenges_native(&mut transcript, &U_i1);
+        // compute the KZG challenge used as input in the circuit
+        let kzg_challenge = KZGChallengesGadget::get_challenge_native(
+            &mut transcript,
+            &U_i1.get_commitment_labels(),
+            &U_i1,
+        )?;
 
         // get KZG evals
         let kzg_evaluations = W_i1
             .get_openings()
             .iter()
-            .zip(&kzg_challenges)
-            .map(|((v, _), &c)| EvalGadget::evaluate_native(v, c))
+            .map(|(v, _)| EvalGadget::evaluate_native(v, kzg_challenge))
             .collect::<Result<Vec<_>, _>>()?;
 
+        // compute the challenge used to batch the KZG openings
+        let kzg_batching_challenge =
+            KZGChallengesGadget::get_batching_challenge_native(&mut transcript, &kzg_evaluations);
+
         Ok(Self {
             _gc2: PhantomData,
             _avar: PhantomData,
@@ -130,9 +141,11 @@ where
             cf_pedersen_params: protogalaxy.cf_cs_params,
             poseidon_config: protogalaxy.poseidon_config,
             pp_hash: protogalaxy.pp_hash,
+            context: None,
             i: protogalaxy.i,
             z_0: protogalaxy.z_0,
             z_i: protogalaxy.z_i,
+            hash_state: false,
             U_i: protogalaxy.U_i,
             W_i: protogalaxy.W_i,
             u_i: protogalaxy.u_i,
@@ -143,8 +156,11 @@ where
             randomness: aux.L_X_evals,
             cf_U_i: protogalaxy.cf_U_i,
             cf_W_i: protogalaxy.cf_W_i,
-            kzg_challenges,
+            cf_blinding: None,
+            kzg_challenge,
             kzg_evaluations,
+            kzg_batching_challenge,
+            opening_point: None,
         })
     }
 }
@@ -193,6 +209,9 @@ impl<C: CurveGroup>
         _: Option<Self::Proof>,
         L_X_evals: Self::Randomness,
     ) -> Result<Vec<C>, Error> {
+        check_len(U_commitments.len(), 1)?;
+        check_len(u_commitments.len(), 1)?;
+        check_len(L_X_evals.len(), 2)?;
         let U_phi = U_commitments[0];
         let u_phi = u_commitments[0];
         Ok(vec![U_phi * L_X_evals[0] + u_phi * L_X_evals[1]])
@@ -233,7 +252,7 @@ pub mod tests {
 
         // generate a Nova instance and do a step of it
         let mut protogalaxy = PG::init(&pg_params, F_circuit, z_0.clone()).unwrap();
-        protogalaxy.prove_step(&mut rng, vec![], None).unwrap();
+        protogalaxy.prove_step(&mut rng, (), None).unwrap();
 
         let ivc_proof = protogalaxy.ivc_proof();
         PG::verify(pg_params.1, ivc_proof).unwrap();
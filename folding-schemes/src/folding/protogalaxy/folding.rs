@@ -6,11 +6,13 @@ use ark_poly::{
     univariate::{DensePolynomial, SparsePolynomial},
     DenseUVPolynomial, EvaluationDomain, Evaluations, GeneralEvaluationDomain, Polynomial,
 };
-use ark_std::{cfg_into_iter, log2, One, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::marker::PhantomData;
+use ark_std::{cfg_chunks, cfg_into_iter, cfg_iter, log2, string::ToString, vec::Vec, One, Zero};
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
-use std::marker::PhantomData;
 
-use super::utils::{all_powers, betas_star, exponential_powers, pow_i};
+use super::utils::{all_powers, betas_star, exponential_powers, pow_i_table};
 use super::ProtoGalaxyError;
 use super::{CommittedInstance, Witness};
 
@@ -20,7 +22,7 @@ use crate::transcript::Transcript;
 use crate::utils::vec::*;
 use crate::Error;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct ProtoGalaxyProof<F: PrimeField> {
     pub F_coeffs: Vec<F>,
     pub K_coeffs: Vec<F>,
@@ -127,7 +129,7 @@ where
 
         // F(X)
         let F_X: SparsePolynomial<C::ScalarField> =
-            calc_f_from_btree(&f_z, &instance.betas, &deltas).expect("Error calculating F[x]");
+            calc_f_from_btree(&f_z, &instance.betas, &deltas)?;
         let F_X_dense = DensePolynomial::from(F_X.clone());
         let mut F_coeffs = F_X_dense.coeffs;
         F_coeffs.resize(t, C::ScalarField::zero());
@@ -157,7 +159,7 @@ where
             )?;
         }
 
-        let zs: Vec<Vec<C::ScalarField>> = std::iter::once(z.clone())
+        let zs: Vec<Vec<C::ScalarField>> = ark_std::iter::once(z)
             .chain(
                 vec_w
                     .iter()
@@ -184,27 +186,29 @@ where
             .ok_or(Error::NewDomainFail)?;
         let L_X: Vec<DensePolynomial<C::ScalarField>> = lagrange_polys(H);
 
+        // pow_i(betas*) for all the rows, computed once and shared by all the evaluations of G(X)
+        let pows = pow_i_table(&betas_star);
+
         // K(X) computation in a naive way, next iterations will compute K(X) as described in Claim
         // 4.5 of the paper.
-        let mut G_evals: Vec<C::ScalarField> = vec![C::ScalarField::zero(); G_domain.size()];
-        for (hi, h) in G_domain.elements().enumerate() {
-            // each iteration evaluates G(h)
-            // inner = L_0(x) * z + \sum_k L_i(x) * z_j
-            let mut inner: Vec<C::ScalarField> = vec![C::ScalarField::zero(); zs[0].len()];
-            for (z, L) in zs.iter().zip(&L_X) {
-                // Li_z_h = (Li(X)*zj)(h) = Li(h) * zj
-                let Lh = L.evaluate(&h);
-                for (j, zj) in z.iter().enumerate() {
-                    inner[j] += Lh * zj;
-                }
-            }
-            let f_ev = r1cs.eval_at_z(&inner)?;
-
-            G_evals[hi] = cfg_into_iter!(f_ev)
-                .enumerate()
-                .map(|(i, f_ev_i)| pow_i(i, &betas_star) * f_ev_i)
-                .sum();
-        }
+        // The evaluations of G(X) over `G_domain` are independent of each other, so they are
+        // computed in parallel, as well as the row sums inside each of them.
+        let G_evals = cfg_into_iter!(G_domain.elements().collect::<Vec<_>>())
+            .map(|h| {
+                // each iteration evaluates G(h)
+                // inner = L_0(h) * z + \sum_j L_j(h) * z_j
+                let L_h = L_X.iter().map(|L| L.evaluate(&h)).collect::<Vec<_>>();
+                let inner = cfg_into_iter!(0..n)
+                    .map(|i| zs.iter().zip(&L_h).map(|(z, L_h)| *L_h * z[i]).sum())
+                    .collect::<Vec<C::ScalarField>>();
+                let f_ev = r1cs.eval_at_z(&inner)?;
+
+                Ok(cfg_into_iter!(f_ev)
+                    .zip(cfg_iter!(pows))
+                    .map(|(f_ev_i, pow_i)| *pow_i * f_ev_i)
+                    .sum())
+            })
+            .collect::<Result<Vec<C::ScalarField>, Error>>()?;
         let G_X: DensePolynomial<C::ScalarField> =
             Evaluations::<C::ScalarField>::from_vec_and_domain(G_evals, G_domain).interpolate();
         let Z_X: DensePolynomial<C::ScalarField> = H.vanishing_polynomial().into();
@@ -280,8 +284,28 @@ where
         // polys from P
         proof: ProtoGalaxyProof<C::ScalarField>,
     ) -> Result<CommittedInstance<C, true>, Error> {
+        let d = 2; // for the moment hardcoded to 2 since it only supports R1CS
+        let k = vec_instances.len();
         let t = instance.betas.len();
 
+        // the proof may come from untrusted bytes, check its dimensions before using it
+        if proof.F_coeffs.len() != t {
+            return Err(Error::NotSameLength(
+                "proof.F_coeffs.len()".to_string(),
+                proof.F_coeffs.len(),
+                "instance.betas.len()".to_string(),
+                t,
+            ));
+        }
+        if proof.K_coeffs.len() != d * k + 1 {
+            return Err(Error::NotSameLength(
+                "proof.K_coeffs.len()".to_string(),
+                proof.K_coeffs.len(),
+                "d * k + 1".to_string(),
+                d * k + 1,
+            ));
+        }
+
         // absorb the committed instances
         transcript.absorb(instance);
         transcript.absorb(&vec_instances);
@@ -304,7 +328,6 @@ where
 
         transcript.absorb(&proof.K_coeffs);
 
-        let k = vec_instances.len();
         let H =
             GeneralEvaluationDomain::<C::ScalarField>::new(k + 1).ok_or(Error::NewDomainFail)?;
         let L_X: Vec<DensePolynomial<C::ScalarField>> = lagrange_polys(H);
@@ -365,31 +388,23 @@ fn calc_f_from_btree<F: PrimeField>(
         )));
     }
 
-    let mut layers: Vec<Vec<SparsePolynomial<F>>> = Vec::new();
-    let leaves: Vec<SparsePolynomial<F>> = fw
-        .iter()
-        .copied()
-        .map(|e| SparsePolynomial::<F>::from_coefficients_slice(&[(0, e)]))
+    let mut currentNodes: Vec<SparsePolynomial<F>> = cfg_iter!(fw)
+        .map(|e| SparsePolynomial::<F>::from_coefficients_slice(&[(0, *e)]))
         .collect();
-    layers.push(leaves.to_vec());
-    let mut currentNodes = leaves.clone();
+    // each layer of the tree only depends on the previous one, and its nodes are computed in
+    // parallel
+    let mut layer = 0;
     while currentNodes.len() > 1 {
-        let index = layers.len();
-        layers.push(vec![]);
-        for (i, ni) in currentNodes.iter().enumerate().step_by(2) {
-            let left = ni.clone();
-            let right = SparsePolynomial::<F>::from_coefficients_vec(vec![
-                (0, betas[layers.len() - 2]),
-                (1, deltas[layers.len() - 2]),
-            ])
-            .mul(&currentNodes[i + 1]);
-
-            layers[index].push(left + right);
-        }
-        currentNodes = layers[index].clone();
+        let beta_delta = SparsePolynomial::<F>::from_coefficients_vec(vec![
+            (0, betas[layer]),
+            (1, deltas[layer]),
+        ]);
+        currentNodes = cfg_chunks!(currentNodes, 2)
+            .map(|nodes| nodes[0].clone() + beta_delta.mul(&nodes[1]))
+            .collect();
+        layer += 1;
     }
-    let root_index = layers.len() - 1;
-    Ok(layers[root_index][0].clone())
+    Ok(currentNodes.swap_remove(0))
 }
 
 // lagrange_polys method from caulk: https://github.com/caulk-crypto/caulk/tree/8210b51fb8a9eef4335505d1695c44ddc7bf8170/src/multi/setup.rs#L300
@@ -417,6 +432,7 @@ pub mod tests {
     use crate::arith::r1cs::tests::{get_test_r1cs, get_test_z_split};
     use crate::arith::Arith;
     use crate::commitment::{pedersen::Pedersen, CommitmentScheme};
+    use crate::folding::protogalaxy::utils::pow_i;
     use crate::transcript::poseidon::poseidon_canonical_config;
 
     #[test]
@@ -512,9 +528,32 @@ pub mod tests {
         )
         .unwrap();
 
+        // the proof survives a serialization round trip
+        let mut bytes = vec![];
+        proof.serialize_compressed(&mut bytes).unwrap();
+        let proof_deserialized =
+            ProtoGalaxyProof::<Fr>::deserialize_compressed(bytes.as_slice()).unwrap();
+        assert_eq!(proof, proof_deserialized);
+
+        // proofs with wrong dimensions are rejected
+        let mut bad_proof = proof.clone();
+        bad_proof.K_coeffs.pop();
+        assert!(Folding::<Projective>::verify(
+            &mut PoseidonSponge::<Fr>::new(&poseidon_config),
+            &instance,
+            &instances,
+            bad_proof
+        )
+        .is_err());
+
         // verifier
-        let folded_instance_v =
-            Folding::<Projective>::verify(&mut transcript_v, &instance, &instances, proof).unwrap();
+        let folded_instance_v = Folding::<Projective>::verify(
+            &mut transcript_v,
+            &instance,
+            &instances,
+            proof_deserialized,
+        )
+        .unwrap();
 
         // check that prover & verifier folded instances are the same values
         assert_eq!(folded_instance.phi, folded_instance_v.phi);
@@ -527,6 +566,59 @@ pub mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn test_calc_f_from_btree() {
+        let mut rng = ark_std::test_rng();
+        for t in 1..8 {
+            let fw = (0..1 << t).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+            let betas = (0..t).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+            let deltas = (0..t).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+
+            let F_X = calc_f_from_btree(&fw, &betas, &deltas).unwrap();
+
+            // F(x) = \sum_i pow_i(betas + x * deltas) * fw_i
+            let x = Fr::rand(&mut rng);
+            let betas_x = betas_star(&betas, &deltas, x);
+            let expected: Fr = fw
+                .iter()
+                .enumerate()
+                .map(|(i, fw_i)| pow_i(i, &betas_x) * fw_i)
+                .sum();
+            assert_eq!(F_X.evaluate(&x), expected);
+        }
+    }
+
+    // the prover's interaction with the transcript does not depend on how the work is scheduled,
+    // so two runs from the same transcript state produce the same proof and leave the transcript
+    // in the same state
+    #[test]
+    fn test_fold_deterministic() {
+        let k = 7;
+        let (witness, instance, witnesses, instances) = prepare_inputs(k);
+        let r1cs = get_test_r1cs::<Fr>();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let mut transcripts = vec![];
+        let mut proofs = vec![];
+        for _ in 0..2 {
+            let mut transcript_p = PoseidonSponge::<Fr>::new(&poseidon_config);
+            transcript_p.absorb(&Fr::from(42_u32));
+            let (_, _, proof, _) = Folding::<Projective>::prove(
+                &mut transcript_p,
+                &r1cs,
+                &instance,
+                &witness,
+                &instances,
+                &witnesses,
+            )
+            .unwrap();
+            transcripts.push(transcript_p.get_challenge());
+            proofs.push(proof);
+        }
+        assert_eq!(proofs[0], proofs[1]);
+        assert_eq!(transcripts[0], transcripts[1]);
+    }
+
     #[test]
     fn test_fold_various_iterations() {
         let r1cs = get_test_r1cs::<Fr>();
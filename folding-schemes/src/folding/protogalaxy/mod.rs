@@ -17,7 +17,8 @@ use ark_relations::r1cs::{
 };
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Valid};
 use ark_std::{
-    borrow::Borrow, cmp::max, fmt::Debug, log2, marker::PhantomData, rand::RngCore, One, Zero,
+    borrow::Borrow, cmp::max, fmt::Debug, log2, marker::PhantomData, rand::RngCore,
+    string::ToString, vec::Vec, One, Zero,
 };
 use constants::{INCOMING, RUNNING};
 use num_bigint::BigUint;
@@ -27,7 +28,12 @@ use crate::{
         r1cs::{extract_r1cs, extract_w_x, R1CS},
         Arith,
     },
-    commitment::CommitmentScheme,
+    commitment::{
+        versioned::{
+            deserialize_params, into_serialization_error, params_serialized_size, serialize_params,
+        },
+        CommitmentScheme,
+    },
     folding::circuits::{
         cyclefold::{
             fold_cyclefold_circuit, CycleFoldCircuit, CycleFoldCommittedInstance, CycleFoldConfig,
@@ -36,19 +42,23 @@ use crate::{
         nonnative::affine::NonNativeAffineVar,
         CF1, CF2,
     },
-    frontend::{utils::DummyCircuit, FCircuit},
+    frontend::{
+        check_no_custom_gates, check_state, check_state_len, check_step_constants,
+        check_step_constants_row, step_constraints, utils::DummyCircuit, FCircuit,
+    },
     transcript::poseidon::poseidon_canonical_config,
-    utils::{get_cm_coordinates, pp_hash},
-    Error, FoldingScheme,
+    utils::{deterministic_rng, pp_hash, vec::check_len},
+    Error, FoldingScheme, FoldingState,
 };
 
 pub mod circuits;
 pub mod constants;
+pub mod decider;
 pub mod decider_eth;
 pub mod decider_eth_circuit;
 pub mod folding;
 pub mod traits;
-pub(crate) mod utils;
+pub mod utils;
 
 use circuits::AugmentedFCircuit;
 use folding::Folding;
@@ -80,10 +90,10 @@ pub type ProtoGalaxyCycleFoldCircuit<C, GC> = CycleFoldCircuit<ProtoGalaxyCycleF
 /// behaviors (e.g., in satisfiability checks).
 #[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct CommittedInstance<C: CurveGroup, const TYPE: bool> {
-    phi: C,
-    betas: Vec<C::ScalarField>,
-    e: C::ScalarField,
-    x: Vec<C::ScalarField>,
+    pub(crate) phi: C,
+    pub(crate) betas: Vec<C::ScalarField>,
+    pub(crate) e: C::ScalarField,
+    pub(crate) x: Vec<C::ScalarField>,
 }
 
 impl<C: CurveGroup, const TYPE: bool> Dummy<(usize, usize)> for CommittedInstance<C, TYPE> {
@@ -100,6 +110,35 @@ impl<C: CurveGroup, const TYPE: bool> Dummy<(usize, usize)> for CommittedInstanc
     }
 }
 
+impl<C: CurveGroup, const TYPE: bool> CommittedInstance<C, TYPE> {
+    /// Checks that the vectors of the instance have the lengths expected by the given R1CS, which
+    /// is needed for instances coming from untrusted sources such as deserialized bytes.
+    pub fn check_dimensions(&self, r1cs: &R1CS<CF1<C>>) -> Result<(), Error> {
+        if self.x.len() != r1cs.num_public_inputs() {
+            return Err(Error::NotSameLength(
+                "x.len()".to_string(),
+                self.x.len(),
+                "r1cs.num_public_inputs()".to_string(),
+                r1cs.num_public_inputs(),
+            ));
+        }
+        let t = if TYPE == RUNNING {
+            log2(r1cs.num_constraints()) as usize
+        } else {
+            0
+        };
+        if self.betas.len() != t {
+            return Err(Error::NotSameLength(
+                "betas.len()".to_string(),
+                self.betas.len(),
+                "log2(r1cs.num_constraints())".to_string(),
+                t,
+            ));
+        }
+        Ok(())
+    }
+}
+
 impl<C: CurveGroup, const TYPE: bool> Dummy<&R1CS<CF1<C>>> for CommittedInstance<C, TYPE> {
     fn dummy(r1cs: &R1CS<CF1<C>>) -> Self {
         let t = if TYPE == RUNNING {
@@ -118,6 +157,10 @@ impl<C: CurveGroup, const TYPE: bool> CommittedInstanceOps<C> for CommittedInsta
         vec![self.phi]
     }
 
+    fn get_commitment_labels(&self) -> Vec<&'static [u8]> {
+        vec![b"phi"]
+    }
+
     fn is_incoming(&self) -> bool {
         TYPE == INCOMING
     }
@@ -198,6 +241,10 @@ impl<C: CurveGroup, const TYPE: bool> CommittedInstanceVarOps<C> for CommittedIn
         vec![self.phi.clone()]
     }
 
+    fn get_commitment_labels(&self) -> Vec<&'static [u8]> {
+        vec![b"phi"]
+    }
+
     fn get_public_inputs(&self) -> &[FpVar<CF1<C>>] {
         &self.x
     }
@@ -221,8 +268,8 @@ impl<C: CurveGroup, const TYPE: bool> CommittedInstanceVarOps<C> for CommittedIn
 
 #[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct Witness<F: PrimeField> {
-    w: Vec<F>,
-    r_w: F,
+    pub(crate) w: Vec<F>,
+    pub(crate) r_w: F,
 }
 
 impl<F: PrimeField> Witness<F> {
@@ -246,6 +293,19 @@ impl<F: PrimeField> Witness<F> {
             betas: vec![],
         })
     }
+
+    /// Checks that the witness has the length expected by the given R1CS.
+    pub fn check_dimensions(&self, r1cs: &R1CS<F>) -> Result<(), Error> {
+        if self.w.len() != r1cs.num_witnesses() {
+            return Err(Error::NotSameLength(
+                "w.len()".to_string(),
+                self.w.len(),
+                "r1cs.num_witnesses()".to_string(),
+                r1cs.num_witnesses(),
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl<F: PrimeField> Dummy<&R1CS<F>> for Witness<F> {
@@ -332,17 +392,16 @@ where
     CS1: CommitmentScheme<C1, false>,
     CS2: CommitmentScheme<C2, false>,
 {
-    fn serialize_with_mode<W: std::io::prelude::Write>(
+    fn serialize_with_mode<W: ark_std::io::Write>(
         &self,
-        mut writer: W,
+        writer: W,
         compress: ark_serialize::Compress,
     ) -> Result<(), ark_serialize::SerializationError> {
-        self.cs_params.serialize_with_mode(&mut writer, compress)?;
-        self.cf_cs_params.serialize_with_mode(&mut writer, compress)
+        serialize_params::<C1, C2, _>(writer, compress, &self.cs_params, &self.cf_cs_params)
     }
 
     fn serialized_size(&self, compress: ark_serialize::Compress) -> usize {
-        self.cs_params.serialized_size(compress) + self.cf_cs_params.serialized_size(compress)
+        params_serialized_size::<C1, C2>(compress, &self.cs_params, &self.cf_cs_params)
     }
 }
 impl<C1, C2, CS1, CS2> Valid for ProverParams<C1, C2, CS1, CS2>
@@ -372,14 +431,14 @@ where
     CS1: CommitmentScheme<C1, false>,
     CS2: CommitmentScheme<C2, false>,
 {
-    fn deserialize_with_mode<R: std::io::prelude::Read>(
-        mut reader: R,
+    fn deserialize_with_mode<R: ark_std::io::Read>(
+        reader: R,
         compress: ark_serialize::Compress,
         validate: ark_serialize::Validate,
     ) -> Result<Self, ark_serialize::SerializationError> {
-        let cs_params = CS1::ProverParams::deserialize_with_mode(&mut reader, compress, validate)?;
-        let cf_cs_params =
-            CS2::ProverParams::deserialize_with_mode(&mut reader, compress, validate)?;
+        let (cs_params, cf_cs_params) =
+            deserialize_params::<C1, C2, _, _, _>(reader, compress, validate)
+                .map_err(into_serialization_error)?;
         Ok(ProverParams {
             poseidon_config: poseidon_canonical_config::<C1::ScalarField>(),
             cs_params,
@@ -407,6 +466,10 @@ where
     pub cs_vp: CS1::VerifierParams,
     /// Verification parameters of the underlying commitment scheme over C2
     pub cf_cs_vp: CS2::VerifierParams,
+    /// Length of the state of the folded FCircuit
+    pub state_len: usize,
+    /// Table of per-step constants of the folded FCircuit
+    pub step_constants: Vec<Vec<C1::ScalarField>>,
 }
 
 impl<C1, C2, CS1, CS2> Valid for VerifierParams<C1, C2, CS1, CS2>
@@ -429,17 +492,16 @@ where
     CS1: CommitmentScheme<C1>,
     CS2: CommitmentScheme<C2>,
 {
-    fn serialize_with_mode<W: std::io::prelude::Write>(
+    fn serialize_with_mode<W: ark_std::io::Write>(
         &self,
-        mut writer: W,
+        writer: W,
         compress: ark_serialize::Compress,
     ) -> Result<(), ark_serialize::SerializationError> {
-        self.cs_vp.serialize_with_mode(&mut writer, compress)?;
-        self.cf_cs_vp.serialize_with_mode(&mut writer, compress)
+        serialize_params::<C1, C2, _>(writer, compress, &self.cs_vp, &self.cf_cs_vp)
     }
 
     fn serialized_size(&self, compress: ark_serialize::Compress) -> usize {
-        self.cs_vp.serialized_size(compress) + self.cf_cs_vp.serialized_size(compress)
+        params_serialized_size::<C1, C2>(compress, &self.cs_vp, &self.cf_cs_vp)
     }
 }
 
@@ -461,6 +523,10 @@ where
             &self.cs_vp,
             &self.cf_cs_vp,
             &self.poseidon_config,
+            None,
+            ProtoGalaxyCycleFoldConfig::<C1>::CHALLENGE_BIT_LENGTH,
+            self.state_len,
+            &self.step_constants,
         )
     }
 }
@@ -487,8 +553,12 @@ where
 ///
 /// [ProtoGalaxy]: https://eprint.iacr.org/2023/1106.pdf
 /// [CycleFold]: https://eprint.iacr.org/2023/1192.pdf
+///
+/// Generic parameters:
+/// * `K` - the number of incoming instances folded into the running instance
+///   at each step, `K + 1` must be a power of two
 #[derive(Clone, Debug)]
-pub struct ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2>
+pub struct ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2, const K: usize = 1>
 where
     C1: CurveGroup,
     GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
@@ -530,7 +600,7 @@ where
     pub cf_U_i: CycleFoldCommittedInstance<C2>,
 }
 
-impl<C1, GC1, C2, GC2, FC, CS1, CS2> ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2>
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, const K: usize> ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2, K>
 where
     C1: CurveGroup,
     GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
@@ -570,26 +640,27 @@ where
         // For `t_lower_bound`, we configure `F'` with `t = 1` and compute log2
         // of the size of `F'`.
         let state_len = F.state_len();
-        let external_inputs_len = F.external_inputs_len();
 
         // `F'` includes `F` and `ProtoGalaxy.V`, where `F` might be costly.
         // Observing that the cost of `F` is constant with respect to `t`, we
-        // separately compute `step_constraints`, the size of `F`.
+        // separately compute `step_constraints`, the size of `F` (including
+        // the allocation of its external inputs).
         // Later, we only need to re-run the rest of `F'` with updated `t` to
         // get the size of `F'`.
         let cs = ConstraintSystem::<C1::ScalarField>::new_ref();
-        F.generate_step_constraints(
+        step_constraints(
+            F,
             cs.clone(),
             0,
+            &FpVar::new_witness(cs.clone(), || Ok(C1::ScalarField::zero()))?,
             Vec::new_witness(cs.clone(), || Ok(vec![Zero::zero(); state_len]))?,
-            Vec::new_witness(cs.clone(), || Ok(vec![Zero::zero(); external_inputs_len]))?,
+            FC::ExternalInputsVar::new_witness(cs.clone(), || Ok(FC::ExternalInputs::default()))?,
         )?;
         let step_constraints = cs.num_constraints();
 
-        // Create a dummy circuit with the same state length and external inputs
-        // length as `F`, which replaces `F` in the augmented circuit `F'`.
-        let dummy_circuit: DummyCircuit =
-            FCircuit::<C1::ScalarField>::new((state_len, external_inputs_len))?;
+        // Create a dummy circuit with the same state length as `F`, which
+        // replaces `F` in the augmented circuit `F'`.
+        let dummy_circuit: DummyCircuit = FCircuit::<C1::ScalarField>::new(state_len)?;
 
         // Compute `augmentation_constraints`, the size of `F'` without `F`.
         let cs = ConstraintSystem::<C1::ScalarField>::new_ref();
@@ -633,8 +704,8 @@ where
     }
 }
 
-impl<C1, GC1, C2, GC2, FC, CS1, CS2> FoldingScheme<C1, C2, FC>
-    for ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2>
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, const K: usize> FoldingScheme<C1, C2, FC>
+    for ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2, K>
 where
     C1: CurveGroup,
     GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
@@ -654,24 +725,29 @@ where
     type VerifierParam = VerifierParams<C1, C2, CS1, CS2>;
     type RunningInstance = (CommittedInstance<C1, true>, Witness<C1::ScalarField>);
     type IncomingInstance = (CommittedInstance<C1, false>, Witness<C1::ScalarField>);
-    type MultiCommittedInstanceWithWitness =
-        (CommittedInstance<C1, false>, Witness<C1::ScalarField>);
+    type MultiCommittedInstanceWithWitness = Vec<Self::IncomingInstance>;
     type CFInstance = (CycleFoldCommittedInstance<C2>, CycleFoldWitness<C2>);
+    type RunningCommittedInstance = CommittedInstance<C1, true>;
+    type IncomingCommittedInstance = CommittedInstance<C1, false>;
     type IVCProof = IVCProof<C1, C2>;
 
-    fn pp_deserialize_with_mode<R: std::io::prelude::Read>(
+    fn pp_deserialize_with_mode<R: ark_std::io::Read>(
         reader: R,
         compress: ark_serialize::Compress,
         validate: ark_serialize::Validate,
         _fc_params: FC::Params, // FCircuit params
     ) -> Result<Self::ProverParam, Error> {
-        Ok(Self::ProverParam::deserialize_with_mode(
-            reader, compress, validate,
-        )?)
+        let (cs_params, cf_cs_params) =
+            deserialize_params::<C1, C2, _, _, _>(reader, compress, validate)?;
+        Ok(ProverParams {
+            poseidon_config: poseidon_canonical_config::<C1::ScalarField>(),
+            cs_params,
+            cf_cs_params,
+        })
     }
 
-    fn vp_deserialize_with_mode<R: std::io::prelude::Read>(
-        mut reader: R,
+    fn vp_deserialize_with_mode<R: ark_std::io::Read>(
+        reader: R,
         compress: ark_serialize::Compress,
         validate: ark_serialize::Validate,
         fc_params: FC::Params,
@@ -682,7 +758,7 @@ where
         // to serialize them, saving significant space in the VerifierParams serialized size.
 
         let f_circuit = FC::new(fc_params)?;
-        let k = 1;
+        let k = K;
         let d = 2;
         let t = Self::compute_t(&poseidon_config, &f_circuit, d, k)?;
 
@@ -708,8 +784,7 @@ where
         let cs2 = cs2.into_inner().ok_or(Error::NoInnerConstraintSystem)?;
         let cf_r1cs = extract_r1cs::<C1::BaseField>(&cs2)?;
 
-        let cs_vp = CS1::VerifierParams::deserialize_with_mode(&mut reader, compress, validate)?;
-        let cf_cs_vp = CS2::VerifierParams::deserialize_with_mode(&mut reader, compress, validate)?;
+        let (cs_vp, cf_cs_vp) = deserialize_params::<C1, C2, _, _, _>(reader, compress, validate)?;
 
         Ok(Self::VerifierParam {
             poseidon_config,
@@ -717,42 +792,63 @@ where
             cf_r1cs,
             cs_vp,
             cf_cs_vp,
+            state_len: f_circuit.state_len(),
+            step_constants: f_circuit.step_constants(),
         })
     }
 
+    /// Note that, unlike Nova's `PreprocessorParam::with_cs_params`, the `PreprocessorParam` of
+    /// ProtoGalaxy does not take existing commitment scheme parameters, so the setup of `CS1` and
+    /// `CS2` is always run from `rng` and cached parameters can not be reused here.
     fn preprocess(
         mut rng: impl RngCore,
         (poseidon_config, F): &Self::PreprocessorParam,
     ) -> Result<(Self::ProverParam, Self::VerifierParam), Error> {
-        // We fix `k`, the number of incoming instances, to 1, because
-        // multi-instances folding is not supported yet.
-        // TODO (@winderica): Support multi-instances folding and make `k` a
-        // constant generic parameter (as in HyperNova)
-        // Tracking issue: https://github.com/privacy-scaling-explorations/sonobe/issues/82
-        let k = 1;
+        // `k`, the number of incoming instances, is given by the generic
+        // parameter `K`. The Lagrange basis used in folding is defined over a
+        // domain of size `k + 1`, which should be a power of two.
+        let k = K;
+        if k == 0 || !(k + 1).is_power_of_two() {
+            return Err(Error::ProtoGalaxy(ProtoGalaxyError::WrongNumInstances(k)));
+        }
+        trace_span!("protogalaxy_preprocess", k = K);
+        ProtoGalaxyCycleFoldConfig::<C1>::check_challenge_bit_length()?;
+        let state_len = check_state_len(F)?;
+        let step_constants = check_step_constants(F)?;
+        check_no_custom_gates(F, "ProtoGalaxy")?;
         // `d`, the degree of the constraint system, is set to 2, as we only
         // support R1CS for now, whose highest degree is 2.
         let d = 2;
         let t = Self::compute_t(poseidon_config, F, d, k)?;
 
         // prepare the circuit to obtain its R1CS
-        let cs = ConstraintSystem::<C1::ScalarField>::new_ref();
-        let cs2 = ConstraintSystem::<C1::BaseField>::new_ref();
-
-        let augmented_F_circuit =
-            AugmentedFCircuit::<C1, C2, GC2, FC>::empty(poseidon_config, F.clone(), t, d, k);
-        let cf_circuit = ProtoGalaxyCycleFoldCircuit::<C1, GC1>::empty();
-
-        augmented_F_circuit.generate_constraints(cs.clone())?;
-        cs.finalize();
-        let cs = cs.into_inner().ok_or(Error::NoInnerConstraintSystem)?;
-        let r1cs = extract_r1cs::<C1::ScalarField>(&cs)?;
-
-        cf_circuit.generate_constraints(cs2.clone())?;
-        cs2.finalize();
-        let cs2 = cs2.into_inner().ok_or(Error::NoInnerConstraintSystem)?;
-        let cf_r1cs = extract_r1cs::<C1::BaseField>(&cs2)?;
+        let (r1cs, cf_r1cs) = {
+            trace_span!("augmented_circuit_synthesis");
+            let cs = ConstraintSystem::<C1::ScalarField>::new_ref();
+            let cs2 = ConstraintSystem::<C1::BaseField>::new_ref();
+
+            let augmented_F_circuit =
+                AugmentedFCircuit::<C1, C2, GC2, FC>::empty(poseidon_config, F.clone(), t, d, k);
+            let cf_circuit = ProtoGalaxyCycleFoldCircuit::<C1, GC1>::empty();
+
+            augmented_F_circuit.generate_constraints(cs.clone())?;
+            cs.finalize();
+            let cs = cs.into_inner().ok_or(Error::NoInnerConstraintSystem)?;
+
+            cf_circuit.generate_constraints(cs2.clone())?;
+            cs2.finalize();
+            let cs2 = cs2.into_inner().ok_or(Error::NoInnerConstraintSystem)?;
+            (
+                extract_r1cs::<C1::ScalarField>(&cs)?,
+                extract_r1cs::<C1::BaseField>(&cs2)?,
+            )
+        };
 
+        trace_span!(
+            "commitment_setup",
+            n_constraints = r1cs.A.n_rows,
+            cf_n_constraints = cf_r1cs.A.n_rows
+        );
         let (cs_pp, cs_vp) = CS1::setup(&mut rng, r1cs.A.n_rows)?;
         let (cf_cs_pp, cf_cs_vp) = CS2::setup(&mut rng, max(cf_r1cs.A.n_rows, cf_r1cs.A.n_cols))?;
 
@@ -768,6 +864,8 @@ where
                 cf_r1cs,
                 cs_vp,
                 cf_cs_vp,
+                state_len,
+                step_constants,
             },
         ))
     }
@@ -779,6 +877,20 @@ where
         F: FC,
         z_0: Vec<C1::ScalarField>,
     ) -> Result<Self, Error> {
+        if F.state_len() != vp.state_len {
+            return Err(Error::ParamsMismatch(format!(
+                "FCircuit state length {} differs from the preprocessed one {}",
+                F.state_len(),
+                vp.state_len
+            )));
+        }
+        if F.step_constants() != vp.step_constants {
+            return Err(Error::ParamsMismatch(
+                "FCircuit step constants differ from the preprocessed ones".to_string(),
+            ));
+        }
+        check_state("z_0", &z_0, vp.state_len)?;
+
         // compute the public params hash
         let pp_hash = vp.pp_hash()?;
 
@@ -817,19 +929,36 @@ where
     fn prove_step(
         &mut self,
         mut rng: impl RngCore,
-        external_inputs: Vec<C1::ScalarField>,
-        _other_instances: Option<Self::MultiCommittedInstanceWithWitness>,
+        external_inputs: FC::ExternalInputs,
+        other_instances: Option<Self::MultiCommittedInstanceWithWitness>,
     ) -> Result<(), Error> {
-        // Multi-instances folding is not supported yet.
-        if _other_instances.is_some() {
-            return Err(Error::NoMultiInstances);
+        trace_span!(
+            "protogalaxy_prove_step",
+            n_constraints = self.r1cs.A.n_rows,
+            k = K
+        );
+        // `k` is the number of all the incoming instances, including `u_i`
+        // that is not part of the `other_instances`.
+        let k = K;
+        let (us, ws): (
+            Vec<CommittedInstance<C1, false>>,
+            Vec<Witness<C1::ScalarField>>,
+        ) = if k > 1 {
+            other_instances
+                .ok_or(Error::MissingOtherInstances(1, k))?
+                .into_iter()
+                .unzip()
+        } else {
+            other_instances.unwrap_or_default().into_iter().unzip()
+        };
+        if us.len() + 1 != k {
+            return Err(Error::NotSameLength(
+                "other_instances.len()".to_string(),
+                us.len(),
+                "protogalaxy.k - 1".to_string(),
+                k - 1,
+            ));
         }
-        // We fix `k`, the number of incoming instances, to 1, because
-        // multi-instances folding is not supported yet.
-        // TODO (@winderica): Support multi-instances folding and make `k` a
-        // constant generic parameter (as in HyperNova)
-        // Tracking issue: https://github.com/privacy-scaling-explorations/sonobe/issues/82
-        let k = 1;
         // `d`, the degree of the constraint system, is set to 2, as we only
         // support R1CS for now, whose highest degree is 2.
         let d = 2;
@@ -841,29 +970,18 @@ where
 
         let mut augmented_F_circuit: AugmentedFCircuit<C1, C2, GC2, FC>;
 
-        if self.z_i.len() != self.F.state_len() {
-            return Err(Error::NotSameLength(
-                "z_i.len()".to_string(),
-                self.z_i.len(),
-                "F.state_len()".to_string(),
-                self.F.state_len(),
-            ));
-        }
-        if external_inputs.len() != self.F.external_inputs_len() {
-            return Err(Error::NotSameLength(
-                "F.external_inputs_len()".to_string(),
-                self.F.external_inputs_len(),
-                "external_inputs.len()".to_string(),
-                external_inputs.len(),
-            ));
-        }
+        check_state("z_i", &self.z_i, self.F.state_len())?;
 
         let i_bn: BigUint = self.i.into();
         let i_usize: usize = i_bn.try_into().map_err(|_| Error::MaxStep)?;
 
-        let z_i1 = self
-            .F
-            .step_native(i_usize, self.z_i.clone(), external_inputs.clone())?;
+        check_step_constants_row(&self.F.step_constants(), i_usize)?;
+        let z_i1 = {
+            trace_span!("step_native", state_len = self.z_i.len());
+            self.F
+                .step_native(i_usize, self.z_i.clone(), external_inputs.clone())?
+        };
+        check_state("z_{i+1}", &z_i1, self.F.state_len())?;
 
         // folded instance output (public input, x)
         // u_{i+1}.x[0] = H(i+1, z_0, z_{i+1}, U_{i+1})
@@ -903,82 +1021,76 @@ where
             // There is no need to update `self.U_i` etc. as they are unchanged.
         } else {
             // Primary part:
-            // Compute `U_{i+1}` by folding `u_i` into `U_i`.
-            let (U_i1, W_i1, proof, aux) = Folding::prove(
-                &mut transcript_prover,
-                &self.r1cs,
-                &self.U_i,
-                &self.W_i,
-                &[self.u_i.clone()],
-                &[self.w_i.clone()],
-            )?;
-
-            // CycleFold part:
-            // get the vector used as public inputs 'x' in the CycleFold circuit
-            let mut r0_bits = aux.L_X_evals[0].into_bigint().to_bits_le();
-            let mut r1_bits = aux.L_X_evals[1].into_bigint().to_bits_le();
-            r0_bits.resize(C1::ScalarField::MODULUS_BIT_SIZE as usize, false);
-            r1_bits.resize(C1::ScalarField::MODULUS_BIT_SIZE as usize, false);
-
-            // cyclefold circuit for enforcing:
-            // 0 + U_i.phi * L_evals[0] == phi_stars[0]
-            let cf1_u_i_x = [
-                r0_bits
-                    .chunks(C1::BaseField::MODULUS_BIT_SIZE as usize - 1)
-                    .map(<C1::BaseField as PrimeField>::BigInt::from_bits_le)
-                    .map(C1::BaseField::from)
-                    .collect::<Vec<_>>(),
-                get_cm_coordinates(&C1::zero()),
-                get_cm_coordinates(&self.U_i.phi),
-                get_cm_coordinates(&aux.phi_stars[0]),
-            ]
-            .concat();
-            let cf1_circuit = ProtoGalaxyCycleFoldCircuit::<C1, GC1> {
-                _gc: PhantomData,
-                r_bits: Some(r0_bits),
-                points: Some(vec![C1::zero(), self.U_i.phi]),
-                x: Some(cf1_u_i_x.clone()),
+            // Compute `U_{i+1}` by folding `u_i` and the other incoming
+            // instances into `U_i`.
+            let vec_instances = [vec![self.u_i.clone()], us.clone()].concat();
+            let vec_w = [vec![self.w_i.clone()], ws].concat();
+            let (U_i1, W_i1, proof, aux) = {
+                trace_span!("folding_prove", n_constraints = self.r1cs.A.n_rows);
+                Folding::prove(
+                    &mut transcript_prover,
+                    &self.r1cs,
+                    &self.U_i,
+                    &self.W_i,
+                    &vec_instances,
+                    &vec_w,
+                )?
             };
 
-            // cyclefold circuit for enforcing:
-            // phi_stars[0] + u_i.phi * L_evals[1] == U_i1.phi
-            // i.e., U_i.phi * L_evals[0] + u_i.phi * L_evals[1] == U_i1.phi
-            let cf2_u_i_x = [
-                r1_bits
-                    .chunks(C1::BaseField::MODULUS_BIT_SIZE as usize - 1)
-                    .map(<C1::BaseField as PrimeField>::BigInt::from_bits_le)
-                    .map(C1::BaseField::from)
-                    .collect::<Vec<_>>(),
-                get_cm_coordinates(&aux.phi_stars[0]),
-                get_cm_coordinates(&self.u_i.phi),
-                get_cm_coordinates(&U_i1.phi),
+            trace_span!("cyclefold_prove", n_constraints = self.cf_r1cs.A.n_rows);
+            // CycleFold part:
+            // The j-th cyclefold circuit enforces
+            // phi_stars[j - 1] + phis[j] * L_evals[j] == phi_stars[j],
+            // where phis = [U_i.phi, u_i.phi, ...], phi_stars[-1] = 0, and the
+            // last output is U_i1.phi, i.e.,
+            // U_i.phi * L_evals[0] + u_i.phi * L_evals[1] + ... == U_i1.phi
+            let phis = [
+                vec![self.U_i.phi],
+                vec_instances.iter().map(|u| u.phi).collect::<Vec<_>>(),
             ]
             .concat();
-            let cf2_circuit = ProtoGalaxyCycleFoldCircuit::<C1, GC1> {
-                _gc: PhantomData,
-                r_bits: Some(r1_bits),
-                points: Some(vec![aux.phi_stars[0], self.u_i.phi]),
-                x: Some(cf2_u_i_x.clone()),
-            };
+            let phi_outs = [aux.phi_stars.clone(), vec![U_i1.phi]].concat();
+
+            let mut cf_W_i1 = self.cf_W_i.clone();
+            let mut cf_U_i1 = self.cf_U_i.clone();
+            let mut cf_u_i_cmWs = Vec::with_capacity(k + 1);
+            let mut cf_cmTs = Vec::with_capacity(k + 1);
+            for (j, L_eval) in aux.L_X_evals.iter().enumerate() {
+                let mut r_bits = L_eval.into_bigint().to_bits_le();
+                r_bits.resize(C1::ScalarField::MODULUS_BIT_SIZE as usize, false);
+                let prev = if j == 0 { C1::zero() } else { phi_outs[j - 1] };
+
+                #[cfg(test)]
+                assert_eq!(
+                    ProtoGalaxyCycleFoldCircuit::<C1, GC1>::fold_points_native(
+                        &r_bits,
+                        &[prev, phis[j]]
+                    )?,
+                    phi_outs[j]
+                );
+                let cf_circuit =
+                    ProtoGalaxyCycleFoldCircuit::<C1, GC1>::new(r_bits, vec![prev, phis[j]])?;
+
+                // fold the incoming cyclefold instance into the running one
+                let (_cf_w_i, cf_u_i, W, U, cf_cmT, _) = self.fold_cyclefold_circuit(
+                    &mut transcript_prover,
+                    cf_W_i1,
+                    cf_U_i1,
+                    cf_circuit,
+                    &mut rng,
+                )?;
+
+                #[cfg(test)]
+                {
+                    cf_u_i.check_incoming()?;
+                    self.cf_r1cs.check_relation(&_cf_w_i, &cf_u_i)?;
+                }
 
-            // fold self.cf_U_i + cf1_U -> folded running with cf1
-            let (_cf1_w_i, cf1_u_i, cf1_W_i1, cf1_U_i1, cf1_cmT, _) = self.fold_cyclefold_circuit(
-                &mut transcript_prover,
-                self.cf_W_i.clone(), // CycleFold running instance witness
-                self.cf_U_i.clone(), // CycleFold running instance
-                cf1_u_i_x,
-                cf1_circuit,
-                &mut rng,
-            )?;
-            // fold [the output from folding self.cf_U_i + cf1_U] + cf2_U = folded_running_with_cf1 + cf2
-            let (_cf2_w_i, cf2_u_i, cf_W_i1, cf_U_i1, cf2_cmT, _) = self.fold_cyclefold_circuit(
-                &mut transcript_prover,
-                cf1_W_i1,
-                cf1_U_i1.clone(),
-                cf2_u_i_x,
-                cf2_circuit,
-                &mut rng,
-            )?;
+                cf_u_i_cmWs.push(cf_u_i.cmW);
+                cf_cmTs.push(cf_cmT);
+                cf_W_i1 = W;
+                cf_U_i1 = U;
+            }
 
             // Derive `u_{i+1}.x[0], u_{i+1}.x[1]` by hashing folded instances
             u_i1_x = U_i1.hash(
@@ -1000,6 +1112,7 @@ where
                 z_i: self.z_i.clone(),
                 external_inputs: external_inputs.clone(),
                 u_i_phi: self.u_i.phi,
+                us,
                 U_i: self.U_i.clone(),
                 U_i1_phi: U_i1.phi,
                 F_coeffs: proof.F_coeffs.clone(),
@@ -1008,11 +1121,9 @@ where
                 F: self.F.clone(),
                 x: Some(u_i1_x),
                 // cyclefold values
-                cf1_u_i_cmW: cf1_u_i.cmW,
-                cf2_u_i_cmW: cf2_u_i.cmW,
+                cf_u_i_cmWs,
                 cf_U_i: self.cf_U_i.clone(),
-                cf1_cmT,
-                cf2_cmT,
+                cf_cmTs,
                 cf_x: Some(cf_u_i1_x),
             };
 
@@ -1020,18 +1131,9 @@ where
             {
                 let mut transcript_verifier = sponge.clone();
                 assert_eq!(
-                    Folding::verify(
-                        &mut transcript_verifier,
-                        &self.U_i,
-                        &[self.u_i.clone()],
-                        proof
-                    )?,
+                    Folding::verify(&mut transcript_verifier, &self.U_i, &vec_instances, proof)?,
                     U_i1
                 );
-                cf1_u_i.check_incoming()?;
-                cf2_u_i.check_incoming()?;
-                self.cf_r1cs.check_relation(&_cf1_w_i, &cf1_u_i)?;
-                self.cf_r1cs.check_relation(&_cf2_w_i, &cf2_u_i)?;
                 self.cf_r1cs.check_relation(&self.cf_W_i, &self.cf_U_i)?;
             }
 
@@ -1042,14 +1144,22 @@ where
         }
 
         let cs = ConstraintSystem::<C1::ScalarField>::new_ref();
-
-        augmented_F_circuit.generate_constraints(cs.clone())?;
+        {
+            trace_span!("augmented_circuit_synthesis");
+            augmented_F_circuit.generate_constraints(cs.clone())?;
+        }
 
         #[cfg(test)]
         assert!(cs.is_satisfied().unwrap());
 
-        let cs = cs.into_inner().ok_or(Error::NoInnerConstraintSystem)?;
-        let (w_i1, x_i1) = extract_w_x::<C1::ScalarField>(&cs);
+        let (w_i1, x_i1) = {
+            trace_span!(
+                "witness_extraction",
+                n_variables = cs.num_witness_variables()
+            );
+            let cs = cs.into_inner().ok_or(Error::NoInnerConstraintSystem)?;
+            extract_w_x::<C1::ScalarField>(&cs)
+        };
         if x_i1[0] != u_i1_x || x_i1[1] != cf_u_i1_x {
             return Err(Error::NotEqual);
         }
@@ -1063,7 +1173,10 @@ where
         self.i += C1::ScalarField::one();
         self.z_i = z_i1;
         self.w_i = Witness::new(w_i1);
-        self.u_i = self.w_i.commit::<CS1, C1>(&self.cs_params, x_i1)?;
+        self.u_i = {
+            trace_span!("commit", len = self.w_i.w.len());
+            self.w_i.commit::<CS1, C1>(&self.cs_params, x_i1)?
+        };
 
         #[cfg(test)]
         {
@@ -1075,8 +1188,17 @@ where
         Ok(())
     }
 
-    fn state(&self) -> Vec<C1::ScalarField> {
-        self.z_i.clone()
+    fn state(
+        &self,
+    ) -> FoldingState<'_, C1, C2, CommittedInstance<C1, true>, CommittedInstance<C1, false>> {
+        FoldingState {
+            i: self.i,
+            z_0: &self.z_0,
+            z_i: &self.z_i,
+            U_i: &self.U_i,
+            u_i: &self.u_i,
+            cf_U_i: &self.cf_U_i,
+        }
     }
 
     fn ivc_proof(&self) -> Self::IVCProof {
@@ -1110,9 +1232,25 @@ where
             cf_U_i,
         } = ivc_proof;
         let (pp, vp) = params;
+        check_state("z_0", &z_0, vp.state_len)?;
+        check_state("z_i", &z_i, vp.state_len)?;
 
         let f_circuit = FC::new(fcircuit_params)?;
 
+        // the IVC proof may come from untrusted bytes, check that its dimensions match the
+        // circuit before using it
+        for z in [&z_0, &z_i] {
+            if z.len() != f_circuit.state_len() {
+                return Err(Error::NotExpectedLength(z.len(), f_circuit.state_len()));
+            }
+        }
+        U_i.check_dimensions(&vp.r1cs)?;
+        u_i.check_dimensions(&vp.r1cs)?;
+        W_i.check_dimensions(&vp.r1cs)?;
+        w_i.check_dimensions(&vp.r1cs)?;
+        cf_U_i.check_dimensions(&vp.cf_r1cs)?;
+        cf_W_i.check_dimensions(&vp.cf_r1cs)?;
+
         Ok(Self {
             _gc1: PhantomData,
             _c2: PhantomData,
@@ -1150,11 +1288,13 @@ where
             cf_U_i,
         } = ivc_proof;
 
+        check_state("z_0", &z_0, vp.state_len)?;
+        check_state("z_i", &z_i, vp.state_len)?;
+
         let sponge = PoseidonSponge::<C1::ScalarField>::new(&vp.poseidon_config);
 
-        if u_i.x.len() != 2 || U_i.x.len() != 2 {
-            return Err(Error::IVCVerificationFail);
-        }
+        check_len(u_i.x.len(), 2)?;
+        check_len(U_i.x.len(), 2)?;
 
         let pp_hash = vp.pp_hash()?;
 
@@ -1184,7 +1324,7 @@ where
     }
 }
 
-impl<C1, GC1, C2, GC2, FC, CS1, CS2> ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2>
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, const K: usize> ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2, K>
 where
     C1: CurveGroup,
     GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
@@ -1199,6 +1339,64 @@ where
     <C2 as Group>::ScalarField: Absorb,
     C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
 {
+    /// Same as `prove_step`, but drawing the randomness of the step (of the CycleFold instances)
+    /// from `utils::deterministic_rng(seed, i)`. Proving the same steps from the same seed
+    /// results in byte-identical IVC proofs.
+    pub fn prove_step_deterministic(
+        &mut self,
+        seed: [u8; 32],
+        external_inputs: FC::ExternalInputs,
+        other_instances: Option<
+            <Self as FoldingScheme<C1, C2, FC>>::MultiCommittedInstanceWithWitness,
+        >,
+    ) -> Result<(), Error> {
+        self.prove_step(
+            deterministic_rng(seed, self.i),
+            external_inputs,
+            other_instances,
+        )
+    }
+
+    /// Creates a new incoming instance for the given state, which satisfies the R1CS of the
+    /// augmented circuit. This method can be used to generate the `K - 1` 'other' incoming
+    /// instances to be folded together with `u_i` in `prove_step`.
+    pub fn new_incoming_instance(
+        &self,
+        state: Vec<C1::ScalarField>,
+        external_inputs: FC::ExternalInputs,
+    ) -> Result<(CommittedInstance<C1, false>, Witness<C1::ScalarField>), Error> {
+        check_state("state", &state, self.F.state_len())?;
+        // the instance is the output of the base case step of the augmented
+        // circuit starting at `state`
+        let mut augmented_F_circuit = AugmentedFCircuit::<C1, C2, GC2, FC>::empty(
+            &self.poseidon_config,
+            self.F.clone(),
+            self.U_i.betas.len(),
+            2,
+            K,
+        );
+        augmented_F_circuit.pp_hash = self.pp_hash;
+        augmented_F_circuit.z_0.clone_from(&self.z_0);
+        augmented_F_circuit.z_i = state;
+        augmented_F_circuit.external_inputs = external_inputs;
+
+        let cs = ConstraintSystem::<C1::ScalarField>::new_ref();
+        augmented_F_circuit.generate_constraints(cs.clone())?;
+
+        #[cfg(test)]
+        assert!(cs.is_satisfied().unwrap());
+
+        let cs = cs.into_inner().ok_or(Error::NoInnerConstraintSystem)?;
+        let (w, x) = extract_w_x::<C1::ScalarField>(&cs);
+        let w = Witness::new(w);
+        let u = w.commit::<CS1, C1>(&self.cs_params, x)?;
+
+        #[cfg(test)]
+        self.r1cs.check_relation(&w, &u)?;
+
+        Ok((u, w))
+    }
+
     // folds the given cyclefold circuit and its instances
     #[allow(clippy::type_complexity)]
     fn fold_cyclefold_circuit(
@@ -1206,7 +1404,6 @@ where
         transcript: &mut PoseidonSponge<C1::ScalarField>,
         cf_W_i: CycleFoldWitness<C2>, // witness of the running instance
         cf_U_i: CycleFoldCommittedInstance<C2>, // running instance
-        cf_u_i_x: Vec<C2::ScalarField>,
         cf_circuit: ProtoGalaxyCycleFoldCircuit<C1, GC1>,
         rng: &mut impl RngCore,
     ) -> Result<
@@ -1227,7 +1424,6 @@ where
             self.pp_hash,
             cf_W_i,
             cf_U_i,
-            cf_u_i_x,
             cf_circuit,
             rng,
         )
@@ -1240,7 +1436,9 @@ mod tests {
 
     use ark_bn254::{constraints::GVar, Bn254, Fr, G1Projective as Projective};
     use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
-    use ark_std::test_rng;
+    use ark_serialize::Compress;
+    use ark_std::{cfg_into_iter, test_rng};
+    #[cfg(feature = "parallel")]
     use rayon::prelude::*;
 
     use crate::{
@@ -1283,14 +1481,160 @@ mod tests {
 
         let num_steps: usize = 3;
         for _ in 0..num_steps {
+            protogalaxy.prove_step(&mut test_rng(), (), None).unwrap();
+        }
+        assert_eq!(Fr::from(num_steps as u32), protogalaxy.i);
+
+        let ivc_proof = protogalaxy.ivc_proof();
+        PG::<CS1, CS2>::verify(params.1, ivc_proof).unwrap();
+    }
+
+    // serializes the parameters and the IVC proof after two steps, resumes the IVC from the
+    // deserialized values, folds one more step and verifies it
+    #[test]
+    fn test_ivc_proof_serialization() {
+        type PG = ProtoGalaxy<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+        >;
+
+        let mut rng = test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let params = PG::preprocess(&mut rng, &(poseidon_config, F_circuit)).unwrap();
+
+        let mut protogalaxy = PG::init(&params, F_circuit, vec![Fr::from(3_u32)]).unwrap();
+        for _ in 0..2 {
+            protogalaxy.prove_step(&mut rng, (), None).unwrap();
+        }
+
+        for compress in [Compress::Yes, Compress::No] {
+            let mut pp_bytes = vec![];
+            params
+                .0
+                .serialize_with_mode(&mut pp_bytes, compress)
+                .unwrap();
+            let mut vp_bytes = vec![];
+            params
+                .1
+                .serialize_with_mode(&mut vp_bytes, compress)
+                .unwrap();
+            let ivc_proof = protogalaxy.ivc_proof();
+            let mut ivc_proof_bytes = vec![];
+            ivc_proof
+                .serialize_with_mode(&mut ivc_proof_bytes, compress)
+                .unwrap();
+            assert_eq!(ivc_proof_bytes.len(), ivc_proof.serialized_size(compress));
+
+            // reload everything from bytes
+            let pp = PG::pp_deserialize_with_mode(
+                pp_bytes.as_slice(),
+                compress,
+                ark_serialize::Validate::Yes,
+                (),
+            )
+            .unwrap();
+            let vp = PG::vp_deserialize_with_mode(
+                vp_bytes.as_slice(),
+                compress,
+                ark_serialize::Validate::Yes,
+                (),
+            )
+            .unwrap();
+            assert_eq!(vp.pp_hash().unwrap(), params.1.pp_hash().unwrap());
+            let ivc_proof_deserialized =
+                IVCProof::<Projective, Projective2>::deserialize_with_mode(
+                    ivc_proof_bytes.as_slice(),
+                    compress,
+                    ark_serialize::Validate::Yes,
+                )
+                .unwrap();
+            assert_eq!(ivc_proof, ivc_proof_deserialized);
+
+            let mut reloaded =
+                PG::from_ivc_proof(ivc_proof_deserialized, (), (pp, vp.clone())).unwrap();
+            reloaded.prove_step(&mut rng, (), None).unwrap();
+            assert_eq!(reloaded.i, Fr::from(3_u32));
+            PG::verify(vp, reloaded.ivc_proof()).unwrap();
+        }
+
+        // proofs whose dimensions don't match the circuit are rejected
+        let mut ivc_proof = protogalaxy.ivc_proof();
+        ivc_proof.U_i.betas.pop();
+        assert!(PG::from_ivc_proof(ivc_proof, (), params.clone()).is_err());
+        let mut ivc_proof = protogalaxy.ivc_proof();
+        ivc_proof.u_i.x.push(Fr::zero());
+        assert!(PG::from_ivc_proof(ivc_proof, (), params.clone()).is_err());
+        let mut ivc_proof = protogalaxy.ivc_proof();
+        ivc_proof.w_i.w.pop();
+        assert!(PG::from_ivc_proof(ivc_proof, (), params).is_err());
+    }
+
+    /// Tests the ProtoGalaxy+CycleFold IVC folding `K = 3` incoming instances
+    /// at each step
+    #[test]
+    fn test_ivc_multi_instances() {
+        const K: usize = 3;
+        type PG = ProtoGalaxy<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            K,
+        >;
+
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let params = PG::preprocess(&mut test_rng(), &(poseidon_config, F_circuit)).unwrap();
+
+        let z_0 = vec![Fr::from(3_u32)];
+        let mut protogalaxy = PG::init(&params, F_circuit, z_0.clone()).unwrap();
+
+        // missing other instances
+        assert!(protogalaxy
+            .clone()
+            .prove_step(&mut test_rng(), (), None)
+            .is_err());
+
+        let num_steps: usize = 3;
+        for i in 0..num_steps {
+            let other_instances = (0..K - 1)
+                .map(|j| protogalaxy.new_incoming_instance(vec![Fr::from((i * K + j) as u32)], ()))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
             protogalaxy
-                .prove_step(&mut test_rng(), vec![], None)
+                .prove_step(&mut test_rng(), (), Some(other_instances))
                 .unwrap();
         }
         assert_eq!(Fr::from(num_steps as u32), protogalaxy.i);
 
         let ivc_proof = protogalaxy.ivc_proof();
-        PG::<CS1, CS2>::verify(params.1, ivc_proof).unwrap();
+        PG::verify(params.1, ivc_proof).unwrap();
+
+        // `K + 1` should be a power of two
+        type PG4 = ProtoGalaxy<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            4,
+        >;
+        assert!(PG4::preprocess(
+            &mut test_rng(),
+            &(poseidon_canonical_config::<Fr>(), F_circuit)
+        )
+        .is_err());
     }
 
     #[ignore]
@@ -1301,33 +1645,28 @@ mod tests {
 
         let poseidon_config = poseidon_canonical_config::<Fr>();
         for state_len in [1, 10, 100] {
-            for external_inputs_len in [1, 10, 100] {
-                let dummy_circuit: DummyCircuit =
-                    FCircuit::<Fr>::new((state_len, external_inputs_len)).unwrap();
-
-                let costs = (1..32)
-                    .into_par_iter()
-                    .map(|t| {
-                        let cs = ConstraintSystem::<Fr>::new_ref();
-                        AugmentedFCircuit::<Projective, Projective2, GVar2, DummyCircuit>::empty(
-                            &poseidon_config,
-                            dummy_circuit.clone(),
-                            t,
-                            d,
-                            k,
-                        )
-                        .generate_constraints(cs.clone())
-                        .unwrap();
-                        cs.num_constraints()
-                    })
-                    .collect::<Vec<_>>();
-
-                for t_lower_bound in log2(costs[0]) as usize..32 {
-                    let num_constraints =
-                        (1 << t_lower_bound) - costs[0] + costs[t_lower_bound - 1];
-                    let t = log2(num_constraints) as usize;
-                    assert!(t == t_lower_bound || t == t_lower_bound + 1);
-                }
+            let dummy_circuit: DummyCircuit = FCircuit::<Fr>::new(state_len).unwrap();
+
+            let costs = cfg_into_iter!(1..32)
+                .map(|t| {
+                    let cs = ConstraintSystem::<Fr>::new_ref();
+                    AugmentedFCircuit::<Projective, Projective2, GVar2, DummyCircuit>::empty(
+                        &poseidon_config,
+                        dummy_circuit.clone(),
+                        t,
+                        d,
+                        k,
+                    )
+                    .generate_constraints(cs.clone())
+                    .unwrap();
+                    cs.num_constraints()
+                })
+                .collect::<Vec<_>>();
+
+            for t_lower_bound in log2(costs[0]) as usize..32 {
+                let num_constraints = (1 << t_lower_bound) - costs[0] + costs[t_lower_bound - 1];
+                let t = log2(num_constraints) as usize;
+                assert!(t == t_lower_bound || t == t_lower_bound + 1);
             }
         }
     }
@@ -8,12 +8,13 @@ use ark_r1cs_std::{
     ToConstraintFieldGadget,
 };
 use ark_relations::r1cs::SynthesisError;
-use ark_std::{cfg_into_iter, log2, One};
+use ark_std::{cfg_into_iter, cfg_iter, log2, string::ToString, vec::Vec, One};
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
 use super::{
     constants::RUNNING,
-    utils::{pow_i, pow_i_var},
+    utils::{pow_i_table, pow_i_table_var},
     CommittedInstance, CommittedInstanceVar, Witness, WitnessVar,
 };
 use crate::{
@@ -23,7 +24,7 @@ use crate::{
     },
     folding::circuits::CF1,
     transcript::AbsorbNonNative,
-    utils::vec::is_zero_vec,
+    utils::vec::check_zero_rows,
     Error,
 };
 
@@ -90,7 +91,7 @@ impl<C: CurveGroup, const TYPE: bool> Arith<Witness<CF1<C>>, CommittedInstance<C
         u: &CommittedInstance<C, TYPE>,
         e: Vec<C::ScalarField>,
     ) -> Result<(), Error> {
-        let ok = if TYPE == RUNNING {
+        if TYPE == RUNNING {
             if u.betas.len() != log2(e.len()) as usize {
                 return Err(Error::NotSameLength(
                     "instance.betas.len()".to_string(),
@@ -100,14 +101,17 @@ impl<C: CurveGroup, const TYPE: bool> Arith<Witness<CF1<C>>, CommittedInstance<C
                 ));
             }
 
-            u.e == cfg_into_iter!(e)
-                .enumerate()
-                .map(|(i, e_i)| pow_i(i, &u.betas) * e_i)
-                .sum::<CF1<C>>()
+            // the running relation only holds in aggregate, so there is no row to report
+            let pows = pow_i_table(&u.betas);
+            let ok = u.e
+                == cfg_into_iter!(e)
+                    .zip(cfg_iter!(pows))
+                    .map(|(e_i, pow_i)| *pow_i * e_i)
+                    .sum::<CF1<C>>();
+            ok.then_some(()).ok_or(Error::NotSatisfied)
         } else {
-            is_zero_vec(&e)
-        };
-        ok.then_some(()).ok_or(Error::NotSatisfied)
+            check_zero_rows(&e)
+        }
     }
 }
 
@@ -131,9 +135,10 @@ impl<C: CurveGroup> ArithGadget<WitnessVar<CF1<C>>, CommittedInstanceVar<C, RUNN
         u: &CommittedInstanceVar<C, RUNNING>,
         (AzBz, uCz): Self::Evaluation,
     ) -> Result<(), SynthesisError> {
+        let pows = pow_i_table_var(&u.betas);
         let mut e = vec![];
-        for (i, (l, r)) in AzBz.iter().zip(uCz).enumerate() {
-            e.push(pow_i_var(i, &u.betas) * (l - r));
+        for ((l, r), pow_i) in AzBz.iter().zip(uCz).zip(pows) {
+            e.push(pow_i * (l - r));
         }
         // Call `sum` on a vector instead of computing the sum in the above loop
         // to avoid stack overflow (the cause of this is similar to issue #80
@@ -1,6 +1,9 @@
 use ark_ff::PrimeField;
 use ark_r1cs_std::fields::{fp::FpVar, FieldVar};
+use ark_std::{cfg_iter, vec::Vec};
 use num_integer::Integer;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 /// Returns (b, b^2, b^4, ..., b^{2^{t-1}})
 pub fn exponential_powers<F: PrimeField>(b: F, t: usize) -> Vec<F> {
@@ -90,8 +93,35 @@ pub fn pow_i<F: PrimeField>(mut i: usize, betas: &[F]) -> F {
     r
 }
 
+/// Returns the vector `(pow_0(betas), pow_1(betas), ..., pow_{2^t-1}(betas))`, where `t` is the
+/// length of `betas`.
+///
+/// The vector is built by exploiting its tensor-product structure: starting from `(1)`, each
+/// `betas[j]` doubles the table, where the new half is the old one multiplied by `betas[j]`. This
+/// takes `2^t` multiplications in total, instead of the `O(2^t * t)` of calling `pow_i` per index.
+pub fn pow_i_table<F: PrimeField>(betas: &[F]) -> Vec<F> {
+    let mut table = Vec::with_capacity(1 << betas.len());
+    table.push(F::one());
+    for beta in betas {
+        let upper = cfg_iter!(table).map(|p| *p * beta).collect::<Vec<_>>();
+        table.extend(upper);
+    }
+    table
+}
+
+/// The in-circuit version of `pow_i_table`, where every product is shared by all the entries that
+/// contain it, so that the table costs `2^t - t - 1` constraints.
+pub fn pow_i_table_var<F: PrimeField>(betas: &[FpVar<F>]) -> Vec<FpVar<F>> {
+    let mut table = Vec::with_capacity(1 << betas.len());
+    table.push(FpVar::one());
+    for beta in betas {
+        let upper = table.iter().map(|p| p * beta).collect::<Vec<_>>();
+        table.extend(upper);
+    }
+    table
+}
+
 /// The in-circuit version of `pow_i`
-#[allow(dead_code)] // Will remove this once we have the decider circuit for Protogalaxy
 pub fn pow_i_var<F: PrimeField>(mut i: usize, betas: &[FpVar<F>]) -> FpVar<F> {
     let mut j = 0;
     let mut r = FieldVar::one();
@@ -201,4 +231,26 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_pow_i_table() -> Result<(), Box<dyn Error>> {
+        let rng = &mut test_rng();
+
+        for t in 0..10 {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+
+            let betas = (0..t).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+            let betas_var = Vec::new_witness(cs.clone(), || Ok(betas.clone()))?;
+
+            let naive = (0..1 << t).map(|i| pow_i(i, &betas)).collect::<Vec<_>>();
+            let r = pow_i_table(&betas);
+            let r_var = pow_i_table_var(&betas_var);
+            assert_eq!(r, naive);
+            assert_eq!(r, r_var.value()?);
+            assert!(cs.is_satisfied()?);
+            assert_eq!(cs.num_constraints(), (1 << t) - t - 1);
+        }
+
+        Ok(())
+    }
 }
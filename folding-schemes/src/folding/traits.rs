@@ -7,6 +7,7 @@ use ark_ec::CurveGroup;
 use ark_ff::PrimeField;
 use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, ToConstraintFieldGadget};
 use ark_relations::r1cs::SynthesisError;
+use ark_std::vec::Vec;
 
 use crate::{transcript::Transcript, Error};
 
@@ -28,12 +29,34 @@ pub trait CommittedInstanceOps<C: CurveGroup>: Inputize<CF1<C>, Self::Var> {
         z_0: &[CF1<C>],
         z_i: &[CF1<C>],
     ) -> CF1<C>
+    where
+        CF1<C>: Absorb,
+        Self: Sized + Absorb,
+    {
+        self.hash_with_context(sponge, pp_hash, None, i, z_0, z_i)
+    }
+
+    /// `hash_with_context` is `hash` where an application `context` (e.g. a chain ID) is
+    /// absorbed right after `pp_hash`, returning `H(pp_hash, context, i, z_0, z_i, U_i)`. With
+    /// `context = None` nothing is absorbed, and the result is the same as `hash`.
+    fn hash_with_context<T: Transcript<CF1<C>>>(
+        &self,
+        sponge: &T,
+        pp_hash: CF1<C>, // public params hash
+        context: Option<CF1<C>>,
+        i: CF1<C>,
+        z_0: &[CF1<C>],
+        z_i: &[CF1<C>],
+    ) -> CF1<C>
     where
         CF1<C>: Absorb,
         Self: Sized + Absorb,
     {
         let mut sponge = sponge.clone();
         sponge.absorb(&pp_hash);
+        if let Some(context) = context {
+            sponge.absorb(&context);
+        }
         sponge.absorb(&i);
         sponge.absorb(&z_0);
         sponge.absorb(&z_i);
@@ -44,6 +67,10 @@ pub trait CommittedInstanceOps<C: CurveGroup>: Inputize<CF1<C>, Self::Var> {
     /// Returns the commitments contained in the committed instance.
     fn get_commitments(&self) -> Vec<C>;
 
+    /// Returns the domain-separation labels of the commitments contained in the committed
+    /// instance, in the same order as `get_commitments`.
+    fn get_commitment_labels(&self) -> Vec<&'static [u8]>;
+
     /// Returns `true` if the committed instance is an incoming instance, and
     /// `false` if it is a running instance.
     fn is_incoming(&self) -> bool;
@@ -75,12 +102,32 @@ pub trait CommittedInstanceVarOps<C: CurveGroup> {
         z_0: &[FpVar<CF1<C>>],
         z_i: &[FpVar<CF1<C>>],
     ) -> Result<(FpVar<CF1<C>>, Vec<FpVar<CF1<C>>>), SynthesisError>
+    where
+        Self: AbsorbGadget<CF1<C>>,
+    {
+        self.hash_with_context(sponge, pp_hash, None, i, z_0, z_i)
+    }
+
+    /// In-circuit counterpart of `CommittedInstanceOps::hash_with_context`.
+    #[allow(clippy::type_complexity)]
+    fn hash_with_context(
+        &self,
+        sponge: &PoseidonSpongeVar<CF1<C>>,
+        pp_hash: &FpVar<CF1<C>>,
+        context: Option<&FpVar<CF1<C>>>,
+        i: &FpVar<CF1<C>>,
+        z_0: &[FpVar<CF1<C>>],
+        z_i: &[FpVar<CF1<C>>],
+    ) -> Result<(FpVar<CF1<C>>, Vec<FpVar<CF1<C>>>), SynthesisError>
     where
         Self: AbsorbGadget<CF1<C>>,
     {
         let mut sponge = sponge.clone();
         let U_vec = self.to_sponge_field_elements()?;
         sponge.absorb(&pp_hash)?;
+        if let Some(context) = context {
+            sponge.absorb(context)?;
+        }
         sponge.absorb(&i)?;
         sponge.absorb(&z_0)?;
         sponge.absorb(&z_i)?;
@@ -95,6 +142,10 @@ pub trait CommittedInstanceVarOps<C: CurveGroup> {
     /// Returns the commitments contained in the committed instance.
     fn get_commitments(&self) -> Vec<Self::PointVar>;
 
+    /// Returns the domain-separation labels of the commitments contained in the committed
+    /// instance, matching `CommittedInstanceOps::get_commitment_labels`.
+    fn get_commitment_labels(&self) -> Vec<&'static [u8]>;
+
     /// Returns the public inputs contained in the committed instance.
     fn get_public_inputs(&self) -> &[FpVar<CF1<C>>];
 
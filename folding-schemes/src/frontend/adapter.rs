@@ -0,0 +1,373 @@
+/// contains the adapter that allows to fold an arkworks circuit implementing
+/// [`ConstraintSynthesizer`] as an [`FCircuit`]
+use ark_ff::PrimeField;
+use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, R1CSVar};
+use ark_relations::{
+    lc,
+    r1cs::{
+        ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef,
+        SynthesisError, SynthesisMode, Variable,
+    },
+};
+use ark_std::rc::Rc;
+use ark_std::{fmt, string::ToString, vec::Vec};
+
+use super::{FCircuit, VecF, VecFpVar};
+use crate::Error;
+
+/// ArkCircuitBuilder returns the circuit of the `i`-th step, given the values of the state `z_i`
+/// and of the external inputs of the step.
+pub type ArkCircuitBuilder<F, C> = Rc<dyn Fn(usize, Vec<F>, Vec<F>) -> C>;
+
+/// ArkCircuitIOMap declares which variables of the adapted circuit are the incoming state, the
+/// outgoing state and the external inputs of the step. Variables are referred to by their
+/// allocation order, first the public inputs and then the witnesses (ie. the order of the R1CS `z`
+/// vector), without counting the constant `1`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArkCircuitIOMap {
+    /// indexes of the variables holding the state z_i
+    pub state_in: Vec<usize>,
+    /// indexes of the variables holding the next state z_{i+1}
+    pub state_out: Vec<usize>,
+    /// indexes of the variables holding the external inputs
+    pub external_inputs: Vec<usize>,
+}
+
+/// ArkCircuitAdapter implements [`FCircuit`] for an arkworks circuit implementing
+/// [`ConstraintSynthesizer`], where `L` is the number of external inputs. At each step the circuit
+/// is built by the given [`ArkCircuitBuilder`] and synthesized into a separate constraint system,
+/// whose constraints are then copied into the augmented circuit, wiring the variables declared in
+/// the [`ArkCircuitIOMap`] to the state and external inputs of the step. The public inputs of the
+/// adapted circuit become witnesses of the augmented circuit.
+pub struct ArkCircuitAdapter<F: PrimeField, C: ConstraintSynthesizer<F>, const L: usize> {
+    builder: ArkCircuitBuilder<F, C>,
+    io_map: ArkCircuitIOMap,
+}
+
+impl<F: PrimeField, C: ConstraintSynthesizer<F>, const L: usize> Clone
+    for ArkCircuitAdapter<F, C, L>
+{
+    fn clone(&self) -> Self {
+        Self {
+            builder: self.builder.clone(),
+            io_map: self.io_map.clone(),
+        }
+    }
+}
+
+impl<F: PrimeField, C: ConstraintSynthesizer<F>, const L: usize> fmt::Debug
+    for ArkCircuitAdapter<F, C, L>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArkCircuitAdapter")
+            .field("circuit", &ark_std::any::type_name::<C>())
+            .field("io_map", &self.io_map)
+            .finish()
+    }
+}
+
+impl<F: PrimeField, C: ConstraintSynthesizer<F>, const L: usize> ArkCircuitAdapter<F, C, L> {
+    /// builds the circuit of the `i`-th step and synthesizes it into a new constraint system,
+    /// which is in setup mode if `setup` is set
+    fn synthesize(
+        &self,
+        i: usize,
+        z_i: Vec<F>,
+        external_inputs: Vec<F>,
+        setup: bool,
+    ) -> Result<ConstraintSystem<F>, SynthesisError> {
+        let circuit = (self.builder)(i, z_i, external_inputs);
+        let cs = ConstraintSystem::<F>::new_ref();
+        if setup {
+            cs.set_mode(SynthesisMode::Setup);
+        }
+        circuit.generate_constraints(cs.clone())?;
+        cs.finalize();
+        cs.into_inner().ok_or(SynthesisError::MissingCS)
+    }
+
+    /// returns the value assigned to the variable at the given index of the io map
+    fn assigned_value(cs: &ConstraintSystem<F>, index: usize) -> Option<F> {
+        let n_inputs = cs.num_instance_variables - 1;
+        if index < n_inputs {
+            cs.instance_assignment.get(index + 1).copied()
+        } else {
+            cs.witness_assignment.get(index - n_inputs).copied()
+        }
+    }
+}
+
+impl<F: PrimeField, C: ConstraintSynthesizer<F>, const L: usize> FCircuit<F>
+    for ArkCircuitAdapter<F, C, L>
+{
+    /// (builder, io_map)
+    type Params = (ArkCircuitBuilder<F, C>, ArkCircuitIOMap);
+    type ExternalInputs = VecF<F, L>;
+    type ExternalInputsVar = VecFpVar<F, L>;
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        let (builder, io_map) = params;
+        if io_map.state_in.is_empty() {
+            return Err(Error::CantBeZero("io_map.state_in.len()".to_string()));
+        }
+        if io_map.state_in.len() != io_map.state_out.len() {
+            return Err(Error::NotSameLength(
+                "io_map.state_in.len()".to_string(),
+                io_map.state_in.len(),
+                "io_map.state_out.len()".to_string(),
+                io_map.state_out.len(),
+            ));
+        }
+        if io_map.external_inputs.len() != L {
+            return Err(Error::NotExpectedLength(io_map.external_inputs.len(), L));
+        }
+        // each variable can only be wired to one of the incoming values
+        let mut incoming = [io_map.state_in.clone(), io_map.external_inputs.clone()].concat();
+        incoming.sort_unstable();
+        if incoming.windows(2).any(|w| w[0] == w[1]) {
+            return Err(Error::Other(
+                "io_map wires a variable to several incoming values".to_string(),
+            ));
+        }
+
+        let adapter = Self { builder, io_map };
+        // synthesize the circuit once to check that the io map fits in its variables
+        let cs = adapter.synthesize(
+            0,
+            vec![F::zero(); adapter.io_map.state_in.len()],
+            vec![F::zero(); L],
+            true,
+        )?;
+        let n_vars = cs.num_instance_variables - 1 + cs.num_witness_variables;
+        let max_index = incoming
+            .iter()
+            .chain(adapter.io_map.state_out.iter())
+            .max()
+            .copied()
+            .unwrap_or_default();
+        if max_index >= n_vars {
+            return Err(Error::OutOfBounds);
+        }
+        Ok(adapter)
+    }
+
+    fn state_len(&self) -> usize {
+        self.io_map.state_in.len()
+    }
+
+    fn step_native(
+        &self,
+        i: usize,
+        z_i: Vec<F>,
+        external_inputs: Self::ExternalInputs,
+    ) -> Result<Vec<F>, Error> {
+        let cs = self.synthesize(i, z_i.clone(), external_inputs.0.clone(), false)?;
+        if !cs.is_satisfied()? {
+            return Err(Error::WitnessCalculationError(format!(
+                "the adapted circuit is not satisfied at step {}",
+                i
+            )));
+        }
+        // the circuit built by the builder must use the given incoming values
+        for (index, expected) in self.io_map.state_in.iter().zip(z_i.iter()).chain(
+            self.io_map
+                .external_inputs
+                .iter()
+                .zip(external_inputs.0.iter()),
+        ) {
+            if Self::assigned_value(&cs, *index) != Some(*expected) {
+                return Err(Error::WitnessCalculationError(format!(
+                    "variable {} of the adapted circuit is not assigned to its incoming value",
+                    index
+                )));
+            }
+        }
+        self.io_map
+            .state_out
+            .iter()
+            .map(|index| {
+                Self::assigned_value(&cs, *index)
+                    .ok_or(Error::MissingValue(format!("state_out variable {}", index)))
+            })
+            .collect()
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Self::ExternalInputsVar,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        if z_i.len() != self.state_len() || external_inputs.0.len() != L {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let setup = cs.is_in_setup_mode();
+        let (z_i_values, external_inputs_values) = if setup {
+            (vec![F::zero(); z_i.len()], vec![F::zero(); L])
+        } else {
+            (z_i.value()?, external_inputs.0.value()?)
+        };
+        let inner = self.synthesize(i, z_i_values, external_inputs_values, setup)?;
+        let ConstraintMatrices { a, b, c, .. } =
+            inner.to_matrices().ok_or(SynthesisError::MissingCS)?;
+
+        // map each variable of the adapted circuit (besides the constant `1`, at column 0) to a
+        // variable of the augmented circuit: the incoming values are wired to the given z_i and
+        // external inputs, and the rest are allocated as new witnesses
+        let n_vars = inner.num_instance_variables - 1 + inner.num_witness_variables;
+        let mut vars: Vec<Option<FpVar<F>>> = vec![None; n_vars];
+        for (index, var) in self
+            .io_map
+            .state_in
+            .iter()
+            .zip(z_i)
+            .chain(self.io_map.external_inputs.iter().zip(external_inputs.0))
+        {
+            *vars.get_mut(*index).ok_or(SynthesisError::Unsatisfiable)? = Some(var);
+        }
+        let vars = vars
+            .into_iter()
+            .enumerate()
+            .map(|(index, var)| match var {
+                Some(var) => Ok(var),
+                None => FpVar::new_witness(cs.clone(), || {
+                    Self::assigned_value(&inner, index).ok_or(SynthesisError::AssignmentMissing)
+                }),
+            })
+            .collect::<Result<Vec<FpVar<F>>, SynthesisError>>()?;
+
+        let to_lc = |row: &[(F, usize)]| {
+            let mut res = lc!();
+            for (coeff, col) in row.iter() {
+                res = if *col == 0 {
+                    res + (*coeff, Variable::One)
+                } else {
+                    match &vars[col - 1] {
+                        FpVar::Var(v) => res + (*coeff, v.variable),
+                        FpVar::Constant(v) => res + (*coeff * v, Variable::One),
+                    }
+                };
+            }
+            res
+        };
+        for ((a_i, b_i), c_i) in a.iter().zip(b.iter()).zip(c.iter()) {
+            cs.enforce_constraint(to_lc(a_i), to_lc(b_i), to_lc(c_i))?;
+        }
+
+        Ok(self
+            .io_map
+            .state_out
+            .iter()
+            .map(|index| vars[*index].clone())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::eq::EqGadget;
+
+    use crate::frontend::utils::{CubicFCircuit, WrapperCircuit};
+
+    /// CubicCircuit is a plain arkworks circuit which checks that `y = x^3 + x + w`, where `y` is a
+    /// public input and `x`, `w` are witnesses.
+    #[derive(Clone, Debug)]
+    struct CubicCircuit {
+        x: Fr,
+        w: Fr,
+    }
+    impl ConstraintSynthesizer<Fr> for CubicCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let y = FpVar::new_input(cs.clone(), || {
+                Ok(self.x * self.x * self.x + self.x + self.w)
+            })?;
+            let x = FpVar::new_witness(cs.clone(), || Ok(self.x))?;
+            let w = FpVar::new_witness(cs.clone(), || Ok(self.w))?;
+            y.enforce_equal(&(&x * &x * &x + &x + &w))
+        }
+    }
+
+    fn cubic_builder() -> ArkCircuitBuilder<Fr, CubicCircuit> {
+        Rc::new(|_i, z_i, external_inputs| CubicCircuit {
+            x: z_i[0],
+            w: external_inputs[0],
+        })
+    }
+
+    fn cubic_io_map() -> ArkCircuitIOMap {
+        // y is the public input at index 0, followed by the witnesses x and w
+        ArkCircuitIOMap {
+            state_in: vec![1],
+            state_out: vec![0],
+            external_inputs: vec![2],
+        }
+    }
+
+    #[test]
+    fn test_ark_circuit_adapter() {
+        let adapter =
+            ArkCircuitAdapter::<Fr, CubicCircuit, 1>::new((cubic_builder(), cubic_io_map()))
+                .unwrap();
+        assert_eq!(adapter.state_len(), 1);
+
+        // with w = 5 the adapted circuit computes the same step as the CubicFCircuit
+        let z_i = vec![Fr::from(3_u32)];
+        let external_inputs = VecF(vec![Fr::from(5_u32)]);
+        let z_i1 = adapter
+            .step_native(0, z_i.clone(), external_inputs.clone())
+            .unwrap();
+        let cubic = CubicFCircuit::<Fr>::new(()).unwrap();
+        assert_eq!(z_i1, cubic.step_native(0, z_i.clone(), ()).unwrap());
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.clone())).unwrap();
+        let external_inputs_var =
+            VecFpVar::<Fr, 1>::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+        let z_i1_var = adapter
+            .generate_step_constraints(cs.clone(), 0, z_i_var, external_inputs_var)
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+
+        // the adapted circuit is satisfied only for the correct next state, where the
+        // WrapperCircuit sets the external input w to 0
+        for (z_i1, satisfied) in [(30_u32, true), (35_u32, false)] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let wrapper_circuit = WrapperCircuit::<Fr, ArkCircuitAdapter<Fr, CubicCircuit, 1>> {
+                FC: adapter.clone(),
+                z_i: Some(z_i.clone()),
+                z_i1: Some(vec![Fr::from(z_i1)]),
+            };
+            wrapper_circuit.generate_constraints(cs.clone()).unwrap();
+            assert_eq!(cs.is_satisfied().unwrap(), satisfied);
+        }
+    }
+
+    #[test]
+    fn test_ark_circuit_adapter_io_map_errors() {
+        let new = |io_map| ArkCircuitAdapter::<Fr, CubicCircuit, 1>::new((cubic_builder(), io_map));
+
+        // different number of incoming and outgoing state variables
+        let mut io_map = cubic_io_map();
+        io_map.state_out.push(2);
+        assert!(new(io_map).is_err());
+
+        // different number of external inputs than `L`
+        let mut io_map = cubic_io_map();
+        io_map.external_inputs = vec![];
+        assert!(new(io_map).is_err());
+
+        // a variable wired to both the state and the external inputs
+        let mut io_map = cubic_io_map();
+        io_map.external_inputs = vec![1];
+        assert!(new(io_map).is_err());
+
+        // a variable that does not exist in the circuit
+        let mut io_map = cubic_io_map();
+        io_map.external_inputs = vec![3];
+        assert!(matches!(new(io_map), Err(Error::OutOfBounds)));
+    }
+}
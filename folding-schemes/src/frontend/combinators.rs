@@ -0,0 +1,469 @@
+/// contains combinators to compose several FCircuits into the FCircuit of a single IVC step
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    fields::fp::FpVar,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, Namespace, SynthesisError};
+use ark_std::{borrow::Borrow, marker::PhantomData, string::ToString, vec::Vec};
+
+use super::FCircuit;
+use crate::Error;
+
+/// PairVar is the in-circuit representation of a pair `(A, B)` of external inputs, where `VA` and
+/// `VB` are the in-circuit representations of `A` and `B`. It is the `ExternalInputsVar` of the
+/// [`Chain`] and [`Parallel`] combinators, whose external inputs are the pair of the external
+/// inputs of the combined circuits.
+#[derive(Clone, Debug)]
+pub struct PairVar<VA, VB>(pub VA, pub VB);
+
+impl<F: PrimeField, A, B, VA: AllocVar<A, F>, VB: AllocVar<B, F>> AllocVar<(A, B), F>
+    for PairVar<VA, VB>
+{
+    fn new_variable<T: Borrow<(A, B)>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|val| {
+            let cs = cs.into().cs();
+            let (a, b) = val.borrow();
+            Ok(Self(
+                VA::new_variable(cs.clone(), || Ok(a), mode)?,
+                VB::new_variable(cs, || Ok(b), mode)?,
+            ))
+        })
+    }
+}
+
+/// RepeatInputs wraps the `N` external inputs of the [`Repeat`] combinator, one for each
+/// repetition of the inner circuit. Its `Default` value is a vector of `N` default values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RepeatInputs<T, const N: usize>(pub Vec<T>);
+
+impl<T: Clone + Default, const N: usize> Default for RepeatInputs<T, N> {
+    fn default() -> Self {
+        Self(vec![T::default(); N])
+    }
+}
+
+/// RepeatInputsVar is the in-circuit counterpart of [`RepeatInputs`].
+#[derive(Clone, Debug)]
+pub struct RepeatInputsVar<V, const N: usize>(pub Vec<V>);
+
+impl<F: PrimeField, T, V: AllocVar<T, F>, const N: usize> AllocVar<RepeatInputs<T, N>, F>
+    for RepeatInputsVar<V, N>
+{
+    fn new_variable<U: Borrow<RepeatInputs<T, N>>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<U, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|val| {
+            let cs = cs.into().cs();
+            let v = &val.borrow().0;
+            if v.len() != N {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+            Ok(Self(
+                v.iter()
+                    .map(|t| V::new_variable(cs.clone(), || Ok(t), mode))
+                    .collect::<Result<_, _>>()?,
+            ))
+        })
+    }
+}
+
+/// Rejects the combination of circuits that declare per-step constants or custom gates, which are
+/// not forwarded by the combinators.
+fn check_no_step_constants<F: PrimeField, FC: FCircuit<F>>(f_circuit: &FC) -> Result<(), Error> {
+    if !f_circuit.step_constants().is_empty() {
+        return Err(Error::NotSupportedYet(
+            "per-step constants in combined FCircuits".to_string(),
+        ));
+    }
+    if f_circuit.custom_gates().is_some() {
+        return Err(Error::NotSupportedYet(
+            "custom gates in combined FCircuits".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Chain runs the circuit `A` and feeds its output state into the circuit `B`, as a single step.
+/// Both circuits must have the same state length. The external inputs are the pair of the
+/// external inputs of `A` and `B`.
+#[derive(Clone, Debug)]
+pub struct Chain<F: PrimeField, A: FCircuit<F>, B: FCircuit<F>> {
+    pub a: A,
+    pub b: B,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField, A: FCircuit<F>, B: FCircuit<F>> FCircuit<F> for Chain<F, A, B> {
+    /// (A::Params, B::Params)
+    type Params = (A::Params, B::Params);
+    type ExternalInputs = (A::ExternalInputs, B::ExternalInputs);
+    type ExternalInputsVar = PairVar<A::ExternalInputsVar, B::ExternalInputsVar>;
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        let (a, b) = (A::new(params.0)?, B::new(params.1)?);
+        if a.state_len() != b.state_len() {
+            return Err(Error::NotSameLength(
+                "A.state_len()".to_string(),
+                a.state_len(),
+                "B.state_len()".to_string(),
+                b.state_len(),
+            ));
+        }
+        check_no_step_constants(&a)?;
+        check_no_step_constants(&b)?;
+        Ok(Self {
+            a,
+            b,
+            _f: PhantomData,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        self.a.state_len()
+    }
+
+    fn step_native(
+        &self,
+        i: usize,
+        z_i: Vec<F>,
+        external_inputs: Self::ExternalInputs,
+    ) -> Result<Vec<F>, Error> {
+        let (a_inputs, b_inputs) = external_inputs;
+        let z_mid = self.a.step_native(i, z_i, a_inputs)?;
+        self.b.step_native(i, z_mid, b_inputs)
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Self::ExternalInputsVar,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let PairVar(a_inputs, b_inputs) = external_inputs;
+        let z_mid = self
+            .a
+            .generate_step_constraints(cs.clone(), i, z_i, a_inputs)?;
+        self.b.generate_step_constraints(cs, i, z_mid, b_inputs)
+    }
+}
+
+/// Parallel runs the circuits `A` and `B` side by side, where the state is the concatenation of
+/// the state of `A` followed by the state of `B`, and the external inputs are the pair of the
+/// external inputs of `A` and `B`.
+#[derive(Clone, Debug)]
+pub struct Parallel<F: PrimeField, A: FCircuit<F>, B: FCircuit<F>> {
+    pub a: A,
+    pub b: B,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField, A: FCircuit<F>, B: FCircuit<F>> FCircuit<F> for Parallel<F, A, B> {
+    /// (A::Params, B::Params)
+    type Params = (A::Params, B::Params);
+    type ExternalInputs = (A::ExternalInputs, B::ExternalInputs);
+    type ExternalInputsVar = PairVar<A::ExternalInputsVar, B::ExternalInputsVar>;
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        let (a, b) = (A::new(params.0)?, B::new(params.1)?);
+        check_no_step_constants(&a)?;
+        check_no_step_constants(&b)?;
+        Ok(Self {
+            a,
+            b,
+            _f: PhantomData,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        self.a.state_len() + self.b.state_len()
+    }
+
+    fn step_native(
+        &self,
+        i: usize,
+        mut z_i: Vec<F>,
+        external_inputs: Self::ExternalInputs,
+    ) -> Result<Vec<F>, Error> {
+        if z_i.len() != self.state_len() {
+            return Err(Error::NotExpectedLength(z_i.len(), self.state_len()));
+        }
+        let (a_inputs, b_inputs) = external_inputs;
+        let z_b = z_i.split_off(self.a.state_len());
+        Ok([
+            self.a.step_native(i, z_i, a_inputs)?,
+            self.b.step_native(i, z_b, b_inputs)?,
+        ]
+        .concat())
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        i: usize,
+        mut z_i: Vec<FpVar<F>>,
+        external_inputs: Self::ExternalInputsVar,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        if z_i.len() != self.state_len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let PairVar(a_inputs, b_inputs) = external_inputs;
+        let z_b = z_i.split_off(self.a.state_len());
+        Ok([
+            self.a
+                .generate_step_constraints(cs.clone(), i, z_i, a_inputs)?,
+            self.b.generate_step_constraints(cs, i, z_b, b_inputs)?,
+        ]
+        .concat())
+    }
+}
+
+/// Repeat runs the circuit `A` `N` times in a row, as a single step. The external inputs are the
+/// `N` external inputs of each of the repetitions of `A`.
+#[derive(Clone, Debug)]
+pub struct Repeat<F: PrimeField, A: FCircuit<F>, const N: usize> {
+    pub a: A,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField, A: FCircuit<F>, const N: usize> FCircuit<F> for Repeat<F, A, N> {
+    type Params = A::Params;
+    type ExternalInputs = RepeatInputs<A::ExternalInputs, N>;
+    type ExternalInputsVar = RepeatInputsVar<A::ExternalInputsVar, N>;
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        if N == 0 {
+            return Err(Error::CantBeZero("Repeat N".to_string()));
+        }
+        let a = A::new(params)?;
+        check_no_step_constants(&a)?;
+        Ok(Self { a, _f: PhantomData })
+    }
+
+    fn state_len(&self) -> usize {
+        self.a.state_len()
+    }
+
+    fn step_native(
+        &self,
+        i: usize,
+        z_i: Vec<F>,
+        external_inputs: Self::ExternalInputs,
+    ) -> Result<Vec<F>, Error> {
+        if external_inputs.0.len() != N {
+            return Err(Error::NotExpectedLength(external_inputs.0.len(), N));
+        }
+        external_inputs
+            .0
+            .into_iter()
+            .try_fold(z_i, |z, inputs| self.a.step_native(i, z, inputs))
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Self::ExternalInputsVar,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        if external_inputs.0.len() != N {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        external_inputs.0.into_iter().try_fold(z_i, |z, inputs| {
+            self.a.generate_step_constraints(cs.clone(), i, z, inputs)
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use ark_bn254::{constraints::GVar, Fr, G1Projective as Projective};
+    use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+    use ark_r1cs_std::R1CSVar;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    use crate::commitment::pedersen::Pedersen;
+    use crate::folding::nova::{Nova, PreprocessorParam};
+    use crate::frontend::{
+        utils::{CubicFCircuit, StepConstantsFCircuit, WrongStateLenFCircuit},
+        VecF, VecFpVar,
+    };
+    use crate::transcript::poseidon::poseidon_canonical_config;
+    use crate::FoldingScheme;
+
+    /// AddFCircuit computes `z_{i+1} = z_i + w`, where `w` is its only external input
+    #[derive(Clone, Copy, Debug)]
+    struct AddFCircuit;
+    impl FCircuit<Fr> for AddFCircuit {
+        type Params = ();
+        type ExternalInputs = VecF<Fr, 1>;
+        type ExternalInputsVar = VecFpVar<Fr, 1>;
+        fn new(_params: Self::Params) -> Result<Self, Error> {
+            Ok(Self)
+        }
+        fn state_len(&self) -> usize {
+            1
+        }
+        fn step_native(
+            &self,
+            _i: usize,
+            z_i: Vec<Fr>,
+            external_inputs: Self::ExternalInputs,
+        ) -> Result<Vec<Fr>, Error> {
+            Ok(vec![z_i[0] + external_inputs.0[0]])
+        }
+        fn generate_step_constraints(
+            &self,
+            _cs: ConstraintSystemRef<Fr>,
+            _i: usize,
+            z_i: Vec<FpVar<Fr>>,
+            external_inputs: Self::ExternalInputsVar,
+        ) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+            Ok(vec![&z_i[0] + &external_inputs.0[0]])
+        }
+    }
+
+    /// checks that the given circuit computes the same next state natively and in-circuit
+    fn check_step<FC: FCircuit<Fr>>(
+        f_circuit: &FC,
+        z_i: Vec<Fr>,
+        external_inputs: FC::ExternalInputs,
+    ) -> Vec<Fr> {
+        let z_i1 = f_circuit
+            .step_native(0, z_i.clone(), external_inputs.clone())
+            .unwrap();
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let external_inputs_var =
+            FC::ExternalInputsVar::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+        let z_i1_var = f_circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, external_inputs_var)
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+        z_i1
+    }
+
+    #[test]
+    fn test_combinators() {
+        let cubic = CubicFCircuit::<Fr>::new(()).unwrap();
+        let z_0 = vec![Fr::from(3_u32)];
+        let cubic_twice = cubic
+            .step_native(1, cubic.step_native(0, z_0.clone(), ()).unwrap(), ())
+            .unwrap();
+
+        let chain = Chain::<Fr, CubicFCircuit<Fr>, CubicFCircuit<Fr>>::new(((), ())).unwrap();
+        assert_eq!(check_step(&chain, z_0.clone(), ((), ())), cubic_twice);
+
+        let repeat = Repeat::<Fr, CubicFCircuit<Fr>, 2>::new(()).unwrap();
+        assert_eq!(
+            check_step(&repeat, z_0.clone(), RepeatInputs::default()),
+            cubic_twice
+        );
+
+        // the external inputs of each circuit are given by the structure of the pair
+        let parallel = Parallel::<Fr, CubicFCircuit<Fr>, AddFCircuit>::new(((), ())).unwrap();
+        assert_eq!(parallel.state_len(), 2);
+        let z_i1 = check_step(
+            &parallel,
+            vec![Fr::from(3_u32), Fr::from(4_u32)],
+            ((), VecF(vec![Fr::from(5_u32)])),
+        );
+        assert_eq!(z_i1, vec![Fr::from(35_u32), Fr::from(9_u32)]);
+
+        let repeat_add = Repeat::<Fr, AddFCircuit, 3>::new(()).unwrap();
+        let inputs = RepeatInputs([1_u32, 2, 3].map(|w| VecF(vec![Fr::from(w)])).to_vec());
+        assert_eq!(
+            check_step(&repeat_add, z_0.clone(), inputs),
+            vec![Fr::from(9_u32)]
+        );
+    }
+
+    #[test]
+    fn test_combinators_errors() {
+        // the state lengths of chained circuits must match
+        assert!(Chain::<Fr, CubicFCircuit<Fr>, WrongStateLenFCircuit<Fr>>::new(((), ())).is_err());
+        // combined circuits can not declare per-step constants
+        assert!(
+            Parallel::<Fr, CubicFCircuit<Fr>, StepConstantsFCircuit<Fr>>::new(((), 4)).is_err()
+        );
+        // a repetition needs at least one circuit
+        assert!(Repeat::<Fr, CubicFCircuit<Fr>, 0>::new(()).is_err());
+
+        // a wrong number of external inputs is rejected both natively and in-circuit
+        let repeat_add = Repeat::<Fr, AddFCircuit, 3>::new(()).unwrap();
+        let inputs = RepeatInputs(vec![VecF(vec![Fr::from(1_u32)]); 2]);
+        assert!(repeat_add
+            .step_native(0, vec![Fr::from(1_u32)], inputs.clone())
+            .is_err());
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        assert!(
+            RepeatInputsVar::<VecFpVar<Fr, 1>, 3>::new_witness(cs.clone(), || Ok(inputs)).is_err()
+        );
+    }
+
+    #[test]
+    fn test_fold_chain() {
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let n_steps = 3;
+
+        type NChain = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            Chain<Fr, CubicFCircuit<Fr>, CubicFCircuit<Fr>>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        let chain = Chain::<Fr, CubicFCircuit<Fr>, CubicFCircuit<Fr>>::new(((), ())).unwrap();
+        let params = NChain::preprocess(
+            ark_std::test_rng(),
+            &PreprocessorParam::new(poseidon_config.clone(), chain.clone()),
+        )
+        .unwrap();
+        let mut nova_chain = NChain::init(&params, chain, vec![Fr::from(3_u32)]).unwrap();
+        for _ in 0..n_steps {
+            nova_chain
+                .prove_step(ark_std::test_rng(), ((), ()), None)
+                .unwrap();
+        }
+        NChain::verify(params.1, nova_chain.ivc_proof()).unwrap();
+
+        type NCubic = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        let cubic = CubicFCircuit::<Fr>::new(()).unwrap();
+        let params = NCubic::preprocess(
+            ark_std::test_rng(),
+            &PreprocessorParam::new(poseidon_config, cubic),
+        )
+        .unwrap();
+        let mut nova_cubic = NCubic::init(&params, cubic, vec![Fr::from(3_u32)]).unwrap();
+        for _ in 0..2 * n_steps {
+            nova_cubic
+                .prove_step(ark_std::test_rng(), (), None)
+                .unwrap();
+        }
+        NCubic::verify(params.1, nova_cubic.ivc_proof()).unwrap();
+
+        // each step of the chain runs the cubic circuit twice
+        assert_eq!(nova_chain.state().z_i, nova_cubic.state().z_i);
+    }
+}
@@ -1,9 +1,16 @@
+use crate::arith::ccs::CCS;
 use crate::Error;
 use ark_ff::PrimeField;
-use ark_r1cs_std::fields::fp::FpVar;
-use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
-use ark_std::fmt::Debug;
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+};
+use ark_relations::r1cs::{ConstraintSystem, ConstraintSystemRef, Namespace, SynthesisError};
+use ark_std::{borrow::Borrow, fmt::Debug, marker::PhantomData, string::ToString, vec::Vec};
 
+pub mod adapter;
+pub mod combinators;
 pub mod utils;
 
 /// FCircuit defines the trait of the circuit of the F function, which is the one being folded (ie.
@@ -13,6 +20,14 @@ pub mod utils;
 pub trait FCircuit<F: PrimeField>: Clone + Debug {
     type Params: Debug;
 
+    /// the type of the external inputs, ie. the inputs that are not part of the state. Its
+    /// `Default` value is used to generate the circuit shape at setup, so it must have the same
+    /// structure as the values passed at each step. Circuits without external inputs can use `()`.
+    type ExternalInputs: Clone + Debug + Default;
+
+    /// the in-circuit representation of `ExternalInputs`
+    type ExternalInputsVar: AllocVar<Self::ExternalInputs, F> + Clone + Debug;
+
     /// returns a new FCircuit instance
     fn new(params: Self::Params) -> Result<Self, Error>;
 
@@ -20,10 +35,6 @@ pub trait FCircuit<F: PrimeField>: Clone + Debug {
     /// FCircuit inputs.
     fn state_len(&self) -> usize;
 
-    /// returns the number of elements in the external inputs used by the FCircuit. External inputs
-    /// are optional, and in case no external inputs are used, this method should return 0.
-    fn external_inputs_len(&self) -> usize;
-
     /// computes the next state values in place, assigning z_{i+1} into z_i, and computing the new
     /// z_{i+1}
     fn step_native(
@@ -32,7 +43,7 @@ pub trait FCircuit<F: PrimeField>: Clone + Debug {
         &self,
         i: usize,
         z_i: Vec<F>,
-        external_inputs: Vec<F>, // inputs that are not part of the state
+        external_inputs: Self::ExternalInputs, // inputs that are not part of the state
     ) -> Result<Vec<F>, Error>;
 
     /// generates the constraints for the step of F for the given z_i
@@ -43,17 +54,294 @@ pub trait FCircuit<F: PrimeField>: Clone + Debug {
         cs: ConstraintSystemRef<F>,
         i: usize,
         z_i: Vec<FpVar<F>>,
-        external_inputs: Vec<FpVar<F>>, // inputs that are not part of the state
+        external_inputs: Self::ExternalInputsVar, // inputs that are not part of the state
     ) -> Result<Vec<FpVar<F>>, SynthesisError>;
+
+    /// returns the table of public per-step constants of the FCircuit, where the `i`-th row holds
+    /// the constants used at the `i`-th step and all the rows have the same length. The table is
+    /// bound to the public parameters hash at preprocessing, and inside the augmented circuit the
+    /// row of each step is selected from the folded step counter, so that the prover can not pick
+    /// a different one. An empty table (the default) means that no per-step constants are used.
+    fn step_constants(&self) -> Vec<Vec<F>> {
+        vec![]
+    }
+
+    /// generates the constraints for the step of F for the given z_i, where `step_constants` are
+    /// the in-circuit constants of the `i`-th row of `step_constants()`. Circuits declaring
+    /// per-step constants override this method, by default the constants are ignored and
+    /// `generate_step_constraints` is called.
+    fn generate_step_constraints_with_constants(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Self::ExternalInputsVar,
+        _step_constants: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        self.generate_step_constraints(cs, i, z_i, external_inputs)
+    }
+
+    /// returns the custom gates of the step, as a CCS over `(1, z_i, z_{i+1})`, ie. with
+    /// `n = 1 + 2 * state_len` columns. The gates are not synthesized by
+    /// `generate_step_constraints`, which only allocates the z_{i+1} computed by `step_native`:
+    /// they are appended to the CCS of the augmented circuit, so that they keep their degree
+    /// instead of being split into R1CS multiplications. Only HyperNova folds CCS, the folding
+    /// schemes over R1CS reject the FCircuits that declare custom gates. By default there are no
+    /// custom gates.
+    fn custom_gates(&self) -> Option<CCS<F>> {
+        None
+    }
+}
+
+/// Generates the constraints of the step of the given FCircuit, as done inside the augmented
+/// circuits: the per-step constants are selected from `i_var`, the in-circuit step counter, and
+/// the length of the returned z_{i+1} is checked against the FCircuit state length, so that a
+/// misbehaving FCircuit fails at synthesis instead of changing the arity of the hashed state.
+pub(crate) fn step_constraints<F: PrimeField, FC: FCircuit<F>>(
+    f_circuit: &FC,
+    cs: ConstraintSystemRef<F>,
+    i: usize,
+    i_var: &FpVar<F>,
+    z_i: Vec<FpVar<F>>,
+    external_inputs: FC::ExternalInputsVar,
+) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    let step_constants = StepConstantsGadget::select(&f_circuit.step_constants(), i_var)?;
+    let z_i1 = f_circuit.generate_step_constraints_with_constants(
+        cs,
+        i,
+        z_i,
+        external_inputs,
+        step_constants,
+    )?;
+    if z_i1.len() != f_circuit.state_len() {
+        log::error!(
+            "the step of the FCircuit returned a z_{{i+1}} of length {}, but its state length is {}",
+            z_i1.len(),
+            f_circuit.state_len()
+        );
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    Ok(z_i1)
+}
+
+/// Checks that all the rows of the per-step constants table of the given FCircuit have the same
+/// length, returning the table.
+pub fn check_step_constants<F: PrimeField, FC: FCircuit<F>>(
+    f_circuit: &FC,
+) -> Result<Vec<Vec<F>>, Error> {
+    let step_constants = f_circuit.step_constants();
+    check_step_constants_rows(&step_constants)?;
+    Ok(step_constants)
+}
+
+/// Checks that all the rows of the per-step constants table have the same length.
+pub(crate) fn check_step_constants_rows<F: PrimeField>(
+    step_constants: &[Vec<F>],
+) -> Result<(), Error> {
+    if let Some(first) = step_constants.first() {
+        for row in step_constants.iter() {
+            if row.len() != first.len() {
+                return Err(Error::NotSameLength(
+                    "step_constants[0].len()".to_string(),
+                    first.len(),
+                    "step_constants row length".to_string(),
+                    row.len(),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the per-step constants table, if any, has a row for the step `i`.
+pub(crate) fn check_step_constants_row<F: PrimeField>(
+    step_constants: &[Vec<F>],
+    i: usize,
+) -> Result<(), Error> {
+    if !step_constants.is_empty() && i >= step_constants.len() {
+        return Err(Error::StepConstantsExhausted(i, step_constants.len()));
+    }
+    Ok(())
+}
+
+/// StepConstantsGadget selects in-circuit the row of a table of per-step constants.
+pub struct StepConstantsGadget<F: PrimeField> {
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> StepConstantsGadget<F> {
+    /// Returns the row `i` of the given table as constant linear combinations of the selectors
+    /// `i == j`, for each row index `j`. Exactly one selector is enforced to be set, so that the
+    /// circuit is unsatisfiable if `i` is not a valid row index.
+    pub fn select(table: &[Vec<F>], i: &FpVar<F>) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let Some(first) = table.first() else {
+            return Ok(vec![]);
+        };
+        let mut row = vec![FpVar::<F>::zero(); first.len()];
+        let mut n_selected = FpVar::<F>::zero();
+        for (j, table_row) in table.iter().enumerate() {
+            if table_row.len() != first.len() {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+            let selector: FpVar<F> = i.is_eq(&FpVar::constant(F::from(j as u64)))?.into();
+            for (r, c) in row.iter_mut().zip(table_row) {
+                *r += &selector * *c;
+            }
+            n_selected += selector;
+        }
+        n_selected.enforce_equal(&FpVar::one())?;
+        Ok(row)
+    }
+}
+
+/// Checks that the state length declared by the given FCircuit is not zero and matches the length
+/// of the next state returned by its `generate_step_constraints` method, returning the state
+/// length. This is used at preprocessing, so that a misconfigured FCircuit is detected before
+/// building the augmented circuit.
+pub fn check_state_len<F: PrimeField, FC: FCircuit<F>>(f_circuit: &FC) -> Result<usize, Error> {
+    let state_len = f_circuit.state_len();
+    if state_len == 0 {
+        return Err(Error::CantBeZero("FCircuit state length".to_string()));
+    }
+    let cs = ConstraintSystem::<F>::new_ref();
+    let z_i = Vec::<FpVar<F>>::new_witness(cs.clone(), || Ok(vec![F::zero(); state_len]))?;
+    let external_inputs =
+        FC::ExternalInputsVar::new_witness(cs.clone(), || Ok(FC::ExternalInputs::default()))?;
+    let step_constants = f_circuit
+        .step_constants()
+        .first()
+        .map(|row| Vec::<FpVar<F>>::new_constant(cs.clone(), row.clone()))
+        .transpose()?
+        .unwrap_or_default();
+    let z_i1 = f_circuit.generate_step_constraints_with_constants(
+        cs,
+        0,
+        z_i,
+        external_inputs,
+        step_constants,
+    )?;
+    if z_i1.len() != state_len {
+        return Err(Error::StateLenMismatch(
+            "z_{i+1}".to_string(),
+            z_i1.len(),
+            state_len,
+        ));
+    }
+    Ok(state_len)
+}
+
+/// Rejects the FCircuits that declare custom gates, which can not be expressed in the R1CS folded
+/// by the given folding scheme.
+pub(crate) fn check_no_custom_gates<F: PrimeField, FC: FCircuit<F>>(
+    f_circuit: &FC,
+    folding_scheme: &str,
+) -> Result<(), Error> {
+    if f_circuit.custom_gates().is_some() {
+        return Err(Error::NotSupported(format!(
+            "FCircuit custom gates in {}",
+            folding_scheme
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that the given state has the length expected by the folding scheme parameters.
+pub(crate) fn check_state<F: PrimeField>(
+    name: &str,
+    z: &[F],
+    state_len: usize,
+) -> Result<(), Error> {
+    if z.len() != state_len {
+        return Err(Error::StateLenMismatch(
+            name.to_string(),
+            z.len(),
+            state_len,
+        ));
+    }
+    Ok(())
+}
+
+/// VecF wraps a vector of `L` field elements, to be used as `FCircuit::ExternalInputs` by circuits
+/// that take their external inputs as a flat vector. Its `Default` value is a vector of `L` zeros.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VecF<F: PrimeField, const L: usize>(pub Vec<F>);
+
+impl<F: PrimeField, const L: usize> Default for VecF<F, L> {
+    fn default() -> Self {
+        Self(vec![F::zero(); L])
+    }
+}
+
+/// VecFpVar is the in-circuit counterpart of [`VecF`], to be used as
+/// `FCircuit::ExternalInputsVar`.
+#[derive(Clone, Debug)]
+pub struct VecFpVar<F: PrimeField, const L: usize>(pub Vec<FpVar<F>>);
+
+impl<F: PrimeField, const L: usize> AllocVar<VecF<F, L>, F> for VecFpVar<F, L> {
+    fn new_variable<T: Borrow<VecF<F, L>>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        f().and_then(|val| {
+            let cs = cs.into();
+            let v = &val.borrow().0;
+            if v.len() != L {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+            Ok(Self(Vec::new_variable(cs, || Ok(v.clone()), mode)?))
+        })
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
     use ark_bn254::Fr;
+    use ark_r1cs_std::R1CSVar;
     use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
 
-    use utils::{CubicFCircuit, CustomFCircuit, WrapperCircuit};
+    use utils::{
+        CubicFCircuit, CustomFCircuit, StepConstantsFCircuit, WrapperCircuit, WrongStateLenFCircuit,
+    };
+
+    /// Message logged by `step_constraints` for the step of `WrongStateLenFCircuit`
+    pub(crate) const WRONG_STATE_LEN_LOG: &str =
+        "the step of the FCircuit returned a z_{i+1} of length 1, but its state length is 2";
+
+    /// Runs `f`, returning its output together with the messages logged by the current thread
+    /// while it ran. The logger is global to the test binary, but each thread only sees its own
+    /// messages, so that the tests running in parallel don't mix them.
+    pub(crate) fn capture_logs<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+        use std::cell::RefCell;
+
+        std::thread_local! {
+            static LOGS: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+        }
+        struct ThreadLogger;
+        impl log::Log for ThreadLogger {
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                true
+            }
+            fn log(&self, record: &log::Record) {
+                LOGS.with(|logs| {
+                    if let Some(logs) = logs.borrow_mut().as_mut() {
+                        logs.push(record.args().to_string());
+                    }
+                });
+            }
+            fn flush(&self) {}
+        }
+        static LOGGER: ThreadLogger = ThreadLogger;
+        // fails if the logger was already set by a previous call, which is fine
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(log::LevelFilter::Error);
+
+        LOGS.with(|logs| *logs.borrow_mut() = Some(Vec::new()));
+        let output = f();
+        let logs = LOGS.with(|logs| logs.borrow_mut().take().unwrap_or_default());
+        (output, logs)
+    }
 
     #[test]
     fn test_testfcircuit() {
@@ -78,9 +366,77 @@ pub mod tests {
         let wrapper_circuit = WrapperCircuit::<Fr, CustomFCircuit<Fr>> {
             FC: custom_circuit,
             z_i: Some(z_i.clone()),
-            z_i1: Some(custom_circuit.step_native(0, z_i, vec![]).unwrap()),
+            z_i1: Some(custom_circuit.step_native(0, z_i, ()).unwrap()),
         };
         wrapper_circuit.generate_constraints(cs.clone()).unwrap();
         assert_eq!(cs.num_constraints(), n_constraints);
     }
+
+    #[test]
+    fn test_step_constraints_state_len() {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let i = FpVar::new_witness(cs.clone(), || Ok(Fr::from(0_u32))).unwrap();
+        let z_i = Vec::new_witness(cs.clone(), || Ok(vec![Fr::from(3_u32); 2])).unwrap();
+
+        // the step returns 1 element instead of the 2 of the state
+        let F_circuit = WrongStateLenFCircuit::<Fr>::new(()).unwrap();
+        let (result, logs) =
+            capture_logs(|| step_constraints(&F_circuit, cs.clone(), 0, &i, z_i, ()));
+        assert!(matches!(result, Err(SynthesisError::Unsatisfiable)));
+        assert_eq!(logs, vec![WRONG_STATE_LEN_LOG]);
+
+        let F_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let z_i = Vec::new_witness(cs.clone(), || Ok(vec![Fr::from(3_u32)])).unwrap();
+        let z_i1 = step_constraints(&F_circuit, cs.clone(), 0, &i, z_i, ()).unwrap();
+        assert_eq!(z_i1.value().unwrap(), vec![Fr::from(35_u32)]);
+    }
+
+    #[test]
+    fn test_vecfpvar() {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let v = VecF::<Fr, 3>(vec![Fr::from(1_u32), Fr::from(2_u32), Fr::from(3_u32)]);
+        let v_var = VecFpVar::<Fr, 3>::new_witness(cs.clone(), || Ok(v.clone())).unwrap();
+        assert_eq!(v_var.0.value().unwrap(), v.0);
+        assert_eq!(VecF::<Fr, 3>::default().0, vec![Fr::from(0_u32); 3]);
+
+        // a vector of a different length than `L` can not be allocated
+        let w = VecF::<Fr, 3>(vec![Fr::from(1_u32)]);
+        assert!(VecFpVar::<Fr, 3>::new_witness(cs.clone(), || Ok(w)).is_err());
+    }
+
+    #[test]
+    fn test_step_constants_gadget() {
+        let table = StepConstantsFCircuit::<Fr>::new(16)
+            .unwrap()
+            .step_constants();
+
+        // each step selects its own row of the table
+        for (j, row) in table.iter().enumerate() {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let i = FpVar::new_witness(cs.clone(), || Ok(Fr::from(j as u32))).unwrap();
+            let selected = StepConstantsGadget::select(&table, &i).unwrap();
+            assert_eq!(selected.value().unwrap(), *row);
+            assert!(cs.is_satisfied().unwrap());
+        }
+
+        // a prover claiming the constants of a different row than the one of its index does not
+        // satisfy the constraints
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let i = FpVar::new_witness(cs.clone(), || Ok(Fr::from(5_u32))).unwrap();
+        let selected = StepConstantsGadget::select(&table, &i).unwrap();
+        let forged = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(table[4].clone())).unwrap();
+        selected.enforce_equal(&forged).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+
+        // an index out of the table does not select any row
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let i = FpVar::new_witness(cs.clone(), || Ok(Fr::from(16_u32))).unwrap();
+        StepConstantsGadget::select(&table, &i).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+
+        // without a table no constants are returned
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let i = FpVar::new_witness(cs.clone(), || Ok(Fr::from(0_u32))).unwrap();
+        assert!(StepConstantsGadget::select(&[], &i).unwrap().is_empty());
+    }
 }
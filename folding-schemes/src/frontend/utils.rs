@@ -1,43 +1,78 @@
 use ark_ff::PrimeField;
+#[cfg(any(test, feature = "test-utils"))]
+use ark_r1cs_std::R1CSVar;
 use ark_r1cs_std::{
     alloc::AllocVar,
     fields::{fp::FpVar, FieldVar},
 };
 use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
-use ark_std::marker::PhantomData;
 use ark_std::{fmt::Debug, Zero};
+use ark_std::{marker::PhantomData, vec::Vec};
 
 use super::FCircuit;
+#[cfg(any(test, feature = "test-utils"))]
+use crate::arith::ccs::{builder::CCSBuilder, CCS};
 use crate::Error;
 
-/// DummyCircuit is a circuit that has dummy state and external inputs whose
-/// lengths are specified in the `state_len` and `external_inputs_len`
-/// parameters, without any constraints.
+/// DummyCircuit is a circuit that has a dummy state whose length is specified
+/// in the `state_len` parameter, without any constraints and without external
+/// inputs. Natively, each step maps any state to the all-zeros state of the same length, ie.
+/// `z_{i+1} = [0; state_len]`, which makes it useful to measure the cost of the folding itself.
+///
+/// ```
+/// use ark_bn254::{constraints::GVar, Fr, G1Projective as Projective};
+/// use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+/// use folding_schemes::{
+///     commitment::pedersen::Pedersen,
+///     folding::nova::{Nova, PreprocessorParam},
+///     frontend::{utils::DummyCircuit, FCircuit},
+///     transcript::poseidon::poseidon_canonical_config,
+///     FoldingScheme,
+/// };
+///
+/// type N = Nova<
+///     Projective,
+///     GVar,
+///     Projective2,
+///     GVar2,
+///     DummyCircuit,
+///     Pedersen<Projective>,
+///     Pedersen<Projective2>,
+///     false,
+/// >;
+///
+/// let mut rng = ark_std::test_rng();
+/// let F_circuit = <DummyCircuit as FCircuit<Fr>>::new(3)?;
+/// let poseidon_config = poseidon_canonical_config::<Fr>();
+/// let params = N::preprocess(&mut rng, &PreprocessorParam::new(poseidon_config, F_circuit.clone()))?;
+/// let mut nova = N::init(&params, F_circuit, vec![Fr::from(1_u32); 3])?;
+/// for _ in 0..2 {
+///     nova.prove_step(&mut rng, (), None)?;
+/// }
+/// assert_eq!(nova.state().z_i, vec![Fr::from(0_u32); 3]);
+/// N::verify(params.1, nova.ivc_proof())?;
+/// # Ok::<(), folding_schemes::Error>(())
+/// ```
 #[derive(Clone, Debug)]
 pub struct DummyCircuit {
     state_len: usize,
-    external_inputs_len: usize,
 }
 impl<F: PrimeField> FCircuit<F> for DummyCircuit {
-    type Params = (usize, usize);
+    type Params = usize;
+    type ExternalInputs = ();
+    type ExternalInputsVar = ();
 
-    fn new((state_len, external_inputs_len): Self::Params) -> Result<Self, Error> {
-        Ok(Self {
-            state_len,
-            external_inputs_len,
-        })
+    fn new(state_len: Self::Params) -> Result<Self, Error> {
+        Ok(Self { state_len })
     }
     fn state_len(&self) -> usize {
         self.state_len
     }
-    fn external_inputs_len(&self) -> usize {
-        self.external_inputs_len
-    }
     fn step_native(
         &self,
         _i: usize,
         _z_i: Vec<F>,
-        _external_inputs: Vec<F>,
+        _external_inputs: Self::ExternalInputs,
     ) -> Result<Vec<F>, Error> {
         Ok(vec![F::zero(); self.state_len])
     }
@@ -46,7 +81,7 @@ impl<F: PrimeField> FCircuit<F> for DummyCircuit {
         cs: ConstraintSystemRef<F>,
         _i: usize,
         _z_i: Vec<FpVar<F>>,
-        _external_inputs: Vec<FpVar<F>>,
+        _external_inputs: Self::ExternalInputsVar,
     ) -> Result<Vec<FpVar<F>>, SynthesisError> {
         Vec::new_witness(cs.clone(), || Ok(vec![Zero::zero(); self.state_len]))
     }
@@ -55,30 +90,66 @@ impl<F: PrimeField> FCircuit<F> for DummyCircuit {
 /// CubicFCircuit is a struct that implements the FCircuit trait, for the R1CS example circuit
 /// from https://www.vitalik.ca/general/2016/12/10/qap.html, which checks `x^3 + x + 5 = y`.
 /// `z_i` is used as `x`, and `z_{i+1}` is used as `y`, and at the next step, `z_{i+1}` will be
-/// assigned to `z_i`, and a new `z+{i+1}` will be computted.
-#[cfg(test)]
+/// assigned to `z_i`, and a new `z+{i+1}` will be computted. That is, natively each step computes
+/// `z_{i+1} = z_i^3 + z_i + 5` over a state of a single element.
+///
+/// ```
+/// use ark_bn254::{constraints::GVar, Fr, G1Projective as Projective};
+/// use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+/// use folding_schemes::{
+///     commitment::pedersen::Pedersen,
+///     folding::nova::{Nova, PreprocessorParam},
+///     frontend::{utils::CubicFCircuit, FCircuit},
+///     transcript::poseidon::poseidon_canonical_config,
+///     FoldingScheme,
+/// };
+///
+/// type N = Nova<
+///     Projective,
+///     GVar,
+///     Projective2,
+///     GVar2,
+///     CubicFCircuit<Fr>,
+///     Pedersen<Projective>,
+///     Pedersen<Projective2>,
+///     false,
+/// >;
+///
+/// let mut rng = ark_std::test_rng();
+/// let F_circuit = CubicFCircuit::<Fr>::new(())?;
+/// let poseidon_config = poseidon_canonical_config::<Fr>();
+/// let params = N::preprocess(&mut rng, &PreprocessorParam::new(poseidon_config, F_circuit))?;
+/// let mut nova = N::init(&params, F_circuit, vec![Fr::from(3_u32)])?;
+/// for _ in 0..2 {
+///     nova.prove_step(&mut rng, (), None)?;
+/// }
+/// // 3 -> 3^3 + 3 + 5 = 35 -> 35^3 + 35 + 5 = 42915
+/// assert_eq!(nova.state().z_i, vec![Fr::from(42915_u32)]);
+/// N::verify(params.1, nova.ivc_proof())?;
+/// # Ok::<(), folding_schemes::Error>(())
+/// ```
+#[cfg(any(test, feature = "test-utils"))]
 #[derive(Clone, Copy, Debug)]
 pub struct CubicFCircuit<F: PrimeField> {
     _f: PhantomData<F>,
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-utils"))]
 impl<F: PrimeField> FCircuit<F> for CubicFCircuit<F> {
     type Params = ();
+    type ExternalInputs = ();
+    type ExternalInputsVar = ();
     fn new(_params: Self::Params) -> Result<Self, Error> {
         Ok(Self { _f: PhantomData })
     }
     fn state_len(&self) -> usize {
         1
     }
-    fn external_inputs_len(&self) -> usize {
-        0
-    }
     fn step_native(
         &self,
         _i: usize,
         z_i: Vec<F>,
-        _external_inputs: Vec<F>,
+        _external_inputs: Self::ExternalInputs,
     ) -> Result<Vec<F>, Error> {
         Ok(vec![z_i[0] * z_i[0] * z_i[0] + z_i[0] + F::from(5_u32)])
     }
@@ -87,7 +158,7 @@ impl<F: PrimeField> FCircuit<F> for CubicFCircuit<F> {
         cs: ConstraintSystemRef<F>,
         _i: usize,
         z_i: Vec<FpVar<F>>,
-        _external_inputs: Vec<FpVar<F>>,
+        _external_inputs: Self::ExternalInputsVar,
     ) -> Result<Vec<FpVar<F>>, SynthesisError> {
         let five = FpVar::<F>::new_constant(cs.clone(), F::from(5u32))?;
         let z_i = z_i[0].clone();
@@ -96,8 +167,303 @@ impl<F: PrimeField> FCircuit<F> for CubicFCircuit<F> {
     }
 }
 
+/// QuinticFCircuit computes `z_{i+1} = z_i^5 + z_i + 5` over a state of a single element, where
+/// the degree-5 gate `z_i^5 + z_i + 5 - z_{i+1} = 0` (as the S-boxes of Poseidon) is declared as a
+/// custom gate instead of being synthesized, so it can only be folded with HyperNova, which keeps
+/// it as a single row of degree 5 in the folded CCS.
+#[cfg(any(test, feature = "test-utils"))]
+#[derive(Clone, Copy, Debug)]
+pub struct QuinticFCircuit<F: PrimeField> {
+    _f: PhantomData<F>,
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl<F: PrimeField> FCircuit<F> for QuinticFCircuit<F> {
+    type Params = ();
+    type ExternalInputs = ();
+    type ExternalInputsVar = ();
+    fn new(_params: Self::Params) -> Result<Self, Error> {
+        Ok(Self { _f: PhantomData })
+    }
+    fn state_len(&self) -> usize {
+        1
+    }
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        _external_inputs: Self::ExternalInputs,
+    ) -> Result<Vec<F>, Error> {
+        Ok(vec![z_i[0].pow([5]) + z_i[0] + F::from(5_u32)])
+    }
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Self::ExternalInputsVar,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        // z_{i+1} is constrained by the custom gate
+        let z_i1 = FpVar::new_witness(cs, || {
+            let z_i = z_i[0].value()?;
+            Ok(z_i.pow([5]) + z_i + F::from(5_u32))
+        })?;
+        Ok(vec![z_i1])
+    }
+    fn custom_gates(&self) -> Option<CCS<F>> {
+        // over (1, z_i, z_{i+1}): (M_0 z)^5 + M_1 z, with M_0 z = z_i, M_1 z = z_i + 5 - z_{i+1}
+        CCSBuilder::from_degree_gates(
+            3,
+            0,
+            &[(F::one(), vec![0; 5]), (F::one(), vec![1])],
+            vec![vec![
+                vec![(F::one(), 1)],
+                vec![(F::one(), 1), (F::from(5_u32), 0), (-F::one(), 2)],
+            ]],
+        )
+        .ok()
+    }
+}
+
+/// MultiInputsFCircuit is a circuit with a state of five elements, each of them updated by a
+/// different operation. Natively, each step computes
+/// `z_{i+1} = [z_i[0] + 4, z_i[1] + 40, z_i[2] * 4, z_i[3] * 40, z_i[4] + 100]`.
+///
+/// ```
+/// use ark_bn254::{constraints::GVar, Fr, G1Projective as Projective};
+/// use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+/// use folding_schemes::{
+///     commitment::pedersen::Pedersen,
+///     folding::nova::{Nova, PreprocessorParam},
+///     frontend::{utils::MultiInputsFCircuit, FCircuit},
+///     transcript::poseidon::poseidon_canonical_config,
+///     FoldingScheme,
+/// };
+///
+/// type N = Nova<
+///     Projective,
+///     GVar,
+///     Projective2,
+///     GVar2,
+///     MultiInputsFCircuit<Fr>,
+///     Pedersen<Projective>,
+///     Pedersen<Projective2>,
+///     false,
+/// >;
+///
+/// let mut rng = ark_std::test_rng();
+/// let F_circuit = MultiInputsFCircuit::<Fr>::new(())?;
+/// let poseidon_config = poseidon_canonical_config::<Fr>();
+/// let params = N::preprocess(&mut rng, &PreprocessorParam::new(poseidon_config, F_circuit))?;
+/// let mut nova = N::init(&params, F_circuit, vec![Fr::from(1_u32); 5])?;
+/// for _ in 0..2 {
+///     nova.prove_step(&mut rng, (), None)?;
+/// }
+/// assert_eq!(
+///     nova.state().z_i,
+///     [9_u32, 81, 16, 1600, 201].map(Fr::from).to_vec()
+/// );
+/// N::verify(params.1, nova.ivc_proof())?;
+/// # Ok::<(), folding_schemes::Error>(())
+/// ```
+#[cfg(any(test, feature = "test-utils"))]
+#[derive(Clone, Copy, Debug)]
+pub struct MultiInputsFCircuit<F: PrimeField> {
+    _f: PhantomData<F>,
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl<F: PrimeField> FCircuit<F> for MultiInputsFCircuit<F> {
+    type Params = ();
+    type ExternalInputs = ();
+    type ExternalInputsVar = ();
+    fn new(_params: Self::Params) -> Result<Self, Error> {
+        Ok(Self { _f: PhantomData })
+    }
+    fn state_len(&self) -> usize {
+        5
+    }
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        _external_inputs: Self::ExternalInputs,
+    ) -> Result<Vec<F>, Error> {
+        Ok(vec![
+            z_i[0] + F::from(4_u32),
+            z_i[1] + F::from(40_u32),
+            z_i[2] * F::from(4_u32),
+            z_i[3] * F::from(40_u32),
+            z_i[4] + F::from(100_u32),
+        ])
+    }
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Self::ExternalInputsVar,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let four = FpVar::<F>::new_constant(cs.clone(), F::from(4u32))?;
+        let forty = FpVar::<F>::new_constant(cs.clone(), F::from(40u32))?;
+        let onehundred = FpVar::<F>::new_constant(cs.clone(), F::from(100u32))?;
+
+        Ok(vec![
+            &z_i[0] + &four,
+            &z_i[1] + &forty,
+            &z_i[2] * &four,
+            &z_i[3] * &forty,
+            &z_i[4] + &onehundred,
+        ])
+    }
+}
+
+/// WrongStateLenFCircuit declares a state of 2 elements but its step only returns 1 element, it is
+/// used to test that such a misconfigured circuit is rejected at preprocessing.
+#[cfg(test)]
+#[derive(Clone, Copy, Debug)]
+pub struct WrongStateLenFCircuit<F: PrimeField> {
+    _f: PhantomData<F>,
+}
+
+#[cfg(test)]
+impl<F: PrimeField> FCircuit<F> for WrongStateLenFCircuit<F> {
+    type Params = ();
+    type ExternalInputs = ();
+    type ExternalInputsVar = ();
+    fn new(_params: Self::Params) -> Result<Self, Error> {
+        Ok(Self { _f: PhantomData })
+    }
+    fn state_len(&self) -> usize {
+        2
+    }
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        _external_inputs: Self::ExternalInputs,
+    ) -> Result<Vec<F>, Error> {
+        Ok(vec![z_i[0] + z_i[1]])
+    }
+    fn generate_step_constraints(
+        &self,
+        _cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Self::ExternalInputsVar,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        Ok(vec![&z_i[0] + &z_i[1]])
+    }
+}
+
+/// StepConstantsFCircuit computes `z_{i+1} = z_i * a_i + b_i`, where `(a_i, b_i)` is the `i`-th
+/// row of a schedule of per-step constants of `n_steps` rows, which is specified in the
+/// parameters. It is used to test the `FCircuit::step_constants` mechanism.
+#[cfg(test)]
+#[derive(Clone, Copy, Debug)]
+pub struct StepConstantsFCircuit<F: PrimeField> {
+    n_steps: usize,
+    _f: PhantomData<F>,
+}
+
+#[cfg(test)]
+impl<F: PrimeField> FCircuit<F> for StepConstantsFCircuit<F> {
+    type Params = usize;
+    type ExternalInputs = ();
+    type ExternalInputsVar = ();
+    fn new(n_steps: Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            n_steps,
+            _f: PhantomData,
+        })
+    }
+    fn state_len(&self) -> usize {
+        1
+    }
+    fn step_constants(&self) -> Vec<Vec<F>> {
+        (0..self.n_steps as u64)
+            .map(|j| vec![F::from(j + 2), F::from(j * j + 1)])
+            .collect()
+    }
+    fn step_native(
+        &self,
+        i: usize,
+        z_i: Vec<F>,
+        _external_inputs: Self::ExternalInputs,
+    ) -> Result<Vec<F>, Error> {
+        let step_constants = self.step_constants();
+        let row = step_constants
+            .get(i)
+            .ok_or(Error::StepConstantsExhausted(i, self.n_steps))?;
+        Ok(vec![z_i[0] * row[0] + row[1]])
+    }
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Self::ExternalInputsVar,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let row = self
+            .step_constants()
+            .get(i)
+            .cloned()
+            .ok_or(SynthesisError::Unsatisfiable)?;
+        let step_constants = Vec::<FpVar<F>>::new_constant(cs.clone(), row)?;
+        self.generate_step_constraints_with_constants(cs, i, z_i, external_inputs, step_constants)
+    }
+    fn generate_step_constraints_with_constants(
+        &self,
+        _cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Self::ExternalInputsVar,
+        step_constants: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        Ok(vec![&z_i[0] * &step_constants[0] + &step_constants[1]])
+    }
+}
+
 /// CustomFCircuit is a circuit that has the number of constraints specified in the
-/// `n_constraints` parameter. Note that the generated circuit will have very sparse matrices.
+/// `n_constraints` parameter, which must be at least 1. Note that the generated circuit will have
+/// very sparse matrices. Natively, each step squares the single element of the state
+/// `n_constraints - 1` times, ie. `z_{i+1} = z_i^(2^(n_constraints - 1))`.
+///
+/// ```
+/// use ark_bn254::{constraints::GVar, Fr, G1Projective as Projective};
+/// use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+/// use folding_schemes::{
+///     commitment::pedersen::Pedersen,
+///     folding::nova::{Nova, PreprocessorParam},
+///     frontend::{utils::CustomFCircuit, FCircuit},
+///     transcript::poseidon::poseidon_canonical_config,
+///     FoldingScheme,
+/// };
+///
+/// type N = Nova<
+///     Projective,
+///     GVar,
+///     Projective2,
+///     GVar2,
+///     CustomFCircuit<Fr>,
+///     Pedersen<Projective>,
+///     Pedersen<Projective2>,
+///     false,
+/// >;
+///
+/// let mut rng = ark_std::test_rng();
+/// let F_circuit = CustomFCircuit::<Fr>::new(3)?;
+/// let poseidon_config = poseidon_canonical_config::<Fr>();
+/// let params = N::preprocess(&mut rng, &PreprocessorParam::new(poseidon_config, F_circuit))?;
+/// let mut nova = N::init(&params, F_circuit, vec![Fr::from(2_u32)])?;
+/// for _ in 0..2 {
+///     nova.prove_step(&mut rng, (), None)?;
+/// }
+/// // 2 -> 2^4 = 16 -> 16^4 = 65536
+/// assert_eq!(nova.state().z_i, vec![Fr::from(65536_u32)]);
+/// N::verify(params.1, nova.ivc_proof())?;
+/// # Ok::<(), folding_schemes::Error>(())
+/// ```
 #[derive(Clone, Copy, Debug)]
 pub struct CustomFCircuit<F: PrimeField> {
     _f: PhantomData<F>,
@@ -106,6 +472,8 @@ pub struct CustomFCircuit<F: PrimeField> {
 
 impl<F: PrimeField> FCircuit<F> for CustomFCircuit<F> {
     type Params = usize;
+    type ExternalInputs = ();
+    type ExternalInputsVar = ();
 
     fn new(params: Self::Params) -> Result<Self, Error> {
         Ok(Self {
@@ -116,14 +484,11 @@ impl<F: PrimeField> FCircuit<F> for CustomFCircuit<F> {
     fn state_len(&self) -> usize {
         1
     }
-    fn external_inputs_len(&self) -> usize {
-        0
-    }
     fn step_native(
         &self,
         _i: usize,
         z_i: Vec<F>,
-        _external_inputs: Vec<F>,
+        _external_inputs: Self::ExternalInputs,
     ) -> Result<Vec<F>, Error> {
         let mut z_i1 = z_i[0];
         for _ in 0..self.n_constraints - 1 {
@@ -136,7 +501,7 @@ impl<F: PrimeField> FCircuit<F> for CustomFCircuit<F> {
         _cs: ConstraintSystemRef<F>,
         _i: usize,
         z_i: Vec<FpVar<F>>,
-        _external_inputs: Vec<FpVar<F>>,
+        _external_inputs: Self::ExternalInputsVar,
     ) -> Result<Vec<FpVar<F>>, SynthesisError> {
         let mut z_i1 = z_i[0].clone();
         for _ in 0..self.n_constraints - 1 {
@@ -168,9 +533,11 @@ where
             Vec::<FpVar<F>>::new_witness(cs.clone(), || Ok(self.z_i.unwrap_or(vec![F::zero()])))?;
         let z_i1 =
             Vec::<FpVar<F>>::new_input(cs.clone(), || Ok(self.z_i1.unwrap_or(vec![F::zero()])))?;
+        let external_inputs =
+            FC::ExternalInputsVar::new_witness(cs.clone(), || Ok(FC::ExternalInputs::default()))?;
         let computed_z_i1 =
             self.FC
-                .generate_step_constraints(cs.clone(), 0, z_i.clone(), vec![])?;
+                .generate_step_constraints(cs.clone(), 0, z_i.clone(), external_inputs)?;
 
         use ark_r1cs_std::eq::EqGadget;
         computed_z_i1.enforce_equal(&z_i1)?;
@@ -1,16 +1,24 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![allow(non_snake_case)]
 #![allow(non_upper_case_globals)]
 #![allow(non_camel_case_types)]
 
+#[cfg_attr(not(feature = "std"), macro_use)]
+extern crate alloc;
+
 use ark_ec::{pairing::Pairing, CurveGroup};
 use ark_ff::PrimeField;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::rand::CryptoRng;
-use ark_std::{fmt::Debug, rand::RngCore};
+use ark_std::{fmt::Debug, rand::RngCore, string::String, vec::Vec};
 use thiserror::Error;
 
+use crate::folding::circuits::cyclefold::CycleFoldCommittedInstance;
 use crate::frontend::FCircuit;
 
+#[macro_use]
+mod trace;
+
 pub mod arith;
 pub mod commitment;
 pub mod constants;
@@ -21,27 +29,37 @@ pub mod utils;
 
 #[derive(Debug, Error)]
 pub enum Error {
-    // Wrappers on top of other errors
+    // Wrappers on top of other errors. Without `std`, the arkworks errors do not implement
+    // `core::error::Error`, so they can not be the source of the error, and their `From`
+    // conversions are implemented below instead of through `#[from]`.
     #[error("ark_relations::r1cs::SynthesisError")]
-    SynthesisError(#[from] ark_relations::r1cs::SynthesisError),
+    SynthesisError(#[cfg_attr(feature = "std", from)] ark_relations::r1cs::SynthesisError),
     #[error("ark_serialize::SerializationError")]
-    SerializationError(#[from] ark_serialize::SerializationError),
+    SerializationError(#[cfg_attr(feature = "std", from)] ark_serialize::SerializationError),
     #[error("ark_poly_commit::Error")]
-    PolyCommitError(#[from] ark_poly_commit::Error),
+    PolyCommitError(#[cfg_attr(feature = "std", from)] ark_poly_commit::Error),
     #[error("crate::utils::espresso::virtual_polynomial::ArithErrors")]
     ArithError(#[from] utils::espresso::virtual_polynomial::ArithErrors),
     #[error(transparent)]
     ProtoGalaxy(folding::protogalaxy::ProtoGalaxyError),
     #[error("std::io::Error")]
-    IOError(#[from] std::io::Error),
+    IOError(#[cfg_attr(feature = "std", from)] ark_std::io::Error),
 
     // Relation errors
     #[error("Relation not satisfied")]
     NotSatisfied,
+    #[error("Relation not satisfied at row {row}: {lhs} != {rhs}")]
+    NotSatisfiedAt {
+        row: usize,
+        lhs: String,
+        rhs: String,
+    },
     #[error("SNARK setup failed: {0}")]
     SNARKSetupFail(String),
     #[error("SNARK verification failed")]
     SNARKVerificationFail,
+    #[error("Batch verification failed, the proof at index {0} is not valid")]
+    BatchVerificationFail(usize),
     #[error("IVC verification failed")]
     IVCVerificationFail,
     #[error("zkIVC verification failed")]
@@ -88,6 +106,12 @@ pub enum Error {
     IncorrectBlinding(bool, String),
     #[error("Commitment verification failed")]
     CommitmentVerificationFail,
+    #[error("Commitment {0} does not open to the witness")]
+    CommitmentNotSatisfied(String),
+    #[error("Invalid ptau file: {0}")]
+    PtauError(String),
+    #[error("Params mismatch: {0}")]
+    ParamsMismatch(String),
 
     // Other
     #[error("{0}")]
@@ -100,6 +124,10 @@ pub enum Error {
     NotSupportedYet(String),
     #[error("Feature '{0}' is not supported and it will not be")]
     NotSupported(String),
+    #[error("{0} has length {1}, but the FCircuit state length is {2}")]
+    StateLenMismatch(String, usize, usize),
+    #[error("step {0} has no row in the FCircuit step constants table of {1} rows")]
+    StepConstantsExhausted(usize, usize),
     #[error("max i-th step reached (usize limit reached)")]
     MaxStep,
     #[error("Witness calculation error: {0}")]
@@ -112,7 +140,29 @@ pub enum Error {
     NoMultiInstances,
     #[error("Missing 'other' instances, since this is a multi-instances folding scheme. Expected number of instances, mu:{0}, nu:{1}")]
     MissingOtherInstances(usize, usize),
+    #[error("Extra instance at index {0} is not valid: {1}")]
+    InvalidExtraInstance(usize, String),
+}
+
+#[cfg(not(feature = "std"))]
+macro_rules! impl_from_error {
+    ($($variant:ident($error:ty)),* $(,)?) => {
+        $(
+            impl From<$error> for Error {
+                fn from(e: $error) -> Self {
+                    Error::$variant(e)
+                }
+            }
+        )*
+    };
 }
+#[cfg(not(feature = "std"))]
+impl_from_error!(
+    SynthesisError(ark_relations::r1cs::SynthesisError),
+    SerializationError(ark_serialize::SerializationError),
+    PolyCommitError(ark_poly_commit::Error),
+    IOError(ark_std::io::Error),
+);
 
 /// FoldingScheme defines trait that is implemented by the diverse folding schemes. It is defined
 /// over a cycle of curves (C1, C2), where:
@@ -134,6 +184,8 @@ where
     type IncomingInstance: Debug; // contains the CommittedInstance + Witness
     type MultiCommittedInstanceWithWitness: Debug; // type used for the extra instances in the multi-instance folding setting
     type CFInstance: Debug; // CycleFold CommittedInstance & Witness
+    type RunningCommittedInstance: Debug; // running CommittedInstance, without its Witness
+    type IncomingCommittedInstance: Debug; // incoming CommittedInstance, without its Witness
     type IVCProof: PartialEq + Eq + Clone + Debug + CanonicalSerialize + CanonicalDeserialize;
 
     /// deserialize Self::ProverParam and recover the not serialized data that is recomputed on the
@@ -141,7 +193,7 @@ where
     /// Internally it generates the r1cs/ccs & cf_r1cs needed for the VerifierParams. In this way
     /// we avoid needing to serialize them, saving significant space in the VerifierParams
     /// serialized size.
-    fn pp_deserialize_with_mode<R: std::io::prelude::Read>(
+    fn pp_deserialize_with_mode<R: ark_std::io::Read>(
         reader: R,
         compress: ark_serialize::Compress,
         validate: ark_serialize::Validate,
@@ -153,7 +205,7 @@ where
     /// Internally it generates the r1cs/ccs & cf_r1cs needed for the VerifierParams. In this way
     /// we avoid needing to serialize them, saving significant space in the VerifierParams
     /// serialized size.
-    fn vp_deserialize_with_mode<R: std::io::prelude::Read>(
+    fn vp_deserialize_with_mode<R: ark_std::io::Read>(
         reader: R,
         compress: ark_serialize::Compress,
         validate: ark_serialize::Validate,
@@ -174,12 +226,65 @@ where
     fn prove_step(
         &mut self,
         rng: impl RngCore,
-        external_inputs: Vec<C1::ScalarField>,
+        external_inputs: FC::ExternalInputs,
         other_instances: Option<Self::MultiCommittedInstanceWithWitness>,
     ) -> Result<(), Error>;
 
-    /// returns the state at the current step
-    fn state(&self) -> Vec<C1::ScalarField>;
+    /// returns a read-only view of the folding at the current step, ie. the step counter, the
+    /// initial and current states, and the running and incoming committed instances together
+    /// with the running CycleFold instance.
+    ///
+    /// ```
+    /// use ark_bn254::{constraints::GVar, Fr, G1Projective as Projective};
+    /// use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+    /// use folding_schemes::{
+    ///     commitment::pedersen::Pedersen,
+    ///     folding::nova::{Nova, PreprocessorParam},
+    ///     frontend::{utils::CubicFCircuit, FCircuit},
+    ///     transcript::poseidon::poseidon_canonical_config,
+    ///     FoldingScheme,
+    /// };
+    ///
+    /// type N = Nova<
+    ///     Projective,
+    ///     GVar,
+    ///     Projective2,
+    ///     GVar2,
+    ///     CubicFCircuit<Fr>,
+    ///     Pedersen<Projective>,
+    ///     Pedersen<Projective2>,
+    ///     false,
+    /// >;
+    ///
+    /// let mut rng = ark_std::test_rng();
+    /// let F_circuit = CubicFCircuit::<Fr>::new(())?;
+    /// let poseidon_config = poseidon_canonical_config::<Fr>();
+    /// let params = N::preprocess(&mut rng, &PreprocessorParam::new(poseidon_config, F_circuit))?;
+    /// let mut nova = N::init(&params, F_circuit, vec![Fr::from(3_u32)])?;
+    ///
+    /// // verify the IVC proof, as a decider would, every K steps
+    /// const K: u64 = 2;
+    /// let (mut next_check, mut checked) = (K, vec![]);
+    /// for _ in 0..5 {
+    ///     nova.prove_step(&mut rng, (), None)?;
+    ///     if nova.state().i == Fr::from(next_check) {
+    ///         N::verify(params.1.clone(), nova.ivc_proof())?;
+    ///         checked.push(nova.state().z_i.to_vec());
+    ///         next_check += K;
+    ///     }
+    /// }
+    ///
+    /// let state = nova.state();
+    /// assert_eq!(state.i, Fr::from(5_u32));
+    /// assert_eq!(state.z_0, [Fr::from(3_u32)]);
+    /// assert_eq!(state.z_i, nova.z_i);
+    /// assert_eq!(state.U_i, &nova.U_i);
+    /// assert_eq!(checked.len(), 2);
+    /// # Ok::<(), folding_schemes::Error>(())
+    /// ```
+    fn state(
+        &self,
+    ) -> FoldingState<'_, C1, C2, Self::RunningCommittedInstance, Self::IncomingCommittedInstance>;
 
     /// returns the last IVC state proof, which can be verified in the `verify` method
     fn ivc_proof(&self) -> Self::IVCProof;
@@ -197,6 +302,24 @@ where
     fn verify(vp: Self::VerifierParam, ivc_proof: Self::IVCProof) -> Result<(), Error>;
 }
 
+/// Read-only view of a [`FoldingScheme`] at its current step, as returned by
+/// [`FoldingScheme::state`].
+#[derive(Debug)]
+pub struct FoldingState<'a, C1: CurveGroup, C2: CurveGroup, RU, IU> {
+    /// number of steps folded so far
+    pub i: C1::ScalarField,
+    /// initial state
+    pub z_0: &'a [C1::ScalarField],
+    /// state at step `i`
+    pub z_i: &'a [C1::ScalarField],
+    /// running committed instance, which accumulates the steps before `i`
+    pub U_i: &'a RU,
+    /// incoming committed instance, of the step `i`
+    pub u_i: &'a IU,
+    /// running CycleFold committed instance
+    pub cf_U_i: &'a CycleFoldCommittedInstance<C2>,
+}
+
 /// Trait with auxiliary methods for multi-folding schemes (ie. HyperNova, ProtoGalaxy, etc),
 /// allowing to create new instances for the multifold.
 pub trait MultiFolding<C1: CurveGroup, C2: CurveGroup, FC>: Clone + Debug
@@ -214,7 +337,7 @@ where
         &self,
         rng: impl RngCore,
         state: Vec<C1::ScalarField>,
-        external_inputs: Vec<C1::ScalarField>,
+        external_inputs: FC::ExternalInputs,
     ) -> Result<Self::RunningInstance, Error>;
 
     /// Creates a new IncomingInstance for the given state, to be folded in the multi-folding step.
@@ -222,7 +345,7 @@ where
         &self,
         rng: impl RngCore,
         state: Vec<C1::ScalarField>,
-        external_inputs: Vec<C1::ScalarField>,
+        external_inputs: FC::ExternalInputs,
     ) -> Result<Self::IncomingInstance, Error>;
 }
 
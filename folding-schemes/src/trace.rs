@@ -0,0 +1,15 @@
+//! Macros to instrument the provers and verifiers with `tracing` spans, so that a `tracing`
+//! subscriber can report the time spent in each phase (e.g. as a flamegraph). The spans are only
+//! created with the `tracing` feature: without it, the macro expands to nothing, so the
+//! instrumentation has no cost, and the span fields are not even evaluated.
+
+/// Enters an info-level span with the given name and fields until the end of the current scope,
+/// e.g. `trace_span!("nova_prove_step", n_constraints = self.r1cs.A.n_rows);`. To trace a phase
+/// of a function, the phase is wrapped in a block that starts with the span:
+/// `let z_i1 = { trace_span!("step_native"); F.step_native(i, z_i, external_inputs)? };`.
+macro_rules! trace_span {
+    ($name:literal $(, $key:ident = $value:expr)* $(,)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = ::tracing::info_span!($name $(, $key = $value)*).entered();
+    };
+}
@@ -0,0 +1,707 @@
+use ark_crypto_primitives::sponge::{
+    constraints::{AbsorbGadget, CryptographicSpongeVar},
+    Absorb, CryptographicSponge,
+};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    boolean::Boolean, fields::fp::FpVar, groups::CurveVar, uint8::UInt8, ToBitsGadget,
+    ToConstraintFieldGadget,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use ark_std::{marker::PhantomData, vec::Vec};
+use sha3::{Digest, Keccak256};
+
+use super::{AbsorbNonNative, AbsorbNonNativeGadget, Transcript, TranscriptVar};
+
+/// Transcript based on Keccak256, for the verifiers that recompute the challenges natively with the
+/// KECCAK256 opcode of the EVM instead of with Poseidon.
+///
+/// The field elements are absorbed as their canonical big-endian encoding (32 bytes for the 254-bit
+/// fields, as an `uint256`), the labels as raw bytes, and at the first squeeze after absorbing, the
+/// state is updated to `keccak256(state || absorbed)`. The squeezed values are then the blocks
+/// `keccak256(state || counter)`, where `counter` is a big-endian `uint32` that starts at 0 after
+/// each update of the state. The bits of a block are the little-endian bits of the block read as
+/// an `uint256`, and the challenges are taken from its lowest `F::MODULUS_BIT_SIZE - 1` bits, so
+/// that they are always smaller than the modulus.
+#[derive(Clone, Debug)]
+pub struct Keccak256Transcript<F: PrimeField> {
+    state: [u8; 32],
+    absorbed: Vec<u8>,
+    counter: u32,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> Keccak256Transcript<F> {
+    fn absorb_field_elements(&mut self, elements: &[F]) {
+        for element in elements {
+            self.absorbed.extend(field_to_bytes_be(element));
+        }
+    }
+
+    // returns the next block of the squeezed output, updating the state first if anything was
+    // absorbed since the last squeeze
+    fn squeeze_block(&mut self) -> [u8; 32] {
+        if !self.absorbed.is_empty() {
+            let mut hasher = Keccak256::new();
+            hasher.update(self.state);
+            hasher.update(&self.absorbed);
+            self.state = hasher.finalize().into();
+            self.absorbed.clear();
+            self.counter = 0;
+        }
+        let mut hasher = Keccak256::new();
+        hasher.update(self.state);
+        hasher.update(self.counter.to_be_bytes());
+        self.counter += 1;
+        hasher.finalize().into()
+    }
+}
+
+// number of bytes of the canonical encoding of the elements of `F`
+fn field_n_bytes<F: PrimeField>() -> usize {
+    (F::MODULUS_BIT_SIZE as usize).div_ceil(8)
+}
+
+fn field_to_bytes_be<F: PrimeField>(element: &F) -> Vec<u8> {
+    let mut bytes = element.into_bigint().to_bytes_be();
+    bytes.split_off(bytes.len() - field_n_bytes::<F>())
+}
+
+impl<F: PrimeField> CryptographicSponge for Keccak256Transcript<F> {
+    type Config = ();
+
+    fn new(_params: &Self::Config) -> Self {
+        Self {
+            state: [0; 32],
+            absorbed: vec![],
+            counter: 0,
+            _f: PhantomData,
+        }
+    }
+
+    fn absorb(&mut self, input: &impl Absorb) {
+        self.absorb_field_elements(&input.to_sponge_field_elements_as_vec::<F>());
+    }
+
+    fn squeeze_bytes(&mut self, num_bytes: usize) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(num_bytes);
+        while bytes.len() < num_bytes {
+            bytes.extend(self.squeeze_block());
+        }
+        bytes.truncate(num_bytes);
+        bytes
+    }
+
+    fn squeeze_bits(&mut self, num_bits: usize) -> Vec<bool> {
+        let mut bits = Vec::with_capacity(num_bits);
+        while bits.len() < num_bits {
+            let block = self.squeeze_block();
+            bits.extend(
+                block
+                    .iter()
+                    .rev()
+                    .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1)),
+            );
+        }
+        bits.truncate(num_bits);
+        bits
+    }
+
+    fn squeeze_field_elements<F2: PrimeField>(&mut self, num_elements: usize) -> Vec<F2> {
+        (0..num_elements)
+            .map(|_| {
+                let bits = self.squeeze_bits(F2::MODULUS_BIT_SIZE as usize - 1);
+                F2::from(F2::BigInt::from_bits_le(&bits))
+            })
+            .collect()
+    }
+}
+
+impl<F: PrimeField> Transcript<F> for Keccak256Transcript<F> {
+    // Compatible with the in-circuit `TranscriptVar::absorb_point`
+    fn absorb_point<C: CurveGroup<BaseField = F>>(&mut self, p: &C) {
+        let (x, y) = match p.into_affine().xy() {
+            Some((&x, &y)) => (x, y),
+            None => (F::zero(), F::zero()),
+        };
+        self.absorb_field_elements(&[x, y]);
+    }
+    fn absorb_nonnative<V: AbsorbNonNative<F>>(&mut self, v: &V) {
+        self.absorb_field_elements(&v.to_native_sponge_field_elements_as_vec());
+    }
+    fn absorb_label(&mut self, label: &[u8]) {
+        self.absorbed.extend_from_slice(label);
+    }
+    fn get_challenge(&mut self) -> F {
+        self.squeeze_field_elements::<F>(1)[0]
+    }
+    fn get_challenge_nbits(&mut self, nbits: usize) -> Vec<bool> {
+        self.squeeze_bits(nbits)
+    }
+}
+
+/// In-circuit counterpart of `Keccak256Transcript`, which computes the same challenges by
+/// evaluating Keccak256 in-circuit.
+///
+/// Each Keccak-f[1600] permutation costs around 150k constraints, so this is far more expensive
+/// than the Poseidon transcript, and it only makes sense when the challenges have to be
+/// recomputed natively by an EVM verifier.
+#[derive(Clone, Debug)]
+pub struct Keccak256TranscriptVar<F: PrimeField> {
+    cs: ConstraintSystemRef<F>,
+    state: Vec<UInt8<F>>,
+    absorbed: Vec<UInt8<F>>,
+    counter: u32,
+}
+
+// in-circuit counterpart of `field_to_bytes_be`
+fn field_to_bytes_be_gadget<F: PrimeField>(
+    element: &FpVar<F>,
+) -> Result<Vec<UInt8<F>>, SynthesisError> {
+    let mut bits = element.to_bits_le()?;
+    bits.resize(field_n_bytes::<F>() * 8, Boolean::FALSE);
+    Ok(bits.chunks(8).rev().map(UInt8::from_bits_le).collect())
+}
+
+// little-endian bits of the given big-endian bytes, ie. of the block read as an `uint256`
+fn block_to_bits_le<F: PrimeField>(block: &[UInt8<F>]) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    Ok(block
+        .iter()
+        .rev()
+        .map(|byte| byte.to_bits_le())
+        .collect::<Result<Vec<_>, _>>()?
+        .concat())
+}
+
+impl<F: PrimeField> Keccak256TranscriptVar<F> {
+    fn absorb_field_elements(&mut self, elements: &[FpVar<F>]) -> Result<(), SynthesisError> {
+        for element in elements {
+            self.absorbed.extend(field_to_bytes_be_gadget(element)?);
+        }
+        Ok(())
+    }
+
+    // in-circuit counterpart of `Keccak256Transcript::squeeze_block`
+    fn squeeze_block(&mut self) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        if !self.absorbed.is_empty() {
+            self.state = keccak256_gadget(&[self.state.clone(), self.absorbed.clone()].concat())?;
+            self.absorbed.clear();
+            self.counter = 0;
+        }
+        let counter = UInt8::constant_vec(&self.counter.to_be_bytes());
+        self.counter += 1;
+        keccak256_gadget(&[self.state.clone(), counter].concat())
+    }
+}
+
+impl<F: PrimeField> CryptographicSpongeVar<F, Keccak256Transcript<F>>
+    for Keccak256TranscriptVar<F>
+{
+    type Parameters = ();
+
+    fn new(cs: ConstraintSystemRef<F>, _params: &Self::Parameters) -> Self {
+        Self {
+            cs,
+            state: UInt8::constant_vec(&[0; 32]),
+            absorbed: vec![],
+            counter: 0,
+        }
+    }
+
+    fn cs(&self) -> ConstraintSystemRef<F> {
+        self.cs.clone()
+    }
+
+    fn absorb(&mut self, input: &impl AbsorbGadget<F>) -> Result<(), SynthesisError> {
+        self.absorb_field_elements(&input.to_sponge_field_elements()?)
+    }
+
+    fn squeeze_bytes(&mut self, num_bytes: usize) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        let mut bytes = Vec::with_capacity(num_bytes);
+        while bytes.len() < num_bytes {
+            bytes.extend(self.squeeze_block()?);
+        }
+        bytes.truncate(num_bytes);
+        Ok(bytes)
+    }
+
+    fn squeeze_bits(&mut self, num_bits: usize) -> Result<Vec<Boolean<F>>, SynthesisError> {
+        let mut bits = Vec::with_capacity(num_bits);
+        while bits.len() < num_bits {
+            bits.extend(block_to_bits_le(&self.squeeze_block()?)?);
+        }
+        bits.truncate(num_bits);
+        Ok(bits)
+    }
+
+    fn squeeze_field_elements(
+        &mut self,
+        num_elements: usize,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        (0..num_elements)
+            .map(|_| {
+                let bits = self.squeeze_bits(F::MODULUS_BIT_SIZE as usize - 1)?;
+                Boolean::le_bits_to_fp_var(&bits)
+            })
+            .collect()
+    }
+}
+
+impl<F: PrimeField> TranscriptVar<F, Keccak256Transcript<F>> for Keccak256TranscriptVar<F> {
+    fn absorb_point<
+        C: CurveGroup<BaseField = F>,
+        GC: CurveVar<C, F> + ToConstraintFieldGadget<F>,
+    >(
+        &mut self,
+        v: &GC,
+    ) -> Result<(), SynthesisError> {
+        let mut vec = v.to_constraint_field()?;
+        // as in the Poseidon transcript, the last element (which tells whether the point is
+        // infinity) is not absorbed, since the infinity point is represented as `(0, 0)`
+        vec.pop();
+        self.absorb_field_elements(&vec)
+    }
+    fn absorb_nonnative<V: AbsorbNonNativeGadget<F>>(
+        &mut self,
+        v: &V,
+    ) -> Result<(), SynthesisError> {
+        self.absorb_field_elements(&v.to_native_sponge_field_elements()?)
+    }
+    fn absorb_label(&mut self, label: &[u8]) -> Result<(), SynthesisError> {
+        self.absorbed.extend(UInt8::constant_vec(label));
+        Ok(())
+    }
+    fn get_challenge(&mut self) -> Result<FpVar<F>, SynthesisError> {
+        Ok(self.squeeze_field_elements(1)?.remove(0))
+    }
+    fn get_challenge_nbits(&mut self, nbits: usize) -> Result<Vec<Boolean<F>>, SynthesisError> {
+        self.squeeze_bits(nbits)
+    }
+}
+
+/// Digest of the given field elements that an EVM verifier computes as
+/// `uint256(keccak256(abi.encodePacked(elements))) & ((1 << (F::MODULUS_BIT_SIZE - 1)) - 1)`,
+/// ie. the lowest `F::MODULUS_BIT_SIZE - 1` bits of the Keccak256 of their big-endian encodings,
+/// taken as in the challenges of `Keccak256Transcript`.
+pub fn keccak256_digest<F: PrimeField>(elements: &[F]) -> F {
+    let mut hasher = Keccak256::new();
+    for element in elements {
+        hasher.update(field_to_bytes_be(element));
+    }
+    let bits: Vec<bool> = hasher
+        .finalize()
+        .iter()
+        .rev()
+        .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+        .take(F::MODULUS_BIT_SIZE as usize - 1)
+        .collect();
+    F::from(F::BigInt::from_bits_le(&bits))
+}
+
+/// In-circuit counterpart of `keccak256_digest`. It costs a Keccak-f[1600] permutation (~150k
+/// constraints) for each 136 bytes of the encoded elements.
+pub fn keccak256_digest_gadget<F: PrimeField>(
+    elements: &[FpVar<F>],
+) -> Result<FpVar<F>, SynthesisError> {
+    let bytes = elements
+        .iter()
+        .map(field_to_bytes_be_gadget)
+        .collect::<Result<Vec<_>, _>>()?
+        .concat();
+    let mut bits = block_to_bits_le(&keccak256_gadget(&bytes)?)?;
+    bits.truncate(F::MODULUS_BIT_SIZE as usize - 1);
+    Boolean::le_bits_to_fp_var(&bits)
+}
+
+/// Keccak256 of the given bytes as computed by the EVM, i.e. with the original Keccak padding
+/// instead of the SHA3 one.
+pub fn keccak256_gadget<F: PrimeField>(
+    input: &[UInt8<F>],
+) -> Result<Vec<UInt8<F>>, SynthesisError> {
+    // rate of Keccak256 in bytes
+    const RATE: usize = 136;
+
+    // pad10*1
+    let mut padding = vec![0_u8; RATE - input.len() % RATE];
+    let padding_len = padding.len();
+    padding[0] |= 0x01;
+    padding[padding_len - 1] |= 0x80;
+    let message = [input.to_vec(), UInt8::constant_vec(&padding)].concat();
+
+    let mut state = vec![vec![Boolean::FALSE; 64]; 25];
+    for block in message.chunks(RATE) {
+        let bits = block
+            .iter()
+            .map(|byte| byte.to_bits_le())
+            .collect::<Result<Vec<_>, _>>()?
+            .concat();
+        // the lanes are little-endian, so their bits are the concatenation of the bits of their
+        // bytes
+        for (lane, block_lane) in state.iter_mut().zip(bits.chunks(64)) {
+            *lane = xor(lane, block_lane)?;
+        }
+        state = keccak_f1600_gadget(&state)?;
+    }
+    Ok(state[..4]
+        .concat()
+        .chunks(8)
+        .map(UInt8::from_bits_le)
+        .collect())
+}
+
+const KECCAK_ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+// rotation offsets of the lane at position x + 5 * y
+const KECCAK_ROTATIONS: [usize; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+fn xor<F: PrimeField>(
+    a: &[Boolean<F>],
+    b: &[Boolean<F>],
+) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    a.iter().zip(b).map(|(a, b)| a.xor(b)).collect()
+}
+
+// rotation to the left of a lane
+fn rotl<F: PrimeField>(lane: &[Boolean<F>], n: usize) -> Vec<Boolean<F>> {
+    (0..64).map(|i| lane[(i + 64 - n) % 64].clone()).collect()
+}
+
+/// Keccak-f[1600] permutation, applied to the 25 lanes of the `state` (the lane at position
+/// x + 5 * y being at index x + 5 * y), each of them as 64 little-endian bits.
+pub fn keccak_f1600_gadget<F: PrimeField>(
+    state: &[Vec<Boolean<F>>],
+) -> Result<Vec<Vec<Boolean<F>>>, SynthesisError> {
+    let mut a = state.to_vec();
+    for round_constant in KECCAK_ROUND_CONSTANTS {
+        // theta
+        let c = (0..5)
+            .map(|x| (1..5).try_fold(a[x].clone(), |acc, y| xor(&acc, &a[x + 5 * y])))
+            .collect::<Result<Vec<_>, _>>()?;
+        for x in 0..5 {
+            let d = xor(&c[(x + 4) % 5], &rotl(&c[(x + 1) % 5], 1))?;
+            for y in 0..5 {
+                a[x + 5 * y] = xor(&a[x + 5 * y], &d)?;
+            }
+        }
+
+        // rho and pi
+        let mut b = vec![vec![]; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = rotl(&a[x + 5 * y], KECCAK_ROTATIONS[x + 5 * y]);
+            }
+        }
+
+        // chi
+        for x in 0..5 {
+            for y in 0..5 {
+                let not_b1_and_b2 = b[(x + 1) % 5 + 5 * y]
+                    .iter()
+                    .zip(&b[(x + 2) % 5 + 5 * y])
+                    .map(|(b1, b2)| b1.not().and(b2))
+                    .collect::<Result<Vec<_>, _>>()?;
+                a[x + 5 * y] = xor(&b[x + 5 * y], &not_b1_and_b2)?;
+            }
+        }
+
+        // iota
+        let round_constant = (0..64)
+            .map(|i| Boolean::constant((round_constant >> i) & 1 == 1))
+            .collect::<Vec<_>>();
+        a[0] = xor(&a[0], &round_constant)?;
+    }
+    Ok(a)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::folding::circuits::nonnative::affine::NonNativeAffineVar;
+
+    use super::*;
+    use ark_bn254::{constraints::GVar, g1::Config, Fq, Fr, G1Projective as G1};
+    use ark_ec::Group;
+    use ark_ff::UniformRand;
+    use ark_r1cs_std::{
+        alloc::AllocVar, groups::curves::short_weierstrass::ProjectiveVar, R1CSVar,
+    };
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::{rand::RngCore, test_rng};
+
+    #[test]
+    fn test_keccak256_gadget() {
+        let rng = &mut test_rng();
+        // lengths around the rate, to check the padding in all its cases
+        for len in [0, 1, 135, 136, 137, 300] {
+            let mut input = vec![0u8; len];
+            rng.fill_bytes(&mut input);
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let input_var = UInt8::new_witness_vec(cs.clone(), &input).unwrap();
+            let digest_var = keccak256_gadget(&input_var).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+            assert_eq!(
+                digest_var.value().unwrap(),
+                Keccak256::digest(&input).to_vec()
+            );
+        }
+    }
+
+    #[test]
+    fn test_keccak256_digest() {
+        let rng = &mut test_rng();
+        let elements: Vec<Fr> = (0..5).map(|_| Fr::rand(rng)).collect();
+
+        // as computed by `uint256(keccak256(abi.encodePacked(elements))) & ((1 << 253) - 1)`
+        let bytes: Vec<u8> = elements
+            .iter()
+            .flat_map(|e| e.into_bigint().to_bytes_be())
+            .collect();
+        let mut digest = Keccak256::digest(&bytes).to_vec();
+        digest[0] &= 0x1f;
+        let expected = Fr::from_be_bytes_mod_order(&digest);
+        assert_eq!(keccak256_digest(&elements), expected);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let elements_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(elements)).unwrap();
+        let digest_var = keccak256_digest_gadget(&elements_var).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(digest_var.value().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_keccak_f1600_gadget() {
+        let lanes = |state: &[Vec<Boolean<Fr>>]| {
+            state
+                .iter()
+                .map(|bits| {
+                    bits.iter().enumerate().fold(0_u64, |acc, (i, bit)| {
+                        acc | ((bit.value().unwrap() as u64) << i)
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Keccak-f[1600] applied to the zero state, from the KeccakF-1600-IntermediateValues.txt
+        // file of the Keccak team's reference implementation
+        let state = vec![vec![Boolean::<Fr>::FALSE; 64]; 25];
+        let new_state = keccak_f1600_gadget(&state).unwrap();
+        assert_eq!(
+            lanes(&new_state),
+            vec![
+                0xf1258f7940e1dde7,
+                0x84d5ccf933c0478a,
+                0xd598261ea65aa9ee,
+                0xbd1547306f80494d,
+                0x8b284e056253d057,
+                0xff97a42d7f8e6fd4,
+                0x90fee5a0a44647c4,
+                0x8c5bda0cd6192e76,
+                0xad30a6f71b19059c,
+                0x30935ab7d08ffc64,
+                0xeb5aa93f2317d635,
+                0xa9a6e6260d712103,
+                0x81a57c16dbcf555f,
+                0x43b831cd0347c826,
+                0x01f22f1a11a5569f,
+                0x05e5635a21d9ae61,
+                0x64befef28cc970f2,
+                0x613670957bc46611,
+                0xb87c5a554fd00ecb,
+                0x8c3ee88a1ccf32c8,
+                0x940c7922ae3a2614,
+                0x1841f924a2c509e4,
+                0x16f53526e70465c2,
+                0x75f644e97f30a13b,
+                0xeaf1ff7b5ceca249,
+            ]
+        );
+
+        // a random state, against the permutation of the `keccak` crate
+        let rng = &mut test_rng();
+        let mut expected = [0_u64; 25];
+        expected.iter_mut().for_each(|lane| *lane = rng.next_u64());
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let state = expected
+            .iter()
+            .map(|lane| {
+                (0..64)
+                    .map(|i| Boolean::new_witness(cs.clone(), || Ok((lane >> i) & 1 == 1)))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let new_state = keccak_f1600_gadget(&state).unwrap();
+        keccak::f1600(&mut expected);
+        assert_eq!(lanes(&new_state), expected.to_vec());
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    // Test that the challenges can be recomputed with Keccak256 as described in the docs of
+    // `Keccak256Transcript`, as an EVM verifier would do.
+    #[test]
+    fn test_keccak256_transcript_evm_compatible() {
+        let mut tr = Keccak256Transcript::<Fr>::new(&());
+        tr.absorb(&Fr::from(42_u32));
+        let c = tr.get_challenge_nbits(128);
+
+        let mut absorbed = [0u8; 32].to_vec();
+        absorbed.extend(Fr::from(42_u32).into_bigint().to_bytes_be());
+        let state = Keccak256::digest(&absorbed);
+        let block = Keccak256::digest([state.to_vec(), vec![0; 4]].concat());
+        // lowest 128 bits of the block read as an `uint256`
+        let expected: Vec<bool> = block
+            .iter()
+            .rev()
+            .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+            .take(128)
+            .collect();
+        assert_eq!(c, expected);
+    }
+
+    #[test]
+    fn test_transcript_and_transcriptvar_absorb_native_point() {
+        // use 'native' transcript
+        let mut tr = Keccak256Transcript::<Fq>::new(&());
+        let rng = &mut test_rng();
+
+        let p = G1::rand(rng);
+        tr.absorb_point(&p);
+        let c = tr.get_challenge();
+
+        // use 'gadget' transcript
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let mut tr_var = Keccak256TranscriptVar::<Fq>::new(cs.clone(), &());
+        let p_var = ProjectiveVar::<Config, FpVar<Fq>>::new_witness(
+            ConstraintSystem::<Fq>::new_ref(),
+            || Ok(p),
+        )
+        .unwrap();
+        tr_var.absorb_point(&p_var).unwrap();
+        let c_var = tr_var.get_challenge().unwrap();
+
+        // assert that native & gadget transcripts return the same challenge
+        assert_eq!(c, c_var.value().unwrap());
+    }
+
+    #[test]
+    fn test_transcript_and_transcriptvar_absorb_nonnative_point() {
+        // use 'native' transcript
+        let mut tr = Keccak256Transcript::<Fr>::new(&());
+        let rng = &mut test_rng();
+
+        let p = G1::rand(rng);
+        tr.absorb_nonnative(&p);
+        let c = tr.get_challenge();
+
+        // use 'gadget' transcript
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let mut tr_var = Keccak256TranscriptVar::<Fr>::new(cs.clone(), &());
+        let p_var =
+            NonNativeAffineVar::<G1>::new_witness(ConstraintSystem::<Fr>::new_ref(), || Ok(p))
+                .unwrap();
+        tr_var.absorb_nonnative(&p_var).unwrap();
+        let c_var = tr_var.get_challenge().unwrap();
+
+        // assert that native & gadget transcripts return the same challenge
+        assert_eq!(c, c_var.value().unwrap());
+    }
+
+    #[test]
+    fn test_transcript_and_transcriptvar_get_challenges() {
+        // use 'native' transcript
+        let mut tr = Keccak256Transcript::<Fr>::new(&());
+        tr.absorb_label(b"cf_W");
+        tr.absorb(&Fr::from(42_u32));
+        let c = tr.get_challenge();
+        // the counters absorbed by `get_challenges` make its challenges different from `c`
+        let cs_native = tr.get_challenges(2);
+        assert_ne!(c, cs_native[0]);
+        assert_ne!(cs_native[0], cs_native[1]);
+        let bits_native = tr.get_challenge_nbits_vec(128, 2);
+
+        // use 'gadget' transcript
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let mut tr_var = Keccak256TranscriptVar::<Fr>::new(cs.clone(), &());
+        tr_var.absorb_label(b"cf_W").unwrap();
+        let v = FpVar::<Fr>::new_witness(cs.clone(), || Ok(Fr::from(42_u32))).unwrap();
+        tr_var.absorb(&v).unwrap();
+        let c_var = tr_var.get_challenge().unwrap();
+        let cs_var = tr_var.get_challenges(2).unwrap();
+        let bits_var = tr_var.get_challenge_nbits_vec(128, 2).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // assert that native & gadget transcripts return the same challenges
+        assert_eq!(c, c_var.value().unwrap());
+        assert_eq!(cs_native, cs_var.value().unwrap());
+        for (b, b_var) in bits_native.iter().zip(&bits_var) {
+            assert_eq!(b, &b_var.value().unwrap());
+        }
+
+        // while a different label leads to a different challenge
+        let mut tr = Keccak256Transcript::<Fr>::new(&());
+        tr.absorb_label(b"cf_E");
+        tr.absorb(&Fr::from(42_u32));
+        assert_ne!(c, tr.get_challenge());
+    }
+
+    #[test]
+    fn test_transcript_and_transcriptvar_nbits() {
+        let nbits = crate::constants::NOVA_N_BITS_RO;
+
+        // use 'native' transcript
+        let mut tr = Keccak256Transcript::<Fq>::new(&());
+        tr.absorb(&Fq::from(42_u32));
+
+        // get challenge from native transcript
+        let c_bits = tr.get_challenge_nbits(nbits);
+
+        // use 'gadget' transcript
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let mut tr_var = Keccak256TranscriptVar::<Fq>::new(cs.clone(), &());
+        let v = FpVar::<Fq>::new_witness(cs.clone(), || Ok(Fq::from(42_u32))).unwrap();
+        tr_var.absorb(&v).unwrap();
+
+        // get challenge from circuit transcript
+        let c_var = tr_var.get_challenge_nbits(nbits).unwrap();
+        assert_eq!(c_bits, c_var.value().unwrap());
+
+        // multiply point P by the challenge natively and in-circuit, to ensure that we get the
+        // same result
+        let P = G1::generator();
+        let PVar = GVar::new_witness(cs.clone(), || Ok(P)).unwrap();
+        let c_Fr = Fr::from_bigint(BigInteger::from_bits_le(&c_bits)).unwrap();
+        let cPVar = PVar.scalar_mul_le(c_var.iter()).unwrap();
+        assert_eq!(
+            (P * c_Fr).into_affine(),
+            cPVar.value().unwrap().into_affine()
+        );
+    }
+}
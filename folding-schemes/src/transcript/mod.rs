@@ -5,7 +5,9 @@ use ark_r1cs_std::{
     boolean::Boolean, fields::fp::FpVar, groups::CurveVar, ToConstraintFieldGadget,
 };
 use ark_relations::r1cs::SynthesisError;
+use ark_std::vec::Vec;
 
+pub mod keccak;
 pub mod poseidon;
 
 /// An interface for objects that can be absorbed by a `Transcript`.
@@ -58,11 +60,35 @@ pub trait Transcript<F: PrimeField>: CryptographicSponge {
     ///   the primary curve also contains non-native elements, we still regard
     ///   it as native, because the sponge is on the same curve.
     fn absorb_nonnative<V: AbsorbNonNative<F>>(&mut self, v: &V);
+    /// `absorb_label` absorbs a domain-separation label, so that the values absorbed after it are
+    /// bound to their role in the protocol. The label is packed into a single field element, thus
+    /// it must be shorter than the field's capacity in bytes.
+    fn absorb_label(&mut self, label: &[u8]);
 
     fn get_challenge(&mut self) -> F;
     /// get_challenge_nbits returns a field element of size nbits
     fn get_challenge_nbits(&mut self, nbits: usize) -> Vec<bool>;
-    fn get_challenges(&mut self, n: usize) -> Vec<F>;
+    /// `get_challenges` returns `n` challenges. Before squeezing the `j`-th of them, the counter
+    /// `j` is absorbed as a label (see `challenge_counter_label`), so that each challenge is
+    /// domain-separated from the others, and from the ones obtained with `get_challenge`.
+    fn get_challenges(&mut self, n: usize) -> Vec<F> {
+        (0..n)
+            .map(|j| {
+                self.absorb_label(&challenge_counter_label(j));
+                self.get_challenge()
+            })
+            .collect()
+    }
+    /// `get_challenge_nbits_vec` returns `n` challenges of size `nbits`, derived in the same way
+    /// as in `get_challenges`.
+    fn get_challenge_nbits_vec(&mut self, nbits: usize, n: usize) -> Vec<Vec<bool>> {
+        (0..n)
+            .map(|j| {
+                self.absorb_label(&challenge_counter_label(j));
+                self.get_challenge_nbits(nbits)
+            })
+            .collect()
+    }
 }
 
 pub trait TranscriptVar<F: PrimeField, S: CryptographicSponge>:
@@ -97,10 +123,40 @@ pub trait TranscriptVar<F: PrimeField, S: CryptographicSponge>:
         &mut self,
         v: &V,
     ) -> Result<(), SynthesisError>;
+    /// `absorb_label` is the in-circuit counterpart of `Transcript::absorb_label`, where the label
+    /// is a constant of the circuit.
+    fn absorb_label(&mut self, label: &[u8]) -> Result<(), SynthesisError>;
 
     fn get_challenge(&mut self) -> Result<FpVar<F>, SynthesisError>;
     /// returns the bit representation of the challenge, we use its output in-circuit for the
     /// `GC.scalar_mul_le` method.
     fn get_challenge_nbits(&mut self, nbits: usize) -> Result<Vec<Boolean<F>>, SynthesisError>;
-    fn get_challenges(&mut self, n: usize) -> Result<Vec<FpVar<F>>, SynthesisError>;
+    /// In-circuit counterpart of `Transcript::get_challenges`.
+    fn get_challenges(&mut self, n: usize) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        (0..n)
+            .map(|j| {
+                self.absorb_label(&challenge_counter_label(j))?;
+                self.get_challenge()
+            })
+            .collect()
+    }
+    /// In-circuit counterpart of `Transcript::get_challenge_nbits_vec`.
+    fn get_challenge_nbits_vec(
+        &mut self,
+        nbits: usize,
+        n: usize,
+    ) -> Result<Vec<Vec<Boolean<F>>>, SynthesisError> {
+        (0..n)
+            .map(|j| {
+                self.absorb_label(&challenge_counter_label(j))?;
+                self.get_challenge_nbits(nbits)
+            })
+            .collect()
+    }
+}
+
+/// Label absorbed before squeezing the `j`-th challenge in `get_challenges` and
+/// `get_challenge_nbits_vec`, which is the counter `j` encoded as a little-endian `u64`.
+pub fn challenge_counter_label(j: usize) -> [u8; 8] {
+    (j as u64).to_le_bytes()
 }
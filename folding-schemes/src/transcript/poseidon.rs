@@ -6,11 +6,24 @@ use ark_crypto_primitives::sponge::{
 use ark_ec::{AffineRepr, CurveGroup};
 use ark_ff::{BigInteger, PrimeField};
 use ark_r1cs_std::{
-    boolean::Boolean, fields::fp::FpVar, groups::CurveVar, ToConstraintFieldGadget,
+    boolean::Boolean,
+    fields::{fp::FpVar, FieldVar},
+    groups::CurveVar,
+    ToConstraintFieldGadget,
 };
 use ark_relations::r1cs::SynthesisError;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Validate,
+};
+use ark_std::{
+    format,
+    io::{Read, Write},
+    string::ToString,
+    vec::Vec,
+};
 
 use super::{AbsorbNonNative, AbsorbNonNativeGadget, Transcript, TranscriptVar};
+use crate::Error;
 
 impl<F: PrimeField + Absorb> Transcript<F> for PoseidonSponge<F> {
     // Compatible with the in-circuit `TranscriptVar::absorb_point`
@@ -25,6 +38,9 @@ impl<F: PrimeField + Absorb> Transcript<F> for PoseidonSponge<F> {
     fn absorb_nonnative<V: AbsorbNonNative<F>>(&mut self, v: &V) {
         self.absorb(&v.to_native_sponge_field_elements_as_vec());
     }
+    fn absorb_label(&mut self, label: &[u8]) {
+        self.absorb(&F::from_le_bytes_mod_order(label));
+    }
     fn get_challenge(&mut self) -> F {
         let c = self.squeeze_field_elements(1);
         self.absorb(&c[0]);
@@ -32,14 +48,17 @@ impl<F: PrimeField + Absorb> Transcript<F> for PoseidonSponge<F> {
     }
     fn get_challenge_nbits(&mut self, nbits: usize) -> Vec<bool> {
         let bits = self.squeeze_bits(nbits);
-        self.absorb(&F::from(F::BigInt::from_bits_le(&bits)));
+        // the bits are absorbed in chunks that always fit in F, so that a challenge of
+        // `F::MODULUS_BIT_SIZE` bits or more is neither reduced nor rejected, consistently with
+        // the in-circuit transcript. The challenges of the folding schemes fit in a single chunk.
+        self.absorb(
+            &bits
+                .chunks(F::MODULUS_BIT_SIZE as usize - 1)
+                .map(|chunk| F::from(F::BigInt::from_bits_le(chunk)))
+                .collect::<Vec<_>>(),
+        );
         bits
     }
-    fn get_challenges(&mut self, n: usize) -> Vec<F> {
-        let c = self.squeeze_field_elements(n);
-        self.absorb(&c);
-        c
-    }
 }
 
 impl<F: PrimeField> TranscriptVar<F, PoseidonSponge<F>> for PoseidonSpongeVar<F> {
@@ -67,6 +86,9 @@ impl<F: PrimeField> TranscriptVar<F, PoseidonSponge<F>> for PoseidonSpongeVar<F>
     ) -> Result<(), SynthesisError> {
         self.absorb(&v.to_native_sponge_field_elements()?)
     }
+    fn absorb_label(&mut self, label: &[u8]) -> Result<(), SynthesisError> {
+        self.absorb(&FpVar::constant(F::from_le_bytes_mod_order(label)))
+    }
     fn get_challenge(&mut self) -> Result<FpVar<F>, SynthesisError> {
         let c = self.squeeze_field_elements(1)?;
         self.absorb(&c[0])?;
@@ -77,17 +99,18 @@ impl<F: PrimeField> TranscriptVar<F, PoseidonSponge<F>> for PoseidonSpongeVar<F>
     /// `GC.scalar_mul_le` method.
     fn get_challenge_nbits(&mut self, nbits: usize) -> Result<Vec<Boolean<F>>, SynthesisError> {
         let bits = self.squeeze_bits(nbits)?;
-        self.absorb(&Boolean::le_bits_to_fp_var(&bits)?)?;
+        // absorbed in chunks of `F::MODULUS_BIT_SIZE - 1` bits, as in the native transcript
+        self.absorb(
+            &bits
+                .chunks(F::MODULUS_BIT_SIZE as usize - 1)
+                .map(Boolean::le_bits_to_fp_var)
+                .collect::<Result<Vec<_>, _>>()?,
+        )?;
         Ok(bits)
     }
-    fn get_challenges(&mut self, n: usize) -> Result<Vec<FpVar<F>>, SynthesisError> {
-        let c = self.squeeze_field_elements(n)?;
-        self.absorb(&c)?;
-        Ok(c)
-    }
 }
 
-/// This Poseidon configuration generator agrees with Circom's Poseidon(4) in the case of BN254's scalar field
+/// This Poseidon configuration generator agrees with Circom's Poseidon(4) in the case of BN254's scalar field.
 pub fn poseidon_canonical_config<F: PrimeField>() -> PoseidonConfig<F> {
     // 120 bit security target as in
     // https://eprint.iacr.org/2019/458.pdf
@@ -117,6 +140,105 @@ pub fn poseidon_canonical_config<F: PrimeField>() -> PoseidonConfig<F> {
     )
 }
 
+/// Canonical Poseidon configuration for the scalar fields of the Pasta curves (`pallas::Fr` and
+/// `vesta::Fr`, both of 255 bits), with width 3 (rate 2), alpha 5, 8 full rounds and 56 partial
+/// rounds, which target 128-bit security. These are the parameters of the P128Pow5T3 instance of
+/// halo2, and the round constants and MDS matrix generated from them agree with it.
+///
+/// Returns an error for fields that are not of 255 bits.
+pub fn poseidon_canonical_config_pasta<F: PrimeField>() -> Result<PoseidonConfig<F>, Error> {
+    if F::MODULUS_BIT_SIZE != 255 {
+        return Err(Error::NotSupported(format!(
+            "poseidon_canonical_config_pasta over a field of {} bits, it is only defined for the \
+             255-bit Pasta scalar fields",
+            F::MODULUS_BIT_SIZE
+        )));
+    }
+    let full_rounds = 8;
+    let partial_rounds = 56;
+    let alpha = 5;
+    let rate = 2;
+
+    let (ark, mds) = ark_crypto_primitives::sponge::poseidon::find_poseidon_ark_and_mds::<F>(
+        F::MODULUS_BIT_SIZE as u64,
+        rate,
+        full_rounds,
+        partial_rounds,
+        0,
+    );
+
+    Ok(PoseidonConfig::new(
+        full_rounds as usize,
+        partial_rounds as usize,
+        alpha,
+        mds,
+        ark,
+        rate,
+        1,
+    ))
+}
+
+/// Serializes the given Poseidon configuration, as its number of full and partial rounds, alpha,
+/// round constants, MDS matrix, rate and capacity. Its uncompressed output is the one hashed into
+/// the `pp_hash` (see [`pp_hash`](crate::utils::pp_hash)).
+pub fn serialize_poseidon_config<F: PrimeField, W: Write>(
+    config: &PoseidonConfig<F>,
+    mut writer: W,
+    compress: Compress,
+) -> Result<(), SerializationError> {
+    config
+        .full_rounds
+        .serialize_with_mode(&mut writer, compress)?;
+    config
+        .partial_rounds
+        .serialize_with_mode(&mut writer, compress)?;
+    config.alpha.serialize_with_mode(&mut writer, compress)?;
+    config.ark.serialize_with_mode(&mut writer, compress)?;
+    config.mds.serialize_with_mode(&mut writer, compress)?;
+    config.rate.serialize_with_mode(&mut writer, compress)?;
+    config.capacity.serialize_with_mode(&mut writer, compress)?;
+    Ok(())
+}
+
+/// Deserializes a Poseidon configuration serialized by [`serialize_poseidon_config`], checking
+/// that the shapes of its round constants and MDS matrix match its rounds and width.
+pub fn deserialize_poseidon_config<F: PrimeField, R: Read>(
+    mut reader: R,
+    compress: Compress,
+    validate: Validate,
+) -> Result<PoseidonConfig<F>, Error> {
+    let full_rounds = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+    let partial_rounds = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+    let alpha = u64::deserialize_with_mode(&mut reader, compress, validate)?;
+    let ark = Vec::<Vec<F>>::deserialize_with_mode(&mut reader, compress, validate)?;
+    let mds = Vec::<Vec<F>>::deserialize_with_mode(&mut reader, compress, validate)?;
+    let rate = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+    let capacity = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+
+    let width = rate + capacity;
+    if rate == 0
+        || ark.len() != full_rounds + partial_rounds
+        || ark.iter().any(|row| row.len() != width)
+        || mds.len() != width
+        || mds.iter().any(|row| row.len() != width)
+    {
+        return Err(Error::ParamsMismatch(
+            "the shapes of the Poseidon round constants and MDS matrix do not match its rounds and \
+             width"
+                .to_string(),
+        ));
+    }
+    Ok(PoseidonConfig::new(
+        full_rounds,
+        partial_rounds,
+        alpha,
+        mds,
+        ark,
+        rate,
+        capacity,
+    ))
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::folding::circuits::nonnative::affine::NonNativeAffineVar;
@@ -130,6 +252,9 @@ pub mod tests {
     };
     use ark_relations::r1cs::ConstraintSystem;
     use ark_std::test_rng;
+    use halo2_gadgets::poseidon::primitives::{
+        self as halo2_poseidon, ConstantLength, P128Pow5T3, Spec,
+    };
 
     // Test with value taken from https://github.com/iden3/circomlibjs/blob/43cc582b100fc3459cf78d903a6f538e5d7f38ee/test/poseidon.js#L32
     #[test]
@@ -155,6 +280,172 @@ pub mod tests {
         );
     }
 
+    // Known-answer tests of the Pasta configurations: the Poseidon permutation of the state
+    // [0, 1, 2]. The expected values were computed with an independent implementation of the Grain
+    // LFSR and of the permutation of the reference Poseidon scripts, which also reproduces the
+    // circomlibjs vector of `check_against_circom_poseidon`.
+    fn check_pasta_permutation<F: PrimeField + Absorb>(expected: [&str; 3]) {
+        use std::str::FromStr;
+
+        let config = poseidon_canonical_config_pasta::<F>().unwrap();
+        assert_eq!(config.rate, 2);
+        assert_eq!(config.capacity, 1);
+        assert_eq!(config.ark.len(), 8 + 56);
+        // first round constant of P128Pow5T3 in halo2, which is the same for both fields
+        assert_eq!(
+            config.ark[0][0],
+            F::from_str(
+                "24448666467656506447555018649749346340705294023832615387641453784702583464707"
+            )
+            .map_err(|_| ())
+            .unwrap()
+        );
+
+        let mut sponge = PoseidonSponge::<F>::new(&config);
+        sponge.state = vec![F::zero(), F::one(), F::from(2_u32)];
+        // squeezing right after the creation of the sponge applies the permutation to its state
+        let c: Vec<F> = sponge.squeeze_field_elements(1);
+        let expected: Vec<F> = expected
+            .iter()
+            .map(|x| F::from_str(x).map_err(|_| ()).unwrap())
+            .collect();
+        assert_eq!(sponge.state, expected);
+        assert_eq!(c[0], expected[1]);
+
+        // which is what hashing [1, 2] gives, since they are absorbed into the rate part of the
+        // zero state
+        let mut sponge = PoseidonSponge::<F>::new(&config);
+        sponge.absorb(&vec![F::one(), F::from(2_u32)]);
+        assert_eq!(sponge.squeeze_field_elements::<F>(1)[0], expected[1]);
+    }
+
+    #[test]
+    fn check_pasta_poseidon_vectors() {
+        check_pasta_permutation::<ark_pallas::Fr>([
+            "22322561842627156685197453807735251645124552119548776724790988483233524399705",
+            "27090113248495207304570490195654932404673794912237757181609825437423660787185",
+            "17038665073773321051110301570394593864359648438432289432271269645938989965529",
+        ]);
+        check_pasta_permutation::<ark_vesta::Fr>([
+            "19142758212910704988134549186320465225050001548607778483843514680734401733718",
+            "8943457793054409913105520643844025343653237882909500861250463986907015919658",
+            "4653491495579411712133380452970045393126868676144731347343956788496825228765",
+        ]);
+    }
+
+    #[test]
+    fn test_canonical_config_of_pasta_fields() {
+        // the Pasta configuration is opt-in: the canonical configuration of the Pasta fields, and
+        // therefore the `pp_hash` of existing params over them, is unchanged
+        let pallas = poseidon_canonical_config::<ark_pallas::Fr>();
+        let vesta = poseidon_canonical_config::<ark_vesta::Fr>();
+        assert_eq!(pallas.rate, 4);
+        assert_eq!(vesta.rate, 4);
+        assert_eq!(pallas.partial_rounds, 60);
+        assert_eq!(
+            poseidon_canonical_config_pasta::<ark_pallas::Fr>()
+                .unwrap()
+                .rate,
+            2
+        );
+        assert_eq!(
+            poseidon_canonical_config_pasta::<ark_vesta::Fr>()
+                .unwrap()
+                .rate,
+            2
+        );
+        // and the Pasta configuration is not defined for other fields
+        assert!(matches!(
+            poseidon_canonical_config_pasta::<Fr>(),
+            Err(Error::NotSupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_poseidon_config_serialization() {
+        let config = poseidon_canonical_config_pasta::<ark_pallas::Fr>().unwrap();
+        let mut bytes = Vec::new();
+        serialize_poseidon_config(&config, &mut bytes, Compress::Yes).unwrap();
+        let deserialized = deserialize_poseidon_config::<ark_pallas::Fr, _>(
+            bytes.as_slice(),
+            Compress::Yes,
+            Validate::Yes,
+        )
+        .unwrap();
+        assert_eq!(
+            (deserialized.full_rounds, deserialized.partial_rounds),
+            (config.full_rounds, config.partial_rounds)
+        );
+        assert_eq!(deserialized.alpha, config.alpha);
+        assert_eq!(deserialized.ark, config.ark);
+        assert_eq!(deserialized.mds, config.mds);
+        assert_eq!(
+            (deserialized.rate, deserialized.capacity),
+            (config.rate, config.capacity)
+        );
+
+        // a config whose round constants do not match its rounds is rejected
+        let mut short_config = config.clone();
+        short_config.ark.pop();
+        let mut bytes = Vec::new();
+        serialize_poseidon_config(&short_config, &mut bytes, Compress::Yes).unwrap();
+        assert!(matches!(
+            deserialize_poseidon_config::<ark_pallas::Fr, _>(
+                bytes.as_slice(),
+                Compress::Yes,
+                Validate::Yes
+            ),
+            Err(Error::ParamsMismatch(_))
+        ));
+    }
+
+    // Checks the Pasta configurations against the P128Pow5T3 instance of the halo2 reference
+    // implementation: the round constants and the MDS matrix, and the hash of two elements with
+    // its `ConstantLength` domain, whose capacity element (the last one of its state) is
+    // `2 << 64` and whose output is the first element of the permuted state.
+    fn check_pasta_config_against_halo2<F, G>()
+    where
+        F: PrimeField + Absorb,
+        G: ff::PrimeField,
+        P128Pow5T3: Spec<G, 3, 2>,
+    {
+        let to_ark = |x: &G| F::from_le_bytes_mod_order(x.to_repr().as_ref());
+        // the fields are the same
+        assert_eq!(to_ark(&-G::ONE), -F::one());
+
+        let config = poseidon_canonical_config_pasta::<F>().unwrap();
+        let (round_constants, mds, _) = <P128Pow5T3 as Spec<G, 3, 2>>::constants();
+        let round_constants: Vec<Vec<F>> = round_constants
+            .iter()
+            .map(|rc| rc.iter().map(to_ark).collect())
+            .collect();
+        let mds: Vec<Vec<F>> = mds
+            .iter()
+            .map(|row| row.iter().map(to_ark).collect())
+            .collect();
+        assert_eq!(config.ark, round_constants);
+        assert_eq!(config.mds, mds);
+
+        let message = [G::from(3), G::from(5)];
+        let expected =
+            halo2_poseidon::Hash::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init().hash(message);
+        let mut sponge = PoseidonSponge::<F>::new(&config);
+        sponge.state = vec![
+            to_ark(&message[0]),
+            to_ark(&message[1]),
+            F::from(2_u128 << 64),
+        ];
+        // squeezing right after the creation of the sponge applies the permutation to its state
+        sponge.squeeze_field_elements::<F>(1);
+        assert_eq!(sponge.state[0], to_ark(&expected));
+    }
+
+    #[test]
+    fn check_pasta_poseidon_against_halo2() {
+        check_pasta_config_against_halo2::<ark_pallas::Fr, pasta_curves::Fq>();
+        check_pasta_config_against_halo2::<ark_vesta::Fr, pasta_curves::Fp>();
+    }
+
     #[test]
     fn test_transcript_and_transcriptvar_absorb_native_point() {
         // use 'native' transcript
@@ -224,6 +515,73 @@ pub mod tests {
         assert_eq!(c, c_var.value().unwrap());
     }
 
+    #[test]
+    fn test_transcript_and_transcriptvar_get_challenges() {
+        let nbits = crate::constants::NOVA_N_BITS_RO;
+        let config = poseidon_canonical_config::<Fr>();
+        for n in 1..=5 {
+            // use 'native' transcript
+            let mut tr = PoseidonSponge::<Fr>::new(&config);
+            tr.absorb(&Fr::from(42_u32));
+            let cs_native = tr.get_challenges(n);
+            let bits_native = tr.get_challenge_nbits_vec(nbits, n);
+
+            // use 'gadget' transcript
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let mut tr_var = PoseidonSpongeVar::<Fr>::new(cs.clone(), &config);
+            let v = FpVar::<Fr>::new_witness(cs.clone(), || Ok(Fr::from(42_u32))).unwrap();
+            tr_var.absorb(&v).unwrap();
+            let cs_var = tr_var.get_challenges(n).unwrap();
+            let bits_var = tr_var.get_challenge_nbits_vec(nbits, n).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+
+            // assert that native & gadget transcripts return the same challenges
+            assert_eq!(cs_native.len(), n);
+            assert_eq!(cs_native, cs_var.value().unwrap());
+            assert_eq!(bits_native.len(), n);
+            for (b, b_var) in bits_native.iter().zip(&bits_var) {
+                assert_eq!(b.len(), nbits);
+                assert_eq!(b, &b_var.value().unwrap());
+            }
+
+            // the counter makes the challenges distinct from each other, and the first one
+            // distinct from the one given by `get_challenge`
+            for i in 1..n {
+                assert!(!cs_native[..i].contains(&cs_native[i]));
+            }
+            let mut tr = PoseidonSponge::<Fr>::new(&config);
+            tr.absorb(&Fr::from(42_u32));
+            assert_ne!(cs_native[0], tr.get_challenge());
+        }
+    }
+
+    #[test]
+    fn test_transcript_and_transcriptvar_absorb_label() {
+        // use 'native' transcript
+        let config = poseidon_canonical_config::<Fr>();
+        let mut tr = PoseidonSponge::<Fr>::new(&config);
+        tr.absorb_label(b"cf_W");
+        tr.absorb(&Fr::from(42_u32));
+        let c = tr.get_challenge();
+
+        // use 'gadget' transcript
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let mut tr_var = PoseidonSpongeVar::<Fr>::new(cs.clone(), &config);
+        tr_var.absorb_label(b"cf_W").unwrap();
+        let v = FpVar::<Fr>::new_witness(cs.clone(), || Ok(Fr::from(42_u32))).unwrap();
+        tr_var.absorb(&v).unwrap();
+        let c_var = tr_var.get_challenge().unwrap();
+
+        // assert that native & gadget transcripts return the same challenge
+        assert_eq!(c, c_var.value().unwrap());
+
+        // while a different label leads to a different challenge
+        let mut tr = PoseidonSponge::<Fr>::new(&config);
+        tr.absorb_label(b"cf_E");
+        tr.absorb(&Fr::from(42_u32));
+        assert_ne!(c, tr.get_challenge());
+    }
+
     #[test]
     fn test_transcript_and_transcriptvar_nbits() {
         let nbits = crate::constants::NOVA_N_BITS_RO;
@@ -271,4 +629,29 @@ pub mod tests {
             cPVar.value().unwrap().into_affine()
         );
     }
+
+    /// challenges of `Fr::MODULUS_BIT_SIZE` bits or more, whose value can exceed the modulus, keep
+    /// the native and gadget transcripts in sync
+    #[test]
+    fn test_transcript_and_transcriptvar_nbits_above_modulus() {
+        let config = poseidon_canonical_config::<Fr>();
+        let n_bits = Fr::MODULUS_BIT_SIZE as usize;
+        for nbits in [n_bits - 1, n_bits, 2 * n_bits + 1] {
+            let mut tr = PoseidonSponge::<Fr>::new(&config);
+            tr.absorb(&Fr::from(42_u32));
+            let c_bits = tr.get_challenge_nbits(nbits);
+            let c = tr.get_challenge();
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let mut tr_var = PoseidonSpongeVar::<Fr>::new(cs.clone(), &config);
+            let v = FpVar::<Fr>::new_witness(cs.clone(), || Ok(Fr::from(42_u32))).unwrap();
+            tr_var.absorb(&v).unwrap();
+            let c_bits_var = tr_var.get_challenge_nbits(nbits).unwrap();
+            let c_var = tr_var.get_challenge().unwrap();
+
+            assert_eq!(c_bits, c_bits_var.value().unwrap());
+            assert_eq!(c, c_var.value().unwrap());
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
 }
@@ -1,3 +1,5 @@
 pub mod multilinear_polynomial;
+// the sum-check relies on `espresso_subroutines`, which requires `std`
+#[cfg(feature = "std")]
 pub mod sum_check;
 pub mod virtual_polynomial;
@@ -12,6 +12,7 @@ use ark_ff::Field;
 use rayon::prelude::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
 
 pub use ark_poly::DenseMultilinearExtension;
+use ark_std::vec::Vec;
 
 pub fn fix_variables<F: Field>(
     poly: &DenseMultilinearExtension<F>,
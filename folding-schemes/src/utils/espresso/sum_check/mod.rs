@@ -173,6 +173,7 @@ impl<F: PrimeField + Absorb, T: Transcript<F>> SumCheck<F> for IOPSumCheck<F, T>
         Ok(IOPProof {
             point: prover_state.challenges,
             proofs: prover_msgs,
+            degree: poly.aux_info.max_degree,
         })
     }
 
@@ -182,11 +183,19 @@ impl<F: PrimeField + Absorb, T: Transcript<F>> SumCheck<F> for IOPSumCheck<F, T>
         aux_info: &VPAuxInfo<F>,
         transcript: &mut impl Transcript<F>,
     ) -> Result<SumCheckSubClaim<F>, PolyIOPErrors> {
+        if proof.degree != aux_info.max_degree || proof.proofs.len() != aux_info.num_variables {
+            return Err(PolyIOPErrors::InvalidProof(format!(
+                "Proof of {} rounds of degree {}, expected {} rounds of degree {}.",
+                proof.proofs.len(),
+                proof.degree,
+                aux_info.num_variables,
+                aux_info.max_degree
+            )));
+        }
         transcript.absorb(&F::from(aux_info.num_variables as u64));
         transcript.absorb(&F::from(aux_info.max_degree as u64));
         let mut verifier_state = IOPVerifierState::verifier_init(aux_info);
-        for i in 0..aux_info.num_variables {
-            let prover_msg = proof.proofs.get(i).expect("proof is incomplete");
+        for prover_msg in proof.proofs.iter() {
             transcript.absorb(&prover_msg.coeffs);
             IOPVerifierState::verify_round_and_update_state(
                 &mut verifier_state,
@@ -209,13 +218,16 @@ pub mod tests {
     use ark_pallas::Fr;
     use ark_poly::DenseMultilinearExtension;
     use ark_poly::MultilinearExtension;
-    use ark_std::test_rng;
+    use ark_std::{test_rng, UniformRand};
+
+    use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, Polynomial};
 
     use crate::transcript::poseidon::poseidon_canonical_config;
+    use crate::utils::multilinear_polynomial::tests::random_mle_list;
     use crate::utils::sum_check::SumCheck;
-    use crate::utils::virtual_polynomial::VirtualPolynomial;
+    use crate::utils::virtual_polynomial::{bit_decompose, VPAuxInfo, VirtualPolynomial};
 
-    use super::IOPSumCheck;
+    use super::{structs::IOPProof, IOPSumCheck};
 
     #[test]
     pub fn sumcheck_poseidon() {
@@ -256,4 +268,132 @@ pub mod tests {
 
         assert!(res_verify.is_ok());
     }
+
+    // the proof (round polynomials and challenges) must not depend on the number of threads used
+    // by the prover
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn sumcheck_parallel_prover_determinism() {
+        let n_vars = 8;
+        let mut rng = test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        // products of different degrees, sharing some of their multiplicands
+        let mut virtual_poly = VirtualPolynomial::new(n_vars);
+        let (mles, _) = random_mle_list::<Fr, _>(n_vars, 3, &mut rng);
+        virtual_poly
+            .add_mle_list(mles.clone(), Fr::rand(&mut rng))
+            .unwrap();
+        virtual_poly
+            .add_mle_list(mles[..2].to_vec(), Fr::rand(&mut rng))
+            .unwrap();
+        let (other_mles, _) = random_mle_list::<Fr, _>(n_vars, 1, &mut rng);
+        virtual_poly
+            .add_mle_list(other_mles, Fr::rand(&mut rng))
+            .unwrap();
+
+        let prove = |n_threads: usize| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(n_threads)
+                .build()
+                .unwrap()
+                .install(|| {
+                    let mut transcript: PoseidonSponge<Fr> =
+                        PoseidonSponge::<Fr>::new(&poseidon_config);
+                    IOPSumCheck::<Fr, PoseidonSponge<Fr>>::prove(&virtual_poly, &mut transcript)
+                        .unwrap()
+                })
+        };
+        let sequential_proof = prove(1);
+        let parallel_proof = prove(4);
+        assert_eq!(sequential_proof.point, parallel_proof.point);
+        assert_eq!(sequential_proof.proofs, parallel_proof.proofs);
+
+        // the input polynomial is left untouched by the prover
+        let claimed_sum = IOPSumCheck::<Fr, PoseidonSponge<Fr>>::extract_sum(&parallel_proof);
+        let mut transcript: PoseidonSponge<Fr> = PoseidonSponge::<Fr>::new(&poseidon_config);
+        let subclaim = IOPSumCheck::<Fr, PoseidonSponge<Fr>>::verify(
+            claimed_sum,
+            &parallel_proof,
+            &virtual_poly.aux_info,
+            &mut transcript,
+        )
+        .unwrap();
+        assert_eq!(
+            virtual_poly.evaluate(&subclaim.point).unwrap(),
+            subclaim.expected_evaluation
+        );
+    }
+
+    // the round polynomials of the prover are the sums of the virtual polynomial over the boolean
+    // hypercube, for random virtual polynomials of several degrees, and the verifier checks their
+    // degree against the one of the aux info
+    #[test]
+    fn sumcheck_round_polynomials_brute_force() {
+        let n_vars = 4;
+        let mut rng = test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        for degree in 1..=6 {
+            // a product of `degree` MLEs, plus one of a lower degree
+            let mut virtual_poly = VirtualPolynomial::new(n_vars);
+            let (mles, _) = random_mle_list::<Fr, _>(n_vars, degree, &mut rng);
+            virtual_poly
+                .add_mle_list(mles.clone(), Fr::rand(&mut rng))
+                .unwrap();
+            virtual_poly
+                .add_mle_list(mles[..degree.div_ceil(2)].to_vec(), Fr::rand(&mut rng))
+                .unwrap();
+            assert_eq!(virtual_poly.aux_info.max_degree, degree);
+
+            let mut transcript: PoseidonSponge<Fr> = PoseidonSponge::<Fr>::new(&poseidon_config);
+            let proof =
+                IOPSumCheck::<Fr, PoseidonSponge<Fr>>::prove(&virtual_poly, &mut transcript)
+                    .unwrap();
+            assert_eq!(proof.degree, degree);
+
+            // p_i(X) = \sum_{b \in \{0,1\}^{n-i-1}} f(r_0, ..., r_{i-1}, X, b)
+            for (i, msg) in proof.proofs.iter().enumerate() {
+                assert_eq!(msg.coeffs.len(), degree + 1);
+                let p_i = DensePolynomial::from_coefficients_slice(&msg.coeffs);
+                let n_free_vars = n_vars - i - 1;
+                for _ in 0..=degree {
+                    let x = Fr::rand(&mut rng);
+                    let brute_force: Fr = (0..1 << n_free_vars)
+                        .map(|b| {
+                            let b = bit_decompose(b, n_free_vars).into_iter().map(Fr::from);
+                            let point = [proof.point[..i].to_vec(), vec![x], b.collect()].concat();
+                            virtual_poly.evaluate(&point).unwrap()
+                        })
+                        .sum();
+                    assert_eq!(p_i.evaluate(&x), brute_force);
+                }
+            }
+
+            let verify = |proof: &IOPProof<Fr>, aux_info: &VPAuxInfo<Fr>| {
+                let claimed_sum = IOPSumCheck::<Fr, PoseidonSponge<Fr>>::extract_sum(proof);
+                let mut transcript: PoseidonSponge<Fr> =
+                    PoseidonSponge::<Fr>::new(&poseidon_config);
+                IOPSumCheck::<Fr, PoseidonSponge<Fr>>::verify(
+                    claimed_sum,
+                    proof,
+                    aux_info,
+                    &mut transcript,
+                )
+            };
+            let subclaim = verify(&proof, &virtual_poly.aux_info).unwrap();
+            assert_eq!(
+                virtual_poly.evaluate(&subclaim.point).unwrap(),
+                subclaim.expected_evaluation
+            );
+
+            // a proof of another degree than the one of the aux info is rejected
+            let mut aux_info = virtual_poly.aux_info.clone();
+            aux_info.max_degree += 1;
+            assert!(verify(&proof, &aux_info).is_err());
+            // as well as a round polynomial with more coefficients than the degree allows
+            let mut bad_proof = proof.clone();
+            bad_proof.proofs[1].coeffs.push(Fr::ZERO);
+            assert!(verify(&bad_proof, &virtual_poly.aux_info).is_err());
+        }
+    }
 }
@@ -11,20 +11,20 @@
 
 use super::SumCheckProver;
 use crate::utils::{
-    lagrange_poly::compute_lagrange_interpolated_poly, multilinear_polynomial::fix_variables,
-    virtual_polynomial::VirtualPolynomial,
+    lagrange_poly::compute_lagrange_interpolated_poly, virtual_polynomial::VirtualPolynomial,
 };
 use ark_ff::{batch_inversion, PrimeField};
 use ark_poly::DenseMultilinearExtension;
-use ark_std::{cfg_into_iter, end_timer, start_timer};
-use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator};
+use ark_std::{cfg_into_iter, cfg_iter_mut, end_timer, start_timer};
 use std::sync::Arc;
 
 use super::structs::{IOPProverMessage, IOPProverState};
 use espresso_subroutines::poly_iop::prelude::PolyIOPErrors;
 
-// #[cfg(feature = "parallel")]
-use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+#[cfg(feature = "parallel")]
+use rayon::prelude::{
+    IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator, ParallelSlice,
+};
 
 impl<F: PrimeField> SumCheckProver<F> for IOPProverState<F> {
     type VirtualPolynomial = VirtualPolynomial<F>;
@@ -86,13 +86,10 @@ impl<F: PrimeField> SumCheckProver<F> for IOPProverState<F> {
         //    g(r_1, ..., r_{m-1}, x_m ... x_n)
         //
         // eval g over r_m, and mutate g to g(r_1, ... r_m,, x_{m+1}... x_n)
-        let mut flattened_ml_extensions: Vec<DenseMultilinearExtension<F>> = self
-            .poly
-            .flattened_ml_extensions
-            .par_iter()
-            .map(|x| x.as_ref().clone())
-            .collect();
-
+        //
+        // The tables are updated in place, so that they are only copied once (in the first
+        // round, where they are still shared with the input polynomial) instead of at every
+        // round.
         if let Some(chal) = challenge {
             if self.round == 0 {
                 return Err(PolyIOPErrors::InvalidProver(
@@ -102,14 +99,8 @@ impl<F: PrimeField> SumCheckProver<F> for IOPProverState<F> {
             self.challenges.push(*chal);
 
             let r = self.challenges[self.round - 1];
-            // #[cfg(feature = "parallel")]
-            flattened_ml_extensions
-                .par_iter_mut()
-                .for_each(|mle| *mle = fix_variables(mle, &[r]));
-            // #[cfg(not(feature = "parallel"))]
-            // flattened_ml_extensions
-            //     .iter_mut()
-            //     .for_each(|mle| *mle = fix_variables(mle, &[r]));
+            cfg_iter_mut!(self.poly.flattened_ml_extensions)
+                .for_each(|mle| fix_first_variable_in_place(Arc::make_mut(mle), &r));
         } else if self.round > 0 {
             return Err(PolyIOPErrors::InvalidProver(
                 "verifier message is empty".to_string(),
@@ -119,47 +110,17 @@ impl<F: PrimeField> SumCheckProver<F> for IOPProverState<F> {
 
         self.round += 1;
 
-        let products_list = self.poly.products.clone();
         let mut products_sum = vec![F::ZERO; self.poly.aux_info.max_degree + 1];
 
         // Step 2: generate sum for the partial evaluated polynomial:
         // f(r_1, ... r_m,, x_{m+1}... x_n)
-
-        products_list.iter().for_each(|(coefficient, products)| {
-            let mut sum = cfg_into_iter!(0..1 << (self.poly.aux_info.num_variables - self.round))
-                .fold(
-                    || {
-                        (
-                            vec![(F::ZERO, F::ZERO); products.len()],
-                            vec![F::ZERO; products.len() + 1],
-                        )
-                    },
-                    |(mut buf, mut acc), b| {
-                        buf.iter_mut()
-                            .zip(products.iter())
-                            .for_each(|((eval, step), f)| {
-                                let table = &flattened_ml_extensions[*f];
-                                *eval = table[b << 1];
-                                *step = table[(b << 1) + 1] - table[b << 1];
-                            });
-                        acc[0] += buf.iter().map(|(eval, _)| eval).product::<F>();
-                        acc[1..].iter_mut().for_each(|acc| {
-                            buf.iter_mut().for_each(|(eval, step)| *eval += step as &_);
-                            *acc += buf.iter().map(|(eval, _)| eval).product::<F>();
-                        });
-                        (buf, acc)
-                    },
-                )
-                .map(|(_, partial)| partial)
-                .reduce(
-                    || vec![F::ZERO; products.len() + 1],
-                    |mut sum, partial| {
-                        sum.iter_mut()
-                            .zip(partial.iter())
-                            .for_each(|(sum, partial)| *sum += partial);
-                        sum
-                    },
-                );
+        let n_points = 1 << (self.poly.aux_info.num_variables - self.round);
+        for (coefficient, products) in self.poly.products.iter() {
+            let tables = products
+                .iter()
+                .map(|f| self.poly.flattened_ml_extensions[*f].evaluations.as_slice())
+                .collect::<Vec<_>>();
+            let mut sum = sum_of_products(&tables, n_points);
             sum.iter_mut().for_each(|sum| *sum *= coefficient);
             let extraploation = cfg_into_iter!(0..self.poly.aux_info.max_degree - products.len())
                 .map(|i| {
@@ -172,21 +133,111 @@ impl<F: PrimeField> SumCheckProver<F> for IOPProverState<F> {
                 .iter_mut()
                 .zip(sum.iter().chain(extraploation.iter()))
                 .for_each(|(products_sum, sum)| *products_sum += sum);
-        });
+        }
+
+        // the interpolated polynomial drops its leading zero coefficients, which are restored so
+        // that every message has `max_degree + 1` coefficients, as the verifier expects
+        let mut coeffs = compute_lagrange_interpolated_poly::<F>(&products_sum).coeffs;
+        coeffs.resize(self.poly.aux_info.max_degree + 1, F::ZERO);
+        Ok(IOPProverMessage { coeffs })
+    }
+}
 
-        // update prover's state to the partial evaluated polynomial
-        self.poly.flattened_ml_extensions = flattened_ml_extensions
-            .par_iter()
-            .map(|x| Arc::new(x.clone()))
+/// Fixes the first variable of the given MLE to `r`, halving its table of evaluations.
+fn fix_first_variable_in_place<F: PrimeField>(mle: &mut DenseMultilinearExtension<F>, r: &F) {
+    let half_len = mle.evaluations.len() >> 1;
+    #[cfg(not(feature = "parallel"))]
+    fix_first_variable_sequential(&mut mle.evaluations, r);
+    #[cfg(feature = "parallel")]
+    {
+        mle.evaluations = mle
+            .evaluations
+            .par_chunks(2)
+            .map(|pair| pair[0] + (pair[1] - pair[0]) * r)
             .collect();
+    }
+    mle.evaluations.truncate(half_len);
+    mle.num_vars -= 1;
+}
 
-        let prover_poly = compute_lagrange_interpolated_poly::<F>(&products_sum);
-        Ok(IOPProverMessage {
-            coeffs: prover_poly.coeffs,
-        })
+/// Sequential update of the first half of `evaluations` to the ones of the MLE with its first
+/// variable fixed to `r`.
+#[cfg(any(not(feature = "parallel"), test))]
+fn fix_first_variable_sequential<F: PrimeField>(evaluations: &mut [F], r: &F) {
+    // the entry `b` only depends on the entries `2b` and `2b + 1`, thus the sequential update
+    // never overwrites an entry that has not been read yet
+    for b in 0..evaluations.len() >> 1 {
+        evaluations[b] =
+            evaluations[b << 1] + (evaluations[(b << 1) + 1] - evaluations[b << 1]) * r;
     }
 }
 
+/// Evaluates at the points `0, 1, ..., tables.len()` the univariate polynomial
+/// `\sum_{b \in \{0,1\}^{n}} \prod_j g_j(X, b)`, where the `g_j` are the multilinear polynomials
+/// given by `tables` and `n_points = 2^n`.
+#[cfg(not(feature = "parallel"))]
+fn sum_of_products<F: PrimeField>(tables: &[&[F]], n_points: usize) -> Vec<F> {
+    sum_of_products_sequential(tables, n_points)
+}
+
+#[cfg(any(not(feature = "parallel"), test))]
+fn sum_of_products_sequential<F: PrimeField>(tables: &[&[F]], n_points: usize) -> Vec<F> {
+    let mut buf = vec![(F::ZERO, F::ZERO); tables.len()];
+    let mut acc = vec![F::ZERO; tables.len() + 1];
+    for b in 0..n_points {
+        accumulate_product(tables, &mut buf, &mut acc, b);
+    }
+    acc
+}
+
+/// Parallel version of `sum_of_products`, where each thread accumulates the evaluations over a
+/// part of the boolean hypercube, which are then added together. As the additions are exact, the
+/// result is the same as the one of the sequential version.
+#[cfg(feature = "parallel")]
+fn sum_of_products<F: PrimeField>(tables: &[&[F]], n_points: usize) -> Vec<F> {
+    (0..n_points)
+        .into_par_iter()
+        .fold(
+            || {
+                (
+                    vec![(F::ZERO, F::ZERO); tables.len()],
+                    vec![F::ZERO; tables.len() + 1],
+                )
+            },
+            |(mut buf, mut acc), b| {
+                accumulate_product(tables, &mut buf, &mut acc, b);
+                (buf, acc)
+            },
+        )
+        .map(|(_, partial)| partial)
+        .reduce(
+            || vec![F::ZERO; tables.len() + 1],
+            |mut sum, partial| {
+                sum.iter_mut()
+                    .zip(partial.iter())
+                    .for_each(|(sum, partial)| *sum += partial);
+                sum
+            },
+        )
+}
+
+/// Accumulates into `acc` the evaluations at `X = 0, 1, ..., tables.len()` of
+/// `\prod_j g_j(X, b)`, using `buf` to store the evaluations of each `g_j(X, b)` and their
+/// increments between consecutive `X`.
+fn accumulate_product<F: PrimeField>(tables: &[&[F]], buf: &mut [(F, F)], acc: &mut [F], b: usize) {
+    buf.iter_mut()
+        .zip(tables.iter())
+        .for_each(|((eval, step), table)| {
+            *eval = table[b << 1];
+            *step = table[(b << 1) + 1] - table[b << 1];
+        });
+    acc[0] += buf.iter().map(|(eval, _)| eval).product::<F>();
+    acc[1..].iter_mut().for_each(|acc| {
+        buf.iter_mut().for_each(|(eval, step)| *eval += step as &_);
+        *acc += buf.iter().map(|(eval, _)| eval).product::<F>();
+    });
+}
+
 #[allow(clippy::filter_map_bool_then)]
 fn barycentric_weights<F: PrimeField>(points: &[F]) -> Vec<F> {
     let mut weights = points
@@ -222,3 +273,36 @@ fn extrapolate<F: PrimeField>(points: &[F], weights: &[F], evals: &[F], at: &F)
         .sum::<F>()
         * sum_inv
 }
+
+#[cfg(all(test, feature = "parallel"))]
+mod tests {
+    use super::*;
+    use ark_pallas::Fr;
+    use ark_std::{test_rng, UniformRand};
+
+    // the parallel round computations output the same values as the sequential ones
+    #[test]
+    fn test_parallel_round_matches_sequential() {
+        let mut rng = test_rng();
+        let n_vars = 6;
+        let tables = (0..3)
+            .map(|_| (0..1 << n_vars).map(|_| Fr::rand(&mut rng)).collect())
+            .collect::<Vec<Vec<Fr>>>();
+        let tables = tables.iter().map(|t| t.as_slice()).collect::<Vec<_>>();
+        for n in 1..=tables.len() {
+            assert_eq!(
+                sum_of_products(&tables[..n], 1 << (n_vars - 1)),
+                sum_of_products_sequential(&tables[..n], 1 << (n_vars - 1))
+            );
+        }
+
+        let r = Fr::rand(&mut rng);
+        let mut mle = DenseMultilinearExtension::from_evaluations_slice(n_vars, tables[0]);
+        fix_first_variable_in_place(&mut mle, &r);
+        let mut expected = tables[0].to_vec();
+        fix_first_variable_sequential(&mut expected, &r);
+        expected.truncate(1 << (n_vars - 1));
+        assert_eq!(mle.evaluations, expected);
+        assert_eq!(mle.num_vars, n_vars - 1);
+    }
+}
@@ -17,14 +17,18 @@ use ark_serialize::CanonicalSerialize;
 /// - messages from prover to verifier at each round through the interactive
 ///   protocol.
 /// - a point that is generated by the transcript for evaluation
+/// - the degree of the polynomials of the messages
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct IOPProof<F: PrimeField> {
     pub point: Vec<F>,
     pub proofs: Vec<IOPProverMessage<F>>,
+    /// degree of the univariate polynomial sent at each round, which is the `max_degree` of the
+    /// virtual polynomial, and which the verifier checks against its `VPAuxInfo`
+    pub degree: usize,
 }
 
 /// A message from the prover to the verifier at a given round
-/// is a list of coeffs.
+/// is a list of coeffs, which has exactly `degree + 1` elements (the leading ones can be zero).
 #[derive(Clone, Debug, Default, PartialEq, Eq, CanonicalSerialize)]
 pub struct IOPProverMessage<F: PrimeField> {
     pub(crate) coeffs: Vec<F>,
@@ -50,6 +54,7 @@ pub struct IOPProverState<F: PrimeField> {
 pub struct IOPVerifierState<F: PrimeField> {
     pub(crate) round: usize,
     pub(crate) num_vars: usize,
+    pub(crate) max_degree: usize,
     pub(crate) finished: bool,
     /// a list storing the univariate polynomial in evaluation form sent by the
     /// prover at each round
@@ -36,6 +36,7 @@ impl<F: PrimeField + Absorb> SumCheckVerifier<F> for IOPVerifierState<F> {
         let res = Self {
             round: 1,
             num_vars: index_info.num_variables,
+            max_degree: index_info.max_degree,
             finished: false,
             polynomials_received: Vec::with_capacity(index_info.num_variables),
             challenges: Vec::with_capacity(index_info.num_variables),
@@ -57,6 +58,14 @@ impl<F: PrimeField + Absorb> SumCheckVerifier<F> for IOPVerifierState<F> {
                 "Incorrect verifier state: Verifier is already finished.".to_string(),
             ));
         }
+        if prover_msg.coeffs.len() != self.max_degree + 1 {
+            return Err(PolyIOPErrors::InvalidProof(format!(
+                "Prover message has {} coefficients, expected {} for degree {}.",
+                prover_msg.coeffs.len(),
+                self.max_degree + 1,
+                self.max_degree
+            )));
+        }
 
         // In an interactive protocol, the verifier should
         //
@@ -10,12 +10,21 @@
 //! This module defines our main mathematical object `VirtualPolynomial`; and
 //! various functions associated with it.
 
+use alloc::sync::Arc;
 use ark_ff::PrimeField;
 use ark_poly::{DenseMultilinearExtension, MultilinearExtension};
 use ark_serialize::CanonicalSerialize;
-use ark_std::{end_timer, start_timer};
-use rayon::prelude::*;
-use std::{cmp::max, collections::HashMap, marker::PhantomData, ops::Add, sync::Arc};
+use ark_std::{
+    cfg_iter_mut,
+    cmp::max,
+    collections::BTreeMap,
+    end_timer,
+    marker::PhantomData,
+    ops::Add,
+    start_timer,
+    string::{String, ToString},
+    vec::Vec,
+};
 use thiserror::Error;
 
 //-- aritherrors
@@ -74,7 +83,7 @@ pub struct VirtualPolynomial<F: PrimeField> {
     /// to.
     pub flattened_ml_extensions: Vec<Arc<DenseMultilinearExtension<F>>>,
     /// Pointers to the above poly extensions
-    raw_pointers_lookup_table: HashMap<*const DenseMultilinearExtension<F>, usize>,
+    raw_pointers_lookup_table: BTreeMap<*const DenseMultilinearExtension<F>, usize>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, CanonicalSerialize)]
@@ -121,14 +130,14 @@ impl<F: PrimeField> VirtualPolynomial<F> {
             },
             products: Vec::new(),
             flattened_ml_extensions: Vec::new(),
-            raw_pointers_lookup_table: HashMap::new(),
+            raw_pointers_lookup_table: BTreeMap::new(),
         }
     }
 
     /// Creates an new virtual polynomial from a MLE and its coefficient.
     pub fn new_from_mle(mle: &Arc<DenseMultilinearExtension<F>>, coefficient: F) -> Self {
         let mle_ptr: *const DenseMultilinearExtension<F> = Arc::as_ptr(mle);
-        let mut hm = HashMap::new();
+        let mut hm = BTreeMap::new();
         hm.insert(mle_ptr, 0);
 
         VirtualPolynomial {
@@ -384,7 +393,7 @@ fn build_eq_x_r_helper<F: PrimeField>(r: &[F], buf: &mut Vec<F>) -> Result<(), A
         // *buf = res;
 
         let mut res = vec![F::zero(); buf.len() << 1];
-        res.par_iter_mut().enumerate().for_each(|(i, val)| {
+        cfg_iter_mut!(res).enumerate().for_each(|(i, val)| {
             let bi = buf[i >> 1];
             let tmp = r[0] * bi;
             if i & 1 == 0 {
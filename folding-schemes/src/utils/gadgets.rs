@@ -6,6 +6,7 @@ use ark_r1cs_std::{
     R1CSVar,
 };
 use ark_relations::r1cs::{Namespace, SynthesisError};
+use ark_std::{borrow::ToOwned, vec::Vec};
 use core::borrow::Borrow;
 
 use crate::utils::vec::SparseMatrix;
@@ -139,7 +140,7 @@ pub fn eval_mle<F: PrimeField>(
     // pad to 2^n_vars
     let mut poly: Vec<FpVar<F>> = [
         v.to_owned(),
-        std::iter::repeat(FpVar::zero())
+        ark_std::iter::repeat(FpVar::zero())
             .take((1 << n_vars) - v.len())
             .collect(),
     ]
@@ -1,8 +1,7 @@
 /// A boolean hypercube structure to create an ergonomic evaluation domain
 use crate::utils::virtual_polynomial::bit_decompose;
 use ark_ff::PrimeField;
-
-use std::marker::PhantomData;
+use ark_std::{marker::PhantomData, vec::Vec};
 
 /// A boolean hypercube that returns its points as an iterator
 /// If you iterate on it for 3 variables you will get points in little-endian order:
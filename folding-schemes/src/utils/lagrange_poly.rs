@@ -1,5 +1,6 @@
 use ark_ff::PrimeField;
 use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_std::vec::Vec;
 
 /// Computes the lagrange interpolated polynomial from the given points `p_i`
 pub fn compute_lagrange_interpolated_poly<F: PrimeField>(p_i: &[F]) -> DensePolynomial<F> {
@@ -1,9 +1,10 @@
 /// Some basic MLE utilities
-use ark_ff::PrimeField;
+use ark_ff::{Field, PrimeField};
 use ark_poly::{DenseMultilinearExtension, SparseMultilinearExtension};
-use ark_std::log2;
+use ark_std::{borrow::ToOwned, log2, vec::Vec};
 
 use super::vec::SparseMatrix;
+use crate::Error;
 
 /// Pad matrix so that its columns and rows are powers of two
 pub fn pad_matrix<F: PrimeField>(m: &SparseMatrix<F>) -> SparseMatrix<F> {
@@ -39,7 +40,7 @@ pub fn vec_to_dense_mle<F: PrimeField>(n_vars: usize, v: &[F]) -> DenseMultiline
         // pad to 2^n_vars
         [
             v.to_owned(),
-            std::iter::repeat(F::zero())
+            ark_std::iter::repeat(F::zero())
                 .take((1 << n_vars) - v.len())
                 .collect(),
         ]
@@ -82,7 +83,7 @@ pub fn dense_vec_to_dense_mle<F: PrimeField>(
     // Pad to 2^n_vars
     let v_padded: Vec<F> = [
         v.to_owned(),
-        std::iter::repeat(F::zero())
+        ark_std::iter::repeat(F::zero())
             .take((1 << n_vars) - v.len())
             .collect(),
     ]
@@ -100,6 +101,35 @@ pub fn dense_vec_to_mle<F: PrimeField>(n_vars: usize, v: &[F]) -> SparseMultilin
     SparseMultilinearExtension::<F>::from_evaluations(n_vars, &v_sparse)
 }
 
+/// Expands the challenge `c` into the point `(c, c^2, c^4, ..., c^{2^{n-1}})`. The multilinear
+/// polynomial `f` evaluated at it is the univariate polynomial `Σ_i f_i c^i` of degree `< 2^n`,
+/// since every monomial of `f` maps to a distinct power of `c`, so a single challenge suffices
+/// for the Schwartz-Zippel argument.
+pub fn point_from_challenge<F: Field>(c: F, n: usize) -> Vec<F> {
+    ark_std::iter::successors(Some(c), |c_k| Some(c_k.square()))
+        .take(n)
+        .collect()
+}
+
+/// Evaluates at `point` the multilinear polynomial whose evaluations over the boolean hypercube
+/// are `v` padded with zeros to `2^point.len()` elements, with the first variable being the least
+/// significant bit of the index.
+pub fn evaluate_mle<F: PrimeField>(v: &[F], point: &[F]) -> Result<F, Error> {
+    let N = 1 << point.len();
+    if v.len() > N {
+        return Err(Error::NotExpectedLength(v.len(), N));
+    }
+    let mut g = v.to_vec();
+    g.resize(N, F::zero());
+    for r in point {
+        g = g
+            .chunks(2)
+            .map(|pair| pair[0] + *r * (pair[1] - pair[0]))
+            .collect();
+    }
+    Ok(g[0])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
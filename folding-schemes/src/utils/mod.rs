@@ -1,17 +1,21 @@
-use std::path::Path;
-use std::path::PathBuf;
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
 
 use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
 use ark_ec::{AffineRepr, CurveGroup};
 use ark_ff::PrimeField;
-use ark_serialize::CanonicalSerialize;
-use ark_std::Zero;
+use ark_serialize::{CanonicalSerialize, Compress};
+use ark_std::{vec::Vec, Zero};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
 use sha3::{Digest, Sha3_256};
 
 use crate::arith::ArithSerializer;
 use crate::commitment::CommitmentScheme;
+use crate::transcript::poseidon::serialize_poseidon_config;
 use crate::Error;
 
+#[cfg(feature = "std")]
+pub mod alloc;
 pub mod gadgets;
 pub mod hypercube;
 pub mod lagrange_poly;
@@ -21,9 +25,21 @@ pub mod vec;
 // expose espresso local modules
 pub mod espresso;
 pub use crate::utils::espresso::multilinear_polynomial;
+#[cfg(feature = "std")]
 pub use crate::utils::espresso::sum_check;
 pub use crate::utils::espresso::virtual_polynomial;
 
+/// Returns the RNG of the `prove_step_deterministic` methods of the folding schemes, which is
+/// ChaCha20 (`rand_chacha::ChaCha20Rng`) seeded with `seed`, using the step number `i` as its
+/// stream. So all the randomness of a step is fixed by the seed, while the steps of an IVC
+/// proved from the same seed do not reuse it.
+pub fn deterministic_rng<F: PrimeField>(seed: [u8; 32], i: F) -> ChaCha20Rng {
+    let mut rng = ChaCha20Rng::from_seed(seed);
+    // the number of steps fits in the lowest limb
+    rng.set_stream(i.into_bigint().as_ref()[0]);
+    rng
+}
+
 /// For a given x, returns [1, x^1, x^2, ..., x^n-1];
 pub fn powers_of<F: PrimeField>(x: F, n: usize) -> Vec<F> {
     let mut c: Vec<F> = vec![F::zero(); n];
@@ -43,13 +59,25 @@ pub fn get_cm_coordinates<C: CurveGroup>(cm: &C) -> Vec<C::BaseField> {
     vec![*cm_x, *cm_y]
 }
 
-/// returns the hash of the given public parameters of the Folding Scheme
+/// returns the hash of the given public parameters of the Folding Scheme, where
+/// `full_challenge_bit_length` is the bit length of the challenge used to fold the instances of
+/// the AugmentedFCircuit when it is a full-width one, and `None` for the default truncated
+/// challenges, so that the `pp_hash` of the params in the default mode is unchanged.
+/// `cf_challenge_bit_length` is the bit length of the CycleFold challenge (see
+/// [`CycleFoldConfig::CHALLENGE_BIT_LENGTH`](crate::folding::circuits::cyclefold::CycleFoldConfig::CHALLENGE_BIT_LENGTH)),
+/// `state_len` is the length of the state of the folded FCircuit and `step_constants` is its
+/// table of per-step constants (see [`FCircuit::step_constants`](crate::frontend::FCircuit::step_constants)).
+#[allow(clippy::too_many_arguments)]
 pub fn pp_hash<C1, C2, CS1, CS2, const H: bool>(
     arith: &impl ArithSerializer,
     cf_arith: &impl ArithSerializer,
     cs_vp: &CS1::VerifierParams,
     cf_cs_vp: &CS2::VerifierParams,
     poseidon_config: &PoseidonConfig<C1::ScalarField>,
+    full_challenge_bit_length: Option<usize>,
+    cf_challenge_bit_length: usize,
+    state_len: usize,
+    step_constants: &[Vec<C1::ScalarField>],
 ) -> Result<C1::ScalarField, Error>
 where
     C1: CurveGroup,
@@ -66,6 +94,18 @@ where
     hasher.update(arith.params_to_le_bytes());
     // CycleFold Circuit Arith params
     hasher.update(cf_arith.params_to_le_bytes());
+    // folding challenge bit length, for full-width challenges
+    if let Some(challenge_bit_length) = full_challenge_bit_length {
+        hasher.update((challenge_bit_length as u64).to_le_bytes());
+    }
+    // CycleFold challenge bit length
+    hasher.update((cf_challenge_bit_length as u64).to_le_bytes());
+    // FCircuit state length
+    hasher.update((state_len as u64).to_le_bytes());
+    // FCircuit per-step constants
+    let mut step_constants_bytes = Vec::new();
+    step_constants.serialize_uncompressed(&mut step_constants_bytes)?;
+    hasher.update(step_constants_bytes);
     // cs_vp & cf_cs_vp (commitments setup)
     let mut cs_vp_bytes = Vec::new();
     cs_vp.serialize_uncompressed(&mut cs_vp_bytes)?;
@@ -75,27 +115,7 @@ where
     hasher.update(cf_cs_vp_bytes);
     // poseidon params
     let mut poseidon_config_bytes = Vec::new();
-    poseidon_config
-        .full_rounds
-        .serialize_uncompressed(&mut poseidon_config_bytes)?;
-    poseidon_config
-        .partial_rounds
-        .serialize_uncompressed(&mut poseidon_config_bytes)?;
-    poseidon_config
-        .alpha
-        .serialize_uncompressed(&mut poseidon_config_bytes)?;
-    poseidon_config
-        .ark
-        .serialize_uncompressed(&mut poseidon_config_bytes)?;
-    poseidon_config
-        .mds
-        .serialize_uncompressed(&mut poseidon_config_bytes)?;
-    poseidon_config
-        .rate
-        .serialize_uncompressed(&mut poseidon_config_bytes)?;
-    poseidon_config
-        .capacity
-        .serialize_uncompressed(&mut poseidon_config_bytes)?;
+    serialize_poseidon_config(poseidon_config, &mut poseidon_config_bytes, Compress::No)?;
     hasher.update(poseidon_config_bytes);
 
     let public_params_hash = hasher.finalize();
@@ -108,26 +128,37 @@ where
 /// or passing their content already read.
 ///
 /// This enum implements the [`From`] trait for both [`Path`], [`PathBuf`] and [`Vec<u8>`].
+#[cfg(feature = "std")]
 #[derive(Debug, Clone)]
 pub enum PathOrBin {
     Path(PathBuf),
     Bin(Vec<u8>),
 }
 
+#[cfg(feature = "std")]
 impl From<&Path> for PathOrBin {
     fn from(value: &Path) -> Self {
         PathOrBin::Path(value.into())
     }
 }
 
+#[cfg(feature = "std")]
 impl From<PathBuf> for PathOrBin {
     fn from(value: PathBuf) -> Self {
         PathOrBin::Path(value)
     }
 }
 
+#[cfg(feature = "std")]
 impl From<Vec<u8>> for PathOrBin {
     fn from(value: Vec<u8>) -> Self {
         PathOrBin::Bin(value)
     }
 }
+
+#[cfg(feature = "std")]
+impl From<&[u8]> for PathOrBin {
+    fn from(value: &[u8]) -> Self {
+        PathOrBin::Bin(value.to_vec())
+    }
+}
@@ -3,9 +3,12 @@ use ark_poly::{
     univariate::DensePolynomial, EvaluationDomain, Evaluations, GeneralEvaluationDomain,
 };
 pub use ark_relations::r1cs::Matrix as R1CSMatrix;
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use ark_std::cfg_iter;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::collections::BTreeMap;
+use ark_std::io::{Read, Write};
 use ark_std::rand::Rng;
+use ark_std::{cfg_iter, string::ToString, vec::Vec};
+#[cfg(feature = "parallel")]
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
 use crate::Error;
@@ -53,6 +56,112 @@ impl<F: PrimeField> SparseMatrix<F> {
         }
         r
     }
+
+    /// Serializes the matrix in a compact CSR layout, much smaller than the `CanonicalSerialize`
+    /// one, which writes a full field element and a `u64` column index for each entry:
+    /// - the distinct coefficients (mostly small values such as ±1 or powers of two) are written
+    ///   once in a table, and each entry refers to its coefficient by its (varint) index in it,
+    /// - each row is written as its (varint) number of entries, followed by the entries, whose
+    ///   column indexes are delta-encoded with respect to the previous entry of the row (as
+    ///   zigzag varints, so that the rows do not need to be sorted).
+    pub fn serialize_compressed_opt<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        let mut table: Vec<F> = Vec::new();
+        let mut table_index: BTreeMap<F, u64> = BTreeMap::new();
+        for (coeff, _) in self.coeffs.iter().flatten() {
+            table_index.entry(*coeff).or_insert_with(|| {
+                table.push(*coeff);
+                table.len() as u64 - 1
+            });
+        }
+
+        write_varint(&mut writer, self.n_rows as u64)?;
+        write_varint(&mut writer, self.n_cols as u64)?;
+        write_varint(&mut writer, table.len() as u64)?;
+        for coeff in table.iter() {
+            coeff.serialize_compressed(&mut writer)?;
+        }
+        for row in self.coeffs.iter() {
+            write_varint(&mut writer, row.len() as u64)?;
+            let mut prev_col = 0_i64;
+            for (coeff, col) in row.iter() {
+                let delta = *col as i64 - prev_col;
+                write_varint(&mut writer, ((delta << 1) ^ (delta >> 63)) as u64)?;
+                write_varint(&mut writer, table_index[coeff])?;
+                prev_col = *col as i64;
+            }
+        }
+        Ok(())
+    }
+
+    /// Deserializes a matrix serialized with `serialize_compressed_opt`, checking that the
+    /// coefficients are valid field elements and that the column indexes are within the matrix.
+    pub fn deserialize_compressed_opt<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let n_rows = read_varint_usize(&mut reader)?;
+        let n_cols = read_varint_usize(&mut reader)?;
+        let table_len = read_varint_usize(&mut reader)?;
+        // the lengths are not trusted, so the vectors are not preallocated
+        let table = (0..table_len)
+            .map(|_| F::deserialize_compressed(&mut reader))
+            .collect::<Result<Vec<F>, _>>()?;
+
+        let mut coeffs = Vec::new();
+        for _ in 0..n_rows {
+            let row_len = read_varint_usize(&mut reader)?;
+            let mut row = Vec::new();
+            let mut prev_col = 0_i64;
+            for _ in 0..row_len {
+                let zigzag = read_varint(&mut reader)?;
+                let delta = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+                let col = prev_col
+                    .checked_add(delta)
+                    .filter(|col| *col >= 0 && (*col as u64) < n_cols as u64)
+                    .ok_or(Error::OutOfBounds)?;
+                let coeff = table
+                    .get(read_varint_usize(&mut reader)?)
+                    .ok_or(Error::OutOfBounds)?;
+                row.push((*coeff, col as usize));
+                prev_col = col;
+            }
+            coeffs.push(row);
+        }
+        Ok(Self {
+            n_rows,
+            n_cols,
+            coeffs,
+        })
+    }
+}
+
+/// Writes `v` as an unsigned LEB128 varint.
+pub(crate) fn write_varint<W: Write>(mut writer: W, mut v: u64) -> Result<(), Error> {
+    while v >= 0x80 {
+        writer.write_all(&[(v as u8) | 0x80])?;
+        v >>= 7;
+    }
+    writer.write_all(&[v as u8])?;
+    Ok(())
+}
+
+/// Reads an unsigned LEB128 varint, rejecting the ones that overflow a `u64`.
+pub(crate) fn read_varint<R: Read>(mut reader: R) -> Result<u64, Error> {
+    let mut v = 0_u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0_u8];
+        reader.read_exact(&mut byte)?;
+        let bits = (byte[0] & 0x7f) as u64;
+        if shift == 63 && bits > 1 {
+            break;
+        }
+        v |= bits << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(v);
+        }
+    }
+    Err(SerializationError::InvalidData.into())
+}
+
+pub(crate) fn read_varint_usize<R: Read>(reader: R) -> Result<usize, Error> {
+    usize::try_from(read_varint(reader)?).map_err(|_| Error::OutOfBounds)
 }
 
 pub fn dense_matrix_to_sparse<F: PrimeField>(m: Vec<Vec<F>>) -> SparseMatrix<F> {
@@ -105,6 +214,67 @@ pub fn is_zero_vec<F: PrimeField>(vec: &[F]) -> bool {
     cfg_iter!(vec).all(|a| a.is_zero())
 }
 
+/// Returns the smallest row `i < n_rows` for which `is_failing(i)` holds. With the `parallel`
+/// feature the rows are checked in parallel, and the search stops as soon as the smallest failing
+/// row is known, so the returned row does not depend on the thread scheduling.
+pub fn first_failing_row(
+    n_rows: usize,
+    is_failing: impl Fn(usize) -> bool + Sync + Send,
+) -> Option<usize> {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::iter::IntoParallelIterator;
+        (0..n_rows).into_par_iter().find_first(|i| is_failing(*i))
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        (0..n_rows).find(|i| is_failing(*i))
+    }
+}
+
+/// Checks that the evaluation `e` of a relation is zero at every row, returning the first row at
+/// which it is not.
+pub fn check_zero_rows<F: PrimeField>(e: &[F]) -> Result<(), Error> {
+    match first_failing_row(e.len(), |i| !e[i].is_zero()) {
+        None => Ok(()),
+        Some(row) => Err(Error::NotSatisfiedAt {
+            row,
+            lhs: e[row].to_string(),
+            rhs: F::zero().to_string(),
+        }),
+    }
+}
+
+/// Checks that a vector of length `got` has the `expected` length, so that the verifiers can
+/// reject malformed inputs instead of indexing out of bounds.
+pub fn check_len(got: usize, expected: usize) -> Result<(), Error> {
+    if got != expected {
+        return Err(Error::NotExpectedLength(got, expected));
+    }
+    Ok(())
+}
+
+/// Checks that the evaluation `e` of a relation equals the `expected` vector (e.g. the error term
+/// of a relaxed instance), returning the first row at which they differ.
+pub fn check_equal_rows<F: PrimeField>(e: &[F], expected: &[F]) -> Result<(), Error> {
+    if e.len() != expected.len() {
+        return Err(Error::NotSameLength(
+            "e.len()".to_string(),
+            e.len(),
+            "expected.len()".to_string(),
+            expected.len(),
+        ));
+    }
+    match first_failing_row(e.len(), |i| e[i] != expected[i]) {
+        None => Ok(()),
+        Some(row) => Err(Error::NotSatisfiedAt {
+            row,
+            lhs: e[row].to_string(),
+            rhs: expected[row].to_string(),
+        }),
+    }
+}
+
 pub fn mat_vec_mul_dense<F: PrimeField>(M: &[Vec<F>], z: &[F]) -> Result<Vec<F>, Error> {
     if M.is_empty() {
         return Err(Error::Empty);
@@ -173,6 +343,7 @@ pub fn poly_from_vec<F: PrimeField>(v: Vec<F>) -> Result<DensePolynomial<F>, Err
 pub mod tests {
     use super::*;
     use ark_pallas::Fr;
+    use ark_std::{UniformRand, Zero};
 
     pub fn to_F_matrix<F: PrimeField>(M: Vec<Vec<usize>>) -> SparseMatrix<F> {
         dense_matrix_to_sparse(to_F_dense_matrix(M))
@@ -198,6 +369,95 @@ pub mod tests {
         assert_eq!(A_sparse.to_dense(), A);
     }
 
+    #[test]
+    fn test_sparse_matrix_compressed_opt_roundtrip() {
+        let mut rng = ark_std::test_rng();
+        let mut M = SparseMatrix::<Fr>::rand(&mut rng, 20, 30);
+        // rows with unsorted columns and empty rows are kept as they are
+        M.coeffs[0] = vec![
+            (Fr::from(3_u32), 7),
+            (-Fr::from(1_u32), 2),
+            (Fr::from(3_u32), 29),
+        ];
+        M.coeffs[1] = vec![];
+
+        let mut bytes = Vec::new();
+        M.serialize_compressed_opt(&mut bytes).unwrap();
+        let M_deserialized = SparseMatrix::<Fr>::deserialize_compressed_opt(&bytes[..]).unwrap();
+        assert_eq!(M_deserialized, M);
+
+        // truncated data
+        assert!(SparseMatrix::<Fr>::deserialize_compressed_opt(&bytes[..bytes.len() - 1]).is_err());
+
+        // column index out of bounds: a 1x1 matrix with the entry (1, 1)
+        let mut bytes = Vec::new();
+        for v in [1, 1, 1] {
+            write_varint(&mut bytes, v).unwrap();
+        }
+        Fr::from(1_u32).serialize_compressed(&mut bytes).unwrap();
+        let entry_start = bytes.len();
+        for v in [1, 2, 0] {
+            write_varint(&mut bytes, v).unwrap();
+        }
+        assert!(matches!(
+            SparseMatrix::<Fr>::deserialize_compressed_opt(&bytes[..]),
+            Err(Error::OutOfBounds)
+        ));
+        // coefficient index out of bounds
+        bytes.truncate(entry_start);
+        for v in [1, 0, 1] {
+            write_varint(&mut bytes, v).unwrap();
+        }
+        assert!(matches!(
+            SparseMatrix::<Fr>::deserialize_compressed_opt(&bytes[..]),
+            Err(Error::OutOfBounds)
+        ));
+    }
+
+    #[test]
+    fn test_failing_row_is_deterministic() {
+        let mut rng = ark_std::test_rng();
+        let n = 1 << 16;
+        for _ in 0..10 {
+            // several failing rows, so that different threads find different ones
+            let mut rows: Vec<usize> = (0..64).map(|_| rng.gen_range(0..n)).collect();
+            let mut e = vec![Fr::zero(); n];
+            for row in rows.iter() {
+                e[*row] = Fr::rand(&mut rng) + Fr::from(1_u32);
+            }
+            rows.sort();
+            let min_row = rows[0];
+
+            for _ in 0..5 {
+                match check_zero_rows(&e) {
+                    Err(Error::NotSatisfiedAt { row, .. }) => assert_eq!(row, min_row),
+                    r => panic!("unexpected result {:?}", r),
+                }
+                match check_equal_rows(&vec![Fr::zero(); n], &e) {
+                    Err(Error::NotSatisfiedAt { row, .. }) => assert_eq!(row, min_row),
+                    r => panic!("unexpected result {:?}", r),
+                }
+            }
+            assert_eq!(first_failing_row(n, |i| rows.contains(&i)), Some(min_row));
+        }
+        assert!(check_zero_rows(&vec![Fr::zero(); n]).is_ok());
+        assert_eq!(first_failing_row(n, |_| false), None);
+    }
+
+    #[test]
+    fn test_varint() {
+        for v in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut bytes = Vec::new();
+            write_varint(&mut bytes, v).unwrap();
+            assert_eq!(read_varint(&bytes[..]).unwrap(), v);
+        }
+        // overflowing a u64
+        assert!(read_varint(&[0xff_u8; 10][..]).is_err());
+        assert!(
+            read_varint(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02][..]).is_err()
+        );
+    }
+
     // test mat_vec_mul & mat_vec_mul_sparse
     #[test]
     fn test_mat_vec_mul() {
@@ -0,0 +1,70 @@
+//! Verifies a Nova IVC proof in the browser through the `wasm` bindings, to check that the
+//! verification path works on wasm32-unknown-unknown. The proof is generated by the Rust prover in
+//! the same test and only reaches the verifier as bytes, as it would from a server. Run with:
+//!
+//! wasm-pack test --headless --chrome folding-schemes --no-default-features --features wasm,test-utils -- --test wasm
+#![cfg(all(
+    target_arch = "wasm32",
+    target_os = "unknown",
+    feature = "wasm",
+    feature = "test-utils"
+))]
+
+use ark_bn254::{constraints::GVar, Fr, G1Projective as Projective};
+use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+use ark_serialize::CanonicalSerialize;
+use folding_schemes::{
+    commitment::pedersen::Pedersen,
+    folding::nova::{wasm::verify_ivc, Nova, PreprocessorParam},
+    frontend::{utils::CubicFCircuit, FCircuit},
+    transcript::poseidon::poseidon_canonical_config,
+    FoldingScheme,
+};
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn test_verify_ivc_in_browser() {
+    type N = Nova<
+        Projective,
+        GVar,
+        Projective2,
+        GVar2,
+        CubicFCircuit<Fr>,
+        Pedersen<Projective>,
+        Pedersen<Projective2>,
+        false,
+    >;
+    let mut rng = ark_std::test_rng();
+    let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+    let prep_param = PreprocessorParam::new(poseidon_canonical_config::<Fr>(), f_circuit);
+    let params = N::preprocess(&mut rng, &prep_param).unwrap();
+    let mut nova = N::init(&params, f_circuit, vec![Fr::from(3_u32)]).unwrap();
+    for _ in 0..2 {
+        nova.prove_step(&mut rng, (), None).unwrap();
+    }
+
+    let mut vp_bytes = vec![];
+    params.1.serialize_standalone(&mut vp_bytes).unwrap();
+    let mut proof_bytes = vec![];
+    nova.ivc_proof()
+        .serialize_compressed(&mut proof_bytes)
+        .unwrap();
+
+    let state = verify_ivc(&vp_bytes, &proof_bytes).unwrap();
+    // 3 -> 3^3 + 3 + 5 = 35 -> 35^3 + 35 + 5 = 42915
+    assert_eq!(state.steps, "2");
+    assert_eq!(state.z_0, vec!["3"]);
+    assert_eq!(state.z_i, vec!["42915"]);
+
+    // a tampered proof and truncated params are rejected with an error instead of a panic
+    let mut tampered_proof = nova.ivc_proof();
+    tampered_proof.z_i[0] += Fr::from(1_u32);
+    let mut tampered_bytes = vec![];
+    tampered_proof
+        .serialize_compressed(&mut tampered_bytes)
+        .unwrap();
+    assert!(verify_ivc(&vp_bytes, &tampered_bytes).is_err());
+    assert!(verify_ivc(&vp_bytes[..vp_bytes.len() - 1], &proof_bytes).is_err());
+}
@@ -6,13 +6,21 @@ use ark_r1cs_std::fields::fp::FpVar::Var;
 use ark_r1cs_std::R1CSVar;
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
 use ark_std::fmt::Debug;
-use folding_schemes::{frontend::FCircuit, utils::PathOrBin, Error};
+use folding_schemes::{
+    frontend::{FCircuit, VecF, VecFpVar},
+    utils::PathOrBin,
+    Error,
+};
 use num_bigint::BigInt;
 use std::fmt;
 use std::rc::Rc;
 
+pub mod signals;
 pub mod utils;
+pub use signals::CircomSignalMap;
+use signals::CircomSignals;
 use utils::CircomWrapper;
+pub use utils::WitnessBackend;
 
 type ClosurePointer<F> = Rc<dyn Fn(usize, Vec<F>, Vec<F>) -> Result<Vec<F>, Error>>;
 
@@ -31,17 +39,56 @@ impl<F: PrimeField> fmt::Debug for CustomStepNative<F> {
     }
 }
 
-/// Define CircomFCircuit
+/// Parameters of a CircomFCircuit: the `.r1cs` of the circuit and the backend used to compute its
+/// witness, which can be either the wasm witness calculator or the native witness generator
+/// compiled from the C++ code generated by circom (much faster for large circuits).
+///
+/// By default, the state is read from the `ivc_input` signal, the next state is written to the
+/// `ivc_output` signal and the external inputs are read from the `external_inputs` signal. Other
+/// names can be used by setting a `signal_map`.
 #[derive(Clone, Debug)]
-pub struct CircomFCircuit<F: PrimeField> {
+pub struct CircomFCircuitParams {
+    pub r1cs: PathOrBin,
+    pub witness_backend: WitnessBackend,
+    pub signal_map: Option<CircomSignalMap>,
+}
+
+/// Define CircomFCircuit, where `L` is the number of external inputs.
+///
+/// When using the wasm backend, the wasm witness calculator is instantiated at the first witness
+/// calculation and reused by the next steps, and it is shared by the clones of the
+/// CircomFCircuit. Since it is kept behind a `RefCell`, the CircomFCircuit is not thread-safe and
+/// must be used from a single thread.
+#[derive(Clone, Debug)]
+pub struct CircomFCircuit<F: PrimeField, const L: usize> {
     circom_wrapper: CircomWrapper<F>,
     pub state_len: usize,
-    pub external_inputs_len: usize,
+    signals: CircomSignals,
     r1cs: CircomR1CS<F>,
     custom_step_native_code: Option<CustomStepNative<F>>,
 }
 
-impl<F: PrimeField> CircomFCircuit<F> {
+impl<F: PrimeField, const L: usize> CircomFCircuit<F, L> {
+    /// Creates a CircomFCircuit from the in-memory `.r1cs` and `.wasm` artifacts (for example
+    /// embedded with `include_bytes!`), without accessing the filesystem.
+    pub fn from_bytes(r1cs: &[u8], wasm: &[u8]) -> Result<Self, Error> {
+        Self::new(CircomFCircuitParams {
+            r1cs: r1cs.into(),
+            witness_backend: WitnessBackend::Wasm(wasm.into()),
+            signal_map: None,
+        })
+    }
+
+    /// Creates a CircomFCircuit from the `.r1cs` and `.wasm` artifacts at the given paths. Not
+    /// available on wasm32-unknown-unknown, where `from_bytes` must be used instead.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn from_paths(
+        r1cs_path: impl AsRef<std::path::Path>,
+        wasm_path: impl AsRef<std::path::Path>,
+    ) -> Result<Self, Error> {
+        Self::from_bytes(&std::fs::read(r1cs_path)?, &std::fs::read(wasm_path)?)
+    }
+
     pub fn set_custom_step_native(&mut self, func: ClosurePointer<F>) {
         self.custom_step_native_code = Some(CustomStepNative::<F> { func });
     }
@@ -58,21 +105,15 @@ impl<F: PrimeField> CircomFCircuit<F> {
             #[cfg(test)]
             assert_eq!(z_i.len(), self.state_len());
             #[cfg(test)]
-            assert_eq!(external_inputs.len(), self.external_inputs_len());
-
-            let inputs_bi = z_i
-                .iter()
-                .map(|val| self.circom_wrapper.ark_primefield_to_num_bigint(*val))
-                .collect::<Vec<BigInt>>();
-            let mut inputs_map = vec![("ivc_input".to_string(), inputs_bi)];
-
-            if self.external_inputs_len() > 0 {
-                let external_inputs_bi = external_inputs
-                    .iter()
-                    .map(|val| self.circom_wrapper.ark_primefield_to_num_bigint(*val))
-                    .collect::<Vec<BigInt>>();
-                inputs_map.push(("external_inputs".to_string(), external_inputs_bi));
-            }
+            assert_eq!(external_inputs.len(), L);
+
+            let to_bigints = |values: Vec<F>| {
+                values
+                    .into_iter()
+                    .map(|val| self.circom_wrapper.ark_primefield_to_num_bigint(val))
+                    .collect::<Vec<BigInt>>()
+            };
+            let inputs_map = self.inputs_map(to_bigints(z_i), to_bigints(external_inputs));
 
             // Computes witness
             let witness = self
@@ -83,25 +124,39 @@ impl<F: PrimeField> CircomFCircuit<F> {
                 })?;
 
             // Extracts the z_i1(next state) from the witness vector.
-            let z_i1 = witness[1..1 + self.state_len()].to_vec();
-            Ok(z_i1)
+            Ok(self.next_state(&witness))
         }
     }
 }
 
-impl<F: PrimeField> FCircuit<F> for CircomFCircuit<F> {
-    /// (r1cs_path, wasm_path, state_len, external_inputs_len)
-    type Params = (PathOrBin, PathOrBin, usize, usize);
+impl<F: PrimeField, const L: usize> FCircuit<F> for CircomFCircuit<F, L> {
+    type Params = CircomFCircuitParams;
+    type ExternalInputs = VecF<F, L>;
+    type ExternalInputsVar = VecFpVar<F, L>;
 
     fn new(params: Self::Params) -> Result<Self, Error> {
-        let (r1cs_path, wasm_path, state_len, external_inputs_len) = params;
-        let circom_wrapper = CircomWrapper::new(r1cs_path, wasm_path)?;
+        let circom_wrapper = CircomWrapper::new(params.r1cs, params.witness_backend)?;
 
         let r1cs = circom_wrapper.extract_r1cs()?;
+        // the public signals of the circuit (besides the constant `1`) are the `ivc_output`
+        // followed by the `ivc_input`, which both have the length of the state
+        let n_public = r1cs.num_inputs - 1;
+        if n_public == 0 || n_public % 2 != 0 {
+            return Err(Error::Other(format!(
+                "circom circuit should have the same number of public ivc inputs and outputs, \
+                 but it has {} public signals",
+                n_public
+            )));
+        }
+        let state_len = n_public / 2;
+        let signals = match params.signal_map {
+            Some(signal_map) => CircomSignals::resolve(signal_map, state_len, n_public, L)?,
+            None => CircomSignals::default_names(state_len, L),
+        };
         Ok(Self {
             circom_wrapper,
             state_len,
-            external_inputs_len,
+            signals,
             r1cs,
             custom_step_native_code: None,
         })
@@ -110,17 +165,14 @@ impl<F: PrimeField> FCircuit<F> for CircomFCircuit<F> {
     fn state_len(&self) -> usize {
         self.state_len
     }
-    fn external_inputs_len(&self) -> usize {
-        self.external_inputs_len
-    }
 
     fn step_native(
         &self,
         _i: usize,
         z_i: Vec<F>,
-        external_inputs: Vec<F>,
+        external_inputs: Self::ExternalInputs,
     ) -> Result<Vec<F>, Error> {
-        self.execute_custom_step_native(_i, z_i, external_inputs)
+        self.execute_custom_step_native(_i, z_i, external_inputs.0)
     }
 
     fn generate_step_constraints(
@@ -128,20 +180,17 @@ impl<F: PrimeField> FCircuit<F> for CircomFCircuit<F> {
         cs: ConstraintSystemRef<F>,
         _i: usize,
         z_i: Vec<FpVar<F>>,
-        external_inputs: Vec<FpVar<F>>,
+        external_inputs: Self::ExternalInputsVar,
     ) -> Result<Vec<FpVar<F>>, SynthesisError> {
         #[cfg(test)]
         assert_eq!(z_i.len(), self.state_len());
         #[cfg(test)]
-        assert_eq!(external_inputs.len(), self.external_inputs_len());
+        assert_eq!(external_inputs.0.len(), L);
 
-        let input_values = self.fpvars_to_bigints(&z_i)?;
-        let mut inputs_map = vec![("ivc_input".to_string(), input_values)];
-
-        if self.external_inputs_len() > 0 {
-            let external_inputs_bi = self.fpvars_to_bigints(&external_inputs)?;
-            inputs_map.push(("external_inputs".to_string(), external_inputs_bi));
-        }
+        let inputs_map = self.inputs_map(
+            self.fpvars_to_bigints(&z_i)?,
+            self.fpvars_to_bigints(&external_inputs.0)?,
+        );
 
         let witness = self
             .circom_wrapper
@@ -176,15 +225,35 @@ impl<F: PrimeField> FCircuit<F> for CircomFCircuit<F> {
         // }
 
         // Extracts the z_i1(next state) from the witness vector.
-        let z_i1: Vec<FpVar<F>> = Vec::<FpVar<F>>::new_witness(cs.clone(), || {
-            Ok(witness[1..1 + self.state_len()].to_vec())
-        })?;
+        let z_i1: Vec<FpVar<F>> =
+            Vec::<FpVar<F>>::new_witness(cs.clone(), || Ok(self.next_state(&witness)))?;
 
         Ok(z_i1)
     }
 }
 
-impl<F: PrimeField> CircomFCircuit<F> {
+impl<F: PrimeField, const L: usize> CircomFCircuit<F, L> {
+    // Builds the inputs of the witness calculation, assigning the state and the external inputs
+    // to their signals.
+    fn inputs_map(
+        &self,
+        z_i: Vec<BigInt>,
+        external_inputs: Vec<BigInt>,
+    ) -> Vec<(String, Vec<BigInt>)> {
+        let mut inputs_map = vec![(self.signals.state_input.clone(), z_i)];
+        inputs_map.extend(self.signals.external_inputs_map(&external_inputs));
+        inputs_map
+    }
+
+    // Reads the next state from the state output signal in the witness.
+    fn next_state(&self, witness: &[F]) -> Vec<F> {
+        self.signals
+            .state_output_indexes
+            .iter()
+            .map(|&i| witness[i])
+            .collect()
+    }
+
     fn fpvars_to_bigints(&self, fpvars: &[FpVar<F>]) -> Result<Vec<BigInt>, SynthesisError> {
         let mut input_values = Vec::new();
         // converts each FpVar to PrimeField value, then to num_bigint::BigInt.
@@ -204,10 +273,25 @@ impl<F: PrimeField> CircomFCircuit<F> {
 #[cfg(test)]
 pub mod tests {
     use super::*;
-    use ark_bn254::Fr;
+    use ark_bn254::{constraints::GVar, Fr, G1Projective as Projective};
+    use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
     use ark_relations::r1cs::ConstraintSystem;
+    use folding_schemes::{
+        commitment::pedersen::Pedersen,
+        folding::nova::{Nova, PreprocessorParam},
+        transcript::poseidon::poseidon_canonical_config,
+        FoldingScheme,
+    };
     use std::path::PathBuf;
 
+    fn wasm_params(r1cs_path: PathBuf, wasm_path: PathBuf) -> CircomFCircuitParams {
+        CircomFCircuitParams {
+            r1cs: r1cs_path.into(),
+            witness_backend: WitnessBackend::Wasm(wasm_path.into()),
+            signal_map: None,
+        }
+    }
+
     // Tests the step_native function of CircomFCircuit.
     #[test]
     fn test_circom_step_native() {
@@ -216,10 +300,10 @@ pub mod tests {
             PathBuf::from("./src/circom/test_folder/cubic_circuit_js/cubic_circuit.wasm");
 
         let circom_fcircuit =
-            CircomFCircuit::<Fr>::new((r1cs_path.into(), wasm_path.into(), 1, 0)).unwrap(); // state_len:1, external_inputs_len:0
+            CircomFCircuit::<Fr, 0>::new(wasm_params(r1cs_path, wasm_path)).unwrap();
 
         let z_i = vec![Fr::from(3u32)];
-        let z_i1 = circom_fcircuit.step_native(1, z_i, vec![]).unwrap();
+        let z_i1 = circom_fcircuit.step_native(1, z_i, VecF(vec![])).unwrap();
         assert_eq!(z_i1, vec![Fr::from(35u32)]);
     }
 
@@ -231,7 +315,7 @@ pub mod tests {
             PathBuf::from("./src/circom/test_folder/cubic_circuit_js/cubic_circuit.wasm");
 
         let circom_fcircuit =
-            CircomFCircuit::<Fr>::new((r1cs_path.into(), wasm_path.into(), 1, 0)).unwrap(); // state_len:1, external_inputs_len:0
+            CircomFCircuit::<Fr, 0>::new(wasm_params(r1cs_path, wasm_path)).unwrap();
 
         let cs = ConstraintSystem::<Fr>::new_ref();
 
@@ -239,7 +323,7 @@ pub mod tests {
 
         let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
         let z_i1_var = circom_fcircuit
-            .generate_step_constraints(cs.clone(), 1, z_i_var, vec![])
+            .generate_step_constraints(cs.clone(), 1, z_i_var, VecFpVar(vec![]))
             .unwrap();
         assert_eq!(z_i1_var.value().unwrap(), vec![Fr::from(35u32)]);
     }
@@ -252,14 +336,18 @@ pub mod tests {
             PathBuf::from("./src/circom/test_folder/cubic_circuit_js/cubic_circuit.wasm");
 
         let circom_fcircuit =
-            CircomFCircuit::<Fr>::new((r1cs_path.into(), wasm_path.into(), 1, 0)).unwrap(); // state_len:1, external_inputs_len:0
+            CircomFCircuit::<Fr, 0>::new(wasm_params(r1cs_path, wasm_path)).unwrap();
 
         // Allocates z_i1 by using step_native function.
         let z_i = vec![Fr::from(3_u32)];
         let wrapper_circuit = folding_schemes::frontend::utils::WrapperCircuit {
             FC: circom_fcircuit.clone(),
             z_i: Some(z_i.clone()),
-            z_i1: Some(circom_fcircuit.step_native(0, z_i.clone(), vec![]).unwrap()),
+            z_i1: Some(
+                circom_fcircuit
+                    .step_native(0, z_i.clone(), VecF(vec![]))
+                    .unwrap(),
+            ),
         };
 
         let cs = ConstraintSystem::<Fr>::new_ref();
@@ -278,10 +366,10 @@ pub mod tests {
             "./src/circom/test_folder/with_external_inputs_js/with_external_inputs.wasm",
         );
         let circom_fcircuit =
-            CircomFCircuit::<Fr>::new((r1cs_path.into(), wasm_path.into(), 1, 2)).unwrap(); // state_len:1, external_inputs_len:2
+            CircomFCircuit::<Fr, 2>::new(wasm_params(r1cs_path, wasm_path)).unwrap();
         let cs = ConstraintSystem::<Fr>::new_ref();
         let z_i = vec![Fr::from(3u32)];
-        let external_inputs = vec![Fr::from(6u32), Fr::from(7u32)];
+        let external_inputs = VecF(vec![Fr::from(6u32), Fr::from(7u32)]);
 
         // run native step
         let z_i1_native = circom_fcircuit
@@ -291,7 +379,7 @@ pub mod tests {
         // run gadget step
         let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
         let external_inputs_var =
-            Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs.clone())).unwrap();
+            VecFpVar::new_witness(cs.clone(), || Ok(external_inputs.clone())).unwrap();
         let z_i1_var = circom_fcircuit
             .generate_step_constraints(cs.clone(), 1, z_i_var, external_inputs_var)
             .unwrap();
@@ -303,7 +391,7 @@ pub mod tests {
         let wrong_z_i = vec![Fr::from(0)];
         let wrong_z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(wrong_z_i)).unwrap();
         let external_inputs_var =
-            Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+            VecFpVar::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
         let _z_i1_var = circom_fcircuit.generate_step_constraints(
             cs.clone(),
             1,
@@ -321,17 +409,19 @@ pub mod tests {
         let wasm_path =
             PathBuf::from("./src/circom/test_folder/no_external_inputs_js/no_external_inputs.wasm");
         let circom_fcircuit =
-            CircomFCircuit::<Fr>::new((r1cs_path.into(), wasm_path.into(), 3, 0)).unwrap();
+            CircomFCircuit::<Fr, 0>::new(wasm_params(r1cs_path, wasm_path)).unwrap();
         let cs = ConstraintSystem::<Fr>::new_ref();
         let z_i = vec![Fr::from(3u32), Fr::from(4u32), Fr::from(5u32)];
         let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.clone())).unwrap();
 
         // run native step
-        let z_i1_native = circom_fcircuit.step_native(1, z_i.clone(), vec![]).unwrap();
+        let z_i1_native = circom_fcircuit
+            .step_native(1, z_i.clone(), VecF(vec![]))
+            .unwrap();
 
         // run gadget step
         let z_i1_var = circom_fcircuit
-            .generate_step_constraints(cs.clone(), 1, z_i_var, vec![])
+            .generate_step_constraints(cs.clone(), 1, z_i_var, VecFpVar(vec![]))
             .unwrap();
 
         assert_eq!(z_i1_var.value().unwrap(), z_i1_native);
@@ -340,8 +430,12 @@ pub mod tests {
         let cs = ConstraintSystem::<Fr>::new_ref();
         let wrong_z_i = vec![Fr::from(0u32), Fr::from(4u32), Fr::from(5u32)];
         let wrong_z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(wrong_z_i)).unwrap();
-        let _z_i1_var =
-            circom_fcircuit.generate_step_constraints(cs.clone(), 1, wrong_z_i_var, vec![]);
+        let _z_i1_var = circom_fcircuit.generate_step_constraints(
+            cs.clone(),
+            1,
+            wrong_z_i_var,
+            VecFpVar(vec![]),
+        );
         // TODO:: https://github.com/privacy-scaling-explorations/sonobe/issues/104
         // Disable check for now
         // assert!(z_i1_var.is_err())
@@ -354,7 +448,7 @@ pub mod tests {
             PathBuf::from("./src/circom/test_folder/cubic_circuit_js/cubic_circuit.wasm");
 
         let mut circom_fcircuit =
-            CircomFCircuit::<Fr>::new((r1cs_path.into(), wasm_path.into(), 1, 0)).unwrap(); // state_len:1, external_inputs_len:0
+            CircomFCircuit::<Fr, 0>::new(wasm_params(r1cs_path, wasm_path)).unwrap();
 
         circom_fcircuit.set_custom_step_native(Rc::new(|_i, z_i, _external| {
             let z = z_i[0];
@@ -366,7 +460,11 @@ pub mod tests {
         let wrapper_circuit = folding_schemes::frontend::utils::WrapperCircuit {
             FC: circom_fcircuit.clone(),
             z_i: Some(z_i.clone()),
-            z_i1: Some(circom_fcircuit.step_native(0, z_i.clone(), vec![]).unwrap()),
+            z_i1: Some(
+                circom_fcircuit
+                    .step_native(0, z_i.clone(), VecF(vec![]))
+                    .unwrap(),
+            ),
         };
 
         let cs = ConstraintSystem::<Fr>::new_ref();
@@ -377,4 +475,137 @@ pub mod tests {
             "Constraint system is not satisfied"
         );
     }
+
+    fn multiple_inputs_params(
+        state_input: &str,
+        external_inputs: Vec<(&str, usize)>,
+    ) -> CircomFCircuitParams {
+        let r1cs_path = PathBuf::from("./src/circom/test_folder/multiple_inputs.r1cs");
+        let wasm_path =
+            PathBuf::from("./src/circom/test_folder/multiple_inputs_js/multiple_inputs.wasm");
+        let sym_path = PathBuf::from("./src/circom/test_folder/multiple_inputs.sym");
+        CircomFCircuitParams {
+            signal_map: Some(CircomSignalMap {
+                sym: sym_path.into(),
+                state_input: state_input.to_string(),
+                state_output: "next_state".to_string(),
+                external_inputs: external_inputs
+                    .into_iter()
+                    .map(|(name, size)| (name.to_string(), size))
+                    .collect(),
+            }),
+            ..wasm_params(r1cs_path, wasm_path)
+        }
+    }
+
+    // Tests a circuit whose signals are declared in a different order than the external inputs,
+    // mapping them by name.
+    #[test]
+    fn test_circom_signal_map() {
+        let circom_fcircuit =
+            CircomFCircuit::<Fr, 3>::new(multiple_inputs_params("state", vec![("a", 1), ("b", 2)]))
+                .unwrap();
+        assert_eq!(circom_fcircuit.state_len(), 2);
+
+        let z_i = vec![Fr::from(3u32), Fr::from(4u32)];
+        // a = 5, b = [6, 7]
+        let external_inputs = VecF(vec![Fr::from(5u32), Fr::from(6u32), Fr::from(7u32)]);
+
+        let z_i1_native = circom_fcircuit
+            .step_native(1, z_i.clone(), external_inputs.clone())
+            .unwrap();
+        assert_eq!(
+            z_i1_native,
+            vec![Fr::from(3u32 * 5 + 6), Fr::from(4u32 * 7 + 5)]
+        );
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let external_inputs_var =
+            VecFpVar::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+        let z_i1_var = circom_fcircuit
+            .generate_step_constraints(cs.clone(), 1, z_i_var, external_inputs_var)
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1_native);
+    }
+
+    #[test]
+    fn test_circom_signal_map_errors() {
+        let err_msg = |params| match CircomFCircuit::<Fr, 3>::new(params) {
+            Err(Error::Other(msg)) => msg,
+            other => panic!("expected an error, got {:?}", other.map(|_| ())),
+        };
+
+        // misnamed signals list the available ones
+        let msg = err_msg(multiple_inputs_params(
+            "ivc_input",
+            vec![("a", 1), ("b", 2)],
+        ));
+        assert!(msg.contains("`ivc_input` not found"));
+        assert!(msg.contains("a[1], b[2], next_state[2], state[2]"));
+        let msg = err_msg(multiple_inputs_params("state", vec![("a", 1), ("c", 2)]));
+        assert!(msg.contains("`c` not found"));
+
+        // wrong sizes
+        let msg = err_msg(multiple_inputs_params("state", vec![("a", 2), ("b", 1)]));
+        assert!(msg.contains("`a` has size 1, but 2 was expected"));
+
+        // private signals can not be used as the state
+        let msg = err_msg(multiple_inputs_params("b", vec![("a", 1), ("b", 2)]));
+        assert!(msg.contains("`b` is not public"));
+
+        // the external input signals must cover all the external inputs
+        assert!(
+            CircomFCircuit::<Fr, 3>::new(multiple_inputs_params("state", vec![("b", 2)])).is_err()
+        );
+    }
+
+    // Tests building the CircomFCircuit from artifacts embedded in the binary, so that no file is
+    // read at runtime, and folding it.
+    #[test]
+    fn test_circom_from_bytes_fold() {
+        let r1cs = include_bytes!("./test_folder/cubic_circuit.r1cs");
+        let wasm = include_bytes!("./test_folder/cubic_circuit_js/cubic_circuit.wasm");
+        let f_circuit = CircomFCircuit::<Fr, 0>::from_bytes(r1cs, wasm).unwrap();
+
+        // the path-based constructor builds the same circuit
+        let f_circuit_from_paths = CircomFCircuit::<Fr, 0>::from_paths(
+            "./src/circom/test_folder/cubic_circuit.r1cs",
+            "./src/circom/test_folder/cubic_circuit_js/cubic_circuit.wasm",
+        )
+        .unwrap();
+        assert_eq!(
+            f_circuit_from_paths
+                .step_native(0, vec![Fr::from(3u32)], VecF(vec![]))
+                .unwrap(),
+            f_circuit
+                .step_native(0, vec![Fr::from(3u32)], VecF(vec![]))
+                .unwrap()
+        );
+
+        type N = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CircomFCircuit<Fr, 0>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let nova_params = N::preprocess(
+            &mut rng,
+            &PreprocessorParam::new(poseidon_config, f_circuit.clone()),
+        )
+        .unwrap();
+        let mut nova = N::init(&nova_params, f_circuit, vec![Fr::from(3u32)]).unwrap();
+        for _ in 0..2 {
+            nova.prove_step(&mut rng, VecF(vec![]), None).unwrap();
+        }
+        // 3 -> 3^3 + 3 + 5 = 35 -> 35^3 + 35 + 5 = 42915
+        assert_eq!(nova.state().z_i, vec![Fr::from(42915u32)]);
+        N::verify(nova_params.1, nova.ivc_proof()).unwrap();
+    }
 }
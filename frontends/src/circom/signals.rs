@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+
+use folding_schemes::{utils::PathOrBin, Error};
+
+use crate::utils::read_path_or_bin;
+
+/// Names of the signals of the circom circuit that are used as the IVC state and as the external
+/// inputs, so that the CircomFCircuit does not depend on the order in which they are declared.
+///
+/// The state input and output signals must be public (circom makes the outputs public), and they
+/// must be the only public signals of the circuit.
+#[derive(Clone, Debug)]
+pub struct CircomSignalMap {
+    /// `.sym` file generated by circom with `--sym`, from which the signals are read
+    pub sym: PathOrBin,
+    /// name of the input signal that receives the state z_i
+    pub state_input: String,
+    /// name of the output signal that contains the next state z_{i+1}
+    pub state_output: String,
+    /// names and sizes of the input signals that receive the external inputs, in the order in
+    /// which their values appear in the external inputs of the step. Single signals have size 1.
+    pub external_inputs: Vec<(String, usize)>,
+}
+
+/// Signals of a CircomFCircuit resolved against the circuit, which are used to build the inputs
+/// of the witness calculation and to read the next state from the witness.
+#[derive(Clone, Debug)]
+pub(crate) struct CircomSignals {
+    pub(crate) state_input: String,
+    /// indexes in the witness of the elements of the state output signal
+    pub(crate) state_output_indexes: Vec<usize>,
+    pub(crate) external_inputs: Vec<(String, usize)>,
+}
+
+impl CircomSignals {
+    /// Signals of the default convention, where the state is the public `ivc_input` signal, the
+    /// next state is the `ivc_output` signal (which is the first one in the witness after the
+    /// constant `1`), and the external inputs are in the `external_inputs` signal.
+    pub(crate) fn default_names(state_len: usize, n_external_inputs: usize) -> Self {
+        let external_inputs = if n_external_inputs > 0 {
+            vec![("external_inputs".to_string(), n_external_inputs)]
+        } else {
+            vec![]
+        };
+        Self {
+            state_input: "ivc_input".to_string(),
+            state_output_indexes: (1..1 + state_len).collect(),
+            external_inputs,
+        }
+    }
+
+    /// Resolves the given signal map against the `.sym` file of the circuit, checking that the
+    /// signals exist with the expected sizes, and that the state signals are public.
+    pub(crate) fn resolve(
+        signal_map: CircomSignalMap,
+        state_len: usize,
+        n_public_signals: usize,
+        n_external_inputs: usize,
+    ) -> Result<Self, Error> {
+        let sym = String::from_utf8(read_path_or_bin(signal_map.sym)?)
+            .map_err(|e| Error::Other(format!("circom .sym file is not valid UTF-8: {}", e)))?;
+        let signals = parse_sym(&sym)?;
+
+        let is_public = |indexes: &[i64]| {
+            indexes
+                .iter()
+                .all(|&i| i >= 1 && (i as usize) <= n_public_signals)
+        };
+
+        let state_input = get_signal(&signals, &signal_map.state_input, state_len)?;
+        let state_output = get_signal(&signals, &signal_map.state_output, state_len)?;
+        for (name, indexes) in [
+            (&signal_map.state_input, state_input),
+            (&signal_map.state_output, state_output),
+        ] {
+            if !is_public(indexes) {
+                return Err(Error::Other(format!(
+                    "circom state signal `{}` is not public",
+                    name
+                )));
+            }
+        }
+
+        let mut total_size = 0;
+        for (name, size) in signal_map.external_inputs.iter() {
+            if name == &signal_map.state_input || name == &signal_map.state_output {
+                return Err(Error::Other(format!(
+                    "circom signal `{}` can not be both a state and an external input",
+                    name
+                )));
+            }
+            get_signal(&signals, name, *size)?;
+            total_size += size;
+        }
+        if total_size != n_external_inputs {
+            return Err(Error::NotSameLength(
+                "external input signals".to_string(),
+                total_size,
+                "external inputs".to_string(),
+                n_external_inputs,
+            ));
+        }
+
+        Ok(Self {
+            state_input: signal_map.state_input,
+            state_output_indexes: state_output.iter().map(|&i| i as usize).collect(),
+            external_inputs: signal_map.external_inputs,
+        })
+    }
+
+    /// Splits the flattened external inputs into their named signals.
+    pub(crate) fn external_inputs_map<T: Clone>(
+        &self,
+        external_inputs: &[T],
+    ) -> Vec<(String, Vec<T>)> {
+        let mut offset = 0;
+        self.external_inputs
+            .iter()
+            .map(|(name, size)| {
+                let values = external_inputs[offset..offset + size].to_vec();
+                offset += size;
+                (name.clone(), values)
+            })
+            .collect()
+    }
+}
+
+/// Parses the signals of the main component from a circom `.sym` file, whose lines have the form
+/// `label_index,witness_index,component_index,name`, returning the witness indexes of the
+/// elements of each signal (-1 for the signals removed by the optimizer) by signal name.
+fn parse_sym(sym: &str) -> Result<BTreeMap<String, Vec<i64>>, Error> {
+    let mut signals: BTreeMap<String, Vec<i64>> = BTreeMap::new();
+    for line in sym.lines().filter(|line| !line.trim().is_empty()) {
+        let fields: Vec<&str> = line.trim().splitn(4, ',').collect();
+        if fields.len() != 4 {
+            return Err(Error::Other(format!(
+                "malformed circom .sym line: {}",
+                line
+            )));
+        }
+        let witness_index = fields[1]
+            .parse::<i64>()
+            .map_err(|_| Error::Other(format!("malformed circom .sym line: {}", line)))?;
+        // only the signals of the main component, not the ones of its subcomponents
+        let Some(name) = fields[3].strip_prefix("main.") else {
+            continue;
+        };
+        let base_name = name.split('[').next().unwrap_or(name);
+        if base_name.contains('.') {
+            continue;
+        }
+        signals
+            .entry(base_name.to_string())
+            .or_default()
+            .push(witness_index);
+    }
+    Ok(signals)
+}
+
+/// Returns the witness indexes of the given signal, checking that it has the expected size.
+fn get_signal<'a>(
+    signals: &'a BTreeMap<String, Vec<i64>>,
+    name: &str,
+    expected_size: usize,
+) -> Result<&'a [i64], Error> {
+    let indexes = signals.get(name).ok_or_else(|| {
+        Error::Other(format!(
+            "circom signal `{}` not found, available signals: {}",
+            name,
+            available_signals(signals)
+        ))
+    })?;
+    if indexes.len() != expected_size {
+        return Err(Error::Other(format!(
+            "circom signal `{}` has size {}, but {} was expected, available signals: {}",
+            name,
+            indexes.len(),
+            expected_size,
+            available_signals(signals)
+        )));
+    }
+    Ok(indexes)
+}
+
+fn available_signals(signals: &BTreeMap<String, Vec<i64>>) -> String {
+    signals
+        .iter()
+        .map(|(name, indexes)| format!("{}[{}]", name, indexes.len()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sym() {
+        let sym = "1,1,0,main.out[0]\n2,2,0,main.out[1]\n3,3,0,main.in[0][0]\n\
+                   4,4,0,main.in[0][1]\n5,-1,0,main.x\n6,5,1,main.sub.in\n";
+        let signals = parse_sym(sym).unwrap();
+        assert_eq!(signals.len(), 3);
+        assert_eq!(signals["out"], vec![1, 2]);
+        assert_eq!(signals["in"], vec![3, 4]);
+        assert_eq!(signals["x"], vec![-1]);
+        assert_eq!(available_signals(&signals), "in[2], out[2], x[1]");
+
+        assert!(parse_sym("1,1,main.out").is_err());
+    }
+}
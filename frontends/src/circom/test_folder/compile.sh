@@ -1,4 +1,12 @@
 #!/bin/bash
-circom ./frontends/src/circom/test_folder/cubic_circuit.circom --r1cs --sym --wasm --prime bn128 --output ./frontends/src/circom/test_folder/
+circom ./frontends/src/circom/test_folder/cubic_circuit.circom --r1cs --sym --wasm --c --prime bn128 --output ./frontends/src/circom/test_folder/
 circom ./frontends/src/circom/test_folder/with_external_inputs.circom --r1cs --sym --wasm --prime bn128 --output ./frontends/src/circom/test_folder/
 circom ./frontends/src/circom/test_folder/no_external_inputs.circom --r1cs --sym --wasm --prime bn128 --output ./frontends/src/circom/test_folder/
+circom ./frontends/src/circom/test_folder/multiple_inputs.circom --r1cs --sym --wasm --prime bn128 --output ./frontends/src/circom/test_folder/
+circom ./frontends/src/circom/test_folder/pipeline_circuit.circom --r1cs --sym --wasm --prime bn128 --output ./frontends/src/circom/test_folder/
+
+# native witness generator of cubic_circuit, used by the tests behind the `circom-native-witness`
+# feature (requires the nlohmann-json and gmp libraries)
+if [ "$1" == "--native" ]; then
+    make -C ./frontends/src/circom/test_folder/cubic_circuit_cpp/
+fi
@@ -0,0 +1,18 @@
+pragma circom 2.0.3;
+
+// circuit that declares its external inputs before and after the state, with names different
+// from the ones of the default convention
+template MultipleInputs () {
+    signal input b[2];
+    signal input state[2];
+    signal input a;
+    signal output next_state[2];
+
+    signal temp;
+
+    temp <== state[0] * a;
+    next_state[0] <== temp + b[0];
+    next_state[1] <== state[1] * b[1] + a;
+}
+
+component main {public [state]} = MultipleInputs();
@@ -0,0 +1,20 @@
+pragma circom 2.0.3;
+
+// Applies the step of cubic_circuit.circom N times, so that the witness calculation of a step is
+// large enough to be compared against its folding. Used by the `circom_pipeline` benchmark.
+template PipelineCircuit (N) {
+    signal input ivc_input[1];
+    signal output ivc_output[1];
+
+    signal x[N + 1];
+    signal x2[N];
+
+    x[0] <== ivc_input[0];
+    for (var i = 0; i < N; i++) {
+        x2[i] <== x[i] * x[i];
+        x[i + 1] <== x2[i] * x[i] + x[i] + 5;
+    }
+    ivc_output[0] <== x[N];
+}
+
+component main {public [ivc_input]} = PipelineCircuit(16384);
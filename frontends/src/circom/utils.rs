@@ -5,50 +5,89 @@ use ark_circom::{
 use ark_ff::{BigInteger, PrimeField};
 use ark_serialize::Read;
 use num_bigint::{BigInt, Sign};
-use std::{fs::File, io::Cursor, marker::PhantomData, path::PathBuf};
+use std::{cell::RefCell, fmt, io::Cursor, marker::PhantomData, rc::Rc};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 use folding_schemes::{utils::PathOrBin, Error};
 
+use crate::utils::read_path_or_bin;
+
+// Lazily instantiated WitnessCalculator, shared by the clones of a CircomWrapper. Compiling and
+// instantiating the wasm module is far more expensive than computing a witness, so it is done once
+// at the first witness calculation and the instance is reused afterwards. Reusing it is sound since
+// the circom runtime resets its witness memory (`init`) at the beginning of each calculation.
+// wasmer requires mutable access to the instance, hence the RefCell, which makes the wrapper not
+// thread-safe: a CircomWrapper (and its clones) must be used from a single thread.
+#[derive(Clone, Default)]
+struct CachedWitnessCalculator(Rc<RefCell<Option<WitnessCalculator>>>);
+
+impl fmt::Debug for CachedWitnessCalculator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let instantiated = self.0.try_borrow().map(|c| c.is_some()).unwrap_or(true);
+        write!(f, "WitnessCalculator {{ instantiated: {} }}", instantiated)
+    }
+}
+
+/// Backend used to compute the witness of a circom circuit.
+#[derive(Clone, Debug)]
+pub enum WitnessBackend {
+    /// wasm witness calculator generated by circom with `--wasm`, run with wasmer
+    Wasm(PathOrBin),
+    /// native witness generator binary, built with `make` from the C++ code generated by circom
+    /// with `--c`. Not available on wasm32-unknown-unknown, where processes can not be spawned.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    NativeBin(PathBuf),
+}
+
+// Witness generator of a CircomWrapper, built from its WitnessBackend.
+#[derive(Clone, Debug)]
+enum WitnessGenerator {
+    Wasm {
+        wasmfile_bytes: Vec<u8>,
+        calculator: CachedWitnessCalculator,
+    },
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    NativeBin(PathBuf),
+}
+
 // A struct that wraps Circom functionalities, allowing for extraction of R1CS and witnesses
-// based on file paths to Circom's .r1cs and .wasm.
+// based on Circom's .r1cs and on the witness generator of the chosen WitnessBackend.
 #[derive(Clone, Debug)]
 pub struct CircomWrapper<F: PrimeField> {
     r1csfile_bytes: Vec<u8>,
-    wasmfile_bytes: Vec<u8>,
+    witness_generator: WitnessGenerator,
     _marker: PhantomData<F>,
 }
 
 impl<F: PrimeField> CircomWrapper<F> {
-    // Creates a new instance of the CircomWrapper with the file paths.
-    pub fn new(r1cs: PathOrBin, wasm: PathOrBin) -> Result<Self, Error> {
-        match (r1cs, wasm) {
-            (PathOrBin::Path(r1cs_path), PathOrBin::Path(wasm_path)) => {
-                Self::new_from_path(r1cs_path, wasm_path)
+    // Creates a new instance of the CircomWrapper with the R1CS and the witness backend, reading
+    // the files passed by path.
+    pub fn new(r1cs: PathOrBin, witness_backend: WitnessBackend) -> Result<Self, Error> {
+        let r1csfile_bytes = read_path_or_bin(r1cs)?;
+        let witness_generator = match witness_backend {
+            WitnessBackend::Wasm(wasm) => WitnessGenerator::Wasm {
+                wasmfile_bytes: read_path_or_bin(wasm)?,
+                calculator: CachedWitnessCalculator::default(),
+            },
+            #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+            WitnessBackend::NativeBin(bin_path) => {
+                if !bin_path.is_file() {
+                    return Err(Error::WitnessCalculationError(format!(
+                        "native witness generator {} not found",
+                        bin_path.display()
+                    )));
+                }
+                WitnessGenerator::NativeBin(bin_path)
             }
-            (PathOrBin::Bin(r1cs_bin), PathOrBin::Bin(wasm_bin)) => Ok(Self {
-                r1csfile_bytes: r1cs_bin,
-                wasmfile_bytes: wasm_bin,
-                _marker: PhantomData,
-            }),
-            _ => unreachable!("You should pass the same enum branch for both inputs"),
-        }
-    }
-
-    // Creates a new instance of the CircomWrapper with the file paths.
-    fn new_from_path(r1cs_file_path: PathBuf, wasm_file_path: PathBuf) -> Result<Self, Error> {
-        let mut file = File::open(r1cs_file_path)?;
-        let metadata = File::metadata(&file)?;
-        let mut r1csfile_bytes = vec![0; metadata.len() as usize];
-        file.read_exact(&mut r1csfile_bytes)?;
-
-        let mut file = File::open(wasm_file_path)?;
-        let metadata = File::metadata(&file)?;
-        let mut wasmfile_bytes = vec![0; metadata.len() as usize];
-        file.read_exact(&mut wasmfile_bytes)?;
-
-        Ok(CircomWrapper {
+        };
+        Ok(Self {
             r1csfile_bytes,
-            wasmfile_bytes,
+            witness_generator,
             _marker: PhantomData,
         })
     }
@@ -91,14 +130,41 @@ impl<F: PrimeField> CircomWrapper<F> {
             .collect()
     }
 
-    // Calculates the witness given the Wasm filepath and inputs.
+    // Calculates the witness given the inputs, using the witness backend of the wrapper.
     pub fn calculate_witness(
         &self,
         inputs: &[(String, Vec<BigInt>)],
     ) -> Result<Vec<BigInt>, Error> {
-        let mut calculator = WitnessCalculator::from_binary(&self.wasmfile_bytes).map_err(|e| {
-            Error::WitnessCalculationError(format!("Failed to create WitnessCalculator: {}", e))
+        match &self.witness_generator {
+            WitnessGenerator::Wasm {
+                wasmfile_bytes,
+                calculator,
+            } => Self::calculate_witness_wasm(wasmfile_bytes, calculator, inputs),
+            #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+            WitnessGenerator::NativeBin(bin_path) => {
+                Self::calculate_witness_native(bin_path, inputs)
+            }
+        }
+    }
+
+    // Calculates the witness with the wasm WitnessCalculator, instantiating it from the Wasm
+    // binary at the first call and reusing it in the next ones.
+    fn calculate_witness_wasm(
+        wasmfile_bytes: &[u8],
+        calculator: &CachedWitnessCalculator,
+        inputs: &[(String, Vec<BigInt>)],
+    ) -> Result<Vec<BigInt>, Error> {
+        let mut cached = calculator.0.try_borrow_mut().map_err(|_| {
+            Error::WitnessCalculationError("WitnessCalculator is already in use".to_string())
         })?;
+        if cached.is_none() {
+            *cached = Some(WitnessCalculator::from_binary(wasmfile_bytes).map_err(|e| {
+                Error::WitnessCalculationError(format!("Failed to create WitnessCalculator: {}", e))
+            })?);
+        }
+        let calculator = cached
+            .as_mut()
+            .ok_or(Error::MissingValue("WitnessCalculator".to_string()))?;
         calculator
             .calculate_witness(inputs.iter().cloned(), true)
             .map_err(|e| {
@@ -106,6 +172,55 @@ impl<F: PrimeField> CircomWrapper<F> {
             })
     }
 
+    // Calculates the witness with the native witness generator, which reads the inputs from a
+    // JSON file and writes the witness into a `.wtns` file.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    fn calculate_witness_native(
+        bin_path: &Path,
+        inputs: &[(String, Vec<BigInt>)],
+    ) -> Result<Vec<BigInt>, Error> {
+        // the directory is created with a random name and removed when dropped
+        let dir = tempfile::TempDir::new()?;
+        let input_path = dir.path().join("input.json");
+        let wtns_path = dir.path().join("witness.wtns");
+
+        let inputs_json = serde_json::Value::Object(
+            inputs
+                .iter()
+                .map(|(name, values)| {
+                    let values = values
+                        .iter()
+                        .map(|v| serde_json::Value::String(v.to_string()))
+                        .collect();
+                    (name.clone(), serde_json::Value::Array(values))
+                })
+                .collect(),
+        );
+        fs::write(&input_path, inputs_json.to_string())?;
+
+        let output = Command::new(bin_path)
+            .arg(&input_path)
+            .arg(&wtns_path)
+            .output()
+            .map_err(|e| {
+                Error::WitnessCalculationError(format!(
+                    "Failed to run the native witness generator {}: {}",
+                    bin_path.display(),
+                    e
+                ))
+            })?;
+        if !output.status.success() {
+            return Err(Error::WitnessCalculationError(format!(
+                "Native witness generator {} failed ({}): {}",
+                bin_path.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        parse_wtns(&fs::read(&wtns_path)?)
+    }
+
     // Converts a num_bigint::BigInt to a PrimeField::BigInt.
     pub fn num_bigint_to_ark_bigint(&self, value: &BigInt) -> Result<F::BigInt, Error> {
         let big_uint = value.to_biguint().ok_or_else(|| {
@@ -132,6 +247,66 @@ impl<F: PrimeField> CircomWrapper<F> {
     }
 }
 
+// Parses the witness from the content of a `.wtns` file, which consists of the "wtns" magic, the
+// version and the number of sections, followed by the sections, each one prefixed by its type
+// (u32) and its size (u64). Section 1 is the header, with the byte size of the field elements,
+// the prime and the number of witness values, and section 2 contains the witness values, all of
+// them encoded in little-endian.
+pub fn parse_wtns(bytes: &[u8]) -> Result<Vec<BigInt>, Error> {
+    let malformed =
+        |reason: &str| Error::WitnessCalculationError(format!("Malformed .wtns: {}", reason));
+    let mut reader = Cursor::new(bytes);
+    let mut read_bytes = |n: usize| -> Result<Vec<u8>, Error> {
+        let mut buf = vec![0; n];
+        reader
+            .read_exact(&mut buf)
+            .map_err(|_| malformed("unexpected end of file"))?;
+        Ok(buf)
+    };
+    let to_u32 = |b: Vec<u8>| u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+
+    if read_bytes(4)? != b"wtns" {
+        return Err(malformed("wrong magic"));
+    }
+    let _version = to_u32(read_bytes(4)?);
+    let n_sections = to_u32(read_bytes(4)?);
+
+    let mut header: Option<(usize, usize)> = None;
+    let mut witness_bytes: Option<Vec<u8>> = None;
+    for _ in 0..n_sections {
+        let section_type = to_u32(read_bytes(4)?);
+        let size_bytes = read_bytes(8)?;
+        let mut size = [0u8; 8];
+        size.copy_from_slice(&size_bytes);
+        let section = read_bytes(u64::from_le_bytes(size) as usize)?;
+        match section_type {
+            1 => {
+                if section.len() < 4 {
+                    return Err(malformed("header too short"));
+                }
+                let n8 = to_u32(section[..4].to_vec()) as usize;
+                if section.len() != 4 + n8 + 4 {
+                    return Err(malformed("wrong header size"));
+                }
+                let n_witness = to_u32(section[4 + n8..].to_vec()) as usize;
+                header = Some((n8, n_witness));
+            }
+            2 => witness_bytes = Some(section),
+            _ => {}
+        }
+    }
+
+    let (n8, n_witness) = header.ok_or_else(|| malformed("missing header section"))?;
+    let witness_bytes = witness_bytes.ok_or_else(|| malformed("missing witness section"))?;
+    if n8 == 0 || witness_bytes.len() != n8 * n_witness {
+        return Err(malformed("wrong witness section size"));
+    }
+    Ok(witness_bytes
+        .chunks(n8)
+        .map(|chunk| BigInt::from_bytes_le(Sign::Plus, chunk))
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -139,6 +314,7 @@ mod tests {
     use ark_circom::circom::{CircomBuilder, CircomConfig};
     use ark_circom::CircomCircuit;
     use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+    use std::time::Instant;
 
     //To generate .r1cs and .wasm files, run the below command in the terminal.
     //bash ./frontends/src/circom/test_folder/compile.sh
@@ -168,7 +344,9 @@ mod tests {
             PathBuf::from("./src/circom/test_folder/cubic_circuit_js/cubic_circuit.wasm");
 
         let inputs = vec![("ivc_input".to_string(), vec![BigInt::from(3)])];
-        let wrapper = CircomWrapper::<Fr>::new(r1cs_path.into(), wasm_path.into()).unwrap();
+        let wrapper =
+            CircomWrapper::<Fr>::new(r1cs_path.into(), WitnessBackend::Wasm(wasm_path.into()))
+                .unwrap();
 
         let (r1cs, witness) = wrapper.extract_r1cs_and_witness(&inputs).unwrap();
 
@@ -184,4 +362,208 @@ mod tests {
         circom_circuit.generate_constraints(cs.clone()).unwrap();
         assert!(cs.is_satisfied().unwrap());
     }
+
+    // Test that the WitnessCalculator is instantiated only once, so that the witness calculations
+    // after the first one are much faster, and that reusing it gives the same witnesses
+    #[test]
+    fn test_reuse_witness_calculator() {
+        let r1cs_path = PathBuf::from("./src/circom/test_folder/cubic_circuit.r1cs");
+        let wasm_path =
+            PathBuf::from("./src/circom/test_folder/cubic_circuit_js/cubic_circuit.wasm");
+        let wrapper =
+            CircomWrapper::<Fr>::new(r1cs_path.into(), WitnessBackend::Wasm(wasm_path.into()))
+                .unwrap();
+        let inputs = |x: u32| vec![("ivc_input".to_string(), vec![BigInt::from(x)])];
+
+        let start = Instant::now();
+        let first = wrapper.extract_witness(&inputs(3)).unwrap();
+        let first_elapsed = start.elapsed();
+
+        // clones share the instantiated calculator
+        let wrapper_clone = wrapper.clone();
+        let start = Instant::now();
+        let second = wrapper_clone.extract_witness(&inputs(3)).unwrap();
+        let second_elapsed = start.elapsed();
+
+        assert_eq!(first, second);
+        assert_eq!(first[1], Fr::from(35_u32));
+        // a different input after the reuse is computed from a clean state
+        assert_eq!(
+            wrapper.extract_witness(&inputs(4)).unwrap()[1],
+            Fr::from(73_u32)
+        );
+
+        assert!(
+            second_elapsed * 10 < first_elapsed,
+            "second witness calculation ({:?}) is not much faster than the first one ({:?})",
+            second_elapsed,
+            first_elapsed
+        );
+    }
+
+    // builds the content of a .wtns file with the given witness values of 32 bytes
+    fn build_wtns(witness: &[BigInt]) -> Vec<u8> {
+        let n8 = 32_usize;
+        let mut header = (n8 as u32).to_le_bytes().to_vec();
+        header.extend(vec![0xff; n8]); // prime, ignored by the parser
+        header.extend((witness.len() as u32).to_le_bytes());
+        let mut values = vec![];
+        for w in witness {
+            let mut bytes = w.to_bytes_le().1;
+            bytes.resize(n8, 0);
+            values.extend(bytes);
+        }
+
+        let mut wtns = b"wtns".to_vec();
+        wtns.extend(2_u32.to_le_bytes()); // version
+        wtns.extend(2_u32.to_le_bytes()); // number of sections
+        for (section_type, section) in [(1_u32, header), (2_u32, values)] {
+            wtns.extend(section_type.to_le_bytes());
+            wtns.extend((section.len() as u64).to_le_bytes());
+            wtns.extend(section);
+        }
+        wtns
+    }
+
+    #[test]
+    fn test_parse_wtns() {
+        let witness = vec![BigInt::from(1), BigInt::from(35), BigInt::from(u64::MAX)];
+        let wtns = build_wtns(&witness);
+        assert_eq!(parse_wtns(&wtns).unwrap(), witness);
+
+        // truncated file
+        assert!(parse_wtns(&wtns[..wtns.len() - 1]).is_err());
+        // wrong magic
+        let mut wrong_magic = wtns.clone();
+        wrong_magic[0] = b'x';
+        assert!(parse_wtns(&wrong_magic).is_err());
+    }
+
+    #[test]
+    fn test_native_witness_backend_errors() {
+        let r1cs_path = PathBuf::from("./src/circom/test_folder/cubic_circuit.r1cs");
+        // missing generator binary
+        assert!(CircomWrapper::<Fr>::new(
+            r1cs_path.clone().into(),
+            WitnessBackend::NativeBin(PathBuf::from("./src/circom/test_folder/missing_binary"))
+        )
+        .is_err());
+
+        // the stderr of a failing generator is surfaced in the error
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let dir = tempfile::TempDir::new().unwrap();
+            let bin_path = dir.path().join("failing_generator");
+            fs::write(&bin_path, "#!/bin/sh\necho 'invalid input' >&2\nexit 1\n").unwrap();
+            fs::set_permissions(&bin_path, fs::Permissions::from_mode(0o755)).unwrap();
+
+            let wrapper =
+                CircomWrapper::<Fr>::new(r1cs_path.into(), WitnessBackend::NativeBin(bin_path))
+                    .unwrap();
+            let inputs = vec![("ivc_input".to_string(), vec![BigInt::from(3)])];
+            match wrapper.calculate_witness(&inputs) {
+                Err(Error::WitnessCalculationError(e)) => assert!(e.contains("invalid input")),
+                other => panic!("expected WitnessCalculationError, got {:?}", other),
+            }
+        }
+    }
+
+    // Test that the native witness generator computes the same witness as the wasm one. The
+    // generator is compiled by compile.sh from the C++ code generated by circom, which requires
+    // the nlohmann-json and gmp libraries, hence the test is behind the `circom-native-witness`
+    // feature.
+    #[cfg(feature = "circom-native-witness")]
+    #[test]
+    fn test_native_witness_backend() {
+        let r1cs_path = PathBuf::from("./src/circom/test_folder/cubic_circuit.r1cs");
+        let wasm_path =
+            PathBuf::from("./src/circom/test_folder/cubic_circuit_js/cubic_circuit.wasm");
+        let bin_path = PathBuf::from("./src/circom/test_folder/cubic_circuit_cpp/cubic_circuit");
+
+        let wasm_wrapper = CircomWrapper::<Fr>::new(
+            r1cs_path.clone().into(),
+            WitnessBackend::Wasm(wasm_path.into()),
+        )
+        .unwrap();
+        let native_wrapper =
+            CircomWrapper::<Fr>::new(r1cs_path.into(), WitnessBackend::NativeBin(bin_path))
+                .unwrap();
+
+        for x in [3_u32, 4, 1000] {
+            let inputs = vec![("ivc_input".to_string(), vec![BigInt::from(x)])];
+            let (r1cs, native_witness) = native_wrapper.extract_r1cs_and_witness(&inputs).unwrap();
+            assert_eq!(
+                native_witness,
+                Some(wasm_wrapper.extract_witness(&inputs).unwrap())
+            );
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let circom_circuit = CircomCircuit {
+                r1cs,
+                witness: native_witness,
+                public_inputs_indexes: vec![],
+                allocate_inputs_as_witnesses: false,
+            };
+            circom_circuit.generate_constraints(cs.clone()).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+
+    // Exports the R1CS of Nova's AugmentedFCircuit in the circom `.r1cs` format, and reads it back
+    // with the circom-compat parser to check that the encoding matches the circom spec.
+    #[test]
+    fn test_export_augmented_r1cs() {
+        use ark_bn254::{constraints::GVar, G1Projective as Projective};
+        use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+        use folding_schemes::{
+            commitment::pedersen::Pedersen,
+            folding::nova::{get_r1cs, Nova, PreprocessorParam},
+            frontend::{utils::CubicFCircuit, FCircuit},
+            transcript::poseidon::poseidon_canonical_config,
+            FoldingScheme,
+        };
+
+        type N = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            CubicFCircuit<Fr>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let (pp, _) = N::preprocess(
+            &mut rng,
+            &PreprocessorParam::new(poseidon_config.clone(), f_circuit),
+        )
+        .unwrap();
+
+        let mut bytes = Vec::new();
+        pp.export_augmented_r1cs::<GVar2, _, _>(f_circuit, &mut bytes)
+            .unwrap();
+
+        let (r1cs, _) =
+            get_r1cs::<Projective, GVar, Projective2, GVar2, _>(&poseidon_config, f_circuit)
+                .unwrap();
+        let r1cs_file = r1cs_reader::R1CSFile::<Fr>::new(Cursor::new(&bytes)).unwrap();
+        let circom_r1cs = R1CS::<Fr>::from(r1cs_file);
+
+        assert_eq!(circom_r1cs.num_inputs, 1 + r1cs.l);
+        assert_eq!(circom_r1cs.num_variables, r1cs.A.n_cols);
+        assert_eq!(circom_r1cs.constraints.len(), r1cs.A.n_rows);
+        let to_lc = |row: &Vec<(Fr, usize)>| -> Vec<(usize, Fr)> {
+            row.iter().map(|(coeff, col)| (*col, *coeff)).collect()
+        };
+        for (i, (a, b, c)) in circom_r1cs.constraints.iter().enumerate() {
+            assert_eq!(a, &to_lc(&r1cs.A.coeffs[i]));
+            assert_eq!(b, &to_lc(&r1cs.B.coeffs[i]));
+            assert_eq!(c, &to_lc(&r1cs.C.coeffs[i]));
+        }
+    }
 }
@@ -1,3 +1,4 @@
 pub mod circom;
 pub mod noir;
 pub mod noname;
+mod utils;
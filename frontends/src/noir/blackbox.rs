@@ -0,0 +1,503 @@
+/// Lowering to R1CS of the ACIR black-box functions, which the AcirCircuitSonobe bridge does not
+/// translate. Each black-box call is replaced by an arkworks gadget wired to the variables of the
+/// witnesses of its inputs and outputs.
+use std::collections::{BTreeSet, HashMap};
+
+use acvm::acir::{
+    acir_field::GenericFieldElement,
+    circuit::{
+        opcodes::{BlackBoxFuncCall, ConstantOrWitnessEnum, FunctionInput},
+        Circuit, Opcode,
+    },
+    native_types::{Witness as AcvmWitness, WitnessMap},
+};
+use ark_crypto_primitives::prf::blake2s::constraints::evaluate_blake2s;
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::fp::FpVar,
+    uint32::UInt32,
+    R1CSVar,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use folding_schemes::{transcript::keccak::keccak_f1600_gadget, Error};
+
+type Bits<F> = Vec<Boolean<F>>;
+
+fn is_supported<F>(call: &BlackBoxFuncCall<F>) -> bool {
+    matches!(
+        call,
+        BlackBoxFuncCall::RANGE { .. }
+            | BlackBoxFuncCall::AND { .. }
+            | BlackBoxFuncCall::XOR { .. }
+            | BlackBoxFuncCall::Sha256Compression { .. }
+            | BlackBoxFuncCall::Keccakf1600 { .. }
+            | BlackBoxFuncCall::Blake2s { .. }
+    )
+}
+
+fn black_box_calls<F: PrimeField>(
+    circuit: &Circuit<GenericFieldElement<F>>,
+) -> impl Iterator<Item = &BlackBoxFuncCall<GenericFieldElement<F>>> {
+    circuit.opcodes.iter().filter_map(|opcode| match opcode {
+        Opcode::BlackBoxFuncCall(call) => Some(call),
+        _ => None,
+    })
+}
+
+/// Checks that all the black-box functions called by the circuit can be lowered to R1CS,
+/// returning an error with the ones that can not.
+pub(crate) fn check_black_boxes<F: PrimeField>(
+    circuit: &Circuit<GenericFieldElement<F>>,
+) -> Result<(), Error> {
+    let unsupported: BTreeSet<&str> = black_box_calls(circuit)
+        .filter(|call| !is_supported(call))
+        .map(|call| call.name())
+        .collect();
+    if !unsupported.is_empty() {
+        return Err(Error::NotSupportedYet(format!(
+            "noir black-box functions {}",
+            unsupported.into_iter().collect::<Vec<_>>().join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Allocates the witnesses of the inputs and outputs of the black-box calls that are not already
+/// assigned, so that both the black-box gadgets and the AcirCircuitSonobe bridge use the same
+/// variables for them.
+pub(crate) fn allocate_black_box_witnesses<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    circuit: &Circuit<GenericFieldElement<F>>,
+    witness_map: &WitnessMap<GenericFieldElement<F>>,
+    already_assigned: &HashMap<AcvmWitness, &FpVar<F>>,
+) -> Result<HashMap<AcvmWitness, FpVar<F>>, SynthesisError> {
+    let mut vars = HashMap::new();
+    for call in black_box_calls(circuit) {
+        let input_witnesses =
+            call.get_inputs_vec()
+                .into_iter()
+                .filter_map(|input| match input.input_ref() {
+                    ConstantOrWitnessEnum::Witness(witness) => Some(*witness),
+                    ConstantOrWitnessEnum::Constant(_) => None,
+                });
+        for witness in input_witnesses.chain(call.get_outputs_vec()) {
+            if already_assigned.contains_key(&witness) || vars.contains_key(&witness) {
+                continue;
+            }
+            let var = FpVar::new_witness(cs.clone(), || {
+                witness_map
+                    .get(&witness)
+                    .map(|value| value.into_repr())
+                    .ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            vars.insert(witness, var);
+        }
+    }
+    Ok(vars)
+}
+
+/// Generates the constraints of the black-box calls of the circuit, where `vars` contains the
+/// variables of all the witnesses of their inputs and outputs.
+pub(crate) fn generate_black_box_constraints<F: PrimeField>(
+    circuit: &Circuit<GenericFieldElement<F>>,
+    vars: &HashMap<AcvmWitness, &FpVar<F>>,
+) -> Result<(), SynthesisError> {
+    for call in black_box_calls(circuit) {
+        match call {
+            BlackBoxFuncCall::RANGE { input } => {
+                let n_bits = input.num_bits() as usize;
+                // ranges of the size of the field are trivially satisfied
+                if n_bits < F::MODULUS_BIT_SIZE as usize {
+                    to_bits(&input_var(input, vars)?, n_bits)?;
+                }
+            }
+            BlackBoxFuncCall::AND { lhs, rhs, output } => {
+                let n_bits = lhs.num_bits() as usize;
+                let lhs = to_bits(&input_var(lhs, vars)?, n_bits)?;
+                let rhs = to_bits(&input_var(rhs, vars)?, n_bits)?;
+                enforce_output(&and(&lhs, &rhs)?, output, vars)?;
+            }
+            BlackBoxFuncCall::XOR { lhs, rhs, output } => {
+                let n_bits = lhs.num_bits() as usize;
+                let lhs = to_bits(&input_var(lhs, vars)?, n_bits)?;
+                let rhs = to_bits(&input_var(rhs, vars)?, n_bits)?;
+                enforce_output(&xor(&lhs, &rhs)?, output, vars)?;
+            }
+            BlackBoxFuncCall::Sha256Compression {
+                inputs,
+                hash_values,
+                outputs,
+            } => {
+                let block = input_words(inputs.iter(), 32, vars)?;
+                let state = input_words(hash_values.iter(), 32, vars)?;
+                let new_state = sha256_compression(&block, &state)?;
+                for (word, output) in new_state.iter().zip(outputs.iter()) {
+                    enforce_output(word, output, vars)?;
+                }
+            }
+            BlackBoxFuncCall::Keccakf1600 { inputs, outputs } => {
+                let state = input_words(inputs.iter(), 64, vars)?;
+                let new_state = keccak_f1600_gadget(&state)?;
+                for (lane, output) in new_state.iter().zip(outputs.iter()) {
+                    enforce_output(lane, output, vars)?;
+                }
+            }
+            BlackBoxFuncCall::Blake2s { inputs, outputs } => {
+                let message = input_words(inputs.iter(), 8, vars)?;
+                for (byte, output) in blake2s(&message)?.iter().zip(outputs.iter()) {
+                    enforce_output(byte, output, vars)?;
+                }
+            }
+            // rejected by `check_black_boxes` when creating the NoirFCircuit
+            _ => return Err(SynthesisError::Unsatisfiable),
+        }
+    }
+    Ok(())
+}
+
+fn input_var<F: PrimeField>(
+    input: &FunctionInput<GenericFieldElement<F>>,
+    vars: &HashMap<AcvmWitness, &FpVar<F>>,
+) -> Result<FpVar<F>, SynthesisError> {
+    match input.input_ref() {
+        ConstantOrWitnessEnum::Constant(value) => Ok(FpVar::constant(value.into_repr())),
+        ConstantOrWitnessEnum::Witness(witness) => vars
+            .get(witness)
+            .map(|var| (*var).clone())
+            .ok_or(SynthesisError::AssignmentMissing),
+    }
+}
+
+fn input_words<'a, F: PrimeField>(
+    inputs: impl Iterator<Item = &'a FunctionInput<GenericFieldElement<F>>>,
+    n_bits: usize,
+    vars: &HashMap<AcvmWitness, &FpVar<F>>,
+) -> Result<Vec<Bits<F>>, SynthesisError> {
+    inputs
+        .map(|input| to_bits(&input_var(input, vars)?, n_bits))
+        .collect()
+}
+
+fn enforce_output<F: PrimeField>(
+    bits: &[Boolean<F>],
+    output: &AcvmWitness,
+    vars: &HashMap<AcvmWitness, &FpVar<F>>,
+) -> Result<(), SynthesisError> {
+    let output = vars.get(output).ok_or(SynthesisError::AssignmentMissing)?;
+    Boolean::le_bits_to_fp_var(bits)?.enforce_equal(output)
+}
+
+/// Decomposes `x` into `n_bits` little-endian bits, which enforces that `x` fits in `n_bits`.
+fn to_bits<F: PrimeField>(x: &FpVar<F>, n_bits: usize) -> Result<Bits<F>, SynthesisError> {
+    let cs = x.cs();
+    let mode = if cs.is_none() {
+        AllocationMode::Constant
+    } else {
+        AllocationMode::Witness
+    };
+    let bits = (0..n_bits)
+        .map(|i| {
+            Boolean::new_variable(cs.clone(), || Ok(x.value()?.into_bigint().get_bit(i)), mode)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Boolean::le_bits_to_fp_var(&bits)?.enforce_equal(x)?;
+    Ok(bits)
+}
+
+fn constant_bits<F: PrimeField>(value: u64, n_bits: usize) -> Bits<F> {
+    (0..n_bits)
+        .map(|i| Boolean::constant((value >> i) & 1 == 1))
+        .collect()
+}
+
+fn xor<F: PrimeField>(a: &[Boolean<F>], b: &[Boolean<F>]) -> Result<Bits<F>, SynthesisError> {
+    a.iter().zip(b).map(|(a, b)| a.xor(b)).collect()
+}
+
+fn and<F: PrimeField>(a: &[Boolean<F>], b: &[Boolean<F>]) -> Result<Bits<F>, SynthesisError> {
+    a.iter().zip(b).map(|(a, b)| a.and(b)).collect()
+}
+
+fn rotr<F: PrimeField>(a: &[Boolean<F>], n: usize) -> Bits<F> {
+    (0..a.len()).map(|i| a[(i + n) % a.len()].clone()).collect()
+}
+
+fn shr<F: PrimeField>(a: &[Boolean<F>], n: usize) -> Bits<F> {
+    (0..a.len())
+        .map(|i| a.get(i + n).cloned().unwrap_or(Boolean::FALSE))
+        .collect()
+}
+
+// sum modulo 2^32 of the given 32-bit words
+fn add32<F: PrimeField>(words: &[&Bits<F>]) -> Result<Bits<F>, SynthesisError> {
+    let words = words
+        .iter()
+        .map(|word| UInt32::from_bits_le(word))
+        .collect::<Vec<_>>();
+    Ok(UInt32::addmany(&words)?.to_bits_le())
+}
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA-256 compression function, applied to the 16 words of the `block` and the 8 words of the
+/// `state`, all of them as 32 little-endian bits.
+///
+/// The `Sha256Gadget` of ark-crypto-primitives only exposes the full hash, whose compression
+/// function always starts from its own padded state, so it can not be used for the compression of
+/// an arbitrary `state` that ACIR calls. It is checked against it in the tests.
+fn sha256_compression<F: PrimeField>(
+    block: &[Bits<F>],
+    state: &[Bits<F>],
+) -> Result<Vec<Bits<F>>, SynthesisError> {
+    let mut w = block.to_vec();
+    for t in 16..64 {
+        let s0 = xor(
+            &xor(&rotr(&w[t - 15], 7), &rotr(&w[t - 15], 18))?,
+            &shr(&w[t - 15], 3),
+        )?;
+        let s1 = xor(
+            &xor(&rotr(&w[t - 2], 17), &rotr(&w[t - 2], 19))?,
+            &shr(&w[t - 2], 10),
+        )?;
+        let w_t = add32(&[&s1, &w[t - 7], &s0, &w[t - 16]])?;
+        w.push(w_t);
+    }
+
+    let mut v = state.to_vec();
+    for (t, w_t) in w.iter().enumerate() {
+        let (a, b, c, d, e, f, g, h) = (&v[0], &v[1], &v[2], &v[3], &v[4], &v[5], &v[6], &v[7]);
+        let s1 = xor(&xor(&rotr(e, 6), &rotr(e, 11))?, &rotr(e, 25))?;
+        // ch = (e & f) ^ (!e & g) = g ^ (e & (f ^ g))
+        let ch = xor(g, &and(e, &xor(f, g)?)?)?;
+        let s0 = xor(&xor(&rotr(a, 2), &rotr(a, 13))?, &rotr(a, 22))?;
+        // maj = (a & b) ^ (a & c) ^ (b & c) = (a & b) ^ (c & (a ^ b))
+        let maj = xor(&and(a, b)?, &and(c, &xor(a, b)?)?)?;
+        let k = constant_bits(SHA256_K[t] as u64, 32);
+
+        let new_e = add32(&[d, h, &s1, &ch, &k, w_t])?;
+        let new_a = add32(&[h, &s1, &ch, &k, w_t, &s0, &maj])?;
+        v = vec![
+            new_a,
+            a.clone(),
+            b.clone(),
+            c.clone(),
+            new_e,
+            e.clone(),
+            f.clone(),
+            g.clone(),
+        ];
+    }
+
+    state
+        .iter()
+        .zip(v.iter())
+        .map(|(s, v)| add32(&[s, v]))
+        .collect()
+}
+
+/// BLAKE2s-256 of the given `message` bytes, each of them as 8 little-endian bits, returning the
+/// 32 bytes of the digest in the same representation.
+fn blake2s<F: PrimeField>(message: &[Bits<F>]) -> Result<Vec<Bits<F>>, SynthesisError> {
+    Ok(evaluate_blake2s(&message.concat())?
+        .iter()
+        .flat_map(|word| word.to_bits_le())
+        .collect::<Vec<_>>()
+        .chunks(8)
+        .map(|byte| byte.to_vec())
+        .collect())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_crypto_primitives::crh::{
+        sha256::{
+            constraints::{Sha256Gadget, UnitVar},
+            Sha256,
+        },
+        CRHSchemeGadget,
+    };
+    use ark_r1cs_std::uint8::UInt8;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::{rand::RngCore, test_rng};
+    use blake2::{Blake2s256, Digest};
+    use sha2::digest::generic_array::GenericArray;
+
+    const SHA256_IV: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    fn alloc_words(cs: ConstraintSystemRef<Fr>, words: &[u64], n_bits: usize) -> Vec<Bits<Fr>> {
+        words
+            .iter()
+            .map(|w| {
+                let var = FpVar::new_witness(cs.clone(), || Ok(Fr::from(*w))).unwrap();
+                to_bits(&var, n_bits).unwrap()
+            })
+            .collect()
+    }
+
+    fn words_value(words: &[Bits<Fr>]) -> Vec<Fr> {
+        words
+            .iter()
+            .map(|w| Boolean::le_bits_to_fp_var(w).unwrap().value().unwrap())
+            .collect()
+    }
+
+    fn u64_words(words: &[u32]) -> Vec<u64> {
+        words.iter().map(|w| *w as u64).collect()
+    }
+
+    #[test]
+    fn test_sha256_compression_gadget() {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        // padded block of the empty message
+        let mut block = vec![0_u64; 16];
+        block[0] = 0x80000000;
+        let block = alloc_words(cs.clone(), &block, 32);
+        let state = alloc_words(cs.clone(), &u64_words(&SHA256_IV), 32);
+
+        let digest = sha256_compression(&block, &state).unwrap();
+        // sha256("")
+        let expected: Vec<Fr> = [
+            0xe3b0c442_u64,
+            0x98fc1c14,
+            0x9afbf4c8,
+            0x996fb924,
+            0x27ae41e4,
+            0x649b934c,
+            0xa495991b,
+            0x7852b855,
+        ]
+        .into_iter()
+        .map(Fr::from)
+        .collect();
+        assert_eq!(words_value(&digest), expected);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    // checks the SHA-256 compression of random blocks and states against the `sha2` crate
+    #[test]
+    fn test_sha256_compression_gadget_random() {
+        let rng = &mut test_rng();
+        for _ in 0..3 {
+            let block: Vec<u32> = (0..16).map(|_| rng.next_u32()).collect();
+            let mut state: [u32; 8] = [0; 8];
+            state.iter_mut().for_each(|w| *w = rng.next_u32());
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let block_var = alloc_words(cs.clone(), &u64_words(&block), 32);
+            let state_var = alloc_words(cs.clone(), &u64_words(&state), 32);
+            let new_state = sha256_compression(&block_var, &state_var).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+
+            let block_bytes: Vec<u8> = block.iter().flat_map(|w| w.to_be_bytes()).collect();
+            sha2::compress256(&mut state, &[*GenericArray::from_slice(&block_bytes)]);
+            assert_eq!(
+                words_value(&new_state),
+                state.iter().map(|w| Fr::from(*w)).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    // checks that the compression of a single padded block from the IV gives the same digest as
+    // the `Sha256Gadget` of ark-crypto-primitives
+    #[test]
+    fn test_sha256_compression_gadget_matches_sha256_gadget() {
+        let rng = &mut test_rng();
+        let mut message = vec![0_u8; 40];
+        rng.fill_bytes(&mut message);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let message_var = UInt8::new_witness_vec(cs.clone(), &message).unwrap();
+        let digest_var = <Sha256Gadget<Fr> as CRHSchemeGadget<Sha256, Fr>>::evaluate(
+            &UnitVar::default(),
+            &message_var,
+        )
+        .unwrap();
+
+        // padded block: the message, the bit 1 and the length in bits of the message
+        let mut padded = message.clone();
+        padded.push(0x80);
+        padded.resize(56, 0);
+        padded.extend_from_slice(&(message.len() as u64 * 8).to_be_bytes());
+        let block: Vec<u64> = padded
+            .chunks(4)
+            .map(|w| u32::from_be_bytes([w[0], w[1], w[2], w[3]]) as u64)
+            .collect();
+        let block = alloc_words(cs.clone(), &block, 32);
+        let state = alloc_words(cs.clone(), &u64_words(&SHA256_IV), 32);
+        let new_state = sha256_compression(&block, &state).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let digest: Vec<u8> = words_value(&new_state)
+            .iter()
+            .flat_map(|w| (w.into_bigint().as_ref()[0] as u32).to_be_bytes())
+            .collect();
+        assert_eq!(digest, digest_var.0.value().unwrap());
+    }
+
+    #[test]
+    fn test_blake2s_gadget() {
+        let digest = |message: &[u8]| {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let message_var = alloc_words(
+                cs.clone(),
+                &message.iter().map(|b| *b as u64).collect::<Vec<_>>(),
+                8,
+            );
+            let digest_var = blake2s(&message_var).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+            words_value(&digest_var)
+                .iter()
+                .map(|b| b.into_bigint().as_ref()[0] as u8)
+                .collect::<Vec<_>>()
+        };
+
+        // BLAKE2s-256("abc"), from the appendix B of RFC 7693
+        assert_eq!(
+            digest(b"abc"),
+            vec![
+                0x50, 0x8c, 0x5e, 0x8c, 0x32, 0x7c, 0x14, 0xe2, 0xe1, 0xa7, 0x2b, 0xa3, 0x4e, 0xeb,
+                0x45, 0x2f, 0x37, 0x45, 0x8b, 0x20, 0x9e, 0xd6, 0x3a, 0x29, 0x4d, 0x99, 0x9b, 0x4c,
+                0x86, 0x67, 0x59, 0x82,
+            ]
+        );
+
+        // lengths around the block size, against the `blake2` crate
+        let rng = &mut test_rng();
+        for len in [0, 1, 63, 64, 65, 130] {
+            let mut message = vec![0_u8; len];
+            rng.fill_bytes(&mut message);
+            assert_eq!(digest(&message), Blake2s256::digest(&message).to_vec());
+        }
+    }
+
+    #[test]
+    fn test_range_decomposition() {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let x = FpVar::new_witness(cs.clone(), || Ok(Fr::from(255_u32))).unwrap();
+        to_bits(&x, 8).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // 256 does not fit in 8 bits
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let x = FpVar::new_witness(cs.clone(), || Ok(Fr::from(256_u32))).unwrap();
+        to_bits(&x, 8).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
@@ -1,9 +1,15 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+
+mod blackbox;
 
 use acvm::{
     acir::{
         acir_field::GenericFieldElement,
-        circuit::{Circuit, Program},
+        circuit::{
+            brillig::{BrilligBytecode, BrilligOutputs},
+            opcodes::ConstantOrWitnessEnum,
+            Circuit, Opcode, Program,
+        },
         native_types::{Witness as AcvmWitness, WitnessMap},
     },
     blackbox_solver::StubbedBlackBoxSolver,
@@ -13,30 +19,36 @@ use ark_ff::PrimeField;
 use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, R1CSVar};
 use ark_relations::r1cs::ConstraintSynthesizer;
 use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
-use folding_schemes::{frontend::FCircuit, utils::PathOrBin, Error};
+use folding_schemes::{
+    frontend::{FCircuit, VecF, VecFpVar},
+    utils::PathOrBin,
+    Error,
+};
 use noir_arkworks_backend::{
-    read_program_from_binary, read_program_from_file, sonobe_bridge::AcirCircuitSonobe,
-    FilesystemError,
+    read_program_from_binary, sonobe_bridge::AcirCircuitSonobe, FilesystemError,
 };
 
+use crate::utils::read_path_or_bin;
+
+/// NoirFCircuit wraps a compiled noir circuit, where `L` is the number of external inputs.
+///
+/// The unconstrained (Brillig) functions of the program are executed by the ACVM to compute the
+/// witness hints, while the constraints are only the ones of the ACIR of the circuit.
 #[derive(Clone, Debug)]
-pub struct NoirFCircuit<F: PrimeField> {
+pub struct NoirFCircuit<F: PrimeField, const L: usize> {
     pub circuit: Circuit<GenericFieldElement<F>>,
+    pub unconstrained_functions: Vec<BrilligBytecode<GenericFieldElement<F>>>,
     pub state_len: usize,
-    pub external_inputs_len: usize,
 }
 
-impl<F: PrimeField> FCircuit<F> for NoirFCircuit<F> {
-    type Params = (PathOrBin, usize, usize);
-
-    fn new(params: Self::Params) -> Result<Self, Error> {
-        let (source, state_len, external_inputs_len) = params;
-        let program = match source {
-            PathOrBin::Path(path) => read_program_from_file(path),
-            PathOrBin::Bin(bytes) => read_program_from_binary(&bytes),
-        }
-        .map_err(|ee| Error::Other(format!("{:?}", ee)))?;
+impl<F: PrimeField, const L: usize> NoirFCircuit<F, L> {
+    /// Creates a NoirFCircuit from the already parsed compiled noir program.
+    pub fn from_program(
+        program: Program<GenericFieldElement<F>>,
+        state_len: usize,
+    ) -> Result<Self, Error> {
         let circuit: Circuit<GenericFieldElement<F>> = program.functions[0].clone();
+        blackbox::check_black_boxes(&circuit)?;
         let ivc_input_length = circuit.public_parameters.0.len();
         let ivc_return_length = circuit.return_values.0.len();
 
@@ -49,32 +61,63 @@ impl<F: PrimeField> FCircuit<F> for NoirFCircuit<F> {
             ));
         }
 
-        Ok(NoirFCircuit {
+        let f_circuit = NoirFCircuit {
             circuit,
+            unconstrained_functions: program.unconstrained_functions,
             state_len,
-            external_inputs_len,
-        })
+        };
+        let unconstrained_outputs = f_circuit.unconstrained_outputs();
+        if !unconstrained_outputs.is_empty() {
+            log::warn!(
+                "[WARNING]: the outputs {:?} of the noir circuit are computed by unconstrained \
+                 functions without being constrained, so the folding will not enforce them",
+                unconstrained_outputs
+            );
+        }
+        Ok(f_circuit)
     }
 
-    fn state_len(&self) -> usize {
-        self.state_len
+    /// Creates a NoirFCircuit from the in-memory compiled noir program (the json artifact
+    /// generated by `nargo compile`), without accessing the filesystem.
+    pub fn from_bytes(program: &[u8], state_len: usize) -> Result<Self, Error> {
+        Self::new((program.into(), state_len))
     }
 
-    fn external_inputs_len(&self) -> usize {
-        self.external_inputs_len
+    /// Creates a NoirFCircuit from the compiled noir program at the given path. Not available on
+    /// wasm32-unknown-unknown, where `from_bytes` must be used instead.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn from_path(path: impl AsRef<std::path::Path>, state_len: usize) -> Result<Self, Error> {
+        Self::from_bytes(&std::fs::read(path)?, state_len)
+    }
+}
+
+impl<F: PrimeField, const L: usize> FCircuit<F> for NoirFCircuit<F, L> {
+    type Params = (PathOrBin, usize);
+    type ExternalInputs = VecF<F, L>;
+    type ExternalInputsVar = VecFpVar<F, L>;
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        let (source, state_len) = params;
+        let program = read_program_from_binary(&read_path_or_bin(source)?)
+            .map_err(|ee| Error::Other(format!("{:?}", ee)))?;
+        Self::from_program(program, state_len)
+    }
+
+    fn state_len(&self) -> usize {
+        self.state_len
     }
 
     fn step_native(
         &self,
         _i: usize,
         z_i: Vec<F>,
-        external_inputs: Vec<F>, // inputs that are not part of the state
+        external_inputs: Self::ExternalInputs, // inputs that are not part of the state
     ) -> Result<Vec<F>, Error> {
         let mut acvm = ACVM::new(
             &StubbedBlackBoxSolver,
             &self.circuit.opcodes,
             WitnessMap::new(),
-            &[],
+            &self.unconstrained_functions,
             &[],
         );
 
@@ -99,7 +142,7 @@ impl<F: PrimeField> FCircuit<F> for NoirFCircuit<F> {
             .iter()
             .map(|witness| {
                 let idx = witness.as_usize() - z_i.len();
-                let value = external_inputs[idx].to_string();
+                let value = external_inputs.0[idx].to_string();
                 let f = GenericFieldElement::<F>::try_from_str(&value)
                     .ok_or(SynthesisError::Unsatisfiable)?;
                 acvm.overwrite_witness(AcvmWitness(witness.witness_index()), f);
@@ -132,13 +175,13 @@ impl<F: PrimeField> FCircuit<F> for NoirFCircuit<F> {
         cs: ConstraintSystemRef<F>,
         _i: usize,
         z_i: Vec<FpVar<F>>,
-        external_inputs: Vec<FpVar<F>>, // inputs that are not part of the state
+        external_inputs: Self::ExternalInputsVar, // inputs that are not part of the state
     ) -> Result<Vec<FpVar<F>>, SynthesisError> {
         let mut acvm = ACVM::new(
             &StubbedBlackBoxSolver,
             &self.circuit.opcodes,
             WitnessMap::new(),
-            &[],
+            &self.unconstrained_functions,
             &[],
         );
 
@@ -173,9 +216,9 @@ impl<F: PrimeField> FCircuit<F> for NoirFCircuit<F> {
             .map(|witness| {
                 let idx = witness.as_usize() - z_i.len();
                 let witness = AcvmWitness(witness.witness_index());
-                already_assigned_witness_values.insert(witness, &external_inputs[idx]);
+                already_assigned_witness_values.insert(witness, &external_inputs.0[idx]);
 
-                let val = external_inputs[idx].value()?;
+                let val = external_inputs.0[idx].value()?;
                 let value = if val == F::zero() {
                     "0".to_string()
                 } else {
@@ -207,8 +250,28 @@ impl<F: PrimeField> FCircuit<F> for NoirFCircuit<F> {
             })
             .collect::<Result<Vec<FpVar<F>>, SynthesisError>>()?;
 
-        // initialize circuit and set already assigned values
-        let mut acir_circuit = AcirCircuitSonobe::from((&self.circuit, witness_map));
+        // lower the black-box calls, whose input and output witnesses are assigned here so that
+        // the rest of the circuit uses the same variables
+        let black_box_vars = blackbox::allocate_black_box_witnesses(
+            cs.clone(),
+            &self.circuit,
+            &witness_map,
+            &already_assigned_witness_values,
+        )?;
+        already_assigned_witness_values.extend(black_box_vars.iter().map(|(w, var)| (*w, var)));
+        blackbox::generate_black_box_constraints(&self.circuit, &already_assigned_witness_values)?;
+
+        // initialize circuit and set already assigned values. The bridge only translates the
+        // ACIR constraints: the black-box calls are lowered above, and the Brillig calls only
+        // compute witness hints, which the ACVM has already assigned.
+        let mut circuit = self.circuit.clone();
+        circuit.opcodes.retain(|opcode| {
+            !matches!(
+                opcode,
+                Opcode::BlackBoxFuncCall(_) | Opcode::BrilligCall { .. }
+            )
+        });
+        let mut acir_circuit = AcirCircuitSonobe::from((&circuit, witness_map));
         acir_circuit.already_assigned_witnesses = already_assigned_witness_values;
 
         acir_circuit.generate_constraints(cs.clone())?;
@@ -217,10 +280,95 @@ impl<F: PrimeField> FCircuit<F> for NoirFCircuit<F> {
     }
 }
 
+impl<F: PrimeField, const L: usize> NoirFCircuit<F, L> {
+    /// Returns the positions of the outputs of the circuit (the z_{i+1} elements) that are
+    /// computed by unconstrained functions without being constrained by the circuit. The
+    /// witnesses that are only copied from the output of an unconstrained function are also
+    /// considered unconstrained.
+    pub fn unconstrained_outputs(&self) -> Vec<usize> {
+        let mut hinted = BTreeSet::new();
+        let mut constrained = BTreeSet::new();
+        let mut copies = vec![];
+        for opcode in self.circuit.opcodes.iter() {
+            match opcode {
+                Opcode::BrilligCall { outputs, .. } => {
+                    for output in outputs {
+                        match output {
+                            BrilligOutputs::Simple(witness) => {
+                                hinted.insert(*witness);
+                            }
+                            BrilligOutputs::Array(witnesses) => hinted.extend(witnesses),
+                        }
+                    }
+                }
+                Opcode::AssertZero(expr) => {
+                    if expr.mul_terms.is_empty() && expr.linear_combinations.len() == 2 {
+                        copies.push((expr.linear_combinations[0].1, expr.linear_combinations[1].1));
+                    } else {
+                        constrained.extend(expr.linear_combinations.iter().map(|(_, w)| *w));
+                        constrained
+                            .extend(expr.mul_terms.iter().flat_map(|(_, w_1, w_2)| [*w_1, *w_2]));
+                    }
+                }
+                Opcode::BlackBoxFuncCall(call) => {
+                    constrained.extend(call.get_inputs_vec().into_iter().filter_map(|input| {
+                        match input.input_ref() {
+                            ConstantOrWitnessEnum::Witness(witness) => Some(*witness),
+                            ConstantOrWitnessEnum::Constant(_) => None,
+                        }
+                    }));
+                    constrained.extend(call.get_outputs_vec());
+                }
+                // the memory and call opcodes are not inspected
+                _ => {}
+            }
+        }
+
+        // propagate the unconstrained witnesses through the copies
+        let is_free = |w: &AcvmWitness, hinted: &BTreeSet<AcvmWitness>| {
+            hinted.contains(w) && !constrained.contains(w)
+        };
+        loop {
+            let mut changed = false;
+            for (w_1, w_2) in copies.iter() {
+                if is_free(w_1, &hinted) && !hinted.contains(w_2) {
+                    changed |= hinted.insert(*w_2);
+                }
+                if is_free(w_2, &hinted) && !hinted.contains(w_1) {
+                    changed |= hinted.insert(*w_1);
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        self.circuit
+            .return_values
+            .0
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| is_free(w, &hinted))
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 pub fn load_noir_circuit<F: PrimeField>(
     path: String,
 ) -> Result<Circuit<GenericFieldElement<F>>, FilesystemError> {
-    let program: Program<GenericFieldElement<F>> = read_program_from_file(path)?;
+    let program: Program<GenericFieldElement<F>> =
+        noir_arkworks_backend::read_program_from_file(path)?;
+    let circuit: Circuit<GenericFieldElement<F>> = program.functions[0].clone();
+    Ok(circuit)
+}
+
+/// Loads the circuit from the in-memory compiled noir program.
+pub fn load_noir_circuit_from_bytes<F: PrimeField>(
+    program: &[u8],
+) -> Result<Circuit<GenericFieldElement<F>>, FilesystemError> {
+    let program: Program<GenericFieldElement<F>> = read_program_from_binary(program)?;
     let circuit: Circuit<GenericFieldElement<F>> = program.functions[0].clone();
     Ok(circuit)
 }
@@ -228,11 +376,21 @@ pub fn load_noir_circuit<F: PrimeField>(
 #[cfg(test)]
 mod tests {
     use crate::noir::load_noir_circuit;
-    use ark_bn254::Fr;
+    use acvm::acir::{acir_field::GenericFieldElement, circuit::Program};
+    use ark_bn254::{constraints::GVar, Fr, G1Projective as Projective};
+    use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
     use ark_r1cs_std::R1CSVar;
     use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar};
     use ark_relations::r1cs::ConstraintSystem;
-    use folding_schemes::frontend::FCircuit;
+    use folding_schemes::{
+        commitment::pedersen::Pedersen,
+        folding::nova::{Nova, PreprocessorParam},
+        frontend::{FCircuit, VecF, VecFpVar},
+        transcript::poseidon::poseidon_canonical_config,
+        FoldingScheme,
+    };
+    use noir_arkworks_backend::read_program_from_binary;
+    use sha2::{Digest, Sha256};
     use std::env;
 
     use crate::noir::NoirFCircuit;
@@ -245,13 +403,13 @@ mod tests {
             cur_path.to_str().unwrap()
         );
         let circuit = load_noir_circuit(circuit_path).unwrap();
-        let noirfcircuit = NoirFCircuit {
+        let noirfcircuit = NoirFCircuit::<Fr, 2> {
             circuit,
+            unconstrained_functions: vec![],
             state_len: 2,
-            external_inputs_len: 2,
         };
         let inputs = vec![Fr::from(2), Fr::from(5)];
-        let res = noirfcircuit.step_native(0, inputs.clone(), inputs);
+        let res = noirfcircuit.step_native(0, inputs.clone(), VecF(inputs));
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), vec![Fr::from(4), Fr::from(25)]);
     }
@@ -265,14 +423,14 @@ mod tests {
             cur_path.to_str().unwrap()
         );
         let circuit = load_noir_circuit(circuit_path).unwrap();
-        let noirfcircuit = NoirFCircuit {
+        let noirfcircuit = NoirFCircuit::<Fr, 2> {
             circuit,
+            unconstrained_functions: vec![],
             state_len: 2,
-            external_inputs_len: 2,
         };
         let inputs = vec![Fr::from(2), Fr::from(5)];
         let z_i = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(inputs.clone())).unwrap();
-        let external_inputs = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(inputs)).unwrap();
+        let external_inputs = VecFpVar::new_witness(cs.clone(), || Ok(VecF(inputs))).unwrap();
         let output = noirfcircuit
             .generate_step_constraints(cs.clone(), 0, z_i, external_inputs)
             .unwrap();
@@ -289,18 +447,127 @@ mod tests {
             cur_path.to_str().unwrap()
         );
         let circuit = load_noir_circuit(circuit_path).unwrap();
-        let noirfcircuit = NoirFCircuit {
+        let noirfcircuit = NoirFCircuit::<Fr, 0> {
             circuit,
+            unconstrained_functions: vec![],
             state_len: 2,
-            external_inputs_len: 0,
         };
         let inputs = vec![Fr::from(2), Fr::from(5)];
         let z_i = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(inputs.clone())).unwrap();
-        let external_inputs = vec![];
+        let external_inputs = VecFpVar(vec![]);
         let output = noirfcircuit
             .generate_step_constraints(cs.clone(), 0, z_i, external_inputs)
             .unwrap();
         assert_eq!(output[0].value().unwrap(), Fr::from(4));
         assert_eq!(output[1].value().unwrap(), Fr::from(25));
     }
+
+    #[test]
+    fn test_from_bytes() {
+        let program = include_bytes!("./test_folder/test_circuit/target/test_circuit.json");
+        let noirfcircuit = NoirFCircuit::<Fr, 2>::from_bytes(program, 2).unwrap();
+        let inputs = vec![Fr::from(2), Fr::from(5)];
+        let res = noirfcircuit.step_native(0, inputs.clone(), VecF(inputs));
+        assert_eq!(res.unwrap(), vec![Fr::from(4), Fr::from(25)]);
+    }
+
+    // Tests folding a noir circuit that hashes its state with sha256, whose black-box calls are
+    // lowered to R1CS by the frontend.
+    #[test]
+    fn test_fold_sha256() {
+        let cur_path = env::current_dir().unwrap();
+        let circuit_path = format!(
+            "{}/src/noir/test_folder/test_sha256/target/test_sha256.json",
+            cur_path.to_str().unwrap()
+        );
+        let f_circuit = NoirFCircuit::<Fr, 0>::from_path(circuit_path, 8).unwrap();
+
+        let sha256 = |state: &[u32]| -> Vec<u32> {
+            let bytes: Vec<u8> = state.iter().flat_map(|w| w.to_be_bytes()).collect();
+            Sha256::digest(bytes)
+                .chunks(4)
+                .map(|w| u32::from_be_bytes([w[0], w[1], w[2], w[3]]))
+                .collect()
+        };
+        let to_fr = |state: &[u32]| state.iter().map(|w| Fr::from(*w)).collect::<Vec<Fr>>();
+        let z_0: Vec<u32> = (0..8).collect();
+        let z_1 = sha256(&z_0);
+        let z_2 = sha256(&z_1);
+
+        let z_1_native = f_circuit.step_native(0, to_fr(&z_0), VecF(vec![])).unwrap();
+        assert_eq!(z_1_native, to_fr(&z_1));
+
+        let state = fold(f_circuit, to_fr(&z_0), vec![VecF(vec![]); 2]);
+        assert_eq!(state, to_fr(&z_2));
+    }
+
+    // folds the given circuit with Nova, one step per external input, returning the last state
+    fn fold<const L: usize>(
+        f_circuit: NoirFCircuit<Fr, L>,
+        z_0: Vec<Fr>,
+        external_inputs: Vec<VecF<Fr, L>>,
+    ) -> Vec<Fr> {
+        type N<const L: usize> = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            NoirFCircuit<Fr, L>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let nova_params = N::<L>::preprocess(
+            &mut rng,
+            &PreprocessorParam::new(poseidon_config, f_circuit.clone()),
+        )
+        .unwrap();
+        let mut nova = N::<L>::init(&nova_params, f_circuit, z_0).unwrap();
+        for external_inputs_i in external_inputs {
+            nova.prove_step(&mut rng, external_inputs_i, None).unwrap();
+        }
+        N::<L>::verify(nova_params.1, nova.ivc_proof()).unwrap();
+        nova.state().z_i.to_vec()
+    }
+
+    // Tests folding a noir circuit that computes a witness hint with an unconstrained function.
+    #[test]
+    fn test_fold_brillig_hint() {
+        let cur_path = env::current_dir().unwrap();
+        let program = std::fs::read(format!(
+            "{}/src/noir/test_folder/test_brillig_hint/target/test_brillig_hint.json",
+            cur_path.to_str().unwrap()
+        ))
+        .unwrap();
+        // build the circuit from the parsed program
+        let program: Program<GenericFieldElement<Fr>> = read_program_from_binary(&program).unwrap();
+        let f_circuit = NoirFCircuit::<Fr, 1>::from_program(program, 1).unwrap();
+        assert!(!f_circuit.unconstrained_functions.is_empty());
+        assert!(f_circuit.unconstrained_outputs().is_empty());
+
+        // 12 / 3 + 1 = 5, 5 / 5 + 1 = 2
+        let z_1 = f_circuit
+            .step_native(0, vec![Fr::from(12)], VecF(vec![Fr::from(3)]))
+            .unwrap();
+        assert_eq!(z_1, vec![Fr::from(5)]);
+        let state = fold(
+            f_circuit,
+            vec![Fr::from(12)],
+            vec![VecF(vec![Fr::from(3)]), VecF(vec![Fr::from(5)])],
+        );
+        assert_eq!(state, vec![Fr::from(2)]);
+    }
+
+    #[test]
+    fn test_unconstrained_outputs() {
+        let cur_path = env::current_dir().unwrap();
+        let circuit_path = format!(
+            "{}/src/noir/test_folder/test_unconstrained_output/target/test_unconstrained_output.json",
+            cur_path.to_str().unwrap()
+        );
+        let f_circuit = NoirFCircuit::<Fr, 0>::from_path(circuit_path, 1).unwrap();
+        assert_eq!(f_circuit.unconstrained_outputs(), vec![0]);
+    }
 }
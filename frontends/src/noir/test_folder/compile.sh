@@ -1,7 +1,7 @@
 #!/bin/bash
 CUR_DIR=$(pwd)
 TEST_PATH="${CUR_DIR}/frontends/src/noir/test_folder/"
-for test_path in test_circuit test_mimc test_no_external_inputs; do
+for test_path in test_circuit test_mimc test_no_external_inputs test_sha256 test_brillig_hint test_unconstrained_output; do
 	FOLDER="${TEST_PATH}${test_path}/"
 	cd ${FOLDER} && nargo compile && cd ${TEST_PATH}
 done
@@ -1,74 +1,134 @@
 use ark_noname::sonobe::NonameSonobeCircuit;
 use ark_r1cs_std::alloc::AllocVar;
 use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::R1CSVar;
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
 use num_bigint::BigUint;
 use std::marker::PhantomData;
 
-use self::utils::NonameInputs;
+use self::utils::{NonameInputs, NonameSignature};
 
 use ark_ff::PrimeField;
 use ark_noname::utils::compile_source_code;
-use folding_schemes::{frontend::FCircuit, Error};
+use folding_schemes::{
+    frontend::{FCircuit, VecF, VecFpVar},
+    Error,
+};
 use noname::backends::{r1cs::R1CS as R1CSNoname, BackendField};
 use noname::witness::CompiledCircuit;
 pub mod utils;
+
+/// Parameters of a NonameFCircuit.
+#[derive(Debug, Clone)]
+pub struct NonameFCircuitParams {
+    /// source code of the noname circuit
+    pub code: String,
+    /// field of the struct returned by `main` that contains the next state, or `None` if `main`
+    /// returns the next state directly
+    pub next_state_field: Option<String>,
+}
+
+impl From<String> for NonameFCircuitParams {
+    fn from(code: String) -> Self {
+        Self {
+            code,
+            next_state_field: None,
+        }
+    }
+}
+
+/// NonameFCircuit wraps a compiled noname circuit, where `L` is the number of external inputs.
+///
+/// The public argument of the `main` function is the state, and its private arguments (`Field`s or
+/// arrays of `Field`s) are the external inputs, which are read in order from the `L` external
+/// inputs of the step.
 #[derive(Debug, Clone)]
-pub struct NonameFCircuit<F: PrimeField, BF: BackendField> {
-    pub state_len: usize,
-    pub external_inputs_len: usize,
+pub struct NonameFCircuit<F: PrimeField, BF: BackendField, const L: usize> {
+    pub signature: NonameSignature,
     pub circuit: CompiledCircuit<R1CSNoname<BF>>,
     _f: PhantomData<F>,
 }
 
-impl<F: PrimeField, BF: BackendField> FCircuit<F> for NonameFCircuit<F, BF> {
-    type Params = (String, usize, usize);
+impl<F: PrimeField, BF: BackendField, const L: usize> NonameFCircuit<F, BF, L> {
+    /// Creates a NonameFCircuit from the in-memory source code of the noname circuit, without
+    /// accessing the filesystem.
+    pub fn from_bytes(code: &[u8]) -> Result<Self, Error> {
+        let code = String::from_utf8(code.to_vec())
+            .map_err(|e| Error::Other(format!("noname source code is not valid UTF-8: {}", e)))?;
+        Self::new(code.into())
+    }
+
+    /// Creates a NonameFCircuit from the source code of the noname circuit at the given path. Not
+    /// available on wasm32-unknown-unknown, where `from_bytes` must be used instead.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    // reads the next state from the outputs of the noname witness
+    fn next_state<T: Clone>(&self, outputs: &[T]) -> Vec<T> {
+        let offset = self.signature.next_state_offset;
+        outputs[offset..offset + self.signature.state.len].to_vec()
+    }
+}
+
+impl<F: PrimeField, BF: BackendField, const L: usize> FCircuit<F> for NonameFCircuit<F, BF, L> {
+    type Params = NonameFCircuitParams;
+    type ExternalInputs = VecF<F, L>;
+    type ExternalInputsVar = VecFpVar<F, L>;
 
     fn new(params: Self::Params) -> Result<Self, Error> {
-        let (code, state_len, external_inputs_len) = params;
-        let compiled_circuit = compile_source_code::<BF>(&code).map_err(|_| {
+        let signature = NonameSignature::parse(&params.code, params.next_state_field.as_deref())?;
+        if signature.external_inputs_len() != L {
+            let names: Vec<String> = signature
+                .external_inputs
+                .iter()
+                .map(|arg| format!("`{}`", arg.name))
+                .collect();
+            return Err(Error::NotSameLength(
+                format!("noname private arguments [{}]", names.join(", ")),
+                signature.external_inputs_len(),
+                "external inputs".to_string(),
+                L,
+            ));
+        }
+        let compiled_circuit = compile_source_code::<BF>(&params.code).map_err(|_| {
             Error::Other("Encountered an error while compiling a noname circuit".to_owned())
         })?;
         Ok(NonameFCircuit {
-            state_len,
-            external_inputs_len,
+            signature,
             circuit: compiled_circuit,
             _f: PhantomData,
         })
     }
 
     fn state_len(&self) -> usize {
-        self.state_len
-    }
-
-    fn external_inputs_len(&self) -> usize {
-        self.external_inputs_len
+        self.signature.state.len
     }
 
     fn step_native(
         &self,
         _i: usize,
         z_i: Vec<F>,
-        external_inputs: Vec<F>,
+        external_inputs: Self::ExternalInputs,
     ) -> Result<Vec<F>, Error> {
         let wtns_external_inputs =
-            NonameInputs::from((&external_inputs, "external_inputs".to_string()));
-        let wtns_ivc_inputs = NonameInputs::from((&z_i, "ivc_inputs".to_string()));
+            NonameInputs::from_args(&self.signature.external_inputs, &external_inputs.0);
+        let wtns_ivc_inputs = NonameInputs::from_args(&[self.signature.state.clone()], &z_i);
 
         let noname_witness = self
             .circuit
             .generate_witness(wtns_ivc_inputs.0, wtns_external_inputs.0)
             .map_err(|e| Error::WitnessCalculationError(e.to_string()))?;
 
-        let z_i1_end_index = z_i.len() + 1;
-        let assigned_z_i1 = (1..z_i1_end_index)
+        let outputs: Vec<F> = (1..=self.signature.return_len)
             .map(|idx| {
                 let value: BigUint = Into::into(noname_witness.witness[idx]);
                 F::from(value)
             })
             .collect();
 
-        Ok(assigned_z_i1)
+        Ok(self.next_state(&outputs))
     }
 
     fn generate_step_constraints(
@@ -76,22 +136,30 @@ impl<F: PrimeField, BF: BackendField> FCircuit<F> for NonameFCircuit<F, BF> {
         cs: ConstraintSystemRef<F>,
         _i: usize,
         z_i: Vec<FpVar<F>>,
-        external_inputs: Vec<FpVar<F>>,
+        external_inputs: Self::ExternalInputsVar,
     ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let external_inputs_values = external_inputs
+            .0
+            .iter()
+            .map(R1CSVar::value)
+            .collect::<Result<Vec<F>, SynthesisError>>()?;
+        let z_i_values = z_i
+            .iter()
+            .map(R1CSVar::value)
+            .collect::<Result<Vec<F>, SynthesisError>>()?;
         let wtns_external_inputs =
-            NonameInputs::from_fpvars((&external_inputs, "external_inputs".to_string()))?;
-        let wtns_ivc_inputs = NonameInputs::from_fpvars((&z_i, "ivc_inputs".to_string()))?;
+            NonameInputs::from_args(&self.signature.external_inputs, &external_inputs_values);
+        let wtns_ivc_inputs = NonameInputs::from_args(&[self.signature.state.clone()], &z_i_values);
         let noname_witness = self
             .circuit
             .generate_witness(wtns_ivc_inputs.0, wtns_external_inputs.0)
             .map_err(|_| SynthesisError::Unsatisfiable)?;
-        let z_i1_end_index = z_i.len() + 1;
-        let assigned_z_i1: Vec<FpVar<F>> = (1..z_i1_end_index)
+        let assigned_outputs: Vec<FpVar<F>> = (1..=self.signature.return_len)
             .map(|idx| -> Result<FpVar<F>, SynthesisError> {
-                // the assigned zi1 is of the same size than the initial zi and is located in the
-                // output of the witness vector
-                // we prefer to assign z_i1 here since (1) we have to return it, (2) we cant return
-                // anything with the `generate_constraints` method used below
+                // all the public outputs of the circuit are located after the constant 1 in the
+                // witness vector, and the next state is a slice of them.
+                // we prefer to assign them here since (1) we have to return z_i1, (2) we cant
+                // return anything with the `generate_constraints` method used below
                 let value: BigUint = Into::into(noname_witness.witness[idx]);
                 let field_element = F::from(value);
                 FpVar::<F>::new_witness(cs.clone(), || Ok(field_element))
@@ -102,25 +170,32 @@ impl<F: PrimeField, BF: BackendField> FCircuit<F> for NonameFCircuit<F, BF> {
             compiled_circuit: self.circuit.clone(),
             witness: noname_witness,
             assigned_z_i: &z_i,
-            assigned_external_inputs: &external_inputs,
-            assigned_z_i1: &assigned_z_i1,
+            assigned_external_inputs: &external_inputs.0,
+            assigned_z_i1: &assigned_outputs,
         };
         noname_circuit.generate_constraints(cs.clone())?;
 
-        Ok(assigned_z_i1)
+        Ok(self.next_state(&assigned_outputs))
     }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use ark_bn254::Fr;
+    use ark_bn254::{constraints::GVar, Fr, G1Projective as Projective};
+    use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
     use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, R1CSVar};
     use noname::backends::r1cs::R1csBn254Field;
 
-    use folding_schemes::frontend::FCircuit;
+    use folding_schemes::{
+        commitment::pedersen::Pedersen,
+        folding::nova::{Nova, PreprocessorParam},
+        frontend::{FCircuit, VecF, VecFpVar},
+        transcript::poseidon::poseidon_canonical_config,
+        Error, FoldingScheme,
+    };
 
-    use super::NonameFCircuit;
+    use super::{NonameFCircuit, NonameFCircuitParams};
     use ark_relations::r1cs::ConstraintSystem;
 
     const NONAME_CIRCUIT_EXTERNAL_INPUTS: &str =
@@ -137,18 +212,66 @@ mod tests {
     return [out, ivc_inputs[1]];
 }";
 
+    const NONAME_CIRCUIT_NAMED_EXTERNAL_INPUTS: &str =
+        "fn main(pub ivc_inputs: [Field; 2], aa: Field, bb: Field) -> [Field; 2] {
+    let xx = ivc_inputs[0] + aa;
+    let yy = ivc_inputs[1] * bb;
+    return [xx, yy];
+}";
+
+    const NONAME_CIRCUIT_STRUCT_OUTPUT: &str = "struct Output {
+    sum: Field,
+    next_state: [Field; 2],
+}
+
+fn main(pub ivc_inputs: [Field; 2], aa: Field) -> Output {
+    let sum = ivc_inputs[0] + ivc_inputs[1];
+    return Output { sum: sum, next_state: [ivc_inputs[1], sum * aa] };
+}";
+
+    // folds the given circuit with Nova, one step per external input, returning the last state
+    fn fold<const L: usize>(
+        f_circuit: NonameFCircuit<Fr, R1csBn254Field, L>,
+        z_0: Vec<Fr>,
+        external_inputs: Vec<VecF<Fr, L>>,
+    ) -> Vec<Fr> {
+        type N<const L: usize> = Nova<
+            Projective,
+            GVar,
+            Projective2,
+            GVar2,
+            NonameFCircuit<Fr, R1csBn254Field, L>,
+            Pedersen<Projective>,
+            Pedersen<Projective2>,
+            false,
+        >;
+        let mut rng = ark_std::test_rng();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let nova_params = N::<L>::preprocess(
+            &mut rng,
+            &PreprocessorParam::new(poseidon_config, f_circuit.clone()),
+        )
+        .unwrap();
+        let mut nova = N::<L>::init(&nova_params, f_circuit, z_0).unwrap();
+        for external_inputs_i in external_inputs {
+            nova.prove_step(&mut rng, external_inputs_i, None).unwrap();
+        }
+        N::<L>::verify(nova_params.1, nova.ivc_proof()).unwrap();
+        nova.state().z_i.to_vec()
+    }
+
     #[test]
     fn test_step_native() {
         let cs = ConstraintSystem::<Fr>::new_ref();
-        let params = (NONAME_CIRCUIT_EXTERNAL_INPUTS.to_owned(), 2, 2);
-        let circuit = NonameFCircuit::<Fr, R1csBn254Field>::new(params).unwrap();
+        let params = NONAME_CIRCUIT_EXTERNAL_INPUTS.to_owned().into();
+        let circuit = NonameFCircuit::<Fr, R1csBn254Field, 2>::new(params).unwrap();
         let inputs_public = vec![Fr::from(2), Fr::from(5)];
-        let inputs_private = vec![Fr::from(8), Fr::from(2)];
+        let inputs_private = VecF(vec![Fr::from(8), Fr::from(2)]);
 
         let ivc_inputs_var =
             Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(inputs_public.clone())).unwrap();
         let external_inputs_var =
-            Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(inputs_private.clone())).unwrap();
+            VecFpVar::new_witness(cs.clone(), || Ok(inputs_private.clone())).unwrap();
 
         let z_i1 = circuit
             .generate_step_constraints(cs.clone(), 0, ivc_inputs_var, external_inputs_var)
@@ -164,15 +287,14 @@ mod tests {
     #[test]
     fn test_step_constraints() {
         let cs = ConstraintSystem::<Fr>::new_ref();
-        let params = (NONAME_CIRCUIT_EXTERNAL_INPUTS.to_owned(), 2, 2);
-        let circuit = NonameFCircuit::<Fr, R1csBn254Field>::new(params).unwrap();
+        let params = NONAME_CIRCUIT_EXTERNAL_INPUTS.to_owned().into();
+        let circuit = NonameFCircuit::<Fr, R1csBn254Field, 2>::new(params).unwrap();
         let inputs_public = vec![Fr::from(2), Fr::from(5)];
-        let inputs_private = vec![Fr::from(8), Fr::from(2)];
+        let inputs_private = VecF(vec![Fr::from(8), Fr::from(2)]);
 
         let ivc_inputs_var =
             Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(inputs_public)).unwrap();
-        let external_inputs_var =
-            Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(inputs_private)).unwrap();
+        let external_inputs_var = VecFpVar::new_witness(cs.clone(), || Ok(inputs_private)).unwrap();
 
         let z_i1 = circuit
             .generate_step_constraints(cs.clone(), 0, ivc_inputs_var, external_inputs_var)
@@ -185,16 +307,102 @@ mod tests {
     #[test]
     fn test_generate_constraints_no_external_inputs() {
         let cs = ConstraintSystem::<Fr>::new_ref();
-        let params = (NONAME_CIRCUIT_NO_EXTERNAL_INPUTS.to_owned(), 2, 0);
+        let params = NONAME_CIRCUIT_NO_EXTERNAL_INPUTS.to_owned().into();
         let inputs_public = vec![Fr::from(2), Fr::from(5)];
 
         let ivc_inputs_var =
             Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(inputs_public)).unwrap();
 
-        let f_circuit = NonameFCircuit::<Fr, R1csBn254Field>::new(params).unwrap();
+        let f_circuit = NonameFCircuit::<Fr, R1csBn254Field, 0>::new(params).unwrap();
         f_circuit
-            .generate_step_constraints(cs.clone(), 0, ivc_inputs_var, vec![])
+            .generate_step_constraints(cs.clone(), 0, ivc_inputs_var, VecFpVar(vec![]))
             .unwrap();
         assert!(cs.is_satisfied().unwrap());
     }
+
+    #[test]
+    fn test_from_bytes() {
+        let circuit = NonameFCircuit::<Fr, R1csBn254Field, 0>::from_bytes(
+            NONAME_CIRCUIT_NO_EXTERNAL_INPUTS.as_bytes(),
+        )
+        .unwrap();
+        let z_i1 = circuit
+            .step_native(0, vec![Fr::from(2), Fr::from(5)], VecF(vec![]))
+            .unwrap();
+        assert_eq!(z_i1, vec![Fr::from(10), Fr::from(5)]);
+
+        assert!(NonameFCircuit::<Fr, R1csBn254Field, 0>::from_bytes(&[0xff, 0xfe]).is_err());
+    }
+
+    #[test]
+    fn test_fold_named_external_inputs() {
+        let f_circuit = NonameFCircuit::<Fr, R1csBn254Field, 2>::new(
+            NONAME_CIRCUIT_NAMED_EXTERNAL_INPUTS.to_owned().into(),
+        )
+        .unwrap();
+        assert_eq!(f_circuit.state_len(), 2);
+
+        let external_inputs = vec![
+            VecF(vec![Fr::from(1), Fr::from(2)]),
+            VecF(vec![Fr::from(3), Fr::from(4)]),
+            VecF(vec![Fr::from(5), Fr::from(6)]),
+        ];
+        let state = fold(f_circuit, vec![Fr::from(2), Fr::from(5)], external_inputs);
+        // [2, 5] -> [3, 10] -> [6, 40] -> [11, 240]
+        assert_eq!(state, vec![Fr::from(11), Fr::from(240)]);
+    }
+
+    #[test]
+    fn test_struct_output() {
+        let params = NonameFCircuitParams {
+            code: NONAME_CIRCUIT_STRUCT_OUTPUT.to_owned(),
+            next_state_field: Some("next_state".to_string()),
+        };
+        let f_circuit = NonameFCircuit::<Fr, R1csBn254Field, 1>::new(params).unwrap();
+
+        let z_i = vec![Fr::from(2), Fr::from(5)];
+        let external_inputs = VecF(vec![Fr::from(3)]);
+        let z_i1 = f_circuit
+            .step_native(0, z_i.clone(), external_inputs.clone())
+            .unwrap();
+        assert_eq!(z_i1, vec![Fr::from(5), Fr::from(21)]);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let external_inputs_var =
+            VecFpVar::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+        let z_i1_var = f_circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, external_inputs_var)
+            .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+
+        let state = fold(
+            f_circuit,
+            vec![Fr::from(2), Fr::from(5)],
+            vec![VecF(vec![Fr::from(3)]), VecF(vec![Fr::from(1)])],
+        );
+        // [2, 5] -> [5, 21] -> [21, 26]
+        assert_eq!(state, vec![Fr::from(21), Fr::from(26)]);
+    }
+
+    #[test]
+    fn test_external_inputs_arity_mismatch() {
+        let result = NonameFCircuit::<Fr, R1csBn254Field, 3>::new(
+            NONAME_CIRCUIT_NAMED_EXTERNAL_INPUTS.to_owned().into(),
+        );
+        match result {
+            Err(Error::NotSameLength(args, 2, _, 3)) => {
+                assert!(args.contains("`aa`") && args.contains("`bb`"))
+            }
+            other => panic!("expected an arity mismatch, got {:?}", other.map(|_| ())),
+        }
+
+        // the next state field must exist in the returned struct
+        let params = NonameFCircuitParams {
+            code: NONAME_CIRCUIT_STRUCT_OUTPUT.to_owned(),
+            next_state_field: Some("state".to_string()),
+        };
+        assert!(NonameFCircuit::<Fr, R1csBn254Field, 1>::new(params).is_err());
+    }
 }
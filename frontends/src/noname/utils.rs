@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use ark_ff::PrimeField;
 use ark_r1cs_std::{fields::fp::FpVar, R1CSVar};
 use ark_relations::r1cs::SynthesisError;
+use folding_schemes::Error;
 use noname::inputs::JsonInputs;
 use serde_json::json;
 
@@ -56,3 +57,275 @@ impl NonameInputs {
         }
     }
 }
+
+impl NonameInputs {
+    /// Assigns the given values to the arguments, in order, where the arguments of size 1 are
+    /// `Field`s and the rest are arrays of `Field`s.
+    pub fn from_args<F: PrimeField>(args: &[NonameArg], values: &[F]) -> Self {
+        let to_string = |value: &F| {
+            if value.is_zero() {
+                "0".to_string()
+            } else {
+                value.to_string()
+            }
+        };
+        let mut inputs = HashMap::new();
+        let mut offset = 0;
+        for arg in args {
+            let arg_values = &values[offset..offset + arg.len];
+            offset += arg.len;
+            let value = if arg.is_array {
+                json!(arg_values.iter().map(to_string).collect::<Vec<String>>())
+            } else {
+                json!(to_string(&arg_values[0]))
+            };
+            inputs.insert(arg.name.clone(), value);
+        }
+        NonameInputs(JsonInputs(inputs))
+    }
+}
+
+/// Argument of the `main` function of a noname circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NonameArg {
+    pub name: String,
+    pub public: bool,
+    /// whether the argument is an array of `Field`s or a single `Field`
+    pub is_array: bool,
+    /// number of field elements of the argument
+    pub len: usize,
+}
+
+/// Signature of the `main` function of a noname circuit, where the public argument is the state
+/// and the private arguments are the external inputs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NonameSignature {
+    pub state: NonameArg,
+    pub external_inputs: Vec<NonameArg>,
+    /// number of field elements of the returned value
+    pub return_len: usize,
+    /// position of the next state in the flattened returned value
+    pub next_state_offset: usize,
+}
+
+impl NonameSignature {
+    /// Parses the signature of the `main` function from the source code of the noname circuit,
+    /// where `next_state_field` is the field of the returned struct that contains the next state,
+    /// or `None` if the next state is returned directly.
+    pub fn parse(code: &str, next_state_field: Option<&str>) -> Result<Self, Error> {
+        let err = |msg: String| Error::Other(format!("noname signature: {}", msg));
+        let start = code
+            .find("fn main(")
+            .ok_or_else(|| err("function `main` not found".to_string()))?
+            + "fn main(".len();
+        let end = start
+            + code[start..]
+                .find(')')
+                .ok_or_else(|| err("unclosed arguments of `main`".to_string()))?;
+        let body = start
+            + code[start..]
+                .find('{')
+                .ok_or_else(|| err("missing body of `main`".to_string()))?;
+        let structs = parse_structs(code);
+
+        let mut state = None;
+        let mut external_inputs = vec![];
+        for arg in code[start..end].split(',').map(str::trim) {
+            if arg.is_empty() {
+                continue;
+            }
+            let (public, arg) = match arg.strip_prefix("pub ") {
+                Some(arg) => (true, arg.trim()),
+                None => (false, arg),
+            };
+            let (name, ty) = arg
+                .split_once(':')
+                .ok_or_else(|| err(format!("argument `{}` has no type", arg)))?;
+            let (name, ty) = (name.trim().to_string(), ty.trim());
+            let len = match ty {
+                "Field" => 1,
+                _ => parse_field_array(ty).ok_or_else(|| {
+                    err(format!(
+                        "argument `{}` has type `{}`, but only `Field` and `[Field; N]` are \
+                         supported",
+                        name, ty
+                    ))
+                })?,
+            };
+            let arg = NonameArg {
+                name,
+                public,
+                is_array: ty != "Field",
+                len,
+            };
+            if !public {
+                external_inputs.push(arg);
+            } else if state.is_none() {
+                state = Some(arg);
+            } else {
+                return Err(err(format!(
+                    "argument `{}` is public, but only the state can be public",
+                    arg.name
+                )));
+            }
+        }
+        let state = state.ok_or_else(|| err("missing public state argument".to_string()))?;
+
+        let return_ty = code[end + 1..body]
+            .trim()
+            .strip_prefix("->")
+            .ok_or_else(|| err("`main` does not return the next state".to_string()))?
+            .trim();
+        let return_len = type_len(return_ty, &structs)
+            .ok_or_else(|| err(format!("unsupported return type `{}`", return_ty)))?;
+        let (next_state_offset, next_state_len) = match next_state_field {
+            None => (0, return_len),
+            Some(field) => {
+                let fields = structs.get(return_ty).ok_or_else(|| {
+                    err(format!(
+                        "the next state is the field `{}`, but `main` returns `{}`, which is \
+                         not a struct",
+                        field, return_ty
+                    ))
+                })?;
+                let mut offset = 0;
+                let mut next_state = None;
+                for (name, ty) in fields {
+                    let len = type_len(ty, &structs)
+                        .ok_or_else(|| err(format!("unsupported type `{}`", ty)))?;
+                    if name == field {
+                        next_state = Some((offset, len));
+                    }
+                    offset += len;
+                }
+                next_state.ok_or_else(|| {
+                    err(format!("struct `{}` has no field `{}`", return_ty, field))
+                })?
+            }
+        };
+        if next_state_len != state.len {
+            return Err(Error::NotSameLength(
+                format!("state argument `{}`", state.name),
+                state.len,
+                "next state".to_string(),
+                next_state_len,
+            ));
+        }
+
+        Ok(Self {
+            state,
+            external_inputs,
+            return_len,
+            next_state_offset,
+        })
+    }
+
+    /// Number of field elements of the external inputs.
+    pub fn external_inputs_len(&self) -> usize {
+        self.external_inputs.iter().map(|arg| arg.len).sum()
+    }
+}
+
+// Returns N for the types `[Field; N]`.
+fn parse_field_array(ty: &str) -> Option<usize> {
+    let (elem, len) = ty.strip_prefix('[')?.strip_suffix(']')?.split_once(';')?;
+    if elem.trim() != "Field" {
+        return None;
+    }
+    len.trim().parse().ok()
+}
+
+// Returns the number of field elements of the type, which can be a `Field`, an array or a struct.
+fn type_len(ty: &str, structs: &HashMap<String, Vec<(String, String)>>) -> Option<usize> {
+    let ty = ty.trim();
+    if ty == "Field" {
+        return Some(1);
+    }
+    if let Some(array) = ty.strip_prefix('[').and_then(|ty| ty.strip_suffix(']')) {
+        let (elem, len) = array.rsplit_once(';')?;
+        return Some(type_len(elem, structs)? * len.trim().parse::<usize>().ok()?);
+    }
+    structs
+        .get(ty)?
+        .iter()
+        .map(|(_, field_ty)| type_len(field_ty, structs))
+        .sum()
+}
+
+// Parses the struct definitions of the source code, returning the fields of each struct.
+fn parse_structs(code: &str) -> HashMap<String, Vec<(String, String)>> {
+    let mut structs = HashMap::new();
+    let mut rest = code;
+    while let Some(start) = rest.find("struct ") {
+        rest = &rest[start + "struct ".len()..];
+        let (Some(open), Some(close)) = (rest.find('{'), rest.find('}')) else {
+            break;
+        };
+        if open > close {
+            continue;
+        }
+        let name = rest[..open].trim().to_string();
+        let fields = rest[open + 1..close]
+            .split(',')
+            .filter_map(|field| {
+                let (field_name, ty) = field.split_once(':')?;
+                Some((field_name.trim().to_string(), ty.trim().to_string()))
+            })
+            .collect();
+        structs.insert(name, fields);
+        rest = &rest[close + 1..];
+    }
+    structs
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_signature() {
+        let code = "struct Output {
+    aux: Field,
+    state: [Field; 2],
+}
+
+fn main(pub ivc_inputs: [Field; 2], aa: Field, bb: [Field; 3]) -> Output {
+    return Output { aux: aa, state: ivc_inputs };
+}";
+        let signature = NonameSignature::parse(code, Some("state")).unwrap();
+        assert_eq!(signature.state.name, "ivc_inputs");
+        assert_eq!(signature.state.len, 2);
+        assert_eq!(
+            signature
+                .external_inputs
+                .iter()
+                .map(|arg| (arg.name.as_str(), arg.is_array, arg.len))
+                .collect::<Vec<_>>(),
+            vec![("aa", false, 1), ("bb", true, 3)]
+        );
+        assert_eq!(signature.external_inputs_len(), 4);
+        assert_eq!(signature.return_len, 3);
+        assert_eq!(signature.next_state_offset, 1);
+
+        // the returned value is a struct, so the field of the next state is needed
+        assert!(NonameSignature::parse(code, None).is_err());
+        assert!(NonameSignature::parse(code, Some("aux")).is_err());
+    }
+
+    #[test]
+    fn test_parse_signature_errors() {
+        // the error names the offending argument
+        let code = "fn main(pub ivc_inputs: [Field; 2], cc: Bool) -> [Field; 2] {}";
+        match NonameSignature::parse(code, None) {
+            Err(Error::Other(msg)) => assert!(msg.contains("`cc`")),
+            other => panic!("expected an error, got {:?}", other),
+        }
+        let code = "fn main(pub ivc_inputs: [Field; 2], pub other: Field) -> [Field; 2] {}";
+        match NonameSignature::parse(code, None) {
+            Err(Error::Other(msg)) => assert!(msg.contains("`other`")),
+            other => panic!("expected an error, got {:?}", other),
+        }
+        let code = "fn main(pub ivc_inputs: [Field; 2]) -> [Field; 3] {}";
+        assert!(NonameSignature::parse(code, None).is_err());
+    }
+}
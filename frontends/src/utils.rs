@@ -0,0 +1,17 @@
+use folding_schemes::{utils::PathOrBin, Error};
+
+// Returns the content of the given file, reading it if it is passed by path.
+pub(crate) fn read_path_or_bin(path_or_bin: PathOrBin) -> Result<Vec<u8>, Error> {
+    match path_or_bin {
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        PathOrBin::Path(path) => Ok(std::fs::read(path)?),
+        // there is no filesystem in the browser, so the artifacts must be passed as bytes
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        PathOrBin::Path(path) => Err(Error::Other(format!(
+            "can not read {} on wasm32-unknown-unknown, which has no filesystem, pass the \
+             artifact as bytes instead",
+            path.display()
+        ))),
+        PathOrBin::Bin(bytes) => Ok(bytes),
+    }
+}
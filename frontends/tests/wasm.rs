@@ -0,0 +1,55 @@
+//! Folds the circom test circuit in the browser, to check that the frontends work on
+//! wasm32-unknown-unknown. The `.r1cs` and `.wasm` artifacts are embedded in the test binary, since
+//! there is no filesystem in the browser, so they must be generated first with
+//! `frontends/src/circom/test_folder/compile.sh`. Run with:
+//!
+//! wasm-pack test --headless --chrome frontends --no-default-features --features wasm,parallel -- --test wasm
+#![cfg(all(target_arch = "wasm32", target_os = "unknown", feature = "wasm"))]
+
+use ark_bn254::{constraints::GVar, Fr, G1Projective as Projective};
+use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+use folding_schemes::{
+    commitment::pedersen::Pedersen,
+    folding::nova::{Nova, PreprocessorParam},
+    frontend::VecF,
+    transcript::poseidon::poseidon_canonical_config,
+    FoldingScheme,
+};
+use frontends::circom::CircomFCircuit;
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn test_circom_fold_in_browser() {
+    let r1cs = include_bytes!("../src/circom/test_folder/cubic_circuit.r1cs");
+    let wasm = include_bytes!("../src/circom/test_folder/cubic_circuit_js/cubic_circuit.wasm");
+    let f_circuit = CircomFCircuit::<Fr, 0>::from_bytes(r1cs, wasm).unwrap();
+
+    type N = Nova<
+        Projective,
+        GVar,
+        Projective2,
+        GVar2,
+        CircomFCircuit<Fr, 0>,
+        Pedersen<Projective>,
+        Pedersen<Projective2>,
+        false,
+    >;
+    // the deterministic test rng does not need getrandom, in a real application an rng seeded from
+    // `getrandom` (which uses the browser's crypto API with its `js` feature) should be used
+    let mut rng = ark_std::test_rng();
+    let poseidon_config = poseidon_canonical_config::<Fr>();
+    let nova_params = N::preprocess(
+        &mut rng,
+        &PreprocessorParam::new(poseidon_config, f_circuit.clone()),
+    )
+    .unwrap();
+    let mut nova = N::init(&nova_params, f_circuit, vec![Fr::from(3u32)]).unwrap();
+    for _ in 0..2 {
+        nova.prove_step(&mut rng, VecF(vec![]), None).unwrap();
+    }
+    // 3 -> 3^3 + 3 + 5 = 35 -> 35^3 + 35 + 5 = 42915
+    assert_eq!(nova.state().z_i, vec![Fr::from(42915u32)]);
+    N::verify(nova_params.1, nova.ivc_proof()).unwrap();
+}
@@ -0,0 +1,71 @@
+//! Bare-metal binary that verifies the IVC proof written to `fixtures/` by the tests of this crate
+//! (`cargo test -p no-std-verifier`). It is only built, not run, in CI, so that linking the
+//! verifier into a `#![no_std]` binary is checked.
+#![no_std]
+#![no_main]
+
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    cell::UnsafeCell,
+    panic::PanicInfo,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+const HEAP_SIZE: usize = 64 << 20;
+
+/// Allocator over a static buffer which never frees, enough for a single verification.
+struct BumpAlloc {
+    heap: UnsafeCell<[u8; HEAP_SIZE]>,
+    next: AtomicUsize,
+}
+
+unsafe impl Sync for BumpAlloc {}
+
+unsafe impl GlobalAlloc for BumpAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let base = self.heap.get() as usize;
+        let mut next = self.next.load(Ordering::Relaxed);
+        loop {
+            let start = (base + next).next_multiple_of(layout.align()) - base;
+            let end = match start.checked_add(layout.size()) {
+                Some(end) if end <= HEAP_SIZE => end,
+                _ => return core::ptr::null_mut(),
+            };
+            match self
+                .next
+                .compare_exchange_weak(next, end, Ordering::Relaxed, Ordering::Relaxed)
+            {
+                Ok(_) => return (base + start) as *mut u8,
+                Err(current) => next = current,
+            }
+        }
+    }
+
+    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {}
+}
+
+#[global_allocator]
+static ALLOC: BumpAlloc = BumpAlloc {
+    heap: UnsafeCell::new([0; HEAP_SIZE]),
+    next: AtomicUsize::new(0),
+};
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {
+        core::hint::spin_loop();
+    }
+}
+
+static VP: &[u8] = include_bytes!("../../fixtures/vp.bin");
+static IVC_PROOF: &[u8] = include_bytes!("../../fixtures/ivc_proof.bin");
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    if no_std_verifier::verify(VP, IVC_PROOF).is_err() {
+        panic!("invalid IVC proof");
+    }
+    loop {
+        core::hint::spin_loop();
+    }
+}
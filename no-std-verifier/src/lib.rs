@@ -0,0 +1,36 @@
+//! Verifier of Nova+CycleFold IVC proofs that is built without `std`. It exists to check that the
+//! verification paths of `folding-schemes` (deserialization of the verifier params and of the IVC
+//! proof, and `Nova::verify`) build and run with only `core` and `alloc`.
+#![no_std]
+
+use ark_bn254::{constraints::GVar, Fr, G1Projective as Projective};
+use ark_grumpkin::{constraints::GVar as GVar2, Projective as Projective2};
+use ark_serialize::{CanonicalDeserialize, Compress, Validate};
+use folding_schemes::{
+    commitment::pedersen::Pedersen,
+    folding::nova::{IVCProof, Nova},
+    Error, FoldingScheme,
+};
+
+pub use folding_schemes::frontend::utils::CubicFCircuit;
+
+/// Nova over BN254 & Grumpkin with Pedersen commitments, folding the `CubicFCircuit`, which
+/// checks `z_{i+1} = z_i^3 + z_i + 5`.
+pub type N = Nova<
+    Projective,
+    GVar,
+    Projective2,
+    GVar2,
+    CubicFCircuit<Fr>,
+    Pedersen<Projective>,
+    Pedersen<Projective2>,
+    false,
+>;
+
+/// Verifies the IVC proof, given the compressed serializations of the verifier params of `N` and of
+/// the proof.
+pub fn verify(vp: &[u8], ivc_proof: &[u8]) -> Result<(), Error> {
+    let vp = N::vp_deserialize_with_mode(vp, Compress::Yes, Validate::Yes, ())?;
+    let ivc_proof = IVCProof::deserialize_compressed(ivc_proof)?;
+    N::verify(vp, ivc_proof)
+}
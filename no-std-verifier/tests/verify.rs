@@ -0,0 +1,42 @@
+use ark_bn254::Fr;
+use ark_serialize::CanonicalSerialize;
+use folding_schemes::{
+    folding::nova::PreprocessorParam, frontend::FCircuit,
+    transcript::poseidon::poseidon_canonical_config, FoldingScheme,
+};
+use no_std_verifier::{verify, CubicFCircuit, N};
+
+/// Generates an IVC proof, checks that the no-std verifier accepts it and rejects a tampered one,
+/// and writes it to `fixtures/` for the `verifier` binary. Both the prover and the verifier use
+/// `folding-schemes` built without `std`.
+#[test]
+fn test_verify_ivc_proof() {
+    let mut rng = ark_std::test_rng();
+    let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+    let prep_param = PreprocessorParam::new(poseidon_canonical_config::<Fr>(), f_circuit);
+    let params = N::preprocess(&mut rng, &prep_param).unwrap();
+
+    let mut nova = N::init(&params, f_circuit, vec![Fr::from(3_u32)]).unwrap();
+    for _ in 0..3 {
+        nova.prove_step(&mut rng, (), None).unwrap();
+    }
+
+    let mut vp = Vec::new();
+    params.1.serialize_compressed(&mut vp).unwrap();
+    let mut ivc_proof = Vec::new();
+    nova.ivc_proof()
+        .serialize_compressed(&mut ivc_proof)
+        .unwrap();
+    verify(&vp, &ivc_proof).unwrap();
+
+    let mut tampered_proof = nova.ivc_proof();
+    tampered_proof.z_i[0] += Fr::from(1_u32);
+    let mut tampered = Vec::new();
+    tampered_proof.serialize_compressed(&mut tampered).unwrap();
+    assert!(verify(&vp, &tampered).is_err());
+
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures");
+    std::fs::create_dir_all(fixtures).unwrap();
+    std::fs::write(format!("{}/vp.bin", fixtures), &vp).unwrap();
+    std::fs::write(format!("{}/ivc_proof.bin", fixtures), &ivc_proof).unwrap();
+}
@@ -7,11 +7,14 @@ use std::{
     fmt::{self, Debug, Formatter},
     fs::{self, create_dir_all, File},
     io::{self, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::{Command, Stdio},
     str,
 };
 
+/// Maximum size in bytes of the deployed bytecode of a contract, as set by EIP-170.
+pub const MAX_CONTRACT_SIZE: usize = 24576;
+
 // from: https://github.com/privacy-scaling-explorations/halo2-solidity-verifier/blob/85cb77b171ce3ee493628007c7a1cfae2ea878e6/examples/separately.rs#L56
 pub fn save_solidity(name: impl AsRef<str>, solidity: &str) {
     let curdir = PathBuf::from(".");
@@ -32,13 +35,37 @@ pub fn save_solidity(name: impl AsRef<str>, solidity: &str) {
 /// # Panics
 /// Panics if executable `solc` can not be found, or compilation fails.
 pub fn compile_solidity(solidity: impl AsRef<[u8]>, contract_name: &str) -> Vec<u8> {
-    let mut process = match Command::new("solc")
+    let mut command = Command::new("solc");
+    command.arg("--bin").arg("--optimize").arg("-");
+    run_solc(command, Some(solidity.as_ref()), contract_name)
+}
+
+/// Compile the solidity `files` of `dir`, which may import each other, then return creation
+/// bytecode of `contract_name`.
+///
+/// # Panics
+/// Panics if executable `solc` can not be found, or compilation fails.
+pub fn compile_solidity_files(
+    dir: impl AsRef<Path>,
+    files: &[&str],
+    contract_name: &str,
+) -> Vec<u8> {
+    let mut command = Command::new("solc");
+    command
+        .current_dir(dir)
+        .arg("--bin")
+        .arg("--optimize")
+        .arg("--base-path")
+        .arg(".")
+        .args(files);
+    run_solc(command, None, contract_name)
+}
+
+fn run_solc(mut command: Command, stdin: Option<&[u8]>, contract_name: &str) -> Vec<u8> {
+    let mut process = match command
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
-        .arg("--bin")
-        .arg("--optimize")
-        .arg("-")
         .spawn()
     {
         Ok(process) => process,
@@ -49,12 +76,11 @@ pub fn compile_solidity(solidity: impl AsRef<[u8]>, contract_name: &str) -> Vec<
             panic!("Failed to spawn process with command 'solc':\n{err}");
         }
     };
-    process
-        .stdin
-        .take()
-        .unwrap()
-        .write_all(solidity.as_ref())
-        .unwrap();
+    let mut process_stdin = process.stdin.take().unwrap();
+    if let Some(stdin) = stdin {
+        process_stdin.write_all(stdin).unwrap();
+    }
+    drop(process_stdin);
     let output = process.wait_with_output().unwrap();
     let stdout = str::from_utf8(&output.stdout).unwrap();
     if let Some(binary) = find_binary(stdout, contract_name) {
@@ -69,12 +95,15 @@ pub fn compile_solidity(solidity: impl AsRef<[u8]>, contract_name: &str) -> Vec<
 
 /// Find binary from `stdout` with given `contract_name`.
 /// `contract_name` is provided since `solc` may compile multiple contracts or libraries.
-/// hence, we need to find the correct binary.
+/// hence, we need to find the correct binary, whose header is `======= <file>:<contract_name> =======`.
 fn find_binary(stdout: &str, contract_name: &str) -> Option<Vec<u8>> {
-    let start_contract = stdout.find(contract_name)?;
+    let start_contract = stdout.find(&format!(":{contract_name} ======="))?;
     let stdout_contract = &stdout[start_contract..];
     let start = stdout_contract.find("Binary:")? + 8;
-    Some(hex::decode(&stdout_contract[start..stdout_contract.len() - 1]).unwrap())
+    let end = stdout_contract[start..]
+        .find('\n')
+        .map_or(stdout_contract.len(), |end| start + end);
+    Some(hex::decode(&stdout_contract[start..end]).unwrap())
 }
 
 /// Evm runner.
@@ -110,6 +139,15 @@ impl Evm {
     /// # Panics
     /// Panics if execution reverts or halts unexpectedly.
     pub fn create(&mut self, bytecode: Vec<u8>) -> Address {
+        self.create_with_code_size(bytecode).0
+    }
+
+    /// Same as `create`, but also returns the size of the deployed bytecode, which is the one
+    /// bounded by `MAX_CONTRACT_SIZE`.
+    ///
+    /// # Panics
+    /// Panics if execution reverts or halts unexpectedly.
+    pub fn create_with_code_size(&mut self, bytecode: Vec<u8>) -> (Address, usize) {
         let (_, output) = self.transact_success_or_panic(TxEnv {
             gas_limit: u64::MAX,
             transact_to: TransactTo::Create(CreateScheme::Create),
@@ -117,7 +155,7 @@ impl Evm {
             ..Default::default()
         });
         match output {
-            Output::Create(_, Some(address)) => address,
+            Output::Create(code, Some(address)) => (address, code.len()),
             _ => unreachable!(),
         }
     }
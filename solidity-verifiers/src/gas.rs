@@ -0,0 +1,86 @@
+//! Harness to measure the exact gas used to verify proofs with the generated verifiers, by
+//! deploying them in `revm`. Meant to be called from tests and benches.
+
+use crate::evm::{compile_solidity, revm::primitives::Address, Evm};
+use crate::{
+    get_decider_template_for_cyclefold_decider,
+    get_decider_template_for_hypernova_cyclefold_decider, HyperNovaCycleFoldVerifierKey,
+    NovaCycleFoldVerifierKey,
+};
+
+/// Result of a call to a verifier contract.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GasMeasurement {
+    /// Gas used by the whole transaction, including its intrinsic cost and its calldata.
+    pub gas_used: u64,
+    /// Whether the verifier returned `true` as its first word, which is `false` if it reverted.
+    pub verified: bool,
+}
+
+/// Verifier contract deployed in its own `Evm`.
+#[derive(Debug)]
+pub struct GasHarness {
+    evm: Evm,
+    address: Address,
+}
+
+impl GasHarness {
+    /// Deploys the given creation bytecode, followed by the ABI-encoded `constructor_args`.
+    pub fn from_bytecode(bytecode: Vec<u8>, constructor_args: &[u8]) -> Self {
+        let mut evm = Evm::default();
+        let address = evm.create([bytecode, constructor_args.to_vec()].concat());
+        Self { evm, address }
+    }
+
+    /// Compiles `solidity` with `solc` and deploys its `contract_name` contract.
+    ///
+    /// # Panics
+    /// Panics if executable `solc` can not be found, or compilation fails.
+    pub fn from_solidity(solidity: &str, contract_name: &str, constructor_args: &[u8]) -> Self {
+        Self::from_bytecode(compile_solidity(solidity, contract_name), constructor_args)
+    }
+
+    /// Compiles and deploys the NovaDecider contract of the given verifier key, whose layout of
+    /// the public inputs is the one of the decider from which the key was taken.
+    ///
+    /// # Panics
+    /// Panics if executable `solc` can not be found, or compilation fails.
+    pub fn nova_cyclefold(vk: NovaCycleFoldVerifierKey) -> Self {
+        Self::from_solidity(
+            &get_decider_template_for_cyclefold_decider(vk),
+            "NovaDecider",
+            &[],
+        )
+    }
+
+    /// Compiles and deploys the HyperNovaDecider contract of the given verifier key.
+    ///
+    /// # Panics
+    /// Panics if executable `solc` can not be found, or compilation fails.
+    pub fn hypernova_cyclefold(vk: HyperNovaCycleFoldVerifierKey) -> Self {
+        Self::from_solidity(
+            &get_decider_template_for_hypernova_cyclefold_decider(vk),
+            HyperNovaCycleFoldVerifierKey::DECIDER_NAME,
+            &[],
+        )
+    }
+
+    /// Address of the deployed verifier.
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Calls the verifier with the given calldata, which selects the entrypoint, as the ones of
+    /// `prepare_calldata` and `prepare_calldata_abi` (`verifyNovaProof`) and
+    /// `prepare_calldata_batch` (`verifyBatch`) of the Nova decider, or the one of
+    /// `prepare_calldata` (`verifyHyperNovaProof`) of the HyperNova one.
+    pub fn measure(&mut self, calldata: Vec<u8>) -> GasMeasurement {
+        let (gas_used, output) = self.evm.call(self.address, calldata);
+        // the boolean is returned in the first word, while the output of a revert starts with the
+        // 4-byte selector of its error
+        GasMeasurement {
+            gas_used,
+            verified: output.len() >= 32 && output[31] == 1 && output[..31].iter().all(|b| *b == 0),
+        }
+    }
+}
@@ -1,9 +1,16 @@
 pub mod evm;
+pub mod gas;
 pub mod utils;
 pub mod verifiers;
 
 pub use verifiers::*;
 pub use verifiers::{
-    get_decider_template_for_cyclefold_decider, Groth16VerifierKey, KZG10VerifierKey,
-    NovaCycleFoldVerifierKey, ProtocolVerifierKey,
+    foundry_test_file, get_decider_template_for_cyclefold_decider,
+    get_decider_template_for_cyclefold_decider_with_foundry_test,
+    get_decider_template_for_hypernova_cyclefold_decider,
+    get_decider_template_for_hypernova_cyclefold_decider_with_foundry_test,
+    get_foundry_test_for_decider, get_shared_decider_templates_for_cyclefold_deciders,
+    get_split_decider_templates_for_cyclefold_decider, ContractNames, Groth16VerifierKey,
+    HyperNovaCycleFoldVerifierKey, KZG10VerifierKey, NovaCycleFoldSharedVerifiers,
+    NovaCycleFoldSplitVerifier, NovaCycleFoldVerifierKey, ProtocolVerifierKey,
 };
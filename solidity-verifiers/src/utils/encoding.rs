@@ -1,5 +1,6 @@
 /// Defines encodings of G1 and G2 elements for use in Solidity templates.
 use ark_bn254::{Fq, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField};
 use std::fmt::{self, Display};
 
 #[derive(Debug, Default)]
@@ -34,6 +35,21 @@ impl Display for G2Repr {
     }
 }
 
+/// ABI-encodes the given base field elements as `uint256` words.
+pub fn fq_to_abi_words(elements: &[Fq]) -> Vec<u8> {
+    elements
+        .iter()
+        .flat_map(|e| e.into_bigint().to_bytes_be())
+        .collect()
+}
+
+/// ABI-encodes `n` as a `uint256` word, as used for the offsets and lengths of dynamic arrays.
+pub fn usize_to_abi_word(n: usize) -> Vec<u8> {
+    let mut word = vec![0u8; 24];
+    word.extend((n as u64).to_be_bytes());
+    word
+}
+
 /// Converts a G2 element to a representation that can be used in Solidity templates.
 pub fn g2_to_fq_repr(g2: G2Affine) -> G2Repr {
     G2Repr([
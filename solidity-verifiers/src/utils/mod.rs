@@ -22,7 +22,47 @@ pub fn get_function_selector_for_nova_cyclefold_verifier(
     first_param_array_length: usize,
 ) -> [u8; 4] {
     let mut hasher = Sha3::keccak256();
-    let fn_sig = format!("verifyNovaProof(uint256[{}],uint256[4],uint256[2],uint256[3],uint256[2],uint256[2][2],uint256[2],uint256[4],uint256[2][2])", first_param_array_length);
+    let fn_sig = format!("verifyNovaProof(uint256[{}],uint256[4],uint256[2],uint256[3],uint256[2],uint256[2][2],uint256[2],uint256[4],uint256[2])", first_param_array_length);
+    hasher.input_str(&fn_sig);
+    let hash = &mut [0u8; 32];
+    hasher.result(hash);
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// ABI type of the `NovaProof` struct taken by the nova cyclefold verifier, see
+/// `folding_schemes::folding::nova::decider_eth::NovaProof`
+pub(crate) const NOVA_PROOF_ABI_TYPE: &str = "(uint256,uint256[],uint256[],uint256[2],uint256[2],uint256[2],uint256[2],uint256,uint256[2],uint256[2][2],uint256[2],uint256,uint256,uint256[2],uint256[2])";
+
+/// Computes the function selector for the nova cyclefold verifier that takes the proof as a
+/// `NovaProof` struct
+pub fn get_function_selector_for_nova_cyclefold_abi_verifier() -> [u8; 4] {
+    let mut hasher = Sha3::keccak256();
+    let fn_sig = format!("verifyNovaProof({})", NOVA_PROOF_ABI_TYPE);
+    hasher.input_str(&fn_sig);
+    let hash = &mut [0u8; 32];
+    hasher.result(hash);
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Computes the function selector for the batch verification of the nova cyclefold verifier
+pub fn get_function_selector_for_nova_cyclefold_batch_verifier() -> [u8; 4] {
+    let mut hasher = Sha3::keccak256();
+    let fn_sig = format!("verifyBatch({}[])", NOVA_PROOF_ABI_TYPE);
+    hasher.input_str(&fn_sig);
+    let hash = &mut [0u8; 32];
+    hasher.result(hash);
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Computes the function selector for the hypernova cyclefold verifier
+/// It is computed on the fly since it depends on the length of the first parameter array and on
+/// the number of variables of the opened MLE
+pub fn get_function_selector_for_hypernova_cyclefold_verifier(
+    first_param_array_length: usize,
+    n_vars: usize,
+) -> [u8; 4] {
+    let mut hasher = Sha3::keccak256();
+    let fn_sig = format!("verifyHyperNovaProof(uint256[{}],uint256[6],uint256[3],uint256[{}],uint256[2],uint256[2][2],uint256[2],uint256[{}])", first_param_array_length, n_vars, 2 * n_vars + 4);
     hasher.input_str(&fn_sig);
     let hash = &mut [0u8; 32];
     hasher.result(hash);
@@ -0,0 +1,55 @@
+use askama::Template;
+
+use crate::evm::revm::primitives::hex;
+use crate::utils::HeaderInclusion;
+use crate::{GPL3_SDPX_IDENTIFIER, PRAGMA_GROTH16_VERIFIER};
+
+/// Byte of the calldata flipped by the mutation test, which is the last byte of `i`, the first
+/// argument after the selector of the decider verifiers.
+const MUTATED_BYTE: usize = 4 + 31;
+
+#[derive(Template, Default)]
+#[template(path = "foundry_test.askama.sol", ext = "sol")]
+pub(crate) struct FoundryTest {
+    contract_name: String,
+    verifier_file: String,
+    // hex-encoded calldata of the proof
+    calldata: String,
+    mutated_byte: usize,
+}
+
+/// Returns the file name of the Foundry test of the `contract_name` contract.
+pub fn foundry_test_file(contract_name: &str) -> String {
+    format!("{contract_name}.t.sol")
+}
+
+/// Renders a Foundry test of the `contract_name` decider verifier, which is imported from
+/// `verifier_file` in the same directory. The test deploys the verifier, checks that it accepts
+/// `calldata`, the calldata of a proof returned by `prepare_calldata`, and that it reverts once
+/// a byte of `i` is flipped.
+///
+/// # Panics
+/// Panics if `calldata` is too short to contain `i`.
+pub fn get_foundry_test_for_decider(
+    contract_name: &str,
+    verifier_file: &str,
+    calldata: &[u8],
+    pragma: Option<String>,
+) -> String {
+    assert!(
+        calldata.len() > MUTATED_BYTE,
+        "the calldata does not contain the selector and i"
+    );
+    HeaderInclusion::<FoundryTest>::builder()
+        .sdpx(GPL3_SDPX_IDENTIFIER.to_string())
+        .pragma_version(pragma.unwrap_or(PRAGMA_GROTH16_VERIFIER.to_string()))
+        .template(FoundryTest {
+            contract_name: contract_name.to_string(),
+            verifier_file: verifier_file.to_string(),
+            calldata: hex::encode(calldata),
+            mutated_byte: MUTATED_BYTE,
+        })
+        .build()
+        .render()
+        .unwrap()
+}
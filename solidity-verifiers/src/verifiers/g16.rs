@@ -1,17 +1,24 @@
-use crate::utils::encoding::{g1_to_fq_repr, g2_to_fq_repr};
+use crate::utils::encoding::{fq_to_abi_words, g1_to_fq_repr, g2_to_fq_repr, usize_to_abi_word};
 use crate::utils::encoding::{G1Repr, G2Repr};
 use crate::utils::HeaderInclusion;
 use crate::{ProtocolVerifierKey, GPL3_SDPX_IDENTIFIER};
-use ark_bn254::Bn254;
+use ark_bn254::{Bn254, G2Affine};
 use ark_groth16::VerifyingKey as ArkVerifyingKey;
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+};
 use askama::Template;
 
-use super::PRAGMA_GROTH16_VERIFIER;
+use super::{ContractNames, PRAGMA_GROTH16_VERIFIER};
 
 #[derive(Template, Default)]
 #[template(path = "groth16_verifier.askama.sol", ext = "sol")]
 pub struct Groth16Verifier {
+    /// Name of the contract.
+    pub name: String,
+    /// Whether the verifying key is set at the constructor instead of being hard-coded, in which
+    /// case the other fields are not used and the public signals are taken as dynamic arrays.
+    pub constructor_vk: bool,
     /// The `alpha * G`, where `G` is the generator of `G1`.
     pub vkey_alpha_g1: G1Repr,
     /// The `alpha * H`, where `H` is the generator of `G2`.
@@ -29,6 +36,8 @@ pub struct Groth16Verifier {
 impl From<Groth16VerifierKey> for Groth16Verifier {
     fn from(g16_vk: Groth16VerifierKey) -> Self {
         Self {
+            name: ContractNames::default().groth16_verifier,
+            constructor_vk: false,
             vkey_alpha_g1: g1_to_fq_repr(g16_vk.0.alpha_g1),
             vkey_beta_g2: g2_to_fq_repr(g16_vk.0.beta_g2),
             vkey_gamma_g2: g2_to_fq_repr(g16_vk.0.gamma_g2),
@@ -47,15 +56,72 @@ impl From<Groth16VerifierKey> for Groth16Verifier {
 
 // Ideally this would be linked to the `Decider` trait in FoldingSchemes.
 // For now, this is the easiest as NovaCycleFold isn't clear target from where we can get all it's needed arguments.
-#[derive(CanonicalDeserialize, CanonicalSerialize, Clone, PartialEq, Debug)]
+#[derive(CanonicalSerialize, Clone, PartialEq, Debug)]
 pub struct Groth16VerifierKey(pub(crate) ArkVerifyingKey<Bn254>);
 
+impl Valid for Groth16VerifierKey {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.0.check()?;
+        // `gamma_abc_g1` has an element for the constant `1` besides the public inputs
+        if self.0.gamma_abc_g1.is_empty() {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for Groth16VerifierKey {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let vk = Self(ArkVerifyingKey::deserialize_with_mode(
+            reader, compress, validate,
+        )?);
+        if let Validate::Yes = validate {
+            vk.check()?;
+        }
+        Ok(vk)
+    }
+}
+
 impl From<ArkVerifyingKey<Bn254>> for Groth16VerifierKey {
     fn from(value: ArkVerifyingKey<Bn254>) -> Self {
         Self(value)
     }
 }
 
+impl Groth16VerifierKey {
+    /// ABI-encoded constructor arguments of the Groth16 verifier shared by several deciders, which
+    /// set it to this verifying key. See `NovaCycleFoldSharedVerifiers`.
+    pub fn constructor_args(&self) -> Vec<u8> {
+        // points of G2 as in the snarkjs template, ie. [x.c1, x.c0, y.c1, y.c0]
+        let g2 = |p: G2Affine| [p.x.c1, p.x.c0, p.y.c1, p.y.c0];
+        let vk = &self.0;
+        let static_args = [
+            vec![vk.alpha_g1.x, vk.alpha_g1.y],
+            g2(vk.beta_g2).to_vec(),
+            g2(vk.gamma_g2).to_vec(),
+            g2(vk.delta_g2).to_vec(),
+        ]
+        .concat();
+        let ic = vk
+            .gamma_abc_g1
+            .iter()
+            .flat_map(|p| [p.x, p.y])
+            .collect::<Vec<_>>();
+        [
+            fq_to_abi_words(&static_args),
+            // offset of the dynamic `_IC` array, placed after the static arguments, and its length
+            usize_to_abi_word((static_args.len() + 1) * 32),
+            usize_to_abi_word(vk.gamma_abc_g1.len()),
+            fq_to_abi_words(&ic),
+        ]
+        .concat()
+    }
+}
+
 impl ProtocolVerifierKey for Groth16VerifierKey {
     const PROTOCOL_NAME: &'static str = "Groth16";
 
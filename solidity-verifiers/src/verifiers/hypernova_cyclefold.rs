@@ -0,0 +1,449 @@
+#![allow(non_snake_case)]
+#![allow(non_camel_case_types)]
+#![allow(clippy::upper_case_acronyms)]
+
+use ark_bn254::{Bn254, Fq, Fr, G1Projective};
+use ark_ff::PrimeField;
+use ark_groth16::VerifyingKey as ArkG16VerifierKey;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+};
+use askama::Template;
+
+use folding_schemes::commitment::zeromorph::VerifierKey as ZeromorphVerifierKey;
+use folding_schemes::folding::hypernova::decider_eth::VerifierParam as DeciderVerifierParam;
+
+use super::foundry::{foundry_test_file, get_foundry_test_for_decider};
+use super::g16::Groth16Verifier;
+use super::kzg::KZG10Verifier;
+use crate::utils::HeaderInclusion;
+use crate::{
+    ContractNames, Groth16VerifierKey, KZG10VerifierKey, ProtocolVerifierKey,
+    PRAGMA_GROTH16_VERIFIER,
+};
+
+pub fn get_decider_template_for_hypernova_cyclefold_decider(
+    hypernova_cyclefold_vk: HyperNovaCycleFoldVerifierKey,
+) -> String {
+    HeaderInclusion::<HyperNovaCycleFoldDecider>::builder()
+        .template(hypernova_cyclefold_vk)
+        .build()
+        .render()
+        .unwrap()
+}
+
+/// Renders the decider verifier as `get_decider_template_for_hypernova_cyclefold_decider`,
+/// together with a Foundry test that checks it against `calldata` when the calldata of a proof is
+/// given, as `get_decider_template_for_cyclefold_decider_with_foundry_test`.
+pub fn get_decider_template_for_hypernova_cyclefold_decider_with_foundry_test(
+    hypernova_cyclefold_vk: HyperNovaCycleFoldVerifierKey,
+    calldata: Option<&[u8]>,
+) -> Vec<(String, String)> {
+    let decider = HyperNovaCycleFoldVerifierKey::DECIDER_NAME;
+    let verifier_file = format!("{decider}.sol");
+    let mut artifacts = vec![(
+        verifier_file.clone(),
+        get_decider_template_for_hypernova_cyclefold_decider(hypernova_cyclefold_vk),
+    )];
+    if let Some(calldata) = calldata {
+        artifacts.push((
+            foundry_test_file(decider),
+            get_foundry_test_for_decider(decider, &verifier_file, calldata, None),
+        ));
+    }
+    artifacts
+}
+
+#[derive(Template, Default)]
+#[template(path = "hypernova_cyclefold_decider.askama.sol", ext = "sol")]
+pub struct HyperNovaCycleFoldDecider {
+    pp_hash: Fr, // public params hash
+    groth16_verifier: Groth16Verifier,
+    kzg10_verifier: KZG10Verifier,
+    // z_len denotes the FCircuit state (z_i) length
+    z_len: usize,
+    // number of variables of the MLE of the witness, which is opened with Zeromorph
+    n_vars: usize,
+    // exponent `D + 1 - 2^n_vars` by which the degree check of Zeromorph shifts the quotients,
+    // for the maximum degree `D` of the SRS
+    zeromorph_shift: usize,
+    public_inputs_len: usize,
+    // bit size of the coordinates of the points, which are packed into elements of
+    // `bits_per_element` bits
+    coordinate_bits: usize,
+    bits_per_element: usize,
+    decider_name: String,
+    groth16_name: String,
+    kzg10_name: String,
+}
+
+impl From<HyperNovaCycleFoldVerifierKey> for HyperNovaCycleFoldDecider {
+    fn from(value: HyperNovaCycleFoldVerifierKey) -> Self {
+        let names = ContractNames::default();
+        let groth16_verifier = Groth16Verifier::from(value.g16_vk);
+        let public_inputs_len = groth16_verifier.gamma_abc_len;
+        Self {
+            pp_hash: value.pp_hash,
+            groth16_verifier,
+            kzg10_verifier: KZG10Verifier::from(value.kzg_vk),
+            z_len: value.z_len,
+            n_vars: value.n_vars,
+            zeromorph_shift: value.max_degree + 1 - (1 << value.n_vars),
+            public_inputs_len,
+            coordinate_bits: Fq::MODULUS_BIT_SIZE as usize,
+            bits_per_element: Fr::MODULUS_BIT_SIZE as usize - 1,
+            decider_name: HyperNovaCycleFoldVerifierKey::DECIDER_NAME.to_string(),
+            groth16_name: names.groth16_verifier,
+            kzg10_name: names.kzg10_verifier,
+        }
+    }
+}
+
+/// Verifier key of the HyperNova+CycleFold decider, whose KZG10 key and `max_degree` are the ones
+/// of the Zeromorph opening of the MLE of the witness, and whose `n_vars` is the number of
+/// variables of that MLE, which is given by the size of the witness of the decider circuit.
+#[derive(CanonicalSerialize, PartialEq, Debug, Clone)]
+pub struct HyperNovaCycleFoldVerifierKey {
+    pp_hash: Fr,
+    g16_vk: Groth16VerifierKey,
+    kzg_vk: KZG10VerifierKey,
+    z_len: usize,
+    n_vars: usize,
+    max_degree: usize,
+}
+
+impl Valid for HyperNovaCycleFoldVerifierKey {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.g16_vk.check()?;
+        self.kzg_vk.check()?;
+        if self.z_len == 0 || self.n_vars == 0 {
+            return Err(SerializationError::InvalidData);
+        }
+        // the SRS must have at least the `2^n_vars` powers of the committed MLE
+        if self.n_vars >= usize::BITS as usize || self.max_degree < (1 << self.n_vars) - 1 {
+            return Err(SerializationError::InvalidData);
+        }
+        // the Groth16 key has an element for the constant `1` and one for each public input of
+        // the decider: pp_hash, i, the states, 9 elements for the commitments, the challenge, the
+        // evaluation and rho, and the `n_vars` elements of the opening point
+        if self.g16_vk.0.gamma_abc_g1.len() != 1 + 2 + self.z_len * 2 + 9 + self.n_vars {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for HyperNovaCycleFoldVerifierKey {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let vk = Self {
+            pp_hash: Fr::deserialize_with_mode(&mut reader, compress, validate)?,
+            g16_vk: Groth16VerifierKey::deserialize_with_mode(&mut reader, compress, validate)?,
+            kzg_vk: KZG10VerifierKey::deserialize_with_mode(&mut reader, compress, validate)?,
+            z_len: usize::deserialize_with_mode(&mut reader, compress, validate)?,
+            n_vars: usize::deserialize_with_mode(&mut reader, compress, validate)?,
+            max_degree: usize::deserialize_with_mode(&mut reader, compress, validate)?,
+        };
+        if let Validate::Yes = validate {
+            vk.check()?;
+        }
+        Ok(vk)
+    }
+}
+
+impl ProtocolVerifierKey for HyperNovaCycleFoldVerifierKey {
+    const PROTOCOL_NAME: &'static str = "HyperNovaCycleFold";
+
+    fn render_as_template(self, pragma: Option<String>) -> Vec<u8> {
+        HeaderInclusion::<HyperNovaCycleFoldDecider>::builder()
+            .pragma_version(pragma.unwrap_or(PRAGMA_GROTH16_VERIFIER.to_string()))
+            .template(self)
+            .build()
+            .render()
+            .unwrap()
+            .into_bytes()
+    }
+}
+
+// implements From assuming that the 'batchCheck' method from the KZG10 template will not be used
+// in the HyperNovaCycleFoldDecider verifier contract
+impl
+    From<(
+        DeciderVerifierParam<
+            G1Projective,
+            (ZeromorphVerifierKey<Bn254>, usize),
+            ArkG16VerifierKey<Bn254>,
+        >,
+        usize,
+    )> for HyperNovaCycleFoldVerifierKey
+{
+    fn from(
+        value: (
+            DeciderVerifierParam<
+                G1Projective,
+                (ZeromorphVerifierKey<Bn254>, usize),
+                ArkG16VerifierKey<Bn254>,
+            >,
+            usize,
+        ),
+    ) -> Self {
+        let decider_vp = value.0;
+        let (cs_vp, n_vars) = decider_vp.cs_vp;
+        Self {
+            pp_hash: decider_vp.pp_hash,
+            g16_vk: Groth16VerifierKey::from(decider_vp.snark_vp),
+            // pass `Vec::new()` since batchCheck will not be used
+            kzg_vk: KZG10VerifierKey::from((cs_vp.vk, Vec::new())),
+            z_len: value.1,
+            n_vars,
+            max_degree: cs_vp.max_degree,
+        }
+    }
+}
+
+impl HyperNovaCycleFoldVerifierKey {
+    /// Name of the decider contract.
+    pub const DECIDER_NAME: &'static str = "HyperNovaDecider";
+
+    /// Number of variables of the MLE of the witness, which determines the length of the
+    /// Zeromorph proof taken by the verifier contract.
+    pub fn n_vars(&self) -> usize {
+        self.n_vars
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::{constraints::GVar, Bn254, Fr, G1Projective as G1};
+    use ark_groth16::Groth16;
+    use ark_grumpkin::{constraints::GVar as GVar2, Projective as G2};
+    use std::time::Instant;
+
+    use folding_schemes::{
+        commitment::{
+            pedersen::Pedersen,
+            zeromorph::{VerifierKey as ZeromorphVerifierKey, Zeromorph},
+        },
+        folding::{
+            hypernova::{
+                decider_eth::{prepare_calldata, MultilinearDecider as DeciderEth},
+                HyperNova,
+            },
+            nova::PreprocessorParam,
+            traits::CommittedInstanceOps,
+        },
+        frontend::{utils::CubicFCircuit, FCircuit},
+        transcript::poseidon::poseidon_canonical_config,
+        Decider, FoldingScheme,
+    };
+
+    use super::{DeciderVerifierParam, HyperNovaCycleFoldDecider};
+    use crate::verifiers::tests::setup;
+    use crate::{
+        evm::{compile_solidity, save_solidity, Evm},
+        utils::{get_function_selector_for_hypernova_cyclefold_verifier, HeaderInclusion},
+        verifiers::hypernova_cyclefold::get_decider_template_for_hypernova_cyclefold_decider,
+        HyperNovaCycleFoldVerifierKey, ProtocolVerifierKey,
+    };
+
+    type HN = HyperNova<
+        G1,
+        GVar,
+        G2,
+        GVar2,
+        CubicFCircuit<Fr>,
+        Zeromorph<'static, Bn254>,
+        Pedersen<G2>,
+        1,
+        1,
+        false,
+    >;
+    type DECIDER = DeciderEth<
+        G1,
+        GVar,
+        G2,
+        GVar2,
+        CubicFCircuit<Fr>,
+        Zeromorph<'static, Bn254>,
+        Pedersen<G2>,
+        Groth16<Bn254>,
+        HN,
+        1,
+        1,
+    >;
+
+    /// Returns a verifier key from the test setup, whose Groth16 key is padded to the number of
+    /// public inputs of the decider, so that it passes the validation on load.
+    fn test_vk(z_len: usize, n_vars: usize) -> HyperNovaCycleFoldVerifierKey {
+        let (pp_hash, kzg_pk, kzg_vk, _, mut g16_vk, _) = setup(1 << n_vars);
+        g16_vk.gamma_abc_g1 = vec![g16_vk.gamma_abc_g1[0]; 1 + 2 + z_len * 2 + 9 + n_vars];
+
+        let decider_vp = DeciderVerifierParam {
+            pp_hash,
+            snark_vp: g16_vk,
+            cs_vp: (ZeromorphVerifierKey::new(&kzg_pk, kzg_vk).unwrap(), n_vars),
+        };
+        HyperNovaCycleFoldVerifierKey::from((decider_vp, z_len))
+    }
+
+    #[test]
+    fn hypernova_cyclefold_vk_serde_roundtrip() {
+        let hypernova_cyclefold_vk = test_vk(1, 4);
+
+        let mut bytes = vec![];
+        hypernova_cyclefold_vk
+            .serialize_protocol_verifier_key(&mut bytes)
+            .unwrap();
+        let obtained_hypernova_cyclefold_vk =
+            HyperNovaCycleFoldVerifierKey::deserialize_protocol_verifier_key(bytes.as_slice())
+                .unwrap();
+        assert_eq!(hypernova_cyclefold_vk, obtained_hypernova_cyclefold_vk);
+
+        // Groth16 key inconsistent with the length of the state
+        let mut invalid_bytes = vec![];
+        HyperNovaCycleFoldVerifierKey {
+            z_len: 2,
+            ..hypernova_cyclefold_vk.clone()
+        }
+        .serialize_protocol_verifier_key(&mut invalid_bytes)
+        .unwrap();
+        assert!(
+            HyperNovaCycleFoldVerifierKey::deserialize_protocol_verifier_key(
+                invalid_bytes.as_slice()
+            )
+            .is_err()
+        );
+
+        // SRS without enough powers for the MLE of the witness
+        let mut invalid_bytes = vec![];
+        HyperNovaCycleFoldVerifierKey {
+            max_degree: 14,
+            ..hypernova_cyclefold_vk
+        }
+        .serialize_protocol_verifier_key(&mut invalid_bytes)
+        .unwrap();
+        assert!(
+            HyperNovaCycleFoldVerifierKey::deserialize_protocol_verifier_key(
+                invalid_bytes.as_slice()
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn hypernova_cyclefold_decider_template_renders() {
+        let decider_solidity_code = HeaderInclusion::<HyperNovaCycleFoldDecider>::builder()
+            .template(test_vk(1, 4))
+            .build();
+
+        save_solidity(
+            "HyperNovaDecider.sol",
+            &decider_solidity_code.render().unwrap(),
+        );
+    }
+
+    /// Actions performed by this test:
+    /// - runs the HyperNovaCycleFold folding scheme with the CubicFCircuit for 3 steps
+    /// - generates a DeciderEth proof, and executes it through the EVM
+    /// - modifies the calldata and checks that it does not pass the EVM check, both in the Groth16
+    ///   and in the Zeromorph checks
+    #[test]
+    fn hypernova_cyclefold_solidity_verifier() {
+        let mut rng = rand::rngs::OsRng;
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let z_0 = vec![Fr::from(3_u32)];
+
+        let prep_param = PreprocessorParam::new(poseidon_config, f_circuit);
+        let hypernova_params = HN::preprocess(&mut rng, &prep_param).unwrap();
+        let mut hypernova = HN::init(&hypernova_params, f_circuit, z_0).unwrap();
+        for _ in 0..3 {
+            hypernova
+                .prove_step(&mut rng, (), Some((vec![], vec![])))
+                .unwrap();
+        }
+
+        let (decider_pp, decider_vp) =
+            DECIDER::preprocess(&mut rng, hypernova_params, hypernova.clone()).unwrap();
+        let hypernova_cyclefold_vk =
+            HyperNovaCycleFoldVerifierKey::from((decider_vp.clone(), f_circuit.state_len()));
+
+        let start = Instant::now();
+        let proof = DECIDER::prove(rng, decider_pp, hypernova.clone()).unwrap();
+        println!("generated Decider proof: {:?}", start.elapsed());
+
+        let verified = DECIDER::verify(
+            decider_vp,
+            hypernova.i,
+            hypernova.z_0.clone(),
+            hypernova.z_i.clone(),
+            &hypernova.U_i.get_commitments(),
+            &hypernova.u_i.get_commitments(),
+            &proof,
+        )
+        .unwrap();
+        assert!(verified);
+
+        let function_selector = get_function_selector_for_hypernova_cyclefold_verifier(
+            hypernova.z_0.len() * 2 + 1,
+            hypernova_cyclefold_vk.n_vars(),
+        );
+        let calldata: Vec<u8> = prepare_calldata(
+            function_selector,
+            hypernova.i,
+            hypernova.z_0,
+            hypernova.z_i,
+            &hypernova.U_i,
+            &hypernova.u_i,
+            proof,
+        )
+        .unwrap();
+
+        let decider_solidity_code =
+            get_decider_template_for_hypernova_cyclefold_decider(hypernova_cyclefold_vk);
+        let bytecode = compile_solidity(
+            decider_solidity_code,
+            HyperNovaCycleFoldVerifierKey::DECIDER_NAME,
+        );
+        let mut evm = Evm::default();
+        let verifier_address = evm.create(bytecode);
+
+        let (gas_used, output) = evm.call(verifier_address, calldata.clone());
+        assert_eq!(*output.last().unwrap(), 1);
+        println!("HyperNovaDecider verification gas used: {}", gas_used);
+
+        // change i to make calldata invalid, placed between bytes 4 - 35
+        let mut invalid_calldata = calldata.clone();
+        invalid_calldata[35] += 1;
+        let (_, output) = evm.call(verifier_address, invalid_calldata);
+        assert_eq!(*output.last().unwrap(), 0);
+
+        // change z_i, placed between bytes 68 - 100
+        let mut invalid_calldata = calldata.clone();
+        invalid_calldata[99] += 1;
+        let (_, output) = evm.call(verifier_address, invalid_calldata);
+        assert_eq!(*output.last().unwrap(), 0);
+
+        // change the first element of the opening point, placed after the 4-byte selector and
+        // [i, z_0, z_i], the 3 commitments and [challenge, eval, rho], so that it does not match
+        // the sum-check point bound by the Groth16 proof
+        let point_offset = 4 + 32 * (1 + 2 + 6 + 3);
+        let mut invalid_calldata = calldata.clone();
+        invalid_calldata[point_offset + 31] += 1;
+        let (_, output) = evm.call(verifier_address, invalid_calldata);
+        assert_eq!(*output.last().unwrap(), 0);
+
+        // swap q_hat and the KZG proof of the Zeromorph opening, the last two points, so that the
+        // points are still valid but the opening check fails
+        let mut invalid_calldata = calldata;
+        let len = invalid_calldata.len();
+        let (q_hat, pi) = invalid_calldata[len - 128..].split_at_mut(64);
+        q_hat.swap_with_slice(pi);
+        let (_, output) = evm.call(verifier_address, invalid_calldata);
+        assert_eq!(*output.last().unwrap(), 0);
+    }
+}
@@ -1,17 +1,22 @@
-use crate::utils::encoding::{g1_to_fq_repr, g2_to_fq_repr};
+use crate::utils::encoding::{fq_to_abi_words, g1_to_fq_repr, g2_to_fq_repr};
 use crate::utils::encoding::{G1Repr, G2Repr};
 use crate::utils::HeaderInclusion;
 use crate::{ProtocolVerifierKey, MIT_SDPX_IDENTIFIER};
-use ark_bn254::{Bn254, G1Affine};
+use ark_bn254::{Bn254, G1Affine, G2Affine};
 use ark_poly_commit::kzg10::VerifierKey;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use askama::Template;
 
-use super::PRAGMA_KZG10_VERIFIER;
+use super::{ContractNames, PRAGMA_KZG10_VERIFIER};
 
 #[derive(Template, Default)]
 #[template(path = "kzg10_verifier.askama.sol", ext = "sol")]
 pub struct KZG10Verifier {
+    /// Name of the contract.
+    pub(crate) name: String,
+    /// Whether `g1`, `g2` and `vk` are set at the constructor instead of being hard-coded, in
+    /// which case `g1_crs` is not supported.
+    pub(crate) constructor_vk: bool,
     /// The generator of `G1`.
     pub(crate) g1: G1Repr,
     /// The generator of `G2`.
@@ -27,6 +32,8 @@ pub struct KZG10Verifier {
 impl From<KZG10VerifierKey> for KZG10Verifier {
     fn from(data: KZG10VerifierKey) -> Self {
         Self {
+            name: ContractNames::default().kzg10_verifier,
+            constructor_vk: false,
             g1: g1_to_fq_repr(data.vk.g),
             g2: g2_to_fq_repr(data.vk.h),
             vk: g2_to_fq_repr(data.vk.beta_h),
@@ -55,6 +62,23 @@ impl From<(VerifierKey<Bn254>, Vec<G1Affine>)> for KZG10VerifierKey {
     }
 }
 
+impl KZG10VerifierKey {
+    /// ABI-encoded constructor arguments of the KZG10 verifier shared by several deciders, which
+    /// set it to this verifying key. See `NovaCycleFoldSharedVerifiers`.
+    pub fn constructor_args(&self) -> Vec<u8> {
+        // points of G2 as in the template, ie. [[x.c0, x.c1], [y.c0, y.c1]]
+        let g2 = |p: G2Affine| [p.x.c0, p.x.c1, p.y.c0, p.y.c1];
+        fq_to_abi_words(
+            &[
+                vec![self.vk.g.x, self.vk.g.y],
+                g2(self.vk.h).to_vec(),
+                g2(self.vk.beta_h).to_vec(),
+            ]
+            .concat(),
+        )
+    }
+}
+
 impl ProtocolVerifierKey for KZG10VerifierKey {
     const PROTOCOL_NAME: &'static str = "KZG";
 
@@ -8,18 +8,55 @@ pub const PRAGMA_KZG10_VERIFIER: &str = "pragma solidity >=0.8.1 <=0.8.4;";
 /// Default SDPX License identifier
 pub const GPL3_SDPX_IDENTIFIER: &str = "// SPDX-License-Identifier: GPL-3.0";
 pub const MIT_SDPX_IDENTIFIER: &str = "// SPDX-License-Identifier: MIT";
+
+/// Names of the generated contracts, which can be overridden so that the contracts of several
+/// verifiers do not clash when they are placed together.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContractNames {
+    pub groth16_verifier: String,
+    pub kzg10_verifier: String,
+    pub decider: String,
+}
+
+impl Default for ContractNames {
+    fn default() -> Self {
+        Self {
+            groth16_verifier: "Groth16Verifier".to_string(),
+            kzg10_verifier: "KZG10Verifier".to_string(),
+            decider: "NovaDecider".to_string(),
+        }
+    }
+}
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use std::{fs, io, path::Path};
 
+pub mod foundry;
 pub mod g16;
+pub mod hypernova_cyclefold;
 pub mod kzg;
 pub mod nova_cyclefold;
 
+pub use foundry::{foundry_test_file, get_foundry_test_for_decider};
 pub use g16::Groth16VerifierKey;
+pub use hypernova_cyclefold::{
+    get_decider_template_for_hypernova_cyclefold_decider,
+    get_decider_template_for_hypernova_cyclefold_decider_with_foundry_test,
+    HyperNovaCycleFoldVerifierKey,
+};
 pub use kzg::KZG10VerifierKey;
-pub use nova_cyclefold::{get_decider_template_for_cyclefold_decider, NovaCycleFoldVerifierKey};
+pub use nova_cyclefold::{
+    get_decider_template_for_cyclefold_decider,
+    get_decider_template_for_cyclefold_decider_with_foundry_test,
+    get_shared_decider_templates_for_cyclefold_deciders,
+    get_split_decider_templates_for_cyclefold_decider, NovaCycleFoldSharedVerifiers,
+    NovaCycleFoldSplitVerifier, NovaCycleFoldVerifierKey,
+};
 
 pub trait ProtocolVerifierKey: CanonicalDeserialize + CanonicalSerialize {
     const PROTOCOL_NAME: &'static str;
+    /// Version of the serialization format of the key, which is bumped whenever the key or its
+    /// serialization change, so that keys stored with another version are rejected when loaded.
+    const FORMAT_VERSION: u8 = 1;
 
     fn serialize_name<W: Write>(&self, writer: &mut W) -> Result<(), SerializationError> {
         Self::PROTOCOL_NAME
@@ -27,27 +64,55 @@ pub trait ProtocolVerifierKey: CanonicalDeserialize + CanonicalSerialize {
             .serialize_uncompressed(writer)
     }
 
+    /// Serializes the name of the protocol and the format version, followed by the key.
     fn serialize_protocol_verifier_key<W: Write>(
         &self,
         writer: &mut W,
     ) -> Result<(), SerializationError> {
         self.serialize_name(writer)?;
+        Self::FORMAT_VERSION.serialize_uncompressed(&mut *writer)?;
         self.serialize_compressed(writer)
     }
+
+    /// Deserializes a key serialized with `serialize_protocol_verifier_key`, checking that it
+    /// belongs to this protocol and format version, and validating its contents (the points are
+    /// on the curve and in the right subgroup, and the lengths of the vectors are consistent).
     fn deserialize_protocol_verifier_key<R: Read + Copy>(
         mut reader: R,
     ) -> Result<Self, SerializationError> {
         let name: String = String::deserialize_uncompressed(&mut reader)?;
-        let data = Self::deserialize_compressed(&mut reader)?;
-
         if name != Self::PROTOCOL_NAME {
             return Err(SerializationError::InvalidData);
         }
+        let version = u8::deserialize_uncompressed(&mut reader)?;
+        if version != Self::FORMAT_VERSION {
+            return Err(SerializationError::InvalidData);
+        }
+
+        Self::deserialize_compressed(&mut reader)
+    }
 
-        Ok(data)
+    /// Stores the key, as serialized by `serialize_protocol_verifier_key`, at `path`.
+    fn save_protocol_verifier_key(&self, path: impl AsRef<Path>) -> Result<(), SerializationError> {
+        let mut bytes = vec![];
+        self.serialize_protocol_verifier_key(&mut bytes)?;
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    /// Loads a key stored with `save_protocol_verifier_key`, validating it as
+    /// `deserialize_protocol_verifier_key`.
+    fn load_protocol_verifier_key(path: impl AsRef<Path>) -> Result<Self, SerializationError> {
+        let bytes = fs::read(path)?;
+        Self::deserialize_protocol_verifier_key(bytes.as_slice())
     }
 
     fn render_as_template(self, pragma: Option<String>) -> Vec<u8>;
+
+    /// Renders the verifier of the key, as `render_as_template`, and writes it to `path`.
+    fn save_solidity(self, path: impl AsRef<Path>, pragma: Option<String>) -> io::Result<()> {
+        fs::write(path, self.render_as_template(pragma))
+    }
 }
 
 #[cfg(test)]
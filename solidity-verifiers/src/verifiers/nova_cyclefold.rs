@@ -3,18 +3,25 @@
 #![allow(clippy::upper_case_acronyms)]
 
 use ark_bn254::{Bn254, Fq, Fr, G1Affine, G1Projective};
+use ark_ff::PrimeField;
 use ark_groth16::VerifyingKey as ArkG16VerifierKey;
 use ark_poly_commit::kzg10::VerifierKey as ArkKZG10VerifierKey;
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+};
 use askama::Template;
+use std::io;
 
-use folding_schemes::folding::circuits::nonnative::uint::NonNativeUintVar;
 use folding_schemes::folding::nova::decider_eth::VerifierParam as DeciderVerifierParam;
 
+use super::foundry::{foundry_test_file, get_foundry_test_for_decider};
 use super::g16::Groth16Verifier;
 use super::kzg::KZG10Verifier;
 use crate::utils::HeaderInclusion;
-use crate::{Groth16VerifierKey, KZG10VerifierKey, ProtocolVerifierKey, PRAGMA_GROTH16_VERIFIER};
+use crate::{
+    ContractNames, Groth16VerifierKey, KZG10VerifierKey, ProtocolVerifierKey, GPL3_SDPX_IDENTIFIER,
+    MIT_SDPX_IDENTIFIER, PRAGMA_GROTH16_VERIFIER,
+};
 
 pub fn get_decider_template_for_cyclefold_decider(
     nova_cyclefold_vk: NovaCycleFoldVerifierKey,
@@ -26,42 +33,352 @@ pub fn get_decider_template_for_cyclefold_decider(
         .unwrap()
 }
 
+/// Renders the decider verifier as `get_decider_template_for_cyclefold_decider`, together with a
+/// Foundry test that checks it against `calldata` when the calldata of a proof is given (see
+/// `get_foundry_test_for_decider`). Returns the file names and sources of the verifier and of
+/// the test, which have to be placed in the same directory.
+pub fn get_decider_template_for_cyclefold_decider_with_foundry_test(
+    nova_cyclefold_vk: NovaCycleFoldVerifierKey,
+    calldata: Option<&[u8]>,
+) -> Vec<(String, String)> {
+    let decider = ContractNames::default().decider;
+    let verifier_file = format!("{decider}.sol");
+    let mut artifacts = vec![(
+        verifier_file.clone(),
+        get_decider_template_for_cyclefold_decider(nova_cyclefold_vk),
+    )];
+    if let Some(calldata) = calldata {
+        artifacts.push((
+            foundry_test_file(&decider),
+            get_foundry_test_for_decider(&decider, &verifier_file, calldata, None),
+        ));
+    }
+    artifacts
+}
+
+/// Renders the decider verifier split into the Groth16 verifier, the KZG10 verifier and the
+/// NovaDecider contracts, which are deployed separately. See `NovaCycleFoldSplitVerifier`.
+pub fn get_split_decider_templates_for_cyclefold_decider(
+    nova_cyclefold_vk: NovaCycleFoldVerifierKey,
+) -> NovaCycleFoldSplitVerifier {
+    nova_cyclefold_vk.render_split_as_templates(None)
+}
+
+/// Solidity sources of the decider verifier split into three contracts, for the configurations
+/// in which the single NovaDecider contract (which embeds the Groth16 and KZG10 verifiers)
+/// exceeds the EIP-170 limit of 24,576 bytes of deployed code. The size of the Groth16 verifier
+/// grows with the number of public inputs, while the other two contracts have a fixed size.
+///
+/// The NovaDecider contract takes the same calldata as the single one, and delegates the Groth16
+/// and KZG10 checks to the other two contracts, whose addresses are set at its constructor. See
+/// `NovaCycleFoldSplitVerifier::deployment_order`.
+#[derive(Clone, Debug)]
+pub struct NovaCycleFoldSplitVerifier {
+    /// names of the contracts
+    pub names: ContractNames,
+    /// source of the Groth16 verifier contract
+    pub groth16_verifier: String,
+    /// source of the KZG10 verifier contract
+    pub kzg10_verifier: String,
+    /// source of the decider contract
+    pub decider: String,
+}
+
+impl NovaCycleFoldSplitVerifier {
+    /// Returns the steps to deploy the contracts and verify the proofs.
+    pub fn deployment_order(&self) -> String {
+        let ContractNames {
+            groth16_verifier,
+            kzg10_verifier,
+            decider,
+        } = &self.names;
+        format!(
+            "\
+1. Deploy the `{groth16_verifier}` contract of `{groth16_verifier}.sol`, with no constructor arguments.
+2. Deploy the `{kzg10_verifier}` contract of `{kzg10_verifier}.sol`, with no constructor arguments.
+3. Deploy the `{decider}` contract of `{decider}.sol`, with the addresses of the contracts
+   deployed at steps 1 and 2 as constructor arguments, in that order.
+4. Verify the proofs by calling `{decider}.verifyNovaProof`.
+"
+        )
+    }
+
+    /// Returns the file names and sources of the contracts, in the order in which they have to
+    /// be deployed.
+    pub fn artifacts(&self) -> [(String, &str); 3] {
+        [
+            (
+                format!("{}.sol", self.names.groth16_verifier),
+                &self.groth16_verifier,
+            ),
+            (
+                format!("{}.sol", self.names.kzg10_verifier),
+                &self.kzg10_verifier,
+            ),
+            (format!("{}.sol", self.names.decider), &self.decider),
+        ]
+    }
+}
+
+/// Renders the decider verifiers of several Nova+CycleFold instances, which share the code of the
+/// Groth16 and KZG10 verifiers, see `NovaCycleFoldSharedVerifiers`. `deciders` holds the name and
+/// the verifier key of each decider, and `names` the names of the shared Groth16 and KZG10
+/// verifiers (its `decider` is ignored).
+///
+/// Returns an `InvalidInput` error if two deciders have the same name, since their files would
+/// clash.
+pub fn get_shared_decider_templates_for_cyclefold_deciders(
+    deciders: Vec<(String, NovaCycleFoldVerifierKey)>,
+    names: &ContractNames,
+    pragma: Option<String>,
+) -> io::Result<NovaCycleFoldSharedVerifiers> {
+    let pragma = pragma.unwrap_or(PRAGMA_GROTH16_VERIFIER.to_string());
+    for (i, (name, _)) in deciders.iter().enumerate() {
+        if deciders[..i].iter().any(|(other, _)| other == name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("decider name {name} is repeated"),
+            ));
+        }
+    }
+
+    let shared = HeaderInclusion::<SharedVerifiers>::builder()
+        .pragma_version(pragma.clone())
+        .template(SharedVerifiers {
+            groth16_verifier: Groth16Verifier {
+                name: names.groth16_verifier.clone(),
+                constructor_vk: true,
+                ..Default::default()
+            },
+            kzg10_verifier: KZG10Verifier {
+                name: names.kzg10_verifier.clone(),
+                constructor_vk: true,
+                ..Default::default()
+            },
+            coordinate_bits: Fq::MODULUS_BIT_SIZE as usize,
+            bits_per_element: Fr::MODULUS_BIT_SIZE as usize - 1,
+        })
+        .build()
+        .render()
+        .unwrap();
+    let deciders = deciders
+        .into_iter()
+        .map(|(name, vk)| {
+            let decider_names = ContractNames {
+                decider: name.clone(),
+                ..names.clone()
+            };
+            let decider = HeaderInclusion::<NovaCycleFoldDecider>::builder()
+                .pragma_version(pragma.clone())
+                .template(NovaCycleFoldDecider {
+                    split: true,
+                    shared: true,
+                    shared_file: NovaCycleFoldSharedVerifiers::SHARED_FILE.to_string(),
+                    ..NovaCycleFoldDecider::from(vk).with_names(&decider_names)
+                })
+                .build()
+                .render()
+                .unwrap();
+            (name, decider)
+        })
+        .collect();
+
+    Ok(NovaCycleFoldSharedVerifiers {
+        names: names.clone(),
+        shared,
+        deciders,
+    })
+}
+
+/// Solidity sources of the decider verifiers of several Nova+CycleFold instances, which can be
+/// placed in the same directory without clashing. The Groth16 and KZG10 verifiers are emitted
+/// once in `SHARED_FILE`, along with the library used by all the deciders, and their verifying
+/// keys are set at their constructors instead of being hard-coded (see
+/// `Groth16VerifierKey::constructor_args` and `KZG10VerifierKey::constructor_args`), so that
+/// the same code is deployed for the verifying keys of each decider.
+///
+/// Each decider takes the same calldata as the single NovaDecider contract, and delegates the
+/// Groth16 and KZG10 checks to the contracts at the addresses set at its constructor. See
+/// `NovaCycleFoldSharedVerifiers::deployment_order`.
+#[derive(Clone, Debug)]
+pub struct NovaCycleFoldSharedVerifiers {
+    /// names of the shared contracts
+    pub names: ContractNames,
+    /// source of the shared contracts
+    pub shared: String,
+    /// name and source of each decider contract
+    pub deciders: Vec<(String, String)>,
+}
+
+impl NovaCycleFoldSharedVerifiers {
+    /// File name of the shared contracts, which is imported by the deciders.
+    pub const SHARED_FILE: &'static str = "SharedVerifiers.sol";
+
+    /// Returns the steps to deploy the contracts and verify the proofs.
+    pub fn deployment_order(&self) -> String {
+        let (groth16_verifier, kzg10_verifier) =
+            (&self.names.groth16_verifier, &self.names.kzg10_verifier);
+        let shared_file = Self::SHARED_FILE;
+        format!(
+            "\
+For each decider of this directory:
+1. Deploy the `{groth16_verifier}` contract of `{shared_file}`, with the verifying key of the
+   decider's Groth16 proofs as constructor arguments.
+2. Deploy the `{kzg10_verifier}` contract of `{shared_file}`, with the verifying key of the
+   decider's KZG10 proofs as constructor arguments. Deciders with the same KZG10 verifying key
+   can use the same deployment.
+3. Deploy the decider contract of `<decider>.sol`, with the addresses of the contracts
+   deployed at steps 1 and 2 as constructor arguments, in that order.
+4. Verify the proofs by calling `<decider>.verifyNovaProof`.
+"
+        )
+    }
+
+    /// Returns the file names and sources of the contracts, with the shared contracts first.
+    pub fn artifacts(&self) -> Vec<(String, &str)> {
+        let mut artifacts = vec![(Self::SHARED_FILE.to_string(), self.shared.as_str())];
+        artifacts.extend(
+            self.deciders
+                .iter()
+                .map(|(name, decider)| (format!("{name}.sol"), decider.as_str())),
+        );
+        artifacts
+    }
+}
+
+#[derive(Template, Default)]
+#[template(path = "shared_verifiers.askama.sol", ext = "sol")]
+struct SharedVerifiers {
+    // both rendered with `constructor_vk`, so their keys are not used
+    groth16_verifier: Groth16Verifier,
+    kzg10_verifier: KZG10Verifier,
+    coordinate_bits: usize,
+    bits_per_element: usize,
+}
+
 #[derive(Template, Default)]
 #[template(path = "nova_cyclefold_decider.askama.sol", ext = "sol")]
 pub struct NovaCycleFoldDecider {
     pp_hash: Fr, // public params hash
+    context: Fr, // application context of the IVC
     groth16_verifier: Groth16Verifier,
     kzg10_verifier: KZG10Verifier,
     // z_len denotes the FCircuit state (z_i) length
     z_len: usize,
+    // whether the decider exposes the digests of z_0 and z_i instead of their elements, and the
+    // resulting number of public inputs taken by the states
+    hash_state: bool,
+    state_inputs_len: usize,
     public_inputs_len: usize,
-    num_limbs: usize,
-    bits_per_limb: usize,
+    // bit size of the coordinates of the points, which are packed into elements of
+    // `bits_per_element` bits
+    coordinate_bits: usize,
+    bits_per_element: usize,
+    // whether the Groth16 and KZG10 verifiers are deployed as separate contracts instead of
+    // being embedded in the NovaDecider contract
+    split: bool,
+    // whether those contracts are the ones of `shared_file`, shared with other deciders, which
+    // implies `split`
+    shared: bool,
+    shared_file: String,
+    decider_name: String,
+    groth16_name: String,
+    kzg10_name: String,
+}
+
+impl NovaCycleFoldDecider {
+    fn with_names(mut self, names: &ContractNames) -> Self {
+        self.decider_name = names.decider.clone();
+        self.groth16_name = names.groth16_verifier.clone();
+        self.kzg10_name = names.kzg10_verifier.clone();
+        self.groth16_verifier.name = names.groth16_verifier.clone();
+        self.kzg10_verifier.name = names.kzg10_verifier.clone();
+        self
+    }
 }
 
 impl From<NovaCycleFoldVerifierKey> for NovaCycleFoldDecider {
     fn from(value: NovaCycleFoldVerifierKey) -> Self {
         let groth16_verifier = Groth16Verifier::from(value.g16_vk);
         let public_inputs_len = groth16_verifier.gamma_abc_len;
-        let bits_per_limb = NonNativeUintVar::<Fq>::bits_per_limb();
         Self {
             pp_hash: value.pp_hash,
+            context: value.context,
             groth16_verifier,
             kzg10_verifier: KZG10Verifier::from(value.kzg_vk),
             z_len: value.z_len,
+            hash_state: value.hash_state,
+            state_inputs_len: if value.hash_state { 2 } else { value.z_len * 2 },
             public_inputs_len,
-            num_limbs: (250_f32 / (bits_per_limb as f32)).ceil() as usize,
-            bits_per_limb,
+            coordinate_bits: Fq::MODULUS_BIT_SIZE as usize,
+            bits_per_element: Fr::MODULUS_BIT_SIZE as usize - 1,
+            split: false,
+            shared: false,
+            shared_file: String::new(),
+            decider_name: String::new(),
+            groth16_name: String::new(),
+            kzg10_name: String::new(),
         }
+        .with_names(&ContractNames::default())
     }
 }
 
-#[derive(CanonicalDeserialize, CanonicalSerialize, PartialEq, Debug, Clone)]
+// public inputs of the decider that precede the states: pp_hash, context and i
+const PREFIX_INPUTS_LEN: usize = 3;
+// elements into which a point is packed, see `point_packing.askama.sol`
+const PACKED_POINT_LEN: usize = 3;
+// public inputs of the decider that follow the states: the packed U_i.cmW, U_i.cmE and cmT, and
+// the KZG challenge, the evaluations of W and E and the batching challenge
+const SUFFIX_INPUTS_LEN: usize = 3 * PACKED_POINT_LEN + 4;
+
+#[derive(CanonicalSerialize, PartialEq, Debug, Clone)]
 pub struct NovaCycleFoldVerifierKey {
     pp_hash: Fr,
+    context: Fr,
     g16_vk: Groth16VerifierKey,
     kzg_vk: KZG10VerifierKey,
     z_len: usize,
+    hash_state: bool,
+}
+
+impl Valid for NovaCycleFoldVerifierKey {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.g16_vk.check()?;
+        self.kzg_vk.check()?;
+        if self.z_len == 0 {
+            return Err(SerializationError::InvalidData);
+        }
+        // the Groth16 key has an element for the constant `1` and one for each public input of
+        // the decider
+        let state_inputs_len = if self.hash_state { 2 } else { self.z_len * 2 };
+        if self.g16_vk.0.gamma_abc_g1.len()
+            != 1 + PREFIX_INPUTS_LEN + state_inputs_len + SUFFIX_INPUTS_LEN
+        {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for NovaCycleFoldVerifierKey {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let vk = Self {
+            pp_hash: Fr::deserialize_with_mode(&mut reader, compress, validate)?,
+            context: Fr::deserialize_with_mode(&mut reader, compress, validate)?,
+            g16_vk: Groth16VerifierKey::deserialize_with_mode(&mut reader, compress, validate)?,
+            kzg_vk: KZG10VerifierKey::deserialize_with_mode(&mut reader, compress, validate)?,
+            z_len: usize::deserialize_with_mode(&mut reader, compress, validate)?,
+            hash_state: bool::deserialize_with_mode(&mut reader, compress, validate)?,
+        };
+        if let Validate::Yes = validate {
+            vk.check()?;
+        }
+        Ok(vk)
+    }
 }
 
 impl ProtocolVerifierKey for NovaCycleFoldVerifierKey {
@@ -78,13 +395,15 @@ impl ProtocolVerifierKey for NovaCycleFoldVerifierKey {
     }
 }
 
-impl From<(Fr, Groth16VerifierKey, KZG10VerifierKey, usize)> for NovaCycleFoldVerifierKey {
-    fn from(value: (Fr, Groth16VerifierKey, KZG10VerifierKey, usize)) -> Self {
+impl From<(Fr, Fr, Groth16VerifierKey, KZG10VerifierKey, usize)> for NovaCycleFoldVerifierKey {
+    fn from(value: (Fr, Fr, Groth16VerifierKey, KZG10VerifierKey, usize)) -> Self {
         Self {
             pp_hash: value.0,
-            g16_vk: value.1,
-            kzg_vk: value.2,
-            z_len: value.3,
+            context: value.1,
+            g16_vk: value.2,
+            kzg_vk: value.3,
+            z_len: value.4,
+            hash_state: false,
         }
     }
 }
@@ -113,16 +432,91 @@ impl
         let kzg_vk = KZG10VerifierKey::from((decider_vp.cs_vp, Vec::new()));
         Self {
             pp_hash: decider_vp.pp_hash,
+            context: decider_vp.context,
             g16_vk,
             kzg_vk,
             z_len: value.1,
+            hash_state: decider_vp.hash_state,
         }
     }
 }
 
 impl NovaCycleFoldVerifierKey {
+    /// Same as `render_as_template`, with the given names for the decider contract and for the
+    /// Groth16 and KZG10 verifiers from which it inherits.
+    pub fn render_as_template_with_names(
+        self,
+        pragma: Option<String>,
+        names: &ContractNames,
+    ) -> String {
+        HeaderInclusion::<NovaCycleFoldDecider>::builder()
+            .pragma_version(pragma.unwrap_or(PRAGMA_GROTH16_VERIFIER.to_string()))
+            .template(NovaCycleFoldDecider::from(self).with_names(names))
+            .build()
+            .render()
+            .unwrap()
+    }
+
+    /// Renders the decider verifier split into three contracts, see `NovaCycleFoldSplitVerifier`.
+    /// The same `pragma` is set in all of them.
+    pub fn render_split_as_templates(self, pragma: Option<String>) -> NovaCycleFoldSplitVerifier {
+        self.render_split_as_templates_with_names(pragma, &ContractNames::default())
+    }
+
+    /// Same as `render_split_as_templates`, with the given names for the contracts.
+    pub fn render_split_as_templates_with_names(
+        self,
+        pragma: Option<String>,
+        names: &ContractNames,
+    ) -> NovaCycleFoldSplitVerifier {
+        let pragma = pragma.unwrap_or(PRAGMA_GROTH16_VERIFIER.to_string());
+        let mut decider = NovaCycleFoldDecider {
+            split: true,
+            ..NovaCycleFoldDecider::from(self).with_names(names)
+        };
+        let groth16_verifier = HeaderInclusion::<Groth16Verifier>::builder()
+            .sdpx(GPL3_SDPX_IDENTIFIER.to_string())
+            .pragma_version(pragma.clone())
+            .template(std::mem::take(&mut decider.groth16_verifier))
+            .build()
+            .render()
+            .unwrap();
+        let kzg10_verifier = HeaderInclusion::<KZG10Verifier>::builder()
+            .sdpx(MIT_SDPX_IDENTIFIER.to_string())
+            .pragma_version(pragma.clone())
+            .template(std::mem::take(&mut decider.kzg10_verifier))
+            .build()
+            .render()
+            .unwrap();
+        let decider = HeaderInclusion::<NovaCycleFoldDecider>::builder()
+            .pragma_version(pragma)
+            .template(decider)
+            .build()
+            .render()
+            .unwrap();
+        NovaCycleFoldSplitVerifier {
+            names: names.clone(),
+            groth16_verifier,
+            kzg10_verifier,
+            decider,
+        }
+    }
+
+    /// Verifying key of the decider's Groth16 proofs, whose `constructor_args` are the ones of
+    /// the shared Groth16 verifier, see `NovaCycleFoldSharedVerifiers`.
+    pub fn g16_vk(&self) -> &Groth16VerifierKey {
+        &self.g16_vk
+    }
+
+    /// Verifying key of the decider's KZG10 proofs, whose `constructor_args` are the ones of the
+    /// shared KZG10 verifier, see `NovaCycleFoldSharedVerifiers`.
+    pub fn kzg_vk(&self) -> &KZG10VerifierKey {
+        &self.kzg_vk
+    }
+
     pub fn new(
         pp_hash: Fr,
+        context: Fr,
         vkey_g16: ArkG16VerifierKey<Bn254>,
         vkey_kzg: ArkKZG10VerifierKey<Bn254>,
         crs_points: Vec<G1Affine>,
@@ -130,52 +524,75 @@ impl NovaCycleFoldVerifierKey {
     ) -> Self {
         Self {
             pp_hash,
+            context,
             g16_vk: Groth16VerifierKey::from(vkey_g16),
             kzg_vk: KZG10VerifierKey::from((vkey_kzg, crs_points)),
             z_len,
+            hash_state: false,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use ark_bn254::{constraints::GVar, Bn254, Fr, G1Projective as G1};
+    use alloy_primitives::U256;
+    use alloy_sol_types::SolValue;
+    use ark_bn254::{constraints::GVar, Bn254, Fq, Fr, G1Affine, G1Projective as G1};
     use ark_ff::PrimeField;
     use ark_groth16::Groth16;
     use ark_grumpkin::{constraints::GVar as GVar2, Projective as G2};
-    use ark_r1cs_std::alloc::AllocVar;
-    use ark_r1cs_std::fields::fp::FpVar;
-    use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
     use ark_std::Zero;
     use askama::Template;
-    use std::marker::PhantomData;
+    use crypto::{digest::Digest, sha3::Sha3};
     use std::time::Instant;
 
     use folding_schemes::{
         commitment::{kzg::KZG, pedersen::Pedersen},
         folding::{
             nova::{
-                decider_eth::{prepare_calldata, Decider as DeciderEth},
+                decider_eth::{
+                    decode_verifier_output, prepare_calldata, prepare_calldata_abi,
+                    prepare_calldata_batch, Decider as DeciderEth, NovaProof, Statement,
+                },
                 Nova, PreprocessorParam,
             },
             traits::CommittedInstanceOps,
         },
-        frontend::FCircuit,
+        frontend::{
+            utils::{CubicFCircuit, MultiInputsFCircuit},
+            FCircuit,
+        },
         transcript::poseidon::poseidon_canonical_config,
-        Decider, Error, FoldingScheme,
+        Decider, FoldingScheme,
     };
 
     use super::{DeciderVerifierParam, NovaCycleFoldDecider};
+    use crate::gas::GasHarness;
     use crate::verifiers::tests::{setup, DEFAULT_SETUP_LEN};
     use crate::{
-        evm::{compile_solidity, save_solidity, Evm},
-        utils::{get_function_selector_for_nova_cyclefold_verifier, HeaderInclusion},
-        verifiers::nova_cyclefold::get_decider_template_for_cyclefold_decider,
-        NovaCycleFoldVerifierKey, ProtocolVerifierKey,
+        evm::{
+            compile_solidity, compile_solidity_files,
+            revm::primitives::{hex, Address},
+            save_solidity, Evm, MAX_CONTRACT_SIZE,
+        },
+        utils::{
+            get_function_selector_for_nova_cyclefold_abi_verifier,
+            get_function_selector_for_nova_cyclefold_batch_verifier,
+            get_function_selector_for_nova_cyclefold_verifier, HeaderInclusion,
+            NOVA_PROOF_ABI_TYPE,
+        },
+        verifiers::foundry::foundry_test_file,
+        verifiers::nova_cyclefold::{
+            get_decider_template_for_cyclefold_decider,
+            get_decider_template_for_cyclefold_decider_with_foundry_test,
+            get_shared_decider_templates_for_cyclefold_deciders,
+            get_split_decider_templates_for_cyclefold_decider,
+        },
+        ContractNames, Groth16VerifierKey, NovaCycleFoldVerifierKey, ProtocolVerifierKey,
     };
 
     type NOVA<FC> = Nova<G1, GVar, G2, GVar2, FC, KZG<'static, Bn254>, Pedersen<G2>, false>;
-    type DECIDER<FC> = DeciderEth<
+    type DECIDER<FC, const HASH_STATE: bool> = DeciderEth<
         G1,
         GVar,
         G2,
@@ -185,152 +602,206 @@ mod tests {
         Pedersen<G2>,
         Groth16<Bn254>,
         NOVA<FC>,
+        HASH_STATE,
     >;
 
     type FS_PP<FC> = <NOVA<FC> as FoldingScheme<G1, G2, FC>>::ProverParam;
     type FS_VP<FC> = <NOVA<FC> as FoldingScheme<G1, G2, FC>>::VerifierParam;
-    type DECIDER_PP<FC> = <DECIDER<FC> as Decider<G1, G2, FC, NOVA<FC>>>::ProverParam;
-    type DECIDER_VP<FC> = <DECIDER<FC> as Decider<G1, G2, FC, NOVA<FC>>>::VerifierParam;
-
-    /// Test circuit to be folded
-    #[derive(Clone, Copy, Debug)]
-    pub struct CubicFCircuit<F: PrimeField> {
-        _f: PhantomData<F>,
-    }
-    impl<F: PrimeField> FCircuit<F> for CubicFCircuit<F> {
-        type Params = ();
-        fn new(_params: Self::Params) -> Result<Self, Error> {
-            Ok(Self { _f: PhantomData })
-        }
-        fn state_len(&self) -> usize {
-            1
-        }
-        fn external_inputs_len(&self) -> usize {
-            0
-        }
-        fn step_native(
-            &self,
-            _i: usize,
-            z_i: Vec<F>,
-            _external_inputs: Vec<F>,
-        ) -> Result<Vec<F>, Error> {
-            Ok(vec![z_i[0] * z_i[0] * z_i[0] + z_i[0] + F::from(5_u32)])
-        }
-        fn generate_step_constraints(
-            &self,
-            cs: ConstraintSystemRef<F>,
-            _i: usize,
-            z_i: Vec<FpVar<F>>,
-            _external_inputs: Vec<FpVar<F>>,
-        ) -> Result<Vec<FpVar<F>>, SynthesisError> {
-            let five = FpVar::<F>::new_constant(cs.clone(), F::from(5u32))?;
-            let z_i = z_i[0].clone();
-
-            Ok(vec![&z_i * &z_i * &z_i + &z_i + &five])
-        }
-    }
+    // the decider params are the same type for both layouts of the public inputs
+    type DECIDER_PP<FC> = <DECIDER<FC, false> as Decider<G1, G2, FC, NOVA<FC>>>::ProverParam;
+    type DECIDER_VP<FC> = <DECIDER<FC, false> as Decider<G1, G2, FC, NOVA<FC>>>::VerifierParam;
 
-    /// This is the circuit that we want to fold, it implements the FCircuit trait. The parameter z_i
-    /// denotes the current state which contains 5 elements, and z_{i+1} denotes the next state which
-    /// we get by applying the step.
-    /// In this example we set z_i and z_{i+1} to have five elements, and at each step we do different
-    /// operations on each of them.
-    #[derive(Clone, Copy, Debug)]
-    pub struct MultiInputsFCircuit<F: PrimeField> {
-        _f: PhantomData<F>,
-    }
-    impl<F: PrimeField> FCircuit<F> for MultiInputsFCircuit<F> {
-        type Params = ();
-
-        fn new(_params: Self::Params) -> Result<Self, Error> {
-            Ok(Self { _f: PhantomData })
-        }
-        fn state_len(&self) -> usize {
-            5
-        }
-        fn external_inputs_len(&self) -> usize {
-            0
-        }
+    #[test]
+    fn nova_cyclefold_vk_serde_roundtrip() {
+        let nova_cyclefold_vk = test_vk(1, true);
 
-        /// computes the next state values in place, assigning z_{i+1} into z_i, and computing the new
-        /// z_{i+1}
-        fn step_native(
-            &self,
-            _i: usize,
-            z_i: Vec<F>,
-            _external_inputs: Vec<F>,
-        ) -> Result<Vec<F>, Error> {
-            let a = z_i[0] + F::from(4_u32);
-            let b = z_i[1] + F::from(40_u32);
-            let c = z_i[2] * F::from(4_u32);
-            let d = z_i[3] * F::from(40_u32);
-            let e = z_i[4] + F::from(100_u32);
-
-            Ok(vec![a, b, c, d, e])
-        }
+        let mut bytes = vec![];
+        nova_cyclefold_vk
+            .serialize_protocol_verifier_key(&mut bytes)
+            .unwrap();
+        let obtained_nova_cyclefold_vk =
+            NovaCycleFoldVerifierKey::deserialize_protocol_verifier_key(bytes.as_slice()).unwrap();
 
-        /// generates the constraints for the step of F for the given z_i
-        fn generate_step_constraints(
-            &self,
-            cs: ConstraintSystemRef<F>,
-            _i: usize,
-            z_i: Vec<FpVar<F>>,
-            _external_inputs: Vec<FpVar<F>>,
-        ) -> Result<Vec<FpVar<F>>, SynthesisError> {
-            let four = FpVar::<F>::new_constant(cs.clone(), F::from(4u32))?;
-            let forty = FpVar::<F>::new_constant(cs.clone(), F::from(40u32))?;
-            let onehundred = FpVar::<F>::new_constant(cs.clone(), F::from(100u32))?;
-            let a = z_i[0].clone() + four.clone();
-            let b = z_i[1].clone() + forty.clone();
-            let c = z_i[2].clone() * four;
-            let d = z_i[3].clone() * forty;
-            let e = z_i[4].clone() + onehundred;
-
-            Ok(vec![a, b, c, d, e])
-        }
+        assert_eq!(nova_cyclefold_vk, obtained_nova_cyclefold_vk)
     }
 
-    #[test]
-    fn nova_cyclefold_vk_serde_roundtrip() {
-        let (pp_hash, _, kzg_vk, _, g16_vk, _) = setup(DEFAULT_SETUP_LEN);
+    /// Returns a verifier key from the test setup, whose Groth16 key is padded to the number of
+    /// public inputs of the decider, so that it passes the validation on load.
+    fn test_vk(z_len: usize, hash_state: bool) -> NovaCycleFoldVerifierKey {
+        let (pp_hash, _, kzg_vk, _, mut g16_vk, _) = setup(DEFAULT_SETUP_LEN);
+        let state_inputs_len = if hash_state { 2 } else { z_len * 2 };
+        g16_vk.gamma_abc_g1 = vec![g16_vk.gamma_abc_g1[0]; 1 + 3 + state_inputs_len + 13];
 
         let decider_vp = DeciderVerifierParam {
             pp_hash,
+            context: Fr::from(1_u32),
+            hash_state,
             snark_vp: g16_vk,
             cs_vp: kzg_vk,
         };
-        let nova_cyclefold_vk = NovaCycleFoldVerifierKey::from((decider_vp, 1));
+        NovaCycleFoldVerifierKey::from((decider_vp, z_len))
+    }
 
+    #[test]
+    fn nova_cyclefold_vk_rejects_invalid_data() {
+        let nova_cyclefold_vk = test_vk(2, false);
         let mut bytes = vec![];
         nova_cyclefold_vk
             .serialize_protocol_verifier_key(&mut bytes)
             .unwrap();
-        let obtained_nova_cyclefold_vk =
-            NovaCycleFoldVerifierKey::deserialize_protocol_verifier_key(bytes.as_slice()).unwrap();
 
-        assert_eq!(nova_cyclefold_vk, obtained_nova_cyclefold_vk)
+        // key of another protocol
+        assert!(Groth16VerifierKey::deserialize_protocol_verifier_key(bytes.as_slice()).is_err());
+
+        // another format version, which follows the length and the bytes of the protocol name
+        let mut invalid_bytes = bytes.clone();
+        invalid_bytes[8 + NovaCycleFoldVerifierKey::PROTOCOL_NAME.len()] += 1;
+        assert!(NovaCycleFoldVerifierKey::deserialize_protocol_verifier_key(
+            invalid_bytes.as_slice()
+        )
+        .is_err());
+
+        // Groth16 key inconsistent with the length of the state
+        let mut invalid_bytes = vec![];
+        NovaCycleFoldVerifierKey {
+            z_len: 3,
+            ..nova_cyclefold_vk.clone()
+        }
+        .serialize_protocol_verifier_key(&mut invalid_bytes)
+        .unwrap();
+        assert!(NovaCycleFoldVerifierKey::deserialize_protocol_verifier_key(
+            invalid_bytes.as_slice()
+        )
+        .is_err());
+
+        // point which is not on the curve, whose x coordinate has no y
+        let mut invalid_bytes = vec![];
+        let mut invalid_vk = nova_cyclefold_vk;
+        let x = (1_u32..)
+            .map(Fq::from)
+            .find(|x| G1Affine::get_point_from_x_unchecked(*x, false).is_none())
+            .unwrap();
+        invalid_vk.g16_vk.0.gamma_abc_g1[1] = G1Affine::new_unchecked(x, Fq::from(0_u32));
+        invalid_vk
+            .serialize_protocol_verifier_key(&mut invalid_bytes)
+            .unwrap();
+        assert!(NovaCycleFoldVerifierKey::deserialize_protocol_verifier_key(
+            invalid_bytes.as_slice()
+        )
+        .is_err());
+    }
+
+    // Stores a verifier key, and checks that the verifiers generated from the reloaded key are
+    // byte-identical to the ones generated from the original key
+    #[test]
+    fn nova_cyclefold_verifier_from_reloaded_vk() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        for hash_state in [false, true] {
+            let nova_cyclefold_vk = test_vk(2, hash_state);
+            let vk_path = dir.join(format!("nova_cyclefold_vk_{}.bin", hash_state));
+            nova_cyclefold_vk
+                .save_protocol_verifier_key(&vk_path)
+                .unwrap();
+            let reloaded_vk =
+                NovaCycleFoldVerifierKey::load_protocol_verifier_key(&vk_path).unwrap();
+            assert_eq!(nova_cyclefold_vk, reloaded_vk);
+
+            let fresh_path = dir.join(format!("NovaDecider_fresh_{}.sol", hash_state));
+            let reloaded_path = dir.join(format!("NovaDecider_reloaded_{}.sol", hash_state));
+            nova_cyclefold_vk
+                .clone()
+                .save_solidity(&fresh_path, None)
+                .unwrap();
+            reloaded_vk
+                .clone()
+                .save_solidity(&reloaded_path, None)
+                .unwrap();
+            assert_eq!(
+                std::fs::read(fresh_path).unwrap(),
+                std::fs::read(reloaded_path).unwrap()
+            );
+
+            let fresh_split = nova_cyclefold_vk.render_split_as_templates(None);
+            let reloaded_split = reloaded_vk.render_split_as_templates(None);
+            assert_eq!(fresh_split.artifacts(), reloaded_split.artifacts());
+        }
     }
 
     #[test]
     fn nova_cyclefold_decider_template_renders() {
         let (pp_hash, _, kzg_vk, _, g16_vk, _) = setup(DEFAULT_SETUP_LEN);
-        let decider_vp = DeciderVerifierParam {
-            pp_hash,
-            snark_vp: g16_vk,
-            cs_vp: kzg_vk,
-        };
-        let nova_cyclefold_vk = NovaCycleFoldVerifierKey::from((decider_vp, 1));
+        for hash_state in [false, true] {
+            let decider_vp = DeciderVerifierParam {
+                pp_hash,
+                context: Fr::from(1_u32),
+                hash_state,
+                snark_vp: g16_vk.clone(),
+                cs_vp: kzg_vk.clone(),
+            };
+            let nova_cyclefold_vk = NovaCycleFoldVerifierKey::from((decider_vp, 1));
 
-        let decider_solidity_code = HeaderInclusion::<NovaCycleFoldDecider>::builder()
-            .template(nova_cyclefold_vk)
-            .build();
+            let decider_solidity_code = HeaderInclusion::<NovaCycleFoldDecider>::builder()
+                .template(nova_cyclefold_vk)
+                .build();
 
-        save_solidity("NovaDecider.sol", &decider_solidity_code.render().unwrap());
+            save_solidity("NovaDecider.sol", &decider_solidity_code.render().unwrap());
+        }
+    }
+
+    /// Deploys the decider verifier, either as the single NovaDecider contract or split into the
+    /// Groth16Verifier, KZG10Verifier and NovaDecider contracts, checking that each deployed
+    /// contract fits in the EIP-170 limit. Returns the address of the NovaDecider contract.
+    fn deploy_decider_verifier(
+        evm: &mut Evm,
+        nova_cyclefold_vk: NovaCycleFoldVerifierKey,
+        split: bool,
+    ) -> Address {
+        if !split {
+            let decider_solidity_code =
+                get_decider_template_for_cyclefold_decider(nova_cyclefold_vk);
+            let bytecode = compile_solidity(decider_solidity_code, "NovaDecider");
+            let (address, code_size) = evm.create_with_code_size(bytecode);
+            assert!(code_size <= MAX_CONTRACT_SIZE);
+            return address;
+        }
+
+        let split_verifier = get_split_decider_templates_for_cyclefold_decider(nova_cyclefold_vk);
+        for (name, solidity) in split_verifier.artifacts() {
+            save_solidity(name, solidity);
+        }
+
+        let mut deploy = |solidity: &str, contract_name: &str, constructor_args: &[Address]| {
+            let bytecode = [
+                compile_solidity(solidity, contract_name),
+                address_args(constructor_args),
+            ]
+            .concat();
+            let (address, code_size) = evm.create_with_code_size(bytecode);
+            println!("{} deployed bytecode size: {}", contract_name, code_size);
+            assert!(code_size <= MAX_CONTRACT_SIZE);
+            address
+        };
+        let groth16_address = deploy(&split_verifier.groth16_verifier, "Groth16Verifier", &[]);
+        let kzg10_address = deploy(&split_verifier.kzg10_verifier, "KZG10Verifier", &[]);
+        deploy(
+            &split_verifier.decider,
+            "NovaDecider",
+            &[groth16_address, kzg10_address],
+        )
+    }
+
+    /// ABI-encoded constructor arguments of the given addresses, each left-padded to 32 bytes.
+    fn address_args(addresses: &[Address]) -> Vec<u8> {
+        addresses
+            .iter()
+            .flat_map(|address| [&[0u8; 12], address.as_slice()].concat())
+            .collect()
     }
 
     /// Initializes Nova parameters and DeciderEth parameters. Only for test purposes.
     #[allow(clippy::type_complexity)]
-    fn init_params<FC: FCircuit<Fr, Params = ()>>(
+    fn init_params<FC: FCircuit<Fr, Params = ()>, const HASH_STATE: bool>(
     ) -> ((FS_PP<FC>, FS_VP<FC>), (DECIDER_PP<FC>, DECIDER_VP<FC>)) {
         let mut rng = rand::rngs::OsRng;
         let poseidon_config = poseidon_canonical_config::<Fr>();
@@ -349,7 +820,8 @@ mod tests {
         )
         .unwrap();
         let decider_params =
-            DECIDER::preprocess(&mut rng, nova_params.clone(), nova.clone()).unwrap();
+            DECIDER::<FC, HASH_STATE>::preprocess(&mut rng, nova_params.clone(), nova.clone())
+                .unwrap();
 
         (nova_params, decider_params)
     }
@@ -361,13 +833,20 @@ mod tests {
     /// - generates a DeciderEth proof, and executes it through the EVM
     /// - modifies the calldata and checks that it does not pass the EVM check
     /// - modifies the z_0 and checks that it does not pass the EVM check
+    ///
+    /// The verifier is deployed as a single contract, or split into three contracts when `split`
+    /// is set. Returns the gas used by the verification.
     #[allow(clippy::type_complexity)]
-    fn nova_cyclefold_solidity_verifier_opt<FC: FCircuit<Fr, Params = ()>>(
+    fn nova_cyclefold_solidity_verifier_opt<
+        FC: FCircuit<Fr, Params = ()>,
+        const HASH_STATE: bool,
+    >(
         fs_params: (FS_PP<FC>, FS_VP<FC>),
         decider_params: (DECIDER_PP<FC>, DECIDER_VP<FC>),
         z_0: Vec<Fr>,
         n_steps: usize,
-    ) {
+        split: bool,
+    ) -> u64 {
         let (decider_pp, decider_vp) = decider_params;
 
         let f_circuit = FC::new(()).unwrap();
@@ -379,14 +858,15 @@ mod tests {
 
         let mut nova = NOVA::<FC>::init(&fs_params, f_circuit, z_0).unwrap();
         for _ in 0..n_steps {
-            nova.prove_step(&mut rng, vec![], None).unwrap();
+            nova.prove_step(&mut rng, FC::ExternalInputs::default(), None)
+                .unwrap();
         }
 
         let start = Instant::now();
-        let proof = DECIDER::<FC>::prove(rng, decider_pp, nova.clone()).unwrap();
+        let proof = DECIDER::<FC, HASH_STATE>::prove(rng, decider_pp, nova.clone()).unwrap();
         println!("generated Decider proof: {:?}", start.elapsed());
 
-        let verified = DECIDER::<FC>::verify(
+        let verified = DECIDER::<FC, HASH_STATE>::verify(
             decider_vp,
             nova.i,
             nova.z_0.clone(),
@@ -404,24 +884,24 @@ mod tests {
         let calldata: Vec<u8> = prepare_calldata(
             function_selector,
             nova.i,
-            nova.z_0,
-            nova.z_i,
+            nova.z_0.clone(),
+            nova.z_i.clone(),
             &nova.U_i,
             &nova.u_i,
             proof,
         )
         .unwrap();
 
-        let decider_solidity_code = get_decider_template_for_cyclefold_decider(nova_cyclefold_vk);
-
-        let nova_cyclefold_verifier_bytecode =
-            compile_solidity(decider_solidity_code, "NovaDecider");
-
         let mut evm = Evm::default();
-        let verifier_address = evm.create(nova_cyclefold_verifier_bytecode);
+        let verifier_address = deploy_decider_verifier(&mut evm, nova_cyclefold_vk, split);
 
-        let (_, output) = evm.call(verifier_address, calldata.clone());
-        assert_eq!(*output.last().unwrap(), 1);
+        let (gas_used, output) = evm.call(verifier_address, calldata.clone());
+        // the verifier returns the verified i and z_i
+        assert_eq!(
+            decode_verifier_output(&output, nova.z_i.len()).unwrap(),
+            (nova.i, nova.z_i)
+        );
+        println!("NovaDecider verification gas used: {}", gas_used);
 
         // change i to make calldata invalid, placed between bytes 4 - 35
         let mut invalid_calldata = calldata.clone();
@@ -440,26 +920,30 @@ mod tests {
         invalid_calldata[99] += 1;
         let (_, output) = evm.call(verifier_address, invalid_calldata.clone());
         assert_eq!(*output.last().unwrap(), 0);
+
+        gas_used
     }
 
     #[test]
     fn nova_cyclefold_solidity_verifier() {
-        let (nova_params, decider_params) = init_params::<CubicFCircuit<Fr>>();
+        let (nova_params, decider_params) = init_params::<CubicFCircuit<Fr>, false>();
         let z_0 = vec![Fr::from(3_u32)];
-        nova_cyclefold_solidity_verifier_opt::<CubicFCircuit<Fr>>(
+        nova_cyclefold_solidity_verifier_opt::<CubicFCircuit<Fr>, false>(
             nova_params.clone(),
             decider_params.clone(),
             z_0.clone(),
             2,
+            false,
         );
-        nova_cyclefold_solidity_verifier_opt::<CubicFCircuit<Fr>>(
+        nova_cyclefold_solidity_verifier_opt::<CubicFCircuit<Fr>, false>(
             nova_params,
             decider_params,
             z_0,
             3,
+            false,
         );
 
-        let (nova_params, decider_params) = init_params::<MultiInputsFCircuit<Fr>>();
+        let (nova_params, decider_params) = init_params::<MultiInputsFCircuit<Fr>, false>();
         let z_0 = vec![
             Fr::from(1_u32),
             Fr::from(1_u32),
@@ -467,17 +951,604 @@ mod tests {
             Fr::from(1_u32),
             Fr::from(1_u32),
         ];
-        nova_cyclefold_solidity_verifier_opt::<MultiInputsFCircuit<Fr>>(
+        nova_cyclefold_solidity_verifier_opt::<MultiInputsFCircuit<Fr>, false>(
             nova_params.clone(),
             decider_params.clone(),
             z_0.clone(),
             2,
+            false,
         );
-        nova_cyclefold_solidity_verifier_opt::<MultiInputsFCircuit<Fr>>(
+        nova_cyclefold_solidity_verifier_opt::<MultiInputsFCircuit<Fr>, false>(
             nova_params,
             decider_params,
             z_0.clone(),
             3,
+            false,
+        );
+    }
+
+    // Compares the verification gas of the decider that exposes the full states as public inputs
+    // with the one of the decider that exposes their digests
+    #[test]
+    fn nova_cyclefold_solidity_verifier_hash_state() {
+        let z_0 = vec![Fr::from(1_u32); 5];
+
+        let (nova_params, decider_params) = init_params::<MultiInputsFCircuit<Fr>, false>();
+        let gas_full = nova_cyclefold_solidity_verifier_opt::<MultiInputsFCircuit<Fr>, false>(
+            nova_params,
+            decider_params,
+            z_0.clone(),
+            2,
+            false,
+        );
+
+        let (nova_params, decider_params) = init_params::<MultiInputsFCircuit<Fr>, true>();
+        let gas_hashed = nova_cyclefold_solidity_verifier_opt::<MultiInputsFCircuit<Fr>, true>(
+            nova_params,
+            decider_params,
+            z_0,
+            2,
+            false,
+        );
+
+        println!(
+            "NovaDecider verification gas used with |z_i| = 5: {} with the full states, {} with their digests",
+            gas_full, gas_hashed
+        );
+        assert!(gas_hashed < gas_full);
+    }
+
+    // Verifies proofs through the verifier split into three contracts, for both layouts of the
+    // states in the public inputs
+    #[test]
+    fn nova_cyclefold_split_solidity_verifier() {
+        let (nova_params, decider_params) = init_params::<MultiInputsFCircuit<Fr>, false>();
+        let z_0 = vec![Fr::from(1_u32); 5];
+        nova_cyclefold_solidity_verifier_opt::<MultiInputsFCircuit<Fr>, false>(
+            nova_params,
+            decider_params,
+            z_0.clone(),
+            2,
+            true,
+        );
+
+        let (nova_params, decider_params) = init_params::<MultiInputsFCircuit<Fr>, true>();
+        nova_cyclefold_solidity_verifier_opt::<MultiInputsFCircuit<Fr>, true>(
+            nova_params,
+            decider_params,
+            z_0,
+            2,
+            true,
         );
     }
+
+    // Verifies a batch of proofs through `verifyBatch`, comparing its gas per proof with the gas
+    // of verifying a single proof, and checks that both the contract and `verify_batch` point to
+    // the same invalid proof
+    #[test]
+    fn nova_cyclefold_solidity_verifier_batch() {
+        const N_PROOFS: usize = 10;
+        let (fs_params, (decider_pp, decider_vp)) = init_params::<CubicFCircuit<Fr>, false>();
+        let mut rng = rand::rngs::OsRng;
+
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let nova_cyclefold_vk =
+            NovaCycleFoldVerifierKey::from((decider_vp.clone(), f_circuit.state_len()));
+        let mut nova = NOVA::init(&fs_params, f_circuit, vec![Fr::from(3_u32)]).unwrap();
+        nova.prove_step(&mut rng, (), None).unwrap();
+
+        let mut statements = vec![];
+        let mut proofs = vec![];
+        for _ in 0..N_PROOFS {
+            nova.prove_step(&mut rng, (), None).unwrap();
+            proofs.push(
+                DECIDER::<CubicFCircuit<Fr>, false>::prove(rng, decider_pp.clone(), nova.clone())
+                    .unwrap(),
+            );
+            statements.push(Statement {
+                i: nova.i,
+                z_0: nova.z_0.clone(),
+                z_i: nova.z_i.clone(),
+                running_commitments: nova.U_i.get_commitments(),
+                incoming_commitments: nova.u_i.get_commitments(),
+            });
+        }
+        DECIDER::<CubicFCircuit<Fr>, false>::verify_batch(rng, &decider_vp, &statements, &proofs)
+            .unwrap();
+
+        let single_selector = get_function_selector_for_nova_cyclefold_verifier(3);
+        let batch_selector = get_function_selector_for_nova_cyclefold_batch_verifier();
+        for split in [false, true] {
+            let mut evm = Evm::default();
+            let verifier_address =
+                deploy_decider_verifier(&mut evm, nova_cyclefold_vk.clone(), split);
+
+            let calldata = prepare_calldata(
+                single_selector,
+                nova.i,
+                nova.z_0.clone(),
+                nova.z_i.clone(),
+                &nova.U_i,
+                &nova.u_i,
+                proofs[N_PROOFS - 1].clone(),
+            )
+            .unwrap();
+            let (gas_single, output) = evm.call(verifier_address, calldata);
+            assert_eq!(output[31], 1);
+
+            let calldata = prepare_calldata_batch(batch_selector, &statements, &proofs).unwrap();
+            let (gas_batch, output) = evm.call(verifier_address, calldata);
+            assert_eq!(*output.last().unwrap(), 1);
+
+            let gas_per_proof = gas_batch / N_PROOFS as u64;
+            println!(
+                "NovaDecider verification gas used (split: {}): {} for a single proof, {} per proof for a batch of {}",
+                split, gas_single, gas_per_proof, N_PROOFS
+            );
+            assert!(gas_per_proof < gas_single);
+
+            // an invalid statement makes the batch revert with its index
+            let mut invalid_statements = statements.clone();
+            invalid_statements[3].z_i[0] += Fr::from(1_u32);
+            let calldata =
+                prepare_calldata_batch(batch_selector, &invalid_statements, &proofs).unwrap();
+            let (_, output) = evm.call(verifier_address, calldata);
+            assert!(String::from_utf8_lossy(&output).contains("Batch: invalid proof at index 3"));
+        }
+
+        let mut invalid_statements = statements.clone();
+        invalid_statements[3].z_i[0] += Fr::from(1_u32);
+        assert!(matches!(
+            DECIDER::<CubicFCircuit<Fr>, false>::verify_batch(
+                rng,
+                &decider_vp,
+                &invalid_statements,
+                &proofs
+            ),
+            Err(folding_schemes::Error::BatchVerificationFail(3))
+        ));
+    }
+
+    // Verifies a proof through the `verifyNovaProof(NovaProof)` entrypoint, whose calldata is
+    // decoded by the Solidity ABI decoder, checking that it is accepted as the packed one
+    #[test]
+    fn nova_cyclefold_solidity_verifier_abi() {
+        let (fs_params, (decider_pp, decider_vp)) = init_params::<CubicFCircuit<Fr>, false>();
+        let mut rng = rand::rngs::OsRng;
+
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let nova_cyclefold_vk = NovaCycleFoldVerifierKey::from((decider_vp, f_circuit.state_len()));
+        let mut nova = NOVA::init(&fs_params, f_circuit, vec![Fr::from(3_u32)]).unwrap();
+        for _ in 0..2 {
+            nova.prove_step(&mut rng, (), None).unwrap();
+        }
+        let proof =
+            DECIDER::<CubicFCircuit<Fr>, false>::prove(rng, decider_pp, nova.clone()).unwrap();
+
+        let prepare = |selector, z_i: Vec<Fr>, abi: bool| {
+            let prepare_fn = if abi {
+                prepare_calldata_abi
+            } else {
+                prepare_calldata
+            };
+            prepare_fn(
+                selector,
+                nova.i,
+                nova.z_0.clone(),
+                z_i,
+                &nova.U_i,
+                &nova.u_i,
+                proof.clone(),
+            )
+            .unwrap()
+        };
+        let abi_selector = get_function_selector_for_nova_cyclefold_abi_verifier();
+        let packed_calldata = prepare(
+            get_function_selector_for_nova_cyclefold_verifier(nova.z_0.len() * 2 + 1),
+            nova.z_i.clone(),
+            false,
+        );
+        let abi_calldata = prepare(abi_selector, nova.z_i.clone(), true);
+        let (nova_proof,) = <(NovaProof,)>::abi_decode_params(&abi_calldata[4..], true).unwrap();
+        let word = |value: Fr| U256::from_limbs(value.into_bigint().0);
+        assert_eq!(nova_proof.i, word(nova.i));
+        assert_eq!(
+            nova_proof.z0,
+            nova.z_0.iter().copied().map(word).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            nova_proof.zi,
+            nova.z_i.iter().copied().map(word).collect::<Vec<_>>()
+        );
+        // the fields of the struct are the words of the packed calldata
+        assert_eq!(
+            [
+                vec![nova_proof.i],
+                nova_proof.z0.clone(),
+                nova_proof.zi.clone(),
+                nova_proof.U_i_cmW.to_vec(),
+                nova_proof.U_i_cmE.to_vec(),
+                nova_proof.u_i_cmW.to_vec(),
+                nova_proof.cmT.to_vec(),
+                vec![nova_proof.r],
+                nova_proof.pA.to_vec(),
+                nova_proof.pB.concat(),
+                nova_proof.pC.to_vec(),
+                vec![nova_proof.kzg_challenge, nova_proof.kzg_batching_challenge],
+                nova_proof.kzg_evaluations.to_vec(),
+                nova_proof.kzg_proof.to_vec(),
+            ]
+            .concat()
+            .iter()
+            .flat_map(|w| w.to_be_bytes::<32>())
+            .collect::<Vec<_>>(),
+            packed_calldata[4..]
+        );
+
+        let mut invalid_z_i = nova.z_i.clone();
+        invalid_z_i[0] += Fr::from(1_u32);
+        let invalid_calldata = prepare(abi_selector, invalid_z_i, true);
+
+        for split in [false, true] {
+            let mut evm = Evm::default();
+            let verifier_address =
+                deploy_decider_verifier(&mut evm, nova_cyclefold_vk.clone(), split);
+
+            let (_, packed_output) = evm.call(verifier_address, packed_calldata.clone());
+            let (_, abi_output) = evm.call(verifier_address, abi_calldata.clone());
+            assert_eq!(
+                decode_verifier_output(&abi_output, nova.z_i.len()).unwrap(),
+                (nova.i, nova.z_i.clone())
+            );
+            assert_eq!(abi_output, packed_output);
+
+            let (_, output) = evm.call(verifier_address, invalid_calldata.clone());
+            assert!(String::from_utf8_lossy(&output).contains("Groth16: verifying proof failed"));
+
+            // the ABI decoder rejects a truncated struct
+            let truncated_calldata = abi_calldata[..abi_calldata.len() - 32].to_vec();
+            let (_, output) = evm.call(verifier_address, truncated_calldata);
+            assert!(output.is_empty());
+        }
+    }
+
+    /// Example of a contract acting on the state verified by the NovaDecider, which only accepts
+    /// proofs of later steps than the last one it has seen.
+    const STATE_CONSUMER: &str = r#"
+contract StateConsumer {
+    NovaDecider public immutable verifier;
+    uint256 public step;
+    uint256[] public state;
+
+    constructor(address _verifier) {
+        verifier = NovaDecider(_verifier);
+    }
+
+    function advance(NovaDecider.NovaProof calldata proof) external {
+        (bool ok, uint256 i, uint256[] memory z_i) = verifier.verifyNovaProof(proof);
+        require(ok, "StateConsumer: invalid proof");
+        require(i > step, "StateConsumer: stale state");
+        step = i;
+        state = z_i;
+    }
+}
+"#;
+
+    fn selector(signature: &str) -> Vec<u8> {
+        let mut hasher = Sha3::keccak256();
+        hasher.input_str(signature);
+        let hash = &mut [0u8; 32];
+        hasher.result(hash);
+        hash[..4].to_vec()
+    }
+
+    // Updates the state kept by a consumer contract with the i and z_i returned by the verifier,
+    // checking that they are the ones of the `NovaProof` decoded from the calldata
+    #[test]
+    fn nova_cyclefold_solidity_verifier_state_consumer() {
+        let (vk, calldata) =
+            decider_vk_and_calldata::<CubicFCircuit<Fr>>(vec![Fr::from(3_u32)], true);
+        let (nova_proof,) = <(NovaProof,)>::abi_decode_params(&calldata[4..], true).unwrap();
+        let source = get_decider_template_for_cyclefold_decider(vk) + STATE_CONSUMER;
+
+        let mut evm = Evm::default();
+        let verifier_address = evm.create(compile_solidity(&source, "NovaDecider"));
+        let consumer_address = evm.create(
+            [
+                compile_solidity(&source, "StateConsumer"),
+                address_args(&[verifier_address]),
+            ]
+            .concat(),
+        );
+
+        let advance_calldata = [
+            selector(&format!("advance({})", NOVA_PROOF_ABI_TYPE)),
+            calldata[4..].to_vec(),
+        ]
+        .concat();
+        let (_, output) = evm.call(consumer_address, advance_calldata.clone());
+        assert!(output.is_empty());
+
+        let (_, step) = evm.call(consumer_address, selector("step()"));
+        assert_eq!(step, nova_proof.i.to_be_bytes::<32>());
+        for (k, z) in nova_proof.zi.iter().enumerate() {
+            let (_, state) = evm.call(
+                consumer_address,
+                [selector("state(uint256)"), vec![0; 31], vec![k as u8]].concat(),
+            );
+            assert_eq!(state, z.to_be_bytes::<32>());
+        }
+
+        // the same proof can not be applied twice
+        let (_, output) = evm.call(consumer_address, advance_calldata);
+        assert!(String::from_utf8_lossy(&output).contains("StateConsumer: stale state"));
+    }
+
+    /// Runs two steps of Nova for the given FCircuit, and returns the verifier key of its decider
+    /// together with the calldata of a decider proof, for the `verifyNovaProof(NovaProof)`
+    /// entrypoint if `abi` is set, or for the packed one otherwise.
+    fn decider_vk_and_calldata<FC: FCircuit<Fr, Params = ()>>(
+        z_0: Vec<Fr>,
+        abi: bool,
+    ) -> (NovaCycleFoldVerifierKey, Vec<u8>) {
+        let (fs_params, (decider_pp, decider_vp)) = init_params::<FC, false>();
+        let mut rng = rand::rngs::OsRng;
+
+        let f_circuit = FC::new(()).unwrap();
+        let vk = NovaCycleFoldVerifierKey::from((decider_vp, f_circuit.state_len()));
+        let mut nova = NOVA::<FC>::init(&fs_params, f_circuit, z_0).unwrap();
+        for _ in 0..2 {
+            nova.prove_step(&mut rng, FC::ExternalInputs::default(), None)
+                .unwrap();
+        }
+        let proof = DECIDER::<FC, false>::prove(rng, decider_pp, nova.clone()).unwrap();
+
+        let calldata = if abi {
+            prepare_calldata_abi(
+                get_function_selector_for_nova_cyclefold_abi_verifier(),
+                nova.i,
+                nova.z_0,
+                nova.z_i,
+                &nova.U_i,
+                &nova.u_i,
+                proof,
+            )
+        } else {
+            prepare_calldata(
+                get_function_selector_for_nova_cyclefold_verifier(nova.z_0.len() * 2 + 1),
+                nova.i,
+                nova.z_0,
+                nova.z_i,
+                &nova.U_i,
+                &nova.u_i,
+                proof,
+            )
+        }
+        .unwrap();
+        (vk, calldata)
+    }
+
+    // Generates the verifiers of two deciders into the same directory, sharing the Groth16 and
+    // KZG10 verifiers, compiles all the sources together, and verifies a proof of each decider
+    // through the shared verifiers deployed with its verifying keys
+    #[test]
+    fn nova_cyclefold_shared_solidity_verifiers() {
+        let deciders = vec![
+            (
+                "CubicNovaDecider".to_string(),
+                decider_vk_and_calldata::<CubicFCircuit<Fr>>(vec![Fr::from(3_u32)], false),
+            ),
+            (
+                "MultiInputsNovaDecider".to_string(),
+                decider_vk_and_calldata::<MultiInputsFCircuit<Fr>>(vec![Fr::from(1_u32); 5], false),
+            ),
+        ];
+        let names = ContractNames {
+            groth16_verifier: "SharedGroth16Verifier".to_string(),
+            kzg10_verifier: "SharedKZG10Verifier".to_string(),
+            ..Default::default()
+        };
+
+        let shared_verifiers = get_shared_decider_templates_for_cyclefold_deciders(
+            deciders
+                .iter()
+                .map(|(name, (vk, _))| (name.clone(), vk.clone()))
+                .collect(),
+            &names,
+            None,
+        )
+        .unwrap();
+        // deciders with the same name would be written to the same file
+        let (name, (vk, _)) = &deciders[0];
+        let repeated = get_shared_decider_templates_for_cyclefold_deciders(
+            vec![(name.clone(), vk.clone()), (name.clone(), vk.clone())],
+            &names,
+            None,
+        );
+        assert_eq!(
+            repeated.unwrap_err().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+
+        let artifacts = shared_verifiers.artifacts();
+        for (file, solidity) in &artifacts {
+            save_solidity(file, solidity);
+        }
+        // the sources are compiled together, so any clash among them makes the compilation fail
+        let files = artifacts
+            .iter()
+            .map(|(file, _)| file.as_str())
+            .collect::<Vec<_>>();
+        let compile =
+            |contract_name: &str| compile_solidity_files("generated", &files, contract_name);
+        let groth16_bytecode = compile(&names.groth16_verifier);
+        let kzg10_bytecode = compile(&names.kzg10_verifier);
+
+        let mut evm = Evm::default();
+        for (name, (vk, calldata)) in deciders {
+            let groth16_address =
+                evm.create([groth16_bytecode.clone(), vk.g16_vk().constructor_args()].concat());
+            let kzg10_address =
+                evm.create([kzg10_bytecode.clone(), vk.kzg_vk().constructor_args()].concat());
+            let (decider_address, code_size) = evm.create_with_code_size(
+                [
+                    compile(&name),
+                    address_args(&[groth16_address, kzg10_address]),
+                ]
+                .concat(),
+            );
+            assert!(code_size <= MAX_CONTRACT_SIZE);
+
+            let (gas_used, output) = evm.call(decider_address, calldata.clone());
+            assert_eq!(output[31], 1);
+            println!("{} verification gas used: {}", name, gas_used);
+
+            // change i to make calldata invalid, placed between bytes 4 - 35
+            let mut invalid_calldata = calldata;
+            invalid_calldata[35] += 1;
+            let (_, output) = evm.call(decider_address, invalid_calldata);
+            assert_eq!(*output.last().unwrap(), 0);
+        }
+    }
+
+    /// Gas ceiling of the verification of a single proof, which can be overridden through the
+    /// `NOVA_DECIDER_GAS_CEILING` environment variable.
+    const NOVA_DECIDER_GAS_CEILING: u64 = 800_000;
+
+    // Checks that the gas used to verify a proof stays under the ceiling, for both layouts of the
+    // states in the public inputs, and through the batch entrypoint
+    #[test]
+    fn nova_cyclefold_solidity_verifier_gas_ceiling() {
+        const N_PROOFS: usize = 2;
+        let gas_ceiling = std::env::var("NOVA_DECIDER_GAS_CEILING")
+            .map(|ceiling| ceiling.parse().unwrap())
+            .unwrap_or(NOVA_DECIDER_GAS_CEILING);
+
+        fn measure<const HASH_STATE: bool>() -> (u64, u64) {
+            let (fs_params, (decider_pp, decider_vp)) =
+                init_params::<CubicFCircuit<Fr>, HASH_STATE>();
+            let mut rng = rand::rngs::OsRng;
+
+            let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+            let mut harness = GasHarness::nova_cyclefold(NovaCycleFoldVerifierKey::from((
+                decider_vp,
+                f_circuit.state_len(),
+            )));
+            let mut nova = NOVA::init(&fs_params, f_circuit, vec![Fr::from(3_u32)]).unwrap();
+            nova.prove_step(&mut rng, (), None).unwrap();
+
+            let mut statements = vec![];
+            let mut proofs = vec![];
+            for _ in 0..N_PROOFS {
+                nova.prove_step(&mut rng, (), None).unwrap();
+                proofs.push(
+                    DECIDER::<CubicFCircuit<Fr>, HASH_STATE>::prove(
+                        rng,
+                        decider_pp.clone(),
+                        nova.clone(),
+                    )
+                    .unwrap(),
+                );
+                statements.push(Statement {
+                    i: nova.i,
+                    z_0: nova.z_0.clone(),
+                    z_i: nova.z_i.clone(),
+                    running_commitments: nova.U_i.get_commitments(),
+                    incoming_commitments: nova.u_i.get_commitments(),
+                });
+            }
+
+            let single = harness.measure(
+                prepare_calldata(
+                    get_function_selector_for_nova_cyclefold_verifier(3),
+                    nova.i,
+                    nova.z_0.clone(),
+                    nova.z_i.clone(),
+                    &nova.U_i,
+                    &nova.u_i,
+                    proofs[N_PROOFS - 1].clone(),
+                )
+                .unwrap(),
+            );
+            assert!(single.verified);
+            let batch = harness.measure(
+                prepare_calldata_batch(
+                    get_function_selector_for_nova_cyclefold_batch_verifier(),
+                    &statements,
+                    &proofs,
+                )
+                .unwrap(),
+            );
+            assert!(batch.verified);
+            (single.gas_used, batch.gas_used / N_PROOFS as u64)
+        }
+
+        for (hash_state, (gas_single, gas_per_proof)) in
+            [(false, measure::<false>()), (true, measure::<true>())]
+        {
+            println!(
+                "NovaDecider verification gas used (hash_state: {}): {} for a single proof, {} per proof for a batch of {}",
+                hash_state, gas_single, gas_per_proof, N_PROOFS
+            );
+            assert!(gas_single <= gas_ceiling);
+            assert!(gas_per_proof <= gas_ceiling);
+        }
+    }
+
+    // Emits the Foundry test of the verifier for the calldata of a real proof, and checks that it
+    // compiles together with the verifier
+    #[test]
+    fn nova_cyclefold_foundry_test_compiles() {
+        let (fs_params, (decider_pp, decider_vp)) = init_params::<CubicFCircuit<Fr>, false>();
+        let mut rng = rand::rngs::OsRng;
+
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let nova_cyclefold_vk = NovaCycleFoldVerifierKey::from((decider_vp, f_circuit.state_len()));
+        let mut nova = NOVA::init(&fs_params, f_circuit, vec![Fr::from(3_u32)]).unwrap();
+        for _ in 0..2 {
+            nova.prove_step(&mut rng, (), None).unwrap();
+        }
+        let proof =
+            DECIDER::<CubicFCircuit<Fr>, false>::prove(rng, decider_pp, nova.clone()).unwrap();
+        let calldata = prepare_calldata(
+            get_function_selector_for_nova_cyclefold_verifier(nova.z_0.len() * 2 + 1),
+            nova.i,
+            nova.z_0,
+            nova.z_i,
+            &nova.U_i,
+            &nova.u_i,
+            proof,
+        )
+        .unwrap();
+
+        // without calldata only the verifier is emitted
+        assert_eq!(
+            get_decider_template_for_cyclefold_decider_with_foundry_test(
+                nova_cyclefold_vk.clone(),
+                None
+            )
+            .len(),
+            1
+        );
+
+        let artifacts = get_decider_template_for_cyclefold_decider_with_foundry_test(
+            nova_cyclefold_vk,
+            Some(&calldata),
+        );
+        assert_eq!(artifacts.len(), 2);
+        assert_eq!(artifacts[1].0, foundry_test_file("NovaDecider"));
+        assert!(artifacts[1].1.contains(&hex::encode(&calldata)));
+
+        // written to their own directory, since other tests write their own NovaDecider.sol
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        for (file, solidity) in &artifacts {
+            std::fs::write(dir.join(file), solidity).unwrap();
+        }
+        let files = artifacts
+            .iter()
+            .map(|(file, _)| file.as_str())
+            .collect::<Vec<_>>();
+        assert!(!compile_solidity_files(dir, &files, "NovaDeciderTest").is_empty());
+    }
 }
@@ -0,0 +1,37 @@
+/*
+    Foundry test of the {{ contract_name }} contract generated by Sonobe's solidity-verifiers,
+    which checks it against the calldata of a decider proof.
+
+    More details at https://github.com/privacy-scaling-explorations/sonobe
+    Usage and design documentation at https://privacy-scaling-explorations.github.io/sonobe-docs/
+
+    Place it next to `{{ verifier_file }}` in a Foundry project and run `forge test`. It does not
+    depend on forge-std, so it can also be compiled with solc alone.
+*/
+
+import "./{{ verifier_file }}";
+
+contract {{ contract_name }}Test {
+    {{ contract_name }} verifier;
+
+    // calldata of a proof, as returned by `prepare_calldata`
+    bytes constant PROOF_CALLDATA = hex"{{ calldata }}";
+
+    function setUp() public {
+        verifier = new {{ contract_name }}();
+    }
+
+    function testVerifiesProof() public {
+        (bool success, bytes memory output) = address(verifier).call(PROOF_CALLDATA);
+        require(success, "the verifier reverted");
+        require(abi.decode(output, (bool)), "the verifier returned false");
+    }
+
+    function testRevertsOnMutatedCalldata() public {
+        bytes memory mutated = PROOF_CALLDATA;
+        // flip a bit of byte {{ mutated_byte }} of the calldata, which is part of `i`
+        mutated[{{ mutated_byte }}] ^= bytes1(0x01);
+        (bool success, ) = address(verifier).call(mutated);
+        require(!success, "the verifier accepted a mutated proof");
+    }
+}
@@ -20,12 +20,64 @@
     along with snarkJS. If not, see <https://www.gnu.org/licenses/>.
 */
 
-contract Groth16Verifier {
+contract {{ name }} {
     // Scalar field size
     uint256 constant r    = 21888242871839275222246405745257275088548364400416034343698204186575808495617;
     // Base field size
     uint256 constant q   = 21888242871839275222246405745257275088696311157297823662689037894645226208583;
 
+    {% if constructor_vk -%}
+    // Verification Key data, set at the constructor. The points of G2 are encoded as [x.c1, x.c0, y.c1, y.c0].
+    uint256[2] alpha;
+    uint256[4] beta;
+    uint256[4] gamma;
+    uint256[4] delta;
+    uint256[2][] IC;
+
+    constructor(uint256[2] memory _alpha, uint256[4] memory _beta, uint256[4] memory _gamma, uint256[4] memory _delta, uint256[2][] memory _IC) {
+        require(_IC.length > 0, "Groth16: empty IC");
+        alpha = _alpha;
+        beta = _beta;
+        gamma = _gamma;
+        delta = _delta;
+        for (uint256 i = 0; i < _IC.length; i++) {
+            IC.push(_IC[i]);
+        }
+    }
+
+    function verifyProof(uint[2] calldata _pA, uint[2][2] calldata _pB, uint[2] calldata _pC, uint[] calldata _pubSignals) public view returns (bool) {
+        if (_pubSignals.length + 1 != IC.length) {
+            return false;
+        }
+        for (uint i = 0; i < _pubSignals.length; i++) {
+            if (_pubSignals[i] >= r) {
+                return false;
+            }
+        }
+
+        uint[] memory input = new uint[](24);
+        input[0] = _pA[0];
+        input[1] = (q - (_pA[1] % q)) % q;
+        input[2] = _pB[0][0];
+        input[3] = _pB[0][1];
+        input[4] = _pB[1][0];
+        input[5] = _pB[1][1];
+        (input[6], input[7]) = (alpha[0], alpha[1]);
+        setG2(input, 8, beta[0], beta[1], beta[2], beta[3]);
+        (input[12], input[13]) = batchVkX(1, _pubSignals);
+        setG2(input, 14, gamma[0], gamma[1], gamma[2], gamma[3]);
+        (input[18], input[19]) = (_pC[0], _pC[1]);
+        setG2(input, 20, delta[0], delta[1], delta[2], delta[3]);
+
+        uint[1] memory out;
+        bool success;
+        assembly {
+            success := staticcall(sub(gas(), 2000), 8, add(input, 32), 768, out, 0x20)
+        }
+        return success && out[0] == 1;
+    }
+
+    {%- else -%}
     // Verification Key data
     uint256 constant alphax  = {{ vkey_alpha_g1.0[0]    }};
     uint256 constant alphay  = {{ vkey_alpha_g1.0[1]    }};
@@ -166,4 +218,190 @@ contract Groth16Verifier {
             return(0, 0x20)
         }
     }
+
+    {%- endif %}
+
+    /**
+     * @notice  Random linear combination of the checks of several proofs, see `verifyProofBatch`.
+     */
+    struct ProofBatch {
+        uint[] pairs; // (-r_j * A_j, B_j) of each proof
+        uint rSum; // sum_j r_j
+        uint[2] rC; // sum_j r_j * C_j
+        {% if constructor_vk %}uint[]{% else %}uint[{{ gamma_abc_len - 1 }}]{% endif %} rPubSignals; // sum_j r_j * pubSignals_j
+    }
+
+    /**
+     * @notice  Verifies the proofs (_pA[j], _pB[j], _pC[j]) with public signals _pubSignals[j] at once, through a random linear combination
+     *          of their checks whose coefficients r_j are derived from the keccak256 of all of them.
+     * @dev     As `verifyProof`, returns false if any public signal is not in the scalar field.
+     */
+    function verifyProofs(uint[2][] memory _pA, uint[2][2][] memory _pB, uint[2][] memory _pC, {% if constructor_vk %}uint[]{% else %}uint[{{ gamma_abc_len - 1 }}]{% endif %}[] memory _pubSignals) public view returns (bool) {
+        uint n = _pA.length;
+        if (n == 0 || _pB.length != n || _pC.length != n || _pubSignals.length != n) {
+            return false;
+        }
+        uint seed = uint(keccak256(abi.encode(_pA, _pB, _pC, _pubSignals)));
+        ProofBatch memory batch;
+        batch.pairs = new uint[](6 * n);
+        {%- if constructor_vk %}
+        batch.rPubSignals = new uint[](IC.length - 1);
+        {%- endif %}
+        for (uint j = 0; j < n; j++) {
+            uint r_j = uint(keccak256(abi.encodePacked(seed, j))) % r;
+            if (!addToBatch(batch, j, r_j, _pA[j], _pB[j], _pC[j], _pubSignals[j])) {
+                return false;
+            }
+        }
+        return verifyProofBatch(batch.pairs, batch.rSum, batch.rC, batch.rPubSignals);
+    }
+
+    // adds the j-th proof, weighted by r_j, to the batch, returning false if its public signals are not as many as the ones of the
+    // verifying key or are not in the scalar field
+    function addToBatch(ProofBatch memory _batch, uint _j, uint _r_j, uint[2] memory _pA, uint[2][2] memory _pB, uint[2] memory _pC, {% if constructor_vk %}uint[]{% else %}uint[{{ gamma_abc_len - 1 }}]{% endif %} memory _pubSignals) internal view returns (bool) {
+        if (_pubSignals.length != _batch.rPubSignals.length) {
+            return false;
+        }
+        for (uint i = 0; i < _pubSignals.length; i++) {
+            if (_pubSignals[i] >= r) {
+                return false;
+            }
+            _batch.rPubSignals[i] = addmod(_batch.rPubSignals[i], mulmod(_r_j, _pubSignals[i], r), r);
+        }
+        (_batch.pairs[6 * _j], _batch.pairs[6 * _j + 1]) = batchMulScalar(_pA[0], (q - (_pA[1] % q)) % q, _r_j);
+        _batch.pairs[6 * _j + 2] = _pB[0][0];
+        _batch.pairs[6 * _j + 3] = _pB[0][1];
+        _batch.pairs[6 * _j + 4] = _pB[1][0];
+        _batch.pairs[6 * _j + 5] = _pB[1][1];
+        _batch.rSum = addmod(_batch.rSum, _r_j, r);
+        (uint cx, uint cy) = batchMulScalar(_pC[0], _pC[1], _r_j);
+        (_batch.rC[0], _batch.rC[1]) = batchAdd(_batch.rC[0], _batch.rC[1], cx, cy);
+        return true;
+    }
+
+    /**
+     * @notice  Checks a random linear combination of proofs, where the j-th proof (A_j, B_j, C_j) with public signals pubSignals_j is weighted by r_j:
+     *          prod_j e(-r_j * A_j, B_j) * e(R * alpha, beta) * e(vk_x, gamma) * e(sum_j r_j * C_j, delta) == 1,
+     *          with R = sum_j r_j and vk_x = R * IC0 + sum_i (sum_j r_j * pubSignals_j[i]) * IC{i+1}.
+     * @dev     This is not a proof check on its own, since the terms are chosen by the caller (eg. all-zero terms satisfy it), which is
+     *          why it is internal. Proofs are checked through it by `verifyProofs`, which derives the terms from them.
+     * @param   _pairs  the points (-r_j * A_j, B_j) of the proofs, 6 words each, in the same encoding as in `verifyProof`
+     * @param   _rSum  R = sum_j r_j
+     * @param   _rC  sum_j r_j * C_j
+     * @param   _rPubSignals  sum_j r_j * pubSignals_j
+     */
+    function verifyProofBatch(uint[] memory _pairs, uint _rSum, uint[2] memory _rC, {% if constructor_vk %}uint[]{% else %}uint[{{ gamma_abc_len - 1 }}]{% endif %} memory _rPubSignals) internal view returns (bool) {
+        if (_pairs.length == 0 || _pairs.length % 6 != 0 || _rPubSignals.length + 1 != {% if constructor_vk %}IC.length{% else %}{{ gamma_abc_len }}{% endif %}) {
+            return false;
+        }
+        uint n = _pairs.length;
+        uint[] memory input = new uint[](n + 18);
+        for (uint k = 0; k < n; k++) {
+            input[k] = _pairs[k];
+        }
+
+        {%- if constructor_vk %}
+        (input[n], input[n + 1]) = batchMulScalar(alpha[0], alpha[1], _rSum % r);
+        setG2(input, n + 2, beta[0], beta[1], beta[2], beta[3]);
+        (input[n + 6], input[n + 7]) = batchVkX(_rSum % r, _rPubSignals);
+        setG2(input, n + 8, gamma[0], gamma[1], gamma[2], gamma[3]);
+        (input[n + 12], input[n + 13]) = (_rC[0], _rC[1]);
+        setG2(input, n + 14, delta[0], delta[1], delta[2], delta[3]);
+        {%- else %}
+        (input[n], input[n + 1]) = batchMulScalar(alphax, alphay, _rSum % r);
+        setG2(input, n + 2, betax1, betax2, betay1, betay2);
+        (input[n + 6], input[n + 7]) = batchVkX(_rSum % r, _rPubSignals);
+        setG2(input, n + 8, gammax1, gammax2, gammay1, gammay2);
+        (input[n + 12], input[n + 13]) = (_rC[0], _rC[1]);
+        setG2(input, n + 14, deltax1, deltax2, deltay1, deltay2);
+        {%- endif %}
+
+        uint[1] memory out;
+        bool success;
+        assembly {
+            success := staticcall(sub(gas(), 2000), 8, add(input, 32), mul(mload(input), 32), out, 0x20)
+        }
+        return success && out[0] == 1;
+    }
+
+    // vk_x of `verifyProofBatch`, ie. R * IC0 + sum_i _rPubSignals[i] * IC{i+1}
+    {%- if constructor_vk %}
+    function batchVkX(uint _rSum, uint[] memory _rPubSignals) internal view returns (uint x, uint y) {
+        (x, y) = batchMulScalar(IC[0][0], IC[0][1], _rSum);
+        for (uint i = 0; i < _rPubSignals.length; i++) {
+            (uint px, uint py) = batchMulScalar(IC[i + 1][0], IC[i + 1][1], _rPubSignals[i]);
+            (x, y) = batchAdd(x, y, px, py);
+        }
+    }
+    {%- else %}
+    function batchVkX(uint _rSum, uint[{{ gamma_abc_len - 1 }}] memory _rPubSignals) internal view returns (uint x, uint y) {
+        assembly {
+            function g1_mulAcc(pR, px, py, s) {
+                let mIn := mload(0x40)
+                mstore(mIn, px)
+                mstore(add(mIn, 32), py)
+                mstore(add(mIn, 64), mod(s, r))
+
+                if iszero(staticcall(sub(gas(), 2000), 7, mIn, 96, mIn, 64)) {
+                    revert(0, 0)
+                }
+
+                mstore(add(mIn, 64), mload(pR))
+                mstore(add(mIn, 96), mload(add(pR, 32)))
+
+                if iszero(staticcall(sub(gas(), 2000), 6, mIn, 128, pR, 64)) {
+                    revert(0, 0)
+                }
+            }
+
+            let pR := mload(0x40)
+            mstore(0x40, add(pR, 64))
+            mstore(pR, 0)
+            mstore(add(pR, 32), 0)
+
+            g1_mulAcc(pR, IC0x, IC0y, _rSum)
+            {% for (i, _) in gamma_abc_g1.iter().enumerate() %}
+            {% if loop.first -%}
+            {%- else -%}
+            g1_mulAcc(pR, IC{{i}}x, IC{{i}}y, mload(add(_rPubSignals, {{(i-1)*32}})))
+            {%- endif -%}
+            {% endfor %}
+
+            x := mload(pR)
+            y := mload(add(pR, 32))
+        }
+    }
+    {%- endif %}
+
+    // sets the point of G2 encoded as [x.c1, x.c0, y.c1, y.c0] at _input[_offset..]
+    function setG2(uint[] memory _input, uint _offset, uint _x1, uint _x2, uint _y1, uint _y2) internal pure {
+        _input[_offset] = _x1;
+        _input[_offset + 1] = _x2;
+        _input[_offset + 2] = _y1;
+        _input[_offset + 3] = _y2;
+    }
+
+    // s * (x, y) in G1, for the terms of `verifyProofs`
+    function batchMulScalar(uint _x, uint _y, uint _s) internal view returns (uint, uint) {
+        uint[3] memory input = [_x, _y, _s];
+        uint[2] memory out;
+        bool success;
+        assembly {
+            success := staticcall(sub(gas(), 2000), 7, input, 96, out, 64)
+        }
+        require(success, "bn254: scalar mul failed");
+        return (out[0], out[1]);
+    }
+
+    // (x1, y1) + (x2, y2) in G1, for the terms of `verifyProofs`
+    function batchAdd(uint _x1, uint _y1, uint _x2, uint _y2) internal view returns (uint, uint) {
+        uint[4] memory input = [_x1, _y1, _x2, _y2];
+        uint[2] memory out;
+        bool success;
+        assembly {
+            success := staticcall(sub(gas(), 2000), 6, input, 128, out, 64)
+        }
+        require(success, "bn254: point add failed");
+        return (out[0], out[1]);
+    }
 }
@@ -0,0 +1,217 @@
+/*
+    Sonobe's HyperNova + CycleFold decider verifier.
+    Joint effort by 0xPARC & PSE.
+
+    More details at https://github.com/privacy-scaling-explorations/sonobe
+    Usage and design documentation at https://privacy-scaling-explorations.github.io/sonobe-docs/
+
+    Uses the https://github.com/iden3/snarkjs/blob/master/templates/verifier_groth16.sol.ejs
+    Groth16 verifier implementation and a KZG10 Solidity template adapted from
+    https://github.com/weijiekoh/libkzg.
+    Additionally we implement the HyperNovaDecider contract, which combines the
+    Groth16 verifier with a verifier of the Zeromorph opening of the MLE of the
+    witness, built on the KZG10 one, to verify the zkSNARK proofs coming from
+    HyperNova+CycleFold folding.
+*/
+
+/* =============================== */
+/* KZG10 verifier methods */
+{{ kzg10_verifier }}
+
+/* =============================== */
+/* Groth16 verifier methods */
+{{ groth16_verifier }}
+
+
+/* =============================== */
+/* HyperNova+CycleFold Decider verifier */
+{% include "point_packing.askama.sol" %}
+
+/**
+ * @author  PSE & 0xPARC
+ * @title   HyperNovaDecider contract, for verifying HyperNova IVC SNARK proofs.
+ * @dev     This is an askama template which, when templated, features a Groth16 and KZG10 verifiers from which this contract inherits.
+ */
+contract {{ decider_name }} is {{ groth16_name }}, {{ kzg10_name }} {
+
+    // the challenges of the Zeromorph opening, and the limbs in which the coordinates of the points are split when absorbing them, have {{ bits_per_element }} bits
+    uint256 constant LOW_BITS_MASK = (1 << {{ bits_per_element }}) - 1;
+
+    /**
+     * @notice  Returns the squeezed challenge of a keccak256 transcript whose state has just been updated.
+     * @dev     Compatible with sonobe::folding-schemes::transcript::keccak::Keccak256Transcript::get_challenge.
+     */
+    function squeeze(bytes32 state) internal pure returns (uint256) {
+        return uint256(keccak256(abi.encodePacked(state, uint32(0)))) & LOW_BITS_MASK;
+    }
+
+    /**
+     * @notice  Encodes a point as absorbed by a keccak256 transcript, ie. as the {{ bits_per_element }}-bit limbs of its coordinates.
+     * @dev     Compatible with sonobe::folding-schemes::transcript::Transcript::absorb_nonnative.
+     */
+    function limbs(uint256 x, uint256 y) internal pure returns (bytes memory) {
+        return abi.encodePacked(x & LOW_BITS_MASK, x >> {{ bits_per_element }}, y & LOW_BITS_MASK, y >> {{ bits_per_element }});
+    }
+
+    /**
+     * @notice  Recomputes the challenges [y, x, z] of the Zeromorph opening at the point `u`.
+     */
+    function zeromorphChallenges(
+        uint256[{{ n_vars }}] memory u,
+        uint256 eval,
+        uint256[{{ 2 * n_vars + 4 }}] calldata proof
+    ) internal pure returns (uint256[3] memory yxz) {
+        bytes memory absorbed = abi.encodePacked(u, eval);
+        for (uint256 k = 0; k < {{ n_vars }}; k++) {
+            absorbed = abi.encodePacked(absorbed, limbs(proof[2 * k], proof[2 * k + 1]));
+        }
+        bytes32 state = keccak256(abi.encodePacked(bytes32(0), absorbed));
+        yxz[0] = squeeze(state);
+
+        // x and z are squeezed after absorbing q_hat, each one preceded by the label of its index
+        state = keccak256(abi.encodePacked(state, limbs(proof[{{ 2 * n_vars }}], proof[{{ 2 * n_vars + 1 }}]), bytes8(0)));
+        yxz[1] = squeeze(state);
+        state = keccak256(abi.encodePacked(state, bytes8(0x0100000000000000)));
+        yxz[2] = squeeze(state);
+    }
+
+    /**
+     * @notice  Returns base^exp mod m, by square and multiply.
+     */
+    function powmod(uint256 base, uint256 exp, uint256 m) internal pure returns (uint256 result) {
+        result = 1;
+        while (exp > 0) {
+            if (exp & 1 == 1) {
+                result = mulmod(result, base, m);
+            }
+            base = mulmod(base, base, m);
+            exp >>= 1;
+        }
+    }
+
+    /**
+     * @notice  Computes the scalars s_k by which the commitments to the quotients q_k are subtracted, together with Phi_n(x).
+     * @dev     The degree check shifts q_k by x^{D + 1 - 2^k}, where D = {{ zeromorph_shift }} + 2^{{ n_vars }} - 1 is the maximum degree of the SRS.
+     * @dev     Compatible with sonobe::folding-schemes::commitment::zeromorph::quotient_scalars.
+     */
+    function zeromorphScalars(uint256[{{ n_vars }}] memory u, uint256[3] memory yxz)
+        internal
+        pure
+        returns (uint256[{{ n_vars }}] memory s, uint256 phi)
+    {
+        uint256 m = BN254_SCALAR_FIELD;
+        // xPows[k] = x^{2^k}, phis[k] = prod_{i >= k} (1 + x^{2^i}) and xProds[k] = x^{D + 1 - 2^n} prod_{i >= k} x^{2^i} = x^{D + 1 - 2^k}
+        uint256[{{ n_vars }}] memory xPows;
+        xPows[0] = yxz[1];
+        for (uint256 k = 1; k < {{ n_vars }}; k++) {
+            xPows[k] = mulmod(xPows[k - 1], xPows[k - 1], m);
+        }
+        uint256[{{ n_vars + 1 }}] memory phis;
+        uint256[{{ n_vars + 1 }}] memory xProds;
+        phis[{{ n_vars }}] = 1;
+        xProds[{{ n_vars }}] = powmod(yxz[1], {{ zeromorph_shift }}, m);
+        for (uint256 k = {{ n_vars }}; k > 0; k--) {
+            phis[k - 1] = mulmod(phis[k], addmod(1, xPows[k - 1], m), m);
+            xProds[k - 1] = mulmod(xProds[k], xPows[k - 1], m);
+        }
+
+        // s_k = y^k x^{D + 1 - 2^k} + z (x^{2^k} Phi_{n-k-1}(x^{2^{k+1}}) - u_k Phi_{n-k}(x^{2^k}))
+        uint256 yk = 1;
+        for (uint256 k = 0; k < {{ n_vars }}; k++) {
+            uint256 zTerm = addmod(mulmod(xPows[k], phis[k + 1], m), m - mulmod(u[k], phis[k], m), m);
+            s[k] = addmod(mulmod(yk, xProds[k], m), mulmod(yxz[2], zTerm, m), m);
+            yk = mulmod(yk, yxz[0], m);
+        }
+        phi = phis[0];
+    }
+
+    /**
+     * @notice  Verifies the Zeromorph proof that the MLE committed in `C`, of {{ n_vars }} variables, evaluates to `eval` at the point `u`.
+     * @dev     Compatible with sonobe::folding-schemes::commitment::zeromorph::Zeromorph::verify, with a Keccak256Transcript.
+     * @param   proof  [q_0[2], ..., q_{n-1}[2], q_hat[2], pi[2]]
+     */
+    function verifyZeromorph(uint256[2] memory C, uint256[{{ n_vars }}] memory u, uint256 eval, uint256[{{ 2 * n_vars + 4 }}] calldata proof)
+        internal
+        view
+        returns (bool)
+    {
+        uint256[3] memory yxz = zeromorphChallenges(u, eval, proof);
+        (uint256[{{ n_vars }}] memory s, uint256 phi) = zeromorphScalars(u, yxz);
+
+        // lhs = q_hat + z * C - (z * eval * Phi_n(x)) * g1 + x * pi - sum_k s_k * q_k
+        uint256[2] memory pi = [proof[{{ 2 * n_vars + 2 }}], proof[{{ 2 * n_vars + 3 }}]];
+        uint256[2] memory lhs = add([proof[{{ 2 * n_vars }}], proof[{{ 2 * n_vars + 1 }}]], mulScalar(C, yxz[2]));
+        lhs = add(lhs, mulScalar(negate(G_1), mulmod(mulmod(yxz[2], eval, BN254_SCALAR_FIELD), phi, BN254_SCALAR_FIELD)));
+        lhs = add(lhs, mulScalar(pi, yxz[1]));
+        for (uint256 k = 0; k < {{ n_vars }}; k++) {
+            lhs = add(lhs, mulScalar(negate([proof[2 * k], proof[2 * k + 1]]), s[k]));
+        }
+
+        // e(lhs, g2) * e(-pi, vk) == 1
+        return pairing(lhs, G_2, negate(pi), VK);
+    }
+
+    /**
+     * @notice  Verifies a hypernova cyclefold proof consisting of a Zeromorph proof and of a groth16 proof.
+     * @dev     The selector of this function is "dynamic", since it depends on `z_len` and on the number of variables of the MLE of the witness.
+     *          The opening point is built in-circuit from the sum-check point of the folded instance, so it is bound by the groth16 proof through the public inputs.
+     */
+    function verifyHyperNovaProof(
+        // inputs are grouped to prevent errors due stack too deep
+        uint256[{{ 1 + z_len * 2 }}] calldata i_z0_zi, // [i, z0, zi] where |z0| == |zi|
+        uint256[6] calldata U_i_C_u_i_C_blinding_C, // [U_i.C[2], u_i.C[2], blinding_C[2]]
+        uint256[3] calldata challenge_eval_rho, // [challenge, eval, rho]
+        uint256[{{ n_vars }}] calldata point, // point at which the MLE of the witness is opened
+        uint256[2] calldata pA, // groth16
+        uint256[2][2] calldata pB, // groth16
+        uint256[2] calldata pC, // groth16
+        uint256[{{ 2 * n_vars + 4 }}] calldata zeromorph_proof // [q_0[2], ..., q_{n-1}[2], q_hat[2], pi[2]]
+    ) public view returns (bool) {
+
+        require(i_z0_zi[0] >= 2, "Folding: the number of folded steps should be at least 2");
+
+        uint256[{{ public_inputs_len - 1 }}] memory public_inputs;
+        public_inputs[0] = {{pp_hash}};
+
+        for (uint i = 0; i < {{ 1 + z_len * 2 }}; i++) {
+            public_inputs[1 + i] = i_z0_zi[i];
+        }
+
+        // C = U_i.C + rho * blinding_C + rho^2 * u_i.C
+        uint256 rho = challenge_eval_rho[2];
+        uint256[2] memory C = add(
+            add([U_i_C_u_i_C_blinding_C[0], U_i_C_u_i_C_blinding_C[1]], mulScalar([U_i_C_u_i_C_blinding_C[4], U_i_C_u_i_C_blinding_C[5]], rho)),
+            mulScalar([U_i_C_u_i_C_blinding_C[2], U_i_C_u_i_C_blinding_C[3]], mulmod(rho, rho, BN254_SCALAR_FIELD))
+        );
+        {
+            uint256[3] memory C_packed = PointPacking.pack(C);
+
+            for (uint8 k = 0; k < 3; k++) {
+                public_inputs[{{ 2 + z_len * 2 }} + k] = C_packed[k];
+            }
+        }
+
+        {
+            // add challenge, evaluation, opening point and rho
+            public_inputs[{{ 2 + z_len * 2 + 3 }}] = challenge_eval_rho[0];
+            public_inputs[{{ 2 + z_len * 2 + 4 }}] = challenge_eval_rho[1];
+            for (uint256 k = 0; k < {{ n_vars }}; k++) {
+                public_inputs[{{ 2 + z_len * 2 + 5 }} + k] = point[k];
+            }
+            public_inputs[{{ 2 + z_len * 2 + 5 + n_vars }}] = rho;
+
+            uint256[3] memory blinding_C_packed = PointPacking.pack([U_i_C_u_i_C_blinding_C[4], U_i_C_u_i_C_blinding_C[5]]);
+
+            for (uint8 k = 0; k < 3; k++) {
+                public_inputs[{{ 2 + z_len * 2 + 6 + n_vars }} + k] = blinding_C_packed[k];
+            }
+        }
+
+        require(verifyZeromorph(C, point, challenge_eval_rho[1], zeromorph_proof), "Zeromorph: verifying opening proof failed");
+
+        bool success_g16 = this.verifyProof(pA, pB, pC, public_inputs);
+        require(success_g16 == true, "Groth16: verifying proof failed");
+
+        return(true);
+    }
+}
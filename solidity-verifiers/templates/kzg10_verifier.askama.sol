@@ -10,7 +10,7 @@
  * - Use the bn254 scalar field instead of checking for overflow on the babyjub prime
  * - In batch checking, we compute auxiliary polynomials and their commitments at the same time.
  */
-contract KZG10Verifier {
+contract {{ name }} {
 
     // prime of field F_p over which y^2 = x^3 + 3 is defined
     uint256 public constant BN254_PRIME_FIELD =
@@ -111,6 +111,18 @@ contract KZG10Verifier {
         return out[0] == 1;
     }
 
+    {% if constructor_vk -%}
+    // set at the constructor, so that the same contract can be deployed for different setups
+    uint256[2] G_1;
+    uint256[2][2] G_2;
+    uint256[2][2] VK;
+
+    constructor(uint256[2] memory g1, uint256[2][2] memory g2, uint256[2][2] memory vk) {
+        G_1 = g1;
+        G_2 = g2;
+        VK = vk;
+    }
+    {%- else -%}
     uint256[2] G_1 = [
             {{ g1.0[0] }},
             {{ g1.0[1] }}
@@ -135,6 +147,7 @@ contract KZG10Verifier {
             {{ vk.0[1][1] }}
         ]
     ];
+    {%- endif %}
 
     {% if g1_crs_len>0 %} // only enabled if g1_crs_len>0, for batch_check
     uint256[2][{{ g1_crs_len }}] G1_CRS = [
@@ -185,6 +198,52 @@ contract KZG10Verifier {
         return pairing(pi, VK, rhs_pairing, G_2);
     }
 
+    /**
+     * @notice  Verifies the point evaluation proofs (c[j], pi[j], x[j], y[j]) of `check` at once, through a random linear combination of
+     *          their checks whose coefficients s_j are derived from the keccak256 of all of them.
+     * @return  result Indicates if the KZG proofs are correct.
+     */
+    function checkProofs(uint256[2][] memory c, uint256[2][] memory pi, uint256[] memory x, uint256[] memory y)
+        public
+        view
+        returns (bool result)
+    {
+        uint256 n = c.length;
+        if (n == 0 || pi.length != n || x.length != n || y.length != n) {
+            return false;
+        }
+        uint256 seed = uint256(keccak256(abi.encode(c, pi, x, y)));
+        uint256[2] memory piSum;
+        uint256[2] memory cSum;
+        uint256 ySum;
+        for (uint256 j = 0; j < n; j++) {
+            uint256 s_j = uint256(keccak256(abi.encodePacked(seed, j))) % BN254_SCALAR_FIELD;
+            piSum = add(piSum, mulScalar(pi[j], s_j));
+            cSum = add(cSum, mulScalar(add(mulScalar(negate(pi[j]), x[j]), negate(c[j])), s_j));
+            ySum = addmod(ySum, mulmod(s_j, y[j], BN254_SCALAR_FIELD), BN254_SCALAR_FIELD);
+        }
+        return checkCombined(piSum, cSum, ySum);
+    }
+
+    /**
+     * @notice  Checks a random linear combination of point evaluation proofs, where the j-th proof (c_j, pi_j, x_j, y_j) of `check` is weighted by s_j.
+     * @dev     The combined check is e(pi, vk) * e(c + y * g1, g2) = 1, as in `check`. This is not a proof check on its own, since the
+     *          terms are chosen by the caller (eg. all-zero terms satisfy it), which is why it is internal. Proofs are checked through it
+     *          by `checkProofs`, which derives the terms from them.
+     * @param   pi  sum_j s_j * pi_j
+     * @param   c  sum_j s_j * (-x_j * pi_j - c_j)
+     * @param   y  sum_j s_j * y_j
+     * @return  result Indicates if the combination holds.
+     */
+    function checkCombined(uint256[2] memory pi, uint256[2] memory c, uint256 y)
+        internal
+        view
+        returns (bool result)
+    {
+        uint256[2] memory rhs_pairing = add(c, mulScalar(G_1, y));
+        return pairing(pi, VK, rhs_pairing, G_2);
+    }
+
     function evalPolyAt(uint256[] memory _coefficients, uint256 _index) public pure returns (uint256) {
         uint256 m = BN254_SCALAR_FIELD;
         uint256 result = 0;
@@ -13,7 +13,26 @@
     Nova+CycleFold folding.
 */
 
+// `verifyBatch` takes an array of structs
+pragma abicoder v2;
 
+{% if shared -%}
+/* =============================== */
+/* Groth16 and KZG10 verifiers shared with other deciders, which are deployed as separate contracts */
+import "./{{ shared_file }}";
+{%- else if split -%}
+/* =============================== */
+/* Interfaces of the Groth16 and KZG10 verifiers, which are deployed as separate contracts */
+interface I{{ groth16_name }} {
+    function verifyProof(uint[2] calldata _pA, uint[2][2] calldata _pB, uint[2] calldata _pC, uint[{{ public_inputs_len - 1 }}] calldata _pubSignals) external view returns (bool);
+    function verifyProofs(uint[2][] calldata _pA, uint[2][2][] calldata _pB, uint[2][] calldata _pC, uint[{{ public_inputs_len - 1 }}][] calldata _pubSignals) external view returns (bool);
+}
+
+interface I{{ kzg10_name }} {
+    function check(uint256[2] calldata c, uint256[2] calldata pi, uint256 x, uint256 y) external view returns (bool result);
+    function checkProofs(uint256[2][] calldata c, uint256[2][] calldata pi, uint256[] calldata x, uint256[] calldata y) external view returns (bool result);
+}
+{%- else -%}
 /* =============================== */
 /* KZG10 verifier methods */
 {{ kzg10_verifier }}
@@ -21,30 +40,79 @@
 /* =============================== */
 /* Groth16 verifier methods */
 {{ groth16_verifier }}
+{%- endif %}
 
 
 /* =============================== */
 /* Nova+CycleFold Decider verifier */
+{%- if !shared %}
+{% include "point_packing.askama.sol" %}
+{%- endif %}
+
+{% if split -%}
 /**
- * @notice  Computes the decomposition of a `uint256` into num_limbs limbs of bits_per_limb bits each.
- * @dev     Compatible with sonobe::folding-schemes::folding::circuits::nonnative::nonnative_field_to_field_elements.
+ * @author  PSE & 0xPARC
+ * @title   NovaDecider contract, for verifying Nova IVC SNARK proofs.
+ * @dev     This is an askama template which, when templated, delegates the Groth16 and KZG10 checks to the verifiers deployed at the addresses given to the constructor.
  */
-library LimbsDecomposition {
-    function decompose(uint256 x) internal pure returns (uint256[{{num_limbs}}] memory) {
-        uint256[{{num_limbs}}] memory limbs;
-        for (uint8 i = 0; i < {{num_limbs}}; i++) {
-            limbs[i] = (x >> ({{bits_per_limb}} * i)) & ((1 << {{bits_per_limb}}) - 1);
+contract {{ decider_name }} {
+    uint256 constant BN254_PRIME_FIELD =
+        21888242871839275222246405745257275088696311157297823662689037894645226208583;
+    uint256 constant BN254_SCALAR_FIELD =
+        21888242871839275222246405745257275088548364400416034343698204186575808495617;
+
+    {% if !shared %}I{% endif %}{{ groth16_name }} public immutable groth16Verifier;
+    {% if !shared %}I{% endif %}{{ kzg10_name }} public immutable kzg10Verifier;
+
+    constructor(address _groth16Verifier, address _kzg10Verifier) {
+        groth16Verifier = {% if !shared %}I{% endif %}{{ groth16_name }}(_groth16Verifier);
+        kzg10Verifier = {% if !shared %}I{% endif %}{{ kzg10_name }}(_kzg10Verifier);
+    }
+
+    /**
+     * @notice  Performs scalar multiplication in G_1, as in `KZG10Verifier`.
+     */
+    function mulScalar(uint256[2] memory p, uint256 s) internal view returns (uint256[2] memory r) {
+        uint256[3] memory input = [p[0], p[1], s];
+        bool success;
+        assembly {
+            success := staticcall(sub(gas(), 2000), 7, input, 0x60, r, 0x40)
         }
-        return limbs;
+        require(success, "bn254: scalar mul failed");
     }
-}
 
+    /**
+     * @notice  Adds two points in G_1, as in `KZG10Verifier`.
+     */
+    function add(uint256[2] memory p1, uint256[2] memory p2) internal view returns (uint256[2] memory r) {
+        uint256[4] memory input = [p1[0], p1[1], p2[0], p2[1]];
+        bool success;
+        assembly {
+            success := staticcall(sub(gas(), 2000), 6, input, 0x80, r, 0x40)
+        }
+        require(success, "bn254: point add failed");
+    }
+    {%- if shared %}
+
+    /**
+     * @notice  Copies the public inputs into a dynamic array, as taken by the shared `{{ groth16_name }}`.
+     */
+    function toDynamic(uint256[{{ public_inputs_len - 1 }}] memory a) internal pure returns (uint256[] memory b) {
+        b = new uint256[]({{ public_inputs_len - 1 }});
+        for (uint256 k = 0; k < {{ public_inputs_len - 1 }}; k++) {
+            b[k] = a[k];
+        }
+    }
+    {%- endif %}
+{%- else -%}
 /**
  * @author  PSE & 0xPARC
  * @title   NovaDecider contract, for verifying Nova IVC SNARK proofs.
  * @dev     This is an askama template which, when templated, features a Groth16 and KZG10 verifiers from which this contract inherits.
  */
-contract NovaDecider is Groth16Verifier, KZG10Verifier {
+contract {{ decider_name }} is {{ groth16_name }}, {{ kzg10_name }} {
+{%- endif %}
+
     /**
      * @notice  Computes the linear combination of a and b with r as the coefficient.
      * @dev     All ops are done mod the BN254 scalar field prime
@@ -54,10 +122,130 @@ contract NovaDecider is Groth16Verifier, KZG10Verifier {
             result := addmod(a, mulmod(r, b, BN254_SCALAR_FIELD), BN254_SCALAR_FIELD)
         }
     }
+    {%- if hash_state %}
 
     /**
-     * @notice  Verifies a nova cyclefold proof consisting of two KZG proofs and of a groth16 proof.
+     * @notice  Computes the digest of the {{ z_len }} elements of `z`, as `uint256(keccak256(abi.encodePacked(z))) & ((1 << {{bits_per_element}}) - 1)`.
+     * @dev     Compatible with sonobe::folding-schemes::transcript::keccak::keccak256_digest.
+     */
+    function hashState(uint256[] memory z) internal pure returns (uint256 digest) {
+        assembly {
+            digest := and(keccak256(add(z, 0x20), {{ z_len * 32 }}), sub(shl({{bits_per_element}}, 1), 1))
+        }
+    }
+    {%- endif %}
+
+    /**
+     * @notice  A nova cyclefold proof, as taken by `verifyNovaProof(NovaProof)` and `verifyBatch`.
+     * @dev     Compatible with sonobe::folding-schemes::folding::nova::decider_eth::NovaProof.
+     */
+    struct NovaProof {
+        uint256 i;
+        uint256[] z0;
+        uint256[] zi;
+        // commitments of the running instance, of the incoming instance and the cross term
+        uint256[2] U_i_cmW;
+        uint256[2] U_i_cmE;
+        uint256[2] u_i_cmW;
+        uint256[2] cmT;
+        // challenge of the last fold
+        uint256 r;
+        // Groth16 proof
+        uint256[2] pA;
+        uint256[2][2] pB;
+        uint256[2] pC;
+        // batched KZG opening of W and E, with their evaluations [eval_W, eval_E]
+        uint256 kzg_challenge;
+        uint256 kzg_batching_challenge;
+        uint256[2] kzg_evaluations;
+        uint256[2] kzg_proof;
+    }
+
+    /**
+     * @notice  Groth16 and KZG checks of a batch of proofs, as taken by `verifyProofs` and `checkProofs`.
+     */
+    struct BatchChecks {
+        uint256[2][] pA;
+        uint256[2][2][] pB;
+        uint256[2][] pC;
+        uint256[{% if !shared %}{{ public_inputs_len - 1 }}{% endif %}][] publicInputs;
+        uint256[2][] kzgCommitments;
+        uint256[2][] kzgProofs;
+        uint256[] kzgChallenges;
+        uint256[] kzgEvals;
+    }
+
+    /**
+     * @notice  Computes the public inputs of the Groth16 proof, together with the commitment and evaluation of the batched KZG check.
+     */
+    function publicInputs(NovaProof memory proof)
+        internal view returns (uint256[{{ public_inputs_len - 1 }}] memory public_inputs, uint256[2] memory cm, uint256 eval) {
+
+        require(proof.i >= 2, "Folding: the number of folded steps should be at least 2");
+        require(proof.z0.length == {{ z_len }} && proof.zi.length == {{ z_len }}, "Folding: the states should have {{ z_len }} elements");
+
+        public_inputs[0] = {{pp_hash}};
+        public_inputs[1] = {{context}};
+        public_inputs[2] = proof.i;
+
+        {%- if hash_state %}
+
+        // the decider circuit exposes the digests of z0 and zi instead of their elements
+        public_inputs[3] = hashState(proof.z0);
+        public_inputs[4] = hashState(proof.zi);
+        {%- else %}
+
+        for (uint i = 0; i < {{ z_len }}; i++) {
+            public_inputs[3 + i] = proof.z0[i];
+            public_inputs[{{ 3 + z_len }} + i] = proof.zi[i];
+        }
+        {%- endif %}
+
+        // U_i.cmW + r * u_i.cmW
+        uint256[2] memory cmW = add(proof.U_i_cmW, mulScalar(proof.u_i_cmW, proof.r));
+        {
+            uint256[3] memory cmW_packed = PointPacking.pack(cmW);
+
+            for (uint8 k = 0; k < 3; k++) {
+                public_inputs[{{ state_inputs_len + 3 }} + k] = cmW_packed[k];
+            }
+        }
+
+        // U_i.cmE + r * cmT
+        uint256[2] memory cmE = add(proof.U_i_cmE, mulScalar(proof.cmT, proof.r));
+        {
+            uint256[3] memory cmE_packed = PointPacking.pack(cmE);
+
+            for (uint8 k = 0; k < 3; k++) {
+                public_inputs[{{ state_inputs_len + 3 + 3 }} + k] = cmE_packed[k];
+            }
+        }
+
+        // batched KZG check: (cmW + gamma * cmE) opens to (eval_W + gamma * eval_E) at challenge
+        cm = add(cmW, mulScalar(cmE, proof.kzg_batching_challenge));
+        eval = rlc(proof.kzg_evaluations[0], proof.kzg_batching_challenge, proof.kzg_evaluations[1]);
+
+        {
+            // add challenges
+            public_inputs[{{ state_inputs_len + 3 + 3 * 2 }}] = proof.kzg_challenge;
+            public_inputs[{{ state_inputs_len + 3 + 3 * 2 + 1 }}] = proof.kzg_evaluations[0];
+            public_inputs[{{ state_inputs_len + 3 + 3 * 2 + 2 }}] = proof.kzg_evaluations[1];
+            public_inputs[{{ state_inputs_len + 3 + 3 * 2 + 3 }}] = proof.kzg_batching_challenge;
+
+            uint256[3] memory cmT_packed = PointPacking.pack(proof.cmT);
+
+            for (uint8 k = 0; k < 3; k++) {
+                public_inputs[{{ state_inputs_len + 3 + 3 * 2 }} + 4 + k] = cmT_packed[k];
+            }
+        }
+    }
+
+    /**
+     * @notice  Verifies a nova cyclefold proof consisting of a batched KZG proof and of a groth16 proof.
      * @dev     The selector of this function is "dynamic", since it depends on `z_len`.
+     * @return  ok  Whether the proof is valid, which is always true since an invalid proof reverts.
+     * @return  i  The number of folded steps of the verified proof.
+     * @return  z_i  The verified state after `i` steps.
      */
     function verifyNovaProof(
         // inputs are grouped to prevent errors due stack too deep
@@ -68,80 +256,145 @@ contract NovaDecider is Groth16Verifier, KZG10Verifier {
         uint256[2] calldata pA, // groth16 
         uint256[2][2] calldata pB, // groth16
         uint256[2] calldata pC, // groth16
-        uint256[4] calldata challenge_W_challenge_E_kzg_evals, // [challenge_W, challenge_E, eval_W, eval_E]
-        uint256[2][2] calldata kzg_proof // [proof_W, proof_E]
-    ) public view returns (bool) {
+        uint256[4] calldata challenge_gamma_kzg_evals, // [challenge, gamma, eval_W, eval_E]
+        uint256[2] calldata kzg_proof // batched proof for W and E
+    ) public view returns (bool ok, uint256 i, uint256[] memory z_i) {
+        NovaProof memory proof = toNovaProof(i_z0_zi, U_i_cmW_U_i_cmE, u_i_cmW, cmT_r, pA, pB, pC, challenge_gamma_kzg_evals, kzg_proof);
+        verifyNovaProofParts(proof);
+        return (true, proof.i, proof.zi);
+    }
 
-        require(i_z0_zi[0] >= 2, "Folding: the number of folded steps should be at least 2");
+    /**
+     * @notice  Verifies a nova cyclefold proof given as a `NovaProof` struct, for callers that build the calldata with standard ABI tooling.
+     * @return  ok  Whether the proof is valid, which is always true since an invalid proof reverts.
+     * @return  i  The number of folded steps of the verified proof.
+     * @return  z_i  The verified state after `i` steps.
+     */
+    function verifyNovaProof(NovaProof memory proof) public view returns (bool ok, uint256 i, uint256[] memory z_i) {
+        verifyNovaProofParts(proof);
+        return (true, proof.i, proof.zi);
+    }
 
-        // from gamma_abc_len, we subtract 1. 
-        uint256[{{ public_inputs_len - 1 }}] memory public_inputs; 
+    /**
+     * @notice  Groups the arguments of the packed `verifyNovaProof` entrypoint into a `NovaProof`.
+     */
+    function toNovaProof(
+        uint256[{{ 1 + z_len * 2 }}] calldata i_z0_zi,
+        uint256[4] calldata U_i_cmW_U_i_cmE,
+        uint256[2] calldata u_i_cmW,
+        uint256[3] calldata cmT_r,
+        uint256[2] calldata pA,
+        uint256[2][2] calldata pB,
+        uint256[2] calldata pC,
+        uint256[4] calldata challenge_gamma_kzg_evals,
+        uint256[2] calldata kzg_proof
+    ) internal pure returns (NovaProof memory proof) {
+        proof.i = i_z0_zi[0];
+        proof.z0 = new uint256[]({{ z_len }});
+        proof.zi = new uint256[]({{ z_len }});
+        for (uint256 k = 0; k < {{ z_len }}; k++) {
+            proof.z0[k] = i_z0_zi[1 + k];
+            proof.zi[k] = i_z0_zi[{{ 1 + z_len }} + k];
+        }
+        proof.U_i_cmW = [U_i_cmW_U_i_cmE[0], U_i_cmW_U_i_cmE[1]];
+        proof.U_i_cmE = [U_i_cmW_U_i_cmE[2], U_i_cmW_U_i_cmE[3]];
+        proof.u_i_cmW = u_i_cmW;
+        proof.cmT = [cmT_r[0], cmT_r[1]];
+        proof.r = cmT_r[2];
+        proof.pA = pA;
+        proof.pB = pB;
+        proof.pC = pC;
+        proof.kzg_challenge = challenge_gamma_kzg_evals[0];
+        proof.kzg_batching_challenge = challenge_gamma_kzg_evals[1];
+        proof.kzg_evaluations = [challenge_gamma_kzg_evals[2], challenge_gamma_kzg_evals[3]];
+        proof.kzg_proof = kzg_proof;
+    }
 
-        public_inputs[0] = {{pp_hash}};
-        public_inputs[1] = i_z0_zi[0];
+    /**
+     * @notice  Verifies the batched KZG proof and the groth16 proof of a nova cyclefold proof, as taken by both `verifyNovaProof` entrypoints, reverting if any of them fails.
+     */
+    function verifyNovaProofParts(NovaProof memory proof) internal view {
+
+        (uint256[{{ public_inputs_len - 1 }}] memory public_inputs, uint256[2] memory cm, uint256 eval) = publicInputs(proof);
+
+        require({% if split %}kzg10Verifier{% else %}this{% endif %}.check(cm, proof.kzg_proof, proof.kzg_challenge, eval), "KZG: verifying batched proof failed");
 
-        for (uint i = 0; i < {{ z_len * 2 }}; i++) {
-            public_inputs[2 + i] = i_z0_zi[1 + i];
+        bool success_g16 = {% if split %}groth16Verifier{% else %}this{% endif %}.verifyProof(proof.pA, proof.pB, proof.pC, {% if shared %}toDynamic(public_inputs){% else %}public_inputs{% endif %});
+        require(success_g16 == true, "Groth16: verifying proof failed");
+    }
+
+    /**
+     * @notice  Verifies a batch of nova cyclefold proofs at once.
+     * @dev     The Groth16 and the KZG checks of the proofs are done at once through `verifyProofs` and `checkProofs`, which
+     *          combine them into a single pairing check each. If any of the two fails, the proofs are verified one by one,
+     *          reverting with the index of the first invalid one.
+     */
+    function verifyBatch(NovaProof[] calldata proofs) public view returns (bool) {
+        uint256 n = proofs.length;
+        require(n > 0, "Batch: no proofs to verify");
+
+        BatchChecks memory checks;
+        checks.pA = new uint256[2][](n);
+        checks.pB = new uint256[2][2][](n);
+        checks.pC = new uint256[2][](n);
+        checks.publicInputs = new uint256[{% if !shared %}{{ public_inputs_len - 1 }}{% endif %}][](n);
+        checks.kzgCommitments = new uint256[2][](n);
+        checks.kzgProofs = new uint256[2][](n);
+        checks.kzgChallenges = new uint256[](n);
+        checks.kzgEvals = new uint256[](n);
+        for (uint256 j = 0; j < n; j++) {
+            accumulate(checks, proofs[j], j);
         }
 
-        {
-            // U_i.cmW + r * u_i.cmW
-            uint256[2] memory mulScalarPoint = super.mulScalar([u_i_cmW[0], u_i_cmW[1]], cmT_r[2]);
-            uint256[2] memory cmW = super.add([U_i_cmW_U_i_cmE[0], U_i_cmW_U_i_cmE[1]], mulScalarPoint);
-
-            {
-                uint256[{{num_limbs}}] memory cmW_x_limbs = LimbsDecomposition.decompose(cmW[0]);
-                uint256[{{num_limbs}}] memory cmW_y_limbs = LimbsDecomposition.decompose(cmW[1]);
-        
-                for (uint8 k = 0; k < {{num_limbs}}; k++) {
-                    public_inputs[{{ z_len * 2 + 2 }} + k] = cmW_x_limbs[k];
-                    public_inputs[{{ z_len * 2 + 2 + num_limbs }} + k] = cmW_y_limbs[k];
-                }
-            }
-        
-            require(this.check(cmW, kzg_proof[0], challenge_W_challenge_E_kzg_evals[0], challenge_W_challenge_E_kzg_evals[2]), "KZG: verifying proof for challenge W failed");
+        bool valid = {% if split %}groth16Verifier.{% endif %}verifyProofs(checks.pA, checks.pB, checks.pC, checks.publicInputs)
+            && {% if split %}kzg10Verifier.{% endif %}checkProofs(checks.kzgCommitments, checks.kzgProofs, checks.kzgChallenges, checks.kzgEvals);
+        if (valid) {
+            return true;
         }
 
-        {
-            // U_i.cmE + r * cmT
-            uint256[2] memory mulScalarPoint = super.mulScalar([cmT_r[0], cmT_r[1]], cmT_r[2]);
-            uint256[2] memory cmE = super.add([U_i_cmW_U_i_cmE[2], U_i_cmW_U_i_cmE[3]], mulScalarPoint);
-
-            {
-                uint256[{{num_limbs}}] memory cmE_x_limbs = LimbsDecomposition.decompose(cmE[0]);
-                uint256[{{num_limbs}}] memory cmE_y_limbs = LimbsDecomposition.decompose(cmE[1]);
-            
-                for (uint8 k = 0; k < {{num_limbs}}; k++) {
-                    public_inputs[{{ z_len * 2 + 2 + num_limbs * 2 }} + k] = cmE_x_limbs[k];
-                    public_inputs[{{ z_len * 2 + 2 + num_limbs * 3 }} + k] = cmE_y_limbs[k];
-                }
+        for (uint256 j = 0; j < n; j++) {
+            try this.verifyNovaProof(proofs[j]) returns (bool, uint256, uint256[] memory) {
+            } catch {
+                revert(string(abi.encodePacked("Batch: invalid proof at index ", toString(j))));
             }
-
-            require(this.check(cmE, kzg_proof[1], challenge_W_challenge_E_kzg_evals[1], challenge_W_challenge_E_kzg_evals[3]), "KZG: verifying proof for challenge E failed");
         }
+        // all the proofs are valid on their own, which for the combined checks to fail can only happen with negligible probability
+        revert("Batch: verifying combined proofs failed");
+    }
 
-        {
-            // add challenges
-            public_inputs[{{ z_len * 2 + 2 + num_limbs * 4 }}] = challenge_W_challenge_E_kzg_evals[0];
-            public_inputs[{{ z_len * 2 + 2 + num_limbs * 4 + 1 }}] = challenge_W_challenge_E_kzg_evals[1];
-            public_inputs[{{ z_len * 2 + 2 + num_limbs * 4 + 2 }}] = challenge_W_challenge_E_kzg_evals[2];
-            public_inputs[{{ z_len * 2 + 2 + num_limbs * 4 + 3 }}] = challenge_W_challenge_E_kzg_evals[3];
-
-            uint256[{{num_limbs}}] memory cmT_x_limbs;
-            uint256[{{num_limbs}}] memory cmT_y_limbs;
-        
-            cmT_x_limbs = LimbsDecomposition.decompose(cmT_r[0]);
-            cmT_y_limbs = LimbsDecomposition.decompose(cmT_r[1]);
-        
-            for (uint8 k = 0; k < {{num_limbs}}; k++) {
-                public_inputs[{{ z_len * 2 + 2 + num_limbs * 4 }} + 4 + k] = cmT_x_limbs[k]; 
-                public_inputs[{{ z_len * 2 + 2 + num_limbs * 5 }} + 4 + k] = cmT_y_limbs[k];
-            }
+    /**
+     * @notice  Sets the Groth16 and KZG checks of the j-th proof of a batch.
+     */
+    function accumulate(BatchChecks memory checks, NovaProof memory proof, uint256 j) internal view {
+        (uint256[{{ public_inputs_len - 1 }}] memory public_inputs, uint256[2] memory cm, uint256 eval) = publicInputs(proof);
 
-            bool success_g16 = this.verifyProof(pA, pB, pC, public_inputs);
-            require(success_g16 == true, "Groth16: verifying proof failed");
-        }
+        checks.pA[j] = proof.pA;
+        checks.pB[j] = proof.pB;
+        checks.pC[j] = proof.pC;
+        checks.publicInputs[j] = {% if shared %}toDynamic(public_inputs){% else %}public_inputs{% endif %};
+        checks.kzgCommitments[j] = cm;
+        checks.kzgProofs[j] = proof.kzg_proof;
+        checks.kzgChallenges[j] = proof.kzg_challenge;
+        checks.kzgEvals[j] = eval;
+    }
 
-        return(true);
+    /**
+     * @notice  Returns the decimal representation of `value`.
+     */
+    function toString(uint256 value) internal pure returns (string memory) {
+        if (value == 0) {
+            return "0";
+        }
+        uint256 digits;
+        for (uint256 v = value; v != 0; v /= 10) {
+            digits++;
+        }
+        bytes memory buffer = new bytes(digits);
+        while (value != 0) {
+            digits--;
+            buffer[digits] = bytes1(uint8(48 + (value % 10)));
+            value /= 10;
+        }
+        return string(buffer);
     }
 }
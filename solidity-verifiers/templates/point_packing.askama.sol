@@ -0,0 +1,11 @@
+/**
+ * @notice  Packs the bits of `x || y` of a point, where each coordinate has {{coordinate_bits}} bits, into 3 elements of {{bits_per_element}} bits each.
+ * @dev     Compatible with sonobe::folding-schemes::folding::circuits::nonnative::affine::nonnative_affine_to_packed_field_elements.
+ */
+library PointPacking {
+    function pack(uint256[2] memory p) internal pure returns (uint256[3] memory packed) {
+        packed[0] = p[0] & ((1 << {{bits_per_element}}) - 1);
+        packed[1] = (p[0] >> {{bits_per_element}}) | ((p[1] & ((1 << {{ bits_per_element * 2 - coordinate_bits }}) - 1)) << {{ coordinate_bits - bits_per_element }});
+        packed[2] = p[1] >> {{ bits_per_element * 2 - coordinate_bits }};
+    }
+}
@@ -0,0 +1,27 @@
+/*
+    Sonobe's Groth16 and KZG10 verifiers shared by several Nova+CycleFold decider verifiers.
+    Joint effort by 0xPARC & PSE.
+
+    More details at https://github.com/privacy-scaling-explorations/sonobe
+    Usage and design documentation at https://privacy-scaling-explorations.github.io/sonobe-docs/
+
+    Unlike the verifiers embedded in each NovaDecider contract, the verifying keys of these
+    verifiers are set at their constructors, so that their code is emitted once and deployed for
+    the verifying keys of each decider. The deciders import this file and take the addresses of
+    the deployed verifiers at their constructors.
+*/
+
+// the constructor of the Groth16 verifier takes an array of arrays
+pragma abicoder v2;
+
+/* =============================== */
+/* Groth16 verifier */
+{{ groth16_verifier }}
+
+/* =============================== */
+/* KZG10 verifier */
+{{ kzg10_verifier }}
+
+/* =============================== */
+/* Point packing of the deciders */
+{% include "point_packing.askama.sol" %}